@@ -9,6 +9,7 @@
 //! - Message event parsing (@mentions, DMs)
 //! - Thread support for conversations
 //! - Response posting via Slack Web API
+//! - Quick-reply buttons (interactivity `block_actions` come back as messages)
 //!
 //! # Security
 //!
@@ -94,6 +95,54 @@ struct SlackMessageMetadata {
 
     /// Team ID.
     team_id: Option<String>,
+
+    /// Quick-reply options added by the agent for this response.
+    /// Rendered as buttons; a click is emitted as a regular message.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    reply_options: Vec<String>,
+}
+
+/// Interactivity payload sent when a user clicks a message button.
+#[derive(Debug, Deserialize)]
+struct SlackInteraction {
+    /// Interaction type (block_actions, view_submission, etc.)
+    #[serde(rename = "type")]
+    interaction_type: String,
+
+    /// User who clicked.
+    user: SlackIdRef,
+
+    /// Channel containing the message.
+    channel: Option<SlackIdRef>,
+
+    /// Team the interaction came from.
+    team: Option<SlackIdRef>,
+
+    /// The message the button belongs to.
+    message: Option<SlackInteractionMessage>,
+
+    /// Triggered actions (one per click).
+    #[serde(default)]
+    actions: Vec<SlackAction>,
+}
+
+/// An object that is only referenced by ID.
+#[derive(Debug, Deserialize)]
+struct SlackIdRef {
+    id: String,
+}
+
+/// Message fields needed to reply in the right thread.
+#[derive(Debug, Deserialize)]
+struct SlackInteractionMessage {
+    ts: Option<String>,
+    thread_ts: Option<String>,
+}
+
+/// A single button action.
+#[derive(Debug, Deserialize)]
+struct SlackAction {
+    value: Option<String>,
 }
 
 /// Slack API response for chat.postMessage.
@@ -149,6 +198,20 @@ impl Guest for SlackChannel {
             }
         };
 
+        // Button clicks arrive form-encoded as `payload=<json>`
+        if let Some(encoded) = body_str.strip_prefix("payload=") {
+            let decoded = form_decode(encoded);
+            match serde_json::from_str::<SlackInteraction>(&decoded) {
+                Ok(interaction) => handle_interaction(interaction),
+                Err(e) => channel_host::log(
+                    channel_host::LogLevel::Error,
+                    &format!("Failed to parse Slack interaction: {}", e),
+                ),
+            }
+            // Slack only needs a fast 200 for interactions
+            return json_response(200, serde_json::json!({"ok": true}));
+        }
+
         // Parse as Slack event
         let event_wrapper: SlackEventWrapper = match serde_json::from_str(body_str) {
             Ok(e) => e,
@@ -215,6 +278,10 @@ impl Guest for SlackChannel {
             payload["thread_ts"] = serde_json::Value::String(thread_ts);
         }
 
+        if !metadata.reply_options.is_empty() {
+            payload["blocks"] = reply_blocks(&response.content, &metadata.reply_options);
+        }
+
         let payload_bytes = serde_json::to_vec(&payload)
             .map_err(|e| format!("Failed to serialize payload: {}", e))?;
 
@@ -336,6 +403,7 @@ fn emit_message(
         thread_ts: thread_ts.clone(),
         message_ts: message_ts.clone(),
         team_id,
+        reply_options: Vec::new(),
     };
 
     let metadata_json = serde_json::to_string(&metadata).unwrap_or_else(|_| "{}".to_string());
@@ -352,6 +420,80 @@ fn emit_message(
     });
 }
 
+/// Emit a button click as a user message carrying the button's value.
+fn handle_interaction(interaction: SlackInteraction) {
+    if interaction.interaction_type != "block_actions" {
+        return;
+    }
+    let Some(channel) = interaction.channel.map(|c| c.id) else {
+        return;
+    };
+    let Some(value) = interaction.actions.into_iter().find_map(|a| a.value) else {
+        return;
+    };
+    let thread_ts = interaction
+        .message
+        .and_then(|m| m.thread_ts.or(m.ts));
+    emit_message(
+        interaction.user.id,
+        value,
+        channel,
+        thread_ts,
+        interaction.team.map(|t| t.id),
+    );
+}
+
+/// Build Block Kit blocks: the prompt text followed by one button per option.
+fn reply_blocks(text: &str, options: &[String]) -> serde_json::Value {
+    let buttons: Vec<serde_json::Value> = options
+        .iter()
+        .take(25) // Slack's per-block element limit
+        .enumerate()
+        .map(|(i, option)| {
+            serde_json::json!({
+                "type": "button",
+                "text": {"type": "plain_text", "text": truncate_chars(option, 75)},
+                "value": option,
+                "action_id": format!("reply_option_{}", i),
+            })
+        })
+        .collect();
+    serde_json::json!([
+        {"type": "section", "text": {"type": "mrkdwn", "text": text}},
+        {"type": "actions", "elements": buttons},
+    ])
+}
+
+/// Truncate to at most `max` characters.
+fn truncate_chars(s: &str, max: usize) -> String {
+    s.chars().take(max).collect()
+}
+
+/// Decode an `application/x-www-form-urlencoded` value.
+fn form_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
 /// Strip leading bot mention from text.
 fn strip_bot_mention(text: &str) -> String {
     // Slack mentions look like <@U12345678>
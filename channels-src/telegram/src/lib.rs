@@ -197,6 +197,24 @@ struct TelegramMessageMetadata {
 
     /// Whether this is a private (DM) chat.
     is_private: bool,
+
+    /// Quick-reply options added by the agent for this response.
+    /// Rendered as a one-time reply keyboard.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    reply_options: Vec<String>,
+
+    /// Image added by the agent (e.g. a chart); sent as a photo with the
+    /// response content as its caption.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image: Option<ResponseImage>,
+}
+
+/// Image attached to an agent response.
+#[derive(Debug, Serialize, Deserialize)]
+struct ResponseImage {
+    mime_type: String,
+    file_name: String,
+    data_base64: String,
 }
 
 /// Channel configuration injected by host.
@@ -515,6 +533,24 @@ impl Guest for TelegramChannel {
         let metadata: TelegramMessageMetadata = serde_json::from_str(&response.metadata_json)
             .map_err(|e| format!("Failed to parse metadata: {}", e))?;
 
+        if let Some(ref image) = metadata.image {
+            let msg_id = send_photo(
+                metadata.chat_id,
+                image,
+                &response.content,
+                metadata.message_id,
+            )
+            .map_err(|e| e.to_string())?;
+            channel_host::log(
+                channel_host::LogLevel::Debug,
+                &format!(
+                    "Sent image to chat {}: message_id={}",
+                    metadata.chat_id, msg_id
+                ),
+            );
+            return Ok(());
+        }
+
         // Try sending with Markdown first; fall back to plain text if Telegram
         // can't parse the entities (e.g. model leaked <tool_call> with underscores).
         let result = send_message(
@@ -522,6 +558,7 @@ impl Guest for TelegramChannel {
             &response.content,
             metadata.message_id,
             Some("Markdown"),
+            &metadata.reply_options,
         );
 
         match result {
@@ -545,6 +582,7 @@ impl Guest for TelegramChannel {
                     &response.content,
                     metadata.message_id,
                     None,
+                    &metadata.reply_options,
                 )
                 .map_err(|e| format!("Plain-text retry also failed: {}", e))?;
 
@@ -649,6 +687,7 @@ fn send_message(
     text: &str,
     reply_to_message_id: i64,
     parse_mode: Option<&str>,
+    reply_options: &[String],
 ) -> Result<i64, SendError> {
     let mut payload = serde_json::json!({
         "chat_id": chat_id,
@@ -660,6 +699,10 @@ fn send_message(
         payload["parse_mode"] = serde_json::Value::String(mode.to_string());
     }
 
+    if let Some(keyboard) = reply_keyboard(reply_options) {
+        payload["reply_markup"] = keyboard;
+    }
+
     let payload_bytes = serde_json::to_vec(&payload)
         .map_err(|e| SendError::Other(format!("Failed to serialize payload: {}", e)))?;
 
@@ -713,6 +756,105 @@ fn send_message(
     }
 }
 
+/// Send an image via sendPhoto, or sendDocument for formats Telegram can't
+/// show as a photo (SVG). The body is multipart since the file is uploaded.
+fn send_photo(
+    chat_id: i64,
+    image: &ResponseImage,
+    caption: &str,
+    reply_to_message_id: i64,
+) -> Result<i64, SendError> {
+    use base64::Engine;
+
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(&image.data_base64)
+        .map_err(|e| SendError::Other(format!("Invalid image data: {}", e)))?;
+    let (method, field) = if is_photo_type(&image.mime_type) {
+        ("sendPhoto", "photo")
+    } else {
+        ("sendDocument", "document")
+    };
+
+    let boundary = "ironclaw-image-boundary";
+    let fields = [
+        ("chat_id", chat_id.to_string()),
+        ("caption", caption.to_string()),
+        ("reply_to_message_id", reply_to_message_id.to_string()),
+    ];
+    let body = multipart_body(boundary, &fields, field, image, &data);
+    let headers = serde_json::json!({
+        "Content-Type": format!("multipart/form-data; boundary={}", boundary)
+    });
+    let url = format!(
+        "https://api.telegram.org/bot{{TELEGRAM_BOT_TOKEN}}/{}",
+        method
+    );
+
+    let http_response =
+        channel_host::http_request("POST", &url, &headers.to_string(), Some(&body), None)
+            .map_err(|e| SendError::Other(format!("HTTP request failed: {}", e)))?;
+
+    if http_response.status != 200 {
+        let body_str = String::from_utf8_lossy(&http_response.body);
+        return Err(SendError::Other(format!(
+            "Telegram API returned status {}: {}",
+            http_response.status, body_str
+        )));
+    }
+
+    let api_response: TelegramApiResponse<SentMessage> =
+        serde_json::from_slice(&http_response.body)
+            .map_err(|e| SendError::Other(format!("Failed to parse response: {}", e)))?;
+    if !api_response.ok {
+        return Err(SendError::Other(format!(
+            "Telegram API error: {}",
+            api_response
+                .description
+                .unwrap_or_else(|| "unknown".to_string())
+        )));
+    }
+
+    Ok(api_response.result.map(|r| r.message_id).unwrap_or(0))
+}
+
+/// Whether Telegram accepts this image type in sendPhoto.
+fn is_photo_type(mime_type: &str) -> bool {
+    matches!(mime_type, "image/png" | "image/jpeg" | "image/webp")
+}
+
+/// Build a multipart/form-data body with text fields and one file part.
+fn multipart_body(
+    boundary: &str,
+    fields: &[(&str, String)],
+    file_field: &str,
+    image: &ResponseImage,
+    data: &[u8],
+) -> Vec<u8> {
+    let mut body = Vec::with_capacity(data.len() + 512);
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                boundary, name, value
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+            boundary,
+            file_field,
+            image.file_name.replace('"', ""),
+            image.mime_type
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(data);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
 // ============================================================================
 // Webhook Management
 // ============================================================================
@@ -1033,6 +1175,7 @@ fn handle_message(message: TelegramMessage) {
         message_id: message.message_id,
         user_id: from.id,
         is_private,
+        reply_options: Vec::new(),
     };
 
     let metadata_json = serde_json::to_string(&metadata).unwrap_or_else(|_| "{}".to_string());
@@ -1145,10 +1288,77 @@ export!(TelegramChannel);
 // Tests
 // ============================================================================
 
+/// Build a one-time reply keyboard for quick-reply options.
+///
+/// Short options share a row (up to three per row); tapping a button sends
+/// its text as a normal message, so the agent sees it like a typed reply.
+fn reply_keyboard(options: &[String]) -> Option<serde_json::Value> {
+    if options.is_empty() {
+        return None;
+    }
+    let per_row = if options.iter().all(|o| o.chars().count() <= 12) {
+        3
+    } else {
+        1
+    };
+    let rows: Vec<Vec<serde_json::Value>> = options
+        .chunks(per_row)
+        .map(|row| {
+            row.iter()
+                .map(|o| serde_json::json!({ "text": o }))
+                .collect()
+        })
+        .collect();
+    Some(serde_json::json!({
+        "keyboard": rows,
+        "one_time_keyboard": true,
+        "resize_keyboard": true,
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_multipart_body_layout() {
+        let image = ResponseImage {
+            mime_type: "image/png".to_string(),
+            file_name: "chart-ab12.png".to_string(),
+            data_base64: String::new(),
+        };
+        let fields = [
+            ("chat_id", "42".to_string()),
+            ("caption", "Costs".to_string()),
+        ];
+        let body = multipart_body("b", &fields, "photo", &image, b"\x89PNG");
+        let text = String::from_utf8_lossy(&body);
+
+        assert!(text
+            .starts_with("--b\r\nContent-Disposition: form-data; name=\"chat_id\"\r\n\r\n42\r\n"));
+        assert!(text.contains(
+            "name=\"photo\"; filename=\"chart-ab12.png\"\r\nContent-Type: image/png\r\n\r\n"
+        ));
+        assert!(text.ends_with("PNG\r\n--b--\r\n"));
+        assert!(is_photo_type("image/png"));
+        assert!(!is_photo_type("image/svg+xml"));
+    }
+
+    #[test]
+    fn test_reply_keyboard_layout() {
+        assert!(reply_keyboard(&[]).is_none());
+
+        let short: Vec<String> = ["S", "M", "L", "XL"].iter().map(|s| s.to_string()).collect();
+        let keyboard = reply_keyboard(&short).unwrap();
+        assert_eq!(keyboard["keyboard"].as_array().unwrap().len(), 2);
+        assert_eq!(keyboard["keyboard"][0][2]["text"], "L");
+        assert_eq!(keyboard["one_time_keyboard"], true);
+
+        let long = vec!["Book the earlier flight".to_string(), "Wait".to_string()];
+        let keyboard = reply_keyboard(&long).unwrap();
+        assert_eq!(keyboard["keyboard"].as_array().unwrap().len(), 2);
+    }
+
     #[test]
     fn test_clean_message_text() {
         // Without bot_username: strips any leading @mention
@@ -0,0 +1,37 @@
+-- V10: Per-extension secret namespaces
+--
+-- A secret is only injected for an extension that holds a grant for it.
+-- Grants are recorded when an extension stores a secret through its auth
+-- flow, or when it is installed and claims a secret no other extension has.
+
+CREATE TABLE secret_grants (
+    user_id TEXT NOT NULL,
+    secret_name TEXT NOT NULL,
+    extension TEXT NOT NULL,
+    granted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, secret_name, extension)
+);
+
+CREATE INDEX idx_secret_grants_extension ON secret_grants(user_id, extension);
+
+-- Existing secrets: the auth flows tag each secret they store with the
+-- extension name as provider ("mcp:<name>" for MCP OAuth tokens), so that
+-- extension keeps its own secrets.
+INSERT INTO secret_grants (user_id, secret_name, extension)
+SELECT user_id, name,
+       CASE WHEN provider LIKE 'mcp:%' THEN substr(provider, 5) ELSE provider END
+FROM secrets
+WHERE provider IS NOT NULL
+ON CONFLICT DO NOTHING;
+
+-- Tools stored in the database keep the secrets their capabilities allowed
+-- ("prefix_*" patterns match by prefix).
+INSERT INTO secret_grants (user_id, secret_name, extension)
+SELECT s.user_id, s.name, t.name
+FROM secrets s
+JOIN wasm_tools t ON t.user_id = s.user_id
+JOIN tool_capabilities c ON c.wasm_tool_id = t.id
+CROSS JOIN LATERAL unnest(c.allowed_secrets) AS pattern
+WHERE s.name = pattern
+   OR (right(pattern, 1) = '*' AND left(s.name, length(pattern) - 1) = left(pattern, -1))
+ON CONFLICT DO NOTHING;
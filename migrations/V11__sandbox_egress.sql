@@ -0,0 +1,23 @@
+-- V11: Sandbox egress audit log
+--
+-- Every request a sandboxed command sends through the network proxy, so a
+-- job's traffic can be reviewed afterwards (`ironclaw logs egress`). Paths
+-- are stored without their query string.
+
+CREATE TABLE sandbox_egress (
+    id UUID PRIMARY KEY,
+    job_id UUID,
+    method TEXT NOT NULL,
+    host TEXT NOT NULL,
+    path TEXT NOT NULL,
+    decision TEXT NOT NULL,
+    reason TEXT,
+    status INTEGER,
+    request_bytes BIGINT NOT NULL DEFAULT 0,
+    response_bytes BIGINT NOT NULL DEFAULT 0,
+    credential_injected BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_sandbox_egress_job ON sandbox_egress(job_id, created_at);
+CREATE INDEX idx_sandbox_egress_created ON sandbox_egress(created_at);
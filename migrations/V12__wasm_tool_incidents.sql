@@ -0,0 +1,18 @@
+-- V12: WASM tool misbehavior strikes
+--
+-- One row per strike against a WASM tool: a trap, a denied HTTP or tool
+-- call, a leak-detector hit or rate-limit abuse. Keyed by name rather than
+-- by wasm_tools id, since tools installed as files have no row there.
+-- `ironclaw tool unquarantine` clears a tool's strikes.
+
+CREATE TABLE wasm_tool_incidents (
+    id UUID PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    tool_name TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    detail TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cleared_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_wasm_tool_incidents_tool ON wasm_tool_incidents(user_id, tool_name, created_at);
@@ -0,0 +1,8 @@
+-- V13: Prompt cache accounting on LLM calls
+--
+-- Providers that support prompt caching report how many input tokens were
+-- served from the cache and how many were written to it. Both are already
+-- included in input_tokens; these columns break them out.
+
+ALTER TABLE llm_calls ADD COLUMN IF NOT EXISTS cache_read_tokens INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE llm_calls ADD COLUMN IF NOT EXISTS cache_write_tokens INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,36 @@
+-- V14: Secret groups
+--
+-- An admin shares a secret with a named group; tools running for any member
+-- of the group resolve the owner's copy when the member has none of their
+-- own. Every such use is recorded with the acting user and consuming tool.
+
+CREATE TABLE secret_group_members (
+    group_name TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (group_name, user_id)
+);
+
+CREATE INDEX idx_secret_group_members_user ON secret_group_members(user_id);
+
+CREATE TABLE secret_group_shares (
+    group_name TEXT NOT NULL,
+    owner_id TEXT NOT NULL,
+    secret_name TEXT NOT NULL,
+    shared_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (group_name, owner_id, secret_name)
+);
+
+CREATE INDEX idx_secret_group_shares_secret ON secret_group_shares(secret_name);
+
+CREATE TABLE secret_group_uses (
+    id UUID PRIMARY KEY,
+    group_name TEXT NOT NULL,
+    owner_id TEXT NOT NULL,
+    secret_name TEXT NOT NULL,
+    acting_user TEXT NOT NULL,
+    tool TEXT NOT NULL,
+    used_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_secret_group_uses_used ON secret_group_uses(used_at);
@@ -0,0 +1,40 @@
+#!/usr/bin/env bash
+# Regenerate the worker gRPC bindings from proto/worker.proto.
+#
+# The bindings are checked in so normal builds need neither protoc nor
+# tonic-build. Run this after editing the .proto and commit the result.
+#
+# Prerequisites: protoc on PATH (or PROTOC set).
+
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+root="$(pwd)"
+
+gen="$(mktemp -d)"
+trap 'rm -rf "$gen"' EXIT
+
+mkdir -p "$gen/src"
+cat > "$gen/Cargo.toml" <<'TOML'
+[package]
+name = "gen-worker-proto"
+version = "0.0.0"
+edition = "2021"
+
+[dependencies]
+tonic-build = "=0.11.0"
+TOML
+cat > "$gen/src/main.rs" <<'RUST'
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    tonic_build::configure()
+        .out_dir(&args[1])
+        .compile(&[&args[2]], &[&args[3]])
+        .expect("failed to compile proto/worker.proto");
+}
+RUST
+
+cargo run --quiet --manifest-path "$gen/Cargo.toml" -- \
+    "$root/src/worker/proto" "$root/proto/worker.proto" "$root/proto"
+
+echo "Wrote src/worker/proto/ironclaw.worker.v1.rs"
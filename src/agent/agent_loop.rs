@@ -1,32 +1,68 @@
 //! Main agent loop.
 
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use futures::StreamExt;
+use futures::{FutureExt, StreamExt};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+use crate::agent::approvals::{self, ApprovalRule, ApprovalRules};
+use crate::agent::away::{self, AwayDesk, Backlog, SenderRole};
+use crate::agent::budget::BudgetManager;
+use crate::agent::citations::{CitationPolicy, SourceLedger};
 use crate::agent::compaction::ContextCompactor;
 use crate::agent::context_monitor::ContextMonitor;
+use crate::agent::dead_man::{CriticalFailure, DeadManSwitch, spawn_dead_man_monitor};
+use crate::agent::dedup::{self, DedupOutcome, ResponseDeduplicator};
+use crate::agent::focus::{self as focus_mode, FocusMode};
+use crate::agent::followups::{self, FollowupTracker, JobSnapshot};
+use crate::agent::generation::{self, GenerationPrefs};
 use crate::agent::heartbeat::spawn_heartbeat;
+use crate::agent::load::AgentLoad;
+use crate::agent::model_tier::{ModelTier, ModelTierRouter, TierMode, TurnSignals};
+use crate::agent::output_summary::OutputSummarizer;
+use crate::agent::profiling::{self, Phase, TurnProfiler};
+use crate::agent::project::{self, ProjectBinding};
+use crate::agent::recovery::{self, RecoveryBudget};
+use crate::agent::redact::{self, Redaction};
 use crate::agent::routine_engine::{RoutineEngine, spawn_cron_ticker};
+use crate::agent::routine_watch::spawn_file_watcher;
 use crate::agent::self_repair::{DefaultSelfRepair, RepairResult, SelfRepair};
-use crate::agent::session::{PendingApproval, Session, ThreadState};
+use crate::agent::session::{PendingApproval, PendingInput, Session, ThreadState, TurnToolCall};
 use crate::agent::session_manager::SessionManager;
 use crate::agent::submission::{Submission, SubmissionParser, SubmissionResult};
-use crate::agent::{HeartbeatConfig as AgentHeartbeatConfig, MessageIntent, Router, Scheduler};
-use crate::channels::{ChannelManager, IncomingMessage, OutgoingResponse, StatusUpdate};
+use crate::agent::tool_hints::ToolHintStore;
+use crate::agent::{
+    AgentRouter, HeartbeatConfig as AgentHeartbeatConfig, MessageIntent, Router, Scheduler,
+};
+use crate::channels::web::log_layer::LogBroadcaster;
+use crate::channels::{
+    ChannelManager, IncomingMessage, NotificationCategory, OutgoingResponse, StatusUpdate,
+};
 use crate::config::{AgentConfig, Config, HeartbeatConfig, RoutineConfig};
+use crate::contacts::ContactStore;
 use crate::context::ContextManager;
 use crate::context::JobContext;
 use crate::db::Database;
-use crate::error::Error;
+use crate::error::{Error, LlmError};
+use crate::event_bus::{BusEvent, EventBus, TurnOutcome};
 use crate::extensions::ExtensionManager;
 use crate::hot_reload::{ConfigWatcher, HotReloadConfig};
-use crate::llm::{ChatMessage, LlmProvider, Reasoning, ReasoningContext, RespondResult};
-use crate::safety::SafetyLayer;
-use crate::tools::ToolRegistry;
-use crate::workspace::Workspace;
+use crate::llm::redaction::SKILL_METADATA_KEY;
+use crate::llm::{ChatMessage, LlmProvider, Reasoning, ReasoningContext, RespondResult, Role};
+use crate::locale::UserLocale;
+use crate::media::{Attachment, AttachmentPipeline, MediaCache};
+use crate::safety::{SafetyLayer, SanitizedOutput, Severity, VaultAccess};
+use crate::sandbox::{EphemeralCredentials, HostFallback};
+use crate::tools::builtin::{InputForm, ScratchpadStore};
+use crate::tools::wasm::MisbehaviorTracker;
+use crate::tools::{ProgressSender, ToolRegistry};
+use crate::workspace::{
+    Glossary, GlossaryEntry, HistoryIndexer, IndexedTurn, ScopedOwner, Workspace,
+};
 
 /// Collapse a tool output string into a single-line preview for display.
 pub(crate) fn truncate_for_preview(output: &str, max_chars: usize) -> String {
@@ -60,6 +96,11 @@ enum AgenticLoopResult {
         /// The pending approval request to store.
         pending: PendingApproval,
     },
+    /// `ask_user` needs an answer from the user before continuing.
+    NeedInput {
+        /// The pending input request to store.
+        pending: PendingInput,
+    },
 }
 
 /// Core dependencies for the agent.
@@ -72,8 +113,53 @@ pub struct AgentDeps {
     pub tools: Arc<ToolRegistry>,
     pub workspace: Option<Arc<Workspace>>,
     pub extension_manager: Option<Arc<ExtensionManager>>,
+    /// Cross-channel identity links; `None` treats every channel id as its own user.
+    pub contacts: Option<Arc<ContactStore>>,
+    /// Turn counters shared with channels that shed load (the gateway).
+    pub load: Option<Arc<AgentLoad>>,
+    /// Condenses large tool outputs before they re-enter the context.
+    pub output_summarizer: Option<Arc<OutputSummarizer>>,
+    /// Routes chat turns between a cheap and the primary model.
+    pub model_tiers: Option<Arc<ModelTierRouter>>,
+    /// Focus mode switch; background activity is held while it is on.
+    pub focus: Option<Arc<FocusMode>>,
+    /// Queues completed turns for conversation history recall (opt-in).
+    pub history_index: Option<HistoryIndexer>,
+    /// Bus that turn starts and finishes are published on.
+    pub events: Option<Arc<EventBus>>,
+    /// Restricted host execution standing in for an unavailable sandbox.
+    pub host_fallback: Option<Arc<HostFallback>>,
+    /// Issuers of short-lived credentials minted for each job.
+    pub ephemeral_credentials: Option<Arc<EphemeralCredentials>>,
+    /// Escalates critical failures nobody responds to.
+    pub dead_man: Option<Arc<DeadManSwitch>>,
+    /// Quarantines misbehaving WASM tools; notices go to the active channel.
+    pub misbehavior: Option<Arc<MisbehaviorTracker>>,
+    /// Extracts the attachments sent with messages into the turn's input.
+    pub attachments: Option<Arc<AttachmentPipeline>>,
+    /// Tags tool outputs with sources for answers to cite; `None` disables citations.
+    pub citations: Option<Arc<CitationPolicy>>,
+    /// Skill each tool belongs to; LLM calls after a skill's tool ran in the
+    /// turn are tagged with that skill.
+    pub skill_tools: HashMap<String, String>,
+    /// Acknowledges and queues other people's messages while the user is away.
+    pub away: Option<Arc<AwayDesk>>,
+    /// Per-turn notes from the `scratchpad` tool, discarded as turns end.
+    pub scratchpad: Option<Arc<ScratchpadStore>>,
+    /// Images rendered by tools, delivered to the channel after the call.
+    pub media_cache: Option<Arc<MediaCache>>,
+    /// Token and cost tallies of chat turns, held against the budget caps.
+    pub budget: Option<Arc<BudgetManager>>,
+    /// Recent log entries kept for the gateway, scrubbed by `/redact`.
+    pub logs: Option<Arc<LogBroadcaster>>,
+    /// Agent personas messages are routed to; each turn's memory is
+    /// restricted to the routed agent's scope. `None` uses the whole workspace.
+    pub agents: Option<Arc<AgentRouter>>,
 }
 
+/// Turns of thread history checked for tool use when picking a model tier.
+const RECENT_TURNS_FOR_TIER: usize = 3;
+
 /// The main agent that coordinates all components.
 pub struct Agent {
     config: AgentConfig,
@@ -84,6 +170,9 @@ pub struct Agent {
     router: Router,
     session_manager: Arc<SessionManager>,
     context_monitor: ContextMonitor,
+    dedup: ResponseDeduplicator,
+    tool_hints: ToolHintStore,
+    profiler: TurnProfiler,
     heartbeat_config: Option<HeartbeatConfig>,
     routine_config: Option<RoutineConfig>,
     /// Hot-reloadable configuration container, updated by the config reload task.
@@ -92,6 +181,9 @@ pub struct Agent {
     /// Configuration file watcher, kept alive to maintain filesystem watches.
     #[allow(dead_code)] // Held to keep the file watcher alive
     config_watcher: Option<Arc<ConfigWatcher>>,
+    /// After the channels close, how long to wait for background work the
+    /// messages started (single-message CLI mode).
+    followup_wait: Option<Duration>,
 }
 
 impl Agent {
@@ -113,31 +205,56 @@ impl Agent {
 
         let session_manager = session_manager.unwrap_or_else(|| Arc::new(SessionManager::new()));
 
-        let scheduler = Arc::new(Scheduler::new(
-            config.clone(),
-            context_manager.clone(),
-            deps.llm.clone(),
-            deps.safety.clone(),
-            deps.tools.clone(),
-            deps.store.clone(),
-        ));
+        let channels = Arc::new(channels);
+        let scheduler = Arc::new(
+            Scheduler::new(
+                config.clone(),
+                context_manager.clone(),
+                deps.llm.clone(),
+                deps.safety.clone(),
+                deps.tools.clone(),
+                deps.store.clone(),
+            )
+            .with_output_summarizer(deps.output_summarizer.clone())
+            .with_ephemeral_credentials(deps.ephemeral_credentials.clone())
+            .with_channels(Arc::clone(&channels)),
+        );
+
+        let dedup = ResponseDeduplicator::new(config.dedup_window);
+        let tool_hints = ToolHintStore::new(config.tool_hint_half_life);
+        let profiler = if config.profile_turns {
+            TurnProfiler::new(profiling::default_profile_path())
+        } else {
+            TurnProfiler::disabled()
+        };
 
         Self {
             config,
             deps,
-            channels: Arc::new(channels),
+            channels,
             context_manager,
             scheduler,
             router: Router::new(),
             session_manager,
             context_monitor: ContextMonitor::new(),
+            dedup,
+            tool_hints,
+            profiler,
             heartbeat_config,
             routine_config,
             hot_config: None,
             config_watcher: None,
+            followup_wait: None,
         }
     }
 
+    /// Keep running for up to `wait` after the channels close, until jobs and
+    /// routine runs started by the handled messages finish.
+    pub fn with_followup_wait(mut self, wait: Duration) -> Self {
+        self.followup_wait = Some(wait);
+        self
+    }
+
     // Convenience accessors
     fn store(&self) -> Option<&Arc<dyn Database>> {
         self.deps.store.as_ref()
@@ -159,11 +276,176 @@ impl Agent {
         self.deps.workspace.as_ref()
     }
 
+    /// The workspace a turn for `message` reads and writes: the memory of
+    /// the agent it routes to, or the whole workspace without agents.
+    async fn message_workspace(&self, message: &IncomingMessage) -> Option<Arc<Workspace>> {
+        let base = self.workspace()?;
+        let Some(agents) = self.deps.agents.as_ref() else {
+            return Some(Arc::clone(base));
+        };
+        let scoped = match agents.route(message).await {
+            Ok(decision) => {
+                agents
+                    .workspace_for(&decision.agent_name, base, &message.channel)
+                    .await
+            }
+            Err(e) => {
+                tracing::warn!("Agent routing failed: {}", e);
+                None
+            }
+        };
+        // An agent removed since routing gets only the pool every agent
+        // shares, never the unscoped workspace.
+        Some(Arc::new(scoped.unwrap_or_else(|| {
+            base.scoped(ScopedOwner {
+                home: None,
+                read_shared: false,
+            })
+        })))
+    }
+
+    /// The user id per-person state is keyed by for a message's sender,
+    /// plus the contact it resolved through. Unlinked senders keep their
+    /// channel-specific id.
+    fn person(&self, message: &IncomingMessage) -> (String, Option<String>) {
+        let Some(contacts) = self.deps.contacts.as_ref() else {
+            return (message.user_id.clone(), None);
+        };
+        match contacts.resolve(&message.channel, &message.user_id) {
+            Ok(Some(contact)) => (contact.user_id, Some(contact.id)),
+            Ok(None) => (message.user_id.clone(), None),
+            Err(e) => {
+                tracing::warn!("Contact lookup failed for {}: {}", message.user_id, e);
+                (message.user_id.clone(), None)
+            }
+        }
+    }
+
+    /// Remember the sender's identity so `ironclaw contacts suggest` can
+    /// propose links across channels.
+    fn observe_sender(&self, message: &IncomingMessage) {
+        let Some(contacts) = self.deps.contacts.as_ref() else {
+            return;
+        };
+        let username = message.metadata.get("username").and_then(|v| v.as_str());
+        if let Err(e) = contacts.observe(
+            &message.channel,
+            &message.user_id,
+            username,
+            message.user_name.as_deref(),
+        ) {
+            tracing::debug!("Could not record contact identity: {}", e);
+        }
+    }
+
+    /// How the sender of `message` relates to the user, for away handling.
+    fn sender_role(&self, away: &AwayDesk, message: &IncomingMessage) -> SenderRole {
+        let is_contact =
+            self.deps.contacts.as_ref().is_some_and(|c| {
+                matches!(c.resolve(&message.channel, &message.user_id), Ok(Some(_)))
+            });
+        away.role_of(message, is_contact)
+    }
+
+    /// Summarize messages queued while the user was away. Falls back to the
+    /// plain list when the LLM call fails.
+    async fn summarize_backlog(&self, backlog: Backlog) -> String {
+        let listing = backlog.render();
+        let request = crate::llm::CompletionRequest::new(vec![
+            ChatMessage::system(away::BACKLOG_PROMPT),
+            ChatMessage::user(listing.clone()),
+        ])
+        .with_max_tokens(800)
+        .with_temperature(0.3);
+        let body = match self.llm().complete(request).await {
+            Ok(response) if !response.content.trim().is_empty() => {
+                response.content.trim().to_string()
+            }
+            Ok(_) => listing.trim().to_string(),
+            Err(e) => {
+                tracing::warn!("Could not summarize the away backlog: {}", e);
+                listing.trim().to_string()
+            }
+        };
+        format!(
+            "While you were away, {} message(s) came in:\n\n{}",
+            backlog.len(),
+            body
+        )
+    }
+
+    /// Raise a security alert on routed channels when a tool's output
+    /// carried high-severity injection patterns.
+    async fn alert_on_injection(
+        &self,
+        message: &IncomingMessage,
+        tool_name: &str,
+        sanitized: &SanitizedOutput,
+    ) {
+        let serious: Vec<&str> = sanitized
+            .warnings
+            .iter()
+            .filter(|w| matches!(w.severity, Severity::High | Severity::Critical))
+            .map(|w| w.description.as_str())
+            .collect();
+        if serious.is_empty() {
+            return;
+        }
+        let alert = OutgoingResponse::text(format!(
+            "Security alert: output of tool '{}' in a {} conversation looked like a prompt \
+             injection and was sanitized: {}",
+            tool_name,
+            message.channel,
+            serious.join("; ")
+        ));
+        self.channels
+            .route(
+                NotificationCategory::SecurityAlert,
+                &message.user_id,
+                alert,
+                None,
+            )
+            .await;
+    }
+
     /// Run the agent main loop.
     pub async fn run(self) -> Result<(), Error> {
         // Start channels
         let mut message_stream = self.channels.start_all().await?;
 
+        // Safe mode after an escalation: no background work until an admin
+        // answers.
+        let safe_mode = self
+            .deps
+            .dead_man
+            .as_ref()
+            .and_then(|switch| switch.safe_mode());
+        if let Some(ref marker) = safe_mode {
+            tracing::warn!(
+                "Starting in safe mode since {} ({}): heartbeat, routines and self-repair are off",
+                marker.since,
+                marker.reason
+            );
+            let notice = OutgoingResponse::text(format!(
+                "Started in safe mode after an escalation on {}: {}. Heartbeat, routines and self-repair are off. Send any message to return to normal on the next start.",
+                marker.since.format("%Y-%m-%d %H:%M UTC"),
+                marker.reason
+            ));
+            let _ = self.channels.broadcast_all("default", notice).await;
+        }
+
+        // Watch for unattended critical failures
+        let dead_man_handle = self.deps.dead_man.clone().map(|switch| {
+            spawn_dead_man_monitor(switch, self.channels.clone(), self.store().cloned())
+        });
+
+        // Tell the user when a WASM tool is quarantined
+        let quarantine_handle = self
+            .deps
+            .misbehavior
+            .clone()
+            .map(|tracker| spawn_quarantine_notifier(tracker, self.channels.clone()));
+
         // Start self-repair task with notification forwarding
         let repair = Arc::new(DefaultSelfRepair::new(
             self.context_manager.clone(),
@@ -172,98 +454,164 @@ impl Agent {
         ));
         let repair_interval = self.config.repair_check_interval;
         let repair_channels = self.channels.clone();
-        let repair_handle = tokio::spawn(async move {
-            loop {
-                tokio::time::sleep(repair_interval).await;
-
-                // Check stuck jobs
-                let stuck_jobs = repair.detect_stuck_jobs().await;
-                for job in stuck_jobs {
-                    tracing::info!("Attempting to repair stuck job {}", job.job_id);
-                    let result = repair.repair_stuck_job(&job).await;
-                    let notification = match &result {
-                        Ok(RepairResult::Success { message }) => {
-                            tracing::info!("Repair succeeded: {}", message);
-                            Some(format!(
-                                "Job {} was stuck for {}s, recovery succeeded: {}",
-                                job.job_id,
-                                job.stuck_duration.as_secs(),
-                                message
-                            ))
-                        }
-                        Ok(RepairResult::Failed { message }) => {
-                            tracing::error!("Repair failed: {}", message);
-                            Some(format!(
-                                "Job {} was stuck for {}s, recovery failed permanently: {}",
-                                job.job_id,
-                                job.stuck_duration.as_secs(),
-                                message
-                            ))
-                        }
-                        Ok(RepairResult::ManualRequired { message }) => {
-                            tracing::warn!("Manual intervention needed: {}", message);
-                            Some(format!(
-                                "Job {} needs manual intervention: {}",
-                                job.job_id, message
-                            ))
-                        }
-                        Ok(RepairResult::Retry { message }) => {
-                            tracing::warn!("Repair needs retry: {}", message);
-                            None // Don't spam the user on retries
-                        }
-                        Err(e) => {
-                            tracing::error!("Repair error: {}", e);
-                            None
-                        }
-                    };
-
-                    if let Some(msg) = notification {
-                        let response = OutgoingResponse::text(format!("Self-Repair: {}", msg));
-                        let _ = repair_channels.broadcast_all("default", response).await;
+        let repair_focus = self.deps.focus.clone();
+        let repair_handle = safe_mode.is_none().then(|| {
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(repair_interval).await;
+                    if repair_focus.as_ref().is_some_and(|f| f.is_active()) {
+                        continue;
                     }
-                }
 
-                // Check broken tools
-                let broken_tools = repair.detect_broken_tools().await;
-                for tool in broken_tools {
-                    tracing::info!("Attempting to repair broken tool: {}", tool.name);
-                    match repair.repair_broken_tool(&tool).await {
-                        Ok(RepairResult::Success { message }) => {
-                            let response = OutgoingResponse::text(format!(
-                                "Self-Repair: Tool '{}' repaired: {}",
-                                tool.name, message
-                            ));
-                            let _ = repair_channels.broadcast_all("default", response).await;
-                        }
-                        Ok(result) => {
-                            tracing::info!("Tool repair result: {:?}", result);
+                    // Check stuck jobs
+                    let stuck_jobs = repair.detect_stuck_jobs().await;
+                    for job in stuck_jobs {
+                        tracing::info!("Attempting to repair stuck job {}", job.job_id);
+                        let result = repair.repair_stuck_job(&job).await;
+                        let notification = match &result {
+                            Ok(RepairResult::Success { message }) => {
+                                tracing::info!("Repair succeeded: {}", message);
+                                Some(format!(
+                                    "Job {} was stuck for {}s, recovery succeeded: {}",
+                                    job.job_id,
+                                    job.stuck_duration.as_secs(),
+                                    message
+                                ))
+                            }
+                            Ok(RepairResult::Failed { message }) => {
+                                tracing::error!("Repair failed: {}", message);
+                                Some(format!(
+                                    "Job {} was stuck for {}s, recovery failed permanently: {}",
+                                    job.job_id,
+                                    job.stuck_duration.as_secs(),
+                                    message
+                                ))
+                            }
+                            Ok(RepairResult::ManualRequired { message }) => {
+                                tracing::warn!("Manual intervention needed: {}", message);
+                                Some(format!(
+                                    "Job {} needs manual intervention: {}",
+                                    job.job_id, message
+                                ))
+                            }
+                            Ok(RepairResult::Retry { message }) => {
+                                tracing::warn!("Repair needs retry: {}", message);
+                                None // Don't spam the user on retries
+                            }
+                            Err(e) => {
+                                tracing::error!("Repair error: {}", e);
+                                None
+                            }
+                        };
+
+                        if let Some(msg) = notification {
+                            let response = OutgoingResponse::text(format!("Self-Repair: {}", msg));
+                            if !repair_channels
+                                .route(
+                                    NotificationCategory::JobFinished,
+                                    "default",
+                                    response.clone(),
+                                    None,
+                                )
+                                .await
+                            {
+                                let _ = repair_channels.broadcast_all("default", response).await;
+                            }
                         }
-                        Err(e) => {
-                            tracing::error!("Tool repair error: {}", e);
+                    }
+
+                    // Check broken tools
+                    let broken_tools = repair.detect_broken_tools().await;
+                    for tool in broken_tools {
+                        tracing::info!("Attempting to repair broken tool: {}", tool.name);
+                        match repair.repair_broken_tool(&tool).await {
+                            Ok(RepairResult::Success { message }) => {
+                                let response = OutgoingResponse::text(format!(
+                                    "Self-Repair: Tool '{}' repaired: {}",
+                                    tool.name, message
+                                ));
+                                let _ = repair_channels.broadcast_all("default", response).await;
+                            }
+                            Ok(result) => {
+                                tracing::info!("Tool repair result: {:?}", result);
+                            }
+                            Err(e) => {
+                                tracing::error!("Tool repair error: {}", e);
+                            }
                         }
                     }
                 }
-            }
+            })
         });
 
         // Spawn session pruning task
         let session_mgr = self.session_manager.clone();
         let session_idle_timeout = self.config.session_idle_timeout;
+        let host_fallback = self.deps.host_fallback.clone();
         let pruning_handle = tokio::spawn(async move {
             let mut interval = tokio::time::interval(std::time::Duration::from_secs(600)); // Every 10 min
             interval.tick().await; // Skip immediate first tick
             loop {
                 interval.tick().await;
-                session_mgr.prune_stale_sessions(session_idle_timeout).await;
+                let pruned = session_mgr.prune_stale_sessions(session_idle_timeout).await;
+                // Host fallback consent ends with the session it was given in.
+                if pruned > 0
+                    && let Some(ref fallback) = host_fallback
+                {
+                    fallback.retain_threads(&session_mgr.thread_ids().await);
+                }
             }
         });
 
+        // Spawn job watcher: finished jobs go to channels with a job_finished rule
+        let job_watch_handle = {
+            let context_manager = self.context_manager.clone();
+            let scheduler = self.scheduler.clone();
+            let channels = self.channels.clone();
+            tokio::spawn(async move {
+                let mut tracker = FollowupTracker::default();
+                let mut interval = tokio::time::interval(std::time::Duration::from_secs(5));
+                loop {
+                    interval.tick().await;
+                    let jobs = job_snapshots(&context_manager, &scheduler).await;
+                    for response in tracker.poll(&jobs).0 {
+                        let notice = OutgoingResponse::text(response.content);
+                        channels
+                            .route(NotificationCategory::JobFinished, "default", notice, None)
+                            .await;
+                    }
+                }
+            })
+        };
+
+        // Spawn focus ticker: ends expired focus windows and sends the digest
+        let focus_handle = self.deps.focus.clone().map(|focus| {
+            let channels = self.channels.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
+                loop {
+                    interval.tick().await;
+                    if let Some(digest) = focus.take_expired() {
+                        let _ = channels
+                            .broadcast_all("default", OutgoingResponse::text(digest))
+                            .await;
+                    }
+                }
+            })
+        });
+
         // Spawn heartbeat if enabled
-        let heartbeat_handle = if let Some(ref hb_config) = self.heartbeat_config {
+        let heartbeat_handle = if let Some(ref hb_config) = self.heartbeat_config
+            && safe_mode.is_none()
+        {
             if hb_config.enabled {
                 if let Some(workspace) = self.workspace() {
-                    let config = AgentHeartbeatConfig::default()
-                        .with_interval(std::time::Duration::from_secs(hb_config.interval_secs));
+                    let config =
+                        AgentHeartbeatConfig::default()
+                            .with_interval(std::time::Duration::from_secs(hb_config.interval_secs))
+                            .with_locale(self.config.locale.for_channel(
+                                hb_config.notify_channel.as_deref().unwrap_or_default(),
+                            ));
 
                     // Set up notification channel
                     let (notify_tx, mut notify_rx) =
@@ -273,12 +621,23 @@ impl Agent {
                     let notify_channel = hb_config.notify_channel.clone();
                     let notify_user = hb_config.notify_user.clone();
                     let channels = self.channels.clone();
+                    let focus = self.deps.focus.clone();
                     tokio::spawn(async move {
                         while let Some(response) = notify_rx.recv().await {
+                            let Some(response) = hold_for_focus(&focus, "heartbeat", response)
+                            else {
+                                continue;
+                            };
                             let user = notify_user.as_deref().unwrap_or("default");
 
-                            // Try the configured channel first, fall back to
-                            // broadcasting on all channels.
+                            // Routing rules first, then the configured
+                            // channel, then every channel.
+                            if channels
+                                .route(digest_category(&response), user, response.clone(), None)
+                                .await
+                            {
+                                continue;
+                            }
                             let targeted_ok = if let Some(ref channel) = notify_channel {
                                 channels
                                     .broadcast(channel, user, response.clone())
@@ -325,20 +684,27 @@ impl Agent {
         };
 
         // Spawn routine engine if enabled
-        let routine_handle = if let Some(ref rt_config) = self.routine_config {
+        let routine_handle = if let Some(ref rt_config) = self.routine_config
+            && safe_mode.is_none()
+        {
             if rt_config.enabled {
                 if let (Some(store), Some(workspace)) = (self.store(), self.workspace()) {
                     // Set up notification channel (same pattern as heartbeat)
                     let (notify_tx, mut notify_rx) =
                         tokio::sync::mpsc::channel::<OutgoingResponse>(32);
 
-                    let engine = Arc::new(RoutineEngine::new(
+                    let mut engine = RoutineEngine::new(
                         rt_config.clone(),
                         Arc::clone(store),
                         self.llm().clone(),
                         Arc::clone(workspace),
                         notify_tx,
-                    ));
+                    );
+                    if let Some(ref focus) = self.deps.focus {
+                        engine = engine.with_focus(Arc::clone(focus));
+                    }
+                    engine = engine.with_tools(Arc::clone(self.tools()));
+                    let engine = Arc::new(engine);
 
                     // Register routine tools
                     self.deps
@@ -350,22 +716,51 @@ impl Agent {
 
                     // Spawn notification forwarder
                     let channels = self.channels.clone();
+                    let focus = self.deps.focus.clone();
                     tokio::spawn(async move {
                         while let Some(response) = notify_rx.recv().await {
+                            let Some(response) = hold_for_focus(&focus, "routine", response) else {
+                                continue;
+                            };
                             let user = response
                                 .metadata
                                 .get("notify_user")
                                 .and_then(|v| v.as_str())
                                 .unwrap_or("default")
                                 .to_string();
-                            let results = channels.broadcast_all(&user, response).await;
-                            for (ch, result) in results {
-                                if let Err(e) = result {
-                                    tracing::warn!(
-                                        "Failed to broadcast routine notification to {}: {}",
-                                        ch,
-                                        e
-                                    );
+                            let notify_channel = response
+                                .metadata
+                                .get("notify_channel")
+                                .and_then(|v| v.as_str())
+                                .map(String::from);
+
+                            // Same fallback as the heartbeat: routing rules,
+                            // the routine's channel, then every channel.
+                            if channels
+                                .route(digest_category(&response), &user, response.clone(), None)
+                                .await
+                            {
+                                continue;
+                            }
+                            let targeted_ok = if let Some(ref channel) = notify_channel {
+                                channels
+                                    .broadcast(channel, &user, response.clone())
+                                    .await
+                                    .is_ok()
+                            } else {
+                                false
+                            };
+
+                            if !targeted_ok {
+                                let results = channels.broadcast_all(&user, response).await;
+                                for (ch, result) in results {
+                                    if let Err(e) = result {
+                                        tracing::warn!(
+                                            "Failed to broadcast routine notification to {}: {}",
+                                            ch,
+                                            e
+                                        );
+                                    }
                                 }
                             }
                         }
@@ -376,6 +771,9 @@ impl Agent {
                         std::time::Duration::from_secs(rt_config.cron_check_interval_secs);
                     let cron_handle = spawn_cron_ticker(Arc::clone(&engine), cron_interval);
 
+                    // Spawn file watcher for file-triggered routines
+                    let watch_handle = spawn_file_watcher(Arc::clone(&engine));
+
                     // Store engine reference for event trigger checking
                     // Safety: we're in run() which takes self, no other reference exists
                     let engine_ref = Arc::clone(&engine);
@@ -388,7 +786,7 @@ impl Agent {
                         rt_config.max_concurrent_routines
                     );
 
-                    Some((cron_handle, engine_ref))
+                    Some((cron_handle, watch_handle, engine_ref))
                 } else {
                     tracing::warn!("Routines enabled but store/workspace not available");
                     None
@@ -401,11 +799,12 @@ impl Agent {
         };
 
         // Extract engine ref for use in message loop
-        let routine_engine_for_loop = routine_handle.as_ref().map(|(_, e)| Arc::clone(e));
+        let routine_engine_for_loop = routine_handle.as_ref().map(|(_, _, e)| Arc::clone(e));
 
         // Main message loop
         tracing::info!("Agent {} ready and listening", self.config.name);
 
+        let mut streams_ended = false;
         loop {
             let message = tokio::select! {
                 biased;
@@ -418,18 +817,66 @@ impl Agent {
                         Some(m) => m,
                         None => {
                             tracing::info!("All channel streams ended, shutting down...");
+                            streams_ended = true;
                             break;
                         }
                     }
                 }
             };
 
-            match self.handle_message(&message).await {
+            if let Some(ref tracker) = self.deps.misbehavior {
+                tracker.note_activity(&message.channel, &message.user_id);
+            }
+
+            if let Some(ref switch) = self.deps.dead_man
+                && switch.record_interaction(&message.user_id)
+            {
+                let _ = self
+                    .channels
+                    .respond(
+                        &message,
+                        OutgoingResponse::text(
+                            "Escalation acknowledged. Safe mode ends with the next start.",
+                        ),
+                    )
+                    .await;
+            }
+
+            if let Some(ref away) = self.deps.away {
+                let role = self.sender_role(away, &message);
+                let now = chrono::Utc::now();
+                if let Some(action) = away.intercept(&message, role, now) {
+                    if let Some(reply) = action.reply {
+                        let _ = self
+                            .channels
+                            .respond(&message, OutgoingResponse::text(reply))
+                            .await;
+                    }
+                    if action.queued {
+                        continue;
+                    }
+                } else if role == SenderRole::Owner && !away.is_away(now) && away.queued() > 0 {
+                    let backlog = self.summarize_backlog(away.take_backlog()).await;
+                    let _ = self
+                        .channels
+                        .respond(&message, OutgoingResponse::text(backlog))
+                        .await;
+                }
+            }
+
+            let turn = self.deps.load.as_ref().map(|l| l.begin());
+            let result = self.handle_message(&message).await;
+            drop(turn);
+
+            match result {
                 Ok(Some(response)) if !response.is_empty() => {
+                    let delivery_started = Instant::now();
                     let _ = self
                         .channels
-                        .respond(&message, OutgoingResponse::text(response))
+                        .respond_final(&message, OutgoingResponse::text(response))
                         .await;
+                    self.profiler
+                        .record(Phase::Delivery, None, delivery_started);
                 }
                 Ok(Some(_)) => {
                     // Empty response, nothing to send (e.g. approval handled via send_status)
@@ -437,16 +884,29 @@ impl Agent {
                 Ok(None) => {
                     // Shutdown signal received (/quit, /exit, /shutdown)
                     tracing::info!("Shutdown command received, exiting...");
+                    self.profiler.discard();
                     break;
                 }
                 Err(e) => {
                     tracing::error!("Error handling message: {}", e);
+                    if let Error::Llm(LlmError::AuthFailed { ref provider }) = e
+                        && let Some(ref switch) = self.deps.dead_man
+                    {
+                        switch.record_failure(
+                            CriticalFailure::LlmAuth,
+                            &format!("{} rejected the credentials", provider),
+                        );
+                    }
+                    let delivery_started = Instant::now();
                     let _ = self
                         .channels
                         .respond(&message, OutgoingResponse::text(format!("Error: {}", e)))
                         .await;
+                    self.profiler
+                        .record(Phase::Delivery, None, delivery_started);
                 }
             }
+            self.profiler.finish();
 
             // Check event triggers (cheap in-memory regex, fires async if matched)
             if let Some(ref engine) = routine_engine_for_loop {
@@ -457,15 +917,33 @@ impl Agent {
             }
         }
 
+        if streams_ended && let Some(wait) = self.followup_wait {
+            self.wait_for_followups(wait, routine_engine_for_loop.as_deref())
+                .await;
+        }
+
         // Cleanup
         tracing::info!("Agent shutting down...");
-        repair_handle.abort();
+        if let Some(handle) = repair_handle {
+            handle.abort();
+        }
+        if let Some(handle) = dead_man_handle {
+            handle.abort();
+        }
+        if let Some(handle) = quarantine_handle {
+            handle.abort();
+        }
         pruning_handle.abort();
+        job_watch_handle.abort();
+        if let Some(handle) = focus_handle {
+            handle.abort();
+        }
         if let Some(handle) = heartbeat_handle {
             handle.abort();
         }
-        if let Some((cron_handle, _)) = routine_handle {
+        if let Some((cron_handle, watch_handle, _)) = routine_handle {
             cron_handle.abort();
+            watch_handle.abort();
         }
         self.scheduler.stop_all().await;
         self.channels.shutdown_all().await?;
@@ -473,10 +951,39 @@ impl Agent {
         Ok(())
     }
 
+    /// Report jobs as they finish until nothing started by the handled
+    /// messages is still running, or `wait` runs out.
+    async fn wait_for_followups(&self, wait: Duration, routines: Option<&RoutineEngine>) {
+        let deadline = Instant::now() + wait;
+        let mut tracker = FollowupTracker::default();
+        loop {
+            let jobs = job_snapshots(&self.context_manager, &self.scheduler).await;
+            let (finished, pending) = tracker.poll(&jobs);
+            for response in finished {
+                let _ = self.channels.broadcast_all("default", response).await;
+            }
+
+            let running_routines = routines.map_or(0, RoutineEngine::running);
+            if (pending.is_empty() && running_routines == 0) || Instant::now() >= deadline {
+                let done = followups::done_notification(&pending, running_routines);
+                let _ = self.channels.broadcast_all("default", done).await;
+                return;
+            }
+            tokio::time::sleep(followups::POLL_INTERVAL).await;
+        }
+    }
+
     async fn handle_message(&self, message: &IncomingMessage) -> Result<Option<String>, Error> {
         // Parse submission type first
         let submission = SubmissionParser::parse(&message.content);
 
+        // Only user input counts as a turn for profiling; control
+        // submissions are cheap and would skew the percentiles.
+        if matches!(submission, Submission::UserInput { .. }) {
+            self.profiler.begin(&message.channel);
+            self.observe_sender(message);
+        }
+
         // Hydrate thread from DB if it's a historical thread not in memory
         if let Some(ref external_thread_id) = message.thread_id {
             self.maybe_hydrate_thread(message, external_thread_id).await;
@@ -505,6 +1012,7 @@ impl Agent {
         if let Some(pending) = pending_auth {
             match &submission {
                 Submission::UserInput { content } => {
+                    self.profiler.discard();
                     return self
                         .process_auth_token(message, &pending, content, session, thread_id)
                         .await;
@@ -520,6 +1028,16 @@ impl Agent {
             }
         }
 
+        // Structured input interception: while an `ask_user` form is open,
+        // the next user message on the thread is its answer.
+        let pending_input = {
+            let sess = session.lock().await;
+            sess.threads
+                .get(&thread_id)
+                .filter(|t| t.state == ThreadState::AwaitingInput)
+                .and_then(|t| t.pending_input.clone())
+        };
+
         tracing::debug!(
             "Received message from {} on {} ({} chars)",
             message.user_id,
@@ -529,10 +1047,40 @@ impl Agent {
 
         // Process based on submission type
         let result = match submission {
-            Submission::UserInput { content } => {
-                self.process_user_input(message, session, thread_id, &content)
-                    .await
-            }
+            Submission::UserInput { content } => match pending_input {
+                Some(pending) => {
+                    self.process_input_answer(message, session, thread_id, pending, &content)
+                        .await
+                }
+                None => {
+                    self.publish_event(BusEvent::TurnStarted {
+                        user_id: message.user_id.clone(),
+                        channel: message.channel.clone(),
+                        thread_id,
+                    })
+                    .await;
+                    let result = self
+                        .process_user_input(message, session, thread_id, &content)
+                        .await;
+                    let outcome = match result {
+                        Ok(SubmissionResult::Response { .. } | SubmissionResult::Ok { .. }) => {
+                            TurnOutcome::Completed
+                        }
+                        Ok(SubmissionResult::NeedApproval { .. }) => TurnOutcome::AwaitingApproval,
+                        Ok(SubmissionResult::NeedInput { .. }) => TurnOutcome::AwaitingInput,
+                        Ok(SubmissionResult::Interrupted) => TurnOutcome::Interrupted,
+                        Ok(SubmissionResult::Error { .. }) | Err(_) => TurnOutcome::Failed,
+                    };
+                    self.publish_event(BusEvent::TurnFinished {
+                        user_id: message.user_id.clone(),
+                        channel: message.channel.clone(),
+                        thread_id,
+                        outcome,
+                    })
+                    .await;
+                    result
+                }
+            },
             Submission::SystemCommand { command, args } => {
                 self.handle_system_command(&command, &args).await
             }
@@ -545,6 +1093,21 @@ impl Agent {
             Submission::Heartbeat => self.process_heartbeat().await,
             Submission::Summarize => self.process_summarize(session, thread_id).await,
             Submission::Suggest => self.process_suggest(session, thread_id).await,
+            Submission::Export { format } => {
+                self.process_export(session, thread_id, format.as_deref())
+                    .await
+            }
+            Submission::Project { args } => self.process_project(session, &args).await,
+            Submission::Approvals { args } => self.process_approvals(message, session, &args).await,
+            Submission::Elevated { args } => self.process_elevated(message, session, &args).await,
+            Submission::Budget { args } => Ok(self.process_budget(message, &args)),
+            Submission::Generation { setting, args } => {
+                self.process_generation(session, &setting, &args).await
+            }
+            Submission::Sandbox { args } => self.process_sandbox(message, thread_id, &args),
+            Submission::Redact { args } => self.process_redact(session, thread_id, &args).await,
+            Submission::Redacted { args } => self.process_redacted(message, session, &args).await,
+            Submission::Glossary { args } => self.process_glossary(&args).await,
             Submission::Quit => return Ok(None),
             Submission::SwitchThread { thread_id: target } => {
                 self.process_switch_thread(message, target).await
@@ -585,6 +1148,20 @@ impl Agent {
                 description,
                 parameters,
             } => {
+                // Point channels routed for approvals at the waiting prompt.
+                let notice = OutgoingResponse::text(format!(
+                    "Approval needed on {}: {} ({})",
+                    message.channel, tool_name, description
+                ));
+                self.channels
+                    .route(
+                        NotificationCategory::ApprovalNeeded,
+                        &message.user_id,
+                        notice,
+                        Some(&message.channel),
+                    )
+                    .await;
+
                 // Each channel renders the approval prompt via send_status.
                 // Web gateway shows an inline card, REPL prints a formatted prompt, etc.
                 let _ = self
@@ -604,6 +1181,42 @@ impl Agent {
                 // Empty string signals the caller to skip respond() (no duplicate text)
                 Ok(Some(String::new()))
             }
+            SubmissionResult::NeedInput {
+                request_id,
+                form,
+                error,
+            } => {
+                let prompt = match error {
+                    Some(error) => format!("{}\n\n{}", error, form.render_text()),
+                    None => form.render_text(),
+                };
+
+                // Every channel gets the text prompt. Channels with
+                // quick-reply buttons (Telegram, Slack) turn `reply_options`
+                // into buttons; the rest show the numbered list.
+                let mut response = OutgoingResponse::text(prompt.clone());
+                let reply_options = form.reply_options();
+                if !reply_options.is_empty() {
+                    response.metadata = serde_json::json!({ "reply_options": reply_options });
+                }
+                let _ = self.channels.respond(message, response).await;
+
+                // The web gateway additionally renders a form card.
+                let _ = self
+                    .channels
+                    .send_status(
+                        &message.channel,
+                        StatusUpdate::InputRequested {
+                            request_id: request_id.to_string(),
+                            prompt,
+                            form: serde_json::to_value(&form).unwrap_or_default(),
+                        },
+                        &message.metadata,
+                    )
+                    .await;
+
+                Ok(Some(String::new()))
+            }
         }
     }
 
@@ -668,23 +1281,30 @@ impl Agent {
             thread.restore_from_messages(chat_messages);
         }
 
-        // Restore response chain from conversation metadata
+        // Restore response chain and generation settings from conversation metadata
+        let mut generation = GenerationPrefs::default();
         if let Some(store) = self.store()
             && let Ok(Some(metadata)) = store.get_conversation_metadata(thread_uuid).await
-            && let Some(rid) = metadata
+        {
+            generation = GenerationPrefs::from_metadata(&metadata);
+            if let Some(rid) = metadata
                 .get("last_response_id")
                 .and_then(|v| v.as_str())
                 .map(String::from)
-        {
-            thread.last_response_id = Some(rid.clone());
-            self.llm()
-                .seed_response_chain(&thread_uuid.to_string(), rid);
-            tracing::debug!("Restored response chain for thread {}", thread_uuid);
+            {
+                thread.last_response_id = Some(rid.clone());
+                self.llm()
+                    .seed_response_chain(&thread_uuid.to_string(), rid);
+                tracing::debug!("Restored response chain for thread {}", thread_uuid);
+            }
         }
 
         // Insert into session and register with session manager
         {
             let mut sess = session.lock().await;
+            if GenerationPrefs::from_metadata(&sess.metadata).is_default() {
+                generation.write_to(&mut sess.metadata);
+            }
             sess.threads.insert(thread_uuid, thread);
             sess.active_thread = Some(thread_uuid);
             sess.last_active_at = chrono::Utc::now();
@@ -706,6 +1326,38 @@ impl Agent {
         );
     }
 
+    /// Run the attachments in the message metadata through the pipeline
+    /// and wrap the summary like tool output: the content is untrusted.
+    async fn read_attachments(&self, message: &IncomingMessage) -> Option<String> {
+        let pipeline = self.deps.attachments.as_ref()?;
+        let attachments = Attachment::from_metadata(&message.metadata);
+        if attachments.is_empty() {
+            return None;
+        }
+        let _ = self
+            .channels
+            .send_status(
+                &message.channel,
+                StatusUpdate::Status(format!(
+                    "Reading {} attachment{}...",
+                    attachments.len(),
+                    if attachments.len() == 1 { "" } else { "s" }
+                )),
+                &message.metadata,
+            )
+            .await;
+        let summary = pipeline.process(&attachments).await;
+        let sanitized = self.safety().sanitize_tool_output(
+            &self.person(message).0,
+            "attachments",
+            &summary.render(),
+        );
+        Some(
+            self.safety()
+                .wrap_for_llm("attachments", &sanitized.content, sanitized.was_modified),
+        )
+    }
+
     async fn process_user_input(
         &self,
         message: &IncomingMessage,
@@ -713,6 +1365,9 @@ impl Agent {
         thread_id: Uuid,
         content: &str,
     ) -> Result<SubmissionResult, Error> {
+        // `/again <text>` (or metadata `"dedup": false`) forces a fresh turn.
+        let (content, bypass_dedup) = dedup::strip_override(content, &message.metadata);
+
         // First check thread state without holding lock during I/O
         let thread_state = {
             let sess = session.lock().await;
@@ -723,27 +1378,13 @@ impl Agent {
             thread.state
         };
 
-        // Check thread state
-        match thread_state {
-            ThreadState::Processing => {
-                return Ok(SubmissionResult::error(
-                    "Turn in progress. Use /interrupt to cancel.",
-                ));
-            }
-            ThreadState::AwaitingApproval => {
-                return Ok(SubmissionResult::error(
-                    "Waiting for approval. Use /interrupt to cancel.",
-                ));
-            }
-            ThreadState::Completed => {
-                return Ok(SubmissionResult::error(
-                    "Thread completed. Use /thread new.",
-                ));
-            }
-            ThreadState::Idle | ThreadState::Interrupted => {
-                // Can proceed
-            }
-        }
+        let _crash_context = crate::crash::enter(format!(
+            "turn thread={} channel={} user={} input={:?}",
+            thread_id,
+            message.channel,
+            message.user_id,
+            content.chars().take(80).collect::<String>()
+        ));
 
         // Safety validation for user input
         let validation = self.safety().validate_input(content);
@@ -774,8 +1415,55 @@ impl Agent {
             content: content.to_string(),
             ..message.clone()
         };
+        let intent = self.router.route_command(&temp_message);
+
+        // Reuse the result of an identical message from this user that is
+        // still running or was answered within the dedup window, in any
+        // thread. This runs before the busy-thread checks so a double-send
+        // waits for the first turn. Commands and short replies always get
+        // their own turn.
+        let dedup_ticket =
+            if bypass_dedup || intent.is_some() || dedup::is_exempt(content, thread_state) {
+                None
+            } else {
+                let attachments = Attachment::from_metadata(&message.metadata);
+                match self.dedup.check(&message.user_id, content, &attachments) {
+                    DedupOutcome::Fresh(ticket) => Some(ticket),
+                    DedupOutcome::Completed(response) => {
+                        tracing::debug!("Reusing recent response for duplicate message");
+                        return Ok(SubmissionResult::response(response));
+                    }
+                    DedupOutcome::InFlight(rx) => {
+                        tracing::debug!("Duplicate message in flight, waiting for its result");
+                        match dedup::wait_for_in_flight(rx).await {
+                            Some(response) => return Ok(SubmissionResult::response(response)),
+                            // Original turn was abandoned; run our own.
+                            None => None,
+                        }
+                    }
+                }
+            };
+
+        // Check thread state
+        let busy = match thread_state {
+            ThreadState::Processing => Some("Turn in progress. Use /interrupt to cancel."),
+            ThreadState::AwaitingApproval => {
+                Some("Waiting for approval. Use /interrupt to cancel.")
+            }
+            ThreadState::AwaitingInput => {
+                Some("Waiting for your answer. Use /interrupt to cancel.")
+            }
+            ThreadState::Completed => Some("Thread completed. Use /thread new."),
+            ThreadState::Idle | ThreadState::Interrupted => None,
+        };
+        if let Some(reason) = busy {
+            if let Some(ticket) = dedup_ticket {
+                self.dedup.abandon(ticket);
+            }
+            return Ok(SubmissionResult::error(reason));
+        }
 
-        if let Some(intent) = self.router.route_command(&temp_message) {
+        if let Some(intent) = intent {
             // Explicit command like /status, /job, /list - handle directly
             return self.handle_job_or_command(intent, message).await;
         }
@@ -783,6 +1471,19 @@ impl Agent {
         // Natural language goes through the agentic loop
         // Job tools (create_job, list_jobs, etc.) are in the tool registry
 
+        // Extracted attachment content travels with the message text as
+        // part of the turn's stored input.
+        let with_attachments;
+        let content = match self.read_attachments(message).await {
+            Some(block) => {
+                with_attachments = format!("{}\n\n{}", content, block);
+                with_attachments.as_str()
+            }
+            None => content,
+        };
+
+        self.profiler.mark(Phase::Routing, None);
+
         // Auto-compact if needed BEFORE adding new turn
         {
             let mut sess = session.lock().await;
@@ -809,13 +1510,17 @@ impl Agent {
                     )
                     .await;
 
+                let compaction_started = Instant::now();
                 let compactor = ContextCompactor::new(self.llm().clone());
+                let workspace = self.message_workspace(message).await;
                 if let Err(e) = compactor
-                    .compact(thread, strategy, self.workspace().map(|w| w.as_ref()))
+                    .compact(thread, strategy, workspace.as_deref())
                     .await
                 {
                     tracing::warn!("Auto-compaction failed: {}", e);
                 }
+                self.profiler
+                    .record(Phase::Compaction, None, compaction_started);
             }
         }
 
@@ -870,6 +1575,9 @@ impl Agent {
             .ok_or_else(|| Error::from(crate::error::JobError::NotFound { id: thread_id }))?;
 
         if thread.state == ThreadState::Interrupted {
+            if let Some(ticket) = dedup_ticket {
+                self.dedup.abandon(ticket);
+            }
             let _ = self
                 .channels
                 .send_status(
@@ -884,6 +1592,9 @@ impl Agent {
         // Complete, fail, or request approval
         match result {
             Ok(AgenticLoopResult::Response(response)) => {
+                if let Some(ticket) = dedup_ticket {
+                    self.dedup.complete(ticket, &response);
+                }
                 thread.complete_turn(&response);
                 self.persist_response_chain(thread);
                 let _ = self
@@ -897,10 +1608,21 @@ impl Agent {
 
                 // Fire-and-forget: persist turn to DB
                 self.persist_turn(thread_id, &message.user_id, content, Some(&response));
+                if let Some(ref index) = self.deps.history_index {
+                    index.record(IndexedTurn {
+                        channel: message.channel.clone(),
+                        user_input: content.to_string(),
+                        response: Some(response.clone()),
+                        at: chrono::Utc::now(),
+                    });
+                }
 
                 Ok(SubmissionResult::response(response))
             }
             Ok(AgenticLoopResult::NeedApproval { pending }) => {
+                if let Some(ticket) = dedup_ticket {
+                    self.dedup.abandon(ticket);
+                }
                 // Store pending approval in thread and update state
                 let request_id = pending.request_id;
                 let tool_name = pending.tool_name.clone();
@@ -922,7 +1644,23 @@ impl Agent {
                     parameters,
                 })
             }
+            Ok(AgenticLoopResult::NeedInput { pending }) => {
+                if let Some(ticket) = dedup_ticket {
+                    self.dedup.abandon(ticket);
+                }
+                let request_id = pending.request_id;
+                let form = pending.form.clone();
+                thread.await_input(pending);
+                Ok(SubmissionResult::NeedInput {
+                    request_id,
+                    form,
+                    error: None,
+                })
+            }
             Err(e) => {
+                if let Some(ticket) = dedup_ticket {
+                    self.dedup.abandon(ticket);
+                }
                 thread.fail_turn(e.to_string());
 
                 // Persist the user message even on failure
@@ -933,6 +1671,12 @@ impl Agent {
         }
     }
 
+    async fn publish_event(&self, event: BusEvent) {
+        if let Some(ref events) = self.deps.events {
+            events.publish(event).await;
+        }
+    }
+
     /// Fire-and-forget: persist a turn (user message + optional assistant response) to the DB.
     fn persist_turn(
         &self,
@@ -1020,6 +1764,9 @@ impl Agent {
     /// When `resume_after_tool` is true the loop already knows a tool was
     /// executed earlier in this turn (e.g. an approved tool), so it won't
     /// force the LLM to use tools if it responds with text.
+    ///
+    /// With model tiers configured, the turn runs on the tier the policy
+    /// picks and its outcome feeds back into later picks.
     async fn run_agentic_loop(
         &self,
         message: &IncomingMessage,
@@ -1027,9 +1774,100 @@ impl Agent {
         thread_id: Uuid,
         initial_messages: Vec<ChatMessage>,
         resume_after_tool: bool,
+    ) -> Result<AgenticLoopResult, Error> {
+        let result = self
+            .run_tiered_loop(
+                message,
+                session,
+                thread_id,
+                initial_messages,
+                resume_after_tool,
+            )
+            .await;
+
+        // The turn is over unless it is waiting on the user; its scratchpad
+        // notes go with it.
+        let paused = matches!(
+            result,
+            Ok(AgenticLoopResult::NeedApproval { .. } | AgenticLoopResult::NeedInput { .. })
+        );
+        if !paused && let Some(ref scratchpad) = self.deps.scratchpad {
+            let discarded = scratchpad.discard(thread_id);
+            if discarded > 0 {
+                tracing::debug!("Discarded {} scratchpad note(s)", discarded);
+            }
+        }
+        result
+    }
+
+    /// Run the loop on the model tier picked for this turn, if tiers are on.
+    async fn run_tiered_loop(
+        &self,
+        message: &IncomingMessage,
+        session: Arc<Mutex<Session>>,
+        thread_id: Uuid,
+        initial_messages: Vec<ChatMessage>,
+        resume_after_tool: bool,
+    ) -> Result<AgenticLoopResult, Error> {
+        let Some(tiers) = self.deps.model_tiers.clone() else {
+            return self
+                .agentic_loop(
+                    message,
+                    session,
+                    thread_id,
+                    initial_messages,
+                    resume_after_tool,
+                    None,
+                )
+                .await;
+        };
+
+        let recent_tool_calls = {
+            let sess = session.lock().await;
+            sess.threads
+                .get(&thread_id)
+                .map(|thread| {
+                    thread
+                        .turns
+                        .iter()
+                        .rev()
+                        .take(RECENT_TURNS_FOR_TIER)
+                        .map(|turn| turn.tool_calls.len())
+                        .sum()
+                })
+                .unwrap_or(0)
+        };
+        let signals = TurnSignals::new(&message.content, recent_tool_calls);
+        let tier = tiers.select(&signals);
+        tracing::debug!(%tier, kind = ?signals.kind(), "Selected model tier");
+
+        let result = self
+            .agentic_loop(
+                message,
+                session,
+                thread_id,
+                initial_messages,
+                resume_after_tool,
+                Some(tier),
+            )
+            .await;
+        tiers.record_outcome(tier, signals.kind(), result.is_ok());
+        result
+    }
+
+    async fn agentic_loop(
+        &self,
+        message: &IncomingMessage,
+        session: Arc<Mutex<Session>>,
+        thread_id: Uuid,
+        initial_messages: Vec<ChatMessage>,
+        resume_after_tool: bool,
+        tier: Option<ModelTier>,
     ) -> Result<AgenticLoopResult, Error> {
         // Load workspace system prompt (identity files: AGENTS.md, SOUL.md, etc.)
-        let system_prompt = if let Some(ws) = self.workspace() {
+        let prompt_started = Instant::now();
+        let workspace = self.message_workspace(message).await;
+        let system_prompt = if let Some(ws) = workspace.as_ref() {
             match ws.system_prompt().await {
                 Ok(prompt) if !prompt.is_empty() => Some(prompt),
                 Ok(_) => None,
@@ -1042,20 +1880,98 @@ impl Agent {
             None
         };
 
-        let mut reasoning = Reasoning::new(self.llm().clone(), self.safety().clone());
-        if let Some(prompt) = system_prompt {
-            reasoning = reasoning.with_system_prompt(prompt);
-        }
+        // A bound project adds its context to the prompt and scopes tool use.
+        let (session_id, project) = {
+            let sess = session.lock().await;
+            (sess.id, sess.project.clone())
+        };
+        let system_prompt = match (system_prompt, project.as_ref()) {
+            (Some(prompt), Some(p)) => Some(format!("{}\n\n{}", prompt, p.context_block())),
+            (None, Some(p)) => Some(p.context_block()),
+            (prompt, None) => prompt,
+        };
+
+        // Tell the model the user's local time so "at 6pm" resolves correctly.
+        let (user_id, contact) = self.person(message);
+        let locale = UserLocale::resolve(
+            self.store(),
+            &user_id,
+            &message.channel,
+            &self.config.locale,
+        )
+        .await;
+        let time_line = locale.prompt_line(chrono::Utc::now());
+        let system_prompt = match system_prompt {
+            Some(prompt) => format!("{}\n\n{}", prompt, time_line),
+            None => time_line,
+        };
+
+        // Glossary terms that come up in this turn; the answer is held to
+        // the whole glossary before it is returned.
+        let glossary = match workspace.as_ref() {
+            Some(ws) => ws.glossary().await.unwrap_or_else(|e| {
+                tracing::debug!("Could not load glossary: {}", e);
+                Glossary::default()
+            }),
+            None => Glossary::default(),
+        };
+        let turn_text = initial_messages
+            .iter()
+            .rev()
+            .find(|m| m.role == Role::User)
+            .map_or(message.content.as_str(), |m| m.content.as_str());
+        let system_prompt = match glossary.prompt_section(turn_text) {
+            Some(section) => format!("{}\n\n{}", system_prompt, section),
+            None => system_prompt,
+        };
+
+        // Sources are numbered in the tool results for the answer to cite.
+        let system_prompt = match &self.deps.citations {
+            Some(policy) => format!("{}\n\n{}", system_prompt, policy.prompt_section()),
+            None => system_prompt,
+        };
+
+        let llm = match (&self.deps.model_tiers, tier) {
+            (Some(tiers), Some(tier)) => tiers.provider(tier).clone(),
+            _ => self.llm().clone(),
+        };
+        let generation = GenerationPrefs::from_metadata(&session.lock().await.metadata);
+        let reasoning = Reasoning::new(llm.clone(), self.safety().clone())
+            .with_system_prompt(system_prompt)
+            .with_temperature(generation.temperature())
+            .with_max_tokens(generation.max_tokens())
+            .with_style_guidance(generation.prompt_section());
+        self.profiler
+            .record(Phase::PromptBuild, None, prompt_started);
 
         // Build context with messages that we'll mutate during the loop
         let mut context_messages = initial_messages;
 
-        // Create a JobContext for tool execution (chat doesn't have a real job)
-        let job_ctx = JobContext::with_user(&message.user_id, "chat", "Interactive chat session");
+        // Create a JobContext for tool execution (chat doesn't have a real job).
+        // The thread and turn identify where outbound content came from.
+        let turn = session
+            .lock()
+            .await
+            .threads
+            .get(&thread_id)
+            .and_then(|t| t.last_turn().map(|turn| turn.turn_number));
+        let mut job_ctx = JobContext::with_user(&user_id, "chat", "Interactive chat session");
+        job_ctx.conversation_id = Some(thread_id);
+        job_ctx.session_id = Some(session_id);
+        job_ctx.metadata = serde_json::json!({ "origin_channel": message.channel, "turn": turn });
+        job_ctx.working_dir = project.as_ref().map(|p| p.root.clone());
+        job_ctx.timezone = Some(locale.tz.name().to_string());
+        job_ctx.contact = contact;
+        if self.deps.agents.is_some() {
+            job_ctx.memory = workspace.as_ref().map(|ws| ws.owner());
+        }
+        let policy = project.as_ref().map(|p| &p.policy);
 
         const MAX_TOOL_ITERATIONS: usize = 10;
         let mut iteration = 0;
         let mut tools_executed = resume_after_tool;
+        let mut citations_retried = false;
+        let mut recovery = RecoveryBudget::new(self.config.turn_recovery_attempts);
 
         loop {
             iteration += 1;
@@ -1081,27 +1997,123 @@ impl Agent {
                 }
             }
 
-            // Refresh tool definitions each iteration so newly built tools become visible
-            let tool_defs = self.tools().tool_definitions().await;
+            // Refresh tool definitions each iteration so newly built tools become visible,
+            // annotated with hints from recent failures of the same tool.
+            let build_started = Instant::now();
+            let mut tool_defs = self
+                .tool_hints
+                .annotate(&message.user_id, self.tools().tool_definitions().await);
+            if let Some(policy) = policy {
+                tool_defs.retain(|def| !policy.is_disabled(&def.name));
+            }
 
             // Call LLM with current context
+            let skill = {
+                let sess = session.lock().await;
+                sess.threads
+                    .get(&thread_id)
+                    .and_then(|t| t.last_turn())
+                    .and_then(|turn| turn_skill(&self.deps.skill_tools, &turn.tool_calls))
+            };
             let context = ReasoningContext::new()
                 .with_messages(context_messages.clone())
                 .with_tools(tool_defs)
-                .with_metadata({
-                    let mut m = std::collections::HashMap::new();
-                    m.insert("thread_id".to_string(), thread_id.to_string());
-                    m
-                });
+                .with_metadata(request_metadata(thread_id, skill));
+            self.profiler
+                .record(Phase::PromptBuild, None, build_started);
+
+            // Stop before the call once a budget cap is reached.
+            if let Some(ref budget) = self.deps.budget
+                && let Err(exceeded) = budget.check(&user_id, session_id)
+            {
+                tracing::info!("Budget cap reached for {}: {}", user_id, exceeded.scope);
+                return Ok(AgenticLoopResult::Response(exceeded.message()));
+            }
 
-            let output = reasoning.respond_with_tools(&context).await?;
+            let llm_started = Instant::now();
+            let output = if self.config.stream_responses {
+                let (chunks, forwarder) = self.forward_reply_chunks(message);
+                let output = reasoning
+                    .respond_with_tools_streaming(&context, &chunks)
+                    .await;
+                drop(chunks);
+                let _ = forwarder.await;
+                output
+            } else {
+                reasoning.respond_with_tools(&context).await
+            };
+            self.profiler
+                .record(Phase::Llm, Some(&iteration.to_string()), llm_started);
+            let output = match output {
+                Ok(output) => output,
+                Err(e) => {
+                    let Some(attempt) = recovery.take(&e) else {
+                        return Err(e.into());
+                    };
+                    // Resume from the context as it stands: every tool
+                    // result so far is in it, plus a note on what broke.
+                    let summary = recovery::summarize(&e);
+                    tracing::warn!(
+                        "LLM call failed mid-turn, resuming (attempt {}/{}): {}",
+                        attempt,
+                        recovery.max_attempts(),
+                        summary
+                    );
+                    {
+                        let mut sess = session.lock().await;
+                        if let Some(thread) = sess.threads.get_mut(&thread_id)
+                            && let Some(turn) = thread.last_turn_mut()
+                        {
+                            turn.record_recovery(attempt, summary.clone());
+                        }
+                    }
+                    let _ = self
+                        .channels
+                        .send_status(
+                            &message.channel,
+                            StatusUpdate::Status(format!(
+                                "Step failed, retrying ({}/{})...",
+                                attempt,
+                                recovery.max_attempts()
+                            )),
+                            &message.metadata,
+                        )
+                        .await;
+                    tokio::time::sleep(recovery::delay(&e, attempt)).await;
+                    context_messages.push(recovery::recovery_note(&summary));
+                    // The failed call did no work; don't count it against
+                    // the iteration limit.
+                    iteration -= 1;
+                    continue;
+                }
+            };
 
             // Track token usage for budget enforcement
             tracing::debug!(
-                "LLM call used {} input + {} output tokens",
+                "LLM call used {} input ({} cached, {} written to cache) + {} output tokens",
                 output.usage.input_tokens,
+                output.usage.cache_read_tokens,
+                output.usage.cache_write_tokens,
                 output.usage.output_tokens
             );
+            if let (Some(tiers), Some(tier)) = (&self.deps.model_tiers, tier) {
+                tiers.record_usage(tier, output.usage.input_tokens, output.usage.output_tokens);
+            }
+            if let Some(ref budget) = self.deps.budget {
+                let (input, output_tokens) =
+                    (output.usage.input_tokens, output.usage.output_tokens);
+                let cost = llm.calculate_cost(input, output_tokens);
+                for warning in budget.record(&user_id, session_id, input, output_tokens, cost) {
+                    let _ = self
+                        .channels
+                        .send_status(
+                            &message.channel,
+                            StatusUpdate::Status(warning.message()),
+                            &message.metadata,
+                        )
+                        .await;
+                }
+            }
 
             match output.result {
                 RespondResult::Text(text) => {
@@ -1120,6 +2132,53 @@ impl Agent {
                         continue;
                     }
 
+                    // transformResponse: rewrite avoided and banned wording.
+                    let (text, replaced) = glossary.enforce(&text);
+                    if replaced > 0 {
+                        tracing::debug!("Glossary rewrote {} phrase(s) in the response", replaced);
+                    }
+
+                    // Resolve citations; a strict skill's answer gets one
+                    // chance to source its uncited claims before they go.
+                    let text = match &self.deps.citations {
+                        Some(policy) => {
+                            let ledger = SourceLedger::from_messages(&context_messages);
+                            let mut text = text;
+                            if policy.is_strict(&ledger) {
+                                let uncited = SourceLedger::uncited_claims(&text);
+                                if !uncited.is_empty() && !citations_retried {
+                                    citations_retried = true;
+                                    tracing::debug!(
+                                        "{} uncited claim(s) in a strict answer, asking for sources",
+                                        uncited.len()
+                                    );
+                                    context_messages.push(ChatMessage::assistant(&text));
+                                    context_messages.push(ChatMessage::user(format!(
+                                        "These statements cite no source: {}. Add a [S<n>] \
+                                         citation to each, or remove it if no tool result \
+                                         supports it. Reply with the full revised answer.",
+                                        uncited
+                                            .iter()
+                                            .map(|c| format!("\"{}\"", c))
+                                            .collect::<Vec<_>>()
+                                            .join(", ")
+                                    )));
+                                    continue;
+                                }
+                                if !uncited.is_empty() {
+                                    text = SourceLedger::drop_claims(&text, &uncited);
+                                }
+                            }
+                            let dialect = self
+                                .channels
+                                .message_capabilities(&message.channel)
+                                .await
+                                .markdown;
+                            ledger.render(&text, dialect)
+                        }
+                        None => text,
+                    };
+
                     // Tools have been executed or we've tried multiple times, return response
                     return Ok(AgenticLoopResult::Response(text));
                 }
@@ -1163,33 +2222,52 @@ impl Agent {
 
                     // Execute each tool (with approval checking)
                     for tc in tool_calls {
+                        if policy.is_some_and(|p| p.is_disabled(&tc.name)) {
+                            context_messages.push(ChatMessage::tool_result(
+                                &tc.id,
+                                &tc.name,
+                                format!(
+                                    "Error: tool '{}' is disabled by the project policy",
+                                    tc.name
+                                ),
+                            ));
+                            continue;
+                        }
+                        let policy_requires_approval =
+                            policy.is_some_and(|p| p.requires_approval(&tc.name));
+
                         // Check if tool requires approval
+                        let params = approvals::call_params(&tc.arguments);
                         if let Some(tool) = self.tools().get(&tc.name).await
-                            && tool.requires_approval()
+                            && (tool.requires_approval_for(&tc.arguments)
+                                || tool.requires_explicit_approval(&params)
+                                || policy_requires_approval)
                         {
-                            // Check if auto-approved for this session
-                            let mut is_auto_approved = {
-                                let sess = session.lock().await;
-                                sess.is_tool_auto_approved(&tc.name)
+                            // Check if auto-approved for this session or by a
+                            // remembered approval rule; the project policy
+                            // overrides both.
+                            let mut is_auto_approved = !policy_requires_approval && {
+                                let (auto_approved, project_root) = {
+                                    let sess = session.lock().await;
+                                    (
+                                        sess.is_tool_auto_approved(&tc.name),
+                                        sess.project.as_ref().map(|p| p.root.clone()),
+                                    )
+                                };
+                                auto_approved
+                                    || self
+                                        .approval_rules(message, &session)
+                                        .await
+                                        .find(&tc.name, &tc.arguments, project_root.as_deref())
+                                        .is_some()
                             };
 
-                            // For shell commands, override auto-approval for
-                            // destructive patterns that should always require
-                            // explicit per-invocation approval.
-                            if is_auto_approved
-                                && tc.name == "shell"
-                                && let Some(cmd) = tc
-                                    .arguments
-                                    .as_str()
-                                    .and_then(|s| serde_json::from_str::<serde_json::Value>(s).ok())
-                                    .and_then(|v| {
-                                        v.get("command").and_then(|c| c.as_str().map(String::from))
-                                    })
-                                && crate::tools::builtin::shell::requires_explicit_approval(&cmd)
-                            {
+                            // Destructive shell commands, outgoing mail and the
+                            // like are approved one call at a time.
+                            if is_auto_approved && tool.requires_explicit_approval(&params) {
                                 tracing::info!(
-                                    "Shell command '{}' requires explicit approval despite auto-approve",
-                                    cmd.chars().take(80).collect::<String>()
+                                    "'{}' requires explicit approval despite auto-approve",
+                                    tc.name
                                 );
                                 is_auto_approved = false;
                             }
@@ -1200,7 +2278,9 @@ impl Agent {
                                     request_id: Uuid::new_v4(),
                                     tool_name: tc.name.clone(),
                                     parameters: tc.arguments.clone(),
-                                    description: tool.description().to_string(),
+                                    description: tool
+                                        .approval_details(&params)
+                                        .unwrap_or_else(|| tool.description().to_string()),
                                     tool_call_id: tc.id.clone(),
                                     context_messages: context_messages.clone(),
                                 };
@@ -1220,9 +2300,14 @@ impl Agent {
                             )
                             .await;
 
+                        let tool_started = Instant::now();
+                        let (progress, forwarder) = self.forward_tool_progress(message);
                         let tool_result = self
-                            .execute_chat_tool(&tc.name, &tc.arguments, &job_ctx)
+                            .execute_chat_tool(&tc.name, &tc.arguments, &job_ctx, progress)
                             .await;
+                        let _ = forwarder.await;
+                        self.profiler
+                            .record(Phase::Tool, Some(&tc.name), tool_started);
 
                         let _ = self
                             .channels
@@ -1298,17 +2383,73 @@ impl Agent {
                             return Ok(AgenticLoopResult::Response(instructions));
                         }
 
+                        // If ask_user returned a form, park the turn until the
+                        // user answers; the answer becomes this call's result.
+                        if let Some(form) = detect_input_request(&tc.name, &tool_result) {
+                            let pending = PendingInput {
+                                request_id: Uuid::new_v4(),
+                                expires_at: chrono::Utc::now()
+                                    + chrono::Duration::seconds(form.timeout_secs as i64),
+                                form,
+                                tool_call_id: tc.id.clone(),
+                                context_messages: context_messages.clone(),
+                            };
+                            return Ok(AgenticLoopResult::NeedInput { pending });
+                        }
+
+                        // Charts are shown to the user straight away; the
+                        // LLM only sees the description in the tool result.
+                        if let Some((media_id, title)) = detect_chart_image(&tc.name, &tool_result)
+                            && let Some(ref cache) = self.deps.media_cache
+                            && let Some((data, mime_type)) = cache.get(&media_id).await
+                        {
+                            let _ = self
+                                .channels
+                                .send_status(
+                                    &message.channel,
+                                    StatusUpdate::Image {
+                                        media_id,
+                                        mime_type,
+                                        title,
+                                        data,
+                                    },
+                                    &message.metadata,
+                                )
+                                .await;
+                        }
+
                         // Add tool result to context for next LLM call
                         let result_content = match tool_result {
                             Ok(output) => {
-                                // Sanitize output before showing to LLM
-                                let sanitized =
-                                    self.safety().sanitize_tool_output(&tc.name, &output);
-                                self.safety().wrap_for_llm(
+                                let sources = self.tag_sources(
+                                    &context_messages,
+                                    &tc.name,
+                                    &tc.arguments,
+                                    &output,
+                                );
+                                // Sanitize output before showing to LLM; the
+                                // summarizer and output store only see the
+                                // sanitized text.
+                                let sanitize_started = Instant::now();
+                                let sanitized = self.safety().sanitize_tool_output(
+                                    &job_ctx.user_id,
                                     &tc.name,
-                                    &sanitized.content,
+                                    &output,
+                                );
+                                self.alert_on_injection(message, &tc.name, &sanitized).await;
+                                self.profiler.record(
+                                    Phase::Sanitization,
+                                    Some(&tc.name),
+                                    sanitize_started,
+                                );
+                                let content =
+                                    self.condense_tool_output(&tc.name, sanitized.content).await;
+                                let wrapped = self.safety().wrap_for_llm(
+                                    &tc.name,
+                                    &content,
                                     sanitized.was_modified,
-                                )
+                                );
+                                sources + &wrapped
                             }
                             Err(e) => format!("Error: {}", e),
                         };
@@ -1324,12 +2465,93 @@ impl Agent {
         }
     }
 
-    /// Execute a tool for chat (without full job context).
+    /// Source tags for a tool output, numbered after the sources already in
+    /// the context. Empty when citations are off.
+    fn tag_sources(
+        &self,
+        context_messages: &[ChatMessage],
+        tool_name: &str,
+        arguments: &serde_json::Value,
+        output: &str,
+    ) -> String {
+        if self.deps.citations.is_none() {
+            return String::new();
+        }
+        SourceLedger::from_messages(context_messages).record(tool_name, arguments, output)
+    }
+
+    /// Summarize an oversized, already sanitized tool output when a
+    /// summarizer is configured.
+    async fn condense_tool_output(&self, tool_name: &str, output: String) -> String {
+        match &self.deps.output_summarizer {
+            Some(summarizer) => summarizer.condense(tool_name, output).await,
+            None => output,
+        }
+    }
+
+    /// Forward a tool's progress emissions to the message's channel as
+    /// stream chunks, or status lines for `{"status": ...}`. The task ends
+    /// once the returned sender is dropped.
+    fn forward_tool_progress(
+        &self,
+        message: &IncomingMessage,
+    ) -> (ProgressSender, tokio::task::JoinHandle<()>) {
+        let (progress, mut rx) = tokio::sync::mpsc::unbounded_channel::<serde_json::Value>();
+        let channels = Arc::clone(&self.channels);
+        let channel = message.channel.clone();
+        let metadata = message.metadata.clone();
+        let forwarder = tokio::spawn(async move {
+            while let Some(value) = rx.recv().await {
+                let status = value
+                    .as_object()
+                    .filter(|fields| fields.len() == 1)
+                    .and_then(|fields| fields.get("status"))
+                    .and_then(|status| status.as_str());
+                let update = match (status, &value) {
+                    (Some(status), _) => StatusUpdate::Status(status.to_string()),
+                    (None, serde_json::Value::String(text)) => {
+                        StatusUpdate::StreamChunk(format!("{}\n", text))
+                    }
+                    (None, other) => StatusUpdate::StreamChunk(format!("{}\n", other)),
+                };
+                let _ = channels.send_status(&channel, update, &metadata).await;
+            }
+        });
+        (progress, forwarder)
+    }
+
+    /// Forward reply text to the message's channel as stream chunks while
+    /// the model generates it. The task ends once the returned sender is
+    /// dropped.
+    fn forward_reply_chunks(
+        &self,
+        message: &IncomingMessage,
+    ) -> (
+        tokio::sync::mpsc::UnboundedSender<String>,
+        tokio::task::JoinHandle<()>,
+    ) {
+        let (chunks, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+        let channels = Arc::clone(&self.channels);
+        let channel = message.channel.clone();
+        let metadata = message.metadata.clone();
+        let forwarder = tokio::spawn(async move {
+            while let Some(text) = rx.recv().await {
+                let _ = channels
+                    .send_status(&channel, StatusUpdate::StreamChunk(text), &metadata)
+                    .await;
+            }
+        });
+        (chunks, forwarder)
+    }
+
+    /// Execute a tool for chat (without full job context), streaming its
+    /// partial results to `progress`.
     async fn execute_chat_tool(
         &self,
         tool_name: &str,
         params: &serde_json::Value,
         job_ctx: &JobContext,
+        progress: ProgressSender,
     ) -> Result<String, Error> {
         let tool =
             self.tools()
@@ -1348,9 +2570,18 @@ impl Agent {
                 .map(|e| format!("{}: {}", e.field, e.message))
                 .collect::<Vec<_>>()
                 .join("; ");
+            let reason = format!("Invalid tool parameters: {}", details);
+            self.tool_hints.record_failure(
+                &job_ctx.user_id,
+                tool_name,
+                params,
+                &self
+                    .safety()
+                    .sanitize_tool_output(&job_ctx.user_id, tool_name, &reason),
+            );
             return Err(crate::error::ToolError::InvalidParameters {
                 name: tool_name.to_string(),
-                reason: format!("Invalid tool parameters: {}", details),
+                reason,
             }
             .into());
         }
@@ -1364,12 +2595,43 @@ impl Agent {
         // Execute with per-tool timeout
         let timeout = tool.execution_timeout();
         let start = std::time::Instant::now();
+        // A panicking tool fails its call like any other error, so the
+        // turn carries on with the error in the tool result.
         let result = tokio::time::timeout(timeout, async {
-            tool.execute(params.clone(), job_ctx).await
+            AssertUnwindSafe(tool.execute_streaming(params.clone(), job_ctx, progress))
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|panic| {
+                    let reason = panic
+                        .downcast_ref::<&str>()
+                        .map(|s| s.to_string())
+                        .or_else(|| panic.downcast_ref::<String>().cloned())
+                        .unwrap_or_else(|| "unknown panic".to_string());
+                    Err(crate::tools::ToolError::ExecutionFailed(format!(
+                        "tool panicked: {}",
+                        reason
+                    )))
+                })
         })
         .await;
         let elapsed = start.elapsed();
 
+        match &result {
+            Ok(Ok(_)) => self
+                .tool_hints
+                .record_success(&job_ctx.user_id, tool_name, params),
+            Ok(Err(e)) => self.tool_hints.record_failure(
+                &job_ctx.user_id,
+                tool_name,
+                params,
+                &self
+                    .safety()
+                    .sanitize_tool_output(&job_ctx.user_id, tool_name, &e.to_string()),
+            ),
+            // Timeouts say nothing about the call shape; don't hint on them.
+            Err(_) => {}
+        }
+
         match &result {
             Ok(Ok(output)) => {
                 let result_str = serde_json::to_string(&output.result)
@@ -1443,8 +2705,14 @@ impl Agent {
                 description,
                 category,
             } => {
-                self.handle_create_job(&message.user_id, title, description, category)
-                    .await?
+                self.handle_create_job(
+                    &message.user_id,
+                    &message.channel,
+                    title,
+                    description,
+                    category,
+                )
+                .await?
             }
             MessageIntent::CheckJobStatus { job_id } => {
                 self.handle_check_status(&message.user_id, job_id).await?
@@ -1551,6 +2819,11 @@ impl Agent {
                 thread.interrupt();
                 Ok(SubmissionResult::ok_with_message("Interrupted."))
             }
+            ThreadState::AwaitingInput => {
+                thread.pending_input = None;
+                thread.interrupt();
+                Ok(SubmissionResult::ok_with_message("Interrupted."))
+            }
             _ => Ok(SubmissionResult::ok_with_message("Nothing to interrupt.")),
         }
     }
@@ -1660,15 +2933,9 @@ impl Agent {
         }
 
         if approved {
-            // If always, add to auto-approved set
+            // If always, remember the approval scoped to this call
             if always {
-                let mut sess = session.lock().await;
-                sess.auto_approve_tool(&pending.tool_name);
-                tracing::info!(
-                    "Auto-approved tool '{}' for session {}",
-                    pending.tool_name,
-                    sess.id
-                );
+                self.remember_approval(message, &session, &pending).await;
             }
 
             // Reset thread state to processing
@@ -1680,8 +2947,36 @@ impl Agent {
             }
 
             // Execute the approved tool and continue the loop
-            let job_ctx =
-                JobContext::with_user(&message.user_id, "chat", "Interactive chat session");
+            let (user_id, contact) = self.person(message);
+            let turn = session
+                .lock()
+                .await
+                .threads
+                .get(&thread_id)
+                .and_then(|t| t.last_turn().map(|turn| turn.turn_number));
+            let mut job_ctx = JobContext::with_user(&user_id, "chat", "Interactive chat session");
+            job_ctx.conversation_id = Some(thread_id);
+            job_ctx.session_id = Some(session.lock().await.id);
+            job_ctx.metadata =
+                serde_json::json!({ "origin_channel": message.channel, "turn": turn });
+            job_ctx.working_dir = session
+                .lock()
+                .await
+                .project
+                .as_ref()
+                .map(|p| p.root.clone());
+            let locale = UserLocale::resolve(
+                self.store(),
+                &user_id,
+                &message.channel,
+                &self.config.locale,
+            )
+            .await;
+            job_ctx.timezone = Some(locale.tz.name().to_string());
+            job_ctx.contact = contact;
+            if self.deps.agents.is_some() {
+                job_ctx.memory = self.message_workspace(message).await.map(|ws| ws.owner());
+            }
 
             let _ = self
                 .channels
@@ -1694,9 +2989,11 @@ impl Agent {
                 )
                 .await;
 
+            let (progress, forwarder) = self.forward_tool_progress(message);
             let tool_result = self
-                .execute_chat_tool(&pending.tool_name, &pending.parameters, &job_ctx)
+                .execute_chat_tool(&pending.tool_name, &pending.parameters, &job_ctx, progress)
                 .await;
+            let _ = forwarder.await;
 
             let _ = self
                 .channels
@@ -1778,14 +3075,28 @@ impl Agent {
             // Add tool result to context
             let result_content = match tool_result {
                 Ok(output) => {
-                    let sanitized = self
-                        .safety()
-                        .sanitize_tool_output(&pending.tool_name, &output);
-                    self.safety().wrap_for_llm(
+                    let sources = self.tag_sources(
+                        &context_messages,
                         &pending.tool_name,
-                        &sanitized.content,
-                        sanitized.was_modified,
-                    )
+                        &pending.parameters,
+                        &output,
+                    );
+                    let sanitized = self.safety().sanitize_tool_output(
+                        &job_ctx.user_id,
+                        &pending.tool_name,
+                        &output,
+                    );
+                    self.alert_on_injection(message, &pending.tool_name, &sanitized)
+                        .await;
+                    let content = self
+                        .condense_tool_output(&pending.tool_name, sanitized.content)
+                        .await;
+                    sources
+                        + &self.safety().wrap_for_llm(
+                            &pending.tool_name,
+                            &content,
+                            sanitized.was_modified,
+                        )
                 }
                 Err(e) => format!("Error: {}", e),
             };
@@ -1845,6 +3156,16 @@ impl Agent {
                         parameters,
                     })
                 }
+                Ok(AgenticLoopResult::NeedInput { pending }) => {
+                    let request_id = pending.request_id;
+                    let form = pending.form.clone();
+                    thread.await_input(pending);
+                    Ok(SubmissionResult::NeedInput {
+                        request_id,
+                        form,
+                        error: None,
+                    })
+                }
                 Err(e) => {
                     thread.fail_turn(e.to_string());
                     Ok(SubmissionResult::error(e.to_string()))
@@ -1876,31 +3197,164 @@ impl Agent {
         }
     }
 
-    /// Handle an auth token submitted while the thread is in auth mode.
+    /// Handle the user's answer to a pending `ask_user` form.
     ///
-    /// The token goes directly to the extension manager's credential store,
-    /// completely bypassing logging, turn creation, history, and compaction.
-    async fn process_auth_token(
+    /// An invalid answer re-prompts with the validation errors and keeps the
+    /// request open. An expired request is dropped and the message is
+    /// processed as fresh input.
+    async fn process_input_answer(
         &self,
         message: &IncomingMessage,
-        pending: &crate::agent::session::PendingAuth,
-        token: &str,
         session: Arc<Mutex<Session>>,
         thread_id: Uuid,
-    ) -> Result<Option<String>, Error> {
-        let token = token.trim();
+        pending: PendingInput,
+        content: &str,
+    ) -> Result<SubmissionResult, Error> {
+        if pending.is_expired() {
+            {
+                let mut sess = session.lock().await;
+                if let Some(thread) = sess.threads.get_mut(&thread_id) {
+                    thread.pending_input = None;
+                    thread.fail_turn("Input request timed out");
+                }
+            }
+            return self
+                .process_user_input(message, session, thread_id, content)
+                .await;
+        }
 
-        // Clear auth mode regardless of outcome
+        let answers = match pending.form.parse_answer(content) {
+            Ok(answers) => answers,
+            Err(error) => {
+                return Ok(SubmissionResult::NeedInput {
+                    request_id: pending.request_id,
+                    form: pending.form,
+                    error: Some(error),
+                });
+            }
+        };
+        let answers = serde_json::json!({ "answers": answers });
+
+        // Clear the request and resume the turn
         {
             let mut sess = session.lock().await;
             if let Some(thread) = sess.threads.get_mut(&thread_id) {
-                thread.pending_auth = None;
+                thread.pending_input = None;
+                thread.state = ThreadState::Processing;
+                if let Some(turn) = thread.last_turn_mut() {
+                    turn.record_tool_result(answers.clone());
+                }
             }
         }
 
-        let ext_mgr = match self.deps.extension_manager.as_ref() {
-            Some(mgr) => mgr,
-            None => return Ok(Some("Extension manager not available.".to_string())),
+        let mut context_messages = pending.context_messages;
+        context_messages.push(ChatMessage::tool_result(
+            &pending.tool_call_id,
+            "ask_user",
+            answers.to_string(),
+        ));
+
+        let _ = self
+            .channels
+            .send_status(
+                &message.channel,
+                StatusUpdate::Thinking("Processing...".into()),
+                &message.metadata,
+            )
+            .await;
+
+        // Continue the agentic loop (a tool was already executed this turn)
+        let result = self
+            .run_agentic_loop(message, session.clone(), thread_id, context_messages, true)
+            .await;
+
+        let mut sess = session.lock().await;
+        let thread = sess
+            .threads
+            .get_mut(&thread_id)
+            .ok_or_else(|| Error::from(crate::error::JobError::NotFound { id: thread_id }))?;
+
+        if thread.state == ThreadState::Interrupted {
+            return Ok(SubmissionResult::Interrupted);
+        }
+
+        match result {
+            Ok(AgenticLoopResult::Response(response)) => {
+                thread.complete_turn(&response);
+                self.persist_response_chain(thread);
+                let _ = self
+                    .channels
+                    .send_status(
+                        &message.channel,
+                        StatusUpdate::Status("Done".into()),
+                        &message.metadata,
+                    )
+                    .await;
+                Ok(SubmissionResult::response(response))
+            }
+            Ok(AgenticLoopResult::NeedApproval { pending }) => {
+                let request_id = pending.request_id;
+                let tool_name = pending.tool_name.clone();
+                let description = pending.description.clone();
+                let parameters = pending.parameters.clone();
+                thread.await_approval(pending);
+                let _ = self
+                    .channels
+                    .send_status(
+                        &message.channel,
+                        StatusUpdate::Status("Awaiting approval".into()),
+                        &message.metadata,
+                    )
+                    .await;
+                Ok(SubmissionResult::NeedApproval {
+                    request_id,
+                    tool_name,
+                    description,
+                    parameters,
+                })
+            }
+            Ok(AgenticLoopResult::NeedInput { pending }) => {
+                let request_id = pending.request_id;
+                let form = pending.form.clone();
+                thread.await_input(pending);
+                Ok(SubmissionResult::NeedInput {
+                    request_id,
+                    form,
+                    error: None,
+                })
+            }
+            Err(e) => {
+                thread.fail_turn(e.to_string());
+                Ok(SubmissionResult::error(e.to_string()))
+            }
+        }
+    }
+
+    /// Handle an auth token submitted while the thread is in auth mode.
+    ///
+    /// The token goes directly to the extension manager's credential store,
+    /// completely bypassing logging, turn creation, history, and compaction.
+    async fn process_auth_token(
+        &self,
+        message: &IncomingMessage,
+        pending: &crate::agent::session::PendingAuth,
+        token: &str,
+        session: Arc<Mutex<Session>>,
+        thread_id: Uuid,
+    ) -> Result<Option<String>, Error> {
+        let token = token.trim();
+
+        // Clear auth mode regardless of outcome
+        {
+            let mut sess = session.lock().await;
+            if let Some(thread) = sess.threads.get_mut(&thread_id) {
+                thread.pending_auth = None;
+            }
+        }
+
+        let ext_mgr = match self.deps.extension_manager.as_ref() {
+            Some(mgr) => mgr,
+            None => return Ok(Some("Extension manager not available.".to_string())),
         };
 
         match ext_mgr.auth(&pending.extension_name, Some(token)).await {
@@ -2080,6 +3534,7 @@ impl Agent {
     async fn handle_create_job(
         &self,
         user_id: &str,
+        channel: &str,
         title: String,
         description: String,
         category: Option<String>,
@@ -2090,14 +3545,14 @@ impl Agent {
             .create_job_for_user(user_id, &title, &description)
             .await?;
 
-        // Update category if provided
-        if let Some(cat) = category {
-            self.context_manager
-                .update_context(job_id, |ctx| {
-                    ctx.category = Some(cat);
-                })
-                .await?;
-        }
+        // Record where the job came from (cancellation notices go there) and
+        // the category if provided
+        self.context_manager
+            .update_context(job_id, |ctx| {
+                ctx.metadata = serde_json::json!({ "origin_channel": channel });
+                ctx.category = category;
+            })
+            .await?;
 
         // Persist new job to database (fire-and-forget)
         if let Some(store) = self.store()
@@ -2149,14 +3604,19 @@ impl Agent {
             None => {
                 // Show summary of all jobs
                 let summary = self.context_manager.summary_for(user_id).await;
-                Ok(format!(
+                let mut out = format!(
                     "Jobs summary:\n  Total: {}\n  In Progress: {}\n  Completed: {}\n  Failed: {}\n  Stuck: {}",
                     summary.total,
                     summary.in_progress,
                     summary.completed,
                     summary.failed,
                     summary.stuck
-                ))
+                );
+                if let Some(ref load) = self.deps.load {
+                    out.push_str("\n\nResponsiveness:\n");
+                    out.push_str(&load.responsiveness().render());
+                }
+                Ok(out)
             }
         }
     }
@@ -2242,7 +3702,7 @@ impl Agent {
         };
 
         let runner = crate::agent::HeartbeatRunner::new(
-            crate::agent::HeartbeatConfig::default(),
+            crate::agent::HeartbeatConfig::default().with_locale(self.config.locale.locale.clone()),
             workspace.clone(),
             self.llm().clone(),
         );
@@ -2314,6 +3774,59 @@ impl Agent {
         }
     }
 
+    /// Export the current thread to the exports directory as PDF or Markdown.
+    async fn process_export(
+        &self,
+        session: Arc<Mutex<Session>>,
+        thread_id: Uuid,
+        format: Option<&str>,
+    ) -> Result<SubmissionResult, Error> {
+        let pdf = match format {
+            None | Some("pdf") => true,
+            Some("md" | "markdown") => false,
+            Some(other) => {
+                return Ok(SubmissionResult::error(format!(
+                    "Unknown export format: {}. Use /export pdf or /export md",
+                    other
+                )));
+            }
+        };
+        let (transcript, turns) = {
+            let sess = session.lock().await;
+            let thread = sess
+                .threads
+                .get(&thread_id)
+                .ok_or_else(|| Error::from(crate::error::JobError::NotFound { id: thread_id }))?;
+            (thread.transcript_markdown(), thread.turns.len())
+        };
+
+        if turns == 0 {
+            return Ok(SubmissionResult::ok_with_message(
+                "Nothing to export (empty thread).",
+            ));
+        }
+
+        let stem = format!(
+            "conversation-{}-{}",
+            &thread_id.to_string()[..8],
+            chrono::Utc::now().format("%Y%m%d-%H%M%S")
+        );
+        let written = if pdf {
+            let bytes = crate::media::PdfRenderer::default().render_markdown(&transcript);
+            crate::media::write_export(&format!("{}.pdf", stem), &bytes)
+        } else {
+            crate::media::write_export(&format!("{}.md", stem), transcript.as_bytes())
+        };
+        match written {
+            Ok(path) => Ok(SubmissionResult::response(format!(
+                "Exported {} turn(s) to {}",
+                turns,
+                path.display()
+            ))),
+            Err(e) => Ok(SubmissionResult::error(format!("Export failed: {}", e))),
+        }
+    }
+
     /// Suggest next steps based on the current thread.
     async fn process_suggest(
         &self,
@@ -2361,6 +3874,623 @@ impl Agent {
         }
     }
 
+    /// Show, bind (`/project use <dir>`), or clear the session's project.
+    async fn process_project(
+        &self,
+        session: Arc<Mutex<Session>>,
+        args: &[String],
+    ) -> Result<SubmissionResult, Error> {
+        let subcommand = args.first().map(|a| a.to_ascii_lowercase());
+        let path = match subcommand.as_deref() {
+            None => {
+                let sess = session.lock().await;
+                return Ok(match sess.project {
+                    Some(ref binding) => SubmissionResult::response(binding.describe()),
+                    None => SubmissionResult::ok_with_message(
+                        "No project bound. Use /project use <dir>.",
+                    ),
+                });
+            }
+            Some("clear") => {
+                let previous = session.lock().await.project.take();
+                return Ok(SubmissionResult::ok_with_message(match previous {
+                    Some(binding) => format!("Unbound project {}.", binding.name),
+                    None => "No project bound.".to_string(),
+                }));
+            }
+            Some("use") if args.len() > 1 => args[1..].join(" "),
+            Some("use") => return Ok(SubmissionResult::error("Usage: /project use <dir>")),
+            Some(_) => args.join(" "),
+        };
+
+        let mut binding = match ProjectBinding::open(&path) {
+            Ok(binding) => binding,
+            Err(e) => return Ok(SubmissionResult::error(format!("Project: {}", e))),
+        };
+        if let Some(docs) = project::read_context_docs(&binding.root) {
+            binding.summary = Some(self.summarize_project_docs(&binding.name, &docs).await);
+        }
+
+        let description = binding.describe();
+        tracing::info!("Session bound to project {}", binding.root.display());
+        session.lock().await.project = Some(binding);
+        Ok(SubmissionResult::response(description))
+    }
+
+    /// The approval rules of the message's user: from the database when
+    /// there is one, otherwise from the session.
+    async fn approval_rules(
+        &self,
+        message: &IncomingMessage,
+        session: &Arc<Mutex<Session>>,
+    ) -> ApprovalRules {
+        match self.store() {
+            Some(store) => ApprovalRules::load(store.as_ref(), &self.person(message).0).await,
+            None => session.lock().await.approval_rules.clone(),
+        }
+    }
+
+    async fn save_approval_rules(
+        &self,
+        message: &IncomingMessage,
+        session: &Arc<Mutex<Session>>,
+        rules: ApprovalRules,
+    ) -> Result<(), Error> {
+        match self.store() {
+            Some(store) => rules.save(store.as_ref(), &self.person(message).0).await?,
+            None => session.lock().await.approval_rules = rules,
+        }
+        Ok(())
+    }
+
+    /// Remember an "always" answer as a rule scoped to the approved call.
+    async fn remember_approval(
+        &self,
+        message: &IncomingMessage,
+        session: &Arc<Mutex<Session>>,
+        pending: &PendingApproval,
+    ) {
+        let project_root = session
+            .lock()
+            .await
+            .project
+            .as_ref()
+            .map(|p| p.root.clone());
+        let Some(rule) = ApprovalRule::for_call(
+            &pending.tool_name,
+            &pending.parameters,
+            project_root.as_deref(),
+        ) else {
+            tracing::info!(
+                "Approved '{}' once; the call has no scope that can be remembered",
+                pending.tool_name
+            );
+            return;
+        };
+
+        let mut rules = self.approval_rules(message, session).await;
+        let description = rules.add(rule).describe();
+        match self.save_approval_rules(message, session, rules).await {
+            Ok(()) => tracing::info!("Remembered approval: {}", description),
+            Err(e) => tracing::warn!("Failed to save approval rule {}: {}", description, e),
+        }
+    }
+
+    /// List, revoke or clear remembered approvals
+    /// (`/approvals [revoke <id>|clear]`).
+    async fn process_approvals(
+        &self,
+        message: &IncomingMessage,
+        session: Arc<Mutex<Session>>,
+        args: &[String],
+    ) -> Result<SubmissionResult, Error> {
+        let mut rules = self.approval_rules(message, &session).await;
+        let reply = match args.first().map(|a| a.to_ascii_lowercase()).as_deref() {
+            None | Some("list") => return Ok(SubmissionResult::response(rules.describe())),
+            Some("revoke") if args.len() == 2 => match rules.revoke(&args[1]) {
+                Some(rule) => format!("Revoked: {}", rule.describe()),
+                None => {
+                    return Ok(SubmissionResult::error(format!(
+                        "No single approval matches '{}'. See /approvals.",
+                        args[1]
+                    )));
+                }
+            },
+            Some("clear") => {
+                let count = rules.rules.len();
+                rules.rules.clear();
+                // Blanket per-session approvals go too.
+                session.lock().await.auto_approved_tools.clear();
+                format!("Cleared {} remembered approval(s).", count)
+            }
+            _ => {
+                return Ok(SubmissionResult::error(
+                    "Usage: /approvals, /approvals revoke <id> or /approvals clear",
+                ));
+            }
+        };
+        self.save_approval_rules(message, &session, rules).await?;
+        Ok(SubmissionResult::ok_with_message(reply))
+    }
+
+    /// Show or toggle elevated mode for this session
+    /// (`/elevated [on [minutes]|off]`).
+    async fn process_elevated(
+        &self,
+        message: &IncomingMessage,
+        session: Arc<Mutex<Session>>,
+        args: &[String],
+    ) -> Result<SubmissionResult, Error> {
+        let mut sess = session.lock().await;
+        let session_id = sess.id.to_string();
+        match args.first().map(|a| a.to_ascii_lowercase()).as_deref() {
+            None | Some("status") => Ok(SubmissionResult::ok_with_message(
+                if sess.elevated.is_active_for_session(&session_id) {
+                    "Elevated mode is on for this session."
+                } else {
+                    "Elevated mode is off."
+                },
+            )),
+            Some("on") => {
+                let minutes = match args.get(1).map(|m| m.parse::<u64>()) {
+                    None => 60,
+                    Some(Ok(m)) => m,
+                    Some(Err(_)) => {
+                        return Ok(SubmissionResult::error("Usage: /elevated on [minutes]"));
+                    }
+                };
+                sess.elevated.set_duration(minutes.saturating_mul(60));
+                sess.elevated.activate(&self.person(message).0, &session_id);
+                Ok(SubmissionResult::ok_with_message(format!(
+                    "Elevated mode on for up to {} minute(s). /elevated off to end it.",
+                    minutes.clamp(1, 480)
+                )))
+            }
+            Some("off") => {
+                sess.elevated.deactivate();
+                Ok(SubmissionResult::ok_with_message("Elevated mode off."))
+            }
+            _ => Ok(SubmissionResult::error(
+                "Usage: /elevated, /elevated on [minutes] or /elevated off",
+            )),
+        }
+    }
+
+    /// Show today's spend (`/budget`) or lift the caps for the rest of the
+    /// day (`/budget override`).
+    fn process_budget(&self, message: &IncomingMessage, args: &[String]) -> SubmissionResult {
+        let Some(ref budget) = self.deps.budget else {
+            return SubmissionResult::error("Budget tracking is not available.");
+        };
+        let user_id = self.person(message).0;
+        match args.first().map(|a| a.to_ascii_lowercase()).as_deref() {
+            None | Some("status") => SubmissionResult::response(budget.report(&user_id).render()),
+            Some("override") => {
+                budget.grant_override(&user_id);
+                tracing::info!("Budget caps overridden for {} until end of day", user_id);
+                SubmissionResult::ok_with_message(
+                    "Budget caps lifted for the rest of today (UTC). Resend your last message \
+                     to continue.",
+                )
+            }
+            _ => SubmissionResult::error("Usage: /budget or /budget override"),
+        }
+    }
+
+    /// Show or change a per-session generation setting
+    /// (`/temperature`, `/style`, `/verbosity`). Changes are copied to the
+    /// active thread's conversation metadata so they survive a restart.
+    async fn process_generation(
+        &self,
+        session: Arc<Mutex<Session>>,
+        setting: &str,
+        args: &[String],
+    ) -> Result<SubmissionResult, Error> {
+        let mut sess = session.lock().await;
+        let mut prefs = GenerationPrefs::from_metadata(&sess.metadata);
+        let Some(value) = args.first().map(|a| a.to_ascii_lowercase()) else {
+            return Ok(SubmissionResult::ok_with_message(format!(
+                "Generation: {}.",
+                prefs.describe()
+            )));
+        };
+
+        let reset = value == "reset" || value == "default";
+        let applied = match setting {
+            "temperature" => if reset {
+                Ok(None)
+            } else {
+                generation::parse_temperature(&value).map(Some)
+            }
+            .map(|t| prefs.temperature = t),
+            "style" => if reset {
+                Ok(None)
+            } else {
+                value.parse().map(Some)
+            }
+            .map(|style| prefs.style = style),
+            "verbosity" => if reset {
+                Ok(None)
+            } else {
+                value.parse().map(Some)
+            }
+            .map(|verbosity| prefs.verbosity = verbosity),
+            _ => Err(format!("Unknown setting '{}'.", setting)),
+        };
+        if let Err(e) = applied {
+            return Ok(SubmissionResult::error(e));
+        }
+
+        prefs.write_to(&mut sess.metadata);
+        if let (Some(store), Some(thread_id)) = (self.store(), sess.active_thread) {
+            let val = prefs.to_value();
+            if let Err(e) = store
+                .update_conversation_metadata_field(thread_id, generation::METADATA_KEY, &val)
+                .await
+            {
+                tracing::warn!(
+                    "Failed to persist generation settings for thread {}: {}",
+                    thread_id,
+                    e
+                );
+            }
+        }
+        Ok(SubmissionResult::ok_with_message(format!(
+            "Generation: {}.",
+            prefs.describe()
+        )))
+    }
+
+    /// Show the sandbox mode, or accept or withdraw the host fallback risk
+    /// for the current thread (`/sandbox [accept-risk|revoke]`).
+    fn process_sandbox(
+        &self,
+        message: &IncomingMessage,
+        thread_id: Uuid,
+        args: &[String],
+    ) -> Result<SubmissionResult, Error> {
+        let Some(ref fallback) = self.deps.host_fallback else {
+            return Ok(match args.first() {
+                None => SubmissionResult::ok_with_message(
+                    "Commands run in the Docker sandbox, or directly if it is disabled. \
+                     Host fallback is not active.",
+                ),
+                Some(_) => SubmissionResult::error(
+                    "Host fallback is not active, so there is no risk to accept.",
+                ),
+            });
+        };
+        let user_id = self.person(message).0;
+        match args.first().map(|a| a.to_ascii_lowercase()).as_deref() {
+            None | Some("status") => {
+                let state = if fallback.is_acknowledged(thread_id) {
+                    "Risk accepted: shell commands run on the host (degraded mode). \
+                     /sandbox revoke to stop."
+                } else {
+                    "Risk not accepted: shell commands are refused."
+                };
+                let policy = fallback.policy();
+                Ok(SubmissionResult::ok_with_message(format!(
+                    "{}\n\n{}\n\nAllowed programs: {}\nAllowed directories: {}",
+                    crate::sandbox::fallback::RISK_BANNER,
+                    state,
+                    policy.allowed_bins.join(", "),
+                    policy
+                        .allowed_roots
+                        .iter()
+                        .map(|r| r.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )))
+            }
+            Some("accept-risk") => {
+                fallback.acknowledge(thread_id, &user_id);
+                Ok(SubmissionResult::ok_with_message(
+                    "Host fallback enabled for this conversation until the session ends. \
+                     Commands are restricted and recorded in the sandbox fallback audit log.",
+                ))
+            }
+            Some("revoke") => {
+                fallback.revoke(thread_id);
+                Ok(SubmissionResult::ok_with_message(
+                    "Host fallback disabled; shell commands are refused again.",
+                ))
+            }
+            _ => Ok(SubmissionResult::error(
+                "Usage: /sandbox, /sandbox accept-risk or /sandbox revoke",
+            )),
+        }
+    }
+
+    /// Remove the last turns from the thread, the stored conversation,
+    /// memory and logs (`/redact last [n]`).
+    async fn process_redact(
+        &self,
+        session: Arc<Mutex<Session>>,
+        thread_id: Uuid,
+        args: &[String],
+    ) -> Result<SubmissionResult, Error> {
+        const USAGE: &str = "Usage: /redact last [n]";
+        let count = match args {
+            [cmd] if cmd.eq_ignore_ascii_case("last") => 1,
+            [cmd, n] if cmd.eq_ignore_ascii_case("last") => match n.parse::<usize>() {
+                Ok(n) if n > 0 => n,
+                _ => return Ok(SubmissionResult::error(USAGE)),
+            },
+            _ => return Ok(SubmissionResult::error(USAGE)),
+        };
+
+        let redaction = {
+            let mut sess = session.lock().await;
+            let thread = sess
+                .threads
+                .get_mut(&thread_id)
+                .ok_or_else(|| Error::from(crate::error::JobError::NotFound { id: thread_id }))?;
+            match thread.state {
+                ThreadState::Processing => {
+                    return Ok(SubmissionResult::error(
+                        "Wait for the current turn to finish before redacting.",
+                    ));
+                }
+                // The pending request carries the turn's context with it.
+                ThreadState::AwaitingApproval | ThreadState::AwaitingInput => {
+                    return Ok(SubmissionResult::error(
+                        "Answer the pending request or /interrupt it before redacting.",
+                    ));
+                }
+                _ => {}
+            }
+            if thread.turns.is_empty() {
+                return Ok(SubmissionResult::ok_with_message("Nothing to redact."));
+            }
+            let redaction = Redaction::new(&thread.remove_last_turns(count));
+            thread.start_turn(redact::REDACTED_INPUT);
+            thread.complete_turn(redact::marker_response(redaction.turns));
+            thread.last_response_id = None;
+            redaction
+        };
+
+        // Checkpoints and the provider's response chain still hold the turns.
+        let undo_mgr = self.session_manager.get_undo_manager(thread_id).await;
+        undo_mgr.lock().await.clear();
+        self.llm().clear_response_chain(&thread_id.to_string());
+        if let Some(ref index) = self.deps.history_index {
+            index.forget(redaction.user_inputs.clone());
+        }
+
+        let mut report = match redaction
+            .apply(
+                self.store().map(|s| s.as_ref()),
+                thread_id,
+                self.workspace().map(|w| w.as_ref()),
+                &redact::default_log_dir(),
+            )
+            .await
+        {
+            Ok(report) => report,
+            Err(e) => {
+                return Ok(SubmissionResult::error(format!(
+                    "Removed from this thread, but scrubbing stored copies failed: {}",
+                    e
+                )));
+            }
+        };
+        if let Some(ref logs) = self.deps.logs {
+            report.log_entries_scrubbed = logs.rewrite_recent(|m| redaction.scrub(m));
+        }
+        Ok(SubmissionResult::ok_with_message(report.summary()))
+    }
+
+    /// Inspect tool outputs kept by the redaction vault
+    /// (`/redacted [show <id>|audit]`).
+    ///
+    /// Originals are only shown on the local REPL, so they never travel
+    /// over a remote channel.
+    async fn process_redacted(
+        &self,
+        message: &IncomingMessage,
+        session: Arc<Mutex<Session>>,
+        args: &[String],
+    ) -> Result<SubmissionResult, Error> {
+        let Some(vault) = self.safety().redaction_vault() else {
+            return Ok(SubmissionResult::error(
+                "Redacted outputs are not preserved. Set SAFETY_PRESERVE_REDACTED=true \
+                 (requires the secrets master key).",
+            ));
+        };
+        match args.first().map(|a| a.to_ascii_lowercase()).as_deref() {
+            None | Some("list") => {
+                let entries = match vault.list(&self.person(message).0) {
+                    Ok(entries) => entries,
+                    Err(e) => return Ok(SubmissionResult::error(e.to_string())),
+                };
+                if entries.is_empty() {
+                    return Ok(SubmissionResult::ok_with_message(
+                        "No preserved tool outputs.",
+                    ));
+                }
+                let mut out = String::from("Preserved tool outputs:\n");
+                for entry in entries.iter().take(20) {
+                    out.push_str(&format!(
+                        "  {}  {}  {}  {}{}\n",
+                        entry.short_id(),
+                        entry.created_at.format("%Y-%m-%d %H:%M"),
+                        entry.tool_name,
+                        entry.patterns.join(", "),
+                        if entry.blocked { " (blocked)" } else { "" }
+                    ));
+                }
+                out.push_str("\nShow one with /redacted show <id> (local REPL, elevated mode).");
+                Ok(SubmissionResult::response(out))
+            }
+            Some("show") if args.len() == 2 => {
+                if message.channel != "repl" {
+                    return Ok(SubmissionResult::error(
+                        "Preserved outputs can only be shown on the local REPL.",
+                    ));
+                }
+                let (session_id, elevated) = {
+                    let sess = session.lock().await;
+                    (sess.id.to_string(), sess.elevated.clone())
+                };
+                let user_id = self.person(message).0;
+                let access = VaultAccess {
+                    user_id: &user_id,
+                    session_id: &session_id,
+                    elevated: &elevated,
+                };
+                match vault.reveal(&args[1], &access) {
+                    Ok((entry, content)) => Ok(SubmissionResult::response(format!(
+                        "{} output from {} ({}):\n\n{}",
+                        entry.tool_name,
+                        entry.created_at.format("%Y-%m-%d %H:%M"),
+                        entry.patterns.join(", "),
+                        content
+                    ))),
+                    Err(e) => Ok(SubmissionResult::error(e.to_string())),
+                }
+            }
+            Some("audit") => {
+                let records = match vault.audit_log() {
+                    Ok(records) => records,
+                    Err(e) => return Ok(SubmissionResult::error(e.to_string())),
+                };
+                if records.is_empty() {
+                    return Ok(SubmissionResult::ok_with_message(
+                        "No preserved output has been read.",
+                    ));
+                }
+                let mut out = String::from("Redaction vault reads:\n");
+                for r in records.iter().rev().take(20) {
+                    out.push_str(&format!(
+                        "  {}  {}  {}  {}\n",
+                        r.at.format("%Y-%m-%d %H:%M"),
+                        r.user_id,
+                        r.entry,
+                        if r.granted { "granted" } else { "denied" }
+                    ));
+                }
+                Ok(SubmissionResult::response(out))
+            }
+            _ => Ok(SubmissionResult::error(
+                "Usage: /redacted, /redacted show <id> or /redacted audit",
+            )),
+        }
+    }
+
+    /// Show or edit the glossary in the workspace's GLOSSARY.md
+    /// (`/glossary [add|avoid|remove|ban|unban ...]`).
+    async fn process_glossary(&self, args: &[String]) -> Result<SubmissionResult, Error> {
+        const USAGE: &str = "Usage: /glossary, /glossary add <term>[: definition], \
+                             /glossary avoid <term>: <phrase>[, ...], /glossary remove <term>, \
+                             /glossary ban <word>[ -> replacement], /glossary unban <word>";
+        let Some(ws) = self.workspace() else {
+            return Ok(SubmissionResult::error(
+                "The glossary needs a workspace (database).",
+            ));
+        };
+        let mut glossary = ws.glossary().await?;
+
+        let subcommand = args.first().map(|a| a.to_ascii_lowercase());
+        let rest = args.get(1..).unwrap_or_default().join(" ");
+        let (head, tail) = match rest.split_once(':') {
+            Some((head, tail)) => (head.trim(), tail.trim()),
+            None => (rest.trim(), ""),
+        };
+        let message = match subcommand.as_deref() {
+            None | Some("list") => {
+                return Ok(if glossary.is_empty() {
+                    SubmissionResult::ok_with_message(
+                        "The glossary is empty. Add a term with /glossary add <term>: <definition>.",
+                    )
+                } else {
+                    SubmissionResult::response(glossary.render())
+                });
+            }
+            Some("add") if !head.is_empty() => {
+                glossary.upsert(GlossaryEntry {
+                    term: head.to_string(),
+                    definition: tail.to_string(),
+                    avoid: Vec::new(),
+                });
+                format!("Added '{}' to the glossary.", head)
+            }
+            Some("avoid") if !head.is_empty() && !tail.is_empty() => {
+                let avoid: Vec<String> = tail
+                    .split(',')
+                    .map(|p| p.trim().to_string())
+                    .filter(|p| !p.is_empty())
+                    .collect();
+                let message = format!("'{}' will replace: {}.", head, avoid.join(", "));
+                glossary.upsert(GlossaryEntry {
+                    term: head.to_string(),
+                    definition: String::new(),
+                    avoid,
+                });
+                message
+            }
+            Some("remove") if !rest.is_empty() => {
+                if !glossary.remove(&rest) {
+                    return Ok(SubmissionResult::error(format!(
+                        "'{}' is not in the glossary.",
+                        rest
+                    )));
+                }
+                format!("Removed '{}' from the glossary.", rest)
+            }
+            Some("ban") if !rest.is_empty() => {
+                let (word, replacement) = match rest.split_once("->") {
+                    Some((word, replacement)) => (word.trim(), Some(replacement.trim())),
+                    None => (rest.trim(), None),
+                };
+                glossary.ban(word, replacement);
+                match replacement.filter(|r| !r.is_empty()) {
+                    Some(r) => format!("Banned '{}'; answers will say '{}' instead.", word, r),
+                    None => format!("Banned '{}'.", word),
+                }
+            }
+            Some("unban") if !rest.is_empty() => {
+                if !glossary.unban(&rest) {
+                    return Ok(SubmissionResult::error(format!(
+                        "'{}' is not banned.",
+                        rest
+                    )));
+                }
+                format!("'{}' is no longer banned.", rest)
+            }
+            _ => return Ok(SubmissionResult::error(USAGE)),
+        };
+
+        ws.save_glossary(&glossary).await?;
+        Ok(SubmissionResult::ok_with_message(message))
+    }
+
+    /// Condense a project's AGENTS.md/README.md for the system prompt,
+    /// falling back to a plain excerpt when the LLM call fails.
+    async fn summarize_project_docs(&self, name: &str, docs: &str) -> String {
+        let request = crate::llm::CompletionRequest::new(vec![
+            ChatMessage::system(
+                "Summarize this project's documentation for an assistant that will work in the \
+                 repository. In at most 10 short bullet points cover what the project is, how to \
+                 build and test it, and conventions or rules the assistant must follow.",
+            ),
+            ChatMessage::user(format!("Project: {}\n\n{}", name, docs)),
+        ])
+        .with_max_tokens(512)
+        .with_temperature(0.2);
+
+        match self.llm().complete(request).await {
+            Ok(response) if !response.content.trim().is_empty() => {
+                response.content.trim().to_string()
+            }
+            Ok(_) => project::excerpt(docs),
+            Err(e) => {
+                tracing::warn!("Project summary failed, using excerpt: {}", e);
+                project::excerpt(docs)
+            }
+        }
+    }
+
     /// Handle system commands that bypass thread-state checks entirely.
     async fn handle_system_command(
         &self,
@@ -2372,8 +4502,9 @@ impl Agent {
                 "System:\n",
                 "  /help             Show this help\n",
                 "  /model [name]     Show or switch the active model\n",
+                "  /model <tier>     Set the model tier: auto, cheap or primary\n",
                 "  /version          Show version info\n",
-                "  /tools            List available tools\n",
+                "  /tools [name]     Show tool documentation\n",
                 "  /debug            Toggle debug mode\n",
                 "  /ping             Connectivity check\n",
                 "\n",
@@ -2392,11 +4523,30 @@ impl Agent {
                 "  /new              New conversation thread\n",
                 "  /thread <id>      Switch to thread\n",
                 "  /resume <id>      Resume from checkpoint\n",
+                "  /project [dir]    Show or bind the project directory\n",
+                "  /project clear    Unbind the project directory\n",
+                "  /approvals        List remembered approvals\n",
+                "  /approvals revoke <id>  Forget one (or /approvals clear)\n",
+                "  /elevated [on|off] Show or toggle elevated mode\n",
+                "  /budget [override] Token and cost spend, or lift today's caps\n",
+                "  /temperature <0-2> Sampling temperature (or reset)\n",
+                "  /style <concise|detailed>  Response style (or reset)\n",
+                "  /verbosity <low|normal|high>  Response length (or reset)\n",
+                "  /redact last [n]  Remove the last turn(s) from history, memory and logs\n",
+                "  /redacted         List preserved redacted tool outputs\n",
+                "  /glossary         Show the project glossary\n",
+                "  /glossary add <term>: <definition>  Add or update a term\n",
+                "  /glossary ban <word> [-> replacement]  Ban a word\n",
+                "  /sandbox [accept-risk|revoke] Sandbox mode and host fallback\n",
                 "\n",
                 "Agent:\n",
                 "  /heartbeat        Run heartbeat check\n",
                 "  /summarize        Summarize current thread\n",
                 "  /suggest          Suggest next steps\n",
+                "  /export [md]      Export this thread as PDF (or Markdown)\n",
+                "  /focus <hours>    Hold routines and alerts (e.g. 2h, 90m)\n",
+                "  /focus off        End focus and show what was held\n",
+                "  /away [backlog]   Away status, or summarize queued messages now\n",
                 "\n",
                 "  /quit             Exit",
             ))),
@@ -2409,13 +4559,21 @@ impl Agent {
                 env!("CARGO_PKG_VERSION")
             ))),
 
-            "tools" => {
-                let tools = self.tools().list().await;
-                Ok(SubmissionResult::response(format!(
-                    "Available tools: {}",
-                    tools.join(", ")
-                )))
-            }
+            "tools" => match args.first() {
+                Some(name) => match self.tools().tool_doc(name).await {
+                    Some(doc) => Ok(SubmissionResult::response(doc.to_markdown())),
+                    None => Ok(SubmissionResult::error(format!(
+                        "Unknown tool: {}. Try /tools",
+                        name
+                    ))),
+                },
+                None => {
+                    let docs = self.tools().tool_docs().await;
+                    Ok(SubmissionResult::response(
+                        crate::tools::docs::render_markdown(&docs),
+                    ))
+                }
+            },
 
             "debug" => {
                 // Debug toggle is handled client-side in the REPL.
@@ -2429,10 +4587,24 @@ impl Agent {
                 if args.is_empty() {
                     // Show current model
                     let name = self.llm().active_model_name();
-                    Ok(SubmissionResult::response(format!(
-                        "Active model: {}",
-                        name
-                    )))
+                    let mut out = format!("Active model: {}", name);
+                    if let Some(ref tiers) = self.deps.model_tiers {
+                        out.push('\n');
+                        out.push_str(&tiers.report().summary());
+                    }
+                    Ok(SubmissionResult::response(out))
+                } else if let Some(ref tiers) = self.deps.model_tiers
+                    && let Ok(mode) = args[0].parse::<TierMode>()
+                {
+                    tiers.set_mode(mode);
+                    Ok(SubmissionResult::response(match mode {
+                        TierMode::Auto => "Model tier: auto (picked per turn)".to_string(),
+                        TierMode::Pinned(tier) => format!(
+                            "Model tier pinned to {} ({})",
+                            tier,
+                            tiers.provider(tier).active_model_name()
+                        ),
+                    }))
                 } else {
                     let requested = &args[0];
 
@@ -2469,6 +4641,81 @@ impl Agent {
                 }
             }
 
+            "focus" => {
+                let Some(ref focus) = self.deps.focus else {
+                    return Ok(SubmissionResult::error("Focus mode is not available."));
+                };
+                match args.first().map(|a| a.to_lowercase()).as_deref() {
+                    None => {
+                        let status = focus.status();
+                        Ok(SubmissionResult::response(match status.until {
+                            Some(until) => format!(
+                                "Focus mode on until {} UTC, {} item(s) held.",
+                                until.format("%Y-%m-%d %H:%M"),
+                                status.deferred
+                            ),
+                            None => "Focus mode is off.".to_string(),
+                        }))
+                    }
+                    Some("off" | "stop" | "end") => {
+                        Ok(SubmissionResult::response(focus.stop().unwrap_or_else(
+                            || "Focus mode off. Nothing was held back.".into(),
+                        )))
+                    }
+                    Some(_) => match focus_mode::parse_focus_duration(args) {
+                        Some(duration) => {
+                            let until = focus.start(duration);
+                            Ok(SubmissionResult::response(format!(
+                                "Focus mode on until {} UTC. Routines and background alerts \
+                                 are held; emergencies still come through.",
+                                until.format("%Y-%m-%d %H:%M")
+                            )))
+                        }
+                        None => Ok(SubmissionResult::error(
+                            "Usage: /focus <duration> (e.g. 2h, 90m, up to 24h) or /focus off",
+                        )),
+                    },
+                }
+            }
+
+            "away" => {
+                let Some(ref away) = self.deps.away else {
+                    return Ok(SubmissionResult::error(
+                        "Away auto-responses are off. Set AWAY_ENABLED=true to turn them on.",
+                    ));
+                };
+                match args.first().map(|a| a.to_lowercase()).as_deref() {
+                    None => {
+                        let now = chrono::Utc::now();
+                        let state = if away.is_away(now) {
+                            match away.back_at(now) {
+                                Some(back) => {
+                                    format!("Away until {} UTC.", back.format("%Y-%m-%d %H:%M"))
+                                }
+                                None => "Away.".to_string(),
+                            }
+                        } else {
+                            "Available.".to_string()
+                        };
+                        Ok(SubmissionResult::response(format!(
+                            "{} {} message(s) queued.",
+                            state,
+                            away.queued()
+                        )))
+                    }
+                    Some("backlog") => {
+                        let backlog = away.take_backlog();
+                        if backlog.is_empty() {
+                            return Ok(SubmissionResult::response("No messages are queued."));
+                        }
+                        Ok(SubmissionResult::response(
+                            self.summarize_backlog(backlog).await,
+                        ))
+                    }
+                    Some(_) => Ok(SubmissionResult::error("Usage: /away [backlog]")),
+                }
+            }
+
             _ => Ok(SubmissionResult::error(format!(
                 "Unknown command: {}. Try /help",
                 command
@@ -2494,6 +4741,103 @@ impl Agent {
     }
 }
 
+/// The jobs the context manager knows about, as seen right now.
+async fn job_snapshots(
+    context_manager: &ContextManager,
+    scheduler: &Scheduler,
+) -> Vec<JobSnapshot> {
+    let mut jobs = Vec::new();
+    for id in context_manager.all_jobs().await {
+        if let Ok(ctx) = context_manager.get_context(id).await {
+            jobs.push(JobSnapshot {
+                id,
+                title: ctx.title,
+                state: ctx.state,
+                scheduled: scheduler.is_running(id).await,
+            });
+        }
+    }
+    jobs
+}
+
+/// The skill of the most recent tool call in a turn that belongs to one.
+fn turn_skill(skill_tools: &HashMap<String, String>, calls: &[TurnToolCall]) -> Option<String> {
+    calls
+        .iter()
+        .rev()
+        .find_map(|call| skill_tools.get(&call.name))
+        .cloned()
+}
+
+/// Metadata sent with a chat turn's LLM requests.
+fn request_metadata(thread_id: Uuid, skill: Option<String>) -> HashMap<String, String> {
+    let mut metadata = HashMap::new();
+    metadata.insert("thread_id".to_string(), thread_id.to_string());
+    if let Some(skill) = skill {
+        metadata.insert(SKILL_METADATA_KEY.to_string(), skill);
+    }
+    metadata
+}
+
+/// Routing category of a heartbeat or routine notification.
+fn digest_category(response: &OutgoingResponse) -> NotificationCategory {
+    if response.metadata.get("severity").and_then(|v| v.as_str()) == Some("emergency") {
+        NotificationCategory::SecurityAlert
+    } else {
+        NotificationCategory::RoutineDigest
+    }
+}
+
+/// Send quarantine reports to the channel the user last wrote from, and
+/// wherever security alerts are routed. Before any message arrives they go
+/// to every channel.
+fn spawn_quarantine_notifier(
+    tracker: Arc<MisbehaviorTracker>,
+    channels: Arc<ChannelManager>,
+) -> tokio::task::JoinHandle<()> {
+    let mut reports = tracker.subscribe();
+    tokio::spawn(async move {
+        loop {
+            let report = match reports.recv().await {
+                Ok(report) => report,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            };
+            let notice = OutgoingResponse::text(report.notice());
+            match tracker.active_channel() {
+                Some((channel, user)) => {
+                    if let Err(e) = channels.broadcast(&channel, &user, notice.clone()).await {
+                        tracing::warn!("Quarantine notice on {} failed: {}", channel, e);
+                    }
+                    channels
+                        .route(
+                            NotificationCategory::SecurityAlert,
+                            &user,
+                            notice,
+                            Some(&channel),
+                        )
+                        .await;
+                }
+                None => {
+                    let _ = channels.broadcast_all("default", notice).await;
+                }
+            }
+        }
+    })
+}
+
+/// Hold a background notification while focus mode is on.
+fn hold_for_focus(
+    focus: &Option<Arc<FocusMode>>,
+    source: &str,
+    response: OutgoingResponse,
+) -> Option<OutgoingResponse> {
+    match focus {
+        Some(focus) => focus.hold(source, response),
+        None => Some(response),
+    }
+}
+
 /// Parsed auth result fields for emitting StatusUpdate::AuthRequired.
 struct ParsedAuthData {
     auth_url: Option<String>,
@@ -2545,11 +4889,44 @@ fn detect_auth_awaiting(
     Some((name, instructions))
 }
 
+/// Check if an `ask_user` call returned a form that needs an answer.
+fn detect_input_request(tool_name: &str, result: &Result<String, Error>) -> Option<InputForm> {
+    if tool_name != "ask_user" {
+        return None;
+    }
+    let output = result.as_ref().ok()?;
+    let parsed: serde_json::Value = serde_json::from_str(output).ok()?;
+    if parsed.get("awaiting_input") != Some(&serde_json::Value::Bool(true)) {
+        return None;
+    }
+    serde_json::from_value(parsed.get("form")?.clone()).ok()
+}
+
+/// Check if a `generate_chart` call rendered an image to deliver.
+///
+/// Returns `Some((media_id, title))` for the cached image.
+fn detect_chart_image(
+    tool_name: &str,
+    result: &Result<String, Error>,
+) -> Option<(String, Option<String>)> {
+    if tool_name != "generate_chart" {
+        return None;
+    }
+    let output = result.as_ref().ok()?;
+    let parsed: serde_json::Value = serde_json::from_str(output).ok()?;
+    let media_id = parsed.get("media_id")?.as_str()?.to_string();
+    let title = parsed
+        .get("title")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    Some((media_id, title))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::error::Error;
 
-    use super::detect_auth_awaiting;
+    use super::{detect_auth_awaiting, detect_chart_image, detect_input_request};
 
     #[test]
     fn test_detect_auth_awaiting_positive() {
@@ -2643,6 +5020,49 @@ mod tests {
         assert!(detect_auth_awaiting("tool_activate", &result).is_none());
     }
 
+    #[test]
+    fn test_detect_input_request() {
+        let result: Result<String, Error> = Ok(serde_json::json!({
+            "awaiting_input": true,
+            "form": {
+                "question": "Which size?",
+                "fields": [{"name": "size", "type": "choice", "options": ["S", "M"]}],
+                "timeout_secs": 600
+            }
+        })
+        .to_string());
+
+        let form = detect_input_request("ask_user", &result).unwrap();
+        assert_eq!(form.question, "Which size?");
+        assert_eq!(form.reply_options(), vec!["S", "M"]);
+        assert!(detect_input_request("echo", &result).is_none());
+
+        let plain: Result<String, Error> = Ok("\"hello\"".to_string());
+        assert!(detect_input_request("ask_user", &plain).is_none());
+    }
+
+    #[test]
+    fn test_detect_chart_image() {
+        let result: Result<String, Error> = Ok(serde_json::json!({
+            "media_id": "chart-0123456789abcdef",
+            "mime_type": "image/png",
+            "title": "Job costs"
+        })
+        .to_string());
+
+        let (media_id, title) = detect_chart_image("generate_chart", &result).unwrap();
+        assert_eq!(media_id, "chart-0123456789abcdef");
+        assert_eq!(title.as_deref(), Some("Job costs"));
+        assert!(detect_chart_image("generate_pdf", &result).is_none());
+
+        let failed: Result<String, Error> =
+            Err(Error::from(crate::error::ToolError::ExecutionFailed {
+                name: "generate_chart".to_string(),
+                reason: "bad rows".to_string(),
+            }));
+        assert!(detect_chart_image("generate_chart", &failed).is_none());
+    }
+
     // --- truncate_for_preview tests ---
 
     use super::truncate_for_preview;
@@ -2708,4 +5128,117 @@ mod tests {
         // 'h','e','l','l','o',' ','世','界' = 8 chars
         assert_eq!(result, "hello 世界...");
     }
+
+    /// Records the last user message of each request it is sent.
+    struct RecordingLlm {
+        seen: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl RecordingLlm {
+        fn record(&self, messages: &[crate::llm::ChatMessage]) {
+            let user = messages
+                .iter()
+                .rev()
+                .find(|m| m.role == crate::llm::Role::User)
+                .map(|m| m.content.clone())
+                .unwrap_or_default();
+            self.seen.lock().unwrap().push(user);
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl crate::llm::LlmProvider for RecordingLlm {
+        fn model_name(&self) -> &str {
+            "recording"
+        }
+
+        fn cost_per_token(&self) -> (rust_decimal::Decimal, rust_decimal::Decimal) {
+            (rust_decimal::Decimal::ZERO, rust_decimal::Decimal::ZERO)
+        }
+
+        async fn complete(
+            &self,
+            request: crate::llm::CompletionRequest,
+        ) -> Result<crate::llm::CompletionResponse, crate::error::LlmError> {
+            self.record(&request.messages);
+            Ok(crate::llm::CompletionResponse {
+                content: "ok".to_string(),
+                input_tokens: 0,
+                output_tokens: 0,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                finish_reason: crate::llm::FinishReason::Stop,
+                response_id: None,
+            })
+        }
+
+        async fn complete_with_tools(
+            &self,
+            request: crate::llm::ToolCompletionRequest,
+        ) -> Result<crate::llm::ToolCompletionResponse, crate::error::LlmError> {
+            self.record(&request.messages);
+            Ok(crate::llm::ToolCompletionResponse {
+                content: Some("ok".to_string()),
+                tool_calls: Vec::new(),
+                input_tokens: 0,
+                output_tokens: 0,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                finish_reason: crate::llm::FinishReason::Stop,
+                response_id: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_skill_tool_use_exempts_turn_from_redaction() {
+        use std::collections::HashMap;
+        use std::sync::Arc;
+
+        use crate::agent::session::Turn;
+        use crate::llm::{ChatMessage, Reasoning, ReasoningContext, RedactingProvider, Redactor};
+        use crate::safety::SafetyLayer;
+
+        let inner = Arc::new(RecordingLlm {
+            seen: std::sync::Mutex::new(Vec::new()),
+        });
+        let llm = Arc::new(RedactingProvider::new(
+            inner.clone(),
+            Redactor::new(vec!["Alice".to_string()]),
+            vec!["legal".to_string()],
+        ));
+        let safety = Arc::new(SafetyLayer::new(&crate::config::SafetyConfig {
+            max_output_length: 100_000,
+            injection_check_enabled: false,
+            preserve_redacted: false,
+            preserve_redacted_days: 7,
+            provenance: Vec::new(),
+        }));
+        let reasoning = Reasoning::new(llm, safety);
+        let skill_tools = HashMap::from([
+            ("contract_search".to_string(), "legal".to_string()),
+            ("http".to_string(), "research".to_string()),
+        ]);
+        let thread_id = uuid::Uuid::new_v4();
+
+        let mut turn = Turn::new(0, "Draft a letter to Alice");
+        for tools in [&["echo"][..], &["contract_search", "echo"], &["http"]] {
+            for name in tools {
+                turn.record_tool_call(*name, serde_json::json!({}));
+            }
+            let skill = super::turn_skill(&skill_tools, &turn.tool_calls);
+            let context = ReasoningContext::new()
+                .with_messages(vec![ChatMessage::user("Draft a letter to Alice")])
+                .with_metadata(super::request_metadata(thread_id, skill));
+            reasoning.respond_with_tools(&context).await.unwrap();
+        }
+
+        let seen = inner.seen.lock().unwrap();
+        // No skill tool yet: redacted.
+        assert!(seen[0].contains("PERSON_1") && !seen[0].contains("Alice"));
+        // After the exempt skill's tool ran: sent as-is.
+        assert!(seen[1].contains("Alice"));
+        // The latest skill tool belongs to a skill that is not exempt.
+        assert!(!seen[2].contains("Alice"));
+    }
 }
@@ -0,0 +1,493 @@
+//! Parameter-scoped approval memory.
+//!
+//! Answering "always" to an approval prompt records an [`ApprovalRule`]
+//! scoped to what was actually approved rather than trusting the whole
+//! tool: a shell command prefix such as `cargo test` (limited to the bound
+//! project, if any), or an HTTP method and host such as `GET
+//! api.github.com`. Tools without a narrower scope are remembered as a
+//! whole, except `pipeline`, whose approval is never remembered. Rules are
+//! stored per user in the settings table under [`SETTINGS_KEY`] and can be
+//! listed and revoked with `/approvals`.
+//!
+//! Shell commands that chain or redirect never match a prefix rule, and
+//! destructive commands still ask every time regardless of the rules.
+//! Shells and interpreters (`bash`, `python3`, `env`, ...) run whatever
+//! their arguments say, so for them only the exact command is remembered.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::error::DatabaseError;
+
+/// Settings key holding a user's approval rules.
+pub const SETTINGS_KEY: &str = "approval_rules";
+
+/// Characters that let one shell command run or feed another.
+const SHELL_CHAINING: &[&str] = &[";", "&", "|", "`", "$(", ">", "<", "\n"];
+
+/// Programs whose arguments are code to run; approving one invocation says
+/// nothing about the next. Versioned names (`python3.12`) are matched
+/// without their version suffix.
+const INTERPRETERS: &[&str] = &[
+    "sh",
+    "bash",
+    "zsh",
+    "dash",
+    "ksh",
+    "fish",
+    "csh",
+    "tcsh",
+    "pwsh",
+    "powershell",
+    "python",
+    "pypy",
+    "node",
+    "deno",
+    "bun",
+    "npx",
+    "bunx",
+    "uvx",
+    "pipx",
+    "perl",
+    "ruby",
+    "php",
+    "lua",
+    "tclsh",
+    "osascript",
+    "awk",
+    "gawk",
+    "env",
+    "sudo",
+    "doas",
+    "su",
+    "xargs",
+    "exec",
+    "eval",
+    "nohup",
+    "timeout",
+    "nice",
+    "time",
+    "watch",
+    "find",
+];
+
+/// Tools whose approval is never remembered: what a call does depends on
+/// state that can change after it was approved (a pipeline's stored steps).
+const NEVER_REMEMBERED: &[&str] = &["pipeline"];
+
+/// What an approval rule covers within its tool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ApprovalScope {
+    /// Every call of the tool.
+    Any,
+    /// Shell commands starting with these words.
+    CommandPrefix { prefix: String },
+    /// Exactly this shell command (words compared, spacing ignored).
+    Command { command: String },
+    /// HTTP requests with this method to this host.
+    Http { method: String, host: String },
+}
+
+/// A remembered "always allow" decision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRule {
+    pub id: Uuid,
+    pub tool: String,
+    pub scope: ApprovalScope,
+    /// Project root the rule is limited to; `None` applies everywhere.
+    #[serde(default)]
+    pub project: Option<PathBuf>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ApprovalRule {
+    /// The rule to remember when a call is approved with "always".
+    ///
+    /// Returns `None` when the call has no safe scope to remember, e.g. a
+    /// shell command that chains other commands.
+    pub fn for_call(
+        tool: &str,
+        arguments: &serde_json::Value,
+        project: Option<&Path>,
+    ) -> Option<Self> {
+        if NEVER_REMEMBERED.contains(&tool) {
+            return None;
+        }
+        let params = call_params(arguments);
+        let (scope, project) = match tool {
+            "shell" => {
+                let command = params.get("command").and_then(|c| c.as_str())?;
+                let scope = if runs_interpreter(command) {
+                    if is_chained(command) {
+                        return None;
+                    }
+                    ApprovalScope::Command {
+                        command: normalize_command(command),
+                    }
+                } else {
+                    ApprovalScope::CommandPrefix {
+                        prefix: command_prefix(command)?,
+                    }
+                };
+                (scope, project.map(Path::to_path_buf))
+            }
+            "http" => {
+                let (method, host) = http_target(&params)?;
+                (ApprovalScope::Http { method, host }, None)
+            }
+            _ => (ApprovalScope::Any, None),
+        };
+        Some(Self {
+            id: Uuid::new_v4(),
+            tool: tool.to_string(),
+            scope,
+            project,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Whether this rule approves the given call.
+    pub fn matches(
+        &self,
+        tool: &str,
+        arguments: &serde_json::Value,
+        project: Option<&Path>,
+    ) -> bool {
+        if self.tool != tool || NEVER_REMEMBERED.contains(&tool) {
+            return false;
+        }
+        if let Some(ref root) = self.project
+            && project != Some(root.as_path())
+        {
+            return false;
+        }
+        match &self.scope {
+            ApprovalScope::Any => true,
+            ApprovalScope::CommandPrefix { prefix } => call_params(arguments)
+                .get("command")
+                .and_then(|c| c.as_str())
+                .is_some_and(|command| command_matches(prefix, command)),
+            ApprovalScope::Command { command: approved } => call_params(arguments)
+                .get("command")
+                .and_then(|c| c.as_str())
+                .is_some_and(|command| {
+                    !is_chained(command) && normalize_command(command) == *approved
+                }),
+            ApprovalScope::Http { method, host } => http_target(&call_params(arguments))
+                .is_some_and(|(m, h)| &m == method && &h == host),
+        }
+    }
+
+    /// Short id shown by `/approvals` and accepted by `/approvals revoke`.
+    pub fn short_id(&self) -> String {
+        self.id.simple().to_string()[..8].to_string()
+    }
+
+    /// One-line description, e.g. "shell `cargo test` in /home/me/acme".
+    pub fn describe(&self) -> String {
+        let what = match &self.scope {
+            ApprovalScope::Any => format!("{} (any call)", self.tool),
+            ApprovalScope::CommandPrefix { prefix } => format!("{} `{}`", self.tool, prefix),
+            ApprovalScope::Command { command } => {
+                format!("{} `{}` (exact command)", self.tool, command)
+            }
+            ApprovalScope::Http { method, host } => format!("{} {} {}", self.tool, method, host),
+        };
+        match self.project {
+            Some(ref root) => format!("{} in {}", what, root.display()),
+            None => what,
+        }
+    }
+
+    fn same_grant(&self, other: &Self) -> bool {
+        self.tool == other.tool && self.scope == other.scope && self.project == other.project
+    }
+}
+
+/// A user's approval rules.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApprovalRules {
+    pub rules: Vec<ApprovalRule>,
+}
+
+impl ApprovalRules {
+    /// Load a user's rules; unreadable settings count as no rules.
+    pub async fn load(store: &dyn Database, user_id: &str) -> Self {
+        match store.get_setting(user_id, SETTINGS_KEY).await {
+            Ok(Some(value)) => serde_json::from_value(value).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable approval rules for {}: {}", user_id, e);
+                Self::default()
+            }),
+            Ok(None) => Self::default(),
+            Err(e) => {
+                tracing::warn!("Failed to load approval rules for {}: {}", user_id, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Persist the rules for a user.
+    pub async fn save(&self, store: &dyn Database, user_id: &str) -> Result<(), DatabaseError> {
+        let value =
+            serde_json::to_value(self).map_err(|e| DatabaseError::Serialization(e.to_string()))?;
+        store.set_setting(user_id, SETTINGS_KEY, &value).await
+    }
+
+    /// The first rule approving this call.
+    pub fn find(
+        &self,
+        tool: &str,
+        arguments: &serde_json::Value,
+        project: Option<&Path>,
+    ) -> Option<&ApprovalRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(tool, arguments, project))
+    }
+
+    /// Add a rule unless an identical grant already exists.
+    pub fn add(&mut self, rule: ApprovalRule) -> &ApprovalRule {
+        match self.rules.iter().position(|r| r.same_grant(&rule)) {
+            Some(i) => &self.rules[i],
+            None => {
+                self.rules.push(rule);
+                self.rules.last().expect("just pushed")
+            }
+        }
+    }
+
+    /// Remove the rule whose id starts with `id`; ambiguous prefixes remove nothing.
+    pub fn revoke(&mut self, id: &str) -> Option<ApprovalRule> {
+        let id = id.trim().to_ascii_lowercase();
+        if id.is_empty() {
+            return None;
+        }
+        let mut matching = self
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.id.simple().to_string().starts_with(&id));
+        let (index, _) = matching.next()?;
+        if matching.next().is_some() {
+            return None;
+        }
+        Some(self.rules.remove(index))
+    }
+
+    /// Listing for `/approvals`.
+    pub fn describe(&self) -> String {
+        if self.rules.is_empty() {
+            return "No remembered approvals.".to_string();
+        }
+        let mut out = String::from("Remembered approvals:\n");
+        for rule in &self.rules {
+            out.push_str(&format!(
+                "  {}  {}  (since {})\n",
+                rule.short_id(),
+                rule.describe(),
+                rule.created_at.format("%Y-%m-%d")
+            ));
+        }
+        out.push_str("\nRevoke one with /approvals revoke <id>, or all with /approvals clear.");
+        out
+    }
+}
+
+/// Tool call arguments as an object; some providers send them JSON-encoded.
+pub fn call_params(arguments: &serde_json::Value) -> serde_json::Value {
+    match arguments.as_str() {
+        Some(s) => serde_json::from_str(s).unwrap_or(serde_json::Value::Null),
+        None => arguments.clone(),
+    }
+}
+
+/// The program and, if present, its subcommand: `cargo test --all` gives
+/// `cargo test`, `ls -la` gives `ls`.
+fn command_prefix(command: &str) -> Option<String> {
+    if is_chained(command) {
+        return None;
+    }
+    let mut words = command.split_whitespace();
+    let program = words.next()?;
+    let prefix = match words.next() {
+        Some(sub)
+            if !sub.starts_with('-')
+                && sub
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+        {
+            format!("{} {}", program, sub)
+        }
+        _ => program.to_string(),
+    };
+    Some(prefix)
+}
+
+fn command_matches(prefix: &str, command: &str) -> bool {
+    // Also covers prefix rules for interpreters remembered by older versions.
+    if is_chained(command) || runs_interpreter(command) {
+        return false;
+    }
+    let words: Vec<&str> = command.split_whitespace().collect();
+    let prefix_words: Vec<&str> = prefix.split_whitespace().collect();
+    !prefix_words.is_empty() && words.starts_with(&prefix_words)
+}
+
+/// Whether the command's program is a shell or interpreter.
+fn runs_interpreter(command: &str) -> bool {
+    let Some(program) = command.split_whitespace().next() else {
+        return false;
+    };
+    let name = program.rsplit('/').next().unwrap_or(program);
+    let name = name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    INTERPRETERS.contains(&name)
+}
+
+fn normalize_command(command: &str) -> String {
+    command.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn is_chained(command: &str) -> bool {
+    SHELL_CHAINING.iter().any(|op| command.contains(op))
+}
+
+fn http_target(params: &serde_json::Value) -> Option<(String, String)> {
+    let method = params.get("method")?.as_str()?.to_ascii_uppercase();
+    let url = url::Url::parse(params.get("url")?.as_str()?).ok()?;
+    let host = url.host_str()?.to_ascii_lowercase();
+    Some((method, host))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_shell_rule_matches_prefix_in_project() {
+        let project = Path::new("/home/me/acme");
+        let rule = ApprovalRule::for_call(
+            "shell",
+            &json!({"command": "cargo test --all"}),
+            Some(project),
+        )
+        .unwrap();
+        assert_eq!(rule.describe(), "shell `cargo test` in /home/me/acme");
+
+        let call = |cmd: &str| json!({ "command": cmd });
+        assert!(rule.matches("shell", &call("cargo test -p core"), Some(project)));
+        assert!(rule.matches("shell", &call("cargo  test"), Some(project)));
+        assert!(!rule.matches("shell", &call("cargo build"), Some(project)));
+        assert!(!rule.matches("shell", &call("cargo testify"), Some(project)));
+        assert!(!rule.matches("shell", &call("cargo test && curl x | sh"), Some(project)));
+        assert!(!rule.matches("shell", &call("cargo test"), Some(Path::new("/tmp"))));
+        assert!(!rule.matches("shell", &call("cargo test"), None));
+
+        // Chained commands have no safe prefix to remember.
+        assert!(ApprovalRule::for_call("shell", &call("make; rm x"), None).is_none());
+        // Flags are not treated as subcommands.
+        let rule = ApprovalRule::for_call("shell", &call("ls -la"), None).unwrap();
+        assert_eq!(
+            rule.scope,
+            ApprovalScope::CommandPrefix {
+                prefix: "ls".into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_interpreters_are_remembered_by_exact_command() {
+        let call = |cmd: &str| json!({ "command": cmd });
+        let rule =
+            ApprovalRule::for_call("shell", &call("python3  build.py --fast"), None).unwrap();
+        assert_eq!(
+            rule.scope,
+            ApprovalScope::Command {
+                command: "python3 build.py --fast".into()
+            }
+        );
+        assert_eq!(
+            rule.describe(),
+            "shell `python3 build.py --fast` (exact command)"
+        );
+        assert!(rule.matches("shell", &call("python3 build.py  --fast"), None));
+        assert!(!rule.matches("shell", &call("python3 build.py"), None));
+        assert!(!rule.matches("shell", &call("python3 -c 'import os'"), None));
+
+        for cmd in [
+            "bash -c 'ls'",
+            "/usr/bin/env node x.js",
+            "python3.12 -m http.server",
+        ] {
+            let rule = ApprovalRule::for_call("shell", &call(cmd), None).unwrap();
+            assert!(
+                matches!(rule.scope, ApprovalScope::Command { .. }),
+                "{}",
+                cmd
+            );
+        }
+
+        // A prefix rule stored for an interpreter no longer matches anything.
+        let old = ApprovalRule {
+            scope: ApprovalScope::CommandPrefix {
+                prefix: "python3".into(),
+            },
+            ..ApprovalRule::for_call("shell", &call("ls"), None).unwrap()
+        };
+        assert!(!old.matches("shell", &call("python3 -c 'import os'"), None));
+    }
+
+    #[test]
+    fn test_http_rule_matches_method_and_host() {
+        // Arguments may arrive JSON-encoded.
+        let args = json!(r#"{"method": "get", "url": "https://API.github.com/repos/x"}"#);
+        let rule = ApprovalRule::for_call("http", &args, Some(Path::new("/p"))).unwrap();
+        assert_eq!(rule.describe(), "http GET api.github.com");
+
+        let call = |method: &str, url: &str| json!({ "method": method, "url": url });
+        assert!(rule.matches("http", &call("GET", "https://api.github.com/user"), None));
+        assert!(!rule.matches("http", &call("POST", "https://api.github.com/user"), None));
+        assert!(!rule.matches(
+            "http",
+            &call("GET", "https://evil.example/api.github.com"),
+            None
+        ));
+        assert!(!rule.matches("shell", &call("GET", "https://api.github.com"), None));
+    }
+
+    #[test]
+    fn test_rules_dedupe_and_revoke() {
+        let mut rules = ApprovalRules::default();
+        let args = json!({"command": "git status"});
+        let first = rules
+            .add(ApprovalRule::for_call("shell", &args, None).unwrap())
+            .id;
+        let again = rules
+            .add(ApprovalRule::for_call("shell", &args, None).unwrap())
+            .id;
+        assert_eq!(first, again);
+        rules.add(ApprovalRule::for_call("memory_write", &json!({}), None).unwrap());
+        assert_eq!(rules.rules.len(), 2);
+        assert!(rules.find("memory_write", &json!({"x": 1}), None).is_some());
+
+        // Pipelines are never remembered, and older rules for them are ignored.
+        let run = json!({"action": "run", "name": "deploy"});
+        assert!(ApprovalRule::for_call("pipeline", &run, None).is_none());
+        let legacy = ApprovalRule {
+            tool: "pipeline".to_string(),
+            ..ApprovalRule::for_call("memory_write", &json!({}), None).unwrap()
+        };
+        assert!(!legacy.matches("pipeline", &run, None));
+
+        assert!(rules.revoke("").is_none());
+        let short = rules.rules[0].short_id();
+        assert_eq!(rules.revoke(&short).map(|r| r.id), Some(first));
+        assert!(rules.find("shell", &args, None).is_none());
+        assert_eq!(rules.rules.len(), 1);
+    }
+}
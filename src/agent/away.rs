@@ -0,0 +1,548 @@
+//! Auto-responses while the user is away.
+//!
+//! The user counts as away while focus mode is on or outside the configured
+//! working hours. Messages from other people that arrive then get a short
+//! acknowledgement in the user's persona and are queued instead of being
+//! answered by the agent. The queue comes back as one backlog, summarized
+//! per channel and sender role, with the user's first message after they
+//! return (or on `/away backlog`).
+//!
+//! Who counts as "other people" is decided by [`SenderRole`]: the owner's
+//! own identities (the local REPL and web gateway, plus `AWAY_OWNERS`) are
+//! never intercepted; senders linked in the contacts store are contacts,
+//! everyone else is unknown. Rules pick the reply per channel and role:
+//!
+//! ```json
+//! [
+//!   {"channel": "slack", "role": "contact", "reply": "In a meeting, back {back}."},
+//!   {"role": "unknown", "reply": ""},
+//!   {"channel": "telegram", "queue": false}
+//! ]
+//! ```
+//!
+//! The first matching rule wins; without one the default persona text is
+//! used. An empty reply queues silently, and `"queue": false` sends the
+//! acknowledgement but lets the agent handle the message as usual.
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Timelike, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::agent::focus::FocusMode;
+use crate::channels::IncomingMessage;
+use crate::config::AwayConfig;
+use crate::locale::Tz;
+
+/// Queued messages kept for the backlog; older ones are counted but dropped.
+const MAX_QUEUED: usize = 200;
+
+/// Longest message text kept per queued message.
+const MAX_QUEUED_CHARS: usize = 2000;
+
+/// Channels where the sender is always the owner.
+const OWNER_CHANNELS: &[&str] = &["repl", "gateway", "cli"];
+
+/// How a sender relates to the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SenderRole {
+    /// The user themselves.
+    Owner,
+    /// Someone linked in the contacts store.
+    Contact,
+    /// Anyone else.
+    Unknown,
+}
+
+impl SenderRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SenderRole::Owner => "owner",
+            SenderRole::Contact => "contact",
+            SenderRole::Unknown => "unknown",
+        }
+    }
+}
+
+/// One auto-response rule. Unset fields match anything.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AwayRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<SenderRole>,
+    /// Acknowledgement text; `{name}` and `{back}` are filled in. Empty
+    /// means no acknowledgement. The default persona when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply: Option<String>,
+    /// Hold the message for the backlog instead of letting the agent answer.
+    #[serde(default = "default_true")]
+    pub queue: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl AwayRule {
+    fn matches(&self, channel: &str, role: SenderRole) -> bool {
+        self.channel
+            .as_deref()
+            .is_none_or(|c| c == "*" || c == channel)
+            && self.role.is_none_or(|r| r == role)
+    }
+}
+
+/// Parse a working-hours window "HH:MM-HH:MM". It may not wrap midnight.
+pub fn parse_working_hours(s: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let (start, end) = s.split_once('-')?;
+    let start = NaiveTime::parse_from_str(start.trim(), "%H:%M").ok()?;
+    let end = NaiveTime::parse_from_str(end.trim(), "%H:%M").ok()?;
+    (start < end).then_some((start, end))
+}
+
+/// A message held while the user was away.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedMessage {
+    pub at: DateTime<Utc>,
+    pub channel: String,
+    pub role: SenderRole,
+    /// Display name, or the channel user id.
+    pub sender: String,
+    pub text: String,
+}
+
+/// Held messages, grouped by channel and then sender role.
+#[derive(Debug, Clone, Default)]
+pub struct Backlog {
+    pub groups: BTreeMap<(String, SenderRole), Vec<QueuedMessage>>,
+    /// Messages past the queue limit, counted only.
+    pub dropped: usize,
+}
+
+impl Backlog {
+    pub fn len(&self) -> usize {
+        self.groups.values().map(Vec::len).sum::<usize>() + self.dropped
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The backlog as a plain list, used as the summarizer's input and as
+    /// the fallback when summarizing fails.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for ((channel, role), messages) in &self.groups {
+            out.push_str(&format!("\n{} ({}):\n", channel, role.as_str()));
+            for m in messages {
+                let first_line = m.text.lines().find(|l| !l.trim().is_empty());
+                out.push_str(&format!(
+                    "- {} {}: {}\n",
+                    m.at.format("%H:%M UTC"),
+                    m.sender,
+                    first_line.unwrap_or("").trim()
+                ));
+            }
+        }
+        if self.dropped > 0 {
+            out.push_str(&format!("\n...and {} more\n", self.dropped));
+        }
+        out
+    }
+}
+
+/// What to do with an intercepted message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AwayAction {
+    /// Acknowledgement to send, if any.
+    pub reply: Option<String>,
+    /// Whether the message was queued (and must not reach the agent).
+    pub queued: bool,
+}
+
+#[derive(Default)]
+struct AwayState {
+    queue: Vec<QueuedMessage>,
+    dropped: usize,
+    /// Senders acknowledged since the user was last present.
+    acknowledged: HashSet<(String, String)>,
+}
+
+/// Away detection, auto-acknowledgement and the message queue.
+pub struct AwayDesk {
+    config: AwayConfig,
+    hours: Option<(NaiveTime, NaiveTime)>,
+    tz: Tz,
+    focus: Option<Arc<FocusMode>>,
+    state: Mutex<AwayState>,
+}
+
+impl AwayDesk {
+    /// Build the desk; a malformed working-hours window is ignored with a
+    /// warning.
+    pub fn new(config: AwayConfig, tz: Tz, focus: Option<Arc<FocusMode>>) -> Self {
+        let hours = config.working_hours.as_deref().and_then(|s| {
+            let window = parse_working_hours(s);
+            if window.is_none() {
+                tracing::warn!("Ignoring AWAY_WORKING_HOURS '{}': expected HH:MM-HH:MM", s);
+            }
+            window
+        });
+        Self {
+            config,
+            hours,
+            tz,
+            focus,
+            state: Mutex::new(AwayState::default()),
+        }
+    }
+
+    /// Role of a message's sender. `is_contact` is whether the contacts
+    /// store knows the identity.
+    pub fn role_of(&self, message: &IncomingMessage, is_contact: bool) -> SenderRole {
+        let identity = format!("{}:{}", message.channel, message.user_id);
+        if OWNER_CHANNELS.contains(&message.channel.as_str())
+            || self.config.owners.contains(&identity)
+        {
+            SenderRole::Owner
+        } else if is_contact {
+            SenderRole::Contact
+        } else {
+            SenderRole::Unknown
+        }
+    }
+
+    /// Whether the user is unavailable at `now`.
+    pub fn is_away(&self, now: DateTime<Utc>) -> bool {
+        self.focus.as_ref().is_some_and(|f| f.is_active()) || !self.in_working_hours(now)
+    }
+
+    fn in_working_hours(&self, now: DateTime<Utc>) -> bool {
+        let Some((start, end)) = self.hours else {
+            return true;
+        };
+        let local = self.tz.to_local(now);
+        let time = NaiveTime::from_hms_opt(local.hour(), local.minute(), 0).unwrap_or_default();
+        self.config.working_days.contains(&local.weekday()) && start <= time && time < end
+    }
+
+    /// When the user is expected back: the end of focus mode or the next
+    /// start of working hours, whichever is later.
+    pub fn back_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let focus_end = self
+            .focus
+            .as_ref()
+            .and_then(|f| f.status().until)
+            .filter(|until| *until > now);
+        let from = focus_end.unwrap_or(now);
+        if self.in_working_hours(from) {
+            return focus_end;
+        }
+        let (start, _) = self.hours?;
+        let local = self.tz.to_local(from);
+        (0..8).find_map(|days| {
+            let date = local.date_naive() + Duration::days(days);
+            let candidate = date.and_time(start);
+            let offset = *local.offset();
+            let at = candidate.and_local_timezone(offset).single()?;
+            (self.config.working_days.contains(&date.weekday()) && at > local)
+                .then(|| at.with_timezone(&Utc))
+        })
+    }
+
+    /// Decide what to do with an inbound message. `None` lets it through
+    /// untouched: the desk is disabled, the sender is the owner or the user
+    /// is not away.
+    pub fn intercept(
+        &self,
+        message: &IncomingMessage,
+        role: SenderRole,
+        now: DateTime<Utc>,
+    ) -> Option<AwayAction> {
+        if !self.config.enabled || role == SenderRole::Owner || !self.is_away(now) {
+            return None;
+        }
+        let rule = self
+            .config
+            .rules
+            .iter()
+            .find(|r| r.matches(&message.channel, role));
+        let queued = rule.is_none_or(|r| r.queue);
+        let template = rule
+            .and_then(|r| r.reply.as_deref())
+            .unwrap_or(&self.config.persona);
+
+        let mut state = self.state.lock().expect("away lock");
+        if queued {
+            if state.queue.len() >= MAX_QUEUED {
+                state.dropped += 1;
+            } else {
+                state.queue.push(QueuedMessage {
+                    at: message.received_at,
+                    channel: message.channel.clone(),
+                    role,
+                    sender: message
+                        .user_name
+                        .clone()
+                        .unwrap_or_else(|| message.user_id.clone()),
+                    text: message.content.chars().take(MAX_QUEUED_CHARS).collect(),
+                });
+            }
+        }
+
+        // One acknowledgement per sender per absence.
+        let first = state
+            .acknowledged
+            .insert((message.channel.clone(), message.user_id.clone()));
+        let reply =
+            (first && !template.trim().is_empty()).then(|| self.fill(template, message, now));
+        Some(AwayAction { reply, queued })
+    }
+
+    fn fill(&self, template: &str, message: &IncomingMessage, now: DateTime<Utc>) -> String {
+        let name = message.user_name.as_deref().unwrap_or("there");
+        let back = match self.back_at(now) {
+            Some(at) => {
+                let local = self.tz.to_local(at);
+                if local.date_naive() == self.tz.to_local(now).date_naive() {
+                    format!("at {}", local.format("%H:%M"))
+                } else {
+                    format!("on {}", local.format("%A at %H:%M"))
+                }
+            }
+            None => "soon".to_string(),
+        };
+        template.replace("{name}", name).replace("{back}", &back)
+    }
+
+    /// Number of queued messages.
+    pub fn queued(&self) -> usize {
+        let state = self.state.lock().expect("away lock");
+        state.queue.len() + state.dropped
+    }
+
+    /// Drain the queue. Also resets who has been acknowledged, so the next
+    /// absence acknowledges everyone again.
+    pub fn take_backlog(&self) -> Backlog {
+        let mut state = self.state.lock().expect("away lock");
+        state.acknowledged.clear();
+        let mut backlog = Backlog {
+            dropped: std::mem::take(&mut state.dropped),
+            ..Backlog::default()
+        };
+        for message in std::mem::take(&mut state.queue) {
+            backlog
+                .groups
+                .entry((message.channel.clone(), message.role))
+                .or_default()
+                .push(message);
+        }
+        backlog
+    }
+}
+
+/// Instructions for summarizing a backlog.
+pub const BACKLOG_PROMPT: &str = "While the user was away, the messages below arrived and \
+     were acknowledged but not answered. Summarize them for the user, keeping the grouping by \
+     channel and sender role. Give one line per sender saying what they want and whether a \
+     reply is needed. Put anything urgent or time-sensitive first. Do not answer the messages.";
+
+/// Working days from a comma-separated list ("mon,tue,wed").
+pub fn parse_working_days(s: &str) -> Result<Vec<Weekday>, String> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|d| {
+            d.parse::<Weekday>()
+                .map_err(|_| format!("unknown weekday '{}'", d))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn desk(rules: Vec<AwayRule>) -> AwayDesk {
+        let config = AwayConfig {
+            enabled: true,
+            working_hours: Some("09:00-17:00".to_string()),
+            owners: vec!["telegram:42".to_string()],
+            rules,
+            ..AwayConfig::default()
+        };
+        AwayDesk::new(config, Tz::utc(), None)
+    }
+
+    fn message(channel: &str, user: &str, text: &str) -> IncomingMessage {
+        let mut m = IncomingMessage::new(channel, user, text);
+        m.user_name = Some("Ana".to_string());
+        m
+    }
+
+    // 2026-03-06 is a Friday.
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_away_outside_working_hours_and_days() {
+        let desk = desk(Vec::new());
+        assert!(!desk.is_away(at(6, 10)));
+        assert!(desk.is_away(at(6, 8)));
+        assert!(desk.is_away(at(6, 17)));
+        assert!(desk.is_away(at(7, 10)));
+
+        assert_eq!(desk.back_at(at(6, 8)), Some(at(6, 9)));
+        assert_eq!(desk.back_at(at(6, 18)), Some(at(9, 9)));
+        assert_eq!(desk.back_at(at(6, 10)), None);
+    }
+
+    #[test]
+    fn test_focus_counts_as_away() {
+        let focus = Arc::new(FocusMode::new());
+        let config = AwayConfig {
+            enabled: true,
+            ..AwayConfig::default()
+        };
+        let desk = AwayDesk::new(config, Tz::utc(), Some(Arc::clone(&focus)));
+        let now = Utc::now();
+        assert!(!desk.is_away(now));
+        let until = focus.start(Duration::hours(1));
+        assert!(desk.is_away(now));
+        assert_eq!(desk.back_at(now), Some(until));
+    }
+
+    #[test]
+    fn test_intercept_acknowledges_once_and_queues() {
+        let desk = desk(Vec::new());
+        let night = at(6, 22);
+        let first = desk
+            .intercept(
+                &message("slack", "U1", "Can you review my PR?"),
+                SenderRole::Contact,
+                night,
+            )
+            .unwrap();
+        assert!(first.queued);
+        let reply = first.reply.unwrap();
+        assert!(reply.contains("on Monday at 09:00"), "{reply}");
+
+        let second = desk
+            .intercept(&message("slack", "U1", "ping"), SenderRole::Contact, night)
+            .unwrap();
+        assert_eq!(second.reply, None);
+        assert!(second.queued);
+
+        assert!(
+            desk.intercept(
+                &message("slack", "U1", "hi"),
+                SenderRole::Contact,
+                at(6, 10)
+            )
+            .is_none()
+        );
+        assert!(
+            desk.intercept(&message("telegram", "42", "note"), SenderRole::Owner, night)
+                .is_none()
+        );
+
+        assert_eq!(desk.queued(), 2);
+        let backlog = desk.take_backlog();
+        assert_eq!(backlog.len(), 2);
+        let text = backlog.render();
+        assert!(text.contains("slack (contact):"), "{text}");
+        assert!(text.contains("Ana: Can you review my PR?"), "{text}");
+        assert_eq!(desk.queued(), 0);
+
+        // Acknowledged again after the user came back.
+        let again = desk
+            .intercept(
+                &message("slack", "U1", "hello?"),
+                SenderRole::Contact,
+                night,
+            )
+            .unwrap();
+        assert!(again.reply.is_some());
+    }
+
+    #[test]
+    fn test_rules_match_channel_and_role() {
+        let desk = desk(vec![
+            AwayRule {
+                channel: Some("slack".to_string()),
+                role: Some(SenderRole::Contact),
+                reply: Some("Hi {name}, back {back}.".to_string()),
+                queue: true,
+            },
+            AwayRule {
+                channel: None,
+                role: Some(SenderRole::Unknown),
+                reply: Some(String::new()),
+                queue: true,
+            },
+            AwayRule {
+                channel: Some("telegram".to_string()),
+                role: None,
+                reply: None,
+                queue: false,
+            },
+        ]);
+        let night = at(6, 8);
+
+        let slack = desk
+            .intercept(&message("slack", "U1", "x"), SenderRole::Contact, night)
+            .unwrap();
+        assert_eq!(slack.reply.as_deref(), Some("Hi Ana, back at 09:00."));
+
+        let stranger = desk
+            .intercept(&message("slack", "U2", "x"), SenderRole::Unknown, night)
+            .unwrap();
+        assert_eq!(
+            stranger,
+            AwayAction {
+                reply: None,
+                queued: true
+            }
+        );
+
+        let telegram = desk
+            .intercept(&message("telegram", "7", "x"), SenderRole::Contact, night)
+            .unwrap();
+        assert!(!telegram.queued);
+        assert!(telegram.reply.is_some());
+        assert_eq!(desk.queued(), 2);
+    }
+
+    #[test]
+    fn test_role_of() {
+        let desk = desk(Vec::new());
+        assert_eq!(
+            desk.role_of(&message("repl", "default", ""), false),
+            SenderRole::Owner
+        );
+        assert_eq!(
+            desk.role_of(&message("telegram", "42", ""), false),
+            SenderRole::Owner
+        );
+        assert_eq!(
+            desk.role_of(&message("telegram", "7", ""), true),
+            SenderRole::Contact
+        );
+        assert_eq!(
+            desk.role_of(&message("telegram", "7", ""), false),
+            SenderRole::Unknown
+        );
+        assert_eq!(parse_working_hours("18:00-09:00"), None);
+        assert_eq!(
+            parse_working_days("mon, fri").unwrap(),
+            vec![Weekday::Mon, Weekday::Fri]
+        );
+        assert!(parse_working_days("mon,funday").is_err());
+    }
+}
@@ -0,0 +1,485 @@
+//! Token and cost caps on chat turns.
+//!
+//! `BudgetManager` tallies the tokens and cost of every LLM call a chat
+//! turn makes, per session and per user per UTC day, and holds them against
+//! the caps in [`BudgetConfig`]. Crossing the warning threshold of a cap
+//! sends one status update; reaching a cap blocks further calls until the
+//! user approves an override with `/budget override`, which lifts that
+//! user's caps for the rest of the day. Tallies are kept in memory and
+//! start over when the agent restarts.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::config::BudgetConfig;
+
+/// Which cap a warning or block is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetScope {
+    SessionTokens,
+    DailyTokens,
+    DailyCost,
+}
+
+impl std::fmt::Display for BudgetScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BudgetScope::SessionTokens => "session token",
+            BudgetScope::DailyTokens => "daily token",
+            BudgetScope::DailyCost => "daily cost",
+        })
+    }
+}
+
+/// Tokens and cost run up against one cap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Spend {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost: Decimal,
+}
+
+impl Spend {
+    pub fn tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    fn add(&mut self, input_tokens: u32, output_tokens: u32, cost: Decimal) {
+        self.input_tokens += u64::from(input_tokens);
+        self.output_tokens += u64::from(output_tokens);
+        self.cost += cost;
+    }
+}
+
+/// A cap's threshold crossed by the last call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetWarning {
+    pub scope: BudgetScope,
+    pub used: String,
+    pub limit: String,
+    pub percent: u8,
+}
+
+impl BudgetWarning {
+    pub fn message(&self) -> String {
+        format!(
+            "Budget: {}% of the {} cap used ({} of {}).",
+            self.percent, self.scope, self.used, self.limit
+        )
+    }
+}
+
+/// A cap that blocks further LLM calls.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetExceeded {
+    pub scope: BudgetScope,
+    pub used: String,
+    pub limit: String,
+}
+
+impl BudgetExceeded {
+    pub fn message(&self) -> String {
+        format!(
+            "The {} cap is reached ({} of {}), so I've stopped before calling the model \
+             again. Send /budget override to allow more usage for the rest of today.",
+            self.scope, self.used, self.limit
+        )
+    }
+}
+
+/// One session's spend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSpend {
+    pub session_id: Uuid,
+    pub spend: Spend,
+}
+
+/// Current spend and caps for one user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetReport {
+    /// UTC day the daily tallies cover.
+    pub day: NaiveDate,
+    pub today: Spend,
+    pub sessions: Vec<SessionSpend>,
+    pub session_tokens_cap: Option<u64>,
+    pub daily_tokens_cap: Option<u64>,
+    pub daily_cost_cap: Option<Decimal>,
+    /// Whether the user lifted their caps for today.
+    pub overridden: bool,
+}
+
+impl BudgetReport {
+    /// Plain-text rendering for `/budget` and `ironclaw status --budget`.
+    pub fn render(&self) -> String {
+        let mut out = format!("Budget for {} (UTC)\n", self.day);
+        out.push_str(&format!(
+            "  Tokens today:  {}\n",
+            against(self.today.tokens().to_string(), self.daily_tokens_cap)
+        ));
+        out.push_str(&format!(
+            "  Cost today:    {}\n",
+            against(
+                format!("${:.4}", self.today.cost),
+                self.daily_cost_cap.map(|c| format!("${:.2}", c))
+            )
+        ));
+        for session in &self.sessions {
+            out.push_str(&format!(
+                "  Session {}: {}\n",
+                &session.session_id.to_string()[..8],
+                against(session.spend.tokens().to_string(), self.session_tokens_cap)
+            ));
+        }
+        if self.overridden {
+            out.push_str("  Caps overridden for the rest of today.\n");
+        }
+        out
+    }
+}
+
+fn against(used: String, limit: Option<impl std::fmt::Display>) -> String {
+    match limit {
+        Some(limit) => format!("{} of {}", used, limit),
+        None => format!("{} (no cap)", used),
+    }
+}
+
+struct SessionTally {
+    user_id: String,
+    spend: Spend,
+}
+
+#[derive(Default)]
+struct BudgetState {
+    day: Option<NaiveDate>,
+    users: HashMap<String, Spend>,
+    sessions: HashMap<Uuid, SessionTally>,
+    overrides: HashSet<String>,
+    /// Warnings already sent today, so each fires once per cap.
+    warned: HashSet<(String, Option<Uuid>, BudgetScope)>,
+}
+
+impl BudgetState {
+    /// Start the daily tallies over when the UTC day changes. Session
+    /// tallies run for the life of the session.
+    fn roll(&mut self, today: NaiveDate) {
+        if self.day != Some(today) {
+            self.day = Some(today);
+            self.users.clear();
+            self.overrides.clear();
+            self.warned.retain(|(_, session, _)| session.is_some());
+        }
+    }
+}
+
+/// Tracks chat turn spend against the configured caps.
+pub struct BudgetManager {
+    config: BudgetConfig,
+    state: Mutex<BudgetState>,
+}
+
+impl BudgetManager {
+    pub fn new(config: BudgetConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(BudgetState::default()),
+        }
+    }
+
+    fn state(&self, today: NaiveDate) -> std::sync::MutexGuard<'_, BudgetState> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.roll(today);
+        state
+    }
+
+    /// Usage of every cap that is set: scope, fraction used, and the used
+    /// and limit amounts as shown to the user.
+    fn usage(&self, user: &Spend, session: &Spend) -> Vec<(BudgetScope, f64, String, String)> {
+        let mut usage = Vec::new();
+        if let Some(cap) = self.config.session_tokens {
+            usage.push((
+                BudgetScope::SessionTokens,
+                session.tokens() as f64 / cap.max(1) as f64,
+                session.tokens().to_string(),
+                cap.to_string(),
+            ));
+        }
+        if let Some(cap) = self.config.daily_tokens {
+            usage.push((
+                BudgetScope::DailyTokens,
+                user.tokens() as f64 / cap.max(1) as f64,
+                user.tokens().to_string(),
+                cap.to_string(),
+            ));
+        }
+        if let Some(cap) = self.config.daily_cost {
+            let fraction = if cap.is_zero() {
+                f64::INFINITY
+            } else {
+                (user.cost / cap).to_f64().unwrap_or(f64::INFINITY)
+            };
+            usage.push((
+                BudgetScope::DailyCost,
+                fraction,
+                format!("${:.4}", user.cost),
+                format!("${:.2}", cap),
+            ));
+        }
+        usage
+    }
+
+    /// Whether another LLM call is allowed for this user and session.
+    pub fn check(&self, user_id: &str, session_id: Uuid) -> Result<(), BudgetExceeded> {
+        self.check_on(Utc::now().date_naive(), user_id, session_id)
+    }
+
+    fn check_on(
+        &self,
+        today: NaiveDate,
+        user_id: &str,
+        session_id: Uuid,
+    ) -> Result<(), BudgetExceeded> {
+        let state = self.state(today);
+        if state.overrides.contains(user_id) {
+            return Ok(());
+        }
+        let user = state.users.get(user_id).copied().unwrap_or_default();
+        let session = state
+            .sessions
+            .get(&session_id)
+            .map(|s| s.spend)
+            .unwrap_or_default();
+        match self
+            .usage(&user, &session)
+            .into_iter()
+            .find(|(_, fraction, _, _)| *fraction >= 1.0)
+        {
+            Some((scope, _, used, limit)) => Err(BudgetExceeded { scope, used, limit }),
+            None => Ok(()),
+        }
+    }
+
+    /// Add one call's usage, returning warnings for thresholds it crossed.
+    pub fn record(
+        &self,
+        user_id: &str,
+        session_id: Uuid,
+        input_tokens: u32,
+        output_tokens: u32,
+        cost: Decimal,
+    ) -> Vec<BudgetWarning> {
+        self.record_on(
+            Utc::now().date_naive(),
+            user_id,
+            session_id,
+            input_tokens,
+            output_tokens,
+            cost,
+        )
+    }
+
+    fn record_on(
+        &self,
+        today: NaiveDate,
+        user_id: &str,
+        session_id: Uuid,
+        input_tokens: u32,
+        output_tokens: u32,
+        cost: Decimal,
+    ) -> Vec<BudgetWarning> {
+        let mut state = self.state(today);
+        let user = state.users.entry(user_id.to_string()).or_default();
+        user.add(input_tokens, output_tokens, cost);
+        let user = *user;
+        let session = state
+            .sessions
+            .entry(session_id)
+            .or_insert_with(|| SessionTally {
+                user_id: user_id.to_string(),
+                spend: Spend::default(),
+            });
+        session.spend.add(input_tokens, output_tokens, cost);
+        let session = session.spend;
+
+        if state.overrides.contains(user_id) {
+            return Vec::new();
+        }
+        let threshold = f64::from(self.config.warn_percent) / 100.0;
+        let mut warnings = Vec::new();
+        for (scope, fraction, used, limit) in self.usage(&user, &session) {
+            let key = (
+                user_id.to_string(),
+                (scope == BudgetScope::SessionTokens).then_some(session_id),
+                scope,
+            );
+            if fraction >= threshold && fraction < 1.0 && state.warned.insert(key) {
+                warnings.push(BudgetWarning {
+                    scope,
+                    used,
+                    limit,
+                    percent: (fraction * 100.0).floor() as u8,
+                });
+            }
+        }
+        warnings
+    }
+
+    /// Lift the user's caps until the end of the UTC day.
+    pub fn grant_override(&self, user_id: &str) {
+        self.state(Utc::now().date_naive())
+            .overrides
+            .insert(user_id.to_string());
+    }
+
+    pub fn report(&self, user_id: &str) -> BudgetReport {
+        self.report_on(Utc::now().date_naive(), user_id)
+    }
+
+    fn report_on(&self, today: NaiveDate, user_id: &str) -> BudgetReport {
+        let state = self.state(today);
+        let mut sessions: Vec<SessionSpend> = state
+            .sessions
+            .iter()
+            .filter(|(_, tally)| tally.user_id == user_id)
+            .map(|(id, tally)| SessionSpend {
+                session_id: *id,
+                spend: tally.spend,
+            })
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.spend.tokens()));
+        BudgetReport {
+            day: today,
+            today: state.users.get(user_id).copied().unwrap_or_default(),
+            sessions,
+            session_tokens_cap: self.config.session_tokens,
+            daily_tokens_cap: self.config.daily_tokens,
+            daily_cost_cap: self.config.daily_cost,
+            overridden: state.overrides.contains(user_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    fn manager(config: BudgetConfig) -> BudgetManager {
+        BudgetManager::new(BudgetConfig {
+            warn_percent: 80,
+            ..config
+        })
+    }
+
+    #[test]
+    fn test_daily_token_cap_blocks_and_warns_once() {
+        let budget = manager(BudgetConfig {
+            daily_tokens: Some(1000),
+            ..Default::default()
+        });
+        let session = Uuid::new_v4();
+
+        assert!(
+            budget
+                .record_on(day(1), "alice", session, 500, 100, Decimal::ZERO)
+                .is_empty()
+        );
+        let warnings = budget.record_on(day(1), "alice", session, 200, 50, Decimal::ZERO);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].scope, BudgetScope::DailyTokens);
+        assert_eq!(warnings[0].percent, 85);
+        // Already warned about this cap today.
+        assert!(
+            budget
+                .record_on(day(1), "alice", session, 10, 0, Decimal::ZERO)
+                .is_empty()
+        );
+        assert!(budget.check_on(day(1), "alice", session).is_ok());
+
+        budget.record_on(day(1), "alice", session, 200, 0, Decimal::ZERO);
+        let exceeded = budget.check_on(day(1), "alice", session).unwrap_err();
+        assert_eq!(exceeded.scope, BudgetScope::DailyTokens);
+        assert!(exceeded.message().contains("/budget override"));
+
+        // Other users have their own tally; the next day starts over.
+        assert!(budget.check_on(day(1), "bob", Uuid::new_v4()).is_ok());
+        assert!(budget.check_on(day(2), "alice", session).is_ok());
+    }
+
+    #[test]
+    fn test_session_cap_survives_day_change() {
+        let budget = manager(BudgetConfig {
+            session_tokens: Some(100),
+            ..Default::default()
+        });
+        let session = Uuid::new_v4();
+        budget.record_on(day(1), "alice", session, 100, 0, Decimal::ZERO);
+        let exceeded = budget.check_on(day(2), "alice", session).unwrap_err();
+        assert_eq!(exceeded.scope, BudgetScope::SessionTokens);
+        assert!(budget.check_on(day(2), "alice", Uuid::new_v4()).is_ok());
+    }
+
+    #[test]
+    fn test_cost_cap_and_override() {
+        let budget = manager(BudgetConfig {
+            daily_cost: Some(Decimal::new(100, 2)), // $1.00
+            ..Default::default()
+        });
+        let session = Uuid::new_v4();
+        let today = Utc::now().date_naive();
+        budget.record_on(today, "alice", session, 10, 10, Decimal::new(120, 2));
+        let exceeded = budget.check_on(today, "alice", session).unwrap_err();
+        assert_eq!(exceeded.scope, BudgetScope::DailyCost);
+
+        budget.grant_override("alice");
+        assert!(budget.check_on(today, "alice", session).is_ok());
+        assert!(budget.report_on(today, "alice").overridden);
+        // Overrides last for the day only.
+        assert!(
+            budget
+                .check_on(today.succ_opt().unwrap(), "alice", session)
+                .is_ok()
+        );
+        assert!(
+            !budget
+                .report_on(today.succ_opt().unwrap(), "alice")
+                .overridden
+        );
+    }
+
+    #[test]
+    fn test_report_lists_own_sessions() {
+        let budget = manager(BudgetConfig {
+            daily_tokens: Some(10_000),
+            ..Default::default()
+        });
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        budget.record_on(day(1), "alice", a, 10, 5, Decimal::new(1, 2));
+        budget.record_on(day(1), "alice", b, 100, 50, Decimal::new(2, 2));
+        budget.record_on(day(1), "bob", Uuid::new_v4(), 1, 1, Decimal::ZERO);
+
+        let report = budget.report_on(day(1), "alice");
+        assert_eq!(report.today.tokens(), 165);
+        assert_eq!(report.today.cost, Decimal::new(3, 2));
+        assert_eq!(report.sessions.len(), 2);
+        assert_eq!(report.sessions[0].session_id, b);
+
+        let text = report.render();
+        assert!(text.contains("165 of 10000"));
+        assert!(text.contains("(no cap)"));
+
+        let json = serde_json::to_string(&report).unwrap();
+        let back: BudgetReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.today, report.today);
+    }
+}
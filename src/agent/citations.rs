@@ -0,0 +1,637 @@
+//! Source tracking from tool outputs into cited answers.
+//!
+//! Every tool output that enters the context is preceded by `<source>` tags
+//! naming where it came from: the memory document and line range a search
+//! hit sits in, the file `read_file` returned, the URL `http` or
+//! `web_fetch` fetched, or just the tool for anything else. The tags number
+//! the sources `S1`, `S2`, ... in the order they first appear, and the model
+//! is asked to cite them inline as `[S1]`. Because the numbering lives in
+//! the context messages themselves, a turn resumed after an approval picks
+//! up where it left off.
+//!
+//! Before the answer is sent, [`SourceLedger::render`] renumbers the markers
+//! the answer actually uses, drops markers that name no known source, and
+//! appends a source list in the channel's markdown dialect.
+//!
+//! Skills can demand citations ([`CitationPolicy`]): when a turn used a tool
+//! from such a skill, sentences that look like factual claims but cite
+//! nothing are sent back to the model once, and removed if it still cannot
+//! source them.
+
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+use crate::agent::approvals::call_params;
+use crate::channels::MarkdownDialect;
+use crate::llm::{ChatMessage, Role};
+
+/// What a cited source points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceKind {
+    /// A workspace memory document.
+    Memory,
+    /// A file on disk.
+    File,
+    /// A fetched URL.
+    Url,
+    /// Output of a tool with no better locator.
+    Tool,
+}
+
+impl SourceKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SourceKind::Memory => "memory",
+            SourceKind::File => "file",
+            SourceKind::Url => "url",
+            SourceKind::Tool => "tool",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "memory" => Some(SourceKind::Memory),
+            "file" => Some(SourceKind::File),
+            "url" => Some(SourceKind::Url),
+            "tool" => Some(SourceKind::Tool),
+            _ => None,
+        }
+    }
+}
+
+/// One citable source seen during a turn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+    /// Position in the turn, cited as `[S<id>]`.
+    pub id: usize,
+    pub kind: SourceKind,
+    /// Document path, file path, URL or tool name.
+    pub locator: String,
+    /// First and last line, 1-based, when known.
+    pub lines: Option<(usize, usize)>,
+    /// Original URL of an ingested memory document.
+    pub url: Option<String>,
+    /// Tool whose output carried the source.
+    pub tool: String,
+}
+
+impl Source {
+    /// Human-readable location: `notes/plan.md, lines 4-9`.
+    pub fn label(&self) -> String {
+        let base = match self.kind {
+            SourceKind::Tool => format!("{} output", self.locator),
+            _ => self.locator.clone(),
+        };
+        match self.lines {
+            Some((start, end)) if start == end => format!("{}, line {}", base, start),
+            Some((start, end)) => format!("{}, lines {}-{}", base, start, end),
+            None => base,
+        }
+    }
+
+    /// Link target, for sources that have one.
+    fn link(&self) -> Option<&str> {
+        match self.kind {
+            SourceKind::Url => Some(&self.locator),
+            _ => self.url.as_deref(),
+        }
+    }
+
+    fn same_place(&self, other: &Source) -> bool {
+        self.kind == other.kind && self.locator == other.locator && self.lines == other.lines
+    }
+
+    fn tag(&self) -> String {
+        let mut tag = format!(
+            "<source id=\"S{}\" kind=\"{}\" ref=\"{}\" tool=\"{}\"",
+            self.id,
+            self.kind.as_str(),
+            escape_attr(&self.locator),
+            escape_attr(&self.tool)
+        );
+        if let Some((start, end)) = self.lines {
+            tag.push_str(&format!(" lines=\"{}-{}\"", start, end));
+        }
+        if let Some(ref url) = self.url {
+            tag.push_str(&format!(" url=\"{}\"", escape_attr(url)));
+        }
+        tag.push_str("/>");
+        tag
+    }
+
+    fn parse_tag(line: &str) -> Option<Self> {
+        let body = line.strip_prefix("<source ")?.strip_suffix("/>")?;
+        let attr = |name: &str| -> Option<String> {
+            let start = body.find(&format!("{}=\"", name))? + name.len() + 2;
+            let end = start + body[start..].find('"')?;
+            Some(unescape_attr(&body[start..end]))
+        };
+        let lines = attr("lines").and_then(|l| {
+            let (start, end) = l.split_once('-')?;
+            Some((start.parse().ok()?, end.parse().ok()?))
+        });
+        Some(Self {
+            id: attr("id")?.strip_prefix('S')?.parse().ok()?,
+            kind: SourceKind::parse(&attr("kind")?)?,
+            locator: attr("ref")?,
+            lines,
+            url: attr("url"),
+            tool: attr("tool").unwrap_or_default(),
+        })
+    }
+}
+
+/// The sources seen so far in a turn's context.
+#[derive(Debug, Clone, Default)]
+pub struct SourceLedger {
+    sources: Vec<Source>,
+}
+
+impl SourceLedger {
+    /// Rebuild the ledger from the `<source>` tags in the context's tool results.
+    pub fn from_messages(messages: &[ChatMessage]) -> Self {
+        let mut ledger = Self::default();
+        for message in messages.iter().filter(|m| m.role == Role::Tool) {
+            // Tags come before the wrapped output; anything inside it is
+            // tool-controlled (and escaped), so parsing stops there.
+            for line in message.content.lines() {
+                let Some(source) = Source::parse_tag(line.trim()) else {
+                    break;
+                };
+                if !ledger.sources.iter().any(|s| s.id == source.id) {
+                    ledger.sources.push(source);
+                }
+            }
+        }
+        ledger
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    pub fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    /// Tools whose outputs supplied the sources.
+    pub fn tools(&self) -> HashSet<&str> {
+        self.sources.iter().map(|s| s.tool.as_str()).collect()
+    }
+
+    /// Record the sources in a tool's output and return the tag lines to
+    /// put in front of it. A place already seen keeps its number.
+    pub fn record(&mut self, tool_name: &str, arguments: &Value, output: &str) -> String {
+        let mut tags = String::new();
+        for mut source in sources_of(tool_name, &call_params(arguments), output) {
+            match self.sources.iter().find(|s| s.same_place(&source)) {
+                Some(existing) => source = existing.clone(),
+                None => {
+                    source.id = self.sources.len() + 1;
+                    self.sources.push(source.clone());
+                }
+            }
+            tags.push_str(&source.tag());
+            tags.push('\n');
+        }
+        tags
+    }
+
+    /// Resolve the answer's `[S<n>]` markers: cited sources are numbered
+    /// `[1]`, `[2]`, ... in order of first use and listed at the end; markers
+    /// for unknown sources are removed.
+    pub fn render(&self, text: &str, dialect: MarkdownDialect) -> String {
+        let mut cited: Vec<&Source> = Vec::new();
+        let body = replace_markers(text, |id| {
+            let source = self.sources.iter().find(|s| s.id == id)?;
+            let n = match cited.iter().position(|s| s.id == id) {
+                Some(i) => i + 1,
+                None => {
+                    cited.push(source);
+                    cited.len()
+                }
+            };
+            Some(format!("[{}]", n))
+        });
+        if cited.is_empty() {
+            return body;
+        }
+
+        let heading = match dialect {
+            MarkdownDialect::Markdown => "**Sources**",
+            MarkdownDialect::Slack | MarkdownDialect::Telegram => "*Sources*",
+            MarkdownDialect::Plain => "Sources:",
+        };
+        // Plain line breaks would run together in a markdown paragraph.
+        let bullet = match dialect {
+            MarkdownDialect::Markdown => "- ",
+            _ => "",
+        };
+        let mut out = format!("{}\n\n{}", body.trim_end(), heading);
+        for (i, source) in cited.iter().enumerate() {
+            let label = source.label();
+            let entry = match source.link() {
+                Some(url) if url != label => dialect.link(&label, url),
+                _ => label,
+            };
+            out.push_str(&format!("\n{}[{}] {}", bullet, i + 1, entry));
+        }
+        out
+    }
+
+    /// Sentences that read as factual claims but cite no source.
+    pub fn uncited_claims(text: &str) -> Vec<String> {
+        claim_sentences(text)
+            .into_iter()
+            .filter(|s| !has_marker(s) && looks_factual(s))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Remove the given sentences from the answer, noting how many went.
+    pub fn drop_claims(text: &str, claims: &[String]) -> String {
+        let mut remaining: Vec<&str> = claims.iter().map(String::as_str).collect();
+        let mut kept = Vec::new();
+        for line in text.lines() {
+            let mut out = line.to_string();
+            remaining.retain(|claim| match out.find(claim) {
+                Some(at) => {
+                    out.replace_range(at..at + claim.len(), "");
+                    false
+                }
+                None => true,
+            });
+            // A line emptied by the removal goes too, bullet and all.
+            let rest = out.trim().trim_start_matches(['-', '*', '#', '>']).trim();
+            if rest.is_empty() && !line.trim().is_empty() {
+                continue;
+            }
+            kept.push(out.trim_end().to_string());
+        }
+        while kept.last().is_some_and(|l| l.is_empty()) {
+            kept.pop();
+        }
+        let note = format!(
+            "({} statement(s) without a source were removed: this answer must cite its sources.)",
+            claims.len()
+        );
+        if kept.iter().all(|l| l.is_empty()) {
+            format!("I could not find sources for this answer.\n\n{}", note)
+        } else {
+            format!("{}\n\n{}", kept.join("\n"), note)
+        }
+    }
+}
+
+/// Which skills require cited answers.
+#[derive(Debug, Clone, Default)]
+pub struct CitationPolicy {
+    /// Tools of the skills that demand citations.
+    strict_tools: HashSet<String>,
+}
+
+impl CitationPolicy {
+    pub fn new(strict_tools: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            strict_tools: strict_tools.into_iter().collect(),
+        }
+    }
+
+    /// Instructions added to the system prompt.
+    pub fn prompt_section(&self) -> String {
+        let mut section = String::from(
+            "## Citations\n\n\
+             Tool results are preceded by <source id=\"S1\" .../> tags. When a statement \
+             relies on a tool result, cite it inline with the source id in brackets, e.g. \
+             \"The deadline is March 3 [S2].\" Cite only ids that appear in the context; \
+             do not write a source list yourself.",
+        );
+        if !self.strict_tools.is_empty() {
+            let mut tools: Vec<&str> = self.strict_tools.iter().map(String::as_str).collect();
+            tools.sort_unstable();
+            section.push_str(&format!(
+                " When you have used any of {}, every factual statement must carry a \
+                 citation; leave out anything the sources do not support.",
+                tools.join(", ")
+            ));
+        }
+        section
+    }
+
+    /// Whether an answer drawing on these tools must cite every claim.
+    pub fn is_strict(&self, ledger: &SourceLedger) -> bool {
+        ledger
+            .tools()
+            .iter()
+            .any(|tool| self.strict_tools.contains(*tool))
+    }
+}
+
+/// Sources a tool's output draws on.
+fn sources_of(tool_name: &str, params: &Value, output: &str) -> Vec<Source> {
+    let source = |kind, locator: &str, lines| Source {
+        id: 0,
+        kind,
+        locator: locator.to_string(),
+        lines,
+        url: None,
+        tool: tool_name.to_string(),
+    };
+    let param = |name: &str| params.get(name).and_then(|v| v.as_str());
+
+    match tool_name {
+        "memory_search" => {
+            let output: Value = serde_json::from_str(output).unwrap_or_default();
+            let results = output
+                .get("results")
+                .and_then(|r| r.as_array())
+                .cloned()
+                .unwrap_or_default();
+            results
+                .iter()
+                .filter_map(|r| {
+                    let path = r.get("path")?.as_str()?;
+                    let lines = r.get("lines").and_then(|l| {
+                        let l = l.as_array()?;
+                        Some((l.first()?.as_u64()? as usize, l.get(1)?.as_u64()? as usize))
+                    });
+                    let mut s = source(SourceKind::Memory, path, lines);
+                    s.url = r.get("source").and_then(|u| u.as_str()).map(String::from);
+                    Some(s)
+                })
+                .collect()
+        }
+        "memory_read" => param("path")
+            .map(|p| vec![source(SourceKind::Memory, p, None)])
+            .unwrap_or_default(),
+        "read_file" => param("path")
+            .map(|p| {
+                let offset = params.get("offset").and_then(|v| v.as_u64());
+                let limit = params.get("limit").and_then(|v| v.as_u64());
+                let lines = match (offset, limit) {
+                    (offset, Some(limit)) if limit > 0 => {
+                        let start = offset.unwrap_or(0) as usize + 1;
+                        Some((start, start + limit as usize - 1))
+                    }
+                    _ => None,
+                };
+                vec![source(SourceKind::File, p, lines)]
+            })
+            .unwrap_or_default(),
+        "http" | "web_fetch" => param("url")
+            .map(|u| vec![source(SourceKind::Url, u, None)])
+            .unwrap_or_default(),
+        _ => vec![source(SourceKind::Tool, tool_name, None)],
+    }
+}
+
+/// Replace each `[S<n>]` marker with `f(n)`, or remove it when `f` gives `None`.
+fn replace_markers(text: &str, mut f: impl FnMut(usize) -> Option<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("[S") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let digits = after.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits > 0 && after[digits..].starts_with(']') {
+            let id = after[..digits].parse().unwrap_or(0);
+            match f(id) {
+                Some(marker) => out.push_str(&marker),
+                // Drop the space before a removed marker too.
+                None => {
+                    let trimmed = out.trim_end_matches(' ').len();
+                    out.truncate(trimmed);
+                }
+            }
+            rest = &after[digits + 1..];
+        } else {
+            out.push_str("[S");
+            rest = after;
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn has_marker(sentence: &str) -> bool {
+    let mut found = false;
+    replace_markers(sentence, |_| {
+        found = true;
+        None
+    });
+    found
+}
+
+/// Sentences of the prose parts of an answer; code blocks are skipped.
+fn claim_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut in_code = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let line = line
+            .trim()
+            .trim_start_matches(['-', '*', '#', '>'])
+            .trim_start();
+        let mut start = 0;
+        let bytes = line.as_bytes();
+        for (i, &b) in bytes.iter().enumerate() {
+            let ends = matches!(b, b'.' | b'!' | b'?')
+                && bytes.get(i + 1).is_none_or(|n| n.is_ascii_whitespace());
+            if ends {
+                // Keep a citation that follows the full stop with its sentence.
+                let mut end = i + 1;
+                let tail = &line[end..];
+                let trimmed = tail.trim_start();
+                if trimmed.starts_with("[S") {
+                    end += tail.len() - trimmed.len() + trimmed.find(']').map_or(0, |p| p + 1);
+                }
+                sentences.push(line[start..end].trim());
+                start = end;
+            }
+        }
+        if start < line.len() {
+            sentences.push(line[start..].trim());
+        }
+    }
+    sentences.retain(|s| !s.is_empty());
+    sentences
+}
+
+/// A declarative sentence with a number or a proper noun past its first
+/// word. Questions, short fragments and statements about the assistant
+/// itself are not claims.
+fn looks_factual(sentence: &str) -> bool {
+    if sentence.ends_with('?') || sentence.ends_with(':') {
+        return false;
+    }
+    let words: Vec<&str> = sentence.split_whitespace().collect();
+    if words.len() < 4 {
+        return false;
+    }
+    let first = words[0].to_ascii_lowercase();
+    if matches!(
+        first.as_str(),
+        "i" | "i'm" | "i've" | "i'll" | "let" | "would" | "should" | "please" | "here" | "if"
+    ) {
+        return false;
+    }
+    sentence.chars().any(|c| c.is_ascii_digit())
+        || words[1..]
+            .iter()
+            .any(|w| w.chars().next().is_some_and(|c| c.is_uppercase()) && w.len() > 1)
+}
+
+fn escape_attr(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', " ")
+}
+
+fn unescape_attr(s: &str) -> String {
+    s.replace("&quot;", "\"")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search_output() -> String {
+        serde_json::json!({
+            "query": "deadline",
+            "results": [
+                {"content": "Due March 3", "path": "projects/alpha.md", "lines": [4, 9], "source": null},
+                {"content": "Kickoff", "path": "notes/kickoff.md", "lines": [1, 2],
+                 "source": "https://example.com/kickoff"},
+            ],
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_record_tags_memory_hits_and_reuses_ids() {
+        let mut ledger = SourceLedger::default();
+        let tags = ledger.record("memory_search", &serde_json::json!({}), &search_output());
+        assert!(tags.contains(r#"<source id="S1" kind="memory" ref="projects/alpha.md""#));
+        assert!(tags.contains(r#"lines="4-9""#));
+        assert!(tags.contains(r#"url="https://example.com/kickoff""#));
+
+        let again = ledger.record("memory_search", &serde_json::json!({}), &search_output());
+        assert_eq!(ledger.sources().len(), 2);
+        assert!(again.contains(r#"id="S2""#));
+    }
+
+    #[test]
+    fn test_ledger_survives_the_context() {
+        let mut ledger = SourceLedger::default();
+        let tags = ledger.record(
+            "http",
+            &serde_json::json!(r#"{"url":"https://example.com/a?b=1&c=2"}"#),
+            "{}",
+        );
+        let content = format!(
+            "{}<tool_output name=\"http\" sanitized=\"false\">\n&lt;source id=\"S9\" kind=\"url\" ref=\"x\"/&gt;\n</tool_output>",
+            tags
+        );
+        let messages = vec![
+            ChatMessage::user("hi"),
+            ChatMessage::tool_result("call_1", "http", content),
+        ];
+
+        let rebuilt = SourceLedger::from_messages(&messages);
+        assert_eq!(rebuilt.sources(), ledger.sources());
+        assert_eq!(
+            rebuilt.sources()[0].locator,
+            "https://example.com/a?b=1&c=2"
+        );
+    }
+
+    #[test]
+    fn test_read_file_line_range() {
+        let mut ledger = SourceLedger::default();
+        ledger.record(
+            "read_file",
+            &serde_json::json!({"path": "src/main.rs", "offset": 10, "limit": 5}),
+            "",
+        );
+        assert_eq!(ledger.sources()[0].label(), "src/main.rs, lines 11-15");
+    }
+
+    #[test]
+    fn test_render_renumbers_and_lists_sources() {
+        let mut ledger = SourceLedger::default();
+        ledger.record("memory_search", &serde_json::json!({}), &search_output());
+        ledger.record(
+            "web_fetch",
+            &serde_json::json!({"url": "https://example.com/post"}),
+            "",
+        );
+
+        let text = "Kickoff was in May [S2]. The post agrees [S3][S2]. Made up [S7].";
+        let rendered = ledger.render(text, MarkdownDialect::Markdown);
+        assert!(rendered.starts_with("Kickoff was in May [1]. The post agrees [2][1]. Made up."));
+        assert!(rendered.contains(
+            "**Sources**\n- [1] [notes/kickoff.md, lines 1-2](https://example.com/kickoff)\n- [2] https://example.com/post"
+        ));
+        assert!(!rendered.contains("alpha"));
+
+        let plain = ledger.render("See [S1].", MarkdownDialect::Plain);
+        assert!(plain.ends_with("Sources:\n[1] projects/alpha.md, lines 4-9"));
+
+        assert_eq!(
+            ledger.render("No citations.", MarkdownDialect::Slack),
+            "No citations."
+        );
+    }
+
+    #[test]
+    fn test_uncited_claims() {
+        let text = "I checked your notes. The deadline is March 3 [S1]. \
+                    The budget is 40k dollars.\n\
+                    - Owner is Dana Reyes\n\
+                    ```\nlet x = 42;\n```\n\
+                    Do you want a reminder for that date?";
+        let claims = SourceLedger::uncited_claims(text);
+        assert_eq!(
+            claims,
+            vec!["The budget is 40k dollars.", "Owner is Dana Reyes"]
+        );
+
+        let dropped = SourceLedger::drop_claims(text, &claims);
+        assert!(dropped.contains("The deadline is March 3 [S1]."));
+        assert!(!dropped.contains("budget"));
+        assert!(!dropped.contains("Dana"));
+        assert!(dropped.contains("2 statement(s) without a source were removed"));
+    }
+
+    #[test]
+    fn test_policy_strict_only_for_listed_tools() {
+        assert!(
+            !CitationPolicy::default()
+                .prompt_section()
+                .contains("every factual")
+        );
+        let policy = CitationPolicy::new(vec!["http".to_string()]);
+        assert!(
+            policy
+                .prompt_section()
+                .contains("any of http, every factual")
+        );
+        let mut ledger = SourceLedger::default();
+        ledger.record("time", &serde_json::json!({}), "");
+        assert!(!policy.is_strict(&ledger));
+        ledger.record("http", &serde_json::json!({"url": "https://a.test"}), "");
+        assert!(policy.is_strict(&ledger));
+    }
+}
@@ -146,8 +146,8 @@ mod tests {
     use crate::context::{ActionRecord, JobContext, JobState};
     use crate::error::{DatabaseError, WorkspaceError};
     use crate::history::{
-        ConversationMessage, ConversationSummary, JobEventRecord, LlmCallRecord, SandboxJobRecord,
-        SandboxJobSummary, SettingRow,
+        ActivityStats, ConversationMessage, ConversationSummary, JobEventRecord, LlmCallRecord,
+        SandboxJobRecord, SandboxJobSummary, SettingRow,
     };
     use crate::workspace::{
         MemoryChunk, MemoryConnection, MemoryDocument, MemorySpace, ProfileType, SearchConfig,
@@ -178,6 +178,9 @@ mod tests {
                 async fn run_migrations(&self) -> Result<(), DatabaseError> {
                     Ok(())
                 }
+                async fn ping(&self) -> Result<(), DatabaseError> {
+                    Ok(())
+                }
                 async fn create_conversation(
                     &self,
                     _channel: &str,
@@ -257,6 +260,13 @@ mod tests {
                 ) -> Result<Vec<ConversationMessage>, DatabaseError> {
                     Ok(vec![])
                 }
+                async fn delete_conversation_messages(
+                    &self,
+                    _conversation_id: Uuid,
+                    _message_ids: &[Uuid],
+                ) -> Result<u64, DatabaseError> {
+                    Ok(0)
+                }
                 async fn conversation_belongs_to_user(
                     &self,
                     _conversation_id: Uuid,
@@ -401,6 +411,19 @@ mod tests {
                 ) -> Result<Vec<JobEventRecord>, DatabaseError> {
                     Ok(vec![])
                 }
+                async fn record_egress(
+                    &self,
+                    _record: &crate::history::EgressRecord,
+                ) -> Result<(), DatabaseError> {
+                    Ok(())
+                }
+                async fn list_egress(
+                    &self,
+                    _job_id: Option<Uuid>,
+                    _limit: i64,
+                ) -> Result<Vec<crate::history::EgressRecord>, DatabaseError> {
+                    Ok(vec![])
+                }
                 async fn create_routine(&self, _routine: &Routine) -> Result<(), DatabaseError> {
                     Ok(())
                 }
@@ -423,6 +446,9 @@ mod tests {
                 async fn list_event_routines(&self) -> Result<Vec<Routine>, DatabaseError> {
                     Ok(vec![])
                 }
+                async fn list_file_routines(&self) -> Result<Vec<Routine>, DatabaseError> {
+                    Ok(vec![])
+                }
                 async fn list_due_cron_routines(&self) -> Result<Vec<Routine>, DatabaseError> {
                     Ok(vec![])
                 }
@@ -490,6 +516,13 @@ mod tests {
                 ) -> Result<(), DatabaseError> {
                     Ok(())
                 }
+                async fn activity_since(
+                    &self,
+                    _user_id: Option<&str>,
+                    _since: DateTime<Utc>,
+                ) -> Result<ActivityStats, DatabaseError> {
+                    Ok(ActivityStats::default())
+                }
                 async fn get_setting(
                     &self,
                     _user_id: &str,
@@ -609,6 +642,13 @@ mod tests {
                 ) -> Result<Vec<MemoryDocument>, WorkspaceError> {
                     Ok(vec![])
                 }
+                async fn workspace_usage(
+                    &self,
+                    _user_id: &str,
+                    _agent_id: Option<Uuid>,
+                ) -> Result<Vec<crate::workspace::DocumentUsage>, WorkspaceError> {
+                    Ok(vec![])
+                }
                 async fn delete_chunks(&self, _document_id: Uuid) -> Result<(), WorkspaceError> {
                     Ok(())
                 }
@@ -628,6 +668,12 @@ mod tests {
                 ) -> Result<(), WorkspaceError> {
                     Ok(())
                 }
+                async fn list_chunks(
+                    &self,
+                    _document_id: Uuid,
+                ) -> Result<Vec<MemoryChunk>, WorkspaceError> {
+                    Ok(vec![])
+                }
                 async fn get_chunks_without_embeddings(
                     &self,
                     _user_id: &str,
@@ -928,6 +974,9 @@ mod tests {
             async fn run_migrations(&self) -> Result<(), DatabaseError> {
                 Ok(())
             }
+            async fn ping(&self) -> Result<(), DatabaseError> {
+                Ok(())
+            }
             async fn create_conversation(
                 &self,
                 _channel: &str,
@@ -1007,6 +1056,13 @@ mod tests {
             ) -> Result<Vec<ConversationMessage>, DatabaseError> {
                 Ok(vec![])
             }
+            async fn delete_conversation_messages(
+                &self,
+                _conversation_id: Uuid,
+                _message_ids: &[Uuid],
+            ) -> Result<u64, DatabaseError> {
+                Ok(0)
+            }
             async fn conversation_belongs_to_user(
                 &self,
                 _conversation_id: Uuid,
@@ -1148,6 +1204,19 @@ mod tests {
             ) -> Result<Vec<JobEventRecord>, DatabaseError> {
                 Ok(vec![])
             }
+            async fn record_egress(
+                &self,
+                _record: &crate::history::EgressRecord,
+            ) -> Result<(), DatabaseError> {
+                Ok(())
+            }
+            async fn list_egress(
+                &self,
+                _job_id: Option<Uuid>,
+                _limit: i64,
+            ) -> Result<Vec<crate::history::EgressRecord>, DatabaseError> {
+                Ok(vec![])
+            }
             async fn create_routine(&self, _routine: &Routine) -> Result<(), DatabaseError> {
                 Ok(())
             }
@@ -1167,6 +1236,9 @@ mod tests {
             async fn list_event_routines(&self) -> Result<Vec<Routine>, DatabaseError> {
                 Ok(vec![])
             }
+            async fn list_file_routines(&self) -> Result<Vec<Routine>, DatabaseError> {
+                Ok(vec![])
+            }
             async fn list_due_cron_routines(&self) -> Result<Vec<Routine>, DatabaseError> {
                 Ok(vec![])
             }
@@ -1234,6 +1306,13 @@ mod tests {
             ) -> Result<(), DatabaseError> {
                 Ok(())
             }
+            async fn activity_since(
+                &self,
+                _user_id: Option<&str>,
+                _since: DateTime<Utc>,
+            ) -> Result<ActivityStats, DatabaseError> {
+                Ok(ActivityStats::default())
+            }
             async fn get_setting(
                 &self,
                 _user_id: &str,
@@ -1354,6 +1433,13 @@ mod tests {
             ) -> Result<Vec<MemoryDocument>, WorkspaceError> {
                 Ok(vec![])
             }
+            async fn workspace_usage(
+                &self,
+                _user_id: &str,
+                _agent_id: Option<Uuid>,
+            ) -> Result<Vec<crate::workspace::DocumentUsage>, WorkspaceError> {
+                Ok(vec![])
+            }
             async fn delete_chunks(&self, _document_id: Uuid) -> Result<(), WorkspaceError> {
                 Ok(())
             }
@@ -1373,6 +1459,12 @@ mod tests {
             ) -> Result<(), WorkspaceError> {
                 Ok(())
             }
+            async fn list_chunks(
+                &self,
+                _document_id: Uuid,
+            ) -> Result<Vec<MemoryChunk>, WorkspaceError> {
+                Ok(vec![])
+            }
             async fn get_chunks_without_embeddings(
                 &self,
                 _user_id: &str,
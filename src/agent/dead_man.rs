@@ -0,0 +1,475 @@
+//! Dead-man switch for unattended failures.
+//!
+//! Critical failures are counted until an admin sends a message:
+//!
+//! - a channel failing its health check for longer than
+//!   `DEADMAN_CHANNEL_DOWN_SECS` (one failure per outage),
+//! - the database not answering a ping (one failure per check),
+//! - the LLM provider rejecting our credentials (one failure per turn).
+//!
+//! When the count reaches `DEADMAN_THRESHOLD`, the switch escalates once:
+//! an alert goes to a channel that is still healthy, the escalation is
+//! POSTed to the paging webhook if one is set, and a safe-mode marker is
+//! written so the next start runs with heartbeat, routines and self-repair
+//! off. Any admin message clears the count, and in safe mode removes the
+//! marker so the start after that is normal again.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::channels::{ChannelManager, OutgoingResponse};
+use crate::config::DeadManConfig;
+use crate::db::Database;
+
+/// Failures kept for the escalation report; older ones are only counted.
+const MAX_RECORDED: usize = 20;
+
+/// A failure that nobody is around to notice.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CriticalFailure {
+    ChannelDown { channel: String },
+    DatabaseUnreachable,
+    LlmAuth,
+}
+
+impl std::fmt::Display for CriticalFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ChannelDown { channel } => write!(f, "channel '{}' down", channel),
+            Self::DatabaseUnreachable => f.write_str("database unreachable"),
+            Self::LlmAuth => f.write_str("LLM authentication failed"),
+        }
+    }
+}
+
+/// One recorded failure.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureRecord {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub failure: CriticalFailure,
+    pub detail: String,
+}
+
+/// Why the switch fired.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Escalation {
+    pub at: DateTime<Utc>,
+    /// Failures since the last admin message.
+    pub failure_count: u32,
+    /// The most recent of them.
+    pub failures: Vec<FailureRecord>,
+    pub last_admin_at: Option<DateTime<Utc>>,
+}
+
+impl Escalation {
+    /// Alert text for a channel.
+    pub fn message(&self, safe_mode: bool) -> String {
+        let mut text = format!(
+            "ESCALATION: {} critical failure(s) with no admin response{}.\n",
+            self.failure_count,
+            match self.last_admin_at {
+                Some(at) => format!(" since {}", at.format("%Y-%m-%d %H:%M UTC")),
+                None => String::new(),
+            }
+        );
+        for record in &self.failures {
+            text.push_str(&format!(
+                "- {} {}: {}\n",
+                record.at.format("%H:%M"),
+                record.failure,
+                record.detail
+            ));
+        }
+        if safe_mode {
+            text.push_str(
+                "The next start will be in safe mode (no heartbeat, routines or self-repair). Reply to acknowledge.",
+            );
+        } else {
+            text.push_str("Reply to acknowledge.");
+        }
+        text
+    }
+
+    /// Body POSTed to the paging webhook.
+    pub fn webhook_payload(&self) -> serde_json::Value {
+        serde_json::json!({
+            "event": "ironclaw.escalation",
+            "severity": "critical",
+            "summary": format!(
+                "IronClaw: {} unattended critical failure(s)",
+                self.failure_count
+            ),
+            "at": self.at,
+            "failure_count": self.failure_count,
+            "failures": self.failures,
+            "last_admin_at": self.last_admin_at,
+        })
+    }
+}
+
+/// Written after an escalation; its presence at startup means safe mode.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafeModeMarker {
+    pub since: DateTime<Utc>,
+    pub reason: String,
+}
+
+impl SafeModeMarker {
+    /// The marker left by an earlier escalation, if any.
+    pub fn load(path: &Path) -> Option<Self> {
+        let bytes = std::fs::read(path).ok()?;
+        match serde_json::from_slice(&bytes) {
+            Ok(marker) => Some(marker),
+            Err(e) => {
+                tracing::warn!(
+                    "Ignoring unreadable safe-mode marker {}: {}",
+                    path.display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    fn write(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_vec_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+}
+
+/// Default location of the safe-mode marker.
+pub fn default_safe_mode_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".ironclaw")
+        .join("safe_mode.json")
+}
+
+#[derive(Default)]
+struct SwitchState {
+    failure_count: u32,
+    recent: Vec<FailureRecord>,
+    /// Channels currently failing their health check, and since when.
+    down_since: HashMap<String, DateTime<Utc>>,
+    /// Channels already counted for their current outage.
+    reported_down: Vec<String>,
+    last_admin_at: Option<DateTime<Utc>>,
+    escalated: bool,
+}
+
+/// Counts critical failures and decides when to escalate.
+pub struct DeadManSwitch {
+    config: DeadManConfig,
+    state: Mutex<SwitchState>,
+    /// Safe mode this process started in.
+    safe_mode: Mutex<Option<SafeModeMarker>>,
+}
+
+impl DeadManSwitch {
+    /// A switch that starts in safe mode when an earlier escalation left
+    /// its marker behind.
+    pub fn new(config: DeadManConfig) -> Self {
+        let safe_mode = SafeModeMarker::load(&config.safe_mode_path);
+        Self {
+            config,
+            state: Mutex::new(SwitchState::default()),
+            safe_mode: Mutex::new(safe_mode),
+        }
+    }
+
+    /// The marker this process started with, while it is still in place.
+    pub fn safe_mode(&self) -> Option<SafeModeMarker> {
+        self.safe_mode.lock().unwrap().clone()
+    }
+
+    /// Record a critical failure.
+    pub fn record_failure(&self, failure: CriticalFailure, detail: &str) {
+        tracing::warn!("Critical failure: {} ({})", failure, detail);
+        let mut state = self.state.lock().unwrap();
+        state.failure_count += 1;
+        state.recent.push(FailureRecord {
+            at: Utc::now(),
+            failure,
+            detail: detail.to_string(),
+        });
+        if state.recent.len() > MAX_RECORDED {
+            state.recent.remove(0);
+        }
+    }
+
+    /// Note a message from `user_id`. An admin message clears the failure
+    /// count and, in safe mode, removes the marker. Returns whether this
+    /// message ended safe mode.
+    pub fn record_interaction(&self, user_id: &str) -> bool {
+        if !self.config.admin_users.is_empty()
+            && !self.config.admin_users.iter().any(|u| u == user_id)
+        {
+            return false;
+        }
+        {
+            let mut state = self.state.lock().unwrap();
+            state.failure_count = 0;
+            state.recent.clear();
+            state.last_admin_at = Some(Utc::now());
+            state.escalated = false;
+        }
+
+        let Some(_) = self.safe_mode.lock().unwrap().take() else {
+            return false;
+        };
+        if let Err(e) = std::fs::remove_file(&self.config.safe_mode_path)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::warn!("Failed to remove safe-mode marker: {}", e);
+        }
+        true
+    }
+
+    /// Feed one health check result for `channel`. An outage is counted
+    /// once, when it has lasted `channel_down_after`.
+    pub fn channel_health(&self, channel: &str, healthy: bool, now: DateTime<Utc>) {
+        let outage = {
+            let mut state = self.state.lock().unwrap();
+            if healthy {
+                state.down_since.remove(channel);
+                state.reported_down.retain(|c| c != channel);
+                return;
+            }
+            let since = *state.down_since.entry(channel.to_string()).or_insert(now);
+            let down_for = (now - since).to_std().unwrap_or_default();
+            if down_for < self.config.channel_down_after
+                || state.reported_down.iter().any(|c| c == channel)
+            {
+                return;
+            }
+            state.reported_down.push(channel.to_string());
+            since
+        };
+        self.record_failure(
+            CriticalFailure::ChannelDown {
+                channel: channel.to_string(),
+            },
+            &format!("failing health checks since {}", outage.format("%H:%M UTC")),
+        );
+    }
+
+    /// Channels currently failing their health check.
+    fn down_channels(&self) -> Vec<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .down_since
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// The escalation to send, once per run of unattended failures.
+    pub fn due(&self, now: DateTime<Utc>) -> Option<Escalation> {
+        let mut state = self.state.lock().unwrap();
+        if state.escalated || state.failure_count < self.config.threshold {
+            return None;
+        }
+        state.escalated = true;
+        Some(Escalation {
+            at: now,
+            failure_count: state.failure_count,
+            failures: state.recent.clone(),
+            last_admin_at: state.last_admin_at,
+        })
+    }
+
+    /// Send the alert, page the webhook and arm safe mode for the next start.
+    async fn escalate(&self, escalation: &Escalation, channels: &ChannelManager) {
+        tracing::error!(
+            failures = escalation.failure_count,
+            "Dead-man switch fired: escalating unattended failures"
+        );
+        self.alert(escalation, channels).await;
+
+        if let Some(ref url) = self.config.webhook_url {
+            match crate::outbound::client()
+                .post(url)
+                .json(&escalation.webhook_payload())
+                .send()
+                .await
+            {
+                Ok(response) if response.status().is_success() => {}
+                Ok(response) => tracing::warn!("Escalation webhook returned {}", response.status()),
+                Err(e) => tracing::warn!("Escalation webhook failed: {}", e),
+            }
+        }
+
+        if self.config.safe_mode {
+            let marker = SafeModeMarker {
+                since: escalation.at,
+                reason: format!(
+                    "{} unattended critical failure(s)",
+                    escalation.failure_count
+                ),
+            };
+            if let Err(e) = marker.write(&self.config.safe_mode_path) {
+                tracing::error!("Failed to write safe-mode marker: {}", e);
+            }
+        }
+    }
+
+    /// Deliver the alert on the configured channel, or else on every
+    /// channel that is not down.
+    async fn alert(&self, escalation: &Escalation, channels: &ChannelManager) {
+        let mut response = OutgoingResponse::text(escalation.message(self.config.safe_mode));
+        // Emergencies go out even during focus mode.
+        response.metadata = serde_json::json!({ "severity": "emergency" });
+        let user = &self.config.alert_user;
+        if let Some(ref channel) = self.config.alert_channel {
+            match channels.broadcast(channel, user, response.clone()).await {
+                Ok(()) => return,
+                Err(e) => tracing::warn!("Escalation alert on {} failed: {}", channel, e),
+            }
+        }
+
+        let down = self.down_channels();
+        for channel in channels.channel_names().await {
+            if down.contains(&channel) || self.config.alert_channel.as_ref() == Some(&channel) {
+                continue;
+            }
+            if let Err(e) = channels.broadcast(&channel, user, response.clone()).await {
+                tracing::warn!("Escalation alert on {} failed: {}", channel, e);
+            }
+        }
+    }
+}
+
+/// Check channels and the database every `check_interval` and escalate
+/// when the switch says so.
+pub fn spawn_dead_man_monitor(
+    switch: Arc<DeadManSwitch>,
+    channels: Arc<ChannelManager>,
+    store: Option<Arc<dyn Database>>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(switch.config.check_interval);
+        interval.tick().await; // Skip the immediate first tick
+        loop {
+            interval.tick().await;
+            let now = Utc::now();
+            for (channel, result) in channels.health_check_all().await {
+                switch.channel_health(&channel, result.is_ok(), now);
+            }
+            if let Some(ref store) = store
+                && let Err(e) = store.ping().await
+            {
+                switch.record_failure(CriticalFailure::DatabaseUnreachable, &e.to_string());
+            }
+            if let Some(escalation) = switch.due(now) {
+                switch.escalate(&escalation, &channels).await;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn switch(dir: &Path) -> DeadManSwitch {
+        DeadManSwitch::new(DeadManConfig {
+            enabled: true,
+            safe_mode_path: dir.join("safe_mode.json"),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_escalates_once_at_threshold() {
+        let dir = tempfile::tempdir().unwrap();
+        let switch = switch(dir.path());
+        let now = Utc::now();
+
+        switch.record_failure(CriticalFailure::LlmAuth, "401");
+        switch.record_failure(CriticalFailure::DatabaseUnreachable, "refused");
+        assert!(switch.due(now).is_none());
+
+        switch.record_failure(CriticalFailure::LlmAuth, "401");
+        let escalation = switch.due(now).unwrap();
+        assert_eq!(escalation.failure_count, 3);
+        assert!(escalation.message(true).contains("safe mode"));
+        assert_eq!(
+            escalation.webhook_payload()["failures"][1]["kind"],
+            "database_unreachable"
+        );
+
+        switch.record_failure(CriticalFailure::LlmAuth, "401");
+        assert!(switch.due(now).is_none());
+    }
+
+    #[test]
+    fn test_admin_message_resets_the_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let switch = DeadManSwitch::new(DeadManConfig {
+            admin_users: vec!["owner".to_string()],
+            safe_mode_path: dir.path().join("safe_mode.json"),
+            ..Default::default()
+        });
+        for _ in 0..3 {
+            switch.record_failure(CriticalFailure::LlmAuth, "401");
+        }
+
+        assert!(!switch.record_interaction("stranger"));
+        switch.record_interaction("owner");
+        assert!(switch.due(Utc::now()).is_none());
+    }
+
+    #[test]
+    fn test_channel_outage_counts_once_after_the_grace_period() {
+        let dir = tempfile::tempdir().unwrap();
+        let switch = DeadManSwitch::new(DeadManConfig {
+            threshold: 1,
+            safe_mode_path: dir.path().join("safe_mode.json"),
+            ..Default::default()
+        });
+        let start = Utc::now();
+
+        switch.channel_health("telegram", false, start);
+        switch.channel_health("telegram", false, start + chrono::Duration::minutes(30));
+        assert!(switch.due(start).is_none());
+        assert_eq!(switch.down_channels(), ["telegram"]);
+
+        switch.channel_health("telegram", false, start + chrono::Duration::minutes(61));
+        switch.channel_health("telegram", false, start + chrono::Duration::minutes(62));
+        let escalation = switch.due(start).unwrap();
+        assert_eq!(escalation.failure_count, 1);
+
+        switch.channel_health("telegram", true, start + chrono::Duration::minutes(63));
+        assert!(switch.down_channels().is_empty());
+    }
+
+    #[test]
+    fn test_safe_mode_marker_survives_until_admin_message() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("safe_mode.json");
+        SafeModeMarker {
+            since: Utc::now(),
+            reason: "3 unattended critical failure(s)".to_string(),
+        }
+        .write(&path)
+        .unwrap();
+
+        let switch = switch(dir.path());
+        assert!(switch.safe_mode().is_some());
+
+        assert!(switch.record_interaction("default"));
+        assert!(switch.safe_mode().is_none());
+        assert!(!path.exists());
+        assert!(!switch.record_interaction("default"));
+    }
+}
@@ -0,0 +1,383 @@
+//! Response deduplication for identical questions sent in quick succession.
+//!
+//! Double-sends and the same question arriving over two channels would
+//! otherwise run two full agentic loops. The deduplicator keys each user
+//! message by a hash of `(user_id, normalized content, attachment sources)`,
+//! whatever thread it lands in, and within a configurable window hands back
+//! the in-flight or just-completed response instead of starting another turn.
+//! The agent loop checks before rejecting a message to a busy thread, so a
+//! double-send waits for the first turn's answer.
+//!
+//! Short replies ("yes", "continue") and messages to a thread waiting on
+//! approval or input are never deduplicated: repeating them is how a
+//! conversation moves on. Intentionally repeated commands can bypass the
+//! cache with the `/again` prefix or by setting `"dedup": false` in the
+//! message metadata.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+
+use crate::agent::routine::content_hash;
+use crate::agent::session::ThreadState;
+use crate::media::{Attachment, AttachmentSource};
+
+/// Prefix that forces a fresh turn even when an identical message was just answered.
+pub const DEDUP_OVERRIDE_PREFIX: &str = "/again ";
+
+/// Messages this short (after normalization) are replies, not questions.
+const SHORT_REPLY_MAX_CHARS: usize = 24;
+
+/// State of a deduplicated message.
+enum DedupEntry {
+    /// A turn for this message is currently running.
+    InFlight {
+        started_at: Instant,
+        rx: watch::Receiver<Option<String>>,
+    },
+    /// A turn for this message completed recently.
+    Completed {
+        response: String,
+        completed_at: Instant,
+    },
+}
+
+/// What the caller should do with an incoming message.
+pub enum DedupOutcome {
+    /// No matching turn; run the agentic loop and report back through the ticket.
+    Fresh(DedupTicket),
+    /// An identical message was answered within the window.
+    Completed(String),
+    /// An identical message is being processed right now.
+    InFlight(watch::Receiver<Option<String>>),
+}
+
+/// Handle for a fresh turn registered with the deduplicator.
+///
+/// Pass it to [`ResponseDeduplicator::complete`] with the final response, or
+/// to [`ResponseDeduplicator::abandon`] when the turn errors, is interrupted,
+/// or pauses for approval so the next identical message runs normally.
+pub struct DedupTicket {
+    key: u64,
+    tx: watch::Sender<Option<String>>,
+}
+
+/// Caches turn results per user and content hash for a short window.
+pub struct ResponseDeduplicator {
+    window: Duration,
+    entries: Mutex<HashMap<u64, DedupEntry>>,
+}
+
+impl ResponseDeduplicator {
+    /// Create a deduplicator. A zero window disables deduplication.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether deduplication is active.
+    pub fn is_enabled(&self) -> bool {
+        !self.window.is_zero()
+    }
+
+    /// Look up a message, registering it as in-flight when no match exists.
+    pub fn check(&self, user_id: &str, content: &str, attachments: &[Attachment]) -> DedupOutcome {
+        let key = dedup_key(user_id, content, attachments);
+        let (tx, rx) = watch::channel(None);
+
+        if !self.is_enabled() {
+            return DedupOutcome::Fresh(DedupTicket { key, tx });
+        }
+
+        let mut entries = match self.entries.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let now = Instant::now();
+        let window = self.window;
+        entries.retain(|_, entry| match entry {
+            DedupEntry::InFlight { started_at, .. } => {
+                // In-flight turns can legitimately run long; keep them for a
+                // generous multiple of the window before assuming they leaked.
+                now.duration_since(*started_at) < window * 20
+            }
+            DedupEntry::Completed { completed_at, .. } => {
+                now.duration_since(*completed_at) < window
+            }
+        });
+
+        match entries.get(&key) {
+            Some(DedupEntry::Completed { response, .. }) => {
+                DedupOutcome::Completed(response.clone())
+            }
+            Some(DedupEntry::InFlight { rx, .. }) => DedupOutcome::InFlight(rx.clone()),
+            None => {
+                entries.insert(
+                    key,
+                    DedupEntry::InFlight {
+                        started_at: now,
+                        rx,
+                    },
+                );
+                DedupOutcome::Fresh(DedupTicket { key, tx })
+            }
+        }
+    }
+
+    /// Record the response for a fresh turn and wake any waiters.
+    pub fn complete(&self, ticket: DedupTicket, response: &str) {
+        let _ = ticket.tx.send(Some(response.to_string()));
+        if !self.is_enabled() {
+            return;
+        }
+        let mut entries = match self.entries.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        entries.insert(
+            ticket.key,
+            DedupEntry::Completed {
+                response: response.to_string(),
+                completed_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Forget a fresh turn that did not produce a reusable response.
+    pub fn abandon(&self, ticket: DedupTicket) {
+        // Dropping the sender closes the channel; waiters observe `None`.
+        let DedupTicket { key, tx } = ticket;
+        drop(tx);
+        let mut entries = match self.entries.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if matches!(entries.get(&key), Some(DedupEntry::InFlight { .. })) {
+            entries.remove(&key);
+        }
+    }
+
+    /// Number of tracked entries (in-flight and completed).
+    pub fn len(&self) -> usize {
+        self.entries.lock().map(|e| e.len()).unwrap_or(0)
+    }
+
+    /// Whether no entries are tracked.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Wait for an in-flight turn to finish.
+///
+/// Returns `None` if the original turn was abandoned (error, interrupt, or
+/// approval pause), in which case the caller should run its own turn.
+pub async fn wait_for_in_flight(mut rx: watch::Receiver<Option<String>>) -> Option<String> {
+    loop {
+        if let Some(response) = rx.borrow().clone() {
+            return Some(response);
+        }
+        if rx.changed().await.is_err() {
+            return rx.borrow().clone();
+        }
+    }
+}
+
+/// Strip a dedup override from a message.
+///
+/// Returns the content to process and whether deduplication should be skipped.
+pub fn strip_override<'a>(content: &'a str, metadata: &serde_json::Value) -> (&'a str, bool) {
+    let trimmed = content.trim_start();
+    if let Some(head) = trimmed.get(..DEDUP_OVERRIDE_PREFIX.len())
+        && head.eq_ignore_ascii_case(DEDUP_OVERRIDE_PREFIX)
+    {
+        return (trimmed[DEDUP_OVERRIDE_PREFIX.len()..].trim_start(), true);
+    }
+    let bypass = metadata.get("dedup").and_then(|v| v.as_bool()) == Some(false);
+    (content, bypass)
+}
+
+/// Whether a message in a thread in `state` must run its own turn: short
+/// replies, and anything sent while the thread waits on the user.
+pub fn is_exempt(content: &str, state: ThreadState) -> bool {
+    matches!(
+        state,
+        ThreadState::AwaitingApproval | ThreadState::AwaitingInput
+    ) || normalize(content).chars().count() <= SHORT_REPLY_MAX_CHARS
+}
+
+fn normalize(content: &str) -> String {
+    content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Hash a user's message after whitespace and case normalization. The
+/// attachments' sources are part of the key, so the same text with a
+/// different file is a different question.
+fn dedup_key(user_id: &str, content: &str, attachments: &[Attachment]) -> u64 {
+    let mut key = format!("{user_id}\u{0}{}", normalize(content));
+    for attachment in attachments {
+        let (AttachmentSource::Url(source) | AttachmentSource::Inline(source)) = &attachment.source;
+        key.push('\u{0}');
+        key.push_str(source);
+    }
+    content_hash(&key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fresh_then_completed() {
+        let dedup = ResponseDeduplicator::new(Duration::from_secs(30));
+        let ticket = match dedup.check("alice", "What is the weather?", &[]) {
+            DedupOutcome::Fresh(t) => t,
+            _ => panic!("expected fresh"),
+        };
+        dedup.complete(ticket, "Sunny");
+
+        match dedup.check("alice", "  what is   the weather? ", &[]) {
+            DedupOutcome::Completed(r) => assert_eq!(r, "Sunny"),
+            _ => panic!("expected completed"),
+        }
+    }
+
+    #[test]
+    fn test_different_users_do_not_collide() {
+        let dedup = ResponseDeduplicator::new(Duration::from_secs(30));
+        let ticket = match dedup.check("alice", "hello", &[]) {
+            DedupOutcome::Fresh(t) => t,
+            _ => panic!("expected fresh"),
+        };
+        dedup.complete(ticket, "hi alice");
+        assert!(matches!(
+            dedup.check("bob", "hello", &[]),
+            DedupOutcome::Fresh(_)
+        ));
+    }
+
+    #[test]
+    fn test_attachments_are_part_of_the_key() {
+        let dedup = ResponseDeduplicator::new(Duration::from_secs(30));
+        let file = |url: &str| Attachment {
+            name: None,
+            mime_type: None,
+            source: AttachmentSource::Url(url.to_string()),
+        };
+        let ticket = match dedup.check("alice", "Summarize this file", &[file("https://a/1")]) {
+            DedupOutcome::Fresh(t) => t,
+            _ => panic!("expected fresh"),
+        };
+        dedup.complete(ticket, "A summary");
+        assert!(matches!(
+            dedup.check("alice", "Summarize this file", &[file("https://a/1")]),
+            DedupOutcome::Completed(_)
+        ));
+        assert!(matches!(
+            dedup.check("alice", "Summarize this file", &[file("https://a/2")]),
+            DedupOutcome::Fresh(_)
+        ));
+    }
+
+    #[test]
+    fn test_short_replies_and_waiting_threads_are_exempt() {
+        assert!(is_exempt("yes", ThreadState::Idle));
+        assert!(is_exempt("  Continue  ", ThreadState::Idle));
+        let question = "What changed in the deploy script last week?";
+        assert!(!is_exempt(question, ThreadState::Idle));
+        assert!(is_exempt(question, ThreadState::AwaitingApproval));
+        assert!(is_exempt(question, ThreadState::AwaitingInput));
+    }
+
+    #[test]
+    fn test_in_flight_then_abandon() {
+        let dedup = ResponseDeduplicator::new(Duration::from_secs(30));
+        let ticket = match dedup.check("alice", "hello", &[]) {
+            DedupOutcome::Fresh(t) => t,
+            _ => panic!("expected fresh"),
+        };
+        assert!(matches!(
+            dedup.check("alice", "hello", &[]),
+            DedupOutcome::InFlight(_)
+        ));
+        dedup.abandon(ticket);
+        assert!(dedup.is_empty());
+        assert!(matches!(
+            dedup.check("alice", "hello", &[]),
+            DedupOutcome::Fresh(_)
+        ));
+    }
+
+    #[test]
+    fn test_disabled_window_always_fresh() {
+        let dedup = ResponseDeduplicator::new(Duration::ZERO);
+        let ticket = match dedup.check("alice", "hello", &[]) {
+            DedupOutcome::Fresh(t) => t,
+            _ => panic!("expected fresh"),
+        };
+        dedup.complete(ticket, "hi");
+        assert!(matches!(
+            dedup.check("alice", "hello", &[]),
+            DedupOutcome::Fresh(_)
+        ));
+        assert!(dedup.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_in_flight_receives_response() {
+        let dedup = std::sync::Arc::new(ResponseDeduplicator::new(Duration::from_secs(30)));
+        let ticket = match dedup.check("alice", "hello", &[]) {
+            DedupOutcome::Fresh(t) => t,
+            _ => panic!("expected fresh"),
+        };
+        let rx = match dedup.check("alice", "hello", &[]) {
+            DedupOutcome::InFlight(rx) => rx,
+            _ => panic!("expected in-flight"),
+        };
+        let d = dedup.clone();
+        tokio::spawn(async move { d.complete(ticket, "hi") });
+        assert_eq!(wait_for_in_flight(rx).await.as_deref(), Some("hi"));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_abandoned_returns_none() {
+        let dedup = ResponseDeduplicator::new(Duration::from_secs(30));
+        let ticket = match dedup.check("alice", "hello", &[]) {
+            DedupOutcome::Fresh(t) => t,
+            _ => panic!("expected fresh"),
+        };
+        let rx = match dedup.check("alice", "hello", &[]) {
+            DedupOutcome::InFlight(rx) => rx,
+            _ => panic!("expected in-flight"),
+        };
+        dedup.abandon(ticket);
+        assert_eq!(wait_for_in_flight(rx).await, None);
+    }
+
+    #[test]
+    fn test_strip_override_prefix() {
+        let (content, bypass) = strip_override("/again run the report", &serde_json::Value::Null);
+        assert_eq!(content, "run the report");
+        assert!(bypass);
+    }
+
+    #[test]
+    fn test_strip_override_metadata() {
+        let meta = serde_json::json!({"dedup": false});
+        let (content, bypass) = strip_override("run the report", &meta);
+        assert_eq!(content, "run the report");
+        assert!(bypass);
+
+        let (_, bypass) = strip_override("run the report", &serde_json::Value::Null);
+        assert!(!bypass);
+    }
+}
@@ -0,0 +1,730 @@
+//! Sub-agent delegation.
+//!
+//! The `delegate` tool hands a self-contained subtask to a [`SubAgent`]: a
+//! short agent loop with its own session, only the tools the parent named,
+//! a token budget and, by default, the cheap model. The parent turn waits
+//! for it and gets a [`DelegateResult`] back as the tool output, so a
+//! planner model can farm out lookups and research without a sandbox job.
+//!
+//! ```text
+//!  parent turn ──delegate{task, tools}──▶ SubAgent ──▶ LLM ⇄ scoped tools
+//!       ▲                                    │
+//!       └────────── DelegateResult ◀─────────┤
+//!                                            └──▶ progress ──▶ StatusUpdate::Status
+//! ```
+//!
+//! A sub-agent sees nothing of the parent conversation, can't delegate
+//! further or ask the user anything, and is refused calls that would need
+//! approval. Its spend counts toward the user's budget caps, including
+//! the parent session's.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use std::time::Instant;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::agent::budget::BudgetManager;
+use crate::agent::model_tier::ModelTier;
+use crate::config::DelegateConfig;
+use crate::context::JobContext;
+use crate::llm::{ChatMessage, LlmProvider, Reasoning, ReasoningContext, RespondResult, ToolCall};
+use crate::safety::SafetyLayer;
+use crate::tools::{ProgressSender, ToolError, ToolRegistry};
+
+/// Tools a sub-agent never gets: delegation doesn't nest, and nobody is
+/// there to answer a question.
+pub const FORBIDDEN_TOOLS: &[&str] = &["delegate", "ask_user"];
+
+const BRIEF: &str = "You are a sub-agent working on one task handed to you by another agent. \
+Use your tools as needed, then reply with a concise final answer: the result, the facts it rests \
+on, and anything you could not finish. Your reply goes back to that agent, not to a person, so \
+don't ask questions.";
+
+/// What a sub-agent is asked to do.
+#[derive(Debug, Clone)]
+pub struct DelegateRequest {
+    pub task: String,
+    /// Background the sub-agent needs; it sees nothing else of the parent
+    /// conversation.
+    pub context: Option<String>,
+    /// Tools it may use; the configured default set when empty.
+    pub tools: Vec<String>,
+    /// Token budget, capped at the configured maximum.
+    pub max_tokens: Option<u64>,
+    /// Model to run on; the configured tier when unset.
+    pub tier: Option<ModelTier>,
+}
+
+/// How a delegation ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DelegateStatus {
+    /// The sub-agent gave a final answer.
+    Completed,
+    /// Its token budget, or one of the user's budget caps, ran out first.
+    BudgetExhausted,
+    /// It was still calling tools at the iteration limit.
+    IterationLimit,
+    /// An LLM call failed.
+    Failed,
+}
+
+impl DelegateStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DelegateStatus::Completed => "completed",
+            DelegateStatus::BudgetExhausted => "budget exhausted",
+            DelegateStatus::IterationLimit => "iteration limit reached",
+            DelegateStatus::Failed => "failed",
+        }
+    }
+}
+
+/// One tool call a sub-agent made.
+#[derive(Debug, Clone, Serialize)]
+pub struct DelegatedCall {
+    pub tool: String,
+    pub ok: bool,
+    pub elapsed_ms: u64,
+}
+
+/// Outcome handed back to the parent turn.
+#[derive(Debug, Clone, Serialize)]
+pub struct DelegateResult {
+    pub status: DelegateStatus,
+    /// The final answer; otherwise the last text it wrote and why it stopped.
+    pub summary: String,
+    pub session_id: Uuid,
+    pub model: String,
+    pub tools: Vec<String>,
+    pub tool_calls: Vec<DelegatedCall>,
+    pub tokens_used: u64,
+    pub token_budget: u64,
+    pub iterations: usize,
+}
+
+/// Runs delegated subtasks.
+///
+/// Tools are looked up at run time, so ones registered later (WASM, MCP)
+/// can be delegated too. The registry is held weakly because it owns the
+/// `delegate` tool that owns this.
+pub struct SubAgent {
+    llm: Arc<dyn LlmProvider>,
+    cheap_llm: Option<Arc<dyn LlmProvider>>,
+    safety: Arc<SafetyLayer>,
+    registry: Weak<ToolRegistry>,
+    budget: Option<Arc<BudgetManager>>,
+    config: DelegateConfig,
+}
+
+impl SubAgent {
+    pub fn new(
+        llm: Arc<dyn LlmProvider>,
+        safety: Arc<SafetyLayer>,
+        registry: Weak<ToolRegistry>,
+        config: DelegateConfig,
+    ) -> Self {
+        Self {
+            llm,
+            cheap_llm: None,
+            safety,
+            registry,
+            budget: None,
+            config,
+        }
+    }
+
+    /// Model for [`ModelTier::Cheap`]; the primary one serves it otherwise.
+    pub fn with_cheap_llm(mut self, llm: Arc<dyn LlmProvider>) -> Self {
+        self.cheap_llm = Some(llm);
+        self
+    }
+
+    /// Count spend against the user's budget caps.
+    pub fn with_budget(mut self, budget: Arc<BudgetManager>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn config(&self) -> &DelegateConfig {
+        &self.config
+    }
+
+    fn provider(&self, tier: ModelTier) -> &Arc<dyn LlmProvider> {
+        match tier {
+            ModelTier::Cheap => self.cheap_llm.as_ref().unwrap_or(&self.llm),
+            ModelTier::Primary => &self.llm,
+        }
+    }
+
+    fn registry(&self) -> Result<Arc<ToolRegistry>, ToolError> {
+        self.registry
+            .upgrade()
+            .ok_or_else(|| ToolError::ExecutionFailed("tool registry is gone".to_string()))
+    }
+
+    /// The tools a request gets: the named ones, or the configured defaults
+    /// that are registered. Forbidden and unknown names are refused.
+    pub async fn resolve_tools(&self, requested: &[String]) -> Result<Vec<String>, ToolError> {
+        let registry = self.registry()?;
+        let mut tools = Vec::new();
+        if requested.is_empty() {
+            for name in &self.config.default_tools {
+                if !FORBIDDEN_TOOLS.contains(&name.as_str()) && registry.has(name).await {
+                    tools.push(name.clone());
+                }
+            }
+            return Ok(tools);
+        }
+        for name in requested {
+            let name = name.trim();
+            if FORBIDDEN_TOOLS.contains(&name) {
+                return Err(ToolError::InvalidParameters(format!(
+                    "a sub-agent can't use '{}'",
+                    name
+                )));
+            }
+            if !registry.has(name).await {
+                return Err(ToolError::InvalidParameters(format!(
+                    "unknown tool '{}'",
+                    name
+                )));
+            }
+            if !tools.iter().any(|t| t == name) {
+                tools.push(name.to_string());
+            }
+        }
+        Ok(tools)
+    }
+
+    /// Run `request` to completion, reporting each step on `progress` as
+    /// `{"status": ...}`. Running out of budget or iterations, or an LLM
+    /// failure, still returns a result with whatever the sub-agent had.
+    pub async fn run(
+        &self,
+        request: DelegateRequest,
+        ctx: &JobContext,
+        progress: &ProgressSender,
+    ) -> Result<DelegateResult, ToolError> {
+        let tools = self.resolve_tools(&request.tools).await?;
+        let registry = self.registry()?;
+        let names: Vec<&str> = tools.iter().map(String::as_str).collect();
+        let tool_defs = registry.tool_definitions_for(&names).await;
+        drop(registry);
+
+        let llm = Arc::clone(self.provider(request.tier.unwrap_or(self.config.tier)));
+        let reasoning = Reasoning::new(Arc::clone(&llm), Arc::clone(&self.safety))
+            .with_system_prompt(BRIEF.to_string());
+        let token_budget = request
+            .max_tokens
+            .map_or(self.config.max_tokens, |n| n.min(self.config.max_tokens))
+            .max(1);
+
+        // Its own session: tools see a fresh conversation, and the provider
+        // doesn't chain onto the parent's responses. Spend still counts
+        // against the parent session's budget cap.
+        let session_id = Uuid::new_v4();
+        let budget_session = ctx.session_id.unwrap_or(session_id);
+        let mut sub_ctx = ctx.clone();
+        sub_ctx.conversation_id = Some(session_id);
+        let metadata = HashMap::from([("thread_id".to_string(), session_id.to_string())]);
+
+        let mut prompt = request.task.clone();
+        if let Some(context) = request.context.as_deref().filter(|c| !c.trim().is_empty()) {
+            prompt = format!("{}\n\n## Context\n{}", prompt, context);
+        }
+        let mut messages = vec![ChatMessage::user(prompt)];
+
+        let mut result = DelegateResult {
+            status: DelegateStatus::Completed,
+            summary: String::new(),
+            session_id,
+            model: llm.model_name().to_string(),
+            tools,
+            tool_calls: Vec::new(),
+            tokens_used: 0,
+            token_budget,
+            iterations: 0,
+        };
+        report(
+            progress,
+            format!(
+                "Sub-agent started on {} with {}",
+                result.model,
+                if result.tools.is_empty() {
+                    "no tools".to_string()
+                } else {
+                    result.tools.join(", ")
+                }
+            ),
+        );
+
+        let mut last_text = String::new();
+        let stopped = loop {
+            if result.iterations >= self.config.max_iterations {
+                break Some((
+                    DelegateStatus::IterationLimit,
+                    format!(
+                        "Stopped after {} LLM calls without a final answer.",
+                        result.iterations
+                    ),
+                ));
+            }
+            if result.tokens_used >= token_budget {
+                break Some((
+                    DelegateStatus::BudgetExhausted,
+                    format!(
+                        "Stopped after using {} of {} tokens without a final answer.",
+                        result.tokens_used, token_budget
+                    ),
+                ));
+            }
+            if let Some(ref budget) = self.budget
+                && let Err(exceeded) = budget.check(&ctx.user_id, budget_session)
+            {
+                break Some((DelegateStatus::BudgetExhausted, exceeded.message()));
+            }
+
+            result.iterations += 1;
+            let context = ReasoningContext::new()
+                .with_messages(messages.clone())
+                .with_tools(tool_defs.clone())
+                .with_metadata(metadata.clone());
+            let output = match reasoning.respond_with_tools(&context).await {
+                Ok(output) => output,
+                Err(e) => break Some((DelegateStatus::Failed, format!("LLM call failed: {}", e))),
+            };
+
+            result.tokens_used += u64::from(output.usage.total());
+            if let Some(ref budget) = self.budget {
+                let (input, output_tokens) =
+                    (output.usage.input_tokens, output.usage.output_tokens);
+                let cost = llm.calculate_cost(input, output_tokens);
+                budget.record(&ctx.user_id, budget_session, input, output_tokens, cost);
+            }
+
+            match output.result {
+                RespondResult::Text(text) => {
+                    result.summary = text;
+                    break None;
+                }
+                RespondResult::ToolCalls {
+                    tool_calls,
+                    content,
+                } => {
+                    if let Some(text) = content.as_deref().filter(|t| !t.trim().is_empty()) {
+                        last_text = text.to_string();
+                    }
+                    messages.push(ChatMessage::assistant_with_tool_calls(
+                        content,
+                        tool_calls.clone(),
+                    ));
+                    for call in &tool_calls {
+                        report(progress, format!("Sub-agent calling {}", call.name));
+                        let start = Instant::now();
+                        let outcome = self.call_tool(call, &result.tools, &sub_ctx).await;
+                        result.tool_calls.push(DelegatedCall {
+                            tool: call.name.clone(),
+                            ok: outcome.is_ok(),
+                            elapsed_ms: start.elapsed().as_millis() as u64,
+                        });
+                        let content = match outcome {
+                            Ok(output) => {
+                                let sanitized = self.safety.sanitize_tool_output(
+                                    &ctx.user_id,
+                                    &call.name,
+                                    &output,
+                                );
+                                self.safety.wrap_for_llm(
+                                    &call.name,
+                                    &sanitized.content,
+                                    sanitized.was_modified,
+                                )
+                            }
+                            Err(e) => format!("Error: {}", e),
+                        };
+                        messages.push(ChatMessage::tool_result(&call.id, &call.name, content));
+                    }
+                }
+            }
+        };
+
+        if let Some((status, reason)) = stopped {
+            result.status = status;
+            result.summary = if last_text.is_empty() {
+                reason
+            } else {
+                format!("{}\n\n({})", last_text, reason)
+            };
+        }
+        report(
+            progress,
+            format!(
+                "Sub-agent {}: {} tool call(s), {} tokens",
+                result.status.as_str(),
+                result.tool_calls.len(),
+                result.tokens_used
+            ),
+        );
+        Ok(result)
+    }
+
+    /// Run one of the sub-agent's tool calls through the same checks as a
+    /// direct call, minus approval, which it can't get.
+    async fn call_tool(
+        &self,
+        call: &ToolCall,
+        allowed: &[String],
+        ctx: &JobContext,
+    ) -> Result<String, String> {
+        if !allowed.contains(&call.name) {
+            return Err(format!(
+                "tool '{}' is not available to this sub-agent",
+                call.name
+            ));
+        }
+        let tool = self
+            .registry()
+            .map_err(|e| e.to_string())?
+            .get(&call.name)
+            .await
+            .ok_or_else(|| format!("tool '{}' not found", call.name))?;
+
+        let validation = self
+            .safety
+            .validator()
+            .validate_tool_params(&call.arguments);
+        if !validation.is_valid {
+            let details = validation
+                .errors
+                .iter()
+                .map(|e| format!("{}: {}", e.field, e.message))
+                .collect::<Vec<_>>()
+                .join("; ");
+            return Err(format!("Invalid tool parameters: {}", details));
+        }
+        if tool.requires_approval_for(&call.arguments)
+            || tool.requires_explicit_approval(&call.arguments)
+        {
+            return Err(format!(
+                "'{}' needs the user's approval for these parameters, which a sub-agent can't ask for",
+                call.name
+            ));
+        }
+
+        let timeout = tool.execution_timeout();
+        let output = tokio::time::timeout(timeout, tool.execute(call.arguments.clone(), ctx))
+            .await
+            .map_err(|_| format!("timed out after {:?}", timeout))?
+            .map_err(|e| e.to_string())?;
+        Ok(match output.result {
+            serde_json::Value::String(text) => text,
+            other => other.to_string(),
+        })
+    }
+}
+
+/// Send a progress line; nobody may be listening.
+fn report(progress: &ProgressSender, status: String) {
+    let _ = progress.send(serde_json::json!({ "status": status }));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use rust_decimal::Decimal;
+
+    use super::*;
+    use crate::config::SafetyConfig;
+    use crate::error::LlmError;
+    use crate::llm::{
+        CompletionRequest, CompletionResponse, FinishReason, ToolCompletionRequest,
+        ToolCompletionResponse,
+    };
+    use crate::tools::{Tool, ToolOutput};
+
+    /// Plays back scripted tool-completion replies, recording the tool
+    /// names it was offered.
+    struct ScriptedLlm {
+        name: &'static str,
+        replies: Mutex<Vec<ToolCompletionResponse>>,
+        offered: Mutex<Vec<Vec<String>>>,
+    }
+
+    impl ScriptedLlm {
+        fn new(name: &'static str, mut replies: Vec<ToolCompletionResponse>) -> Arc<Self> {
+            replies.reverse();
+            Arc::new(Self {
+                name,
+                replies: Mutex::new(replies),
+                offered: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    fn reply(content: &str, tool_calls: Vec<ToolCall>) -> ToolCompletionResponse {
+        ToolCompletionResponse {
+            content: Some(content.to_string()),
+            tool_calls,
+            input_tokens: 100,
+            output_tokens: 20,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            finish_reason: FinishReason::Stop,
+            response_id: None,
+        }
+    }
+
+    fn call(name: &str) -> ToolCall {
+        ToolCall {
+            id: format!("call_{}", name),
+            name: name.to_string(),
+            arguments: serde_json::json!({"text": "hi"}),
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for ScriptedLlm {
+        fn model_name(&self) -> &str {
+            self.name
+        }
+
+        fn cost_per_token(&self) -> (Decimal, Decimal) {
+            (Decimal::ZERO, Decimal::ZERO)
+        }
+
+        async fn complete(
+            &self,
+            _request: CompletionRequest,
+        ) -> Result<CompletionResponse, LlmError> {
+            unimplemented!()
+        }
+
+        async fn complete_with_tools(
+            &self,
+            request: ToolCompletionRequest,
+        ) -> Result<ToolCompletionResponse, LlmError> {
+            self.offered
+                .lock()
+                .unwrap()
+                .push(request.tools.iter().map(|t| t.name.clone()).collect());
+            self.replies
+                .lock()
+                .unwrap()
+                .pop()
+                .ok_or_else(|| LlmError::RequestFailed {
+                    provider: self.name.to_string(),
+                    reason: "script ran out".to_string(),
+                })
+        }
+    }
+
+    struct UpperTool;
+
+    #[async_trait]
+    impl Tool for UpperTool {
+        fn name(&self) -> &str {
+            "upper"
+        }
+        fn description(&self) -> &str {
+            "Uppercases text"
+        }
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+        async fn execute(
+            &self,
+            params: serde_json::Value,
+            _ctx: &JobContext,
+        ) -> Result<ToolOutput, ToolError> {
+            let text = params["text"].as_str().unwrap_or_default().to_uppercase();
+            Ok(ToolOutput::text(text, Duration::ZERO))
+        }
+    }
+
+    struct RiskyTool;
+
+    #[async_trait]
+    impl Tool for RiskyTool {
+        fn name(&self) -> &str {
+            "risky"
+        }
+        fn description(&self) -> &str {
+            "Always asks first"
+        }
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+        async fn execute(
+            &self,
+            _params: serde_json::Value,
+            _ctx: &JobContext,
+        ) -> Result<ToolOutput, ToolError> {
+            panic!("a sub-agent ran a tool that needs approval");
+        }
+        fn requires_approval(&self) -> bool {
+            true
+        }
+    }
+
+    fn sub_agent(
+        registry: &Arc<ToolRegistry>,
+        primary: Arc<ScriptedLlm>,
+        cheap: Option<Arc<ScriptedLlm>>,
+        config: DelegateConfig,
+    ) -> SubAgent {
+        registry.register_sync(Arc::new(UpperTool));
+        registry.register_sync(Arc::new(RiskyTool));
+        let safety = Arc::new(SafetyLayer::new(&SafetyConfig {
+            max_output_length: 100_000,
+            injection_check_enabled: true,
+            preserve_redacted: false,
+            preserve_redacted_days: 7,
+            provenance: Vec::new(),
+        }));
+        let mut agent = SubAgent::new(primary, safety, Arc::downgrade(registry), config);
+        if let Some(cheap) = cheap {
+            agent = agent.with_cheap_llm(cheap);
+        }
+        agent
+    }
+
+    fn request(tools: &[&str]) -> DelegateRequest {
+        DelegateRequest {
+            task: "shout hi".to_string(),
+            context: None,
+            tools: tools.iter().map(|t| t.to_string()).collect(),
+            max_tokens: None,
+            tier: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_uses_scoped_tools_and_reports_progress() {
+        let registry = Arc::new(ToolRegistry::new());
+        let primary = ScriptedLlm::new("primary", Vec::new());
+        let cheap = ScriptedLlm::new(
+            "cheap",
+            vec![
+                reply("", vec![call("upper"), call("risky")]),
+                reply("HI", Vec::new()),
+            ],
+        );
+        let agent = sub_agent(
+            &registry,
+            primary.clone(),
+            Some(cheap.clone()),
+            DelegateConfig::default(),
+        );
+
+        let (progress, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let ctx = JobContext::new("test", "test");
+        let result = agent
+            .run(request(&["upper", "risky"]), &ctx, &progress)
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, DelegateStatus::Completed);
+        assert_eq!(result.summary, "HI");
+        assert_eq!(result.model, "cheap");
+        assert_eq!(result.iterations, 2);
+        assert_eq!(result.tokens_used, 240);
+        assert_eq!(result.tool_calls.len(), 2);
+        assert!(result.tool_calls[0].ok);
+        // Approval can't be asked for, so the call is refused, not run.
+        assert!(!result.tool_calls[1].ok);
+        assert!(primary.offered.lock().unwrap().is_empty());
+        assert_eq!(cheap.offered.lock().unwrap()[0], vec!["upper", "risky"]);
+
+        let mut lines = Vec::new();
+        while let Ok(value) = rx.try_recv() {
+            lines.push(value["status"].as_str().unwrap().to_string());
+        }
+        assert!(lines.iter().any(|l| l == "Sub-agent calling upper"));
+        assert!(lines.last().unwrap().starts_with("Sub-agent completed"));
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_at_token_budget() {
+        let registry = Arc::new(ToolRegistry::new());
+        let primary = ScriptedLlm::new(
+            "primary",
+            vec![
+                reply("looking", vec![call("upper")]),
+                reply("still looking", vec![call("upper")]),
+            ],
+        );
+        let agent = sub_agent(&registry, primary, None, DelegateConfig::default());
+
+        let (progress, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let ctx = JobContext::new("test", "test");
+        let mut req = request(&["upper"]);
+        req.tier = Some(ModelTier::Cheap);
+        req.max_tokens = Some(100);
+        let result = agent.run(req, &ctx, &progress).await.unwrap();
+
+        assert_eq!(result.status, DelegateStatus::BudgetExhausted);
+        assert_eq!(result.model, "primary");
+        assert_eq!(result.iterations, 1);
+        assert!(result.summary.starts_with("looking"));
+    }
+
+    #[tokio::test]
+    async fn test_run_counts_toward_the_parent_session_budget() {
+        let registry = Arc::new(ToolRegistry::new());
+        let primary = ScriptedLlm::new(
+            "primary",
+            vec![
+                reply("looking", vec![call("upper")]),
+                reply("still looking", vec![call("upper")]),
+            ],
+        );
+        let budget = Arc::new(BudgetManager::new(crate::config::BudgetConfig {
+            session_tokens: Some(200),
+            ..Default::default()
+        }));
+        let agent = sub_agent(&registry, primary, None, DelegateConfig::default())
+            .with_budget(Arc::clone(&budget));
+
+        let parent = Uuid::new_v4();
+        budget.record("default", parent, 100, 50, Decimal::ZERO);
+        let (progress, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut ctx = JobContext::new("test", "test");
+        ctx.session_id = Some(parent);
+        let result = agent
+            .run(request(&["upper"]), &ctx, &progress)
+            .await
+            .unwrap();
+
+        // The parent's 150 tokens plus the first call's 120 exceed the cap.
+        assert_eq!(result.status, DelegateStatus::BudgetExhausted);
+        assert_eq!(result.iterations, 1);
+        assert_ne!(result.session_id, parent);
+        assert!(budget.check("default", parent).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_tools_refuses_nesting_and_unknown_tools() {
+        let registry = Arc::new(ToolRegistry::new());
+        let primary = ScriptedLlm::new("primary", Vec::new());
+        let config = DelegateConfig {
+            default_tools: vec!["upper".to_string(), "missing".to_string()],
+            ..DelegateConfig::default()
+        };
+        let agent = sub_agent(&registry, primary, None, config);
+
+        assert_eq!(agent.resolve_tools(&[]).await.unwrap(), vec!["upper"]);
+        assert!(
+            agent
+                .resolve_tools(&["delegate".to_string()])
+                .await
+                .is_err()
+        );
+        assert!(agent.resolve_tools(&["nope".to_string()]).await.is_err());
+    }
+}
@@ -0,0 +1,251 @@
+//! Time-boxed focus mode.
+//!
+//! `/focus 2h` pauses routines and self-repair and holds background
+//! notifications (heartbeat alerts, routine results, repair reports) until
+//! the focus window ends. Anything marked as an emergency still goes out
+//! immediately. When focus ends, by `/focus off` or by running out, the
+//! held items are delivered as one digest.
+//!
+//! A notification is an emergency when its metadata has
+//! `"severity": "emergency"`; heartbeat and routine prompts ask the LLM to
+//! start such replies with [`EMERGENCY_PREFIX`].
+
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::channels::OutgoingResponse;
+use crate::locale::{UserLocale, parse_when};
+
+/// Reply prefix the LLM uses to mark findings that must not wait.
+pub const EMERGENCY_PREFIX: &str = "EMERGENCY:";
+
+/// Longest focus window accepted.
+const MAX_FOCUS_HOURS: i64 = 24;
+
+/// Held items kept for the digest; older ones are counted but dropped.
+const MAX_DEFERRED: usize = 50;
+
+/// Severity tag for a background notification, from the LLM's reply.
+pub fn severity_of(text: &str) -> &'static str {
+    if text.trim_start().starts_with(EMERGENCY_PREFIX) {
+        "emergency"
+    } else {
+        "normal"
+    }
+}
+
+/// Parse a `/focus` duration: "2h", "90m", "1h 30m" or a bare number of hours.
+pub fn parse_focus_duration(args: &[String]) -> Option<Duration> {
+    let text = args.join(" ");
+    let duration = match text.trim().parse::<f64>() {
+        Ok(hours) if hours > 0.0 => Duration::minutes((hours * 60.0).round() as i64),
+        Ok(_) => return None,
+        Err(_) => {
+            let now = Utc::now();
+            parse_when(&format!("in {}", text), now, &UserLocale::default())? - now
+        }
+    };
+    (duration > Duration::zero() && duration <= Duration::hours(MAX_FOCUS_HOURS))
+        .then_some(duration)
+}
+
+/// Something held back while focused.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeferredItem {
+    pub at: DateTime<Utc>,
+    /// What produced it: "heartbeat", "routine", "self-repair".
+    pub source: String,
+    pub summary: String,
+}
+
+/// Snapshot for `/focus` and the gateway status endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusStatus {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime<Utc>>,
+    pub deferred: usize,
+}
+
+#[derive(Default)]
+struct FocusState {
+    until: Option<DateTime<Utc>>,
+    deferred: Vec<DeferredItem>,
+    dropped: usize,
+}
+
+/// Shared focus-mode switch. Cheap to query from background loops.
+#[derive(Default)]
+pub struct FocusMode {
+    state: Mutex<FocusState>,
+}
+
+impl FocusMode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enter focus for `duration`, or extend the current window. Returns the end time.
+    pub fn start(&self, duration: Duration) -> DateTime<Utc> {
+        let until = Utc::now() + duration;
+        self.state.lock().expect("focus lock").until = Some(until);
+        until
+    }
+
+    /// Leave focus now. Returns the digest of held items, if any.
+    pub fn stop(&self) -> Option<String> {
+        let mut state = self.state.lock().expect("focus lock");
+        state.until = None;
+        Self::drain_digest(&mut state)
+    }
+
+    /// Whether focus is on. An expired window counts as off.
+    pub fn is_active(&self) -> bool {
+        self.state
+            .lock()
+            .expect("focus lock")
+            .until
+            .is_some_and(|until| Utc::now() < until)
+    }
+
+    pub fn status(&self) -> FocusStatus {
+        let state = self.state.lock().expect("focus lock");
+        let until = state.until.filter(|until| Utc::now() < *until);
+        FocusStatus {
+            active: until.is_some(),
+            until,
+            deferred: state.deferred.len() + state.dropped,
+        }
+    }
+
+    /// Record a held item. Repeats of the same source and summary are
+    /// kept once.
+    pub fn defer(&self, source: &str, summary: &str) {
+        let mut state = self.state.lock().expect("focus lock");
+        if state
+            .deferred
+            .iter()
+            .any(|d| d.source == source && d.summary == summary)
+        {
+            return;
+        }
+        if state.deferred.len() >= MAX_DEFERRED {
+            state.dropped += 1;
+            return;
+        }
+        state.deferred.push(DeferredItem {
+            at: Utc::now(),
+            source: source.to_string(),
+            summary: summary.to_string(),
+        });
+    }
+
+    /// Pass a background notification through focus: returned as-is when
+    /// not focused or when it is an emergency, otherwise held for the digest.
+    pub fn hold(&self, source: &str, response: OutgoingResponse) -> Option<OutgoingResponse> {
+        let emergency =
+            response.metadata.get("severity").and_then(|v| v.as_str()) == Some("emergency");
+        if emergency || !self.is_active() {
+            return Some(response);
+        }
+        self.defer(source, &response.content);
+        None
+    }
+
+    /// If a focus window has run out, end it and return the digest.
+    pub fn take_expired(&self) -> Option<String> {
+        let mut state = self.state.lock().expect("focus lock");
+        match state.until {
+            Some(until) if Utc::now() >= until => {
+                state.until = None;
+                Some(
+                    Self::drain_digest(&mut state)
+                        .unwrap_or_else(|| "Focus mode ended. Nothing was held back.".to_string()),
+                )
+            }
+            _ => None,
+        }
+    }
+
+    fn drain_digest(state: &mut FocusState) -> Option<String> {
+        if state.deferred.is_empty() && state.dropped == 0 {
+            return None;
+        }
+        let items = std::mem::take(&mut state.deferred);
+        let dropped = std::mem::take(&mut state.dropped);
+        let mut out = format!(
+            "Focus mode ended. {} item(s) were held back:\n",
+            items.len() + dropped
+        );
+        for item in &items {
+            let first_line = item.summary.lines().find(|l| !l.trim().is_empty());
+            out.push_str(&format!(
+                "\n- {} [{}] {}",
+                item.at.format("%H:%M UTC"),
+                item.source,
+                first_line.unwrap_or("").trim()
+            ));
+        }
+        if dropped > 0 {
+            out.push_str(&format!("\n- ...and {} more", dropped));
+        }
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hold_defers_until_stopped_but_passes_emergencies() {
+        let focus = FocusMode::new();
+        let normal = OutgoingResponse::text("Routine 'inbox': 3 new mails");
+        assert!(focus.hold("routine", normal.clone()).is_some());
+
+        focus.start(Duration::hours(1));
+        assert!(focus.is_active());
+        assert!(focus.hold("routine", normal.clone()).is_none());
+        assert!(focus.hold("routine", normal).is_none());
+
+        let mut urgent = OutgoingResponse::text("EMERGENCY: disk full");
+        urgent.metadata = serde_json::json!({ "severity": "emergency" });
+        assert!(focus.hold("heartbeat", urgent).is_some());
+        assert_eq!(focus.status().deferred, 1);
+
+        let digest = focus.stop().unwrap();
+        assert!(digest.contains("1 item(s)"));
+        assert!(digest.contains("[routine] Routine 'inbox': 3 new mails"));
+        assert!(!focus.is_active());
+        assert_eq!(focus.status().deferred, 0);
+    }
+
+    #[test]
+    fn test_take_expired_ends_focus() {
+        let focus = FocusMode::new();
+        focus.start(Duration::hours(1));
+        assert!(focus.take_expired().is_none());
+
+        focus.start(Duration::seconds(-1));
+        assert!(!focus.is_active());
+        let digest = focus.take_expired().unwrap();
+        assert!(digest.contains("Nothing was held back"));
+        assert!(focus.take_expired().is_none());
+    }
+
+    #[test]
+    fn test_parse_focus_duration() {
+        let parse = |s: &str| parse_focus_duration(&[s.to_string()]);
+        assert_eq!(parse("2"), Some(Duration::hours(2)));
+        assert_eq!(parse("1.5"), Some(Duration::minutes(90)));
+        assert_eq!(parse("90m"), Some(Duration::minutes(90)));
+        assert_eq!(parse("2h"), Some(Duration::hours(2)));
+        assert_eq!(parse("0"), None);
+        assert_eq!(parse("48h"), None);
+        assert_eq!(parse("soon"), None);
+        assert_eq!(severity_of("EMERGENCY: server down"), "emergency");
+        assert_eq!(severity_of("all fine"), "normal");
+    }
+}
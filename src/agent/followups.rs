@@ -0,0 +1,143 @@
+//! Waiting on background work after a single CLI message.
+//!
+//! `ironclaw -m "..." --wait-for-followups <secs>` keeps the agent alive
+//! after the reply until the jobs and routine runs the message spawned have
+//! finished, or the wait runs out. Each job that finishes is reported to the
+//! channels as a notification carrying a `followup` metadata entry, and a
+//! final `followup: "done"` notification says whether the wait timed out, so
+//! the REPL can build its result envelope.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::channels::OutgoingResponse;
+use crate::context::JobState;
+
+/// How often background work is checked while waiting.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A job as seen by one poll.
+#[derive(Debug, Clone)]
+pub struct JobSnapshot {
+    pub id: Uuid,
+    pub title: String,
+    pub state: JobState,
+    /// A worker is executing the job.
+    pub scheduled: bool,
+}
+
+impl JobSnapshot {
+    /// Still being worked on. Stuck jobs may yet recover; pending jobs no
+    /// worker picked up are not waited for.
+    pub fn running(&self) -> bool {
+        self.scheduled || matches!(self.state, JobState::InProgress | JobState::Stuck)
+    }
+
+    /// Done as far as the caller is concerned. Completed jobs awaiting
+    /// submission count as done.
+    pub fn finished(&self) -> bool {
+        !self.running() && self.state != JobState::Pending
+    }
+}
+
+/// Tracks which finished jobs have already been reported.
+#[derive(Debug, Default)]
+pub struct FollowupTracker {
+    reported: HashSet<Uuid>,
+}
+
+impl FollowupTracker {
+    /// Notifications for jobs that finished since the last poll, and the ids
+    /// of jobs still running.
+    pub fn poll(&mut self, jobs: &[JobSnapshot]) -> (Vec<OutgoingResponse>, Vec<Uuid>) {
+        let mut notifications = Vec::new();
+        let mut pending = Vec::new();
+        for job in jobs {
+            if job.running() {
+                pending.push(job.id);
+            } else if job.finished() && self.reported.insert(job.id) {
+                let mut response =
+                    OutgoingResponse::text(format!("Job '{}' {}", job.title, job.state));
+                response.metadata = serde_json::json!({
+                    "followup": "job",
+                    "job_id": job.id.to_string(),
+                    "title": job.title,
+                    "state": job.state.to_string(),
+                });
+                notifications.push(response);
+            }
+        }
+        (notifications, pending)
+    }
+}
+
+/// The closing notification of a wait.
+pub fn done_notification(pending_jobs: &[Uuid], running_routines: usize) -> OutgoingResponse {
+    let timed_out = !pending_jobs.is_empty() || running_routines > 0;
+    let content = if timed_out {
+        format!(
+            "Stopped waiting with {} job(s) and {} routine run(s) still in progress",
+            pending_jobs.len(),
+            running_routines
+        )
+    } else {
+        String::new()
+    };
+    let mut response = OutgoingResponse::text(content);
+    response.metadata = serde_json::json!({
+        "followup": "done",
+        "timed_out": timed_out,
+        "pending_jobs": pending_jobs.iter().map(Uuid::to_string).collect::<Vec<_>>(),
+        "running_routines": running_routines,
+    });
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(state: JobState) -> JobSnapshot {
+        JobSnapshot {
+            id: Uuid::new_v4(),
+            title: "Fetch report".to_string(),
+            state,
+            scheduled: false,
+        }
+    }
+
+    #[test]
+    fn test_tracker_reports_each_finished_job_once() {
+        let running = job(JobState::InProgress);
+        let mut done = job(JobState::Completed);
+        let mut queued = job(JobState::Pending);
+        let unscheduled = job(JobState::Pending);
+        queued.scheduled = true;
+        let mut tracker = FollowupTracker::default();
+
+        let (notes, pending) =
+            tracker.poll(&[running.clone(), done.clone(), queued.clone(), unscheduled]);
+        assert_eq!(pending, vec![running.id, queued.id]);
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].content, "Job 'Fetch report' completed");
+        assert_eq!(notes[0].metadata["state"], "completed");
+
+        done.state = JobState::Accepted;
+        let (notes, _) = tracker.poll(&[running, done]);
+        assert!(notes.is_empty());
+    }
+
+    #[test]
+    fn test_done_notification() {
+        let done = done_notification(&[], 0);
+        assert_eq!(done.metadata["timed_out"], false);
+        assert!(done.content.is_empty());
+
+        let id = Uuid::new_v4();
+        let done = done_notification(&[id], 1);
+        assert_eq!(done.metadata["timed_out"], true);
+        assert_eq!(done.metadata["pending_jobs"][0], id.to_string());
+    }
+}
@@ -0,0 +1,296 @@
+//! Per-session generation controls.
+//!
+//! `/temperature 0.2`, `/style concise` and `/verbosity high` tune how the
+//! agent answers for the rest of the conversation without touching global
+//! config. The settings live in the session metadata under `"generation"`
+//! and are copied to the active thread's conversation metadata so they
+//! survive a restart. Unset fields fall back to the defaults the prompt
+//! builder and provider requests already use.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Metadata key the settings are stored under.
+pub const METADATA_KEY: &str = "generation";
+
+/// Temperature used when the session has not set one.
+pub const DEFAULT_TEMPERATURE: f32 = 0.7;
+
+/// Highest temperature accepted; providers reject anything above this.
+const MAX_TEMPERATURE: f32 = 2.0;
+
+/// How the answer is shaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseStyle {
+    Concise,
+    Detailed,
+}
+
+impl ResponseStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Concise => "concise",
+            Self::Detailed => "detailed",
+        }
+    }
+
+    fn guidance(&self) -> &'static str {
+        match self {
+            Self::Concise => {
+                "Answer in as few words as the question allows. Skip preamble, recaps and caveats unless they change the answer."
+            }
+            Self::Detailed => {
+                "Give thorough answers: explain your reasoning, cover edge cases and include examples where they help."
+            }
+        }
+    }
+}
+
+impl FromStr for ResponseStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "concise" => Ok(Self::Concise),
+            "detailed" => Ok(Self::Detailed),
+            other => Err(format!(
+                "Unknown style '{}'. Use concise or detailed.",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ResponseStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How much the agent says, and how many tokens it may spend saying it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    Low,
+    Normal,
+    High,
+}
+
+impl Verbosity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+        }
+    }
+
+    /// Completion token cap for responses at this verbosity.
+    pub fn max_tokens(&self) -> u32 {
+        match self {
+            Self::Low => 2048,
+            Self::Normal => 4096,
+            Self::High => 8192,
+        }
+    }
+
+    fn guidance(&self) -> Option<&'static str> {
+        match self {
+            Self::Low => Some("Keep replies short: a few sentences or a brief list."),
+            Self::Normal => None,
+            Self::High => Some("Longer replies are welcome when the topic warrants them."),
+        }
+    }
+}
+
+impl FromStr for Verbosity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "normal" => Ok(Self::Normal),
+            "high" => Ok(Self::High),
+            other => Err(format!(
+                "Unknown verbosity '{}'. Use low, normal or high.",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Verbosity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Generation settings for one session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationPrefs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<ResponseStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verbosity: Option<Verbosity>,
+}
+
+impl GenerationPrefs {
+    /// Read the settings from session or conversation metadata. Missing or
+    /// malformed entries read as the defaults.
+    pub fn from_metadata(metadata: &serde_json::Value) -> Self {
+        metadata
+            .get(METADATA_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Store the settings in `metadata`, removing the entry when nothing is set.
+    pub fn write_to(&self, metadata: &mut serde_json::Value) {
+        if !metadata.is_object() {
+            *metadata = serde_json::json!({});
+        }
+        if let Some(map) = metadata.as_object_mut() {
+            if self.is_default() {
+                map.remove(METADATA_KEY);
+            } else {
+                map.insert(METADATA_KEY.to_string(), self.to_value());
+            }
+        }
+    }
+
+    /// The settings as a JSON value, for conversation metadata.
+    pub fn to_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    /// Whether nothing has been set.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Sampling temperature for responses.
+    pub fn temperature(&self) -> f32 {
+        self.temperature.unwrap_or(DEFAULT_TEMPERATURE)
+    }
+
+    /// Completion token cap for responses.
+    pub fn max_tokens(&self) -> u32 {
+        self.verbosity.unwrap_or(Verbosity::Normal).max_tokens()
+    }
+
+    /// Instructions for the system prompt, or `None` when nothing is set.
+    pub fn prompt_section(&self) -> Option<String> {
+        let lines: Vec<&str> = [
+            self.style.map(|style| style.guidance()),
+            self.verbosity.and_then(|verbosity| verbosity.guidance()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        (!lines.is_empty()).then(|| {
+            format!(
+                "## Response Style\n{}",
+                lines
+                    .iter()
+                    .map(|line| format!("- {}", line))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )
+        })
+    }
+
+    /// One-line summary for command replies.
+    pub fn describe(&self) -> String {
+        let or_default = |value: Option<String>| value.unwrap_or_else(|| "default".to_string());
+        format!(
+            "temperature {} ({}), style {}, verbosity {}",
+            self.temperature(),
+            if self.temperature.is_some() {
+                "session"
+            } else {
+                "default"
+            },
+            or_default(self.style.map(|s| s.to_string())),
+            or_default(self.verbosity.map(|v| v.to_string())),
+        )
+    }
+}
+
+/// Parse a `/temperature` value: a number between 0 and 2.
+pub fn parse_temperature(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(t) if (0.0..=MAX_TEMPERATURE).contains(&t) => Ok(t),
+        _ => Err(format!(
+            "Temperature must be a number between 0 and {}.",
+            MAX_TEMPERATURE
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_match_previous_behavior() {
+        let prefs = GenerationPrefs::default();
+        assert_eq!(prefs.temperature(), 0.7);
+        assert_eq!(prefs.max_tokens(), 4096);
+        assert!(prefs.prompt_section().is_none());
+    }
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let mut metadata = serde_json::json!({"other": 1});
+        let prefs = GenerationPrefs {
+            temperature: Some(0.2),
+            style: Some(ResponseStyle::Concise),
+            verbosity: Some(Verbosity::High),
+        };
+
+        prefs.write_to(&mut metadata);
+        assert_eq!(GenerationPrefs::from_metadata(&metadata), prefs);
+        assert_eq!(metadata["generation"]["style"], "concise");
+
+        GenerationPrefs::default().write_to(&mut metadata);
+        assert!(metadata.get("generation").is_none());
+        assert_eq!(metadata["other"], 1);
+    }
+
+    #[test]
+    fn test_malformed_metadata_reads_as_default() {
+        let metadata = serde_json::json!({"generation": {"style": "shouty"}});
+        assert!(GenerationPrefs::from_metadata(&metadata).is_default());
+        assert!(GenerationPrefs::from_metadata(&serde_json::Value::Null).is_default());
+    }
+
+    #[test]
+    fn test_prompt_section_and_token_cap() {
+        let prefs = GenerationPrefs {
+            style: Some(ResponseStyle::Detailed),
+            verbosity: Some(Verbosity::Low),
+            ..Default::default()
+        };
+        let section = prefs.prompt_section().unwrap();
+        assert!(section.contains("thorough"));
+        assert!(section.contains("short"));
+        assert_eq!(prefs.max_tokens(), 2048);
+    }
+
+    #[test]
+    fn test_parse_values() {
+        assert_eq!(parse_temperature("0.3"), Ok(0.3));
+        assert!(parse_temperature("2.5").is_err());
+        assert!(parse_temperature("warm").is_err());
+        assert_eq!(
+            "Concise".parse::<ResponseStyle>(),
+            Ok(ResponseStyle::Concise)
+        );
+        assert!("loud".parse::<Verbosity>().is_err());
+    }
+}
@@ -30,6 +30,7 @@ use tokio::sync::mpsc;
 
 use crate::channels::OutgoingResponse;
 use crate::llm::{ChatMessage, CompletionRequest, FinishReason, LlmProvider};
+use crate::locale::UserLocale;
 use crate::workspace::Workspace;
 
 /// Configuration for the heartbeat runner.
@@ -45,6 +46,8 @@ pub struct HeartbeatConfig {
     pub notify_user_id: Option<String>,
     /// Channel to notify on heartbeat findings.
     pub notify_channel: Option<String>,
+    /// Locale of the notified user, so checks see their local time.
+    pub locale: UserLocale,
 }
 
 impl Default for HeartbeatConfig {
@@ -55,6 +58,7 @@ impl Default for HeartbeatConfig {
             max_failures: 3,
             notify_user_id: None,
             notify_channel: None,
+            locale: UserLocale::default(),
         }
     }
 }
@@ -72,6 +76,12 @@ impl HeartbeatConfig {
         self
     }
 
+    /// Set the locale used for the local time in checks.
+    pub fn with_locale(mut self, locale: UserLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+
     /// Set the notification target.
     pub fn with_notify(mut self, user_id: impl Into<String>, channel: impl Into<String>) -> Self {
         self.notify_user_id = Some(user_id.into());
@@ -185,16 +195,19 @@ impl HeartbeatRunner {
 
         // Build the heartbeat prompt
         let prompt = format!(
-            "Read the HEARTBEAT.md checklist below and follow it strictly. \
+            "{}\n\n\
+             Read the HEARTBEAT.md checklist below and follow it strictly. \
              Do not infer or repeat old tasks. Check each item and report findings.\n\
              \n\
              If nothing needs attention, reply EXACTLY with: HEARTBEAT_OK\n\
              \n\
              If something needs attention, provide a concise summary of what needs action.\n\
+             If it is an emergency that cannot wait, start the summary with: EMERGENCY:\n\
              \n\
              ## HEARTBEAT.md\n\
              \n\
              {}",
+            self.config.locale.prompt_line(chrono::Utc::now()),
             checklist
         );
 
@@ -279,6 +292,7 @@ impl HeartbeatRunner {
             thread_id: None,
             metadata: serde_json::json!({
                 "source": "heartbeat",
+                "severity": crate::agent::focus::severity_of(message),
             }),
         };
 
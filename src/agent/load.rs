@@ -0,0 +1,248 @@
+//! Agent load feedback.
+//!
+//! The agent loop records how many turns are in flight and how long they
+//! take. Channels read it to shed load (e.g. the gateway answers 429 with a
+//! Retry-After estimate) before messages pile up behind a busy agent.
+//!
+//! A probe on the main runtime also measures scheduling lag: how late a
+//! 100ms timer fires. Lag is what the user feels as a sluggish REPL when
+//! background work crowds the interactive threads, so it is reported in
+//! `/status` and the gateway status endpoint.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::agent::priority::{BackgroundStats, background_stats};
+
+/// Weight of the newest sample in the turn-duration moving average.
+const EWMA_WEIGHT: f64 = 0.2;
+
+/// Interval between scheduling-lag probes.
+const PROBE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Lag samples kept (one minute of probes).
+const LAG_SAMPLES: usize = 600;
+
+/// Shared view of how busy the agent is.
+#[derive(Debug, Default)]
+pub struct AgentLoad {
+    in_flight: AtomicUsize,
+    /// Moving average of turn duration in milliseconds (0 = no samples yet).
+    avg_turn_ms: AtomicU64,
+    /// Recent scheduling lag samples in microseconds, oldest first.
+    lag_us: Mutex<VecDeque<u64>>,
+}
+
+/// Responsiveness of the interactive runtime.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Responsiveness {
+    /// Lag samples in the window (0 until the probe has run).
+    pub samples: usize,
+    pub lag_p50_ms: f64,
+    pub lag_p99_ms: f64,
+    pub lag_max_ms: f64,
+    pub turns_in_flight: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_turn_ms: Option<u64>,
+    /// Present when background work runs on its own runtime.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<BackgroundStats>,
+}
+
+impl Responsiveness {
+    /// Lines for `/status`.
+    pub fn render(&self) -> String {
+        let mut out = if self.samples == 0 {
+            "  Scheduling lag: no samples yet".to_string()
+        } else {
+            format!(
+                "  Scheduling lag: p50 {:.1}ms, p99 {:.1}ms, max {:.1}ms (last {}s)",
+                self.lag_p50_ms,
+                self.lag_p99_ms,
+                self.lag_max_ms,
+                (self.samples as u64 * PROBE_INTERVAL.as_millis() as u64).div_ceil(1000)
+            )
+        };
+        out.push_str(&format!("\n  Turns in flight: {}", self.turns_in_flight));
+        if let Some(avg) = self.avg_turn_ms {
+            out.push_str(&format!(" (avg {:.1}s)", avg as f64 / 1000.0));
+        }
+        match self.background {
+            Some(bg) => out.push_str(&format!(
+                "\n  Background: {} running, {} queued (limit {}, {} threads)",
+                bg.running, bg.queued, bg.max_tasks, bg.threads
+            )),
+            None => out.push_str("\n  Background: shared runtime"),
+        }
+        out
+    }
+}
+
+impl AgentLoad {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Mark a turn as started. The turn ends when the guard is dropped.
+    pub fn begin(self: &Arc<Self>) -> TurnGuard {
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        TurnGuard {
+            load: Arc::clone(self),
+            started: Instant::now(),
+        }
+    }
+
+    /// Turns currently being processed.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    /// Average turn duration, if any turn has completed.
+    pub fn avg_turn(&self) -> Option<Duration> {
+        match self.avg_turn_ms.load(Ordering::Acquire) {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+
+    /// Sample scheduling lag on the current runtime until `self` is dropped.
+    pub fn spawn_lag_probe(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let load = Arc::downgrade(self);
+        tokio::spawn(async move {
+            loop {
+                let started = Instant::now();
+                tokio::time::sleep(PROBE_INTERVAL).await;
+                let Some(load) = load.upgrade() else {
+                    break;
+                };
+                load.record_lag(started.elapsed().saturating_sub(PROBE_INTERVAL));
+            }
+        })
+    }
+
+    fn record_lag(&self, lag: Duration) {
+        let mut samples = self.lag_us.lock().unwrap_or_else(|e| e.into_inner());
+        if samples.len() == LAG_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(lag.as_micros() as u64);
+    }
+
+    /// Lag percentiles over the last minute, turn counters and background
+    /// runtime state.
+    pub fn responsiveness(&self) -> Responsiveness {
+        let mut lags: Vec<u64> = self
+            .lag_us
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .copied()
+            .collect();
+        lags.sort_unstable();
+        let percentile = |p: f64| -> f64 {
+            if lags.is_empty() {
+                return 0.0;
+            }
+            let index = ((lags.len() - 1) as f64 * p).round() as usize;
+            lags[index] as f64 / 1000.0
+        };
+        Responsiveness {
+            samples: lags.len(),
+            lag_p50_ms: percentile(0.5),
+            lag_p99_ms: percentile(0.99),
+            lag_max_ms: percentile(1.0),
+            turns_in_flight: self.in_flight(),
+            avg_turn_ms: self.avg_turn().map(|d| d.as_millis() as u64),
+            background: background_stats(),
+        }
+    }
+
+    fn record(&self, elapsed: Duration) {
+        let sample = elapsed.as_millis().max(1) as f64;
+        // Lost updates under contention only skew the average slightly.
+        let prev = self.avg_turn_ms.load(Ordering::Acquire);
+        let next = if prev == 0 {
+            sample
+        } else {
+            prev as f64 * (1.0 - EWMA_WEIGHT) + sample * EWMA_WEIGHT
+        };
+        self.avg_turn_ms
+            .store(next.round() as u64, Ordering::Release);
+    }
+}
+
+/// Ends a turn on drop.
+pub struct TurnGuard {
+    load: Arc<AgentLoad>,
+    started: Instant,
+}
+
+impl Drop for TurnGuard {
+    fn drop(&mut self) {
+        self.load.in_flight.fetch_sub(1, Ordering::AcqRel);
+        self.load.record(self.started.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turn_guard_tracks_in_flight_and_average() {
+        let load = AgentLoad::new();
+        assert_eq!(load.in_flight(), 0);
+        assert!(load.avg_turn().is_none());
+
+        let a = load.begin();
+        let b = load.begin();
+        assert_eq!(load.in_flight(), 2);
+        drop(a);
+        drop(b);
+        assert_eq!(load.in_flight(), 0);
+        assert!(load.avg_turn().is_some());
+
+        load.record(Duration::from_millis(1000));
+        load.record(Duration::from_millis(1000));
+        let avg = load.avg_turn().unwrap().as_millis();
+        assert!(avg > 300 && avg < 1000, "avg was {avg}");
+    }
+
+    #[test]
+    fn test_lag_percentiles_over_window() {
+        let load = AgentLoad::new();
+        assert_eq!(load.responsiveness().samples, 0);
+        assert!(load.responsiveness().render().contains("no samples yet"));
+
+        for ms in 1..=100 {
+            load.record_lag(Duration::from_millis(ms));
+        }
+        let report = load.responsiveness();
+        assert_eq!(report.samples, 100);
+        assert_eq!(report.lag_max_ms, 100.0);
+        assert_eq!(report.lag_p50_ms, 51.0);
+        assert_eq!(report.lag_p99_ms, 99.0);
+        assert!(report.render().contains("p99 99.0ms"));
+
+        // Old samples fall out of the window.
+        for _ in 0..LAG_SAMPLES {
+            load.record_lag(Duration::ZERO);
+        }
+        assert_eq!(load.responsiveness().lag_max_ms, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_lag_probe_stops_with_load() {
+        let load = AgentLoad::new();
+        let probe = load.spawn_lag_probe();
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        assert!(load.responsiveness().samples >= 3);
+        drop(load);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(probe.is_finished());
+    }
+}
@@ -6,21 +6,56 @@
 //! - Tool invocation with safety
 //! - Self-repair for stuck jobs
 //! - Proactive heartbeat execution
-//! - Routine-based scheduled and reactive jobs
+//! - Routine-based scheduled and reactive jobs (cron, events, webhooks, file watches)
 //! - Turn-based session management with undo
 //! - Context compaction for long conversations
+//! - Deduplication of identical messages sent in quick succession
+//! - Failure hints that steer the LLM away from repeating broken tool calls
+//! - Optional per-phase turn latency profiling
+//! - Per-session project binding (working directory, context, tool policy)
+//! - Summarization of oversized tool outputs before they reach the LLM
+//! - Time-boxed focus mode that holds background activity
+//! - Parameter-scoped approval memory (`/approvals`)
+//! - Dead-man switch that escalates unattended critical failures
+//! - Per-session temperature, style and verbosity (`/temperature`, `/style`, `/verbosity`)
+//! - Source citations from memory and tool outputs, strict for some skills
+//! - Away auto-responses with a summarized backlog (`/away`)
+//! - Bounded recovery from LLM failures partway through a turn
+//! - Per-session and per-day token and cost caps (`/budget`)
+//! - Redaction of turns from history, memory and logs (`/redact`)
+//! - Scoped sub-agents for delegated subtasks (`delegate` tool)
 
 mod agent_loop;
+pub mod approvals;
 pub mod auth_profiles;
+pub mod away;
+pub mod budget;
+pub mod citations;
 pub mod command_queue;
 pub mod compaction;
 pub mod config_reload;
 pub mod context_monitor;
+pub mod dead_man;
+pub mod dedup;
+pub mod delegate;
+pub mod focus;
+pub mod followups;
+pub mod generation;
 mod heartbeat;
+pub mod load;
+pub mod model_tier;
 pub mod multi_agent;
+pub mod output_summary;
+pub mod priority;
+pub mod profiling;
+pub mod project;
+pub mod recovery;
+pub mod redact;
+pub mod report;
 mod router;
 pub mod routine;
 pub mod routine_engine;
+pub mod routine_watch;
 mod scheduler;
 mod self_repair;
 pub mod session;
@@ -28,28 +63,49 @@ mod session_manager;
 pub mod session_pruning;
 pub mod submission;
 pub mod task;
+pub mod tool_hints;
 pub mod undo;
 pub mod worker;
 
 pub(crate) use agent_loop::truncate_for_preview;
 pub use agent_loop::{Agent, AgentDeps};
+pub use approvals::{ApprovalRule, ApprovalRules, ApprovalScope};
+pub use away::{AwayDesk, AwayRule, Backlog, SenderRole};
+pub use budget::{BudgetManager, BudgetReport};
+pub use citations::{CitationPolicy, Source, SourceKind, SourceLedger};
 pub use command_queue::{
     CommandLane, CommandQueue, QueueConfig, QueueStats, QueuedCommand, classify_lane,
 };
 pub use compaction::{CompactionResult, ContextCompactor};
 pub use config_reload::spawn_config_reload_task;
 pub use context_monitor::{CompactionStrategy, ContextBreakdown, ContextMonitor};
+pub use dead_man::{CriticalFailure, DeadManSwitch, Escalation, SafeModeMarker};
+pub use dedup::{DedupOutcome, ResponseDeduplicator};
+pub use delegate::{DelegateResult, DelegateStatus, SubAgent};
+pub use focus::{FocusMode, FocusStatus};
+pub use generation::{GenerationPrefs, ResponseStyle, Verbosity};
 pub use heartbeat::{HeartbeatConfig, HeartbeatResult, HeartbeatRunner, spawn_heartbeat};
+pub use load::{AgentLoad, Responsiveness};
+pub use model_tier::{ModelTier, ModelTierRouter, TierMode, TierReport};
 pub use multi_agent::{AgentIdentity, AgentRouter, RoutingDecision, RoutingStrategy};
+pub use profiling::{Phase, TurnProfile, TurnProfiler};
+pub use project::{ProjectBinding, ProjectPolicy};
+pub use recovery::RecoveryBudget;
+pub use redact::{Redaction, RedactionReport};
+pub use report::{ActivityReport, ReportFormat, ReportSection};
 pub use router::{MessageIntent, Router};
 pub use routine::{Routine, RoutineAction, RoutineRun, Trigger};
 pub use routine_engine::RoutineEngine;
 pub use scheduler::Scheduler;
 pub use self_repair::{BrokenTool, RepairResult, RepairTask, SelfRepair, StuckJob};
-pub use session::{PendingApproval, PendingAuth, Session, Thread, ThreadState, Turn, TurnState};
+pub use session::{
+    PendingApproval, PendingAuth, PendingInput, Session, Thread, ThreadState, Turn, TurnRecovery,
+    TurnState,
+};
 pub use session_manager::SessionManager;
 pub use session_pruning::{GlobalSession, PruneResult, PruningConfig, SessionPruner};
 pub use submission::{Submission, SubmissionParser, SubmissionResult};
 pub use task::{Task, TaskContext, TaskHandler, TaskOutput, TaskStatus};
+pub use tool_hints::ToolHintStore;
 pub use undo::{Checkpoint, UndoManager};
 pub use worker::{Worker, WorkerDeps};
@@ -0,0 +1,470 @@
+//! Per-turn model tier selection.
+//!
+//! With a cheap model configured, each chat turn is routed to either the
+//! cheap or the primary model. Short chat-only messages go to the cheap
+//! model; code, tool-heavy conversations and long messages stay on the
+//! primary one. Outcomes are tracked per task kind, so a kind where the
+//! cheap model keeps failing is sent back to the primary model, and turns
+//! forced onto the cheap model with `/model cheap` teach the policy which
+//! kinds it handles well.
+//!
+//! ```text
+//!  /model cheap|primary ──▶ pinned tier
+//!  /model auto ──▶ signals (length, code, tools) ──▶ kind ──▶ cheap success rate ──▶ tier
+//! ```
+//!
+//! Savings are what the primary model would have charged for the tokens the
+//! cheap model handled, minus what the cheap model charged.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::llm::LlmProvider;
+
+/// Which model serves a turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelTier {
+    Cheap,
+    Primary,
+}
+
+impl ModelTier {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModelTier::Cheap => "cheap",
+            ModelTier::Primary => "primary",
+        }
+    }
+}
+
+impl std::fmt::Display for ModelTier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How tiers are chosen, set with `/model auto|cheap|primary`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TierMode {
+    /// Pick per turn from the message and history.
+    #[default]
+    Auto,
+    /// Always use one tier.
+    Pinned(ModelTier),
+}
+
+impl std::str::FromStr for TierMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(TierMode::Auto),
+            "cheap" => Ok(TierMode::Pinned(ModelTier::Cheap)),
+            "primary" => Ok(TierMode::Pinned(ModelTier::Primary)),
+            _ => Err(format!("unknown tier mode '{}'", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for TierMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TierMode::Auto => f.write_str("auto"),
+            TierMode::Pinned(tier) => tier.fmt(f),
+        }
+    }
+}
+
+/// The kind of work a turn is, used to track how the cheap model fares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    /// Conversation without code or recent tool use.
+    Chat,
+    /// The message contains code.
+    Code,
+    /// The conversation has been driving tools.
+    Tools,
+}
+
+/// What the policy looks at for one turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TurnSignals {
+    /// Length of the user message in characters.
+    pub chars: usize,
+    pub has_code: bool,
+    /// Tool calls made in the thread's recent turns.
+    pub recent_tool_calls: usize,
+}
+
+/// Recent tool calls at which a conversation counts as tool-heavy.
+const TOOL_HEAVY_CALLS: usize = 2;
+
+impl TurnSignals {
+    pub fn new(message: &str, recent_tool_calls: usize) -> Self {
+        Self {
+            chars: message.chars().count(),
+            has_code: looks_like_code(message),
+            recent_tool_calls,
+        }
+    }
+
+    pub fn kind(&self) -> TaskKind {
+        if self.has_code {
+            TaskKind::Code
+        } else if self.recent_tool_calls >= TOOL_HEAVY_CALLS {
+            TaskKind::Tools
+        } else {
+            TaskKind::Chat
+        }
+    }
+}
+
+/// Rough check for code in a message: fences, or several lines that read
+/// like source.
+fn looks_like_code(message: &str) -> bool {
+    if message.contains("```") {
+        return true;
+    }
+    let code_lines = message
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            line.ends_with(';')
+                || line.ends_with('{')
+                || line.starts_with("fn ")
+                || line.starts_with("def ")
+                || line.starts_with("import ")
+                || line.starts_with("#include")
+                || line.contains("=> ")
+        })
+        .count();
+    code_lines >= 2
+}
+
+/// Thresholds for the auto policy.
+#[derive(Debug, Clone, Copy)]
+pub struct TierPolicy {
+    /// Longest message (in characters) the cheap model gets by default.
+    pub max_cheap_chars: usize,
+    /// Cheap turns of a kind needed before its success rate counts.
+    pub min_samples: u32,
+    /// Cheap success rate below which a kind goes to the primary model,
+    /// and above which code and tool turns may use the cheap one.
+    pub min_success_rate: f64,
+}
+
+impl Default for TierPolicy {
+    fn default() -> Self {
+        Self {
+            max_cheap_chars: 500,
+            min_samples: 5,
+            min_success_rate: 0.8,
+        }
+    }
+}
+
+/// How the cheap model has done on one kind of task.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct KindStats {
+    pub cheap_turns: u32,
+    pub cheap_successes: u32,
+}
+
+impl KindStats {
+    fn success_rate(&self) -> f64 {
+        if self.cheap_turns == 0 {
+            return 1.0;
+        }
+        self.cheap_successes as f64 / self.cheap_turns as f64
+    }
+}
+
+/// Token and cost totals for one tier.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct TierUsage {
+    pub turns: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost: Decimal,
+}
+
+/// Snapshot of routing decisions and realized savings.
+#[derive(Debug, Clone, Serialize)]
+pub struct TierReport {
+    pub mode: String,
+    pub cheap_model: String,
+    pub primary_model: String,
+    pub cheap: TierUsage,
+    pub primary: TierUsage,
+    /// What the primary model would have charged for the cheap tier's tokens.
+    pub cheap_at_primary_cost: Decimal,
+    pub saved: Decimal,
+    pub kinds: HashMap<TaskKind, KindStats>,
+}
+
+#[derive(Default)]
+struct RouterState {
+    mode: TierMode,
+    kinds: HashMap<TaskKind, KindStats>,
+    cheap: TierUsage,
+    primary: TierUsage,
+    cheap_at_primary_cost: Decimal,
+}
+
+/// Routes chat turns between a cheap and a primary model.
+pub struct ModelTierRouter {
+    primary: Arc<dyn LlmProvider>,
+    cheap: Arc<dyn LlmProvider>,
+    policy: TierPolicy,
+    state: Mutex<RouterState>,
+}
+
+impl ModelTierRouter {
+    pub fn new(primary: Arc<dyn LlmProvider>, cheap: Arc<dyn LlmProvider>) -> Self {
+        Self {
+            primary,
+            cheap,
+            policy: TierPolicy::default(),
+            state: Mutex::new(RouterState::default()),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: TierPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, RouterState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn mode(&self) -> TierMode {
+        self.state().mode
+    }
+
+    pub fn set_mode(&self, mode: TierMode) {
+        self.state().mode = mode;
+    }
+
+    pub fn provider(&self, tier: ModelTier) -> &Arc<dyn LlmProvider> {
+        match tier {
+            ModelTier::Cheap => &self.cheap,
+            ModelTier::Primary => &self.primary,
+        }
+    }
+
+    /// Pick the tier for a turn.
+    pub fn select(&self, signals: &TurnSignals) -> ModelTier {
+        let state = self.state();
+        if let TierMode::Pinned(tier) = state.mode {
+            return tier;
+        }
+        if signals.chars > self.policy.max_cheap_chars {
+            return ModelTier::Primary;
+        }
+
+        let kind = signals.kind();
+        let stats = state.kinds.get(&kind).copied().unwrap_or_default();
+        let proven = stats.cheap_turns >= self.policy.min_samples;
+        let good = stats.success_rate() >= self.policy.min_success_rate;
+        match kind {
+            // Chat starts on the cheap model until it proves unreliable.
+            TaskKind::Chat if !proven || good => ModelTier::Cheap,
+            // Code and tool work need a track record first.
+            TaskKind::Code | TaskKind::Tools if proven && good => ModelTier::Cheap,
+            _ => ModelTier::Primary,
+        }
+    }
+
+    /// Record the tokens one LLM call used on a tier.
+    pub fn record_usage(&self, tier: ModelTier, input_tokens: u32, output_tokens: u32) {
+        let actual = self
+            .provider(tier)
+            .calculate_cost(input_tokens, output_tokens);
+        let at_primary = self.primary.calculate_cost(input_tokens, output_tokens);
+
+        let mut state = self.state();
+        let usage = match tier {
+            ModelTier::Cheap => {
+                state.cheap_at_primary_cost += at_primary;
+                &mut state.cheap
+            }
+            ModelTier::Primary => &mut state.primary,
+        };
+        usage.input_tokens += u64::from(input_tokens);
+        usage.output_tokens += u64::from(output_tokens);
+        usage.cost += actual;
+    }
+
+    /// Record how a turn went.
+    pub fn record_outcome(&self, tier: ModelTier, kind: TaskKind, success: bool) {
+        let mut state = self.state();
+        match tier {
+            ModelTier::Cheap => {
+                state.cheap.turns += 1;
+                let stats = state.kinds.entry(kind).or_default();
+                stats.cheap_turns += 1;
+                if success {
+                    stats.cheap_successes += 1;
+                }
+            }
+            ModelTier::Primary => state.primary.turns += 1,
+        }
+    }
+
+    pub fn report(&self) -> TierReport {
+        let state = self.state();
+        TierReport {
+            mode: state.mode.to_string(),
+            cheap_model: self.cheap.active_model_name(),
+            primary_model: self.primary.active_model_name(),
+            cheap: state.cheap,
+            primary: state.primary,
+            cheap_at_primary_cost: state.cheap_at_primary_cost,
+            saved: (state.cheap_at_primary_cost - state.cheap.cost).max(Decimal::ZERO),
+            kinds: state.kinds.clone(),
+        }
+    }
+}
+
+impl TierReport {
+    /// Short human-readable summary for `/model`.
+    pub fn summary(&self) -> String {
+        format!(
+            "Tier mode: {} (cheap: {}, primary: {})\n\
+             Cheap turns: {}, primary turns: {}\n\
+             Realized savings: ${}",
+            self.mode,
+            self.cheap_model,
+            self.primary_model,
+            self.cheap.turns,
+            self.primary.turns,
+            self.saved.round_dp(4)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::error::LlmError;
+    use crate::llm::{
+        CompletionRequest, CompletionResponse, ToolCompletionRequest, ToolCompletionResponse,
+    };
+
+    struct PricedLlm {
+        name: &'static str,
+        input: Decimal,
+        output: Decimal,
+    }
+
+    #[async_trait]
+    impl LlmProvider for PricedLlm {
+        fn model_name(&self) -> &str {
+            self.name
+        }
+
+        fn cost_per_token(&self) -> (Decimal, Decimal) {
+            (self.input, self.output)
+        }
+
+        async fn complete(&self, _req: CompletionRequest) -> Result<CompletionResponse, LlmError> {
+            unimplemented!()
+        }
+
+        async fn complete_with_tools(
+            &self,
+            _req: ToolCompletionRequest,
+        ) -> Result<ToolCompletionResponse, LlmError> {
+            unimplemented!()
+        }
+    }
+
+    fn router() -> ModelTierRouter {
+        let primary = Arc::new(PricedLlm {
+            name: "big",
+            input: Decimal::new(10, 6),
+            output: Decimal::new(30, 6),
+        });
+        let cheap = Arc::new(PricedLlm {
+            name: "small",
+            input: Decimal::new(1, 6),
+            output: Decimal::new(2, 6),
+        });
+        ModelTierRouter::new(primary, cheap)
+    }
+
+    #[test]
+    fn test_signals_classify_turns() {
+        assert_eq!(TurnSignals::new("thanks!", 0).kind(), TaskKind::Chat);
+        assert_eq!(
+            TurnSignals::new("why does this fail?\n```\nlet x = 1;\n```", 0).kind(),
+            TaskKind::Code
+        );
+        assert_eq!(
+            TurnSignals::new("fn main() {\n    println!(\"hi\");\n}", 0).kind(),
+            TaskKind::Code
+        );
+        assert_eq!(
+            TurnSignals::new("and the next one", 3).kind(),
+            TaskKind::Tools
+        );
+    }
+
+    #[test]
+    fn test_select_uses_heuristics_and_history() {
+        let router = router();
+        assert_eq!(
+            router.select(&TurnSignals::new("hi there", 0)),
+            ModelTier::Cheap
+        );
+        assert_eq!(
+            router.select(&TurnSignals::new(&"long ".repeat(200), 0)),
+            ModelTier::Primary
+        );
+        let tools = TurnSignals::new("keep going", 4);
+        assert_eq!(router.select(&tools), ModelTier::Primary);
+
+        // Forced cheap turns that succeed earn tool work a place on the cheap model.
+        router.set_mode("cheap".parse().unwrap());
+        assert_eq!(router.select(&tools), ModelTier::Cheap);
+        for _ in 0..5 {
+            router.record_outcome(ModelTier::Cheap, TaskKind::Tools, true);
+        }
+        router.set_mode(TierMode::Auto);
+        assert_eq!(router.select(&tools), ModelTier::Cheap);
+
+        // Chat falls back to the primary model once the cheap one keeps failing.
+        for _ in 0..5 {
+            router.record_outcome(ModelTier::Cheap, TaskKind::Chat, false);
+        }
+        assert_eq!(
+            router.select(&TurnSignals::new("hi there", 0)),
+            ModelTier::Primary
+        );
+    }
+
+    #[test]
+    fn test_report_savings() {
+        let router = router();
+        router.record_usage(ModelTier::Cheap, 1000, 100);
+        router.record_outcome(ModelTier::Cheap, TaskKind::Chat, true);
+        router.record_usage(ModelTier::Primary, 500, 50);
+        router.record_outcome(ModelTier::Primary, TaskKind::Code, true);
+
+        let report = router.report();
+        assert_eq!(report.cheap.turns, 1);
+        assert_eq!(report.primary.turns, 1);
+        // 1000 * 10e-6 + 100 * 30e-6 = 0.013 at primary prices; 0.0012 actual.
+        assert_eq!(report.cheap_at_primary_cost, Decimal::new(13, 3));
+        assert_eq!(report.saved, Decimal::new(118, 4));
+        assert!(report.summary().contains("$0.0118"));
+    }
+}
@@ -4,6 +4,10 @@
 //! own system prompt, allowed tools, and isolated workspace prefix. Messages
 //! are routed to the most appropriate agent based on intent analysis, channel
 //! origin, and explicit `@agent` mentions.
+//!
+//! Each agent's `memory_scope` decides whose memory it sees; use
+//! [`AgentRouter::workspace_for`] to get the workspace a routed message
+//! should use.
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -13,6 +17,7 @@ use tokio::sync::RwLock;
 
 use crate::channels::IncomingMessage;
 use crate::error::Error;
+use crate::workspace::{MemoryScope, ScopedOwner, Workspace};
 
 /// Identity definition for a single agent persona.
 ///
@@ -34,6 +39,9 @@ pub struct AgentIdentity {
     pub enabled: bool,
     /// Priority for tie-breaking when multiple agents match (higher = preferred).
     pub priority: i32,
+    /// Whose memory documents this agent reads and writes.
+    #[serde(default)]
+    pub memory_scope: MemoryScope,
 }
 
 impl AgentIdentity {
@@ -53,6 +61,7 @@ impl AgentIdentity {
             workspace_prefix,
             enabled: true,
             priority: 0,
+            memory_scope: MemoryScope::default(),
         }
     }
 
@@ -80,6 +89,17 @@ impl AgentIdentity {
         self
     }
 
+    /// Set the memory scope for this agent.
+    pub fn with_memory_scope(mut self, scope: MemoryScope) -> Self {
+        self.memory_scope = scope;
+        self
+    }
+
+    /// `base` restricted to the memory this agent may see in `channel`.
+    pub fn workspace(&self, base: &Workspace, channel: &str) -> Workspace {
+        base.scoped(ScopedOwner::resolve(self.memory_scope, &self.name, channel))
+    }
+
     /// Check whether a given tool name is permitted for this agent.
     ///
     /// An empty `allowed_tools` list means all tools are permitted.
@@ -182,6 +202,20 @@ impl AgentRouter {
         self.agents.read().await.get(name).cloned()
     }
 
+    /// The workspace a message routed to `agent_name` from `channel` should use.
+    ///
+    /// Returns `None` for an unknown agent rather than falling back to the
+    /// unscoped `base`, so a stale routing decision can't read every agent's notes.
+    pub async fn workspace_for(
+        &self,
+        agent_name: &str,
+        base: &Workspace,
+        channel: &str,
+    ) -> Option<Workspace> {
+        let agents = self.agents.read().await;
+        agents.get(agent_name).map(|a| a.workspace(base, channel))
+    }
+
     /// List all registered agent identities.
     pub async fn list_agents(&self) -> Vec<AgentIdentity> {
         self.agents.read().await.values().cloned().collect()
@@ -389,6 +423,7 @@ impl AgentRouter {
 mod tests {
     use crate::agent::multi_agent::{AgentIdentity, AgentRouter, RoutingStrategy};
     use crate::channels::IncomingMessage;
+    use crate::workspace::MemoryScope;
 
     fn make_default_agent() -> AgentIdentity {
         AgentIdentity::new(
@@ -432,6 +467,7 @@ mod tests {
         assert!(agent.enabled);
         assert_eq!(agent.priority, 0);
         assert!(agent.allowed_tools.is_empty());
+        assert_eq!(agent.memory_scope, MemoryScope::Private);
     }
 
     #[test]
@@ -716,6 +752,71 @@ mod tests {
         assert_eq!(deserialized.workspace_prefix, agent.workspace_prefix);
         assert_eq!(deserialized.enabled, agent.enabled);
         assert_eq!(deserialized.priority, agent.priority);
+        assert_eq!(deserialized.memory_scope, agent.memory_scope);
+
+        // Identities saved before memory scopes existed default to private.
+        let mut legacy = serde_json::to_value(&agent).unwrap();
+        legacy.as_object_mut().unwrap().remove("memory_scope");
+        let legacy: AgentIdentity = serde_json::from_value(legacy).unwrap();
+        assert_eq!(legacy.memory_scope, MemoryScope::Private);
+    }
+
+    #[cfg(feature = "libsql")]
+    #[tokio::test]
+    async fn test_memory_tools_see_only_the_routed_agents_memory() {
+        use std::sync::Arc;
+
+        use crate::context::JobContext;
+        use crate::db::Database;
+        use crate::db::libsql_backend::LibSqlBackend;
+        use crate::tools::Tool;
+        use crate::tools::builtin::{MemoryReadTool, MemoryWriteTool};
+        use crate::workspace::Workspace;
+
+        let dir = tempfile::tempdir().unwrap();
+        let backend = LibSqlBackend::new_local(&dir.path().join("memory.db"))
+            .await
+            .unwrap();
+        backend.run_migrations().await.unwrap();
+        let base = Arc::new(Workspace::new_with_db("default", Arc::new(backend)));
+
+        let router = AgentRouter::new(make_default_agent());
+        router.register_agent(make_coder_agent()).await;
+        router.register_agent(make_researcher_agent()).await;
+
+        // The context the agent loop builds for a message's turn.
+        async fn turn_ctx(router: &AgentRouter, base: &Workspace, content: &str) -> JobContext {
+            let message = IncomingMessage::new("cli", "user1", content);
+            let decision = router.route(&message).await.unwrap();
+            let workspace = router
+                .workspace_for(&decision.agent_name, base, &message.channel)
+                .await
+                .unwrap();
+            let mut ctx = JobContext::with_user("default", "chat", "test");
+            ctx.memory = Some(workspace.owner());
+            ctx
+        }
+        let coder = turn_ctx(&router, &base, "@coder remember the deploy key rotation").await;
+        let researcher = turn_ctx(&router, &base, "@researcher what did the coder write?").await;
+
+        let write = MemoryWriteTool::new(Arc::clone(&base));
+        let read = MemoryReadTool::new(Arc::clone(&base));
+        write
+            .execute(
+                serde_json::json!({
+                    "content": "Deploy keys rotate on Fridays",
+                    "target": "notes.md",
+                    "append": false,
+                }),
+                &coder,
+            )
+            .await
+            .unwrap();
+
+        let params = serde_json::json!({ "path": "notes.md" });
+        assert!(read.execute(params.clone(), &coder).await.is_ok());
+        assert!(read.execute(params.clone(), &researcher).await.is_err());
+        assert!(!base.exists("notes.md").await.unwrap());
     }
 
     #[tokio::test]
@@ -0,0 +1,169 @@
+//! Scheduling priorities: interactive turns before background work.
+//!
+//! Job workers and routine runs are spawned with [`spawn_background`]. Once
+//! [`init_background`] has run they execute on a dedicated runtime whose
+//! worker threads run at a lower OS priority (on Linux), so the main
+//! runtime's threads stay free for channels and the agent loop. A semaphore
+//! caps how many background tasks run at once; the rest wait their turn.
+//! Long loops call [`yield_every`] so one task cannot pin a worker thread.
+//!
+//! Without `init_background` (tests, one-shot CLI commands) background tasks
+//! are plain `tokio::spawn`s.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use serde::Serialize;
+use tokio::runtime::Runtime;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+
+use crate::config::SchedulingConfig;
+
+/// The background runtime and its admission control.
+struct BackgroundPool {
+    runtime: Runtime,
+    permits: Arc<Semaphore>,
+    threads: usize,
+    max_tasks: usize,
+    running: Arc<AtomicUsize>,
+    queued: Arc<AtomicUsize>,
+}
+
+static POOL: OnceLock<BackgroundPool> = OnceLock::new();
+
+/// Snapshot of the background runtime, shown in `/status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BackgroundStats {
+    pub threads: usize,
+    pub max_tasks: usize,
+    pub running: usize,
+    /// Tasks waiting for a slot.
+    pub queued: usize,
+}
+
+/// Decrements a counter when dropped, so aborted tasks are not counted.
+struct CountGuard(Arc<AtomicUsize>);
+
+impl CountGuard {
+    fn new(counter: &Arc<AtomicUsize>) -> Self {
+        counter.fetch_add(1, Ordering::AcqRel);
+        Self(Arc::clone(counter))
+    }
+}
+
+impl Drop for CountGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Start the background runtime. Later calls are no-ops.
+pub fn init_background(config: &SchedulingConfig) -> std::io::Result<()> {
+    if !config.enabled || POOL.get().is_some() {
+        return Ok(());
+    }
+    #[cfg(target_os = "linux")]
+    let nice = config.nice;
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(config.threads)
+        .thread_name("ironclaw-background")
+        .on_thread_start(move || {
+            // On Linux the nice value is per thread, so only these threads
+            // are deprioritized.
+            #[cfg(target_os = "linux")]
+            if nice > 0 {
+                // SAFETY: nice() only changes the calling thread's priority.
+                unsafe {
+                    libc::nice(nice);
+                }
+            }
+        })
+        .enable_all()
+        .build()?;
+    let _ = POOL.set(BackgroundPool {
+        runtime,
+        permits: Arc::new(Semaphore::new(config.max_tasks)),
+        threads: config.threads,
+        max_tasks: config.max_tasks,
+        running: Arc::new(AtomicUsize::new(0)),
+        queued: Arc::new(AtomicUsize::new(0)),
+    });
+    tracing::info!(
+        threads = config.threads,
+        max_tasks = config.max_tasks,
+        "Background runtime started"
+    );
+    Ok(())
+}
+
+/// Spawn background work: on the background runtime once it is running and
+/// a slot is free, otherwise on the current runtime.
+pub fn spawn_background<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let Some(pool) = POOL.get() else {
+        return tokio::spawn(future);
+    };
+    let permits = Arc::clone(&pool.permits);
+    let running = Arc::clone(&pool.running);
+    let waiting = CountGuard::new(&pool.queued);
+    pool.runtime.spawn(async move {
+        // The semaphore is never closed.
+        let _permit = permits.acquire_owned().await;
+        drop(waiting);
+        let _running = CountGuard::new(&running);
+        future.await
+    })
+}
+
+/// State of the background runtime, if it was started.
+pub fn background_stats() -> Option<BackgroundStats> {
+    POOL.get().map(|pool| BackgroundStats {
+        threads: pool.threads,
+        max_tasks: pool.max_tasks,
+        running: pool.running.load(Ordering::Acquire),
+        queued: pool.queued.load(Ordering::Acquire),
+    })
+}
+
+/// Yield to the scheduler every `every` calls. Call once per iteration of
+/// loops that may run long without awaiting anything that blocks.
+pub async fn yield_every(counter: &mut u32, every: u32) {
+    *counter = counter.wrapping_add(1);
+    if every <= 1 || (*counter).is_multiple_of(every) {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fallback_and_yield_every() {
+        // Tests never start the pool, so work runs on the test runtime.
+        assert!(background_stats().is_none());
+        assert_eq!(spawn_background(async { 41 + 1 }).await.unwrap(), 42);
+
+        let mut counter = 0;
+        for _ in 0..10 {
+            yield_every(&mut counter, 4).await;
+        }
+        assert_eq!(counter, 10);
+    }
+
+    #[test]
+    fn test_count_guard() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let guard = CountGuard::new(&counter);
+        let second = CountGuard::new(&counter);
+        assert_eq!(counter.load(Ordering::Acquire), 2);
+        drop(guard);
+        drop(second);
+        assert_eq!(counter.load(Ordering::Acquire), 0);
+    }
+}
@@ -0,0 +1,487 @@
+//! Turn-level latency profiling.
+//!
+//! When enabled, the agent loop records how long each phase of a user turn
+//! takes (routing, compaction, prompt build, each LLM call, each tool,
+//! output sanitization, delivery). Finished turns are appended as JSON lines
+//! to `~/.ironclaw/logs/turns.jsonl`, where `ironclaw logs turn <id> --timing`
+//! renders a waterfall for one turn and `ironclaw logs turns` aggregates
+//! p50/p95 latency per phase. Profiles can also be exported in folded-stack
+//! format for flamegraph tools.
+//!
+//! Messages are handled one at a time by the agent loop, so the profiler
+//! tracks a single active turn.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Profile files larger than this are rotated to `turns.jsonl.1`.
+const MAX_PROFILE_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// A phase of a turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Parsing, session resolution, safety checks, dedup, checkpointing.
+    Routing,
+    /// Automatic context compaction before the turn.
+    Compaction,
+    /// Loading the system prompt and assembling the LLM request.
+    PromptBuild,
+    /// A single LLM call.
+    Llm,
+    /// A single tool execution.
+    Tool,
+    /// Sanitizing tool output before it reaches the LLM.
+    Sanitization,
+    /// Sending the response back through the channel.
+    Delivery,
+}
+
+impl Phase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Routing => "routing",
+            Phase::Compaction => "compaction",
+            Phase::PromptBuild => "prompt_build",
+            Phase::Llm => "llm",
+            Phase::Tool => "tool",
+            Phase::Sanitization => "sanitization",
+            Phase::Delivery => "delivery",
+        }
+    }
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A timed span within a turn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseSpan {
+    pub phase: Phase,
+    /// Tool name for tool/sanitization spans, iteration for LLM spans.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Offset from the start of the turn, in milliseconds.
+    pub start_ms: f64,
+    pub duration_ms: f64,
+}
+
+/// Timing profile of one completed turn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnProfile {
+    pub id: Uuid,
+    pub started_at: DateTime<Utc>,
+    pub channel: String,
+    pub total_ms: f64,
+    pub spans: Vec<PhaseSpan>,
+}
+
+impl TurnProfile {
+    /// Milliseconds spent in each phase, summed across spans.
+    pub fn phase_totals(&self) -> BTreeMap<Phase, f64> {
+        let mut totals = BTreeMap::new();
+        for span in &self.spans {
+            *totals.entry(span.phase).or_insert(0.0) += span.duration_ms;
+        }
+        totals
+    }
+
+    /// Render a text waterfall of the turn's spans.
+    pub fn render_timing(&self, width: usize) -> String {
+        let width = width.max(10);
+        let mut out = format!(
+            "Turn {} ({}, {})\nTotal: {:.1} ms\n\n",
+            self.id,
+            self.channel,
+            self.started_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            self.total_ms
+        );
+        let scale = if self.total_ms > 0.0 {
+            width as f64 / self.total_ms
+        } else {
+            0.0
+        };
+        for span in &self.spans {
+            let name = match &span.label {
+                Some(label) => format!("{}:{}", span.phase, label),
+                None => span.phase.to_string(),
+            };
+            let offset = ((span.start_ms * scale).round() as usize).min(width - 1);
+            let len = ((span.duration_ms * scale).round() as usize).clamp(1, width - offset);
+            out.push_str(&format!(
+                "{:<28} {:>9.1} ms |{}{}{}|\n",
+                truncate(&name, 28),
+                span.duration_ms,
+                " ".repeat(offset),
+                "#".repeat(len),
+                " ".repeat(width - offset - len)
+            ));
+        }
+
+        out.push_str("\nBy phase:\n");
+        for (phase, ms) in self.phase_totals() {
+            let pct = if self.total_ms > 0.0 {
+                ms / self.total_ms * 100.0
+            } else {
+                0.0
+            };
+            out.push_str(&format!("  {:<14} {:>9.1} ms {:>5.1}%\n", phase, ms, pct));
+        }
+        out
+    }
+
+    /// Folded-stack lines (`turn;phase;label micros`) for flamegraph tools.
+    pub fn to_folded(&self) -> String {
+        let mut stacks: BTreeMap<String, f64> = BTreeMap::new();
+        let mut accounted = 0.0;
+        for span in &self.spans {
+            let stack = match &span.label {
+                Some(label) => format!("turn;{};{}", span.phase, label.replace([';', ' '], "_")),
+                None => format!("turn;{}", span.phase),
+            };
+            *stacks.entry(stack).or_insert(0.0) += span.duration_ms;
+            accounted += span.duration_ms;
+        }
+        let untracked = self.total_ms - accounted;
+        if untracked > 0.0 {
+            stacks.insert("turn".to_string(), untracked);
+        }
+        stacks
+            .into_iter()
+            .map(|(stack, ms)| format!("{} {}\n", stack, (ms * 1000.0).round() as u64))
+            .collect()
+    }
+}
+
+/// p50/p95 latency for one phase across many turns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseStats {
+    /// Phase name, or `total` for whole turns.
+    pub name: String,
+    /// Number of turns that included the phase.
+    pub turns: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Aggregate per-phase latency (summed per turn) across profiles.
+///
+/// The first entry is always the whole-turn `total`.
+pub fn aggregate(profiles: &[TurnProfile]) -> Vec<PhaseStats> {
+    let mut per_phase: BTreeMap<Phase, Vec<f64>> = BTreeMap::new();
+    let mut totals = Vec::with_capacity(profiles.len());
+    for profile in profiles {
+        totals.push(profile.total_ms);
+        for (phase, ms) in profile.phase_totals() {
+            per_phase.entry(phase).or_default().push(ms);
+        }
+    }
+
+    let mut stats = Vec::new();
+    if !totals.is_empty() {
+        stats.push(phase_stats("total", totals));
+    }
+    for (phase, samples) in per_phase {
+        stats.push(phase_stats(phase.as_str(), samples));
+    }
+    stats
+}
+
+fn phase_stats(name: &str, mut samples: Vec<f64>) -> PhaseStats {
+    samples.sort_by(|a, b| a.total_cmp(b));
+    PhaseStats {
+        name: name.to_string(),
+        turns: samples.len(),
+        p50_ms: percentile(&samples, 0.50),
+        p95_ms: percentile(&samples, 0.95),
+        max_ms: samples.last().copied().unwrap_or(0.0),
+    }
+}
+
+/// Nearest-rank percentile of sorted samples.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Default location of the turn profile log.
+pub fn default_profile_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".ironclaw")
+        .join("logs")
+        .join("turns.jsonl")
+}
+
+/// Read profiles from a turn profile log, oldest first.
+///
+/// Malformed lines are skipped.
+pub fn read_profiles(path: &Path) -> std::io::Result<Vec<TurnProfile>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+struct ActiveTurn {
+    id: Uuid,
+    started: Instant,
+    started_at: DateTime<Utc>,
+    channel: String,
+    spans: Vec<PhaseSpan>,
+}
+
+/// Records phase timings for the turn currently being handled.
+pub struct TurnProfiler {
+    path: Option<PathBuf>,
+    current: Mutex<Option<ActiveTurn>>,
+}
+
+impl TurnProfiler {
+    /// Create a profiler that appends finished turns to `path`.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path: Some(path),
+            current: Mutex::new(None),
+        }
+    }
+
+    /// Create a profiler that records nothing.
+    pub fn disabled() -> Self {
+        Self {
+            path: None,
+            current: Mutex::new(None),
+        }
+    }
+
+    /// Whether profiling is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Start profiling a turn. Any unfinished turn is discarded.
+    pub fn begin(&self, channel: &str) -> Option<Uuid> {
+        self.path.as_ref()?;
+        let id = Uuid::new_v4();
+        *self.lock() = Some(ActiveTurn {
+            id,
+            started: Instant::now(),
+            started_at: Utc::now(),
+            channel: channel.to_string(),
+            spans: Vec::new(),
+        });
+        Some(id)
+    }
+
+    /// Record a span that started at `started` and ends now.
+    ///
+    /// Does nothing when no turn is being profiled.
+    pub fn record(&self, phase: Phase, label: Option<&str>, started: Instant) {
+        let mut current = self.lock();
+        let Some(turn) = current.as_mut() else {
+            return;
+        };
+        let start_ms = started
+            .saturating_duration_since(turn.started)
+            .as_secs_f64()
+            * 1000.0;
+        turn.spans.push(PhaseSpan {
+            phase,
+            label: label.map(String::from),
+            start_ms,
+            duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+        });
+    }
+
+    /// Record a span from the end of the previous span (or the start of the
+    /// turn) until now.
+    pub fn mark(&self, phase: Phase, label: Option<&str>) {
+        let started = {
+            let current = self.lock();
+            let Some(turn) = current.as_ref() else {
+                return;
+            };
+            turn.spans
+                .last()
+                .map(|s| {
+                    turn.started
+                        + std::time::Duration::from_secs_f64((s.start_ms + s.duration_ms) / 1000.0)
+                })
+                .unwrap_or(turn.started)
+        };
+        self.record(phase, label, started);
+    }
+
+    /// Drop the active turn without saving it.
+    pub fn discard(&self) {
+        self.lock().take();
+    }
+
+    /// Finish the active turn and append it to the profile log.
+    pub fn finish(&self) -> Option<TurnProfile> {
+        let turn = self.lock().take()?;
+        let profile = TurnProfile {
+            id: turn.id,
+            started_at: turn.started_at,
+            channel: turn.channel,
+            total_ms: turn.started.elapsed().as_secs_f64() * 1000.0,
+            spans: turn.spans,
+        };
+        tracing::debug!(
+            "Turn {} took {:.1} ms ({} spans)",
+            profile.id,
+            profile.total_ms,
+            profile.spans.len()
+        );
+        if let Some(path) = &self.path
+            && let Err(e) = append_profile(path, &profile)
+        {
+            tracing::warn!("Failed to write turn profile to {}: {}", path.display(), e);
+        }
+        Some(profile)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<ActiveTurn>> {
+        match self.current.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+fn append_profile(path: &Path, profile: &TurnProfile) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::metadata(path).is_ok_and(|m| m.len() > MAX_PROFILE_FILE_BYTES) {
+        let mut rotated = path.as_os_str().to_owned();
+        rotated.push(".1");
+        std::fs::rename(path, PathBuf::from(rotated))?;
+    }
+    let line = serde_json::to_string(profile)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", line)
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        let cut: String = s.chars().take(max - 1).collect();
+        format!("{}…", cut)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(total: f64, spans: &[(Phase, Option<&str>, f64, f64)]) -> TurnProfile {
+        TurnProfile {
+            id: Uuid::new_v4(),
+            started_at: Utc::now(),
+            channel: "repl".to_string(),
+            total_ms: total,
+            spans: spans
+                .iter()
+                .map(|(phase, label, start, dur)| PhaseSpan {
+                    phase: *phase,
+                    label: label.map(String::from),
+                    start_ms: *start,
+                    duration_ms: *dur,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_profiler_records_and_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("turns.jsonl");
+        let profiler = TurnProfiler::new(path.clone());
+
+        let id = profiler.begin("repl").unwrap();
+        profiler.mark(Phase::Routing, None);
+        profiler.record(Phase::Tool, Some("echo"), Instant::now());
+        let finished = profiler.finish().unwrap();
+        assert_eq!(finished.id, id);
+        assert_eq!(finished.spans.len(), 2);
+
+        // Nothing active after finish.
+        profiler.record(Phase::Llm, None, Instant::now());
+        assert!(profiler.finish().is_none());
+
+        let read = read_profiles(&path).unwrap();
+        assert_eq!(read.len(), 1);
+        assert_eq!(read[0].id, id);
+        assert_eq!(read[0].spans[1].label.as_deref(), Some("echo"));
+    }
+
+    #[test]
+    fn test_disabled_profiler_is_noop() {
+        let profiler = TurnProfiler::disabled();
+        assert!(profiler.begin("repl").is_none());
+        profiler.record(Phase::Llm, None, Instant::now());
+        assert!(profiler.finish().is_none());
+    }
+
+    #[test]
+    fn test_aggregate_percentiles() {
+        let profiles: Vec<TurnProfile> = (1..=20)
+            .map(|i| {
+                let ms = i as f64 * 10.0;
+                profile(ms + 5.0, &[(Phase::Llm, None, 0.0, ms)])
+            })
+            .collect();
+        let stats = aggregate(&profiles);
+        assert_eq!(stats[0].name, "total");
+        let llm = stats.iter().find(|s| s.name == "llm").unwrap();
+        assert_eq!(llm.turns, 20);
+        assert_eq!(llm.p50_ms, 100.0);
+        assert_eq!(llm.p95_ms, 190.0);
+        assert_eq!(llm.max_ms, 200.0);
+    }
+
+    #[test]
+    fn test_timing_and_folded_output() {
+        let p = profile(
+            100.0,
+            &[
+                (Phase::Routing, None, 0.0, 10.0),
+                (Phase::Llm, Some("1"), 10.0, 60.0),
+                (Phase::Tool, Some("http get"), 70.0, 20.0),
+            ],
+        );
+        let timing = p.render_timing(40);
+        assert!(timing.contains("llm:1"));
+        assert!(timing.contains("By phase:"));
+
+        let folded = p.to_folded();
+        assert!(folded.contains("turn;llm;1 60000\n"));
+        assert!(folded.contains("turn;tool;http_get 20000\n"));
+        assert!(folded.contains("turn 10000\n"));
+    }
+}
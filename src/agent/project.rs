@@ -0,0 +1,254 @@
+//! Project binding for sessions.
+//!
+//! `/project use <dir>` binds a session to a host project directory. While
+//! bound:
+//! - relative paths in file tools and the shell's working directory default
+//!   to the project root,
+//! - sandbox jobs mount the project root instead of a fresh directory,
+//! - a summary of the project's AGENTS.md/README.md is added to the system
+//!   prompt,
+//! - the optional `.ironclaw/project.json` policy can disable tools or force
+//!   per-call approval for them.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Per-project policy file, relative to the project root.
+pub const POLICY_FILE: &str = ".ironclaw/project.json";
+
+/// Files read (in order) to build the project context summary.
+const CONTEXT_FILES: &[&str] = &["AGENTS.md", "README.md"];
+
+/// Maximum characters of context documents handed to the summarizer.
+pub const MAX_CONTEXT_DOC_CHARS: usize = 12_000;
+
+/// Maximum characters of the fallback excerpt when summarization fails.
+const MAX_EXCERPT_CHARS: usize = 2_000;
+
+/// Tool policy declared by a project.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectPolicy {
+    /// Tools hidden from the LLM and refused while bound.
+    #[serde(default)]
+    pub disabled_tools: Vec<String>,
+    /// Tools that need explicit approval on every call, even if the session
+    /// auto-approved them.
+    #[serde(default)]
+    pub require_approval: Vec<String>,
+}
+
+impl ProjectPolicy {
+    /// Load the policy from a project root. A missing file is an empty policy.
+    pub fn load(root: &Path) -> Result<Self, String> {
+        let path = root.join(POLICY_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(raw) => {
+                serde_json::from_str(&raw).map_err(|e| format!("invalid {}: {}", path.display(), e))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("cannot read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Whether the project disables a tool.
+    pub fn is_disabled(&self, tool: &str) -> bool {
+        self.disabled_tools.iter().any(|t| t == tool)
+    }
+
+    /// Whether the project forces approval for a tool.
+    pub fn requires_approval(&self, tool: &str) -> bool {
+        self.require_approval.iter().any(|t| t == tool)
+    }
+
+    /// Whether the policy has no rules.
+    pub fn is_empty(&self) -> bool {
+        self.disabled_tools.is_empty() && self.require_approval.is_empty()
+    }
+}
+
+/// A session's binding to a host project directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectBinding {
+    /// Canonical project root.
+    pub root: PathBuf,
+    /// Display name (the root's directory name).
+    pub name: String,
+    /// Summary of the project's context documents.
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Tool policy from the project's policy file.
+    #[serde(default)]
+    pub policy: ProjectPolicy,
+}
+
+impl ProjectBinding {
+    /// Resolve a user-supplied directory and load its policy.
+    ///
+    /// The summary is filled in separately since it may need the LLM.
+    pub fn open(path: &str) -> Result<Self, String> {
+        let expanded = expand_home(path.trim());
+        let root = expanded
+            .canonicalize()
+            .map_err(|e| format!("cannot open {}: {}", expanded.display(), e))?;
+        if !root.is_dir() {
+            return Err(format!("{} is not a directory", root.display()));
+        }
+        let name = root
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| root.display().to_string());
+        let policy = ProjectPolicy::load(&root)?;
+        Ok(Self {
+            root,
+            name,
+            summary: None,
+            policy,
+        })
+    }
+
+    /// Text appended to the system prompt while the project is bound.
+    pub fn context_block(&self) -> String {
+        let mut block = format!(
+            "## Active project: {}\n\nThe session is bound to `{}`. Relative file paths \
+             and shell commands run from this directory.",
+            self.name,
+            self.root.display()
+        );
+        if let Some(ref summary) = self.summary {
+            block.push_str("\n\n");
+            block.push_str(summary);
+        }
+        if !self.policy.disabled_tools.is_empty() {
+            block.push_str(&format!(
+                "\n\nDisabled by project policy: {}.",
+                self.policy.disabled_tools.join(", ")
+            ));
+        }
+        block
+    }
+
+    /// Short status line for `/project`.
+    pub fn describe(&self) -> String {
+        let mut out = format!("Project: {} ({})", self.name, self.root.display());
+        if self.summary.is_none() {
+            out.push_str("\nNo AGENTS.md or README.md found.");
+        }
+        if !self.policy.disabled_tools.is_empty() {
+            out.push_str(&format!(
+                "\nDisabled tools: {}",
+                self.policy.disabled_tools.join(", ")
+            ));
+        }
+        if !self.policy.require_approval.is_empty() {
+            out.push_str(&format!(
+                "\nAlways ask before: {}",
+                self.policy.require_approval.join(", ")
+            ));
+        }
+        out
+    }
+}
+
+/// Expand a leading `~` to the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    if path == "~" {
+        return dirs::home_dir().unwrap_or_else(|| PathBuf::from(path));
+    }
+    if let Some(rest) = path.strip_prefix("~/")
+        && let Some(home) = dirs::home_dir()
+    {
+        return home.join(rest);
+    }
+    PathBuf::from(path)
+}
+
+/// Read the project's context documents, capped for summarization.
+pub fn read_context_docs(root: &Path) -> Option<String> {
+    let mut combined = String::new();
+    for file in CONTEXT_FILES {
+        if let Ok(content) = std::fs::read_to_string(root.join(file))
+            && !content.trim().is_empty()
+        {
+            combined.push_str(&format!("# {}\n\n{}\n\n", file, content.trim()));
+        }
+    }
+    if combined.is_empty() {
+        return None;
+    }
+    Some(truncate_chars(&combined, MAX_CONTEXT_DOC_CHARS))
+}
+
+/// Fallback summary: the leading part of the context documents.
+pub fn excerpt(docs: &str) -> String {
+    let mut out = truncate_chars(docs.trim(), MAX_EXCERPT_CHARS);
+    if docs.trim().chars().count() > MAX_EXCERPT_CHARS {
+        out.push_str("\n…");
+    }
+    out
+}
+
+fn truncate_chars(s: &str, max: usize) -> String {
+    match s.char_indices().nth(max) {
+        Some((idx, _)) => s[..idx].to_string(),
+        None => s.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_reads_policy_and_context() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".ironclaw")).unwrap();
+        std::fs::write(
+            dir.path().join(POLICY_FILE),
+            r#"{"disabled_tools": ["shell"], "require_approval": ["write_file"]}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("AGENTS.md"), "Run `make test`.").unwrap();
+        std::fs::write(dir.path().join("README.md"), "Acme widgets.").unwrap();
+
+        let binding = ProjectBinding::open(dir.path().to_str().unwrap()).unwrap();
+        assert!(binding.policy.is_disabled("shell"));
+        assert!(binding.policy.requires_approval("write_file"));
+        assert!(!binding.policy.is_disabled("read_file"));
+
+        let docs = read_context_docs(&binding.root).unwrap();
+        assert!(docs.find("# AGENTS.md").unwrap() < docs.find("# README.md").unwrap());
+        assert!(
+            binding
+                .context_block()
+                .contains("Disabled by project policy: shell")
+        );
+    }
+
+    #[test]
+    fn test_open_rejects_missing_and_files() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(ProjectBinding::open(dir.path().join("nope").to_str().unwrap()).is_err());
+
+        let file = dir.path().join("file.txt");
+        std::fs::write(&file, "x").unwrap();
+        assert!(ProjectBinding::open(file.to_str().unwrap()).is_err());
+
+        std::fs::create_dir_all(dir.path().join(".ironclaw")).unwrap();
+        std::fs::write(dir.path().join(POLICY_FILE), "not json").unwrap();
+        assert!(ProjectBinding::open(dir.path().to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_excerpt_and_home_expansion() {
+        let long = "a".repeat(MAX_EXCERPT_CHARS + 10);
+        assert!(excerpt(&long).ends_with('…'));
+        assert_eq!(excerpt("short"), "short");
+        assert!(read_context_docs(tempfile::tempdir().unwrap().path()).is_none());
+
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(expand_home("~/code"), home.join("code"));
+        }
+        assert_eq!(expand_home("/tmp/x"), PathBuf::from("/tmp/x"));
+    }
+}
@@ -0,0 +1,159 @@
+//! Recovery from failures partway through a turn.
+//!
+//! When the LLM call inside the agentic loop fails with a transient error
+//! (a provider 5xx, a dropped connection, a malformed reply), the turn does
+//! not start over or give up. It resumes from its current context, which
+//! still holds every tool result gathered so far, with a short note about
+//! the failure appended so the model picks up where it broke off instead of
+//! repeating work. Each turn has a fixed budget of recoveries, and every
+//! one is recorded on the turn.
+
+use std::time::Duration;
+
+use crate::agent::agent_loop::truncate_for_preview;
+use crate::error::LlmError;
+use crate::llm::ChatMessage;
+
+/// Maximum length of the error excerpt placed in the recovery note.
+const MAX_ERROR_CHARS: usize = 200;
+
+/// Longest rate-limit wait honoured before resuming.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(30);
+
+/// Pause before resuming, multiplied by the attempt number.
+const BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Recoveries left for one turn.
+#[derive(Debug, Clone)]
+pub struct RecoveryBudget {
+    max_attempts: u32,
+    used: u32,
+}
+
+impl RecoveryBudget {
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            used: 0,
+        }
+    }
+
+    /// Spend an attempt on `error`, returning its 1-based number, or `None`
+    /// if the error is not worth resuming from or the budget is spent.
+    pub fn take(&mut self, error: &LlmError) -> Option<u32> {
+        if !is_recoverable(error) || self.used >= self.max_attempts {
+            return None;
+        }
+        self.used += 1;
+        Some(self.used)
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+}
+
+/// Whether resuming with the same context could succeed.
+///
+/// Auth and configuration failures and an overflowing context fail the
+/// same way on every attempt, so they end the turn.
+pub fn is_recoverable(error: &LlmError) -> bool {
+    match error {
+        LlmError::RequestFailed { .. }
+        | LlmError::RateLimited { .. }
+        | LlmError::InvalidResponse { .. }
+        | LlmError::SessionExpired { .. }
+        | LlmError::Http(_)
+        | LlmError::Json(_)
+        | LlmError::Io(_) => true,
+        LlmError::ContextLengthExceeded { .. }
+        | LlmError::ModelNotAvailable { .. }
+        | LlmError::AuthFailed { .. }
+        | LlmError::SessionRenewalFailed { .. } => false,
+    }
+}
+
+/// How long to wait before recovery `attempt`.
+pub fn delay(error: &LlmError, attempt: u32) -> Duration {
+    match error {
+        LlmError::RateLimited {
+            retry_after: Some(wait),
+            ..
+        } => (*wait).min(MAX_RATE_LIMIT_WAIT),
+        _ => BASE_DELAY * attempt,
+    }
+}
+
+/// One-line summary of the error for the context and the turn's log.
+pub fn summarize(error: &LlmError) -> String {
+    truncate_for_preview(&error.to_string(), MAX_ERROR_CHARS)
+}
+
+/// Note appended to the context before the turn resumes.
+pub fn recovery_note(summary: &str) -> ChatMessage {
+    ChatMessage::user(format!(
+        "[The previous step failed and is being retried: {}. The tool results \
+         above are complete. Continue the task from there; do not repeat tool \
+         calls that already succeeded.]",
+        summary
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request_failed() -> LlmError {
+        LlmError::RequestFailed {
+            provider: "openai".to_string(),
+            reason: "HTTP 500: upstream error".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_budget_is_bounded() {
+        let mut budget = RecoveryBudget::new(2);
+        assert_eq!(budget.take(&request_failed()), Some(1));
+        assert_eq!(budget.take(&request_failed()), Some(2));
+        assert_eq!(budget.take(&request_failed()), None);
+
+        let mut none = RecoveryBudget::new(0);
+        assert_eq!(none.take(&request_failed()), None);
+    }
+
+    #[test]
+    fn test_permanent_errors_are_not_retried() {
+        let mut budget = RecoveryBudget::new(3);
+        let auth = LlmError::AuthFailed {
+            provider: "anthropic".to_string(),
+        };
+        let overflow = LlmError::ContextLengthExceeded {
+            used: 210_000,
+            limit: 200_000,
+        };
+        assert_eq!(budget.take(&auth), None);
+        assert_eq!(budget.take(&overflow), None);
+        // Refusals don't spend the budget.
+        assert_eq!(budget.take(&request_failed()), Some(1));
+    }
+
+    #[test]
+    fn test_delay_honours_capped_rate_limit() {
+        let limited = |secs| LlmError::RateLimited {
+            provider: "openai".to_string(),
+            retry_after: Some(Duration::from_secs(secs)),
+        };
+        assert_eq!(delay(&limited(5), 1), Duration::from_secs(5));
+        assert_eq!(delay(&limited(600), 1), MAX_RATE_LIMIT_WAIT);
+        assert_eq!(delay(&request_failed(), 3), BASE_DELAY * 3);
+    }
+
+    #[test]
+    fn test_note_carries_summary() {
+        let summary = summarize(&request_failed());
+        assert!(summary.contains("HTTP 500"));
+        let note = recovery_note(&summary);
+        assert!(note.content.contains("HTTP 500"));
+        assert!(note.content.contains("do not repeat"));
+    }
+}
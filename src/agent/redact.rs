@@ -0,0 +1,465 @@
+//! Redaction of conversation turns (`/redact last [n]`).
+//!
+//! For the moment someone pastes a password into the chat: the last turns
+//! are removed from the thread and the persisted conversation, and a marker
+//! turn takes their place so the transcript shows that something was taken
+//! out. The text is then scrubbed from everywhere else it may have landed:
+//!
+//! - workspace documents, which are re-chunked and re-embedded on write
+//!   (this covers indexed conversation history and compaction summaries)
+//! - turns still queued for history indexing
+//! - the gateway's buffer of recent log entries
+//! - log files under `~/.ironclaw/logs`
+//!
+//! Scrubbing replaces the whole text of each removed message and any
+//! secret-looking token the user typed with `[redacted]`. Short messages
+//! are only removed, never scrubbed elsewhere, so a "yes please" does not
+//! wipe every other "yes please" in memory.
+
+use std::path::{Path, PathBuf};
+
+use uuid::Uuid;
+
+use crate::agent::session::Turn;
+use crate::db::Database;
+use crate::error::{DatabaseError, Error};
+use crate::history::ConversationMessage;
+use crate::safety::LeakDetector;
+use crate::workspace::Workspace;
+
+/// What scrubbed text is replaced with.
+pub const REDACTED: &str = "[redacted]";
+
+/// User side of the marker turn left in place of redacted turns.
+pub const REDACTED_INPUT: &str = "[message redacted]";
+
+/// Messages shorter than this are not scrubbed outside the conversation.
+const MIN_WHOLE_MESSAGE_CHARS: usize = 20;
+
+/// Shortest token that can look like a secret.
+const MIN_TOKEN_CHARS: usize = 8;
+
+/// Shortest letters-and-digits token (hex keys, base32 codes) scrubbed.
+const MIN_ALPHANUMERIC_TOKEN_CHARS: usize = 16;
+
+/// Assistant side of the marker turn.
+pub fn marker_response(turns: usize) -> String {
+    format!(
+        "[{} turn(s) removed from this conversation by the user]",
+        turns
+    )
+}
+
+/// The text of a set of removed turns and what to scrub for it.
+#[derive(Debug, Clone)]
+pub struct Redaction {
+    /// Turns removed.
+    pub turns: usize,
+    /// User inputs of the removed turns.
+    pub user_inputs: Vec<String>,
+    /// Every message of the removed turns, user and assistant.
+    pub messages: Vec<String>,
+    /// Text scrubbed from memory and logs, longest first.
+    pub fragments: Vec<String>,
+}
+
+impl Redaction {
+    pub fn new(turns: &[Turn]) -> Self {
+        let user_inputs: Vec<String> = turns.iter().map(|t| t.user_input.clone()).collect();
+        let messages: Vec<String> = turns
+            .iter()
+            .flat_map(|t| std::iter::once(t.user_input.clone()).chain(t.response.clone()))
+            .collect();
+        let fragments = fragments(&user_inputs, &messages);
+        Self {
+            turns: turns.len(),
+            user_inputs,
+            messages,
+            fragments,
+        }
+    }
+
+    /// Replace the fragments in `text`; `None` when there is nothing to scrub.
+    pub fn scrub(&self, text: &str) -> Option<String> {
+        scrub(text, &self.fragments)
+    }
+
+    /// Remove the turns from the persisted conversation, then scrub the
+    /// workspace and log files. Log files that cannot be rewritten are
+    /// skipped with a warning.
+    pub async fn apply(
+        &self,
+        db: Option<&dyn Database>,
+        conversation_id: Uuid,
+        workspace: Option<&Workspace>,
+        log_dir: &Path,
+    ) -> Result<RedactionReport, Error> {
+        let mut report = RedactionReport {
+            turns: self.turns,
+            ..Default::default()
+        };
+        if let Some(db) = db {
+            report.messages_deleted = self.redact_conversation(db, conversation_id).await?;
+        }
+        if let Some(ws) = workspace {
+            report.documents_scrubbed = self.scrub_workspace(ws).await?;
+        }
+        report.log_files_scrubbed = self.scrub_log_files(log_dir);
+        Ok(report)
+    }
+
+    /// Delete the turns' messages from a persisted conversation, append the
+    /// marker turn and drop the provider response chain, which still
+    /// references the removed messages. Returns how many messages were
+    /// deleted.
+    async fn redact_conversation(
+        &self,
+        db: &dyn Database,
+        conversation_id: Uuid,
+    ) -> Result<u64, DatabaseError> {
+        let persisted = db.list_conversation_messages(conversation_id).await?;
+        let ids = matching_messages(&persisted, &self.messages);
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let deleted = db
+            .delete_conversation_messages(conversation_id, &ids)
+            .await?;
+        db.add_conversation_message(conversation_id, "user", REDACTED_INPUT)
+            .await?;
+        db.add_conversation_message(conversation_id, "assistant", &marker_response(self.turns))
+            .await?;
+        db.update_conversation_metadata_field(
+            conversation_id,
+            "last_response_id",
+            &serde_json::Value::Null,
+        )
+        .await?;
+        Ok(deleted)
+    }
+
+    /// Scrub the workspace's own documents. Returns how many were rewritten.
+    async fn scrub_workspace(&self, ws: &Workspace) -> Result<usize, Error> {
+        if self.fragments.is_empty() {
+            return Ok(0);
+        }
+        let mut scrubbed = 0;
+        for path in ws.list_all().await? {
+            let doc = ws.read(&path).await?;
+            // Shared documents belong to another owner; writing would only
+            // shadow them with a scrubbed copy.
+            if doc.agent_id != ws.agent_id() {
+                continue;
+            }
+            if let Some(content) = self.scrub(&doc.content) {
+                ws.write(&path, &content).await?;
+                scrubbed += 1;
+            }
+        }
+        Ok(scrubbed)
+    }
+
+    /// Scrub `*.log` files in `dir`. Returns how many were rewritten.
+    fn scrub_log_files(&self, dir: &Path) -> usize {
+        if self.fragments.is_empty() {
+            return 0;
+        }
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return 0;
+        };
+        let mut scrubbed = 0;
+        for path in entries.flatten().map(|e| e.path()) {
+            if path.extension().and_then(|e| e.to_str()) != Some("log") {
+                continue;
+            }
+            let content = match std::fs::read(&path) {
+                Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+                Err(e) => {
+                    tracing::warn!("Cannot read {} for redaction: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if let Some(content) = self.scrub(&content) {
+                match std::fs::write(&path, content) {
+                    Ok(()) => scrubbed += 1,
+                    Err(e) => {
+                        tracing::warn!("Cannot scrub {}: {}", path.display(), e)
+                    }
+                }
+            }
+        }
+        scrubbed
+    }
+}
+
+/// What a redaction removed and rewrote.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RedactionReport {
+    pub turns: usize,
+    pub messages_deleted: u64,
+    pub documents_scrubbed: usize,
+    pub log_files_scrubbed: usize,
+    pub log_entries_scrubbed: usize,
+}
+
+impl RedactionReport {
+    /// One-line summary for the user.
+    pub fn summary(&self) -> String {
+        format!(
+            "Redacted {} turn(s): {} stored message(s) deleted, {} memory document(s), \
+             {} log file(s) and {} recent log entr{} scrubbed.",
+            self.turns,
+            self.messages_deleted,
+            self.documents_scrubbed,
+            self.log_files_scrubbed,
+            self.log_entries_scrubbed,
+            if self.log_entries_scrubbed == 1 {
+                "y"
+            } else {
+                "ies"
+            }
+        )
+    }
+}
+
+/// Default directory of the agent's log files.
+pub fn default_log_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".ironclaw")
+        .join("logs")
+}
+
+/// Text to scrub for redacted messages: each long enough message whole,
+/// plus known secret formats and secret-looking tokens from what the user
+/// typed. Longest first, so a whole message goes before the tokens in it.
+fn fragments(user_inputs: &[String], messages: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = messages
+        .iter()
+        .map(|m| m.trim())
+        .filter(|m| m.chars().count() >= MIN_WHOLE_MESSAGE_CHARS)
+        .map(String::from)
+        .collect();
+
+    let detector = LeakDetector::new();
+    for input in user_inputs {
+        for m in detector.scan(input).matches {
+            if let Some(secret) = input.get(m.location) {
+                out.push(secret.to_string());
+            }
+        }
+        for token in input.split_whitespace() {
+            let token = token.trim_matches(|c: char| {
+                matches!(
+                    c,
+                    '"' | '\'' | '`' | ',' | '.' | ';' | ':' | '(' | ')' | '[' | ']' | '<' | '>'
+                )
+            });
+            if looks_secret(token) {
+                out.push(token.to_string());
+            }
+        }
+    }
+
+    out.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    out.dedup();
+    out
+}
+
+/// Passwords and keys mix character classes; words and dates do not.
+fn looks_secret(token: &str) -> bool {
+    let len = token.chars().count();
+    if len < MIN_TOKEN_CHARS || token.contains("://") {
+        return false;
+    }
+    let lower = token.chars().any(char::is_lowercase);
+    let upper = token.chars().any(char::is_uppercase);
+    let digit = token.chars().any(|c| c.is_ascii_digit());
+    let symbol = token.chars().any(|c| !c.is_alphanumeric());
+    let classes = [lower, upper, digit, symbol].iter().filter(|c| **c).count();
+    classes >= 3 || (classes == 2 && digit && !symbol && len >= MIN_ALPHANUMERIC_TOKEN_CHARS)
+}
+
+fn scrub(text: &str, fragments: &[String]) -> Option<String> {
+    let mut scrubbed: Option<String> = None;
+    for fragment in fragments {
+        let current = scrubbed.as_deref().unwrap_or(text);
+        if current.contains(fragment.as_str()) {
+            let replaced = current.replace(fragment.as_str(), REDACTED);
+            scrubbed = Some(replaced);
+        }
+    }
+    scrubbed
+}
+
+/// IDs of the persisted messages holding `texts`, matched newest first so
+/// an earlier message with the same text is left alone.
+fn matching_messages(persisted: &[ConversationMessage], texts: &[String]) -> Vec<Uuid> {
+    let mut wanted: Vec<&str> = texts.iter().map(String::as_str).collect();
+    let mut ids = Vec::new();
+    for message in persisted.iter().rev() {
+        if wanted.is_empty() {
+            break;
+        }
+        if let Some(pos) = wanted.iter().position(|t| *t == message.content) {
+            wanted.swap_remove(pos);
+            ids.push(message.id);
+        }
+    }
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::agent::session::Thread;
+
+    fn thread_with(turns: &[(&str, &str)]) -> Thread {
+        let mut thread = Thread::new(Uuid::new_v4());
+        for (input, response) in turns {
+            thread.start_turn(*input);
+            thread.complete_turn(*response);
+        }
+        thread
+    }
+
+    #[test]
+    fn test_secret_tokens_and_whole_messages_are_scrubbed() {
+        let mut thread = thread_with(&[
+            ("what's the weather", "Sunny."),
+            (
+                "my db password is Tr0ub4dor&3, can you connect?",
+                "Connected to the database.",
+            ),
+        ]);
+        let redaction = Redaction::new(&thread.remove_last_turns(1));
+        assert_eq!(redaction.turns, 1);
+        assert_eq!(thread.turns.len(), 1);
+
+        assert!(redaction.fragments.contains(&"Tr0ub4dor&3".to_string()));
+        assert!(
+            redaction
+                .fragments
+                .contains(&"Connected to the database.".to_string())
+        );
+        // Ordinary words are left alone.
+        assert!(!redaction.fragments.iter().any(|f| f == "password"));
+
+        assert_eq!(
+            redaction.scrub("login with Tr0ub4dor&3 failed").as_deref(),
+            Some("login with [redacted] failed")
+        );
+        assert_eq!(
+            redaction
+                .scrub("user: my db password is Tr0ub4dor&3, can you connect?")
+                .as_deref(),
+            Some("user: [redacted]")
+        );
+        assert!(redaction.scrub("nothing to see").is_none());
+    }
+
+    #[test]
+    fn test_short_messages_are_not_scrubbed_elsewhere() {
+        let mut thread = thread_with(&[("yes please", "Done.")]);
+        let redaction = Redaction::new(&thread.remove_last_turns(1));
+        assert!(redaction.fragments.is_empty());
+        assert_eq!(redaction.messages, vec!["yes please", "Done."]);
+    }
+
+    #[test]
+    fn test_looks_secret() {
+        assert!(looks_secret("hunter2!x"));
+        assert!(looks_secret("Password123"));
+        assert!(looks_secret("9f86d081884c7d659a2feaa0c55ad015"));
+        assert!(!looks_secret("2026-10-18"));
+        assert!(!looks_secret("https://Example.com/a1"));
+        assert!(!looks_secret("database"));
+        assert!(!looks_secret("don't-worry"));
+    }
+
+    #[test]
+    fn test_matching_messages_prefers_newest() {
+        let message = |content: &str| ConversationMessage {
+            id: Uuid::new_v4(),
+            role: "user".to_string(),
+            content: content.to_string(),
+            created_at: Utc::now(),
+        };
+        let persisted = vec![message("ok"), message("secret"), message("ok")];
+        let ids = matching_messages(&persisted, &["ok".to_string(), "secret".to_string()]);
+        assert_eq!(ids, vec![persisted[2].id, persisted[1].id]);
+    }
+
+    #[cfg(feature = "libsql")]
+    #[tokio::test]
+    async fn test_apply_removes_messages_and_scrubs_memory() {
+        use std::sync::Arc;
+
+        use crate::db::libsql_backend::LibSqlBackend;
+
+        let dir = tempfile::tempdir().unwrap();
+        let backend = LibSqlBackend::new_local(&dir.path().join("redact.db"))
+            .await
+            .unwrap();
+        backend.run_migrations().await.unwrap();
+        let db: Arc<dyn Database> = Arc::new(backend);
+
+        let conversation = Uuid::new_v4();
+        db.ensure_conversation(conversation, "repl", "default", None)
+            .await
+            .unwrap();
+        let mut thread = thread_with(&[
+            ("plan the release", "Sure, Friday."),
+            ("the token is ghp_Zx81kq0PLm3nA7vR", "Saved."),
+        ]);
+        for turn in &thread.turns {
+            db.add_conversation_message(conversation, "user", &turn.user_input)
+                .await
+                .unwrap();
+            db.add_conversation_message(conversation, "assistant", turn.response.as_ref().unwrap())
+                .await
+                .unwrap();
+        }
+
+        let ws = Workspace::new_with_db("default", Arc::clone(&db));
+        ws.write("notes.md", "Release token: ghp_Zx81kq0PLm3nA7vR")
+            .await
+            .unwrap();
+        let logs = dir.path().join("logs");
+        std::fs::create_dir(&logs).unwrap();
+        std::fs::write(logs.join("ironclaw.log"), "got ghp_Zx81kq0PLm3nA7vR\n").unwrap();
+
+        let redaction = Redaction::new(&thread.remove_last_turns(1));
+        let report = redaction
+            .apply(Some(db.as_ref()), conversation, Some(&ws), &logs)
+            .await
+            .unwrap();
+        assert_eq!(report.messages_deleted, 2);
+        assert_eq!(report.documents_scrubbed, 1);
+        assert_eq!(report.log_files_scrubbed, 1);
+
+        let contents: Vec<String> = db
+            .list_conversation_messages(conversation)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|m| m.content)
+            .collect();
+        assert_eq!(contents.len(), 4);
+        assert!(contents.contains(&REDACTED_INPUT.to_string()));
+        assert!(!contents.iter().any(|c| c.contains("ghp_")));
+        assert_eq!(
+            ws.read("notes.md").await.unwrap().content,
+            "Release token: [redacted]"
+        );
+        let hits = ws.search("Release", 5).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(!hits[0].content.contains("ghp_"));
+        assert_eq!(
+            std::fs::read_to_string(logs.join("ironclaw.log")).unwrap(),
+            "got [redacted]\n"
+        );
+    }
+}
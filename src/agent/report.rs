@@ -0,0 +1,593 @@
+//! Agent activity reports.
+//!
+//! Compiles tool usage, jobs, LLM spend, memory edits and the busiest
+//! conversations over a time window into a report that renders as Markdown,
+//! HTML or PDF. The bundled weekly report routine ([`weekly_report_routine`])
+//! produces one on a cron schedule, saves it under `reports/` in the
+//! workspace and delivers it through the routine's notify channel;
+//! `ironclaw cron report` exports one on demand.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::agent::routine::{
+    NotifyConfig, Routine, RoutineAction, RoutineGuardrails, Trigger, next_cron_fire,
+};
+use crate::db::Database;
+use crate::error::DatabaseError;
+use crate::history::ActivityStats;
+use crate::media::PdfRenderer;
+
+/// Name of the bundled weekly report routine.
+pub const WEEKLY_REPORT_ROUTINE: &str = "weekly-activity-report";
+
+/// Default schedule for the bundled routine: Mondays at 09:00.
+pub const WEEKLY_REPORT_SCHEDULE: &str = "0 0 9 * * MON";
+
+/// A section of the activity report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportSection {
+    Tools,
+    Jobs,
+    Costs,
+    Memory,
+    Conversations,
+}
+
+impl ReportSection {
+    pub const ALL: [ReportSection; 5] = [
+        ReportSection::Tools,
+        ReportSection::Jobs,
+        ReportSection::Costs,
+        ReportSection::Memory,
+        ReportSection::Conversations,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportSection::Tools => "tools",
+            ReportSection::Jobs => "jobs",
+            ReportSection::Costs => "costs",
+            ReportSection::Memory => "memory",
+            ReportSection::Conversations => "conversations",
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            ReportSection::Tools => "Tool usage",
+            ReportSection::Jobs => "Jobs",
+            ReportSection::Costs => "Costs",
+            ReportSection::Memory => "Memory changes",
+            ReportSection::Conversations => "Notable conversations",
+        }
+    }
+
+    /// Parse a comma-separated section list ("tools,costs").
+    pub fn parse_list(s: &str) -> Result<Vec<Self>, String> {
+        let mut sections = Vec::new();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let section = part.parse()?;
+            if !sections.contains(&section) {
+                sections.push(section);
+            }
+        }
+        if sections.is_empty() {
+            return Err("no report sections given".to_string());
+        }
+        Ok(sections)
+    }
+}
+
+impl FromStr for ReportSection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ReportSection::ALL
+            .into_iter()
+            .find(|section| section.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                format!(
+                    "unknown report section '{}' (expected one of: tools, jobs, costs, memory, conversations)",
+                    s
+                )
+            })
+    }
+}
+
+/// Output format of a rendered report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Html,
+    Pdf,
+}
+
+impl ReportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "markdown",
+            ReportFormat::Html => "html",
+            ReportFormat::Pdf => "pdf",
+        }
+    }
+
+    /// Whether the rendering is binary rather than text.
+    pub fn is_binary(&self) -> bool {
+        matches!(self, ReportFormat::Pdf)
+    }
+
+    /// File extension for saved reports.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+            ReportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "html" => Ok(ReportFormat::Html),
+            "pdf" => Ok(ReportFormat::Pdf),
+            other => Err(format!(
+                "unknown report format '{}' (expected markdown, html or pdf)",
+                other
+            )),
+        }
+    }
+}
+
+/// Format-neutral building blocks, rendered to Markdown or HTML.
+enum Block {
+    Heading(String),
+    Paragraph(String),
+    List(Vec<String>),
+    Table {
+        headers: &'static [&'static str],
+        rows: Vec<Vec<String>>,
+    },
+}
+
+/// Activity over a time window.
+#[derive(Debug, Clone)]
+pub struct ActivityReport {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub sections: Vec<ReportSection>,
+    pub stats: ActivityStats,
+}
+
+impl ActivityReport {
+    /// Collect activity for the `period` ending now. `user_id` of `None`
+    /// covers every user.
+    pub async fn generate(
+        store: &dyn Database,
+        user_id: Option<&str>,
+        period: Duration,
+        sections: Vec<ReportSection>,
+    ) -> Result<Self, DatabaseError> {
+        let until = Utc::now();
+        let since = until - period;
+        let stats = store.activity_since(user_id, since).await?;
+        Ok(Self {
+            since,
+            until,
+            sections,
+            stats,
+        })
+    }
+
+    pub fn render(&self, format: ReportFormat) -> Vec<u8> {
+        match format {
+            ReportFormat::Markdown => self.to_markdown().into_bytes(),
+            ReportFormat::Html => self.to_html().into_bytes(),
+            ReportFormat::Pdf => self.to_pdf(),
+        }
+    }
+
+    pub fn to_pdf(&self) -> Vec<u8> {
+        PdfRenderer::default().render_markdown(&self.to_markdown())
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n", self.title());
+        for block in self.blocks() {
+            out.push('\n');
+            match block {
+                Block::Heading(text) => out.push_str(&format!("## {}\n", text)),
+                Block::Paragraph(text) => out.push_str(&format!("{}\n", text)),
+                Block::List(items) => {
+                    for item in items {
+                        out.push_str(&format!("- {}\n", item));
+                    }
+                }
+                Block::Table { headers, rows } => {
+                    out.push_str(&format!("| {} |\n", headers.join(" | ")));
+                    out.push_str(&format!("|{}\n", "---|".repeat(headers.len())));
+                    for row in rows {
+                        let cells: Vec<String> =
+                            row.iter().map(|c| c.replace('|', "\\|")).collect();
+                        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let title = escape_html(&self.title());
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+             <style>body{{font-family:sans-serif;max-width:50em;margin:2em auto}}\
+             table{{border-collapse:collapse}}td,th{{border:1px solid #ccc;padding:4px 8px;text-align:left}}</style>\n\
+             </head>\n<body>\n<h1>{title}</h1>\n"
+        );
+        for block in self.blocks() {
+            match block {
+                Block::Heading(text) => out.push_str(&format!("<h2>{}</h2>\n", escape_html(&text))),
+                Block::Paragraph(text) => out.push_str(&format!("<p>{}</p>\n", escape_html(&text))),
+                Block::List(items) => {
+                    out.push_str("<ul>\n");
+                    for item in items {
+                        out.push_str(&format!("<li>{}</li>\n", escape_html(&item)));
+                    }
+                    out.push_str("</ul>\n");
+                }
+                Block::Table { headers, rows } => {
+                    out.push_str("<table>\n<tr>");
+                    for h in headers {
+                        out.push_str(&format!("<th>{}</th>", escape_html(h)));
+                    }
+                    out.push_str("</tr>\n");
+                    for row in rows {
+                        out.push_str("<tr>");
+                        for cell in row {
+                            out.push_str(&format!("<td>{}</td>", escape_html(&cell)));
+                        }
+                        out.push_str("</tr>\n");
+                    }
+                    out.push_str("</table>\n");
+                }
+            }
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    fn title(&self) -> String {
+        format!(
+            "Agent activity: {} to {}",
+            self.since.format("%Y-%m-%d"),
+            self.until.format("%Y-%m-%d")
+        )
+    }
+
+    fn blocks(&self) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        for section in &self.sections {
+            blocks.push(Block::Heading(section.title().to_string()));
+            match section {
+                ReportSection::Tools => self.tools_blocks(&mut blocks),
+                ReportSection::Jobs => self.jobs_blocks(&mut blocks),
+                ReportSection::Costs => self.costs_blocks(&mut blocks),
+                ReportSection::Memory => self.memory_blocks(&mut blocks),
+                ReportSection::Conversations => self.conversation_blocks(&mut blocks),
+            }
+        }
+        blocks
+    }
+
+    fn tools_blocks(&self, blocks: &mut Vec<Block>) {
+        let usage = &self.stats.tool_usage;
+        if usage.is_empty() {
+            blocks.push(Block::Paragraph("No tool calls.".to_string()));
+            return;
+        }
+        let total: i64 = usage.iter().map(|t| t.calls).sum();
+        blocks.push(Block::Paragraph(format!(
+            "{} tool calls across {} tools.",
+            total,
+            usage.len()
+        )));
+        blocks.push(Block::Table {
+            headers: &["Tool", "Calls", "Failures"],
+            rows: usage
+                .iter()
+                .map(|t| {
+                    vec![
+                        t.tool_name.clone(),
+                        t.calls.to_string(),
+                        t.failures.to_string(),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    fn jobs_blocks(&self, blocks: &mut Vec<Block>) {
+        let jobs = &self.stats.jobs;
+        if jobs.is_empty() {
+            blocks.push(Block::Paragraph("No jobs.".to_string()));
+            return;
+        }
+        let done = jobs
+            .iter()
+            .filter(|j| matches!(j.status.as_str(), "completed" | "submitted" | "accepted"))
+            .count();
+        let failed = jobs.iter().filter(|j| j.status == "failed").count();
+        blocks.push(Block::Paragraph(format!(
+            "{} jobs: {} completed, {} failed, {} other.",
+            jobs.len(),
+            done,
+            failed,
+            jobs.len() - done - failed
+        )));
+        blocks.push(Block::Table {
+            headers: &["Job", "Status", "Finished", "Cost"],
+            rows: jobs
+                .iter()
+                .map(|j| {
+                    vec![
+                        j.title.clone(),
+                        j.status.clone(),
+                        j.completed_at
+                            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                            .unwrap_or_else(|| "-".to_string()),
+                        j.actual_cost
+                            .map(format_cost)
+                            .unwrap_or_else(|| "-".to_string()),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    fn costs_blocks(&self, blocks: &mut Vec<Block>) {
+        let llm = &self.stats.llm;
+        let job_cost: Decimal = self.stats.jobs.iter().filter_map(|j| j.actual_cost).sum();
+        blocks.push(Block::List(vec![
+            format!("LLM calls: {}", llm.calls),
+            format!("Tokens: {} in, {} out", llm.input_tokens, llm.output_tokens),
+            format!("LLM spend: {}", format_cost(llm.cost)),
+            format!("Job spend: {}", format_cost(job_cost)),
+        ]));
+    }
+
+    fn memory_blocks(&self, blocks: &mut Vec<Block>) {
+        let changes = &self.stats.memory_changes;
+        if changes.is_empty() {
+            blocks.push(Block::Paragraph("No memory changes.".to_string()));
+            return;
+        }
+        blocks.push(Block::List(
+            changes
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{} ({} {})",
+                        c.path,
+                        if c.created { "created" } else { "updated" },
+                        c.updated_at.format("%Y-%m-%d")
+                    )
+                })
+                .collect(),
+        ));
+    }
+
+    fn conversation_blocks(&self, blocks: &mut Vec<Block>) {
+        let conversations = &self.stats.conversations;
+        if conversations.is_empty() {
+            blocks.push(Block::Paragraph("No conversations.".to_string()));
+            return;
+        }
+        blocks.push(Block::Table {
+            headers: &["Channel", "Messages", "Started with", "Last active"],
+            rows: conversations
+                .iter()
+                .map(|c| {
+                    vec![
+                        c.channel.clone(),
+                        c.messages.to_string(),
+                        c.preview
+                            .as_deref()
+                            .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+                            .unwrap_or_default(),
+                        c.last_activity.format("%Y-%m-%d %H:%M").to_string(),
+                    ]
+                })
+                .collect(),
+        });
+    }
+}
+
+fn format_cost(cost: Decimal) -> String {
+    format!("${}", cost.round_dp(4))
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The bundled weekly activity report routine.
+pub fn weekly_report_routine(
+    user_id: &str,
+    schedule: &str,
+    timezone: Option<String>,
+    channel: Option<String>,
+    sections: Vec<ReportSection>,
+    format: ReportFormat,
+) -> Result<Routine, String> {
+    let next_fire_at = next_cron_fire(schedule, timezone.as_deref())?;
+    let now = Utc::now();
+    Ok(Routine {
+        id: Uuid::new_v4(),
+        name: WEEKLY_REPORT_ROUTINE.to_string(),
+        description: "Weekly summary of what the agent did".to_string(),
+        user_id: user_id.to_string(),
+        enabled: true,
+        trigger: Trigger::Cron {
+            schedule: schedule.to_string(),
+            timezone,
+        },
+        action: RoutineAction::Report {
+            sections,
+            period_days: 7,
+            format,
+        },
+        guardrails: RoutineGuardrails::default(),
+        notify: NotifyConfig {
+            channel,
+            user: user_id.to_string(),
+            ..Default::default()
+        },
+        last_run_at: None,
+        next_fire_at,
+        run_count: 0,
+        consecutive_failures: 0,
+        state: serde_json::json!({}),
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::{ConversationActivity, JobActivity, LlmUsage, MemoryChange, ToolUsage};
+
+    fn sample_report(sections: Vec<ReportSection>) -> ActivityReport {
+        let until: DateTime<Utc> = "2026-03-09T09:00:00Z".parse().unwrap();
+        ActivityReport {
+            since: until - Duration::days(7),
+            until,
+            sections,
+            stats: ActivityStats {
+                tool_usage: vec![ToolUsage {
+                    tool_name: "shell".to_string(),
+                    calls: 12,
+                    failures: 1,
+                }],
+                jobs: vec![JobActivity {
+                    id: Uuid::new_v4(),
+                    title: "Fix <CI> | build".to_string(),
+                    status: "completed".to_string(),
+                    created_at: until - Duration::days(2),
+                    completed_at: Some(until - Duration::days(1)),
+                    actual_cost: Some(Decimal::new(125, 2)),
+                }],
+                llm: LlmUsage {
+                    calls: 40,
+                    input_tokens: 120_000,
+                    output_tokens: 8_000,
+                    cost: Decimal::new(342, 2),
+                },
+                memory_changes: vec![MemoryChange {
+                    path: "notes/ci.md".to_string(),
+                    created: true,
+                    updated_at: until - Duration::days(1),
+                }],
+                conversations: vec![ConversationActivity {
+                    id: Uuid::new_v4(),
+                    channel: "telegram".to_string(),
+                    messages: 18,
+                    preview: Some("why is\nCI red?".to_string()),
+                    last_activity: until - Duration::hours(3),
+                }],
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_sections_and_format() {
+        assert_eq!(
+            ReportSection::parse_list("costs, Tools,costs").unwrap(),
+            vec![ReportSection::Costs, ReportSection::Tools]
+        );
+        assert!(ReportSection::parse_list("tools,weather").is_err());
+        assert!(ReportSection::parse_list(" , ").is_err());
+        assert_eq!(
+            "md".parse::<ReportFormat>().unwrap(),
+            ReportFormat::Markdown
+        );
+        assert_eq!("HTML".parse::<ReportFormat>().unwrap(), ReportFormat::Html);
+        assert_eq!("pdf".parse::<ReportFormat>().unwrap(), ReportFormat::Pdf);
+        assert!(ReportFormat::Pdf.is_binary());
+    }
+
+    #[test]
+    fn test_markdown_renders_selected_sections() {
+        let md = sample_report(vec![ReportSection::Jobs, ReportSection::Costs]).to_markdown();
+        assert!(md.starts_with("# Agent activity: 2026-03-02 to 2026-03-09\n"));
+        assert!(md.contains("1 jobs: 1 completed, 0 failed, 0 other."));
+        assert!(md.contains("| Fix <CI> \\| build | completed | 2026-03-08 09:00 | $1.25 |"));
+        assert!(md.contains("- LLM spend: $3.42"));
+        assert!(!md.contains("Tool usage"));
+        assert!(!md.contains("telegram"));
+    }
+
+    #[test]
+    fn test_html_escapes_content() {
+        let html = sample_report(ReportSection::ALL.to_vec()).to_html();
+        assert!(html.contains("<h2>Tool usage</h2>"));
+        assert!(html.contains("<td>Fix &lt;CI&gt; | build</td>"));
+        assert!(html.contains("<td>why is CI red?</td>"));
+        assert!(html.contains("<li>notes/ci.md (created 2026-03-08)</li>"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn test_pdf_renders_markdown_layout() {
+        let pdf = sample_report(ReportSection::ALL.to_vec()).render(ReportFormat::Pdf);
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert!(text.contains("/Title (Agent activity: 2026-03-02 to 2026-03-09)"));
+        assert!(text.contains("(Fix <CI> | build"));
+    }
+
+    #[test]
+    fn test_weekly_report_routine() {
+        let routine = weekly_report_routine(
+            "default",
+            WEEKLY_REPORT_SCHEDULE,
+            None,
+            Some("telegram".to_string()),
+            ReportSection::ALL.to_vec(),
+            ReportFormat::Html,
+        )
+        .unwrap();
+        assert_eq!(routine.name, WEEKLY_REPORT_ROUTINE);
+        assert!(routine.next_fire_at.is_some());
+        assert_eq!(routine.notify.channel.as_deref(), Some("telegram"));
+        assert_eq!(routine.action.type_tag(), "report");
+        let restored = RoutineAction::from_db("report", routine.action.to_config_json()).unwrap();
+        match restored {
+            RoutineAction::Report {
+                sections,
+                period_days,
+                format,
+            } => {
+                assert_eq!(sections.len(), 5);
+                assert_eq!(period_days, 7);
+                assert_eq!(format, ReportFormat::Html);
+            }
+            other => panic!("unexpected action {:?}", other),
+        }
+    }
+}
@@ -9,6 +9,7 @@
 //! │  Trigger  │────▶│ Engine  │────▶│  Execution Mode  │
 //! │ cron/event│     │guardrail│     │lightweight│full_job│
 //! │ webhook   │     │ check   │     └──────────────────┘
+//! │ file      │     │         │              │
 //! │ manual    │     └─────────┘              │
 //! └──────────┘                               ▼
 //!                                     ┌──────────────┐
@@ -26,6 +27,9 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::agent::report::{ReportFormat, ReportSection};
+use crate::locale::Tz;
+
 /// A routine is a named, persistent, user-owned task with a trigger and an action.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Routine {
@@ -55,7 +59,12 @@ pub struct Routine {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Trigger {
     /// Fire on a cron schedule (e.g. "0 9 * * MON-FRI" or "every 2h").
-    Cron { schedule: String },
+    Cron {
+        schedule: String,
+        /// IANA time zone the schedule is evaluated in (UTC when unset).
+        #[serde(default)]
+        timezone: Option<String>,
+    },
     /// Fire when a channel message matches a pattern.
     Event {
         /// Optional channel filter (e.g. "telegram", "slack").
@@ -70,10 +79,22 @@ pub enum Trigger {
         /// Optional shared secret for HMAC validation.
         secret: Option<String>,
     },
+    /// Fire when files matching a host path glob are created or modified.
+    File {
+        /// Path glob, `~` expands to the home directory (e.g. "~/Downloads/*.pdf").
+        path: String,
+        /// Seconds a file must be quiet before the routine fires (default: 2).
+        #[serde(default = "default_debounce_secs")]
+        debounce_secs: u64,
+    },
     /// Only fires via tool call or CLI.
     Manual,
 }
 
+fn default_debounce_secs() -> u64 {
+    2
+}
+
 impl Trigger {
     /// The string tag stored in the DB trigger_type column.
     pub fn type_tag(&self) -> &'static str {
@@ -81,6 +102,7 @@ impl Trigger {
             Trigger::Cron { .. } => "cron",
             Trigger::Event { .. } => "event",
             Trigger::Webhook { .. } => "webhook",
+            Trigger::File { .. } => "file",
             Trigger::Manual => "manual",
         }
     }
@@ -94,7 +116,11 @@ impl Trigger {
                     .and_then(|v| v.as_str())
                     .ok_or("cron trigger missing 'schedule'")?
                     .to_string();
-                Ok(Trigger::Cron { schedule })
+                let timezone = config
+                    .get("timezone")
+                    .and_then(|v| v.as_str())
+                    .map(String::from);
+                Ok(Trigger::Cron { schedule, timezone })
             }
             "event" => {
                 let pattern = config
@@ -119,6 +145,21 @@ impl Trigger {
                     .map(String::from);
                 Ok(Trigger::Webhook { path, secret })
             }
+            "file" => {
+                let path = config
+                    .get("path")
+                    .and_then(|v| v.as_str())
+                    .ok_or("file trigger missing 'path'")?
+                    .to_string();
+                let debounce_secs = config
+                    .get("debounce_secs")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or_else(default_debounce_secs);
+                Ok(Trigger::File {
+                    path,
+                    debounce_secs,
+                })
+            }
             "manual" => Ok(Trigger::Manual),
             other => Err(format!("unknown trigger type: {other}")),
         }
@@ -127,7 +168,10 @@ impl Trigger {
     /// Serialize trigger-specific config to JSON for DB storage.
     pub fn to_config_json(&self) -> serde_json::Value {
         match self {
-            Trigger::Cron { schedule } => serde_json::json!({ "schedule": schedule }),
+            Trigger::Cron { schedule, timezone } => serde_json::json!({
+                "schedule": schedule,
+                "timezone": timezone,
+            }),
             Trigger::Event { channel, pattern } => serde_json::json!({
                 "pattern": pattern,
                 "channel": channel,
@@ -136,6 +180,13 @@ impl Trigger {
                 "path": path,
                 "secret": secret,
             }),
+            Trigger::File {
+                path,
+                debounce_secs,
+            } => serde_json::json!({
+                "path": path,
+                "debounce_secs": debounce_secs,
+            }),
             Trigger::Manual => serde_json::json!({}),
         }
     }
@@ -166,6 +217,27 @@ pub enum RoutineAction {
         #[serde(default = "default_max_iterations")]
         max_iterations: u32,
     },
+    /// Activity report over the last `period_days`, no LLM call.
+    Report {
+        /// Sections to include (default: all).
+        #[serde(default = "default_report_sections")]
+        sections: Vec<ReportSection>,
+        /// Days covered by the report (default: 7).
+        #[serde(default = "default_report_period_days")]
+        period_days: u32,
+        /// Format of the copy saved to the workspace.
+        #[serde(default)]
+        format: ReportFormat,
+    },
+    /// Call one tool directly, no LLM. Registered by WASM tools that
+    /// declare a `schedule` capability.
+    Tool {
+        /// Registered tool name.
+        tool: String,
+        /// Parameters passed to the tool.
+        #[serde(default)]
+        params: serde_json::Value,
+    },
 }
 
 fn default_max_tokens() -> u32 {
@@ -176,12 +248,22 @@ fn default_max_iterations() -> u32 {
     10
 }
 
+fn default_report_sections() -> Vec<ReportSection> {
+    ReportSection::ALL.to_vec()
+}
+
+fn default_report_period_days() -> u32 {
+    7
+}
+
 impl RoutineAction {
     /// The string tag stored in the DB action_type column.
     pub fn type_tag(&self) -> &'static str {
         match self {
             RoutineAction::Lightweight { .. } => "lightweight",
             RoutineAction::FullJob { .. } => "full_job",
+            RoutineAction::Report { .. } => "report",
+            RoutineAction::Tool { .. } => "tool",
         }
     }
 
@@ -235,6 +317,42 @@ impl RoutineAction {
                     max_iterations,
                 })
             }
+            "report" => {
+                let sections = match config.get("sections").and_then(|v| v.as_array()) {
+                    Some(arr) => arr
+                        .iter()
+                        .filter_map(|v| v.as_str())
+                        .map(ReportSection::from_str)
+                        .collect::<Result<Vec<_>, _>>()?,
+                    None => default_report_sections(),
+                };
+                let period_days = config
+                    .get("period_days")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(default_report_period_days() as u64)
+                    as u32;
+                let format = match config.get("format").and_then(|v| v.as_str()) {
+                    Some(f) => f.parse()?,
+                    None => ReportFormat::default(),
+                };
+                Ok(RoutineAction::Report {
+                    sections,
+                    period_days,
+                    format,
+                })
+            }
+            "tool" => {
+                let tool = config
+                    .get("tool")
+                    .and_then(|v| v.as_str())
+                    .ok_or("tool action missing 'tool'")?
+                    .to_string();
+                let params = config
+                    .get("params")
+                    .cloned()
+                    .unwrap_or_else(|| serde_json::json!({}));
+                Ok(RoutineAction::Tool { tool, params })
+            }
             other => Err(format!("unknown action type: {other}")),
         }
     }
@@ -260,6 +378,19 @@ impl RoutineAction {
                 "description": description,
                 "max_iterations": max_iterations,
             }),
+            RoutineAction::Report {
+                sections,
+                period_days,
+                format,
+            } => serde_json::json!({
+                "sections": sections.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+                "period_days": period_days,
+                "format": format.as_str(),
+            }),
+            RoutineAction::Tool { tool, params } => serde_json::json!({
+                "tool": tool,
+                "params": params,
+            }),
         }
     }
 }
@@ -370,26 +501,59 @@ pub fn content_hash(content: &str) -> u64 {
 }
 
 /// Parse a cron expression and compute the next fire time from now.
-pub fn next_cron_fire(schedule: &str) -> Result<Option<DateTime<Utc>>, String> {
+///
+/// With a `timezone` the schedule is matched against local wall-clock time,
+/// so "0 9 * * *" stays at 09:00 local across DST changes.
+pub fn next_cron_fire(
+    schedule: &str,
+    timezone: Option<&str>,
+) -> Result<Option<DateTime<Utc>>, String> {
+    let tz = timezone.map(Tz::parse).transpose()?;
+    next_cron_fire_after(schedule, tz.as_ref(), Utc::now())
+}
+
+fn next_cron_fire_after(
+    schedule: &str,
+    tz: Option<&Tz>,
+    now: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>, String> {
     let cron_schedule =
         cron::Schedule::from_str(schedule).map_err(|e| format!("invalid cron: {e}"))?;
-    Ok(cron_schedule.upcoming(Utc).next())
+    let Some(tz) = tz else {
+        return Ok(cron_schedule.after(&now).next());
+    };
+
+    // Walk the schedule in wall-clock time (expressed as naive UTC) and map
+    // each match back to an instant. Times skipped by a DST gap shift forward;
+    // repeated times fire once.
+    let wall_now = tz.to_local(now).naive_local().and_utc();
+    Ok(cron_schedule
+        .after(&wall_now)
+        .take(8)
+        .map(|wall| tz.from_local(wall.naive_utc()))
+        .find(|fire| *fire > now))
 }
 
 #[cfg(test)]
 mod tests {
     use crate::agent::routine::{
         RoutineAction, RoutineGuardrails, RunStatus, Trigger, content_hash, next_cron_fire,
+        next_cron_fire_after,
     };
+    use crate::locale::Tz;
+    use chrono::{DateTime, Utc};
 
     #[test]
     fn test_trigger_roundtrip() {
         let trigger = Trigger::Cron {
             schedule: "0 9 * * MON-FRI".to_string(),
+            timezone: Some("Europe/Berlin".to_string()),
         };
         let json = trigger.to_config_json();
         let parsed = Trigger::from_db("cron", json).expect("parse cron");
-        assert!(matches!(parsed, Trigger::Cron { schedule } if schedule == "0 9 * * MON-FRI"));
+        assert!(
+            matches!(parsed, Trigger::Cron { schedule, timezone } if schedule == "0 9 * * MON-FRI" && timezone.as_deref() == Some("Europe/Berlin"))
+        );
     }
 
     #[test]
@@ -404,6 +568,28 @@ mod tests {
             if channel == Some("telegram".to_string()) && pattern == r"deploy\s+\w+"));
     }
 
+    #[test]
+    fn test_file_trigger_roundtrip() {
+        let trigger = Trigger::File {
+            path: "~/Downloads/*.pdf".to_string(),
+            debounce_secs: 5,
+        };
+        let json = trigger.to_config_json();
+        let parsed = Trigger::from_db("file", json).expect("parse file");
+        assert!(matches!(parsed, Trigger::File { path, debounce_secs }
+            if path == "~/Downloads/*.pdf" && debounce_secs == 5));
+
+        let parsed = Trigger::from_db("file", serde_json::json!({"path": "/tmp/*"})).unwrap();
+        assert!(matches!(
+            parsed,
+            Trigger::File {
+                debounce_secs: 2,
+                ..
+            }
+        ));
+        assert!(Trigger::from_db("file", serde_json::json!({})).is_err());
+    }
+
     #[test]
     fn test_action_lightweight_roundtrip() {
         let action = RoutineAction::Lightweight {
@@ -434,6 +620,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_action_tool_roundtrip() {
+        let action = RoutineAction::Tool {
+            tool: "feed_sync".to_string(),
+            params: serde_json::json!({"action": "sync"}),
+        };
+        let json = action.to_config_json();
+        let parsed = RoutineAction::from_db("tool", json).expect("parse tool");
+        assert!(matches!(parsed, RoutineAction::Tool { tool, params }
+            if tool == "feed_sync" && params["action"] == "sync"));
+        assert!(RoutineAction::from_db("tool", serde_json::json!({})).is_err());
+    }
+
     #[test]
     fn test_run_status_display_parse() {
         for status in [
@@ -461,14 +660,36 @@ mod tests {
     #[test]
     fn test_next_cron_fire_valid() {
         // Every minute should always have a next fire
-        let next = next_cron_fire("* * * * * *").expect("valid cron");
+        let next = next_cron_fire("* * * * * *", None).expect("valid cron");
         assert!(next.is_some());
     }
 
     #[test]
     fn test_next_cron_fire_invalid() {
-        let result = next_cron_fire("not a cron");
+        let result = next_cron_fire("not a cron", None);
         assert!(result.is_err());
+        assert!(next_cron_fire("* * * * * *", Some("Nowhere/Special")).is_err());
+    }
+
+    #[test]
+    fn test_next_cron_fire_keeps_local_time_across_dst() {
+        let ny = Tz::parse("America/New_York").unwrap();
+        let daily_nine = "0 0 9 * * *";
+
+        // Before the fall-back change 09:00 EDT is 13:00 UTC, after it 14:00 UTC.
+        let before: DateTime<Utc> = "2026-10-31T14:00:00Z".parse().unwrap();
+        let next = next_cron_fire_after(daily_nine, Some(&ny), before).unwrap();
+        assert_eq!(next, Some("2026-11-01T14:00:00Z".parse().unwrap()));
+
+        let summer: DateTime<Utc> = "2026-07-01T12:00:00Z".parse().unwrap();
+        let next = next_cron_fire_after(daily_nine, Some(&ny), summer).unwrap();
+        assert_eq!(next, Some("2026-07-01T13:00:00Z".parse().unwrap()));
+
+        // A schedule inside the spring-forward gap still fires that day.
+        let gap = "0 30 2 * * *";
+        let eve: DateTime<Utc> = "2026-03-08T05:00:00Z".parse().unwrap();
+        let next = next_cron_fire_after(gap, Some(&ny), eve).unwrap();
+        assert_eq!(next, Some("2026-03-08T07:30:00Z".parse().unwrap()));
     }
 
     #[test]
@@ -483,7 +704,8 @@ mod tests {
     fn test_trigger_type_tag() {
         assert_eq!(
             Trigger::Cron {
-                schedule: String::new()
+                schedule: String::new(),
+                timezone: None,
             }
             .type_tag(),
             "cron"
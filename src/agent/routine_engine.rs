@@ -3,31 +3,47 @@
 //! Handles loading routines, checking triggers, enforcing guardrails,
 //! and executing both lightweight (single LLM call) and full-job routines.
 //!
-//! The engine runs two independent loops:
+//! The engine runs three independent loops:
 //! - A **cron ticker** that polls the DB every N seconds for due cron routines
 //! - An **event matcher** called synchronously from the agent main loop
+//! - A **file watcher** that fires routines when matching host files settle
+//!   (see [`crate::agent::routine_watch`])
 //!
 //! Lightweight routines execute inline (single LLM call, no scheduler slot).
-//! Full-job routines are delegated to the existing `Scheduler`.
+//! Full-job routines are delegated to the existing `Scheduler`. Report
+//! routines query the database directly and never touch the LLM, and tool
+//! routines (registered by WASM tools with a `schedule` capability) call
+//! one tool from the registry.
 
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
 use chrono::Utc;
 use regex::Regex;
-use tokio::sync::{RwLock, mpsc};
+use tokio::sync::{Notify, RwLock, mpsc};
 use uuid::Uuid;
 
+use crate::agent::focus::{self, FocusMode};
+use crate::agent::priority::{spawn_background, yield_every};
+use crate::agent::report::{ActivityReport, ReportFormat, ReportSection};
 use crate::agent::routine::{
     NotifyConfig, Routine, RoutineAction, RoutineRun, RunStatus, Trigger, next_cron_fire,
 };
+use crate::agent::routine_watch::FileWatch;
 use crate::channels::{IncomingMessage, OutgoingResponse};
 use crate::config::RoutineConfig;
+use crate::context::JobContext;
 use crate::db::Database;
 use crate::llm::{ChatMessage, CompletionRequest, FinishReason, LlmProvider};
+use crate::locale::{DEFAULT_LANGUAGE, Tz, UserLocale};
+use crate::tools::ToolRegistry;
 use crate::workspace::Workspace;
 
+/// Longest tool output kept as a tool routine's run summary.
+const MAX_TOOL_SUMMARY_BYTES: usize = 2000;
+
 /// The routine execution engine.
 pub struct RoutineEngine {
     config: RoutineConfig,
@@ -40,6 +56,12 @@ pub struct RoutineEngine {
     running_count: Arc<AtomicUsize>,
     /// Compiled event regex cache: routine_id -> compiled regex.
     event_cache: Arc<RwLock<Vec<(Uuid, Routine, Regex)>>>,
+    /// Wakes the file watcher to reload file-triggered routines.
+    file_watch_reload: Arc<Notify>,
+    /// While focused, triggers are held instead of fired.
+    focus: Option<Arc<FocusMode>>,
+    /// Tools that tool routines call.
+    tools: Option<Arc<ToolRegistry>>,
 }
 
 impl RoutineEngine {
@@ -58,9 +80,43 @@ impl RoutineEngine {
             notify_tx,
             running_count: Arc::new(AtomicUsize::new(0)),
             event_cache: Arc::new(RwLock::new(Vec::new())),
+            file_watch_reload: Arc::new(Notify::new()),
+            focus: None,
+            tools: None,
+        }
+    }
+
+    /// Let tool routines call tools from this registry.
+    pub fn with_tools(mut self, tools: Arc<ToolRegistry>) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    /// Pause triggers while focus mode is on.
+    pub fn with_focus(mut self, focus: Arc<FocusMode>) -> Self {
+        self.focus = Some(focus);
+        self
+    }
+
+    /// Whether triggers are paused. Notes the held routine for the focus digest.
+    fn paused(&self, routine: &Routine, trigger: &str) -> bool {
+        match self.focus {
+            Some(ref focus) if focus.is_active() => {
+                focus.defer(
+                    "routine",
+                    &format!("'{}' ({} trigger) paused", routine.name, trigger),
+                );
+                true
+            }
+            _ => false,
         }
     }
 
+    /// Routine runs in progress.
+    pub fn running(&self) -> usize {
+        self.running_count.load(Ordering::Relaxed)
+    }
+
     /// Refresh the in-memory event trigger cache from DB.
     pub async fn refresh_event_cache(&self) {
         match self.store.list_event_routines().await {
@@ -90,6 +146,59 @@ impl RoutineEngine {
         }
     }
 
+    /// Ask the file watcher to reload file-triggered routines.
+    pub fn refresh_file_watches(&self) {
+        self.file_watch_reload.notify_one();
+    }
+
+    pub(crate) fn file_watch_reload(&self) -> Arc<Notify> {
+        Arc::clone(&self.file_watch_reload)
+    }
+
+    /// Load and compile all enabled file-triggered routines.
+    pub async fn file_watches(&self) -> Vec<FileWatch> {
+        let routines = match self.store.list_file_routines().await {
+            Ok(r) => r,
+            Err(e) => {
+                tracing::error!("Failed to load file routines: {}", e);
+                return Vec::new();
+            }
+        };
+        routines
+            .into_iter()
+            .filter_map(|routine| {
+                let name = routine.name.clone();
+                FileWatch::new(routine)
+                    .map_err(|e| tracing::warn!(routine = %name, "Skipping file trigger: {}", e))
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Fire a file-triggered routine for settled paths. Returns false when
+    /// concurrency limits or focus mode refuse the run, so the paths can be
+    /// retried.
+    pub async fn fire_file_trigger(&self, routine: &Routine, paths: &[PathBuf]) -> bool {
+        if self.paused(routine, "file") {
+            return false;
+        }
+        if self.running_count.load(Ordering::Relaxed) >= self.config.max_concurrent_routines {
+            tracing::debug!(routine = %routine.name, "Deferred: global max concurrent reached");
+            return false;
+        }
+        if !self.check_concurrent(routine).await {
+            tracing::debug!(routine = %routine.name, "Deferred: max concurrent reached");
+            return false;
+        }
+        let detail = paths
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.spawn_fire(routine.clone(), "file", Some(detail));
+        true
+    }
+
     /// Check incoming message against event triggers. Returns number of routines fired.
     ///
     /// Called synchronously from the main loop after handle_message(). The actual
@@ -97,8 +206,12 @@ impl RoutineEngine {
     pub async fn check_event_triggers(&self, message: &IncomingMessage) -> usize {
         let cache = self.event_cache.read().await;
         let mut fired = 0;
+        let mut checked = 0;
 
         for (_, routine, re) in cache.iter() {
+            // Runs on every message; don't hold the thread through many regexes
+            yield_every(&mut checked, 16).await;
+
             // Channel filter
             if let Trigger::Event {
                 channel: Some(ch), ..
@@ -113,6 +226,10 @@ impl RoutineEngine {
                 continue;
             }
 
+            if self.paused(routine, "event") {
+                continue;
+            }
+
             // Cooldown check
             if !self.check_cooldown(routine) {
                 tracing::debug!(routine = %routine.name, "Skipped: cooldown active");
@@ -140,6 +257,8 @@ impl RoutineEngine {
     }
 
     /// Check all due cron routines and fire them. Called by the cron ticker.
+    ///
+    /// While focused, due routines are left due and run once focus ends.
     pub async fn check_cron_triggers(&self) {
         let routines = match self.store.list_due_cron_routines().await {
             Ok(r) => r,
@@ -150,6 +269,10 @@ impl RoutineEngine {
         };
 
         for routine in routines {
+            if self.paused(&routine, "cron") {
+                continue;
+            }
+
             if self.running_count.load(Ordering::Relaxed) >= self.config.max_concurrent_routines {
                 tracing::warn!("Global max concurrent routines reached, skipping remaining");
                 break;
@@ -163,7 +286,7 @@ impl RoutineEngine {
                 continue;
             }
 
-            let detail = if let Trigger::Cron { ref schedule } = routine.trigger {
+            let detail = if let Trigger::Cron { ref schedule, .. } = routine.trigger {
                 Some(schedule.clone())
             } else {
                 None
@@ -220,9 +343,10 @@ impl RoutineEngine {
             notify_tx: self.notify_tx.clone(),
             running_count: self.running_count.clone(),
             max_lightweight_tokens: self.config.max_lightweight_tokens,
+            tools: self.tools.clone(),
         };
 
-        tokio::spawn(async move {
+        spawn_background(async move {
             execute_routine(engine, routine, run).await;
         });
 
@@ -252,11 +376,12 @@ impl RoutineEngine {
             notify_tx: self.notify_tx.clone(),
             running_count: self.running_count.clone(),
             max_lightweight_tokens: self.config.max_lightweight_tokens,
+            tools: self.tools.clone(),
         };
 
         // Record the run in DB, then spawn execution
         let store = self.store.clone();
-        tokio::spawn(async move {
+        spawn_background(async move {
             if let Err(e) = store.create_routine_run(&run).await {
                 tracing::error!(routine = %routine.name, "Failed to record run: {}", e);
                 return;
@@ -299,6 +424,7 @@ struct EngineContext {
     notify_tx: mpsc::Sender<OutgoingResponse>,
     running_count: Arc<AtomicUsize>,
     max_lightweight_tokens: u32,
+    tools: Option<Arc<ToolRegistry>>,
 }
 
 /// Execute a routine run. Handles both lightweight and full_job modes.
@@ -306,20 +432,40 @@ async fn execute_routine(ctx: EngineContext, routine: Routine, run: RoutineRun)
     // Increment running count (atomic: survives panics in the execution below)
     ctx.running_count.fetch_add(1, Ordering::Relaxed);
 
+    let payload = trigger_payload(&routine, &run);
     let result = match &routine.action {
         RoutineAction::Lightweight {
             prompt,
             context_paths,
             max_tokens,
-        } => execute_lightweight(&ctx, &routine, prompt, context_paths, *max_tokens).await,
+        } => {
+            let prompt = format!("{}{}", prompt, payload);
+            execute_lightweight(&ctx, &routine, &prompt, context_paths, *max_tokens).await
+        }
         RoutineAction::FullJob { description, .. } => {
+            let description = format!("{}{}", description, payload);
             // Full job mode: for now, execute as lightweight with the description
             // as prompt. Full scheduler integration will come as a follow-up.
             tracing::info!(
                 routine = %routine.name,
                 "FullJob mode executing as lightweight (scheduler integration pending)"
             );
-            execute_lightweight(&ctx, &routine, description, &[], ctx.max_lightweight_tokens).await
+            execute_lightweight(
+                &ctx,
+                &routine,
+                &description,
+                &[],
+                ctx.max_lightweight_tokens,
+            )
+            .await
+        }
+        RoutineAction::Report {
+            sections,
+            period_days,
+            format,
+        } => execute_report(&ctx, &routine, sections, *period_days, *format).await,
+        RoutineAction::Tool { tool, params } => {
+            execute_tool(&ctx, &routine, tool, params.clone()).await
         }
     };
 
@@ -346,8 +492,12 @@ async fn execute_routine(ctx: EngineContext, routine: Routine, run: RoutineRun)
 
     // Update routine runtime state
     let now = Utc::now();
-    let next_fire = if let Trigger::Cron { ref schedule } = routine.trigger {
-        next_cron_fire(schedule).unwrap_or(None)
+    let next_fire = if let Trigger::Cron {
+        ref schedule,
+        ref timezone,
+    } = routine.trigger
+    {
+        next_cron_fire(schedule, timezone.as_deref()).unwrap_or(None)
     } else {
         None
     };
@@ -384,6 +534,21 @@ async fn execute_routine(ctx: EngineContext, routine: Routine, run: RoutineRun)
     .await;
 }
 
+/// Prompt addendum describing what fired the routine. Only file triggers
+/// carry a payload: the paths that settled.
+fn trigger_payload(routine: &Routine, run: &RoutineRun) -> String {
+    match (&routine.trigger, &run.trigger_detail) {
+        (Trigger::File { .. }, Some(paths)) => {
+            let list: Vec<String> = paths.lines().map(|p| format!("- {}", p)).collect();
+            format!(
+                "\n\n---\n\n# Trigger\n\nThese files were created or changed:\n{}",
+                list.join("\n")
+            )
+        }
+        _ => String::new(),
+    }
+}
+
 /// Execute a lightweight routine (single LLM call).
 async fn execute_lightweight(
     ctx: &EngineContext,
@@ -419,6 +584,17 @@ async fn execute_lightweight(
     let mut full_prompt = String::new();
     full_prompt.push_str(prompt);
 
+    // Cron routines know their zone; tell the model what "today" means there.
+    if let Trigger::Cron {
+        timezone: Some(ref name),
+        ..
+    } = routine.trigger
+        && let Ok(tz) = Tz::parse(name)
+    {
+        full_prompt.push_str("\n\n");
+        full_prompt.push_str(&UserLocale::new(tz, DEFAULT_LANGUAGE).prompt_line(Utc::now()));
+    }
+
     if !context_parts.is_empty() {
         full_prompt.push_str("\n\n---\n\n# Context\n\n");
         full_prompt.push_str(&context_parts.join("\n\n"));
@@ -431,7 +607,8 @@ async fn execute_lightweight(
 
     full_prompt.push_str(
         "\n\n---\n\nIf nothing needs attention, reply EXACTLY with: ROUTINE_OK\n\
-         If something needs attention, provide a concise summary.",
+         If something needs attention, provide a concise summary.\n\
+         If it is an emergency that cannot wait, start the summary with: EMERGENCY:",
     );
 
     // Get system prompt
@@ -495,6 +672,93 @@ async fn execute_lightweight(
     Ok((RunStatus::Attention, Some(content.to_string()), tokens_used))
 }
 
+/// Execute a report routine: summarize recent activity without an LLM call.
+///
+/// A copy in the configured format is saved to `reports/` in the workspace,
+/// or to the exports directory for PDF (whose path is added to the summary);
+/// the Markdown rendering becomes the run summary and notification.
+async fn execute_report(
+    ctx: &EngineContext,
+    routine: &Routine,
+    sections: &[ReportSection],
+    period_days: u32,
+    format: ReportFormat,
+) -> Result<(RunStatus, Option<String>, Option<i32>), String> {
+    // The single-user "default" owner sees everything, like the CLI export.
+    let scope = (routine.user_id != "default").then_some(routine.user_id.as_str());
+    let report = ActivityReport::generate(
+        ctx.store.as_ref(),
+        scope,
+        chrono::Duration::days(i64::from(period_days.max(1))),
+        sections.to_vec(),
+    )
+    .await
+    .map_err(|e| format!("Failed to collect activity: {}", e))?;
+
+    let file_name = format!(
+        "activity-{}.{}",
+        report.until.format("%Y-%m-%d"),
+        format.extension()
+    );
+    let mut summary = report.to_markdown();
+    match format {
+        ReportFormat::Pdf => match crate::media::write_export(&file_name, &report.to_pdf()) {
+            Ok(path) => summary.push_str(&format!("\nPDF copy: {}\n", path.display())),
+            Err(e) => {
+                tracing::warn!(routine = %routine.name, "Failed to save PDF report {}: {}", file_name, e)
+            }
+        },
+        ReportFormat::Markdown | ReportFormat::Html => {
+            let path = format!("reports/{}", file_name);
+            let rendered = String::from_utf8_lossy(&report.render(format)).into_owned();
+            if let Err(e) = ctx.workspace.write(&path, &rendered).await {
+                tracing::warn!(routine = %routine.name, "Failed to save report to {}: {}", path, e);
+            }
+        }
+    }
+
+    Ok((RunStatus::Attention, Some(summary), None))
+}
+
+/// Execute a tool routine: one direct call, no LLM.
+async fn execute_tool(
+    ctx: &EngineContext,
+    routine: &Routine,
+    tool_name: &str,
+    params: serde_json::Value,
+) -> Result<(RunStatus, Option<String>, Option<i32>), String> {
+    let tools = ctx
+        .tools
+        .as_ref()
+        .ok_or("Tool routines need the tool registry")?;
+    let tool = tools
+        .get(tool_name)
+        .await
+        .ok_or_else(|| format!("Tool '{}' is not loaded", tool_name))?;
+
+    let job_ctx = JobContext::with_user(
+        &routine.user_id,
+        &routine.name,
+        format!("Scheduled run of the {} tool", tool_name),
+    );
+    let output = tokio::time::timeout(tool.execution_timeout(), tool.execute(params, &job_ctx))
+        .await
+        .map_err(|_| format!("Tool '{}' timed out", tool_name))?
+        .map_err(|e| format!("Tool '{}' failed: {}", tool_name, e))?;
+
+    let summary = match output.result {
+        serde_json::Value::String(text) => text,
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    let summary = truncate(summary.trim(), MAX_TOOL_SUMMARY_BYTES);
+    Ok((
+        RunStatus::Ok,
+        (!summary.is_empty()).then_some(summary),
+        None,
+    ))
+}
+
 /// Send a notification based on the routine's notify config and run status.
 async fn send_notification(
     tx: &mpsc::Sender<OutgoingResponse>,
@@ -533,6 +797,9 @@ async fn send_notification(
             "source": "routine",
             "routine_name": routine_name,
             "status": status.to_string(),
+            "severity": summary.map_or("normal", focus::severity_of),
+            "notify_channel": notify.channel,
+            "notify_user": notify.user,
         }),
     };
 
@@ -571,6 +838,57 @@ fn truncate(s: &str, max: usize) -> String {
 mod tests {
     use crate::agent::routine::{NotifyConfig, RunStatus};
 
+    #[test]
+    fn test_file_trigger_payload_lists_paths() {
+        use crate::agent::routine::{
+            Routine, RoutineAction, RoutineGuardrails, RoutineRun, Trigger,
+        };
+
+        let routine = Routine {
+            id: uuid::Uuid::new_v4(),
+            name: "invoices".to_string(),
+            description: String::new(),
+            user_id: "default".to_string(),
+            enabled: true,
+            trigger: Trigger::File {
+                path: "/in/*.pdf".to_string(),
+                debounce_secs: 2,
+            },
+            action: RoutineAction::Lightweight {
+                prompt: "File it".to_string(),
+                context_paths: Vec::new(),
+                max_tokens: 1024,
+            },
+            guardrails: RoutineGuardrails::default(),
+            notify: NotifyConfig::default(),
+            last_run_at: None,
+            next_fire_at: None,
+            run_count: 0,
+            consecutive_failures: 0,
+            state: serde_json::json!({}),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        let mut run = RoutineRun {
+            id: uuid::Uuid::new_v4(),
+            routine_id: routine.id,
+            trigger_type: "file".to_string(),
+            trigger_detail: Some("/in/a.pdf\n/in/b.pdf".to_string()),
+            started_at: chrono::Utc::now(),
+            completed_at: None,
+            status: RunStatus::Running,
+            result_summary: None,
+            tokens_used: None,
+            job_id: None,
+            created_at: chrono::Utc::now(),
+        };
+        let payload = super::trigger_payload(&routine, &run);
+        assert!(payload.contains("- /in/a.pdf\n- /in/b.pdf"));
+
+        run.trigger_detail = None;
+        assert!(super::trigger_payload(&routine, &run).is_empty());
+    }
+
     #[test]
     fn test_notification_gating() {
         let config = NotifyConfig {
@@ -0,0 +1,344 @@
+//! Filesystem triggers for routines.
+//!
+//! A file-triggered routine watches a host path glob such as
+//! `~/Downloads/*.pdf`. Create and modify events are debounced per path: a
+//! file only counts once it has been quiet for the trigger's debounce window,
+//! so a download in progress fires once rather than on every write. Paths
+//! that settle together are batched into a single run, and the routine's
+//! cooldown rate-limits runs. Paths that settle during a cooldown wait for
+//! the next run instead of being dropped.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::agent::routine::{Routine, Trigger};
+use crate::agent::routine_engine::RoutineEngine;
+
+/// Most paths handed to a single run; the rest wait for the next one.
+const MAX_PATHS_PER_RUN: usize = 20;
+
+/// How often settled paths are checked.
+const TICK: Duration = Duration::from_millis(500);
+
+/// A file trigger compiled for matching.
+#[derive(Debug, Clone)]
+pub struct FileWatch {
+    pub routine: Routine,
+    /// Deepest directory without glob characters; this is what the OS watches.
+    pub root: PathBuf,
+    /// Whether the glob reaches below `root`.
+    pub recursive: bool,
+    pattern: glob::Pattern,
+    debounce: Duration,
+}
+
+impl FileWatch {
+    /// Compile a routine's file trigger. Fails for other trigger types or
+    /// invalid globs.
+    pub fn new(routine: Routine) -> Result<Self, String> {
+        let Trigger::File {
+            ref path,
+            debounce_secs,
+        } = routine.trigger
+        else {
+            return Err(format!("routine '{}' has no file trigger", routine.name));
+        };
+        let expanded = expand_home(path);
+        let pattern = glob::Pattern::new(&expanded.to_string_lossy())
+            .map_err(|e| format!("invalid path glob '{}': {}", path, e))?;
+        let (root, recursive) = split_glob(&expanded);
+        Ok(Self {
+            routine,
+            root,
+            recursive,
+            pattern,
+            debounce: Duration::from_secs(debounce_secs),
+        })
+    }
+
+    pub fn matches(&self, path: &Path) -> bool {
+        self.pattern.matches_path(path)
+    }
+}
+
+/// Expand a leading `~` to the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~") {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => dirs::home_dir()
+            .map(|home| home.join(rest.trim_start_matches('/')))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Split a glob into the directory to watch and whether to watch recursively.
+fn split_glob(path: &Path) -> (PathBuf, bool) {
+    let is_glob = |c: &Component| {
+        c.as_os_str()
+            .to_string_lossy()
+            .contains(['*', '?', '[', '{'])
+    };
+    let components: Vec<Component> = path.components().collect();
+    match components.iter().position(is_glob) {
+        // A plain path: watch its directory.
+        None => (
+            path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            false,
+        ),
+        Some(first) => {
+            let root: PathBuf = components[..first].iter().collect();
+            // Anything past the first glob component beyond the file name
+            // (e.g. `*/invoice.pdf` or `**/*.pdf`) needs a recursive watch.
+            (root, first + 1 < components.len())
+        }
+    }
+}
+
+/// Per-path debouncing and per-routine rate limiting.
+#[derive(Debug, Default)]
+pub struct Debouncer {
+    /// routine -> path -> last event time
+    pending: HashMap<Uuid, BTreeMap<PathBuf, Instant>>,
+    last_fired: HashMap<Uuid, Instant>,
+}
+
+impl Debouncer {
+    /// Note an event for `path` on a routine's watch.
+    pub fn record(&mut self, routine_id: Uuid, path: PathBuf, now: Instant) {
+        self.pending
+            .entry(routine_id)
+            .or_default()
+            .insert(path, now);
+    }
+
+    /// Paths that have been quiet for the debounce window, if the routine is
+    /// out of cooldown.
+    pub fn ready(&self, watch: &FileWatch, now: Instant) -> Vec<PathBuf> {
+        let id = watch.routine.id;
+        if let Some(last) = self.last_fired.get(&id)
+            && now.duration_since(*last) < watch.routine.guardrails.cooldown
+        {
+            return Vec::new();
+        }
+        self.pending
+            .get(&id)
+            .map(|paths| {
+                paths
+                    .iter()
+                    .filter(|(_, seen)| now.duration_since(**seen) >= watch.debounce)
+                    .map(|(path, _)| path.clone())
+                    .take(MAX_PATHS_PER_RUN)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Record that a routine ran for `paths`.
+    pub fn mark_fired(&mut self, routine_id: Uuid, paths: &[PathBuf], now: Instant) {
+        if let Some(pending) = self.pending.get_mut(&routine_id) {
+            for path in paths {
+                pending.remove(path);
+            }
+        }
+        self.last_fired.insert(routine_id, now);
+    }
+
+    /// Forget routines that no longer have a file trigger.
+    pub fn retain(&mut self, ids: &HashSet<Uuid>) {
+        self.pending.retain(|id, _| ids.contains(id));
+        self.last_fired.retain(|id, _| ids.contains(id));
+    }
+}
+
+/// Start OS watches for every root; paths from create/modify events are sent
+/// to `tx`.
+fn start_watcher(
+    watches: &[FileWatch],
+    tx: mpsc::UnboundedSender<PathBuf>,
+) -> Option<RecommendedWatcher> {
+    if watches.is_empty() {
+        return None;
+    }
+
+    let mut watcher = match notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
+        if let Ok(event) = res
+            && matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+        {
+            for path in event.paths {
+                let _ = tx.send(path);
+            }
+        }
+    }) {
+        Ok(w) => w,
+        Err(e) => {
+            tracing::error!("Failed to start routine file watcher: {}", e);
+            return None;
+        }
+    };
+
+    // One watch per root, recursive if any routine needs it.
+    let mut roots: HashMap<&Path, bool> = HashMap::new();
+    for watch in watches {
+        *roots.entry(watch.root.as_path()).or_default() |= watch.recursive;
+    }
+    for (root, recursive) in roots {
+        let mode = if recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        if let Err(e) = watcher.watch(root, mode) {
+            tracing::warn!("Cannot watch {} for routines: {}", root.display(), e);
+        }
+    }
+    Some(watcher)
+}
+
+/// Spawn the file trigger task. It reloads its watches whenever
+/// [`RoutineEngine::refresh_file_watches`] is called.
+pub fn spawn_file_watcher(engine: Arc<RoutineEngine>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let reload = engine.file_watch_reload();
+        let mut debouncer = Debouncer::default();
+        let mut ticker = tokio::time::interval(TICK);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            let watches = engine.file_watches().await;
+            let ids = watches.iter().map(|w| w.routine.id).collect();
+            debouncer.retain(&ids);
+
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let _watcher = start_watcher(&watches, tx);
+            if !watches.is_empty() {
+                tracing::info!("Watching files for {} routine(s)", watches.len());
+            }
+
+            loop {
+                tokio::select! {
+                    _ = reload.notified() => break,
+                    Some(path) = rx.recv() => {
+                        let now = Instant::now();
+                        for watch in watches.iter().filter(|w| w.matches(&path)) {
+                            debouncer.record(watch.routine.id, path.clone(), now);
+                        }
+                    }
+                    _ = ticker.tick() => {
+                        let now = Instant::now();
+                        for watch in &watches {
+                            let paths = debouncer.ready(watch, now);
+                            if !paths.is_empty()
+                                && engine.fire_file_trigger(&watch.routine, &paths).await
+                            {
+                                debouncer.mark_fired(watch.routine.id, &paths, now);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::routine::{NotifyConfig, RoutineAction, RoutineGuardrails};
+
+    fn file_routine(path: &str, cooldown_secs: u64) -> Routine {
+        Routine {
+            id: Uuid::new_v4(),
+            name: "invoices".to_string(),
+            description: String::new(),
+            user_id: "default".to_string(),
+            enabled: true,
+            trigger: Trigger::File {
+                path: path.to_string(),
+                debounce_secs: 2,
+            },
+            action: RoutineAction::Lightweight {
+                prompt: "File it".to_string(),
+                context_paths: Vec::new(),
+                max_tokens: 1024,
+            },
+            guardrails: RoutineGuardrails {
+                cooldown: Duration::from_secs(cooldown_secs),
+                max_concurrent: 1,
+                dedup_window: None,
+            },
+            notify: NotifyConfig::default(),
+            last_run_at: None,
+            next_fire_at: None,
+            run_count: 0,
+            consecutive_failures: 0,
+            state: serde_json::json!({}),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_file_watch_roots_and_matching() {
+        let watch = FileWatch::new(file_routine("/data/inbox/*.pdf", 0)).unwrap();
+        assert_eq!(watch.root, PathBuf::from("/data/inbox"));
+        assert!(!watch.recursive);
+        assert!(watch.matches(Path::new("/data/inbox/invoice.pdf")));
+        assert!(!watch.matches(Path::new("/data/inbox/notes.txt")));
+
+        let watch = FileWatch::new(file_routine("/data/**/*.csv", 0)).unwrap();
+        assert_eq!(watch.root, PathBuf::from("/data"));
+        assert!(watch.recursive);
+
+        let watch = FileWatch::new(file_routine("/data/report.csv", 0)).unwrap();
+        assert_eq!(watch.root, PathBuf::from("/data"));
+
+        if let Some(home) = dirs::home_dir() {
+            let watch = FileWatch::new(file_routine("~/Downloads/*.pdf", 0)).unwrap();
+            assert_eq!(watch.root, home.join("Downloads"));
+        }
+        assert!(FileWatch::new(file_routine("/data/[", 0)).is_err());
+    }
+
+    #[test]
+    fn test_debouncer_waits_for_quiet_and_batches() {
+        let watch = FileWatch::new(file_routine("/in/*", 60)).unwrap();
+        let id = watch.routine.id;
+        let mut debouncer = Debouncer::default();
+        let t0 = Instant::now();
+
+        debouncer.record(id, PathBuf::from("/in/a.pdf"), t0);
+        debouncer.record(id, PathBuf::from("/in/b.pdf"), t0 + Duration::from_secs(1));
+        // a.pdf is still being written.
+        debouncer.record(id, PathBuf::from("/in/a.pdf"), t0 + Duration::from_secs(1));
+        assert!(
+            debouncer
+                .ready(&watch, t0 + Duration::from_secs(2))
+                .is_empty()
+        );
+
+        let ready = debouncer.ready(&watch, t0 + Duration::from_secs(3));
+        assert_eq!(
+            ready,
+            vec![PathBuf::from("/in/a.pdf"), PathBuf::from("/in/b.pdf")]
+        );
+        debouncer.mark_fired(id, &ready, t0 + Duration::from_secs(3));
+
+        // During the cooldown new files wait rather than being dropped.
+        debouncer.record(id, PathBuf::from("/in/c.pdf"), t0 + Duration::from_secs(4));
+        assert!(
+            debouncer
+                .ready(&watch, t0 + Duration::from_secs(30))
+                .is_empty()
+        );
+        assert_eq!(
+            debouncer.ready(&watch, t0 + Duration::from_secs(63)),
+            vec![PathBuf::from("/in/c.pdf")]
+        );
+    }
+}
@@ -8,16 +8,24 @@ use tokio::sync::{RwLock, mpsc, oneshot};
 use tokio::task::JoinHandle;
 use uuid::Uuid;
 
+use crate::agent::output_summary::OutputSummarizer;
+use crate::agent::priority::spawn_background;
 use crate::agent::task::{Task, TaskContext, TaskOutput};
 use crate::agent::worker::{Worker, WorkerDeps};
+use crate::channels::ChannelManager;
 use crate::config::AgentConfig;
 use crate::context::{ContextManager, JobContext, JobState};
 use crate::db::Database;
 use crate::error::{Error, JobError};
 use crate::llm::LlmProvider;
 use crate::safety::SafetyLayer;
+use crate::sandbox::EphemeralCredentials;
 use crate::tools::ToolRegistry;
 
+/// How long `stop` waits for a cancelled worker to record its partial
+/// result before aborting it.
+const STOP_WAIT: Duration = Duration::from_secs(2);
+
 /// Message to send to a worker.
 #[derive(Debug)]
 pub enum WorkerMessage {
@@ -49,6 +57,10 @@ pub struct Scheduler {
     safety: Arc<SafetyLayer>,
     tools: Arc<ToolRegistry>,
     store: Option<Arc<dyn Database>>,
+    output_summarizer: Option<Arc<OutputSummarizer>>,
+    channels: Option<Arc<ChannelManager>>,
+    /// Short-lived credentials minted when a job starts and revoked when it ends.
+    ephemeral: Option<Arc<EphemeralCredentials>>,
     /// Running jobs (main LLM-driven jobs).
     jobs: Arc<RwLock<HashMap<Uuid, ScheduledJob>>>,
     /// Running sub-tasks (tool executions, background tasks).
@@ -72,11 +84,37 @@ impl Scheduler {
             safety,
             tools,
             store,
+            output_summarizer: None,
+            channels: None,
+            ephemeral: None,
             jobs: Arc::new(RwLock::new(HashMap::new())),
             subtasks: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// Condense large tool outputs in jobs with this summarizer.
+    pub fn with_output_summarizer(mut self, summarizer: Option<Arc<OutputSummarizer>>) -> Self {
+        self.output_summarizer = summarizer;
+        self
+    }
+
+    /// Notify the originating channel through these channels when a job is
+    /// cancelled.
+    pub fn with_channels(mut self, channels: Arc<ChannelManager>) -> Self {
+        self.channels = Some(channels);
+        self
+    }
+
+    /// Mint credentials from these issuers for each job before it starts,
+    /// and revoke them when it ends.
+    pub fn with_ephemeral_credentials(
+        mut self,
+        credentials: Option<Arc<EphemeralCredentials>>,
+    ) -> Self {
+        self.ephemeral = credentials;
+        self
+    }
+
     /// Schedule a job for execution.
     pub async fn schedule(&self, job_id: Uuid) -> Result<(), JobError> {
         // Hold write lock for the entire check-insert sequence to prevent
@@ -94,6 +132,17 @@ impl Scheduler {
                 });
             }
 
+            // A job that needs credentials it cannot get does not start.
+            if let Some(ref ephemeral) = self.ephemeral {
+                ephemeral
+                    .start_job(job_id)
+                    .await
+                    .map_err(|e| JobError::Failed {
+                        id: job_id,
+                        reason: e.to_string(),
+                    })?;
+            }
+
             // Transition job to in_progress
             self.context_manager
                 .update_context(job_id, |ctx| {
@@ -120,11 +169,13 @@ impl Scheduler {
                 store: self.store.clone(),
                 timeout: self.config.job_timeout,
                 use_planning: self.config.use_planning,
+                output_summarizer: self.output_summarizer.clone(),
+                channels: self.channels.clone(),
             };
             let worker = Worker::new(job_id, deps);
 
-            // Spawn worker task
-            let handle = tokio::spawn(async move {
+            // Spawn worker task (on the background runtime when it runs)
+            let handle = spawn_background(async move {
                 if let Err(e) = worker.run(rx).await {
                     tracing::error!("Worker for job {} failed: {}", job_id, e);
                 }
@@ -139,6 +190,7 @@ impl Scheduler {
 
         // Cleanup task for this job to avoid capacity leaks
         let jobs = Arc::clone(&self.jobs);
+        let ephemeral = self.ephemeral.clone();
         tokio::spawn(async move {
             loop {
                 let finished = {
@@ -151,6 +203,9 @@ impl Scheduler {
 
                 if finished {
                     jobs.write().await.remove(&job_id);
+                    if let Some(ephemeral) = ephemeral {
+                        ephemeral.end_job(job_id).await;
+                    }
                     break;
                 }
 
@@ -350,7 +405,7 @@ impl Scheduler {
             .into());
         }
 
-        if tool.requires_approval() {
+        if tool.requires_approval_for(&params) || tool.requires_explicit_approval(&params) {
             return Err(crate::error::ToolError::AuthRequired {
                 name: tool_name.to_string(),
             }
@@ -396,19 +451,25 @@ impl Scheduler {
 
     /// Stop a running job.
     pub async fn stop(&self, job_id: Uuid) -> Result<(), JobError> {
-        let mut jobs = self.jobs.write().await;
+        // Don't hold the lock while the worker winds down.
+        let scheduled = self.jobs.write().await.remove(&job_id);
 
-        if let Some(scheduled) = jobs.remove(&job_id) {
-            // Send stop signal
+        if let Some(mut scheduled) = scheduled {
+            // Cancel in-flight work; the worker records the partial result
+            // and notifies the originating channel.
+            self.context_manager.cancel(job_id).await;
             let _ = scheduled.tx.send(WorkerMessage::Stop).await;
 
-            // Give it a moment to clean up
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-
-            // Abort if still running
-            if !scheduled.handle.is_finished() {
+            // Give it a moment to wind down, then abort if still running
+            if tokio::time::timeout(STOP_WAIT, &mut scheduled.handle)
+                .await
+                .is_err()
+            {
                 scheduled.handle.abort();
             }
+            if let Some(ref ephemeral) = self.ephemeral {
+                ephemeral.end_job(job_id).await;
+            }
 
             // Update job state
             self.context_manager
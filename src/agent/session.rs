@@ -16,7 +16,11 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::agent::approvals::ApprovalRules;
+use crate::agent::project::ProjectBinding;
 use crate::llm::ChatMessage;
+use crate::safety::elevated::ElevatedMode;
+use crate::tools::builtin::InputForm;
 
 /// A session containing one or more threads.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +42,16 @@ pub struct Session {
     /// Tools that have been auto-approved for this session ("always approve").
     #[serde(default)]
     pub auto_approved_tools: HashSet<String>,
+    /// Scoped "always approve" rules, used when there is no database to
+    /// persist them per user.
+    #[serde(default)]
+    pub approval_rules: ApprovalRules,
+    /// Host project the session is bound to (`/project use <dir>`).
+    #[serde(default)]
+    pub project: Option<ProjectBinding>,
+    /// Elevated mode, bound to this session (`/elevated`).
+    #[serde(default)]
+    pub elevated: ElevatedMode,
 }
 
 impl Session {
@@ -53,6 +67,9 @@ impl Session {
             last_active_at: now,
             metadata: serde_json::Value::Null,
             auto_approved_tools: HashSet::new(),
+            approval_rules: ApprovalRules::default(),
+            project: None,
+            elevated: ElevatedMode::new(),
         }
     }
 
@@ -115,6 +132,8 @@ pub enum ThreadState {
     Processing,
     /// Thread is waiting for user approval.
     AwaitingApproval,
+    /// Thread is waiting for the user to answer an `ask_user` form.
+    AwaitingInput,
     /// Thread has completed (no more turns expected).
     Completed,
     /// Thread was interrupted.
@@ -150,6 +169,32 @@ pub struct PendingApproval {
     pub context_messages: Vec<ChatMessage>,
 }
 
+/// Pending structured input request stored on a thread.
+///
+/// Created when the `ask_user` tool runs. The next user message on the
+/// thread is validated against the form and, once valid, becomes the tool
+/// result the agentic loop resumes from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingInput {
+    /// Unique request ID.
+    pub request_id: Uuid,
+    /// The form the user has to fill in.
+    pub form: InputForm,
+    /// Tool call ID from LLM (for proper context continuation).
+    pub tool_call_id: String,
+    /// Context messages at the time of the request (to resume from).
+    pub context_messages: Vec<ChatMessage>,
+    /// When the request stops accepting answers.
+    pub expires_at: DateTime<Utc>,
+}
+
+impl PendingInput {
+    /// Whether the answer window has passed.
+    pub fn is_expired(&self) -> bool {
+        Utc::now() >= self.expires_at
+    }
+}
+
 /// A conversation thread within a session.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Thread {
@@ -173,6 +218,9 @@ pub struct Thread {
     /// Pending auth token request (thread is in auth mode).
     #[serde(default)]
     pub pending_auth: Option<PendingAuth>,
+    /// Pending structured input request (when state is AwaitingInput).
+    #[serde(default)]
+    pub pending_input: Option<PendingInput>,
     /// Last NEAR AI response ID for response chaining. Persisted to DB
     /// metadata so we can resume chaining across restarts.
     #[serde(default)]
@@ -193,6 +241,7 @@ impl Thread {
             metadata: serde_json::Value::Null,
             pending_approval: None,
             pending_auth: None,
+            pending_input: None,
             last_response_id: None,
         }
     }
@@ -210,6 +259,7 @@ impl Thread {
             metadata: serde_json::Value::Null,
             pending_approval: None,
             pending_auth: None,
+            pending_input: None,
             last_response_id: None,
         }
     }
@@ -276,6 +326,18 @@ impl Thread {
         self.updated_at = Utc::now();
     }
 
+    /// Mark the thread as waiting for structured user input.
+    pub fn await_input(&mut self, pending: PendingInput) {
+        self.state = ThreadState::AwaitingInput;
+        self.pending_input = Some(pending);
+        self.updated_at = Utc::now();
+    }
+
+    /// Take the pending input request (clearing it from the thread).
+    pub fn take_pending_input(&mut self) -> Option<PendingInput> {
+        self.pending_input.take()
+    }
+
     /// Enter auth mode: next user message will be routed directly to
     /// the credential store, bypassing the normal pipeline entirely.
     pub fn enter_auth_mode(&mut self, extension_name: String) {
@@ -317,6 +379,29 @@ impl Thread {
         messages
     }
 
+    /// The conversation as a Markdown transcript, one section per turn.
+    pub fn transcript_markdown(&self) -> String {
+        let mut out = format!(
+            "# Conversation {}\n\nStarted {} UTC, {} turn(s).\n",
+            self.id,
+            self.created_at.format("%Y-%m-%d %H:%M"),
+            self.turns.len()
+        );
+        for turn in &self.turns {
+            out.push_str(&format!(
+                "\n## You ({})\n\n{}\n",
+                turn.started_at.format("%Y-%m-%d %H:%M"),
+                turn.user_input.trim()
+            ));
+            if let Some(ref response) = turn.response {
+                out.push_str(&format!("\n## Assistant\n\n{}\n", response.trim()));
+            } else if let Some(ref error) = turn.error {
+                out.push_str(&format!("\n> Turn failed: {}\n", error));
+            }
+        }
+        out
+    }
+
     /// Truncate turns to a specific count (keeping most recent).
     pub fn truncate_turns(&mut self, keep: usize) {
         if self.turns.len() > keep {
@@ -329,6 +414,14 @@ impl Thread {
         }
     }
 
+    /// Remove the last `n` turns, returning them oldest first.
+    pub fn remove_last_turns(&mut self, n: usize) -> Vec<Turn> {
+        let start = self.turns.len().saturating_sub(n);
+        let removed = self.turns.split_off(start);
+        self.updated_at = Utc::now();
+        removed
+    }
+
     /// Restore thread state from a checkpoint's messages.
     ///
     /// Clears existing turns and rebuilds from message pairs.
@@ -394,6 +487,9 @@ pub struct Turn {
     pub completed_at: Option<DateTime<Utc>>,
     /// Error message (if failed).
     pub error: Option<String>,
+    /// Failures the turn resumed from.
+    #[serde(default)]
+    pub recoveries: Vec<TurnRecovery>,
 }
 
 impl Turn {
@@ -408,6 +504,7 @@ impl Turn {
             started_at: Utc::now(),
             completed_at: None,
             error: None,
+            recoveries: Vec::new(),
         }
     }
 
@@ -454,6 +551,26 @@ impl Turn {
             call.error = Some(error.into());
         }
     }
+
+    /// Record a failure the turn is resuming from.
+    pub fn record_recovery(&mut self, attempt: u32, error: impl Into<String>) {
+        self.recoveries.push(TurnRecovery {
+            attempt,
+            error: error.into(),
+            at: Utc::now(),
+        });
+    }
+}
+
+/// A failure partway through a turn that the agent resumed from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnRecovery {
+    /// 1-based recovery attempt within the turn.
+    pub attempt: u32,
+    /// Summary of the error.
+    pub error: String,
+    /// When the failure happened.
+    pub at: DateTime<Utc>,
 }
 
 /// Record of a tool call made during a turn.
@@ -508,6 +625,19 @@ mod tests {
         assert_eq!(messages.len(), 4);
     }
 
+    #[test]
+    fn test_transcript_markdown() {
+        let mut thread = Thread::new(Uuid::new_v4());
+        thread.start_turn("What is the plan?");
+        thread.complete_turn("Ship on Friday.");
+
+        let md = thread.transcript_markdown();
+        assert!(md.starts_with(&format!("# Conversation {}\n", thread.id)));
+        assert!(md.contains("1 turn(s)"));
+        assert!(md.contains("\nWhat is the plan?\n"));
+        assert!(md.contains("## Assistant\n\nShip on Friday.\n"));
+    }
+
     #[test]
     fn test_turn_tool_calls() {
         let mut turn = Turn::new(0, "Test input");
@@ -900,6 +1030,20 @@ mod tests {
         assert!(turn.tool_calls[0].result.is_none());
     }
 
+    #[test]
+    fn test_turn_recoveries_default_when_missing() {
+        let mut turn = Turn::new(0, "test");
+        turn.record_recovery(1, "Provider openai request failed: HTTP 500");
+        assert_eq!(turn.recoveries.len(), 1);
+        assert_eq!(turn.recoveries[0].attempt, 1);
+
+        // Turns serialized before recoveries were tracked still load.
+        let mut value = serde_json::to_value(&turn).unwrap();
+        value.as_object_mut().unwrap().remove("recoveries");
+        let restored: Turn = serde_json::from_value(value).unwrap();
+        assert!(restored.recoveries.is_empty());
+    }
+
     #[test]
     fn test_turn_number_increments() {
         let mut thread = Thread::new(Uuid::new_v4());
@@ -978,6 +1122,31 @@ mod tests {
         assert!(thread.pending_approval.is_none());
     }
 
+    #[test]
+    fn test_pending_input_flow() {
+        let mut thread = Thread::new(Uuid::new_v4());
+        let form = InputForm::from_params(&serde_json::json!({"question": "Which city?"})).unwrap();
+
+        thread.await_input(PendingInput {
+            request_id: Uuid::new_v4(),
+            form,
+            tool_call_id: "call_789".to_string(),
+            context_messages: vec![],
+            expires_at: Utc::now() - chrono::Duration::seconds(1),
+        });
+        assert_eq!(thread.state, ThreadState::AwaitingInput);
+
+        // Survives a serialization round trip (sessions are persisted)
+        let json = serde_json::to_string(&thread).unwrap();
+        let restored: Thread = serde_json::from_str(&json).unwrap();
+        let pending = restored.pending_input.as_ref().unwrap();
+        assert_eq!(pending.form.question, "Which city?");
+        assert!(pending.is_expired());
+
+        assert!(thread.take_pending_input().is_some());
+        assert!(thread.pending_input.is_none());
+    }
+
     #[test]
     fn test_active_thread_accessors() {
         let mut session = Session::new("user-1");
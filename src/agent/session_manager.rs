@@ -3,7 +3,7 @@
 //! Maps external channel thread IDs to internal UUIDs and manages undo state
 //! for each thread.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use tokio::sync::{Mutex, RwLock};
@@ -167,6 +167,16 @@ impl SessionManager {
         mgr
     }
 
+    /// Ids of every thread in a live session.
+    pub async fn thread_ids(&self) -> HashSet<Uuid> {
+        let sessions: Vec<_> = self.sessions.read().await.values().cloned().collect();
+        let mut ids = HashSet::new();
+        for session in sessions {
+            ids.extend(session.lock().await.threads.keys().copied());
+        }
+        ids
+    }
+
     /// Remove sessions that have been idle for longer than the given duration.
     ///
     /// Returns the number of sessions pruned.
@@ -299,8 +309,8 @@ mod tests {
         let manager = SessionManager::new();
 
         // Create two sessions and resolve threads (which updates last_active_at)
-        let (_, _thread_id) = manager.resolve_thread("user-active", "cli", None).await;
-        let (s2, _thread_id) = manager.resolve_thread("user-stale", "cli", None).await;
+        let (_, active_thread) = manager.resolve_thread("user-active", "cli", None).await;
+        let (s2, stale_thread) = manager.resolve_thread("user-stale", "cli", None).await;
 
         // Backdate the stale session's last_active_at AFTER thread creation
         {
@@ -313,6 +323,8 @@ mod tests {
             .prune_stale_sessions(std::time::Duration::from_secs(86400 * 7))
             .await;
         assert_eq!(pruned, 1);
+        let live = manager.thread_ids().await;
+        assert!(live.contains(&active_thread) && !live.contains(&stale_thread));
 
         // Active session should still exist
         let sessions = manager.sessions.read().await;
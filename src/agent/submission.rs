@@ -6,6 +6,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::tools::builtin::InputForm;
+
 /// Parses user input into Submission types.
 pub struct SubmissionParser;
 
@@ -40,6 +42,11 @@ impl SubmissionParser {
         if lower == "/suggest" {
             return Submission::Suggest;
         }
+        if lower == "/export" || lower.starts_with("/export ") {
+            return Submission::Export {
+                format: lower.split_whitespace().nth(1).map(String::from),
+            };
+        }
         if lower == "/thread new" || lower == "/new" {
             return Submission::NewThread;
         }
@@ -56,10 +63,15 @@ impl SubmissionParser {
                 args: vec![],
             };
         }
-        if lower == "/tools" {
+        if lower == "/tools" || lower.starts_with("/tools ") {
+            let args: Vec<String> = trimmed
+                .split_whitespace()
+                .skip(1)
+                .map(|s| s.to_string())
+                .collect();
             return Submission::SystemCommand {
                 command: "tools".to_string(),
-                args: vec![],
+                args,
             };
         }
         if lower == "/ping" {
@@ -74,6 +86,106 @@ impl SubmissionParser {
                 args: vec![],
             };
         }
+        if lower == "/project" || lower.starts_with("/project ") {
+            let args: Vec<String> = trimmed
+                .split_whitespace()
+                .skip(1)
+                .map(|s| s.to_string())
+                .collect();
+            return Submission::Project { args };
+        }
+        if lower == "/approvals" || lower.starts_with("/approvals ") {
+            let args: Vec<String> = trimmed
+                .split_whitespace()
+                .skip(1)
+                .map(|s| s.to_string())
+                .collect();
+            return Submission::Approvals { args };
+        }
+        if lower == "/elevated" || lower.starts_with("/elevated ") {
+            let args: Vec<String> = trimmed
+                .split_whitespace()
+                .skip(1)
+                .map(|s| s.to_string())
+                .collect();
+            return Submission::Elevated { args };
+        }
+        if lower == "/budget" || lower.starts_with("/budget ") {
+            let args: Vec<String> = trimmed
+                .split_whitespace()
+                .skip(1)
+                .map(|s| s.to_string())
+                .collect();
+            return Submission::Budget { args };
+        }
+        for setting in ["temperature", "style", "verbosity"] {
+            let command = format!("/{}", setting);
+            if lower == command || lower.starts_with(&format!("{} ", command)) {
+                let args: Vec<String> = trimmed
+                    .split_whitespace()
+                    .skip(1)
+                    .map(|s| s.to_string())
+                    .collect();
+                return Submission::Generation {
+                    setting: setting.to_string(),
+                    args,
+                };
+            }
+        }
+        if lower == "/sandbox" || lower.starts_with("/sandbox ") {
+            let args: Vec<String> = trimmed
+                .split_whitespace()
+                .skip(1)
+                .map(|s| s.to_string())
+                .collect();
+            return Submission::Sandbox { args };
+        }
+        if lower == "/redact" || lower.starts_with("/redact ") {
+            let args: Vec<String> = trimmed
+                .split_whitespace()
+                .skip(1)
+                .map(|s| s.to_string())
+                .collect();
+            return Submission::Redact { args };
+        }
+        if lower == "/redacted" || lower.starts_with("/redacted ") {
+            let args: Vec<String> = trimmed
+                .split_whitespace()
+                .skip(1)
+                .map(|s| s.to_string())
+                .collect();
+            return Submission::Redacted { args };
+        }
+        if lower == "/glossary" || lower.starts_with("/glossary ") {
+            let args: Vec<String> = trimmed
+                .split_whitespace()
+                .skip(1)
+                .map(|s| s.to_string())
+                .collect();
+            return Submission::Glossary { args };
+        }
+        if lower == "/away" || lower.starts_with("/away ") {
+            let args: Vec<String> = trimmed
+                .split_whitespace()
+                .skip(1)
+                .map(|s| s.to_string())
+                .collect();
+            return Submission::SystemCommand {
+                command: "away".to_string(),
+                args,
+            };
+        }
+        if lower == "/focus" || lower.starts_with("/focus ") {
+            let args: Vec<String> = trimmed
+                .split_whitespace()
+                .skip(1)
+                .map(|s| s.to_string())
+                .collect();
+            return Submission::SystemCommand {
+                command: "focus".to_string(),
+                args,
+            };
+        }
         if lower.starts_with("/model") {
             let args: Vec<String> = trimmed
                 .split_whitespace()
@@ -212,6 +324,74 @@ pub enum Submission {
     /// Suggest next steps based on the current thread.
     Suggest,
 
+    /// Export the current thread as a document (`/export [pdf|md]`).
+    Export {
+        /// Requested format; PDF when absent.
+        format: Option<String>,
+    },
+
+    /// Show, bind, or clear the session's project (`/project [use <dir>|clear]`).
+    Project {
+        /// Subcommand and its arguments.
+        args: Vec<String>,
+    },
+
+    /// List or revoke remembered approvals (`/approvals [revoke <id>|clear]`).
+    Approvals {
+        /// Subcommand and its arguments.
+        args: Vec<String>,
+    },
+
+    /// Show or toggle elevated mode (`/elevated [on [minutes]|off]`).
+    Elevated {
+        /// Subcommand and its arguments.
+        args: Vec<String>,
+    },
+
+    /// Show token and cost spend, or lift today's caps
+    /// (`/budget [override]`).
+    Budget {
+        /// Subcommand and its arguments.
+        args: Vec<String>,
+    },
+
+    /// Show or change a per-session generation setting
+    /// (`/temperature`, `/style`, `/verbosity`).
+    Generation {
+        /// Which setting: "temperature", "style" or "verbosity".
+        setting: String,
+        /// New value, or "reset"; empty to show the current settings.
+        args: Vec<String>,
+    },
+
+    /// Show the sandbox mode or accept the host fallback risk
+    /// (`/sandbox [accept-risk|revoke]`).
+    Sandbox {
+        /// Subcommand and its arguments.
+        args: Vec<String>,
+    },
+
+    /// Remove the last turns from the conversation, memory and logs
+    /// (`/redact last [n]`).
+    Redact {
+        /// Subcommand and its arguments.
+        args: Vec<String>,
+    },
+
+    /// Inspect tool outputs kept by the redaction vault
+    /// (`/redacted [show <id>|audit]`).
+    Redacted {
+        /// Subcommand and its arguments.
+        args: Vec<String>,
+    },
+
+    /// Show or edit the project glossary
+    /// (`/glossary [add|avoid|remove|ban|unban ...]`).
+    Glossary {
+        /// Subcommand and its arguments.
+        args: Vec<String>,
+    },
+
     /// Quit the agent. Bypasses thread-state checks.
     Quit,
 
@@ -289,6 +469,16 @@ impl Submission {
                 | Self::Heartbeat
                 | Self::Summarize
                 | Self::Suggest
+                | Self::Export { .. }
+                | Self::Project { .. }
+                | Self::Approvals { .. }
+                | Self::Elevated { .. }
+                | Self::Budget { .. }
+                | Self::Generation { .. }
+                | Self::Sandbox { .. }
+                | Self::Redact { .. }
+                | Self::Redacted { .. }
+                | Self::Glossary { .. }
                 | Self::SystemCommand { .. }
         )
     }
@@ -315,6 +505,16 @@ pub enum SubmissionResult {
         parameters: serde_json::Value,
     },
 
+    /// Need structured input from the user before continuing.
+    NeedInput {
+        /// ID of the input request.
+        request_id: Uuid,
+        /// The form to present.
+        form: InputForm,
+        /// Validation error from a previous answer, shown above the form.
+        error: Option<String>,
+    },
+
     /// Successfully processed (for control commands).
     Ok {
         /// Optional message.
@@ -464,6 +664,104 @@ mod tests {
         assert!(matches!(submission, Submission::Suggest));
     }
 
+    #[test]
+    fn test_parser_export() {
+        let submission = SubmissionParser::parse("/export");
+        assert!(matches!(submission, Submission::Export { format: None }));
+        assert!(submission.is_control());
+
+        let submission = SubmissionParser::parse("/export MD");
+        assert!(matches!(submission, Submission::Export { format: Some(f) } if f == "md"));
+    }
+
+    #[test]
+    fn test_parser_project() {
+        let submission = SubmissionParser::parse("/project use ~/Code/Acme");
+        match submission {
+            Submission::Project { args } => assert_eq!(args, vec!["use", "~/Code/Acme"]),
+            _ => panic!("Expected Project, got {:?}", submission),
+        }
+        assert!(SubmissionParser::parse("/project").is_control());
+        assert!(matches!(
+            SubmissionParser::parse("/projects"),
+            Submission::UserInput { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parser_approvals() {
+        let submission = SubmissionParser::parse("/approvals revoke 1a2b3c4d");
+        match submission {
+            Submission::Approvals { args } => assert_eq!(args, vec!["revoke", "1a2b3c4d"]),
+            _ => panic!("Expected Approvals, got {:?}", submission),
+        }
+        assert!(SubmissionParser::parse("/APPROVALS").is_control());
+    }
+
+    #[test]
+    fn test_parser_generation_settings() {
+        match SubmissionParser::parse("/temperature 0.2") {
+            Submission::Generation { setting, args } => {
+                assert_eq!(setting, "temperature");
+                assert_eq!(args, vec!["0.2"]);
+            }
+            other => panic!("Expected Generation, got {:?}", other),
+        }
+        match SubmissionParser::parse("/Style concise") {
+            Submission::Generation { setting, args } => {
+                assert_eq!(setting, "style");
+                assert_eq!(args, vec!["concise"]);
+            }
+            other => panic!("Expected Generation, got {:?}", other),
+        }
+        assert!(SubmissionParser::parse("/verbosity").is_control());
+        assert!(!matches!(
+            SubmissionParser::parse("/stylesheet"),
+            Submission::Generation { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parser_elevated_and_redacted() {
+        match SubmissionParser::parse("/elevated on 15") {
+            Submission::Elevated { args } => assert_eq!(args, vec!["on", "15"]),
+            other => panic!("Expected Elevated, got {:?}", other),
+        }
+        match SubmissionParser::parse("/Redacted show 1a2b3c4d") {
+            Submission::Redacted { args } => assert_eq!(args, vec!["show", "1a2b3c4d"]),
+            other => panic!("Expected Redacted, got {:?}", other),
+        }
+        assert!(SubmissionParser::parse("/redacted").is_control());
+        match SubmissionParser::parse("/redact last 2") {
+            Submission::Redact { args } => assert_eq!(args, vec!["last", "2"]),
+            other => panic!("Expected Redact, got {:?}", other),
+        }
+        assert!(SubmissionParser::parse("/redact").is_control());
+        match SubmissionParser::parse("/budget override") {
+            Submission::Budget { args } => assert_eq!(args, vec!["override"]),
+            other => panic!("Expected Budget, got {:?}", other),
+        }
+        assert!(SubmissionParser::parse("/budget").is_control());
+        match SubmissionParser::parse("/sandbox accept-risk") {
+            Submission::Sandbox { args } => assert_eq!(args, vec!["accept-risk"]),
+            other => panic!("Expected Sandbox, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parser_glossary() {
+        match SubmissionParser::parse("/glossary add Deployment Unit: a release bundle") {
+            Submission::Glossary { args } => {
+                assert_eq!(
+                    args,
+                    vec!["add", "Deployment", "Unit:", "a", "release", "bundle"]
+                )
+            }
+            other => panic!("Expected Glossary, got {:?}", other),
+        }
+        assert!(SubmissionParser::parse("/glossary").is_control());
+    }
+
     #[test]
     fn test_parser_invalid_commands_become_user_input() {
         // Invalid UUID should become user input
@@ -595,6 +893,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parser_system_command_focus() {
+        let submission = SubmissionParser::parse("/focus 1h 30m");
+        assert!(
+            matches!(submission, Submission::SystemCommand { command, args } if command == "focus" && args == vec!["1h", "30m"])
+        );
+        assert!(matches!(
+            SubmissionParser::parse("/focused on this"),
+            Submission::UserInput { .. }
+        ));
+    }
+
     #[test]
     fn test_parser_system_command_version() {
         let submission = SubmissionParser::parse("/version");
@@ -611,6 +921,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parser_system_command_tools_with_name() {
+        let submission = SubmissionParser::parse("/tools memory_search");
+        assert!(
+            matches!(submission, Submission::SystemCommand { command, args } if command == "tools" && args == vec!["memory_search".to_string()])
+        );
+    }
+
     #[test]
     fn test_parser_system_command_ping() {
         let submission = SubmissionParser::parse("/ping");
@@ -7,9 +7,11 @@ use futures::future::join_all;
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
+use crate::agent::output_summary::OutputSummarizer;
 use crate::agent::scheduler::WorkerMessage;
 use crate::agent::task::TaskOutput;
-use crate::context::{ContextManager, JobState};
+use crate::channels::{ChannelManager, OutgoingResponse};
+use crate::context::{ContextManager, JobState, Memory};
 use crate::db::Database;
 use crate::error::Error;
 use crate::llm::{
@@ -31,6 +33,9 @@ pub struct WorkerDeps {
     pub store: Option<Arc<dyn Database>>,
     pub timeout: Duration,
     pub use_planning: bool,
+    pub output_summarizer: Option<Arc<OutputSummarizer>>,
+    /// Channels to tell about cancelled jobs (the job's `origin_channel`).
+    pub channels: Option<Arc<ChannelManager>>,
 }
 
 /// Worker that executes a single job.
@@ -111,6 +116,8 @@ impl Worker {
 
         // Get job context
         let job_ctx = self.context_manager().get_context(self.job_id).await?;
+        let _crash_context =
+            crate::crash::enter(format!("job {} {:?}", self.job_id, job_ctx.title));
 
         // Create reasoning engine
         let reasoning = Reasoning::new(self.llm().clone(), self.safety().clone());
@@ -131,21 +138,31 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
             job_ctx.title, job_ctx.description
         )));
 
-        // Main execution loop with timeout
+        // Main execution loop with timeout. Cancellation drops the loop,
+        // aborting in-flight LLM and HTTP calls; processes and containers
+        // started by tools are terminated by their drop guards.
+        let cancellation = self.context_manager().cancellation(self.job_id).await;
         let result = tokio::time::timeout(self.timeout(), async {
-            self.execution_loop(&mut rx, &reasoning, &mut reason_ctx)
-                .await
+            tokio::select! {
+                biased;
+                _ = cancellation.cancelled() => None,
+                result = self.execution_loop(&mut rx, &reasoning, &mut reason_ctx) => Some(result),
+            }
         })
         .await;
 
         match result {
-            Ok(Ok(())) => {
+            Ok(Some(Ok(()))) => {
                 tracing::info!("Worker for job {} completed successfully", self.job_id);
             }
-            Ok(Err(e)) => {
+            Ok(Some(Err(e))) => {
                 tracing::error!("Worker for job {} failed: {}", self.job_id, e);
                 self.mark_failed(&e.to_string()).await?;
             }
+            Ok(None) => {
+                tracing::info!("Worker for job {} cancelled", self.job_id);
+                self.mark_cancelled().await?;
+            }
             Err(_) => {
                 tracing::warn!("Worker for job {} timed out", self.job_id);
                 self.mark_stuck("Execution timeout").await?;
@@ -394,7 +411,7 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
             })?;
 
         // Tools requiring approval are blocked in autonomous jobs
-        if tool.requires_approval() {
+        if tool.requires_approval_for(params) || tool.requires_explicit_approval(params) {
             return Err(crate::error::ToolError::AuthRequired {
                 name: tool_name.to_string(),
             }
@@ -475,9 +492,11 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
         // Record action in memory and get the ActionRecord for persistence
         let action = match &result {
             Ok(Ok(output)) => {
-                let output_str = serde_json::to_string_pretty(&output.result)
-                    .ok()
-                    .map(|s| safety.sanitize_tool_output(tool_name, &s).content);
+                let output_str = serde_json::to_string_pretty(&output.result).ok().map(|s| {
+                    safety
+                        .sanitize_tool_output(&job_ctx.user_id, tool_name, &s)
+                        .content
+                });
                 context_manager
                     .update_memory(job_id, |mem| {
                         let rec = mem.create_action(tool_name, params.clone()).succeed(
@@ -552,15 +571,28 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
     ) -> Result<bool, Error> {
         match result {
             Ok(output) => {
-                // Sanitize output
-                let sanitized = self
-                    .safety()
-                    .sanitize_tool_output(&selection.tool_name, &output);
+                // Sanitize output before it is summarized or stored
+                let user_id = self
+                    .context_manager()
+                    .get_context(self.job_id)
+                    .await?
+                    .user_id;
+                let sanitized =
+                    self.safety()
+                        .sanitize_tool_output(&user_id, &selection.tool_name, &output);
+                let content = match &self.deps.output_summarizer {
+                    Some(summarizer) => {
+                        summarizer
+                            .condense(&selection.tool_name, sanitized.content)
+                            .await
+                    }
+                    None => sanitized.content,
+                };
 
                 // Add to context
                 let wrapped = self.safety().wrap_for_llm(
                     &selection.tool_name,
-                    &sanitized.content,
+                    &content,
                     sanitized.was_modified,
                 );
 
@@ -745,6 +777,61 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
         Ok(())
     }
 
+    /// Record what the job got done before it was cancelled and tell the
+    /// channel it came from.
+    async fn mark_cancelled(&self) -> Result<(), Error> {
+        let memory = self.context_manager().get_memory(self.job_id).await?;
+        let (summary, partial) = partial_result(&memory);
+
+        let (title, user_id, origin_channel) = self
+            .context_manager()
+            .update_context(self.job_id, |ctx| {
+                if !ctx.metadata.is_object() {
+                    ctx.metadata = serde_json::json!({});
+                }
+                ctx.metadata["partial_result"] = partial;
+                // Already Cancelled when cancel_job transitioned it first.
+                let _ = ctx.transition_to(JobState::Cancelled, Some(summary.clone()));
+                (
+                    ctx.title.clone(),
+                    ctx.user_id.clone(),
+                    ctx.metadata
+                        .get("origin_channel")
+                        .and_then(|v| v.as_str())
+                        .map(String::from),
+                )
+            })
+            .await?;
+        self.persist_status(JobState::Cancelled, Some(summary.clone()));
+
+        if let Some(channels) = self.deps.channels.clone() {
+            let response = OutgoingResponse::text(format!(
+                "Job '{}' ({}) was cancelled. {}",
+                title, self.job_id, summary
+            ));
+            let delivered = match origin_channel {
+                Some(ref channel) => channels
+                    .broadcast(channel, &user_id, response.clone())
+                    .await
+                    .is_ok(),
+                None => false,
+            };
+            if !delivered {
+                for (channel, result) in channels.broadcast_all(&user_id, response).await {
+                    if let Err(e) = result {
+                        tracing::warn!(
+                            "Failed to notify {} of cancelled job {}: {}",
+                            channel,
+                            self.job_id,
+                            e
+                        );
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     async fn mark_stuck(&self, reason: &str) -> Result<(), Error> {
         self.context_manager()
             .update_context(self.job_id, |ctx| ctx.mark_stuck(reason))
@@ -759,6 +846,41 @@ Report when the job is complete or if you encounter issues you cannot resolve."#
     }
 }
 
+/// Summarize the actions a cancelled job finished: a one-line summary for
+/// the status reason and notification, and the structured partial result
+/// stored in the job metadata.
+fn partial_result(memory: &Memory) -> (String, serde_json::Value) {
+    let completed: Vec<&str> = memory
+        .actions
+        .iter()
+        .filter(|a| a.success)
+        .map(|a| a.tool_name.as_str())
+        .collect();
+    let failed = memory.failed_actions();
+
+    let summary = if completed.is_empty() {
+        "No actions had completed.".to_string()
+    } else {
+        format!(
+            "Completed {} action(s) before cancellation: {}.",
+            completed.len(),
+            completed.join(", ")
+        )
+    };
+    let partial = serde_json::json!({
+        "partial": true,
+        "completed_actions": completed,
+        "failed_actions": failed,
+        "last_output": memory
+            .actions
+            .iter()
+            .rev()
+            .find(|a| a.success)
+            .and_then(|a| a.output_sanitized.clone()),
+    });
+    (summary, partial)
+}
+
 /// Convert a TaskOutput to a string result for tool execution.
 impl From<TaskOutput> for Result<String, Error> {
     fn from(output: TaskOutput) -> Self {
@@ -774,8 +896,42 @@ impl From<TaskOutput> for Result<String, Error> {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use uuid::Uuid;
+
+    use crate::context::Memory;
     use crate::util::llm_signals_completion;
 
+    use super::partial_result;
+
+    #[test]
+    fn test_partial_result_lists_completed_actions() {
+        let mut memory = Memory::new(Uuid::new_v4());
+        let (summary, partial) = partial_result(&memory);
+        assert_eq!(summary, "No actions had completed.");
+        assert_eq!(partial["completed_actions"], serde_json::json!([]));
+
+        let ok = memory
+            .create_action("shell", serde_json::json!({"command": "cargo build"}))
+            .succeed(
+                None,
+                serde_json::json!({"exit_code": 0}),
+                Duration::from_secs(1),
+            );
+        memory.record_action(ok);
+        let failed = memory
+            .create_action("http", serde_json::json!({}))
+            .fail("timeout", Duration::from_secs(1));
+        memory.record_action(failed);
+
+        let (summary, partial) = partial_result(&memory);
+        assert_eq!(summary, "Completed 1 action(s) before cancellation: shell.");
+        assert_eq!(partial["partial"], true);
+        assert_eq!(partial["failed_actions"], 1);
+        assert_eq!(partial["last_output"]["exit_code"], 0);
+    }
+
     #[test]
     fn test_completion_positive_signals() {
         assert!(llm_signals_completion("The job is complete."));
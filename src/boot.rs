@@ -0,0 +1,322 @@
+//! Startup boot profile.
+//!
+//! Startup is split into two stages:
+//!
+//! ```text
+//!  critical ──► config, database, LLM, channels ──► first prompt
+//!  deferred ──► WASM tools, MCP servers, workspace seeding, embedding
+//!               backfill (background, after the first prompt is possible)
+//! ```
+//!
+//! [`BootProfiler`] times every phase of both stages. The profile is written
+//! to disk when the agent becomes ready and again as each deferred phase
+//! finishes, so `ironclaw status --boot` can show where the last start spent
+//! its time.
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Whether a phase blocks the first prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BootStage {
+    /// Runs before the agent accepts input.
+    Critical,
+    /// Runs in the background once the agent is up.
+    Deferred,
+}
+
+/// One timed startup phase.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BootPhase {
+    pub name: String,
+    pub stage: BootStage,
+    /// Offset from process start.
+    pub start_ms: u64,
+    pub duration_ms: u64,
+}
+
+/// Timings of one startup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootProfile {
+    pub started_at: DateTime<Utc>,
+    /// Time until the agent accepted input, once it has.
+    pub ready_ms: Option<u64>,
+    pub phases: Vec<BootPhase>,
+}
+
+impl BootProfile {
+    /// Deferred phases still running when the profile was written are absent,
+    /// so this is the slowest known phase end.
+    pub fn total_ms(&self) -> u64 {
+        self.phases
+            .iter()
+            .map(|p| p.start_ms + p.duration_ms)
+            .chain(self.ready_ms)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Load a profile written by an earlier start.
+    pub fn load(path: &Path) -> std::io::Result<Option<Self>> {
+        match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Table of phases, slowest first within each stage.
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "Boot profile ({})\n",
+            self.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+        match self.ready_ms {
+            Some(ms) => out.push_str(&format!("  Ready for input after {ms}ms\n")),
+            None => out.push_str("  Did not reach the first prompt\n"),
+        }
+        let total = self.total_ms();
+        if self.ready_ms.is_some_and(|ready| total > ready) {
+            out.push_str(&format!("  Background init finished after {total}ms\n"));
+        }
+        for (stage, label) in [
+            (BootStage::Critical, "Critical"),
+            (BootStage::Deferred, "Deferred"),
+        ] {
+            let mut phases: Vec<_> = self.phases.iter().filter(|p| p.stage == stage).collect();
+            if phases.is_empty() {
+                continue;
+            }
+            phases.sort_by_key(|p| std::cmp::Reverse(p.duration_ms));
+            out.push_str(&format!("\n  {label}:\n"));
+            for p in phases {
+                out.push_str(&format!(
+                    "    {:<24} {:>7}ms  (at {}ms)\n",
+                    p.name, p.duration_ms, p.start_ms
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// Records startup phase timings. Share it as `Arc<BootProfiler>`.
+pub struct BootProfiler {
+    started: Instant,
+    profile: Mutex<BootProfile>,
+    path: Option<PathBuf>,
+}
+
+impl Default for BootProfiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BootProfiler {
+    /// Start the clock.
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            profile: Mutex::new(BootProfile {
+                started_at: Utc::now(),
+                ready_ms: None,
+                phases: Vec::new(),
+            }),
+            path: None,
+        }
+    }
+
+    /// Save the profile to `path` on readiness and after deferred phases.
+    pub fn with_path(mut self, path: PathBuf) -> Self {
+        self.path = Some(path);
+        self
+    }
+
+    /// Run a critical phase and record how long it took.
+    pub async fn time<T>(&self, name: &str, phase: impl Future<Output = T>) -> T {
+        let start = self.started.elapsed();
+        let output = phase.await;
+        self.record(name, BootStage::Critical, start, self.started.elapsed());
+        output
+    }
+
+    /// Time a critical phase until the returned guard is dropped. Handy for
+    /// blocks that return early with `?`.
+    pub fn phase(&self, name: &str) -> PhaseGuard<'_> {
+        PhaseGuard {
+            profiler: self,
+            name: name.to_string(),
+            start: self.started.elapsed(),
+        }
+    }
+
+    /// Run a phase in the background, recording it once it finishes.
+    pub fn defer<F>(self: &Arc<Self>, name: &str, phase: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let profiler = Arc::clone(self);
+        let name = name.to_string();
+        tokio::spawn(async move {
+            let start = profiler.started.elapsed();
+            phase.await;
+            profiler.record(
+                &name,
+                BootStage::Deferred,
+                start,
+                profiler.started.elapsed(),
+            );
+            if profiler.is_ready() {
+                profiler.save();
+            }
+        })
+    }
+
+    /// Mark the agent as ready for input and save the profile.
+    pub fn mark_ready(&self) {
+        let ready = ms(self.started.elapsed());
+        self.lock().ready_ms = Some(ready);
+        tracing::info!(ready_ms = ready, "Startup complete");
+        self.save();
+    }
+
+    /// Current profile.
+    pub fn snapshot(&self) -> BootProfile {
+        self.lock().clone()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.lock().ready_ms.is_some()
+    }
+
+    fn record(&self, name: &str, stage: BootStage, start: Duration, end: Duration) {
+        let phase = BootPhase {
+            name: name.to_string(),
+            stage,
+            start_ms: ms(start),
+            duration_ms: ms(end.saturating_sub(start)),
+        };
+        tracing::debug!(
+            phase = %phase.name,
+            duration_ms = phase.duration_ms,
+            "Boot phase finished"
+        );
+        self.lock().phases.push(phase);
+    }
+
+    fn save(&self) {
+        let Some(ref path) = self.path else {
+            return;
+        };
+        let result = serde_json::to_vec_pretty(&self.snapshot())
+            .map_err(std::io::Error::other)
+            .and_then(|json| {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, json)
+            });
+        if let Err(e) = result {
+            tracing::debug!("Failed to save boot profile to {}: {}", path.display(), e);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BootProfile> {
+        self.profile
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Records a critical phase when dropped. See [`BootProfiler::phase`].
+pub struct PhaseGuard<'a> {
+    profiler: &'a BootProfiler,
+    name: String,
+    start: Duration,
+}
+
+impl Drop for PhaseGuard<'_> {
+    fn drop(&mut self) {
+        self.profiler.record(
+            &self.name,
+            BootStage::Critical,
+            self.start,
+            self.profiler.started.elapsed(),
+        );
+    }
+}
+
+/// Default location of the saved profile (`~/.ironclaw/boot_profile.json`).
+pub fn default_profile_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".ironclaw")
+        .join("boot_profile.json")
+}
+
+fn ms(d: Duration) -> u64 {
+    d.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_profile_records_stages_and_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("boot_profile.json");
+        let profiler = Arc::new(BootProfiler::new().with_path(path.clone()));
+
+        let answer = profiler
+            .time("database", async {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                42
+            })
+            .await;
+        assert_eq!(answer, 42);
+        drop(profiler.phase("llm"));
+
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let deferred = profiler.defer("mcp_servers", async move {
+            let _ = rx.await;
+        });
+
+        // Ready before the deferred phase finishes.
+        profiler.mark_ready();
+        let saved = BootProfile::load(&path).unwrap().unwrap();
+        assert_eq!(saved.phases.len(), 2);
+        assert!(saved.ready_ms.unwrap() >= saved.phases[0].duration_ms);
+
+        tx.send(()).unwrap();
+        deferred.await.unwrap();
+        let saved = BootProfile::load(&path).unwrap().unwrap();
+        assert_eq!(
+            saved
+                .phases
+                .iter()
+                .map(|p| (p.name.as_str(), p.stage))
+                .collect::<Vec<_>>(),
+            vec![
+                ("database", BootStage::Critical),
+                ("llm", BootStage::Critical),
+                ("mcp_servers", BootStage::Deferred)
+            ]
+        );
+        assert!(saved.render().contains("Deferred:\n    mcp_servers"));
+        assert!(
+            BootProfile::load(&dir.path().join("missing.json"))
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -7,6 +7,7 @@ use chrono::{DateTime, Utc};
 use futures::Stream;
 use uuid::Uuid;
 
+use crate::channels::MessageCapabilities;
 use crate::error::ChannelError;
 
 /// A message received from an external channel.
@@ -127,6 +128,12 @@ pub enum StatusUpdate {
         description: String,
         parameters: serde_json::Value,
     },
+    /// Agent is waiting for the user to fill in a structured form.
+    InputRequested {
+        request_id: String,
+        prompt: String,
+        form: serde_json::Value,
+    },
     /// Extension needs user authentication (token or OAuth).
     AuthRequired {
         extension_name: String,
@@ -140,6 +147,13 @@ pub enum StatusUpdate {
         success: bool,
         message: String,
     },
+    /// An image produced during the turn (e.g. a chart), shown alongside the reply.
+    Image {
+        media_id: String,
+        mime_type: String,
+        title: Option<String>,
+        data: Vec<u8>,
+    },
 }
 
 /// Trait for message channels.
@@ -151,6 +165,14 @@ pub trait Channel: Send + Sync {
     /// Get the channel name (e.g., "cli", "slack", "telegram", "http").
     fn name(&self) -> &str;
 
+    /// What one outbound message on this channel can carry.
+    ///
+    /// The channel manager splits longer responses to fit. Default is no
+    /// length limit, standard markdown and no edits.
+    fn message_capabilities(&self) -> MessageCapabilities {
+        MessageCapabilities::default()
+    }
+
     /// Start listening for messages.
     ///
     /// Returns a stream of incoming messages. The channel should handle
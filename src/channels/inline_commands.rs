@@ -85,7 +85,7 @@ pub enum CommandCategory {
     Model,
     /// Context management: /compact, /summarize.
     Context,
-    /// Actions: /suggest, /heartbeat, /interrupt, /cancel.
+    /// Actions: /suggest, /export, /heartbeat, /interrupt, /cancel.
     Action,
 }
 
@@ -201,6 +201,12 @@ pub fn available_commands() -> Vec<CommandInfo> {
             args: String::new(),
             category: CommandCategory::Action,
         },
+        CommandInfo {
+            name: "export".to_string(),
+            description: "Export the conversation as PDF or Markdown".to_string(),
+            args: "[pdf|md]".to_string(),
+            category: CommandCategory::Action,
+        },
         CommandInfo {
             name: "heartbeat".to_string(),
             description: "Trigger a manual heartbeat check".to_string(),
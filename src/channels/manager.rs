@@ -6,12 +6,27 @@ use std::sync::Arc;
 use futures::stream;
 use tokio::sync::RwLock;
 
-use crate::channels::{Channel, IncomingMessage, MessageStream, OutgoingResponse, StatusUpdate};
+use crate::boot::BootProfiler;
+use crate::channels::status_tracker::ChannelStatus;
+use crate::channels::{
+    Channel, ChannelStatusTracker, IncomingMessage, MessageCapabilities, MessageStream,
+    NotificationCategory, NotificationRouter, OutboundSplitter, OutgoingResponse, OutputPipeline,
+    OverflowLinks, StatusUpdate,
+};
 use crate::error::ChannelError;
 
 /// Manages multiple input channels and merges their message streams.
+///
+/// Outbound messages are fitted to each channel's
+/// [`MessageCapabilities`](crate::channels::MessageCapabilities) before
+/// they are sent.
 pub struct ChannelManager {
     channels: Arc<RwLock<HashMap<String, Box<dyn Channel>>>>,
+    splitter: OutboundSplitter,
+    pipeline: Option<Arc<OutputPipeline>>,
+    router: Option<Arc<NotificationRouter>>,
+    status_tracker: Option<Arc<ChannelStatusTracker>>,
+    boot: Option<Arc<BootProfiler>>,
 }
 
 impl ChannelManager {
@@ -19,9 +34,60 @@ impl ChannelManager {
     pub fn new() -> Self {
         Self {
             channels: Arc::new(RwLock::new(HashMap::new())),
+            splitter: OutboundSplitter::default(),
+            pipeline: None,
+            router: None,
+            status_tracker: None,
+            boot: None,
         }
     }
 
+    /// Record channel startup in `tracker` (read by the gateway health probes).
+    pub fn set_status_tracker(&mut self, tracker: Arc<ChannelStatusTracker>) {
+        self.status_tracker = Some(tracker);
+    }
+
+    /// Time each channel's startup in `profiler` and mark the agent ready
+    /// once all channels have started.
+    pub fn set_boot_profiler(&mut self, profiler: Arc<BootProfiler>) {
+        self.boot = Some(profiler);
+    }
+
+    /// Deliver categorized notifications by the user's routing rules.
+    pub fn set_notification_router(&mut self, router: Arc<NotificationRouter>) {
+        self.router = Some(router);
+    }
+
+    /// Post a gateway link instead of many parts for very long responses.
+    pub fn set_overflow_links(&mut self, links: OverflowLinks) {
+        self.splitter = std::mem::take(&mut self.splitter).with_overflow_links(links);
+    }
+
+    /// Run final responses through `pipeline` before they are sent.
+    pub fn set_output_pipeline(&mut self, pipeline: Arc<OutputPipeline>) {
+        self.pipeline = Some(pipeline);
+    }
+
+    /// Send `response` to `channel` through `send`, split to fit the channel.
+    async fn send_split<F, Fut>(
+        &self,
+        channel: &dyn Channel,
+        response: OutgoingResponse,
+        send: F,
+    ) -> Result<(), ChannelError>
+    where
+        F: Fn(OutgoingResponse) -> Fut,
+        Fut: std::future::Future<Output = Result<(), ChannelError>>,
+    {
+        for part in self
+            .splitter
+            .prepare(&channel.message_capabilities(), response)
+        {
+            send(part).await?;
+        }
+        Ok(())
+    }
+
     /// Add a channel to the manager.
     pub fn add(&mut self, channel: Box<dyn Channel>) {
         let name = channel.name().to_string();
@@ -35,23 +101,50 @@ impl ChannelManager {
         }
     }
 
-    /// Start all channels and return a merged stream of messages.
+    /// Start all channels concurrently and return a merged stream of messages.
     pub async fn start_all(&self) -> Result<MessageStream, ChannelError> {
         let channels = self.channels.read().await;
         let mut streams = Vec::new();
 
-        for (name, channel) in channels.iter() {
-            match channel.start().await {
+        if let Some(ref status) = self.status_tracker {
+            for name in channels.keys() {
+                status.register_channel(name).await;
+            }
+        }
+        let starts = channels.iter().map(|(name, channel)| async move {
+            let result = match self.boot {
+                Some(ref boot) => boot.time(&format!("channel:{name}"), channel.start()).await,
+                None => channel.start().await,
+            };
+            (name, result)
+        });
+
+        for (name, result) in futures::future::join_all(starts).await {
+            match result {
                 Ok(stream) => {
                     tracing::info!("Started channel: {}", name);
+                    if let Some(ref status) = self.status_tracker {
+                        status.set_status(name, ChannelStatus::Connected).await;
+                    }
                     streams.push(stream);
                 }
                 Err(e) => {
                     tracing::error!("Failed to start channel {}: {}", name, e);
+                    if let Some(ref status) = self.status_tracker {
+                        status
+                            .set_status(name, ChannelStatus::Error(e.to_string()))
+                            .await;
+                    }
                     // Continue with other channels, don't fail completely
                 }
             }
         }
+        if let Some(ref status) = self.status_tracker {
+            status.mark_startup_complete();
+        }
+        if let Some(ref boot) = self.boot {
+            boot.mark_ready();
+        }
 
         if streams.is_empty() {
             return Err(ChannelError::StartupFailed {
@@ -73,7 +166,10 @@ impl ChannelManager {
     ) -> Result<(), ChannelError> {
         let channels = self.channels.read().await;
         if let Some(channel) = channels.get(&msg.channel) {
-            channel.respond(msg, response).await
+            self.send_split(channel.as_ref(), response, |part| {
+                channel.respond(msg, part)
+            })
+            .await
         } else {
             Err(ChannelError::SendFailed {
                 name: msg.channel.clone(),
@@ -82,6 +178,30 @@ impl ChannelManager {
         }
     }
 
+    /// Send a turn's final response, after the output pipeline has run over
+    /// it for the message's channel.
+    pub async fn respond_final(
+        &self,
+        msg: &IncomingMessage,
+        mut response: OutgoingResponse,
+    ) -> Result<(), ChannelError> {
+        if let Some(ref pipeline) = self.pipeline {
+            let caps = self.message_capabilities(&msg.channel).await;
+            response.content = pipeline.run(&msg.channel, &caps, response.content).await;
+        }
+        self.respond(msg, response).await
+    }
+
+    /// What a channel can carry; defaults for unknown channels.
+    pub async fn message_capabilities(&self, channel: &str) -> MessageCapabilities {
+        self.channels
+            .read()
+            .await
+            .get(channel)
+            .map(|c| c.message_capabilities())
+            .unwrap_or_default()
+    }
+
     /// Send a status update to a specific channel.
     ///
     /// The metadata contains channel-specific routing info (e.g., Telegram chat_id)
@@ -112,7 +232,10 @@ impl ChannelManager {
     ) -> Result<(), ChannelError> {
         let channels = self.channels.read().await;
         if let Some(channel) = channels.get(channel_name) {
-            channel.broadcast(user_id, response).await
+            self.send_split(channel.as_ref(), response, |part| {
+                channel.broadcast(user_id, part)
+            })
+            .await
         } else {
             Err(ChannelError::SendFailed {
                 name: channel_name.to_string(),
@@ -133,13 +256,49 @@ impl ChannelManager {
         let mut results = Vec::new();
 
         for (name, channel) in channels.iter() {
-            let result = channel.broadcast(user_id, response.clone()).await;
+            let result = self
+                .send_split(channel.as_ref(), response.clone(), |part| {
+                    channel.broadcast(user_id, part)
+                })
+                .await;
             results.push((name.clone(), result));
         }
 
         results
     }
 
+    /// Send a categorized notification to the channels its active routing
+    /// rules name, skipping `skip_channel`. Returns whether any rule
+    /// delivered it; when none did, the caller sends it the usual way.
+    pub async fn route(
+        &self,
+        category: NotificationCategory,
+        user_id: &str,
+        response: OutgoingResponse,
+        skip_channel: Option<&str>,
+    ) -> bool {
+        let Some(ref router) = self.router else {
+            return false;
+        };
+        let mut delivered = false;
+        for rule in router.targets(category, chrono::Utc::now()) {
+            if skip_channel == Some(rule.channel.as_str()) {
+                continue;
+            }
+            let user = rule.user.as_deref().unwrap_or(user_id);
+            match self.broadcast(&rule.channel, user, response.clone()).await {
+                Ok(()) => delivered = true,
+                Err(e) => tracing::warn!(
+                    "Failed to route {} notification to {}: {}",
+                    category,
+                    rule.channel,
+                    e
+                ),
+            }
+        }
+        delivered
+    }
+
     /// Check health of all channels.
     pub async fn health_check_all(&self) -> HashMap<String, Result<(), ChannelError>> {
         let channels = self.channels.read().await;
@@ -279,4 +438,96 @@ mod tests {
         let results = manager.broadcast_all("user1", response).await;
         assert!(results.is_empty());
     }
+
+    /// Records what it is asked to send; messages are capped at 20 chars.
+    struct SmallChannel {
+        sent: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Channel for SmallChannel {
+        fn name(&self) -> &str {
+            "small"
+        }
+
+        fn message_capabilities(&self) -> crate::channels::MessageCapabilities {
+            crate::channels::MessageCapabilities {
+                max_message_len: Some(20),
+                ..Default::default()
+            }
+        }
+
+        async fn start(&self) -> Result<MessageStream, ChannelError> {
+            Ok(Box::pin(stream::empty()))
+        }
+
+        async fn respond(
+            &self,
+            _msg: &IncomingMessage,
+            response: OutgoingResponse,
+        ) -> Result<(), ChannelError> {
+            self.sent.lock().unwrap().push(response.content);
+            Ok(())
+        }
+
+        async fn health_check(&self) -> Result<(), ChannelError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_respond_splits_to_channel_limit() {
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut manager = ChannelManager::new();
+        manager.add(Box::new(SmallChannel {
+            sent: Arc::clone(&sent),
+        }));
+
+        let msg = IncomingMessage::new("small", "user", "hi");
+        manager
+            .respond(
+                &msg,
+                OutgoingResponse::text("First paragraph.\n\nSecond paragraph."),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec!["First paragraph.", "Second paragraph."]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_route_follows_rules() {
+        use crate::channels::{NotificationCategory, NotificationRouter, RouteRule};
+
+        let mut manager = ChannelManager::new();
+        manager.add(Box::new(SmallChannel {
+            sent: Arc::new(std::sync::Mutex::new(Vec::new())),
+        }));
+        let alert = || OutgoingResponse::text("alert");
+        let security = NotificationCategory::SecurityAlert;
+        assert!(!manager.route(security, "user", alert(), None).await);
+
+        manager.set_notification_router(Arc::new(NotificationRouter::new(
+            vec![RouteRule {
+                category: security,
+                channel: "small".to_string(),
+                user: None,
+                hours: None,
+            }],
+            crate::locale::Tz::utc(),
+        )));
+        assert!(manager.route(security, "user", alert(), None).await);
+        assert!(
+            !manager
+                .route(security, "user", alert(), Some("small"))
+                .await
+        );
+        assert!(
+            !manager
+                .route(NotificationCategory::JobFinished, "user", alert(), None)
+                .await
+        );
+    }
 }
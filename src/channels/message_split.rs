@@ -0,0 +1,453 @@
+//! Fitting outbound messages to what a channel can carry.
+//!
+//! Each channel declares its [`MessageCapabilities`]: the longest message it
+//! accepts, the markdown dialect it renders and whether sent messages can be
+//! edited. Responses longer than the limit are split on paragraph, line and
+//! word boundaries. Fenced code blocks are closed at the end of a part and
+//! reopened, with their language tag, at the start of the next, so every
+//! part renders on its own. When a response would need more parts than is
+//! reasonable to post, a single message with the beginning of the response
+//! and a gateway link to the full text is sent instead.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::channels::OutgoingResponse;
+
+/// Markdown flavour a channel renders.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkdownDialect {
+    /// CommonMark / GitHub-flavoured markdown.
+    #[default]
+    Markdown,
+    /// Slack mrkdwn.
+    Slack,
+    /// Telegram's legacy Markdown parse mode.
+    Telegram,
+    /// No formatting; markup is shown as typed.
+    Plain,
+}
+
+impl MarkdownDialect {
+    /// Format a link in this dialect.
+    pub fn link(&self, label: &str, url: &str) -> String {
+        match self {
+            MarkdownDialect::Markdown | MarkdownDialect::Telegram => {
+                format!("[{}]({})", label, url)
+            }
+            MarkdownDialect::Slack => format!("<{}|{}>", url, label),
+            MarkdownDialect::Plain => format!("{}: {}", label, url),
+        }
+    }
+}
+
+/// What a channel can carry in one outbound message.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageCapabilities {
+    /// Longest message in characters, or `None` for no limit.
+    pub max_message_len: Option<usize>,
+    /// Markdown flavour the channel renders.
+    pub markdown: MarkdownDialect,
+    /// Whether sent messages can be edited in place.
+    pub supports_edits: bool,
+}
+
+/// Most parts a response is split into before it is linked instead.
+pub const DEFAULT_MAX_PARTS: usize = 4;
+
+/// How long overflowed responses stay readable through the gateway.
+const OVERFLOW_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Most overflowed responses kept at once; the oldest go first.
+const MAX_OVERFLOW_ENTRIES: usize = 256;
+
+/// Full text of responses too long to post, served by the gateway at
+/// `/overflow/{id}`. The random id is the only credential, so entries expire.
+#[derive(Debug, Default)]
+pub struct OverflowStore {
+    entries: Mutex<HashMap<Uuid, (Instant, String)>>,
+}
+
+impl OverflowStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<Uuid, (Instant, String)>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Keep `content` and return its id.
+    pub fn put(&self, content: String) -> Uuid {
+        let now = Instant::now();
+        let mut entries = self.entries();
+        entries.retain(|_, (stored, _)| now.duration_since(*stored) < OVERFLOW_TTL);
+        while entries.len() >= MAX_OVERFLOW_ENTRIES {
+            let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, (stored, _))| *stored)
+                .map(|(id, _)| *id)
+            else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+        let id = Uuid::new_v4();
+        entries.insert(id, (now, content));
+        id
+    }
+
+    pub fn get(&self, id: &Uuid) -> Option<String> {
+        self.entries()
+            .get(id)
+            .filter(|(stored, _)| stored.elapsed() < OVERFLOW_TTL)
+            .map(|(_, content)| content.clone())
+    }
+}
+
+/// Where overflow links point.
+#[derive(Debug, Clone)]
+pub struct OverflowLinks {
+    /// Gateway base URL, e.g. `http://127.0.0.1:3000`.
+    pub base_url: String,
+    pub store: Arc<OverflowStore>,
+}
+
+/// Turns one response into the messages a channel can take.
+#[derive(Debug, Clone)]
+pub struct OutboundSplitter {
+    max_parts: usize,
+    overflow: Option<OverflowLinks>,
+}
+
+impl Default for OutboundSplitter {
+    fn default() -> Self {
+        Self {
+            max_parts: DEFAULT_MAX_PARTS,
+            overflow: None,
+        }
+    }
+}
+
+impl OutboundSplitter {
+    pub fn with_max_parts(mut self, max_parts: usize) -> Self {
+        self.max_parts = max_parts.max(1);
+        self
+    }
+
+    /// Link to the gateway instead of posting more than `max_parts` parts.
+    /// Without this, long responses are always split.
+    pub fn with_overflow_links(mut self, links: OverflowLinks) -> Self {
+        self.overflow = Some(links);
+        self
+    }
+
+    /// The messages to send for `response` on a channel with `caps`.
+    pub fn prepare(
+        &self,
+        caps: &MessageCapabilities,
+        response: OutgoingResponse,
+    ) -> Vec<OutgoingResponse> {
+        let Some(max) = caps.max_message_len else {
+            return vec![response];
+        };
+        if response.content.chars().count() <= max {
+            return vec![response];
+        }
+
+        let part = |content: String| OutgoingResponse {
+            content,
+            thread_id: response.thread_id.clone(),
+            metadata: response.metadata.clone(),
+        };
+
+        let parts = split_message(&response.content, max);
+        if parts.len() <= self.max_parts {
+            return parts.into_iter().map(part).collect();
+        }
+        let Some(ref links) = self.overflow else {
+            return parts.into_iter().map(part).collect();
+        };
+
+        let id = links.store.put(response.content.clone());
+        let url = format!("{}/overflow/{}", links.base_url.trim_end_matches('/'), id);
+        let notice = format!(
+            "\n\n… {}",
+            caps.markdown.link("Read the full response", &url)
+        );
+        let room = max.saturating_sub(notice.chars().count()).max(1);
+        let head = split_message(&response.content, room)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        vec![part(head + &notice)]
+    }
+}
+
+/// A run of the response that is kept together when possible.
+enum Segment {
+    /// A paragraph of prose.
+    Text(String),
+    /// A fenced code block.
+    Code {
+        /// The opening fence line, e.g. ```` ```rust ````.
+        open: String,
+        /// The fence that closes the block, e.g. ```` ``` ````.
+        close: String,
+        body: String,
+    },
+}
+
+impl Segment {
+    fn render(&self) -> String {
+        match self {
+            Segment::Text(text) => text.clone(),
+            Segment::Code { open, close, body } => format!("{}\n{}\n{}", open, body, close),
+        }
+    }
+}
+
+/// The fence a line opens, if it opens one.
+fn fence_marker(line: &str) -> Option<String> {
+    let trimmed = line.trim_start();
+    let ch = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let marker: String = trimmed.chars().take_while(|c| *c == ch).collect();
+    (marker.len() >= 3).then_some(marker)
+}
+
+fn parse_segments(content: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut code: Option<(String, String, Vec<&str>)> = None;
+
+    let flush = |paragraph: &mut Vec<&str>, segments: &mut Vec<Segment>| {
+        if !paragraph.is_empty() {
+            segments.push(Segment::Text(paragraph.join("\n")));
+            paragraph.clear();
+        }
+    };
+
+    for line in content.lines() {
+        if let Some((_, close, body)) = code.as_mut() {
+            if line.trim() == close.as_str() {
+                let (open, close, body) = code.take().unwrap_or_default();
+                segments.push(Segment::Code {
+                    open,
+                    close,
+                    body: body.join("\n"),
+                });
+            } else {
+                body.push(line);
+            }
+        } else if let Some(marker) = fence_marker(line) {
+            flush(&mut paragraph, &mut segments);
+            code = Some((line.trim().to_string(), marker, Vec::new()));
+        } else if line.trim().is_empty() {
+            flush(&mut paragraph, &mut segments);
+        } else {
+            paragraph.push(line);
+        }
+    }
+    flush(&mut paragraph, &mut segments);
+    // An unterminated block is closed so every part renders.
+    if let Some((open, close, body)) = code {
+        segments.push(Segment::Code {
+            open,
+            close,
+            body: body.join("\n"),
+        });
+    }
+    segments
+}
+
+fn char_len(s: &str) -> usize {
+    s.chars().count()
+}
+
+/// Append `piece` to `current` with `sep`, starting a new part when it
+/// would not fit in `max` characters.
+fn push_joined(parts: &mut Vec<String>, current: &mut String, piece: &str, sep: &str, max: usize) {
+    if current.is_empty() {
+        current.push_str(piece);
+    } else if char_len(current) + char_len(sep) + char_len(piece) <= max {
+        current.push_str(sep);
+        current.push_str(piece);
+    } else {
+        parts.push(std::mem::take(current));
+        current.push_str(piece);
+    }
+}
+
+/// Break a line longer than `max` on spaces, and words longer than `max`
+/// anywhere.
+fn split_line(line: &str, max: usize) -> Vec<String> {
+    if char_len(line) <= max {
+        return vec![line.to_string()];
+    }
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    for word in line.split(' ') {
+        if char_len(word) <= max {
+            push_joined(&mut parts, &mut current, word, " ", max);
+            continue;
+        }
+        let chars: Vec<char> = word.chars().collect();
+        for chunk in chars.chunks(max) {
+            push_joined(
+                &mut parts,
+                &mut current,
+                &chunk.iter().collect::<String>(),
+                " ",
+                max,
+            );
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Split text on line boundaries into parts of at most `max` characters.
+fn split_lines(text: &str, max: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        for piece in split_line(line, max) {
+            push_joined(&mut parts, &mut current, &piece, "\n", max);
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Split `content` into parts of at most `max` characters.
+///
+/// Paragraphs and code blocks are kept whole when they fit. A code block
+/// that has to be split is closed and reopened around each break.
+pub fn split_message(content: &str, max: usize) -> Vec<String> {
+    let max = max.max(1);
+    if char_len(content) <= max {
+        return vec![content.to_string()];
+    }
+
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    for segment in parse_segments(content) {
+        let rendered = segment.render();
+        if char_len(&rendered) <= max {
+            push_joined(&mut parts, &mut current, &rendered, "\n\n", max);
+            continue;
+        }
+
+        if !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+        }
+        let pieces = match segment {
+            Segment::Text(text) => split_lines(&text, max),
+            Segment::Code { open, close, body } => {
+                let overhead = char_len(&open) + char_len(&close) + 2;
+                split_lines(&body, max.saturating_sub(overhead).max(1))
+                    .into_iter()
+                    .map(|piece| format!("{}\n{}\n{}", open, piece, close))
+                    .collect()
+            }
+        };
+        let mut pieces = pieces.into_iter();
+        let last = pieces.next_back();
+        parts.extend(pieces);
+        current = last.unwrap_or_default();
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_prefers_paragraphs_and_reopens_code_blocks() {
+        let code: Vec<String> = (0..8).map(|i| format!("let x{} = {};", i, i)).collect();
+        let content = format!(
+            "First paragraph.\n\nSecond paragraph.\n\n```rust\n{}\n```\n\nDone.",
+            code.join("\n")
+        );
+        let parts = split_message(&content, 60);
+
+        assert_eq!(parts[0], "First paragraph.\n\nSecond paragraph.");
+        for part in &parts {
+            assert!(part.chars().count() <= 60, "too long: {:?}", part);
+            assert_eq!(
+                part.matches("```").count() % 2,
+                0,
+                "unbalanced fence: {:?}",
+                part
+            );
+        }
+        let code_parts: Vec<&String> = parts.iter().filter(|p| p.contains("let x")).collect();
+        assert!(code_parts.len() > 1);
+        assert!(code_parts.iter().all(|p| p.starts_with("```rust\n")));
+        assert!(parts.last().unwrap().ends_with("Done."));
+
+        // Every line survives the split.
+        for line in &code {
+            assert!(parts.iter().any(|p| p.contains(line.as_str())));
+        }
+        assert_eq!(split_message("short", 60), vec!["short"]);
+    }
+
+    #[test]
+    fn test_split_breaks_long_lines_and_words() {
+        let parts = split_message("aaaa bbbb cccc dddddddddddd", 10);
+        assert_eq!(parts, vec!["aaaa bbbb", "cccc", "dddddddddd", "dd"]);
+    }
+
+    #[test]
+    fn test_prepare_splits_or_links() {
+        let caps = MessageCapabilities {
+            max_message_len: Some(120),
+            markdown: MarkdownDialect::Slack,
+            supports_edits: false,
+        };
+        let long = (0..40)
+            .map(|i| format!("Paragraph number {}.", i))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let response = OutgoingResponse::text(long.clone()).in_thread("t1");
+
+        // No limit: untouched.
+        let sent =
+            OutboundSplitter::default().prepare(&MessageCapabilities::default(), response.clone());
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].content, long);
+
+        // No gateway: split, however many parts it takes.
+        let sent = OutboundSplitter::default().prepare(&caps, response.clone());
+        assert!(sent.len() > DEFAULT_MAX_PARTS);
+        assert!(sent.iter().all(|r| r.thread_id.as_deref() == Some("t1")));
+
+        // With a gateway: one message linking to the full text.
+        let store = Arc::new(OverflowStore::new());
+        let splitter = OutboundSplitter::default().with_overflow_links(OverflowLinks {
+            base_url: "http://gw.local/".to_string(),
+            store: Arc::clone(&store),
+        });
+        let sent = splitter.prepare(&caps, response);
+        assert_eq!(sent.len(), 1);
+        let content = &sent[0].content;
+        assert!(content.starts_with("Paragraph number 0."));
+        let url_start = content.find("<http://gw.local/overflow/").unwrap();
+        let id: Uuid = content[url_start + 26..url_start + 62].parse().unwrap();
+        assert_eq!(store.get(&id).as_deref(), Some(long.as_str()));
+    }
+}
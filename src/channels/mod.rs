@@ -33,7 +33,10 @@ pub mod delivery_retry;
 mod http;
 pub mod inline_commands;
 mod manager;
+pub mod message_split;
+pub mod postprocess;
 mod repl;
+pub mod routing;
 pub mod self_message;
 pub mod status_tracker;
 pub mod wasm;
@@ -49,7 +52,12 @@ pub use inline_commands::{
     parse_inline_command,
 };
 pub use manager::ChannelManager;
+pub use message_split::{
+    MarkdownDialect, MessageCapabilities, OutboundSplitter, OverflowLinks, OverflowStore,
+};
+pub use postprocess::{OutputPipeline, OutputProcessor};
 pub use repl::ReplChannel;
+pub use routing::{NotificationCategory, NotificationRouter, RouteRule};
 pub use self_message::SelfMessageFilter;
 pub use status_tracker::ChannelStatusTracker;
 pub use web::GatewayChannel;
@@ -0,0 +1,699 @@
+//! Output post-processors for final responses.
+//!
+//! Before a turn's response is sent, it runs through an [`OutputPipeline`]:
+//! a list of registered [`OutputProcessor`]s, each switched on or off per
+//! channel. Built in, in the order they run:
+//!
+//! | Name               | What it does                                                  |
+//! |--------------------|---------------------------------------------------------------|
+//! | `markdown_lint`    | Trailing whitespace, runs of blank lines, `*`/`+` bullets to `-`, unclosed code fences |
+//! | `code_format`      | Formats fenced code with `rustfmt` / `prettier` when installed |
+//! | `link_check`       | Probes https links and notes the ones that did not respond    |
+//! | `markdown_dialect` | Rewrites CommonMark into the channel's [`MarkdownDialect`]   |
+//!
+//! Processors see prose and code separately: only `code_format` touches the
+//! inside of fenced blocks, and inline code spans are left alone. A processor
+//! that fails leaves the text as it was.
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use regex::Regex;
+use tokio::io::AsyncWriteExt;
+
+use crate::channels::{MarkdownDialect, MessageCapabilities};
+
+/// Names of the built-in processors, in the order they run.
+pub const BUILTIN_PROCESSORS: &[&str] = &[
+    "markdown_lint",
+    "code_format",
+    "link_check",
+    "markdown_dialect",
+];
+
+/// Channel key whose list applies to channels without their own.
+pub const DEFAULT_CHANNEL_KEY: &str = "*";
+
+/// Longest a formatter may take on one code block.
+const FORMAT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest a link probe may take.
+const LINK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Most links probed per response.
+const MAX_LINKS: usize = 10;
+
+/// One step of the pipeline.
+#[async_trait]
+pub trait OutputProcessor: Send + Sync {
+    /// Name used to switch the processor on per channel.
+    fn name(&self) -> &str;
+
+    /// The rewritten text, or `None` to leave it unchanged.
+    async fn process(&self, text: &str, caps: &MessageCapabilities) -> Option<String>;
+}
+
+/// Registered processors and which channels use them.
+pub struct OutputPipeline {
+    processors: Vec<Arc<dyn OutputProcessor>>,
+    /// Channel name (or [`DEFAULT_CHANNEL_KEY`]) to enabled processor names.
+    enabled: RwLock<HashMap<String, Vec<String>>>,
+}
+
+impl OutputPipeline {
+    /// An empty pipeline with the given per-channel processor lists.
+    pub fn new(enabled: HashMap<String, Vec<String>>) -> Self {
+        Self {
+            processors: Vec::new(),
+            enabled: RwLock::new(enabled),
+        }
+    }
+
+    /// A pipeline with the built-in processors registered.
+    pub fn with_builtins(enabled: HashMap<String, Vec<String>>) -> Self {
+        let mut pipeline = Self::new(enabled);
+        pipeline.register(Arc::new(MarkdownLint));
+        pipeline.register(Arc::new(CodeFormatter::default()));
+        pipeline.register(Arc::new(LinkChecker::new()));
+        pipeline.register(Arc::new(DialectNormalizer));
+        pipeline
+    }
+
+    /// Add a processor; processors run in registration order.
+    pub fn register(&mut self, processor: Arc<dyn OutputProcessor>) {
+        self.processors.push(processor);
+    }
+
+    /// Names of the registered processors.
+    pub fn processor_names(&self) -> Vec<String> {
+        self.processors
+            .iter()
+            .map(|p| p.name().to_string())
+            .collect()
+    }
+
+    /// Processors enabled for `channel`.
+    pub fn enabled_for(&self, channel: &str) -> Vec<String> {
+        let enabled = self.enabled.read().unwrap_or_else(|e| e.into_inner());
+        enabled
+            .get(channel)
+            .or_else(|| enabled.get(DEFAULT_CHANNEL_KEY))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Switch one processor on or off for `channel`. The channel starts
+    /// from the default list if it has none of its own.
+    pub fn set_enabled(&self, channel: &str, processor: &str, on: bool) {
+        let current = self.enabled_for(channel);
+        let mut enabled = self.enabled.write().unwrap_or_else(|e| e.into_inner());
+        let list = enabled.entry(channel.to_string()).or_insert(current);
+        list.retain(|name| name != processor);
+        if on {
+            list.push(processor.to_string());
+        }
+    }
+
+    /// Run the processors enabled for `channel` over `text`.
+    pub async fn run(&self, channel: &str, caps: &MessageCapabilities, text: String) -> String {
+        let enabled = self.enabled_for(channel);
+        let mut text = text;
+        for processor in &self.processors {
+            if !enabled.iter().any(|name| name == processor.name()) {
+                continue;
+            }
+            if let Some(processed) = processor.process(&text, caps).await {
+                text = processed;
+            }
+        }
+        text
+    }
+}
+
+/// A response split into prose and fenced code blocks. Rendering joins the
+/// lines back exactly as they were.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Block {
+    Prose(Vec<String>),
+    Code {
+        open: String,
+        lang: String,
+        body: Vec<String>,
+        close: Option<String>,
+    },
+}
+
+fn is_fence(line: &str) -> bool {
+    line.trim_start().starts_with("```")
+}
+
+fn parse_blocks(text: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut prose = Vec::new();
+    let mut lines = text.split('\n');
+    while let Some(line) = lines.next() {
+        if !is_fence(line) {
+            prose.push(line.to_string());
+            continue;
+        }
+        if !prose.is_empty() {
+            blocks.push(Block::Prose(std::mem::take(&mut prose)));
+        }
+        let lang = line.trim_start().trim_start_matches('`').trim().to_string();
+        let mut body = Vec::new();
+        let mut close = None;
+        for line in lines.by_ref() {
+            if is_fence(line) {
+                close = Some(line.to_string());
+                break;
+            }
+            body.push(line.to_string());
+        }
+        blocks.push(Block::Code {
+            open: line.to_string(),
+            lang,
+            body,
+            close,
+        });
+    }
+    if !prose.is_empty() {
+        blocks.push(Block::Prose(prose));
+    }
+    blocks
+}
+
+fn render_blocks(blocks: &[Block]) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for block in blocks {
+        match block {
+            Block::Prose(prose) => lines.extend(prose.iter().map(String::as_str)),
+            Block::Code {
+                open, body, close, ..
+            } => {
+                lines.push(open);
+                lines.extend(body.iter().map(String::as_str));
+                if let Some(close) = close {
+                    lines.push(close);
+                }
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+/// Apply `f` to the parts of `line` outside inline code spans.
+fn map_outside_inline_code(line: &str, f: impl Fn(&str) -> String) -> String {
+    line.split('`')
+        .enumerate()
+        .map(|(i, part)| {
+            if i % 2 == 0 {
+                f(part)
+            } else {
+                part.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("`")
+}
+
+fn changed(original: &str, processed: String) -> Option<String> {
+    (processed != original).then_some(processed)
+}
+
+static BULLET: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\s*)[*+] ").expect("valid bullet regex"));
+
+/// Tidies markdown without changing what it says.
+pub struct MarkdownLint;
+
+#[async_trait]
+impl OutputProcessor for MarkdownLint {
+    fn name(&self) -> &str {
+        "markdown_lint"
+    }
+
+    async fn process(&self, text: &str, _caps: &MessageCapabilities) -> Option<String> {
+        let mut blocks = parse_blocks(text);
+        for block in &mut blocks {
+            match block {
+                Block::Prose(lines) => {
+                    let mut tidy: Vec<String> = Vec::with_capacity(lines.len());
+                    for line in lines.iter() {
+                        let line = BULLET.replace(line.trim_end(), "$1- ").into_owned();
+                        let blank_run =
+                            line.is_empty() && tidy.last().is_some_and(|l| l.is_empty());
+                        if !blank_run {
+                            tidy.push(line);
+                        }
+                    }
+                    *lines = tidy;
+                }
+                Block::Code { close, .. } => {
+                    if close.is_none() {
+                        *close = Some("```".to_string());
+                    }
+                }
+            }
+        }
+        changed(text, render_blocks(&blocks))
+    }
+}
+
+static HEADING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s{0,3}#{1,6}\s+(.*?)\s*#*\s*$").expect("valid heading regex"));
+static BOLD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\*\*(.+?)\*\*|__(.+?)__").expect("valid bold regex"));
+static STRIKE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"~~(.+?)~~").expect("valid strikethrough regex"));
+static LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[([^\]]+)\]\((https?://[^)\s]+)\)").expect("valid link regex"));
+
+/// Rewrites CommonMark prose into the channel's markdown dialect.
+pub struct DialectNormalizer;
+
+impl DialectNormalizer {
+    fn convert_line(line: &str, dialect: MarkdownDialect) -> String {
+        let line = match HEADING.captures(line) {
+            Some(caps) => match dialect {
+                MarkdownDialect::Slack | MarkdownDialect::Telegram => format!("*{}*", &caps[1]),
+                _ => caps[1].to_string(),
+            },
+            None => line.to_string(),
+        };
+        map_outside_inline_code(&line, |part| {
+            let part = if dialect == MarkdownDialect::Telegram {
+                part.to_string()
+            } else {
+                LINK.replace_all(part, |c: &regex::Captures| dialect.link(&c[1], &c[2]))
+                    .into_owned()
+            };
+            let part = BOLD.replace_all(&part, |c: &regex::Captures| {
+                let inner = c.get(1).or_else(|| c.get(2)).map_or("", |m| m.as_str());
+                match dialect {
+                    MarkdownDialect::Plain => inner.to_string(),
+                    _ => format!("*{}*", inner),
+                }
+            });
+            STRIKE
+                .replace_all(&part, |c: &regex::Captures| match dialect {
+                    MarkdownDialect::Slack => format!("~{}~", &c[1]),
+                    _ => c[1].to_string(),
+                })
+                .into_owned()
+        })
+    }
+}
+
+#[async_trait]
+impl OutputProcessor for DialectNormalizer {
+    fn name(&self) -> &str {
+        "markdown_dialect"
+    }
+
+    async fn process(&self, text: &str, caps: &MessageCapabilities) -> Option<String> {
+        if caps.markdown == MarkdownDialect::Markdown {
+            return None;
+        }
+        let mut blocks = parse_blocks(text);
+        for block in &mut blocks {
+            if let Block::Prose(lines) = block {
+                for line in lines.iter_mut() {
+                    *line = Self::convert_line(line, caps.markdown);
+                }
+            }
+        }
+        changed(text, render_blocks(&blocks))
+    }
+}
+
+/// An external formatter reading code on stdin and writing it to stdout.
+#[derive(Debug, Clone)]
+pub struct Formatter {
+    /// Fence languages handled, with the file extension passed as `{ext}`.
+    pub langs: Vec<(String, String)>,
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+/// Formats fenced code blocks with whatever formatters are installed.
+pub struct CodeFormatter {
+    formatters: Vec<Formatter>,
+}
+
+impl Default for CodeFormatter {
+    fn default() -> Self {
+        let langs = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(lang, ext)| (lang.to_string(), ext.to_string()))
+                .collect()
+        };
+        Self::new(vec![
+            Formatter {
+                langs: langs(&[("rust", "rs"), ("rs", "rs")]),
+                program: "rustfmt".to_string(),
+                args: vec!["--edition".to_string(), "2024".to_string()],
+            },
+            Formatter {
+                langs: langs(&[
+                    ("js", "js"),
+                    ("javascript", "js"),
+                    ("jsx", "jsx"),
+                    ("ts", "ts"),
+                    ("typescript", "ts"),
+                    ("tsx", "tsx"),
+                    ("json", "json"),
+                    ("css", "css"),
+                    ("scss", "scss"),
+                    ("html", "html"),
+                    ("yaml", "yaml"),
+                    ("yml", "yaml"),
+                ]),
+                program: "prettier".to_string(),
+                args: vec!["--stdin-filepath".to_string(), "snippet.{ext}".to_string()],
+            },
+        ])
+    }
+}
+
+impl CodeFormatter {
+    pub fn new(formatters: Vec<Formatter>) -> Self {
+        Self { formatters }
+    }
+
+    fn formatter_for(&self, lang: &str) -> Option<(&Formatter, &str)> {
+        let lang = lang.to_ascii_lowercase();
+        self.formatters.iter().find_map(|f| {
+            f.langs
+                .iter()
+                .find(|(l, _)| *l == lang)
+                .map(|(_, ext)| (f, ext.as_str()))
+        })
+    }
+
+    /// Formatted `code`, or `None` if the formatter is missing or failed
+    /// (snippets are often incomplete programs).
+    async fn format(formatter: &Formatter, ext: &str, code: &str) -> Option<String> {
+        if !on_path(&formatter.program) {
+            return None;
+        }
+        let mut child = tokio::process::Command::new(&formatter.program)
+            .args(formatter.args.iter().map(|a| a.replace("{ext}", ext)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .ok()?;
+        let mut stdin = child.stdin.take()?;
+        let input = format!("{}\n", code);
+        tokio::spawn(async move {
+            let _ = stdin.write_all(input.as_bytes()).await;
+        });
+        let output = tokio::time::timeout(FORMAT_TIMEOUT, child.wait_with_output())
+            .await
+            .ok()?
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let formatted = String::from_utf8(output.stdout).ok()?;
+        let formatted = formatted.trim_end_matches('\n');
+        (!formatted.trim().is_empty()).then(|| formatted.to_string())
+    }
+}
+
+/// Whether `program` is an executable file on `PATH`.
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+#[async_trait]
+impl OutputProcessor for CodeFormatter {
+    fn name(&self) -> &str {
+        "code_format"
+    }
+
+    async fn process(&self, text: &str, _caps: &MessageCapabilities) -> Option<String> {
+        let mut blocks = parse_blocks(text);
+        let mut any = false;
+        for block in &mut blocks {
+            if let Block::Code {
+                lang,
+                body,
+                close: Some(_),
+                ..
+            } = block
+                && let Some((formatter, ext)) = self.formatter_for(lang)
+                && let Some(formatted) = Self::format(formatter, ext, &body.join("\n")).await
+            {
+                *body = formatted.split('\n').map(str::to_string).collect();
+                any = true;
+            }
+        }
+        if !any {
+            return None;
+        }
+        changed(text, render_blocks(&blocks))
+    }
+}
+
+static URL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"https?://[^\s<>()\[\]"'`|]+"#).expect("valid URL regex"));
+
+/// Probes the https links in a response and notes the dead ones.
+pub struct LinkChecker {
+    client: reqwest::Client,
+}
+
+impl Default for LinkChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LinkChecker {
+    pub fn new() -> Self {
+        let client = crate::outbound::client_builder()
+            .timeout(LINK_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+        Self { client }
+    }
+
+    /// Links in the prose of `text`, deduplicated, at most [`MAX_LINKS`].
+    fn links(text: &str) -> Vec<String> {
+        let mut links: Vec<String> = Vec::new();
+        for block in parse_blocks(text) {
+            let Block::Prose(lines) = block else {
+                continue;
+            };
+            for line in lines {
+                for m in URL.find_iter(&line) {
+                    let url = m
+                        .as_str()
+                        .trim_end_matches(['.', ',', ';', ':', '!', '?', '*', '_']);
+                    if !links.iter().any(|l| l == url) {
+                        links.push(url.to_string());
+                    }
+                }
+            }
+        }
+        links.truncate(MAX_LINKS);
+        links
+    }
+
+    /// Whether `url` is known to be dead. Links that may not be probed
+    /// (non-https, private addresses) count as alive.
+    async fn is_dead(&self, url: &str) -> bool {
+        let target = url.to_string();
+        let allowed = tokio::task::spawn_blocking(move || {
+            crate::tools::builtin::validate_url(&target).is_ok()
+        })
+        .await
+        .unwrap_or(false);
+        if !allowed {
+            return false;
+        }
+        let status = match self.client.head(url).send().await {
+            Ok(r) if r.status() == reqwest::StatusCode::METHOD_NOT_ALLOWED => {
+                self.client.get(url).send().await.map(|r| r.status())
+            }
+            Ok(r) => Ok(r.status()),
+            Err(e) => Err(e),
+        };
+        match status {
+            Ok(status) => {
+                status == reqwest::StatusCode::NOT_FOUND
+                    || status == reqwest::StatusCode::GONE
+                    || status.is_server_error()
+            }
+            Err(_) => true,
+        }
+    }
+
+    fn annotate(text: &str, dead: &[String]) -> String {
+        format!("{}\n\n(Could not reach: {})", text, dead.join(", "))
+    }
+}
+
+#[async_trait]
+impl OutputProcessor for LinkChecker {
+    fn name(&self) -> &str {
+        "link_check"
+    }
+
+    async fn process(&self, text: &str, _caps: &MessageCapabilities) -> Option<String> {
+        let links = Self::links(text);
+        if links.is_empty() {
+            return None;
+        }
+        let checks = futures::future::join_all(links.iter().map(|url| self.is_dead(url))).await;
+        let dead: Vec<String> = links
+            .into_iter()
+            .zip(checks)
+            .filter_map(|(url, dead)| dead.then_some(url))
+            .collect();
+        if dead.is_empty() {
+            return None;
+        }
+        tracing::debug!(links = ?dead, "Response links did not respond");
+        Some(Self::annotate(text, &dead))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caps(markdown: MarkdownDialect) -> MessageCapabilities {
+        MessageCapabilities {
+            markdown,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_blocks_round_trip() {
+        for text in [
+            "plain",
+            "a\n\n```rust\nfn main() {}\n```\nb\n",
+            "```\nunclosed",
+            "",
+        ] {
+            assert_eq!(render_blocks(&parse_blocks(text)), text);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_markdown_lint() {
+        let caps = caps(MarkdownDialect::Markdown);
+        let text = "Items:  \n* one\n+ two\n\n\n\nEnd\n```sh\n* not a bullet";
+        let linted = MarkdownLint.process(text, &caps).await.unwrap();
+        assert_eq!(
+            linted,
+            "Items:\n- one\n- two\n\nEnd\n```sh\n* not a bullet\n```"
+        );
+        assert_eq!(MarkdownLint.process(&linted, &caps).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_dialect_conversion_skips_code() {
+        let text = "## Result\n**Done**, see [docs](https://x.dev/a) ~~old~~ `**raw**`\n```\n**code**\n```";
+
+        let slack = DialectNormalizer
+            .process(text, &caps(MarkdownDialect::Slack))
+            .await
+            .unwrap();
+        assert_eq!(
+            slack,
+            "*Result*\n*Done*, see <https://x.dev/a|docs> ~old~ `**raw**`\n```\n**code**\n```"
+        );
+
+        let plain = DialectNormalizer
+            .process(text, &caps(MarkdownDialect::Plain))
+            .await
+            .unwrap();
+        assert!(plain.starts_with("Result\nDone, see docs: https://x.dev/a old `**raw**`"));
+
+        let telegram = DialectNormalizer
+            .process(text, &caps(MarkdownDialect::Telegram))
+            .await
+            .unwrap();
+        assert!(telegram.contains("[docs](https://x.dev/a)"));
+
+        assert_eq!(
+            DialectNormalizer
+                .process(text, &caps(MarkdownDialect::Markdown))
+                .await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_code_formatter_runs_matching_formatter() {
+        if !on_path("tr") {
+            return;
+        }
+        let formatter = CodeFormatter::new(vec![Formatter {
+            langs: vec![("shout".to_string(), "txt".to_string())],
+            program: "tr".to_string(),
+            args: vec!["a-z".to_string(), "A-Z".to_string()],
+        }]);
+        let text = "Run:\n```shout\nhello\n```\n```rust\nkeep\n```";
+        let formatted = formatter
+            .process(text, &caps(MarkdownDialect::Markdown))
+            .await
+            .unwrap();
+        assert_eq!(formatted, "Run:\n```shout\nHELLO\n```\n```rust\nkeep\n```");
+    }
+
+    #[test]
+    fn test_link_extraction() {
+        let text = "See https://a.dev/x. and <https://b.dev/y> or [c](https://c.dev/z)\n```\nhttps://code.dev\n```";
+        assert_eq!(
+            LinkChecker::links(text),
+            vec!["https://a.dev/x", "https://b.dev/y", "https://c.dev/z"]
+        );
+        assert_eq!(
+            LinkChecker::annotate("Hi", &["https://a.dev".to_string()]),
+            "Hi\n\n(Could not reach: https://a.dev)"
+        );
+    }
+
+    struct Upper;
+
+    #[async_trait]
+    impl OutputProcessor for Upper {
+        fn name(&self) -> &str {
+            "upper"
+        }
+        async fn process(&self, text: &str, _caps: &MessageCapabilities) -> Option<String> {
+            Some(text.to_uppercase())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_is_toggled_per_channel() {
+        let mut pipeline = OutputPipeline::new(HashMap::from([
+            ("*".to_string(), vec!["upper".to_string()]),
+            ("repl".to_string(), Vec::new()),
+        ]));
+        pipeline.register(Arc::new(Upper));
+        let caps = MessageCapabilities::default();
+
+        assert_eq!(pipeline.run("slack", &caps, "hi".into()).await, "HI");
+        assert_eq!(pipeline.run("repl", &caps, "hi".into()).await, "hi");
+
+        pipeline.set_enabled("repl", "upper", true);
+        pipeline.set_enabled("slack", "upper", false);
+        assert_eq!(pipeline.run("repl", &caps, "hi".into()).await, "HI");
+        assert_eq!(pipeline.run("slack", &caps, "hi".into()).await, "hi");
+        assert_eq!(pipeline.run("telegram", &caps, "hi".into()).await, "HI");
+    }
+}
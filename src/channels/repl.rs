@@ -18,8 +18,8 @@
 
 use std::borrow::Cow;
 use std::io::{self, Write};
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
 use rustyline::completion::Completer;
@@ -29,6 +29,7 @@ use rustyline::highlight::Highlighter;
 use rustyline::hint::Hinter;
 use rustyline::validate::Validator;
 use rustyline::{CompletionType, Editor, Helper};
+use serde::Serialize;
 use termimad::MadSkin;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
@@ -63,8 +64,14 @@ const SLASH_COMMANDS: &[&str] = &[
     "/heartbeat",
     "/summarize",
     "/suggest",
+    "/export",
     "/thread",
     "/resume",
+    "/project",
+    "/temperature",
+    "/style",
+    "/verbosity",
+    "/glossary",
 ];
 
 /// Rustyline helper for slash-command tab completion.
@@ -173,6 +180,45 @@ fn format_json_params(params: &serde_json::Value, indent: &str) -> String {
     }
 }
 
+/// A notification received after the reply (job finished, routine ran).
+#[derive(Debug, Clone, Serialize)]
+pub struct Followup {
+    pub content: String,
+    pub metadata: serde_json::Value,
+}
+
+/// Machine-readable result of a single-message run (`-m ... --json`).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ResultEnvelope {
+    /// `ok`, or `timeout` when background work was still running at exit.
+    pub status: String,
+    pub response: Option<String>,
+    pub followups: Vec<Followup>,
+    /// Jobs still running when the wait for follow-ups ran out.
+    pub pending_jobs: Vec<String>,
+}
+
+impl ResultEnvelope {
+    fn record(&mut self, response: &OutgoingResponse) {
+        if response.metadata.get("followup").and_then(|v| v.as_str()) == Some("done") {
+            if response.metadata["timed_out"].as_bool() == Some(true) {
+                self.status = "timeout".to_string();
+            }
+            if let Some(pending) = response.metadata["pending_jobs"].as_array() {
+                self.pending_jobs = pending
+                    .iter()
+                    .filter_map(|id| id.as_str().map(String::from))
+                    .collect();
+            }
+            return;
+        }
+        self.followups.push(Followup {
+            content: response.content.clone(),
+            metadata: response.metadata.clone(),
+        });
+    }
+}
+
 /// REPL channel with line editing and markdown rendering.
 pub struct ReplChannel {
     /// Optional single message to send (for -m flag).
@@ -181,6 +227,10 @@ pub struct ReplChannel {
     debug_mode: Arc<AtomicBool>,
     /// Whether we're currently streaming (chunks have been printed without a trailing newline).
     is_streaming: Arc<AtomicBool>,
+    /// Text printed by the current run of chunks.
+    streamed: Mutex<String>,
+    /// Collected instead of rendered, and printed as JSON on shutdown.
+    envelope: Option<Mutex<ResultEnvelope>>,
 }
 
 impl ReplChannel {
@@ -190,6 +240,8 @@ impl ReplChannel {
             single_message: None,
             debug_mode: Arc::new(AtomicBool::new(false)),
             is_streaming: Arc::new(AtomicBool::new(false)),
+            streamed: Mutex::new(String::new()),
+            envelope: None,
         }
     }
 
@@ -197,11 +249,27 @@ impl ReplChannel {
     pub fn with_message(message: String) -> Self {
         Self {
             single_message: Some(message),
-            debug_mode: Arc::new(AtomicBool::new(false)),
-            is_streaming: Arc::new(AtomicBool::new(false)),
+            ..Self::new()
         }
     }
 
+    /// Print the reply and follow-ups as one JSON [`ResultEnvelope`] on
+    /// stdout at shutdown instead of rendering them. Status lines still go
+    /// to stderr.
+    pub fn with_result_envelope(mut self) -> Self {
+        self.envelope = Some(Mutex::new(ResultEnvelope {
+            status: "ok".to_string(),
+            ..Default::default()
+        }));
+        self
+    }
+
+    fn envelope(&self) -> Option<std::sync::MutexGuard<'_, ResultEnvelope>> {
+        self.envelope
+            .as_ref()
+            .map(|e| e.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
     fn is_debug(&self) -> bool {
         self.debug_mode.load(Ordering::Relaxed)
     }
@@ -370,16 +438,26 @@ impl Channel for ReplChannel {
         _msg: &IncomingMessage,
         response: OutgoingResponse,
     ) -> Result<(), ChannelError> {
+        if let Some(mut envelope) = self.envelope() {
+            envelope.response = Some(response.content);
+            return Ok(());
+        }
+
         let width = crossterm::terminal::size()
             .map(|(w, _)| w as usize)
             .unwrap_or(80);
 
-        // If we were streaming, the content was already printed via StreamChunk.
-        // Just finish the line and reset.
+        // If we were streaming, the content was usually already printed via
+        // StreamChunk; just finish the line. The final reply can still differ
+        // (a draft the agent revised), and then it is rendered below.
         if self.is_streaming.swap(false, Ordering::Relaxed) {
+            let streamed =
+                std::mem::take(&mut *self.streamed.lock().unwrap_or_else(|e| e.into_inner()));
             println!();
-            println!();
-            return Ok(());
+            if streamed.trim() == response.content.trim() {
+                println!();
+                return Ok(());
+            }
         }
 
         // Dim separator line before the response
@@ -411,6 +489,15 @@ impl Channel for ReplChannel {
                 eprintln!("  \x1b[33m\u{25CB} {name}\x1b[0m");
             }
             StatusUpdate::ToolCompleted { name, success } => {
+                // Chunks streamed while a tool ran were its progress, not the
+                // reply; end them so the reply still renders.
+                if self.is_streaming.swap(false, Ordering::Relaxed) {
+                    println!();
+                }
+                self.streamed
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .clear();
                 if success {
                     eprintln!("  \x1b[32m\u{25CF} {name}\x1b[0m");
                 } else {
@@ -420,6 +507,9 @@ impl Channel for ReplChannel {
             StatusUpdate::ToolResult { name: _, preview } => {
                 eprintln!("    \x1b[90m{preview}\x1b[0m");
             }
+            StatusUpdate::StreamChunk(_) if self.envelope.is_some() => {
+                // The full reply arrives through respond()
+            }
             StatusUpdate::StreamChunk(chunk) => {
                 // Print separator on the false-to-true transition
                 if !self.is_streaming.swap(true, Ordering::Relaxed) {
@@ -431,6 +521,10 @@ impl Channel for ReplChannel {
                 }
                 print!("{chunk}");
                 let _ = io::stdout().flush();
+                self.streamed
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push_str(&chunk);
             }
             StatusUpdate::JobStarted {
                 job_id,
@@ -528,6 +622,27 @@ impl Channel for ReplChannel {
                     eprintln!("\x1b[31m  {extension_name}: {message}\x1b[0m");
                 }
             }
+            StatusUpdate::InputRequested { .. } => {
+                // The numbered text prompt arrives as a regular response
+            }
+            StatusUpdate::Image {
+                media_id,
+                mime_type,
+                title,
+                data,
+            } => {
+                // A terminal can't show it; save it where the user can open it
+                let file_name =
+                    format!("{media_id}.{}", crate::media::mime_to_extension(&mime_type));
+                let label = title.unwrap_or_else(|| "image".to_string());
+                match crate::media::write_export(&file_name, &data) {
+                    Ok(path) => eprintln!(
+                        "  \x1b[36m[image]\x1b[0m {label} \x1b[4m{}\x1b[0m",
+                        path.display()
+                    ),
+                    Err(e) => eprintln!("  \x1b[31m[image]\x1b[0m {label}: could not save: {e}"),
+                }
+            }
         }
         Ok(())
     }
@@ -537,6 +652,14 @@ impl Channel for ReplChannel {
         _user_id: &str,
         response: OutgoingResponse,
     ) -> Result<(), ChannelError> {
+        if let Some(mut envelope) = self.envelope() {
+            envelope.record(&response);
+            return Ok(());
+        }
+        if response.content.is_empty() {
+            return Ok(());
+        }
+
         let skin = make_skin();
         let width = crossterm::terminal::size()
             .map(|(w, _)| w as usize)
@@ -554,6 +677,10 @@ impl Channel for ReplChannel {
     }
 
     async fn shutdown(&self) -> Result<(), ChannelError> {
+        if let Some(envelope) = self.envelope() {
+            let json = serde_json::to_string(&*envelope).unwrap_or_default();
+            println!("{json}");
+        }
         Ok(())
     }
 }
@@ -631,6 +758,33 @@ mod tests {
         assert_eq!(ch.single_message.as_deref(), Some("hello"));
     }
 
+    #[tokio::test]
+    async fn test_result_envelope_collects_reply_and_followups() {
+        let ch = ReplChannel::with_message("hello".to_string()).with_result_envelope();
+        let msg = IncomingMessage::new("repl", "user", "hello");
+        ch.respond(&msg, OutgoingResponse::text("Started a job"))
+            .await
+            .unwrap();
+
+        let mut job = OutgoingResponse::text("Job 'Fetch' completed");
+        job.metadata = serde_json::json!({"followup": "job", "state": "completed"});
+        ch.broadcast("default", job).await.unwrap();
+        let mut done = OutgoingResponse::text("Stopped waiting");
+        done.metadata = serde_json::json!({
+            "followup": "done",
+            "timed_out": true,
+            "pending_jobs": ["abc"],
+        });
+        ch.broadcast("default", done).await.unwrap();
+
+        let envelope = ch.envelope().unwrap().clone();
+        assert_eq!(envelope.status, "timeout");
+        assert_eq!(envelope.response.as_deref(), Some("Started a job"));
+        assert_eq!(envelope.followups.len(), 1);
+        assert_eq!(envelope.followups[0].metadata["state"], "completed");
+        assert_eq!(envelope.pending_jobs, vec!["abc"]);
+    }
+
     #[test]
     fn test_repl_channel_default() {
         let ch = ReplChannel::default();
@@ -0,0 +1,265 @@
+//! Routing rules for outbound notifications.
+//!
+//! Background notifications carry a [`NotificationCategory`]. Rules map a
+//! category to a channel (and optionally a recipient), optionally only
+//! within a local time-of-day window:
+//!
+//! ```json
+//! [
+//!   {"category": "security_alert", "channel": "telegram"},
+//!   {"category": "routine_digest", "channel": "email", "hours": "08:00-18:00"}
+//! ]
+//! ```
+//!
+//! Every rule active at send time gets a copy. When no rule for the
+//! category is active, the notification goes wherever it went before
+//! (the source's own channel, or every channel). Rules live in the
+//! `notifications.routes` setting and can be edited in chat with the
+//! `notification_routes` tool.
+
+use std::sync::RwLock;
+
+use chrono::{DateTime, NaiveTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::locale::Tz;
+
+/// What a background notification is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    /// A background job finished or a stuck job was repaired.
+    JobFinished,
+    /// A tool call is waiting for the user's approval.
+    ApprovalNeeded,
+    /// Suspicious tool output or an emergency finding.
+    SecurityAlert,
+    /// Routine and heartbeat results.
+    RoutineDigest,
+}
+
+impl NotificationCategory {
+    pub const ALL: [NotificationCategory; 4] = [
+        NotificationCategory::JobFinished,
+        NotificationCategory::ApprovalNeeded,
+        NotificationCategory::SecurityAlert,
+        NotificationCategory::RoutineDigest,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationCategory::JobFinished => "job_finished",
+            NotificationCategory::ApprovalNeeded => "approval_needed",
+            NotificationCategory::SecurityAlert => "security_alert",
+            NotificationCategory::RoutineDigest => "routine_digest",
+        }
+    }
+}
+
+impl std::fmt::Display for NotificationCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for NotificationCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|c| c.as_str() == s)
+            .ok_or_else(|| {
+                format!(
+                    "unknown notification category '{}' (expected one of: {})",
+                    s,
+                    Self::ALL.map(|c| c.as_str()).join(", ")
+                )
+            })
+    }
+}
+
+/// One routing rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteRule {
+    pub category: NotificationCategory,
+    /// Channel name, e.g. "telegram".
+    pub channel: String,
+    /// Recipient on the channel; the source's recipient when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Local time window "HH:MM-HH:MM"; may wrap past midnight. Always
+    /// active when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hours: Option<String>,
+}
+
+impl RouteRule {
+    /// Check the rule is well formed.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.channel.trim().is_empty() {
+            return Err("channel must not be empty".to_string());
+        }
+        if let Some(ref hours) = self.hours
+            && parse_window(hours).is_none()
+        {
+            return Err(format!(
+                "invalid hours '{}' (expected HH:MM-HH:MM, e.g. 22:00-07:00)",
+                hours
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether the rule applies at local wall-clock time `local`.
+    pub fn active_at(&self, local: NaiveTime) -> bool {
+        let Some(ref hours) = self.hours else {
+            return true;
+        };
+        match parse_window(hours) {
+            Some((start, end)) if start <= end => start <= local && local < end,
+            Some((start, end)) => local >= start || local < end,
+            None => false,
+        }
+    }
+}
+
+fn parse_window(s: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let (start, end) = s.split_once('-')?;
+    let start = NaiveTime::parse_from_str(start.trim(), "%H:%M").ok()?;
+    let end = NaiveTime::parse_from_str(end.trim(), "%H:%M").ok()?;
+    (start != end).then_some((start, end))
+}
+
+/// The rule set, shared by the channel manager and the chat tool.
+pub struct NotificationRouter {
+    rules: RwLock<Vec<RouteRule>>,
+    /// Zone the time windows are read in.
+    tz: Tz,
+}
+
+impl NotificationRouter {
+    /// Build a router; malformed rules are dropped with a warning.
+    pub fn new(rules: Vec<RouteRule>, tz: Tz) -> Self {
+        let rules = rules
+            .into_iter()
+            .filter(|rule| match rule.validate() {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::warn!("Ignoring notification route to {}: {}", rule.channel, e);
+                    false
+                }
+            })
+            .collect();
+        Self {
+            rules: RwLock::new(rules),
+            tz,
+        }
+    }
+
+    pub fn rules(&self) -> Vec<RouteRule> {
+        self.rules.read().expect("routes lock").clone()
+    }
+
+    /// Append a rule. Returns the new rule set.
+    pub fn add(&self, rule: RouteRule) -> Result<Vec<RouteRule>, String> {
+        rule.validate()?;
+        let mut rules = self.rules.write().expect("routes lock");
+        if !rules.contains(&rule) {
+            rules.push(rule);
+        }
+        Ok(rules.clone())
+    }
+
+    /// Remove the rule at `index` (0-based). Returns the new rule set.
+    pub fn remove(&self, index: usize) -> Result<Vec<RouteRule>, String> {
+        let mut rules = self.rules.write().expect("routes lock");
+        if index >= rules.len() {
+            return Err(format!("no route at index {}", index));
+        }
+        rules.remove(index);
+        Ok(rules.clone())
+    }
+
+    /// Rules for `category` active at `now`.
+    pub fn targets(&self, category: NotificationCategory, now: DateTime<Utc>) -> Vec<RouteRule> {
+        let local = self.tz.to_local(now);
+        let local = NaiveTime::from_hms_opt(local.hour(), local.minute(), 0).unwrap_or_default();
+        self.rules
+            .read()
+            .expect("routes lock")
+            .iter()
+            .filter(|rule| rule.category == category && rule.active_at(local))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn rule(category: NotificationCategory, channel: &str, hours: Option<&str>) -> RouteRule {
+        RouteRule {
+            category,
+            channel: channel.to_string(),
+            user: None,
+            hours: hours.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_targets_respect_category_and_hours() {
+        let router = NotificationRouter::new(
+            vec![
+                rule(NotificationCategory::SecurityAlert, "telegram", None),
+                rule(
+                    NotificationCategory::RoutineDigest,
+                    "email",
+                    Some("08:00-18:00"),
+                ),
+                rule(NotificationCategory::RoutineDigest, "bad", Some("late")),
+            ],
+            Tz::utc(),
+        );
+        assert_eq!(router.rules().len(), 2);
+
+        let noon = Utc.with_ymd_and_hms(2026, 3, 2, 12, 0, 0).unwrap();
+        let night = Utc.with_ymd_and_hms(2026, 3, 2, 23, 0, 0).unwrap();
+        let digest = NotificationCategory::RoutineDigest;
+        assert_eq!(router.targets(digest, noon)[0].channel, "email");
+        assert!(router.targets(digest, night).is_empty());
+        assert_eq!(
+            router
+                .targets(NotificationCategory::SecurityAlert, night)
+                .len(),
+            1
+        );
+        assert!(
+            router
+                .targets(NotificationCategory::JobFinished, noon)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_window_wraps_midnight() {
+        let quiet = rule(
+            NotificationCategory::JobFinished,
+            "email",
+            Some("22:00-07:00"),
+        );
+        let at = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        assert!(quiet.active_at(at(23)));
+        assert!(quiet.active_at(at(3)));
+        assert!(!quiet.active_at(at(12)));
+        assert!(
+            "routine_digest"
+                .parse::<NotificationCategory>()
+                .is_ok_and(|c| c == NotificationCategory::RoutineDigest)
+        );
+        assert!("digest".parse::<NotificationCategory>().is_err());
+    }
+}
@@ -9,11 +9,12 @@
 //! Error occurred      --> record_error(name, reason)
 //! Status changed      --> set_status(name, status)
 //! Dashboard queries   --> get_all_statuses(), message_throughput()
+//! All channels started --> mark_startup_complete()
 //! ```
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::Instant;
 
 use chrono::{DateTime, Utc};
@@ -69,6 +70,7 @@ impl ChannelMetrics {
 pub struct ChannelStatusTracker {
     channels: Arc<RwLock<HashMap<String, Arc<ChannelMetrics>>>>,
     started_at: Instant,
+    startup_complete: AtomicBool,
 }
 
 impl ChannelStatusTracker {
@@ -77,9 +79,20 @@ impl ChannelStatusTracker {
         Self {
             channels: Arc::new(RwLock::new(HashMap::new())),
             started_at: Instant::now(),
+            startup_complete: AtomicBool::new(false),
         }
     }
 
+    /// Record that every channel has been started (or failed to start).
+    pub fn mark_startup_complete(&self) {
+        self.startup_complete.store(true, Ordering::Release);
+    }
+
+    /// Whether channel startup has finished.
+    pub fn is_startup_complete(&self) -> bool {
+        self.startup_complete.load(Ordering::Acquire)
+    }
+
     /// Register a new channel. If the channel already exists, this is a no-op.
     pub async fn register_channel(&self, name: &str) {
         let mut channels = self.channels.write().await;
@@ -8,6 +8,7 @@ use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
+use crate::channels::MessageCapabilities;
 use crate::tools::wasm::{Capabilities as ToolCapabilities, RateLimitConfig};
 
 /// Minimum allowed polling interval (30 seconds).
@@ -49,6 +50,9 @@ pub struct ChannelCapabilities {
 
     /// Callback timeout duration.
     pub callback_timeout: Duration,
+
+    /// What one outbound message can carry on the platform.
+    pub outbound: MessageCapabilities,
 }
 
 impl Default for ChannelCapabilities {
@@ -62,6 +66,7 @@ impl Default for ChannelCapabilities {
             emit_rate_limit: EmitRateLimitConfig::default(),
             max_message_size: 64 * 1024, // 64 KB
             callback_timeout: Duration::from_secs(30),
+            outbound: MessageCapabilities::default(),
         }
     }
 }
@@ -29,7 +29,10 @@
 //!       "allowed_paths": ["/webhook/slack"],
 //!       "allow_polling": false,
 //!       "workspace_prefix": "channels/slack/",
-//!       "emit_rate_limit": { "messages_per_minute": 100 }
+//!       "emit_rate_limit": { "messages_per_minute": 100 },
+//!       "max_response_chars": 4000,
+//!       "markdown": "slack",
+//!       "supports_edits": true
 //!     }
 //!   },
 //!   "config": {
@@ -46,6 +49,7 @@ use serde::{Deserialize, Serialize};
 use crate::channels::wasm::capabilities::{
     ChannelCapabilities, EmitRateLimitConfig, MIN_POLL_INTERVAL_MS,
 };
+use crate::channels::{MarkdownDialect, MessageCapabilities};
 use crate::tools::wasm::{CapabilitiesFile as ToolCapabilitiesFile, RateLimitSchema};
 
 /// Root schema for a channel capabilities JSON file.
@@ -169,6 +173,12 @@ impl ChannelCapabilitiesSchema {
             if let Some(timeout_secs) = channel.callback_timeout_secs {
                 caps.callback_timeout = Duration::from_secs(timeout_secs);
             }
+
+            caps.outbound = MessageCapabilities {
+                max_message_len: channel.max_response_chars,
+                markdown: channel.markdown.unwrap_or_default(),
+                supports_edits: channel.supports_edits,
+            };
         }
 
         caps
@@ -206,6 +216,19 @@ pub struct ChannelSpecificCapabilitiesSchema {
     #[serde(default)]
     pub callback_timeout_secs: Option<u64>,
 
+    /// Longest outbound message the platform accepts, in characters.
+    /// Longer responses are split before they reach the channel.
+    #[serde(default)]
+    pub max_response_chars: Option<usize>,
+
+    /// Markdown dialect the platform renders.
+    #[serde(default)]
+    pub markdown: Option<MarkdownDialect>,
+
+    /// Whether the platform can edit messages after they are sent.
+    #[serde(default)]
+    pub supports_edits: bool,
+
     /// Webhook configuration (secret header, etc.).
     #[serde(default)]
     pub webhook: Option<WebhookSchema>,
@@ -372,6 +395,7 @@ pub struct PollConfigSchema {
 
 #[cfg(test)]
 mod tests {
+    use crate::channels::MarkdownDialect;
     use crate::channels::wasm::schema::ChannelCapabilitiesFile;
 
     #[test]
@@ -514,6 +538,27 @@ mod tests {
         assert_eq!(caps.emit_rate_limit.messages_per_hour, 1000);
     }
 
+    #[test]
+    fn test_outbound_message_capabilities() {
+        let json = r#"{
+            "name": "telegram",
+            "capabilities": {
+                "channel": {
+                    "max_response_chars": 4096,
+                    "markdown": "telegram",
+                    "supports_edits": true
+                }
+            }
+        }"#;
+
+        let file = ChannelCapabilitiesFile::from_json(json).unwrap();
+        let caps = file.to_capabilities();
+
+        assert_eq!(caps.outbound.max_message_len, Some(4096));
+        assert_eq!(caps.outbound.markdown, MarkdownDialect::Telegram);
+        assert!(caps.outbound.supports_edits);
+    }
+
     #[test]
     fn test_webhook_schema() {
         let json = r#"{
@@ -46,7 +46,9 @@ use crate::channels::wasm::host::{ChannelEmitRateLimiter, ChannelHostState, Emit
 use crate::channels::wasm::router::RegisteredEndpoint;
 use crate::channels::wasm::runtime::{PreparedChannelModule, WasmChannelRuntime};
 use crate::channels::wasm::schema::ChannelConfig;
-use crate::channels::{Channel, IncomingMessage, MessageStream, OutgoingResponse, StatusUpdate};
+use crate::channels::{
+    Channel, IncomingMessage, MessageCapabilities, MessageStream, OutgoingResponse, StatusUpdate,
+};
 use crate::error::ChannelError;
 use crate::pairing::PairingStore;
 use crate::safety::LeakDetector;
@@ -268,20 +270,21 @@ impl near::agent::channel_host::Host for ChannelStoreData {
             .scan_http_request(&url, &header_vec, body.as_deref())
             .map_err(|e| format!("Potential secret leak blocked: {}", e))?;
 
-        // Get the max response size from capabilities (default 10MB).
-        let max_response_bytes = self
-            .host_state
-            .capabilities()
-            .tool_capabilities
-            .http
-            .as_ref()
-            .map(|h| h.max_response_bytes)
-            .unwrap_or(10 * 1024 * 1024);
+        // Size and content-type limits for this endpoint.
+        let limits = crate::tools::wasm::ResponseLimits::resolve(
+            self.host_state
+                .capabilities()
+                .tool_capabilities
+                .http
+                .as_ref(),
+            &url,
+            &method,
+        );
 
         // Make the HTTP request using blocking I/O
         // We're already in a spawn_blocking context, so we can use block_on
         let result = tokio::runtime::Handle::current().block_on(async {
-            let client = reqwest::Client::new();
+            let client = crate::outbound::client();
 
             let mut request = match method.to_uppercase().as_str() {
                 "GET" => client.get(&url),
@@ -331,28 +334,22 @@ impl near::agent::channel_host::Host for ChannelStoreData {
                 .collect();
             let headers_json = serde_json::to_string(&response_headers).unwrap_or_default();
 
-            // Enforce max response body size to prevent memory exhaustion.
-            let max_response = max_response_bytes;
-            if let Some(cl) = response.content_length()
-                && cl as usize > max_response
-            {
-                return Err(format!(
-                    "Response body too large: {} bytes exceeds limit of {} bytes",
-                    cl, max_response
-                ));
+            let empty = status == 204
+                || status == 304
+                || method.eq_ignore_ascii_case("HEAD")
+                || response.content_length() == Some(0);
+            if !empty {
+                let content_type = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok());
+                limits
+                    .check_content_type(content_type)
+                    .map_err(|e| e.to_string())?;
             }
-            let body = response
-                .bytes()
-                .await
-                .map_err(|e| format!("Failed to read response body: {}", e))?;
-            if body.len() > max_response {
-                return Err(format!(
-                    "Response body too large: {} bytes exceeds limit of {} bytes",
-                    body.len(),
-                    max_response
-                ));
-            }
-            let body = body.to_vec();
+
+            // Enforce max response body size to prevent memory exhaustion.
+            let body = limits.read_body(response).await?;
 
             tracing::info!(
                 status = status,
@@ -1350,6 +1347,24 @@ impl WasmChannel {
             StatusUpdate::StreamChunk(_) => {
                 // No-op, too noisy
             }
+            StatusUpdate::Image {
+                media_id,
+                mime_type,
+                title,
+                data,
+            } => {
+                // Images go out as a message of their own; the guest sends
+                // `metadata.image` as a photo, or just the caption if it can't.
+                let caption = title.clone().unwrap_or_else(|| "Chart".to_string());
+                let metadata = image_metadata(metadata, media_id, mime_type, data);
+                let metadata_json = serde_json::to_string(&metadata).unwrap_or_default();
+                self.call_on_respond(Uuid::new_v4(), &caption, None, &metadata_json)
+                    .await
+                    .map_err(|e| ChannelError::SendFailed {
+                        name: self.name.clone(),
+                        reason: e.to_string(),
+                    })?;
+            }
             _ => {
                 // Done, Interrupted, Status, ToolStarted, ToolCompleted: cancel and fire once
                 self.cancel_typing_task().await;
@@ -1688,6 +1703,10 @@ impl Channel for WasmChannel {
         &self.name
     }
 
+    fn message_capabilities(&self) -> MessageCapabilities {
+        self.capabilities.outbound.clone()
+    }
+
     async fn start(&self) -> Result<MessageStream, ChannelError> {
         // Create message channel
         let (tx, rx) = mpsc::channel(256);
@@ -1777,7 +1796,9 @@ impl Channel for WasmChannel {
         // IMPORTANT: Use the ORIGINAL message's metadata, not the response's metadata.
         // The original metadata contains channel-specific routing info (e.g., Telegram chat_id)
         // that the WASM channel needs to send the reply to the correct destination.
-        let metadata_json = serde_json::to_string(&msg.metadata).unwrap_or_default();
+        // Response metadata may only add keys (e.g. `reply_options` for buttons).
+        let metadata = merge_response_metadata(&msg.metadata, &response.metadata);
+        let metadata_json = serde_json::to_string(&metadata).unwrap_or_default();
         self.call_on_respond(
             msg.id,
             &response.content,
@@ -1885,6 +1906,10 @@ impl Channel for SharedWasmChannel {
         self.inner.name()
     }
 
+    fn message_capabilities(&self) -> MessageCapabilities {
+        self.inner.message_capabilities()
+    }
+
     async fn start(&self) -> Result<MessageStream, ChannelError> {
         self.inner.start().await
     }
@@ -2007,6 +2032,11 @@ fn status_to_wit(status: &StatusUpdate, metadata: &serde_json::Value) -> wit_cha
             message: format!("Approval needed: {} - {}", tool_name, description),
             metadata_json,
         },
+        StatusUpdate::InputRequested { .. } => wit_channel::StatusUpdate {
+            status: wit_channel::StatusType::Thinking,
+            message: "Waiting for your answer".to_string(),
+            metadata_json,
+        },
         StatusUpdate::JobStarted { job_id, title, .. } => wit_channel::StatusUpdate {
             status: wit_channel::StatusType::Thinking,
             message: format!("Job started: {} ({})", title, job_id),
@@ -2030,7 +2060,47 @@ fn status_to_wit(status: &StatusUpdate, metadata: &serde_json::Value) -> wit_cha
             ),
             metadata_json,
         },
+        StatusUpdate::Image { title, .. } => wit_channel::StatusUpdate {
+            status: wit_channel::StatusType::Thinking,
+            message: format!("Image: {}", title.as_deref().unwrap_or("chart")),
+            metadata_json,
+        },
+    }
+}
+
+/// Overlay response metadata onto the original message metadata.
+///
+/// Keys already present in the original (routing info) are never replaced.
+fn merge_response_metadata(
+    original: &serde_json::Value,
+    response: &serde_json::Value,
+) -> serde_json::Value {
+    let mut merged = original.clone();
+    if let (Some(target), Some(extra)) = (merged.as_object_mut(), response.as_object()) {
+        for (key, value) in extra {
+            target.entry(key.clone()).or_insert_with(|| value.clone());
+        }
     }
+    merged
+}
+
+/// Attach an image to the original message metadata as `image`:
+/// `{mime_type, file_name, data_base64}`.
+fn image_metadata(
+    original: &serde_json::Value,
+    media_id: &str,
+    mime_type: &str,
+    data: &[u8],
+) -> serde_json::Value {
+    use base64::Engine;
+    let image = serde_json::json!({
+        "image": {
+            "mime_type": mime_type,
+            "file_name": format!("{}.{}", media_id, crate::media::mime_to_extension(mime_type)),
+            "data_base64": base64::engine::general_purpose::STANDARD.encode(data),
+        }
+    });
+    merge_response_metadata(original, &image)
 }
 
 /// Clone a WIT StatusUpdate (the generated type doesn't derive Clone).
@@ -2521,6 +2591,35 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_merge_response_metadata_keeps_routing() {
+        use super::merge_response_metadata;
+
+        let original = serde_json::json!({"chat_id": 42, "message_id": 7});
+        let response = serde_json::json!({"chat_id": 1, "reply_options": ["Yes", "No"]});
+        let merged = merge_response_metadata(&original, &response);
+
+        assert_eq!(merged["chat_id"], 42);
+        assert_eq!(merged["reply_options"], serde_json::json!(["Yes", "No"]));
+        assert_eq!(
+            merge_response_metadata(&original, &serde_json::Value::Null),
+            original
+        );
+    }
+
+    #[test]
+    fn test_image_metadata_keeps_routing() {
+        use super::image_metadata;
+
+        let original = serde_json::json!({"chat_id": 42});
+        let merged = image_metadata(&original, "chart-ab12", "image/png", b"png");
+
+        assert_eq!(merged["chat_id"], 42);
+        assert_eq!(merged["image"]["file_name"], "chart-ab12.png");
+        assert_eq!(merged["image"]["mime_type"], "image/png");
+        assert_eq!(merged["image"]["data_base64"], "cG5n");
+    }
+
     #[test]
     fn test_clone_wit_status_update() {
         use super::{clone_wit_status_update, wit_channel};
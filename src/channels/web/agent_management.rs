@@ -11,6 +11,8 @@
 use axum::{Json, extract::Path, http::StatusCode, response::IntoResponse};
 use serde::{Deserialize, Serialize};
 
+use crate::workspace::MemoryScope;
+
 /// Agent info returned by the API.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentInfo {
@@ -39,6 +41,9 @@ pub struct AgentInfo {
     /// Workspace/memory space for this agent.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub workspace: Option<String>,
+    /// Whose memory the agent reads and writes.
+    #[serde(default)]
+    pub memory_scope: MemoryScope,
     /// When this agent was created.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub created_at: Option<String>,
@@ -58,6 +63,8 @@ pub struct CreateAgentRequest {
     #[serde(default)]
     pub enabled_skills: Vec<String>,
     pub workspace: Option<String>,
+    #[serde(default)]
+    pub memory_scope: MemoryScope,
 }
 
 /// Request to update an agent.
@@ -70,6 +77,7 @@ pub struct UpdateAgentRequest {
     pub enabled_tools: Option<Vec<String>>,
     pub enabled_skills: Option<Vec<String>>,
     pub workspace: Option<String>,
+    pub memory_scope: Option<MemoryScope>,
     pub active: Option<bool>,
 }
 
@@ -123,6 +131,8 @@ impl AgentStore {
             enabled_tools: Vec::new(),
             enabled_skills: Vec::new(),
             workspace: None,
+            // The default agent keeps using the pool every agent shares.
+            memory_scope: MemoryScope::Shared,
             created_at: Some(chrono::Utc::now().to_rfc3339()),
         };
 
@@ -165,6 +175,7 @@ impl AgentStore {
             enabled_tools: req.enabled_tools,
             enabled_skills: req.enabled_skills,
             workspace: req.workspace,
+            memory_scope: req.memory_scope,
             created_at: Some(chrono::Utc::now().to_rfc3339()),
         };
 
@@ -202,6 +213,9 @@ impl AgentStore {
         if let Some(workspace) = req.workspace {
             agent.workspace = Some(workspace);
         }
+        if let Some(scope) = req.memory_scope {
+            agent.memory_scope = scope;
+        }
         if let Some(active) = req.active {
             agent.active = active;
         }
@@ -364,11 +378,13 @@ mod tests {
             enabled_tools: vec!["memory_search".to_string()],
             enabled_skills: Vec::new(),
             workspace: None,
+            memory_scope: MemoryScope::Private,
         };
 
         let agent = store.create(req).await.unwrap();
         assert_eq!(agent.id, "research");
         assert!(!agent.is_default);
+        assert_eq!(agent.memory_scope, MemoryScope::Private);
 
         let agents = store.list().await;
         assert_eq!(agents.len(), 2);
@@ -386,6 +402,7 @@ mod tests {
             enabled_tools: Vec::new(),
             enabled_skills: Vec::new(),
             workspace: None,
+            memory_scope: MemoryScope::Private,
         };
 
         assert!(store.create(req).await.is_err());
@@ -402,12 +419,14 @@ mod tests {
             enabled_tools: None,
             enabled_skills: None,
             workspace: None,
+            memory_scope: Some(MemoryScope::Channel),
             active: None,
         };
 
         let updated = store.update("default", req).await.unwrap();
         assert_eq!(updated.name, "Updated Name");
         assert_eq!(updated.model.unwrap(), "gpt-4");
+        assert_eq!(updated.memory_scope, MemoryScope::Channel);
     }
 
     #[tokio::test]
@@ -428,6 +447,7 @@ mod tests {
             enabled_tools: Vec::new(),
             enabled_skills: Vec::new(),
             workspace: None,
+            memory_scope: MemoryScope::Private,
         };
         store.create(req).await.unwrap();
         assert_eq!(store.list().await.len(), 2);
@@ -448,6 +468,7 @@ mod tests {
             enabled_tools: Vec::new(),
             enabled_skills: Vec::new(),
             workspace: None,
+            memory_scope: MemoryScope::Private,
         };
         store.create(req).await.unwrap();
 
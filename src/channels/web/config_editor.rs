@@ -260,6 +260,66 @@ fn build_gateway_section(settings: &std::collections::HashMap<String, Value>) ->
                 required: false,
                 sensitive: true,
             },
+            ConfigField {
+                key: "gateway.rate_limit.requests".to_string(),
+                label: "Rate Limit Requests".to_string(),
+                description:
+                    "Chat requests allowed per window for one token (applies without restart)"
+                        .to_string(),
+                field_type: ConfigFieldType::Integer,
+                value: get_or_default(
+                    settings,
+                    "gateway.rate_limit.requests",
+                    Value::Number(30.into()),
+                ),
+                default_value: Value::Number(30.into()),
+                required: false,
+                sensitive: false,
+            },
+            ConfigField {
+                key: "gateway.rate_limit.window_secs".to_string(),
+                label: "Rate Limit Window".to_string(),
+                description: "Rate limit window in seconds".to_string(),
+                field_type: ConfigFieldType::Duration,
+                value: get_or_default(
+                    settings,
+                    "gateway.rate_limit.window_secs",
+                    Value::Number(60.into()),
+                ),
+                default_value: Value::Number(60.into()),
+                required: false,
+                sensitive: false,
+            },
+            ConfigField {
+                key: "gateway.rate_limit.soft_queue_depth".to_string(),
+                label: "Soft Queue Depth".to_string(),
+                description: "Agent queue depth at which chat requests start to cost more"
+                    .to_string(),
+                field_type: ConfigFieldType::Integer,
+                value: get_or_default(
+                    settings,
+                    "gateway.rate_limit.soft_queue_depth",
+                    Value::Number(8.into()),
+                ),
+                default_value: Value::Number(8.into()),
+                required: false,
+                sensitive: false,
+            },
+            ConfigField {
+                key: "gateway.rate_limit.max_queue_depth".to_string(),
+                label: "Max Queue Depth".to_string(),
+                description: "Agent queue depth at which chat requests are refused with 429"
+                    .to_string(),
+                field_type: ConfigFieldType::Integer,
+                value: get_or_default(
+                    settings,
+                    "gateway.rate_limit.max_queue_depth",
+                    Value::Number(32.into()),
+                ),
+                default_value: Value::Number(32.into()),
+                required: false,
+                sensitive: false,
+            },
         ],
     }
 }
@@ -663,6 +723,7 @@ mod tests {
         assert!(keys.contains(&"gateway.enabled"));
         assert!(keys.contains(&"gateway.port"));
         assert!(keys.contains(&"gateway.auth_token"));
+        assert!(keys.contains(&"gateway.rate_limit.max_queue_depth"));
     }
 
     #[test]
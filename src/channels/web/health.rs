@@ -0,0 +1,332 @@
+//! Health probes for Kubernetes and other process supervisors.
+//!
+//! ```text
+//! GET /healthz   ──► liveness: 200 while the gateway serves requests
+//! GET /readyz    ──► readiness: configured checks, 503 if any fails
+//! GET /startupz  ──► startup: 200 once every channel has been started
+//! ```
+//!
+//! Readiness runs the checks listed in `GATEWAY_READY_CHECKS` (default
+//! `database,llm,channels`, or `none`), each bounded by
+//! `GATEWAY_HEALTH_TIMEOUT_MS`. A check that times out counts as failed.
+
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Json;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+
+use crate::channels::ChannelStatusTracker;
+use crate::db::Database;
+
+/// Default per-check timeout.
+pub const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A readiness check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadyCheck {
+    /// The database answers a trivial query.
+    Database,
+    /// An LLM provider is configured.
+    Llm,
+    /// Channel startup finished and no channel failed to start.
+    Channels,
+}
+
+impl ReadyCheck {
+    pub const ALL: [ReadyCheck; 3] = [Self::Database, Self::Llm, Self::Channels];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Database => "database",
+            Self::Llm => "llm",
+            Self::Channels => "channels",
+        }
+    }
+
+    /// Parse a comma-separated list; `none` or an empty string disables
+    /// all checks.
+    pub fn parse_list(s: &str) -> Result<Vec<Self>, String> {
+        let s = s.trim();
+        if s.is_empty() || s.eq_ignore_ascii_case("none") {
+            return Ok(Vec::new());
+        }
+        let mut checks = Vec::new();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let check = part.parse()?;
+            if !checks.contains(&check) {
+                checks.push(check);
+            }
+        }
+        Ok(checks)
+    }
+}
+
+impl FromStr for ReadyCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "database" | "db" => Ok(Self::Database),
+            "llm" => Ok(Self::Llm),
+            "channels" => Ok(Self::Channels),
+            other => Err(format!(
+                "unknown readiness check '{other}' (expected database, llm or channels)"
+            )),
+        }
+    }
+}
+
+/// Which readiness checks run and how long each may take.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthConfig {
+    pub ready_checks: Vec<ReadyCheck>,
+    pub check_timeout: Duration,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            ready_checks: ReadyCheck::ALL.to_vec(),
+            check_timeout: DEFAULT_CHECK_TIMEOUT,
+        }
+    }
+}
+
+/// Everything the probes look at besides the database.
+#[derive(Clone, Default)]
+pub struct HealthProbes {
+    pub config: HealthConfig,
+    /// Channel startup state, fed by the channel manager.
+    pub channels: Option<Arc<ChannelStatusTracker>>,
+    /// Model of the configured LLM provider.
+    pub llm_model: Option<String>,
+}
+
+/// Outcome of one check.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl CheckResult {
+    fn pass(name: &'static str, detail: impl Into<Option<String>>) -> Self {
+        Self {
+            name,
+            ok: true,
+            detail: detail.into(),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            ok: false,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
+/// Probe response: 200 when every check passed, 503 otherwise.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbeReport {
+    pub status: &'static str,
+    pub checks: Vec<CheckResult>,
+}
+
+impl ProbeReport {
+    pub fn new(checks: Vec<CheckResult>) -> Self {
+        let status = if checks.iter().all(|c| c.ok) {
+            "ok"
+        } else {
+            "unavailable"
+        };
+        Self { status, checks }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.checks.iter().all(|c| c.ok)
+    }
+}
+
+impl IntoResponse for ProbeReport {
+    fn into_response(self) -> Response {
+        let code = if self.is_ok() {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        };
+        (code, Json(self)).into_response()
+    }
+}
+
+impl HealthProbes {
+    /// Liveness: answering at all is the signal.
+    pub fn liveness(&self) -> ProbeReport {
+        ProbeReport::new(Vec::new())
+    }
+
+    /// Startup: passes once the channel manager has started every channel.
+    pub fn startup(&self) -> ProbeReport {
+        let check = match self.channels {
+            Some(ref tracker) if tracker.is_startup_complete() => {
+                CheckResult::pass("startup", None)
+            }
+            Some(_) => CheckResult::fail("startup", "channels are still starting"),
+            None => CheckResult::fail("startup", "channel status is not available"),
+        };
+        ProbeReport::new(vec![check])
+    }
+
+    /// Readiness: run the configured checks concurrently, each under the
+    /// configured timeout.
+    pub async fn readiness(&self, store: Option<&dyn Database>) -> ProbeReport {
+        let timeout = self.config.check_timeout;
+        let checks = self.config.ready_checks.iter().map(|check| async move {
+            let name = check.name();
+            let run = async {
+                match check {
+                    ReadyCheck::Database => self.check_database(store).await,
+                    ReadyCheck::Llm => self.check_llm(),
+                    ReadyCheck::Channels => self.check_channels().await,
+                }
+            };
+            with_timeout(name, timeout, run).await
+        });
+        ProbeReport::new(futures::future::join_all(checks).await)
+    }
+
+    async fn check_database(&self, store: Option<&dyn Database>) -> CheckResult {
+        let name = ReadyCheck::Database.name();
+        match store {
+            Some(store) => match store.ping().await {
+                Ok(()) => CheckResult::pass(name, None),
+                Err(e) => CheckResult::fail(name, e.to_string()),
+            },
+            None => CheckResult::fail(name, "no database configured"),
+        }
+    }
+
+    fn check_llm(&self) -> CheckResult {
+        let name = ReadyCheck::Llm.name();
+        match self.llm_model {
+            Some(ref model) if !model.is_empty() => CheckResult::pass(name, model.clone()),
+            _ => CheckResult::fail(name, "no LLM provider configured"),
+        }
+    }
+
+    async fn check_channels(&self) -> CheckResult {
+        let name = ReadyCheck::Channels.name();
+        let Some(ref tracker) = self.channels else {
+            return CheckResult::fail(name, "channel status is not available");
+        };
+        if !tracker.is_startup_complete() {
+            return CheckResult::fail(name, "channels are still starting");
+        }
+        let failed: Vec<String> = tracker
+            .get_all_statuses()
+            .await
+            .into_iter()
+            .filter(|c| c.status != "connected")
+            .map(|c| format!("{} ({})", c.name, c.status))
+            .collect();
+        if failed.is_empty() {
+            CheckResult::pass(name, None)
+        } else {
+            CheckResult::fail(name, format!("not running: {}", failed.join(", ")))
+        }
+    }
+}
+
+async fn with_timeout(
+    name: &'static str,
+    timeout: Duration,
+    check: impl Future<Output = CheckResult>,
+) -> CheckResult {
+    tokio::time::timeout(timeout, check)
+        .await
+        .unwrap_or_else(|_| {
+            CheckResult::fail(name, format!("timed out after {}ms", timeout.as_millis()))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channels::status_tracker::ChannelStatus;
+
+    #[test]
+    fn test_parse_check_list() {
+        assert_eq!(
+            ReadyCheck::parse_list("db, LLM,channels,db").unwrap(),
+            ReadyCheck::ALL.to_vec()
+        );
+        assert!(ReadyCheck::parse_list("none").unwrap().is_empty());
+        assert!(ReadyCheck::parse_list("database,disk").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_readiness_reports_failing_checks() {
+        let tracker = Arc::new(ChannelStatusTracker::new());
+        tracker.register_channel("repl").await;
+        tracker.register_channel("telegram").await;
+        tracker.set_status("repl", ChannelStatus::Connected).await;
+        tracker
+            .set_status("telegram", ChannelStatus::Error("bad token".into()))
+            .await;
+
+        let mut probes = HealthProbes {
+            config: HealthConfig {
+                ready_checks: vec![ReadyCheck::Llm, ReadyCheck::Channels],
+                ..Default::default()
+            },
+            channels: Some(Arc::clone(&tracker)),
+            llm_model: Some("gpt-4o".into()),
+        };
+
+        // Not started yet: both startup and readiness fail.
+        assert!(!probes.startup().is_ok());
+        let report = probes.readiness(None).await;
+        assert_eq!(report.status, "unavailable");
+        assert!(report.checks[0].ok);
+
+        tracker.mark_startup_complete();
+        assert!(probes.startup().is_ok());
+        let report = probes.readiness(None).await;
+        assert_eq!(
+            report.checks[1].detail.as_deref(),
+            Some("not running: telegram (error)")
+        );
+
+        tracker
+            .set_status("telegram", ChannelStatus::Connected)
+            .await;
+        assert!(probes.readiness(None).await.is_ok());
+
+        probes.config.ready_checks = vec![ReadyCheck::Database];
+        let report = probes.readiness(None).await;
+        assert_eq!(
+            report.checks[0].detail.as_deref(),
+            Some("no database configured")
+        );
+        assert!(probes.liveness().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_slow_check_times_out() {
+        let result = with_timeout("slow", Duration::from_millis(10), async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            CheckResult::pass("slow", None)
+        })
+        .await;
+        assert!(!result.ok);
+        assert_eq!(result.detail.as_deref(), Some("timed out after 10ms"));
+    }
+}
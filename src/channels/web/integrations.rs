@@ -0,0 +1,450 @@
+//! Workspace API keys for third-party integrations.
+//!
+//! Services like Zapier or n8n get a key that can only do what its scopes
+//! allow: push documents into named memory spaces (`space:<name>`) or
+//! trigger named routines (`routine:<name>`). Keys never grant chat access.
+//!
+//! Keys live in the settings table under `integrations.keys.<id>`. Only a
+//! SHA-256 of the token is stored; the token itself is shown once when the
+//! key is created. Each key has its own request budget per minute, and a
+//! revoked key is kept (for the audit trail) but refused.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::http::{HeaderMap, header};
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+use crate::channels::web::rate_limit::RateLimiter;
+use crate::db::Database;
+use crate::error::DatabaseError;
+
+/// Settings key prefix for stored keys.
+pub const SETTINGS_PREFIX: &str = "integrations.keys.";
+
+/// Prefix of every integration token, so they are recognizable in logs and
+/// secret scanners.
+pub const TOKEN_PREFIX: &str = "ick_";
+
+/// Requests per minute when none is given.
+pub const DEFAULT_RATE_PER_MINUTE: u32 = 60;
+
+/// How often `last_used_at` is written back, at most.
+const TOUCH_INTERVAL_SECS: i64 = 60;
+
+/// What a key may do.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum IntegrationScope {
+    /// Write documents into this memory space.
+    Space(String),
+    /// Trigger this routine.
+    Routine(String),
+}
+
+impl IntegrationScope {
+    /// Parse `space:<name>` or `routine:<name>`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let (kind, name) = s
+            .split_once(':')
+            .ok_or_else(|| format!("scope '{s}' must look like space:<name> or routine:<name>"))?;
+        let name = name.trim();
+        if name.is_empty() || name.chars().any(|c| c.is_control() || c == '/') {
+            return Err(format!("scope '{s}' has an invalid name"));
+        }
+        match kind.trim() {
+            "space" => Ok(Self::Space(name.to_string())),
+            "routine" => Ok(Self::Routine(name.to_string())),
+            other => Err(format!(
+                "unknown scope kind '{other}' (expected space or routine)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for IntegrationScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Space(name) => write!(f, "space:{name}"),
+            Self::Routine(name) => write!(f, "routine:{name}"),
+        }
+    }
+}
+
+impl Serialize for IntegrationScope {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for IntegrationScope {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::parse(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A stored integration key (never contains the token).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrationKey {
+    pub id: String,
+    pub name: String,
+    /// Hex SHA-256 of the full token.
+    pub token_sha256: String,
+    pub scopes: Vec<IntegrationScope>,
+    pub rate_per_minute: u32,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub revoked_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl IntegrationKey {
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
+
+    pub fn allows(&self, scope: &IntegrationScope) -> bool {
+        self.scopes.contains(scope)
+    }
+}
+
+/// Why a request was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrationAuthError {
+    /// No token, unknown token, or revoked key.
+    Unauthorized,
+    /// Valid key without the scope for this endpoint.
+    Forbidden(IntegrationScope),
+    /// Over the key's per-minute budget.
+    RateLimited(Duration),
+    Storage(String),
+}
+
+/// Split `ick_<id>_<secret>` into its id.
+fn token_id(token: &str) -> Option<&str> {
+    let rest = token.strip_prefix(TOKEN_PREFIX)?;
+    let (id, secret) = rest.split_once('_')?;
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric()) && !secret.is_empty())
+        .then_some(id)
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn random_alphanumeric(len: usize) -> String {
+    OsRng
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// Integration keys of one user, persisted in the settings table.
+#[derive(Clone)]
+pub struct IntegrationKeyStore {
+    db: Arc<dyn Database>,
+    user_id: String,
+}
+
+impl IntegrationKeyStore {
+    pub fn new(db: Arc<dyn Database>, user_id: impl Into<String>) -> Self {
+        Self {
+            db,
+            user_id: user_id.into(),
+        }
+    }
+
+    /// Create a key and return it with its token. The token is not stored
+    /// and cannot be recovered later.
+    pub async fn create(
+        &self,
+        name: &str,
+        scopes: Vec<IntegrationScope>,
+        rate_per_minute: u32,
+    ) -> Result<(IntegrationKey, String), DatabaseError> {
+        let id = random_alphanumeric(8);
+        let token = format!("{TOKEN_PREFIX}{id}_{}", random_alphanumeric(32));
+        let key = IntegrationKey {
+            id,
+            name: name.to_string(),
+            token_sha256: hash_token(&token),
+            scopes,
+            rate_per_minute: rate_per_minute.max(1),
+            created_at: Utc::now(),
+            revoked_at: None,
+            last_used_at: None,
+        };
+        self.save(&key).await?;
+        Ok((key, token))
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<IntegrationKey>, DatabaseError> {
+        let value = self
+            .db
+            .get_setting(&self.user_id, &format!("{SETTINGS_PREFIX}{id}"))
+            .await?;
+        Ok(value.and_then(|v| serde_json::from_value(v).ok()))
+    }
+
+    /// All keys, including revoked ones, oldest first.
+    pub async fn list(&self) -> Result<Vec<IntegrationKey>, DatabaseError> {
+        let settings = self.db.get_all_settings(&self.user_id).await?;
+        let mut keys: Vec<IntegrationKey> = settings
+            .into_iter()
+            .filter(|(k, _)| k.starts_with(SETTINGS_PREFIX))
+            .filter_map(|(_, v)| serde_json::from_value(v).ok())
+            .collect();
+        keys.sort_by_key(|k| k.created_at);
+        Ok(keys)
+    }
+
+    /// Revoke a key. Returns false if it does not exist.
+    pub async fn revoke(&self, id: &str) -> Result<bool, DatabaseError> {
+        let Some(mut key) = self.get(id).await? else {
+            return Ok(false);
+        };
+        if key.revoked_at.is_none() {
+            key.revoked_at = Some(Utc::now());
+            self.save(&key).await?;
+        }
+        Ok(true)
+    }
+
+    /// Delete a key entirely. Returns false if it does not exist.
+    pub async fn delete(&self, id: &str) -> Result<bool, DatabaseError> {
+        self.db
+            .delete_setting(&self.user_id, &format!("{SETTINGS_PREFIX}{id}"))
+            .await
+    }
+
+    /// Resolve a token to its live key.
+    pub async fn authenticate(&self, token: &str) -> Result<Option<IntegrationKey>, DatabaseError> {
+        let Some(id) = token_id(token) else {
+            return Ok(None);
+        };
+        let Some(mut key) = self.get(id).await? else {
+            return Ok(None);
+        };
+        let matches: bool = hash_token(token)
+            .as_bytes()
+            .ct_eq(key.token_sha256.as_bytes())
+            .into();
+        if !matches || key.is_revoked() {
+            return Ok(None);
+        }
+
+        let now = Utc::now();
+        let stale = key
+            .last_used_at
+            .is_none_or(|t| (now - t).num_seconds() >= TOUCH_INTERVAL_SECS);
+        if stale {
+            key.last_used_at = Some(now);
+            if let Err(e) = self.save(&key).await {
+                tracing::warn!(key = %key.id, "Failed to record integration key use: {}", e);
+            }
+        }
+        Ok(Some(key))
+    }
+
+    async fn save(&self, key: &IntegrationKey) -> Result<(), DatabaseError> {
+        let value =
+            serde_json::to_value(key).map_err(|e| DatabaseError::Serialization(e.to_string()))?;
+        self.db
+            .set_setting(
+                &self.user_id,
+                &format!("{SETTINGS_PREFIX}{}", key.id),
+                &value,
+            )
+            .await
+    }
+}
+
+/// One token bucket per key, sized from that key's `rate_per_minute`.
+#[derive(Debug, Clone, Default)]
+pub struct IntegrationLimiter {
+    limiters: Arc<Mutex<HashMap<String, RateLimiter>>>,
+}
+
+impl IntegrationLimiter {
+    /// Admit one request for `key`. `queued` is the agent's backlog, so
+    /// routine triggers back off when the agent is saturated.
+    pub fn check(&self, key: &IntegrationKey, queued: usize) -> Result<(), Duration> {
+        let limiter = {
+            let mut limiters = self.limiters.lock().unwrap_or_else(|e| e.into_inner());
+            let limiter = limiters
+                .entry(key.id.clone())
+                .or_insert_with(|| RateLimiter::new(u64::from(key.rate_per_minute), 60));
+            if limiter.limits().requests != u64::from(key.rate_per_minute) {
+                *limiter = RateLimiter::new(u64::from(key.rate_per_minute), 60);
+            }
+            limiter.clone()
+        };
+        limiter.check(&key.id, queued)
+    }
+}
+
+/// Bearer token of a request, if any. Integration keys are only accepted
+/// from the `Authorization` header, never from the query string.
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// Authenticate a request and check it against `scope` and the key's budget.
+pub async fn authorize(
+    keys: &IntegrationKeyStore,
+    limiter: &IntegrationLimiter,
+    headers: &HeaderMap,
+    scope: &IntegrationScope,
+    queued: usize,
+) -> Result<IntegrationKey, IntegrationAuthError> {
+    let token = bearer_token(headers).ok_or(IntegrationAuthError::Unauthorized)?;
+    let key = keys
+        .authenticate(token)
+        .await
+        .map_err(|e| IntegrationAuthError::Storage(e.to_string()))?
+        .ok_or(IntegrationAuthError::Unauthorized)?;
+    if !key.allows(scope) {
+        return Err(IntegrationAuthError::Forbidden(scope.clone()));
+    }
+    limiter
+        .check(&key, queued)
+        .map_err(IntegrationAuthError::RateLimited)?;
+    Ok(key)
+}
+
+/// Workspace path for a document pushed into `space`. `name` is an optional
+/// file name chosen by the caller; it may not leave the space's directory.
+pub fn push_path(space: &str, name: Option<&str>) -> Result<String, String> {
+    let dir = format!("integrations/{space}");
+    let Some(name) = name.map(str::trim).filter(|n| !n.is_empty()) else {
+        let stamp = Utc::now().format("%Y%m%dT%H%M%S");
+        return Ok(format!("{dir}/{stamp}-{}.md", random_alphanumeric(6)));
+    };
+    let clean = name
+        .split('/')
+        .all(|part| !part.is_empty() && part != "." && part != ".." && !part.contains('\\'));
+    if !clean || name.chars().any(char::is_control) {
+        return Err(format!("invalid document name '{name}'"));
+    }
+    Ok(format!("{dir}/{name}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_parse_and_display() {
+        assert_eq!(
+            IntegrationScope::parse("space:crm").unwrap(),
+            IntegrationScope::Space("crm".into())
+        );
+        assert_eq!(
+            IntegrationScope::parse("routine: daily digest").unwrap(),
+            IntegrationScope::Routine("daily digest".into())
+        );
+        assert_eq!(
+            IntegrationScope::Routine("x".into()).to_string(),
+            "routine:x"
+        );
+        assert!(IntegrationScope::parse("chat:all").is_err());
+        assert!(IntegrationScope::parse("space:").is_err());
+        assert!(IntegrationScope::parse("space:a/b").is_err());
+        assert!(IntegrationScope::parse("crm").is_err());
+    }
+
+    #[test]
+    fn test_token_id() {
+        assert_eq!(token_id("ick_ab12cd34_secret"), Some("ab12cd34"));
+        assert_eq!(token_id("ick_ab12cd34_"), None);
+        assert_eq!(token_id("ick_../x_secret"), None);
+        assert_eq!(token_id("gateway-token"), None);
+    }
+
+    #[test]
+    fn test_push_path_stays_in_space() {
+        assert_eq!(
+            push_path("crm", Some("leads/acme.md")).unwrap(),
+            "integrations/crm/leads/acme.md"
+        );
+        assert!(
+            push_path("crm", None)
+                .unwrap()
+                .starts_with("integrations/crm/")
+        );
+        assert!(push_path("crm", Some("../MEMORY.md")).is_err());
+        assert!(push_path("crm", Some("/etc/passwd")).is_err());
+        assert!(push_path("crm", Some("a//b")).is_err());
+    }
+
+    #[test]
+    fn test_limiter_uses_per_key_rate() {
+        let key = |id: &str, rate| IntegrationKey {
+            id: id.into(),
+            name: id.into(),
+            token_sha256: String::new(),
+            scopes: vec![],
+            rate_per_minute: rate,
+            created_at: Utc::now(),
+            revoked_at: None,
+            last_used_at: None,
+        };
+        let limiter = IntegrationLimiter::default();
+        let slow = key("slow", 2);
+        let fast = key("fast", 10);
+        assert!(limiter.check(&slow, 0).is_ok());
+        assert!(limiter.check(&slow, 0).is_ok());
+        assert!(limiter.check(&slow, 0).is_err());
+        for _ in 0..10 {
+            assert!(limiter.check(&fast, 0).is_ok());
+        }
+    }
+
+    #[cfg(feature = "libsql")]
+    #[tokio::test]
+    async fn test_store_authenticate_and_revoke() {
+        use crate::db::libsql_backend::LibSqlBackend;
+
+        let dir = tempfile::tempdir().unwrap();
+        let backend = LibSqlBackend::new_local(&dir.path().join("keys.db"))
+            .await
+            .unwrap();
+        backend.run_migrations().await.unwrap();
+        let store = IntegrationKeyStore::new(Arc::new(backend), "default");
+
+        let (key, token) = store
+            .create("zapier", vec![IntegrationScope::Space("crm".into())], 30)
+            .await
+            .unwrap();
+        assert!(token.starts_with(TOKEN_PREFIX));
+        assert_ne!(key.token_sha256, token);
+
+        let found = store.authenticate(&token).await.unwrap().unwrap();
+        assert_eq!(found.id, key.id);
+        assert!(found.last_used_at.is_some());
+
+        let forged = format!("{TOKEN_PREFIX}{}_wrong", key.id);
+        assert!(store.authenticate(&forged).await.unwrap().is_none());
+
+        assert!(store.revoke(&key.id).await.unwrap());
+        assert!(store.authenticate(&token).await.unwrap().is_none());
+        assert_eq!(store.list().await.unwrap().len(), 1);
+        assert!(store.delete(&key.id).await.unwrap());
+        assert!(store.list().await.unwrap().is_empty());
+    }
+}
@@ -94,6 +94,24 @@ impl LogBroadcaster {
             .map(|buf| buf.iter().cloned().collect())
             .unwrap_or_default()
     }
+
+    /// Rewrite the messages of recent entries, e.g. to scrub redacted
+    /// text. `rewrite` returns `None` to keep a message as is. Returns how
+    /// many entries changed; entries already sent to live subscribers are
+    /// out of reach.
+    pub fn rewrite_recent(&self, rewrite: impl Fn(&str) -> Option<String>) -> usize {
+        let Ok(mut buf) = self.recent.lock() else {
+            return 0;
+        };
+        let mut changed = 0;
+        for entry in buf.iter_mut() {
+            if let Some(message) = rewrite(&entry.message) {
+                entry.message = message;
+                changed += 1;
+            }
+        }
+        changed
+    }
 }
 
 impl Default for LogBroadcaster {
@@ -300,6 +318,28 @@ mod tests {
         assert_eq!(recent[0].message, "before anyone listened");
     }
 
+    #[test]
+    fn test_rewrite_recent_entries() {
+        let broadcaster = LogBroadcaster::new();
+        for message in ["user said hunter2-ok", "unrelated"] {
+            broadcaster.send(LogEntry {
+                level: "INFO".to_string(),
+                target: "test".to_string(),
+                message: message.to_string(),
+                timestamp: "2024-01-01T00:00:00.000Z".to_string(),
+            });
+        }
+
+        let changed = broadcaster.rewrite_recent(|m| {
+            m.contains("hunter2-ok")
+                .then(|| m.replace("hunter2-ok", "[redacted]"))
+        });
+        assert_eq!(changed, 1);
+        let recent = broadcaster.recent_entries();
+        assert_eq!(recent[0].message, "user said [redacted]");
+        assert_eq!(recent[1].message, "unrelated");
+    }
+
     #[test]
     fn test_message_visitor_finish_message_only() {
         let v = MessageVisitor {
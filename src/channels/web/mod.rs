@@ -12,18 +12,26 @@
 //!         ─── GET  /api/memory/* ────► Workspace
 //!         ─── GET  /api/jobs/* ──────► Database
 //!         ◄── GET  / ───────────────── Static HTML/CSS/JS
+//!         ◄── GET  /openapi.json ───── OpenAPI 3.1 spec
+//!
+//! Supervisor ◄── GET /healthz, /readyz, /startupz ── health probes
 //! ```
 
 pub mod agent_management;
 pub mod auth;
 pub mod canvas;
 pub mod config_editor;
+pub mod health;
+pub mod integrations;
 pub mod log_layer;
 pub mod mdns;
 pub mod network_mode;
 pub mod openai_compat;
+pub mod openapi;
 pub mod pid_lock;
 pub mod presence;
+pub mod rate_limit;
+pub mod sdk;
 pub mod server;
 pub mod sse;
 pub mod tailscale;
@@ -38,10 +46,13 @@ use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 
 use crate::agent::SessionManager;
-use crate::channels::{Channel, IncomingMessage, MessageStream, OutgoingResponse, StatusUpdate};
+use crate::channels::{
+    Channel, IncomingMessage, MessageStream, OutgoingResponse, OverflowStore, StatusUpdate,
+};
 use crate::config::GatewayConfig;
 use crate::db::Database;
 use crate::error::ChannelError;
+use crate::event_bus::{Backpressure, BusEvent, DEFAULT_SUBSCRIBER_CAPACITY, EventBus, Topic};
 use crate::extensions::ExtensionManager;
 use crate::orchestrator::job_manager::ContainerJobManager;
 use crate::tools::ToolRegistry;
@@ -59,6 +70,8 @@ pub struct GatewayChannel {
     state: Arc<GatewayState>,
     /// The actual auth token in use (generated or from config).
     auth_token: String,
+    /// Bus whose job events are streamed to browsers over SSE.
+    events: Option<Arc<EventBus>>,
 }
 
 impl GatewayChannel {
@@ -94,12 +107,23 @@ impl GatewayChannel {
             ws_tracker: Some(Arc::new(ws::WsConnectionTracker::new())),
             llm_provider: None,
             chat_rate_limiter: server::RateLimiter::new(30, 60),
+            model_tiers: None,
+            focus: None,
+            memory_quota: Default::default(),
+            overflow: Arc::new(OverflowStore::new()),
+            health: health::HealthProbes {
+                config: config.health.clone(),
+                ..Default::default()
+            },
+            integration_limiter: Default::default(),
+            budget: None,
         });
 
         Self {
             config,
             state,
             auth_token,
+            events: None,
         }
     }
 
@@ -120,7 +144,14 @@ impl GatewayChannel {
             shutdown_tx: tokio::sync::RwLock::new(None),
             ws_tracker: self.state.ws_tracker.clone(),
             llm_provider: self.state.llm_provider.clone(),
-            chat_rate_limiter: server::RateLimiter::new(30, 60),
+            chat_rate_limiter: self.state.chat_rate_limiter.clone(),
+            model_tiers: self.state.model_tiers.clone(),
+            focus: self.state.focus.clone(),
+            memory_quota: self.state.memory_quota.clone(),
+            overflow: Arc::clone(&self.state.overflow),
+            health: self.state.health.clone(),
+            integration_limiter: self.state.integration_limiter.clone(),
+            budget: self.state.budget.clone(),
         };
         mutate(&mut new_state);
         self.state = Arc::new(new_state);
@@ -190,6 +221,59 @@ impl GatewayChannel {
         self
     }
 
+    /// Inject the model tier router so the status endpoint reports savings.
+    pub fn with_model_tiers(mut self, tiers: Arc<crate::agent::ModelTierRouter>) -> Self {
+        self.rebuild_state(|s| s.model_tiers = Some(tiers));
+        self
+    }
+
+    /// Inject the budget manager so `/api/budget` reports current spend.
+    pub fn with_budget(mut self, budget: Arc<crate::agent::BudgetManager>) -> Self {
+        self.rebuild_state(|s| s.budget = Some(budget));
+        self
+    }
+
+    /// Inject the focus mode switch so the status endpoint reports it.
+    pub fn with_focus(mut self, focus: Arc<crate::agent::FocusMode>) -> Self {
+        self.rebuild_state(|s| s.focus = Some(focus));
+        self
+    }
+
+    /// Set the quotas `/api/memory/usage` reports against.
+    pub fn with_memory_quota(mut self, quota: crate::workspace::QuotaConfig) -> Self {
+        self.rebuild_state(|s| s.memory_quota = quota);
+        self
+    }
+
+    /// Report channel startup from `tracker` on `/readyz` and `/startupz`.
+    pub fn with_channel_status(
+        mut self,
+        tracker: Arc<crate::channels::ChannelStatusTracker>,
+    ) -> Self {
+        self.rebuild_state(|s| s.health.channels = Some(tracker));
+        self
+    }
+
+    /// Record the configured model for the `/readyz` LLM check.
+    pub fn with_llm_model(mut self, model: impl Into<String>) -> Self {
+        let model = model.into();
+        self.rebuild_state(|s| s.health.llm_model = Some(model));
+        self
+    }
+
+    /// Stream job events from `bus` to SSE clients once the gateway starts.
+    pub fn with_event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.events = Some(bus);
+        self
+    }
+
+    /// Feed the agent's turn counters to the chat rate limiter so the
+    /// gateway sheds load while the agent is backed up.
+    pub fn with_agent_load(self, load: Arc<crate::agent::AgentLoad>) -> Self {
+        self.state.chat_rate_limiter.set_agent_load(load);
+        self
+    }
+
     /// Get the auth token (for printing to console on startup).
     pub fn auth_token(&self) -> &str {
         &self.auth_token
@@ -210,6 +294,7 @@ impl Channel for GatewayChannel {
     async fn start(&self) -> Result<MessageStream, ChannelError> {
         let (tx, rx) = mpsc::channel(256);
         *self.state.msg_tx.write().await = Some(tx);
+        self.state.reload_rate_limits().await;
 
         let addr: SocketAddr = format!("{}:{}", self.config.host, self.config.port)
             .parse()
@@ -223,6 +308,23 @@ impl Channel for GatewayChannel {
 
         server::start_server(addr, self.state.clone(), self.auth_token.clone()).await?;
 
+        if let Some(ref bus) = self.events {
+            // SSE clients reconnect and refetch, so falling behind is fine.
+            let mut sub = bus.subscribe(
+                &[Topic::Job],
+                DEFAULT_SUBSCRIBER_CAPACITY,
+                Backpressure::Lossy,
+            );
+            let state = Arc::clone(&self.state);
+            tokio::spawn(async move {
+                while let Some(event) = sub.recv().await {
+                    if let BusEvent::Job { ref event, .. } = *event {
+                        state.sse.broadcast(event.clone());
+                    }
+                }
+            });
+        }
+
         Ok(Box::pin(ReceiverStream::new(rx)))
     }
 
@@ -298,6 +400,16 @@ impl Channel for GatewayChannel {
                 parameters: serde_json::to_string_pretty(&parameters)
                     .unwrap_or_else(|_| parameters.to_string()),
             },
+            StatusUpdate::InputRequested {
+                request_id,
+                prompt,
+                form,
+            } => SseEvent::InputRequested {
+                request_id,
+                prompt,
+                form,
+                thread_id: thread_id.clone(),
+            },
             StatusUpdate::AuthRequired {
                 extension_name,
                 instructions,
@@ -318,6 +430,22 @@ impl Channel for GatewayChannel {
                 success,
                 message,
             },
+            StatusUpdate::Image {
+                media_id,
+                mime_type,
+                title,
+                data,
+            } => {
+                use base64::Engine;
+                let encoded = base64::engine::general_purpose::STANDARD.encode(&data);
+                SseEvent::Image {
+                    data_url: format!("data:{};base64,{}", mime_type, encoded),
+                    media_id,
+                    mime_type,
+                    title,
+                    thread_id: thread_id.clone(),
+                }
+            }
         };
 
         self.state.sse.broadcast(event);
@@ -365,6 +493,7 @@ mod tests {
             port: 8080,
             auth_token: Some("test-token-123".to_string()),
             user_id: "test-user".to_string(),
+            health: Default::default(),
         }
     }
 
@@ -374,6 +503,7 @@ mod tests {
             port: 3000,
             auth_token: None,
             user_id: "user1".to_string(),
+            health: Default::default(),
         }
     }
 
@@ -9,19 +9,22 @@ use std::sync::Arc;
 use axum::{
     Json,
     extract::State,
-    http::{HeaderValue, StatusCode},
+    http::{HeaderMap, HeaderValue, StatusCode, Uri, header},
     response::{
         IntoResponse, Response,
         sse::{Event, KeepAlive, Sse},
     },
 };
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 
+use crate::llm::streaming::response_stream;
 use crate::llm::{
-    ChatMessage, CompletionRequest, FinishReason, Role, ToolCall, ToolCompletionRequest,
-    ToolDefinition,
+    ChatMessage, CompletionRequest, FinishReason, Role, StreamDelta, ToolCall,
+    ToolCompletionRequest, ToolCompletionResponse, ToolDefinition,
 };
 
+use super::rate_limit::client_key;
 use super::server::GatewayState;
 
 // ---------------------------------------------------------------------------
@@ -401,16 +404,37 @@ fn parse_stop(val: &serde_json::Value) -> Option<Vec<String>> {
 
 pub async fn chat_completions_handler(
     State(state): State<Arc<GatewayState>>,
+    headers: HeaderMap,
+    uri: Uri,
     Json(req): Json<OpenAiChatRequest>,
-) -> Result<impl IntoResponse, (StatusCode, Json<OpenAiErrorResponse>)> {
-    if !state.chat_rate_limiter.check() {
-        return Err(openai_error(
+) -> Response {
+    // The proxy bypasses the agent queue, so only the per-token bucket applies.
+    if let Err(retry_after) = state
+        .chat_rate_limiter
+        .check(&client_key(&headers, &uri), 0)
+    {
+        let mut response = openai_error(
             StatusCode::TOO_MANY_REQUESTS,
             "Rate limit exceeded. Please try again later.",
             "rate_limit_error",
-        ));
+        )
+        .into_response();
+        if let Ok(value) = HeaderValue::from_str(&retry_after.as_secs().to_string()) {
+            response.headers_mut().insert(header::RETRY_AFTER, value);
+        }
+        return response;
+    }
+
+    match chat_completions(&state, req).await {
+        Ok(response) => response,
+        Err(error) => error.into_response(),
     }
+}
 
+async fn chat_completions(
+    state: &GatewayState,
+    req: OpenAiChatRequest,
+) -> Result<Response, (StatusCode, Json<OpenAiErrorResponse>)> {
     let llm = state.llm_provider.as_ref().ok_or_else(|| {
         openai_error(
             StatusCode::SERVICE_UNAVAILABLE,
@@ -557,11 +581,13 @@ pub async fn chat_completions_handler(
 
 /// Handle streaming responses.
 ///
-/// The current `LlmProvider` returns complete responses (no streaming method).
-/// We execute the LLM call first, then simulate chunked delivery by splitting
-/// the response into word-boundary chunks. This ensures LLM failures return
-/// proper HTTP errors instead of SSE error events. True token streaming can be
-/// added later by extending `LlmProvider` with a `complete_stream()` method.
+/// Deltas come from `LlmProvider::complete_stream`, so a provider that
+/// streams natively has its tokens forwarded as they arrive. Providers that
+/// don't, and requests with `stop` sequences (only supported on plain
+/// completions), get the finished response split into word-boundary chunks;
+/// the `x-ironclaw-streaming` header says which happened. The stream is
+/// opened before the SSE response starts, so a failing call still returns a
+/// proper HTTP error. A failure after that arrives as an SSE `error` event.
 async fn handle_streaming(
     llm: Arc<dyn crate::llm::LlmProvider>,
     req: OpenAiChatRequest,
@@ -574,15 +600,34 @@ async fn handle_streaming(
     let id = chat_completion_id();
     let created = unix_timestamp();
 
-    // Execute the LLM call before starting the SSE stream.
-    // Since streaming is simulated (LlmProvider returns complete responses),
-    // this lets us return proper HTTP errors on failure.
-    enum LlmResult {
-        Simple(crate::llm::CompletionResponse),
-        WithTools(crate::llm::ToolCompletionResponse),
-    }
+    let stop = if has_tools {
+        None
+    } else {
+        req.stop.as_ref().and_then(parse_stop)
+    };
+    let native = llm.supports_streaming() && stop.is_none();
 
-    let llm_result = if has_tools {
+    let mut deltas = if stop.is_some() {
+        let mut comp_req = CompletionRequest::new(messages);
+        if let Some(t) = req.temperature {
+            comp_req = comp_req.with_temperature(t);
+        }
+        if let Some(mt) = req.max_tokens {
+            comp_req = comp_req.with_max_tokens(mt);
+        }
+        comp_req.stop_sequences = stop;
+        let resp = llm.complete(comp_req).await.map_err(map_llm_error)?;
+        response_stream(ToolCompletionResponse {
+            content: Some(resp.content),
+            tool_calls: Vec::new(),
+            input_tokens: resp.input_tokens,
+            output_tokens: resp.output_tokens,
+            cache_read_tokens: resp.cache_read_tokens,
+            cache_write_tokens: resp.cache_write_tokens,
+            finish_reason: resp.finish_reason,
+            response_id: resp.response_id,
+        })
+    } else {
         let tools = convert_tools(req.tools.as_deref().unwrap_or(&[]));
         let mut tool_req = ToolCompletionRequest::new(messages, tools);
         if let Some(t) = req.temperature {
@@ -591,31 +636,15 @@ async fn handle_streaming(
         if let Some(mt) = req.max_tokens {
             tool_req = tool_req.with_max_tokens(mt);
         }
-        if let Some(ref tc) = req.tool_choice
+        if has_tools
+            && let Some(ref tc) = req.tool_choice
             && let Some(choice) = normalize_tool_choice(tc)
         {
             tool_req = tool_req.with_tool_choice(choice);
         }
-        LlmResult::WithTools(
-            llm.complete_with_tools(tool_req)
-                .await
-                .map_err(map_llm_error)?,
-        )
-    } else {
-        let mut comp_req = CompletionRequest::new(messages);
-        if let Some(t) = req.temperature {
-            comp_req = comp_req.with_temperature(t);
-        }
-        if let Some(mt) = req.max_tokens {
-            comp_req = comp_req.with_max_tokens(mt);
-        }
-        if let Some(ref stop_val) = req.stop {
-            comp_req.stop_sequences = parse_stop(stop_val);
-        }
-        LlmResult::Simple(llm.complete(comp_req).await.map_err(map_llm_error)?)
+        llm.complete_stream(tool_req).await.map_err(map_llm_error)?
     };
 
-    // LLM succeeded — emit the response as SSE chunks
     let (tx, rx) = tokio::sync::mpsc::channel::<Result<Event, std::convert::Infallible>>(64);
 
     tokio::spawn(async move {
@@ -638,32 +667,19 @@ async fn handle_streaming(
         let data = serde_json::to_string(&role_chunk).unwrap_or_default();
         let _ = tx.send(Ok(Event::default().data(data))).await;
 
-        match llm_result {
-            LlmResult::WithTools(resp) => {
-                // Stream content chunks
-                if let Some(ref content) = resp.content {
-                    stream_content_chunks(&tx, &id, created, &model_name, content).await;
+        let mut tool_index = 0;
+        let mut finish_reason = FinishReason::Unknown;
+        while let Some(delta) = deltas.next().await {
+            match delta {
+                Ok(StreamDelta::Text(text)) if native => {
+                    if !send_content_chunk(&tx, &id, created, &model_name, text).await {
+                        return;
+                    }
                 }
-
-                // Stream tool calls
-                if !resp.tool_calls.is_empty() {
-                    let deltas: Vec<OpenAiToolCallDelta> = resp
-                        .tool_calls
-                        .iter()
-                        .enumerate()
-                        .map(|(i, tc)| OpenAiToolCallDelta {
-                            index: i as u32,
-                            id: Some(tc.id.clone()),
-                            call_type: Some("function".to_string()),
-                            function: Some(OpenAiToolCallFunctionDelta {
-                                name: Some(tc.name.clone()),
-                                arguments: Some(
-                                    serde_json::to_string(&tc.arguments).unwrap_or_default(),
-                                ),
-                            }),
-                        })
-                        .collect();
-
+                Ok(StreamDelta::Text(text)) => {
+                    stream_content_chunks(&tx, &id, created, &model_name, &text).await;
+                }
+                Ok(StreamDelta::ToolCall(tc)) => {
                     let chunk = OpenAiChatChunk {
                         id: id.clone(),
                         object: "chat.completion.chunk",
@@ -674,23 +690,50 @@ async fn handle_streaming(
                             delta: OpenAiDelta {
                                 role: None,
                                 content: None,
-                                tool_calls: Some(deltas),
+                                tool_calls: Some(vec![OpenAiToolCallDelta {
+                                    index: tool_index,
+                                    id: Some(tc.id),
+                                    call_type: Some("function".to_string()),
+                                    function: Some(OpenAiToolCallFunctionDelta {
+                                        name: Some(tc.name),
+                                        arguments: Some(
+                                            serde_json::to_string(&tc.arguments)
+                                                .unwrap_or_default(),
+                                        ),
+                                    }),
+                                }]),
                             },
                             finish_reason: None,
                         }],
                     };
+                    tool_index += 1;
                     let data = serde_json::to_string(&chunk).unwrap_or_default();
+                    if tx.send(Ok(Event::default().data(data))).await.is_err() {
+                        return;
+                    }
+                }
+                Ok(StreamDelta::Done {
+                    finish_reason: reason,
+                    ..
+                }) => {
+                    finish_reason = reason;
+                    break;
+                }
+                Err(e) => {
+                    tracing::warn!("Streamed completion failed: {}", e);
+                    let (_, Json(body)) = map_llm_error(e);
+                    let data = serde_json::to_string(&body).unwrap_or_default();
                     let _ = tx.send(Ok(Event::default().data(data))).await;
+                    return;
                 }
-
-                // Final chunk with finish_reason
-                send_finish_chunk(&tx, &id, created, &model_name, resp.finish_reason).await;
-            }
-            LlmResult::Simple(resp) => {
-                stream_content_chunks(&tx, &id, created, &model_name, &resp.content).await;
-                send_finish_chunk(&tx, &id, created, &model_name, resp.finish_reason).await;
             }
         }
+        if tool_index > 0 && finish_reason == FinishReason::Unknown {
+            finish_reason = FinishReason::ToolUse;
+        }
+
+        // Final chunk with finish_reason
+        send_finish_chunk(&tx, &id, created, &model_name, finish_reason).await;
 
         // Send [DONE] sentinel
         let _ = tx.send(Ok(Event::default().data("[DONE]"))).await;
@@ -701,11 +744,38 @@ async fn handle_streaming(
     let mut response = sse.into_response();
     response.headers_mut().insert(
         "x-ironclaw-streaming",
-        HeaderValue::from_static("simulated"),
+        HeaderValue::from_static(if native { "native" } else { "simulated" }),
     );
     Ok(response)
 }
 
+/// Send one content delta. Returns false once the client has gone away.
+async fn send_content_chunk(
+    tx: &tokio::sync::mpsc::Sender<Result<Event, std::convert::Infallible>>,
+    id: &str,
+    created: u64,
+    model: &str,
+    content: String,
+) -> bool {
+    let chunk = OpenAiChatChunk {
+        id: id.to_string(),
+        object: "chat.completion.chunk",
+        created,
+        model: model.to_string(),
+        choices: vec![OpenAiChunkChoice {
+            index: 0,
+            delta: OpenAiDelta {
+                role: None,
+                content: Some(content),
+                tool_calls: None,
+            },
+            finish_reason: None,
+        }],
+    };
+    let data = serde_json::to_string(&chunk).unwrap_or_default();
+    tx.send(Ok(Event::default().data(data))).await.is_ok()
+}
+
 /// Split content into word-boundary chunks and send as SSE events.
 async fn stream_content_chunks(
     tx: &tokio::sync::mpsc::Sender<Result<Event, std::convert::Infallible>>,
@@ -718,48 +788,15 @@ async fn stream_content_chunks(
     let mut buf = String::new();
     for word in content.split_inclusive(char::is_whitespace) {
         buf.push_str(word);
-        if buf.len() >= 20 {
-            let chunk = OpenAiChatChunk {
-                id: id.to_string(),
-                object: "chat.completion.chunk",
-                created,
-                model: model.to_string(),
-                choices: vec![OpenAiChunkChoice {
-                    index: 0,
-                    delta: OpenAiDelta {
-                        role: None,
-                        content: Some(buf.clone()),
-                        tool_calls: None,
-                    },
-                    finish_reason: None,
-                }],
-            };
-            let data = serde_json::to_string(&chunk).unwrap_or_default();
-            if tx.send(Ok(Event::default().data(data))).await.is_err() {
-                return;
-            }
-            buf.clear();
+        if buf.len() >= 20
+            && !send_content_chunk(tx, id, created, model, std::mem::take(&mut buf)).await
+        {
+            return;
         }
     }
     // Flush remaining
     if !buf.is_empty() {
-        let chunk = OpenAiChatChunk {
-            id: id.to_string(),
-            object: "chat.completion.chunk",
-            created,
-            model: model.to_string(),
-            choices: vec![OpenAiChunkChoice {
-                index: 0,
-                delta: OpenAiDelta {
-                    role: None,
-                    content: Some(buf),
-                    tool_calls: None,
-                },
-                finish_reason: None,
-            }],
-        };
-        let data = serde_json::to_string(&chunk).unwrap_or_default();
-        let _ = tx.send(Ok(Event::default().data(data))).await;
+        send_content_chunk(tx, id, created, model, buf).await;
     }
 }
 
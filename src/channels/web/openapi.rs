@@ -0,0 +1,1439 @@
+//! OpenAPI 3.1 description of the web gateway API.
+//!
+//! The spec is assembled from a static operation table that mirrors the
+//! routes registered in `server.rs`, plus component schemas for the DTOs in
+//! `types.rs`. It is served at `GET /openapi.json` and consumed by
+//! `ironclaw gateway sdk` to emit typed clients.
+//!
+//! When adding a gateway route, add a matching [`ApiOperation`] here so the
+//! spec and generated SDKs stay in sync. A test parses the router in
+//! `server.rs` and fails for any route that has no operation and is not one
+//! of the UI pages listed in [`UNDOCUMENTED_ROUTES`].
+
+use serde_json::{Map, Value, json};
+
+/// HTTP method of an operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+    Delete,
+}
+
+impl HttpMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Get => "get",
+            Self::Post => "post",
+            Self::Put => "put",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+/// Where a parameter is carried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamLocation {
+    Path,
+    Query,
+}
+
+/// A path or query parameter.
+#[derive(Debug, Clone, Copy)]
+pub struct ApiParam {
+    pub name: &'static str,
+    pub location: ParamLocation,
+    /// Scalar schema type (`string`, `integer`, `boolean`).
+    pub ty: &'static str,
+    pub required: bool,
+}
+
+const fn path(name: &'static str) -> ApiParam {
+    ApiParam {
+        name,
+        location: ParamLocation::Path,
+        ty: "string",
+        required: true,
+    }
+}
+
+const fn query(name: &'static str, ty: &'static str) -> ApiParam {
+    ApiParam {
+        name,
+        location: ParamLocation::Query,
+        ty,
+        required: false,
+    }
+}
+
+/// One gateway endpoint.
+#[derive(Debug, Clone, Copy)]
+pub struct ApiOperation {
+    /// Stable identifier, also used as the SDK method name.
+    pub operation_id: &'static str,
+    pub method: HttpMethod,
+    pub path: &'static str,
+    pub tag: &'static str,
+    pub summary: &'static str,
+    pub params: &'static [ApiParam],
+    /// Component schema name of the JSON request body.
+    pub request: Option<&'static str>,
+    /// Component schema name of the JSON response body (`None` = untyped/no body).
+    pub response: Option<&'static str>,
+    /// Whether the bearer token is required.
+    pub auth: bool,
+}
+
+/// Every documented gateway operation.
+pub const OPERATIONS: &[ApiOperation] = &[
+    // Health
+    ApiOperation {
+        operation_id: "health",
+        method: HttpMethod::Get,
+        path: "/api/health",
+        tag: "admin",
+        summary: "Liveness check",
+        params: &[],
+        request: None,
+        response: Some("HealthResponse"),
+        auth: false,
+    },
+    ApiOperation {
+        operation_id: "healthz",
+        method: HttpMethod::Get,
+        path: "/healthz",
+        tag: "admin",
+        summary: "Liveness probe",
+        params: &[],
+        request: None,
+        response: Some("ProbeReport"),
+        auth: false,
+    },
+    ApiOperation {
+        operation_id: "readyz",
+        method: HttpMethod::Get,
+        path: "/readyz",
+        tag: "admin",
+        summary: "Readiness probe (database, LLM, channels); 503 when a check fails",
+        params: &[],
+        request: None,
+        response: Some("ProbeReport"),
+        auth: false,
+    },
+    ApiOperation {
+        operation_id: "startupz",
+        method: HttpMethod::Get,
+        path: "/startupz",
+        tag: "admin",
+        summary: "Startup probe; 503 until every channel has been started",
+        params: &[],
+        request: None,
+        response: Some("ProbeReport"),
+        auth: false,
+    },
+    ApiOperation {
+        operation_id: "getOpenApi",
+        method: HttpMethod::Get,
+        path: "/openapi.json",
+        tag: "admin",
+        summary: "This OpenAPI document",
+        params: &[],
+        request: None,
+        response: None,
+        auth: false,
+    },
+    ApiOperation {
+        operation_id: "getOverflow",
+        method: HttpMethod::Get,
+        path: "/overflow/{id}",
+        tag: "chat",
+        summary: "Full text of a truncated message (text/plain); the unguessable id is the credential",
+        params: &[path("id")],
+        request: None,
+        response: None,
+        auth: false,
+    },
+    // Chat
+    ApiOperation {
+        operation_id: "sendMessage",
+        method: HttpMethod::Post,
+        path: "/api/chat/send",
+        tag: "chat",
+        summary: "Send a message to the agent",
+        params: &[],
+        request: Some("SendMessageRequest"),
+        response: Some("SendMessageResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "chatHistory",
+        method: HttpMethod::Get,
+        path: "/api/chat/history",
+        tag: "sessions",
+        summary: "Fetch turn history for a thread",
+        params: &[
+            query("thread_id", "string"),
+            query("limit", "integer"),
+            query("before", "string"),
+        ],
+        request: None,
+        response: Some("HistoryResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "listThreads",
+        method: HttpMethod::Get,
+        path: "/api/chat/threads",
+        tag: "sessions",
+        summary: "List conversation threads",
+        params: &[],
+        request: None,
+        response: Some("ThreadListResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "newThread",
+        method: HttpMethod::Post,
+        path: "/api/chat/thread/new",
+        tag: "sessions",
+        summary: "Create a new conversation thread",
+        params: &[],
+        request: None,
+        response: Some("ThreadInfo"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "chatEvents",
+        method: HttpMethod::Get,
+        path: "/api/chat/events",
+        tag: "chat",
+        summary: "Server-sent event stream of agent activity",
+        params: &[],
+        request: None,
+        response: None,
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "chatSocket",
+        method: HttpMethod::Get,
+        path: "/api/chat/ws",
+        tag: "chat",
+        summary: "WebSocket for sending messages and receiving events",
+        params: &[],
+        request: None,
+        response: None,
+        auth: true,
+    },
+    // Approvals
+    ApiOperation {
+        operation_id: "submitApproval",
+        method: HttpMethod::Post,
+        path: "/api/chat/approval",
+        tag: "approvals",
+        summary: "Approve or deny a pending tool call",
+        params: &[],
+        request: Some("ApprovalRequest"),
+        response: Some("SendMessageResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "submitAuthToken",
+        method: HttpMethod::Post,
+        path: "/api/chat/auth-token",
+        tag: "approvals",
+        summary: "Submit a manual auth token for an extension",
+        params: &[],
+        request: Some("AuthTokenRequest"),
+        response: Some("ActionResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "cancelAuth",
+        method: HttpMethod::Post,
+        path: "/api/chat/auth-cancel",
+        tag: "approvals",
+        summary: "Cancel an in-progress auth flow",
+        params: &[],
+        request: Some("AuthCancelRequest"),
+        response: Some("ActionResponse"),
+        auth: true,
+    },
+    // Memory
+    ApiOperation {
+        operation_id: "memoryTree",
+        method: HttpMethod::Get,
+        path: "/api/memory/tree",
+        tag: "memory",
+        summary: "Every path in the workspace",
+        params: &[query("depth", "integer")],
+        request: None,
+        response: Some("MemoryTreeResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "memoryList",
+        method: HttpMethod::Get,
+        path: "/api/memory/list",
+        tag: "memory",
+        summary: "Entries directly under a directory",
+        params: &[query("path", "string")],
+        request: None,
+        response: Some("MemoryListResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "memoryRead",
+        method: HttpMethod::Get,
+        path: "/api/memory/read",
+        tag: "memory",
+        summary: "Read a workspace document",
+        params: &[ApiParam {
+            required: true,
+            ..query("path", "string")
+        }],
+        request: None,
+        response: Some("MemoryReadResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "memoryWrite",
+        method: HttpMethod::Post,
+        path: "/api/memory/write",
+        tag: "memory",
+        summary: "Write a workspace document",
+        params: &[],
+        request: Some("MemoryWriteRequest"),
+        response: Some("MemoryWriteResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "memorySearch",
+        method: HttpMethod::Post,
+        path: "/api/memory/search",
+        tag: "memory",
+        summary: "Hybrid search over the workspace",
+        params: &[],
+        request: Some("MemorySearchRequest"),
+        response: Some("MemorySearchResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "memoryUsage",
+        method: HttpMethod::Get,
+        path: "/api/memory/usage",
+        tag: "memory",
+        summary: "Storage used by the workspace and each space against its quotas",
+        params: &[],
+        request: None,
+        response: None,
+        auth: true,
+    },
+    // Jobs
+    ApiOperation {
+        operation_id: "listJobs",
+        method: HttpMethod::Get,
+        path: "/api/jobs",
+        tag: "jobs",
+        summary: "List jobs",
+        params: &[],
+        request: None,
+        response: Some("JobListResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "jobsSummary",
+        method: HttpMethod::Get,
+        path: "/api/jobs/summary",
+        tag: "jobs",
+        summary: "Job counts by state",
+        params: &[],
+        request: None,
+        response: Some("JobSummaryResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "getJob",
+        method: HttpMethod::Get,
+        path: "/api/jobs/{id}",
+        tag: "jobs",
+        summary: "Job details and state transitions",
+        params: &[path("id")],
+        request: None,
+        response: Some("JobDetailResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "cancelJob",
+        method: HttpMethod::Post,
+        path: "/api/jobs/{id}/cancel",
+        tag: "jobs",
+        summary: "Cancel a job",
+        params: &[path("id")],
+        request: None,
+        response: None,
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "restartJob",
+        method: HttpMethod::Post,
+        path: "/api/jobs/{id}/restart",
+        tag: "jobs",
+        summary: "Restart a finished or failed job",
+        params: &[path("id")],
+        request: None,
+        response: None,
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "promptJob",
+        method: HttpMethod::Post,
+        path: "/api/jobs/{id}/prompt",
+        tag: "jobs",
+        summary: "Queue a follow-up prompt for a running sandbox job",
+        params: &[path("id")],
+        request: Some("JobPromptRequest"),
+        response: None,
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "jobEvents",
+        method: HttpMethod::Get,
+        path: "/api/jobs/{id}/events",
+        tag: "jobs",
+        summary: "Events recorded for a sandbox job",
+        params: &[path("id")],
+        request: None,
+        response: None,
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "listJobFiles",
+        method: HttpMethod::Get,
+        path: "/api/jobs/{id}/files/list",
+        tag: "jobs",
+        summary: "List files in a job's project directory",
+        params: &[path("id"), query("path", "string")],
+        request: None,
+        response: Some("ProjectFilesResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "readJobFile",
+        method: HttpMethod::Get,
+        path: "/api/jobs/{id}/files/read",
+        tag: "jobs",
+        summary: "Read a file from a job's project directory",
+        params: &[path("id"), query("path", "string")],
+        request: None,
+        response: Some("ProjectFileReadResponse"),
+        auth: true,
+    },
+    // Logs
+    ApiOperation {
+        operation_id: "logEvents",
+        method: HttpMethod::Get,
+        path: "/api/logs/events",
+        tag: "admin",
+        summary: "Server-sent event stream of log records",
+        params: &[],
+        request: None,
+        response: None,
+        auth: true,
+    },
+    // Extensions
+    ApiOperation {
+        operation_id: "listExtensions",
+        method: HttpMethod::Get,
+        path: "/api/extensions",
+        tag: "extensions",
+        summary: "List installed extensions",
+        params: &[],
+        request: None,
+        response: Some("ExtensionListResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "listTools",
+        method: HttpMethod::Get,
+        path: "/api/extensions/tools",
+        tag: "extensions",
+        summary: "List registered tools",
+        params: &[],
+        request: None,
+        response: Some("ToolListResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "getToolDocs",
+        method: HttpMethod::Get,
+        path: "/api/extensions/tools/docs",
+        tag: "extensions",
+        summary: "Documentation for registered tools (JSON, or Markdown with format=markdown)",
+        params: &[query("name", "string"), query("format", "string")],
+        request: None,
+        response: Some("ToolDocsResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "installExtension",
+        method: HttpMethod::Post,
+        path: "/api/extensions/install",
+        tag: "extensions",
+        summary: "Install an extension",
+        params: &[],
+        request: Some("InstallExtensionRequest"),
+        response: Some("ActionResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "activateExtension",
+        method: HttpMethod::Post,
+        path: "/api/extensions/{name}/activate",
+        tag: "extensions",
+        summary: "Activate an installed extension",
+        params: &[path("name")],
+        request: None,
+        response: Some("ActionResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "decideExtensionConsent",
+        method: HttpMethod::Post,
+        path: "/api/extensions/{name}/consent",
+        tag: "extensions",
+        summary: "Grant or deny the capabilities a WASM tool requests",
+        params: &[path("name")],
+        request: Some("ConsentDecisionRequest"),
+        response: Some("ActionResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "dryRunExtension",
+        method: HttpMethod::Post,
+        path: "/api/extensions/{name}/dry-run",
+        tag: "extensions",
+        summary: "Run an installed WASM tool with all access stubbed and report what it touched",
+        params: &[path("name")],
+        request: Some("DryRunRequest"),
+        response: Some("DryRunReport"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "removeExtension",
+        method: HttpMethod::Post,
+        path: "/api/extensions/{name}/remove",
+        tag: "extensions",
+        summary: "Remove an installed extension",
+        params: &[path("name")],
+        request: None,
+        response: Some("ActionResponse"),
+        auth: true,
+    },
+    // Routines
+    ApiOperation {
+        operation_id: "listRoutines",
+        method: HttpMethod::Get,
+        path: "/api/routines",
+        tag: "routines",
+        summary: "List routines",
+        params: &[],
+        request: None,
+        response: Some("RoutineListResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "routinesSummary",
+        method: HttpMethod::Get,
+        path: "/api/routines/summary",
+        tag: "routines",
+        summary: "Routine counts and today's runs",
+        params: &[],
+        request: None,
+        response: Some("RoutineSummaryResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "getRoutine",
+        method: HttpMethod::Get,
+        path: "/api/routines/{id}",
+        tag: "routines",
+        summary: "Routine details with recent runs",
+        params: &[path("id")],
+        request: None,
+        response: Some("RoutineDetailResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "triggerRoutine",
+        method: HttpMethod::Post,
+        path: "/api/routines/{id}/trigger",
+        tag: "routines",
+        summary: "Run a routine now",
+        params: &[path("id")],
+        request: None,
+        response: None,
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "toggleRoutine",
+        method: HttpMethod::Post,
+        path: "/api/routines/{id}/toggle",
+        tag: "routines",
+        summary: "Enable or disable a routine (flips it when no body is sent)",
+        params: &[path("id")],
+        request: Some("ToggleRoutineRequest"),
+        response: None,
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "deleteRoutine",
+        method: HttpMethod::Delete,
+        path: "/api/routines/{id}",
+        tag: "routines",
+        summary: "Delete a routine",
+        params: &[path("id")],
+        request: None,
+        response: None,
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "listRoutineRuns",
+        method: HttpMethod::Get,
+        path: "/api/routines/{id}/runs",
+        tag: "routines",
+        summary: "Run history for a routine",
+        params: &[path("id")],
+        request: None,
+        response: None,
+        auth: true,
+    },
+    // Admin
+    ApiOperation {
+        operation_id: "listSettings",
+        method: HttpMethod::Get,
+        path: "/api/settings",
+        tag: "admin",
+        summary: "List all settings",
+        params: &[],
+        request: None,
+        response: Some("SettingsListResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "exportSettings",
+        method: HttpMethod::Get,
+        path: "/api/settings/export",
+        tag: "admin",
+        summary: "Export settings as a key/value map",
+        params: &[],
+        request: None,
+        response: Some("SettingsMap"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "importSettings",
+        method: HttpMethod::Post,
+        path: "/api/settings/import",
+        tag: "admin",
+        summary: "Import settings from a key/value map",
+        params: &[],
+        request: Some("SettingsMap"),
+        response: None,
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "getSetting",
+        method: HttpMethod::Get,
+        path: "/api/settings/{key}",
+        tag: "admin",
+        summary: "Read a single setting",
+        params: &[path("key")],
+        request: None,
+        response: Some("SettingResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "setSetting",
+        method: HttpMethod::Put,
+        path: "/api/settings/{key}",
+        tag: "admin",
+        summary: "Write a single setting",
+        params: &[path("key")],
+        request: Some("SettingWriteRequest"),
+        response: None,
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "deleteSetting",
+        method: HttpMethod::Delete,
+        path: "/api/settings/{key}",
+        tag: "admin",
+        summary: "Delete a single setting",
+        params: &[path("key")],
+        request: None,
+        response: None,
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "gatewayStatus",
+        method: HttpMethod::Get,
+        path: "/api/gateway/status",
+        tag: "admin",
+        summary: "Connection counts for the gateway",
+        params: &[],
+        request: None,
+        response: Some("GatewayStatusResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "getBudget",
+        method: HttpMethod::Get,
+        path: "/api/budget",
+        tag: "admin",
+        summary: "Token and cost spend against the configured caps",
+        params: &[],
+        request: None,
+        response: None,
+        auth: true,
+    },
+    // OpenAI-compatible API
+    ApiOperation {
+        operation_id: "chatCompletions",
+        method: HttpMethod::Post,
+        path: "/v1/chat/completions",
+        tag: "openai",
+        summary: "OpenAI-compatible chat completion (streams when stream=true)",
+        params: &[],
+        request: Some("OpenAiChatRequest"),
+        response: None,
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "listModels",
+        method: HttpMethod::Get,
+        path: "/v1/models",
+        tag: "openai",
+        summary: "OpenAI-compatible model list",
+        params: &[],
+        request: None,
+        response: None,
+        auth: true,
+    },
+    // Integrations (bearer is a scoped integration key, not the chat token)
+    ApiOperation {
+        operation_id: "pushToSpace",
+        method: HttpMethod::Post,
+        path: "/api/integrations/spaces/{space}",
+        tag: "integrations",
+        summary: "Store a document in a memory space (key scope space:<space>)",
+        params: &[path("space")],
+        request: Some("IntegrationPushRequest"),
+        response: Some("IntegrationPushResponse"),
+        auth: true,
+    },
+    ApiOperation {
+        operation_id: "triggerRoutineByName",
+        method: HttpMethod::Post,
+        path: "/api/integrations/routines/{name}/trigger",
+        tag: "integrations",
+        summary: "Trigger an enabled routine (key scope routine:<name>)",
+        params: &[path("name")],
+        request: None,
+        response: Some("IntegrationTriggerResponse"),
+        auth: true,
+    },
+];
+
+/// Routes that serve the browser UI rather than the API (HTML, CSS, JS and
+/// project previews); they are left out of the spec.
+pub const UNDOCUMENTED_ROUTES: &[&str] = &[
+    "/",
+    "/style.css",
+    "/app.js",
+    "/projects/{project_id}",
+    "/projects/{project_id}/",
+    "/projects/{project_id}/{*path}",
+];
+
+/// Field shorthand used to describe component schemas.
+///
+/// `ty` is one of `string`, `integer`, `number`, `boolean`, `uuid`, `any`,
+/// `map` (string → any), a component name, or `[T]` for an array of `T`.
+struct Field(&'static str, &'static str, bool);
+
+const fn req(name: &'static str, ty: &'static str) -> Field {
+    Field(name, ty, true)
+}
+
+const fn opt(name: &'static str, ty: &'static str) -> Field {
+    Field(name, ty, false)
+}
+
+/// Component schemas referenced by [`OPERATIONS`].
+fn components() -> Vec<(&'static str, Vec<Field>)> {
+    vec![
+        (
+            "HealthResponse",
+            vec![req("status", "string"), req("channel", "string")],
+        ),
+        (
+            "ProbeReport",
+            vec![req("status", "string"), req("checks", "[ProbeCheck]")],
+        ),
+        (
+            "ProbeCheck",
+            vec![
+                req("name", "string"),
+                req("ok", "boolean"),
+                opt("detail", "string"),
+            ],
+        ),
+        (
+            "SendMessageRequest",
+            vec![req("content", "string"), opt("thread_id", "string")],
+        ),
+        (
+            "SendMessageResponse",
+            vec![req("message_id", "uuid"), req("status", "string")],
+        ),
+        (
+            "ThreadInfo",
+            vec![
+                req("id", "uuid"),
+                req("state", "string"),
+                req("turn_count", "integer"),
+                req("created_at", "string"),
+                req("updated_at", "string"),
+                opt("title", "string"),
+                opt("thread_type", "string"),
+            ],
+        ),
+        (
+            "ThreadListResponse",
+            vec![
+                opt("assistant_thread", "ThreadInfo"),
+                req("threads", "[ThreadInfo]"),
+                opt("active_thread", "uuid"),
+            ],
+        ),
+        (
+            "ToolCallInfo",
+            vec![
+                req("name", "string"),
+                req("has_result", "boolean"),
+                req("has_error", "boolean"),
+            ],
+        ),
+        (
+            "TurnInfo",
+            vec![
+                req("turn_number", "integer"),
+                req("user_input", "string"),
+                opt("response", "string"),
+                req("state", "string"),
+                req("started_at", "string"),
+                opt("completed_at", "string"),
+                req("tool_calls", "[ToolCallInfo]"),
+            ],
+        ),
+        (
+            "HistoryResponse",
+            vec![
+                req("thread_id", "uuid"),
+                req("turns", "[TurnInfo]"),
+                req("has_more", "boolean"),
+                opt("oldest_timestamp", "string"),
+            ],
+        ),
+        (
+            "ApprovalRequest",
+            vec![
+                req("request_id", "string"),
+                req("action", "string"),
+                opt("thread_id", "string"),
+            ],
+        ),
+        (
+            "AuthTokenRequest",
+            vec![req("extension_name", "string"), req("token", "string")],
+        ),
+        ("AuthCancelRequest", vec![req("extension_name", "string")]),
+        (
+            "ActionResponse",
+            vec![
+                req("success", "boolean"),
+                req("message", "string"),
+                opt("auth_url", "string"),
+                opt("awaiting_token", "boolean"),
+                opt("instructions", "string"),
+                opt("consent", "[string]"),
+            ],
+        ),
+        ("MemoryTreeResponse", vec![req("entries", "[TreeEntry]")]),
+        (
+            "TreeEntry",
+            vec![req("path", "string"), req("is_dir", "boolean")],
+        ),
+        (
+            "MemoryListResponse",
+            vec![req("path", "string"), req("entries", "[ListEntry]")],
+        ),
+        (
+            "ListEntry",
+            vec![
+                req("name", "string"),
+                req("path", "string"),
+                req("is_dir", "boolean"),
+                opt("updated_at", "string"),
+            ],
+        ),
+        (
+            "MemoryReadResponse",
+            vec![
+                req("path", "string"),
+                req("content", "string"),
+                opt("updated_at", "string"),
+            ],
+        ),
+        (
+            "MemoryWriteRequest",
+            vec![req("path", "string"), req("content", "string")],
+        ),
+        (
+            "MemoryWriteResponse",
+            vec![req("path", "string"), req("status", "string")],
+        ),
+        (
+            "MemorySearchRequest",
+            vec![req("query", "string"), opt("limit", "integer")],
+        ),
+        ("MemorySearchResponse", vec![req("results", "[SearchHit]")]),
+        (
+            "SearchHit",
+            vec![
+                req("path", "string"),
+                req("content", "string"),
+                req("score", "number"),
+            ],
+        ),
+        (
+            "JobInfo",
+            vec![
+                req("id", "uuid"),
+                req("title", "string"),
+                req("state", "string"),
+                req("user_id", "string"),
+                req("created_at", "string"),
+                opt("started_at", "string"),
+            ],
+        ),
+        ("JobListResponse", vec![req("jobs", "[JobInfo]")]),
+        (
+            "JobSummaryResponse",
+            vec![
+                req("total", "integer"),
+                req("pending", "integer"),
+                req("in_progress", "integer"),
+                req("completed", "integer"),
+                req("failed", "integer"),
+                req("stuck", "integer"),
+            ],
+        ),
+        (
+            "TransitionInfo",
+            vec![
+                req("from", "string"),
+                req("to", "string"),
+                req("timestamp", "string"),
+                opt("reason", "string"),
+            ],
+        ),
+        (
+            "JobDetailResponse",
+            vec![
+                req("id", "uuid"),
+                req("title", "string"),
+                req("description", "string"),
+                req("state", "string"),
+                req("user_id", "string"),
+                req("created_at", "string"),
+                opt("started_at", "string"),
+                opt("completed_at", "string"),
+                opt("elapsed_secs", "integer"),
+                opt("project_dir", "string"),
+                opt("browse_url", "string"),
+                opt("job_mode", "string"),
+                req("transitions", "[TransitionInfo]"),
+            ],
+        ),
+        (
+            "JobPromptRequest",
+            vec![req("content", "string"), opt("done", "boolean")],
+        ),
+        (
+            "ProjectFileEntry",
+            vec![
+                req("name", "string"),
+                req("path", "string"),
+                req("is_dir", "boolean"),
+            ],
+        ),
+        (
+            "ProjectFilesResponse",
+            vec![req("entries", "[ProjectFileEntry]")],
+        ),
+        (
+            "ProjectFileReadResponse",
+            vec![req("path", "string"), req("content", "string")],
+        ),
+        (
+            "RoutineInfo",
+            vec![
+                req("id", "uuid"),
+                req("name", "string"),
+                req("description", "string"),
+                req("enabled", "boolean"),
+                req("trigger_type", "string"),
+                req("trigger_summary", "string"),
+                req("action_type", "string"),
+                opt("last_run_at", "string"),
+                opt("next_fire_at", "string"),
+                req("run_count", "integer"),
+                req("consecutive_failures", "integer"),
+                req("status", "string"),
+            ],
+        ),
+        (
+            "RoutineListResponse",
+            vec![req("routines", "[RoutineInfo]")],
+        ),
+        (
+            "RoutineSummaryResponse",
+            vec![
+                req("total", "integer"),
+                req("enabled", "integer"),
+                req("disabled", "integer"),
+                req("failing", "integer"),
+                req("runs_today", "integer"),
+            ],
+        ),
+        (
+            "RoutineRunInfo",
+            vec![
+                req("id", "uuid"),
+                req("trigger_type", "string"),
+                req("started_at", "string"),
+                opt("completed_at", "string"),
+                req("status", "string"),
+                opt("result_summary", "string"),
+                opt("tokens_used", "integer"),
+            ],
+        ),
+        (
+            "RoutineDetailResponse",
+            vec![
+                req("id", "uuid"),
+                req("name", "string"),
+                req("description", "string"),
+                req("enabled", "boolean"),
+                req("trigger", "any"),
+                req("action", "any"),
+                req("guardrails", "any"),
+                req("notify", "any"),
+                opt("last_run_at", "string"),
+                opt("next_fire_at", "string"),
+                req("run_count", "integer"),
+                req("consecutive_failures", "integer"),
+                req("created_at", "string"),
+                req("recent_runs", "[RoutineRunInfo]"),
+            ],
+        ),
+        ("ToggleRoutineRequest", vec![opt("enabled", "boolean")]),
+        (
+            "OpenAiChatRequest",
+            vec![
+                req("model", "string"),
+                req("messages", "[map]"),
+                opt("temperature", "number"),
+                opt("max_tokens", "integer"),
+                opt("stream", "boolean"),
+                opt("tools", "[map]"),
+                opt("tool_choice", "any"),
+                opt("stop", "any"),
+            ],
+        ),
+        (
+            "ExtensionInfo",
+            vec![
+                req("name", "string"),
+                req("kind", "string"),
+                opt("description", "string"),
+                opt("url", "string"),
+                req("authenticated", "boolean"),
+                req("active", "boolean"),
+                req("tools", "[string]"),
+            ],
+        ),
+        (
+            "ExtensionListResponse",
+            vec![req("extensions", "[ExtensionInfo]")],
+        ),
+        (
+            "ToolInfo",
+            vec![req("name", "string"), req("description", "string")],
+        ),
+        ("ToolListResponse", vec![req("tools", "[ToolInfo]")]),
+        (
+            "ToolParamDoc",
+            vec![
+                req("name", "string"),
+                req("type", "string"),
+                req("required", "boolean"),
+                opt("description", "string"),
+                opt("default", "any"),
+                req("allowed", "[any]"),
+            ],
+        ),
+        (
+            "ToolDoc",
+            vec![
+                req("name", "string"),
+                req("description", "string"),
+                req("parameters", "[ToolParamDoc]"),
+                req("example", "map"),
+                req("requires_approval", "boolean"),
+                req("requires_sanitization", "boolean"),
+                req("domain", "string"),
+                req("source", "string"),
+            ],
+        ),
+        ("ToolDocsResponse", vec![req("tools", "[ToolDoc]")]),
+        (
+            "InstallExtensionRequest",
+            vec![
+                req("name", "string"),
+                opt("url", "string"),
+                opt("kind", "string"),
+            ],
+        ),
+        ("ConsentDecisionRequest", vec![req("grant", "boolean")]),
+        ("DryRunRequest", vec![opt("input", "any")]),
+        (
+            "DryRunReport",
+            vec![
+                req("http", "[HttpAttempt]"),
+                req("secrets", "[DryRunAttempt]"),
+                req("workspace", "[DryRunAttempt]"),
+                req("tool_invocations", "[DryRunAttempt]"),
+                req("logs", "[string]"),
+                opt("output", "string"),
+                opt("error", "string"),
+            ],
+        ),
+        (
+            "HttpAttempt",
+            vec![
+                req("method", "string"),
+                req("url", "string"),
+                req("allowed", "boolean"),
+            ],
+        ),
+        (
+            "DryRunAttempt",
+            vec![req("target", "string"), req("allowed", "boolean")],
+        ),
+        (
+            "SettingResponse",
+            vec![
+                req("key", "string"),
+                req("value", "any"),
+                req("updated_at", "string"),
+            ],
+        ),
+        (
+            "SettingsListResponse",
+            vec![req("settings", "[SettingResponse]")],
+        ),
+        ("SettingWriteRequest", vec![req("value", "any")]),
+        ("SettingsMap", vec![req("settings", "map")]),
+        (
+            "IntegrationPushRequest",
+            vec![req("content", "string"), opt("name", "string")],
+        ),
+        (
+            "IntegrationPushResponse",
+            vec![
+                req("space", "string"),
+                req("path", "string"),
+                req("status", "string"),
+            ],
+        ),
+        (
+            "IntegrationTriggerResponse",
+            vec![req("routine_id", "uuid"), req("status", "string")],
+        ),
+        (
+            "GatewayStatusResponse",
+            vec![
+                req("sse_connections", "integer"),
+                req("ws_connections", "integer"),
+                req("total_connections", "integer"),
+            ],
+        ),
+    ]
+}
+
+/// JSON schema for a field type shorthand.
+fn type_schema(ty: &str) -> Value {
+    if let Some(inner) = ty.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        return json!({ "type": "array", "items": type_schema(inner) });
+    }
+    match ty {
+        "string" | "integer" | "number" | "boolean" => json!({ "type": ty }),
+        "uuid" => json!({ "type": "string", "format": "uuid" }),
+        "any" => json!({}),
+        "map" => json!({ "type": "object", "additionalProperties": true }),
+        name => json!({ "$ref": format!("#/components/schemas/{name}") }),
+    }
+}
+
+fn object_schema(fields: &[Field]) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for Field(name, ty, is_required) in fields {
+        let mut schema = type_schema(ty);
+        if !is_required && let Value::Object(ref mut obj) = schema {
+            // OpenAPI 3.1: optional fields may also be serialized as null.
+            if let Some(t) = obj.get("type").cloned() {
+                obj.insert("type".to_string(), json!([t, "null"]));
+            }
+        }
+        properties.insert((*name).to_string(), schema);
+        if *is_required {
+            required.push(Value::String((*name).to_string()));
+        }
+    }
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+fn operation_json(op: &ApiOperation) -> Value {
+    let mut obj = Map::new();
+    obj.insert("operationId".into(), json!(op.operation_id));
+    obj.insert("summary".into(), json!(op.summary));
+    obj.insert("tags".into(), json!([op.tag]));
+
+    if !op.params.is_empty() {
+        let params: Vec<Value> = op
+            .params
+            .iter()
+            .map(|p| {
+                json!({
+                    "name": p.name,
+                    "in": match p.location {
+                        ParamLocation::Path => "path",
+                        ParamLocation::Query => "query",
+                    },
+                    "required": p.required,
+                    "schema": { "type": p.ty },
+                })
+            })
+            .collect();
+        obj.insert("parameters".into(), Value::Array(params));
+    }
+
+    if let Some(request) = op.request {
+        obj.insert(
+            "requestBody".into(),
+            json!({
+                "required": true,
+                "content": { "application/json": { "schema": type_schema(request) } },
+            }),
+        );
+    }
+
+    let ok = match op.response {
+        Some(response) => json!({
+            "description": "Success",
+            "content": { "application/json": { "schema": type_schema(response) } },
+        }),
+        None => json!({ "description": "Success" }),
+    };
+    let mut responses = Map::new();
+    responses.insert("200".into(), ok);
+    if op.auth {
+        responses.insert(
+            "401".into(),
+            json!({ "description": "Missing or invalid bearer token" }),
+        );
+    }
+    obj.insert("responses".into(), Value::Object(responses));
+
+    if !op.auth {
+        obj.insert("security".into(), json!([]));
+    }
+
+    Value::Object(obj)
+}
+
+/// Build the full OpenAPI document.
+pub fn openapi_spec() -> Value {
+    let mut paths = Map::new();
+    for op in OPERATIONS {
+        let entry = paths
+            .entry(op.path.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(methods) = entry {
+            methods.insert(op.method.as_str().to_string(), operation_json(op));
+        }
+    }
+
+    let schemas: Map<String, Value> = components()
+        .iter()
+        .map(|(name, fields)| ((*name).to_string(), object_schema(fields)))
+        .collect();
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "IronClaw Gateway API",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer" },
+            },
+        },
+        "security": [{ "bearerAuth": [] }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_has_core_sections() {
+        let spec = openapi_spec();
+        assert_eq!(spec["openapi"], "3.1.0");
+        assert!(spec["paths"]["/api/chat/send"]["post"].is_object());
+        assert!(spec["paths"]["/api/chat/approval"]["post"].is_object());
+        assert!(spec["paths"]["/api/extensions"]["get"].is_object());
+        assert!(spec["paths"]["/api/settings/{key}"]["put"].is_object());
+    }
+
+    #[test]
+    fn test_all_refs_resolve() {
+        let spec = openapi_spec();
+        let schemas = spec["components"]["schemas"].as_object().unwrap();
+        let text = spec.to_string();
+        for part in text.split("#/components/schemas/").skip(1) {
+            let name: String = part.chars().take_while(|c| *c != '"').collect();
+            assert!(schemas.contains_key(&name), "dangling ref {name}");
+        }
+    }
+
+    #[test]
+    fn test_operation_ids_unique() {
+        let mut ids: Vec<_> = OPERATIONS.iter().map(|o| o.operation_id).collect();
+        ids.sort_unstable();
+        let before = ids.len();
+        ids.dedup();
+        assert_eq!(before, ids.len());
+    }
+
+    #[test]
+    fn test_public_health_has_no_security() {
+        let spec = openapi_spec();
+        assert_eq!(spec["paths"]["/api/health"]["get"]["security"], json!([]));
+        assert!(spec["paths"]["/api/chat/send"]["post"]["security"].is_null());
+    }
+
+    /// `(path, method)` for every `.route(...)` registered in `server.rs`.
+    fn router_routes() -> Vec<(String, &'static str)> {
+        let source = include_str!("server.rs");
+        let mut routes = Vec::new();
+        for (start, _) in source.match_indices(".route(") {
+            let body = &source[start + ".route(".len()..];
+            let mut depth = 1;
+            let end = body
+                .char_indices()
+                .find(|(_, c)| {
+                    match c {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        _ => {}
+                    }
+                    depth == 0
+                })
+                .map(|(i, _)| i)
+                .expect("unbalanced .route(");
+            let call = &body[..end];
+            let path = call.split('"').nth(1).expect("route path literal");
+            for method in ["get", "post", "put", "delete"] {
+                let called = call.match_indices(&format!("{method}(")).any(|(i, _)| {
+                    !call[..i]
+                        .chars()
+                        .next_back()
+                        .is_some_and(|c| c.is_alphanumeric() || c == '_')
+                });
+                if called {
+                    routes.push((path.to_string(), method));
+                }
+            }
+        }
+        routes
+    }
+
+    #[test]
+    fn test_every_router_route_is_documented() {
+        let routes = router_routes();
+        assert!(
+            routes.len() > 50,
+            "router parse found {} routes",
+            routes.len()
+        );
+        let missing: Vec<_> = routes
+            .iter()
+            .filter(|(path, _)| !UNDOCUMENTED_ROUTES.contains(&path.as_str()))
+            .filter(|(path, method)| {
+                !OPERATIONS
+                    .iter()
+                    .any(|op| op.path == path && op.method.as_str() == *method)
+            })
+            .collect();
+        assert!(
+            missing.is_empty(),
+            "routes without an operation: {missing:?}"
+        );
+    }
+
+    #[test]
+    fn test_every_operation_is_routed() {
+        let routes = router_routes();
+        for op in OPERATIONS {
+            assert!(
+                routes
+                    .iter()
+                    .any(|(path, method)| path == op.path && *method == op.method.as_str()),
+                "{} {} is not routed",
+                op.method.as_str(),
+                op.path
+            );
+        }
+    }
+
+    #[test]
+    fn test_optional_fields_nullable() {
+        let spec = openapi_spec();
+        let thread_id =
+            &spec["components"]["schemas"]["SendMessageRequest"]["properties"]["thread_id"];
+        assert_eq!(thread_id["type"], json!(["string", "null"]));
+    }
+}
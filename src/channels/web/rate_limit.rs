@@ -0,0 +1,347 @@
+//! Adaptive rate limiting for the web gateway.
+//!
+//! Each client token gets its own token bucket. On top of that the limiter
+//! watches the agent's queue depth (messages waiting in the gateway channel
+//! plus turns in flight): past a soft threshold every request drains its
+//! bucket faster, and at the hard threshold requests are refused outright
+//! with a Retry-After estimated from the agent's average turn time.
+//!
+//! Limits live in the `gateway.rate_limit.*` settings and are re-read
+//! whenever those settings change, so admins can tune them without a
+//! restart.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use axum::http::{HeaderMap, HeaderValue, StatusCode, Uri, header};
+use axum::response::{IntoResponse, Response};
+use sha2::{Digest, Sha256};
+
+use crate::agent::AgentLoad;
+
+/// Settings key prefix for the limits.
+pub const SETTINGS_PREFIX: &str = "gateway.rate_limit.";
+
+/// Buckets kept before idle (full) ones are pruned.
+const MAX_BUCKETS: usize = 1024;
+
+/// Rate limiting parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimits {
+    /// Requests allowed per window for one token.
+    pub requests: u64,
+    /// Window length in seconds.
+    pub window_secs: u64,
+    /// Queue depth at which requests start to cost more.
+    pub soft_queue_depth: usize,
+    /// Queue depth at which new requests are refused.
+    pub max_queue_depth: usize,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            requests: 30,
+            window_secs: 60,
+            soft_queue_depth: 8,
+            max_queue_depth: 32,
+        }
+    }
+}
+
+impl RateLimits {
+    /// Read limits from a settings map, keeping `self` for missing or
+    /// invalid keys.
+    pub fn with_settings(self, settings: &HashMap<String, serde_json::Value>) -> Self {
+        let get = |name: &str| {
+            settings
+                .get(&format!("{SETTINGS_PREFIX}{name}"))
+                .and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()))
+        };
+        let limits = Self {
+            requests: get("requests").unwrap_or(self.requests),
+            window_secs: get("window_secs").unwrap_or(self.window_secs),
+            soft_queue_depth: get("soft_queue_depth")
+                .map(|v| v as usize)
+                .unwrap_or(self.soft_queue_depth),
+            max_queue_depth: get("max_queue_depth")
+                .map(|v| v as usize)
+                .unwrap_or(self.max_queue_depth),
+        };
+        if limits.requests == 0
+            || limits.window_secs == 0
+            || limits.max_queue_depth == 0
+            || limits.soft_queue_depth > limits.max_queue_depth
+        {
+            tracing::warn!(?limits, "Ignoring invalid gateway rate limits");
+            return self;
+        }
+        limits
+    }
+
+    /// Tokens refilled per second.
+    fn rate(&self) -> f64 {
+        self.requests as f64 / self.window_secs as f64
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    limits: RwLock<RateLimits>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+    load: RwLock<Option<Arc<AgentLoad>>>,
+}
+
+/// Per-token, queue-depth aware rate limiter. Clones share state.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    inner: Arc<Inner>,
+}
+
+impl RateLimiter {
+    pub fn new(max_requests: u64, window_secs: u64) -> Self {
+        Self::with_limits(RateLimits {
+            requests: max_requests.max(1),
+            window_secs: window_secs.max(1),
+            ..RateLimits::default()
+        })
+    }
+
+    pub fn with_limits(limits: RateLimits) -> Self {
+        let limiter = Self::default();
+        limiter.set_limits(limits);
+        limiter
+    }
+
+    pub fn limits(&self) -> RateLimits {
+        *self.inner.limits.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replace the limits; existing buckets keep their tokens (capped).
+    pub fn set_limits(&self, limits: RateLimits) {
+        *self.inner.limits.write().unwrap_or_else(|e| e.into_inner()) = limits;
+    }
+
+    /// Use the agent's turn counters as queue depth feedback.
+    pub fn set_agent_load(&self, load: Arc<AgentLoad>) {
+        *self.inner.load.write().unwrap_or_else(|e| e.into_inner()) = Some(load);
+    }
+
+    /// The agent's load counters, once set.
+    pub fn agent_load(&self) -> Option<Arc<AgentLoad>> {
+        self.inner
+            .load
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Current queue depth: `queued` messages plus agent turns in flight.
+    pub fn queue_depth(&self, queued: usize) -> usize {
+        let load = self.inner.load.read().unwrap_or_else(|e| e.into_inner());
+        queued + load.as_ref().map_or(0, |l| l.in_flight())
+    }
+
+    /// Try to admit one request for `key` while `queued` messages wait for
+    /// the agent. Returns how long to wait when refused.
+    pub fn check(&self, key: &str, queued: usize) -> Result<(), Duration> {
+        self.check_at(key, queued, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, queued: usize, now: Instant) -> Result<(), Duration> {
+        let limits = self.limits();
+        let depth = self.queue_depth(queued);
+
+        if depth >= limits.max_queue_depth {
+            let per_turn = self
+                .inner
+                .load
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .as_ref()
+                .and_then(|l| l.avg_turn())
+                .unwrap_or(Duration::from_secs(1));
+            let wait = per_turn.as_secs_f64() * (depth + 1 - limits.max_queue_depth) as f64;
+            return Err(clamp_retry(wait, limits.window_secs));
+        }
+
+        // Between the soft and hard thresholds each request costs more, so
+        // busy clients run dry before the queue is full.
+        let cost = if depth > limits.soft_queue_depth {
+            let headroom = (limits.max_queue_depth - limits.soft_queue_depth) as f64;
+            headroom / (limits.max_queue_depth - depth) as f64
+        } else {
+            1.0
+        };
+
+        let capacity = limits.requests as f64;
+        let mut buckets = self.inner.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= MAX_BUCKETS && !buckets.contains_key(key) {
+            let window = Duration::from_secs(limits.window_secs);
+            buckets.retain(|_, b| now.duration_since(b.updated) < window);
+        }
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * limits.rate()).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= cost {
+            bucket.tokens -= cost;
+            Ok(())
+        } else {
+            let wait = (cost - bucket.tokens) / limits.rate();
+            Err(clamp_retry(wait, limits.window_secs))
+        }
+    }
+}
+
+fn clamp_retry(secs: f64, window_secs: u64) -> Duration {
+    Duration::from_secs((secs.ceil() as u64).clamp(1, window_secs.max(1)))
+}
+
+/// Bucket key for a request: a hash of its bearer token (header or
+/// `?token=` query), so raw tokens are never held by the limiter.
+pub fn client_key(headers: &HeaderMap, uri: &Uri) -> String {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(String::from)
+        .or_else(|| {
+            uri.query()?
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("token="))
+                .map(|t| {
+                    urlencoding::decode(t)
+                        .map(|d| d.into_owned())
+                        .unwrap_or_else(|_| t.to_string())
+                })
+        });
+    match token {
+        Some(token) => hex::encode(&Sha256::digest(token.as_bytes())[..8]),
+        None => "anonymous".to_string(),
+    }
+}
+
+/// A 429 response with a Retry-After header.
+pub fn too_many_requests(retry_after: Duration, message: &str) -> Response {
+    let mut response = (StatusCode::TOO_MANY_REQUESTS, message.to_string()).into_response();
+    if let Ok(value) = HeaderValue::from_str(&retry_after.as_secs().to_string()) {
+        response.headers_mut().insert(header::RETRY_AFTER, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets_are_per_token_and_refill() {
+        let limiter = RateLimiter::new(2, 10);
+        let t0 = Instant::now();
+        assert!(limiter.check_at("a", 0, t0).is_ok());
+        assert!(limiter.check_at("a", 0, t0).is_ok());
+        let retry = limiter.check_at("a", 0, t0).unwrap_err();
+        assert_eq!(retry, Duration::from_secs(5));
+
+        // Another token has its own bucket.
+        assert!(limiter.check_at("b", 0, t0).is_ok());
+
+        // 2 requests per 10s refill one within 6s.
+        assert!(
+            limiter
+                .check_at("a", 0, t0 + Duration::from_secs(6))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_queue_depth_sheds_load() {
+        let limiter = RateLimiter::with_limits(RateLimits {
+            requests: 100,
+            window_secs: 60,
+            soft_queue_depth: 2,
+            max_queue_depth: 4,
+        });
+        let load = AgentLoad::new();
+        limiter.set_agent_load(load.clone());
+        let _turn = load.begin();
+
+        // 3 queued + 1 in flight reaches the hard limit.
+        assert_eq!(limiter.queue_depth(3), 4);
+        let retry = limiter.check("a", 3).unwrap_err();
+        assert!(retry >= Duration::from_secs(1));
+
+        // Above the soft limit requests cost double (headroom 2 / remaining 1).
+        let t0 = Instant::now();
+        assert!(limiter.check_at("b", 2, t0).is_ok());
+        let limiter_small = RateLimiter::with_limits(RateLimits {
+            requests: 3,
+            ..limiter.limits()
+        });
+        limiter_small.set_agent_load(load.clone());
+        assert!(limiter_small.check_at("c", 2, t0).is_ok());
+        assert!(limiter_small.check_at("c", 2, t0).is_err());
+    }
+
+    #[test]
+    fn test_limits_from_settings() {
+        let mut settings = HashMap::new();
+        settings.insert(
+            "gateway.rate_limit.requests".to_string(),
+            serde_json::json!(120),
+        );
+        settings.insert(
+            "gateway.rate_limit.max_queue_depth".to_string(),
+            serde_json::json!("64"),
+        );
+        let limits = RateLimits::default().with_settings(&settings);
+        assert_eq!(limits.requests, 120);
+        assert_eq!(limits.max_queue_depth, 64);
+        assert_eq!(limits.window_secs, 60);
+
+        settings.insert(
+            "gateway.rate_limit.window_secs".to_string(),
+            serde_json::json!(0),
+        );
+        assert_eq!(
+            RateLimits::default().with_settings(&settings),
+            RateLimits::default()
+        );
+    }
+
+    #[test]
+    fn test_client_key_hashes_token() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        let uri: Uri = "/api/chat/send".parse().unwrap();
+        let from_header = client_key(&headers, &uri);
+        assert!(!from_header.contains("secret"));
+
+        let uri: Uri = "/api/chat/send?token=secret".parse().unwrap();
+        assert_eq!(client_key(&HeaderMap::new(), &uri), from_header);
+        assert_eq!(
+            client_key(&HeaderMap::new(), &"/x".parse().unwrap()),
+            "anonymous"
+        );
+    }
+
+    #[test]
+    fn test_too_many_requests_sets_retry_after() {
+        let response = too_many_requests(Duration::from_secs(7), "slow down");
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "7");
+    }
+}
@@ -0,0 +1,450 @@
+//! Typed client SDK generation from the gateway OpenAPI spec.
+//!
+//! Walks the document produced by [`openapi_spec`](super::openapi::openapi_spec)
+//! (or any spec with the same shape) and emits a single-file client:
+//!
+//! - TypeScript: interfaces for every component schema plus a `fetch`-based
+//!   `IronClawClient` class.
+//! - Python: `TypedDict`s plus an `IronClawClient` built on `urllib` so the
+//!   generated file has no third-party dependencies.
+
+use serde_json::Value;
+
+/// Target language for a generated client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SdkLanguage {
+    #[value(name = "ts")]
+    TypeScript,
+    Python,
+}
+
+impl SdkLanguage {
+    /// Conventional file name for the generated client.
+    pub fn default_file_name(&self) -> &'static str {
+        match self {
+            Self::TypeScript => "ironclaw-client.ts",
+            Self::Python => "ironclaw_client.py",
+        }
+    }
+}
+
+/// A flattened view of one spec operation.
+struct Op {
+    id: String,
+    method: String,
+    path: String,
+    summary: String,
+    path_params: Vec<String>,
+    query_params: Vec<(String, String)>,
+    request: Option<Value>,
+    response: Option<Value>,
+}
+
+/// Generate a client in the requested language.
+pub fn generate_sdk(spec: &Value, lang: SdkLanguage) -> String {
+    match lang {
+        SdkLanguage::TypeScript => generate_typescript(spec),
+        SdkLanguage::Python => generate_python(spec),
+    }
+}
+
+fn collect_ops(spec: &Value) -> Vec<Op> {
+    let mut ops = Vec::new();
+    let Some(paths) = spec.get("paths").and_then(|p| p.as_object()) else {
+        return ops;
+    };
+    for (path, methods) in paths {
+        let Some(methods) = methods.as_object() else {
+            continue;
+        };
+        for (method, op) in methods {
+            let mut path_params = Vec::new();
+            let mut query_params = Vec::new();
+            for p in op
+                .get("parameters")
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+            {
+                let name = p["name"].as_str().unwrap_or_default().to_string();
+                let ty = p["schema"]["type"].as_str().unwrap_or("string").to_string();
+                match p["in"].as_str() {
+                    Some("path") => path_params.push(name),
+                    Some("query") => query_params.push((name, ty)),
+                    _ => {}
+                }
+            }
+            ops.push(Op {
+                id: op["operationId"]
+                    .as_str()
+                    .map(String::from)
+                    .unwrap_or_else(|| format!("{method}_{path}")),
+                method: method.to_uppercase(),
+                path: path.clone(),
+                summary: op["summary"].as_str().unwrap_or_default().to_string(),
+                path_params,
+                query_params,
+                request: op
+                    .pointer("/requestBody/content/application~1json/schema")
+                    .cloned(),
+                response: op
+                    .pointer("/responses/200/content/application~1json/schema")
+                    .cloned(),
+            });
+        }
+    }
+    ops
+}
+
+fn schemas(spec: &Value) -> Vec<(String, Value)> {
+    spec.pointer("/components/schemas")
+        .and_then(|s| s.as_object())
+        .map(|m| m.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default()
+}
+
+fn ref_name(schema: &Value) -> Option<&str> {
+    schema
+        .get("$ref")
+        .and_then(|r| r.as_str())
+        .and_then(|r| r.rsplit('/').next())
+}
+
+/// Convert `camelCase` to `snake_case`.
+fn snake_case(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 4);
+    for (i, c) in s.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Scalar type name and nullability from a (possibly `[T, "null"]`) type.
+fn scalar_type(schema: &Value) -> (Option<&str>, bool) {
+    match schema.get("type") {
+        Some(Value::String(t)) => (Some(t.as_str()), false),
+        Some(Value::Array(types)) => {
+            let nullable = types.iter().any(|t| t == "null");
+            let t = types
+                .iter()
+                .filter_map(|t| t.as_str())
+                .find(|t| *t != "null");
+            (t, nullable)
+        }
+        _ => (None, false),
+    }
+}
+
+// ── TypeScript ──────────────────────────────────────────────────────
+
+fn ts_type(schema: &Value) -> String {
+    if let Some(name) = ref_name(schema) {
+        return name.to_string();
+    }
+    let (ty, nullable) = scalar_type(schema);
+    let base = match ty {
+        Some("string") => "string".to_string(),
+        Some("integer") | Some("number") => "number".to_string(),
+        Some("boolean") => "boolean".to_string(),
+        Some("array") => format!("Array<{}>", ts_type(&schema["items"])),
+        Some("object") => "Record<string, unknown>".to_string(),
+        _ => "unknown".to_string(),
+    };
+    if nullable {
+        format!("{base} | null")
+    } else {
+        base
+    }
+}
+
+fn generate_typescript(spec: &Value) -> String {
+    let mut out = String::new();
+    out.push_str("// Generated by `ironclaw gateway sdk --lang ts`. Do not edit.\n");
+    out.push_str(&format!(
+        "// API version: {}\n\n",
+        spec["info"]["version"].as_str().unwrap_or("unknown")
+    ));
+
+    for (name, schema) in schemas(spec) {
+        out.push_str(&format!("export interface {name} {{\n"));
+        let required: Vec<&str> = schema["required"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str())
+            .collect();
+        if let Some(props) = schema["properties"].as_object() {
+            for (field, field_schema) in props {
+                let optional = if required.contains(&field.as_str()) {
+                    ""
+                } else {
+                    "?"
+                };
+                out.push_str(&format!(
+                    "  {field}{optional}: {};\n",
+                    ts_type(field_schema)
+                ));
+            }
+        }
+        out.push_str("}\n\n");
+    }
+
+    out.push_str(
+        r#"export class IronClawClient {
+  constructor(
+    private readonly baseUrl: string,
+    private readonly token?: string,
+  ) {}
+
+  private async request<T>(
+    method: string,
+    path: string,
+    query?: Record<string, string | number | boolean | undefined>,
+    body?: unknown,
+  ): Promise<T> {
+    const url = new URL(path, this.baseUrl);
+    for (const [k, v] of Object.entries(query ?? {})) {
+      if (v !== undefined) url.searchParams.set(k, String(v));
+    }
+    const headers: Record<string, string> = {};
+    if (this.token) headers["Authorization"] = `Bearer ${this.token}`;
+    if (body !== undefined) headers["Content-Type"] = "application/json";
+    const res = await fetch(url, {
+      method,
+      headers,
+      body: body === undefined ? undefined : JSON.stringify(body),
+    });
+    if (!res.ok) {
+      throw new Error(`${method} ${path} failed: ${res.status} ${await res.text()}`);
+    }
+    const text = await res.text();
+    return (text ? JSON.parse(text) : undefined) as T;
+  }
+"#,
+    );
+
+    for op in collect_ops(spec) {
+        let mut args: Vec<String> = op
+            .path_params
+            .iter()
+            .map(|p| format!("{p}: string"))
+            .collect();
+        if let Some(ref req) = op.request {
+            args.push(format!("body: {}", ts_type(req)));
+        }
+        if !op.query_params.is_empty() {
+            let fields: Vec<String> = op
+                .query_params
+                .iter()
+                .map(|(n, t)| {
+                    let ty = if t == "integer" { "number" } else { t.as_str() };
+                    format!("{n}?: {ty}")
+                })
+                .collect();
+            args.push(format!("query: {{ {} }} = {{}}", fields.join("; ")));
+        }
+        let ret = op
+            .response
+            .as_ref()
+            .map(ts_type)
+            .unwrap_or_else(|| "void".to_string());
+        let mut path_expr = op.path.clone();
+        for p in &op.path_params {
+            path_expr = path_expr.replace(
+                &format!("{{{p}}}"),
+                &format!("${{encodeURIComponent({p})}}"),
+            );
+        }
+        let query_arg = if op.query_params.is_empty() {
+            "undefined"
+        } else {
+            "query"
+        };
+        let body_arg = if op.request.is_some() {
+            "body"
+        } else {
+            "undefined"
+        };
+        out.push_str(&format!(
+            "\n  /** {} */\n  {}({}): Promise<{ret}> {{\n    return this.request<{ret}>(\"{}\", `{path_expr}`, {query_arg}, {body_arg});\n  }}\n",
+            op.summary,
+            op.id,
+            args.join(", "),
+            op.method,
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+// ── Python ──────────────────────────────────────────────────────────
+
+fn py_type(schema: &Value) -> String {
+    if let Some(name) = ref_name(schema) {
+        return format!("\"{name}\"");
+    }
+    let (ty, nullable) = scalar_type(schema);
+    let base = match ty {
+        Some("string") => "str".to_string(),
+        Some("integer") => "int".to_string(),
+        Some("number") => "float".to_string(),
+        Some("boolean") => "bool".to_string(),
+        Some("array") => format!("List[{}]", py_type(&schema["items"])),
+        Some("object") => "Dict[str, Any]".to_string(),
+        _ => "Any".to_string(),
+    };
+    if nullable {
+        format!("Optional[{base}]")
+    } else {
+        base
+    }
+}
+
+fn generate_python(spec: &Value) -> String {
+    let mut out = String::new();
+    out.push_str("# Generated by `ironclaw gateway sdk --lang python`. Do not edit.\n");
+    out.push_str(&format!(
+        "# API version: {}\n\n",
+        spec["info"]["version"].as_str().unwrap_or("unknown")
+    ));
+    out.push_str(
+        "from __future__ import annotations\n\n\
+         import json\n\
+         import urllib.parse\n\
+         import urllib.request\n\
+         from typing import Any, Dict, List, Optional, TypedDict\n\n\n",
+    );
+
+    for (name, schema) in schemas(spec) {
+        out.push_str(&format!("class {name}(TypedDict, total=False):\n"));
+        match schema["properties"].as_object() {
+            Some(props) if !props.is_empty() => {
+                for (field, field_schema) in props {
+                    out.push_str(&format!("    {field}: {}\n", py_type(field_schema)));
+                }
+            }
+            _ => out.push_str("    pass\n"),
+        }
+        out.push_str("\n\n");
+    }
+
+    out.push_str(
+        r#"class IronClawClient:
+    def __init__(self, base_url: str, token: Optional[str] = None, timeout: float = 30.0):
+        self.base_url = base_url.rstrip("/")
+        self.token = token
+        self.timeout = timeout
+
+    def _request(self, method: str, path: str, query: Optional[Dict[str, Any]] = None, body: Any = None) -> Any:
+        url = self.base_url + path
+        params = {k: v for k, v in (query or {}).items() if v is not None}
+        if params:
+            url += "?" + urllib.parse.urlencode(params)
+        headers = {}
+        if self.token:
+            headers["Authorization"] = f"Bearer {self.token}"
+        data = None
+        if body is not None:
+            headers["Content-Type"] = "application/json"
+            data = json.dumps(body).encode()
+        req = urllib.request.Request(url, data=data, headers=headers, method=method)
+        with urllib.request.urlopen(req, timeout=self.timeout) as resp:
+            text = resp.read().decode()
+            return json.loads(text) if text else None
+"#,
+    );
+
+    for op in collect_ops(spec) {
+        let mut args = vec!["self".to_string()];
+        args.extend(op.path_params.iter().map(|p| format!("{p}: str")));
+        if let Some(ref req) = op.request {
+            args.push(format!("body: {}", py_type(req)));
+        }
+        for (name, ty) in &op.query_params {
+            let ty = match ty.as_str() {
+                "integer" => "int",
+                "boolean" => "bool",
+                _ => "str",
+            };
+            args.push(format!("{name}: Optional[{ty}] = None"));
+        }
+        let ret = op
+            .response
+            .as_ref()
+            .map(py_type)
+            .unwrap_or_else(|| "None".to_string());
+        let mut path_expr = op.path.clone();
+        for p in &op.path_params {
+            path_expr = path_expr.replace(
+                &format!("{{{p}}}"),
+                &format!("{{urllib.parse.quote({p}, safe='')}}"),
+            );
+        }
+        let query_arg = if op.query_params.is_empty() {
+            "None".to_string()
+        } else {
+            let pairs: Vec<String> = op
+                .query_params
+                .iter()
+                .map(|(n, _)| format!("\"{n}\": {n}"))
+                .collect();
+            format!("{{{}}}", pairs.join(", "))
+        };
+        let body_arg = if op.request.is_some() { "body" } else { "None" };
+        out.push_str(&format!(
+            "\n    def {}({}) -> {ret}:\n        \"\"\"{}\"\"\"\n        return self._request(\"{}\", f\"{path_expr}\", {query_arg}, {body_arg})\n",
+            snake_case(&op.id),
+            args.join(", "),
+            op.summary,
+            op.method,
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channels::web::openapi::openapi_spec;
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("sendMessage"), "send_message");
+        assert_eq!(snake_case("health"), "health");
+        assert_eq!(snake_case("listThreads"), "list_threads");
+    }
+
+    #[test]
+    fn test_typescript_client_contains_operations() {
+        let ts = generate_sdk(&openapi_spec(), SdkLanguage::TypeScript);
+        assert!(ts.contains("export interface SendMessageRequest {"));
+        assert!(ts.contains("  thread_id?: string | null;"));
+        assert!(ts.contains("sendMessage(body: SendMessageRequest): Promise<SendMessageResponse>"));
+        assert!(ts.contains("${encodeURIComponent(name)}"));
+        assert!(ts.contains("export class IronClawClient"));
+    }
+
+    #[test]
+    fn test_python_client_contains_operations() {
+        let py = generate_sdk(&openapi_spec(), SdkLanguage::Python);
+        assert!(py.contains("class SendMessageRequest(TypedDict, total=False):"));
+        assert!(py.contains("def send_message(self, body: \"SendMessageRequest\")"));
+        assert!(py.contains("def chat_history(self, thread_id: Optional[str] = None"));
+        assert!(py.contains("urllib.parse.quote(key, safe='')"));
+    }
+
+    #[test]
+    fn test_empty_spec_still_emits_client() {
+        let ts = generate_sdk(&serde_json::json!({}), SdkLanguage::TypeScript);
+        assert!(ts.contains("export class IronClawClient"));
+        let py = generate_sdk(&serde_json::json!({}), SdkLanguage::Python);
+        assert!(py.contains("class IronClawClient:"));
+    }
+}
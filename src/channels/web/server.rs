@@ -5,7 +5,6 @@
 use std::convert::Infallible;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
 
 use axum::{
     Json, Router,
@@ -24,17 +23,29 @@ use tokio_stream::StreamExt;
 use tower_http::cors::{AllowHeaders, CorsLayer};
 use uuid::Uuid;
 
-use crate::agent::SessionManager;
-use crate::channels::IncomingMessage;
+use crate::agent::{
+    BudgetManager, BudgetReport, FocusMode, FocusStatus, ModelTierRouter, Responsiveness,
+    SessionManager, TierReport,
+};
 use crate::channels::web::auth::{AuthState, auth_middleware};
+use crate::channels::web::health::{HealthProbes, ProbeReport};
+use crate::channels::web::integrations::{
+    IntegrationAuthError, IntegrationKey, IntegrationKeyStore, IntegrationLimiter,
+    IntegrationScope, authorize, push_path,
+};
 use crate::channels::web::log_layer::LogBroadcaster;
+use crate::channels::web::rate_limit::{
+    RateLimits, SETTINGS_PREFIX, client_key, too_many_requests,
+};
 use crate::channels::web::sse::SseManager;
 use crate::channels::web::types::*;
+use crate::channels::{IncomingMessage, OverflowStore};
 use crate::db::Database;
-use crate::extensions::ExtensionManager;
+use crate::extensions::{ExtensionError, ExtensionManager};
 use crate::orchestrator::job_manager::ContainerJobManager;
 use crate::tools::ToolRegistry;
-use crate::workspace::Workspace;
+use crate::tools::wasm::DryRunReport;
+use crate::workspace::{QuotaConfig, Workspace, WorkspaceUsage};
 
 /// Shared prompt queue: maps job IDs to pending follow-up prompts for Claude Code bridges.
 pub type PromptQueue = Arc<
@@ -46,79 +57,7 @@ pub type PromptQueue = Arc<
     >,
 >;
 
-/// Simple sliding-window rate limiter.
-///
-/// Tracks the number of requests in the current window. Resets when the window expires.
-/// Not per-IP (since this is a single-user gateway with auth), but prevents flooding.
-pub struct RateLimiter {
-    /// Requests remaining in the current window.
-    remaining: AtomicU64,
-    /// Epoch second when the current window started.
-    window_start: AtomicU64,
-    /// Maximum requests per window.
-    max_requests: u64,
-    /// Window duration in seconds.
-    window_secs: u64,
-}
-
-impl RateLimiter {
-    pub fn new(max_requests: u64, window_secs: u64) -> Self {
-        Self {
-            remaining: AtomicU64::new(max_requests),
-            window_start: AtomicU64::new(
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs(),
-            ),
-            max_requests,
-            window_secs,
-        }
-    }
-
-    /// Try to consume one request. Returns `true` if allowed, `false` if rate limited.
-    ///
-    /// Uses CAS for the window reset to prevent TOCTOU races where two threads
-    /// both detect an expired window and double the effective quota (Finding 8).
-    pub fn check(&self) -> bool {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-
-        let window = self.window_start.load(Ordering::Acquire);
-        if now.saturating_sub(window) >= self.window_secs {
-            // Try to atomically reset the window. Only one thread wins the CAS.
-            if self
-                .window_start
-                .compare_exchange(window, now, Ordering::AcqRel, Ordering::Acquire)
-                .is_ok()
-            {
-                // We won the reset — set remaining (minus 1 for this request)
-                self.remaining
-                    .store(self.max_requests - 1, Ordering::Release);
-                return true;
-            }
-            // Lost the CAS — another thread reset the window, fall through to
-            // the decrement loop with the new window's remaining count.
-        }
-
-        // Try to decrement remaining
-        loop {
-            let current = self.remaining.load(Ordering::Acquire);
-            if current == 0 {
-                return false;
-            }
-            if self
-                .remaining
-                .compare_exchange_weak(current, current - 1, Ordering::AcqRel, Ordering::Acquire)
-                .is_ok()
-            {
-                return true;
-            }
-        }
-    }
-}
+pub use super::rate_limit::RateLimiter;
 
 /// Shared state for all gateway handlers.
 pub struct GatewayState {
@@ -150,8 +89,51 @@ pub struct GatewayState {
     pub ws_tracker: Option<Arc<crate::channels::web::ws::WsConnectionTracker>>,
     /// LLM provider for OpenAI-compatible API proxy.
     pub llm_provider: Option<Arc<dyn crate::llm::LlmProvider>>,
-    /// Rate limiter for chat endpoints (30 messages per 60 seconds).
+    /// Per-token, queue-depth aware limiter for chat endpoints.
     pub chat_rate_limiter: RateLimiter,
+    /// Model tier router, for the savings shown in the status popover.
+    pub model_tiers: Option<Arc<ModelTierRouter>>,
+    /// Focus mode switch, for the status popover.
+    pub focus: Option<Arc<FocusMode>>,
+    /// Storage quotas reported by the memory usage endpoint.
+    pub memory_quota: QuotaConfig,
+    /// Full text of responses too long for their channel, served at
+    /// `/overflow/{id}`.
+    pub overflow: Arc<OverflowStore>,
+    /// Inputs of the `/healthz`, `/readyz` and `/startupz` probes.
+    pub health: HealthProbes,
+    /// Per-key budgets for `/api/integrations/*` requests.
+    pub integration_limiter: IntegrationLimiter,
+    /// Token and cost tallies of chat turns, for `/api/budget`.
+    pub budget: Option<Arc<BudgetManager>>,
+}
+
+impl GatewayState {
+    /// Messages handed to the agent but not yet picked up.
+    pub async fn queued_messages(&self) -> usize {
+        self.msg_tx
+            .read()
+            .await
+            .as_ref()
+            .map_or(0, |tx| tx.max_capacity() - tx.capacity())
+    }
+
+    /// Re-read `gateway.rate_limit.*` settings into the chat limiter.
+    pub async fn reload_rate_limits(&self) {
+        let Some(ref store) = self.store else {
+            return;
+        };
+        match store.get_all_settings(&self.user_id).await {
+            Ok(settings) => {
+                let limits = RateLimits::default().with_settings(&settings);
+                if limits != self.chat_rate_limiter.limits() {
+                    tracing::info!(?limits, "Gateway rate limits updated");
+                    self.chat_rate_limiter.set_limits(limits);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to load gateway rate limits: {}", e),
+        }
+    }
 }
 
 /// Start the gateway HTTP server.
@@ -177,7 +159,15 @@ pub async fn start_server(
             })?;
 
     // Public routes (no auth)
-    let public = Router::new().route("/api/health", get(health_handler));
+    let public = Router::new()
+        .route("/api/health", get(health_handler))
+        .route("/healthz", get(healthz_handler))
+        .route("/readyz", get(readyz_handler))
+        .route("/startupz", get(startupz_handler))
+        .route("/openapi.json", get(openapi_handler))
+        // The unguessable, expiring id is the credential, so links posted
+        // to other channels open without a gateway token.
+        .route("/overflow/{id}", get(overflow_handler));
 
     // Protected routes (require auth)
     let auth_state = AuthState { token: auth_token };
@@ -198,6 +188,7 @@ pub async fn start_server(
         .route("/api/memory/read", get(memory_read_handler))
         .route("/api/memory/write", post(memory_write_handler))
         .route("/api/memory/search", post(memory_search_handler))
+        .route("/api/memory/usage", get(memory_usage_handler))
         // Jobs
         .route("/api/jobs", get(jobs_list_handler))
         .route("/api/jobs/summary", get(jobs_summary_handler))
@@ -213,11 +204,23 @@ pub async fn start_server(
         // Extensions
         .route("/api/extensions", get(extensions_list_handler))
         .route("/api/extensions/tools", get(extensions_tools_handler))
+        .route(
+            "/api/extensions/tools/docs",
+            get(extensions_tool_docs_handler),
+        )
         .route("/api/extensions/install", post(extensions_install_handler))
         .route(
             "/api/extensions/{name}/activate",
             post(extensions_activate_handler),
         )
+        .route(
+            "/api/extensions/{name}/consent",
+            post(extensions_consent_handler),
+        )
+        .route(
+            "/api/extensions/{name}/dry-run",
+            post(extensions_dry_run_handler),
+        )
         .route(
             "/api/extensions/{name}/remove",
             post(extensions_remove_handler),
@@ -248,6 +251,7 @@ pub async fn start_server(
         )
         // Gateway control plane
         .route("/api/gateway/status", get(gateway_status_handler))
+        .route("/api/budget", get(budget_handler))
         // OpenAI-compatible API
         .route(
             "/v1/chat/completions",
@@ -259,6 +263,18 @@ pub async fn start_server(
             auth_middleware,
         ));
 
+    // Integration routes: authenticated per handler with scoped integration
+    // keys, never with the chat token.
+    let integrations = Router::new()
+        .route(
+            "/api/integrations/spaces/{space}",
+            post(integration_push_handler),
+        )
+        .route(
+            "/api/integrations/routines/{name}/trigger",
+            post(integration_trigger_handler),
+        );
+
     // Static file routes (no auth, served from embedded strings)
     let statics = Router::new()
         .route("/", get(index_handler))
@@ -325,6 +341,7 @@ pub async fn start_server(
         .merge(statics)
         .merge(projects)
         .merge(protected)
+        .merge(integrations)
         .layer(cors)
         .layer(security_headers)
         .layer(frame_options)
@@ -380,16 +397,51 @@ async fn health_handler() -> Json<HealthResponse> {
     })
 }
 
+async fn healthz_handler(State(state): State<Arc<GatewayState>>) -> ProbeReport {
+    state.health.liveness()
+}
+
+async fn readyz_handler(State(state): State<Arc<GatewayState>>) -> ProbeReport {
+    state.health.readiness(state.store.as_deref()).await
+}
+
+async fn startupz_handler(State(state): State<Arc<GatewayState>>) -> ProbeReport {
+    state.health.startup()
+}
+
+async fn overflow_handler(
+    State(state): State<Arc<GatewayState>>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let content = state.overflow.get(&id).ok_or(StatusCode::NOT_FOUND)?;
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        content,
+    ))
+}
+
+// --- OpenAPI ---
+
+async fn openapi_handler() -> Json<serde_json::Value> {
+    Json(crate::channels::web::openapi::openapi_spec())
+}
+
 // --- Chat handlers ---
 
 async fn chat_send_handler(
     State(state): State<Arc<GatewayState>>,
+    headers: axum::http::HeaderMap,
+    uri: axum::http::Uri,
     Json(req): Json<SendMessageRequest>,
-) -> Result<(StatusCode, Json<SendMessageResponse>), (StatusCode, String)> {
-    if !state.chat_rate_limiter.check() {
-        return Err((
-            StatusCode::TOO_MANY_REQUESTS,
-            "Rate limit exceeded. Try again shortly.".to_string(),
+) -> Result<(StatusCode, Json<SendMessageResponse>), axum::response::Response> {
+    let queued = state.queued_messages().await;
+    if let Err(retry_after) = state
+        .chat_rate_limiter
+        .check(&client_key(&headers, &uri), queued)
+    {
+        return Err(too_many_requests(
+            retry_after,
+            "Rate limit exceeded. Try again shortly.",
         ));
     }
 
@@ -403,17 +455,13 @@ async fn chat_send_handler(
     let msg_id = msg.id;
 
     let tx_guard = state.msg_tx.read().await;
-    let tx = tx_guard.as_ref().ok_or((
-        StatusCode::SERVICE_UNAVAILABLE,
-        "Channel not started".to_string(),
-    ))?;
+    let tx = tx_guard
+        .as_ref()
+        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Channel not started").into_response())?;
 
-    tx.send(msg).await.map_err(|_| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Channel closed".to_string(),
-        )
-    })?;
+    tx.send(msg)
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Channel closed").into_response())?;
 
     Ok((
         StatusCode::ACCEPTED,
@@ -717,6 +765,7 @@ async fn chat_history_handler(
                         has_error: tc.error.is_some(),
                     })
                     .collect(),
+                recoveries: t.recoveries.iter().map(|r| r.error.clone()).collect(),
             })
             .collect();
 
@@ -772,6 +821,7 @@ fn build_turns_from_db_messages(messages: &[crate::history::ConversationMessage]
                 started_at: msg.created_at.to_rfc3339(),
                 completed_at: None,
                 tool_calls: Vec::new(),
+                recoveries: Vec::new(),
             };
 
             // Check if next message is an assistant response
@@ -1016,6 +1066,21 @@ async fn memory_list_handler(
     }))
 }
 
+async fn memory_usage_handler(
+    State(state): State<Arc<GatewayState>>,
+) -> Result<Json<WorkspaceUsage>, (StatusCode, String)> {
+    let workspace = state.workspace.as_ref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Workspace not available".to_string(),
+    ))?;
+
+    let usage = workspace
+        .usage(&state.memory_quota)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(usage))
+}
+
 #[derive(Deserialize)]
 struct ReadQuery {
     path: String,
@@ -1248,7 +1313,7 @@ async fn jobs_cancel_handler(
         if job.status == "running" || job.status == "creating" {
             // Stop the container if we have a job manager.
             if let Some(ref jm) = state.job_manager
-                && let Err(e) = jm.stop_job(job_id).await
+                && let Err(e) = jm.cancel_job(job_id, "Cancelled by user").await
             {
                 tracing::warn!(job_id = %job_id, error = %e, "Failed to stop container during cancellation");
             }
@@ -1671,6 +1736,45 @@ async fn extensions_tools_handler(
     Ok(Json(ToolListResponse { tools }))
 }
 
+#[derive(Deserialize)]
+struct ToolDocsQuery {
+    name: Option<String>,
+    format: Option<String>,
+}
+
+async fn extensions_tool_docs_handler(
+    State(state): State<Arc<GatewayState>>,
+    Query(query): Query<ToolDocsQuery>,
+) -> Result<axum::response::Response, (StatusCode, String)> {
+    let registry = state.tool_registry.as_ref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Tool registry not available".to_string(),
+    ))?;
+
+    let tools = match &query.name {
+        Some(name) => vec![
+            registry
+                .tool_doc(name)
+                .await
+                .ok_or((StatusCode::NOT_FOUND, format!("Tool '{}' not found", name)))?,
+        ],
+        None => registry.tool_docs().await,
+    };
+
+    match query.format.as_deref() {
+        Some("markdown") | Some("md") => Ok((
+            [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+            crate::tools::docs::render_markdown(&tools),
+        )
+            .into_response()),
+        None | Some("json") => Ok(Json(ToolDocsResponse { tools }).into_response()),
+        Some(other) => Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unsupported format '{}' (use json or markdown)", other),
+        )),
+    }
+}
+
 async fn extensions_install_handler(
     State(state): State<Arc<GatewayState>>,
     Json(req): Json<InstallExtensionRequest>,
@@ -1707,6 +1811,14 @@ async fn extensions_activate_handler(
 
     match ext_mgr.activate(&name).await {
         Ok(result) => Ok(Json(ActionResponse::ok(result.message))),
+        Err(crate::extensions::ExtensionError::ConsentRequired(request)) => {
+            let mut resp = ActionResponse::fail(format!(
+                "'{}' needs your approval for the capabilities it requests",
+                name
+            ));
+            resp.consent = Some(request.summary());
+            Ok(Json(resp))
+        }
         Err(activate_err) => {
             let err_str = activate_err.to_string();
             let needs_auth = err_str.contains("authentication")
@@ -1748,6 +1860,40 @@ async fn extensions_activate_handler(
     }
 }
 
+async fn extensions_consent_handler(
+    State(state): State<Arc<GatewayState>>,
+    Path(name): Path<String>,
+    Json(req): Json<ConsentDecisionRequest>,
+) -> Result<Json<ActionResponse>, (StatusCode, String)> {
+    let ext_mgr = state.extension_manager.as_ref().ok_or((
+        StatusCode::NOT_IMPLEMENTED,
+        "Extension manager not available (secrets store required)".to_string(),
+    ))?;
+
+    match ext_mgr.decide_consent(&name, req.grant).await {
+        Ok(message) => Ok(Json(ActionResponse::ok(message))),
+        Err(e) => Ok(Json(ActionResponse::fail(e.to_string()))),
+    }
+}
+
+async fn extensions_dry_run_handler(
+    State(state): State<Arc<GatewayState>>,
+    Path(name): Path<String>,
+    Json(req): Json<DryRunRequest>,
+) -> Result<Json<DryRunReport>, (StatusCode, String)> {
+    let ext_mgr = state.extension_manager.as_ref().ok_or((
+        StatusCode::NOT_IMPLEMENTED,
+        "Extension manager not available (secrets store required)".to_string(),
+    ))?;
+
+    let input = req.input.unwrap_or_else(|| serde_json::json!({}));
+    match ext_mgr.dry_run(&name, input).await {
+        Ok(report) => Ok(Json(report)),
+        Err(ExtensionError::NotInstalled(msg)) => Err((StatusCode::NOT_FOUND, msg)),
+        Err(e) => Err((StatusCode::BAD_REQUEST, e.to_string())),
+    }
+}
+
 // --- Project file serving handlers ---
 
 /// Redirect `/projects/{id}` to `/projects/{id}/` so relative paths in
@@ -1973,15 +2119,7 @@ async fn routines_trigger_handler(
     }
 
     // Send the routine prompt through the message pipeline as a manual trigger.
-    let prompt = match &routine.action {
-        crate::agent::routine::RoutineAction::Lightweight { prompt, .. } => prompt.clone(),
-        crate::agent::routine::RoutineAction::FullJob {
-            title, description, ..
-        } => format!("{}: {}", title, description),
-    };
-
-    let content = format!("[routine:{}] {}", routine.name, prompt);
-    let msg = IncomingMessage::new("gateway", &state.user_id, content);
+    let msg = IncomingMessage::new("gateway", &state.user_id, routine_trigger_content(&routine));
 
     let tx_guard = state.msg_tx.read().await;
     let tx = tx_guard.as_ref().ok_or((
@@ -2002,6 +2140,25 @@ async fn routines_trigger_handler(
     })))
 }
 
+/// Message that runs `routine` through the agent as a manual trigger.
+fn routine_trigger_content(routine: &crate::agent::routine::Routine) -> String {
+    let prompt = match &routine.action {
+        crate::agent::routine::RoutineAction::Lightweight { prompt, .. } => prompt.clone(),
+        crate::agent::routine::RoutineAction::FullJob {
+            title, description, ..
+        } => format!("{}: {}", title, description),
+        crate::agent::routine::RoutineAction::Report { period_days, .. } => format!(
+            "Summarize what you did over the last {} days: tools used, jobs, costs, memory changes and notable conversations.",
+            period_days
+        ),
+        crate::agent::routine::RoutineAction::Tool { tool, params } => {
+            format!("Run the {} tool with these parameters: {}", tool, params)
+        }
+    };
+
+    format!("[routine:{}] {}", routine.name, prompt)
+}
+
 #[derive(Deserialize)]
 struct ToggleRequest {
     enabled: Option<bool>,
@@ -2134,8 +2291,12 @@ async fn routines_runs_handler(
 /// Convert a Routine to the trimmed RoutineInfo for list display.
 fn routine_to_info(r: &crate::agent::routine::Routine) -> RoutineInfo {
     let (trigger_type, trigger_summary) = match &r.trigger {
-        crate::agent::routine::Trigger::Cron { schedule } => {
-            ("cron".to_string(), format!("cron: {}", schedule))
+        crate::agent::routine::Trigger::Cron { schedule, timezone } => {
+            let summary = match timezone {
+                Some(tz) => format!("cron: {} ({})", schedule, tz),
+                None => format!("cron: {}", schedule),
+            };
+            ("cron".to_string(), summary)
         }
         crate::agent::routine::Trigger::Event {
             pattern, channel, ..
@@ -2147,12 +2308,17 @@ fn routine_to_info(r: &crate::agent::routine::Routine) -> RoutineInfo {
             let p = path.as_deref().unwrap_or("/");
             ("webhook".to_string(), format!("webhook: {}", p))
         }
+        crate::agent::routine::Trigger::File { path, .. } => {
+            ("file".to_string(), format!("file: {}", path))
+        }
         crate::agent::routine::Trigger::Manual => ("manual".to_string(), "manual only".to_string()),
     };
 
     let action_type = match &r.action {
         crate::agent::routine::RoutineAction::Lightweight { .. } => "lightweight",
         crate::agent::routine::RoutineAction::FullJob { .. } => "full_job",
+        crate::agent::routine::RoutineAction::Report { .. } => "report",
+        crate::agent::routine::RoutineAction::Tool { .. } => "tool",
     };
 
     let status = if !r.enabled {
@@ -2179,6 +2345,134 @@ fn routine_to_info(r: &crate::agent::routine::Routine) -> RoutineInfo {
     }
 }
 
+// --- Integration handlers ---
+
+/// Check an integration key against `scope`, mapping refusals to responses.
+async fn authorize_integration(
+    state: &GatewayState,
+    headers: &axum::http::HeaderMap,
+    scope: IntegrationScope,
+    queued: usize,
+) -> Result<IntegrationKey, axum::response::Response> {
+    let store = state.store.as_ref().ok_or_else(|| {
+        (StatusCode::SERVICE_UNAVAILABLE, "Database not available").into_response()
+    })?;
+    let keys = IntegrationKeyStore::new(Arc::clone(store), &state.user_id);
+    authorize(&keys, &state.integration_limiter, headers, &scope, queued)
+        .await
+        .map_err(|e| match e {
+            IntegrationAuthError::Unauthorized => (
+                StatusCode::UNAUTHORIZED,
+                "Invalid or revoked integration key",
+            )
+                .into_response(),
+            IntegrationAuthError::Forbidden(scope) => (
+                StatusCode::FORBIDDEN,
+                format!("Integration key is not scoped to {}", scope),
+            )
+                .into_response(),
+            IntegrationAuthError::RateLimited(retry_after) => too_many_requests(
+                retry_after,
+                "Integration key rate limit exceeded. Try again shortly.",
+            ),
+            IntegrationAuthError::Storage(e) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, e).into_response()
+            }
+        })
+}
+
+async fn integration_push_handler(
+    State(state): State<Arc<GatewayState>>,
+    Path(space): Path<String>,
+    headers: axum::http::HeaderMap,
+    Json(req): Json<IntegrationPushRequest>,
+) -> Result<Json<IntegrationPushResponse>, axum::response::Response> {
+    let key =
+        authorize_integration(&state, &headers, IntegrationScope::Space(space.clone()), 0).await?;
+
+    let workspace = state.workspace.as_ref().ok_or_else(|| {
+        (StatusCode::SERVICE_UNAVAILABLE, "Workspace not available").into_response()
+    })?;
+    if req.content.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Content is empty").into_response());
+    }
+    let internal = |e: crate::error::WorkspaceError| {
+        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    };
+    if workspace
+        .get_space(&space)
+        .await
+        .map_err(internal)?
+        .is_none()
+    {
+        return Err((StatusCode::NOT_FOUND, "Space not found").into_response());
+    }
+
+    let path = push_path(&space, req.name.as_deref())
+        .map_err(|e| (StatusCode::BAD_REQUEST, e).into_response())?;
+    workspace
+        .write(&path, &req.content)
+        .await
+        .map_err(internal)?;
+    workspace
+        .add_to_space(&space, &path)
+        .await
+        .map_err(internal)?;
+
+    tracing::info!(key = %key.id, space = %space, path = %path, "Integration pushed document");
+    Ok(Json(IntegrationPushResponse {
+        space,
+        path,
+        status: "stored",
+    }))
+}
+
+async fn integration_trigger_handler(
+    State(state): State<Arc<GatewayState>>,
+    Path(name): Path<String>,
+    headers: axum::http::HeaderMap,
+) -> Result<(StatusCode, Json<IntegrationTriggerResponse>), axum::response::Response> {
+    let queued = state.queued_messages().await;
+    let key = authorize_integration(
+        &state,
+        &headers,
+        IntegrationScope::Routine(name.clone()),
+        queued,
+    )
+    .await?;
+
+    let store = state.store.as_ref().ok_or_else(|| {
+        (StatusCode::SERVICE_UNAVAILABLE, "Database not available").into_response()
+    })?;
+    let routine = store
+        .get_routine_by_name(&state.user_id, &name)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Routine not found").into_response())?;
+    if !routine.enabled {
+        return Err((StatusCode::CONFLICT, "Routine is disabled").into_response());
+    }
+
+    let msg = IncomingMessage::new("gateway", &state.user_id, routine_trigger_content(&routine))
+        .with_metadata(serde_json::json!({ "integration_key": key.id }));
+    let tx_guard = state.msg_tx.read().await;
+    let tx = tx_guard
+        .as_ref()
+        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Channel not started").into_response())?;
+    tx.send(msg)
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Channel closed").into_response())?;
+
+    tracing::info!(key = %key.id, routine = %routine.name, "Integration triggered routine");
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(IntegrationTriggerResponse {
+            routine_id: routine.id,
+            status: "triggered",
+        }),
+    ))
+}
+
 // --- Settings handlers ---
 
 async fn settings_list_handler(
@@ -2246,6 +2540,10 @@ async fn settings_set_handler(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    if key.starts_with(SETTINGS_PREFIX) {
+        state.reload_rate_limits().await;
+    }
+
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -2265,6 +2563,10 @@ async fn settings_delete_handler(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    if key.starts_with(SETTINGS_PREFIX) {
+        state.reload_rate_limits().await;
+    }
+
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -2298,6 +2600,7 @@ async fn settings_import_handler(
             tracing::error!("Failed to import settings: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
+    state.reload_rate_limits().await;
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -2314,18 +2617,67 @@ async fn gateway_status_handler(
         .map(|t| t.connection_count())
         .unwrap_or(0);
 
+    let limits = state.chat_rate_limiter.limits();
+    let queue_depth = state
+        .chat_rate_limiter
+        .queue_depth(state.queued_messages().await);
+
     Json(GatewayStatusResponse {
         sse_connections,
         ws_connections,
         total_connections: sse_connections + ws_connections,
+        queue_depth,
+        rate_limit: RateLimitStatus {
+            requests: limits.requests,
+            window_secs: limits.window_secs,
+            soft_queue_depth: limits.soft_queue_depth,
+            max_queue_depth: limits.max_queue_depth,
+        },
+        model_tiers: state.model_tiers.as_ref().map(|t| t.report()),
+        focus: state.focus.as_ref().map(|f| f.status()),
+        responsiveness: state
+            .chat_rate_limiter
+            .agent_load()
+            .map(|l| l.responsiveness()),
     })
 }
 
+/// Today's token and cost spend for the gateway user, with the caps.
+async fn budget_handler(
+    State(state): State<Arc<GatewayState>>,
+) -> Result<Json<BudgetReport>, (StatusCode, String)> {
+    let budget = state.budget.as_ref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Budget tracking not available".to_string(),
+    ))?;
+    Ok(Json(budget.report(&state.user_id)))
+}
+
 #[derive(serde::Serialize)]
 struct GatewayStatusResponse {
     sse_connections: u64,
     ws_connections: u64,
     total_connections: u64,
+    /// Messages waiting for the agent plus turns in flight.
+    queue_depth: usize,
+    rate_limit: RateLimitStatus,
+    /// Cheap/primary routing and realized savings, when tiers are enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model_tiers: Option<TierReport>,
+    /// Whether focus mode is holding background activity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    focus: Option<FocusStatus>,
+    /// Scheduling lag and background runtime load.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    responsiveness: Option<Responsiveness>,
+}
+
+#[derive(serde::Serialize)]
+struct RateLimitStatus {
+    requests: u64,
+    window_secs: u64,
+    soft_queue_depth: usize,
+    max_queue_depth: usize,
 }
 
 #[cfg(test)]
@@ -110,8 +110,10 @@ impl SseManager {
                     SseEvent::StreamChunk { .. } => "stream_chunk",
                     SseEvent::Status { .. } => "status",
                     SseEvent::ApprovalNeeded { .. } => "approval_needed",
+                    SseEvent::InputRequested { .. } => "input_requested",
                     SseEvent::AuthRequired { .. } => "auth_required",
                     SseEvent::AuthCompleted { .. } => "auth_completed",
+                    SseEvent::Image { .. } => "image",
                     SseEvent::Error { .. } => "error",
                     SseEvent::JobStarted { .. } => "job_started",
                     SseEvent::JobMessage { .. } => "job_message",
@@ -31,13 +31,14 @@ function authenticate() {
       // Strip token from URL so it's not visible in the address bar
       const cleaned = new URL(window.location);
       cleaned.searchParams.delete('token');
-      window.history.replaceState({}, '', cleaned.pathname + cleaned.search);
+      window.history.replaceState({}, '', cleaned.pathname + cleaned.search + cleaned.hash);
       connectSSE();
       connectLogSSE();
       startGatewayStatusPolling();
       loadThreads();
       loadMemoryTree();
       loadJobs();
+      openDeepLink();
     })
     .catch(() => {
       sessionStorage.removeItem('ironclaw_token');
@@ -47,6 +48,14 @@ function authenticate() {
     });
 }
 
+// Links such as a pull request's transcript link point at #jobs/<id>
+function openDeepLink() {
+  const match = window.location.hash.match(/^#jobs\/([0-9a-f-]{36})$/i);
+  if (!match) return;
+  switchTab('jobs');
+  openJobDetail(match[1]);
+}
+
 document.getElementById('token-input').addEventListener('keydown', (e) => {
   if (e.key === 'Enter') authenticate();
 });
@@ -107,7 +116,7 @@ function connectSSE() {
   eventSource.addEventListener('response', (e) => {
     const data = JSON.parse(e.data);
     if (!isCurrentThread(data.thread_id)) return;
-    addMessage('assistant', data.content);
+    finishStreamedReply(data.content);
     setStatus('');
     enableChatInput();
     // Refresh thread list so new titles appear after first message
@@ -131,6 +140,9 @@ function connectSSE() {
     if (!isCurrentThread(data.thread_id)) return;
     const icon = data.success ? '\u2713' : '\u2717';
     setStatus('Tool ' + data.name + ' ' + icon);
+    // Keep the tool's output; the reply that follows gets its own bubble.
+    document.querySelectorAll('#chat-messages .message[data-streaming]')
+      .forEach((el) => el.removeAttribute('data-streaming'));
   });
 
   eventSource.addEventListener('stream_chunk', (e) => {
@@ -161,6 +173,18 @@ function connectSSE() {
     showApproval(data);
   });
 
+  eventSource.addEventListener('input_requested', (e) => {
+    const data = JSON.parse(e.data);
+    if (!isCurrentThread(data.thread_id)) return;
+    showInputForm(data);
+  });
+
+  eventSource.addEventListener('image', (e) => {
+    const data = JSON.parse(e.data);
+    if (!isCurrentThread(data.thread_id)) return;
+    showImage(data);
+  });
+
   eventSource.addEventListener('auth_required', (e) => {
     const data = JSON.parse(e.data);
     showAuthCard(data);
@@ -355,15 +379,34 @@ function addMessage(role, content) {
 
 function appendToLastAssistant(chunk) {
   const container = document.getElementById('chat-messages');
-  const messages = container.querySelectorAll('.message.assistant');
-  if (messages.length > 0) {
-    const last = messages[messages.length - 1];
+  const messages = container.querySelectorAll('.message');
+  const last = messages[messages.length - 1];
+  // Only extend an assistant message that is still the latest one, never a
+  // reply from an earlier turn.
+  if (last && last.classList.contains('assistant')) {
     const raw = (last.getAttribute('data-raw') || '') + chunk;
     last.setAttribute('data-raw', raw);
+    last.setAttribute('data-streaming', 'true');
     last.innerHTML = renderMarkdown(raw);
     container.scrollTop = container.scrollHeight;
   } else {
     addMessage('assistant', chunk);
+    container.lastElementChild.setAttribute('data-streaming', 'true');
+  }
+}
+
+// The final reply replaces text streamed while it was generated, since the
+// agent may still have rewritten it (glossary, citations, formatting).
+function finishStreamedReply(content) {
+  const container = document.getElementById('chat-messages');
+  const last = container.lastElementChild;
+  if (last && last.classList.contains('assistant') && last.hasAttribute('data-streaming')) {
+    last.removeAttribute('data-streaming');
+    last.setAttribute('data-raw', content);
+    last.innerHTML = renderMarkdown(content);
+    container.scrollTop = container.scrollHeight;
+  } else {
+    addMessage('assistant', content);
   }
 }
 
@@ -443,6 +486,112 @@ function showApproval(data) {
   container.scrollTop = container.scrollHeight;
 }
 
+function showImage(data) {
+  const container = document.getElementById('chat-messages');
+  const figure = document.createElement('figure');
+  figure.className = 'message assistant image-message';
+
+  const link = document.createElement('a');
+  link.href = data.data_url;
+  link.download = data.media_id + (data.mime_type === 'image/svg+xml' ? '.svg' : '.png');
+  const img = document.createElement('img');
+  img.src = data.data_url;
+  img.alt = data.title || 'Chart';
+  img.addEventListener('load', () => { container.scrollTop = container.scrollHeight; });
+  link.appendChild(img);
+  figure.appendChild(link);
+
+  if (data.title) {
+    const caption = document.createElement('figcaption');
+    caption.textContent = data.title;
+    figure.appendChild(caption);
+  }
+
+  container.appendChild(figure);
+  container.scrollTop = container.scrollHeight;
+}
+
+function showInputForm(data) {
+  const container = document.getElementById('chat-messages');
+  const form = data.form || {};
+  const fields = form.fields || [];
+  const card = document.createElement('form');
+  card.className = 'approval-card input-card';
+  card.setAttribute('data-request-id', data.request_id);
+
+  const header = document.createElement('div');
+  header.className = 'approval-header';
+  header.textContent = 'Input requested';
+  card.appendChild(header);
+
+  const inputs = [];
+  fields.forEach((field) => {
+    const row = document.createElement('label');
+    row.className = 'input-card-field';
+    const caption = document.createElement('span');
+    caption.textContent = (field.label || field.name) + (field.required === false ? ' (optional)' : '');
+    row.appendChild(caption);
+
+    let input;
+    if (field.type === 'choice' || field.type === 'boolean') {
+      input = document.createElement('select');
+      const options = field.type === 'boolean' ? ['yes', 'no'] : (field.options || []);
+      if (field.required === false) options.unshift('');
+      options.forEach((opt) => {
+        const el = document.createElement('option');
+        el.value = opt;
+        el.textContent = opt;
+        input.appendChild(el);
+      });
+    } else {
+      input = document.createElement('input');
+      input.type = field.type === 'number' ? 'number' : field.type === 'date' ? 'date' : 'text';
+      if (field.type === 'number') {
+        input.step = 'any';
+        if (field.min !== undefined) input.min = field.min;
+        if (field.max !== undefined) input.max = field.max;
+      }
+      input.required = field.required !== false;
+    }
+    row.appendChild(input);
+    card.appendChild(row);
+    inputs.push({ name: field.name, input });
+  });
+
+  const actions = document.createElement('div');
+  actions.className = 'approval-actions';
+  const submitBtn = document.createElement('button');
+  submitBtn.type = 'submit';
+  submitBtn.className = 'approve';
+  submitBtn.textContent = 'Submit';
+  actions.appendChild(submitBtn);
+  card.appendChild(actions);
+
+  card.addEventListener('submit', (ev) => {
+    ev.preventDefault();
+    const answers = {};
+    inputs.forEach(({ name, input }) => {
+      if (input.value !== '') answers[name] = input.value;
+    });
+    const content = JSON.stringify(answers);
+    addMessage('user', Object.entries(answers).map(([k, v]) => k + ': ' + v).join('\n'));
+    setStatus('Sending...', true);
+    apiFetch('/api/chat/send', {
+      method: 'POST',
+      body: { content, thread_id: currentThreadId || undefined },
+    }).catch((err) => {
+      addMessage('system', 'Failed to send: ' + err.message);
+      setStatus('');
+    });
+    card.querySelectorAll('input, select, button').forEach((el) => {
+      el.disabled = true;
+    });
+  });
+
+  container.appendChild(card);
+  container.scrollTop = container.scrollHeight;
+}
+
 function showJobCard(data) {
   const container = document.getElementById('chat-messages');
   const card = document.createElement('div');
@@ -1289,6 +1438,11 @@ function activateExtension(name) {
         return;
       }
 
+      if (res.consent) {
+        promptExtensionConsent(name, res.consent);
+        return;
+      }
+
       if (res.auth_url) {
         showToast('Opening authentication for ' + name, 'info');
         window.open(res.auth_url, '_blank');
@@ -1302,6 +1456,30 @@ function activateExtension(name) {
     .catch((err) => showToast('Activate failed: ' + err.message, 'error'));
 }
 
+function promptExtensionConsent(name, capabilities) {
+  const grant = confirm(
+    '"' + name + '" requests these capabilities:\n\n  '
+      + capabilities.join('\n  ')
+      + '\n\nAllow and activate?'
+  );
+  apiFetch('/api/extensions/' + encodeURIComponent(name) + '/consent', {
+    method: 'POST',
+    body: { grant: grant },
+  })
+    .then((res) => {
+      if (!res.success) {
+        showToast('Consent failed: ' + res.message, 'error');
+      } else if (grant) {
+        activateExtension(name);
+        return;
+      } else {
+        showToast('Denied capabilities for ' + name, 'info');
+      }
+      loadExtensions();
+    })
+    .catch((err) => showToast('Consent failed: ' + err.message, 'error'));
+}
+
 function removeExtension(name) {
   if (!confirm('Remove extension "' + name + '"?')) return;
   apiFetch('/api/extensions/' + encodeURIComponent(name) + '/remove', { method: 'POST' })
@@ -2062,6 +2240,17 @@ function fetchGatewayStatus() {
     popover.innerHTML = '<div class="gw-stat"><span>SSE clients</span><span>' + (data.sse_clients || 0) + '</span></div>'
       + '<div class="gw-stat"><span>Log clients</span><span>' + (data.log_clients || 0) + '</span></div>'
       + '<div class="gw-stat"><span>Uptime</span><span>' + formatDuration(data.uptime_secs) + '</span></div>';
+    const tiers = data.model_tiers;
+    if (tiers) {
+      popover.innerHTML += '<div class="gw-stat"><span>Model tier</span><span>' + escapeHtml(tiers.mode) + '</span></div>'
+        + '<div class="gw-stat"><span>Cheap / primary turns</span><span>' + tiers.cheap.turns + ' / ' + tiers.primary.turns + '</span></div>'
+        + '<div class="gw-stat"><span>Saved by ' + escapeHtml(tiers.cheap_model) + '</span><span>$' + Number(tiers.saved).toFixed(4) + '</span></div>';
+    }
+    const focus = data.focus;
+    if (focus && focus.active) {
+      popover.innerHTML += '<div class="gw-stat"><span>Focus until</span><span>' + new Date(focus.until).toLocaleTimeString() + '</span></div>'
+        + '<div class="gw-stat"><span>Held back</span><span>' + focus.deferred + '</span></div>';
+    }
   }).catch(() => {});
 }
 
@@ -48,6 +48,9 @@ pub struct TurnInfo {
     pub started_at: String,
     pub completed_at: Option<String>,
     pub tool_calls: Vec<ToolCallInfo>,
+    /// Errors the turn resumed from, oldest first.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub recoveries: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -138,6 +141,14 @@ pub enum SseEvent {
         description: String,
         parameters: String,
     },
+    #[serde(rename = "input_requested")]
+    InputRequested {
+        request_id: String,
+        prompt: String,
+        form: serde_json::Value,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        thread_id: Option<String>,
+    },
     #[serde(rename = "auth_required")]
     AuthRequired {
         extension_name: String,
@@ -154,6 +165,17 @@ pub enum SseEvent {
         success: bool,
         message: String,
     },
+    #[serde(rename = "image")]
+    Image {
+        media_id: String,
+        mime_type: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        /// Inline `data:` URL, so the browser needs no extra request.
+        data_url: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        thread_id: Option<String>,
+    },
     #[serde(rename = "error")]
     Error {
         message: String,
@@ -381,6 +403,11 @@ pub struct ToolListResponse {
     pub tools: Vec<ToolInfo>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ToolDocsResponse {
+    pub tools: Vec<crate::tools::ToolDoc>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct InstallExtensionRequest {
     pub name: String,
@@ -388,6 +415,20 @@ pub struct InstallExtensionRequest {
     pub kind: Option<String>,
 }
 
+/// The user's answer to a capability consent prompt.
+#[derive(Debug, Deserialize)]
+pub struct ConsentDecisionRequest {
+    pub grant: bool,
+}
+
+/// Sample input for a WASM tool dry run.
+#[derive(Debug, Default, Deserialize)]
+pub struct DryRunRequest {
+    /// Parameters passed to the tool (defaults to `{}`).
+    #[serde(default)]
+    pub input: Option<serde_json::Value>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ActionResponse {
     pub success: bool,
@@ -401,6 +442,9 @@ pub struct ActionResponse {
     /// Instructions for manual token entry.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub instructions: Option<String>,
+    /// Capabilities awaiting the user's consent (when activation requires it).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consent: Option<Vec<String>>,
 }
 
 impl ActionResponse {
@@ -411,6 +455,7 @@ impl ActionResponse {
             auth_url: None,
             awaiting_token: None,
             instructions: None,
+            consent: None,
         }
     }
 
@@ -421,6 +466,7 @@ impl ActionResponse {
             auth_url: None,
             awaiting_token: None,
             instructions: None,
+            consent: None,
         }
     }
 }
@@ -508,8 +554,10 @@ impl WsServerMessage {
             SseEvent::Status { .. } => "status",
             SseEvent::JobStarted { .. } => "job_started",
             SseEvent::ApprovalNeeded { .. } => "approval_needed",
+            SseEvent::InputRequested { .. } => "input_requested",
             SseEvent::AuthRequired { .. } => "auth_required",
             SseEvent::AuthCompleted { .. } => "auth_completed",
+            SseEvent::Image { .. } => "image",
             SseEvent::Error { .. } => "error",
             SseEvent::Heartbeat => "heartbeat",
             SseEvent::JobMessage { .. } => "job_message",
@@ -592,6 +640,28 @@ pub struct RoutineRunInfo {
     pub tokens_used: Option<i32>,
 }
 
+// --- Integrations ---
+
+#[derive(Debug, Deserialize)]
+pub struct IntegrationPushRequest {
+    pub content: String,
+    /// Document name inside the space's directory; generated when absent.
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IntegrationPushResponse {
+    pub space: String,
+    pub path: String,
+    pub status: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IntegrationTriggerResponse {
+    pub routine_id: Uuid,
+    pub status: &'static str,
+}
+
 // --- Settings ---
 
 #[derive(Debug, Serialize)]
@@ -509,6 +509,13 @@ mod tests {
             ws_tracker: Some(Arc::new(WsConnectionTracker::new())),
             llm_provider: None,
             chat_rate_limiter: crate::channels::web::server::RateLimiter::new(30, 60),
+            model_tiers: None,
+            focus: None,
+            memory_quota: Default::default(),
+            overflow: Arc::new(crate::channels::OverflowStore::new()),
+            health: Default::default(),
+            integration_limiter: Default::default(),
+            budget: None,
         }
     }
 }
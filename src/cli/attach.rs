@@ -0,0 +1,398 @@
+//! `ironclaw attach`: interactive REPL against a running agent.
+//!
+//! Connects to the local web gateway over its WebSocket endpoint and drives
+//! the regular REPL line editor and renderer, so a daemonized agent (e.g.
+//! under systemd) gets the same streaming output, approval prompts, and
+//! inline commands as `ironclaw run` without spawning a second agent.
+//!
+//! ```text
+//! rustyline ──► attach ── WS {"type":"message"|"approval"} ──► gateway ──► agent
+//! terminal  ◄── ReplChannel renderer ◄── WS {"type":"event"} ◄── gateway
+//! ```
+//!
+//! `/quit`, `/exit`, and Ctrl+D detach instead of shutting the agent down.
+
+use std::time::Duration;
+
+use clap::Args;
+use futures::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+
+use crate::channels::{Channel, IncomingMessage, OutgoingResponse, ReplChannel, StatusUpdate};
+
+/// Interval between keepalive pings sent to the gateway.
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Args, Debug, Clone)]
+pub struct AttachArgs {
+    /// Gateway host (defaults to GATEWAY_HOST or 127.0.0.1)
+    #[arg(long)]
+    pub host: Option<String>,
+
+    /// Gateway port (defaults to GATEWAY_PORT or 3000)
+    #[arg(short, long)]
+    pub port: Option<u16>,
+
+    /// Gateway auth token (defaults to GATEWAY_AUTH_TOKEN)
+    #[arg(long)]
+    pub token: Option<String>,
+
+    /// Conversation thread to attach to (defaults to the active thread)
+    #[arg(short, long)]
+    pub thread: Option<String>,
+}
+
+/// A gateway event translated for the REPL renderer.
+#[derive(Debug)]
+enum AttachEvent {
+    Response {
+        content: String,
+        thread_id: Option<String>,
+    },
+    Status {
+        update: StatusUpdate,
+        thread_id: Option<String>,
+    },
+    Error(String),
+}
+
+/// Run `ironclaw attach`.
+pub async fn run_attach_command(args: AttachArgs) -> anyhow::Result<()> {
+    let _ = dotenvy::dotenv();
+
+    let host = args
+        .host
+        .or_else(|| std::env::var("GATEWAY_HOST").ok())
+        .unwrap_or_else(|| "127.0.0.1".to_string());
+    let port = match args.port {
+        Some(port) => port,
+        None => std::env::var("GATEWAY_PORT")
+            .ok()
+            .and_then(|v| v.parse::<u16>().ok())
+            .unwrap_or(3000),
+    };
+    let token = args
+        .token
+        .or_else(|| std::env::var("GATEWAY_AUTH_TOKEN").ok())
+        .ok_or_else(|| {
+            anyhow::anyhow!("Gateway auth token required: set GATEWAY_AUTH_TOKEN or pass --token")
+        })?;
+
+    let url = format!("ws://{}:{}/api/chat/ws", host, port);
+    let mut request = url.as_str().into_client_request()?;
+    let headers = request.headers_mut();
+    headers.insert(
+        "Authorization",
+        HeaderValue::from_str(&format!("Bearer {}", token))?,
+    );
+    // The gateway only accepts WS upgrades with a local Origin; this client
+    // is authenticated by token, not by browser origin.
+    headers.insert(
+        "Origin",
+        HeaderValue::from_str(&format!("http://localhost:{}", port))?,
+    );
+
+    let (socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to connect to {} (is the agent running with GATEWAY_ENABLED=true?): {}",
+                url,
+                e
+            )
+        })?;
+    let (mut ws_sink, mut ws_stream) = socket.split();
+
+    eprintln!(
+        "\x1b[90mattached to {} (/quit or Ctrl+D to detach)\x1b[0m",
+        url
+    );
+
+    let repl = ReplChannel::new();
+    let mut input = repl.start().await?;
+    let mut thread_id = args.thread;
+    let mut pending_approval: Option<String> = None;
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    ping.tick().await;
+
+    loop {
+        tokio::select! {
+            line = input.next() => {
+                let Some(line) = line else {
+                    break;
+                };
+                let content = line.content.trim().to_string();
+                if is_detach_command(&content) {
+                    break;
+                }
+
+                let frame = match (&pending_approval, approval_action(&content)) {
+                    (Some(request_id), Some(action)) => {
+                        let frame = serde_json::json!({
+                            "type": "approval",
+                            "request_id": request_id,
+                            "action": action,
+                            "thread_id": thread_id,
+                        });
+                        pending_approval = None;
+                        frame
+                    }
+                    _ => serde_json::json!({
+                        "type": "message",
+                        "content": content,
+                        "thread_id": thread_id,
+                    }),
+                };
+                ws_sink.send(Message::text(frame.to_string())).await?;
+            }
+            frame = ws_stream.next() => {
+                let text = match frame {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => {
+                        eprintln!("\x1b[90mgateway closed the connection\x1b[0m");
+                        break;
+                    }
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(anyhow::anyhow!("WebSocket error: {}", e)),
+                };
+                let Ok(value) = serde_json::from_str::<serde_json::Value>(&text) else {
+                    continue;
+                };
+                let Some(event) = parse_server_frame(&value) else {
+                    continue;
+                };
+                render_event(&repl, event, &mut thread_id, &mut pending_approval).await;
+            }
+            _ = ping.tick() => {
+                ws_sink
+                    .send(Message::text(serde_json::json!({ "type": "ping" }).to_string()))
+                    .await?;
+            }
+        }
+    }
+
+    let _ = ws_sink.send(Message::Close(None)).await;
+    eprintln!("\x1b[90mdetached; the agent keeps running\x1b[0m");
+    Ok(())
+}
+
+/// Render an event through the REPL, tracking the attached thread.
+async fn render_event(
+    repl: &ReplChannel,
+    event: AttachEvent,
+    thread_id: &mut Option<String>,
+    pending_approval: &mut Option<String>,
+) {
+    // Ignore traffic for other conversations once our thread is known;
+    // until then, adopt the first thread we hear from.
+    let event_thread = match &event {
+        AttachEvent::Response { thread_id, .. } | AttachEvent::Status { thread_id, .. } => {
+            thread_id.clone()
+        }
+        AttachEvent::Error(_) => None,
+    };
+    if let Some(event_thread) = event_thread {
+        match thread_id {
+            Some(current) if *current != event_thread => return,
+            Some(_) => {}
+            None => *thread_id = Some(event_thread),
+        }
+    }
+
+    let echo = IncomingMessage::new("attach", "user", "");
+    let _ = match event {
+        AttachEvent::Response { content, .. } => {
+            repl.respond(&echo, OutgoingResponse::text(content)).await
+        }
+        AttachEvent::Status { update, .. } => {
+            if let StatusUpdate::ApprovalNeeded { request_id, .. } = &update {
+                *pending_approval = Some(request_id.clone());
+            }
+            repl.send_status(update, &serde_json::Value::Null).await
+        }
+        AttachEvent::Error(message) => {
+            eprintln!("  \x1b[31m\u{2717} {message}\x1b[0m");
+            Ok(())
+        }
+    };
+}
+
+/// Whether a line should end the attach session rather than reach the agent.
+///
+/// `/quit` would otherwise shut the daemon down.
+fn is_detach_command(line: &str) -> bool {
+    matches!(
+        line.to_lowercase().as_str(),
+        "/quit" | "/exit" | "/shutdown" | "/detach"
+    )
+}
+
+/// Map a REPL approval reply to a gateway approval action.
+fn approval_action(line: &str) -> Option<&'static str> {
+    match line.to_lowercase().as_str() {
+        "y" | "yes" | "approve" => Some("approve"),
+        "a" | "always" => Some("always"),
+        "n" | "no" | "deny" => Some("deny"),
+        _ => None,
+    }
+}
+
+/// Translate a gateway WebSocket frame into something the REPL can render.
+fn parse_server_frame(frame: &serde_json::Value) -> Option<AttachEvent> {
+    let str_field =
+        |v: &serde_json::Value, key: &str| v.get(key).and_then(|f| f.as_str()).map(String::from);
+
+    match frame.get("type")?.as_str()? {
+        "error" => Some(AttachEvent::Error(str_field(frame, "message")?)),
+        "event" => {
+            let data = frame.get("data")?;
+            let thread_id = str_field(data, "thread_id");
+            let status = |update| {
+                Some(AttachEvent::Status {
+                    update,
+                    thread_id: thread_id.clone(),
+                })
+            };
+            match frame.get("event_type")?.as_str()? {
+                "response" => Some(AttachEvent::Response {
+                    content: str_field(data, "content")?,
+                    thread_id: thread_id.clone(),
+                }),
+                "thinking" => status(StatusUpdate::Thinking(str_field(data, "message")?)),
+                "status" => status(StatusUpdate::Status(str_field(data, "message")?)),
+                "stream_chunk" => status(StatusUpdate::StreamChunk(str_field(data, "content")?)),
+                "tool_started" => status(StatusUpdate::ToolStarted {
+                    name: str_field(data, "name")?,
+                }),
+                "tool_completed" => status(StatusUpdate::ToolCompleted {
+                    name: str_field(data, "name")?,
+                    success: data.get("success")?.as_bool()?,
+                }),
+                "tool_result" => status(StatusUpdate::ToolResult {
+                    name: str_field(data, "name")?,
+                    preview: str_field(data, "preview")?,
+                }),
+                "job_started" => status(StatusUpdate::JobStarted {
+                    job_id: str_field(data, "job_id")?,
+                    title: str_field(data, "title")?,
+                    browse_url: str_field(data, "browse_url")?,
+                }),
+                "approval_needed" => {
+                    // Parameters arrive as a JSON-encoded string.
+                    let raw = str_field(data, "parameters").unwrap_or_default();
+                    let parameters =
+                        serde_json::from_str(&raw).unwrap_or(serde_json::Value::String(raw));
+                    status(StatusUpdate::ApprovalNeeded {
+                        request_id: str_field(data, "request_id")?,
+                        tool_name: str_field(data, "tool_name")?,
+                        description: str_field(data, "description").unwrap_or_default(),
+                        parameters,
+                    })
+                }
+                "auth_required" => status(StatusUpdate::AuthRequired {
+                    extension_name: str_field(data, "extension_name")?,
+                    instructions: str_field(data, "instructions"),
+                    auth_url: str_field(data, "auth_url"),
+                    setup_url: str_field(data, "setup_url"),
+                }),
+                "auth_completed" => status(StatusUpdate::AuthCompleted {
+                    extension_name: str_field(data, "extension_name")?,
+                    success: data.get("success")?.as_bool()?,
+                    message: str_field(data, "message").unwrap_or_default(),
+                }),
+                "image" => {
+                    use base64::Engine;
+                    let data_url = str_field(data, "data_url")?;
+                    let (_, encoded) = data_url.split_once(";base64,")?;
+                    status(StatusUpdate::Image {
+                        media_id: str_field(data, "media_id")?,
+                        mime_type: str_field(data, "mime_type")?,
+                        title: str_field(data, "title"),
+                        data: base64::engine::general_purpose::STANDARD
+                            .decode(encoded)
+                            .ok()?,
+                    })
+                }
+                "error" => Some(AttachEvent::Error(str_field(data, "message")?)),
+                // Heartbeats, job streams, canvas and config events are
+                // web-UI concerns.
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_response_and_stream_events() {
+        let frame = json!({
+            "type": "event",
+            "event_type": "response",
+            "data": { "type": "response", "content": "hi", "thread_id": "t1" }
+        });
+        match parse_server_frame(&frame) {
+            Some(AttachEvent::Response { content, thread_id }) => {
+                assert_eq!(content, "hi");
+                assert_eq!(thread_id.as_deref(), Some("t1"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let frame = json!({
+            "type": "event",
+            "event_type": "stream_chunk",
+            "data": { "type": "stream_chunk", "content": "par" }
+        });
+        assert!(matches!(
+            parse_server_frame(&frame),
+            Some(AttachEvent::Status {
+                update: StatusUpdate::StreamChunk(_),
+                thread_id: None
+            })
+        ));
+
+        let heartbeat = json!({ "type": "event", "event_type": "heartbeat", "data": {} });
+        assert!(parse_server_frame(&heartbeat).is_none());
+        assert!(parse_server_frame(&json!({ "type": "pong" })).is_none());
+    }
+
+    #[test]
+    fn test_parse_approval_decodes_parameters() {
+        let frame = json!({
+            "type": "event",
+            "event_type": "approval_needed",
+            "data": {
+                "type": "approval_needed",
+                "request_id": "abc",
+                "tool_name": "shell",
+                "description": "Run a command",
+                "parameters": "{\"command\":\"ls\"}"
+            }
+        });
+        match parse_server_frame(&frame) {
+            Some(AttachEvent::Status {
+                update: StatusUpdate::ApprovalNeeded { parameters, .. },
+                ..
+            }) => assert_eq!(parameters["command"], "ls"),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_local_commands() {
+        assert!(is_detach_command("/QUIT"));
+        assert!(is_detach_command("/shutdown"));
+        assert!(!is_detach_command("/undo"));
+        assert_eq!(approval_action("Y"), Some("approve"));
+        assert_eq!(approval_action("always"), Some("always"));
+        assert_eq!(approval_action("n"), Some("deny"));
+        assert_eq!(approval_action("yes please"), None);
+    }
+}
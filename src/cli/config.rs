@@ -7,7 +7,10 @@ use std::sync::Arc;
 
 use clap::Subcommand;
 
-use crate::settings::Settings;
+use secrecy::SecretString;
+
+use crate::secrets::{SecretsCrypto, keychain};
+use crate::settings::{KeySource, Settings};
 
 #[derive(Subcommand, Debug, Clone)]
 pub enum ConfigCommand {
@@ -41,6 +44,54 @@ pub enum ConfigCommand {
 
     /// Show the settings storage info
     Path,
+
+    /// Upgrade stored settings to the current schema
+    Migrate {
+        /// Show pending changes without writing them; exits non-zero if
+        /// any are pending
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Manage the secrets master key
+    #[command(subcommand)]
+    Key(KeyCommand),
+
+    /// Merge MCP servers and channel/extension settings with other machines
+    /// through a git repository
+    Sync {
+        /// Git working copy holding the shared config (default: CONFIG_SYNC_REPO)
+        #[arg(long)]
+        repo: Option<std::path::PathBuf>,
+
+        /// Resolve conflicting keys in favour of this side
+        #[arg(long, value_enum)]
+        prefer: Option<crate::config_sync::Prefer>,
+
+        /// Show what would change without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum KeyCommand {
+    /// Show where the master key is loaded from
+    Status,
+
+    /// Generate a new master key and re-encrypt all stored secrets under it
+    Rotate {
+        /// Store the new key in the OS keyring even if the current key
+        /// comes from SECRETS_MASTER_KEY
+        #[arg(long)]
+        to_keychain: bool,
+    },
+
+    /// Move an env-based master key into the OS keyring
+    ///
+    /// Secrets are re-encrypted under a freshly generated key that is kept
+    /// in the OS keyring (macOS Keychain, Windows DPAPI, Secret Service).
+    Migrate,
 }
 
 /// Run a config command.
@@ -50,6 +101,11 @@ pub enum ConfigCommand {
 pub async fn run_config_command(cmd: ConfigCommand) -> anyhow::Result<()> {
     let _ = dotenvy::dotenv();
 
+    // Key management talks to the secrets store directly.
+    if let ConfigCommand::Key(key_cmd) = cmd {
+        return run_key_command(key_cmd).await;
+    }
+
     // Try to connect to the DB for settings access
     let db: Option<Arc<dyn crate::db::Database>> = match connect_db().await {
         Ok(d) => Some(d),
@@ -69,7 +125,128 @@ pub async fn run_config_command(cmd: ConfigCommand) -> anyhow::Result<()> {
         ConfigCommand::Set { path, value } => set_setting(db_ref, &path, &value).await,
         ConfigCommand::Reset { path } => reset_setting(db_ref, &path).await,
         ConfigCommand::Path => show_path(db_ref.is_some()),
+        ConfigCommand::Migrate { check } => migrate_settings(db_ref, check).await,
+        ConfigCommand::Sync {
+            repo,
+            prefer,
+            dry_run,
+        } => sync_config(db_ref, repo, prefer, dry_run).await,
+        ConfigCommand::Key(_) => unreachable!("handled above"),
+    }
+}
+
+/// Run a master key command.
+async fn run_key_command(cmd: KeyCommand) -> anyhow::Result<()> {
+    match cmd {
+        KeyCommand::Status => key_status().await,
+        KeyCommand::Rotate { to_keychain } => rotate_key(to_keychain).await,
+        KeyCommand::Migrate => {
+            let config = crate::config::Config::from_env()
+                .await
+                .map_err(|e| anyhow::anyhow!("{}", e))?;
+            if config.secrets.source != KeySource::Env {
+                anyhow::bail!(
+                    "Master key is not loaded from SECRETS_MASTER_KEY (source: {:?}); nothing to migrate",
+                    config.secrets.source
+                );
+            }
+            rotate_key(true).await
+        }
+    }
+}
+
+/// Show the master key source.
+async fn key_status() -> anyhow::Result<()> {
+    let config = crate::config::Config::from_env()
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let source = match config.secrets.source {
+        KeySource::Keychain => "OS keyring",
+        KeySource::Env => "SECRETS_MASTER_KEY environment variable",
+        KeySource::None => "not configured (secrets disabled)",
+    };
+    println!("Master key source: {}", source);
+    println!(
+        "OS keyring entry:  {}",
+        if keychain::has_master_key().await {
+            "present"
+        } else {
+            "absent"
+        }
+    );
+    Ok(())
+}
+
+/// Generate a new master key, re-encrypt every stored secret, and persist
+/// the new key.
+///
+/// The new key goes to the OS keyring when the current key already lives
+/// there or `to_keychain` is set. Otherwise (or if the keyring write fails)
+/// the new key is printed so the user can update `SECRETS_MASTER_KEY`.
+async fn rotate_key(to_keychain: bool) -> anyhow::Result<()> {
+    let config = crate::config::Config::from_env()
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let current = config.secrets.master_key().ok_or_else(|| {
+        anyhow::anyhow!("No secrets master key configured. Run 'ironclaw onboard' first.")
+    })?;
+    let use_keychain = to_keychain || config.secrets.source == KeySource::Keychain;
+
+    let new_key = keychain::generate_master_key();
+    let new_key_hex = hex::encode(&new_key);
+    let new_crypto = SecretsCrypto::new(SecretString::from(new_key_hex.clone()))?;
+
+    let store =
+        super::mcp::open_secrets_store(&config, SecretsCrypto::new(current.clone())?).await?;
+
+    // Save the new key before touching the database so a keyring failure
+    // cannot strand secrets encrypted under a key nobody has.
+    let mut stored_in_keychain = false;
+    if use_keychain {
+        let previous = keychain::get_master_key().await.ok();
+        match keychain::store_master_key(&new_key).await {
+            Ok(()) => stored_in_keychain = true,
+            Err(e) => eprintln!("Warning: could not store key in OS keyring: {}", e),
+        }
+        match store.rotate_master_key(&new_crypto).await {
+            Ok(count) => println!("Re-encrypted {} secret(s) under the new master key.", count),
+            Err(e) => {
+                if stored_in_keychain {
+                    // Put the old key back; the database still uses it.
+                    let _ = match previous {
+                        Some(old) => keychain::store_master_key(&old).await,
+                        None => keychain::delete_master_key().await,
+                    };
+                }
+                return Err(anyhow::anyhow!("Key rotation failed: {}", e));
+            }
+        }
+    } else {
+        let count = store
+            .rotate_master_key(&new_crypto)
+            .await
+            .map_err(|e| anyhow::anyhow!("Key rotation failed: {}", e))?;
+        println!("Re-encrypted {} secret(s) under the new master key.", count);
+    }
+
+    if stored_in_keychain {
+        let mut bootstrap = crate::bootstrap::BootstrapConfig::load();
+        bootstrap.secrets_master_key_source = KeySource::Keychain;
+        bootstrap.save()?;
+        println!("New master key stored in the OS keyring.");
+        if config.secrets.source == KeySource::Env || std::env::var("SECRETS_MASTER_KEY").is_ok() {
+            println!(
+                "Remove SECRETS_MASTER_KEY from your environment and .env; it overrides the keyring."
+            );
+        }
+    } else {
+        println!("Update SECRETS_MASTER_KEY to the new master key:");
+        println!();
+        println!("  SECRETS_MASTER_KEY={}", new_key_hex);
+        println!();
+        println!("Secrets can no longer be decrypted with the old key.");
     }
+    Ok(())
 }
 
 /// Bootstrap a DB connection for config commands (backend-agnostic).
@@ -151,6 +328,10 @@ async fn set_setting(
 ) -> anyhow::Result<()> {
     let mut settings = load_settings(store).await;
 
+    if path == "locale.timezone" || path.starts_with("locale.channel_timezones.") {
+        crate::locale::Tz::parse(value).map_err(|e| anyhow::anyhow!("{}", e))?;
+    }
+
     settings
         .set(path, value)
         .map_err(|e| anyhow::anyhow!("{}", e))?;
@@ -196,6 +377,169 @@ async fn reset_setting(store: Option<&dyn crate::db::Database>, path: &str) -> a
     Ok(())
 }
 
+/// Three-way merge the synced configuration with the sync repository, apply
+/// the result locally and publish it.
+async fn sync_config(
+    store: Option<&dyn crate::db::Database>,
+    repo: Option<std::path::PathBuf>,
+    prefer: Option<crate::config_sync::Prefer>,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    use crate::config_sync::{self as sync, SyncRepo};
+    use crate::tools::mcp::config as mcp_config;
+
+    let dir = repo
+        .or_else(|| std::env::var_os("CONFIG_SYNC_REPO").map(std::path::PathBuf::from))
+        .ok_or_else(|| {
+            anyhow::anyhow!("No sync repository. Pass --repo <dir> or set CONFIG_SYNC_REPO.")
+        })?;
+    let repo = SyncRepo::open(&dir)?;
+    repo.pull().await?;
+
+    let mut settings = load_settings(store).await;
+    let mut servers = match store {
+        Some(store) => mcp_config::load_mcp_servers_from_db(store, DEFAULT_USER_ID).await?,
+        None => mcp_config::load_mcp_servers().await?,
+    };
+    let local = sync::snapshot(&settings.to_db_map(), &servers);
+    let base_path = sync::default_base_path();
+    let base = sync::read_map(&base_path)?;
+    let outcome = sync::merge(&base, &local, &repo.read()?, prefer);
+
+    for key in &outcome.pulled {
+        println!("  < {}", key);
+    }
+    for key in &outcome.pushed {
+        println!("  > {}", key);
+    }
+    for conflict in &outcome.conflicts {
+        let show = |v: &Option<serde_json::Value>| match v {
+            Some(v) => v.to_string(),
+            None => "(deleted)".to_string(),
+        };
+        println!("  ! {}", conflict.key);
+        println!("      local:  {}", show(&conflict.local));
+        println!("      remote: {}", show(&conflict.remote));
+    }
+    if outcome.pulled.is_empty() && outcome.pushed.is_empty() && outcome.conflicts.is_empty() {
+        println!("Configuration is in sync with {}.", dir.display());
+    }
+    if dry_run {
+        return Ok(());
+    }
+
+    if !outcome.pulled.is_empty() {
+        let (synced_settings, synced_servers) = sync::split(&outcome.local);
+        let (previous, _) = sync::split(&local);
+        for (path, value) in &synced_settings {
+            if previous.get(path) == Some(value) {
+                continue;
+            }
+            let text = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            settings
+                .set(path, &text)
+                .map_err(|e| anyhow::anyhow!("{}: {}", path, e))?;
+            if let Some(store) = store {
+                store.set_setting(DEFAULT_USER_ID, path, value).await?;
+            }
+        }
+        for path in previous
+            .keys()
+            .filter(|p| !synced_settings.contains_key(*p))
+        {
+            settings
+                .reset(path)
+                .map_err(|e| anyhow::anyhow!("{}: {}", path, e))?;
+            if let Some(store) = store {
+                store.delete_setting(DEFAULT_USER_ID, path).await?;
+            }
+        }
+        if store.is_none() {
+            settings.save()?;
+        }
+
+        if outcome.pulled.iter().any(|k| k.starts_with("mcp_servers.")) {
+            sync::apply_servers(&mut servers, synced_servers);
+            match store {
+                Some(store) => {
+                    mcp_config::save_mcp_servers_to_db(store, DEFAULT_USER_ID, &servers).await?
+                }
+                None => mcp_config::save_mcp_servers(&servers).await?,
+            }
+        }
+    }
+
+    if !outcome.pushed.is_empty() {
+        let host = hostname::get()
+            .map(|h| h.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| "unknown host".to_string());
+        repo.publish(
+            &outcome.remote,
+            &format!("Sync {} key(s) from {}", outcome.pushed.len(), host),
+        )
+        .await?;
+    }
+    sync::write_map(&base_path, &outcome.base)?;
+
+    println!(
+        "{} pulled, {} pushed, {} conflict(s).",
+        outcome.pulled.len(),
+        outcome.pushed.len(),
+        outcome.conflicts.len()
+    );
+    if !outcome.conflicts.is_empty() {
+        anyhow::bail!(
+            "{} conflicting key(s) left unchanged on both sides; rerun with --prefer local or --prefer remote",
+            outcome.conflicts.len()
+        );
+    }
+    Ok(())
+}
+
+/// Run (or with `check`, preview) settings schema migrations.
+async fn migrate_settings(
+    store: Option<&dyn crate::db::Database>,
+    check: bool,
+) -> anyhow::Result<()> {
+    use crate::settings_migrations::{latest_version, migrate_db, migrate_file};
+
+    let report = match store {
+        Some(store) => migrate_db(store, DEFAULT_USER_ID, check).await?,
+        None => migrate_file(&Settings::default_path(), check)?,
+    };
+
+    if report.is_current() {
+        println!(
+            "Settings are at the current schema version ({}).",
+            latest_version()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "Settings schema: version {} -> {}",
+        report.from_version, report.to_version
+    );
+    for migration in &report.applied {
+        println!("  {}: {}", migration.version, migration.description);
+        for change in &migration.changes {
+            println!("      {}", change);
+        }
+    }
+
+    if check {
+        anyhow::bail!(
+            "{} change(s) pending; run 'ironclaw config migrate' to apply",
+            report.change_count()
+        );
+    }
+    println!("Applied {} change(s).", report.change_count());
+    Ok(())
+}
+
 /// Show the settings storage info.
 fn show_path(has_db: bool) -> anyhow::Result<()> {
     if has_db {
@@ -262,4 +606,23 @@ mod tests {
         settings.reset("agent.name").unwrap();
         assert_eq!(settings.agent.name, "ironclaw");
     }
+
+    #[test]
+    fn test_key_command_parsing() {
+        use clap::Parser;
+
+        #[derive(Parser)]
+        struct Cli {
+            #[command(subcommand)]
+            cmd: ConfigCommand,
+        }
+
+        let cli = Cli::try_parse_from(["config", "key", "rotate", "--to-keychain"]).unwrap();
+        assert!(matches!(
+            cli.cmd,
+            ConfigCommand::Key(KeyCommand::Rotate { to_keychain: true })
+        ));
+        let cli = Cli::try_parse_from(["config", "key", "migrate"]).unwrap();
+        assert!(matches!(cli.cmd, ConfigCommand::Key(KeyCommand::Migrate)));
+    }
 }
@@ -0,0 +1,238 @@
+//! Contact CLI commands.
+//!
+//! Link channel identities (Telegram, Slack, email, ...) to one person.
+
+use clap::Subcommand;
+
+use crate::contacts::{Contact, ContactStore, Identity};
+
+/// Contacts subcommands.
+#[derive(Subcommand, Debug, Clone)]
+pub enum ContactsCommand {
+    /// List contacts and their linked identities
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show one contact
+    Show {
+        /// Contact id or name
+        person: String,
+    },
+
+    /// Link a channel identity to a person (creates the contact if needed)
+    Link {
+        /// Contact id or name (e.g., alice)
+        person: String,
+
+        /// Identity as channel:id (e.g., telegram:12345, email:alice@example.com)
+        identity: String,
+    },
+
+    /// Unlink a channel identity from its contact
+    Unlink {
+        /// Identity as channel:id
+        identity: String,
+    },
+
+    /// Delete a contact (its identities become separate users again)
+    Remove {
+        /// Contact id or name
+        person: String,
+    },
+
+    /// Suggest identities that probably belong to the same person
+    Suggest {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Stop suggesting that two identities belong together
+    Dismiss {
+        /// First identity as channel:id
+        a: String,
+
+        /// Second identity as channel:id
+        b: String,
+    },
+}
+
+/// Run contacts CLI command.
+pub fn run_contacts_command(cmd: ContactsCommand) -> Result<(), String> {
+    run_contacts_command_with_store(&ContactStore::new(), cmd)
+}
+
+/// Run contacts CLI command with a given store (for testing).
+pub fn run_contacts_command_with_store(
+    store: &ContactStore,
+    cmd: ContactsCommand,
+) -> Result<(), String> {
+    match cmd {
+        ContactsCommand::List { json } => run_list(store, json),
+        ContactsCommand::Show { person } => match store.get(&person).map_err(|e| e.to_string())? {
+            Some(contact) => {
+                print_contact(&contact);
+                Ok(())
+            }
+            None => Err(format!("Contact not found: {}", person)),
+        },
+        ContactsCommand::Link { person, identity } => {
+            let identity = Identity::parse(&identity).map_err(|e| e.to_string())?;
+            let label = identity.to_string();
+            let contact = store.link(&person, identity).map_err(|e| e.to_string())?;
+            println!(
+                "Linked {} to {} ({} identities).",
+                label,
+                contact.id,
+                contact.identities.len()
+            );
+            Ok(())
+        }
+        ContactsCommand::Unlink { identity } => {
+            let identity = Identity::parse(&identity).map_err(|e| e.to_string())?;
+            match store.unlink(&identity).map_err(|e| e.to_string())? {
+                Some(contact) => {
+                    println!("Unlinked {} from {}.", identity, contact.id);
+                    Ok(())
+                }
+                None => Err(format!("{} is not linked to any contact", identity)),
+            }
+        }
+        ContactsCommand::Remove { person } => {
+            let contact = store.remove(&person).map_err(|e| e.to_string())?;
+            println!("Removed contact {}.", contact.id);
+            Ok(())
+        }
+        ContactsCommand::Suggest { json } => run_suggest(store, json),
+        ContactsCommand::Dismiss { a, b } => {
+            let a = Identity::parse(&a).map_err(|e| e.to_string())?;
+            let b = Identity::parse(&b).map_err(|e| e.to_string())?;
+            store.dismiss(&a, &b).map_err(|e| e.to_string())?;
+            println!("Will no longer suggest linking {} and {}.", a, b);
+            Ok(())
+        }
+    }
+}
+
+fn run_list(store: &ContactStore, json: bool) -> Result<(), String> {
+    let contacts = store.list().map_err(|e| e.to_string())?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&contacts).map_err(|e| e.to_string())?
+        );
+        return Ok(());
+    }
+
+    if contacts.is_empty() {
+        println!("No contacts. Link identities with: ironclaw contacts link <person> <channel:id>");
+        return Ok(());
+    }
+
+    println!("Contacts ({}):", contacts.len());
+    for c in &contacts {
+        let identities: Vec<String> = c.identities.iter().map(|i| i.to_string()).collect();
+        println!("  {}  {}  {}", c.id, c.name, identities.join(", "));
+    }
+    Ok(())
+}
+
+fn print_contact(contact: &Contact) {
+    println!("Contact: {} ({})", contact.name, contact.id);
+    println!("User id: {}", contact.user_id);
+    println!("Identities:");
+    for i in &contact.identities {
+        let mut extra = Vec::new();
+        if let Some(ref u) = i.username {
+            extra.push(format!("username={}", u));
+        }
+        if let Some(ref n) = i.display_name {
+            extra.push(format!("name={}", n));
+        }
+        println!("  {}  {}", i, extra.join(", "));
+    }
+}
+
+fn run_suggest(store: &ContactStore, json: bool) -> Result<(), String> {
+    let suggestions = store.suggestions().map_err(|e| e.to_string())?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&suggestions).map_err(|e| e.to_string())?
+        );
+        return Ok(());
+    }
+
+    if suggestions.is_empty() {
+        println!("No link suggestions.");
+        return Ok(());
+    }
+
+    println!("Possible matches ({}):", suggestions.len());
+    for s in &suggestions {
+        println!("  {}  <->  {}  ({})", s.a, s.b, s.reason);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn test_store() -> (ContactStore, TempDir) {
+        let dir = TempDir::new().unwrap();
+        let store = ContactStore::with_base_dir(dir.path().to_path_buf());
+        (store, dir)
+    }
+
+    #[test]
+    fn test_link_then_unlink() {
+        let (store, _) = test_store();
+        run_contacts_command_with_store(
+            &store,
+            ContactsCommand::Link {
+                person: "alice".to_string(),
+                identity: "telegram:42".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(store.list().unwrap().len(), 1);
+
+        run_contacts_command_with_store(
+            &store,
+            ContactsCommand::Unlink {
+                identity: "telegram:42".to_string(),
+            },
+        )
+        .unwrap();
+        assert!(store.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_identity_and_unknown_contact() {
+        let (store, _) = test_store();
+        let err = run_contacts_command_with_store(
+            &store,
+            ContactsCommand::Link {
+                person: "alice".to_string(),
+                identity: "telegram".to_string(),
+            },
+        )
+        .unwrap_err();
+        assert!(err.contains("channel:id"));
+
+        let result = run_contacts_command_with_store(
+            &store,
+            ContactsCommand::Show {
+                person: "nobody".to_string(),
+            },
+        );
+        assert!(result.is_err());
+    }
+}
@@ -1,7 +1,14 @@
 //! Cron/routine management CLI commands.
 
+use std::path::PathBuf;
+
 use clap::Subcommand;
 
+use crate::agent::report::{
+    ActivityReport, ReportFormat, ReportSection, WEEKLY_REPORT_ROUTINE, WEEKLY_REPORT_SCHEDULE,
+    weekly_report_routine,
+};
+
 #[derive(Subcommand, Debug, Clone)]
 pub enum CronCommand {
     /// List all scheduled routines
@@ -44,6 +51,48 @@ pub enum CronCommand {
         /// Routine name
         name: String,
     },
+
+    /// Export an activity report (tools, jobs, costs, memory, conversations)
+    Report {
+        /// Number of days to cover
+        #[arg(short, long, default_value = "7")]
+        days: u32,
+
+        /// Output format (markdown, html or pdf)
+        #[arg(short, long, default_value = "markdown")]
+        format: ReportFormat,
+
+        /// Comma-separated sections (default: all)
+        #[arg(long)]
+        sections: Option<String>,
+
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Install the bundled weekly activity report routine
+    AddReport {
+        /// Cron schedule (with seconds field)
+        #[arg(long, default_value = WEEKLY_REPORT_SCHEDULE)]
+        schedule: String,
+
+        /// IANA time zone for the schedule (e.g., Europe/Berlin)
+        #[arg(long)]
+        timezone: Option<String>,
+
+        /// Channel to deliver the report on (default: all channels)
+        #[arg(long)]
+        channel: Option<String>,
+
+        /// Comma-separated sections (default: all)
+        #[arg(long)]
+        sections: Option<String>,
+
+        /// Format of the saved copy (markdown, html or pdf)
+        #[arg(long, default_value = "markdown")]
+        format: ReportFormat,
+    },
 }
 
 /// Run a cron command.
@@ -55,6 +104,19 @@ pub async fn run_cron_command(cmd: CronCommand) -> anyhow::Result<()> {
         CronCommand::Disable { name } => toggle_routine(&name, false).await,
         CronCommand::History { name, limit } => show_history(&name, limit).await,
         CronCommand::Run { name } => trigger_routine(&name).await,
+        CronCommand::Report {
+            days,
+            format,
+            sections,
+            output,
+        } => export_report(days, format, sections.as_deref(), output).await,
+        CronCommand::AddReport {
+            schedule,
+            timezone,
+            channel,
+            sections,
+            format,
+        } => add_report_routine(&schedule, timezone, channel, sections.as_deref(), format).await,
     }
 }
 
@@ -87,13 +149,17 @@ async fn list_routines(enabled_only: bool) -> anyhow::Result<()> {
             "disabled"
         };
         let trigger = match &routine.trigger {
-            crate::agent::routine::Trigger::Cron { schedule } => format!("cron({})", schedule),
+            crate::agent::routine::Trigger::Cron { schedule, timezone } => match timezone {
+                Some(tz) => format!("cron({} {})", schedule, tz),
+                None => format!("cron({})", schedule),
+            },
             crate::agent::routine::Trigger::Event { channel, pattern } => {
                 format!("event({}:{})", channel.as_deref().unwrap_or("*"), pattern)
             }
             crate::agent::routine::Trigger::Webhook { path, .. } => {
                 format!("webhook({})", path.as_deref().unwrap_or("/"))
             }
+            crate::agent::routine::Trigger::File { path, .. } => format!("file({})", path),
             crate::agent::routine::Trigger::Manual => "manual".to_string(),
         };
 
@@ -220,6 +286,82 @@ async fn trigger_routine(name: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn parse_sections(sections: Option<&str>) -> anyhow::Result<Vec<ReportSection>> {
+    match sections {
+        Some(s) => ReportSection::parse_list(s).map_err(|e| anyhow::anyhow!(e)),
+        None => Ok(ReportSection::ALL.to_vec()),
+    }
+}
+
+async fn export_report(
+    days: u32,
+    format: ReportFormat,
+    sections: Option<&str>,
+    output: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let sections = parse_sections(sections)?;
+    if format.is_binary() && output.is_none() {
+        anyhow::bail!("{} reports need --output <file>", format.as_str());
+    }
+    let db = connect_db().await?;
+
+    let report = ActivityReport::generate(
+        db.as_ref(),
+        None,
+        chrono::Duration::days(i64::from(days.max(1))),
+        sections,
+    )
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to collect activity: {}", e))?;
+    let rendered = report.render(format);
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, rendered)?;
+            println!("Report written to {}", path.display());
+        }
+        None => print!("{}", String::from_utf8_lossy(&rendered)),
+    }
+
+    Ok(())
+}
+
+async fn add_report_routine(
+    schedule: &str,
+    timezone: Option<String>,
+    channel: Option<String>,
+    sections: Option<&str>,
+    format: ReportFormat,
+) -> anyhow::Result<()> {
+    let sections = parse_sections(sections)?;
+    let routine = weekly_report_routine("default", schedule, timezone, channel, sections, format)
+        .map_err(|e| anyhow::anyhow!(e))?;
+
+    let db = connect_db().await?;
+    if db
+        .get_routine_by_name("default", WEEKLY_REPORT_ROUTINE)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to get routine: {}", e))?
+        .is_some()
+    {
+        anyhow::bail!(
+            "Routine '{}' already exists (remove it or edit it with the routine tools)",
+            WEEKLY_REPORT_ROUTINE
+        );
+    }
+
+    db.create_routine(&routine)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to create routine: {}", e))?;
+
+    println!("Created routine '{}'.", routine.name);
+    if let Some(next) = routine.next_fire_at {
+        println!("  Next run: {}", next);
+    }
+
+    Ok(())
+}
+
 async fn connect_db() -> anyhow::Result<std::sync::Arc<dyn crate::db::Database>> {
     let _ = dotenvy::dotenv();
     let config = crate::config::Config::from_env()
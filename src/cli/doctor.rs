@@ -1,11 +1,22 @@
 //! Doctor diagnostics CLI command.
 //!
-//! Performs comprehensive health checks and reports actionable fixes.
+//! Performs comprehensive health checks and reports actionable fixes. With
+//! `--bundle` it also packs the report, redacted settings and environment,
+//! recent logs and errors, and local crash reports into a `.tar.gz` that can
+//! be attached to a bug report.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use crate::media::accel::{self, Accelerator, AcceleratorStatus};
+use crate::safety::log_redaction::LogRedactor;
 use crate::settings::Settings;
 
+/// Log lines copied into a bundle.
+const BUNDLE_LOG_LINES: usize = 2000;
+
+/// Error and warning lines collected into `errors.txt`.
+const BUNDLE_ERROR_LINES: usize = 200;
+
 /// Diagnostic check result.
 struct Check {
     name: &'static str,
@@ -18,6 +29,8 @@ enum CheckStatus {
     Ok,
     Warning,
     Error,
+    /// Not applicable on this host.
+    Skipped,
 }
 
 impl Check {
@@ -48,17 +61,37 @@ impl Check {
         }
     }
 
+    fn skipped(name: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Skipped,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
     fn icon(&self) -> &'static str {
         match self.status {
             CheckStatus::Ok => "[OK]",
             CheckStatus::Warning => "[WARN]",
             CheckStatus::Error => "[ERR]",
+            CheckStatus::Skipped => "[--]",
         }
     }
 }
 
-/// Run comprehensive diagnostics.
-pub async fn run_doctor_command() -> anyhow::Result<()> {
+/// Run comprehensive diagnostics, or only the accelerator report with `ml`.
+/// With `bundle`, also write a diagnostics archive to `output`.
+pub async fn run_doctor_command(
+    ml: bool,
+    bundle: bool,
+    output: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    if ml {
+        run_ml_diagnostics();
+        return Ok(());
+    }
+
     println!("IronClaw Doctor");
     println!("===============\n");
     println!("Running diagnostics...\n");
@@ -99,17 +132,20 @@ pub async fn run_doctor_command() -> anyhow::Result<()> {
     // Print results
     let mut errors = 0;
     let mut warnings = 0;
+    let mut report = String::new();
 
     for check in &checks {
-        println!("  {} {}: {}", check.icon(), check.name, check.message);
+        let mut lines = format!("  {} {}: {}\n", check.icon(), check.name, check.message);
         if let Some(ref fix) = check.fix {
-            println!("       Fix: {}", fix);
+            lines.push_str(&format!("       Fix: {}\n", fix));
         }
+        print!("{}", lines);
+        report.push_str(&lines);
 
         match check.status {
             CheckStatus::Error => errors += 1,
             CheckStatus::Warning => warnings += 1,
-            CheckStatus::Ok => {}
+            CheckStatus::Ok | CheckStatus::Skipped => {}
         }
     }
 
@@ -130,9 +166,190 @@ pub async fn run_doctor_command() -> anyhow::Result<()> {
         println!("\nAll checks passed! IronClaw is ready to use.");
     }
 
+    if bundle {
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        let path = output.unwrap_or_else(|| {
+            PathBuf::from(format!(
+                "ironclaw-diagnostics-{}.tar.gz",
+                chrono::Utc::now().format("%Y%m%dT%H%M%S")
+            ))
+        });
+        let entries = collect_bundle(&settings, &report, &home.join(".ironclaw"));
+        crate::util::write_tar_gz(&path, "ironclaw-diagnostics", &entries)?;
+        println!(
+            "\nDiagnostics bundle written to {} ({} files).",
+            path.display(),
+            entries.len()
+        );
+        println!("Secrets were redacted, but please review it before sharing.");
+    }
+
     Ok(())
 }
 
+// --- Diagnostics bundle ---
+
+/// Whether a settings or environment key names a credential.
+fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    ["key", "token", "secret", "password", "passwd", "credential"]
+        .iter()
+        .any(|word| key.contains(word))
+}
+
+/// Replace credential-named values with a marker and run the log redactor
+/// over every remaining string.
+fn redact_json(value: &mut serde_json::Value, redactor: &LogRedactor) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if is_sensitive_key(key) && !v.is_null() && !v.is_boolean() {
+                    *v = serde_json::Value::String("[REDACTED]".to_string());
+                } else {
+                    redact_json(v, redactor);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for v in items {
+                redact_json(v, redactor);
+            }
+        }
+        serde_json::Value::String(s) => *s = redactor.redact_owned(s),
+        _ => {}
+    }
+}
+
+/// Environment variables, with credential-named values replaced.
+fn redacted_env(vars: impl Iterator<Item = (String, String)>, redactor: &LogRedactor) -> String {
+    let mut vars: Vec<_> = vars.collect();
+    vars.sort();
+    vars.into_iter()
+        .map(|(k, v)| {
+            let v = if is_sensitive_key(&k) {
+                "[REDACTED]".to_string()
+            } else {
+                redactor.redact_owned(&v)
+            };
+            format!("{}={}\n", k, v)
+        })
+        .collect()
+}
+
+/// Last `n` lines of a file, if it exists.
+fn tail_lines(path: &Path, n: usize) -> Option<Vec<String>> {
+    let text = std::fs::read_to_string(path).ok()?;
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.len().saturating_sub(n);
+    Some(lines[start..].iter().map(|l| l.to_string()).collect())
+}
+
+/// Gather the bundle's files from the data directory `data_dir`.
+fn collect_bundle(settings: &Settings, report: &str, data_dir: &Path) -> Vec<(String, Vec<u8>)> {
+    let redactor = LogRedactor::new();
+    let mut entries = Vec::new();
+
+    let manifest = serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "created_at": chrono::Utc::now().to_rfc3339(),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "family": std::env::consts::FAMILY,
+        "features": {
+            "postgres": cfg!(feature = "postgres"),
+            "libsql": cfg!(feature = "libsql"),
+        },
+    });
+    entries.push((
+        "manifest.json".to_string(),
+        serde_json::to_vec_pretty(&manifest).unwrap_or_default(),
+    ));
+    entries.push(("doctor.txt".to_string(), report.as_bytes().to_vec()));
+
+    let mut config = serde_json::to_value(settings).unwrap_or_default();
+    redact_json(&mut config, &redactor);
+    entries.push((
+        "settings.json".to_string(),
+        serde_json::to_vec_pretty(&config).unwrap_or_default(),
+    ));
+    entries.push((
+        "env.txt".to_string(),
+        redacted_env(std::env::vars(), &redactor).into_bytes(),
+    ));
+
+    if let Some(lines) = tail_lines(
+        &data_dir.join("logs").join("ironclaw.log"),
+        BUNDLE_LOG_LINES,
+    ) {
+        let errors: Vec<&String> = lines
+            .iter()
+            .filter(|l| l.contains("ERROR") || l.contains("WARN") || l.contains("panicked"))
+            .collect();
+        let start = errors.len().saturating_sub(BUNDLE_ERROR_LINES);
+        let errors: String = errors[start..]
+            .iter()
+            .map(|l| format!("{}\n", redactor.redact(l)))
+            .collect();
+        let log: String = lines
+            .iter()
+            .map(|l| format!("{}\n", redactor.redact(l)))
+            .collect();
+        entries.push(("logs/ironclaw.log".to_string(), log.into_bytes()));
+        entries.push(("errors.txt".to_string(), errors.into_bytes()));
+    }
+
+    for path in crate::crash::list_reports(&data_dir.join("crashes")) {
+        if let (Some(name), Ok(text)) = (
+            path.file_name().and_then(|n| n.to_str()),
+            std::fs::read_to_string(&path),
+        ) {
+            entries.push((
+                format!("crashes/{}", name),
+                redactor.redact_owned(&text).into_bytes(),
+            ));
+        }
+    }
+
+    entries
+}
+
+/// Report which inference accelerators this host offers.
+fn run_ml_diagnostics() {
+    println!("IronClaw Doctor (ML)");
+    println!("====================\n");
+    println!("Probing accelerators...\n");
+
+    let statuses = accel::detect();
+    for status in &statuses {
+        let check = check_accelerator(status);
+        println!("  {} {}: {}", check.icon(), check.name, check.message);
+        if let Some(ref fix) = check.fix {
+            println!("       Fix: {}", fix);
+        }
+    }
+
+    let present: Vec<&str> = statuses
+        .iter()
+        .filter(|s| s.present)
+        .map(|s| s.accelerator.as_str())
+        .collect();
+    println!();
+    println!("Available accelerators: {}", present.join(", "));
+}
+
+fn check_accelerator(status: &AcceleratorStatus) -> Check {
+    let name = match status.accelerator {
+        Accelerator::Cpu => "CPU",
+        Accelerator::Metal => "Metal",
+        Accelerator::Cuda => "CUDA",
+    };
+    if status.present {
+        Check::ok(name, format!("Available ({})", status.detail))
+    } else {
+        Check::skipped(name, format!("Not available ({})", status.detail))
+    }
+}
+
 fn check_rust_version() -> Check {
     let version = env!("CARGO_PKG_VERSION");
     Check::ok("Version", format!("IronClaw v{}", version))
@@ -465,10 +682,113 @@ mod tests {
         assert!(matches!(c.status, CheckStatus::Ok | CheckStatus::Warning));
     }
 
+    #[test]
+    fn test_check_accelerator() {
+        let status = AcceleratorStatus {
+            accelerator: Accelerator::Cuda,
+            present: true,
+            detail: "NVIDIA L4".to_string(),
+        };
+        let c = check_accelerator(&status);
+        assert_eq!(c.name, "CUDA");
+        assert!(matches!(c.status, CheckStatus::Ok));
+        assert!(c.fix.is_none());
+
+        let cpu = accel::detect().remove(0);
+        assert!(matches!(check_accelerator(&cpu).status, CheckStatus::Ok));
+    }
+
     #[test]
     fn test_icon_values() {
         assert_eq!(Check::ok("t", "m").icon(), "[OK]");
         assert_eq!(Check::warn("t", "m", "f").icon(), "[WARN]");
         assert_eq!(Check::error("t", "m", "f").icon(), "[ERR]");
+        assert_eq!(Check::skipped("t", "m").icon(), "[--]");
+    }
+
+    #[test]
+    fn test_redact_json_strips_credentials() {
+        let mut value = serde_json::json!({
+            "openai_api_key": "sk-live-123",
+            "database_url": "postgres://bob:hunter2@db:5432/ironclaw",
+            "nested": { "auth_token": "abc", "model": "gpt-4o" },
+            "gateway_token_enabled": true,
+        });
+        redact_json(&mut value, &LogRedactor::new());
+        assert_eq!(value["openai_api_key"], "[REDACTED]");
+        assert_eq!(value["nested"]["auth_token"], "[REDACTED]");
+        assert_eq!(value["nested"]["model"], "gpt-4o");
+        assert_eq!(value["gateway_token_enabled"], true);
+        assert!(!value["database_url"].as_str().unwrap().contains("hunter2"));
+
+        let env = redacted_env(
+            vec![
+                ("GATEWAY_AUTH_TOKEN".to_string(), "s3cret".to_string()),
+                ("GATEWAY_PORT".to_string(), "3000".to_string()),
+            ]
+            .into_iter(),
+            &LogRedactor::new(),
+        );
+        assert!(env.contains("GATEWAY_AUTH_TOKEN=[REDACTED]"));
+        assert!(env.contains("GATEWAY_PORT=3000"));
+    }
+
+    #[test]
+    fn test_bundle_archive_round_trip() {
+        use std::io::Read;
+
+        let dir = tempfile::tempdir().unwrap();
+        let data_dir = dir.path().join(".ironclaw");
+        std::fs::create_dir_all(data_dir.join("logs")).unwrap();
+        std::fs::write(
+            data_dir.join("logs").join("ironclaw.log"),
+            "INFO started\nERROR db failed: postgres://bob:hunter2@db/x\nINFO done\n",
+        )
+        .unwrap();
+        let report = crate::crash::CrashReport {
+            version: "0.0.0".into(),
+            timestamp: String::new(),
+            thread: "main".into(),
+            message: "boom".into(),
+            location: None,
+            active: vec![],
+            backtrace: String::new(),
+            os: "linux".into(),
+            arch: "x86_64".into(),
+        };
+        crate::crash::write_report(&data_dir.join("crashes"), &report).unwrap();
+
+        let entries = collect_bundle(&Settings::default(), "  [OK] Version\n", &data_dir);
+        let names: Vec<&str> = entries.iter().map(|(n, _)| n.as_str()).collect();
+        for expected in [
+            "manifest.json",
+            "doctor.txt",
+            "settings.json",
+            "env.txt",
+            "logs/ironclaw.log",
+            "errors.txt",
+        ] {
+            assert!(names.contains(&expected), "missing {expected}");
+        }
+        assert!(names.iter().any(|n| n.starts_with("crashes/crash-")));
+        let errors = &entries.iter().find(|(n, _)| n == "errors.txt").unwrap().1;
+        let errors = String::from_utf8_lossy(errors);
+        assert!(errors.contains("db failed") && !errors.contains("hunter2"));
+        assert!(!errors.contains("INFO"));
+
+        let archive = dir.path().join("bundle.tar.gz");
+        crate::util::write_tar_gz(&archive, "ironclaw-diagnostics", &entries).unwrap();
+        let mut tar = Vec::new();
+        flate2::read::GzDecoder::new(std::fs::File::open(&archive).unwrap())
+            .read_to_end(&mut tar)
+            .unwrap();
+        assert_eq!(tar.len() % 512, 0);
+        let header = &tar[..512];
+        assert!(header.starts_with(b"ironclaw-diagnostics/manifest.json"));
+        let stored = std::str::from_utf8(&header[148..154]).unwrap();
+        let mut check = header.to_vec();
+        check[148..156].fill(b' ');
+        let sum: u64 = check.iter().map(|&b| u64::from(b)).sum();
+        assert_eq!(u64::from_str_radix(stored, 8).unwrap(), sum);
     }
 }
@@ -1,11 +1,19 @@
 //! Gateway management CLI commands.
 //!
-//! Start, stop, and check status of the web gateway.
+//! Start, stop, and check status of the web gateway, export its API
+//! description as an OpenAPI spec or a generated client SDK, and manage the
+//! scoped API keys used by third-party integrations.
 
 use std::path::PathBuf;
 
 use clap::Subcommand;
 
+use crate::channels::web::integrations::{
+    DEFAULT_RATE_PER_MINUTE, IntegrationKeyStore, IntegrationScope,
+};
+use crate::channels::web::openapi::openapi_spec;
+use crate::channels::web::sdk::{SdkLanguage, generate_sdk};
+
 #[derive(Subcommand, Debug, Clone)]
 pub enum GatewayCommand {
     /// Start the web gateway
@@ -24,6 +32,60 @@ pub enum GatewayCommand {
 
     /// Show gateway status
     Status,
+
+    /// Print the gateway OpenAPI spec (same document as GET /openapi.json)
+    Openapi {
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Generate a typed client SDK for the gateway API
+    Sdk {
+        /// Target language
+        #[arg(short, long, value_enum)]
+        lang: SdkLanguage,
+
+        /// Output file (defaults to ironclaw-client.ts / ironclaw_client.py)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Manage scoped API keys for integrations (Zapier, n8n, ...)
+    #[command(subcommand)]
+    Keys(GatewayKeysCommand),
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum GatewayKeysCommand {
+    /// Create a key; the token is printed once
+    Create {
+        /// Label for the key (e.g. the service using it)
+        name: String,
+
+        /// Allowed action: space:<name> or routine:<name> (repeatable)
+        #[arg(short, long = "scope", required = true)]
+        scopes: Vec<String>,
+
+        /// Requests allowed per minute
+        #[arg(long, default_value_t = DEFAULT_RATE_PER_MINUTE)]
+        rate: u32,
+    },
+
+    /// List keys
+    List,
+
+    /// Revoke a key (it stays listed but is refused)
+    Revoke {
+        /// Key ID
+        id: String,
+    },
+
+    /// Delete a key
+    Delete {
+        /// Key ID
+        id: String,
+    },
 }
 
 /// Run a gateway command.
@@ -32,7 +94,118 @@ pub async fn run_gateway_command(cmd: GatewayCommand) -> anyhow::Result<()> {
         GatewayCommand::Start { port, daemon } => start_gateway(port, daemon).await,
         GatewayCommand::Stop => stop_gateway().await,
         GatewayCommand::Status => gateway_status().await,
+        GatewayCommand::Openapi { output } => export_openapi(output),
+        GatewayCommand::Sdk { lang, output } => generate_client_sdk(lang, output),
+        GatewayCommand::Keys(cmd) => run_keys_command(cmd).await,
+    }
+}
+
+async fn run_keys_command(cmd: GatewayKeysCommand) -> anyhow::Result<()> {
+    let _ = dotenvy::dotenv();
+    let config = crate::config::Config::from_env()
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let db = crate::db::connect_from_config(&config.database)
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let user_id = config
+        .channels
+        .gateway
+        .as_ref()
+        .map_or("default", |g| g.user_id.as_str());
+    let keys = IntegrationKeyStore::new(db, user_id);
+
+    match cmd {
+        GatewayKeysCommand::Create { name, scopes, rate } => {
+            let scopes = scopes
+                .iter()
+                .map(|s| IntegrationScope::parse(s))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| anyhow::anyhow!(e))?;
+            let (key, token) = keys.create(&name, scopes, rate).await?;
+            println!("Created integration key '{}' (ID: {})", key.name, key.id);
+            println!("  Scopes: {}", scope_list(&key.scopes));
+            println!("  Rate: {}/min", key.rate_per_minute);
+            println!("\nToken (shown only once):\n  {}", token);
+            println!("\nSend it as 'Authorization: Bearer <token>' to:");
+            println!("  POST /api/integrations/spaces/<space>");
+            println!("  POST /api/integrations/routines/<name>/trigger");
+        }
+        GatewayKeysCommand::List => {
+            let list = keys.list().await?;
+            if list.is_empty() {
+                println!("No integration keys.");
+                return Ok(());
+            }
+            println!(
+                "{:<10} {:<20} {:<8} {:<10} {:<20} SCOPES",
+                "ID", "NAME", "RATE", "STATUS", "LAST USED"
+            );
+            for key in list {
+                println!(
+                    "{:<10} {:<20} {:<8} {:<10} {:<20} {}",
+                    key.id,
+                    key.name,
+                    format!("{}/min", key.rate_per_minute),
+                    if key.is_revoked() {
+                        "revoked"
+                    } else {
+                        "active"
+                    },
+                    key.last_used_at
+                        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_else(|| "never".to_string()),
+                    scope_list(&key.scopes),
+                );
+            }
+        }
+        GatewayKeysCommand::Revoke { id } => {
+            if !keys.revoke(&id).await? {
+                anyhow::bail!("Integration key '{}' not found", id);
+            }
+            println!("Revoked integration key {}", id);
+        }
+        GatewayKeysCommand::Delete { id } => {
+            if !keys.delete(&id).await? {
+                anyhow::bail!("Integration key '{}' not found", id);
+            }
+            println!("Deleted integration key {}", id);
+        }
+    }
+    Ok(())
+}
+
+fn scope_list(scopes: &[IntegrationScope]) -> String {
+    scopes
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn export_openapi(output: Option<PathBuf>) -> anyhow::Result<()> {
+    let spec = serde_json::to_string_pretty(&openapi_spec())?;
+    match output {
+        Some(path) => {
+            std::fs::write(&path, spec)?;
+            println!("Wrote OpenAPI spec to {}", path.display());
+        }
+        None => println!("{}", spec),
+    }
+    Ok(())
+}
+
+fn generate_client_sdk(lang: SdkLanguage, output: Option<PathBuf>) -> anyhow::Result<()> {
+    let code = generate_sdk(&openapi_spec(), lang);
+    let path = output.unwrap_or_else(|| PathBuf::from(lang.default_file_name()));
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
     }
+    std::fs::write(&path, code)?;
+    println!("Generated {:?} client at {}", lang, path.display());
+    Ok(())
 }
 
 async fn start_gateway(port: u16, daemon: bool) -> anyhow::Result<()> {
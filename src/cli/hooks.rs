@@ -1,7 +1,12 @@
 //! Hooks management CLI commands.
 
+use std::io::Write;
+
 use clap::Subcommand;
 
+use crate::extensions::clawhub::{ClawHubClient, PackageType};
+use crate::hooks::{HookPack, HookPackStore, PackVerification};
+
 #[derive(Subcommand, Debug, Clone)]
 pub enum HooksCommand {
     /// List registered hooks
@@ -66,6 +71,41 @@ pub enum HooksCommand {
         #[arg(short, long)]
         r#type: String,
     },
+
+    /// Search ClawHub for hook packs and automations
+    Search {
+        /// Search query (e.g. "pii", "github")
+        query: String,
+    },
+
+    /// Install a hook pack or automation from ClawHub
+    Install {
+        /// Package name
+        package: String,
+
+        /// Version to install (default: latest)
+        #[arg(long)]
+        version: Option<String>,
+
+        /// Install without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// List installed hook packs
+    Packs,
+
+    /// Check installed packs against the hashes recorded at install
+    Verify {
+        /// Pack name (default: all installed packs)
+        name: Option<String>,
+    },
+
+    /// Uninstall a hook pack and the routines it created
+    Uninstall {
+        /// Pack name
+        name: String,
+    },
 }
 
 /// Run a hooks command.
@@ -83,11 +123,25 @@ pub async fn run_hooks_command(cmd: HooksCommand) -> anyhow::Result<()> {
         HooksCommand::Remove { name, r#type } => remove_hook(name, r#type).await,
         HooksCommand::Enable { name, r#type } => toggle_hook(name, r#type, true).await,
         HooksCommand::Disable { name, r#type } => toggle_hook(name, r#type, false).await,
+        HooksCommand::Search { query } => search_packs(&query).await,
+        HooksCommand::Install {
+            package,
+            version,
+            yes,
+        } => install_pack(&package, version.as_deref(), yes).await,
+        HooksCommand::Packs => list_packs(),
+        HooksCommand::Verify { name } => verify_packs(name.as_deref()),
+        HooksCommand::Uninstall { name } => uninstall_pack(&name).await,
     }
 }
 
 async fn list_hooks(type_filter: Option<String>) -> anyhow::Result<()> {
     let engine = crate::hooks::HookEngine::new();
+    crate::hooks::register_installed_packs(
+        &engine,
+        &HookPackStore::new(HookPackStore::default_dir()),
+    )
+    .await;
 
     let hooks = engine.list_hooks().await;
 
@@ -203,6 +257,213 @@ async fn toggle_hook(name: String, hook_type: String, enabled: bool) -> anyhow::
     Ok(())
 }
 
+async fn search_packs(query: &str) -> anyhow::Result<()> {
+    let client = ClawHubClient::from_env();
+    let mut packages = Vec::new();
+    for package_type in [PackageType::HookPack, PackageType::Automation] {
+        let results = client
+            .search(query, Some(package_type), None)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        packages.extend(results.packages);
+    }
+
+    if packages.is_empty() {
+        println!("No hook packs or automations match '{}'.", query);
+        return Ok(());
+    }
+    for pkg in &packages {
+        let verified = if pkg.verified { " (verified)" } else { "" };
+        println!(
+            "  {} {} [{}]{}",
+            pkg.name, pkg.version, pkg.package_type, verified
+        );
+        println!("    {}", pkg.description);
+    }
+    println!("\nInstall with: ironclaw hooks install <name>");
+    Ok(())
+}
+
+/// Download, verify, review and install a pack. Routines are created
+/// before the manifest is stored, so a failure leaves nothing behind.
+async fn install_pack(package: &str, version: Option<&str>, yes: bool) -> anyhow::Result<()> {
+    let downloaded = ClawHubClient::from_env()
+        .download(package, version)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))?;
+    if !downloaded.package_type.is_manifest() {
+        anyhow::bail!(
+            "'{}' is a {} package, not a hook pack or automation",
+            package,
+            downloaded.package_type
+        );
+    }
+
+    let pack = HookPack::parse(&downloaded.data).map_err(|e| anyhow::anyhow!("{}", e))?;
+    if pack.name != downloaded.name {
+        anyhow::bail!(
+            "Manifest is named '{}' but the package is '{}'",
+            pack.name,
+            downloaded.name
+        );
+    }
+
+    println!("{} {}", pack.name, downloaded.version);
+    if !pack.description.is_empty() {
+        println!("  {}", pack.description);
+    }
+    match downloaded.sha256 {
+        Some(ref hash) => println!("  Checksum verified: {}", hash),
+        None => println!("  Warning: the registry published no checksum for this version."),
+    }
+    println!("\nThis pack will install:");
+    for line in pack.review() {
+        println!("  {}", line);
+    }
+    if !yes && !confirm("Install it?")? {
+        anyhow::bail!("Install cancelled; nothing was changed.");
+    }
+
+    let routines = pack.to_routines("default");
+    let mut created = Vec::new();
+    if !routines.is_empty() {
+        let db = connect_db().await?;
+        for routine in &routines {
+            if let Err(e) = db.create_routine(routine).await {
+                for id in created {
+                    let _ = db.delete_routine(id).await;
+                }
+                anyhow::bail!("Failed to create routine '{}': {}", routine.name, e);
+            }
+            created.push(routine.id);
+        }
+    }
+
+    let path = HookPackStore::new(HookPackStore::default_dir()).install(&pack, &downloaded.data)?;
+    println!(
+        "\nInstalled '{}': {} hook(s), {} routine(s).",
+        pack.name,
+        pack.hooks.len(),
+        created.len()
+    );
+    println!("  Manifest: {}", path.display());
+    Ok(())
+}
+
+fn list_packs() -> anyhow::Result<()> {
+    let packs = HookPackStore::new(HookPackStore::default_dir()).list();
+    if packs.is_empty() {
+        println!("No hook packs installed.");
+        println!("\nTo find one:");
+        println!("  ironclaw hooks search pii");
+        return Ok(());
+    }
+    for pack in packs {
+        println!(
+            "  {} {} ({} hook(s), {} routine(s))",
+            pack.name,
+            pack.version,
+            pack.hooks.len(),
+            pack.routines.len()
+        );
+        if !pack.description.is_empty() {
+            println!("    {}", pack.description);
+        }
+    }
+    Ok(())
+}
+
+fn verify_packs(name: Option<&str>) -> anyhow::Result<()> {
+    let store = HookPackStore::new(HookPackStore::default_dir());
+    let names: Vec<String> = match name {
+        Some(name) => vec![name.to_string()],
+        None => store.list().into_iter().map(|p| p.name).collect(),
+    };
+    if names.is_empty() {
+        println!("No hook packs installed.");
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for name in &names {
+        let status = store
+            .verify(name)
+            .map_err(|e| anyhow::anyhow!("Cannot read pack '{}': {}", name, e))?;
+        match status {
+            PackVerification::Ok { version } => println!("  {} {}: ok", name, version),
+            PackVerification::Unrecorded => {
+                println!("  {}: no install record, reinstall to verify", name)
+            }
+            PackVerification::Modified { expected, actual } => {
+                failed += 1;
+                println!(
+                    "  {}: MODIFIED since install (expected {}, found {})",
+                    name, expected, actual
+                );
+            }
+            PackVerification::Invalid(reason) => {
+                failed += 1;
+                println!("  {}: INVALID ({})", name, reason);
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!("{} pack(s) failed verification", failed);
+    }
+    Ok(())
+}
+
+async fn uninstall_pack(name: &str) -> anyhow::Result<()> {
+    let store = HookPackStore::new(HookPackStore::default_dir());
+    let Some(pack) = store.get(name).map_err(|e| anyhow::anyhow!("{}", e))? else {
+        anyhow::bail!("Hook pack '{}' is not installed", name);
+    };
+
+    let mut removed = 0;
+    if !pack.routines.is_empty() {
+        let db = connect_db().await?;
+        let prefix = pack.item_name("");
+        let routines = db
+            .list_routines("default")
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list routines: {}", e))?;
+        for routine in routines.iter().filter(|r| r.name.starts_with(&prefix)) {
+            db.delete_routine(routine.id)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to delete routine: {}", e))?;
+            removed += 1;
+        }
+    }
+
+    store.remove(name)?;
+    println!(
+        "Uninstalled '{}' ({} hook(s), {} routine(s) removed).",
+        name,
+        pack.hooks.len(),
+        removed
+    );
+    Ok(())
+}
+
+/// Ask a yes/no question on the terminal; anything but "y" is no.
+fn confirm(question: &str) -> anyhow::Result<bool> {
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    Ok(input.trim().eq_ignore_ascii_case("y"))
+}
+
+async fn connect_db() -> anyhow::Result<std::sync::Arc<dyn crate::db::Database>> {
+    let _ = dotenvy::dotenv();
+    let config = crate::config::Config::from_env()
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    crate::db::connect_from_config(&config.database)
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", e))
+}
+
 fn parse_hook_type(s: &str) -> anyhow::Result<crate::hooks::HookType> {
     match s.to_lowercase().as_str() {
         "beforeinbound" | "before_inbound" | "before-inbound" => {
@@ -2,6 +2,8 @@
 
 use clap::Subcommand;
 
+use crate::agent::profiling::{self, TurnProfile};
+
 #[derive(Subcommand, Debug, Clone)]
 pub enum LogsCommand {
     /// Show recent logs
@@ -38,6 +40,42 @@ pub enum LogsCommand {
         /// Job ID
         job_id: uuid::Uuid,
     },
+
+    /// Show outbound requests made through the sandbox network proxy
+    Egress {
+        /// Only requests made for this job
+        #[arg(long)]
+        job: Option<uuid::Uuid>,
+
+        /// Number of most recent requests to show
+        #[arg(short, long, default_value = "100")]
+        limit: i64,
+    },
+
+    /// Show a profiled turn (requires AGENT_PROFILE_TURNS=true)
+    Turn {
+        /// Turn ID, or "last" for the most recent turn
+        id: String,
+
+        /// Show a per-phase timing waterfall
+        #[arg(long)]
+        timing: bool,
+
+        /// Print folded stacks for flamegraph tools (inferno, flamegraph.pl)
+        #[arg(long, conflicts_with = "timing")]
+        folded: bool,
+    },
+
+    /// List recent profiled turns with p50/p95 latency per phase
+    Turns {
+        /// Number of most recent turns to include
+        #[arg(short, long, default_value = "100")]
+        limit: usize,
+
+        /// Print folded stacks for all included turns instead
+        #[arg(long)]
+        folded: bool,
+    },
 }
 
 /// Run a logs command.
@@ -51,6 +89,9 @@ pub async fn run_logs_command(cmd: LogsCommand) -> anyhow::Result<()> {
         } => tail_logs(lines, level, target, follow).await,
         LogsCommand::Search { pattern, limit } => search_logs(&pattern, limit).await,
         LogsCommand::Job { job_id } => job_logs(job_id).await,
+        LogsCommand::Egress { job, limit } => egress_logs(job, limit).await,
+        LogsCommand::Turn { id, timing, folded } => turn_profile(&id, timing, folded),
+        LogsCommand::Turns { limit, folded } => turn_stats(limit, folded),
     }
 }
 
@@ -182,6 +223,156 @@ async fn job_logs(job_id: uuid::Uuid) -> anyhow::Result<()> {
     Ok(())
 }
 
+async fn egress_logs(job: Option<uuid::Uuid>, limit: i64) -> anyhow::Result<()> {
+    let db = connect_db().await?;
+
+    let records = db
+        .list_egress(job, limit)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to get egress records: {}", e))?;
+
+    if records.is_empty() {
+        match job {
+            Some(job_id) => println!("No egress recorded for job {}", job_id),
+            None => println!("No egress recorded"),
+        }
+        return Ok(());
+    }
+
+    match job {
+        Some(job_id) => println!("Egress for job {}:", job_id),
+        None => println!("Recent egress:"),
+    }
+    println!();
+    for record in &records {
+        println!("  {}", format_egress(record));
+    }
+
+    let denied = records.iter().filter(|r| r.decision == "denied").count();
+    let hosts: std::collections::BTreeSet<&str> = records.iter().map(|r| r.host.as_str()).collect();
+    println!();
+    println!(
+        "{} request(s) to {} host(s), {} denied",
+        records.len(),
+        hosts.len(),
+        denied
+    );
+
+    Ok(())
+}
+
+/// One line per request: time, decision, method, host and path, status,
+/// bytes sent/received, and whether a credential was injected.
+fn format_egress(record: &crate::history::EgressRecord) -> String {
+    let status = record
+        .status
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "-".to_string());
+    let mut line = format!(
+        "[{}] {:<7} {:<7} {}{} {} {}B/{}B",
+        record.created_at.format("%Y-%m-%d %H:%M:%S"),
+        record.decision,
+        record.method,
+        record.host,
+        record.path,
+        status,
+        record.request_bytes,
+        record.response_bytes,
+    );
+    if record.credential_injected {
+        line.push_str(" +credential");
+    }
+    if let Some(reason) = &record.reason {
+        line.push_str(&format!(" ({})", reason));
+    }
+    line
+}
+
+/// Load profiled turns, printing a hint when profiling has not run yet.
+fn load_turn_profiles() -> anyhow::Result<Option<Vec<TurnProfile>>> {
+    let path = profiling::default_profile_path();
+    let profiles = profiling::read_profiles(&path)?;
+    if profiles.is_empty() {
+        println!("No turn profiles found at {}", path.display());
+        println!("\nTo record turn timings, set:");
+        println!("  AGENT_PROFILE_TURNS=true");
+        return Ok(None);
+    }
+    Ok(Some(profiles))
+}
+
+fn turn_profile(id: &str, timing: bool, folded: bool) -> anyhow::Result<()> {
+    let Some(profiles) = load_turn_profiles()? else {
+        return Ok(());
+    };
+
+    let profile = if id == "last" {
+        profiles.last()
+    } else {
+        let id = id.to_lowercase();
+        profiles
+            .iter()
+            .rev()
+            .find(|p| p.id.to_string().starts_with(&id))
+    };
+    let Some(profile) = profile else {
+        anyhow::bail!("No profiled turn matching '{}'", id);
+    };
+
+    if folded {
+        print!("{}", profile.to_folded());
+    } else if timing {
+        print!("{}", profile.render_timing(50));
+    } else {
+        println!("Turn {}", profile.id);
+        println!("  Started:  {}", profile.started_at);
+        println!("  Channel:  {}", profile.channel);
+        println!("  Total:    {:.1} ms", profile.total_ms);
+        println!("  Spans:    {}", profile.spans.len());
+        println!("\nUse --timing for a per-phase breakdown.");
+    }
+    Ok(())
+}
+
+fn turn_stats(limit: usize, folded: bool) -> anyhow::Result<()> {
+    let Some(profiles) = load_turn_profiles()? else {
+        return Ok(());
+    };
+    let start = profiles.len().saturating_sub(limit);
+    let recent = &profiles[start..];
+
+    if folded {
+        for profile in recent {
+            print!("{}", profile.to_folded());
+        }
+        return Ok(());
+    }
+
+    println!("Recent turns:");
+    for profile in recent.iter().rev().take(10) {
+        println!(
+            "  {}  {}  {:>9.1} ms  {}",
+            profile.id,
+            profile.started_at.format("%Y-%m-%d %H:%M:%S"),
+            profile.total_ms,
+            profile.channel
+        );
+    }
+    println!();
+    println!("Latency over {} turn(s):", recent.len());
+    println!(
+        "  {:<14} {:>6} {:>11} {:>11} {:>11}",
+        "PHASE", "TURNS", "P50", "P95", "MAX"
+    );
+    for stats in profiling::aggregate(recent) {
+        println!(
+            "  {:<14} {:>6} {:>8.1} ms {:>8.1} ms {:>8.1} ms",
+            stats.name, stats.turns, stats.p50_ms, stats.p95_ms, stats.max_ms
+        );
+    }
+    Ok(())
+}
+
 async fn connect_db() -> anyhow::Result<std::sync::Arc<dyn crate::db::Database>> {
     let _ = dotenvy::dotenv();
     let config = crate::config::Config::from_env()
@@ -191,3 +382,30 @@ async fn connect_db() -> anyhow::Result<std::sync::Arc<dyn crate::db::Database>>
         .await
         .map_err(|e| anyhow::anyhow!("{}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_egress_line() {
+        let record = crate::history::EgressRecord {
+            id: uuid::Uuid::new_v4(),
+            job_id: None,
+            method: "POST".to_string(),
+            host: "api.openai.com".to_string(),
+            path: "/v1/chat".to_string(),
+            decision: "allowed".to_string(),
+            reason: None,
+            status: Some(200),
+            request_bytes: 120,
+            response_bytes: 4096,
+            credential_injected: true,
+            created_at: chrono::Utc::now(),
+        };
+
+        let line = format_egress(&record);
+        assert!(line.contains("allowed POST    api.openai.com/v1/chat 200 120B/4096B"));
+        assert!(line.ends_with("+credential"));
+    }
+}
@@ -505,7 +505,14 @@ async fn get_secrets_store() -> anyhow::Result<Arc<dyn SecretsStore + Send + Syn
     })?;
 
     let crypto = SecretsCrypto::new(master_key.clone())?;
+    open_secrets_store(&config, crypto).await
+}
 
+/// Open the secrets store for the configured database backend using `crypto`.
+pub(crate) async fn open_secrets_store(
+    config: &Config,
+    crypto: SecretsCrypto,
+) -> anyhow::Result<Arc<dyn SecretsStore + Send + Sync>> {
     #[cfg(feature = "postgres")]
     {
         let store = crate::history::Store::new(&config.database).await?;
@@ -7,17 +7,30 @@ use std::sync::Arc;
 
 use clap::Subcommand;
 
-use crate::workspace::{ConnectionType, EmbeddingProvider, ProfileType, SearchConfig, Workspace};
+use crate::workspace::{
+    ConnectionType, EmbedderConfig, EmbeddingProvider, ImportOptions, IngestOptions, IngestStatus,
+    MemoryArchive, ProfileType, QuotaConfig, QuotaStatus, Reranker, SearchConfig, SpaceUsage,
+    Workspace, collect_files, retrieval_eval,
+};
 
 /// Run a memory command using the Database trait (works with any backend).
 pub async fn run_memory_command_with_db(
     cmd: MemoryCommand,
     db: std::sync::Arc<dyn crate::db::Database>,
     embeddings: Option<Arc<dyn EmbeddingProvider>>,
+    embedder: EmbedderConfig,
+    reranker: Option<Arc<dyn Reranker>>,
+    quota: QuotaConfig,
 ) -> anyhow::Result<()> {
+    let semantic = embeddings.is_some();
     let mut workspace = Workspace::new_with_db("default", db);
     if let Some(emb) = embeddings {
-        workspace = workspace.with_embeddings(emb);
+        workspace = workspace
+            .with_embeddings(emb)
+            .with_embedder_config(embedder);
+    }
+    if let Some(rr) = reranker {
+        workspace = workspace.with_reranker(rr);
     }
 
     match cmd {
@@ -33,6 +46,26 @@ pub async fn run_memory_command_with_db(
         MemoryCommand::Spaces { action } => spaces(&workspace, action).await,
         MemoryCommand::Profile { action } => profile(&workspace, action).await,
         MemoryCommand::Connect { action } => connect(&workspace, action).await,
+        MemoryCommand::Eval { cases, k } => eval(&workspace, &cases, k).await,
+        MemoryCommand::Usage { json, enforce } => usage(&workspace, &quota, json, enforce).await,
+        MemoryCommand::Ingest {
+            paths,
+            prefix,
+            force,
+            prune,
+        } => {
+            let options = IngestOptions {
+                prefix,
+                force,
+                prune,
+                ..IngestOptions::default()
+            };
+            ingest(&workspace, semantic, &paths, &options).await
+        }
+        MemoryCommand::Export { path } => export(&workspace, &path).await,
+        MemoryCommand::Import { path, overwrite } => {
+            import(&workspace, semantic, &path, overwrite).await
+        }
     }
 }
 
@@ -98,6 +131,63 @@ pub enum MemoryCommand {
         #[command(subcommand)]
         action: ConnectAction,
     },
+
+    /// Score search quality on labelled queries, with and without re-ranking
+    Eval {
+        /// JSON-lines file of {"query": ..., "relevant": ["path", ...]}
+        cases: std::path::PathBuf,
+
+        /// Cutoff for recall@k
+        #[arg(short, default_value = "5")]
+        k: usize,
+    },
+
+    /// Show storage used per space (documents, chunks, embeddings) against quotas
+    Usage {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Apply the quota action (warn or consolidate) to spaces over quota
+        #[arg(long)]
+        enforce: bool,
+    },
+
+    /// Index local files (Markdown, text, code, HTML, PDF) for memory search
+    Ingest {
+        /// Files, directories or quoted glob patterns (e.g. "notes/**/*.md")
+        #[arg(required = true)]
+        paths: Vec<String>,
+
+        /// Workspace directory the documents are stored under
+        #[arg(long, default_value = crate::workspace::DEFAULT_INGEST_PREFIX)]
+        prefix: String,
+
+        /// Re-index files even if they are unchanged since the last ingest
+        #[arg(long)]
+        force: bool,
+
+        /// Delete ingested documents whose source file no longer exists
+        #[arg(long)]
+        prune: bool,
+    },
+
+    /// Write all memory (documents, chunks, embeddings, connections, spaces,
+    /// profile) to a portable .tar.gz archive
+    Export {
+        /// Archive file to write
+        path: std::path::PathBuf,
+    },
+
+    /// Restore memory from an archive written by `memory export`
+    Import {
+        /// Archive file to read
+        path: std::path::PathBuf,
+
+        /// Replace existing documents and profile facts with the archived ones
+        #[arg(long)]
+        overwrite: bool,
+    },
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -184,10 +274,15 @@ pub async fn run_memory_command(
     cmd: MemoryCommand,
     pool: deadpool_postgres::Pool,
     embeddings: Option<Arc<dyn EmbeddingProvider>>,
+    embedder: EmbedderConfig,
+    quota: QuotaConfig,
 ) -> anyhow::Result<()> {
+    let semantic = embeddings.is_some();
     let mut workspace = Workspace::new("default", pool);
     if let Some(emb) = embeddings {
-        workspace = workspace.with_embeddings(emb);
+        workspace = workspace
+            .with_embeddings(emb)
+            .with_embedder_config(embedder);
     }
 
     match cmd {
@@ -203,6 +298,26 @@ pub async fn run_memory_command(
         MemoryCommand::Spaces { action } => spaces(&workspace, action).await,
         MemoryCommand::Profile { action } => profile(&workspace, action).await,
         MemoryCommand::Connect { action } => connect(&workspace, action).await,
+        MemoryCommand::Eval { cases, k } => eval(&workspace, &cases, k).await,
+        MemoryCommand::Usage { json, enforce } => usage(&workspace, &quota, json, enforce).await,
+        MemoryCommand::Ingest {
+            paths,
+            prefix,
+            force,
+            prune,
+        } => {
+            let options = IngestOptions {
+                prefix,
+                force,
+                prune,
+                ..IngestOptions::default()
+            };
+            ingest(&workspace, semantic, &paths, &options).await
+        }
+        MemoryCommand::Export { path } => export(&workspace, &path).await,
+        MemoryCommand::Import { path, overwrite } => {
+            import(&workspace, semantic, &path, overwrite).await
+        }
     }
 }
 
@@ -232,6 +347,215 @@ async fn search(workspace: &Workspace, query: &str, limit: usize) -> anyhow::Res
     Ok(())
 }
 
+async fn eval(workspace: &Workspace, path: &std::path::Path, k: usize) -> anyhow::Result<()> {
+    let text = std::fs::read_to_string(path)?;
+    let cases = retrieval_eval::parse_cases(&text)?;
+    if cases.is_empty() {
+        anyhow::bail!("no cases in {}", path.display());
+    }
+
+    let config = SearchConfig::default().with_limit(k);
+    let print = |label: &str, report: &retrieval_eval::RetrievalReport| {
+        println!(
+            "{:<16} MRR {:.3}   recall@{} {:.3}",
+            label, report.mrr, report.k, report.recall_at_k
+        );
+    };
+
+    println!("{} case(s)\n", cases.len());
+    let fused = workspace
+        .evaluate_retrieval(&cases, config.clone().without_rerank())
+        .await?;
+    print("fused", &fused);
+    match workspace.reranker() {
+        Some(reranker) => {
+            let reranked = workspace.evaluate_retrieval(&cases, config).await?;
+            print(&format!("rerank ({})", reranker.name()), &reranked);
+        }
+        None => println!("\nNo re-ranker configured (set SEARCH_RERANKER to compare)."),
+    }
+    Ok(())
+}
+
+async fn usage(
+    workspace: &Workspace,
+    quota: &QuotaConfig,
+    json: bool,
+    enforce: bool,
+) -> anyhow::Result<()> {
+    if enforce {
+        for note in workspace.enforce_quotas(quota).await? {
+            println!("{}", note);
+        }
+    }
+
+    let report = workspace.usage(quota).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    let row = |space: &SpaceUsage| {
+        let status = match (&space.quota, space.status) {
+            (None, _) => "",
+            (Some(_), QuotaStatus::Ok) => "ok",
+            (Some(_), QuotaStatus::Near) => "near quota",
+            (Some(_), QuotaStatus::Exceeded) => "OVER QUOTA",
+        };
+        println!(
+            "  {:<24} {:>7} {:>8} {:>10} {:>10}  {}",
+            space.name,
+            space.counts.documents,
+            space.counts.chunks,
+            format_bytes(space.counts.content_bytes),
+            format_bytes(space.counts.embedding_bytes),
+            status
+        );
+    };
+
+    println!(
+        "  {:<24} {:>7} {:>8} {:>10} {:>10}",
+        "SPACE", "DOCS", "CHUNKS", "CONTENT", "EMBEDDINGS"
+    );
+    for space in &report.spaces {
+        row(space);
+    }
+    row(&report.total);
+    if quota.is_empty() {
+        println!("\nNo quotas configured (see MEMORY_QUOTA_* in .env.example).");
+    }
+    Ok(())
+}
+
+async fn ingest(
+    workspace: &Workspace,
+    semantic: bool,
+    paths: &[String],
+    options: &IngestOptions,
+) -> anyhow::Result<()> {
+    let (files, problems) = collect_files(paths);
+    for (input, reason) in &problems {
+        eprintln!("  ! {}: {}", input, reason);
+    }
+    if files.is_empty() && !options.prune {
+        anyhow::bail!("nothing to ingest");
+    }
+    if !semantic {
+        println!("Embeddings are not configured; ingested files are searchable by full text only.");
+    }
+
+    let report = workspace.ingest(&files, options).await?;
+    for (file, path, status) in &report.files {
+        match status {
+            IngestStatus::Added => println!("  + {}", path),
+            IngestStatus::Updated => println!("  ~ {}", path),
+            IngestStatus::Unchanged => {}
+            IngestStatus::Skipped(reason) => println!("  ! {} ({})", file.display(), reason),
+        }
+    }
+    for path in &report.pruned {
+        println!("  - {}", path);
+    }
+    println!(
+        "\n{} added, {} updated, {} unchanged, {} skipped, {} removed",
+        report.count(|s| *s == IngestStatus::Added),
+        report.count(|s| *s == IngestStatus::Updated),
+        report.count(|s| *s == IngestStatus::Unchanged),
+        report.count(|s| matches!(s, IngestStatus::Skipped(_))),
+        report.pruned.len()
+    );
+    if let Some(stats) = workspace
+        .embedding_stats()
+        .filter(|s| s.texts + s.failures > 0)
+    {
+        println!(
+            "Embedded {} chunks in {} batches ({:.1} chunks/s, {} retries, {} failed)",
+            stats.texts,
+            stats.batches,
+            stats.texts_per_sec(),
+            stats.retries,
+            stats.failures
+        );
+    }
+    Ok(())
+}
+
+async fn export(workspace: &Workspace, path: &std::path::Path) -> anyhow::Result<()> {
+    let archive = workspace.export_archive().await?;
+    archive.write(path)?;
+    let m = &archive.manifest;
+    println!(
+        "Exported {} documents, {} chunks ({} embedded), {} connections, {} spaces, {} profile facts to {}",
+        m.documents,
+        m.chunks,
+        m.embeddings,
+        archive.connections.len(),
+        archive.spaces.len(),
+        archive.profiles.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+async fn import(
+    workspace: &Workspace,
+    semantic: bool,
+    path: &std::path::Path,
+    overwrite: bool,
+) -> anyhow::Result<()> {
+    let archive = MemoryArchive::read(path)?;
+    println!(
+        "Archive from IronClaw {} ({}), exported {}",
+        archive.manifest.exported_by,
+        archive.manifest.user_id,
+        archive.manifest.exported_at.format("%Y-%m-%d %H:%M UTC")
+    );
+
+    let report = workspace
+        .import_archive(&archive, ImportOptions { overwrite })
+        .await?;
+    for path in &report.documents_skipped {
+        println!("  = {} (exists, kept)", path);
+    }
+    println!(
+        "\n{} documents imported, {} kept, {} chunks, {} connections, {} spaces, {} profile facts",
+        report.documents_imported,
+        report.documents_skipped.len(),
+        report.chunks,
+        report.connections,
+        report.spaces,
+        report.profiles
+    );
+    if !report.documents_skipped.is_empty() {
+        println!("Use --overwrite to replace the kept documents.");
+    }
+
+    if report.chunks_without_embedding > 0 {
+        if semantic {
+            let filled = workspace.backfill_embeddings().await?;
+            println!("Embedded {} chunks that had no usable embedding.", filled);
+        } else {
+            println!(
+                "{} chunks have no embedding; they are searchable by full text only.",
+                report.chunks_without_embedding
+            );
+        }
+    }
+    Ok(())
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    let b = bytes as f64;
+    if b >= KB * KB {
+        format!("{:.1} MB", b / (KB * KB))
+    } else if b >= KB {
+        format!("{:.1} KB", b / KB)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
 async fn read(workspace: &Workspace, path: &str) -> anyhow::Result<()> {
     match workspace.read(path).await {
         Ok(doc) => {
@@ -2,17 +2,19 @@
 //!
 //! Provides subcommands for:
 //! - Running the agent (`run`)
+//! - Attaching a REPL to a running agent over the gateway (`attach`)
 //! - Interactive onboarding wizard (`onboard`)
-//! - Managing configuration (`config list`, `config get`, `config set`)
+//! - Managing configuration (`config list`, `config get`, `config set`, `config key`)
 //! - Managing WASM tools (`tool install`, `tool list`, `tool remove`)
 //! - Managing MCP servers (`mcp add`, `mcp auth`, `mcp list`, `mcp test`)
 //! - Querying workspace memory (`memory search`, `memory read`, `memory write`)
-//! - Checking system health (`status`, `doctor`)
-//! - Gateway management (`gateway start`, `gateway stop`, `gateway status`)
+//! - Checking system health (`status`, `doctor`, `doctor --bundle`)
+//! - Benchmarking core hot paths (`bench`)
+//! - Gateway management (`gateway start`, `gateway stop`, `gateway status`, `gateway sdk`)
 //! - Session management (`sessions list`, `sessions prune`)
 //! - Hook management (`hooks list`, `hooks add`, `hooks remove`)
 //! - Cron/routine management (`cron list`, `cron enable`, `cron history`)
-//! - Log querying (`logs tail`, `logs search`, `logs job`)
+//! - Log querying (`logs tail`, `logs search`, `logs job`, `logs turn`, `logs turns`)
 //! - Message sending (`message send`)
 //! - Shell completion generation (`completion`)
 //! - Channel management (`channels list`, `channels status`, `channels enable`)
@@ -21,12 +23,17 @@
 //! - Skills management (`skills list`, `skills enable`, `skills disable`)
 //! - Agent management (`agents list`, `agents info`, `agents set-default`)
 //! - Node management (`nodes list`, `nodes add`, `nodes remove`, `nodes ping`)
+//! - Safety guardrail regression runs (`safety test`)
+//! - Secret groups and their audit log (`secrets group`, `secrets audit`)
 
 mod agents;
+mod attach;
+mod bench;
 mod browser;
 mod channels;
 mod completion;
 mod config;
+mod contacts;
 mod cron;
 mod doctor;
 mod gateway;
@@ -38,6 +45,8 @@ mod message;
 mod nodes;
 mod pairing;
 mod plugins;
+mod safety;
+mod secrets;
 mod service;
 mod sessions;
 mod skills;
@@ -46,13 +55,16 @@ mod tool;
 mod webhooks;
 
 pub use agents::{AgentsCommand, run_agents_command};
+pub use attach::{AttachArgs, run_attach_command};
+pub use bench::{BenchArgs, run_bench_command};
 pub use browser::{BrowserCommand, run_browser_command};
 pub use channels::{ChannelsCommand, run_channels_command};
 pub use completion::generate_completions;
 pub use config::{ConfigCommand, run_config_command};
+pub use contacts::{ContactsCommand, run_contacts_command, run_contacts_command_with_store};
 pub use cron::{CronCommand, run_cron_command};
 pub use doctor::run_doctor_command;
-pub use gateway::{GatewayCommand, run_gateway_command};
+pub use gateway::{GatewayCommand, GatewayKeysCommand, run_gateway_command};
 pub use hooks::{HooksCommand, run_hooks_command};
 pub use logs::{LogsCommand, run_logs_command};
 pub use mcp::{McpCommand, run_mcp_command};
@@ -64,13 +76,15 @@ pub use message::{MessageCommand, run_message_command};
 pub use nodes::{Node, NodeManager, NodeStatus, NodesCommand, run_nodes_command};
 pub use pairing::{PairingCommand, run_pairing_command, run_pairing_command_with_store};
 pub use plugins::{PluginsCommand, run_plugins_command};
+pub use safety::{SafetyCommand, run_safety_command};
+pub use secrets::{GroupCommand, SecretsCommand, run_secrets_command};
 pub use service::{
     ServiceConfig, ServiceError, ServiceGenerator, generate_launchd_plist, generate_systemd_unit,
     install_launchd, install_systemd,
 };
 pub use sessions::{SessionsCommand, run_sessions_command};
 pub use skills::{SkillsCommand, run_skills_command};
-pub use status::run_status_command;
+pub use status::{run_boot_profile_command, run_budget_command, run_status_command};
 pub use tool::{ToolCommand, run_tool_command};
 pub use webhooks::{WebhooksCommand, run_webhooks_command};
 
@@ -94,10 +108,24 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub no_db: bool,
 
+    /// Demo mode: throwaway database seeded with synthetic data, no outbound
+    /// side effects, watermarked responses
+    #[arg(long, global = true, conflicts_with = "no_db")]
+    pub demo: bool,
+
     /// Single message mode - send one message and exit
     #[arg(short, long, global = true)]
     pub message: Option<String>,
 
+    /// With --message, keep running up to this many seconds until jobs and
+    /// routine runs the message started have finished, reporting each one
+    #[arg(long, value_name = "SECS", requires = "message")]
+    pub wait_for_followups: Option<u64>,
+
+    /// With --message, print the reply and follow-ups as one JSON object
+    #[arg(long, requires = "message")]
+    pub json: bool,
+
     /// Configuration file path (optional, uses env vars by default)
     #[arg(short, long, global = true)]
     pub config: Option<std::path::PathBuf>,
@@ -112,6 +140,9 @@ pub enum Command {
     /// Run the agent (default if no subcommand given)
     Run,
 
+    /// Attach an interactive REPL to a running agent via the web gateway
+    Attach(AttachArgs),
+
     /// Interactive onboarding wizard
     Onboard {
         /// Skip authentication (use existing session)
@@ -143,11 +174,39 @@ pub enum Command {
     #[command(subcommand)]
     Pairing(PairingCommand),
 
+    /// Link channel identities to people (Telegram, Slack, email, ...)
+    #[command(subcommand)]
+    Contacts(ContactsCommand),
+
     /// Show system health and diagnostics
-    Status,
+    Status {
+        /// Show per-component startup times from the last start instead
+        #[arg(long)]
+        boot: bool,
+
+        /// Show today's token and cost spend from the running gateway instead
+        #[arg(long, conflicts_with = "boot")]
+        budget: bool,
+    },
 
     /// Run comprehensive diagnostics
-    Doctor,
+    Doctor {
+        /// Report machine learning accelerators (Metal, CUDA) instead
+        #[arg(long)]
+        ml: bool,
+
+        /// Also write a redacted diagnostics archive (logs, config, crash
+        /// reports) to share when reporting a bug
+        #[arg(long, conflicts_with = "ml")]
+        bundle: bool,
+
+        /// Archive path for --bundle (default: ./ironclaw-diagnostics-<time>.tar.gz)
+        #[arg(short, long, requires = "bundle")]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// Benchmark core hot paths and print a comparable report
+    Bench(BenchArgs),
 
     /// Manage the web gateway
     #[command(subcommand)]
@@ -201,6 +260,14 @@ pub enum Command {
     #[command(subcommand)]
     Browser(BrowserCommand),
 
+    /// Check the safety layer against a guardrail corpus
+    #[command(subcommand)]
+    Safety(SafetyCommand),
+
+    /// Share secrets with groups of users and audit their use
+    #[command(subcommand)]
+    Secrets(SecretsCommand),
+
     /// Generate shell completion scripts
     Completion {
         /// Shell to generate completions for (bash, zsh, fish, powershell, elvish)
@@ -225,6 +292,11 @@ pub enum Command {
         #[arg(long, default_value = "http://host.docker.internal:50051")]
         orchestrator_url: String,
 
+        /// Transport to the orchestrator: `grpc` streams events and receives
+        /// prompts on one session (URL from IRONCLAW_ORCHESTRATOR_GRPC_URL).
+        #[arg(long, value_enum, default_value = "auto")]
+        orchestrator_proto: crate::worker::api::OrchestratorProto,
+
         /// Maximum iterations before stopping.
         #[arg(long, default_value = "50")]
         max_iterations: u32,
@@ -241,6 +313,11 @@ pub enum Command {
         #[arg(long, default_value = "http://host.docker.internal:50051")]
         orchestrator_url: String,
 
+        /// Transport to the orchestrator: `grpc` streams events and receives
+        /// prompts on one session (URL from IRONCLAW_ORCHESTRATOR_GRPC_URL).
+        #[arg(long, value_enum, default_value = "auto")]
+        orchestrator_proto: crate::worker::api::OrchestratorProto,
+
         /// Maximum agentic turns for Claude Code.
         #[arg(long, default_value = "50")]
         max_turns: u32,
@@ -290,6 +367,24 @@ mod tests {
         assert_eq!(cli.message.as_deref(), Some("hello"));
     }
 
+    #[test]
+    fn parse_wait_for_followups() {
+        let cli = Cli::try_parse_from([
+            "ironclaw",
+            "-m",
+            "fetch the report",
+            "--wait-for-followups",
+            "120",
+            "--json",
+        ])
+        .unwrap();
+        assert_eq!(cli.wait_for_followups, Some(120));
+        assert!(cli.json);
+
+        // Only meaningful in single-message mode.
+        assert!(Cli::try_parse_from(["ironclaw", "--wait-for-followups", "5"]).is_err());
+    }
+
     #[test]
     fn parse_message_long_flag() {
         let cli = Cli::try_parse_from(["ironclaw", "--message", "world"]).unwrap();
@@ -329,13 +424,60 @@ mod tests {
     #[test]
     fn command_status_variant() {
         let cli = Cli::try_parse_from(["ironclaw", "status"]).unwrap();
-        assert!(matches!(cli.command, Some(Command::Status)));
+        assert!(matches!(
+            cli.command,
+            Some(Command::Status {
+                boot: false,
+                budget: false
+            })
+        ));
+        let cli = Cli::try_parse_from(["ironclaw", "status", "--boot"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Status { boot: true, .. })
+        ));
+        let cli = Cli::try_parse_from(["ironclaw", "status", "--budget"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Status { budget: true, .. })
+        ));
     }
 
     #[test]
     fn command_doctor_variant() {
         let cli = Cli::try_parse_from(["ironclaw", "doctor"]).unwrap();
-        assert!(matches!(cli.command, Some(Command::Doctor)));
+        assert!(matches!(
+            cli.command,
+            Some(Command::Doctor {
+                ml: false,
+                bundle: false,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn command_doctor_ml_flag() {
+        let cli = Cli::try_parse_from(["ironclaw", "doctor", "--ml"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Doctor { ml: true, .. })
+        ));
+    }
+
+    #[test]
+    fn command_doctor_bundle_flag() {
+        let cli =
+            Cli::try_parse_from(["ironclaw", "doctor", "--bundle", "-o", "diag.tar.gz"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Doctor {
+                bundle: true,
+                output: Some(_),
+                ..
+            })
+        ));
+        assert!(Cli::try_parse_from(["ironclaw", "doctor", "-o", "x.tar.gz"]).is_err());
     }
 
     #[test]
@@ -344,6 +486,18 @@ mod tests {
         assert!(matches!(cli.command, Some(Command::Completion { .. })));
     }
 
+    #[test]
+    fn command_attach_variant() {
+        let cli = Cli::try_parse_from(["ironclaw", "attach", "-p", "3100", "-t", "abc"]).unwrap();
+        match cli.command {
+            Some(Command::Attach(args)) => {
+                assert_eq!(args.port, Some(3100));
+                assert_eq!(args.thread.as_deref(), Some("abc"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
     #[test]
     fn parse_config_flag() {
         let cli = Cli::try_parse_from(["ironclaw", "-c", "/tmp/config.toml"]).unwrap();
@@ -1,16 +1,29 @@
 //! Plugin management CLI commands.
 
+use std::path::{Path, PathBuf};
+
 use clap::Subcommand;
 
+use crate::extensions::clawhub::ClawHubClient;
+use crate::extensions::plugins::{Plugin, PluginType};
+use crate::extensions::publish::{PackageBundle, PublisherKey};
+use crate::extensions::{PluginArtifact, PluginInstallRequest, PluginManager, PluginStorage};
+
 /// Plugin management commands.
 #[derive(Subcommand, Debug)]
 pub enum PluginsCommand {
     /// List installed plugins.
     List,
-    /// Install a plugin from a registry or local path.
+    /// Install a WASM plugin from a local path or HTTPS URL.
+    ///
+    /// The download, verification, storage, and registration happen as one
+    /// transaction; any failure rolls back partial state.
     Install {
-        /// Plugin name or path.
+        /// Local path or HTTPS URL of the plugin's .wasm binary.
         source: String,
+        /// Expected SHA-256 of the binary (hex).
+        #[arg(long)]
+        sha256: Option<String>,
     },
     /// Remove an installed plugin.
     Remove {
@@ -27,6 +40,29 @@ pub enum PluginsCommand {
         /// Plugin name (all if omitted).
         name: Option<String>,
     },
+    /// Reconcile plugins.json with the binaries actually stored on disk.
+    Repair {
+        /// Report problems without changing anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Package, sign and upload a WASM tool to ClawHub.
+    ///
+    /// The directory needs a clawhub.json manifest, the .wasm binary and
+    /// optionally its capabilities file. Uploading requires CLAWHUB_TOKEN.
+    Publish {
+        /// Package directory.
+        dir: PathBuf,
+        /// Registry API URL (default: CLAWHUB_URL or the public registry).
+        #[arg(long)]
+        registry: Option<String>,
+        /// Ed25519 signing key (PKCS#8); created if missing.
+        #[arg(long)]
+        key: Option<PathBuf>,
+        /// Validate and sign without uploading.
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 /// Run a plugins command.
@@ -50,10 +86,8 @@ pub async fn run_plugins_command(cmd: &PluginsCommand) -> Result<(), Box<dyn std
                 println!("  (none installed)");
             }
         }
-        PluginsCommand::Install { source } => {
-            println!("Installing plugin from: {}", source);
-            println!("Use 'ironclaw tool install' for WASM tools.");
-            println!("Use 'ironclaw mcp add' for MCP servers.");
+        PluginsCommand::Install { source, sha256 } => {
+            install_plugin(source, sha256.clone()).await?;
         }
         PluginsCommand::Remove { name } => {
             println!("Removing plugin: {}", name);
@@ -68,6 +102,153 @@ pub async fn run_plugins_command(cmd: &PluginsCommand) -> Result<(), Box<dyn std
             Some(n) => println!("Updating plugin: {}", n),
             None => println!("Checking all plugins for updates..."),
         },
+        PluginsCommand::Repair { dry_run } => {
+            let manager = PluginManager::new().with_storage(PluginStorage::default_paths());
+            let report = manager.repair(*dry_run).await?;
+            if report.is_clean() {
+                println!(
+                    "Plugins are consistent ({} installed).",
+                    report.healthy.len()
+                );
+                return Ok(());
+            }
+            let verb = if report.dry_run {
+                "Would remove"
+            } else {
+                "Removed"
+            };
+            for name in &report.dropped_entries {
+                println!("  {verb} config entry '{name}' (artifacts missing or corrupt)");
+            }
+            for file in &report.orphaned_files {
+                println!("  {verb} orphaned file '{file}'");
+            }
+        }
+        PluginsCommand::Publish {
+            dir,
+            registry,
+            key,
+            dry_run,
+        } => {
+            publish_plugin(dir, registry.as_deref(), key.as_deref(), *dry_run).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Package and sign a WASM tool directory, then upload it to ClawHub.
+async fn publish_plugin(
+    dir: &Path,
+    registry: Option<&str>,
+    key_path: Option<&Path>,
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let bundle = PackageBundle::from_dir(dir).await?;
+    let key_path = key_path
+        .map(Path::to_path_buf)
+        .unwrap_or_else(PublisherKey::default_path);
+    let key = PublisherKey::load_or_create(&key_path)?;
+    let signature = bundle.sign(&key);
+
+    println!(
+        "Package:   {} {} ({})",
+        bundle.manifest.name, bundle.manifest.version, bundle.manifest.package_type
+    );
+    println!(
+        "Binary:    {} ({} bytes, sha256 {})",
+        bundle.wasm_file,
+        bundle.wasm.len(),
+        signature.payload.wasm_sha256
+    );
+    match signature.payload.capabilities_sha256 {
+        Some(ref sha) => println!("Caps:      sha256 {}", sha),
+        None => println!("Caps:      none (the tool gets no host capabilities)"),
+    }
+    println!("Signed by: {} ({})", key.fingerprint(), key_path.display());
+
+    if dry_run {
+        println!("Dry run; nothing uploaded.");
+        return Ok(());
+    }
+
+    let mut client = ClawHubClient::from_env();
+    if let Some(url) = registry {
+        let token = std::env::var("CLAWHUB_TOKEN").ok();
+        client = ClawHubClient::with_url(url);
+        if let Some(token) = token.filter(|t| !t.is_empty()) {
+            client = client.with_token(token);
+        }
+    }
+    let published = client.publish(&bundle, &signature).await?;
+
+    println!(
+        "Published {} {} to {}",
+        published.name,
+        published.version,
+        client.base_url()
+    );
+    if let Some(ref url) = published.download_url {
+        println!("  Download: {}", url);
     }
     Ok(())
 }
+
+/// Download (or read) a plugin binary and install it transactionally.
+async fn install_plugin(
+    source: &str,
+    sha256: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = if source.starts_with("https://") {
+        let response = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(60))
+            .build()?
+            .get(source)
+            .send()
+            .await?
+            .error_for_status()?;
+        response.bytes().await?.to_vec()
+    } else if source.starts_with("http://") {
+        return Err("Only HTTPS URLs are allowed for plugin downloads".into());
+    } else {
+        tokio::fs::read(source).await?
+    };
+
+    let file_name = source
+        .rsplit('/')
+        .next()
+        .and_then(|s| s.split('?').next())
+        .unwrap_or(source)
+        .to_string();
+    let name = Path::new(&file_name)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .ok_or("cannot derive plugin name from source")?;
+
+    let manager = PluginManager::new().with_storage(PluginStorage::default_paths());
+    let report = manager
+        .install(PluginInstallRequest {
+            plugin: Plugin {
+                name: name.clone(),
+                plugin_type: PluginType::Tool,
+                description: format!("WASM plugin installed from {}", source),
+                version: "0.0.0".to_string(),
+                enabled: true,
+                config: Default::default(),
+                routes: vec![],
+            },
+            artifacts: vec![PluginArtifact {
+                file_name: format!("{}.wasm", name),
+                bytes,
+                expected_sha256: sha256,
+            }],
+            tools: vec![],
+        })
+        .await?;
+
+    println!(
+        "Installed plugin '{}' ({})",
+        report.name,
+        report.artifacts.join(", ")
+    );
+    Ok(())
+}
@@ -0,0 +1,174 @@
+//! Safety CLI commands.
+
+use std::path::PathBuf;
+
+use clap::Subcommand;
+
+use crate::config::SafetyConfig;
+use crate::safety::SafetyLayer;
+use crate::safety::guardrails::{
+    Baseline, BaselineDelta, CaseOutcome, builtin_cases, default_baseline_path, load_cases,
+    run_suite,
+};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SafetyCommand {
+    /// Run the guardrail corpus through the current safety configuration
+    /// and compare with the stored baseline
+    Test {
+        /// Extra cases (JSONL: id, category, input, expect, optional secret)
+        #[arg(long = "cases", value_name = "FILE")]
+        cases: Vec<PathBuf>,
+
+        /// Run only the --cases files, not the built-in corpus
+        #[arg(long, requires = "cases")]
+        no_builtin: bool,
+
+        /// Baseline file (default: ~/.ironclaw/safety-baseline.json)
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+
+        /// Record this run as the new baseline
+        #[arg(long)]
+        update_baseline: bool,
+
+        /// Print the results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Run a safety command against `config`.
+pub async fn run_safety_command(cmd: SafetyCommand, config: &SafetyConfig) -> anyhow::Result<()> {
+    match cmd {
+        SafetyCommand::Test {
+            cases,
+            no_builtin,
+            baseline,
+            update_baseline,
+            json,
+        } => test(
+            config,
+            &cases,
+            no_builtin,
+            baseline.unwrap_or_else(default_baseline_path),
+            update_baseline,
+            json,
+        ),
+    }
+}
+
+fn test(
+    config: &SafetyConfig,
+    files: &[PathBuf],
+    no_builtin: bool,
+    baseline_path: PathBuf,
+    update_baseline: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let mut cases = if no_builtin {
+        Vec::new()
+    } else {
+        builtin_cases()
+    };
+    for file in files {
+        cases.extend(load_cases(file).map_err(anyhow::Error::msg)?);
+    }
+
+    let safety = SafetyLayer::new(config);
+    let outcomes = run_suite(&safety, &cases);
+    let baseline = Baseline::load(&baseline_path).map_err(anyhow::Error::msg)?;
+    let delta = baseline
+        .as_ref()
+        .map(|b| BaselineDelta::compare(b, &outcomes));
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "passed": outcomes.iter().filter(|o| o.passed).count(),
+                "total": outcomes.len(),
+                "cases": outcomes,
+                "baseline": baseline_path,
+                "delta": delta,
+            }))?
+        );
+    } else {
+        print_report(&outcomes, baseline.as_ref(), delta.as_ref());
+    }
+
+    if update_baseline {
+        Baseline::from_outcomes(&outcomes)
+            .save(&baseline_path)
+            .map_err(anyhow::Error::msg)?;
+        if !json {
+            println!("\nBaseline written to {}", baseline_path.display());
+        }
+        return Ok(());
+    }
+
+    match delta {
+        Some(delta) if !delta.regressed.is_empty() => anyhow::bail!(
+            "{} guardrail case(s) regressed since the baseline",
+            delta.regressed.len()
+        ),
+        None if outcomes.iter().any(|o| !o.passed) => {
+            anyhow::bail!(
+                "guardrail cases failed (no baseline at {})",
+                baseline_path.display()
+            )
+        }
+        _ => Ok(()),
+    }
+}
+
+fn print_report(
+    outcomes: &[CaseOutcome],
+    baseline: Option<&Baseline>,
+    delta: Option<&BaselineDelta>,
+) {
+    let passed = outcomes.iter().filter(|o| o.passed).count();
+    println!("Guardrail suite: {}/{} passed\n", passed, outcomes.len());
+
+    let mut categories: Vec<&str> = outcomes.iter().map(|o| o.category.as_str()).collect();
+    categories.sort_unstable();
+    categories.dedup();
+    for category in categories {
+        let in_category: Vec<&CaseOutcome> =
+            outcomes.iter().filter(|o| o.category == category).collect();
+        let ok = in_category.iter().filter(|o| o.passed).count();
+        println!("  {:<14} {}/{}", category, ok, in_category.len());
+    }
+
+    let failed: Vec<&CaseOutcome> = outcomes.iter().filter(|o| !o.passed).collect();
+    if !failed.is_empty() {
+        println!("\nFailing:");
+        for o in failed {
+            println!("  ✗ {} (expected {}, got {})", o.id, o.expect, o.observed);
+        }
+    }
+
+    match (baseline, delta) {
+        (Some(baseline), Some(delta)) => {
+            let when = baseline
+                .recorded_at
+                .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+                .unwrap_or_else(|| "an unknown date".to_string());
+            println!(
+                "\nAgainst baseline from {}: {} regressed, {} fixed, {} new, {} removed",
+                when,
+                delta.regressed.len(),
+                delta.fixed.len(),
+                delta.added.len(),
+                delta.removed.len()
+            );
+            for id in &delta.regressed {
+                println!("  - regressed: {}", id);
+            }
+            for id in &delta.fixed {
+                println!("  + fixed: {}", id);
+            }
+        }
+        _ => println!("\nNo baseline yet; record one with --update-baseline."),
+    }
+}
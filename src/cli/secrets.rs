@@ -0,0 +1,215 @@
+//! Secrets CLI commands.
+//!
+//! Secret groups let an admin share one user's secret with a set of users:
+//! tools running for a member who has no copy of their own resolve the
+//! shared one, and every such use is written to an audit log.
+
+use clap::Subcommand;
+
+use crate::config::Config;
+use crate::secrets::{SecretsCrypto, SecretsStore};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SecretsCommand {
+    /// Manage secret groups and what is shared with them
+    #[command(subcommand)]
+    Group(GroupCommand),
+
+    /// Show recent uses of group-shared secrets
+    Audit {
+        /// Number of entries to show
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+
+        /// Print the entries as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum GroupCommand {
+    /// List groups with their members and shared secrets
+    List,
+
+    /// Add a user to a group
+    AddMember {
+        /// Group name
+        group: String,
+
+        /// User ID to add
+        user: String,
+    },
+
+    /// Remove a user from a group
+    RemoveMember {
+        /// Group name
+        group: String,
+
+        /// User ID to remove
+        user: String,
+    },
+
+    /// Share a stored secret with every member of a group
+    Share {
+        /// Group name
+        group: String,
+
+        /// Name of the secret
+        secret: String,
+
+        /// User the secret is stored under
+        #[arg(long, default_value = "default")]
+        owner: String,
+    },
+
+    /// Stop sharing a secret with a group
+    Unshare {
+        /// Group name
+        group: String,
+
+        /// Name of the secret
+        secret: String,
+
+        /// User the secret is stored under
+        #[arg(long, default_value = "default")]
+        owner: String,
+    },
+}
+
+/// Group names use letters, digits, '_' and '-'.
+fn valid_group_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Run a secrets command.
+pub async fn run_secrets_command(cmd: SecretsCommand) -> anyhow::Result<()> {
+    let config = Config::from_env().await?;
+    let master_key = config.secrets.master_key().ok_or_else(|| {
+        anyhow::anyhow!(
+            "SECRETS_MASTER_KEY not set. Run 'ironclaw onboard' first or set it in .env"
+        )
+    })?;
+    let store =
+        super::mcp::open_secrets_store(&config, SecretsCrypto::new(master_key.clone())?).await?;
+
+    match cmd {
+        SecretsCommand::Group(cmd) => run_group_command(store.as_ref(), cmd).await,
+        SecretsCommand::Audit { limit, json } => {
+            let uses = store.shared_uses(limit).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&uses)?);
+                return Ok(());
+            }
+            if uses.is_empty() {
+                println!("No shared secret uses recorded.");
+            }
+            for u in uses {
+                println!(
+                    "  {}  {:<16} {:<24} {}/{} via {}",
+                    u.used_at.format("%Y-%m-%d %H:%M:%S"),
+                    u.acting_user,
+                    u.tool,
+                    u.owner,
+                    u.secret_name,
+                    u.group
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+async fn run_group_command(store: &dyn SecretsStore, cmd: GroupCommand) -> anyhow::Result<()> {
+    match cmd {
+        GroupCommand::List => {
+            let groups = store.groups().await?;
+            if groups.is_empty() {
+                println!(
+                    "No secret groups. Create one with 'ironclaw secrets group add-member <group> <user>'."
+                );
+            }
+            for group in groups {
+                println!("{}", group.name);
+                println!(
+                    "  members: {}",
+                    if group.members.is_empty() {
+                        "(none)".to_string()
+                    } else {
+                        group.members.join(", ")
+                    }
+                );
+                if group.shares.is_empty() {
+                    println!("  secrets: (none)");
+                }
+                for share in &group.shares {
+                    println!("  secret:  {} (owner {})", share.secret_name, share.owner);
+                }
+            }
+        }
+        GroupCommand::AddMember { group, user } => {
+            if !valid_group_name(&group) {
+                anyhow::bail!("Group names use letters, digits, '_' and '-'");
+            }
+            store.add_group_member(&group, &user).await?;
+            println!("Added '{}' to group '{}'.", user, group);
+        }
+        GroupCommand::RemoveMember { group, user } => {
+            if !store.remove_group_member(&group, &user).await? {
+                anyhow::bail!("'{}' is not a member of group '{}'", user, group);
+            }
+            println!("Removed '{}' from group '{}'.", user, group);
+        }
+        GroupCommand::Share {
+            group,
+            secret,
+            owner,
+        } => {
+            if !valid_group_name(&group) {
+                anyhow::bail!("Group names use letters, digits, '_' and '-'");
+            }
+            store
+                .share_with_group(&owner, &secret, &group)
+                .await
+                .map_err(|e| anyhow::anyhow!("Cannot share '{}': {}", secret, e))?;
+            println!(
+                "Shared '{}' (owner {}) with group '{}'.",
+                secret, owner, group
+            );
+        }
+        GroupCommand::Unshare {
+            group,
+            secret,
+            owner,
+        } => {
+            if !store.unshare_from_group(&owner, &secret, &group).await? {
+                anyhow::bail!(
+                    "'{}' (owner {}) is not shared with group '{}'",
+                    secret,
+                    owner,
+                    group
+                );
+            }
+            println!("Stopped sharing '{}' with group '{}'.", secret, group);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::valid_group_name;
+
+    #[test]
+    fn test_group_names() {
+        assert!(valid_group_name("ops"));
+        assert!(valid_group_name("on-call_2"));
+        assert!(!valid_group_name(""));
+        assert!(!valid_group_name("ops team"));
+        assert!(!valid_group_name(&"x".repeat(65)));
+    }
+}
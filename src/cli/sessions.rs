@@ -1,6 +1,15 @@
 //! Session management CLI commands.
 
+use std::sync::Arc;
+
 use clap::Subcommand;
+use uuid::Uuid;
+
+use crate::agent::Redaction;
+use crate::agent::redact::default_log_dir;
+use crate::agent::session::Thread;
+use crate::llm::ChatMessage;
+use crate::workspace::Workspace;
 
 #[derive(Subcommand, Debug, Clone)]
 pub enum SessionsCommand {
@@ -36,6 +45,20 @@ pub enum SessionsCommand {
         #[arg(long)]
         force: bool,
     },
+
+    /// Remove the last turns of a conversation and scrub them from memory and logs
+    Redact {
+        /// Conversation ID (from `ironclaw sessions list`)
+        conversation: Uuid,
+
+        /// Number of turns to remove
+        #[arg(long, default_value = "1")]
+        last: usize,
+
+        /// User the conversation and memory belong to
+        #[arg(short, long, default_value = "default")]
+        user: String,
+    },
 }
 
 /// Run a sessions command.
@@ -48,6 +71,11 @@ pub async fn run_sessions_command(cmd: SessionsCommand) -> anyhow::Result<()> {
         } => list_sessions(user.as_deref(), &channel, verbose).await,
         SessionsCommand::Prune { max_idle, dry_run } => prune_sessions(max_idle, dry_run).await,
         SessionsCommand::Clear { force } => clear_sessions(force).await,
+        SessionsCommand::Redact {
+            conversation,
+            last,
+            user,
+        } => redact_session(conversation, last, &user).await,
     }
 }
 
@@ -140,7 +168,53 @@ async fn clear_sessions(force: bool) -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn connect_db() -> anyhow::Result<std::sync::Arc<dyn crate::db::Database>> {
+async fn redact_session(conversation: Uuid, last: usize, user: &str) -> anyhow::Result<()> {
+    if last == 0 {
+        anyhow::bail!("--last must be at least 1");
+    }
+    let db = connect_db().await?;
+    if !db.conversation_belongs_to_user(conversation, user).await? {
+        anyhow::bail!("No conversation {} for user '{}'", conversation, user);
+    }
+
+    // Rebuild the turns the same way the agent hydrates a thread.
+    let messages: Vec<ChatMessage> = db
+        .list_conversation_messages(conversation)
+        .await?
+        .iter()
+        .filter_map(|m| match m.role.as_str() {
+            "user" => Some(ChatMessage::user(&m.content)),
+            "assistant" => Some(ChatMessage::assistant(&m.content)),
+            _ => None,
+        })
+        .collect();
+    let mut thread = Thread::with_id(conversation, Uuid::nil());
+    thread.restore_from_messages(messages);
+    let removed = thread.remove_last_turns(last);
+    if removed.is_empty() {
+        println!("Nothing to redact.");
+        return Ok(());
+    }
+
+    let redaction = Redaction::new(&removed);
+    let workspace = Workspace::new_with_db(user, Arc::clone(&db));
+    let report = redaction
+        .apply(
+            Some(db.as_ref()),
+            conversation,
+            Some(&workspace),
+            &default_log_dir(),
+        )
+        .await?;
+    println!("{}", report.summary());
+    println!(
+        "A running agent keeps the turns in memory until it restarts; \
+         use /redact in the conversation to drop them there as well."
+    );
+    Ok(())
+}
+
+async fn connect_db() -> anyhow::Result<Arc<dyn crate::db::Database>> {
     let _ = dotenvy::dotenv();
     let config = crate::config::Config::from_env()
         .await
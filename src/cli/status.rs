@@ -1,10 +1,12 @@
 //! System health and diagnostics CLI command.
 //!
 //! Checks database connectivity, session validity, embeddings,
-//! WASM runtime, tool count, and channel availability.
+//! WASM runtime, tool count, channel availability, and sandbox mode.
 
 use std::path::PathBuf;
 
+use crate::agent::BudgetReport;
+use crate::boot::BootProfile;
 use crate::settings::Settings;
 
 /// Run the status command, printing system health info.
@@ -118,6 +120,28 @@ pub async fn run_status_command() -> anyhow::Result<()> {
         println!("disabled");
     }
 
+    // Sandbox
+    print!("  Sandbox:     ");
+    let env_flag =
+        |key: &str, default: bool| std::env::var(key).map(|v| v == "true").unwrap_or(default);
+    let backend: crate::sandbox::ContainerBackendKind = std::env::var("SANDBOX_BACKEND")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_default();
+    if !env_flag("SANDBOX_ENABLED", true) {
+        println!("disabled");
+    } else if crate::sandbox::backend_available(backend).await {
+        println!("{}", backend);
+    } else if env_flag("SANDBOX_HOST_FALLBACK", false) {
+        println!(
+            "DEGRADED: {} unavailable, host fallback (audit: {})",
+            backend,
+            crate::sandbox::fallback::default_audit_path().display()
+        );
+    } else {
+        println!("{} unavailable (sandboxed commands will fail)", backend);
+    }
+
     // MCP servers
     print!("  MCP Servers: ");
     match crate::tools::mcp::config::load_mcp_servers().await {
@@ -135,6 +159,56 @@ pub async fn run_status_command() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Print the boot profile recorded by the last start (`status --boot`).
+pub fn run_boot_profile_command() -> anyhow::Result<()> {
+    let path = std::env::var("STARTUP_PROFILE_PATH")
+        .ok()
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(crate::boot::default_profile_path);
+
+    match BootProfile::load(&path)? {
+        Some(profile) => print!("{}", profile.render()),
+        None => println!(
+            "No boot profile found at {} (start ironclaw once to record one)",
+            path.display()
+        ),
+    }
+    Ok(())
+}
+
+/// Print today's token and cost spend from the running gateway
+/// (`status --budget`).
+pub async fn run_budget_command() -> anyhow::Result<()> {
+    let gateway_port = std::env::var("GATEWAY_PORT")
+        .ok()
+        .and_then(|v| v.parse::<u16>().ok())
+        .unwrap_or(3000);
+
+    let mut request =
+        reqwest::Client::new().get(format!("http://127.0.0.1:{}/api/budget", gateway_port));
+    if let Ok(token) = std::env::var("GATEWAY_AUTH_TOKEN") {
+        request = request.header("Authorization", format!("Bearer {}", token));
+    }
+
+    let response = request.send().await.map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to reach the gateway (is ironclaw running on port {}?): {}",
+            gateway_port,
+            e
+        )
+    })?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        anyhow::bail!("Failed to read budget: {} {}", status, body);
+    }
+
+    let report: BudgetReport = response.json().await?;
+    print!("{}", report.render());
+    Ok(())
+}
+
 #[cfg(feature = "postgres")]
 async fn check_database() -> anyhow::Result<()> {
     let _ = dotenvy::dotenv();
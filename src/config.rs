@@ -5,9 +5,11 @@
 //! except the 4 bootstrap fields (database_url, pool_size, secrets key
 //! source, onboard_completed) which live in `~/.ironclaw/bootstrap.json`.
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::time::Duration;
 
+use rust_decimal::Decimal;
 use secrecy::{ExposeSecret, SecretString};
 
 use crate::error::ConfigError;
@@ -19,6 +21,7 @@ pub struct Config {
     pub database: DatabaseConfig,
     pub llm: LlmConfig,
     pub embeddings: EmbeddingsConfig,
+    pub memory: MemoryConfig,
     pub tunnel: TunnelConfig,
     pub channels: ChannelsConfig,
     pub agent: AgentConfig,
@@ -30,6 +33,22 @@ pub struct Config {
     pub routines: RoutineConfig,
     pub sandbox: SandboxModeConfig,
     pub claude_code: ClaudeCodeConfig,
+    pub startup: StartupConfig,
+    pub browser: BrowserConfig,
+    pub outbound: OutboundConfig,
+    pub dead_man: DeadManConfig,
+    pub attachments: AttachmentConfig,
+    pub sql: SqlConfig,
+    pub crash: CrashConfig,
+    pub email: EmailConfig,
+    pub calendar: CalendarConfig,
+    pub scheduling: SchedulingConfig,
+    pub web_fetch: WebFetchConfig,
+    pub citations: CitationConfig,
+    pub away: AwayConfig,
+    pub code_review: CodeReviewConfig,
+    pub budget: BudgetConfig,
+    pub delegate: DelegateConfig,
 }
 
 impl Config {
@@ -77,6 +96,7 @@ impl Config {
             database: DatabaseConfig::resolve(bootstrap)?,
             llm: LlmConfig::resolve(settings)?,
             embeddings: EmbeddingsConfig::resolve(settings)?,
+            memory: MemoryConfig::resolve(settings)?,
             tunnel: TunnelConfig::resolve(settings)?,
             channels: ChannelsConfig::resolve(settings)?,
             agent: AgentConfig::resolve(settings)?,
@@ -88,6 +108,22 @@ impl Config {
             routines: RoutineConfig::resolve()?,
             sandbox: SandboxModeConfig::resolve()?,
             claude_code: ClaudeCodeConfig::resolve()?,
+            startup: StartupConfig::resolve()?,
+            browser: BrowserConfig::resolve(settings)?,
+            outbound: OutboundConfig::resolve(settings)?,
+            dead_man: DeadManConfig::resolve()?,
+            attachments: AttachmentConfig::resolve()?,
+            sql: SqlConfig::resolve()?,
+            crash: CrashConfig::resolve()?,
+            email: EmailConfig::resolve()?,
+            calendar: CalendarConfig::resolve()?,
+            scheduling: SchedulingConfig::resolve()?,
+            web_fetch: WebFetchConfig::resolve()?,
+            citations: CitationConfig::resolve()?,
+            away: AwayConfig::resolve()?,
+            code_review: CodeReviewConfig::resolve()?,
+            budget: BudgetConfig::resolve()?,
+            delegate: DelegateConfig::resolve()?,
         })
     }
 }
@@ -399,6 +435,52 @@ pub struct LlmConfig {
     pub bedrock: Option<BedrockDirectConfig>,
     /// OpenRouter config (populated when backend=openrouter)
     pub openrouter: Option<OpenRouterConfig>,
+    /// Outbound redaction of personal data in prompts
+    pub redaction: RedactionConfig,
+}
+
+/// Outbound redaction profile for LLM calls.
+///
+/// When enabled, names, email addresses and phone numbers are replaced with
+/// pseudonyms before a request leaves the host. The mapping table stays in
+/// process memory so responses can be re-identified.
+#[derive(Debug, Clone, Default)]
+pub struct RedactionConfig {
+    /// Whether redaction is on (default: false).
+    pub enabled: bool,
+    /// Backends to redact for (e.g. "openai", "anthropic"). Empty means every
+    /// backend except the local `ollama`.
+    pub providers: Vec<String>,
+    /// Skills whose requests are sent unredacted.
+    pub exempt_skills: Vec<String>,
+    /// Names to pseudonymize in addition to those in the contacts store.
+    pub names: Vec<String>,
+}
+
+impl RedactionConfig {
+    fn resolve() -> Result<Self, ConfigError> {
+        Ok(Self {
+            enabled: parse_optional_env("LLM_REDACTION_ENABLED", false)?,
+            providers: optional_env_list("LLM_REDACTION_PROVIDERS")?
+                .into_iter()
+                .map(|p| p.to_lowercase())
+                .collect(),
+            exempt_skills: optional_env_list("LLM_REDACTION_EXEMPT_SKILLS")?,
+            names: optional_env_list("LLM_REDACTION_NAMES")?,
+        })
+    }
+
+    /// Whether requests to `backend` should be redacted.
+    pub fn applies_to(&self, backend: LlmBackend) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if self.providers.is_empty() {
+            return backend != LlmBackend::Ollama;
+        }
+        let name = backend.to_string();
+        self.providers.contains(&name)
+    }
 }
 
 /// API mode for NEAR AI.
@@ -622,8 +704,54 @@ impl LlmConfig {
             gemini,
             bedrock,
             openrouter,
+            redaction: RedactionConfig::resolve()?,
         })
     }
+
+    /// The same backend and credentials with a different model, for
+    /// auxiliary calls that should run on a cheaper model.
+    pub fn with_model(&self, model: &str) -> Self {
+        let mut config = self.clone();
+        match config.backend {
+            LlmBackend::NearAi => config.nearai.model = model.to_string(),
+            LlmBackend::OpenAi => {
+                if let Some(c) = config.openai.as_mut() {
+                    c.model = model.to_string();
+                }
+            }
+            LlmBackend::Anthropic => {
+                if let Some(c) = config.anthropic.as_mut() {
+                    c.model = model.to_string();
+                }
+            }
+            LlmBackend::Ollama => {
+                if let Some(c) = config.ollama.as_mut() {
+                    c.model = model.to_string();
+                }
+            }
+            LlmBackend::OpenAiCompatible => {
+                if let Some(c) = config.openai_compatible.as_mut() {
+                    c.model = model.to_string();
+                }
+            }
+            LlmBackend::Gemini => {
+                if let Some(c) = config.gemini.as_mut() {
+                    c.model = model.to_string();
+                }
+            }
+            LlmBackend::Bedrock => {
+                if let Some(c) = config.bedrock.as_mut() {
+                    c.model_id = model.to_string();
+                }
+            }
+            LlmBackend::OpenRouter => {
+                if let Some(c) = config.openrouter.as_mut() {
+                    c.model = model.to_string();
+                }
+            }
+        }
+        config
+    }
 }
 
 /// Embeddings provider configuration.
@@ -637,6 +765,12 @@ pub struct EmbeddingsConfig {
     pub openai_api_key: Option<SecretString>,
     /// Model to use for embeddings.
     pub model: String,
+    /// Search re-ranker: "none", "cross-encoder" or "llm".
+    pub reranker: String,
+    /// Base URL of the cross-encoder `/rerank` server.
+    pub rerank_url: Option<String>,
+    /// Batching and concurrency for indexing.
+    pub embedder: crate::workspace::EmbedderConfig,
 }
 
 impl Default for EmbeddingsConfig {
@@ -646,6 +780,9 @@ impl Default for EmbeddingsConfig {
             provider: "openai".to_string(),
             openai_api_key: None,
             model: "text-embedding-3-small".to_string(),
+            reranker: "none".to_string(),
+            rerank_url: None,
+            embedder: crate::workspace::EmbedderConfig::default(),
         }
     }
 }
@@ -669,11 +806,33 @@ impl EmbeddingsConfig {
             })?
             .unwrap_or_else(|| settings.embeddings.enabled || openai_api_key.is_some());
 
+        let reranker = optional_env("SEARCH_RERANKER")?
+            .unwrap_or_else(|| settings.embeddings.reranker.clone());
+        let rerank_url =
+            optional_env("SEARCH_RERANK_URL")?.or_else(|| settings.embeddings.rerank_url.clone());
+
+        let defaults = crate::workspace::EmbedderConfig::default();
+        let embedder = crate::workspace::EmbedderConfig {
+            concurrency: parse_optional_env("EMBEDDING_CONCURRENCY", defaults.concurrency)?,
+            max_batch_size: optional_env("EMBEDDING_BATCH_SIZE")?
+                .map(|s| s.parse())
+                .transpose()
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: "EMBEDDING_BATCH_SIZE".to_string(),
+                    message: format!("must be a positive integer: {e}"),
+                })?,
+            max_retries: parse_optional_env("EMBEDDING_MAX_RETRIES", defaults.max_retries)?,
+            ..defaults
+        };
+
         Ok(Self {
             enabled,
             provider,
             openai_api_key,
             model,
+            reranker,
+            rerank_url,
+            embedder,
         })
     }
 
@@ -683,6 +842,82 @@ impl EmbeddingsConfig {
     }
 }
 
+/// Workspace memory storage configuration.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryConfig {
+    /// Storage quotas, checked hourly and by `ironclaw memory usage`.
+    pub quota: crate::workspace::QuotaConfig,
+    /// Conversation history indexing for recall.
+    pub history: crate::workspace::HistoryIndexConfig,
+}
+
+impl MemoryConfig {
+    fn resolve(settings: &Settings) -> Result<Self, ConfigError> {
+        let mut quota = settings.memory.quota.clone();
+
+        let limit = |key: &str, current: Option<u64>| -> Result<Option<u64>, ConfigError> {
+            optional_env(key)?
+                .map(|s| s.parse::<u64>())
+                .transpose()
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: key.to_string(),
+                    message: format!("must be an integer: {e}"),
+                })
+                .map(|v| v.or(current))
+        };
+        quota.total.max_documents = limit("MEMORY_QUOTA_MAX_DOCUMENTS", quota.total.max_documents)?;
+        quota.total.max_chunks = limit("MEMORY_QUOTA_MAX_CHUNKS", quota.total.max_chunks)?;
+        quota.total.max_embedding_mb = limit(
+            "MEMORY_QUOTA_MAX_EMBEDDING_MB",
+            quota.total.max_embedding_mb,
+        )?;
+
+        if let Some(spaces) = optional_env("MEMORY_QUOTA_SPACES")? {
+            quota.spaces =
+                serde_json::from_str(&spaces).map_err(|e| ConfigError::InvalidValue {
+                    key: "MEMORY_QUOTA_SPACES".to_string(),
+                    message: format!("must be a JSON array of space quotas: {e}"),
+                })?;
+        }
+        if let Some(action) = optional_env("MEMORY_QUOTA_ACTION")? {
+            quota.action =
+                serde_json::from_value(serde_json::Value::String(action)).map_err(|_| {
+                    ConfigError::InvalidValue {
+                        key: "MEMORY_QUOTA_ACTION".to_string(),
+                        message: "must be 'warn' or 'consolidate'".to_string(),
+                    }
+                })?;
+        }
+
+        let mut history = settings.memory.history.clone();
+        if let Some(enabled) = optional_env("MEMORY_HISTORY_ENABLED")? {
+            history.enabled = enabled.parse().map_err(|e| ConfigError::InvalidValue {
+                key: "MEMORY_HISTORY_ENABLED".to_string(),
+                message: format!("must be 'true' or 'false': {e}"),
+            })?;
+        }
+        if let Some(space) = optional_env("MEMORY_HISTORY_SPACE")? {
+            history.space = space;
+        }
+        history.retention_days =
+            parse_optional_env("MEMORY_HISTORY_RETENTION_DAYS", history.retention_days)?;
+        for entry in optional_env_list("MEMORY_HISTORY_CHANNEL_RETENTION")? {
+            let days = entry
+                .split_once('=')
+                .and_then(|(channel, days)| Some((channel.trim(), days.trim().parse().ok()?)));
+            let Some((channel, days)) = days else {
+                return Err(ConfigError::InvalidValue {
+                    key: "MEMORY_HISTORY_CHANNEL_RETENTION".to_string(),
+                    message: format!("expected channel=days, got '{entry}'"),
+                });
+            };
+            history.channel_retention.insert(channel.to_string(), days);
+        }
+
+        Ok(Self { quota, history })
+    }
+}
+
 /// Get the default session file path (~/.ironclaw/session.json).
 fn default_session_path() -> PathBuf {
     dirs::home_dir()
@@ -703,6 +938,11 @@ pub struct ChannelsConfig {
     pub wasm_channels_enabled: bool,
     /// Telegram owner user ID. When set, the bot only responds to this user.
     pub telegram_owner_id: Option<i64>,
+    /// Routing rules for background notifications.
+    pub notification_routes: Vec<crate::channels::RouteRule>,
+    /// Output post-processors enabled per channel name (`"*"` for the
+    /// rest). Empty runs none.
+    pub output_processors: HashMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Clone)]
@@ -728,6 +968,8 @@ pub struct GatewayConfig {
     /// Bearer token for authentication. Random hex generated at startup if unset.
     pub auth_token: Option<String>,
     pub user_id: String,
+    /// Readiness checks and timeouts for `/readyz`.
+    pub health: crate::channels::web::health::HealthConfig,
 }
 
 impl std::fmt::Debug for GatewayConfig {
@@ -740,10 +982,33 @@ impl std::fmt::Debug for GatewayConfig {
                 &self.auth_token.as_ref().map(|_| "[REDACTED]"),
             )
             .field("user_id", &self.user_id)
+            .field("health", &self.health)
             .finish()
     }
 }
 
+/// `GATEWAY_READY_CHECKS` and `GATEWAY_HEALTH_TIMEOUT_MS`.
+fn resolve_gateway_health() -> Result<crate::channels::web::health::HealthConfig, ConfigError> {
+    use crate::channels::web::health::{HealthConfig, ReadyCheck};
+
+    let mut health = HealthConfig::default();
+    if let Some(checks) = optional_env("GATEWAY_READY_CHECKS")? {
+        health.ready_checks =
+            ReadyCheck::parse_list(&checks).map_err(|message| ConfigError::InvalidValue {
+                key: "GATEWAY_READY_CHECKS".to_string(),
+                message,
+            })?;
+    }
+    if let Some(ms) = optional_env("GATEWAY_HEALTH_TIMEOUT_MS")? {
+        let ms: u64 = ms.parse().map_err(|e| ConfigError::InvalidValue {
+            key: "GATEWAY_HEALTH_TIMEOUT_MS".to_string(),
+            message: format!("must be a number of milliseconds: {e}"),
+        })?;
+        health.check_timeout = Duration::from_millis(ms.max(1));
+    }
+    Ok(health)
+}
+
 impl ChannelsConfig {
     fn resolve(settings: &Settings) -> Result<Self, ConfigError> {
         let http = if optional_env("HTTP_PORT")?.is_some() || optional_env("HTTP_HOST")?.is_some() {
@@ -780,6 +1045,7 @@ impl ChannelsConfig {
                     .unwrap_or(3000),
                 auth_token: optional_env("GATEWAY_AUTH_TOKEN")?,
                 user_id: optional_env("GATEWAY_USER_ID")?.unwrap_or_else(|| "default".to_string()),
+                health: resolve_gateway_health()?,
             })
         } else {
             None
@@ -814,10 +1080,49 @@ impl ChannelsConfig {
                     message: format!("must be an integer: {e}"),
                 })?
                 .or(settings.channels.telegram_owner_id),
+            notification_routes: optional_env("NOTIFICATION_ROUTES")?
+                .map(|s| serde_json::from_str(&s))
+                .transpose()
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: "NOTIFICATION_ROUTES".to_string(),
+                    message: format!("must be a JSON array of routing rules: {e}"),
+                })?
+                .unwrap_or_else(|| settings.notifications.routes.clone()),
+            output_processors: parse_output_processors()?,
         })
     }
 }
 
+/// Read `OUTPUT_PROCESSORS`: a JSON object of channel name to processor
+/// names, e.g. `{"*": ["markdown_lint"], "repl": []}`.
+fn parse_output_processors() -> Result<HashMap<String, Vec<String>>, ConfigError> {
+    let invalid = |message: String| ConfigError::InvalidValue {
+        key: "OUTPUT_PROCESSORS".to_string(),
+        message,
+    };
+    let Some(raw) = optional_env("OUTPUT_PROCESSORS")? else {
+        return Ok(HashMap::new());
+    };
+    let processors: HashMap<String, Vec<String>> = serde_json::from_str(&raw).map_err(|e| {
+        invalid(format!(
+            "must be a JSON object of channel to processors: {e}"
+        ))
+    })?;
+    let builtin = crate::channels::postprocess::BUILTIN_PROCESSORS;
+    if let Some(unknown) = processors
+        .values()
+        .flatten()
+        .find(|name| !builtin.contains(&name.as_str()))
+    {
+        return Err(invalid(format!(
+            "unknown processor '{}' (expected one of: {})",
+            unknown,
+            builtin.join(", ")
+        )));
+    }
+    Ok(processors)
+}
+
 /// Get the default channels directory (~/.ironclaw/channels/).
 fn default_channels_dir() -> PathBuf {
     dirs::home_dir()
@@ -841,6 +1146,29 @@ pub struct AgentConfig {
     pub session_idle_timeout: Duration,
     /// Allow chat to use filesystem/shell tools directly (bypass sandbox).
     pub allow_local_tools: bool,
+    /// Window in which an identical message from the same user reuses the
+    /// previous turn's response. Zero disables deduplication.
+    pub dedup_window: Duration,
+    /// Half-life of tool failure hints injected into tool descriptions.
+    /// Zero disables failure hints.
+    pub tool_hint_half_life: Duration,
+    /// Record per-phase turn timings to the turn profile log.
+    pub profile_turns: bool,
+    /// Default time zone and language for users without their own settings.
+    pub locale: crate::locale::LocaleDefaults,
+    /// Tool outputs longer than this many characters are summarized before
+    /// re-entering the context. Zero disables summarization.
+    pub tool_summary_threshold: usize,
+    /// Model for tool output summaries; `None` uses the main model.
+    pub tool_summary_model: Option<String>,
+    /// Cheaper model that short chat turns are routed to; `None` disables
+    /// per-turn model tiers.
+    pub cheap_model: Option<String>,
+    /// Send reply text to channels as the model generates it.
+    pub stream_responses: bool,
+    /// Times a turn resumes after an LLM failure partway through, with the
+    /// error summarized into its context. Zero fails on the first error.
+    pub turn_recovery_attempts: u32,
 }
 
 impl AgentConfig {
@@ -919,18 +1247,134 @@ impl AgentConfig {
                     message: format!("must be 'true' or 'false': {e}"),
                 })?
                 .unwrap_or(false),
+            dedup_window: Duration::from_secs(
+                optional_env("AGENT_DEDUP_WINDOW_SECS")?
+                    .map(|s| s.parse())
+                    .transpose()
+                    .map_err(|e| ConfigError::InvalidValue {
+                        key: "AGENT_DEDUP_WINDOW_SECS".to_string(),
+                        message: format!("must be a non-negative integer: {e}"),
+                    })?
+                    .unwrap_or(settings.agent.dedup_window_secs),
+            ),
+            tool_hint_half_life: Duration::from_secs(
+                optional_env("AGENT_TOOL_HINT_HALF_LIFE_SECS")?
+                    .map(|s| s.parse())
+                    .transpose()
+                    .map_err(|e| ConfigError::InvalidValue {
+                        key: "AGENT_TOOL_HINT_HALF_LIFE_SECS".to_string(),
+                        message: format!("must be a non-negative integer: {e}"),
+                    })?
+                    .unwrap_or(settings.agent.tool_hint_half_life_secs),
+            ),
+            profile_turns: optional_env("AGENT_PROFILE_TURNS")?
+                .map(|s| s.parse())
+                .transpose()
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: "AGENT_PROFILE_TURNS".to_string(),
+                    message: format!("must be 'true' or 'false': {e}"),
+                })?
+                .unwrap_or(settings.agent.profile_turns),
+            locale: resolve_locale(settings)?,
+            tool_summary_threshold: optional_env("AGENT_TOOL_SUMMARY_THRESHOLD")?
+                .map(|s| s.parse())
+                .transpose()
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: "AGENT_TOOL_SUMMARY_THRESHOLD".to_string(),
+                    message: format!("must be a non-negative integer: {e}"),
+                })?
+                .unwrap_or(settings.agent.tool_summary_threshold),
+            tool_summary_model: optional_env("AGENT_TOOL_SUMMARY_MODEL")?
+                .or_else(|| settings.agent.tool_summary_model.clone()),
+            cheap_model: optional_env("AGENT_CHEAP_MODEL")?
+                .or_else(|| settings.agent.cheap_model.clone()),
+            stream_responses: optional_env("AGENT_STREAM_RESPONSES")?
+                .map(|s| s.parse())
+                .transpose()
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: "AGENT_STREAM_RESPONSES".to_string(),
+                    message: format!("must be 'true' or 'false': {e}"),
+                })?
+                .unwrap_or(true),
+            turn_recovery_attempts: optional_env("AGENT_TURN_RECOVERY_ATTEMPTS")?
+                .map(|s| s.parse())
+                .transpose()
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: "AGENT_TURN_RECOVERY_ATTEMPTS".to_string(),
+                    message: format!("must be a non-negative integer: {e}"),
+                })?
+                .unwrap_or(settings.agent.turn_recovery_attempts),
         })
     }
 }
 
+/// Default locale: env var > settings > host zone > UTC.
+fn resolve_locale(settings: &Settings) -> Result<crate::locale::LocaleDefaults, ConfigError> {
+    use crate::locale::{DEFAULT_LANGUAGE, LocaleDefaults, Tz, UserLocale, system_zone_name};
+
+    let tz = match optional_env("AGENT_TIMEZONE")? {
+        Some(name) => Tz::parse(&name).map_err(|e| ConfigError::InvalidValue {
+            key: "AGENT_TIMEZONE".to_string(),
+            message: e,
+        })?,
+        None => settings
+            .locale
+            .timezone
+            .clone()
+            .or_else(system_zone_name)
+            .and_then(|name| match Tz::parse(&name) {
+                Ok(tz) => Some(tz),
+                Err(e) => {
+                    tracing::warn!("Ignoring time zone setting: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_else(Tz::utc),
+    };
+    let language = optional_env("AGENT_LOCALE")?
+        .or_else(|| settings.locale.language.clone())
+        .unwrap_or_else(|| DEFAULT_LANGUAGE.to_string());
+    let channel_timezones = settings
+        .locale
+        .channel_timezones
+        .iter()
+        .filter_map(|(channel, name)| match Tz::parse(name) {
+            Ok(tz) => Some((channel.clone(), tz)),
+            Err(e) => {
+                tracing::warn!("Ignoring time zone for channel {}: {}", channel, e);
+                None
+            }
+        })
+        .collect();
+    Ok(LocaleDefaults {
+        locale: UserLocale::new(tz, language),
+        channel_timezones,
+    })
+}
+
 /// Safety configuration.
 #[derive(Debug, Clone)]
 pub struct SafetyConfig {
     pub max_output_length: usize,
     pub injection_check_enabled: bool,
+    /// Keep the originals of redacted tool outputs in the encrypted
+    /// redaction vault (needs the secrets master key).
+    pub preserve_redacted: bool,
+    /// Days preserved originals are kept.
+    pub preserve_redacted_days: u32,
+    /// Provenance headers / watermarks for outbound content, per destination
+    /// (empty = none).
+    pub provenance: Vec<crate::safety::provenance::ProvenanceRule>,
 }
 
 impl SafetyConfig {
+    /// Resolve from `.env` and the environment alone, for commands that
+    /// don't need a database (e.g. `ironclaw safety test`).
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let _ = dotenvy::dotenv();
+        Self::resolve()
+    }
+
     fn resolve() -> Result<Self, ConfigError> {
         Ok(Self {
             max_output_length: parse_optional_env("SAFETY_MAX_OUTPUT_LENGTH", 100_000)?,
@@ -942,10 +1386,38 @@ impl SafetyConfig {
                     message: format!("must be 'true' or 'false': {e}"),
                 })?
                 .unwrap_or(true),
+            preserve_redacted: optional_env("SAFETY_PRESERVE_REDACTED")?
+                .map(|s| s.parse())
+                .transpose()
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: "SAFETY_PRESERVE_REDACTED".to_string(),
+                    message: format!("must be 'true' or 'false': {e}"),
+                })?
+                .unwrap_or(false),
+            preserve_redacted_days: parse_optional_env("SAFETY_PRESERVE_REDACTED_DAYS", 7)?,
+            provenance: parse_provenance_rules()?,
         })
     }
 }
 
+/// Parse `SAFETY_PROVENANCE_RULES`, a JSON array of provenance rules.
+fn parse_provenance_rules() -> Result<Vec<crate::safety::provenance::ProvenanceRule>, ConfigError> {
+    let invalid = |message: String| ConfigError::InvalidValue {
+        key: "SAFETY_PROVENANCE_RULES".to_string(),
+        message,
+    };
+    let rules: Vec<crate::safety::provenance::ProvenanceRule> =
+        optional_env("SAFETY_PROVENANCE_RULES")?
+            .map(|s| serde_json::from_str(&s))
+            .transpose()
+            .map_err(|e| invalid(format!("must be a JSON array of provenance rules: {e}")))?
+            .unwrap_or_default();
+    for rule in &rules {
+        rule.validate().map_err(invalid)?;
+    }
+    Ok(rules)
+}
+
 /// WASM sandbox configuration.
 #[derive(Debug, Clone)]
 pub struct WasmConfig {
@@ -963,6 +1435,17 @@ pub struct WasmConfig {
     pub cache_compiled: bool,
     /// Directory for compiled module cache.
     pub cache_dir: Option<PathBuf>,
+    /// Tool source directories whose builds are reloaded on change.
+    pub dev_watch: Vec<PathBuf>,
+    /// Allocate instances from Wasmtime's pooling allocator.
+    pub pooling: bool,
+    /// Instances alive at once across all tools when pooling.
+    pub pool_max_instances: u32,
+    /// Instances each tool keeps ready ahead of its next call.
+    pub warm_instances: usize,
+    /// Strikes (traps, denied calls, leak hits, rate-limit abuse) before a
+    /// tool is quarantined; 0 turns quarantine off.
+    pub quarantine_strikes: u32,
 }
 
 /// Secrets management configuration.
@@ -1057,6 +1540,11 @@ impl Default for WasmConfig {
             default_fuel_limit: 10_000_000,
             cache_compiled: true,
             cache_dir: None,
+            dev_watch: Vec::new(),
+            pooling: false,
+            pool_max_instances: 100,
+            warm_instances: 1,
+            quarantine_strikes: crate::tools::wasm::DEFAULT_STRIKE_THRESHOLD,
         }
     }
 }
@@ -1098,12 +1586,23 @@ impl WasmConfig {
                 })?
                 .unwrap_or(true),
             cache_dir: optional_env("WASM_CACHE_DIR")?.map(PathBuf::from),
+            dev_watch: optional_env_list("WASM_DEV_WATCH")?
+                .into_iter()
+                .map(PathBuf::from)
+                .collect(),
+            pooling: parse_optional_env("WASM_POOLING", false)?,
+            pool_max_instances: parse_optional_env("WASM_POOL_MAX_INSTANCES", 100)?,
+            warm_instances: parse_optional_env("WASM_WARM_INSTANCES", 1)?,
+            quarantine_strikes: parse_optional_env(
+                "WASM_QUARANTINE_STRIKES",
+                crate::tools::wasm::DEFAULT_STRIKE_THRESHOLD,
+            )?,
         })
     }
 
     /// Convert to WasmRuntimeConfig.
     pub fn to_runtime_config(&self) -> crate::tools::wasm::WasmRuntimeConfig {
-        use crate::tools::wasm::{FuelConfig, ResourceLimits, WasmRuntimeConfig};
+        use crate::tools::wasm::{FuelConfig, PoolingConfig, ResourceLimits, WasmRuntimeConfig};
         use std::time::Duration;
 
         WasmRuntimeConfig {
@@ -1119,6 +1618,11 @@ impl WasmConfig {
             cache_compiled: self.cache_compiled,
             cache_dir: self.cache_dir.clone(),
             optimization_level: wasmtime::OptLevel::Speed,
+            pooling: self.pooling.then_some(PoolingConfig {
+                max_instances: self.pool_max_instances,
+                max_memory_bytes: self.default_memory_limit,
+            }),
+            warm_instances: self.warm_instances,
         }
     }
 }
@@ -1241,6 +1745,95 @@ impl HeartbeatConfig {
     }
 }
 
+/// Remote browser configuration for the browser tool.
+#[derive(Debug, Clone)]
+pub struct BrowserConfig {
+    /// CDP endpoint (`ws://`, `wss://`, `http://` or `https://`). The browser
+    /// tool is registered only when this is set.
+    pub cdp_endpoint: Option<String>,
+    /// Blank tabs kept open for new sessions.
+    pub pool_size: usize,
+    /// Maximum concurrent browser sessions.
+    pub max_sessions: usize,
+    /// Where screenshots from the remote browser are saved.
+    pub screenshot_dir: PathBuf,
+}
+
+impl BrowserConfig {
+    fn resolve(settings: &Settings) -> Result<Self, ConfigError> {
+        let cdp_endpoint = optional_env("BROWSER_CDP_ENDPOINT")?
+            .or_else(|| settings.browser.cdp_endpoint.clone())
+            .filter(|e| !e.trim().is_empty());
+        if let Some(endpoint) = &cdp_endpoint
+            && !["ws://", "wss://", "http://", "https://"]
+                .iter()
+                .any(|scheme| endpoint.starts_with(scheme))
+        {
+            return Err(ConfigError::InvalidValue {
+                key: "BROWSER_CDP_ENDPOINT".to_string(),
+                message: "must be a ws://, wss://, http:// or https:// URL".to_string(),
+            });
+        }
+
+        Ok(Self {
+            cdp_endpoint,
+            pool_size: parse_optional_env("BROWSER_POOL_SIZE", settings.browser.pool_size)?,
+            max_sessions: parse_optional_env(
+                "BROWSER_MAX_SESSIONS",
+                settings.browser.max_sessions,
+            )?,
+            screenshot_dir: optional_env("BROWSER_SCREENSHOT_DIR")?
+                .map(PathBuf::from)
+                .or_else(|| settings.browser.screenshot_dir.clone())
+                .unwrap_or_else(default_screenshot_dir),
+        })
+    }
+}
+
+/// Corporate proxy and CA bundle for outbound HTTP, installed with
+/// [`crate::outbound::install`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutboundConfig {
+    /// Proxy URL (`http`, `https`, `socks5` or `socks5h`). When unset, the
+    /// standard `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY` variables still apply.
+    pub proxy: Option<String>,
+    /// Hosts reached without the proxy, on top of loopback.
+    pub no_proxy: Vec<String>,
+    /// Extra root certificates (PEM bundle).
+    pub ca_bundle: Option<PathBuf>,
+}
+
+impl OutboundConfig {
+    fn resolve(settings: &Settings) -> Result<Self, ConfigError> {
+        let proxy = optional_env("OUTBOUND_PROXY")?
+            .or_else(|| settings.outbound.proxy.clone())
+            .filter(|p| !p.trim().is_empty());
+        if let Some(proxy) = &proxy
+            && !["http://", "https://", "socks5://", "socks5h://"]
+                .iter()
+                .any(|scheme| proxy.starts_with(scheme))
+        {
+            return Err(ConfigError::InvalidValue {
+                key: "OUTBOUND_PROXY".to_string(),
+                message: "must be an http://, https://, socks5:// or socks5h:// URL".to_string(),
+            });
+        }
+
+        let no_proxy = optional_env_list("OUTBOUND_NO_PROXY")?;
+        Ok(Self {
+            proxy,
+            no_proxy: if no_proxy.is_empty() {
+                settings.outbound.no_proxy.clone()
+            } else {
+                no_proxy
+            },
+            ca_bundle: optional_env("OUTBOUND_CA_BUNDLE")?
+                .map(|p| crate::agent::project::expand_home(&p))
+                .or_else(|| settings.outbound.ca_bundle.clone()),
+        })
+    }
+}
+
 /// Routines configuration.
 #[derive(Debug, Clone)]
 pub struct RoutineConfig {
@@ -1300,12 +1893,69 @@ pub struct SandboxModeConfig {
     pub memory_limit_mb: u64,
     /// CPU shares (relative weight).
     pub cpu_shares: u32,
-    /// Docker image for the sandbox.
+    /// Container image for the sandbox.
     pub image: String,
+    /// Container runtime: docker, podman, or containerd.
+    pub backend: crate::sandbox::ContainerBackendKind,
     /// Whether to auto-pull the image if not found.
     pub auto_pull_image: bool,
     /// Additional domains to allow through the network proxy.
     pub extra_allowed_domains: Vec<String>,
+    /// Directory for the proxy's package artifact cache (None = caching off).
+    pub package_cache_dir: Option<std::path::PathBuf>,
+    /// Package cache size budget in megabytes.
+    pub package_cache_max_mb: u64,
+    /// Warm sandbox containers kept per policy and workspace (0 = off).
+    pub warm_pool_size: usize,
+    /// Seconds an interactive sandbox session may go unused before it is closed.
+    pub session_idle_timeout_secs: u64,
+    /// Run sandbox-required commands on the host under a restricted policy
+    /// when Docker is unavailable (after a per-session risk acknowledgement).
+    pub host_fallback: bool,
+    /// Programs host fallback commands may run.
+    pub fallback_bins: Vec<String>,
+    /// Directories host fallback commands may run in and refer to.
+    pub fallback_roots: Vec<std::path::PathBuf>,
+    /// Maximum processes per sandbox container.
+    pub pids_limit: Option<i64>,
+    /// Size cap for a sandbox container's writable layer in megabytes.
+    pub disk_quota_mb: Option<u64>,
+    /// Size of each tmpfs scratch mount in megabytes.
+    pub tmpfs_size_mb: Option<u64>,
+    /// GPUs passed through to sandbox containers.
+    pub gpus: crate::sandbox::GpuAccess,
+    /// GitHub App minting per-job installation tokens (None = off).
+    pub github_app: Option<GitHubAppIssuerConfig>,
+    /// Token exchange minting per-job cloud credentials (None = off).
+    pub token_exchange: Option<TokenExchangeIssuerConfig>,
+}
+
+/// GitHub App used to mint short-lived installation tokens for jobs.
+#[derive(Debug, Clone)]
+pub struct GitHubAppIssuerConfig {
+    pub app_id: String,
+    pub installation_id: u64,
+    /// PEM private key of the app. Read on the host only.
+    pub key_path: PathBuf,
+    /// Permissions the tokens are narrowed to (`contents` → `read`).
+    pub permissions: std::collections::BTreeMap<String, String>,
+    /// Repositories the tokens are narrowed to.
+    pub repositories: Vec<String>,
+}
+
+/// STS-style endpoint exchanging a long-lived token for short-lived ones.
+/// The subject token itself comes from `SANDBOX_TOKEN_EXCHANGE_SUBJECT_TOKEN`
+/// and is read only when the issuer is built.
+#[derive(Debug, Clone)]
+pub struct TokenExchangeIssuerConfig {
+    pub token_url: String,
+    pub revocation_url: Option<String>,
+    /// Host the minted token is injected for.
+    pub domain: String,
+    /// Secret name the minted token stands in for.
+    pub secret_name: String,
+    pub audience: Option<String>,
+    pub scope: Option<String>,
 }
 
 impl Default for SandboxModeConfig {
@@ -1317,8 +1967,22 @@ impl Default for SandboxModeConfig {
             memory_limit_mb: 2048,
             cpu_shares: 1024,
             image: "ghcr.io/nearai/sandbox:latest".to_string(),
+            backend: crate::sandbox::ContainerBackendKind::default(),
             auto_pull_image: true,
             extra_allowed_domains: Vec::new(),
+            package_cache_dir: None,
+            package_cache_max_mb: 2048,
+            warm_pool_size: 0,
+            session_idle_timeout_secs: 600,
+            host_fallback: false,
+            fallback_bins: crate::sandbox::fallback::default_fallback_bins(),
+            fallback_roots: vec![default_fallback_root()],
+            pids_limit: None,
+            disk_quota_mb: None,
+            tmpfs_size_mb: None,
+            gpus: crate::sandbox::GpuAccess::None,
+            github_app: None,
+            token_exchange: None,
         }
     }
 }
@@ -1329,6 +1993,44 @@ impl SandboxModeConfig {
             .map(|s| s.split(',').map(|d| d.trim().to_string()).collect())
             .unwrap_or_default();
 
+        let package_cache_enabled: bool = parse_optional_env("SANDBOX_PACKAGE_CACHE", false)?;
+        let package_cache_dir = if package_cache_enabled {
+            Some(
+                optional_env("SANDBOX_PACKAGE_CACHE_DIR")?
+                    .map(std::path::PathBuf::from)
+                    .unwrap_or_else(default_package_cache_dir),
+            )
+        } else {
+            None
+        };
+
+        let fallback_bins = optional_env_list("SANDBOX_FALLBACK_BINS")?;
+        let fallback_roots: Vec<std::path::PathBuf> = optional_env_list("SANDBOX_FALLBACK_ROOTS")?
+            .iter()
+            .map(|root| crate::agent::project::expand_home(root))
+            .collect();
+
+        fn limit<T>(key: &str) -> Result<Option<T>, ConfigError>
+        where
+            T: std::str::FromStr,
+            T::Err: std::fmt::Display,
+        {
+            optional_env(key)?
+                .map(|s| s.parse::<T>())
+                .transpose()
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: key.to_string(),
+                    message: format!("must be an integer: {e}"),
+                })
+        }
+        let pids_limit: Option<i64> = limit("SANDBOX_PIDS_LIMIT")?;
+        if pids_limit.is_some_and(|n| n < 1) {
+            return Err(ConfigError::InvalidValue {
+                key: "SANDBOX_PIDS_LIMIT".to_string(),
+                message: "must be at least 1".to_string(),
+            });
+        }
+
         Ok(Self {
             enabled: optional_env("SANDBOX_ENABLED")?
                 .map(|s| s.parse())
@@ -1344,6 +2046,7 @@ impl SandboxModeConfig {
             cpu_shares: parse_optional_env("SANDBOX_CPU_SHARES", 1024)?,
             image: optional_env("SANDBOX_IMAGE")?
                 .unwrap_or_else(|| "ghcr.io/nearai/sandbox:latest".to_string()),
+            backend: parse_optional_env("SANDBOX_BACKEND", Default::default())?,
             auto_pull_image: optional_env("SANDBOX_AUTO_PULL")?
                 .map(|s| s.parse())
                 .transpose()
@@ -1353,9 +2056,87 @@ impl SandboxModeConfig {
                 })?
                 .unwrap_or(true),
             extra_allowed_domains: extra_domains,
+            package_cache_dir,
+            package_cache_max_mb: parse_optional_env("SANDBOX_PACKAGE_CACHE_MAX_MB", 2048)?,
+            warm_pool_size: parse_optional_env("SANDBOX_WARM_POOL_SIZE", 0)?,
+            session_idle_timeout_secs: parse_optional_env(
+                "SANDBOX_SESSION_IDLE_TIMEOUT_SECS",
+                600,
+            )?,
+            host_fallback: parse_optional_env("SANDBOX_HOST_FALLBACK", false)?,
+            fallback_bins: if fallback_bins.is_empty() {
+                crate::sandbox::fallback::default_fallback_bins()
+            } else {
+                fallback_bins
+            },
+            fallback_roots: if fallback_roots.is_empty() {
+                vec![default_fallback_root()]
+            } else {
+                fallback_roots
+            },
+            pids_limit,
+            disk_quota_mb: limit("SANDBOX_DISK_QUOTA_MB")?,
+            tmpfs_size_mb: limit("SANDBOX_TMPFS_SIZE_MB")?,
+            gpus: parse_optional_env("SANDBOX_GPUS", Default::default())?,
+            github_app: GitHubAppIssuerConfig::resolve()?,
+            token_exchange: TokenExchangeIssuerConfig::resolve()?,
         })
     }
 
+    /// Build the per-job credential issuers that are configured, if any.
+    pub fn ephemeral_credentials(
+        &self,
+    ) -> Result<Option<std::sync::Arc<crate::sandbox::EphemeralCredentials>>, ConfigError> {
+        use crate::sandbox::{
+            CredentialIssuer, CredentialLocation, CredentialMapping, GitHubAppIssuer,
+            TokenExchangeIssuer,
+        };
+
+        let mut issuers: Vec<std::sync::Arc<dyn CredentialIssuer>> = Vec::new();
+        if let Some(app) = &self.github_app {
+            let pem =
+                std::fs::read_to_string(&app.key_path).map_err(|e| ConfigError::InvalidValue {
+                    key: "SANDBOX_GITHUB_APP_KEY_PATH".to_string(),
+                    message: format!("cannot read {}: {e}", app.key_path.display()),
+                })?;
+            let issuer = GitHubAppIssuer::new(&app.app_id, app.installation_id, &pem)
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: "SANDBOX_GITHUB_APP_KEY_PATH".to_string(),
+                    message: e.to_string(),
+                })?
+                .with_permissions(app.permissions.clone())
+                .with_repositories(app.repositories.clone());
+            issuers.push(std::sync::Arc::new(issuer));
+        }
+        if let Some(exchange) = &self.token_exchange {
+            let subject_token = optional_env("SANDBOX_TOKEN_EXCHANGE_SUBJECT_TOKEN")?
+                .ok_or_else(|| ConfigError::MissingRequired {
+                    key: "SANDBOX_TOKEN_EXCHANGE_SUBJECT_TOKEN".to_string(),
+                    hint: "SANDBOX_TOKEN_EXCHANGE_SUBJECT_TOKEN is required when SANDBOX_TOKEN_EXCHANGE_URL is set"
+                        .to_string(),
+                })?;
+            let mapping = CredentialMapping {
+                domain: exchange.domain.clone(),
+                secret_name: exchange.secret_name.clone(),
+                location: CredentialLocation::AuthorizationBearer,
+            };
+            let mut issuer = TokenExchangeIssuer::new(mapping, &exchange.token_url, subject_token);
+            if let Some(url) = &exchange.revocation_url {
+                issuer = issuer.with_revocation_url(url);
+            }
+            if let Some(audience) = &exchange.audience {
+                issuer = issuer.with_audience(audience);
+            }
+            if let Some(scope) = &exchange.scope {
+                issuer = issuer.with_scope(scope);
+            }
+            issuers.push(std::sync::Arc::new(issuer));
+        }
+
+        Ok((!issuers.is_empty())
+            .then(|| std::sync::Arc::new(crate::sandbox::EphemeralCredentials::new(issuers))))
+    }
+
     /// Convert to SandboxConfig for the sandbox module.
     pub fn to_sandbox_config(&self) -> crate::sandbox::SandboxConfig {
         use crate::sandbox::SandboxPolicy;
@@ -1374,12 +2155,119 @@ impl SandboxModeConfig {
             cpu_shares: self.cpu_shares,
             network_allowlist: allowlist,
             image: self.image.clone(),
+            backend: self.backend,
             auto_pull_image: self.auto_pull_image,
             proxy_port: 0, // Auto-assign
+            package_cache_dir: self.package_cache_dir.clone(),
+            package_cache_max_bytes: self.package_cache_max_mb * 1024 * 1024,
+            warm_pool_size: self.warm_pool_size,
+            session_idle_timeout: Duration::from_secs(self.session_idle_timeout_secs),
+            network_grant_audit_path: crate::sandbox::proxy::grants::default_audit_path(),
+            pids_limit: self.pids_limit,
+            disk_quota_mb: self.disk_quota_mb,
+            tmpfs_size_mb: self.tmpfs_size_mb,
+            gpus: self.gpus.clone(),
+        }
+    }
+}
+
+impl GitHubAppIssuerConfig {
+    fn resolve() -> Result<Option<Self>, ConfigError> {
+        let app_id = optional_env("SANDBOX_GITHUB_APP_ID")?;
+        let installation_id = optional_env("SANDBOX_GITHUB_APP_INSTALLATION_ID")?;
+        let key_path = optional_env("SANDBOX_GITHUB_APP_KEY_PATH")?;
+        let (app_id, installation_id, key_path) = match (app_id, installation_id, key_path) {
+            (None, None, None) => return Ok(None),
+            (Some(app_id), Some(installation_id), Some(key_path)) => {
+                (app_id, installation_id, key_path)
+            }
+            _ => {
+                return Err(ConfigError::InvalidValue {
+                    key: "SANDBOX_GITHUB_APP_ID".to_string(),
+                    message: "SANDBOX_GITHUB_APP_ID, SANDBOX_GITHUB_APP_INSTALLATION_ID and \
+                              SANDBOX_GITHUB_APP_KEY_PATH must be set together"
+                        .to_string(),
+                });
+            }
+        };
+        let installation_id = installation_id
+            .parse()
+            .map_err(|e| ConfigError::InvalidValue {
+                key: "SANDBOX_GITHUB_APP_INSTALLATION_ID".to_string(),
+                message: format!("must be an integer: {e}"),
+            })?;
+
+        let mut permissions = std::collections::BTreeMap::new();
+        for entry in optional_env_list("SANDBOX_GITHUB_APP_PERMISSIONS")? {
+            let Some((name, level)) = entry.split_once(':') else {
+                return Err(ConfigError::InvalidValue {
+                    key: "SANDBOX_GITHUB_APP_PERMISSIONS".to_string(),
+                    message: format!("'{entry}' is not name:level, e.g. contents:read"),
+                });
+            };
+            permissions.insert(name.trim().to_string(), level.trim().to_string());
         }
+
+        Ok(Some(Self {
+            app_id,
+            installation_id,
+            key_path: crate::agent::project::expand_home(&key_path),
+            permissions,
+            repositories: optional_env_list("SANDBOX_GITHUB_APP_REPOSITORIES")?,
+        }))
+    }
+}
+
+impl TokenExchangeIssuerConfig {
+    fn resolve() -> Result<Option<Self>, ConfigError> {
+        let Some(token_url) = optional_env("SANDBOX_TOKEN_EXCHANGE_URL")? else {
+            return Ok(None);
+        };
+        let domain = optional_env("SANDBOX_TOKEN_EXCHANGE_DOMAIN")?.ok_or_else(|| {
+            ConfigError::MissingRequired {
+                key: "SANDBOX_TOKEN_EXCHANGE_DOMAIN".to_string(),
+                hint: "SANDBOX_TOKEN_EXCHANGE_DOMAIN is required when SANDBOX_TOKEN_EXCHANGE_URL is set"
+                    .to_string(),
+            }
+        })?;
+        Ok(Some(Self {
+            token_url,
+            revocation_url: optional_env("SANDBOX_TOKEN_EXCHANGE_REVOCATION_URL")?,
+            domain,
+            secret_name: optional_env("SANDBOX_TOKEN_EXCHANGE_SECRET_NAME")?
+                .unwrap_or_else(|| "CLOUD_TOKEN".to_string()),
+            audience: optional_env("SANDBOX_TOKEN_EXCHANGE_AUDIENCE")?,
+            scope: optional_env("SANDBOX_TOKEN_EXCHANGE_SCOPE")?,
+        }))
     }
 }
 
+/// Default package cache directory (~/.ironclaw/cache/packages).
+fn default_package_cache_dir() -> std::path::PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join(".ironclaw")
+        .join("cache")
+        .join("packages")
+}
+
+/// Default screenshot directory (~/.ironclaw/screenshots).
+fn default_screenshot_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".ironclaw")
+        .join("screenshots")
+}
+
+/// Default host fallback root: the sandbox job projects directory
+/// (~/.ironclaw/projects).
+fn default_fallback_root() -> std::path::PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join(".ironclaw")
+        .join("projects")
+}
+
 /// Claude Code sandbox configuration.
 #[derive(Debug, Clone)]
 pub struct ClaudeCodeConfig {
@@ -1485,33 +2373,797 @@ impl ClaudeCodeConfig {
     }
 }
 
-// Helper functions
+/// Startup staging and boot profiling.
+#[derive(Debug, Clone)]
+pub struct StartupConfig {
+    /// Load WASM tools and MCP servers, seed the workspace and backfill
+    /// embeddings in the background instead of before the first prompt.
+    pub lazy_init: bool,
+    /// Where the boot profile is written (`ironclaw status --boot`).
+    pub profile_path: PathBuf,
+}
 
-fn optional_env(key: &str) -> Result<Option<String>, ConfigError> {
-    match std::env::var(key) {
-        Ok(val) if val.is_empty() => Ok(None),
-        Ok(val) => Ok(Some(val)),
-        Err(std::env::VarError::NotPresent) => Ok(None),
-        Err(e) => Err(ConfigError::ParseError(format!(
-            "failed to read {key}: {e}"
-        ))),
+impl Default for StartupConfig {
+    fn default() -> Self {
+        Self {
+            lazy_init: true,
+            profile_path: crate::boot::default_profile_path(),
+        }
     }
 }
 
-fn parse_optional_env<T>(key: &str, default: T) -> Result<T, ConfigError>
-where
-    T: std::str::FromStr,
-    T::Err: std::fmt::Display,
-{
-    optional_env(key)?
-        .map(|s| {
-            s.parse().map_err(|e| ConfigError::InvalidValue {
-                key: key.to_string(),
-                message: format!("{e}"),
-            })
-        })
-        .transpose()
-        .map(|opt| opt.unwrap_or(default))
+impl StartupConfig {
+    fn resolve() -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        Ok(Self {
+            lazy_init: parse_optional_env("STARTUP_LAZY_INIT", defaults.lazy_init)?,
+            profile_path: optional_env("STARTUP_PROFILE_PATH")?
+                .map(PathBuf::from)
+                .unwrap_or(defaults.profile_path),
+        })
+    }
+}
+
+/// Dead-man switch: escalation when critical failures pile up unattended.
+#[derive(Debug, Clone)]
+pub struct DeadManConfig {
+    /// Whether failures are tracked and escalated.
+    pub enabled: bool,
+    /// Critical failures since the last admin message that trigger escalation.
+    pub threshold: u32,
+    /// How long a channel must fail its health check to count as down.
+    pub channel_down_after: Duration,
+    /// How often channels and the database are checked.
+    pub check_interval: Duration,
+    /// Channel the escalation alert goes to (any healthy channel when unset).
+    pub alert_channel: Option<String>,
+    /// User the escalation alert is addressed to.
+    pub alert_user: String,
+    /// Paging webhook that receives the escalation as JSON.
+    pub webhook_url: Option<String>,
+    /// Users whose messages count as admin interaction (anyone when empty).
+    pub admin_users: Vec<String>,
+    /// Start the next boot in safe mode after an escalation.
+    pub safe_mode: bool,
+    /// Where the safe-mode marker is written.
+    pub safe_mode_path: PathBuf,
+}
+
+impl Default for DeadManConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 3,
+            channel_down_after: Duration::from_secs(3600),
+            check_interval: Duration::from_secs(60),
+            alert_channel: None,
+            alert_user: "default".to_string(),
+            webhook_url: None,
+            admin_users: Vec::new(),
+            safe_mode: true,
+            safe_mode_path: crate::agent::dead_man::default_safe_mode_path(),
+        }
+    }
+}
+
+impl DeadManConfig {
+    fn resolve() -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        let threshold: u32 = parse_optional_env("DEADMAN_THRESHOLD", defaults.threshold)?;
+        if threshold == 0 {
+            return Err(ConfigError::InvalidValue {
+                key: "DEADMAN_THRESHOLD".to_string(),
+                message: "must be at least 1".to_string(),
+            });
+        }
+        Ok(Self {
+            enabled: parse_optional_env("DEADMAN_ENABLED", defaults.enabled)?,
+            threshold,
+            channel_down_after: Duration::from_secs(parse_optional_env(
+                "DEADMAN_CHANNEL_DOWN_SECS",
+                defaults.channel_down_after.as_secs(),
+            )?),
+            check_interval: Duration::from_secs(
+                parse_optional_env(
+                    "DEADMAN_CHECK_INTERVAL_SECS",
+                    defaults.check_interval.as_secs(),
+                )?
+                .max(1),
+            ),
+            alert_channel: optional_env("DEADMAN_ALERT_CHANNEL")?,
+            alert_user: optional_env("DEADMAN_ALERT_USER")?.unwrap_or(defaults.alert_user),
+            webhook_url: optional_env("DEADMAN_WEBHOOK_URL")?,
+            admin_users: optional_env_list("DEADMAN_ADMIN_USERS")?,
+            safe_mode: parse_optional_env("DEADMAN_SAFE_MODE", defaults.safe_mode)?,
+            safe_mode_path: optional_env("DEADMAN_SAFE_MODE_PATH")?
+                .map(PathBuf::from)
+                .unwrap_or(defaults.safe_mode_path),
+        })
+    }
+}
+
+/// Attachment pipeline: extraction of files sent with messages.
+#[derive(Debug, Clone)]
+pub struct AttachmentConfig {
+    /// Whether attachments in message metadata are processed.
+    pub enabled: bool,
+    /// Largest attachment fetched, in bytes.
+    pub max_bytes: usize,
+    /// Extracted text longer than this is deferred to `tool_output`.
+    pub inline_chars: usize,
+    /// Key for the OCR and transcription APIs (OCR and audio are skipped without one).
+    pub api_key: Option<SecretString>,
+    /// OpenAI-compatible base URL for both APIs.
+    pub base_url: Option<String>,
+    /// Vision model used for OCR (`None` disables OCR).
+    pub vision_model: Option<String>,
+    /// Transcription model (`None` disables transcription).
+    pub transcription_model: Option<String>,
+}
+
+impl Default for AttachmentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_bytes: 25 * 1024 * 1024,
+            inline_chars: 12_000,
+            api_key: None,
+            base_url: None,
+            vision_model: Some("gpt-4o-mini".to_string()),
+            transcription_model: Some("whisper-1".to_string()),
+        }
+    }
+}
+
+impl AttachmentConfig {
+    fn resolve() -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        // "off" disables a model; unset keeps the default.
+        let model = |key: &str, default: Option<String>| -> Result<Option<String>, ConfigError> {
+            Ok(match optional_env(key)? {
+                Some(v) if v.eq_ignore_ascii_case("off") => None,
+                Some(v) => Some(v),
+                None => default,
+            })
+        };
+        Ok(Self {
+            enabled: parse_optional_env("ATTACHMENTS_ENABLED", defaults.enabled)?,
+            max_bytes: parse_optional_env::<usize>("ATTACHMENT_MAX_MB", 25)? * 1024 * 1024,
+            inline_chars: parse_optional_env("ATTACHMENT_INLINE_CHARS", defaults.inline_chars)?,
+            api_key: optional_env("ATTACHMENT_API_KEY")?
+                .or(optional_env("OPENAI_API_KEY")?)
+                .map(SecretString::from),
+            base_url: optional_env("ATTACHMENT_API_BASE_URL")?,
+            vision_model: model("ATTACHMENT_VISION_MODEL", defaults.vision_model)?,
+            transcription_model: model(
+                "ATTACHMENT_TRANSCRIPTION_MODEL",
+                defaults.transcription_model,
+            )?,
+        })
+    }
+}
+
+/// Limits for the `sql` tool.
+#[derive(Debug, Clone)]
+pub struct SqlConfig {
+    /// Whether the tool is registered (it also needs the secrets store).
+    pub enabled: bool,
+    /// Most rows a query returns.
+    pub max_rows: usize,
+    /// Most bytes of row data a query returns.
+    pub max_bytes: usize,
+    /// Statement timeout.
+    pub timeout: Duration,
+}
+
+impl Default for SqlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_rows: 200,
+            max_bytes: 64 * 1024,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl SqlConfig {
+    fn resolve() -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        Ok(Self {
+            enabled: parse_optional_env("SQL_TOOL_ENABLED", defaults.enabled)?,
+            max_rows: parse_optional_env("SQL_MAX_ROWS", defaults.max_rows)?.max(1),
+            max_bytes: parse_optional_env("SQL_MAX_BYTES", defaults.max_bytes)?.max(1024),
+            timeout: Duration::from_secs(
+                parse_optional_env("SQL_TIMEOUT_SECS", defaults.timeout.as_secs())?.max(1),
+            ),
+        })
+    }
+}
+
+/// Local crash reports written by the panic hook.
+#[derive(Debug, Clone)]
+pub struct CrashConfig {
+    /// Install the panic hook (opt-in).
+    pub enabled: bool,
+    /// Where reports are written.
+    pub dir: PathBuf,
+}
+
+impl Default for CrashConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: crate::crash::crash_dir(),
+        }
+    }
+}
+
+impl CrashConfig {
+    fn resolve() -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        Ok(Self {
+            enabled: parse_optional_env("CRASH_REPORTS_ENABLED", defaults.enabled)?,
+            dir: optional_env("CRASH_REPORTS_DIR")?
+                .map(PathBuf::from)
+                .unwrap_or(defaults.dir),
+        })
+    }
+}
+
+/// Limits for the `email_read` and `email_send` tools.
+#[derive(Debug, Clone)]
+pub struct EmailConfig {
+    /// Whether the tools are registered (they also need the secrets store).
+    pub enabled: bool,
+    /// Most messages a search returns.
+    pub max_results: usize,
+    /// Largest message fetched, in bytes.
+    pub max_message_bytes: usize,
+    /// Longest body text passed to the model, in characters.
+    pub max_body_chars: usize,
+    /// Network timeout for one IMAP or SMTP session.
+    pub timeout: Duration,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_results: 50,
+            max_message_bytes: 25 * 1024 * 1024,
+            max_body_chars: 20_000,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl EmailConfig {
+    fn resolve() -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        Ok(Self {
+            enabled: parse_optional_env("EMAIL_TOOL_ENABLED", defaults.enabled)?,
+            max_results: parse_optional_env("EMAIL_MAX_RESULTS", defaults.max_results)?.max(1),
+            max_message_bytes: parse_optional_env(
+                "EMAIL_MAX_MESSAGE_BYTES",
+                defaults.max_message_bytes,
+            )?
+            .max(64 * 1024),
+            max_body_chars: parse_optional_env("EMAIL_MAX_BODY_CHARS", defaults.max_body_chars)?
+                .max(500),
+            timeout: Duration::from_secs(
+                parse_optional_env("EMAIL_TIMEOUT_SECS", defaults.timeout.as_secs())?.max(1),
+            ),
+        })
+    }
+}
+
+/// Limits for the `calendar` tool.
+#[derive(Debug, Clone)]
+pub struct CalendarConfig {
+    /// Whether the tool is registered (it also needs the secrets store).
+    pub enabled: bool,
+    /// Most events one call returns.
+    pub max_events: usize,
+    /// HTTP timeout for CalDAV requests and feed downloads.
+    pub timeout: Duration,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_events: 200,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl CalendarConfig {
+    fn resolve() -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        Ok(Self {
+            enabled: parse_optional_env("CALENDAR_TOOL_ENABLED", defaults.enabled)?,
+            max_events: parse_optional_env("CALENDAR_MAX_EVENTS", defaults.max_events)?.max(1),
+            timeout: Duration::from_secs(
+                parse_optional_env("CALENDAR_TIMEOUT_SECS", defaults.timeout.as_secs())?.max(1),
+            ),
+        })
+    }
+}
+
+/// Background runtime for jobs and routine runs (see `agent::priority`).
+#[derive(Debug, Clone)]
+pub struct SchedulingConfig {
+    /// Run background work on its own runtime instead of the main one.
+    pub enabled: bool,
+    /// Worker threads of the background runtime.
+    pub threads: usize,
+    /// Background tasks running at once; the rest wait.
+    pub max_tasks: usize,
+    /// Nice value added to background threads (Linux only, 0 = unchanged).
+    pub nice: i32,
+}
+
+impl Default for SchedulingConfig {
+    fn default() -> Self {
+        let cores = std::thread::available_parallelism().map_or(2, |n| n.get());
+        Self {
+            enabled: true,
+            threads: (cores / 2).max(1),
+            max_tasks: 8,
+            nice: 10,
+        }
+    }
+}
+
+impl SchedulingConfig {
+    fn resolve() -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        Ok(Self {
+            enabled: parse_optional_env("BACKGROUND_RUNTIME_ENABLED", defaults.enabled)?,
+            threads: parse_optional_env("BACKGROUND_THREADS", defaults.threads)?.max(1),
+            max_tasks: parse_optional_env("BACKGROUND_MAX_TASKS", defaults.max_tasks)?.max(1),
+            nice: parse_optional_env("BACKGROUND_NICE", defaults.nice)?.clamp(0, 19),
+        })
+    }
+}
+
+/// Politeness and cache settings for the `web_fetch` tool.
+#[derive(Debug, Clone)]
+pub struct WebFetchConfig {
+    /// Whether the tool is registered.
+    pub enabled: bool,
+    /// User-Agent sent with every request; its product token (the part
+    /// before `/`) selects the robots.txt group.
+    pub user_agent: String,
+    /// Least time between two requests to one host. A longer robots.txt
+    /// `Crawl-delay` wins.
+    pub min_interval: Duration,
+    /// How long a fetched page is served from cache before it is
+    /// revalidated with its ETag or Last-Modified.
+    pub cache_ttl: Duration,
+    /// Pages kept in the cache.
+    pub cache_entries: usize,
+    /// Most characters of page text one call returns.
+    pub max_chars: usize,
+    /// HTTP timeout per request.
+    pub timeout: Duration,
+}
+
+impl Default for WebFetchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            user_agent: concat!(
+                "IronClaw/",
+                env!("CARGO_PKG_VERSION"),
+                " (+https://github.com/danielsimonjr/ironclaw)"
+            )
+            .to_string(),
+            min_interval: Duration::from_millis(1000),
+            cache_ttl: Duration::from_secs(900),
+            cache_entries: 128,
+            max_chars: 20_000,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl WebFetchConfig {
+    fn resolve() -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        Ok(Self {
+            enabled: parse_optional_env("WEB_FETCH_ENABLED", defaults.enabled)?,
+            user_agent: optional_env("WEB_FETCH_USER_AGENT")?.unwrap_or(defaults.user_agent),
+            min_interval: Duration::from_millis(parse_optional_env(
+                "WEB_FETCH_MIN_INTERVAL_MS",
+                defaults.min_interval.as_millis() as u64,
+            )?),
+            cache_ttl: Duration::from_secs(parse_optional_env(
+                "WEB_FETCH_CACHE_TTL_SECS",
+                defaults.cache_ttl.as_secs(),
+            )?),
+            cache_entries: parse_optional_env("WEB_FETCH_CACHE_ENTRIES", defaults.cache_entries)?,
+            max_chars: parse_optional_env("WEB_FETCH_MAX_CHARS", defaults.max_chars)?.max(100),
+            timeout: Duration::from_secs(
+                parse_optional_env("WEB_FETCH_TIMEOUT_SECS", defaults.timeout.as_secs())?.max(1),
+            ),
+        })
+    }
+}
+
+/// Source citations in answers.
+#[derive(Debug, Clone)]
+pub struct CitationConfig {
+    /// Whether tool outputs are tagged with sources and answers cite them.
+    pub enabled: bool,
+    /// Skills whose answers must cite a source for every factual claim.
+    pub strict_skills: Vec<String>,
+}
+
+impl Default for CitationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            strict_skills: Vec::new(),
+        }
+    }
+}
+
+impl CitationConfig {
+    fn resolve() -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        Ok(Self {
+            enabled: parse_optional_env("CITATIONS_ENABLED", defaults.enabled)?,
+            strict_skills: optional_env("CITATIONS_STRICT_SKILLS")?
+                .map(|s| {
+                    s.split(',')
+                        .map(|d| d.trim().to_string())
+                        .filter(|d| !d.is_empty())
+                        .collect()
+                })
+                .unwrap_or(defaults.strict_skills),
+        })
+    }
+}
+
+/// Auto-responses while the user is away (focus mode or outside working
+/// hours).
+#[derive(Debug, Clone)]
+pub struct AwayConfig {
+    /// Whether inbound messages are acknowledged and queued while away.
+    pub enabled: bool,
+    /// Local working hours "HH:MM-HH:MM"; always available when unset.
+    pub working_hours: Option<String>,
+    pub working_days: Vec<chrono::Weekday>,
+    /// The user's own identities ("channel:id"), never intercepted.
+    pub owners: Vec<String>,
+    /// Default acknowledgement; `{name}` and `{back}` are filled in.
+    pub persona: String,
+    /// Per-channel and per-role overrides, first match wins.
+    pub rules: Vec<crate::agent::away::AwayRule>,
+}
+
+impl Default for AwayConfig {
+    fn default() -> Self {
+        use chrono::Weekday;
+        Self {
+            enabled: false,
+            working_hours: None,
+            working_days: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            owners: Vec::new(),
+            persona: "Hi {name}, I'm away right now and will be back {back}. \
+                      I've noted your message and will get to it then."
+                .to_string(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl AwayConfig {
+    fn resolve() -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        let working_hours = optional_env("AWAY_WORKING_HOURS")?;
+        if let Some(ref hours) = working_hours
+            && crate::agent::away::parse_working_hours(hours).is_none()
+        {
+            return Err(ConfigError::InvalidValue {
+                key: "AWAY_WORKING_HOURS".to_string(),
+                message: "must be HH:MM-HH:MM within one day, e.g. 09:00-18:00".to_string(),
+            });
+        }
+        Ok(Self {
+            enabled: parse_optional_env("AWAY_ENABLED", defaults.enabled)?,
+            working_hours,
+            working_days: match optional_env("AWAY_WORKING_DAYS")? {
+                Some(days) => crate::agent::away::parse_working_days(&days).map_err(|message| {
+                    ConfigError::InvalidValue {
+                        key: "AWAY_WORKING_DAYS".to_string(),
+                        message,
+                    }
+                })?,
+                None => defaults.working_days,
+            },
+            owners: optional_env_list("AWAY_OWNERS")?,
+            persona: optional_env("AWAY_PERSONA")?.unwrap_or(defaults.persona),
+            rules: optional_env("AWAY_RULES")?
+                .map(|s| serde_json::from_str(&s))
+                .transpose()
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: "AWAY_RULES".to_string(),
+                    message: format!("must be a JSON array of auto-response rules: {e}"),
+                })?
+                .unwrap_or(defaults.rules),
+        })
+    }
+}
+
+/// Branch and pull/merge request policy for `code_review_submit`.
+#[derive(Debug, Clone)]
+pub struct CodeReviewConfig {
+    /// Whether the tool is registered (it also needs sandbox jobs).
+    pub enabled: bool,
+    /// Branch patterns the tool never pushes to (`release/*`).
+    pub protected_branches: Vec<String>,
+    /// Prefix every pushed branch must carry.
+    pub branch_prefix: String,
+    /// Branches a request may target; any when empty.
+    pub allowed_bases: Vec<String>,
+    /// Path patterns a submission may not touch (CI definitions by default).
+    pub blocked_paths: Vec<String>,
+    /// Most files one submission may change.
+    pub max_files: usize,
+    /// Open requests as drafts.
+    pub draft: bool,
+    /// Description template file; the built-in one when unset.
+    pub template_path: Option<PathBuf>,
+    /// GitLab instance for `gitlab:` repositories and GitLab remotes.
+    pub gitlab_url: String,
+    /// Commit author, "Name <email>".
+    pub author: String,
+}
+
+impl Default for CodeReviewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            protected_branches: vec![
+                "main".to_string(),
+                "master".to_string(),
+                "develop".to_string(),
+                "release/*".to_string(),
+            ],
+            branch_prefix: "ironclaw/".to_string(),
+            allowed_bases: Vec::new(),
+            blocked_paths: vec![
+                ".github/workflows/*".to_string(),
+                ".gitlab-ci.yml".to_string(),
+            ],
+            max_files: 100,
+            draft: true,
+            template_path: None,
+            gitlab_url: "https://gitlab.com".to_string(),
+            author: "IronClaw <ironclaw@users.noreply.github.com>".to_string(),
+        }
+    }
+}
+
+impl CodeReviewConfig {
+    fn resolve() -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        let list_or = |key: &str, default: Vec<String>| -> Result<Vec<String>, ConfigError> {
+            let list = optional_env_list(key)?;
+            Ok(if list.is_empty() { default } else { list })
+        };
+        let author = optional_env("CODE_REVIEW_AUTHOR")?.unwrap_or(defaults.author);
+        if crate::tools::builtin::code_review::parse_author(&author).is_none() {
+            return Err(ConfigError::InvalidValue {
+                key: "CODE_REVIEW_AUTHOR".to_string(),
+                message: "must be \"Name <email>\"".to_string(),
+            });
+        }
+        Ok(Self {
+            enabled: parse_optional_env("CODE_REVIEW_ENABLED", defaults.enabled)?,
+            protected_branches: list_or(
+                "CODE_REVIEW_PROTECTED_BRANCHES",
+                defaults.protected_branches,
+            )?,
+            branch_prefix: optional_env("CODE_REVIEW_BRANCH_PREFIX")?
+                .unwrap_or(defaults.branch_prefix),
+            allowed_bases: optional_env_list("CODE_REVIEW_ALLOWED_BASES")?,
+            blocked_paths: list_or("CODE_REVIEW_BLOCKED_PATHS", defaults.blocked_paths)?,
+            max_files: parse_optional_env("CODE_REVIEW_MAX_FILES", defaults.max_files)?.max(1),
+            draft: parse_optional_env("CODE_REVIEW_DRAFT", defaults.draft)?,
+            template_path: optional_env("CODE_REVIEW_TEMPLATE")?
+                .map(|p| crate::agent::project::expand_home(&p)),
+            gitlab_url: optional_env("CODE_REVIEW_GITLAB_URL")?
+                .map(|u| u.trim_end_matches('/').to_string())
+                .unwrap_or(defaults.gitlab_url),
+            author,
+        })
+    }
+}
+
+// Helper functions
+
+/// Token and cost caps on chat turns; every cap is off by default.
+#[derive(Debug, Clone)]
+pub struct BudgetConfig {
+    /// Tokens one session may use.
+    pub session_tokens: Option<u64>,
+    /// Tokens one user may use per UTC day.
+    pub daily_tokens: Option<u64>,
+    /// USD one user may spend per UTC day.
+    pub daily_cost: Option<Decimal>,
+    /// Percentage of a cap at which the user is warned.
+    pub warn_percent: u8,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            session_tokens: None,
+            daily_tokens: None,
+            daily_cost: None,
+            warn_percent: 80,
+        }
+    }
+}
+
+impl BudgetConfig {
+    fn resolve() -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        let warn_percent = parse_optional_env("BUDGET_WARN_PERCENT", defaults.warn_percent)?;
+        if !(1..=100).contains(&warn_percent) {
+            return Err(ConfigError::InvalidValue {
+                key: "BUDGET_WARN_PERCENT".to_string(),
+                message: "must be between 1 and 100".to_string(),
+            });
+        }
+        Ok(Self {
+            session_tokens: optional_env("BUDGET_SESSION_TOKENS")?
+                .map(|s| s.parse())
+                .transpose()
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: "BUDGET_SESSION_TOKENS".to_string(),
+                    message: format!("must be a positive integer: {e}"),
+                })?,
+            daily_tokens: optional_env("BUDGET_DAILY_TOKENS")?
+                .map(|s| s.parse())
+                .transpose()
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: "BUDGET_DAILY_TOKENS".to_string(),
+                    message: format!("must be a positive integer: {e}"),
+                })?,
+            daily_cost: optional_env("BUDGET_DAILY_COST_USD")?
+                .map(|s| s.parse())
+                .transpose()
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: "BUDGET_DAILY_COST_USD".to_string(),
+                    message: format!("must be a decimal amount: {e}"),
+                })?,
+            warn_percent,
+        })
+    }
+
+    /// Whether any cap is set.
+    pub fn is_enabled(&self) -> bool {
+        self.session_tokens.is_some() || self.daily_tokens.is_some() || self.daily_cost.is_some()
+    }
+}
+
+/// Limits for sub-agents started with the `delegate` tool.
+#[derive(Debug, Clone)]
+pub struct DelegateConfig {
+    /// Whether the tool is registered.
+    pub enabled: bool,
+    /// Most tokens one delegation may use; a call can ask for fewer.
+    pub max_tokens: u64,
+    /// Most LLM calls one delegation may make.
+    pub max_iterations: usize,
+    /// Model a sub-agent runs on unless the call picks one. `cheap` falls
+    /// back to the primary model when no cheap model is configured.
+    pub tier: crate::agent::ModelTier,
+    /// Tools a sub-agent gets when the call names none.
+    pub default_tools: Vec<String>,
+}
+
+impl Default for DelegateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_tokens: 50_000,
+            max_iterations: 8,
+            tier: crate::agent::ModelTier::Cheap,
+            default_tools: [
+                "memory_search",
+                "memory_read",
+                "read_file",
+                "list_dir",
+                "json",
+                "time",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        }
+    }
+}
+
+impl DelegateConfig {
+    fn resolve() -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        let tier = match optional_env("DELEGATE_MODEL")?.as_deref() {
+            None => defaults.tier,
+            Some("cheap") => crate::agent::ModelTier::Cheap,
+            Some("primary") => crate::agent::ModelTier::Primary,
+            Some(other) => {
+                return Err(ConfigError::InvalidValue {
+                    key: "DELEGATE_MODEL".to_string(),
+                    message: format!("must be \"cheap\" or \"primary\", got \"{other}\""),
+                });
+            }
+        };
+        let default_tools = optional_env_list("DELEGATE_DEFAULT_TOOLS")?;
+        Ok(Self {
+            enabled: parse_optional_env("DELEGATE_ENABLED", defaults.enabled)?,
+            max_tokens: parse_optional_env("DELEGATE_MAX_TOKENS", defaults.max_tokens)?.max(1),
+            max_iterations: parse_optional_env("DELEGATE_MAX_ITERATIONS", defaults.max_iterations)?
+                .max(1),
+            tier,
+            default_tools: if default_tools.is_empty() {
+                defaults.default_tools
+            } else {
+                default_tools
+            },
+        })
+    }
+}
+
+fn optional_env(key: &str) -> Result<Option<String>, ConfigError> {
+    match std::env::var(key) {
+        Ok(val) if val.is_empty() => Ok(None),
+        Ok(val) => Ok(Some(val)),
+        Err(std::env::VarError::NotPresent) => Ok(None),
+        Err(e) => Err(ConfigError::ParseError(format!(
+            "failed to read {key}: {e}"
+        ))),
+    }
+}
+
+/// Comma-separated env var as a list (empty when unset).
+fn optional_env_list(key: &str) -> Result<Vec<String>, ConfigError> {
+    Ok(optional_env(key)?
+        .map(|s| {
+            s.split(',')
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+fn parse_optional_env<T>(key: &str, default: T) -> Result<T, ConfigError>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    optional_env(key)?
+        .map(|s| {
+            s.parse().map_err(|e| ConfigError::InvalidValue {
+                key: key.to_string(),
+                message: format!("{e}"),
+            })
+        })
+        .transpose()
+        .map(|opt| opt.unwrap_or(default))
 }
 
 #[cfg(test)]
@@ -1828,6 +3480,22 @@ mod tests {
         unsafe { std::env::remove_var("IRONCLAW_TEST_PARSE_BOOL") };
     }
 
+    // ==================== OutboundConfig tests ====================
+
+    #[test]
+    fn test_outbound_config_from_settings() {
+        let mut settings = Settings::default();
+        settings.outbound.proxy = Some("socks5h://proxy.corp:1080".to_string());
+        settings.outbound.no_proxy = vec![".internal.corp".to_string()];
+
+        let config = OutboundConfig::resolve(&settings).unwrap();
+        assert_eq!(config.proxy.as_deref(), Some("socks5h://proxy.corp:1080"));
+        assert_eq!(config.no_proxy, vec![".internal.corp".to_string()]);
+
+        settings.outbound.proxy = Some("ftp://proxy.corp".to_string());
+        assert!(OutboundConfig::resolve(&settings).is_err());
+    }
+
     // ==================== TunnelConfig validation tests ====================
 
     #[test]
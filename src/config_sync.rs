@@ -0,0 +1,583 @@
+//! Configuration sync between machines through a git repository.
+//!
+//! `ironclaw config sync` keeps the parts of the configuration that describe
+//! what the agent is set up to do (MCP servers, channels, notification
+//! routes, WASM and safety limits, heartbeat) the same on every machine.
+//! Each machine flattens that configuration into a [`SyncMap`] of keys such
+//! as `mcp_servers.github` and `settings.channels.wasm_channels`, and the
+//! maps are merged through `ironclaw-config.json` in a user-owned git
+//! repository:
+//!
+//! ```text
+//!   local ──┐                  ┌── applied to settings / mcp-servers.json
+//!           ├─ merge(base) ────┤
+//!   repo  ──┘                  └── committed and pushed to the repo
+//! ```
+//!
+//! The merge is three-way against the map recorded after the previous sync
+//! (`~/.ironclaw/config-sync-base.json`), so a key changed on one side only
+//! is taken from that side and a key changed differently on both sides is a
+//! [`Conflict`]. Conflicts are left untouched on both sides until resolved
+//! with `--prefer local|remote` (or by making the values equal).
+//!
+//! Machine-specific keys (database, ports, directories, time zone) are never
+//! synced, and object fields that look like credentials are stripped before
+//! anything is written to the repository. Secrets themselves live in the
+//! encrypted secrets store, which is not synced.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::settings_migrations::SettingsMap;
+use crate::tools::mcp::{McpServerConfig, McpServersFile};
+
+/// File in the sync repository holding the shared configuration.
+pub const SYNC_FILE: &str = "ironclaw-config.json";
+
+/// Settings sections that are synced.
+const SYNCED_SECTIONS: &[&str] = &[
+    "channels",
+    "notifications",
+    "locale",
+    "heartbeat",
+    "wasm",
+    "safety",
+];
+
+/// Keys inside the synced sections that belong to one machine.
+const MACHINE_KEYS: &[&str] = &[
+    "channels.http_host",
+    "channels.http_port",
+    "channels.wasm_channels_dir",
+    "locale.timezone",
+    "wasm.tools_dir",
+    "wasm.cache_dir",
+];
+
+/// Final name segments of fields that are never synced, wherever they
+/// appear (`access_token`, `api_key`, ...).
+const SECRET_SUFFIXES: &[&str] = &[
+    "token",
+    "secret",
+    "password",
+    "key",
+    "credential",
+    "credentials",
+];
+
+/// Flattened configuration, keyed by `settings.<path>` or `mcp_servers.<name>`.
+pub type SyncMap = BTreeMap<String, Value>;
+
+fn looks_secret(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    let last = name.rsplit(['_', '-']).next().unwrap_or_default();
+    SECRET_SUFFIXES.contains(&last) || name.contains("secret") || name.contains("password")
+}
+
+/// Whether a settings key is synced.
+pub fn is_synced_setting(key: &str) -> bool {
+    let section = key.split('.').next().unwrap_or_default();
+    SYNCED_SECTIONS.contains(&section)
+        && !MACHINE_KEYS
+            .iter()
+            .any(|m| key == *m || key.starts_with(&format!("{}.", m)))
+        && !key.rsplit('.').next().is_some_and(looks_secret)
+}
+
+/// Drop credential-looking fields from a value, recursively.
+fn strip_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|k, _| !looks_secret(k));
+            map.values_mut().for_each(strip_secrets);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_secrets),
+        _ => {}
+    }
+}
+
+/// Flatten the local configuration into a sync map.
+pub fn snapshot(settings: &SettingsMap, mcp: &McpServersFile) -> SyncMap {
+    let mut map = SyncMap::new();
+    for (key, value) in settings {
+        if is_synced_setting(key) && !value.is_null() {
+            let mut value = value.clone();
+            strip_secrets(&mut value);
+            map.insert(format!("settings.{}", key), value);
+        }
+    }
+    for server in &mcp.servers {
+        if let Ok(mut value) = serde_json::to_value(server) {
+            strip_secrets(&mut value);
+            map.insert(format!("mcp_servers.{}", server.name), value);
+        }
+    }
+    map
+}
+
+/// Which side wins conflicting keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Prefer {
+    Local,
+    Remote,
+}
+
+/// A key changed differently on both sides since the last sync.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub key: String,
+    /// `None` means deleted on that side.
+    pub local: Option<Value>,
+    pub remote: Option<Value>,
+}
+
+/// Result of a three-way merge.
+#[derive(Debug, Clone, Default)]
+pub struct MergeOutcome {
+    /// What the local configuration should become.
+    pub local: SyncMap,
+    /// What the repository should hold.
+    pub remote: SyncMap,
+    /// The new base: keys both sides agree on.
+    pub base: SyncMap,
+    /// Keys taken from the repository.
+    pub pulled: Vec<String>,
+    /// Keys sent to the repository.
+    pub pushed: Vec<String>,
+    /// Keys left unresolved.
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Three-way merge of the local and repository maps against the last synced
+/// base. Deletions are changes like any other.
+pub fn merge(
+    base: &SyncMap,
+    local: &SyncMap,
+    remote: &SyncMap,
+    prefer: Option<Prefer>,
+) -> MergeOutcome {
+    let mut out = MergeOutcome {
+        local: local.clone(),
+        remote: remote.clone(),
+        ..MergeOutcome::default()
+    };
+    let keys: std::collections::BTreeSet<&String> = base
+        .keys()
+        .chain(local.keys())
+        .chain(remote.keys())
+        .collect();
+    for key in keys {
+        let (b, l, r) = (base.get(key), local.get(key), remote.get(key));
+        let take = |into: &mut SyncMap, value: Option<&Value>| match value {
+            Some(v) => {
+                into.insert(key.clone(), v.clone());
+            }
+            None => {
+                into.remove(key);
+            }
+        };
+        let winner = if l == r {
+            l
+        } else if l == b {
+            out.pulled.push(key.clone());
+            take(&mut out.local, r);
+            r
+        } else if r == b {
+            out.pushed.push(key.clone());
+            take(&mut out.remote, l);
+            l
+        } else {
+            match prefer {
+                Some(Prefer::Local) => {
+                    out.pushed.push(key.clone());
+                    take(&mut out.remote, l);
+                    l
+                }
+                Some(Prefer::Remote) => {
+                    out.pulled.push(key.clone());
+                    take(&mut out.local, r);
+                    r
+                }
+                None => {
+                    out.conflicts.push(Conflict {
+                        key: key.clone(),
+                        local: l.cloned(),
+                        remote: r.cloned(),
+                    });
+                    // Keep the old base so the key stays in conflict.
+                    b
+                }
+            }
+        };
+        take(&mut out.base, winner);
+    }
+    out
+}
+
+/// Split a merged map back into settings values (keyed by settings path)
+/// and MCP servers.
+pub fn split(map: &SyncMap) -> (BTreeMap<String, Value>, Vec<McpServerConfig>) {
+    let mut settings = BTreeMap::new();
+    let mut servers = Vec::new();
+    for (key, value) in map {
+        if let Some(path) = key.strip_prefix("settings.") {
+            settings.insert(path.to_string(), value.clone());
+        } else if key.starts_with("mcp_servers.") {
+            match serde_json::from_value::<McpServerConfig>(value.clone()) {
+                Ok(server) => servers.push(server),
+                Err(e) => tracing::warn!("Skipping synced MCP server {}: {}", key, e),
+            }
+        }
+    }
+    (settings, servers)
+}
+
+/// Merge synced MCP servers into the local file: synced servers replace
+/// their local entry (keeping local-only fields such as OAuth extras that
+/// were stripped), servers removed by the sync are dropped.
+pub fn apply_servers(local: &mut McpServersFile, synced: Vec<McpServerConfig>) {
+    let previous = std::mem::take(&mut local.servers);
+    // A file created by the sync starts at the current schema.
+    local.schema_version = local.schema_version.max(1);
+    local.servers = synced
+        .into_iter()
+        .map(|mut server| {
+            if let (Some(oauth), Some(old)) = (
+                server.oauth.as_mut(),
+                previous
+                    .iter()
+                    .find(|s| s.name == server.name)
+                    .and_then(|s| s.oauth.as_ref()),
+            ) {
+                for (k, v) in &old.extra_params {
+                    oauth.extra_params.entry(k.clone()).or_insert(v.clone());
+                }
+            }
+            server
+        })
+        .collect();
+}
+
+/// Error from the sync repository or state file.
+#[derive(Debug, thiserror::Error)]
+pub enum SyncError {
+    #[error("git {command} failed: {stderr}")]
+    Git { command: String, stderr: String },
+
+    #[error("{path}: {reason}")]
+    File { path: PathBuf, reason: String },
+}
+
+/// A git working copy holding [`SYNC_FILE`].
+pub struct SyncRepo {
+    dir: PathBuf,
+}
+
+impl SyncRepo {
+    /// Open `dir`, which must be a git working copy.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, SyncError> {
+        let dir = dir.into();
+        if !dir.join(".git").exists() {
+            return Err(SyncError::File {
+                path: dir,
+                reason: "not a git repository (run `git init` or clone your config repo there)"
+                    .to_string(),
+            });
+        }
+        Ok(Self { dir })
+    }
+
+    async fn git(&self, args: &[&str]) -> Result<String, SyncError> {
+        let output = tokio::process::Command::new("git")
+            .arg("-C")
+            .arg(&self.dir)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| SyncError::Git {
+                command: args.join(" "),
+                stderr: e.to_string(),
+            })?;
+        if !output.status.success() {
+            return Err(SyncError::Git {
+                command: args.join(" "),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    async fn has_remote(&self) -> Result<bool, SyncError> {
+        Ok(!self.git(&["remote"]).await?.is_empty())
+    }
+
+    /// Fetch the latest shared configuration, if the repo has a remote.
+    ///
+    /// The sync file is regenerated on every sync, so a local commit that
+    /// never made it upstream (a rejected push) is dropped in favour of the
+    /// remote rather than merged.
+    pub async fn pull(&self) -> Result<(), SyncError> {
+        if !self.has_remote().await? {
+            return Ok(());
+        }
+        self.git(&["fetch", "--quiet", "origin"]).await?;
+        let branch = self.git(&["branch", "--show-current"]).await?;
+        let upstream = format!("origin/{}", branch);
+        if branch.is_empty()
+            || self
+                .git(&["rev-parse", "--verify", "--quiet", &upstream])
+                .await
+                .is_err()
+        {
+            // Nothing has been pushed yet.
+            return Ok(());
+        }
+        if self
+            .git(&["merge", "--ff-only", "--quiet", &upstream])
+            .await
+            .is_err()
+        {
+            tracing::warn!(
+                "Sync repository diverged from {}; taking the remote",
+                upstream
+            );
+            self.git(&["reset", "--hard", "--quiet", &upstream]).await?;
+        }
+        Ok(())
+    }
+
+    /// The shared configuration; empty when the file does not exist yet.
+    pub fn read(&self) -> Result<SyncMap, SyncError> {
+        read_map(&self.dir.join(SYNC_FILE))
+    }
+
+    /// Write the shared configuration, commit it and push when there is a
+    /// remote. Returns false when nothing changed.
+    pub async fn publish(&self, map: &SyncMap, message: &str) -> Result<bool, SyncError> {
+        write_map(&self.dir.join(SYNC_FILE), map)?;
+        self.git(&["add", SYNC_FILE]).await?;
+        if self
+            .git(&["status", "--porcelain", "--", SYNC_FILE])
+            .await?
+            .is_empty()
+        {
+            return Ok(false);
+        }
+        self.git(&["commit", "--quiet", "-m", message]).await?;
+        if self.has_remote().await? {
+            self.git(&["push", "--quiet", "-u", "origin", "HEAD"])
+                .await?;
+        }
+        Ok(true)
+    }
+}
+
+/// Where the base of the last sync is kept.
+pub fn default_base_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".ironclaw")
+        .join("config-sync-base.json")
+}
+
+/// Read a sync map; a missing file is an empty map.
+pub fn read_map(path: &Path) -> Result<SyncMap, SyncError> {
+    let file_error = |reason: String| SyncError::File {
+        path: path.to_path_buf(),
+        reason,
+    };
+    match std::fs::read_to_string(path) {
+        Ok(data) => serde_json::from_str(&data).map_err(|e| file_error(e.to_string())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(SyncMap::new()),
+        Err(e) => Err(file_error(e.to_string())),
+    }
+}
+
+/// Write a sync map as sorted, pretty JSON so diffs stay small.
+pub fn write_map(path: &Path, map: &SyncMap) -> Result<(), SyncError> {
+    let file_error = |reason: String| SyncError::File {
+        path: path.to_path_buf(),
+        reason,
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| file_error(e.to_string()))?;
+    }
+    let mut json = serde_json::to_string_pretty(map).map_err(|e| file_error(e.to_string()))?;
+    json.push('\n');
+    std::fs::write(path, json).map_err(|e| file_error(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn map(entries: &[(&str, Value)]) -> SyncMap {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_snapshot_skips_machine_keys_and_secrets() {
+        let settings: SettingsMap = [
+            ("channels.wasm_channels", json!(["slack"])),
+            ("channels.http_port", json!(8080)),
+            ("database_url", json!("postgres://me:pw@localhost/db")),
+            ("locale.timezone", json!("Europe/Berlin")),
+            ("locale.language", json!("en-GB")),
+            ("heartbeat.api_token", json!("abc")),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+        let mut server = McpServerConfig::new("github", "https://mcp.github.com");
+        server.oauth = Some(
+            serde_json::from_value(json!({
+                "client_id": "app",
+                "token_url": "https://github.com/login/oauth/access_token",
+                "extra_params": {"client_secret": "s3cret", "audience": "api"}
+            }))
+            .unwrap(),
+        );
+        let mcp = McpServersFile {
+            servers: vec![server],
+            ..McpServersFile::default()
+        };
+
+        let snap = snapshot(&settings, &mcp);
+        let keys: Vec<&str> = snap.keys().map(String::as_str).collect();
+        assert_eq!(
+            keys,
+            [
+                "mcp_servers.github",
+                "settings.channels.wasm_channels",
+                "settings.locale.language"
+            ]
+        );
+        let oauth = &snap["mcp_servers.github"]["oauth"];
+        assert_eq!(oauth["client_id"], "app");
+        assert!(oauth["token_url"].is_string());
+        assert_eq!(oauth["extra_params"], json!({"audience": "api"}));
+    }
+
+    #[test]
+    fn test_merge_takes_one_sided_changes() {
+        let base = map(&[("a", json!(1)), ("b", json!(1)), ("gone", json!(1))]);
+        let local = map(&[("a", json!(2)), ("b", json!(1)), ("new_local", json!(1))]);
+        let remote = map(&[
+            ("a", json!(1)),
+            ("b", json!(3)),
+            ("gone", json!(1)),
+            ("new_remote", json!(1)),
+        ]);
+        let out = merge(&base, &local, &remote, None);
+
+        assert!(out.conflicts.is_empty());
+        assert_eq!(out.pushed, ["a", "gone", "new_local"]);
+        assert_eq!(out.pulled, ["b", "new_remote"]);
+        let expected = map(&[
+            ("a", json!(2)),
+            ("b", json!(3)),
+            ("new_local", json!(1)),
+            ("new_remote", json!(1)),
+        ]);
+        assert_eq!(out.local, expected);
+        assert_eq!(out.remote, expected);
+        assert_eq!(out.base, expected);
+    }
+
+    #[test]
+    fn test_merge_reports_and_resolves_conflicts() {
+        let base = map(&[("k", json!("base"))]);
+        let local = map(&[("k", json!("mine"))]);
+        let remote = map(&[("k", json!("theirs"))]);
+
+        let out = merge(&base, &local, &remote, None);
+        assert_eq!(
+            out.conflicts,
+            [Conflict {
+                key: "k".to_string(),
+                local: Some(json!("mine")),
+                remote: Some(json!("theirs")),
+            }]
+        );
+        assert_eq!(out.local, local);
+        assert_eq!(out.remote, remote);
+        assert_eq!(out.base, base);
+
+        let out = merge(&base, &local, &remote, Some(Prefer::Remote));
+        assert!(out.conflicts.is_empty());
+        assert_eq!(out.local, remote);
+
+        // First sync on a second machine: no base, differing values conflict.
+        let out = merge(&SyncMap::new(), &local, &remote, None);
+        assert_eq!(out.conflicts.len(), 1);
+    }
+
+    #[test]
+    fn test_split_and_apply_servers_keep_local_extras() {
+        let synced = map(&[
+            ("settings.channels.wasm_channels", json!(["slack"])),
+            (
+                "mcp_servers.notion",
+                json!({"name": "notion", "url": "https://mcp.notion.com", "enabled": false,
+                       "oauth": {"client_id": "n"}}),
+            ),
+        ]);
+        let (settings, servers) = split(&synced);
+        assert_eq!(settings["channels.wasm_channels"], json!(["slack"]));
+
+        let mut local = McpServersFile {
+            servers: vec![
+                serde_json::from_value(json!({
+                    "name": "notion", "url": "https://mcp.notion.com",
+                    "oauth": {"client_id": "n", "extra_params": {"client_secret": "x"}}
+                }))
+                .unwrap(),
+                McpServerConfig::new("old", "https://old.example.com"),
+            ],
+            ..McpServersFile::default()
+        };
+        apply_servers(&mut local, servers);
+        assert_eq!(local.servers.len(), 1);
+        assert!(!local.servers[0].enabled);
+        let oauth = local.servers[0].oauth.as_ref().unwrap();
+        assert_eq!(oauth.extra_params["client_secret"], "x");
+    }
+
+    #[tokio::test]
+    async fn test_repo_publish_commits_changes_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let status = std::process::Command::new("git")
+            .args(["init", "--quiet"])
+            .arg(dir.path())
+            .status();
+        if !status.is_ok_and(|s| s.success()) {
+            return; // git unavailable
+        }
+        for (k, v) in [("user.name", "test"), ("user.email", "test@example.com")] {
+            std::process::Command::new("git")
+                .arg("-C")
+                .arg(dir.path())
+                .args(["config", k, v])
+                .status()
+                .unwrap();
+        }
+
+        let repo = SyncRepo::open(dir.path()).unwrap();
+        repo.pull().await.unwrap();
+        assert!(repo.read().unwrap().is_empty());
+
+        let shared = map(&[("settings.locale.language", json!("en-GB"))]);
+        assert!(repo.publish(&shared, "sync").await.unwrap());
+        assert!(!repo.publish(&shared, "sync").await.unwrap());
+        assert_eq!(repo.read().unwrap(), shared);
+
+        assert!(SyncRepo::open(dir.path().join("missing")).is_err());
+    }
+}
@@ -0,0 +1,15 @@
+//! Contacts: one person, many channel identities.
+//!
+//! The same human shows up as a Telegram numeric id, a Slack member id and
+//! an email address. A contact links those identities so that profile facts,
+//! pairing approvals and quotas follow the person instead of the channel id.
+//!
+//! Identities are linked manually (`ironclaw contacts link`); the store also
+//! remembers identities it has seen and suggests likely links (same username,
+//! same display name, email local part matching a username).
+//!
+//! Stored in ~/.ironclaw/contacts.json.
+
+mod store;
+
+pub use store::{Contact, ContactStore, ContactStoreError, Identity, Suggestion};
@@ -0,0 +1,559 @@
+//! File-backed contact store.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use fs4::FileExt;
+use serde::{Deserialize, Serialize};
+
+/// Identities remembered for link suggestions; oldest are dropped first.
+const SEEN_MAX: usize = 500;
+/// Display names shorter than this are too ambiguous to suggest a link.
+const MIN_NAME_LEN: usize = 4;
+
+/// Error from contact store operations.
+#[derive(Debug, thiserror::Error)]
+pub enum ContactStoreError {
+    #[error("Invalid identity: {0}")]
+    InvalidIdentity(String),
+
+    #[error("Contact not found: {0}")]
+    NotFound(String),
+
+    #[error("{identity} is already linked to {contact}")]
+    AlreadyLinked { identity: String, contact: String },
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// One channel-specific identity, e.g. a Telegram user id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Identity {
+    pub channel: String,
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+}
+
+impl Identity {
+    pub fn new(channel: &str, id: &str) -> Self {
+        Self {
+            channel: channel.trim().to_lowercase(),
+            id: id.trim().to_string(),
+            username: None,
+            display_name: None,
+        }
+    }
+
+    /// Parse `channel:id` (the id may itself contain colons).
+    pub fn parse(s: &str) -> Result<Self, ContactStoreError> {
+        match s.split_once(':') {
+            Some((channel, id)) if !channel.trim().is_empty() && !id.trim().is_empty() => {
+                Ok(Self::new(channel, id))
+            }
+            _ => Err(ContactStoreError::InvalidIdentity(format!(
+                "'{}' (expected channel:id)",
+                s
+            ))),
+        }
+    }
+
+    fn matches(&self, channel: &str, id: &str) -> bool {
+        self.channel.eq_ignore_ascii_case(channel.trim()) && self.id == id.trim()
+    }
+
+    fn key(&self) -> String {
+        format!("{}:{}", self.channel, self.id)
+    }
+}
+
+impl std::fmt::Display for Identity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.channel, self.id)
+    }
+}
+
+/// A person and the identities they use.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contact {
+    /// Short handle, e.g. "alice".
+    pub id: String,
+    pub name: String,
+    /// User id that per-user state (profile facts, quotas, settings) is
+    /// keyed by. Defaults to the first identity's id so existing state is
+    /// kept when that identity is linked.
+    pub user_id: String,
+    pub identities: Vec<Identity>,
+    pub created_at: String,
+}
+
+/// A pair of identities that probably belong to the same person.
+#[derive(Debug, Clone, Serialize)]
+pub struct Suggestion {
+    pub a: Identity,
+    pub b: Identity,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ContactsFile {
+    version: u8,
+    #[serde(default)]
+    contacts: Vec<Contact>,
+    /// Identities observed on inbound messages.
+    #[serde(default)]
+    seen: Vec<Identity>,
+    /// Suggestion pairs the user rejected, as sorted `channel:id` keys.
+    #[serde(default)]
+    dismissed: Vec<(String, String)>,
+}
+
+impl ContactsFile {
+    fn contact_of(&self, channel: &str, id: &str) -> Option<usize> {
+        self.contacts
+            .iter()
+            .position(|c| c.identities.iter().any(|i| i.matches(channel, id)))
+    }
+
+    fn find(&self, person: &str) -> Option<usize> {
+        let person = person.trim();
+        self.contacts
+            .iter()
+            .position(|c| c.id.eq_ignore_ascii_case(person))
+            .or_else(|| {
+                self.contacts
+                    .iter()
+                    .position(|c| c.name.eq_ignore_ascii_case(person))
+            })
+    }
+}
+
+fn default_contacts_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".ironclaw")
+        .join("contacts.json")
+}
+
+fn slug(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.trim().chars() {
+        if c.is_alphanumeric() {
+            out.extend(c.to_lowercase());
+        } else if !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_matches('-').to_string()
+}
+
+fn normalize_handle(s: &str) -> String {
+    s.trim().trim_start_matches('@').to_lowercase()
+}
+
+fn pair_key(a: &Identity, b: &Identity) -> (String, String) {
+    let (a, b) = (a.key(), b.key());
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+/// Why two identities on different channels look like the same person.
+fn match_reason(a: &Identity, b: &Identity) -> Option<String> {
+    if let (Some(ua), Some(ub)) = (&a.username, &b.username) {
+        let (ua, ub) = (normalize_handle(ua), normalize_handle(ub));
+        if !ua.is_empty() && ua == ub {
+            return Some(format!("same username '{}'", ua));
+        }
+    }
+    if let (Some(na), Some(nb)) = (&a.display_name, &b.display_name) {
+        let (na, nb) = (na.trim().to_lowercase(), nb.trim().to_lowercase());
+        if na.chars().count() >= MIN_NAME_LEN && na == nb {
+            return Some(format!("same display name '{}'", na));
+        }
+    }
+    for (email, other) in [(a, b), (b, a)] {
+        if let Some((local, _)) = email.id.split_once('@')
+            && let Some(user) = &other.username
+            && !local.is_empty()
+            && local.eq_ignore_ascii_case(&normalize_handle(user))
+        {
+            return Some(format!("email {} matches username '{}'", email.id, user));
+        }
+    }
+    None
+}
+
+/// Contact store backed by a JSON file.
+#[derive(Debug, Clone)]
+pub struct ContactStore {
+    path: PathBuf,
+}
+
+impl ContactStore {
+    /// Create a contact store using the default file (~/.ironclaw/contacts.json).
+    pub fn new() -> Self {
+        Self {
+            path: default_contacts_path(),
+        }
+    }
+
+    /// Create a contact store in a custom directory (for testing).
+    pub fn with_base_dir(base_dir: PathBuf) -> Self {
+        Self {
+            path: base_dir.join("contacts.json"),
+        }
+    }
+
+    /// All contacts, sorted by id.
+    pub fn list(&self) -> Result<Vec<Contact>, ContactStoreError> {
+        let mut contacts = self.read()?.contacts;
+        contacts.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(contacts)
+    }
+
+    /// Look up a contact by id or name.
+    pub fn get(&self, person: &str) -> Result<Option<Contact>, ContactStoreError> {
+        let file = self.read()?;
+        Ok(file.find(person).map(|i| file.contacts[i].clone()))
+    }
+
+    /// The contact a channel identity belongs to, if any.
+    pub fn resolve(&self, channel: &str, id: &str) -> Result<Option<Contact>, ContactStoreError> {
+        let file = self.read()?;
+        Ok(file
+            .contact_of(channel, id)
+            .map(|i| file.contacts[i].clone()))
+    }
+
+    /// Link an identity to a person, creating the contact if needed.
+    pub fn link(&self, person: &str, identity: Identity) -> Result<Contact, ContactStoreError> {
+        let handle = slug(person);
+        if handle.is_empty() {
+            return Err(ContactStoreError::InvalidIdentity(format!(
+                "'{}' is not a usable contact name",
+                person
+            )));
+        }
+
+        self.update(|file| {
+            let target = file.find(person);
+            if let Some(owner) = file.contact_of(&identity.channel, &identity.id) {
+                if Some(owner) == target {
+                    return Ok(file.contacts[owner].clone());
+                }
+                return Err(ContactStoreError::AlreadyLinked {
+                    identity: identity.to_string(),
+                    contact: file.contacts[owner].id.clone(),
+                });
+            }
+
+            // Carry over what we know about the identity from inbound traffic.
+            let mut identity = identity;
+            if let Some(seen) = file
+                .seen
+                .iter()
+                .find(|s| s.matches(&identity.channel, &identity.id))
+            {
+                identity.username = identity.username.or_else(|| seen.username.clone());
+                identity.display_name = identity.display_name.or_else(|| seen.display_name.clone());
+            }
+
+            let idx = match target {
+                Some(idx) => idx,
+                None => {
+                    file.contacts.push(Contact {
+                        id: handle,
+                        name: person.trim().to_string(),
+                        user_id: identity.id.clone(),
+                        identities: Vec::new(),
+                        created_at: chrono::Utc::now().to_rfc3339(),
+                    });
+                    file.contacts.len() - 1
+                }
+            };
+            file.contacts[idx].identities.push(identity);
+            Ok(file.contacts[idx].clone())
+        })
+    }
+
+    /// Unlink an identity. Returns the contact it was removed from; a
+    /// contact left without identities is deleted.
+    pub fn unlink(&self, identity: &Identity) -> Result<Option<Contact>, ContactStoreError> {
+        self.update(|file| {
+            let Some(idx) = file.contact_of(&identity.channel, &identity.id) else {
+                return Ok(None);
+            };
+            let contact = &mut file.contacts[idx];
+            contact
+                .identities
+                .retain(|i| !i.matches(&identity.channel, &identity.id));
+            let contact = contact.clone();
+            if contact.identities.is_empty() {
+                file.contacts.remove(idx);
+            }
+            Ok(Some(contact))
+        })
+    }
+
+    /// Delete a contact. Its identities go back to being separate users.
+    pub fn remove(&self, person: &str) -> Result<Contact, ContactStoreError> {
+        self.update(|file| match file.find(person) {
+            Some(idx) => Ok(file.contacts.remove(idx)),
+            None => Err(ContactStoreError::NotFound(person.to_string())),
+        })
+    }
+
+    /// Record an identity seen on an inbound message, for suggestions.
+    ///
+    /// Only writes when something new was learned, so calling this on every
+    /// message is cheap.
+    pub fn observe(
+        &self,
+        channel: &str,
+        id: &str,
+        username: Option<&str>,
+        display_name: Option<&str>,
+    ) -> Result<(), ContactStoreError> {
+        let mut identity = Identity::new(channel, id);
+        if identity.channel.is_empty() || identity.id.is_empty() {
+            return Ok(());
+        }
+        identity.username = username
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from);
+        identity.display_name = display_name
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from);
+
+        let file = self.read()?;
+        if file.seen.contains(&identity) {
+            return Ok(());
+        }
+
+        self.update(|file| {
+            file.seen
+                .retain(|s| !s.matches(&identity.channel, &identity.id));
+            file.seen.push(identity);
+            if file.seen.len() > SEEN_MAX {
+                let excess = file.seen.len() - SEEN_MAX;
+                file.seen.drain(..excess);
+            }
+            Ok(())
+        })
+    }
+
+    /// Identity pairs on different channels that look like the same person
+    /// and aren't already linked together or dismissed.
+    pub fn suggestions(&self) -> Result<Vec<Suggestion>, ContactStoreError> {
+        let file = self.read()?;
+        let dismissed: HashSet<(String, String)> = file.dismissed.iter().cloned().collect();
+
+        let mut known: Vec<Identity> = file
+            .contacts
+            .iter()
+            .flat_map(|c| c.identities.iter().cloned())
+            .collect();
+        for seen in &file.seen {
+            if !known.iter().any(|k| k.matches(&seen.channel, &seen.id)) {
+                known.push(seen.clone());
+            }
+        }
+
+        let mut out = Vec::new();
+        for (i, a) in known.iter().enumerate() {
+            for b in &known[i + 1..] {
+                if a.channel == b.channel || dismissed.contains(&pair_key(a, b)) {
+                    continue;
+                }
+                let owner_a = file.contact_of(&a.channel, &a.id);
+                if owner_a.is_some() && owner_a == file.contact_of(&b.channel, &b.id) {
+                    continue;
+                }
+                if let Some(reason) = match_reason(a, b) {
+                    out.push(Suggestion {
+                        a: a.clone(),
+                        b: b.clone(),
+                        reason,
+                    });
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Stop suggesting that two identities belong together.
+    pub fn dismiss(&self, a: &Identity, b: &Identity) -> Result<(), ContactStoreError> {
+        let key = pair_key(a, b);
+        self.update(|file| {
+            if !file.dismissed.contains(&key) {
+                file.dismissed.push(key);
+            }
+            Ok(())
+        })
+    }
+
+    fn read(&self) -> Result<ContactsFile, ContactStoreError> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(serde_json::from_str(&content).unwrap_or_default()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ContactsFile::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Read-modify-write the file under an exclusive lock.
+    fn update<T>(
+        &self,
+        f: impl FnOnce(&mut ContactsFile) -> Result<T, ContactStoreError>,
+    ) -> Result<T, ContactStoreError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut handle = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)?;
+        handle.lock_exclusive()?;
+
+        let content = fs::read_to_string(&self.path).unwrap_or_default();
+        let mut file: ContactsFile = serde_json::from_str(&content).unwrap_or_default();
+        let result = f(&mut file);
+        if result.is_ok() {
+            file.version = 1;
+            let json = serde_json::to_string_pretty(&file)?;
+            handle.set_len(0)?;
+            handle.seek(SeekFrom::Start(0))?;
+            handle.write_all(json.as_bytes())?;
+            handle.sync_all()?;
+        }
+        fs4::FileExt::unlock(&handle)?;
+        result
+    }
+}
+
+impl Default for ContactStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn test_store() -> (ContactStore, TempDir) {
+        let dir = TempDir::new().unwrap();
+        let store = ContactStore::with_base_dir(dir.path().to_path_buf());
+        (store, dir)
+    }
+
+    #[test]
+    fn test_identity_parse() {
+        let id = Identity::parse("Email:alice@example.com").unwrap();
+        assert_eq!(id.channel, "email");
+        assert_eq!(id.id, "alice@example.com");
+        assert_eq!(Identity::parse("matrix:@a:b.org").unwrap().id, "@a:b.org");
+        Identity::parse("telegram").unwrap_err();
+        Identity::parse(":123").unwrap_err();
+    }
+
+    #[test]
+    fn test_link_and_resolve() {
+        let (store, _) = test_store();
+        let c = store
+            .link("Alice Smith", Identity::new("telegram", "12345"))
+            .unwrap();
+        assert_eq!(c.id, "alice-smith");
+        assert_eq!(c.user_id, "12345");
+
+        let c = store
+            .link("alice-smith", Identity::new("slack", "U999"))
+            .unwrap();
+        assert_eq!(c.identities.len(), 2);
+        assert_eq!(c.user_id, "12345");
+
+        let resolved = store.resolve("Slack", "U999").unwrap().unwrap();
+        assert_eq!(resolved.id, "alice-smith");
+        assert!(store.resolve("slack", "U000").unwrap().is_none());
+
+        // Linking the same identity again is a no-op; to someone else is an error.
+        store
+            .link("Alice Smith", Identity::new("slack", "U999"))
+            .unwrap();
+        let err = store
+            .link("bob", Identity::new("slack", "U999"))
+            .unwrap_err();
+        assert!(matches!(err, ContactStoreError::AlreadyLinked { .. }));
+    }
+
+    #[test]
+    fn test_unlink_last_identity_removes_contact() {
+        let (store, _) = test_store();
+        store.link("bob", Identity::new("telegram", "1")).unwrap();
+        store.link("bob", Identity::new("slack", "U1")).unwrap();
+
+        let c = store
+            .unlink(&Identity::new("telegram", "1"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(c.identities.len(), 1);
+        assert_eq!(store.list().unwrap().len(), 1);
+
+        store.unlink(&Identity::new("slack", "U1")).unwrap();
+        assert!(store.list().unwrap().is_empty());
+        assert!(
+            store
+                .unlink(&Identity::new("slack", "U1"))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_suggestions_and_dismiss() {
+        let (store, _) = test_store();
+        store
+            .observe("telegram", "111", Some("@carol"), Some("Carol"))
+            .unwrap();
+        store.observe("slack", "U2", Some("carol"), None).unwrap();
+        store
+            .observe("email", "carol@example.com", None, None)
+            .unwrap();
+        store.observe("slack", "U3", Some("dave"), None).unwrap();
+
+        let suggestions = store.suggestions().unwrap();
+        assert_eq!(suggestions.len(), 3, "{:?}", suggestions);
+        assert!(suggestions[0].reason.contains("same username"));
+
+        store
+            .dismiss(
+                &Identity::new("telegram", "111"),
+                &Identity::new("slack", "U2"),
+            )
+            .unwrap();
+        assert_eq!(store.suggestions().unwrap().len(), 2);
+
+        // Linked pairs are no longer suggested; linking keeps the seen username.
+        let c = store
+            .link("carol", Identity::new("email", "carol@example.com"))
+            .unwrap();
+        assert!(c.identities[0].username.is_none());
+        let c = store.link("carol", Identity::new("slack", "U2")).unwrap();
+        assert_eq!(c.identities[1].username.as_deref(), Some("carol"));
+        let remaining = store.suggestions().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].a.channel, "email");
+        assert_eq!(remaining[0].b.channel, "telegram");
+    }
+}
@@ -0,0 +1,87 @@
+//! Job cancellation signals.
+//!
+//! Cancelling a job fires its [`CancellationSignal`]. The worker races its
+//! execution loop against the signal, so in-flight LLM and HTTP calls are
+//! dropped as soon as it fires; processes and sandbox containers started by
+//! tools are then terminated with [`CANCEL_GRACE_PERIOD`] between SIGTERM
+//! and SIGKILL.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+/// Time a cancelled process or container gets to exit after SIGTERM before
+/// it is killed.
+pub const CANCEL_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// Cloneable, fire-once cancellation flag shared by everyone working on a job.
+#[derive(Clone)]
+pub struct CancellationSignal {
+    tx: Arc<watch::Sender<bool>>,
+}
+
+impl CancellationSignal {
+    pub fn new() -> Self {
+        let (tx, _) = watch::channel(false);
+        Self { tx: Arc::new(tx) }
+    }
+
+    /// Fire the signal. Later calls are no-ops.
+    pub fn cancel(&self) {
+        self.tx.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.tx.borrow()
+    }
+
+    /// Resolve once the signal has fired (immediately if it already has).
+    pub async fn cancelled(&self) {
+        let mut rx = self.tx.subscribe();
+        // The sender lives in `self`, so the channel cannot close while we wait.
+        let _ = rx.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+impl Default for CancellationSignal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for CancellationSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationSignal")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancellation_signal_wakes_waiters() {
+        let signal = CancellationSignal::new();
+        let waiter = {
+            let signal = signal.clone();
+            tokio::spawn(async move { signal.cancelled().await })
+        };
+        assert!(!signal.is_cancelled());
+
+        signal.cancel();
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("waiter should wake")
+            .unwrap();
+        assert!(signal.is_cancelled());
+
+        // Already-fired signals resolve immediately.
+        tokio::time::timeout(Duration::from_millis(100), signal.cancelled())
+            .await
+            .unwrap();
+    }
+}
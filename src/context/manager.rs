@@ -5,7 +5,7 @@ use std::collections::HashMap;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
-use crate::context::{JobContext, Memory};
+use crate::context::{CancellationSignal, JobContext, Memory};
 use crate::error::JobError;
 
 /// Manages contexts for multiple concurrent jobs.
@@ -14,6 +14,8 @@ pub struct ContextManager {
     contexts: RwLock<HashMap<Uuid, JobContext>>,
     /// Memory for each job.
     memories: RwLock<HashMap<Uuid, Memory>>,
+    /// Cancellation signal for each job, created on first use.
+    cancellations: RwLock<HashMap<Uuid, CancellationSignal>>,
     /// Maximum concurrent jobs.
     max_jobs: usize,
 }
@@ -24,6 +26,7 @@ impl ContextManager {
         Self {
             contexts: RwLock::new(HashMap::new()),
             memories: RwLock::new(HashMap::new()),
+            cancellations: RwLock::new(HashMap::new()),
             max_jobs,
         }
     }
@@ -109,6 +112,22 @@ impl ContextManager {
         Ok(f(memory))
     }
 
+    /// The job's cancellation signal.
+    pub async fn cancellation(&self, job_id: Uuid) -> CancellationSignal {
+        self.cancellations
+            .write()
+            .await
+            .entry(job_id)
+            .or_default()
+            .clone()
+    }
+
+    /// Fire the job's cancellation signal, stopping its worker and any tool
+    /// call in flight. The state transition is up to the caller.
+    pub async fn cancel(&self, job_id: Uuid) {
+        self.cancellation(job_id).await.cancel();
+    }
+
     /// List all active job IDs.
     pub async fn active_jobs(&self) -> Vec<Uuid> {
         self.contexts
@@ -172,6 +191,7 @@ impl ContextManager {
             .await
             .remove(&job_id)
             .ok_or(JobError::NotFound { id: job_id })?;
+        self.cancellations.write().await.remove(&job_id);
 
         Ok((context, memory))
     }
@@ -284,6 +304,20 @@ mod tests {
         assert_eq!(context.user_id, "user-123");
     }
 
+    #[tokio::test]
+    async fn test_cancel_fires_shared_signal() {
+        let manager = ContextManager::new(5);
+        let job_id = manager.create_job("Test", "Description").await.unwrap();
+
+        let signal = manager.cancellation(job_id).await;
+        assert!(!signal.is_cancelled());
+        manager.cancel(job_id).await;
+        assert!(signal.is_cancelled());
+
+        manager.remove_job(job_id).await.unwrap();
+        assert!(!manager.cancellation(job_id).await.is_cancelled());
+    }
+
     #[tokio::test]
     async fn test_max_jobs_limit() {
         let manager = ContextManager::new(2);
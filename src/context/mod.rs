@@ -6,10 +6,12 @@
 //! - State machine
 //! - Resource tracking
 
+mod cancel;
 mod manager;
 mod memory;
 mod state;
 
+pub use cancel::{CANCEL_GRACE_PERIOD, CancellationSignal};
 pub use manager::ContextManager;
 pub use memory::{ActionRecord, ConversationMemory, Memory};
 pub use state::{JobContext, JobState, StateTransition};
@@ -1,5 +1,6 @@
 //! Job state machine.
 
+use std::path::PathBuf;
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
@@ -7,6 +8,8 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::workspace::ScopedOwner;
+
 /// State of a job.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -101,6 +104,9 @@ pub struct JobContext {
     pub user_id: String,
     /// Conversation ID if linked to a conversation.
     pub conversation_id: Option<Uuid>,
+    /// Chat session the job runs in; per-session budget caps are kept
+    /// against it.
+    pub session_id: Option<Uuid>,
     /// Job title.
     pub title: String,
     /// Job description.
@@ -135,6 +141,18 @@ pub struct JobContext {
     pub transitions: Vec<StateTransition>,
     /// Metadata.
     pub metadata: serde_json::Value,
+    /// Default directory for relative paths and shell commands (set when the
+    /// chat session is bound to a project).
+    pub working_dir: Option<PathBuf>,
+    /// IANA time zone of the user the job runs for (e.g. "Europe/Berlin").
+    pub timezone: Option<String>,
+    /// Contact the requesting identity is linked to. When set, `user_id` is
+    /// the contact's user id and per-person state (profile facts) follows it.
+    pub contact: Option<String>,
+    /// Memory the agent handling the message may see; `None` leaves the
+    /// workspace unscoped.
+    #[serde(skip)]
+    pub memory: Option<ScopedOwner>,
 }
 
 impl JobContext {
@@ -154,6 +172,7 @@ impl JobContext {
             state: JobState::Pending,
             user_id: user_id.into(),
             conversation_id: None,
+            session_id: None,
             title: title.into(),
             description: description.into(),
             category: None,
@@ -171,6 +190,10 @@ impl JobContext {
             repair_attempts: 0,
             transitions: Vec::new(),
             metadata: serde_json::Value::Null,
+            working_dir: None,
+            timezone: None,
+            contact: None,
+            memory: None,
         }
     }
 
@@ -0,0 +1,210 @@
+//! Local crash reports.
+//!
+//! When enabled (`CRASH_REPORTS_ENABLED=true`), a panic hook writes a JSON
+//! report to `~/.ironclaw/crashes/` with the panic message, location,
+//! backtrace and the turns and jobs that were running at the time. Nothing
+//! leaves the machine; `ironclaw doctor --bundle` picks the reports up.
+//!
+//! Turns and jobs register themselves with [`enter`], which returns a guard
+//! that removes the entry again when dropped.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::safety::log_redaction::LogRedactor;
+
+/// Reports kept on disk; older ones are pruned when a new one is written.
+const MAX_REPORTS: usize = 20;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static ACTIVE: LazyLock<Mutex<BTreeMap<u64, String>>> = LazyLock::new(Default::default);
+
+/// Registration of one running turn or job; dropping it unregisters.
+#[must_use = "the context is removed when the guard is dropped"]
+#[derive(Debug)]
+pub struct ContextGuard {
+    id: u64,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        ACTIVE
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.id);
+    }
+}
+
+/// Record `description` (e.g. `turn thread=… channel=…`) as running until
+/// the guard is dropped.
+pub fn enter(description: impl Into<String>) -> ContextGuard {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    ACTIVE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(id, description.into());
+    ContextGuard { id }
+}
+
+/// Descriptions of everything currently running, oldest first.
+pub fn active_contexts() -> Vec<String> {
+    ACTIVE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .values()
+        .cloned()
+        .collect()
+}
+
+/// A crash report as written to disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub version: String,
+    pub timestamp: String,
+    pub thread: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub active: Vec<String>,
+    pub backtrace: String,
+    pub os: String,
+    pub arch: String,
+}
+
+impl CrashReport {
+    /// Build a report for `info`, redacting secrets from the text fields.
+    fn capture(info: &std::panic::PanicHookInfo<'_>) -> Self {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| (*s).to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "non-string panic payload".to_string());
+        let redactor = LogRedactor::new();
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            thread: std::thread::current()
+                .name()
+                .unwrap_or("unnamed")
+                .to_string(),
+            message: redactor.redact_owned(&message),
+            location: info
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            active: active_contexts()
+                .iter()
+                .map(|c| redactor.redact_owned(c))
+                .collect(),
+            backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+        }
+    }
+}
+
+/// Default report directory, `~/.ironclaw/crashes`.
+pub fn crash_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".ironclaw")
+        .join("crashes")
+}
+
+/// Install the panic hook. The previous hook still runs afterwards, so the
+/// usual panic message is printed as before.
+pub fn install_panic_hook(dir: PathBuf) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let report = CrashReport::capture(info);
+        match write_report(&dir, &report) {
+            Ok(path) => eprintln!("Crash report written to {}", path.display()),
+            Err(e) => eprintln!("Failed to write crash report: {}", e),
+        }
+        previous(info);
+    }));
+}
+
+/// Write `report` into `dir` and prune old reports.
+pub fn write_report(dir: &Path, report: &CrashReport) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let name = format!(
+        "crash-{}-{}.json",
+        Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+        std::process::id()
+    );
+    let path = dir.join(name);
+    std::fs::write(&path, serde_json::to_vec_pretty(report)?)?;
+
+    let mut reports = list_reports(dir);
+    if reports.len() > MAX_REPORTS {
+        let excess = reports.len() - MAX_REPORTS;
+        for old in reports.drain(..excess) {
+            let _ = std::fs::remove_file(old);
+        }
+    }
+    Ok(path)
+}
+
+/// Report files in `dir`, oldest first.
+pub fn list_reports(dir: &Path) -> Vec<PathBuf> {
+    let mut reports: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with("crash-") && n.ends_with(".json"))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    reports.sort();
+    reports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_guard_unregisters() {
+        let marker = format!("turn test-{}", uuid::Uuid::new_v4());
+        {
+            let _guard = enter(marker.clone());
+            assert!(active_contexts().contains(&marker));
+        }
+        assert!(!active_contexts().contains(&marker));
+    }
+
+    #[test]
+    fn test_write_report_prunes_old_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = CrashReport {
+            version: "0.0.0".into(),
+            timestamp: Utc::now().to_rfc3339(),
+            thread: "main".into(),
+            message: "boom".into(),
+            location: Some("src/main.rs:1:1".into()),
+            active: vec!["job 42".into()],
+            backtrace: String::new(),
+            os: "linux".into(),
+            arch: "x86_64".into(),
+        };
+        for _ in 0..MAX_REPORTS + 3 {
+            write_report(dir.path(), &report).unwrap();
+        }
+        std::fs::write(dir.path().join("notes.txt"), "x").unwrap();
+
+        let reports = list_reports(dir.path());
+        assert!(reports.len() <= MAX_REPORTS);
+        let parsed: CrashReport =
+            serde_json::from_slice(&std::fs::read(&reports[0]).unwrap()).unwrap();
+        assert_eq!(parsed.active, vec!["job 42".to_string()]);
+    }
+}
@@ -24,12 +24,14 @@ use crate::context::{ActionRecord, JobContext, JobState};
 use crate::db::Database;
 use crate::error::{DatabaseError, WorkspaceError};
 use crate::history::{
-    ConversationMessage, ConversationSummary, JobEventRecord, LlmCallRecord, SandboxJobRecord,
-    SandboxJobSummary, SettingRow,
+    ACTIVITY_LIST_LIMIT, ActivityStats, ConversationActivity, ConversationMessage,
+    ConversationSummary, EgressRecord, JobActivity, JobEventRecord, LlmCallRecord, LlmUsage,
+    MemoryChange, SandboxJobRecord, SandboxJobSummary, SettingRow, ToolUsage,
 };
 use crate::workspace::{
-    ConnectionType, MemoryChunk, MemoryConnection, MemoryDocument, MemorySpace, ProfileType,
-    RankedResult, SearchConfig, SearchResult, UserProfile, WorkspaceEntry, reciprocal_rank_fusion,
+    ConnectionType, DocumentUsage, MemoryChunk, MemoryConnection, MemoryDocument, MemorySpace,
+    ProfileType, RankedResult, SearchConfig, SearchResult, UserProfile, WorkspaceEntry,
+    reciprocal_rank_fusion,
 };
 
 use crate::db::libsql_migrations;
@@ -277,9 +279,46 @@ fn get_opt_ts(row: &libsql::Row, idx: i32) -> Option<DateTime<Utc>> {
 impl Database for LibSqlBackend {
     async fn run_migrations(&self) -> Result<(), DatabaseError> {
         let conn = self.connect()?;
+        let migration =
+            |e: libsql::Error| DatabaseError::Migration(format!("libSQL migration failed: {}", e));
         conn.execute_batch(libsql_migrations::SCHEMA)
             .await
-            .map_err(|e| DatabaseError::Migration(format!("libSQL migration failed: {}", e)))?;
+            .map_err(migration)?;
+
+        // V13 alters an existing table, which SQLite can't do idempotently.
+        let exists = |sql: &'static str| {
+            let conn = &conn;
+            async move {
+                let mut rows = conn.query(sql, ()).await.map_err(migration)?;
+                Ok::<_, DatabaseError>(rows.next().await.map_err(migration)?.is_some())
+            }
+        };
+        if !exists("SELECT 1 FROM _migrations WHERE version = 13").await? {
+            if exists(
+                "SELECT 1 FROM pragma_table_info('llm_calls') WHERE name = 'cache_read_tokens'",
+            )
+            .await?
+            {
+                conn.execute(
+                    "INSERT OR IGNORE INTO _migrations (version, name) VALUES (13, 'llm_cache_tokens')",
+                    (),
+                )
+                .await
+                .map_err(migration)?;
+            } else {
+                conn.execute_batch(libsql_migrations::LLM_CACHE_TOKENS)
+                    .await
+                    .map_err(migration)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn ping(&self) -> Result<(), DatabaseError> {
+        let conn = self.connect()?;
+        conn.query("SELECT 1", ())
+            .await
+            .map_err(|e| DatabaseError::Query(e.to_string()))?;
         Ok(())
     }
 
@@ -605,6 +644,25 @@ impl Database for LibSqlBackend {
         Ok(messages)
     }
 
+    async fn delete_conversation_messages(
+        &self,
+        conversation_id: Uuid,
+        message_ids: &[Uuid],
+    ) -> Result<u64, DatabaseError> {
+        let conn = self.connect()?;
+        let mut deleted = 0;
+        for id in message_ids {
+            deleted += conn
+                .execute(
+                    "DELETE FROM conversation_messages WHERE conversation_id = ?1 AND id = ?2",
+                    params![conversation_id.to_string(), id.to_string()],
+                )
+                .await
+                .map_err(|e| DatabaseError::Query(e.to_string()))?;
+        }
+        Ok(deleted)
+    }
+
     async fn conversation_belongs_to_user(
         &self,
         conversation_id: Uuid,
@@ -707,6 +765,7 @@ impl Database for LibSqlBackend {
                     state,
                     user_id: get_text(&row, 6),
                     conversation_id: get_opt_text(&row, 1).and_then(|s| s.parse().ok()),
+                    session_id: None,
                     title: get_text(&row, 2),
                     description: get_text(&row, 3),
                     category: get_opt_text(&row, 4),
@@ -719,6 +778,10 @@ impl Database for LibSqlBackend {
                     actual_cost: get_decimal(&row, 12),
                     total_tokens_used: 0,
                     max_tokens: 0,
+                    working_dir: None,
+                    timezone: None,
+                    contact: None,
+                    memory: None,
                     repair_attempts: get_i64(&row, 13) as u32,
                     created_at: get_ts(&row, 14),
                     started_at: get_opt_ts(&row, 15),
@@ -864,8 +927,8 @@ impl Database for LibSqlBackend {
         let id = Uuid::new_v4();
         conn.execute(
                 r#"
-                INSERT INTO llm_calls (id, job_id, conversation_id, provider, model, input_tokens, output_tokens, cost, purpose)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                INSERT INTO llm_calls (id, job_id, conversation_id, provider, model, input_tokens, output_tokens, cache_read_tokens, cache_write_tokens, cost, purpose)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
                 "#,
                 params![
                     id.to_string(),
@@ -875,6 +938,8 @@ impl Database for LibSqlBackend {
                     record.model,
                     record.input_tokens as i64,
                     record.output_tokens as i64,
+                    record.cache_read_tokens as i64,
+                    record.cache_write_tokens as i64,
                     record.cost.to_string(),
                     opt_text(record.purpose),
                 ],
@@ -1309,6 +1374,86 @@ impl Database for LibSqlBackend {
         Ok(events)
     }
 
+    // ==================== Sandbox Egress ====================
+
+    async fn record_egress(&self, record: &EgressRecord) -> Result<(), DatabaseError> {
+        let conn = self.connect()?;
+        conn.execute(
+            r#"
+            INSERT INTO sandbox_egress (
+                id, job_id, method, host, path, decision, reason, status,
+                request_bytes, response_bytes, credential_injected, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+            "#,
+            params![
+                record.id.to_string(),
+                opt_text_owned(record.job_id.map(|id| id.to_string())),
+                record.method.as_str(),
+                record.host.as_str(),
+                record.path.as_str(),
+                record.decision.as_str(),
+                opt_text(record.reason.as_deref()),
+                match record.status {
+                    Some(status) => libsql::Value::Integer(status as i64),
+                    None => libsql::Value::Null,
+                },
+                record.request_bytes,
+                record.response_bytes,
+                record.credential_injected as i64,
+                fmt_ts(&record.created_at),
+            ],
+        )
+        .await
+        .map_err(|e| DatabaseError::Query(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn list_egress(
+        &self,
+        job_id: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<EgressRecord>, DatabaseError> {
+        let conn = self.connect()?;
+        let mut rows = conn
+            .query(
+                r#"
+                SELECT id, job_id, method, host, path, decision, reason, status,
+                       request_bytes, response_bytes, credential_injected, created_at
+                FROM sandbox_egress
+                WHERE ?1 IS NULL OR job_id = ?1
+                ORDER BY created_at DESC
+                LIMIT ?2
+                "#,
+                params![opt_text_owned(job_id.map(|id| id.to_string())), limit],
+            )
+            .await
+            .map_err(|e| DatabaseError::Query(e.to_string()))?;
+
+        let mut records = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| DatabaseError::Query(e.to_string()))?
+        {
+            records.push(EgressRecord {
+                id: get_text(&row, 0).parse().unwrap_or_default(),
+                job_id: get_opt_text(&row, 1).and_then(|id| id.parse().ok()),
+                method: get_text(&row, 2),
+                host: get_text(&row, 3),
+                path: get_text(&row, 4),
+                decision: get_text(&row, 5),
+                reason: get_opt_text(&row, 6),
+                status: row.get::<i64>(7).ok().map(|s| s as i32),
+                request_bytes: get_i64(&row, 8),
+                response_bytes: get_i64(&row, 9),
+                credential_injected: get_opt_bool(&row, 10).unwrap_or(false),
+                created_at: get_ts(&row, 11),
+            });
+        }
+        records.reverse();
+        Ok(records)
+    }
+
     // ==================== Routines ====================
 
     async fn create_routine(&self, routine: &Routine) -> Result<(), DatabaseError> {
@@ -1461,6 +1606,30 @@ impl Database for LibSqlBackend {
         Ok(routines)
     }
 
+    async fn list_file_routines(&self) -> Result<Vec<Routine>, DatabaseError> {
+        let conn = self.connect()?;
+        let mut rows = conn
+            .query(
+                &format!(
+                    "SELECT {} FROM routines WHERE enabled = 1 AND trigger_type = 'file'",
+                    ROUTINE_COLUMNS
+                ),
+                (),
+            )
+            .await
+            .map_err(|e| DatabaseError::Query(e.to_string()))?;
+
+        let mut routines = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| DatabaseError::Query(e.to_string()))?
+        {
+            routines.push(row_to_routine_libsql(&row)?);
+        }
+        Ok(routines)
+    }
+
     async fn list_due_cron_routines(&self) -> Result<Vec<Routine>, DatabaseError> {
         let conn = self.connect()?;
         let now = fmt_ts(&Utc::now());
@@ -1770,6 +1939,154 @@ impl Database for LibSqlBackend {
         Ok(())
     }
 
+    // ==================== Activity ====================
+
+    async fn activity_since(
+        &self,
+        user_id: Option<&str>,
+        since: DateTime<Utc>,
+    ) -> Result<ActivityStats, DatabaseError> {
+        let conn = self.connect()?;
+        let since = fmt_ts(&since);
+        let q = |e: libsql::Error| DatabaseError::Query(e.to_string());
+        let mut stats = ActivityStats::default();
+
+        // Timestamps are a mix of RFC 3339 and SQLite datetime() output, so
+        // compare them through datetime().
+        let mut rows = conn
+            .query(
+                r#"
+                SELECT a.tool_name, COUNT(*) as calls,
+                       SUM(CASE WHEN a.success = 0 THEN 1 ELSE 0 END) as failures
+                FROM job_actions a
+                JOIN agent_jobs j ON j.id = a.job_id
+                WHERE datetime(a.created_at) >= datetime(?2) AND (?1 IS NULL OR j.user_id = ?1)
+                GROUP BY a.tool_name
+                ORDER BY calls DESC
+                LIMIT ?3
+                "#,
+                params![opt_text(user_id), since.clone(), ACTIVITY_LIST_LIMIT],
+            )
+            .await
+            .map_err(q)?;
+        while let Some(row) = rows.next().await.map_err(q)? {
+            stats.tool_usage.push(ToolUsage {
+                tool_name: get_text(&row, 0),
+                calls: get_i64(&row, 1),
+                failures: get_i64(&row, 2),
+            });
+        }
+
+        let mut rows = conn
+            .query(
+                r#"
+                SELECT id, title, status, created_at, completed_at, actual_cost
+                FROM agent_jobs
+                WHERE (datetime(created_at) >= datetime(?2) OR datetime(completed_at) >= datetime(?2))
+                  AND (?1 IS NULL OR user_id = ?1)
+                ORDER BY datetime(COALESCE(completed_at, created_at)) DESC
+                LIMIT ?3
+                "#,
+                params![opt_text(user_id), since.clone(), ACTIVITY_LIST_LIMIT],
+            )
+            .await
+            .map_err(q)?;
+        while let Some(row) = rows.next().await.map_err(q)? {
+            stats.jobs.push(JobActivity {
+                id: get_text(&row, 0).parse().unwrap_or_default(),
+                title: get_text(&row, 1),
+                status: get_text(&row, 2),
+                created_at: get_ts(&row, 3),
+                completed_at: get_opt_ts(&row, 4),
+                actual_cost: get_opt_decimal(&row, 5),
+            });
+        }
+
+        // Costs are stored as decimal text; sum them here to keep precision.
+        let mut rows = conn
+            .query(
+                r#"
+                SELECT l.input_tokens, l.output_tokens, l.cost
+                FROM llm_calls l
+                LEFT JOIN agent_jobs j ON j.id = l.job_id
+                LEFT JOIN conversations c ON c.id = l.conversation_id
+                WHERE datetime(l.created_at) >= datetime(?2)
+                  AND (?1 IS NULL OR j.user_id = ?1 OR c.user_id = ?1)
+                "#,
+                params![opt_text(user_id), since.clone()],
+            )
+            .await
+            .map_err(q)?;
+        let mut llm = LlmUsage::default();
+        while let Some(row) = rows.next().await.map_err(q)? {
+            llm.calls += 1;
+            llm.input_tokens += get_i64(&row, 0);
+            llm.output_tokens += get_i64(&row, 1);
+            llm.cost += get_decimal(&row, 2);
+        }
+        stats.llm = llm;
+
+        let mut rows = conn
+            .query(
+                r#"
+                SELECT path, datetime(created_at) >= datetime(?2) as created, updated_at
+                FROM memory_documents
+                WHERE datetime(updated_at) >= datetime(?2) AND (?1 IS NULL OR user_id = ?1)
+                ORDER BY datetime(updated_at) DESC
+                LIMIT ?3
+                "#,
+                params![opt_text(user_id), since.clone(), ACTIVITY_LIST_LIMIT],
+            )
+            .await
+            .map_err(q)?;
+        while let Some(row) = rows.next().await.map_err(q)? {
+            stats.memory_changes.push(MemoryChange {
+                path: get_text(&row, 0),
+                created: get_i64(&row, 1) != 0,
+                updated_at: get_ts(&row, 2),
+            });
+        }
+
+        let mut rows = conn
+            .query(
+                r#"
+                SELECT
+                    c.id,
+                    c.channel,
+                    COUNT(m.id) as messages,
+                    (
+                        SELECT substr(m2.content, 1, 120)
+                        FROM conversation_messages m2
+                        WHERE m2.conversation_id = c.id AND m2.role = 'user'
+                          AND datetime(m2.created_at) >= datetime(?2)
+                        ORDER BY datetime(m2.created_at)
+                        LIMIT 1
+                    ) as preview,
+                    c.last_activity
+                FROM conversations c
+                JOIN conversation_messages m ON m.conversation_id = c.id
+                WHERE datetime(m.created_at) >= datetime(?2) AND (?1 IS NULL OR c.user_id = ?1)
+                GROUP BY c.id, c.channel, c.last_activity
+                ORDER BY messages DESC
+                LIMIT ?3
+                "#,
+                params![opt_text(user_id), since, ACTIVITY_LIST_LIMIT],
+            )
+            .await
+            .map_err(q)?;
+        while let Some(row) = rows.next().await.map_err(q)? {
+            stats.conversations.push(ConversationActivity {
+                id: get_text(&row, 0).parse().unwrap_or_default(),
+                channel: get_text(&row, 1),
+                messages: get_i64(&row, 2),
+                preview: get_opt_text(&row, 3),
+                last_activity: get_ts(&row, 4),
+            });
+        }
+
+        Ok(stats)
+    }
+
     // ==================== Settings ====================
 
     async fn get_setting(
@@ -2288,6 +2605,55 @@ impl Database for LibSqlBackend {
         Ok(docs)
     }
 
+    async fn workspace_usage(
+        &self,
+        user_id: &str,
+        agent_id: Option<Uuid>,
+    ) -> Result<Vec<DocumentUsage>, WorkspaceError> {
+        let conn = self.connect().map_err(|e| WorkspaceError::SearchFailed {
+            reason: e.to_string(),
+        })?;
+        let agent_id_str = agent_id.map(|id| id.to_string());
+        let mut rows = conn
+            .query(
+                r#"
+                SELECT d.id, d.path, d.updated_at,
+                       length(CAST(d.content AS BLOB)),
+                       COUNT(c.id),
+                       COALESCE(SUM(length(c.embedding)), 0)
+                FROM memory_documents d
+                LEFT JOIN memory_chunks c ON c.document_id = d.id
+                WHERE d.user_id = ?1 AND d.agent_id IS ?2
+                GROUP BY d.id
+                ORDER BY d.updated_at DESC
+                "#,
+                params![user_id, agent_id_str.as_deref()],
+            )
+            .await
+            .map_err(|e| WorkspaceError::SearchFailed {
+                reason: format!("Usage query failed: {}", e),
+            })?;
+
+        let mut usage = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| WorkspaceError::SearchFailed {
+                reason: format!("Usage query failed: {}", e),
+            })?
+        {
+            usage.push(DocumentUsage {
+                document_id: get_text(&row, 0).parse().unwrap_or_default(),
+                path: get_text(&row, 1),
+                updated_at: get_ts(&row, 2),
+                content_bytes: get_i64(&row, 3),
+                chunks: get_i64(&row, 4),
+                embedding_bytes: get_i64(&row, 5),
+            });
+        }
+        Ok(usage)
+    }
+
     // ==================== Workspace: Chunks ====================
 
     async fn delete_chunks(&self, document_id: Uuid) -> Result<(), WorkspaceError> {
@@ -2365,6 +2731,51 @@ impl Database for LibSqlBackend {
         Ok(())
     }
 
+    async fn list_chunks(&self, document_id: Uuid) -> Result<Vec<MemoryChunk>, WorkspaceError> {
+        let conn = self.connect().map_err(|e| WorkspaceError::SearchFailed {
+            reason: e.to_string(),
+        })?;
+        let mut rows = conn
+            .query(
+                r#"
+                SELECT id, document_id, chunk_index, content, embedding, created_at
+                FROM memory_chunks
+                WHERE document_id = ?1
+                ORDER BY chunk_index
+                "#,
+                params![document_id.to_string()],
+            )
+            .await
+            .map_err(|e| WorkspaceError::SearchFailed {
+                reason: format!("Query failed: {}", e),
+            })?;
+
+        let mut chunks = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| WorkspaceError::SearchFailed {
+                reason: format!("Query failed: {}", e),
+            })?
+        {
+            let embedding = row.get::<Vec<u8>>(4).ok().map(|bytes| {
+                bytes
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect()
+            });
+            chunks.push(MemoryChunk {
+                id: get_text(&row, 0).parse().unwrap_or_default(),
+                document_id: get_text(&row, 1).parse().unwrap_or_default(),
+                chunk_index: get_i64(&row, 2) as i32,
+                content: get_text(&row, 3),
+                embedding,
+                created_at: get_ts(&row, 5),
+            });
+        }
+        Ok(chunks)
+    }
+
     async fn get_chunks_without_embeddings(
         &self,
         user_id: &str,
@@ -3250,4 +3661,87 @@ mod tests {
         // Should be parseable by the first branch (RFC 3339)
         assert!(DateTime::parse_from_rfc3339(&formatted).is_ok());
     }
+
+    // ==================== Migrations ====================
+
+    #[tokio::test]
+    async fn test_migrations_add_cache_columns_to_an_existing_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = LibSqlBackend::new_local(&dir.path().join("old.db"))
+            .await
+            .unwrap();
+        // llm_calls as created before V13.
+        backend
+            .connect()
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE llm_calls (
+                    id TEXT PRIMARY KEY,
+                    job_id TEXT,
+                    conversation_id TEXT,
+                    provider TEXT NOT NULL,
+                    model TEXT NOT NULL,
+                    input_tokens INTEGER NOT NULL,
+                    output_tokens INTEGER NOT NULL,
+                    cost TEXT NOT NULL,
+                    purpose TEXT,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+                );",
+            )
+            .await
+            .unwrap();
+
+        backend.run_migrations().await.unwrap();
+        // A second start must not try to add the columns again.
+        backend.run_migrations().await.unwrap();
+
+        let record = LlmCallRecord {
+            job_id: None,
+            conversation_id: None,
+            provider: "anthropic",
+            model: "claude",
+            input_tokens: 100,
+            output_tokens: 10,
+            cache_read_tokens: 80,
+            cache_write_tokens: 20,
+            cost: Decimal::ZERO,
+            purpose: None,
+        };
+        backend.record_llm_call(&record).await.unwrap();
+
+        let conn = backend.connect().unwrap();
+        let mut rows = conn
+            .query(
+                "SELECT cache_read_tokens, cache_write_tokens FROM llm_calls",
+                (),
+            )
+            .await
+            .unwrap();
+        let row = rows.next().await.unwrap().unwrap();
+        assert_eq!(row.get::<i64>(0).unwrap(), 80);
+        assert_eq!(row.get::<i64>(1).unwrap(), 20);
+        let mut rows = conn
+            .query("SELECT 1 FROM _migrations WHERE version = 13", ())
+            .await
+            .unwrap();
+        assert!(rows.next().await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_fresh_database_records_cache_migration() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = LibSqlBackend::new_local(&dir.path().join("new.db"))
+            .await
+            .unwrap();
+        backend.run_migrations().await.unwrap();
+        backend.run_migrations().await.unwrap();
+
+        let mut rows = backend
+            .connect()
+            .unwrap()
+            .query("SELECT 1 FROM _migrations WHERE version = 13", ())
+            .await
+            .unwrap();
+        assert!(rows.next().await.unwrap().is_some());
+    }
 }
@@ -1,7 +1,9 @@
 //! SQLite-dialect migrations for the libSQL/Turso backend.
 //!
-//! Consolidates all PostgreSQL migrations (V1-V8) into a single SQLite-compatible
+//! Consolidates all PostgreSQL migrations (V1-V14) into a single SQLite-compatible
 //! schema. Run once on database creation; idempotent via `IF NOT EXISTS`.
+//! Column additions SQLite can't express idempotently live in their own
+//! constants and are applied once by `run_migrations`.
 
 /// Consolidated schema for libSQL.
 ///
@@ -142,6 +144,8 @@ CREATE TABLE IF NOT EXISTS llm_calls (
     model TEXT NOT NULL,
     input_tokens INTEGER NOT NULL,
     output_tokens INTEGER NOT NULL,
+    cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+    cache_write_tokens INTEGER NOT NULL DEFAULT 0,
     cost TEXT NOT NULL,
     purpose TEXT,
     created_at TEXT NOT NULL DEFAULT (datetime('now'))
@@ -599,6 +603,116 @@ CREATE TRIGGER IF NOT EXISTS update_memory_profiles_updated_at
         UPDATE memory_profiles SET updated_at = datetime('now') WHERE id = NEW.id;
     END;
 
+-- ==================== Secret Grants (V10) ====================
+
+CREATE TABLE IF NOT EXISTS secret_grants (
+    user_id TEXT NOT NULL,
+    secret_name TEXT NOT NULL,
+    extension TEXT NOT NULL,
+    granted_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (user_id, secret_name, extension)
+);
+
+CREATE INDEX IF NOT EXISTS idx_secret_grants_extension ON secret_grants(user_id, extension);
+
+-- One-time backfill for secrets stored before namespaces existed. The schema
+-- runs on every start, so it is gated on the _migrations marker below;
+-- otherwise a grant revoked by removing an extension would come back.
+INSERT OR IGNORE INTO secret_grants (user_id, secret_name, extension)
+SELECT user_id, name,
+       CASE WHEN provider LIKE 'mcp:%' THEN substr(provider, 5) ELSE provider END
+FROM secrets
+WHERE provider IS NOT NULL
+  AND NOT EXISTS (SELECT 1 FROM _migrations WHERE version = 10);
+
+INSERT OR IGNORE INTO secret_grants (user_id, secret_name, extension)
+SELECT s.user_id, s.name, t.name
+FROM secrets s
+JOIN wasm_tools t ON t.user_id = s.user_id
+JOIN tool_capabilities c ON c.wasm_tool_id = t.id
+JOIN json_each(c.allowed_secrets) p
+WHERE (s.name = p.value
+       OR (substr(p.value, -1) = '*'
+           AND substr(s.name, 1, length(p.value) - 1) = substr(p.value, 1, length(p.value) - 1)))
+  AND NOT EXISTS (SELECT 1 FROM _migrations WHERE version = 10);
+
+INSERT OR IGNORE INTO _migrations (version, name) VALUES (10, 'secret_grants');
+
+-- ==================== Sandbox Egress (V11) ====================
+
+CREATE TABLE IF NOT EXISTS sandbox_egress (
+    id TEXT PRIMARY KEY,
+    job_id TEXT,
+    method TEXT NOT NULL,
+    host TEXT NOT NULL,
+    path TEXT NOT NULL,
+    decision TEXT NOT NULL,
+    reason TEXT,
+    status INTEGER,
+    request_bytes INTEGER NOT NULL DEFAULT 0,
+    response_bytes INTEGER NOT NULL DEFAULT 0,
+    credential_injected INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_sandbox_egress_job ON sandbox_egress(job_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_sandbox_egress_created ON sandbox_egress(created_at);
+
+INSERT OR IGNORE INTO _migrations (version, name) VALUES (11, 'sandbox_egress');
+
+-- ==================== WASM Tool Incidents (V12) ====================
+
+CREATE TABLE IF NOT EXISTS wasm_tool_incidents (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    tool_name TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    detail TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    cleared_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_wasm_tool_incidents_tool ON wasm_tool_incidents(user_id, tool_name, created_at);
+
+INSERT OR IGNORE INTO _migrations (version, name) VALUES (12, 'wasm_tool_incidents');
+
+-- ==================== Secret Groups (V14) ====================
+
+CREATE TABLE IF NOT EXISTS secret_group_members (
+    group_name TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    added_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (group_name, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_secret_group_members_user ON secret_group_members(user_id);
+
+CREATE TABLE IF NOT EXISTS secret_group_shares (
+    group_name TEXT NOT NULL,
+    owner_id TEXT NOT NULL,
+    secret_name TEXT NOT NULL,
+    shared_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (group_name, owner_id, secret_name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_secret_group_shares_secret ON secret_group_shares(secret_name);
+
+CREATE TABLE IF NOT EXISTS secret_group_uses (
+    id TEXT PRIMARY KEY,
+    group_name TEXT NOT NULL,
+    owner_id TEXT NOT NULL,
+    secret_name TEXT NOT NULL,
+    acting_user TEXT NOT NULL,
+    tool TEXT NOT NULL,
+    used_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_secret_group_uses_used ON secret_group_uses(used_at);
+
+INSERT OR IGNORE INTO _migrations (version, name) VALUES (14, 'secret_groups');
+
+-- V13 (llm_cache_tokens) is applied by run_migrations; see LLM_CACHE_TOKENS.
+
 -- ==================== Seed data ====================
 
 -- Pre-populate leak detection patterns (matches PostgreSQL V2 migration).
@@ -623,3 +737,16 @@ INSERT OR IGNORE INTO leak_detection_patterns (id, name, pattern, severity, acti
     ('550e8400-e29b-41d4-a716-446655440012', 'high_entropy_hex', '(?<![a-fA-F0-9])[a-fA-F0-9]{64}(?![a-fA-F0-9])', 'medium', 'warn', 1, datetime('now'));
 
 "#;
+
+/// V13: prompt cache accounting on `llm_calls`.
+///
+/// SQLite has no `ADD COLUMN IF NOT EXISTS`, so this can't live in [`SCHEMA`].
+/// Fresh databases get the columns from `CREATE TABLE`; `run_migrations`
+/// runs this once for a table that predates them, then records version 13.
+pub const LLM_CACHE_TOKENS: &str = r#"
+BEGIN;
+ALTER TABLE llm_calls ADD COLUMN cache_read_tokens INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE llm_calls ADD COLUMN cache_write_tokens INTEGER NOT NULL DEFAULT 0;
+INSERT OR IGNORE INTO _migrations (version, name) VALUES (13, 'llm_cache_tokens');
+COMMIT;
+"#;
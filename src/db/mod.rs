@@ -32,12 +32,12 @@ use crate::context::{ActionRecord, JobContext, JobState};
 use crate::error::DatabaseError;
 use crate::error::WorkspaceError;
 use crate::history::{
-    ConversationMessage, ConversationSummary, JobEventRecord, LlmCallRecord, SandboxJobRecord,
-    SandboxJobSummary, SettingRow,
+    ActivityStats, ConversationMessage, ConversationSummary, EgressRecord, JobEventRecord,
+    LlmCallRecord, SandboxJobRecord, SandboxJobSummary, SettingRow,
 };
 use crate::workspace::{
-    MemoryChunk, MemoryConnection, MemoryDocument, MemorySpace, ProfileType, UserProfile,
-    WorkspaceEntry,
+    DocumentUsage, MemoryChunk, MemoryConnection, MemoryDocument, MemorySpace, ProfileType,
+    UserProfile, WorkspaceEntry,
 };
 use crate::workspace::{SearchConfig, SearchResult};
 
@@ -104,6 +104,9 @@ pub trait Database: Send + Sync {
     /// Run schema migrations for this backend.
     async fn run_migrations(&self) -> Result<(), DatabaseError>;
 
+    /// Run a trivial query to check the database is reachable.
+    async fn ping(&self) -> Result<(), DatabaseError>;
+
     // ==================== Conversations ====================
 
     /// Create a new conversation.
@@ -185,6 +188,13 @@ pub trait Database: Send + Sync {
         conversation_id: Uuid,
     ) -> Result<Vec<ConversationMessage>, DatabaseError>;
 
+    /// Delete messages from a conversation. Returns how many were deleted.
+    async fn delete_conversation_messages(
+        &self,
+        conversation_id: Uuid,
+        message_ids: &[Uuid],
+    ) -> Result<u64, DatabaseError>;
+
     /// Check if a conversation belongs to a specific user.
     async fn conversation_belongs_to_user(
         &self,
@@ -315,6 +325,19 @@ pub trait Database: Send + Sync {
     /// Load all job events.
     async fn list_job_events(&self, job_id: Uuid) -> Result<Vec<JobEventRecord>, DatabaseError>;
 
+    // ==================== Sandbox Egress ====================
+
+    /// Persist one request made through the sandbox network proxy.
+    async fn record_egress(&self, record: &EgressRecord) -> Result<(), DatabaseError>;
+
+    /// The most recent `limit` egress records, oldest first. `job_id`
+    /// narrows them to one job.
+    async fn list_egress(
+        &self,
+        job_id: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<EgressRecord>, DatabaseError>;
+
     // ==================== Routines ====================
 
     /// Create a new routine.
@@ -336,6 +359,9 @@ pub trait Database: Send + Sync {
     /// List all enabled event routines.
     async fn list_event_routines(&self) -> Result<Vec<Routine>, DatabaseError>;
 
+    /// List all enabled file-watch routines.
+    async fn list_file_routines(&self) -> Result<Vec<Routine>, DatabaseError>;
+
     /// List due cron routines.
     async fn list_due_cron_routines(&self) -> Result<Vec<Routine>, DatabaseError>;
 
@@ -398,6 +424,16 @@ pub trait Database: Send + Sync {
     /// Increment repair attempts.
     async fn increment_repair_attempts(&self, tool_name: &str) -> Result<(), DatabaseError>;
 
+    // ==================== Activity ====================
+
+    /// Aggregate tool calls, jobs, LLM spend, memory edits and conversations
+    /// since `since`. `None` covers every user.
+    async fn activity_since(
+        &self,
+        user_id: Option<&str>,
+        since: DateTime<Utc>,
+    ) -> Result<ActivityStats, DatabaseError>;
+
     // ==================== Settings ====================
 
     /// Get a single setting.
@@ -498,6 +534,14 @@ pub trait Database: Send + Sync {
         agent_id: Option<Uuid>,
     ) -> Result<Vec<MemoryDocument>, WorkspaceError>;
 
+    /// Per-document storage usage (content size, chunk count, embedding
+    /// bytes) for a user.
+    async fn workspace_usage(
+        &self,
+        user_id: &str,
+        agent_id: Option<Uuid>,
+    ) -> Result<Vec<DocumentUsage>, WorkspaceError>;
+
     // ==================== Workspace: Chunks ====================
 
     /// Delete all chunks for a document.
@@ -519,6 +563,9 @@ pub trait Database: Send + Sync {
         embedding: &[f32],
     ) -> Result<(), WorkspaceError>;
 
+    /// All chunks of a document in order, with their embeddings.
+    async fn list_chunks(&self, document_id: Uuid) -> Result<Vec<MemoryChunk>, WorkspaceError>;
+
     /// Get chunks without embeddings for backfilling.
     async fn get_chunks_without_embeddings(
         &self,
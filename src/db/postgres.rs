@@ -18,12 +18,12 @@ use crate::context::{ActionRecord, JobContext, JobState};
 use crate::db::Database;
 use crate::error::{DatabaseError, WorkspaceError};
 use crate::history::{
-    ConversationMessage, ConversationSummary, JobEventRecord, LlmCallRecord, SandboxJobRecord,
-    SandboxJobSummary, SettingRow, Store,
+    ActivityStats, ConversationMessage, ConversationSummary, EgressRecord, JobEventRecord,
+    LlmCallRecord, SandboxJobRecord, SandboxJobSummary, SettingRow, Store,
 };
 use crate::workspace::{
-    MemoryChunk, MemoryConnection, MemoryDocument, MemorySpace, ProfileType, Repository,
-    SearchConfig, SearchResult, UserProfile, WorkspaceEntry,
+    DocumentUsage, MemoryChunk, MemoryConnection, MemoryDocument, MemorySpace, ProfileType,
+    Repository, SearchConfig, SearchResult, UserProfile, WorkspaceEntry,
 };
 
 /// PostgreSQL database backend.
@@ -58,6 +58,11 @@ impl Database for PgBackend {
         self.store.run_migrations().await
     }
 
+    async fn ping(&self) -> Result<(), DatabaseError> {
+        self.store.conn().await?.query_one("SELECT 1", &[]).await?;
+        Ok(())
+    }
+
     // ==================== Conversations ====================
 
     async fn create_conversation(
@@ -166,6 +171,16 @@ impl Database for PgBackend {
         self.store.list_conversation_messages(conversation_id).await
     }
 
+    async fn delete_conversation_messages(
+        &self,
+        conversation_id: Uuid,
+        message_ids: &[Uuid],
+    ) -> Result<u64, DatabaseError> {
+        self.store
+            .delete_conversation_messages(conversation_id, message_ids)
+            .await
+    }
+
     async fn conversation_belongs_to_user(
         &self,
         conversation_id: Uuid,
@@ -339,6 +354,20 @@ impl Database for PgBackend {
         self.store.list_job_events(job_id).await
     }
 
+    // ==================== Sandbox Egress ====================
+
+    async fn record_egress(&self, record: &EgressRecord) -> Result<(), DatabaseError> {
+        self.store.record_egress(record).await
+    }
+
+    async fn list_egress(
+        &self,
+        job_id: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<EgressRecord>, DatabaseError> {
+        self.store.list_egress(job_id, limit).await
+    }
+
     // ==================== Routines ====================
 
     async fn create_routine(&self, routine: &Routine) -> Result<(), DatabaseError> {
@@ -365,6 +394,10 @@ impl Database for PgBackend {
         self.store.list_event_routines().await
     }
 
+    async fn list_file_routines(&self) -> Result<Vec<Routine>, DatabaseError> {
+        self.store.list_file_routines().await
+    }
+
     async fn list_due_cron_routines(&self) -> Result<Vec<Routine>, DatabaseError> {
         self.store.list_due_cron_routines().await
     }
@@ -452,6 +485,16 @@ impl Database for PgBackend {
         self.store.increment_repair_attempts(tool_name).await
     }
 
+    // ==================== Activity ====================
+
+    async fn activity_since(
+        &self,
+        user_id: Option<&str>,
+        since: DateTime<Utc>,
+    ) -> Result<ActivityStats, DatabaseError> {
+        self.store.activity_since(user_id, since).await
+    }
+
     // ==================== Settings ====================
 
     async fn get_setting(
@@ -574,6 +617,14 @@ impl Database for PgBackend {
         self.repo.list_documents(user_id, agent_id).await
     }
 
+    async fn workspace_usage(
+        &self,
+        user_id: &str,
+        agent_id: Option<Uuid>,
+    ) -> Result<Vec<DocumentUsage>, WorkspaceError> {
+        self.repo.workspace_usage(user_id, agent_id).await
+    }
+
     // ==================== Workspace: Chunks ====================
 
     async fn delete_chunks(&self, document_id: Uuid) -> Result<(), WorkspaceError> {
@@ -600,6 +651,10 @@ impl Database for PgBackend {
         self.repo.update_chunk_embedding(chunk_id, embedding).await
     }
 
+    async fn list_chunks(&self, document_id: Uuid) -> Result<Vec<MemoryChunk>, WorkspaceError> {
+        self.repo.list_chunks(document_id).await
+    }
+
     async fn get_chunks_without_embeddings(
         &self,
         user_id: &str,
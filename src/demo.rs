@@ -0,0 +1,334 @@
+//! Demo mode (`ironclaw --demo`).
+//!
+//! For recording demos and developing UI against realistic data without
+//! touching the real workspace or secrets:
+//!
+//! - the database is a throwaway libSQL file seeded with synthetic
+//!   conversations, memories and jobs ([`DemoStore`], [`seed`]);
+//! - no secrets store is opened, so extensions, MCP servers and WASM
+//!   channels stay off;
+//! - [`apply`] switches off everything that acts on the outside world:
+//!   sandbox and Claude Code jobs, WASM tools, routines, heartbeat, the
+//!   builder, embeddings, local filesystem/shell tools, `web_fetch` and the
+//!   HTTP webhook channel. Of the built-in tools only the
+//!   [`register_demo_tools`](crate::tools::ToolRegistry::register_demo_tools)
+//!   set is registered;
+//! - every response ends with [`WATERMARK`] ([`Watermark`]).
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::channels::MessageCapabilities;
+use crate::channels::postprocess::{DEFAULT_CHANNEL_KEY, OutputPipeline, OutputProcessor};
+use crate::config::Config;
+use crate::context::{JobContext, JobState};
+use crate::db::Database;
+use crate::error::Error;
+use crate::history::SandboxJobRecord;
+use crate::workspace::Workspace;
+
+/// Line appended to every response in demo mode.
+pub const WATERMARK: &str = "[demo mode: synthetic data, no real actions taken]";
+
+/// Name of the watermark output processor.
+const WATERMARK_PROCESSOR: &str = "demo_watermark";
+
+/// Switch off subsystems with side effects outside the process.
+pub fn apply(config: &mut Config) {
+    config.sandbox.enabled = false;
+    config.claude_code.enabled = false;
+    config.wasm.enabled = false;
+    config.routines.enabled = false;
+    config.heartbeat.enabled = false;
+    config.builder.enabled = false;
+    config.embeddings.enabled = false;
+    config.agent.allow_local_tools = false;
+    config.web_fetch.enabled = false;
+    config.channels.wasm_channels_enabled = false;
+    config.channels.http = None;
+}
+
+/// A seeded database in a temporary directory, deleted on drop.
+pub struct DemoStore {
+    pub db: Arc<dyn Database>,
+    dir: PathBuf,
+}
+
+impl DemoStore {
+    /// Create a fresh database and seed it for `user_id`.
+    #[cfg(feature = "libsql")]
+    pub async fn open(user_id: &str) -> Result<Self, Error> {
+        use crate::db::libsql_backend::LibSqlBackend;
+
+        let dir = std::env::temp_dir().join(format!("ironclaw-demo-{}", Uuid::new_v4()));
+        let backend = LibSqlBackend::new_local(&dir.join("demo.db")).await?;
+        backend.run_migrations().await?;
+        let store = Self {
+            db: Arc::new(backend),
+            dir,
+        };
+        seed(&store.db, user_id).await?;
+        Ok(store)
+    }
+
+    /// Demo mode keeps its data in libSQL; without it there is no store.
+    #[cfg(not(feature = "libsql"))]
+    pub async fn open(_user_id: &str) -> Result<Self, Error> {
+        Err(crate::error::ConfigError::InvalidValue {
+            key: "--demo".to_string(),
+            message: "demo mode needs a build with the 'libsql' feature".to_string(),
+        }
+        .into())
+    }
+}
+
+impl Drop for DemoStore {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Fill `db` with synthetic conversations, memories and jobs for `user_id`.
+pub async fn seed(db: &Arc<dyn Database>, user_id: &str) -> Result<(), Error> {
+    let conversations: &[(&str, &[(&str, &str)])] = &[
+        (
+            "gateway",
+            &[
+                ("user", "What's on my plate for the Atlas launch this week?"),
+                (
+                    "assistant",
+                    "Three things: sign off the pricing page copy (Tuesday), the load test \
+                     review with Priya (Wednesday 14:00), and the launch checklist walkthrough \
+                     on Friday. The pricing copy is the only one blocking others.",
+                ),
+                (
+                    "user",
+                    "Draft a short note asking Priya to move the review to 15:00.",
+                ),
+                (
+                    "assistant",
+                    "Hi Priya, could we push Wednesday's load test review to 15:00? \
+                     Something came up right before. Thanks!",
+                ),
+            ],
+        ),
+        (
+            "gateway",
+            &[
+                ("user", "Summarize the flaky test investigation."),
+                (
+                    "assistant",
+                    "`checkout::retries_on_timeout` fails about 1 run in 40. The mock clock \
+                     advances before the retry is scheduled, so the second attempt sees an \
+                     expired deadline. Pinning the clock in the test setup fixed it locally.",
+                ),
+            ],
+        ),
+        (
+            "repl",
+            &[
+                ("user", "Convert 72°F to Celsius."),
+                ("assistant", "72°F is about 22.2°C."),
+            ],
+        ),
+    ];
+    for (channel, messages) in conversations {
+        let id = db.create_conversation(channel, user_id, None).await?;
+        for (role, content) in messages.iter() {
+            db.add_conversation_message(id, role, content).await?;
+        }
+    }
+
+    let workspace = Workspace::new_with_db(user_id, Arc::clone(db));
+    let memories = [
+        (
+            crate::workspace::paths::MEMORY,
+            "# Memory\n\n\
+             - Works on the Atlas project; launch planned for the end of the month.\n\
+             - Prefers short, direct answers and bullet lists.\n\
+             - Priya leads performance testing; Marco owns the pricing page.\n",
+        ),
+        (
+            crate::workspace::paths::USER,
+            "# User\n\nName: Alex Doe\nTime zone: Europe/Lisbon\nRole: engineering lead\n",
+        ),
+        (
+            "projects/atlas.md",
+            "# Atlas launch\n\n\
+             ## Open items\n\
+             - Pricing page copy sign-off\n\
+             - Load test at 2x expected peak\n\
+             - Rollback runbook review\n\n\
+             ## Decisions\n\
+             - Ship behind a feature flag, ramp 10% / 50% / 100% over three days.\n",
+        ),
+        (
+            "notes/flaky-tests.md",
+            "# Flaky tests\n\n\
+             `checkout::retries_on_timeout`: mock clock advanced before the retry was \
+             scheduled. Fixed by pinning the clock in setup.\n",
+        ),
+    ];
+    for (path, content) in memories {
+        workspace.write(path, content).await?;
+    }
+
+    let now = Utc::now();
+    let jobs = [
+        (
+            "Compile launch checklist",
+            "Collect open items from the Atlas notes into a checklist.",
+            JobState::Completed,
+        ),
+        (
+            "Investigate flaky checkout test",
+            "Find why checkout::retries_on_timeout fails intermittently.",
+            JobState::Completed,
+        ),
+        (
+            "Weekly status summary",
+            "Summarize this week's progress on Atlas for the team channel.",
+            JobState::InProgress,
+        ),
+    ];
+    for (title, description, state) in jobs {
+        let mut job = JobContext::with_user(user_id, title, description);
+        job.category = Some("demo".to_string());
+        for step in [JobState::InProgress, JobState::Completed] {
+            if job.state == state {
+                break;
+            }
+            let id = job.job_id;
+            job.transition_to(step, None)
+                .map_err(|reason| crate::error::JobError::ContextError { id, reason })?;
+        }
+        db.save_job(&job).await?;
+    }
+
+    let sandbox_jobs = [
+        (
+            "Run the test suite for atlas-web",
+            "completed",
+            Some(true),
+            None,
+        ),
+        (
+            "Upgrade the HTTP client dependency",
+            "failed",
+            Some(false),
+            Some("2 tests failed after the upgrade"),
+        ),
+    ];
+    for (i, (task, status, success, failure)) in sandbox_jobs.into_iter().enumerate() {
+        let created_at = now - Duration::hours(3 * (i as i64 + 1));
+        db.save_sandbox_job(&SandboxJobRecord {
+            id: Uuid::new_v4(),
+            task: task.to_string(),
+            status: status.to_string(),
+            user_id: user_id.to_string(),
+            project_dir: "/workspace/atlas-web".to_string(),
+            success,
+            failure_reason: failure.map(str::to_string),
+            created_at,
+            started_at: Some(created_at + Duration::seconds(5)),
+            completed_at: Some(created_at + Duration::minutes(4)),
+        })
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Appends [`WATERMARK`] to a response.
+pub struct Watermark;
+
+#[async_trait]
+impl OutputProcessor for Watermark {
+    fn name(&self) -> &str {
+        WATERMARK_PROCESSOR
+    }
+
+    async fn process(&self, text: &str, _caps: &MessageCapabilities) -> Option<String> {
+        if text.trim_end().ends_with(WATERMARK) {
+            return None;
+        }
+        Some(format!("{}\n\n{}", text.trim_end(), WATERMARK))
+    }
+}
+
+/// The configured output pipeline with [`Watermark`] on for every channel,
+/// running last.
+pub fn watermarked_pipeline(mut enabled: HashMap<String, Vec<String>>) -> OutputPipeline {
+    enabled.entry(DEFAULT_CHANNEL_KEY.to_string()).or_default();
+    for list in enabled.values_mut() {
+        list.push(WATERMARK_PROCESSOR.to_string());
+    }
+    let mut pipeline = OutputPipeline::with_builtins(enabled);
+    pipeline.register(Arc::new(Watermark));
+    pipeline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_watermark_on_every_channel() {
+        let mut enabled = HashMap::new();
+        enabled.insert("telegram".to_string(), vec!["markdown_lint".to_string()]);
+        let pipeline = watermarked_pipeline(enabled);
+        let caps = MessageCapabilities::default();
+
+        for channel in ["telegram", "gateway"] {
+            let text = pipeline.run(channel, &caps, "Hello.".to_string()).await;
+            assert_eq!(text, format!("Hello.\n\n{}", WATERMARK));
+            // Running twice does not stack watermarks.
+            assert_eq!(pipeline.run(channel, &caps, text.clone()).await, text);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_demo_registers_no_network_tools() {
+        // Safety: test-only; potential races are acceptable in test contexts.
+        unsafe {
+            std::env::set_var("DATABASE_URL", "postgres://localhost:5432/ironclaw_test");
+        }
+        let mut config = Config::from_env().await.unwrap();
+        apply(&mut config);
+
+        // Mirrors the registration in main for `--demo`.
+        let tools = crate::tools::ToolRegistry::new();
+        tools.register_demo_tools();
+        if config.web_fetch.enabled {
+            tools.register_web_fetch_tool(config.web_fetch.clone(), None);
+        }
+        let mut names = tools.list().await;
+        names.sort();
+        assert_eq!(names, ["ask_user", "echo", "json", "time"]);
+    }
+
+    #[cfg(feature = "libsql")]
+    #[tokio::test]
+    async fn test_demo_store_is_seeded_and_removed() {
+        let store = DemoStore::open("default").await.unwrap();
+        let dir = store.dir.clone();
+
+        let conversations = store
+            .db
+            .list_conversations_with_preview("default", "gateway", 10)
+            .await
+            .unwrap();
+        assert_eq!(conversations.len(), 2);
+        let paths = store.db.list_all_paths("default", None).await.unwrap();
+        assert!(paths.iter().any(|p| p == "projects/atlas.md"));
+        assert_eq!(store.db.list_sandbox_jobs().await.unwrap().len(), 2);
+
+        drop(store);
+        assert!(!dir.exists());
+    }
+}
@@ -328,6 +328,9 @@ pub enum WorkspaceError {
 
     #[error("Heartbeat error: {reason}")]
     HeartbeatError { reason: String },
+
+    #[error("Memory archive error: {reason}")]
+    Archive { reason: String },
 }
 
 /// Orchestrator errors (internal API, container management).
@@ -375,6 +378,9 @@ pub enum WorkerError {
 
     #[error("Missing worker token (IRONCLAW_WORKER_TOKEN not set)")]
     MissingToken,
+
+    #[error("Worker protocol negotiation failed: {reason}")]
+    ProtocolMismatch { reason: String },
 }
 
 /// Hook-related errors.
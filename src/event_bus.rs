@@ -0,0 +1,322 @@
+//! Typed internal event bus.
+//!
+//! Subsystems publish lifecycle events on one bus instead of each keeping its
+//! own notification plumbing; consumers (SSE, outbound webhooks) subscribe to
+//! the topics they care about.
+//!
+//! ```text
+//!  agent loop ──► Turn        ┐
+//!  orchestrator ► Job         ├──► EventBus ──► gateway SSE (Job, lossy)
+//!  workspace ───► Memory      │              └─► outbound webhooks (all, reliable)
+//!  extensions ──► Extension   ┘
+//! ```
+//!
+//! Lifecycle hooks stay direct calls: they run before the action and can
+//! change or block it, which a notification cannot.
+//!
+//! Every subscriber has its own bounded queue and picks what happens when
+//! that queue is full: [`Backpressure::Lossy`] drops the event and counts it,
+//! [`Backpressure::Reliable`] makes the publisher wait for room. A slow
+//! lossy subscriber therefore never holds up anyone else, and a reliable one
+//! slows publishers down instead of buffering without bound.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::channels::web::types::SseEvent;
+
+/// Queue size used when a subscriber has no particular needs.
+pub const DEFAULT_SUBSCRIBER_CAPACITY: usize = 256;
+
+/// Event categories subscribers can filter on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Topic {
+    /// Chat turns starting and finishing.
+    Turn,
+    /// Sandbox job progress.
+    Job,
+    /// Workspace documents written or deleted.
+    Memory,
+    /// Extensions installed, activated or removed.
+    Extension,
+}
+
+impl Topic {
+    pub const ALL: [Topic; 4] = [Self::Turn, Self::Job, Self::Memory, Self::Extension];
+}
+
+/// How a turn ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnOutcome {
+    Completed,
+    Failed,
+    Interrupted,
+    AwaitingApproval,
+    AwaitingInput,
+}
+
+/// What happened to a workspace document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryChange {
+    Written,
+    Deleted,
+}
+
+/// What happened to an extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtensionChange {
+    Installed,
+    Activated,
+    Removed,
+}
+
+/// An event on the bus.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BusEvent {
+    TurnStarted {
+        user_id: String,
+        channel: String,
+        thread_id: Uuid,
+    },
+    TurnFinished {
+        user_id: String,
+        channel: String,
+        thread_id: Uuid,
+        outcome: TurnOutcome,
+    },
+    /// A job status update, in the shape the gateway streams it.
+    Job { job_id: Uuid, event: SseEvent },
+    Memory {
+        user_id: String,
+        path: String,
+        change: MemoryChange,
+    },
+    Extension {
+        name: String,
+        change: ExtensionChange,
+    },
+}
+
+impl BusEvent {
+    pub fn topic(&self) -> Topic {
+        match self {
+            Self::TurnStarted { .. } | Self::TurnFinished { .. } => Topic::Turn,
+            Self::Job { .. } => Topic::Job,
+            Self::Memory { .. } => Topic::Memory,
+            Self::Extension { .. } => Topic::Extension,
+        }
+    }
+
+    /// Dotted event name, as used for webhook event filters
+    /// (e.g. `turn.finished`, `memory.deleted`, `extension.activated`).
+    pub fn name(&self) -> String {
+        match self {
+            Self::TurnStarted { .. } => "turn.started".to_string(),
+            Self::TurnFinished { .. } => "turn.finished".to_string(),
+            Self::Job { .. } => "job.updated".to_string(),
+            Self::Memory { change, .. } => match change {
+                MemoryChange::Written => "memory.written".to_string(),
+                MemoryChange::Deleted => "memory.deleted".to_string(),
+            },
+            Self::Extension { change, .. } => format!(
+                "extension.{}",
+                match change {
+                    ExtensionChange::Installed => "installed",
+                    ExtensionChange::Activated => "activated",
+                    ExtensionChange::Removed => "removed",
+                }
+            ),
+        }
+    }
+}
+
+/// What a subscriber wants when its queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backpressure {
+    /// Drop the event and count it (see [`Subscription::dropped`]).
+    Lossy,
+    /// Make the publisher wait until there is room.
+    Reliable,
+}
+
+struct Subscriber {
+    topics: Vec<Topic>,
+    backpressure: Backpressure,
+    tx: mpsc::Sender<Arc<BusEvent>>,
+    dropped: Arc<AtomicU64>,
+}
+
+/// Receiving end of a subscription. Dropping it unsubscribes.
+pub struct Subscription {
+    rx: mpsc::Receiver<Arc<BusEvent>>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Subscription {
+    /// Next event, or `None` once the bus is gone.
+    pub async fn recv(&mut self) -> Option<Arc<BusEvent>> {
+        self.rx.recv().await
+    }
+
+    /// Events discarded because this (lossy) subscriber fell behind.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// The bus. Share it as `Arc<EventBus>`.
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribe to `topics` with a queue of `capacity` events.
+    pub fn subscribe(
+        &self,
+        topics: &[Topic],
+        capacity: usize,
+        backpressure: Backpressure,
+    ) -> Subscription {
+        let (tx, rx) = mpsc::channel(capacity.max(1));
+        let dropped = Arc::new(AtomicU64::new(0));
+        self.lock().push(Subscriber {
+            topics: topics.to_vec(),
+            backpressure,
+            tx,
+            dropped: Arc::clone(&dropped),
+        });
+        Subscription { rx, dropped }
+    }
+
+    /// Number of live subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        let mut subscribers = self.lock();
+        subscribers.retain(|s| !s.tx.is_closed());
+        subscribers.len()
+    }
+
+    /// Deliver `event` to every subscriber of its topic.
+    pub async fn publish(&self, event: BusEvent) {
+        let topic = event.topic();
+        let event = Arc::new(event);
+
+        let mut reliable = Vec::new();
+        {
+            let mut subscribers = self.lock();
+            subscribers.retain(|s| !s.tx.is_closed());
+            for sub in subscribers.iter().filter(|s| s.topics.contains(&topic)) {
+                match sub.backpressure {
+                    Backpressure::Reliable => reliable.push(sub.tx.clone()),
+                    Backpressure::Lossy => {
+                        if let Err(mpsc::error::TrySendError::Full(_)) =
+                            sub.tx.try_send(Arc::clone(&event))
+                            && sub.dropped.fetch_add(1, Ordering::Relaxed) == 0
+                        {
+                            tracing::warn!(
+                                event = %event.name(),
+                                "Event bus subscriber is falling behind; dropping events"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        // Waiting happens outside the lock so other publishers keep going.
+        for tx in reliable {
+            let _ = tx.send(Arc::clone(&event)).await;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Subscriber>> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn memory(path: &str) -> BusEvent {
+        BusEvent::Memory {
+            user_id: "alice".into(),
+            path: path.into(),
+            change: MemoryChange::Written,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_only_see_their_topics() {
+        let bus = EventBus::new();
+        let mut mem = bus.subscribe(&[Topic::Memory], 8, Backpressure::Lossy);
+        let mut ext = bus.subscribe(&[Topic::Extension], 8, Backpressure::Lossy);
+
+        bus.publish(memory("notes.md")).await;
+        bus.publish(BusEvent::Extension {
+            name: "slack".into(),
+            change: ExtensionChange::Activated,
+        })
+        .await;
+
+        assert_eq!(mem.recv().await.unwrap().name(), "memory.written");
+        assert_eq!(ext.recv().await.unwrap().name(), "extension.activated");
+        assert!(mem.rx.try_recv().is_err());
+
+        drop(ext);
+        assert_eq!(bus.subscriber_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_lossy_subscriber_drops_and_counts() {
+        let bus = EventBus::new();
+        let mut slow = bus.subscribe(&[Topic::Memory], 2, Backpressure::Lossy);
+
+        for i in 0..5 {
+            bus.publish(memory(&format!("{i}.md"))).await;
+        }
+
+        assert_eq!(slow.dropped(), 3);
+        let BusEvent::Memory { path, .. } = &*slow.recv().await.unwrap() else {
+            panic!("expected a memory event");
+        };
+        assert_eq!(path, "0.md");
+    }
+
+    #[tokio::test]
+    async fn test_reliable_subscriber_holds_publisher_back() {
+        let bus = Arc::new(EventBus::new());
+        let mut sub = bus.subscribe(&Topic::ALL, 1, Backpressure::Reliable);
+
+        bus.publish(memory("a.md")).await;
+        let publisher = tokio::spawn({
+            let bus = Arc::clone(&bus);
+            async move { bus.publish(memory("b.md")).await }
+        });
+
+        // The queue is full, so the second publish waits for the reader.
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!publisher.is_finished());
+
+        sub.recv().await.unwrap();
+        publisher.await.unwrap();
+        sub.recv().await.unwrap();
+        assert_eq!(sub.dropped(), 0);
+    }
+}
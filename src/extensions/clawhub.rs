@@ -1,17 +1,24 @@
 //! ClawHub registry client for discovering and installing extensions.
 //!
 //! ClawHub is the central registry for IronClaw/OpenClaw tools, channels,
-//! and plugins. This module provides the client for searching, fetching,
+//! plugins, and hook packs / automations (see [`crate::hooks::packs`]). This module provides the client for searching, fetching,
 //! and installing packages from the registry.
 
 use serde::{Deserialize, Serialize};
 
+use crate::extensions::publish::{PackageBundle, PackageSignature};
+
 /// ClawHub registry client.
 pub struct ClawHubClient {
     client: reqwest::Client,
     base_url: String,
+    /// API token, needed only for publishing.
+    token: Option<String>,
 }
 
+/// Default registry API endpoint.
+pub const DEFAULT_REGISTRY_URL: &str = "https://registry.clawhub.dev/api/v1";
+
 /// A package in the ClawHub registry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegistryPackage {
@@ -73,6 +80,19 @@ pub enum PackageType {
     Skill,
     /// MCP server configuration.
     Mcp,
+    /// Bundle of lifecycle hooks (JSON manifest).
+    #[serde(rename = "hook_pack")]
+    HookPack,
+    /// Bundle of routines, optionally with hooks (JSON manifest).
+    Automation,
+}
+
+impl PackageType {
+    /// Whether the package is a JSON hook/automation manifest rather than
+    /// a WASM binary.
+    pub fn is_manifest(&self) -> bool {
+        matches!(self, Self::HookPack | Self::Automation)
+    }
 }
 
 impl std::fmt::Display for PackageType {
@@ -83,6 +103,8 @@ impl std::fmt::Display for PackageType {
             Self::Plugin => write!(f, "plugin"),
             Self::Skill => write!(f, "skill"),
             Self::Mcp => write!(f, "mcp"),
+            Self::HookPack => write!(f, "hook_pack"),
+            Self::Automation => write!(f, "automation"),
         }
     }
 }
@@ -128,20 +150,42 @@ pub struct VersionInfo {
 impl ClawHubClient {
     /// Create a new ClawHub client with the default registry URL.
     pub fn new() -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            base_url: "https://registry.clawhub.dev/api/v1".to_string(),
-        }
+        Self::with_url(DEFAULT_REGISTRY_URL)
     }
 
     /// Create with a custom registry URL.
     pub fn with_url(url: impl Into<String>) -> Self {
         Self {
-            client: reqwest::Client::new(),
-            base_url: url.into(),
+            client: crate::outbound::client(),
+            base_url: url.into().trim_end_matches('/').to_string(),
+            token: None,
+        }
+    }
+
+    /// Create from `CLAWHUB_URL` / `CLAWHUB_TOKEN`, falling back to the
+    /// default registry without a token.
+    pub fn from_env() -> Self {
+        let client = match std::env::var("CLAWHUB_URL") {
+            Ok(url) if !url.is_empty() => Self::with_url(url),
+            _ => Self::new(),
+        };
+        match std::env::var("CLAWHUB_TOKEN") {
+            Ok(token) if !token.is_empty() => client.with_token(token),
+            _ => client,
         }
     }
 
+    /// Set the API token used for publishing.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Registry API base URL.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
     /// Search for packages in the registry.
     pub async fn search(
         &self,
@@ -214,7 +258,7 @@ impl ClawHubClient {
             .map_err(|e| format!("Failed to parse package info: {}", e))
     }
 
-    /// Download a package binary (WASM).
+    /// Download a package (WASM binary or hook/automation manifest).
     pub async fn download(
         &self,
         name: &str,
@@ -284,6 +328,7 @@ impl ClawHubClient {
         Ok(DownloadedPackage {
             name: name.to_string(),
             version: version_info.version,
+            sha256: version_info.sha256,
             data: bytes.to_vec(),
             package_type: detail.latest.package_type,
             capabilities: detail.capabilities,
@@ -315,6 +360,81 @@ impl ClawHubClient {
             .map_err(|e| format!("Failed to parse featured packages: {}", e))
     }
 
+    /// Upload a signed package and return the registry entry it created.
+    ///
+    /// Sends a multipart form with the manifest, the signature (which
+    /// carries the publisher key and file hashes), the WASM binary and the
+    /// capabilities file, authenticated with the client's token.
+    pub async fn publish(
+        &self,
+        bundle: &PackageBundle,
+        signature: &PackageSignature,
+    ) -> Result<RegistryPackage, String> {
+        let token = self
+            .token
+            .as_deref()
+            .ok_or("Publishing requires an API token; set CLAWHUB_TOKEN")?;
+        let url = format!("{}/packages", self.base_url);
+
+        let manifest = serde_json::to_string(&bundle.manifest)
+            .map_err(|e| format!("Failed to encode manifest: {}", e))?;
+        let signature = serde_json::to_string(signature)
+            .map_err(|e| format!("Failed to encode signature: {}", e))?;
+        let wasm = reqwest::multipart::Part::bytes(bundle.wasm.clone())
+            .file_name(bundle.wasm_file.clone())
+            .mime_str("application/wasm")
+            .map_err(|e| format!("Failed to build upload: {}", e))?;
+        let mut form = reqwest::multipart::Form::new()
+            .text("manifest", manifest)
+            .text("signature", signature)
+            .part("wasm", wasm);
+        if let Some(ref capabilities) = bundle.capabilities {
+            let part = reqwest::multipart::Part::bytes(capabilities.clone())
+                .file_name("capabilities.json")
+                .mime_str("application/json")
+                .map_err(|e| format!("Failed to build upload: {}", e))?;
+            form = form.part("capabilities", part);
+        }
+
+        let response = self
+            .client
+            .post(&url)
+            .header(
+                "User-Agent",
+                format!("ironclaw/{}", env!("CARGO_PKG_VERSION")),
+            )
+            .bearer_auth(token)
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| format!("Publish failed: {}", e))?;
+
+        match response.status() {
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                return Err(format!(
+                    "Registry rejected the token for publishing '{}'",
+                    bundle.manifest.name
+                ));
+            }
+            reqwest::StatusCode::CONFLICT => {
+                return Err(format!(
+                    "{} {} is already published; bump the version",
+                    bundle.manifest.name, bundle.manifest.version
+                ));
+            }
+            status if !status.is_success() => {
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(format!("Registry error ({}): {}", status, error_text));
+            }
+            _ => {}
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse published package: {}", e))
+    }
+
     /// Check if the registry is reachable.
     pub async fn health_check(&self) -> bool {
         let url = format!("{}/health", self.base_url);
@@ -341,7 +461,9 @@ pub struct DownloadedPackage {
     pub name: String,
     /// Version string.
     pub version: String,
-    /// Binary data (WASM component).
+    /// Hash the download was verified against, if the registry published one.
+    pub sha256: Option<String>,
+    /// Binary data (WASM component or JSON manifest).
     pub data: Vec<u8>,
     /// Package type.
     pub package_type: PackageType,
@@ -357,6 +479,12 @@ impl DownloadedPackage {
             PackageType::Channel => "channels",
             PackageType::Skill => "skills",
             PackageType::Mcp => "mcp",
+            PackageType::HookPack | PackageType::Automation => "hooks",
+        };
+        let extension = if self.package_type.is_manifest() {
+            "json"
+        } else {
+            "wasm"
         };
 
         let base = dirs::home_dir()
@@ -364,7 +492,7 @@ impl DownloadedPackage {
             .join(".ironclaw")
             .join(dir);
 
-        base.join(format!("{}.wasm", self.name))
+        base.join(format!("{}.{}", self.name, extension))
     }
 }
 
@@ -377,6 +505,13 @@ mod tests {
         assert_eq!(PackageType::Tool.to_string(), "tool");
         assert_eq!(PackageType::Channel.to_string(), "channel");
         assert_eq!(PackageType::Plugin.to_string(), "plugin");
+        assert_eq!(PackageType::HookPack.to_string(), "hook_pack");
+        assert_eq!(
+            serde_json::to_string(&PackageType::HookPack).unwrap(),
+            "\"hook_pack\""
+        );
+        assert!(PackageType::Automation.is_manifest());
+        assert!(!PackageType::Tool.is_manifest());
     }
 
     #[test]
@@ -410,6 +545,7 @@ mod tests {
         let pkg = DownloadedPackage {
             name: "test-tool".to_string(),
             version: "1.0.0".to_string(),
+            sha256: None,
             data: Vec::new(),
             package_type: PackageType::Tool,
             capabilities: None,
@@ -418,6 +554,13 @@ mod tests {
         let path = pkg.install_path();
         assert!(path.to_string_lossy().contains("tools"));
         assert!(path.to_string_lossy().contains("test-tool.wasm"));
+
+        let pack = DownloadedPackage {
+            name: "pii-scrubber".to_string(),
+            package_type: PackageType::HookPack,
+            ..pkg
+        };
+        assert!(pack.install_path().ends_with("hooks/pii-scrubber.json"));
     }
 
     #[test]
@@ -0,0 +1,240 @@
+//! Capability consent for WASM tools at activation time.
+//!
+//! Before a WASM tool is activated for the first time, the capabilities its
+//! `*.capabilities.json` requests (HTTP endpoints, secrets, workspace
+//! prefixes, tool aliases) are summarized for the user, who grants or denies
+//! them, much like app permissions on a phone.
+//!
+//! ```text
+//! activate("slack")
+//!   -> requested ⊆ <name>.accepted.json     -> Granted, load the tool
+//!   -> requested == <name>.denied.json      -> Denied, refuse without asking
+//!   -> otherwise                            -> Required, show the summary
+//! ```
+//!
+//! Grants reuse the accepted record the loader already enforces, so a
+//! granted set is also what the loader checks against. Installs record an
+//! empty accepted set, so a tool that was never granted cannot be loaded
+//! with capabilities at startup either. Denials are stored
+//! next to it; a tool whose request changes after a denial is asked about
+//! again.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::tools::wasm::{
+    CapabilitySet, PermissionDiff, accepted_path, load_accepted, record_accepted,
+};
+
+/// Capabilities the user refused for a tool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeniedCapabilities {
+    pub capabilities: CapabilitySet,
+    pub denied_at: DateTime<Utc>,
+}
+
+/// What the user is asked to approve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsentRequest {
+    pub name: String,
+    /// Everything the tool asks for.
+    pub requested: CapabilitySet,
+    /// What it asks for beyond an earlier grant (everything, on first use).
+    pub diff: PermissionDiff,
+}
+
+impl ConsentRequest {
+    /// One line per newly requested capability, e.g. `http: api.slack.com`.
+    pub fn summary(&self) -> Vec<String> {
+        self.diff
+            .added
+            .sections()
+            .into_iter()
+            .flat_map(|(label, entries)| entries.iter().map(move |e| format!("{}: {}", label, e)))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for ConsentRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' requests:", self.name)?;
+        for line in self.summary() {
+            write!(f, "\n  {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of checking a tool's requested capabilities against past decisions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsentStatus {
+    /// Nothing beyond an earlier grant is requested.
+    Granted,
+    /// The user denied exactly this request before.
+    Denied,
+    /// The user has to decide.
+    Required(Box<ConsentRequest>),
+}
+
+/// Per-extension consent records kept in the WASM tools directory.
+#[derive(Debug, Clone)]
+pub struct CapabilityConsent {
+    tools_dir: PathBuf,
+}
+
+impl CapabilityConsent {
+    pub fn new(tools_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            tools_dir: tools_dir.into(),
+        }
+    }
+
+    fn denied_path(&self, name: &str) -> PathBuf {
+        self.tools_dir.join(format!("{}.denied.json", name))
+    }
+
+    /// Decide whether `requested` needs the user's consent.
+    pub async fn check(
+        &self,
+        name: &str,
+        requested: &CapabilitySet,
+    ) -> std::io::Result<ConsentStatus> {
+        let accepted = load_accepted(&accepted_path(&self.tools_dir, name))
+            .await?
+            .map(|a| a.capabilities)
+            .unwrap_or_default();
+        let diff = PermissionDiff::between(&accepted, requested);
+        if !diff.broadens() {
+            return Ok(ConsentStatus::Granted);
+        }
+        if let Some(denied) = load_denied(&self.denied_path(name)).await?
+            && denied.capabilities == *requested
+        {
+            return Ok(ConsentStatus::Denied);
+        }
+        Ok(ConsentStatus::Required(Box::new(ConsentRequest {
+            name: name.to_string(),
+            requested: requested.clone(),
+            diff,
+        })))
+    }
+
+    /// Grant `requested`, clearing any earlier denial.
+    pub async fn grant(&self, name: &str, requested: CapabilitySet) -> std::io::Result<()> {
+        record_accepted(&accepted_path(&self.tools_dir, name), requested).await?;
+        remove_if_exists(&self.denied_path(name)).await
+    }
+
+    /// Record a fresh install: nothing is accepted until the user grants
+    /// the tool's request, so the loader holds it back until then. A tool
+    /// that already has a record (an update) keeps it.
+    pub async fn record_install(&self, name: &str) -> std::io::Result<()> {
+        let path = accepted_path(&self.tools_dir, name);
+        if load_accepted(&path).await?.is_none() {
+            record_accepted(&path, CapabilitySet::default()).await?;
+        }
+        Ok(())
+    }
+
+    /// Deny `requested`. The accepted record, if any, is left alone so a
+    /// previously granted set keeps working for the loader.
+    pub async fn deny(&self, name: &str, requested: CapabilitySet) -> std::io::Result<()> {
+        let record = DeniedCapabilities {
+            capabilities: requested,
+            denied_at: Utc::now(),
+        };
+        let json = serde_json::to_vec_pretty(&record)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        tokio::fs::write(self.denied_path(name), json).await
+    }
+
+    /// Drop every consent record for a removed tool.
+    pub async fn forget(&self, name: &str) -> std::io::Result<()> {
+        remove_if_exists(&accepted_path(&self.tools_dir, name)).await?;
+        remove_if_exists(&self.denied_path(name)).await
+    }
+}
+
+async fn load_denied(path: &Path) -> std::io::Result<Option<DeniedCapabilities>> {
+    match tokio::fs::read(path).await {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+async fn remove_if_exists(path: &Path) -> std::io::Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::wasm::CapabilitiesFile;
+
+    fn caps(json: &str) -> CapabilitySet {
+        CapabilitySet::from_file(&CapabilitiesFile::from_json(json).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_consent_reprompts_only_when_request_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let consent = CapabilityConsent::new(dir.path());
+        let v1 = caps(
+            r#"{"http": {"allowlist": [{"host": "slack.com"}]},
+                "secrets": {"allowed_names": ["slack_bot_token"]}}"#,
+        );
+
+        // Nothing requested: nothing to ask.
+        assert_eq!(
+            consent
+                .check("slack", &CapabilitySet::default())
+                .await
+                .unwrap(),
+            ConsentStatus::Granted
+        );
+
+        let ConsentStatus::Required(request) = consent.check("slack", &v1).await.unwrap() else {
+            panic!("first activation should ask");
+        };
+        assert_eq!(
+            request.summary(),
+            vec!["http: slack.com", "secret: slack_bot_token"]
+        );
+
+        consent.deny("slack", v1.clone()).await.unwrap();
+        assert_eq!(
+            consent.check("slack", &v1).await.unwrap(),
+            ConsentStatus::Denied
+        );
+
+        consent.grant("slack", v1.clone()).await.unwrap();
+        assert_eq!(
+            consent.check("slack", &v1).await.unwrap(),
+            ConsentStatus::Granted
+        );
+
+        // An update asking for a new domain only asks about that domain.
+        let v2 = caps(
+            r#"{"http": {"allowlist": [{"host": "slack.com"}, {"host": "files.slack.com"}]},
+                "secrets": {"allowed_names": ["slack_bot_token"]}}"#,
+        );
+        let ConsentStatus::Required(request) = consent.check("slack", &v2).await.unwrap() else {
+            panic!("broadened request should ask again");
+        };
+        assert_eq!(request.summary(), vec!["http: files.slack.com"]);
+
+        consent.forget("slack").await.unwrap();
+        assert!(matches!(
+            consent.check("slack", &v1).await.unwrap(),
+            ConsentStatus::Required(_)
+        ));
+    }
+}
@@ -20,11 +20,11 @@ pub struct OnlineDiscovery {
 
 impl OnlineDiscovery {
     pub fn new() -> Self {
-        let http_client = reqwest::Client::builder()
+        let http_client = crate::outbound::client_builder()
             .timeout(Duration::from_secs(10))
             .user_agent("IronClaw/1.0")
             .build()
-            .unwrap_or_else(|_| reqwest::Client::new());
+            .unwrap_or_else(|_| crate::outbound::client());
 
         Self { http_client }
     }
@@ -105,6 +105,7 @@ impl OnlineDiscovery {
                                 url: url.to_string(),
                             },
                             auth_hint: AuthHint::Dcr,
+                            dependencies: Vec::new(),
                         })
                     } else {
                         None
@@ -179,6 +180,7 @@ impl OnlineDiscovery {
                     keywords: item.topics,
                     source: ExtensionSource::Discovered { url },
                     auth_hint: AuthHint::Dcr,
+                    dependencies: Vec::new(),
                 })
             })
             .collect()
@@ -246,6 +248,7 @@ fn extract_url(source: &ExtensionSource) -> String {
         ExtensionSource::Discovered { url } => url.clone(),
         ExtensionSource::WasmDownload { wasm_url, .. } => wasm_url.clone(),
         ExtensionSource::WasmBuildable { repo_url, .. } => repo_url.clone(),
+        ExtensionSource::LocalPath { wasm_path, .. } => wasm_path.clone(),
     }
 }
 
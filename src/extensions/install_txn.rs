@@ -0,0 +1,238 @@
+//! Install transactions with rollback.
+//!
+//! An [`InstallTransaction`] journals every side effect of an install
+//! (files written, config files rewritten, plugins and tools registered) so
+//! that a failure at any step can undo the partial state in reverse order.
+//!
+//! ```text
+//!  download → verify → store binaries → write config → register → activate
+//!                 ╲         ╲               ╲             ╲          ╲
+//!                  └─────────┴───── any error: rollback() ┴──────────┘
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use crate::extensions::plugin_manager::PluginManager;
+use crate::tools::ToolRegistry;
+
+/// A single undo step recorded by a transaction.
+#[derive(Debug, Clone)]
+pub enum RollbackAction {
+    /// A file was created where none existed; delete it.
+    RemoveFile(PathBuf),
+    /// A file was overwritten; restore its previous contents.
+    RestoreFile { path: PathBuf, previous: Vec<u8> },
+    /// A plugin was registered with the plugin manager.
+    UnregisterPlugin(String),
+    /// A tool was registered with the tool registry.
+    UnregisterTool(String),
+}
+
+/// Outcome of rolling back a transaction.
+#[derive(Debug, Default, Clone)]
+pub struct RollbackReport {
+    /// Number of undo steps applied successfully.
+    pub undone: usize,
+    /// Undo steps that failed (description of the step and error).
+    pub failures: Vec<String>,
+}
+
+/// Journal of side effects for a single install.
+#[derive(Debug)]
+pub struct InstallTransaction {
+    name: String,
+    journal: Vec<RollbackAction>,
+}
+
+impl InstallTransaction {
+    /// Begin a transaction for the named extension or plugin.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            journal: Vec::new(),
+        }
+    }
+
+    /// Name of the extension being installed.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Number of journaled steps.
+    pub fn len(&self) -> usize {
+        self.journal.len()
+    }
+
+    /// Whether nothing has been journaled yet.
+    pub fn is_empty(&self) -> bool {
+        self.journal.is_empty()
+    }
+
+    /// Record an undo step for a side effect performed outside the transaction.
+    pub fn record(&mut self, action: RollbackAction) {
+        self.journal.push(action);
+    }
+
+    /// Write a file, journaling how to undo the write.
+    ///
+    /// Data is staged to a sibling temp file and renamed into place so a
+    /// crash mid-write never leaves a truncated binary behind.
+    pub async fn write_file(&mut self, path: &Path, contents: &[u8]) -> std::io::Result<()> {
+        let previous = match tokio::fs::read(path).await {
+            Ok(bytes) => Some(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let mut staged = path.as_os_str().to_owned();
+        staged.push(".installing");
+        let staged = PathBuf::from(staged);
+        tokio::fs::write(&staged, contents).await?;
+        if let Err(e) = tokio::fs::rename(&staged, path).await {
+            let _ = tokio::fs::remove_file(&staged).await;
+            return Err(e);
+        }
+
+        self.journal.push(match previous {
+            Some(previous) => RollbackAction::RestoreFile {
+                path: path.to_path_buf(),
+                previous,
+            },
+            None => RollbackAction::RemoveFile(path.to_path_buf()),
+        });
+        Ok(())
+    }
+
+    /// Commit the transaction, discarding the undo journal.
+    pub fn commit(self) {
+        tracing::debug!(
+            "Committed install of '{}' ({} steps)",
+            self.name,
+            self.journal.len()
+        );
+    }
+
+    /// Undo every journaled step in reverse order.
+    ///
+    /// Rollback is best-effort: a failing step is reported and the remaining
+    /// steps still run.
+    pub async fn rollback(
+        self,
+        plugins: Option<&PluginManager>,
+        tools: Option<&ToolRegistry>,
+    ) -> RollbackReport {
+        let mut report = RollbackReport::default();
+        for action in self.journal.into_iter().rev() {
+            let result: Result<(), String> = match &action {
+                RollbackAction::RemoveFile(path) => match tokio::fs::remove_file(path).await {
+                    Ok(()) => Ok(()),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                    Err(e) => Err(e.to_string()),
+                },
+                RollbackAction::RestoreFile { path, previous } => tokio::fs::write(path, previous)
+                    .await
+                    .map_err(|e| e.to_string()),
+                RollbackAction::UnregisterPlugin(name) => match plugins {
+                    Some(mgr) => mgr
+                        .unregister(name)
+                        .await
+                        .map(|_| ())
+                        .map_err(|e| e.to_string()),
+                    None => Err("no plugin manager available".to_string()),
+                },
+                RollbackAction::UnregisterTool(name) => match tools {
+                    Some(registry) => {
+                        registry.unregister(name).await;
+                        Ok(())
+                    }
+                    None => Err("no tool registry available".to_string()),
+                },
+            };
+            match result {
+                Ok(()) => report.undone += 1,
+                Err(e) => report.failures.push(format!("{:?}: {}", action, e)),
+            }
+        }
+
+        if report.failures.is_empty() {
+            tracing::info!(
+                "Rolled back install of '{}' ({} steps)",
+                self.name,
+                report.undone
+            );
+        } else {
+            tracing::warn!(
+                "Rollback of '{}' incomplete: {} undone, {} failed",
+                self.name,
+                report.undone,
+                report.failures.len()
+            );
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rollback_removes_new_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tool.wasm");
+
+        let mut txn = InstallTransaction::new("tool");
+        txn.write_file(&path, b"\0asm").await.unwrap();
+        assert!(path.exists());
+
+        let report = txn.rollback(None, None).await;
+        assert_eq!(report.undone, 1);
+        assert!(report.failures.is_empty());
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_rollback_restores_overwritten_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plugins.json");
+        std::fs::write(&path, b"old").unwrap();
+
+        let mut txn = InstallTransaction::new("cfg");
+        txn.write_file(&path, b"new").await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"new");
+
+        txn.rollback(None, None).await;
+        assert_eq!(std::fs::read(&path).unwrap(), b"old");
+    }
+
+    #[tokio::test]
+    async fn test_commit_keeps_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("tool.wasm");
+
+        let mut txn = InstallTransaction::new("tool");
+        txn.write_file(&path, b"\0asm").await.unwrap();
+        assert_eq!(txn.len(), 1);
+        txn.commit();
+        assert!(path.exists());
+        assert!(
+            !dir.path()
+                .join("nested")
+                .join("tool.wasm.installing")
+                .exists()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rollback_reports_missing_targets() {
+        let mut txn = InstallTransaction::new("p");
+        txn.record(RollbackAction::UnregisterPlugin("p".to_string()));
+        let report = txn.rollback(None, None).await;
+        assert_eq!(report.undone, 0);
+        assert_eq!(report.failures.len(), 1);
+    }
+}
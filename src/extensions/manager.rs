@@ -5,27 +5,37 @@
 //! list, remove) flow through here.
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use tokio::sync::RwLock;
 
+use crate::event_bus::{BusEvent, EventBus, ExtensionChange};
+use crate::extensions::consent::{CapabilityConsent, ConsentRequest, ConsentStatus};
 use crate::extensions::discovery::OnlineDiscovery;
 use crate::extensions::registry::ExtensionRegistry;
 use crate::extensions::{
     ActivateResult, AuthResult, ExtensionError, ExtensionKind, ExtensionSource, InstallResult,
-    InstalledExtension, RegistryEntry, ResultSource, SearchResult,
+    InstalledExtension, PlannedInstall, RegistryEntry, ResultSource, SearchResult,
 };
 use crate::secrets::{CreateSecretParams, SecretsStore};
 use crate::tools::ToolRegistry;
 use crate::tools::mcp::McpClient;
 use crate::tools::mcp::auth::{
-    PkceChallenge, authorize_mcp_server, build_authorization_url, discover_full_oauth_metadata,
-    find_available_port, is_authenticated, register_client,
+    PkceChallenge, authorize_mcp_server, build_authorization_url, complete_device_authorization,
+    discover_full_oauth_metadata, find_available_port, is_authenticated, is_headless,
+    register_client, start_device_authorization,
 };
 use crate::tools::mcp::config::McpServerConfig;
 use crate::tools::mcp::session::McpSessionManager;
-use crate::tools::wasm::{WasmToolLoader, WasmToolRuntime, discover_tools};
+use crate::tools::wasm::{
+    Capabilities, CapabilitiesFile, CapabilitySet, DryRunReport, ScheduleSync, WasmToolLoader,
+    WasmToolRuntime, WasmToolWrapper, compute_binary_hash, discover_tools, sync_tool_schedule,
+    verify_binary_integrity,
+};
+
+/// Largest WASM binary accepted for install (50 MB), to prevent disk-fill DoS.
+const MAX_WASM_SIZE: usize = 50 * 1024 * 1024;
 
 /// Pending OAuth authorization state.
 struct PendingAuth {
@@ -50,6 +60,8 @@ pub struct ExtensionManager {
     wasm_tool_runtime: Option<Arc<WasmToolRuntime>>,
     wasm_tools_dir: PathBuf,
     wasm_channels_dir: PathBuf,
+    /// Granted/denied capability sets for WASM tools.
+    consent: CapabilityConsent,
 
     // Shared
     secrets: Arc<dyn SecretsStore + Send + Sync>,
@@ -60,6 +72,8 @@ pub struct ExtensionManager {
     user_id: String,
     /// Optional database store for DB-backed MCP config.
     store: Option<Arc<dyn crate::db::Database>>,
+    /// Bus that installs, activations and removals are published on.
+    events: Option<Arc<EventBus>>,
 }
 
 impl ExtensionManager {
@@ -81,6 +95,7 @@ impl ExtensionManager {
             mcp_session_manager,
             mcp_clients: RwLock::new(HashMap::new()),
             wasm_tool_runtime,
+            consent: CapabilityConsent::new(&wasm_tools_dir),
             wasm_tools_dir,
             wasm_channels_dir,
             secrets,
@@ -89,6 +104,24 @@ impl ExtensionManager {
             _tunnel_url: tunnel_url,
             user_id,
             store,
+            events: None,
+        }
+    }
+
+    /// Publish installs, activations and removals on `bus`.
+    pub fn with_event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.events = Some(bus);
+        self
+    }
+
+    async fn publish(&self, name: &str, change: ExtensionChange) {
+        if let Some(ref events) = self.events {
+            events
+                .publish(BusEvent::Extension {
+                    name: name.to_string(),
+                    change,
+                })
+                .await;
         }
     }
 
@@ -128,10 +161,40 @@ impl ExtensionManager {
         name: &str,
         url: Option<&str>,
         kind_hint: Option<ExtensionKind>,
+    ) -> Result<InstallResult, ExtensionError> {
+        let result = self.install_inner(name, url, kind_hint).await?;
+        if result.kind == ExtensionKind::WasmTool {
+            self.claim_secrets(&result.name).await;
+            self.sync_schedule(&result.name).await;
+        }
+        self.publish(&result.name, ExtensionChange::Installed).await;
+        Ok(result)
+    }
+
+    async fn install_inner(
+        &self,
+        name: &str,
+        url: Option<&str>,
+        kind_hint: Option<ExtensionKind>,
     ) -> Result<InstallResult, ExtensionError> {
         // If we have a registry entry, use it
         if let Some(entry) = self.registry.get(name).await {
-            return self.install_from_entry(&entry).await;
+            if entry.dependencies.is_empty() {
+                return self.install_from_entry(&entry).await;
+            }
+            return self.install_with_dependencies(&entry).await;
+        }
+
+        // A local path installs a WASM tool straight from disk
+        if let Some(path) = url.and_then(local_path_from_url) {
+            if matches!(kind_hint, Some(kind) if kind != ExtensionKind::WasmTool) {
+                return Err(ExtensionError::InstallFailed(
+                    "Only WASM tools can be installed from a local path".to_string(),
+                ));
+            }
+            return self
+                .install_wasm_tool_from_path(name, &path, None, None)
+                .await;
         }
 
         // If a URL was provided, determine kind and install
@@ -179,11 +242,13 @@ impl ExtensionManager {
     pub async fn activate(&self, name: &str) -> Result<ActivateResult, ExtensionError> {
         let kind = self.determine_installed_kind(name).await?;
 
-        match kind {
-            ExtensionKind::McpServer => self.activate_mcp(name).await,
-            ExtensionKind::WasmTool => self.activate_wasm_tool(name).await,
-            ExtensionKind::WasmChannel => Err(ExtensionError::ChannelNeedsRestart),
-        }
+        let result = match kind {
+            ExtensionKind::McpServer => self.activate_mcp(name).await?,
+            ExtensionKind::WasmTool => self.activate_wasm_tool(name).await?,
+            ExtensionKind::WasmChannel => return Err(ExtensionError::ChannelNeedsRestart),
+        };
+        self.publish(name, ExtensionChange::Activated).await;
+        Ok(result)
     }
 
     /// List all installed extensions with their status.
@@ -287,7 +352,16 @@ impl ExtensionManager {
 
     /// Remove an installed extension.
     pub async fn remove(&self, name: &str) -> Result<String, ExtensionError> {
+        let message = self.remove_inner(name).await?;
+        self.publish(name, ExtensionChange::Removed).await;
+        Ok(message)
+    }
+
+    async fn remove_inner(&self, name: &str) -> Result<String, ExtensionError> {
         let kind = self.determine_installed_kind(name).await?;
+        if let Err(e) = self.secrets.revoke_grants(&self.user_id, name).await {
+            tracing::warn!("Failed to revoke secret grants for '{}': {}", name, e);
+        }
 
         match kind {
             ExtensionKind::McpServer => {
@@ -336,6 +410,8 @@ impl ExtensionManager {
                 if cap_path.exists() {
                     let _ = tokio::fs::remove_file(&cap_path).await;
                 }
+                let _ = self.consent.forget(name).await;
+                self.sync_schedule(name).await;
 
                 Ok(format!("Removed WASM tool '{}'", name))
             }
@@ -398,6 +474,68 @@ impl ExtensionManager {
 
     // ── Private helpers ──────────────────────────────────────────────────
 
+    /// Install and activate an entry's dependencies in order, then install
+    /// the entry itself. A dependency that is already installed is left as
+    /// it is; one that cannot be activated yet (e.g. it needs auth) is
+    /// reported in the plan rather than failing the install.
+    async fn install_with_dependencies(
+        &self,
+        entry: &RegistryEntry,
+    ) -> Result<InstallResult, ExtensionError> {
+        let order = self.registry.install_order(&entry.name).await?;
+        let mut plan = Vec::with_capacity(order.len());
+
+        for dep in order.iter().filter(|e| e.name != entry.name) {
+            let already_installed = self.determine_installed_kind(&dep.name).await.is_ok();
+            if !already_installed {
+                self.install_from_entry(dep).await.map_err(|e| {
+                    ExtensionError::InstallFailed(format!(
+                        "dependency '{}' of '{}': {}",
+                        dep.name, entry.name, e
+                    ))
+                })?;
+            }
+            let (activated, error) = match self.activate(&dep.name).await {
+                Ok(_) => (true, None),
+                Err(e) => (false, Some(e.to_string())),
+            };
+            plan.push(PlannedInstall {
+                name: dep.name.clone(),
+                kind: dep.kind,
+                already_installed,
+                activated,
+                error,
+            });
+        }
+
+        let mut result = self.install_from_entry(entry).await?;
+        let deps = plan
+            .iter()
+            .map(|step| {
+                let state = if step.already_installed {
+                    "already installed"
+                } else {
+                    "installed"
+                };
+                match step.error {
+                    Some(ref e) => format!("{} ({}, not active: {})", step.name, state, e),
+                    None => format!("{} ({}, active)", step.name, state),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        result.message = format!("{} Dependencies: {}.", result.message, deps);
+        plan.push(PlannedInstall {
+            name: entry.name.clone(),
+            kind: entry.kind,
+            already_installed: false,
+            activated: false,
+            error: None,
+        });
+        result.plan = plan;
+        Ok(result)
+    }
+
     async fn install_from_entry(
         &self,
         entry: &RegistryEntry,
@@ -419,8 +557,21 @@ impl ExtensionManager {
                 ExtensionSource::WasmDownload { wasm_url, .. } => {
                     self.install_wasm_tool_from_url(&entry.name, wasm_url).await
                 }
+                ExtensionSource::LocalPath {
+                    wasm_path,
+                    capabilities_path,
+                    blake3,
+                } => {
+                    self.install_wasm_tool_from_path(
+                        &entry.name,
+                        Path::new(wasm_path),
+                        capabilities_path.as_deref().map(Path::new),
+                        blake3.as_deref(),
+                    )
+                    .await
+                }
                 _ => Err(ExtensionError::InstallFailed(
-                    "WASM tool entry has no download URL".to_string(),
+                    "WASM tool entry has no download URL or local path".to_string(),
                 )),
             },
             ExtensionKind::WasmChannel => Err(ExtensionError::InstallFailed(
@@ -457,6 +608,7 @@ impl ExtensionManager {
                 "MCP server '{}' installed. Run auth next to authenticate.",
                 name
             ),
+            plan: Vec::new(),
         })
     }
 
@@ -472,10 +624,7 @@ impl ExtensionManager {
             ));
         }
 
-        // 50 MB cap to prevent disk-fill DoS
-        const MAX_WASM_SIZE: usize = 50 * 1024 * 1024;
-
-        let client = reqwest::Client::builder()
+        let client = crate::outbound::client_builder()
             .timeout(std::time::Duration::from_secs(60))
             .build()
             .map_err(|e| ExtensionError::DownloadFailed(e.to_string()))?;
@@ -534,6 +683,11 @@ impl ExtensionManager {
             .await
             .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
 
+        self.consent
+            .record_install(name)
+            .await
+            .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
+
         tracing::info!(
             "Installed WASM tool '{}' ({} bytes) from {} to {}",
             name,
@@ -546,6 +700,165 @@ impl ExtensionManager {
             name: name.to_string(),
             kind: ExtensionKind::WasmTool,
             message: format!("WASM tool '{}' installed. Run activate to load it.", name),
+            plan: Vec::new(),
+        })
+    }
+
+    /// Install a WASM tool from files on disk, without any network access.
+    ///
+    /// `wasm_path` is the `.wasm` file or a directory holding `<name>.wasm`.
+    /// Without an explicit `capabilities_path`, a `<stem>.capabilities.json`
+    /// next to the binary is picked up if present. When `expected_blake3` is
+    /// given the binary must match it; either way the copy in the tools
+    /// directory is re-hashed before the install is reported.
+    async fn install_wasm_tool_from_path(
+        &self,
+        name: &str,
+        wasm_path: &Path,
+        capabilities_path: Option<&Path>,
+        expected_blake3: Option<&str>,
+    ) -> Result<InstallResult, ExtensionError> {
+        let source = if wasm_path.is_dir() {
+            wasm_path.join(format!("{}.wasm", name))
+        } else {
+            wasm_path.to_path_buf()
+        };
+        if !source.is_file() {
+            return Err(ExtensionError::InstallFailed(format!(
+                "No WASM binary at {}",
+                source.display()
+            )));
+        }
+
+        let size = tokio::fs::metadata(&source)
+            .await
+            .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?
+            .len();
+        if size as usize > MAX_WASM_SIZE {
+            return Err(ExtensionError::InstallFailed(format!(
+                "WASM binary too large ({} bytes, max {} bytes)",
+                size, MAX_WASM_SIZE
+            )));
+        }
+        let bytes = tokio::fs::read(&source)
+            .await
+            .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
+        if bytes.len() < 4 || &bytes[..4] != b"\0asm" {
+            return Err(ExtensionError::InstallFailed(format!(
+                "{} is not a valid WASM binary (bad magic number)",
+                source.display()
+            )));
+        }
+
+        let hash = compute_binary_hash(&bytes);
+        if let Some(expected) = expected_blake3 {
+            let expected = hex::decode(expected.trim()).map_err(|_| {
+                ExtensionError::InstallFailed(format!(
+                    "Expected BLAKE3 hash '{}' is not valid hex",
+                    expected
+                ))
+            })?;
+            if !verify_binary_integrity(&bytes, &expected) {
+                return Err(ExtensionError::InstallFailed(format!(
+                    "BLAKE3 mismatch for {}: expected {}, got {}",
+                    source.display(),
+                    hex::encode(&expected),
+                    hex::encode(&hash)
+                )));
+            }
+        }
+
+        // Validate the capabilities file before touching the tools directory
+        let capabilities_path = match capabilities_path {
+            Some(path) => Some(path.to_path_buf()),
+            None => {
+                let stem = source.file_stem().and_then(|s| s.to_str()).unwrap_or(name);
+                let sibling = source.with_file_name(format!("{}.capabilities.json", stem));
+                sibling.is_file().then_some(sibling)
+            }
+        };
+        let capabilities = match &capabilities_path {
+            Some(path) => {
+                let content = tokio::fs::read_to_string(path).await.map_err(|e| {
+                    ExtensionError::InstallFailed(format!(
+                        "Failed to read {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                CapabilitiesFile::from_json(&content).map_err(|e| {
+                    ExtensionError::InstallFailed(format!(
+                        "Invalid capabilities file {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                Some(content)
+            }
+            None => None,
+        };
+
+        tokio::fs::create_dir_all(&self.wasm_tools_dir)
+            .await
+            .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
+
+        // Copy through a temp file so a failed check never leaves a bad binary in place
+        let target = self.wasm_tools_dir.join(format!("{}.wasm", name));
+        let staging = self.wasm_tools_dir.join(format!("{}.wasm.tmp", name));
+        tokio::fs::write(&staging, &bytes)
+            .await
+            .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
+        let copied = tokio::fs::read(&staging)
+            .await
+            .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
+        if !verify_binary_integrity(&copied, &hash) {
+            let _ = tokio::fs::remove_file(&staging).await;
+            return Err(ExtensionError::InstallFailed(format!(
+                "Copy of {} does not match its BLAKE3 hash",
+                source.display()
+            )));
+        }
+        tokio::fs::rename(&staging, &target)
+            .await
+            .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
+
+        // A stale capabilities file would grant the old binary's permissions
+        let capabilities_target = self
+            .wasm_tools_dir
+            .join(format!("{}.capabilities.json", name));
+        match capabilities {
+            Some(content) => tokio::fs::write(&capabilities_target, content)
+                .await
+                .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?,
+            None => {
+                let _ = tokio::fs::remove_file(&capabilities_target).await;
+            }
+        }
+
+        self.consent
+            .record_install(name)
+            .await
+            .map_err(|e| ExtensionError::InstallFailed(e.to_string()))?;
+
+        tracing::info!(
+            "Installed WASM tool '{}' ({} bytes, blake3 {}) from {} to {}",
+            name,
+            bytes.len(),
+            hex::encode(&hash),
+            source.display(),
+            target.display()
+        );
+
+        Ok(InstallResult {
+            name: name.to_string(),
+            kind: ExtensionKind::WasmTool,
+            message: format!(
+                "WASM tool '{}' installed from {} (blake3 {}). Run activate to load it.",
+                name,
+                source.display(),
+                hex::encode(&hash)
+            ),
+            plan: Vec::new(),
         })
     }
 
@@ -564,10 +877,7 @@ impl ExtensionManager {
             let secret_name = server.token_secret_name();
             let params =
                 CreateSecretParams::new(&secret_name, token_value).with_provider(name.to_string());
-            self.secrets
-                .create(&self.user_id, params)
-                .await
-                .map_err(|e| ExtensionError::AuthFailed(e.to_string()))?;
+            self.store_secret(name, params).await?;
 
             tracing::info!("MCP server '{}' authenticated via manual token", name);
             return Ok(AuthResult {
@@ -596,6 +906,17 @@ impl ExtensionManager {
             });
         }
 
+        // Headless installs can't receive the browser redirect, so show a
+        // device code in the channel instead and finish in the background.
+        if is_headless() || find_available_port().await.is_err() {
+            match self.auth_mcp_device(name, &server).await {
+                Ok(result) => return Ok(result),
+                Err(e) => {
+                    tracing::debug!("Device authorization unavailable for '{}': {}", name, e)
+                }
+            }
+        }
+
         // Run the full OAuth flow (opens browser, waits for callback)
         match authorize_mcp_server(&server, &self.secrets, &self.user_id).await {
             Ok(_token) => {
@@ -654,6 +975,52 @@ impl ExtensionManager {
         }
     }
 
+    /// Start a device-code authorization and poll for it in the background.
+    ///
+    /// The returned result carries the verification URL and user code for
+    /// the channel to show; tokens are stored once the user confirms.
+    async fn auth_mcp_device(
+        &self,
+        name: &str,
+        server: &McpServerConfig,
+    ) -> Result<AuthResult, ExtensionError> {
+        let flow = start_device_authorization(server)
+            .await
+            .map_err(|e| ExtensionError::AuthFailed(e.to_string()))?;
+        let authorization = &flow.authorization;
+
+        let result = AuthResult {
+            name: name.to_string(),
+            kind: ExtensionKind::McpServer,
+            auth_url: Some(authorization.browser_url().to_string()),
+            callback_type: Some("device".to_string()),
+            instructions: Some(format!(
+                "Open {} on any device and enter the code {} to authorize '{}'. \
+                 The code expires in {} minutes.",
+                authorization.verification_uri,
+                authorization.user_code,
+                name,
+                authorization.expires_in.as_secs().div_ceil(60)
+            )),
+            setup_url: None,
+            awaiting_token: false,
+            status: "awaiting_authorization".to_string(),
+        };
+
+        let secrets = Arc::clone(&self.secrets);
+        let user_id = self.user_id.clone();
+        let server = server.clone();
+        let name = name.to_string();
+        tokio::spawn(async move {
+            match complete_device_authorization(&flow, &server, &secrets, &user_id).await {
+                Ok(_) => tracing::info!("MCP server '{}' authenticated via device code", name),
+                Err(e) => tracing::warn!("Device authorization for '{}' failed: {}", name, e),
+            }
+        });
+
+        Ok(result)
+    }
+
     /// Build an auth URL for cases where non-interactive auth is needed
     /// (e.g., running via Telegram where we can't open a browser).
     async fn auth_mcp_build_url(
@@ -778,10 +1145,7 @@ impl ExtensionManager {
             // Store the env var value as a secret
             let params =
                 CreateSecretParams::new(&auth.secret_name, &value).with_provider(name.to_string());
-            self.secrets
-                .create(&self.user_id, params)
-                .await
-                .map_err(|e| ExtensionError::AuthFailed(e.to_string()))?;
+            self.store_secret(name, params).await?;
 
             return Ok(AuthResult {
                 name: name.to_string(),
@@ -818,10 +1182,7 @@ impl ExtensionManager {
         if let Some(token_value) = token {
             let params = CreateSecretParams::new(&auth.secret_name, token_value)
                 .with_provider(name.to_string());
-            self.secrets
-                .create(&self.user_id, params)
-                .await
-                .map_err(|e| ExtensionError::AuthFailed(e.to_string()))?;
+            self.store_secret(name, params).await?;
 
             return Ok(AuthResult {
                 name: name.to_string(),
@@ -911,7 +1272,7 @@ impl ExtensionManager {
             .collect();
 
         for tool in tool_impls {
-            self.tool_registry.register(tool).await;
+            self.tool_registry.register_with_source(tool, name).await;
         }
 
         // Store the client
@@ -967,6 +1328,26 @@ impl ExtensionManager {
             None
         };
 
+        let requested = self.requested_capabilities(name).await?;
+        match self
+            .consent
+            .check(name, &requested)
+            .await
+            .map_err(|e| ExtensionError::Other(e.to_string()))?
+        {
+            ConsentStatus::Granted => {}
+            ConsentStatus::Denied => {
+                return Err(ExtensionError::ConsentDenied(name.to_string()));
+            }
+            ConsentStatus::Required(request) => {
+                return Err(ExtensionError::ConsentRequired(request));
+            }
+        }
+
+        // Tools installed before secret namespaces existed claim their
+        // secrets here on first activation.
+        self.claim_secrets(name).await;
+
         let loader = WasmToolLoader::new(Arc::clone(runtime), Arc::clone(&self.tool_registry));
         loader
             .load_from_files(name, &wasm_path, cap_path_option)
@@ -983,6 +1364,207 @@ impl ExtensionManager {
         })
     }
 
+    /// Capabilities a WASM tool still needs the user to approve, if any.
+    pub async fn pending_consent(
+        &self,
+        name: &str,
+    ) -> Result<Option<ConsentRequest>, ExtensionError> {
+        let requested = self.requested_capabilities(name).await?;
+        match self
+            .consent
+            .check(name, &requested)
+            .await
+            .map_err(|e| ExtensionError::Other(e.to_string()))?
+        {
+            ConsentStatus::Required(request) => Ok(Some(*request)),
+            ConsentStatus::Granted | ConsentStatus::Denied => Ok(None),
+        }
+    }
+
+    /// Record the user's decision on the capabilities a WASM tool currently
+    /// requests. Granting lets the next `activate` load it; denying makes
+    /// activation fail without asking again until the request changes.
+    pub async fn decide_consent(&self, name: &str, grant: bool) -> Result<String, ExtensionError> {
+        if self.determine_installed_kind(name).await? != ExtensionKind::WasmTool {
+            return Err(ExtensionError::Other(format!(
+                "'{}' is not a WASM tool; only WASM tools ask for capability consent",
+                name
+            )));
+        }
+        let requested = self.requested_capabilities(name).await?;
+        let result = if grant {
+            self.consent.grant(name, requested).await
+        } else {
+            self.consent.deny(name, requested).await
+        };
+        result.map_err(|e| ExtensionError::Other(e.to_string()))?;
+
+        let verb = if grant { "Granted" } else { "Denied" };
+        tracing::info!("{} capabilities for WASM tool '{}'", verb, name);
+        Ok(format!("{} capabilities requested by '{}'", verb, name))
+    }
+
+    /// Run an installed WASM tool once against `sample_input` with network,
+    /// secret, workspace and tool access stubbed out, and report what it
+    /// tried to touch. The tool is not registered and needs no consent, so
+    /// this works before activation.
+    pub async fn dry_run(
+        &self,
+        name: &str,
+        sample_input: serde_json::Value,
+    ) -> Result<DryRunReport, ExtensionError> {
+        let runtime = self.wasm_tool_runtime.as_ref().ok_or_else(|| {
+            ExtensionError::ActivationFailed("WASM runtime not available".to_string())
+        })?;
+
+        let wasm_path = self.wasm_tools_dir.join(format!("{}.wasm", name));
+        let wasm_bytes = match tokio::fs::read(&wasm_path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(ExtensionError::NotInstalled(format!(
+                    "WASM tool '{}' not found at {}",
+                    name,
+                    wasm_path.display()
+                )));
+            }
+            Err(e) => return Err(ExtensionError::Other(e.to_string())),
+        };
+
+        let cap_path = self
+            .wasm_tools_dir
+            .join(format!("{}.capabilities.json", name));
+        let capabilities = match tokio::fs::read(&cap_path).await {
+            Ok(bytes) => CapabilitiesFile::from_bytes(&bytes)
+                .map_err(|e| {
+                    ExtensionError::Other(format!("Invalid capabilities for '{}': {}", name, e))
+                })?
+                .to_capabilities(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Capabilities::default(),
+            Err(e) => return Err(ExtensionError::Other(e.to_string())),
+        };
+
+        let prepared = runtime
+            .prepare(name, &wasm_bytes, None)
+            .await
+            .map_err(|e| ExtensionError::Other(e.to_string()))?;
+        let wrapper = WasmToolWrapper::new(Arc::clone(runtime), prepared, capabilities);
+        let report = wrapper.dry_run(sample_input).await;
+
+        tracing::info!(
+            name = name,
+            denied = report.has_denied(),
+            "Dry-ran WASM tool"
+        );
+        Ok(report)
+    }
+
+    /// Store a secret for `extension` and put it in that extension's
+    /// namespace.
+    async fn store_secret(
+        &self,
+        extension: &str,
+        params: CreateSecretParams,
+    ) -> Result<(), ExtensionError> {
+        let secret_name = params.name.clone();
+        self.secrets
+            .create(&self.user_id, params)
+            .await
+            .map_err(|e| ExtensionError::AuthFailed(e.to_string()))?;
+        self.secrets
+            .grant(&self.user_id, &secret_name, extension)
+            .await
+            .map_err(|e| ExtensionError::AuthFailed(e.to_string()))
+    }
+
+    /// Grant a WASM tool the already-stored secrets it declares, as long as
+    /// no other extension holds them. A secret another extension owns is
+    /// left alone; the tool gets its own copy by authenticating.
+    /// Register, update or remove the routine for a WASM tool's `schedule`
+    /// capability, going by its installed capabilities file.
+    async fn sync_schedule(&self, name: &str) {
+        let Some(ref store) = self.store else {
+            return;
+        };
+        let cap_path = self
+            .wasm_tools_dir
+            .join(format!("{}.capabilities.json", name));
+        let schedule = match tokio::fs::read(&cap_path).await {
+            Ok(bytes) => CapabilitiesFile::from_bytes(&bytes)
+                .ok()
+                .and_then(|file| file.schedule),
+            Err(_) => None,
+        };
+        match sync_tool_schedule(store.as_ref(), &self.user_id, name, schedule.as_ref()).await {
+            Ok(ScheduleSync::Unscheduled) => {}
+            Ok(outcome) => tracing::info!("Schedule for WASM tool '{}': {:?}", name, outcome),
+            Err(e) => tracing::warn!("Failed to sync schedule for WASM tool '{}': {}", name, e),
+        }
+    }
+
+    async fn claim_secrets(&self, name: &str) {
+        let cap_path = self
+            .wasm_tools_dir
+            .join(format!("{}.capabilities.json", name));
+        let Ok(bytes) = tokio::fs::read(&cap_path).await else {
+            return;
+        };
+        let Ok(file) = CapabilitiesFile::from_bytes(&bytes) else {
+            return;
+        };
+
+        for secret_name in file.declared_secrets() {
+            if !self
+                .secrets
+                .exists(&self.user_id, &secret_name)
+                .await
+                .unwrap_or(false)
+            {
+                continue;
+            }
+            let grantees = match self.secrets.grantees(&self.user_id, &secret_name).await {
+                Ok(grantees) => grantees,
+                Err(e) => {
+                    tracing::warn!("Failed to read grants for secret '{}': {}", secret_name, e);
+                    continue;
+                }
+            };
+            if grantees.iter().any(|g| g == name) {
+                continue;
+            }
+            if !grantees.is_empty() {
+                tracing::warn!(
+                    tool = name,
+                    secret = %secret_name,
+                    owners = ?grantees,
+                    "Not granting a secret that belongs to another extension"
+                );
+                continue;
+            }
+            match self.secrets.grant(&self.user_id, &secret_name, name).await {
+                Ok(()) => tracing::info!(tool = name, secret = %secret_name, "Granted secret"),
+                Err(e) => tracing::warn!("Failed to grant secret '{}': {}", secret_name, e),
+            }
+        }
+    }
+
+    /// Capabilities requested by an installed WASM tool's capabilities file.
+    async fn requested_capabilities(&self, name: &str) -> Result<CapabilitySet, ExtensionError> {
+        let cap_path = self
+            .wasm_tools_dir
+            .join(format!("{}.capabilities.json", name));
+        let content = match tokio::fs::read_to_string(&cap_path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(CapabilitySet::default());
+            }
+            Err(e) => return Err(ExtensionError::Other(e.to_string())),
+        };
+        let file = CapabilitiesFile::from_json(&content).map_err(|e| {
+            ExtensionError::ActivationFailed(format!("Invalid capabilities for '{}': {}", name, e))
+        })?;
+        Ok(CapabilitySet::from_file(&file))
+    }
+
     /// Determine what kind of installed extension this is.
     async fn determine_installed_kind(&self, name: &str) -> Result<ExtensionKind, ExtensionError> {
         // Check MCP servers first
@@ -1023,10 +1605,28 @@ fn infer_kind_from_url(url: &str) -> ExtensionKind {
     }
 }
 
+/// Interpret an install "URL" as a local path: `file://` URLs and anything
+/// without a scheme.
+fn local_path_from_url(url: &str) -> Option<PathBuf> {
+    if let Some(path) = url.strip_prefix("file://") {
+        return Some(PathBuf::from(path));
+    }
+    if url.contains("://") {
+        return None;
+    }
+    match url.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map(|home| home.join(rest)),
+        None => Some(PathBuf::from(url)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::extensions::ExtensionKind;
-    use crate::extensions::manager::infer_kind_from_url;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use crate::extensions::manager::{ExtensionManager, infer_kind_from_url, local_path_from_url};
+    use crate::extensions::{ExtensionError, ExtensionKind};
 
     #[test]
     fn test_infer_kind_from_url() {
@@ -1043,4 +1643,111 @@ mod tests {
             ExtensionKind::McpServer
         );
     }
+
+    #[test]
+    fn test_local_path_from_url() {
+        assert_eq!(
+            local_path_from_url("file:///mnt/usb/tool.wasm"),
+            Some(PathBuf::from("/mnt/usb/tool.wasm"))
+        );
+        assert_eq!(
+            local_path_from_url("./tools/slack"),
+            Some(PathBuf::from("./tools/slack"))
+        );
+        assert_eq!(local_path_from_url("https://example.com/tool.wasm"), None);
+    }
+
+    fn test_manager(tools_dir: PathBuf) -> ExtensionManager {
+        use crate::secrets::{InMemorySecretsStore, SecretsCrypto};
+        use crate::tools::ToolRegistry;
+        use crate::tools::mcp::session::McpSessionManager;
+
+        let master_key =
+            secrecy::SecretString::from("0123456789abcdef0123456789abcdef".to_string());
+        let crypto = Arc::new(SecretsCrypto::new(master_key).unwrap());
+        ExtensionManager::new(
+            Arc::new(McpSessionManager::new()),
+            Arc::new(InMemorySecretsStore::new(crypto)),
+            Arc::new(ToolRegistry::new()),
+            None,
+            tools_dir,
+            PathBuf::from("/tmp/ironclaw-test-channels"),
+            None,
+            "test".to_string(),
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_install_wasm_tool_from_local_path() {
+        let src = tempfile::tempdir().unwrap();
+        let tools = tempfile::tempdir().unwrap();
+        let binary = b"\0asm\x01\0\0\0";
+        std::fs::write(src.path().join("echo.wasm"), binary).unwrap();
+        std::fs::write(src.path().join("echo.capabilities.json"), "{}").unwrap();
+        let manager = test_manager(tools.path().to_path_buf());
+
+        let wrong = hex::encode([0u8; 32]);
+        let err = manager
+            .install_wasm_tool_from_path("echo", src.path(), None, Some(&wrong))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ExtensionError::InstallFailed(ref m) if m.contains("BLAKE3")));
+        assert!(!tools.path().join("echo.wasm").exists());
+
+        let hash = hex::encode(blake3::hash(binary).as_bytes());
+        let result = manager
+            .install_wasm_tool_from_path("echo", src.path(), None, Some(&hash))
+            .await
+            .unwrap();
+        assert_eq!(result.kind, ExtensionKind::WasmTool);
+        assert!(result.message.contains(&hash));
+        assert_eq!(
+            std::fs::read(tools.path().join("echo.wasm")).unwrap(),
+            binary
+        );
+        assert!(tools.path().join("echo.capabilities.json").exists());
+
+        std::fs::write(src.path().join("bad.wasm"), b"not wasm").unwrap();
+        let path = src.path().join("bad.wasm");
+        let err = manager
+            .install("bad", path.to_str(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ExtensionError::InstallFailed(ref m) if m.contains("magic")));
+    }
+
+    #[tokio::test]
+    async fn test_install_records_nothing_accepted_until_granted() {
+        use crate::tools::wasm::{CapabilitySet, accepted_path, load_accepted};
+
+        let src = tempfile::tempdir().unwrap();
+        let tools = tempfile::tempdir().unwrap();
+        std::fs::write(src.path().join("fetch.wasm"), b"\0asm\x01\0\0\0").unwrap();
+        std::fs::write(
+            src.path().join("fetch.capabilities.json"),
+            r#"{"http": {"allowlist": [{"host": "api.example.com"}]}}"#,
+        )
+        .unwrap();
+        let manager = test_manager(tools.path().to_path_buf());
+        let accepted = accepted_path(tools.path(), "fetch");
+
+        manager
+            .install_wasm_tool_from_path("fetch", src.path(), None, None)
+            .await
+            .unwrap();
+        let record = load_accepted(&accepted).await.unwrap().unwrap();
+        assert_eq!(record.capabilities, CapabilitySet::default());
+        assert!(manager.pending_consent("fetch").await.unwrap().is_some());
+
+        manager.decide_consent("fetch", true).await.unwrap();
+        assert!(manager.pending_consent("fetch").await.unwrap().is_none());
+
+        // Reinstalling keeps the grant; a broader request would ask again.
+        manager
+            .install_wasm_tool_from_path("fetch", src.path(), None, None)
+            .await
+            .unwrap();
+        assert!(manager.pending_consent("fetch").await.unwrap().is_none());
+    }
 }
@@ -14,15 +14,23 @@
 //! ```
 
 pub mod clawhub;
+pub mod consent;
 pub mod discovery;
+pub mod install_txn;
 pub mod manager;
 pub mod plugin_manager;
 pub mod plugins;
+pub mod publish;
 pub mod registry;
 
+pub use consent::{CapabilityConsent, ConsentRequest, ConsentStatus};
 pub use discovery::OnlineDiscovery;
+pub use install_txn::{InstallTransaction, RollbackAction, RollbackReport};
 pub use manager::ExtensionManager;
-pub use plugin_manager::{PluginError, PluginManager, PluginSnapshot, PluginSummary};
+pub use plugin_manager::{
+    PluginArtifact, PluginError, PluginInstallReport, PluginInstallRequest, PluginManager,
+    PluginRepairReport, PluginSnapshot, PluginStorage, PluginSummary,
+};
 pub use registry::ExtensionRegistry;
 
 use serde::{Deserialize, Serialize};
@@ -67,6 +75,10 @@ pub struct RegistryEntry {
     pub source: ExtensionSource,
     /// How authentication works.
     pub auth_hint: AuthHint,
+    /// Registry names of extensions this one needs. They are installed and
+    /// activated before it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
 }
 
 /// Where the extension binary or server lives.
@@ -89,6 +101,17 @@ pub enum ExtensionSource {
     },
     /// Discovered online (not yet validated for a specific source type).
     Discovered { url: String },
+    /// WASM binary already on disk, for installs without network access.
+    /// `wasm_path` may name the `.wasm` file or a directory holding
+    /// `<name>.wasm`.
+    LocalPath {
+        wasm_path: String,
+        #[serde(default)]
+        capabilities_path: Option<String>,
+        /// Expected BLAKE3 hash of the binary, hex-encoded.
+        #[serde(default)]
+        blake3: Option<String>,
+    },
 }
 
 /// Hint about what authentication method is needed.
@@ -137,6 +160,24 @@ pub struct InstallResult {
     pub name: String,
     pub kind: ExtensionKind,
     pub message: String,
+    /// Every extension handled, dependencies first and the requested one
+    /// last. Empty when there were no dependencies.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plan: Vec<PlannedInstall>,
+}
+
+/// One step of a dependency-resolved install.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedInstall {
+    pub name: String,
+    pub kind: ExtensionKind,
+    /// Was already installed; left as it was.
+    pub already_installed: bool,
+    /// Activated after install. Only dependencies are activated.
+    pub activated: bool,
+    /// Why activation failed, e.g. authentication is still needed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 /// Result of authenticating an extension.
@@ -223,9 +264,18 @@ pub enum ExtensionError {
     #[error("Config error: {0}")]
     Config(String),
 
+    #[error("Approve capabilities before activating: {0}")]
+    ConsentRequired(Box<ConsentRequest>),
+
+    #[error("Capabilities requested by '{0}' were denied")]
+    ConsentDenied(String),
+
     #[error("Channels require restart to activate")]
     ChannelNeedsRestart,
 
+    #[error("Dependency cycle: {0}")]
+    DependencyCycle(String),
+
     #[error("{0}")]
     Other(String),
 }
@@ -352,6 +402,25 @@ mod tests {
         let _back: ExtensionSource = serde_json::from_value(json).unwrap();
     }
 
+    #[test]
+    fn extension_source_local_path_serde() {
+        let json = serde_json::json!({
+            "type": "local_path",
+            "wasm_path": "/mnt/usb/tools/slack"
+        });
+        let src: ExtensionSource = serde_json::from_value(json).unwrap();
+        assert!(matches!(
+            &src,
+            ExtensionSource::LocalPath {
+                wasm_path,
+                capabilities_path: None,
+                blake3: None,
+            } if wasm_path == "/mnt/usb/tools/slack"
+        ));
+        let json = serde_json::to_value(&src).unwrap();
+        assert_eq!(json["type"], "local_path");
+    }
+
     // --- AuthHint ---
 
     #[test]
@@ -415,6 +484,7 @@ mod tests {
                 url: "https://mcp.notion.so".into(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         }
     }
 
@@ -502,6 +572,7 @@ mod tests {
             name: "slack".into(),
             kind: ExtensionKind::WasmTool,
             message: "Installed".into(),
+            plan: Vec::new(),
         };
         assert_eq!(r.name, "slack");
         assert_eq!(r.kind, ExtensionKind::WasmTool);
@@ -2,14 +2,24 @@
 //!
 //! Manages registration, activation, and lifecycle of plugins across
 //! all plugin types (auth, memory, provider, hook, channel, tool, http_route).
-
-use std::collections::HashMap;
+//!
+//! Installs are transactional: verifying artifacts, storing binaries,
+//! recording the plugin in `plugins.json`, registering tools, and
+//! activating all happen under an [`InstallTransaction`] that rolls back
+//! partial state on any failure. [`PluginManager::repair`] reconciles the
+//! config file with what is actually stored on disk.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tokio::sync::RwLock;
 
+use crate::extensions::install_txn::{InstallTransaction, RollbackAction};
 use crate::extensions::plugins::{Plugin, PluginRoute, PluginStatus, PluginType};
+use crate::tools::{Tool, ToolRegistry};
 
 /// Error type for plugin operations.
 #[derive(Debug, thiserror::Error)]
@@ -26,12 +36,116 @@ pub enum PluginError {
     ValidationFailed(String),
     #[error("Plugin dependency not met: {plugin} requires {dependency}")]
     DependencyNotMet { plugin: String, dependency: String },
+    #[error("Plugin install failed for {name}: {reason} ({rolled_back} step(s) rolled back)")]
+    InstallFailed {
+        name: String,
+        reason: String,
+        rolled_back: usize,
+    },
+    #[error("Plugin storage error: {0}")]
+    Storage(String),
 }
 
 /// Manages all registered plugins.
 pub struct PluginManager {
     plugins: Arc<RwLock<HashMap<String, PluginEntry>>>,
     max_plugins: usize,
+    storage: Option<PluginStorage>,
+    tool_registry: Option<Arc<ToolRegistry>>,
+}
+
+/// On-disk locations for installed plugin binaries and their config file.
+#[derive(Debug, Clone)]
+pub struct PluginStorage {
+    /// Directory holding plugin artifacts.
+    pub install_dir: PathBuf,
+    /// JSON file listing installed plugins (`plugins.json`).
+    pub manifest_path: PathBuf,
+}
+
+impl PluginStorage {
+    /// Default locations under `~/.ironclaw/`.
+    pub fn default_paths() -> Self {
+        let base = dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".ironclaw");
+        Self {
+            install_dir: base.join("plugins"),
+            manifest_path: base.join("plugins.json"),
+        }
+    }
+}
+
+/// Contents of `plugins.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginManifest {
+    #[serde(default)]
+    pub plugins: Vec<InstalledPlugin>,
+}
+
+/// A plugin recorded as installed, with the artifacts it owns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstalledPlugin {
+    pub plugin: Plugin,
+    pub artifacts: Vec<ArtifactRecord>,
+    pub installed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A stored artifact and its content hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactRecord {
+    pub file_name: String,
+    pub sha256: String,
+}
+
+/// A downloaded artifact awaiting verification and storage.
+#[derive(Debug, Clone)]
+pub struct PluginArtifact {
+    /// Bare file name inside the install directory.
+    pub file_name: String,
+    pub bytes: Vec<u8>,
+    /// Expected hex SHA-256; verified before anything is written.
+    pub expected_sha256: Option<String>,
+}
+
+/// Everything needed to install a plugin atomically.
+pub struct PluginInstallRequest {
+    pub plugin: Plugin,
+    pub artifacts: Vec<PluginArtifact>,
+    /// Tools the plugin contributes to the tool registry.
+    pub tools: Vec<Arc<dyn Tool>>,
+}
+
+/// Result of a successful install.
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInstallReport {
+    pub name: String,
+    pub artifacts: Vec<String>,
+    pub tools: Vec<String>,
+    pub activated: bool,
+}
+
+/// Result of reconciling `plugins.json` with stored artifacts.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PluginRepairReport {
+    /// Config entries removed because their artifacts are missing or corrupt.
+    pub dropped_entries: Vec<String>,
+    /// Stored files not referenced by any config entry (removed unless dry run).
+    pub orphaned_files: Vec<String>,
+    /// Config entries that are consistent with storage.
+    pub healthy: Vec<String>,
+    pub dry_run: bool,
+}
+
+impl PluginRepairReport {
+    /// Whether storage and config already agree.
+    pub fn is_clean(&self) -> bool {
+        self.dropped_entries.is_empty() && self.orphaned_files.is_empty()
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
 }
 
 /// Internal plugin entry with status tracking.
@@ -74,6 +188,8 @@ impl PluginManager {
         Self {
             plugins: Arc::new(RwLock::new(HashMap::new())),
             max_plugins: 100,
+            storage: None,
+            tool_registry: None,
         }
     }
 
@@ -83,6 +199,18 @@ impl PluginManager {
         self
     }
 
+    /// Persist installed plugins to the given storage locations.
+    pub fn with_storage(mut self, storage: PluginStorage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Register plugin-contributed tools with this registry on install.
+    pub fn with_tool_registry(mut self, registry: Arc<ToolRegistry>) -> Self {
+        self.tool_registry = Some(registry);
+        self
+    }
+
     /// Register a new plugin.
     pub async fn register(&self, plugin: Plugin) -> Result<(), PluginError> {
         let mut plugins = self.plugins.write().await;
@@ -224,6 +352,253 @@ impl PluginManager {
         entry.plugin.config = config;
         Ok(())
     }
+
+    /// Install a plugin as an atomic unit.
+    ///
+    /// Verifies artifacts, stores them, records the plugin in `plugins.json`,
+    /// registers it and its tools, then activates it if enabled. Any failure
+    /// rolls back every completed step and returns [`PluginError::InstallFailed`].
+    pub async fn install(
+        &self,
+        request: PluginInstallRequest,
+    ) -> Result<PluginInstallReport, PluginError> {
+        let name = request.plugin.name.clone();
+
+        // Verification happens before any side effects, so failures here
+        // need no rollback.
+        let records = Self::verify_artifacts(&request.artifacts).map_err(|reason| {
+            PluginError::InstallFailed {
+                name: name.clone(),
+                reason,
+                rolled_back: 0,
+            }
+        })?;
+
+        let mut txn = InstallTransaction::new(&name);
+        match self.install_steps(&mut txn, request, records).await {
+            Ok(report) => {
+                txn.commit();
+                tracing::info!("Installed plugin '{}'", name);
+                Ok(report)
+            }
+            Err(reason) => {
+                let rollback = txn
+                    .rollback(Some(self), self.tool_registry.as_deref())
+                    .await;
+                for failure in &rollback.failures {
+                    tracing::warn!("Rollback step failed for '{}': {}", name, failure);
+                }
+                Err(PluginError::InstallFailed {
+                    name,
+                    reason,
+                    rolled_back: rollback.undone,
+                })
+            }
+        }
+    }
+
+    fn verify_artifacts(artifacts: &[PluginArtifact]) -> Result<Vec<ArtifactRecord>, String> {
+        let mut seen = HashSet::new();
+        artifacts
+            .iter()
+            .map(|a| {
+                if a.file_name.is_empty()
+                    || a.file_name.contains(['/', '\\'])
+                    || a.file_name.starts_with('.')
+                {
+                    return Err(format!("invalid artifact file name '{}'", a.file_name));
+                }
+                if !seen.insert(a.file_name.as_str()) {
+                    return Err(format!("duplicate artifact '{}'", a.file_name));
+                }
+                if a.file_name.ends_with(".wasm") && !a.bytes.starts_with(b"\0asm") {
+                    return Err(format!("'{}' is not a valid WASM binary", a.file_name));
+                }
+                let actual = sha256_hex(&a.bytes);
+                if let Some(ref expected) = a.expected_sha256
+                    && !expected.eq_ignore_ascii_case(&actual)
+                {
+                    return Err(format!(
+                        "checksum mismatch for '{}': expected {}, got {}",
+                        a.file_name, expected, actual
+                    ));
+                }
+                Ok(ArtifactRecord {
+                    file_name: a.file_name.clone(),
+                    sha256: actual,
+                })
+            })
+            .collect()
+    }
+
+    async fn install_steps(
+        &self,
+        txn: &mut InstallTransaction,
+        request: PluginInstallRequest,
+        records: Vec<ArtifactRecord>,
+    ) -> Result<PluginInstallReport, String> {
+        let PluginInstallRequest {
+            plugin,
+            artifacts,
+            tools,
+        } = request;
+        let name = plugin.name.clone();
+        let enabled = plugin.enabled;
+
+        if self.plugins.read().await.contains_key(&name) {
+            return Err(format!("plugin '{}' is already registered", name));
+        }
+
+        // Store binaries and record the plugin in the config file.
+        if let Some(ref storage) = self.storage {
+            let mut manifest = Self::load_manifest(storage).await?;
+            if manifest.plugins.iter().any(|p| p.plugin.name == name) {
+                return Err(format!("plugin '{}' is already installed", name));
+            }
+
+            for artifact in &artifacts {
+                let path = storage.install_dir.join(&artifact.file_name);
+                txn.write_file(&path, &artifact.bytes)
+                    .await
+                    .map_err(|e| format!("failed to store {}: {}", path.display(), e))?;
+            }
+
+            manifest.plugins.push(InstalledPlugin {
+                plugin: plugin.clone(),
+                artifacts: records.clone(),
+                installed_at: chrono::Utc::now(),
+            });
+            let json = serde_json::to_vec_pretty(&manifest)
+                .map_err(|e| format!("failed to serialize plugins.json: {}", e))?;
+            txn.write_file(&storage.manifest_path, &json)
+                .await
+                .map_err(|e| format!("failed to write plugins.json: {}", e))?;
+        }
+
+        // Register the plugin (disabled until activation succeeds).
+        let mut pending = plugin;
+        pending.enabled = false;
+        self.register(pending).await.map_err(|e| e.to_string())?;
+        txn.record(RollbackAction::UnregisterPlugin(name.clone()));
+
+        // Register contributed tools.
+        let mut tool_names = Vec::new();
+        if !tools.is_empty() {
+            let registry = self
+                .tool_registry
+                .as_ref()
+                .ok_or_else(|| "plugin provides tools but no tool registry is set".to_string())?;
+            for tool in tools {
+                let tool_name = tool.name().to_string();
+                if registry.has(&tool_name).await {
+                    return Err(format!("tool '{}' is already registered", tool_name));
+                }
+                registry.register_with_source(tool, name.as_str()).await;
+                txn.record(RollbackAction::UnregisterTool(tool_name.clone()));
+                tool_names.push(tool_name);
+            }
+        }
+
+        if enabled {
+            self.activate(&name).await.map_err(|e| e.to_string())?;
+        }
+
+        Ok(PluginInstallReport {
+            name,
+            artifacts: records.into_iter().map(|r| r.file_name).collect(),
+            tools: tool_names,
+            activated: enabled,
+        })
+    }
+
+    /// Reconcile `plugins.json` with the artifacts actually stored on disk.
+    ///
+    /// Entries whose artifacts are missing or fail their checksum are
+    /// dropped; files in the install directory that no entry references are
+    /// removed. With `dry_run`, only reports what would change.
+    pub async fn repair(&self, dry_run: bool) -> Result<PluginRepairReport, PluginError> {
+        let storage = self
+            .storage
+            .as_ref()
+            .ok_or_else(|| PluginError::Storage("no plugin storage configured".to_string()))?;
+
+        let mut manifest = Self::load_manifest(storage)
+            .await
+            .map_err(PluginError::Storage)?;
+        let mut report = PluginRepairReport {
+            dry_run,
+            ..Default::default()
+        };
+
+        let mut kept = Vec::new();
+        let mut referenced = HashSet::new();
+        for entry in manifest.plugins.drain(..) {
+            let mut intact = true;
+            for artifact in &entry.artifacts {
+                let path = storage.install_dir.join(&artifact.file_name);
+                match tokio::fs::read(&path).await {
+                    Ok(bytes) if sha256_hex(&bytes) == artifact.sha256 => {}
+                    _ => intact = false,
+                }
+            }
+            if intact {
+                referenced.extend(entry.artifacts.iter().map(|a| a.file_name.clone()));
+                report.healthy.push(entry.plugin.name.clone());
+                kept.push(entry);
+            } else {
+                report.dropped_entries.push(entry.plugin.name.clone());
+            }
+        }
+
+        if let Ok(mut dir) = tokio::fs::read_dir(&storage.install_dir).await {
+            while let Ok(Some(file)) = dir.next_entry().await {
+                let file_name = file.file_name().to_string_lossy().to_string();
+                if !referenced.contains(&file_name) {
+                    report.orphaned_files.push(file_name);
+                }
+            }
+        }
+        report.orphaned_files.sort();
+
+        if dry_run || report.is_clean() {
+            return Ok(report);
+        }
+
+        // Dropped entries may still own some intact files; those are now
+        // orphans too and are removed with the rest.
+        for file_name in &report.orphaned_files {
+            let path = storage.install_dir.join(file_name);
+            if let Err(e) = tokio::fs::remove_file(&path).await {
+                tracing::warn!("Failed to remove orphaned {}: {}", path.display(), e);
+            }
+        }
+        for name in &report.dropped_entries {
+            let _ = self.unregister(name).await;
+        }
+
+        manifest.plugins = kept;
+        let json = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| PluginError::Storage(e.to_string()))?;
+        tokio::fs::write(&storage.manifest_path, json)
+            .await
+            .map_err(|e| PluginError::Storage(e.to_string()))?;
+
+        Ok(report)
+    }
+
+    /// Read `plugins.json`, treating a missing file as empty.
+    async fn load_manifest(storage: &PluginStorage) -> Result<PluginManifest, String> {
+        match tokio::fs::read(&storage.manifest_path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| format!("invalid {}: {}", storage.manifest_path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(PluginManifest::default()),
+            Err(e) => Err(format!(
+                "failed to read {}: {}",
+                storage.manifest_path.display(),
+                e
+            )),
+        }
+    }
 }
 
 impl PluginEntry {
@@ -789,4 +1164,157 @@ mod tests {
         assert_eq!(snap.routes[0].path, "/api/v1/health");
         assert_eq!(snap.routes[1].method, "POST");
     }
+
+    // --- Transactional install / repair ---
+
+    fn storage_in(dir: &std::path::Path) -> PluginStorage {
+        PluginStorage {
+            install_dir: dir.join("plugins"),
+            manifest_path: dir.join("plugins.json"),
+        }
+    }
+
+    fn wasm_artifact(name: &str) -> PluginArtifact {
+        PluginArtifact {
+            file_name: format!("{name}.wasm"),
+            bytes: b"\0asm\x01\0\0\0".to_vec(),
+            expected_sha256: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_install_stores_artifacts_and_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let mgr = PluginManager::new().with_storage(storage_in(dir.path()));
+
+        let report = mgr
+            .install(PluginInstallRequest {
+                plugin: make_plugin("weather", PluginType::Tool, true),
+                artifacts: vec![wasm_artifact("weather")],
+                tools: vec![],
+            })
+            .await
+            .unwrap();
+
+        assert!(report.activated);
+        assert!(dir.path().join("plugins/weather.wasm").exists());
+        let manifest: PluginManifest =
+            serde_json::from_slice(&std::fs::read(dir.path().join("plugins.json")).unwrap())
+                .unwrap();
+        assert_eq!(manifest.plugins.len(), 1);
+        assert_eq!(
+            mgr.get("weather").await.unwrap().status,
+            PluginStatus::Active
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_checksum_mismatch_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let mgr = PluginManager::new().with_storage(storage_in(dir.path()));
+        let mut artifact = wasm_artifact("weather");
+        artifact.expected_sha256 = Some("00".repeat(32));
+
+        let err = mgr
+            .install(PluginInstallRequest {
+                plugin: make_plugin("weather", PluginType::Tool, true),
+                artifacts: vec![artifact],
+                tools: vec![],
+            })
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("checksum mismatch"));
+        assert!(!dir.path().join("plugins/weather.wasm").exists());
+        assert!(!dir.path().join("plugins.json").exists());
+        assert!(mgr.get("weather").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_install_rolls_back_on_tool_conflict() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("plugins.json"), br#"{"plugins":[]}"#).unwrap();
+        let registry = Arc::new(ToolRegistry::new());
+        registry
+            .register(Arc::new(crate::tools::builtin::EchoTool))
+            .await;
+        let mgr = PluginManager::new()
+            .with_storage(storage_in(dir.path()))
+            .with_tool_registry(Arc::clone(&registry));
+
+        let err = mgr
+            .install(PluginInstallRequest {
+                plugin: make_plugin("echoer", PluginType::Tool, true),
+                artifacts: vec![wasm_artifact("echoer")],
+                tools: vec![Arc::new(crate::tools::builtin::EchoTool)],
+            })
+            .await
+            .unwrap_err();
+
+        match err {
+            PluginError::InstallFailed { rolled_back, .. } => assert_eq!(rolled_back, 3),
+            other => panic!("unexpected error: {other}"),
+        }
+        assert!(!dir.path().join("plugins/echoer.wasm").exists());
+        assert_eq!(
+            std::fs::read(dir.path().join("plugins.json")).unwrap(),
+            br#"{"plugins":[]}"#
+        );
+        assert!(mgr.get("echoer").await.is_none());
+        assert!(registry.has("echo").await);
+    }
+
+    #[tokio::test]
+    async fn test_install_rejects_path_traversal() {
+        let mgr = PluginManager::new();
+        let mut artifact = wasm_artifact("x");
+        artifact.file_name = "../evil.wasm".to_string();
+        let err = mgr
+            .install(PluginInstallRequest {
+                plugin: make_plugin("x", PluginType::Tool, true),
+                artifacts: vec![artifact],
+                tools: vec![],
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid artifact file name"));
+    }
+
+    #[tokio::test]
+    async fn test_repair_drops_missing_and_removes_orphans() {
+        let dir = tempfile::tempdir().unwrap();
+        let mgr = PluginManager::new().with_storage(storage_in(dir.path()));
+        for name in ["keep", "broken"] {
+            mgr.install(PluginInstallRequest {
+                plugin: make_plugin(name, PluginType::Tool, true),
+                artifacts: vec![wasm_artifact(name)],
+                tools: vec![],
+            })
+            .await
+            .unwrap();
+        }
+        std::fs::remove_file(dir.path().join("plugins/broken.wasm")).unwrap();
+        std::fs::write(dir.path().join("plugins/stray.wasm"), b"\0asm").unwrap();
+
+        let preview = mgr.repair(true).await.unwrap();
+        assert_eq!(preview.dropped_entries, vec!["broken".to_string()]);
+        assert_eq!(preview.orphaned_files, vec!["stray.wasm".to_string()]);
+        assert!(dir.path().join("plugins/stray.wasm").exists());
+
+        let report = mgr.repair(false).await.unwrap();
+        assert_eq!(report.healthy, vec!["keep".to_string()]);
+        assert!(!dir.path().join("plugins/stray.wasm").exists());
+        assert!(mgr.get("broken").await.is_none());
+
+        assert!(mgr.repair(false).await.unwrap().is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_repair_without_storage_errors() {
+        let mgr = PluginManager::new();
+        assert!(matches!(
+            mgr.repair(true).await,
+            Err(PluginError::Storage(_))
+        ));
+    }
 }
@@ -0,0 +1,423 @@
+//! Packaging and signing WASM tools for publishing to ClawHub.
+//!
+//! A publishable directory holds a manifest, the component and, optionally,
+//! its capabilities file:
+//!
+//! ```text
+//! my-tool/
+//! ├── clawhub.json               # PublishManifest
+//! ├── my-tool.wasm
+//! └── my-tool.capabilities.json  # or capabilities.json
+//! ```
+//!
+//! [`PackageBundle::from_dir`] validates and hashes the files, the bundle is
+//! signed with the local Ed25519 [`PublisherKey`] (created on first use), and
+//! [`ClawHubClient::publish`](crate::extensions::clawhub::ClawHubClient::publish)
+//! uploads it. The signature covers the manifest fields that identify the
+//! release plus the SHA-256 of every file, so the registry and installers can
+//! tell the upload came from the holder of the key.
+
+use std::path::{Path, PathBuf};
+
+use ring::rand::SystemRandom;
+use ring::signature::{ED25519, Ed25519KeyPair, KeyPair, UnparsedPublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::extensions::clawhub::PackageType;
+use crate::tools::wasm::CapabilitiesFile;
+
+/// Manifest file name inside a package directory.
+pub const MANIFEST_FILE: &str = "clawhub.json";
+
+/// Signature algorithm recorded alongside every signature.
+pub const SIGNATURE_ALGORITHM: &str = "ed25519";
+
+/// Largest WASM binary accepted for publishing (50 MB).
+const MAX_WASM_BYTES: usize = 50 * 1024 * 1024;
+
+/// Package metadata from `clawhub.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishManifest {
+    /// Package name (unique in the registry).
+    pub name: String,
+    /// Display name; defaults to the name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    pub description: String,
+    /// Release version (`MAJOR.MINOR.PATCH`).
+    pub version: String,
+    /// Tool, channel or plugin.
+    #[serde(default = "default_package_type")]
+    pub package_type: PackageType,
+    pub author: String,
+    /// SPDX license identifier.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+fn default_package_type() -> PackageType {
+    PackageType::Tool
+}
+
+impl PublishManifest {
+    /// Check the fields the registry relies on.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty()
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "invalid package name '{}': use lowercase letters, digits, '-' and '_'",
+                self.name
+            ));
+        }
+        let parts: Vec<&str> = self.version.split('.').collect();
+        if parts.len() != 3
+            || parts
+                .iter()
+                .any(|p| p.is_empty() || !p.chars().all(|c| c.is_ascii_digit()))
+        {
+            return Err(format!(
+                "invalid version '{}': expected MAJOR.MINOR.PATCH",
+                self.version
+            ));
+        }
+        if !matches!(
+            self.package_type,
+            PackageType::Tool | PackageType::Channel | PackageType::Plugin
+        ) {
+            return Err(format!(
+                "'{}' packages are not WASM; only tools, channels and plugins can be published this way",
+                self.package_type
+            ));
+        }
+        if self.description.trim().is_empty() {
+            return Err("description must not be empty".to_string());
+        }
+        if self.author.trim().is_empty() {
+            return Err("author must not be empty".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// What a publisher signs: the release identity and the hash of each file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedPayload {
+    pub name: String,
+    pub version: String,
+    pub package_type: PackageType,
+    /// Hex SHA-256 of the WASM binary.
+    pub wasm_sha256: String,
+    /// Hex SHA-256 of the capabilities file, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities_sha256: Option<String>,
+}
+
+impl SignedPayload {
+    /// Bytes the signature is computed over.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+}
+
+/// Detached signature over a [`SignedPayload`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageSignature {
+    /// Always [`SIGNATURE_ALGORITHM`].
+    pub algorithm: String,
+    /// Hex-encoded Ed25519 public key of the publisher.
+    pub public_key: String,
+    /// Hex-encoded signature.
+    pub signature: String,
+    pub payload: SignedPayload,
+}
+
+impl PackageSignature {
+    /// Check the signature against its payload and public key.
+    pub fn verify(&self) -> bool {
+        if self.algorithm != SIGNATURE_ALGORITHM {
+            return false;
+        }
+        let (Ok(public_key), Ok(signature)) =
+            (hex::decode(&self.public_key), hex::decode(&self.signature))
+        else {
+            return false;
+        };
+        UnparsedPublicKey::new(&ED25519, public_key)
+            .verify(&self.payload.to_bytes(), &signature)
+            .is_ok()
+    }
+}
+
+/// The publisher's Ed25519 signing key, stored as PKCS#8.
+pub struct PublisherKey {
+    pair: Ed25519KeyPair,
+}
+
+impl PublisherKey {
+    /// Default key location (`~/.ironclaw/keys/clawhub_ed25519.pk8`).
+    pub fn default_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".ironclaw")
+            .join("keys")
+            .join("clawhub_ed25519.pk8")
+    }
+
+    /// Load the key at `path`, generating and saving a new one if none exists.
+    pub fn load_or_create(path: &Path) -> Result<Self, String> {
+        if path.exists() {
+            let pkcs8 = std::fs::read(path)
+                .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+            let pair = Ed25519KeyPair::from_pkcs8(&pkcs8)
+                .map_err(|e| format!("{} is not an Ed25519 key: {}", path.display(), e))?;
+            return Ok(Self { pair });
+        }
+
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .map_err(|e| format!("failed to generate signing key: {}", e))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(path, pkcs8.as_ref())
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600));
+        }
+        tracing::info!("Created ClawHub publisher key at {}", path.display());
+
+        let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+            .map_err(|e| format!("generated key is invalid: {}", e))?;
+        Ok(Self { pair })
+    }
+
+    /// Hex-encoded public key.
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.pair.public_key().as_ref())
+    }
+
+    /// Short fingerprint for display: the first 16 hex characters of the
+    /// SHA-256 of the public key.
+    pub fn fingerprint(&self) -> String {
+        let digest = Sha256::digest(self.pair.public_key().as_ref());
+        hex::encode(digest)[..16].to_string()
+    }
+
+    /// Sign a payload.
+    pub fn sign(&self, payload: SignedPayload) -> PackageSignature {
+        let signature = self.pair.sign(&payload.to_bytes());
+        PackageSignature {
+            algorithm: SIGNATURE_ALGORITHM.to_string(),
+            public_key: self.public_key_hex(),
+            signature: hex::encode(signature.as_ref()),
+            payload,
+        }
+    }
+}
+
+/// A validated package ready to sign and upload.
+#[derive(Debug, Clone)]
+pub struct PackageBundle {
+    pub manifest: PublishManifest,
+    /// File name of the binary inside the package directory.
+    pub wasm_file: String,
+    pub wasm: Vec<u8>,
+    pub capabilities: Option<Vec<u8>>,
+}
+
+impl PackageBundle {
+    /// Read and validate a package directory.
+    pub async fn from_dir(dir: &Path) -> Result<Self, String> {
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let manifest_json = tokio::fs::read_to_string(&manifest_path)
+            .await
+            .map_err(|e| format!("failed to read {}: {}", manifest_path.display(), e))?;
+        let manifest: PublishManifest = serde_json::from_str(&manifest_json)
+            .map_err(|e| format!("invalid {}: {}", MANIFEST_FILE, e))?;
+        manifest.validate()?;
+
+        let wasm_path = dir.join(format!("{}.wasm", manifest.name));
+        let wasm_path = if wasm_path.is_file() {
+            wasm_path
+        } else {
+            single_wasm_in(dir).await?
+        };
+        let wasm = tokio::fs::read(&wasm_path)
+            .await
+            .map_err(|e| format!("failed to read {}: {}", wasm_path.display(), e))?;
+        if wasm.len() > MAX_WASM_BYTES {
+            return Err(format!(
+                "WASM binary too large ({} bytes, max {} bytes)",
+                wasm.len(),
+                MAX_WASM_BYTES
+            ));
+        }
+        if wasm.len() < 4 || &wasm[..4] != b"\0asm" {
+            return Err(format!(
+                "{} is not a WASM binary (bad magic number)",
+                wasm_path.display()
+            ));
+        }
+
+        let stem = wasm_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(&manifest.name);
+        let mut capabilities = None;
+        for candidate in [
+            dir.join(format!("{}.capabilities.json", stem)),
+            dir.join("capabilities.json"),
+        ] {
+            if candidate.is_file() {
+                let content = tokio::fs::read(&candidate)
+                    .await
+                    .map_err(|e| format!("failed to read {}: {}", candidate.display(), e))?;
+                CapabilitiesFile::from_bytes(&content)
+                    .map_err(|e| format!("invalid {}: {}", candidate.display(), e))?;
+                capabilities = Some(content);
+                break;
+            }
+        }
+
+        Ok(Self {
+            wasm_file: wasm_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            manifest,
+            wasm,
+            capabilities,
+        })
+    }
+
+    /// Release identity and file hashes, as signed.
+    pub fn signed_payload(&self) -> SignedPayload {
+        SignedPayload {
+            name: self.manifest.name.clone(),
+            version: self.manifest.version.clone(),
+            package_type: self.manifest.package_type,
+            wasm_sha256: hex::encode(Sha256::digest(&self.wasm)),
+            capabilities_sha256: self
+                .capabilities
+                .as_ref()
+                .map(|c| hex::encode(Sha256::digest(c))),
+        }
+    }
+
+    /// Sign the bundle with a publisher key.
+    pub fn sign(&self, key: &PublisherKey) -> PackageSignature {
+        key.sign(self.signed_payload())
+    }
+}
+
+/// The only `.wasm` file in `dir`, or an error naming what was found.
+async fn single_wasm_in(dir: &Path) -> Result<PathBuf, String> {
+    let mut entries = tokio::fs::read_dir(dir)
+        .await
+        .map_err(|e| format!("failed to read {}: {}", dir.display(), e))?;
+    let mut found = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if path.extension().is_some_and(|e| e == "wasm") {
+            found.push(path);
+        }
+    }
+    match found.len() {
+        1 => Ok(found.remove(0)),
+        0 => Err(format!("no .wasm file in {}", dir.display())),
+        _ => Err(format!(
+            "several .wasm files in {}; name the one to publish <package name>.wasm",
+            dir.display()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_package(dir: &Path) {
+        std::fs::write(
+            dir.join(MANIFEST_FILE),
+            r#"{
+                "name": "weather",
+                "description": "Current conditions",
+                "version": "1.2.0",
+                "author": "jane"
+            }"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("weather.wasm"), b"\0asm\x01\0\0\0").unwrap();
+        std::fs::write(
+            dir.join("weather.capabilities.json"),
+            r#"{"http": {"allowlist": [{"host": "api.weather.gov"}]}}"#,
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_bundle_sign_and_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        write_package(dir.path());
+        let bundle = PackageBundle::from_dir(dir.path()).await.unwrap();
+        assert_eq!(bundle.wasm_file, "weather.wasm");
+        assert_eq!(bundle.manifest.package_type, PackageType::Tool);
+        assert!(bundle.capabilities.is_some());
+
+        let key_path = dir.path().join("keys/publisher.pk8");
+        let key = PublisherKey::load_or_create(&key_path).unwrap();
+        let signature = bundle.sign(&key);
+        assert!(signature.verify());
+        assert_eq!(signature.payload.version, "1.2.0");
+
+        // Reloading gives the same key.
+        let reloaded = PublisherKey::load_or_create(&key_path).unwrap();
+        assert_eq!(reloaded.public_key_hex(), key.public_key_hex());
+
+        // Any change to the payload breaks the signature.
+        let mut tampered = signature.clone();
+        tampered.payload.wasm_sha256 = hex::encode([0u8; 32]);
+        assert!(!tampered.verify());
+    }
+
+    #[test]
+    fn test_manifest_validation() {
+        let manifest: PublishManifest = serde_json::from_value(serde_json::json!({
+            "name": "weather",
+            "description": "Current conditions",
+            "version": "1.2.0",
+            "author": "jane"
+        }))
+        .unwrap();
+        assert!(manifest.validate().is_ok());
+
+        let bad_name = PublishManifest {
+            name: "Weather Tool".to_string(),
+            ..manifest.clone()
+        };
+        assert!(bad_name.validate().is_err());
+        let bad_version = PublishManifest {
+            version: "1.2".to_string(),
+            ..manifest.clone()
+        };
+        assert!(bad_version.validate().is_err());
+        let hook_pack = PublishManifest {
+            package_type: PackageType::HookPack,
+            ..manifest
+        };
+        assert!(hook_pack.validate().is_err());
+    }
+}
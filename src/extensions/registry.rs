@@ -3,10 +3,13 @@
 //! The registry holds well-known MCP servers and WASM tools that can be installed
 //! via conversational commands. Online discoveries are cached here too.
 
+use std::collections::HashMap;
+
 use tokio::sync::RwLock;
 
 use crate::extensions::{
-    AuthHint, ExtensionKind, ExtensionSource, RegistryEntry, ResultSource, SearchResult,
+    AuthHint, ExtensionError, ExtensionKind, ExtensionSource, RegistryEntry, ResultSource,
+    SearchResult,
 };
 
 /// Curated extension registry with fuzzy search.
@@ -96,6 +99,35 @@ impl ExtensionRegistry {
         cache.iter().find(|e| e.name == name).cloned()
     }
 
+    /// Resolve `name` and everything it depends on into install order:
+    /// dependencies first, `name` last. Fails on unknown dependencies and
+    /// on cycles.
+    pub async fn install_order(&self, name: &str) -> Result<Vec<RegistryEntry>, ExtensionError> {
+        let mut entries: HashMap<String, RegistryEntry> = HashMap::new();
+        let mut queue = vec![(name.to_string(), None::<String>)];
+        while let Some((next, parent)) = queue.pop() {
+            if entries.contains_key(&next) {
+                continue;
+            }
+            let entry = self.get(&next).await.ok_or_else(|| match parent {
+                Some(ref parent) => ExtensionError::NotFound(format!(
+                    "'{}' (needed by '{}') is not in the registry",
+                    next, parent
+                )),
+                None => ExtensionError::NotFound(format!("'{}' is not in the registry", next)),
+            })?;
+            for dep in &entry.dependencies {
+                queue.push((dep.clone(), Some(next.clone())));
+            }
+            entries.insert(next, entry);
+        }
+
+        Ok(dependency_order(name, &entries)?
+            .into_iter()
+            .filter_map(|n| entries.remove(&n))
+            .collect())
+    }
+
     /// Add discovered entries to the cache.
     pub async fn cache_discovered(&self, entries: Vec<RegistryEntry>) {
         let mut cache = self.discovery_cache.write().await;
@@ -174,6 +206,7 @@ fn builtin_entries() -> Vec<RegistryEntry> {
                 url: "https://mcp.notion.com/mcp".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         },
         RegistryEntry {
             name: "linear".to_string(),
@@ -193,6 +226,7 @@ fn builtin_entries() -> Vec<RegistryEntry> {
                 url: "https://mcp.linear.app".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         },
         RegistryEntry {
             name: "google-calendar".to_string(),
@@ -211,6 +245,7 @@ fn builtin_entries() -> Vec<RegistryEntry> {
                 url: "https://mcp.google.com/calendar".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         },
         RegistryEntry {
             name: "google-drive".to_string(),
@@ -229,6 +264,7 @@ fn builtin_entries() -> Vec<RegistryEntry> {
                 url: "https://mcp.google.com/drive".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         },
         RegistryEntry {
             name: "github".to_string(),
@@ -248,6 +284,7 @@ fn builtin_entries() -> Vec<RegistryEntry> {
                 url: "https://mcp.github.com".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         },
         RegistryEntry {
             name: "slack".to_string(),
@@ -267,6 +304,7 @@ fn builtin_entries() -> Vec<RegistryEntry> {
                 url: "https://mcp.slack.com".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         },
         RegistryEntry {
             name: "sentry".to_string(),
@@ -286,6 +324,7 @@ fn builtin_entries() -> Vec<RegistryEntry> {
                 url: "https://mcp.sentry.dev/sse".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         },
         RegistryEntry {
             name: "stripe".to_string(),
@@ -305,6 +344,7 @@ fn builtin_entries() -> Vec<RegistryEntry> {
                 url: "https://mcp.stripe.com".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         },
         RegistryEntry {
             name: "cloudflare".to_string(),
@@ -324,6 +364,7 @@ fn builtin_entries() -> Vec<RegistryEntry> {
                 url: "https://mcp.cloudflare.com/sse".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         },
         RegistryEntry {
             name: "asana".to_string(),
@@ -341,6 +382,7 @@ fn builtin_entries() -> Vec<RegistryEntry> {
                 url: "https://mcp.asana.com".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         },
         RegistryEntry {
             name: "intercom".to_string(),
@@ -359,14 +401,54 @@ fn builtin_entries() -> Vec<RegistryEntry> {
                 url: "https://mcp.intercom.com".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         },
     ]
 }
 
+/// Depth-first topological order of `root` and its dependencies, root
+/// last. Every name reachable from `root` must be in `entries`.
+fn dependency_order(
+    root: &str,
+    entries: &HashMap<String, RegistryEntry>,
+) -> Result<Vec<String>, ExtensionError> {
+    fn visit(
+        name: &str,
+        entries: &HashMap<String, RegistryEntry>,
+        path: &mut Vec<String>,
+        order: &mut Vec<String>,
+    ) -> Result<(), ExtensionError> {
+        if order.iter().any(|n| n == name) {
+            return Ok(());
+        }
+        if let Some(start) = path.iter().position(|n| n == name) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(name.to_string());
+            return Err(ExtensionError::DependencyCycle(cycle.join(" -> ")));
+        }
+        let entry = entries.get(name).ok_or_else(|| {
+            ExtensionError::NotFound(format!("'{}' is not in the registry", name))
+        })?;
+        path.push(name.to_string());
+        for dep in &entry.dependencies {
+            visit(dep, entries, path, order)?;
+        }
+        path.pop();
+        order.push(name.to_string());
+        Ok(())
+    }
+
+    let mut order = Vec::new();
+    visit(root, entries, &mut Vec::new(), &mut order)?;
+    Ok(order)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::extensions::registry::{ExtensionRegistry, score_entry};
-    use crate::extensions::{AuthHint, ExtensionKind, ExtensionSource, RegistryEntry};
+    use crate::extensions::{
+        AuthHint, ExtensionError, ExtensionKind, ExtensionSource, RegistryEntry,
+    };
 
     #[test]
     fn test_score_exact_name_match() {
@@ -380,6 +462,7 @@ mod tests {
                 url: "https://example.com".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         };
 
         let score = score_entry(&entry, &["notion".to_string()]);
@@ -402,6 +485,7 @@ mod tests {
                 url: "https://example.com".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         };
 
         let score = score_entry(&entry, &["calendar".to_string()]);
@@ -424,6 +508,7 @@ mod tests {
                 url: "https://example.com".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         };
 
         let score = score_entry(&entry, &["wiki".to_string()]);
@@ -446,6 +531,7 @@ mod tests {
                 url: "https://example.com".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         };
 
         let score = score_entry(&entry, &["xyzfoobar".to_string()]);
@@ -509,6 +595,7 @@ mod tests {
                 url: "https://custom.example.com".to_string(),
             },
             auth_hint: AuthHint::Dcr,
+            dependencies: Vec::new(),
         };
 
         registry.cache_discovered(vec![discovered]).await;
@@ -534,6 +621,7 @@ mod tests {
                 url: "https://example.com".to_string(),
             },
             auth_hint: AuthHint::None,
+            dependencies: Vec::new(),
         };
 
         registry.cache_discovered(vec![entry.clone()]).await;
@@ -542,4 +630,65 @@ mod tests {
         let results = registry.search("dup").await;
         assert_eq!(results.len(), 1, "Should not duplicate cached entries");
     }
+
+    fn tool_with_deps(name: &str, deps: &[&str]) -> RegistryEntry {
+        RegistryEntry {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            kind: ExtensionKind::WasmTool,
+            description: "Test".to_string(),
+            keywords: vec![],
+            source: ExtensionSource::WasmDownload {
+                wasm_url: format!("https://example.com/{}.wasm", name),
+                capabilities_url: None,
+            },
+            auth_hint: AuthHint::None,
+            dependencies: deps.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_install_order_puts_dependencies_first() {
+        let registry = ExtensionRegistry::new();
+        registry
+            .cache_discovered(vec![
+                tool_with_deps("gh-triage", &["gh-labels", "github"]),
+                tool_with_deps("gh-labels", &["github"]),
+            ])
+            .await;
+
+        let order: Vec<String> = registry
+            .install_order("gh-triage")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(order, vec!["github", "gh-labels", "gh-triage"]);
+
+        let plain = registry.install_order("notion").await.unwrap();
+        assert_eq!(plain.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_install_order_rejects_cycles_and_unknown_deps() {
+        let registry = ExtensionRegistry::new();
+        registry
+            .cache_discovered(vec![
+                tool_with_deps("a", &["b"]),
+                tool_with_deps("b", &["c"]),
+                tool_with_deps("c", &["a"]),
+                tool_with_deps("lonely", &["missing"]),
+            ])
+            .await;
+
+        match registry.install_order("a").await {
+            Err(ExtensionError::DependencyCycle(path)) => assert_eq!(path, "a -> b -> c -> a"),
+            other => panic!("expected cycle, got {:?}", other.map(|v| v.len())),
+        }
+        match registry.install_order("lonely").await {
+            Err(ExtensionError::NotFound(msg)) => assert!(msg.contains("needed by 'lonely'")),
+            other => panic!("expected not found, got {:?}", other.map(|v| v.len())),
+        }
+    }
 }
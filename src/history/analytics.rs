@@ -2,10 +2,14 @@
 //!
 //! Analytics methods are implemented directly on [`Store`] for convenience.
 
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 
 use crate::error::DatabaseError;
-use crate::history::Store;
+use crate::history::{
+    ACTIVITY_LIST_LIMIT, ActivityStats, ConversationActivity, JobActivity, LlmUsage, MemoryChange,
+    Store, ToolUsage,
+};
 
 /// Statistics about jobs.
 #[derive(Debug, Default)]
@@ -204,6 +208,157 @@ impl Store {
 
         Ok(entries)
     }
+
+    /// Aggregate activity since a point in time, optionally for one user.
+    pub async fn activity_since(
+        &self,
+        user_id: Option<&str>,
+        since: DateTime<Utc>,
+    ) -> Result<ActivityStats, DatabaseError> {
+        let conn = self.conn().await?;
+        let limit = ACTIVITY_LIST_LIMIT;
+
+        let rows = conn
+            .query(
+                r#"
+                SELECT
+                    a.tool_name,
+                    COUNT(*) as calls,
+                    COUNT(*) FILTER (WHERE a.success = false) as failures
+                FROM job_actions a
+                JOIN agent_jobs j ON j.id = a.job_id
+                WHERE a.created_at >= $2 AND ($1::TEXT IS NULL OR j.user_id = $1)
+                GROUP BY a.tool_name
+                ORDER BY calls DESC
+                LIMIT $3
+                "#,
+                &[&user_id, &since, &limit],
+            )
+            .await?;
+        let tool_usage = rows
+            .iter()
+            .map(|row| ToolUsage {
+                tool_name: row.get("tool_name"),
+                calls: row.get("calls"),
+                failures: row.get("failures"),
+            })
+            .collect();
+
+        let rows = conn
+            .query(
+                r#"
+                SELECT id, title, status, created_at, completed_at, actual_cost
+                FROM agent_jobs
+                WHERE (created_at >= $2 OR completed_at >= $2)
+                  AND ($1::TEXT IS NULL OR user_id = $1)
+                ORDER BY COALESCE(completed_at, created_at) DESC
+                LIMIT $3
+                "#,
+                &[&user_id, &since, &limit],
+            )
+            .await?;
+        let jobs = rows
+            .iter()
+            .map(|row| JobActivity {
+                id: row.get("id"),
+                title: row.get("title"),
+                status: row.get("status"),
+                created_at: row.get("created_at"),
+                completed_at: row.get("completed_at"),
+                actual_cost: row.get("actual_cost"),
+            })
+            .collect();
+
+        let row = conn
+            .query_one(
+                r#"
+                SELECT
+                    COUNT(*) as calls,
+                    COALESCE(SUM(l.input_tokens), 0)::BIGINT as input_tokens,
+                    COALESCE(SUM(l.output_tokens), 0)::BIGINT as output_tokens,
+                    SUM(l.cost) as cost
+                FROM llm_calls l
+                LEFT JOIN agent_jobs j ON j.id = l.job_id
+                LEFT JOIN conversations c ON c.id = l.conversation_id
+                WHERE l.created_at >= $2
+                  AND ($1::TEXT IS NULL OR j.user_id = $1 OR c.user_id = $1)
+                "#,
+                &[&user_id, &since],
+            )
+            .await?;
+        let llm = LlmUsage {
+            calls: row.get("calls"),
+            input_tokens: row.get("input_tokens"),
+            output_tokens: row.get("output_tokens"),
+            cost: row.get::<_, Option<Decimal>>("cost").unwrap_or_default(),
+        };
+
+        let rows = conn
+            .query(
+                r#"
+                SELECT path, created_at >= $2 as created, updated_at
+                FROM memory_documents
+                WHERE updated_at >= $2 AND ($1::TEXT IS NULL OR user_id = $1)
+                ORDER BY updated_at DESC
+                LIMIT $3
+                "#,
+                &[&user_id, &since, &limit],
+            )
+            .await?;
+        let memory_changes = rows
+            .iter()
+            .map(|row| MemoryChange {
+                path: row.get("path"),
+                created: row.get("created"),
+                updated_at: row.get("updated_at"),
+            })
+            .collect();
+
+        let rows = conn
+            .query(
+                r#"
+                SELECT
+                    c.id,
+                    c.channel,
+                    COUNT(m.id) as messages,
+                    (
+                        SELECT LEFT(m2.content, 120)
+                        FROM conversation_messages m2
+                        WHERE m2.conversation_id = c.id AND m2.role = 'user'
+                          AND m2.created_at >= $2
+                        ORDER BY m2.created_at
+                        LIMIT 1
+                    ) as preview,
+                    c.last_activity
+                FROM conversations c
+                JOIN conversation_messages m ON m.conversation_id = c.id
+                WHERE m.created_at >= $2 AND ($1::TEXT IS NULL OR c.user_id = $1)
+                GROUP BY c.id, c.channel, c.last_activity
+                ORDER BY messages DESC
+                LIMIT $3
+                "#,
+                &[&user_id, &since, &limit],
+            )
+            .await?;
+        let conversations = rows
+            .iter()
+            .map(|row| ConversationActivity {
+                id: row.get("id"),
+                channel: row.get("channel"),
+                messages: row.get("messages"),
+                preview: row.get("preview"),
+                last_activity: row.get("last_activity"),
+            })
+            .collect();
+
+        Ok(ActivityStats {
+            tool_usage,
+            jobs,
+            llm,
+            memory_changes,
+            conversations,
+        })
+    }
 }
 
 /// Estimation accuracy metrics.
@@ -14,6 +14,7 @@ pub use analytics::{JobStats, ToolStats};
 #[cfg(feature = "postgres")]
 pub use store::Store;
 pub use store::{
-    ConversationMessage, ConversationSummary, JobEventRecord, LlmCallRecord, SandboxJobRecord,
-    SandboxJobSummary, SettingRow,
+    ACTIVITY_LIST_LIMIT, ActivityStats, ConversationActivity, ConversationMessage,
+    ConversationSummary, EgressRecord, JobActivity, JobEventRecord, LlmCallRecord, LlmUsage,
+    MemoryChange, SandboxJobRecord, SandboxJobSummary, SettingRow, ToolUsage,
 };
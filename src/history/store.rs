@@ -24,6 +24,10 @@ pub struct LlmCallRecord<'a> {
     pub model: &'a str,
     pub input_tokens: u32,
     pub output_tokens: u32,
+    /// Input tokens served from the provider's prompt cache.
+    pub cache_read_tokens: u32,
+    /// Input tokens written to the provider's prompt cache.
+    pub cache_write_tokens: u32,
     pub cost: Decimal,
     pub purpose: Option<&'a str>,
 }
@@ -214,6 +218,7 @@ impl Store {
                     state,
                     user_id: row.get::<_, String>("user_id"),
                     conversation_id: row.get("conversation_id"),
+                    session_id: None,
                     title: row.get("title"),
                     description: row.get("description"),
                     category: row.get("category"),
@@ -234,6 +239,10 @@ impl Store {
                     metadata: serde_json::Value::Null,
                     total_tokens_used: 0,
                     max_tokens: 0,
+                    working_dir: None,
+                    timezone: None,
+                    contact: None,
+                    memory: None,
                 }))
             }
             None => Ok(None),
@@ -374,8 +383,8 @@ impl Store {
 
         conn.execute(
             r#"
-            INSERT INTO llm_calls (id, job_id, conversation_id, provider, model, input_tokens, output_tokens, cost, purpose)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            INSERT INTO llm_calls (id, job_id, conversation_id, provider, model, input_tokens, output_tokens, cache_read_tokens, cache_write_tokens, cost, purpose)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
             "#,
             &[
                 &id,
@@ -385,6 +394,8 @@ impl Store {
                 &record.model,
                 &(record.input_tokens as i32),
                 &(record.output_tokens as i32),
+                &(record.cache_read_tokens as i32),
+                &(record.cache_write_tokens as i32),
                 &record.cost,
                 &record.purpose,
             ],
@@ -478,6 +489,72 @@ pub struct SandboxJobSummary {
     pub interrupted: usize,
 }
 
+// ==================== Activity ====================
+
+/// Aggregated agent activity over a time window, for activity reports.
+#[derive(Debug, Clone, Default)]
+pub struct ActivityStats {
+    /// Tool calls grouped by tool, most used first.
+    pub tool_usage: Vec<ToolUsage>,
+    /// Jobs created or finished in the window, newest first.
+    pub jobs: Vec<JobActivity>,
+    pub llm: LlmUsage,
+    /// Memory documents created or edited in the window, newest first.
+    pub memory_changes: Vec<MemoryChange>,
+    /// Conversations active in the window, busiest first.
+    pub conversations: Vec<ConversationActivity>,
+}
+
+/// Call counts for one tool.
+#[derive(Debug, Clone)]
+pub struct ToolUsage {
+    pub tool_name: String,
+    pub calls: i64,
+    pub failures: i64,
+}
+
+/// A job touched during the window.
+#[derive(Debug, Clone)]
+pub struct JobActivity {
+    pub id: Uuid,
+    pub title: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub actual_cost: Option<Decimal>,
+}
+
+/// LLM call totals.
+#[derive(Debug, Clone, Default)]
+pub struct LlmUsage {
+    pub calls: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost: Decimal,
+}
+
+/// A memory document created or edited during the window.
+#[derive(Debug, Clone)]
+pub struct MemoryChange {
+    pub path: String,
+    pub created: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A conversation active during the window.
+#[derive(Debug, Clone)]
+pub struct ConversationActivity {
+    pub id: Uuid,
+    pub channel: String,
+    pub messages: i64,
+    /// First user message in the window, truncated.
+    pub preview: Option<String>,
+    pub last_activity: DateTime<Utc>,
+}
+
+/// Max rows returned per activity list.
+pub const ACTIVITY_LIST_LIMIT: i64 = 20;
+
 #[cfg(feature = "postgres")]
 impl Store {
     /// Insert a new sandbox job into `agent_jobs`.
@@ -826,6 +903,105 @@ impl Store {
     }
 }
 
+// ==================== Sandbox Egress ====================
+
+/// One outbound request a sandboxed command made through the proxy.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EgressRecord {
+    pub id: Uuid,
+    /// The job the command ran for, when known.
+    pub job_id: Option<Uuid>,
+    pub method: String,
+    pub host: String,
+    /// Request path without the query string, which may carry credentials.
+    pub path: String,
+    /// `allowed`, `denied`, or `cached`.
+    pub decision: String,
+    /// Why a request was denied or did not complete.
+    pub reason: Option<String>,
+    /// Upstream response status, when the request was forwarded.
+    pub status: Option<i32>,
+    pub request_bytes: i64,
+    pub response_bytes: i64,
+    /// Whether the proxy added a credential to the request.
+    pub credential_injected: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(feature = "postgres")]
+impl Store {
+    /// Persist one egress record.
+    pub async fn record_egress(&self, record: &EgressRecord) -> Result<(), DatabaseError> {
+        let conn = self.conn().await?;
+        conn.execute(
+            r#"
+            INSERT INTO sandbox_egress (
+                id, job_id, method, host, path, decision, reason, status,
+                request_bytes, response_bytes, credential_injected, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            "#,
+            &[
+                &record.id,
+                &record.job_id,
+                &record.method,
+                &record.host,
+                &record.path,
+                &record.decision,
+                &record.reason,
+                &record.status,
+                &record.request_bytes,
+                &record.response_bytes,
+                &record.credential_injected,
+                &record.created_at,
+            ],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// The most recent `limit` egress records, oldest first, optionally for
+    /// one job.
+    pub async fn list_egress(
+        &self,
+        job_id: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<EgressRecord>, DatabaseError> {
+        let conn = self.conn().await?;
+        let rows = conn
+            .query(
+                r#"
+                SELECT id, job_id, method, host, path, decision, reason, status,
+                       request_bytes, response_bytes, credential_injected, created_at
+                FROM sandbox_egress
+                WHERE $1::uuid IS NULL OR job_id = $1
+                ORDER BY created_at DESC
+                LIMIT $2
+                "#,
+                &[&job_id, &limit],
+            )
+            .await?;
+        let mut records: Vec<EgressRecord> = rows
+            .iter()
+            .map(|r| EgressRecord {
+                id: r.get("id"),
+                job_id: r.get("job_id"),
+                method: r.get("method"),
+                host: r.get("host"),
+                path: r.get("path"),
+                decision: r.get("decision"),
+                reason: r.get("reason"),
+                status: r.get("status"),
+                request_bytes: r.get("request_bytes"),
+                response_bytes: r.get("response_bytes"),
+                credential_injected: r.get("credential_injected"),
+                created_at: r.get("created_at"),
+            })
+            .collect();
+        records.reverse();
+        Ok(records)
+    }
+}
+
 // ==================== Routines ====================
 
 #[cfg(feature = "postgres")]
@@ -940,6 +1116,18 @@ impl Store {
         rows.iter().map(row_to_routine).collect()
     }
 
+    /// List all enabled file-watch routines.
+    pub async fn list_file_routines(&self) -> Result<Vec<Routine>, DatabaseError> {
+        let conn = self.conn().await?;
+        let rows = conn
+            .query(
+                "SELECT * FROM routines WHERE enabled AND trigger_type = 'file'",
+                &[],
+            )
+            .await?;
+        rows.iter().map(row_to_routine).collect()
+    }
+
     /// List all enabled cron routines whose next_fire_at <= now.
     pub async fn list_due_cron_routines(&self) -> Result<Vec<Routine>, DatabaseError> {
         let conn = self.conn().await?;
@@ -1494,6 +1682,22 @@ impl Store {
             })
             .collect())
     }
+
+    /// Delete messages from a conversation. Returns how many were deleted.
+    pub async fn delete_conversation_messages(
+        &self,
+        conversation_id: Uuid,
+        message_ids: &[Uuid],
+    ) -> Result<u64, DatabaseError> {
+        let conn = self.conn().await?;
+        let deleted = conn
+            .execute(
+                "DELETE FROM conversation_messages WHERE conversation_id = $1 AND id = ANY($2)",
+                &[&conversation_id, &message_ids],
+            )
+            .await?;
+        Ok(deleted)
+    }
 }
 
 #[cfg(feature = "postgres")]
@@ -1786,6 +1990,8 @@ mod tests {
             model: "gpt-4",
             input_tokens: 1500,
             output_tokens: 300,
+            cache_read_tokens: 1200,
+            cache_write_tokens: 0,
             cost: Decimal::new(42, 4), // 0.0042
             purpose: Some("routing"),
         };
@@ -1796,6 +2002,7 @@ mod tests {
         assert_eq!(record.model, "gpt-4");
         assert_eq!(record.input_tokens, 1500);
         assert_eq!(record.output_tokens, 300);
+        assert_eq!(record.cache_read_tokens, 1200);
         assert_eq!(record.cost, Decimal::new(42, 4));
         assert_eq!(record.purpose, Some("routing"));
     }
@@ -1809,6 +2016,8 @@ mod tests {
             model: "claude-3",
             input_tokens: 0,
             output_tokens: 0,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
             cost: Decimal::ZERO,
             purpose: None,
         };
@@ -1827,6 +2036,8 @@ mod tests {
             model: "llama3",
             input_tokens: 10,
             output_tokens: 20,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
             cost: Decimal::ZERO,
             purpose: None,
         };
@@ -1845,6 +2056,8 @@ mod tests {
             model: "gemini-pro",
             input_tokens: 100,
             output_tokens: 50,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
             cost: Decimal::new(1, 3),
             purpose: Some("tool_call"),
         };
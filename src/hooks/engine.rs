@@ -459,7 +459,7 @@ impl HookEngine {
         method: &str,
         event: &HookEvent,
     ) -> Result<HookOutcome, HookError> {
-        let client = reqwest::Client::new();
+        let client = crate::outbound::client();
         let request = match method.to_uppercase().as_str() {
             "POST" => client.post(url).json(event),
             "PUT" => client.put(url).json(event),
@@ -75,7 +75,7 @@ impl GmailPubSubHandler {
     ) -> Self {
         Self {
             config,
-            client: reqwest::Client::new(),
+            client: crate::outbound::client(),
             notification_tx,
             last_history_id: tokio::sync::RwLock::new(None),
         }
@@ -9,16 +9,21 @@
 //! - `transformResponse` — Transform the final response text
 //! - `onMessage` — When a message is received (already handled by routines)
 //! - `transcribeAudio` — Transcribe audio content
+//!
+//! Hooks ship built in ([`bundled`]), are added by hand, or come from hook
+//! packs installed from ClawHub ([`packs`]).
 
 pub mod bundled;
 mod engine;
 pub mod gmail_pubsub;
+pub mod packs;
 pub mod transcribe;
 mod types;
 pub mod webhooks;
 
 pub use bundled::{all_bundled_hooks, register_bundled_hooks};
 pub use engine::HookEngine;
+pub use packs::{HookPack, HookPackStore, PackVerification, register_installed_packs};
 pub use transcribe::{TranscriptionHookResult, is_supported_audio_mime, run_transcribe_audio};
 pub use types::{
     Hook, HookAction, HookContext, HookError, HookEvent, HookOutcome, HookPriority,
@@ -0,0 +1,487 @@
+//! Hook packs: shareable bundles of hooks and automation rules.
+//!
+//! A pack is a JSON manifest published on ClawHub (package type `hook_pack`
+//! or `automation`) that bundles lifecycle hooks, routines, or both, so a
+//! common pattern like a PII scrubber or GitHub triage can be installed in
+//! one command:
+//!
+//! ```json
+//! {
+//!   "name": "pii-scrubber",
+//!   "version": "1.0.0",
+//!   "description": "Redact emails and phone numbers",
+//!   "hooks": [
+//!     { "name": "redact", "hook_type": "beforeOutbound",
+//!       "action": { "inline": { "code": "{{content}}" } } }
+//!   ],
+//!   "routines": [
+//!     { "name": "weekly-audit", "trigger": { "type": "cron", "schedule": "0 0 9 * * MON" },
+//!       "action": { "type": "lightweight", "prompt": "Review redactions" } }
+//!   ]
+//! }
+//! ```
+//!
+//! Installed manifests live in `~/.ironclaw/hooks/<name>.json` next to a
+//! `<name>.sha256` record of the verified bytes. Everything a pack installs
+//! is named `<pack>:<item>` so it can be told apart and removed together.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::agent::routine::{
+    NotifyConfig, Routine, RoutineAction, RoutineGuardrails, Trigger, next_cron_fire,
+};
+use crate::hooks::types::{Hook, HookAction, HookError, HookPriority, HookSource, HookType};
+
+/// A hook as published in a pack.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackHook {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub hook_type: HookType,
+    pub action: HookAction,
+    #[serde(default)]
+    pub priority: HookPriority,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_timeout_ms() -> u64 {
+    5000
+}
+
+/// A routine as published in a pack.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackRoutine {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub trigger: Trigger,
+    pub action: RoutineAction,
+    /// Channel to notify on (default: all channels).
+    #[serde(default)]
+    pub channel: Option<String>,
+}
+
+/// A shareable bundle of hooks and routines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookPack {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub hooks: Vec<PackHook>,
+    #[serde(default)]
+    pub routines: Vec<PackRoutine>,
+}
+
+impl HookPack {
+    /// Parse and validate a manifest.
+    pub fn parse(bytes: &[u8]) -> Result<Self, HookError> {
+        let pack: Self = serde_json::from_slice(bytes).map_err(|e| invalid(e.to_string()))?;
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    fn validate(&self) -> Result<(), HookError> {
+        if !valid_name(&self.name) {
+            return Err(invalid(format!(
+                "pack name '{}' must be lowercase letters, digits, '-' or '_'",
+                self.name
+            )));
+        }
+        if self.version.trim().is_empty() {
+            return Err(invalid("missing version".to_string()));
+        }
+        if self.hooks.is_empty() && self.routines.is_empty() {
+            return Err(invalid("pack contains no hooks or routines".to_string()));
+        }
+
+        let mut seen = HashSet::new();
+        let names = self
+            .hooks
+            .iter()
+            .map(|h| &h.name)
+            .chain(self.routines.iter().map(|r| &r.name));
+        for name in names {
+            if !valid_name(name) {
+                return Err(invalid(format!("invalid item name '{}'", name)));
+            }
+            if !seen.insert(name) {
+                return Err(invalid(format!("duplicate item name '{}'", name)));
+            }
+        }
+
+        for routine in &self.routines {
+            if let Trigger::Cron { schedule, timezone } = &routine.trigger {
+                next_cron_fire(schedule, timezone.as_deref())
+                    .map_err(|e| invalid(format!("routine '{}': {}", routine.name, e)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Name an item of this pack gets once installed.
+    pub fn item_name(&self, item: &str) -> String {
+        format!("{}:{}", self.name, item)
+    }
+
+    /// The pack's hooks, namespaced and attributed to the pack.
+    pub fn to_hooks(&self) -> Vec<Hook> {
+        self.hooks
+            .iter()
+            .map(|h| Hook {
+                name: self.item_name(&h.name),
+                description: h.description.clone(),
+                hook_type: h.hook_type,
+                action: h.action.clone(),
+                priority: h.priority,
+                source: HookSource::Plugin {
+                    name: self.name.clone(),
+                },
+                enabled: true,
+                timeout_ms: h.timeout_ms,
+            })
+            .collect()
+    }
+
+    /// The pack's routines for `user_id`, ready to store.
+    pub fn to_routines(&self, user_id: &str) -> Vec<Routine> {
+        let now = Utc::now();
+        self.routines
+            .iter()
+            .map(|r| {
+                let next_fire_at = match &r.trigger {
+                    Trigger::Cron { schedule, timezone } => {
+                        next_cron_fire(schedule, timezone.as_deref()).unwrap_or(None)
+                    }
+                    _ => None,
+                };
+                Routine {
+                    id: Uuid::new_v4(),
+                    name: self.item_name(&r.name),
+                    description: r.description.clone(),
+                    user_id: user_id.to_string(),
+                    enabled: true,
+                    trigger: r.trigger.clone(),
+                    action: r.action.clone(),
+                    guardrails: RoutineGuardrails::default(),
+                    notify: NotifyConfig {
+                        channel: r.channel.clone(),
+                        user: user_id.to_string(),
+                        ..Default::default()
+                    },
+                    last_run_at: None,
+                    next_fire_at,
+                    run_count: 0,
+                    consecutive_failures: 0,
+                    state: serde_json::json!({}),
+                    created_at: now,
+                    updated_at: now,
+                }
+            })
+            .collect()
+    }
+
+    /// One line per item saying what it will be able to do, for review
+    /// before install. Lines for items that run commands or reach the
+    /// network start with `!`.
+    pub fn review(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for hook in &self.hooks {
+            let (flag, what) = match &hook.action {
+                HookAction::Shell { command } => ("!", format!("runs shell command `{}`", command)),
+                HookAction::Http { url, method } => ("!", format!("calls {} {}", method, url)),
+                HookAction::Webhook { url } => ("!", format!("posts to {}", url)),
+                HookAction::Inline { .. } => (" ", "transforms content inline".to_string()),
+            };
+            lines.push(format!(
+                "{} hook {} on {}: {}",
+                flag,
+                self.item_name(&hook.name),
+                hook.hook_type,
+                what
+            ));
+        }
+        for routine in &self.routines {
+            let (flag, what) = match &routine.action {
+                RoutineAction::FullJob { .. } => ("!", "runs a job with tool access"),
+                RoutineAction::Lightweight { .. } => (" ", "makes an LLM call"),
+                RoutineAction::Report { .. } => (" ", "compiles an activity report"),
+                RoutineAction::Tool { .. } => ("!", "calls a tool directly"),
+            };
+            lines.push(format!(
+                "{} routine {} ({} trigger): {}",
+                flag,
+                self.item_name(&routine.name),
+                routine.trigger.type_tag(),
+                what
+            ));
+        }
+        lines
+    }
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+fn invalid(reason: String) -> HookError {
+    HookError::RegistrationFailed {
+        reason: format!("invalid hook pack: {}", reason),
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Outcome of checking an installed pack against its install record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackVerification {
+    /// Manifest parses and matches the recorded hash.
+    Ok { version: String },
+    /// Manifest was changed after install.
+    Modified { expected: String, actual: String },
+    /// No hash was recorded at install time.
+    Unrecorded,
+    /// Manifest no longer parses or validates.
+    Invalid(String),
+}
+
+/// Installed hook packs on disk.
+pub struct HookPackStore {
+    dir: PathBuf,
+}
+
+impl HookPackStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// `~/.ironclaw/hooks`.
+    pub fn default_dir() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".ironclaw")
+            .join("hooks")
+    }
+
+    fn manifest_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    fn hash_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.sha256", name))
+    }
+
+    /// Store a verified manifest and record its hash. Returns the path.
+    pub fn install(&self, pack: &HookPack, bytes: &[u8]) -> std::io::Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.manifest_path(&pack.name);
+        std::fs::write(&path, bytes)?;
+        std::fs::write(self.hash_path(&pack.name), sha256_hex(bytes))?;
+        Ok(path)
+    }
+
+    /// Remove a pack's files. Returns false if it was not installed.
+    pub fn remove(&self, name: &str) -> std::io::Result<bool> {
+        let path = self.manifest_path(name);
+        if !path.exists() {
+            return Ok(false);
+        }
+        std::fs::remove_file(path)?;
+        let hash = self.hash_path(name);
+        if hash.exists() {
+            std::fs::remove_file(hash)?;
+        }
+        Ok(true)
+    }
+
+    /// Read an installed pack.
+    pub fn get(&self, name: &str) -> Result<Option<HookPack>, HookError> {
+        match std::fs::read(self.manifest_path(name)) {
+            Ok(bytes) => HookPack::parse(&bytes).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(invalid(e.to_string())),
+        }
+    }
+
+    /// All installed packs that still parse, sorted by name. Broken
+    /// manifests are skipped with a warning.
+    pub fn list(&self) -> Vec<HookPack> {
+        let mut packs: Vec<HookPack> = manifest_names(&self.dir)
+            .into_iter()
+            .filter_map(|name| match self.get(&name) {
+                Ok(pack) => pack,
+                Err(e) => {
+                    tracing::warn!(pack = %name, "Skipping hook pack: {}", e);
+                    None
+                }
+            })
+            .collect();
+        packs.sort_by(|a, b| a.name.cmp(&b.name));
+        packs
+    }
+
+    /// Check an installed pack against the hash recorded at install.
+    pub fn verify(&self, name: &str) -> std::io::Result<PackVerification> {
+        let bytes = std::fs::read(self.manifest_path(name))?;
+        let pack = match HookPack::parse(&bytes) {
+            Ok(pack) => pack,
+            Err(e) => return Ok(PackVerification::Invalid(e.to_string())),
+        };
+        let expected = match std::fs::read_to_string(self.hash_path(name)) {
+            Ok(hash) => hash.trim().to_string(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(PackVerification::Unrecorded);
+            }
+            Err(e) => return Err(e),
+        };
+        let actual = sha256_hex(&bytes);
+        if actual == expected {
+            Ok(PackVerification::Ok {
+                version: pack.version,
+            })
+        } else {
+            Ok(PackVerification::Modified { expected, actual })
+        }
+    }
+}
+
+fn manifest_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let path = e.path();
+            (path.extension().is_some_and(|ext| ext == "json"))
+                .then(|| path.file_stem()?.to_str().map(String::from))
+                .flatten()
+        })
+        .collect()
+}
+
+/// Register the hooks of every installed pack with `engine`.
+pub async fn register_installed_packs(engine: &crate::hooks::HookEngine, store: &HookPackStore) {
+    for pack in store.list() {
+        for hook in pack.to_hooks() {
+            if let Err(e) = engine.register(hook.clone()).await {
+                tracing::warn!(hook = hook.name, error = %e, "Failed to register pack hook");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> serde_json::Value {
+        serde_json::json!({
+            "name": "github-triage",
+            "version": "1.2.0",
+            "description": "Label and route new issues",
+            "hooks": [{
+                "name": "notify",
+                "hook_type": "beforeOutbound",
+                "action": {"webhook": {"url": "https://example.com/hook"}}
+            }],
+            "routines": [{
+                "name": "daily",
+                "trigger": {"type": "cron", "schedule": "0 0 9 * * *"},
+                "action": {"type": "lightweight", "prompt": "Triage new issues"},
+                "channel": "slack"
+            }]
+        })
+    }
+
+    #[test]
+    fn test_parse_namespaces_items() {
+        let pack = HookPack::parse(manifest().to_string().as_bytes()).unwrap();
+
+        let hooks = pack.to_hooks();
+        assert_eq!(hooks[0].name, "github-triage:notify");
+        assert_eq!(hooks[0].priority, HookPriority::Normal);
+        assert_eq!(
+            hooks[0].source,
+            HookSource::Plugin {
+                name: "github-triage".to_string()
+            }
+        );
+
+        let routines = pack.to_routines("default");
+        assert_eq!(routines[0].name, "github-triage:daily");
+        assert_eq!(routines[0].notify.channel.as_deref(), Some("slack"));
+        assert!(routines[0].next_fire_at.is_some());
+
+        let review = pack.review();
+        assert_eq!(
+            review[0],
+            "! hook github-triage:notify on beforeOutbound: posts to https://example.com/hook"
+        );
+        assert_eq!(
+            review[1],
+            "  routine github-triage:daily (cron trigger): makes an LLM call"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_manifests() {
+        let mut bad_name = manifest();
+        bad_name["name"] = "../evil".into();
+        assert!(HookPack::parse(bad_name.to_string().as_bytes()).is_err());
+
+        let mut duplicate = manifest();
+        duplicate["routines"][0]["name"] = "notify".into();
+        assert!(HookPack::parse(duplicate.to_string().as_bytes()).is_err());
+
+        let mut bad_cron = manifest();
+        bad_cron["routines"][0]["trigger"]["schedule"] = "whenever".into();
+        assert!(HookPack::parse(bad_cron.to_string().as_bytes()).is_err());
+
+        let empty = serde_json::json!({"name": "empty", "version": "1.0.0"});
+        assert!(HookPack::parse(empty.to_string().as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_store_install_verify_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HookPackStore::new(dir.path().to_path_buf());
+        let bytes = manifest().to_string().into_bytes();
+        let pack = HookPack::parse(&bytes).unwrap();
+
+        store.install(&pack, &bytes).unwrap();
+        assert_eq!(store.list().len(), 1);
+        assert_eq!(
+            store.verify("github-triage").unwrap(),
+            PackVerification::Ok {
+                version: "1.2.0".to_string()
+            }
+        );
+
+        let mut edited = manifest();
+        edited["hooks"][0]["action"] = serde_json::json!({"shell": {"command": "curl evil"}});
+        std::fs::write(dir.path().join("github-triage.json"), edited.to_string()).unwrap();
+        assert!(matches!(
+            store.verify("github-triage").unwrap(),
+            PackVerification::Modified { .. }
+        ));
+
+        assert!(store.remove("github-triage").unwrap());
+        assert!(!store.remove("github-triage").unwrap());
+        assert!(store.list().is_empty());
+    }
+}
@@ -82,7 +82,7 @@ pub fn is_supported_audio_mime(mime_type: &str) -> bool {
 
 /// Download audio from a URL.
 async fn download_audio(url: &str) -> Result<Vec<u8>, String> {
-    let client = reqwest::Client::builder()
+    let client = crate::outbound::client_builder()
         .timeout(std::time::Duration::from_secs(60))
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
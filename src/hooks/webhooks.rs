@@ -1,6 +1,8 @@
 //! Outbound webhook support.
 //!
 //! Sends notifications to external HTTP endpoints when events occur.
+//! Attached to the [`EventBus`], every bus event is delivered under its
+//! dotted name (`turn.finished`, `memory.written`, ...).
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -8,6 +10,9 @@ use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
+use crate::event_bus::{Backpressure, DEFAULT_SUBSCRIBER_CAPACITY, EventBus, Topic};
+use crate::safety::{Provenance, ProvenanceStamp};
+
 /// Configuration for an outbound webhook.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutboundWebhook {
@@ -15,7 +20,7 @@ pub struct OutboundWebhook {
     pub name: String,
     /// Target URL.
     pub url: String,
-    /// Events that trigger this webhook.
+    /// Events that trigger this webhook: exact names, `prefix.*`, or `*`.
     pub events: Vec<String>,
     /// Secret for HMAC signature verification.
     pub secret: Option<String>,
@@ -59,6 +64,7 @@ pub struct WebhookPayload {
 pub struct WebhookManager {
     webhooks: Arc<RwLock<Vec<OutboundWebhook>>>,
     client: reqwest::Client,
+    provenance: Option<Arc<Provenance>>,
 }
 
 impl WebhookManager {
@@ -66,10 +72,17 @@ impl WebhookManager {
     pub fn new() -> Self {
         Self {
             webhooks: Arc::new(RwLock::new(Vec::new())),
-            client: reqwest::Client::new(),
+            client: crate::outbound::client(),
+            provenance: None,
         }
     }
 
+    /// Add provenance headers to deliveries to matching destinations.
+    pub fn with_provenance(mut self, provenance: Arc<Provenance>) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
     /// Register a webhook.
     pub async fn register(&self, webhook: OutboundWebhook) {
         self.webhooks.write().await.push(webhook);
@@ -93,12 +106,13 @@ impl WebhookManager {
         let webhooks = self.webhooks.read().await;
         let matching: Vec<_> = webhooks
             .iter()
-            .filter(|w| w.enabled && w.events.iter().any(|e| e == event || e == "*"))
+            .filter(|w| w.enabled && w.events.iter().any(|e| event_matches(e, event)))
             .cloned()
             .collect();
         drop(webhooks);
 
-        for webhook in matching {
+        for mut webhook in matching {
+            self.add_provenance(&mut webhook, &data);
             let payload = WebhookPayload {
                 event: event.to_string(),
                 timestamp: chrono::Utc::now().to_rfc3339(),
@@ -119,6 +133,50 @@ impl WebhookManager {
     }
 }
 
+impl WebhookManager {
+    /// Add the provenance headers for `webhook`'s destination, taking the
+    /// IDs from the event `data`. Payloads are not watermarked: they carry
+    /// event data, not generated text.
+    fn add_provenance(&self, webhook: &mut OutboundWebhook, data: &serde_json::Value) {
+        let host = reqwest::Url::parse(&webhook.url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string));
+        if let (Some(provenance), Some(host)) = (&self.provenance, host)
+            && let Some(rule) = provenance.rule_for(&host)
+        {
+            let stamp = ProvenanceStamp::from_event(data);
+            webhook.headers.extend(Provenance::headers(rule, &stamp));
+        }
+    }
+
+    /// Fire webhooks for every event published on `bus` until it is dropped.
+    pub fn attach(self: &Arc<Self>, bus: &EventBus) -> tokio::task::JoinHandle<()> {
+        // Reliable: a webhook subscriber should not silently skip events.
+        let mut sub = bus.subscribe(
+            &Topic::ALL,
+            DEFAULT_SUBSCRIBER_CAPACITY,
+            Backpressure::Reliable,
+        );
+        let manager = Arc::clone(self);
+        tokio::spawn(async move {
+            while let Some(event) = sub.recv().await {
+                let data = serde_json::to_value(&*event).unwrap_or_default();
+                manager.fire(&event.name(), data).await;
+            }
+        })
+    }
+}
+
+/// Whether a webhook's event filter selects `event`.
+fn event_matches(filter: &str, event: &str) -> bool {
+    match filter.strip_suffix(".*") {
+        Some(prefix) => event
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('.')),
+        None => filter == "*" || filter == event,
+    }
+}
+
 impl Default for WebhookManager {
     fn default() -> Self {
         Self::new()
@@ -215,6 +273,48 @@ mod tests {
         assert_eq!(list[0].name, "test");
     }
 
+    #[test]
+    fn test_provenance_headers_follow_destination() {
+        let manager = WebhookManager::new().with_provenance(Arc::new(Provenance::new(vec![
+            crate::safety::ProvenanceRule {
+                destination: "hooks.example.com".to_string(),
+                header: true,
+                watermark: false,
+                ids: true,
+                fields: Vec::new(),
+            },
+        ])));
+        let thread_id = uuid::Uuid::new_v4();
+        let data = serde_json::json!({"type": "turn_finished", "thread_id": thread_id});
+
+        let mut stamped = OutboundWebhook {
+            url: "https://hooks.example.com/x".to_string(),
+            ..Default::default()
+        };
+        manager.add_provenance(&mut stamped, &data);
+        assert!(stamped.headers.contains_key("X-Generated-By"));
+        assert_eq!(
+            stamped.headers.get("X-Provenance-Conversation"),
+            Some(&thread_id.to_string())
+        );
+
+        let mut other = OutboundWebhook {
+            url: "https://other.example.com/x".to_string(),
+            ..Default::default()
+        };
+        manager.add_provenance(&mut other, &data);
+        assert!(other.headers.is_empty());
+    }
+
+    #[test]
+    fn test_event_filters() {
+        assert!(event_matches("*", "turn.started"));
+        assert!(event_matches("memory.*", "memory.deleted"));
+        assert!(!event_matches("memory.*", "memoryx.deleted"));
+        assert!(event_matches("job.updated", "job.updated"));
+        assert!(!event_matches("job.updated", "job.started"));
+    }
+
     #[tokio::test]
     async fn test_remove() {
         let manager = WebhookManager::new();
@@ -39,27 +39,36 @@
 //! - **Continuous learning** - Improve estimates from historical data
 
 pub mod agent;
+pub mod boot;
 pub mod bootstrap;
 pub mod channels;
 pub mod cli;
 pub mod config;
+pub mod config_sync;
+pub mod contacts;
 pub mod context;
+pub mod crash;
 pub mod db;
+pub mod demo;
 pub mod error;
 pub mod estimation;
 pub mod evaluation;
+pub mod event_bus;
 pub mod extensions;
 pub mod history;
 pub mod hooks;
 pub mod hot_reload;
 pub mod llm;
+pub mod locale;
 pub mod media;
 pub mod orchestrator;
+pub mod outbound;
 pub mod pairing;
 pub mod safety;
 pub mod sandbox;
 pub mod secrets;
 pub mod settings;
+pub mod settings_migrations;
 pub mod setup;
 pub mod skills;
 pub mod tools;
@@ -0,0 +1,905 @@
+//! Anthropic Messages API provider.
+//!
+//! Talks to `POST /v1/messages` directly instead of going through rig, so
+//! tool calls keep their `tool_use` ids across turns and tool results are
+//! sent back as `tool_result` blocks the model can match up.
+
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::error::LlmError;
+use crate::llm::costs;
+use crate::llm::provider::{
+    CacheControl, ChatMessage, CompletionRequest, CompletionResponse, CompletionStream,
+    FinishReason, LlmProvider, ModelMetadata, Role, StreamDelta, ToolCall, ToolCompletionRequest,
+    ToolCompletionResponse, ToolDefinition,
+};
+use crate::llm::streaming::channel_stream;
+
+/// API version sent in the `anthropic-version` header.
+const API_VERSION: &str = "2023-06-01";
+
+/// `max_tokens` is mandatory on the Messages API; used when the request
+/// leaves it unset.
+const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// Anthropic provider configuration.
+#[derive(Debug, Clone)]
+pub struct AnthropicConfig {
+    pub api_key: String,
+    pub model: String,
+    pub base_url: String,
+}
+
+impl AnthropicConfig {
+    /// Create a new Anthropic config with the default base URL.
+    pub fn new(api_key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            model: model.into(),
+            base_url: "https://api.anthropic.com".to_string(),
+        }
+    }
+}
+
+/// Anthropic Messages API provider.
+pub struct AnthropicProvider {
+    client: reqwest::Client,
+    config: AnthropicConfig,
+    input_cost: Decimal,
+    output_cost: Decimal,
+    active_model: RwLock<String>,
+}
+
+impl AnthropicProvider {
+    /// Create a new Anthropic provider.
+    pub fn new(config: AnthropicConfig) -> Self {
+        let (input_cost, output_cost) =
+            costs::model_cost(&config.model).unwrap_or_else(costs::default_cost);
+        let model_name = config.model.clone();
+        Self {
+            client: crate::outbound::client(),
+            config,
+            input_cost,
+            output_cost,
+            active_model: RwLock::new(model_name),
+        }
+    }
+
+    /// Convert IronClaw messages to Messages API format.
+    ///
+    /// System messages are lifted into the top-level `system` field. Tool
+    /// results become `tool_result` blocks on a user turn, and consecutive
+    /// turns with the same role are merged since the API requires them to
+    /// alternate.
+    fn convert_messages(messages: &[ChatMessage]) -> (Option<String>, Vec<AnthropicMessage>) {
+        let mut system: Option<String> = None;
+        let mut converted: Vec<AnthropicMessage> = Vec::new();
+
+        for msg in messages {
+            let (role, blocks) = match msg.role {
+                Role::System => {
+                    match system {
+                        Some(ref mut s) => {
+                            s.push('\n');
+                            s.push_str(&msg.content);
+                        }
+                        None => system = Some(msg.content.clone()),
+                    }
+                    continue;
+                }
+                Role::User => (
+                    "user",
+                    vec![ContentBlock::Text {
+                        text: msg.content.clone(),
+                    }],
+                ),
+                Role::Tool => (
+                    "user",
+                    vec![ContentBlock::ToolResult {
+                        tool_use_id: msg.tool_call_id.clone().unwrap_or_default(),
+                        content: msg.content.clone(),
+                    }],
+                ),
+                Role::Assistant => {
+                    let mut blocks = Vec::new();
+                    if !msg.content.is_empty() {
+                        blocks.push(ContentBlock::Text {
+                            text: msg.content.clone(),
+                        });
+                    }
+                    for tc in msg.tool_calls.iter().flatten() {
+                        blocks.push(ContentBlock::ToolUse {
+                            id: tc.id.clone(),
+                            name: tc.name.clone(),
+                            input: tc.arguments.clone(),
+                        });
+                    }
+                    if blocks.is_empty() {
+                        continue;
+                    }
+                    ("assistant", blocks)
+                }
+            };
+
+            match converted.last_mut() {
+                Some(last) if last.role == role => last.content.extend(blocks),
+                _ => converted.push(AnthropicMessage {
+                    role: role.to_string(),
+                    content: blocks,
+                }),
+            }
+        }
+
+        (system, converted)
+    }
+
+    /// Convert IronClaw tool definitions to Messages API format. With
+    /// `cache`, the last tool closes a cache breakpoint covering them all.
+    fn convert_tools(tools: &[ToolDefinition], cache: bool) -> Vec<AnthropicTool> {
+        let mut converted: Vec<AnthropicTool> = tools
+            .iter()
+            .map(|t| AnthropicTool {
+                name: t.name.clone(),
+                description: t.description.clone(),
+                input_schema: t.parameters.clone(),
+                cache_control: None,
+            })
+            .collect();
+        if cache && let Some(last) = converted.last_mut() {
+            last.cache_control = Some(CacheMarker::EPHEMERAL);
+        }
+        converted
+    }
+
+    /// The top-level `system` blocks. With `cache`, the breakpoint after
+    /// them also covers the tools, which precede the system prompt.
+    fn system_blocks(system: Option<String>, cache: bool) -> Vec<SystemBlock> {
+        system
+            .map(|text| SystemBlock {
+                kind: "text",
+                text,
+                cache_control: cache.then_some(CacheMarker::EPHEMERAL),
+            })
+            .into_iter()
+            .collect()
+    }
+
+    /// Map IronClaw's `tool_choice` strings onto the Messages API object.
+    fn convert_tool_choice(choice: Option<&str>) -> Option<serde_json::Value> {
+        match choice? {
+            "required" => Some(serde_json::json!({"type": "any"})),
+            "none" => Some(serde_json::json!({"type": "none"})),
+            "auto" => Some(serde_json::json!({"type": "auto"})),
+            name => Some(serde_json::json!({"type": "tool", "name": name})),
+        }
+    }
+
+    fn map_stop_reason(reason: Option<&str>) -> FinishReason {
+        match reason {
+            Some("end_turn") | Some("stop_sequence") => FinishReason::Stop,
+            Some("max_tokens") => FinishReason::Length,
+            Some("tool_use") => FinishReason::ToolUse,
+            Some("refusal") => FinishReason::ContentFilter,
+            _ => FinishReason::Unknown,
+        }
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!("{}/v1/{}", self.config.base_url.trim_end_matches('/'), path)
+    }
+
+    fn get(&self, path: &str) -> reqwest::RequestBuilder {
+        self.client
+            .get(self.api_url(path))
+            .header("x-api-key", &self.config.api_key)
+            .header("anthropic-version", API_VERSION)
+    }
+
+    /// Build a Messages API request for a tool-enabled completion.
+    fn tool_request(&self, request: ToolCompletionRequest) -> AnthropicRequest {
+        let (system, messages) = Self::convert_messages(&request.messages);
+        let CacheControl {
+            system: cache_system,
+            tools: cache_tools,
+        } = request.cache_control;
+        let tools = Self::convert_tools(&request.tools, cache_tools);
+        let tool_choice = if tools.is_empty() {
+            None
+        } else {
+            Self::convert_tool_choice(request.tool_choice.as_deref())
+        };
+        AnthropicRequest {
+            model: self.active_model_name(),
+            max_tokens: request.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+            messages,
+            system: Self::system_blocks(system, cache_system),
+            temperature: request.temperature,
+            stop_sequences: None,
+            tools,
+            tool_choice,
+            stream: false,
+        }
+    }
+
+    /// Send a Messages API request and decode the response.
+    async fn send(&self, body: &AnthropicRequest) -> Result<AnthropicResponse, LlmError> {
+        self.post(body)
+            .await?
+            .json()
+            .await
+            .map_err(|e| LlmError::RequestFailed {
+                provider: "anthropic".to_string(),
+                reason: format!("Failed to parse response: {}", e),
+            })
+    }
+
+    /// Send a Messages API request, mapping error statuses.
+    async fn post(&self, body: &AnthropicRequest) -> Result<reqwest::Response, LlmError> {
+        let response = self
+            .client
+            .post(self.api_url("messages"))
+            .header("x-api-key", &self.config.api_key)
+            .header("anthropic-version", API_VERSION)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| LlmError::RequestFailed {
+                provider: "anthropic".to_string(),
+                reason: format!("HTTP request failed: {}", e),
+            })?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            return Err(LlmError::AuthFailed {
+                provider: "anthropic".to_string(),
+            });
+        }
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get("retry-after")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .map(std::time::Duration::from_secs);
+            return Err(LlmError::RateLimited {
+                provider: "anthropic".to_string(),
+                retry_after,
+            });
+        }
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            let msg = serde_json::from_str::<AnthropicErrorResponse>(&error_text)
+                .map(|e| e.error.message)
+                .unwrap_or(error_text);
+            return Err(LlmError::RequestFailed {
+                provider: "anthropic".to_string(),
+                reason: format!("Status {}: {}", status, msg),
+            });
+        }
+
+        Ok(response)
+    }
+}
+
+// -- Messages API request/response types --
+
+#[derive(Debug, Serialize)]
+struct AnthropicRequest {
+    model: String,
+    max_tokens: u32,
+    messages: Vec<AnthropicMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    system: Vec<SystemBlock>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<AnthropicTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct AnthropicMessage {
+    role: String,
+    content: Vec<ContentBlock>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+    },
+    /// Block types we don't consume (thinking, server tool results, ...).
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Serialize)]
+struct AnthropicTool {
+    name: String,
+    description: String,
+    input_schema: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<CacheMarker>,
+}
+
+#[derive(Debug, Serialize)]
+struct SystemBlock {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<CacheMarker>,
+}
+
+/// A `cache_control` breakpoint: everything up to and including the block
+/// carrying it is cached.
+#[derive(Debug, Clone, Copy, Serialize)]
+struct CacheMarker {
+    #[serde(rename = "type")]
+    kind: &'static str,
+}
+
+impl CacheMarker {
+    /// The only cache type; lives five minutes, refreshed on each hit.
+    const EPHEMERAL: Self = Self { kind: "ephemeral" };
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicResponse {
+    id: Option<String>,
+    #[serde(default)]
+    content: Vec<ContentBlock>,
+    stop_reason: Option<String>,
+    #[serde(default)]
+    usage: AnthropicUsage,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AnthropicUsage {
+    /// Uncached prompt tokens only; see [`total_input`](Self::total_input).
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+    #[serde(default)]
+    cache_read_input_tokens: u32,
+    #[serde(default)]
+    cache_creation_input_tokens: u32,
+}
+
+impl AnthropicUsage {
+    /// All prompt tokens. The API counts cache reads and writes apart from
+    /// `input_tokens`, while IronClaw's `input_tokens` includes them.
+    fn total_input(&self) -> u32 {
+        self.input_tokens + self.cache_read_input_tokens + self.cache_creation_input_tokens
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicErrorResponse {
+    error: AnthropicErrorDetail,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicErrorDetail {
+    message: String,
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    fn model_name(&self) -> &str {
+        &self.config.model
+    }
+
+    fn cost_per_token(&self) -> (Decimal, Decimal) {
+        (self.input_cost, self.output_cost)
+    }
+
+    fn active_model_name(&self) -> String {
+        self.active_model
+            .read()
+            .map(|m| m.clone())
+            .unwrap_or_else(|_| self.config.model.clone())
+    }
+
+    fn set_model(&self, model: &str) -> Result<(), LlmError> {
+        if let Ok(mut active) = self.active_model.write() {
+            *active = model.to_string();
+            Ok(())
+        } else {
+            Err(LlmError::RequestFailed {
+                provider: "anthropic".to_string(),
+                reason: "Failed to acquire model lock".to_string(),
+            })
+        }
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
+        let (system, messages) = Self::convert_messages(&request.messages);
+        let body = AnthropicRequest {
+            model: self.active_model_name(),
+            max_tokens: request.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+            messages,
+            system: Self::system_blocks(system, request.cache_control.system),
+            temperature: request.temperature,
+            stop_sequences: request.stop_sequences,
+            tools: Vec::new(),
+            tool_choice: None,
+            stream: false,
+        };
+
+        let resp = self.send(&body).await?;
+        let content = resp
+            .content
+            .into_iter()
+            .filter_map(|b| match b {
+                ContentBlock::Text { text } => Some(text),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("");
+
+        Ok(CompletionResponse {
+            content,
+            input_tokens: resp.usage.total_input(),
+            output_tokens: resp.usage.output_tokens,
+            cache_read_tokens: resp.usage.cache_read_input_tokens,
+            cache_write_tokens: resp.usage.cache_creation_input_tokens,
+            finish_reason: Self::map_stop_reason(resp.stop_reason.as_deref()),
+            response_id: resp.id,
+        })
+    }
+
+    async fn complete_with_tools(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<ToolCompletionResponse, LlmError> {
+        let body = self.tool_request(request);
+        let resp = self.send(&body).await?;
+        let mut text = String::new();
+        let mut tool_calls = Vec::new();
+        for block in resp.content {
+            match block {
+                ContentBlock::Text { text: t } => text.push_str(&t),
+                ContentBlock::ToolUse { id, name, input } => tool_calls.push(ToolCall {
+                    id,
+                    name,
+                    arguments: input,
+                }),
+                _ => {}
+            }
+        }
+
+        Ok(ToolCompletionResponse {
+            content: if text.is_empty() { None } else { Some(text) },
+            tool_calls,
+            input_tokens: resp.usage.total_input(),
+            output_tokens: resp.usage.output_tokens,
+            cache_read_tokens: resp.usage.cache_read_input_tokens,
+            cache_write_tokens: resp.usage.cache_creation_input_tokens,
+            finish_reason: Self::map_stop_reason(resp.stop_reason.as_deref()),
+            response_id: resp.id,
+        })
+    }
+
+    async fn complete_stream(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        let mut body = self.tool_request(request);
+        body.stream = true;
+        let mut bytes = self.post(&body).await?.bytes_stream();
+
+        Ok(channel_stream(move |tx| async move {
+            let mut decoder = MessageEvents::default();
+            while let Some(chunk) = bytes.next().await {
+                let deltas = match chunk {
+                    Ok(chunk) => decoder.push(&chunk),
+                    Err(e) => Err(format!("Stream interrupted: {}", e)),
+                };
+                match deltas {
+                    Ok(deltas) => {
+                        for delta in deltas {
+                            if tx.send(Ok(delta)).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(reason) => {
+                        let _ = tx
+                            .send(Err(LlmError::RequestFailed {
+                                provider: "anthropic".to_string(),
+                                reason,
+                            }))
+                            .await;
+                        return;
+                    }
+                }
+                if decoder.done {
+                    break;
+                }
+            }
+            let _ = tx.send(Ok(decoder.finish())).await;
+        }))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, LlmError> {
+        let response =
+            self.get("models?limit=1000")
+                .send()
+                .await
+                .map_err(|e| LlmError::RequestFailed {
+                    provider: "anthropic".to_string(),
+                    reason: format!("Failed to list models: {}", e),
+                })?;
+
+        if !response.status().is_success() {
+            return Ok(Vec::new());
+        }
+
+        #[derive(Deserialize)]
+        struct ModelList {
+            #[serde(default)]
+            data: Vec<ModelInfo>,
+        }
+        #[derive(Deserialize)]
+        struct ModelInfo {
+            id: String,
+        }
+
+        let list: ModelList = response.json().await.map_err(|e| LlmError::RequestFailed {
+            provider: "anthropic".to_string(),
+            reason: format!("Failed to parse model list: {}", e),
+        })?;
+
+        Ok(list.data.into_iter().map(|m| m.id).collect())
+    }
+
+    async fn model_metadata(&self) -> Result<ModelMetadata, LlmError> {
+        // The models endpoint doesn't report context windows; every current
+        // Claude model accepts 200k input tokens.
+        Ok(ModelMetadata {
+            id: self.active_model_name(),
+            context_length: Some(200_000),
+        })
+    }
+}
+
+/// A content block being assembled from stream events.
+#[derive(Debug)]
+enum PartialBlock {
+    Text,
+    ToolUse {
+        id: String,
+        name: String,
+        json: String,
+    },
+    Other,
+}
+
+/// Decoder for a Messages API event stream (`"stream": true`).
+///
+/// Text comes out of [`push`](Self::push) as it arrives; a tool call is
+/// emitted when its `content_block_stop` closes the arguments JSON.
+#[derive(Debug, Default)]
+struct MessageEvents {
+    line: Vec<u8>,
+    blocks: std::collections::HashMap<u64, PartialBlock>,
+    id: Option<String>,
+    stop_reason: Option<String>,
+    usage: AnthropicUsage,
+    done: bool,
+}
+
+impl MessageEvents {
+    /// Feed raw response bytes and return the deltas they complete.
+    fn push(&mut self, bytes: &[u8]) -> Result<Vec<StreamDelta>, String> {
+        let mut deltas = Vec::new();
+        for &byte in bytes {
+            if byte != b'\n' {
+                self.line.push(byte);
+                continue;
+            }
+            let line = String::from_utf8_lossy(&self.line).into_owned();
+            self.line.clear();
+            // Every event repeats its type inside the data payload, so the
+            // `event:` lines can be ignored.
+            let Some(data) = line.trim_end_matches('\r').strip_prefix("data:") else {
+                continue;
+            };
+            let event: serde_json::Value = match serde_json::from_str(data.trim()) {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::debug!("Skipping unparseable Anthropic event: {}", e);
+                    continue;
+                }
+            };
+            if let Some(delta) = self.handle_event(&event)? {
+                deltas.push(delta);
+            }
+        }
+        Ok(deltas)
+    }
+
+    fn handle_event(&mut self, event: &serde_json::Value) -> Result<Option<StreamDelta>, String> {
+        let index = event["index"].as_u64().unwrap_or(0);
+        match event["type"].as_str().unwrap_or_default() {
+            "message_start" => {
+                let message = &event["message"];
+                self.id = message["id"].as_str().map(String::from);
+                self.usage = serde_json::from_value(message["usage"].clone()).unwrap_or_default();
+            }
+            "content_block_start" => {
+                let block = &event["content_block"];
+                let partial = match block["type"].as_str() {
+                    Some("text") => PartialBlock::Text,
+                    Some("tool_use") => PartialBlock::ToolUse {
+                        id: block["id"].as_str().unwrap_or_default().to_string(),
+                        name: block["name"].as_str().unwrap_or_default().to_string(),
+                        json: String::new(),
+                    },
+                    _ => PartialBlock::Other,
+                };
+                self.blocks.insert(index, partial);
+            }
+            "content_block_delta" => {
+                let delta = &event["delta"];
+                match (self.blocks.get_mut(&index), delta["type"].as_str()) {
+                    (Some(PartialBlock::Text), Some("text_delta")) => {
+                        return Ok(delta["text"]
+                            .as_str()
+                            .filter(|t| !t.is_empty())
+                            .map(|t| StreamDelta::Text(t.to_string())));
+                    }
+                    (Some(PartialBlock::ToolUse { json, .. }), Some("input_json_delta")) => {
+                        json.push_str(delta["partial_json"].as_str().unwrap_or_default());
+                    }
+                    _ => {}
+                }
+            }
+            "content_block_stop" => {
+                if let Some(PartialBlock::ToolUse { id, name, json }) = self.blocks.remove(&index) {
+                    let arguments = if json.trim().is_empty() {
+                        serde_json::json!({})
+                    } else {
+                        serde_json::from_str(&json)
+                            .map_err(|e| format!("Malformed tool input for {}: {}", name, e))?
+                    };
+                    return Ok(Some(StreamDelta::ToolCall(ToolCall {
+                        id,
+                        name,
+                        arguments,
+                    })));
+                }
+            }
+            "message_delta" => {
+                if let Some(reason) = event["delta"]["stop_reason"].as_str() {
+                    self.stop_reason = Some(reason.to_string());
+                }
+                if let Some(output) = event["usage"]["output_tokens"].as_u64() {
+                    self.usage.output_tokens = output as u32;
+                }
+            }
+            "message_stop" => self.done = true,
+            "error" => {
+                return Err(event["error"]["message"]
+                    .as_str()
+                    .map(String::from)
+                    .unwrap_or_else(|| event["error"].to_string()));
+            }
+            // "ping" and event types added later.
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    /// The closing [`StreamDelta::Done`].
+    fn finish(self) -> StreamDelta {
+        StreamDelta::Done {
+            input_tokens: self.usage.total_input(),
+            output_tokens: self.usage.output_tokens,
+            cache_read_tokens: self.usage.cache_read_input_tokens,
+            cache_write_tokens: self.usage.cache_creation_input_tokens,
+            finish_reason: AnthropicProvider::map_stop_reason(self.stop_reason.as_deref()),
+            response_id: self.id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_messages_groups_tool_results() {
+        let call = ToolCall {
+            id: "toolu_1".to_string(),
+            name: "search".to_string(),
+            arguments: serde_json::json!({"q": "rust"}),
+        };
+        let call2 = ToolCall {
+            id: "toolu_2".to_string(),
+            ..call.clone()
+        };
+        let messages = vec![
+            ChatMessage::system("You are helpful."),
+            ChatMessage::user("Find things"),
+            ChatMessage::assistant_with_tool_calls(None, vec![call, call2]),
+            ChatMessage::tool_result("toolu_1", "search", "one"),
+            ChatMessage::tool_result("toolu_2", "search", "two"),
+        ];
+
+        let (system, converted) = AnthropicProvider::convert_messages(&messages);
+        assert_eq!(system.as_deref(), Some("You are helpful."));
+        assert_eq!(converted.len(), 3);
+        assert_eq!(converted[1].role, "assistant");
+        assert!(matches!(
+            &converted[1].content[0],
+            ContentBlock::ToolUse { id, .. } if id == "toolu_1"
+        ));
+        assert_eq!(converted[2].role, "user");
+        assert_eq!(converted[2].content.len(), 2);
+        assert!(matches!(
+            &converted[2].content[1],
+            ContentBlock::ToolResult { tool_use_id, content } if tool_use_id == "toolu_2" && content == "two"
+        ));
+    }
+
+    #[test]
+    fn test_response_tool_use_parsing() {
+        let resp: AnthropicResponse = serde_json::from_value(serde_json::json!({
+            "id": "msg_1",
+            "content": [
+                {"type": "thinking", "thinking": "hmm", "signature": "x"},
+                {"type": "text", "text": "Let me look."},
+                {"type": "tool_use", "id": "toolu_9", "name": "search", "input": {"q": "x"}}
+            ],
+            "stop_reason": "tool_use",
+            "usage": {
+                "input_tokens": 12,
+                "output_tokens": 5,
+                "cache_creation_input_tokens": 1000
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(resp.content.len(), 3);
+        assert!(matches!(resp.content[0], ContentBlock::Other));
+        assert!(matches!(
+            &resp.content[2],
+            ContentBlock::ToolUse { id, .. } if id == "toolu_9"
+        ));
+        assert_eq!(
+            AnthropicProvider::map_stop_reason(resp.stop_reason.as_deref()),
+            FinishReason::ToolUse
+        );
+        assert_eq!(resp.usage.total_input(), 1012);
+        assert_eq!(resp.usage.cache_creation_input_tokens, 1000);
+    }
+
+    #[test]
+    fn test_cache_control_marks_system_and_last_tool() {
+        let provider = AnthropicProvider::new(AnthropicConfig::new("key", "claude-sonnet-4"));
+        let tool = |name: &str| ToolDefinition {
+            name: name.to_string(),
+            description: String::new(),
+            parameters: serde_json::json!({"type": "object"}),
+        };
+        let request = ToolCompletionRequest::new(
+            vec![ChatMessage::system("Be brief."), ChatMessage::user("hi")],
+            vec![tool("a"), tool("b")],
+        );
+
+        let plain = serde_json::to_value(provider.tool_request(request.clone())).unwrap();
+        assert_eq!(
+            plain["system"],
+            serde_json::json!([{"type": "text", "text": "Be brief."}])
+        );
+        assert!(plain["tools"][1].get("cache_control").is_none());
+
+        let cached = serde_json::to_value(
+            provider.tool_request(request.with_cache_control(CacheControl::STABLE_PREFIX)),
+        )
+        .unwrap();
+        let ephemeral = serde_json::json!({"type": "ephemeral"});
+        assert_eq!(cached["system"][0]["cache_control"], ephemeral);
+        assert!(cached["tools"][0].get("cache_control").is_none());
+        assert_eq!(cached["tools"][1]["cache_control"], ephemeral);
+    }
+
+    #[test]
+    fn test_stream_events_decode_text_and_tool_use() {
+        let events = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\",\"usage\":{\"input_tokens\":20,\"cache_read_input_tokens\":300,\"output_tokens\":1}}}\n\n",
+            "event: content_block_start\n",
+            "data: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hel\"}}\n\n",
+            "data: {\"type\":\"ping\"}\n\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"lo\"}}\n\n",
+            "data: {\"type\":\"content_block_stop\",\"index\":0}\n\n",
+            "data: {\"type\":\"content_block_start\",\"index\":1,\"content_block\":{\"type\":\"tool_use\",\"id\":\"toolu_1\",\"name\":\"echo\",\"input\":{}}}\n\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":1,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"{\\\"te\"}}\n\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":1,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"xt\\\":\\\"hi\\\"}\"}}\n\n",
+            "data: {\"type\":\"content_block_stop\",\"index\":1}\n\n",
+            "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"tool_use\"},\"usage\":{\"output_tokens\":9}}\n\n",
+            "data: {\"type\":\"message_stop\"}\n\n",
+        );
+
+        // Feed in awkward pieces, splitting lines and JSON mid-way.
+        let mut decoder = MessageEvents::default();
+        let mut deltas = Vec::new();
+        for piece in events.as_bytes().chunks(11) {
+            deltas.extend(decoder.push(piece).unwrap());
+        }
+        assert!(decoder.done);
+        deltas.push(decoder.finish());
+
+        assert_eq!(
+            deltas,
+            vec![
+                StreamDelta::Text("Hel".to_string()),
+                StreamDelta::Text("lo".to_string()),
+                StreamDelta::ToolCall(ToolCall {
+                    id: "toolu_1".to_string(),
+                    name: "echo".to_string(),
+                    arguments: serde_json::json!({"text": "hi"}),
+                }),
+                StreamDelta::Done {
+                    input_tokens: 320,
+                    output_tokens: 9,
+                    cache_read_tokens: 300,
+                    cache_write_tokens: 0,
+                    finish_reason: FinishReason::ToolUse,
+                    response_id: Some("msg_1".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stream_error_event() {
+        let mut decoder = MessageEvents::default();
+        let err = decoder
+            .push(b"data: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n")
+            .unwrap_err();
+        assert_eq!(err, "Overloaded");
+    }
+
+    #[test]
+    fn test_convert_tool_choice() {
+        assert_eq!(
+            AnthropicProvider::convert_tool_choice(Some("required")),
+            Some(serde_json::json!({"type": "any"}))
+        );
+        assert_eq!(AnthropicProvider::convert_tool_choice(None), None);
+        assert_eq!(
+            AnthropicProvider::convert_tool_choice(Some("search")),
+            Some(serde_json::json!({"type": "tool", "name": "search"}))
+        );
+    }
+}
@@ -39,7 +39,7 @@ impl ModelDiscovery {
     /// Create a new model discovery instance.
     pub fn new() -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: crate::outbound::client(),
         }
     }
 
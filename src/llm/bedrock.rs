@@ -66,7 +66,7 @@ impl BedrockProvider {
             costs::model_cost(&config.model_id).unwrap_or_else(costs::default_cost);
         let model_id = config.model_id.clone();
         Self {
-            client: reqwest::Client::new(),
+            client: crate::outbound::client(),
             config,
             input_cost,
             output_cost,
@@ -303,8 +303,26 @@ struct ConverseToolResultContent {
 }
 
 #[derive(Debug, Serialize)]
-struct ConverseSystemContent {
-    text: String,
+#[serde(untagged)]
+enum ConverseSystemContent {
+    Text {
+        text: String,
+    },
+    CachePoint {
+        #[serde(rename = "cachePoint")]
+        cache_point: CachePoint,
+    },
+}
+
+/// A prompt cache checkpoint: everything before it is cached.
+#[derive(Debug, Serialize)]
+struct CachePoint {
+    #[serde(rename = "type")]
+    kind: &'static str,
+}
+
+impl CachePoint {
+    const DEFAULT: Self = Self { kind: "default" };
 }
 
 #[derive(Debug, Serialize)]
@@ -322,9 +340,16 @@ struct ConverseToolConfig {
 }
 
 #[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct ConverseTool {
-    tool_spec: ConverseToolSpec,
+#[serde(untagged)]
+enum ConverseTool {
+    Spec {
+        #[serde(rename = "toolSpec")]
+        tool_spec: ConverseToolSpec,
+    },
+    CachePoint {
+        #[serde(rename = "cachePoint")]
+        cache_point: CachePoint,
+    },
 }
 
 #[derive(Debug, Serialize)]
@@ -358,11 +383,29 @@ struct ConverseOutputMessage {
     content: Vec<ConverseContent>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ConverseUsage {
+    /// Uncached prompt tokens only; see [`total_input`](Self::total_input).
     input_tokens: u32,
     output_tokens: u32,
+    #[serde(default)]
+    cache_read_input_tokens: u32,
+    #[serde(default)]
+    cache_write_input_tokens: u32,
+}
+
+impl ConverseUsage {
+    /// All prompt tokens, including cache reads and writes.
+    fn total_input(&self) -> u32 {
+        self.input_tokens + self.cache_read_input_tokens + self.cache_write_input_tokens
+    }
+}
+
+/// Whether a model accepts cache checkpoints (Claude and Nova do; other
+/// models reject requests carrying them).
+fn supports_cache_points(model_id: &str) -> bool {
+    model_id.contains("anthropic.") || model_id.contains("amazon.nova")
 }
 
 /// Convert IronClaw messages to Bedrock Converse API format.
@@ -375,7 +418,7 @@ fn convert_messages(
     for msg in messages {
         match msg.role {
             Role::System => {
-                system.push(ConverseSystemContent {
+                system.push(ConverseSystemContent::Text {
                     text: msg.content.clone(),
                 });
             }
@@ -440,21 +483,35 @@ fn convert_messages(
     (system_opt, converse_msgs)
 }
 
-/// Convert IronClaw tool definitions to Bedrock Converse tool config.
-fn convert_tools(tools: &[ToolDefinition]) -> ConverseToolConfig {
-    ConverseToolConfig {
-        tools: tools
-            .iter()
-            .map(|t| ConverseTool {
-                tool_spec: ConverseToolSpec {
-                    name: t.name.clone(),
-                    description: t.description.clone(),
-                    input_schema: ConverseInputSchema {
-                        json: t.parameters.clone(),
-                    },
+/// Convert IronClaw tool definitions to Bedrock Converse tool config,
+/// closed by a cache checkpoint with `cache`.
+fn convert_tools(tools: &[ToolDefinition], cache: bool) -> ConverseToolConfig {
+    let mut converted: Vec<ConverseTool> = tools
+        .iter()
+        .map(|t| ConverseTool::Spec {
+            tool_spec: ConverseToolSpec {
+                name: t.name.clone(),
+                description: t.description.clone(),
+                input_schema: ConverseInputSchema {
+                    json: t.parameters.clone(),
                 },
-            })
-            .collect(),
+            },
+        })
+        .collect();
+    if cache && !converted.is_empty() {
+        converted.push(ConverseTool::CachePoint {
+            cache_point: CachePoint::DEFAULT,
+        });
+    }
+    ConverseToolConfig { tools: converted }
+}
+
+/// Close the system prompt with a cache checkpoint.
+fn cache_system(system: &mut Option<Vec<ConverseSystemContent>>) {
+    if let Some(blocks) = system {
+        blocks.push(ConverseSystemContent::CachePoint {
+            cache_point: CachePoint::DEFAULT,
+        });
     }
 }
 
@@ -488,7 +545,10 @@ impl LlmProvider for BedrockProvider {
     }
 
     async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
-        let (system, messages) = convert_messages(&request.messages);
+        let (mut system, messages) = convert_messages(&request.messages);
+        if request.cache_control.system && supports_cache_points(&self.active_model_name()) {
+            cache_system(&mut system);
+        }
 
         let converse_req = ConverseRequest {
             messages,
@@ -517,10 +577,7 @@ impl LlmProvider for BedrockProvider {
             })
             .unwrap_or_default();
 
-        let usage = resp.usage.unwrap_or(ConverseUsage {
-            input_tokens: 0,
-            output_tokens: 0,
-        });
+        let usage = resp.usage.unwrap_or_default();
 
         let finish_reason = match resp.stop_reason.as_deref() {
             Some("end_turn") | Some("stop") => FinishReason::Stop,
@@ -532,8 +589,10 @@ impl LlmProvider for BedrockProvider {
 
         Ok(CompletionResponse {
             content,
-            input_tokens: usage.input_tokens,
+            input_tokens: usage.total_input(),
             output_tokens: usage.output_tokens,
+            cache_read_tokens: usage.cache_read_input_tokens,
+            cache_write_tokens: usage.cache_write_input_tokens,
             finish_reason,
             response_id: None,
         })
@@ -543,8 +602,12 @@ impl LlmProvider for BedrockProvider {
         &self,
         request: ToolCompletionRequest,
     ) -> Result<ToolCompletionResponse, LlmError> {
-        let (system, messages) = convert_messages(&request.messages);
-        let tool_config = convert_tools(&request.tools);
+        let (mut system, messages) = convert_messages(&request.messages);
+        let cache = supports_cache_points(&self.active_model_name());
+        if cache && request.cache_control.system {
+            cache_system(&mut system);
+        }
+        let tool_config = convert_tools(&request.tools, cache && request.cache_control.tools);
 
         let converse_req = ConverseRequest {
             messages,
@@ -581,10 +644,7 @@ impl LlmProvider for BedrockProvider {
             }
         }
 
-        let usage = resp.usage.unwrap_or(ConverseUsage {
-            input_tokens: 0,
-            output_tokens: 0,
-        });
+        let usage = resp.usage.unwrap_or_default();
 
         let finish_reason = if !tool_calls.is_empty() {
             FinishReason::ToolUse
@@ -600,8 +660,10 @@ impl LlmProvider for BedrockProvider {
         Ok(ToolCompletionResponse {
             content: text_content,
             tool_calls,
-            input_tokens: usage.input_tokens,
+            input_tokens: usage.total_input(),
             output_tokens: usage.output_tokens,
+            cache_read_tokens: usage.cache_read_input_tokens,
+            cache_write_tokens: usage.cache_write_input_tokens,
             finish_reason,
             response_id: None,
         })
@@ -636,9 +698,19 @@ mod tests {
             parameters: serde_json::json!({"type": "object"}),
         }];
 
-        let config = convert_tools(&tools);
+        let config = convert_tools(&tools, false);
         assert_eq!(config.tools.len(), 1);
-        assert_eq!(config.tools[0].tool_spec.name, "search");
+        assert!(matches!(
+            &config.tools[0],
+            ConverseTool::Spec { tool_spec } if tool_spec.name == "search"
+        ));
+
+        let cached = serde_json::to_value(convert_tools(&tools, true)).unwrap();
+        assert_eq!(cached["tools"][0]["toolSpec"]["name"], "search");
+        assert_eq!(
+            cached["tools"][1],
+            serde_json::json!({"cachePoint": {"type": "default"}})
+        );
     }
 
     #[test]
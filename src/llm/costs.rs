@@ -30,17 +30,37 @@ pub fn model_cost(model_id: &str) -> Option<(Decimal, Decimal)> {
         "o3-mini" | "o3-mini-2025-01-31" => Some((dec!(0.0000011), dec!(0.0000044))),
 
         // Anthropic models
-        "claude-3-5-sonnet-20241022" | "claude-3-5-sonnet-latest" | "claude-sonnet-4-20250514" => {
-            Some((dec!(0.000003), dec!(0.000015)))
-        }
+        "claude-3-5-sonnet-20241022"
+        | "claude-3-5-sonnet-latest"
+        | "claude-3-7-sonnet-20250219"
+        | "claude-3-7-sonnet-latest"
+        | "claude-sonnet-4-20250514"
+        | "claude-sonnet-4-0"
+        | "claude-sonnet-4-5"
+        | "claude-sonnet-4-5-20250929" => Some((dec!(0.000003), dec!(0.000015))),
+        "claude-haiku-4-5" | "claude-haiku-4-5-20251001" => Some((dec!(0.000001), dec!(0.000005))),
         "claude-3-5-haiku-20241022" | "claude-3-5-haiku-latest" => {
             Some((dec!(0.0000008), dec!(0.000004)))
         }
-        "claude-3-opus-20240229" | "claude-3-opus-latest" | "claude-opus-4-20250514" => {
-            Some((dec!(0.000015), dec!(0.000075)))
-        }
+        "claude-3-opus-20240229"
+        | "claude-3-opus-latest"
+        | "claude-opus-4-20250514"
+        | "claude-opus-4-0"
+        | "claude-opus-4-1"
+        | "claude-opus-4-1-20250805" => Some((dec!(0.000015), dec!(0.000075))),
         "claude-3-haiku-20240307" => Some((dec!(0.00000025), dec!(0.00000125))),
 
+        // Google Gemini models (standard-context pricing)
+        "gemini-2.5-pro" => Some((dec!(0.00000125), dec!(0.00001))),
+        "gemini-2.5-flash" => Some((dec!(0.0000003), dec!(0.0000025))),
+        "gemini-2.5-flash-lite" => Some((dec!(0.0000001), dec!(0.0000004))),
+        "gemini-2.0-flash" | "gemini-2.0-flash-001" => Some((dec!(0.0000001), dec!(0.0000004))),
+        "gemini-2.0-flash-lite" | "gemini-2.0-flash-lite-001" => {
+            Some((dec!(0.000000075), dec!(0.0000003)))
+        }
+        "gemini-1.5-pro" | "gemini-1.5-pro-002" => Some((dec!(0.00000125), dec!(0.000005))),
+        "gemini-1.5-flash" | "gemini-1.5-flash-002" => Some((dec!(0.000000075), dec!(0.0000003))),
+
         // Ollama / local models -- free
         _ if is_local_model(id) => Some((Decimal::ZERO, Decimal::ZERO)),
 
@@ -90,6 +110,14 @@ mod tests {
         assert!(output > input);
     }
 
+    #[test]
+    fn test_gemini_costs() {
+        let (input, output) = model_cost("gemini-2.0-flash").unwrap();
+        assert!(input > Decimal::ZERO);
+        assert!(output > input);
+        assert!(model_cost("models/gemini-2.5-pro").is_some());
+    }
+
     #[test]
     fn test_local_model_free() {
         let (input, output) = model_cost("llama3").unwrap();
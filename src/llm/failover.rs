@@ -13,8 +13,8 @@ use rust_decimal::Decimal;
 use tokio::sync::RwLock;
 
 use super::provider::{
-    CompletionRequest, CompletionResponse, LlmProvider, ModelMetadata, ToolCompletionRequest,
-    ToolCompletionResponse,
+    CompletionRequest, CompletionResponse, CompletionStream, LlmProvider, ModelMetadata,
+    ToolCompletionRequest, ToolCompletionResponse,
 };
 use crate::error::LlmError;
 
@@ -274,6 +274,53 @@ impl LlmProvider for FailoverProvider {
         }))
     }
 
+    /// Fails over only while opening the stream; once deltas flow, an error
+    /// mid-stream is the caller's to handle.
+    async fn complete_stream(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        let mut last_error = None;
+        let states = self.states.read().await;
+        let available: Vec<_> = self
+            .providers
+            .iter()
+            .filter(|p| states.get(&p.name).is_none_or(|s| s.is_available()))
+            .collect();
+        drop(states);
+
+        for entry in &available {
+            tracing::debug!(provider = entry.name, "Attempting streamed completion");
+
+            match entry.provider.complete_stream(request.clone()).await {
+                Ok(stream) => {
+                    self.record_success(&entry.name).await;
+                    return Ok(stream);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        provider = entry.name,
+                        error = %e,
+                        "Provider failed, trying next"
+                    );
+                    self.record_failure(&entry.name).await;
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or(LlmError::RequestFailed {
+            provider: "failover".to_string(),
+            reason: "No providers available".to_string(),
+        }))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.providers
+            .first()
+            .is_some_and(|p| p.provider.supports_streaming())
+    }
+
     async fn list_models(&self) -> Result<Vec<String>, LlmError> {
         let mut all_models = Vec::new();
         for entry in &self.providers {
@@ -51,7 +51,7 @@ impl GeminiProvider {
             costs::model_cost(&config.model).unwrap_or_else(costs::default_cost);
         let model_name = config.model.clone();
         Self {
-            client: reqwest::Client::new(),
+            client: crate::outbound::client(),
             config,
             input_cost,
             output_cost,
@@ -62,7 +62,7 @@ impl GeminiProvider {
     /// Convert IronClaw messages to Gemini API format.
     fn convert_messages(messages: &[ChatMessage]) -> (Option<String>, Vec<GeminiContent>) {
         let mut system_instruction = None;
-        let mut contents = Vec::new();
+        let mut contents: Vec<GeminiContent> = Vec::new();
 
         for msg in messages {
             match msg.role {
@@ -74,7 +74,32 @@ impl GeminiProvider {
                     }
                     None => system_instruction = Some(msg.content.clone()),
                 },
-                Role::User | Role::Tool => {
+                Role::Tool => {
+                    // Results for one model turn go back together as
+                    // functionResponse parts, matched to calls by name.
+                    let part = GeminiPart::FunctionResponse {
+                        function_response: GeminiFunctionResponse {
+                            name: msg.name.clone().unwrap_or_default(),
+                            response: serde_json::json!({ "content": msg.content }),
+                        },
+                    };
+                    match contents.last_mut() {
+                        Some(last)
+                            if last.role == "user"
+                                && matches!(
+                                    last.parts.last(),
+                                    Some(GeminiPart::FunctionResponse { .. })
+                                ) =>
+                        {
+                            last.parts.push(part)
+                        }
+                        _ => contents.push(GeminiContent {
+                            role: "user".to_string(),
+                            parts: vec![part],
+                        }),
+                    }
+                }
+                Role::User => {
                     contents.push(GeminiContent {
                         role: "user".to_string(),
                         parts: vec![GeminiPart::Text {
@@ -236,6 +261,9 @@ struct GeminiUsageMetadata {
     candidates_token_count: Option<u32>,
     #[allow(dead_code)]
     total_token_count: Option<u32>,
+    /// Prompt tokens served by implicit caching; part of the prompt count.
+    #[serde(default)]
+    cached_content_token_count: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -370,19 +398,17 @@ impl LlmProvider for GeminiProvider {
             prompt_token_count: Some(0),
             candidates_token_count: Some(0),
             total_token_count: Some(0),
+            cached_content_token_count: None,
         });
 
-        let finish_reason = match candidate.finish_reason.as_deref() {
-            Some("STOP") => FinishReason::Stop,
-            Some("MAX_TOKENS") => FinishReason::Length,
-            Some("SAFETY") => FinishReason::ContentFilter,
-            _ => FinishReason::Stop,
-        };
+        let finish_reason = map_finish_reason(candidate.finish_reason.as_deref());
 
         Ok(CompletionResponse {
             content,
             input_tokens: usage.prompt_token_count.unwrap_or(0),
             output_tokens: usage.candidates_token_count.unwrap_or(0),
+            cache_read_tokens: usage.cached_content_token_count.unwrap_or(0),
+            cache_write_tokens: 0,
             finish_reason,
             response_id: None,
         })
@@ -455,16 +481,16 @@ impl LlmProvider for GeminiProvider {
             }
         });
 
-        let mut text_content = None;
+        let mut text_content = String::new();
         let mut tool_calls = Vec::new();
+        let mut finish = None;
 
-        if let Some(candidate) = candidate
-            && let Some(content) = candidate.content
-        {
-            for part in content.parts {
+        if let Some(candidate) = candidate {
+            finish = candidate.finish_reason;
+            for part in candidate.content.map(|c| c.parts).unwrap_or_default() {
                 match part {
-                    GeminiPart::Text { text } if !text.is_empty() => {
-                        text_content = Some(text);
+                    GeminiPart::Text { text } => {
+                        text_content.push_str(&text);
                     }
                     GeminiPart::FunctionCall { function_call } => {
                         tool_calls.push(ToolCall {
@@ -482,19 +508,26 @@ impl LlmProvider for GeminiProvider {
             prompt_token_count: Some(0),
             candidates_token_count: Some(0),
             total_token_count: Some(0),
+            cached_content_token_count: None,
         });
 
         let finish_reason = if !tool_calls.is_empty() {
             FinishReason::ToolUse
         } else {
-            FinishReason::Stop
+            map_finish_reason(finish.as_deref())
         };
 
         Ok(ToolCompletionResponse {
-            content: text_content,
+            content: if text_content.is_empty() {
+                None
+            } else {
+                Some(text_content)
+            },
             tool_calls,
             input_tokens: usage.prompt_token_count.unwrap_or(0),
             output_tokens: usage.candidates_token_count.unwrap_or(0),
+            cache_read_tokens: usage.cached_content_token_count.unwrap_or(0),
+            cache_write_tokens: 0,
             finish_reason,
             response_id: None,
         })
@@ -586,10 +619,46 @@ impl LlmProvider for GeminiProvider {
     }
 }
 
+fn map_finish_reason(reason: Option<&str>) -> FinishReason {
+    match reason {
+        Some("MAX_TOKENS") => FinishReason::Length,
+        Some("SAFETY") | Some("RECITATION") | Some("PROHIBITED_CONTENT") => {
+            FinishReason::ContentFilter
+        }
+        _ => FinishReason::Stop,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_convert_messages_tool_results_as_function_responses() {
+        let call = ToolCall {
+            id: "call_1".to_string(),
+            name: "search".to_string(),
+            arguments: serde_json::json!({"q": "rust"}),
+        };
+        let messages = vec![
+            ChatMessage::user("Find things"),
+            ChatMessage::assistant_with_tool_calls(None, vec![call.clone(), call]),
+            ChatMessage::tool_result("call_1", "search", "one"),
+            ChatMessage::tool_result("call_1", "search", "two"),
+        ];
+
+        let (_, contents) = GeminiProvider::convert_messages(&messages);
+        assert_eq!(contents.len(), 3);
+        assert_eq!(contents[2].role, "user");
+        assert_eq!(contents[2].parts.len(), 2);
+        assert!(matches!(
+            &contents[2].parts[0],
+            GeminiPart::FunctionResponse { function_response }
+                if function_response.name == "search"
+                    && function_response.response["content"] == "one"
+        ));
+    }
+
     #[test]
     fn test_convert_messages_basic() {
         let messages = vec![
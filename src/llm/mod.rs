@@ -9,35 +9,49 @@
 //! - **Google Gemini**: Direct API access with your own key
 //! - **AWS Bedrock**: AWS-managed models via SigV4 auth
 
+#[cfg(feature = "anthropic")]
+pub mod anthropic;
 pub mod auto_discovery;
 pub mod bedrock;
 mod costs;
 pub mod failover;
+#[cfg(feature = "gemini")]
 pub mod gemini;
 mod nearai;
 mod nearai_chat;
+#[cfg(feature = "ollama")]
+pub mod ollama;
 pub mod openrouter;
 mod provider;
 mod reasoning;
+pub mod redaction;
 mod rig_adapter;
 pub mod session;
+pub mod streaming;
 pub mod thinking;
 
+#[cfg(feature = "anthropic")]
+pub use anthropic::{AnthropicConfig, AnthropicProvider};
 pub use auto_discovery::{DiscoveredModel, ModelDiscovery};
 pub use bedrock::{BedrockConfig, BedrockProvider};
 pub use failover::FailoverProvider;
+#[cfg(feature = "gemini")]
 pub use gemini::{GeminiConfig, GeminiProvider};
 pub use nearai::{ModelInfo, NearAiProvider};
 pub use nearai_chat::NearAiChatProvider;
+#[cfg(feature = "ollama")]
+pub use ollama::OllamaProvider;
 pub use openrouter::OpenRouterProvider;
 pub use provider::{
-    ChatMessage, CompletionRequest, CompletionResponse, FinishReason, LlmProvider, ModelMetadata,
-    Role, ToolCall, ToolCompletionRequest, ToolCompletionResponse, ToolDefinition, ToolResult,
+    CacheControl, ChatMessage, CompletionRequest, CompletionResponse, CompletionStream,
+    FinishReason, LlmProvider, ModelMetadata, Role, StreamDelta, ToolCall, ToolCompletionRequest,
+    ToolCompletionResponse, ToolDefinition, ToolResult,
 };
 pub use reasoning::{
     ActionPlan, Reasoning, ReasoningContext, RespondOutput, RespondResult, TokenUsage,
     ToolSelection,
 };
+pub use redaction::{RedactingProvider, Redactor};
 pub use rig_adapter::RigAdapter;
 pub use session::{SessionConfig, SessionManager, create_session_manager};
 pub use thinking::ThinkingMode;
@@ -54,12 +68,17 @@ use crate::error::LlmError;
 ///
 /// - `NearAi` backend: Uses session manager for authentication (Responses API)
 ///   or API key (Chat Completions API)
+/// - Anthropic, Gemini, Ollama: native providers when their cargo feature is
+///   enabled (the default), rig-core adapter otherwise
 /// - Other backends: Use rig-core adapter with provider-specific clients
+///
+/// The provider is wrapped in a [`RedactingProvider`] when the outbound
+/// redaction profile applies to the backend.
 pub fn create_llm_provider(
     config: &LlmConfig,
     session: Arc<SessionManager>,
 ) -> Result<Arc<dyn LlmProvider>, LlmError> {
-    match config.backend {
+    let provider = match config.backend {
         LlmBackend::NearAi => create_nearai_provider(config, session),
         LlmBackend::OpenAi => create_openai_provider(config),
         LlmBackend::Anthropic => create_anthropic_provider(config),
@@ -68,7 +87,12 @@ pub fn create_llm_provider(
         LlmBackend::Gemini => create_gemini_provider(config),
         LlmBackend::Bedrock => create_bedrock_provider(config),
         LlmBackend::OpenRouter => create_openrouter_provider(config),
-    }
+    }?;
+    Ok(redaction::wrap_provider(
+        provider,
+        &config.redaction,
+        config.backend,
+    ))
 }
 
 fn create_nearai_provider(
@@ -108,6 +132,22 @@ fn create_openai_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, Ll
     Ok(Arc::new(RigAdapter::new(model, &oai.model)))
 }
 
+#[cfg(feature = "anthropic")]
+fn create_anthropic_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, LlmError> {
+    let anth = config
+        .anthropic
+        .as_ref()
+        .ok_or_else(|| LlmError::AuthFailed {
+            provider: "anthropic".to_string(),
+        })?;
+
+    let provider_config = AnthropicConfig::new(anth.api_key.expose_secret(), &anth.model);
+
+    tracing::info!("Using Anthropic Messages API (model: {})", anth.model);
+    Ok(Arc::new(AnthropicProvider::new(provider_config)))
+}
+
+#[cfg(not(feature = "anthropic"))]
 fn create_anthropic_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, LlmError> {
     let anth = config
         .anthropic
@@ -131,6 +171,21 @@ fn create_anthropic_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>,
     Ok(Arc::new(RigAdapter::new(model, &anth.model)))
 }
 
+#[cfg(feature = "ollama")]
+fn create_ollama_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, LlmError> {
+    let oll = config.ollama.as_ref().ok_or_else(|| LlmError::AuthFailed {
+        provider: "ollama".to_string(),
+    })?;
+
+    tracing::info!(
+        "Using Ollama (base_url: {}, model: {})",
+        oll.base_url,
+        oll.model
+    );
+    Ok(Arc::new(OllamaProvider::new(oll.clone())))
+}
+
+#[cfg(not(feature = "ollama"))]
 fn create_ollama_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, LlmError> {
     let oll = config.ollama.as_ref().ok_or_else(|| LlmError::AuthFailed {
         provider: "ollama".to_string(),
@@ -191,6 +246,7 @@ fn create_openai_compatible_provider(config: &LlmConfig) -> Result<Arc<dyn LlmPr
     Ok(Arc::new(RigAdapter::new(model, &compat.model)))
 }
 
+#[cfg(feature = "gemini")]
 fn create_gemini_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, LlmError> {
     let gemini_cfg = config.gemini.as_ref().ok_or_else(|| LlmError::AuthFailed {
         provider: "gemini".to_string(),
@@ -202,6 +258,27 @@ fn create_gemini_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, Ll
     Ok(Arc::new(GeminiProvider::new(provider_config)))
 }
 
+#[cfg(not(feature = "gemini"))]
+fn create_gemini_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, LlmError> {
+    let gemini_cfg = config.gemini.as_ref().ok_or_else(|| LlmError::AuthFailed {
+        provider: "gemini".to_string(),
+    })?;
+
+    use rig::providers::gemini;
+
+    let client: gemini::Client =
+        gemini::Client::new(gemini_cfg.api_key.expose_secret()).map_err(|e| {
+            LlmError::RequestFailed {
+                provider: "gemini".to_string(),
+                reason: format!("Failed to create Gemini client: {}", e),
+            }
+        })?;
+
+    let model = client.completion_model(&gemini_cfg.model);
+    tracing::info!("Using Google Gemini via rig (model: {})", gemini_cfg.model);
+    Ok(Arc::new(RigAdapter::new(model, &gemini_cfg.model)))
+}
+
 fn create_bedrock_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>, LlmError> {
     let bedrock_cfg = config
         .bedrock
@@ -54,10 +54,10 @@ pub struct NearAiProvider {
 impl NearAiProvider {
     /// Create a new NEAR AI provider with a session manager.
     pub fn new(config: NearAiConfig, session: Arc<SessionManager>) -> Self {
-        let client = Client::builder()
+        let client = crate::outbound::client_builder()
             .timeout(std::time::Duration::from_secs(120))
             .build()
-            .unwrap_or_else(|_| Client::new());
+            .unwrap_or_else(|_| crate::outbound::client());
 
         let active_model = std::sync::RwLock::new(config.model.clone());
         Self {
@@ -426,12 +426,15 @@ impl LlmProvider for NearAiProvider {
                     let usage = alt.usage.unwrap_or(NearAiUsage {
                         input_tokens: 0,
                         output_tokens: 0,
+                        input_tokens_details: None,
                     });
                     return Ok(CompletionResponse {
                         content: text,
                         finish_reason: FinishReason::Stop,
                         input_tokens: usage.input_tokens,
                         output_tokens: usage.output_tokens,
+                        cache_read_tokens: usage.cached_tokens(),
+                        cache_write_tokens: 0,
                         response_id: None,
                     });
                 }
@@ -450,6 +453,8 @@ impl LlmProvider for NearAiProvider {
                     finish_reason: FinishReason::Stop,
                     input_tokens: 0,
                     output_tokens: 0,
+                    cache_read_tokens: 0,
+                    cache_write_tokens: 0,
                     response_id: None,
                 });
             }
@@ -517,6 +522,8 @@ impl LlmProvider for NearAiProvider {
             finish_reason: FinishReason::Stop,
             input_tokens: response.usage.input_tokens,
             output_tokens: response.usage.output_tokens,
+            cache_read_tokens: response.usage.cached_tokens(),
+            cache_write_tokens: 0,
             response_id: Some(response.id),
         })
     }
@@ -627,6 +634,7 @@ impl LlmProvider for NearAiProvider {
                     let usage = alt.usage.unwrap_or(NearAiUsage {
                         input_tokens: 0,
                         output_tokens: 0,
+                        input_tokens_details: None,
                     });
 
                     let finish_reason = if tool_calls.is_empty() {
@@ -646,6 +654,8 @@ impl LlmProvider for NearAiProvider {
                         finish_reason,
                         input_tokens: usage.input_tokens,
                         output_tokens: usage.output_tokens,
+                        cache_read_tokens: usage.cached_tokens(),
+                        cache_write_tokens: 0,
                         response_id: None,
                     });
                 }
@@ -664,6 +674,8 @@ impl LlmProvider for NearAiProvider {
                     finish_reason: FinishReason::Stop,
                     input_tokens: 0,
                     output_tokens: 0,
+                    cache_read_tokens: 0,
+                    cache_write_tokens: 0,
                     response_id: None,
                 });
             }
@@ -729,6 +741,8 @@ impl LlmProvider for NearAiProvider {
             finish_reason,
             input_tokens: response.usage.input_tokens,
             output_tokens: response.usage.output_tokens,
+            cache_read_tokens: response.usage.cached_tokens(),
+            cache_write_tokens: 0,
             response_id: Some(response.id),
         })
     }
@@ -772,6 +786,10 @@ impl LlmProvider for NearAiProvider {
     fn get_response_chain_id(&self, thread_id: &str) -> Option<String> {
         self.get_response_id(thread_id)
     }
+
+    fn clear_response_chain(&self, thread_id: &str) {
+        self.clear_chain(thread_id);
+    }
 }
 
 // NEAR AI API types
@@ -947,6 +965,23 @@ struct NearAiContent {
 struct NearAiUsage {
     input_tokens: u32,
     output_tokens: u32,
+    #[serde(default)]
+    input_tokens_details: Option<NearAiInputTokensDetails>,
+}
+
+impl NearAiUsage {
+    /// Input tokens served from the prompt cache.
+    fn cached_tokens(&self) -> u32 {
+        self.input_tokens_details
+            .as_ref()
+            .map_or(0, |d| d.cached_tokens)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct NearAiInputTokensDetails {
+    #[serde(default)]
+    cached_tokens: u32,
 }
 
 #[derive(Debug, Deserialize)]
@@ -4,6 +4,7 @@
 //! with API key authentication (for cloud-api).
 
 use async_trait::async_trait;
+use futures::StreamExt;
 use reqwest::Client;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
@@ -13,9 +14,10 @@ use serde::{Deserialize, Serialize};
 use crate::config::NearAiConfig;
 use crate::error::LlmError;
 use crate::llm::provider::{
-    ChatMessage, CompletionRequest, CompletionResponse, FinishReason, LlmProvider, ModelMetadata,
-    Role, ToolCall, ToolCompletionRequest, ToolCompletionResponse,
+    ChatMessage, CompletionRequest, CompletionResponse, CompletionStream, FinishReason,
+    LlmProvider, ModelMetadata, Role, ToolCall, ToolCompletionRequest, ToolCompletionResponse,
 };
+use crate::llm::streaming::{ChatCompletionChunks, channel_stream};
 
 /// NEAR AI Chat Completions API provider.
 pub struct NearAiChatProvider {
@@ -33,10 +35,10 @@ impl NearAiChatProvider {
             });
         }
 
-        let client = Client::builder()
+        let client = crate::outbound::client_builder()
             .timeout(std::time::Duration::from_secs(120))
             .build()
-            .unwrap_or_else(|_| Client::new());
+            .unwrap_or_else(|_| crate::outbound::client());
 
         let active_model = std::sync::RwLock::new(config.model.clone());
         Ok(Self {
@@ -100,21 +102,7 @@ impl NearAiChatProvider {
         tracing::debug!("NEAR AI Chat response body: {}", response_text);
 
         if !status.is_success() {
-            if status.as_u16() == 401 {
-                return Err(LlmError::AuthFailed {
-                    provider: "nearai_chat".to_string(),
-                });
-            }
-            if status.as_u16() == 429 {
-                return Err(LlmError::RateLimited {
-                    provider: "nearai_chat".to_string(),
-                    retry_after: None,
-                });
-            }
-            return Err(LlmError::RequestFailed {
-                provider: "nearai_chat".to_string(),
-                reason: format!("HTTP {}: {}", status, response_text),
-            });
+            return Err(status_error(status, &response_text));
         }
 
         serde_json::from_str(&response_text).map_err(|e| LlmError::InvalidResponse {
@@ -123,6 +111,42 @@ impl NearAiChatProvider {
         })
     }
 
+    /// Build a chat completions request for `req`.
+    fn tool_request(&self, req: ToolCompletionRequest) -> ChatCompletionRequest {
+        let messages: Vec<ChatCompletionMessage> =
+            req.messages.into_iter().map(|m| m.into()).collect();
+
+        // NEAR AI cloud-api does not support multi-turn tool calling (rejects
+        // any request containing role:"tool" messages with HTTP 400). Rewrite
+        // tool-call / tool-result pairs into plain text so the conversation
+        // history is preserved without using unsupported message roles.
+        let messages = flatten_tool_messages(messages);
+
+        let tools: Vec<ChatCompletionTool> = req
+            .tools
+            .into_iter()
+            .map(|t| ChatCompletionTool {
+                tool_type: "function".to_string(),
+                function: ChatCompletionFunction {
+                    name: t.name,
+                    description: Some(t.description),
+                    parameters: Some(t.parameters),
+                },
+            })
+            .collect();
+
+        ChatCompletionRequest {
+            model: self.active_model_name(),
+            messages,
+            temperature: req.temperature,
+            max_tokens: req.max_tokens,
+            tools: if tools.is_empty() { None } else { Some(tools) },
+            tool_choice: req.tool_choice,
+            stream: None,
+            stream_options: None,
+        }
+    }
+
     /// Fetch available models with full metadata from the `/v1/models` endpoint.
     async fn fetch_models(&self) -> Result<Vec<ApiModelEntry>, LlmError> {
         let url = self.api_url("models");
@@ -184,6 +208,8 @@ impl LlmProvider for NearAiChatProvider {
             max_tokens: req.max_tokens,
             tools: None,
             tool_choice: None,
+            stream: None,
+            stream_options: None,
         };
 
         let response: ChatCompletionResponse = self.send_request(&request).await?;
@@ -212,6 +238,8 @@ impl LlmProvider for NearAiChatProvider {
             finish_reason,
             input_tokens: response.usage.prompt_tokens,
             output_tokens: response.usage.completion_tokens,
+            cache_read_tokens: response.usage.cached_tokens(),
+            cache_write_tokens: 0,
             response_id: None,
         })
     }
@@ -220,37 +248,7 @@ impl LlmProvider for NearAiChatProvider {
         &self,
         req: ToolCompletionRequest,
     ) -> Result<ToolCompletionResponse, LlmError> {
-        let messages: Vec<ChatCompletionMessage> =
-            req.messages.into_iter().map(|m| m.into()).collect();
-
-        // NEAR AI cloud-api does not support multi-turn tool calling (rejects
-        // any request containing role:"tool" messages with HTTP 400). Rewrite
-        // tool-call / tool-result pairs into plain text so the conversation
-        // history is preserved without using unsupported message roles.
-        let messages = flatten_tool_messages(messages);
-
-        let tools: Vec<ChatCompletionTool> = req
-            .tools
-            .into_iter()
-            .map(|t| ChatCompletionTool {
-                tool_type: "function".to_string(),
-                function: ChatCompletionFunction {
-                    name: t.name,
-                    description: Some(t.description),
-                    parameters: Some(t.parameters),
-                },
-            })
-            .collect();
-
-        let request = ChatCompletionRequest {
-            model: self.active_model_name(),
-            messages,
-            temperature: req.temperature,
-            max_tokens: req.max_tokens,
-            tools: if tools.is_empty() { None } else { Some(tools) },
-            tool_choice: req.tool_choice,
-        };
-
+        let request = self.tool_request(req);
         let response: ChatCompletionResponse = self.send_request(&request).await?;
 
         let choice =
@@ -300,10 +298,84 @@ impl LlmProvider for NearAiChatProvider {
             finish_reason,
             input_tokens: response.usage.prompt_tokens,
             output_tokens: response.usage.completion_tokens,
+            cache_read_tokens: response.usage.cached_tokens(),
+            cache_write_tokens: 0,
             response_id: None,
         })
     }
 
+    async fn complete_stream(
+        &self,
+        req: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        let mut request = self.tool_request(req);
+        request.stream = Some(true);
+        request.stream_options = Some(serde_json::json!({ "include_usage": true }));
+
+        let response = self
+            .client
+            .post(self.api_url("chat/completions"))
+            .header("Authorization", format!("Bearer {}", self.api_key()))
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| LlmError::RequestFailed {
+                provider: "nearai_chat".to_string(),
+                reason: e.to_string(),
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(status_error(status, &body));
+        }
+
+        let mut body = response.bytes_stream();
+        Ok(channel_stream(move |tx| async move {
+            let mut decoder = ChatCompletionChunks::new();
+            while let Some(bytes) = body.next().await {
+                let deltas = match bytes {
+                    Ok(bytes) => decoder
+                        .push(&bytes)
+                        .map_err(|reason| LlmError::RequestFailed {
+                            provider: "nearai_chat".to_string(),
+                            reason,
+                        }),
+                    Err(e) => Err(LlmError::RequestFailed {
+                        provider: "nearai_chat".to_string(),
+                        reason: format!("Stream interrupted: {}", e),
+                    }),
+                };
+                match deltas {
+                    Ok(deltas) => {
+                        for delta in deltas {
+                            if tx.send(Ok(delta)).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(e)).await;
+                        return;
+                    }
+                }
+                if decoder.is_done() {
+                    break;
+                }
+            }
+            for delta in decoder.finish() {
+                if tx.send(Ok(delta)).await.is_err() {
+                    return;
+                }
+            }
+        }))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
     fn model_name(&self) -> &str {
         &self.config.model
     }
@@ -359,6 +431,27 @@ struct ChatCompletionRequest {
     tools: Option<Vec<ChatCompletionTool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<serde_json::Value>,
+}
+
+/// Map an unsuccessful HTTP status to an [`LlmError`].
+fn status_error(status: reqwest::StatusCode, body: &str) -> LlmError {
+    match status.as_u16() {
+        401 => LlmError::AuthFailed {
+            provider: "nearai_chat".to_string(),
+        },
+        429 => LlmError::RateLimited {
+            provider: "nearai_chat".to_string(),
+            retry_after: None,
+        },
+        _ => LlmError::RequestFailed {
+            provider: "nearai_chat".to_string(),
+            reason: format!("HTTP {}: {}", status, body),
+        },
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -530,6 +623,23 @@ struct ChatCompletionUsage {
     completion_tokens: u32,
     #[allow(dead_code)]
     total_tokens: u32,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+impl ChatCompletionUsage {
+    /// Prompt tokens served from the automatic prefix cache.
+    fn cached_tokens(&self) -> u32 {
+        self.prompt_tokens_details
+            .as_ref()
+            .map_or(0, |d| d.cached_tokens)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: u32,
 }
 
 #[cfg(test)]
@@ -0,0 +1,643 @@
+//! Ollama provider for local model inference.
+//!
+//! Uses Ollama's native `/api/chat` endpoint, which supports tool calling
+//! for models that advertise it, plus `/api/tags` and `/api/show` for model
+//! listing and context-window discovery. Local models cost nothing.
+
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::config::OllamaConfig;
+use crate::error::LlmError;
+use crate::llm::provider::{
+    ChatMessage, CompletionRequest, CompletionResponse, CompletionStream, FinishReason,
+    LlmProvider, ModelMetadata, Role, StreamDelta, ToolCall, ToolCompletionRequest,
+    ToolCompletionResponse, ToolDefinition,
+};
+use crate::llm::streaming::channel_stream;
+
+/// Ollama provider talking to a local (or LAN) Ollama server.
+pub struct OllamaProvider {
+    client: reqwest::Client,
+    config: OllamaConfig,
+    active_model: RwLock<String>,
+}
+
+impl OllamaProvider {
+    /// Create a new Ollama provider.
+    pub fn new(config: OllamaConfig) -> Self {
+        // Local models can take minutes to load on first use.
+        let client = crate::outbound::client_builder()
+            .timeout(std::time::Duration::from_secs(600))
+            .build()
+            .unwrap_or_else(|_| crate::outbound::client());
+        let active_model = RwLock::new(config.model.clone());
+        Self {
+            client,
+            config,
+            active_model,
+        }
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!(
+            "{}/api/{}",
+            self.config.base_url.trim_end_matches('/'),
+            path
+        )
+    }
+
+    /// Convert IronClaw messages to Ollama chat format.
+    fn convert_messages(messages: &[ChatMessage]) -> Vec<OllamaMessage> {
+        messages
+            .iter()
+            .map(|msg| OllamaMessage {
+                role: match msg.role {
+                    Role::System => "system",
+                    Role::User => "user",
+                    Role::Assistant => "assistant",
+                    Role::Tool => "tool",
+                }
+                .to_string(),
+                content: msg.content.clone(),
+                tool_calls: msg.tool_calls.as_ref().map(|calls| {
+                    calls
+                        .iter()
+                        .map(|tc| OllamaToolCall {
+                            function: OllamaFunctionCall {
+                                name: tc.name.clone(),
+                                arguments: tc.arguments.clone(),
+                            },
+                        })
+                        .collect()
+                }),
+                tool_name: msg.name.clone().filter(|_| msg.role == Role::Tool),
+            })
+            .collect()
+    }
+
+    /// Convert IronClaw tool definitions to Ollama format.
+    fn convert_tools(tools: &[ToolDefinition]) -> Vec<OllamaTool> {
+        tools
+            .iter()
+            .map(|t| OllamaTool {
+                tool_type: "function".to_string(),
+                function: OllamaFunction {
+                    name: t.name.clone(),
+                    description: t.description.clone(),
+                    parameters: t.parameters.clone(),
+                },
+            })
+            .collect()
+    }
+
+    fn map_done_reason(reason: Option<&str>, has_tool_calls: bool) -> FinishReason {
+        if has_tool_calls {
+            return FinishReason::ToolUse;
+        }
+        match reason {
+            Some("stop") | None => FinishReason::Stop,
+            Some("length") => FinishReason::Length,
+            _ => FinishReason::Unknown,
+        }
+    }
+
+    /// Build a chat request for a tool-enabled completion.
+    fn tool_request(&self, request: ToolCompletionRequest) -> OllamaChatRequest {
+        // Ollama has no tool_choice; "none" is honoured by not offering tools.
+        let tools = if request.tool_choice.as_deref() == Some("none") {
+            Vec::new()
+        } else {
+            Self::convert_tools(&request.tools)
+        };
+        OllamaChatRequest {
+            model: self.active_model_name(),
+            messages: Self::convert_messages(&request.messages),
+            tools,
+            stream: false,
+            options: OllamaOptions {
+                temperature: request.temperature,
+                num_predict: request.max_tokens,
+                stop: None,
+            },
+        }
+    }
+
+    /// Send a non-streaming chat request.
+    async fn chat(&self, body: &OllamaChatRequest) -> Result<OllamaChatResponse, LlmError> {
+        self.post(body)
+            .await?
+            .json()
+            .await
+            .map_err(|e| LlmError::RequestFailed {
+                provider: "ollama".to_string(),
+                reason: format!("Failed to parse response: {}", e),
+            })
+    }
+
+    /// Send a chat request, mapping error statuses.
+    async fn post(&self, body: &OllamaChatRequest) -> Result<reqwest::Response, LlmError> {
+        let response = self
+            .client
+            .post(self.api_url("chat"))
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| LlmError::RequestFailed {
+                provider: "ollama".to_string(),
+                reason: format!("HTTP request failed (is Ollama running?): {}", e),
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            let msg = serde_json::from_str::<OllamaErrorResponse>(&error_text)
+                .map(|e| e.error)
+                .unwrap_or(error_text);
+            if status == reqwest::StatusCode::NOT_FOUND {
+                return Err(LlmError::ModelNotAvailable {
+                    provider: "ollama".to_string(),
+                    model: body.model.clone(),
+                });
+            }
+            return Err(LlmError::RequestFailed {
+                provider: "ollama".to_string(),
+                reason: format!("Status {}: {}", status, msg),
+            });
+        }
+
+        Ok(response)
+    }
+}
+
+/// Mint a call id; Ollama doesn't assign them, and results have to be
+/// matched up in the conversation history.
+fn tool_call(call: OllamaToolCall) -> ToolCall {
+    ToolCall {
+        id: format!("call_{}", uuid::Uuid::new_v4().simple()),
+        name: call.function.name,
+        arguments: call.function.arguments,
+    }
+}
+
+// -- Ollama API request/response types --
+
+#[derive(Debug, Serialize)]
+struct OllamaChatRequest {
+    model: String,
+    messages: Vec<OllamaMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<OllamaTool>,
+    stream: bool,
+    options: OllamaOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaMessage {
+    role: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<OllamaToolCall>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaToolCall {
+    function: OllamaFunctionCall,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaFunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaTool {
+    #[serde(rename = "type")]
+    tool_type: String,
+    function: OllamaFunction,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaFunction {
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct OllamaOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OllamaChatResponse {
+    message: Option<OllamaMessage>,
+    done_reason: Option<String>,
+    #[serde(default)]
+    prompt_eval_count: u32,
+    #[serde(default)]
+    eval_count: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaErrorResponse {
+    error: String,
+}
+
+#[async_trait]
+impl LlmProvider for OllamaProvider {
+    fn model_name(&self) -> &str {
+        &self.config.model
+    }
+
+    fn cost_per_token(&self) -> (Decimal, Decimal) {
+        (Decimal::ZERO, Decimal::ZERO)
+    }
+
+    fn active_model_name(&self) -> String {
+        self.active_model
+            .read()
+            .map(|m| m.clone())
+            .unwrap_or_else(|_| self.config.model.clone())
+    }
+
+    fn set_model(&self, model: &str) -> Result<(), LlmError> {
+        if let Ok(mut active) = self.active_model.write() {
+            *active = model.to_string();
+            Ok(())
+        } else {
+            Err(LlmError::RequestFailed {
+                provider: "ollama".to_string(),
+                reason: "Failed to acquire model lock".to_string(),
+            })
+        }
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
+        let body = OllamaChatRequest {
+            model: self.active_model_name(),
+            messages: Self::convert_messages(&request.messages),
+            tools: Vec::new(),
+            stream: false,
+            options: OllamaOptions {
+                temperature: request.temperature,
+                num_predict: request.max_tokens,
+                stop: request.stop_sequences,
+            },
+        };
+
+        let resp = self.chat(&body).await?;
+        Ok(CompletionResponse {
+            content: resp.message.map(|m| m.content).unwrap_or_default(),
+            input_tokens: resp.prompt_eval_count,
+            output_tokens: resp.eval_count,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            finish_reason: Self::map_done_reason(resp.done_reason.as_deref(), false),
+            response_id: None,
+        })
+    }
+
+    async fn complete_with_tools(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<ToolCompletionResponse, LlmError> {
+        let resp = self.chat(&self.tool_request(request)).await?;
+        let (content, calls) = match resp.message {
+            Some(m) => (m.content, m.tool_calls.unwrap_or_default()),
+            None => (String::new(), Vec::new()),
+        };
+        let tool_calls: Vec<ToolCall> = calls.into_iter().map(tool_call).collect();
+
+        Ok(ToolCompletionResponse {
+            content: if content.is_empty() {
+                None
+            } else {
+                Some(content)
+            },
+            finish_reason: Self::map_done_reason(
+                resp.done_reason.as_deref(),
+                !tool_calls.is_empty(),
+            ),
+            tool_calls,
+            input_tokens: resp.prompt_eval_count,
+            output_tokens: resp.eval_count,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            response_id: None,
+        })
+    }
+
+    async fn complete_stream(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        let mut body = self.tool_request(request);
+        body.stream = true;
+        let mut bytes = self.post(&body).await?.bytes_stream();
+
+        Ok(channel_stream(move |tx| async move {
+            let mut decoder = ChatLines::default();
+            while let Some(chunk) = bytes.next().await {
+                let deltas = match chunk {
+                    Ok(chunk) => decoder.push(&chunk),
+                    Err(e) => Err(format!("Stream interrupted: {}", e)),
+                };
+                match deltas {
+                    Ok(deltas) => {
+                        for delta in deltas {
+                            if tx.send(Ok(delta)).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(reason) => {
+                        let _ = tx
+                            .send(Err(LlmError::RequestFailed {
+                                provider: "ollama".to_string(),
+                                reason,
+                            }))
+                            .await;
+                        return;
+                    }
+                }
+                if decoder.done.is_some() {
+                    break;
+                }
+            }
+            let _ = tx.send(Ok(decoder.finish())).await;
+        }))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, LlmError> {
+        let response = self
+            .client
+            .get(self.api_url("tags"))
+            .send()
+            .await
+            .map_err(|e| LlmError::RequestFailed {
+                provider: "ollama".to_string(),
+                reason: format!("Failed to list models: {}", e),
+            })?;
+
+        if !response.status().is_success() {
+            return Ok(Vec::new());
+        }
+
+        #[derive(Deserialize)]
+        struct TagList {
+            #[serde(default)]
+            models: Vec<TagInfo>,
+        }
+        #[derive(Deserialize)]
+        struct TagInfo {
+            name: String,
+        }
+
+        let list: TagList = response.json().await.map_err(|e| LlmError::RequestFailed {
+            provider: "ollama".to_string(),
+            reason: format!("Failed to parse model list: {}", e),
+        })?;
+
+        Ok(list.models.into_iter().map(|m| m.name).collect())
+    }
+
+    async fn model_metadata(&self) -> Result<ModelMetadata, LlmError> {
+        let model = self.active_model_name();
+        let response = self
+            .client
+            .post(self.api_url("show"))
+            .json(&serde_json::json!({ "model": model }))
+            .send()
+            .await
+            .map_err(|e| LlmError::RequestFailed {
+                provider: "ollama".to_string(),
+                reason: format!("Failed to get model metadata: {}", e),
+            })?;
+
+        if !response.status().is_success() {
+            return Ok(ModelMetadata {
+                id: model,
+                context_length: None,
+            });
+        }
+
+        #[derive(Deserialize)]
+        struct ShowResponse {
+            #[serde(default)]
+            model_info: serde_json::Map<String, serde_json::Value>,
+        }
+
+        let info: ShowResponse = response.json().await.map_err(|e| LlmError::RequestFailed {
+            provider: "ollama".to_string(),
+            reason: format!("Failed to parse model info: {}", e),
+        })?;
+
+        Ok(ModelMetadata {
+            id: model,
+            context_length: context_length(&info.model_info),
+        })
+    }
+}
+
+/// Decoder for a streamed `/api/chat` response: one JSON object per line,
+/// the last of which has `"done": true` and carries the usage counts.
+#[derive(Debug, Default)]
+struct ChatLines {
+    line: Vec<u8>,
+    tool_calls: bool,
+    done: Option<OllamaChatResponse>,
+}
+
+impl ChatLines {
+    /// Feed raw response bytes and return the deltas they complete.
+    fn push(&mut self, bytes: &[u8]) -> Result<Vec<StreamDelta>, String> {
+        let mut deltas = Vec::new();
+        for &byte in bytes {
+            if byte != b'\n' {
+                self.line.push(byte);
+                continue;
+            }
+            let line = String::from_utf8_lossy(&self.line).into_owned();
+            self.line.clear();
+            if line.trim().is_empty() {
+                continue;
+            }
+            let value: serde_json::Value =
+                serde_json::from_str(&line).map_err(|e| format!("Malformed stream line: {}", e))?;
+            if let Some(error) = value.get("error") {
+                return Err(error
+                    .as_str()
+                    .map(String::from)
+                    .unwrap_or_else(|| error.to_string()));
+            }
+            let mut chunk: OllamaChatResponse = serde_json::from_value(value.clone())
+                .map_err(|e| format!("Malformed stream line: {}", e))?;
+            if let Some(message) = chunk.message.take() {
+                if !message.content.is_empty() {
+                    deltas.push(StreamDelta::Text(message.content));
+                }
+                for call in message.tool_calls.into_iter().flatten() {
+                    self.tool_calls = true;
+                    deltas.push(StreamDelta::ToolCall(tool_call(call)));
+                }
+            }
+            if value["done"].as_bool() == Some(true) {
+                self.done = Some(chunk);
+            }
+        }
+        Ok(deltas)
+    }
+
+    /// The closing [`StreamDelta::Done`].
+    fn finish(self) -> StreamDelta {
+        let last = self.done.unwrap_or_default();
+        StreamDelta::Done {
+            input_tokens: last.prompt_eval_count,
+            output_tokens: last.eval_count,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            finish_reason: OllamaProvider::map_done_reason(
+                last.done_reason.as_deref(),
+                self.tool_calls,
+            ),
+            response_id: None,
+        }
+    }
+}
+
+/// Pull the context window out of `/api/show` model info, where it is keyed
+/// by architecture (e.g. `llama.context_length`, `qwen2.context_length`).
+fn context_length(model_info: &serde_json::Map<String, serde_json::Value>) -> Option<u32> {
+    model_info
+        .iter()
+        .find(|(k, _)| k.ends_with(".context_length"))
+        .and_then(|(_, v)| v.as_u64())
+        .and_then(|n| u32::try_from(n).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_messages_carries_tool_calls_and_names() {
+        let call = ToolCall {
+            id: "call_1".to_string(),
+            name: "time".to_string(),
+            arguments: serde_json::json!({}),
+        };
+        let messages = vec![
+            ChatMessage::user("What time is it?"),
+            ChatMessage::assistant_with_tool_calls(None, vec![call]),
+            ChatMessage::tool_result("call_1", "time", "12:00"),
+        ];
+
+        let converted = OllamaProvider::convert_messages(&messages);
+        assert_eq!(converted[1].role, "assistant");
+        assert_eq!(
+            converted[1].tool_calls.as_ref().unwrap()[0].function.name,
+            "time"
+        );
+        assert_eq!(converted[2].role, "tool");
+        assert_eq!(converted[2].tool_name.as_deref(), Some("time"));
+        assert!(converted[0].tool_name.is_none());
+    }
+
+    #[test]
+    fn test_response_parsing_with_tool_calls() {
+        let resp: OllamaChatResponse = serde_json::from_value(serde_json::json!({
+            "model": "llama3.1",
+            "message": {
+                "role": "assistant",
+                "content": "",
+                "tool_calls": [{"function": {"name": "time", "arguments": {"tz": "UTC"}}}]
+            },
+            "done": true,
+            "done_reason": "stop",
+            "prompt_eval_count": 30,
+            "eval_count": 8
+        }))
+        .unwrap();
+
+        let calls = resp.message.unwrap().tool_calls.unwrap();
+        assert_eq!(calls[0].function.arguments["tz"], "UTC");
+        assert_eq!(resp.eval_count, 8);
+        assert_eq!(
+            OllamaProvider::map_done_reason(resp.done_reason.as_deref(), true),
+            FinishReason::ToolUse
+        );
+    }
+
+    #[test]
+    fn test_stream_lines_decode_text_tools_and_usage() {
+        let lines = concat!(
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"Hel\"},\"done\":false}\n",
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"lo\"},\"done\":false}\n",
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"\",\"tool_calls\":[{\"function\":{\"name\":\"echo\",\"arguments\":{\"text\":\"hi\"}}}]},\"done\":false}\n",
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,\"done_reason\":\"stop\",\"prompt_eval_count\":14,\"eval_count\":6}\n",
+        );
+
+        let mut decoder = ChatLines::default();
+        let mut deltas = Vec::new();
+        for piece in lines.as_bytes().chunks(9) {
+            deltas.extend(decoder.push(piece).unwrap());
+        }
+        assert!(decoder.done.is_some());
+
+        assert_eq!(deltas[0], StreamDelta::Text("Hel".to_string()));
+        assert_eq!(deltas[1], StreamDelta::Text("lo".to_string()));
+        assert!(matches!(
+            &deltas[2],
+            StreamDelta::ToolCall(call) if call.name == "echo" && call.id.starts_with("call_")
+        ));
+        assert_eq!(
+            decoder.finish(),
+            StreamDelta::Done {
+                input_tokens: 14,
+                output_tokens: 6,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                finish_reason: FinishReason::ToolUse,
+                response_id: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_stream_lines_surface_errors() {
+        let mut decoder = ChatLines::default();
+        let err = decoder
+            .push(b"{\"error\":\"model 'nope' not found\"}\n")
+            .unwrap_err();
+        assert!(err.contains("not found"));
+    }
+
+    #[test]
+    fn test_context_length_from_model_info() {
+        let info = serde_json::json!({
+            "general.architecture": "llama",
+            "llama.context_length": 131072
+        });
+        assert_eq!(context_length(info.as_object().unwrap()), Some(131072));
+        assert_eq!(context_length(&serde_json::Map::new()), None);
+    }
+}
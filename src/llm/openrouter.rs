@@ -14,8 +14,8 @@ use crate::config::OpenRouterConfig;
 use crate::error::LlmError;
 use crate::llm::costs;
 use crate::llm::provider::{
-    ChatMessage, CompletionRequest, CompletionResponse, FinishReason, LlmProvider, ModelMetadata,
-    Role, ToolCall, ToolCompletionRequest, ToolCompletionResponse,
+    CacheControl, ChatMessage, CompletionRequest, CompletionResponse, FinishReason, LlmProvider,
+    ModelMetadata, Role, ToolCall, ToolCompletionRequest, ToolCompletionResponse,
 };
 
 /// OpenRouter LLM provider.
@@ -31,10 +31,10 @@ pub struct OpenRouterProvider {
 impl OpenRouterProvider {
     /// Create a new OpenRouter provider.
     pub fn new(config: OpenRouterConfig) -> Result<Self, LlmError> {
-        let client = Client::builder()
+        let client = crate::outbound::client_builder()
             .timeout(std::time::Duration::from_secs(120))
             .build()
-            .unwrap_or_else(|_| Client::new());
+            .unwrap_or_else(|_| crate::outbound::client());
 
         let active_model = std::sync::RwLock::new(config.model.clone());
         Ok(Self {
@@ -160,6 +160,31 @@ impl OpenRouterProvider {
     }
 }
 
+impl OpenRouterProvider {
+    /// Put a cache breakpoint after the system prompt for models that need
+    /// explicit markers (Anthropic, Gemini). The tools precede the system
+    /// prompt there, so the breakpoint covers them too. Other models cache
+    /// automatically.
+    fn mark_cache(&self, messages: &mut [ChatCompletionMessage], cache: CacheControl) {
+        let model = self.active_model_name();
+        if !cache.is_enabled()
+            || !(model.starts_with("anthropic/") || model.starts_with("google/gemini"))
+        {
+            return;
+        }
+        let Some(system) = messages.iter_mut().rev().find(|m| m.role == "system") else {
+            return;
+        };
+        if let Some(MessageContent::Text(text)) = system.content.take() {
+            system.content = Some(MessageContent::Parts(vec![ContentPart {
+                kind: "text",
+                text,
+                cache_control: Some(serde_json::json!({"type": "ephemeral"})),
+            }]));
+        }
+    }
+}
+
 /// Model entry from the OpenRouter `/models` API.
 #[derive(Debug, Deserialize)]
 struct OpenRouterModelEntry {
@@ -171,8 +196,9 @@ struct OpenRouterModelEntry {
 #[async_trait]
 impl LlmProvider for OpenRouterProvider {
     async fn complete(&self, req: CompletionRequest) -> Result<CompletionResponse, LlmError> {
-        let messages: Vec<ChatCompletionMessage> =
+        let mut messages: Vec<ChatCompletionMessage> =
             req.messages.into_iter().map(|m| m.into()).collect();
+        self.mark_cache(&mut messages, req.cache_control);
 
         let request = ChatCompletionRequest {
             model: self.active_model_name(),
@@ -203,6 +229,8 @@ impl LlmProvider for OpenRouterProvider {
             finish_reason,
             input_tokens: response.usage.prompt_tokens,
             output_tokens: response.usage.completion_tokens,
+            cache_read_tokens: response.usage.cached_tokens().0,
+            cache_write_tokens: response.usage.cached_tokens().1,
             response_id: None,
         })
     }
@@ -211,8 +239,9 @@ impl LlmProvider for OpenRouterProvider {
         &self,
         req: ToolCompletionRequest,
     ) -> Result<ToolCompletionResponse, LlmError> {
-        let messages: Vec<ChatCompletionMessage> =
+        let mut messages: Vec<ChatCompletionMessage> =
             req.messages.into_iter().map(|m| m.into()).collect();
+        self.mark_cache(&mut messages, req.cache_control);
 
         let tools: Vec<ChatCompletionTool> = req
             .tools
@@ -277,6 +306,8 @@ impl LlmProvider for OpenRouterProvider {
             finish_reason,
             input_tokens: response.usage.prompt_tokens,
             output_tokens: response.usage.completion_tokens,
+            cache_read_tokens: response.usage.cached_tokens().0,
+            cache_write_tokens: response.usage.cached_tokens().1,
             response_id: None,
         })
     }
@@ -351,11 +382,11 @@ struct ChatCompletionRequest {
     tool_choice: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize)]
 struct ChatCompletionMessage {
     role: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    content: Option<String>,
+    content: Option<MessageContent>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_call_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -390,7 +421,7 @@ impl From<ChatMessage> for ChatCompletionMessage {
         let content = if role == "assistant" && tool_calls.is_some() && msg.content.is_empty() {
             None
         } else {
-            Some(msg.content)
+            Some(MessageContent::Text(msg.content))
         };
 
         Self {
@@ -403,6 +434,23 @@ impl From<ChatMessage> for ChatCompletionMessage {
     }
 }
 
+/// Message content: plain text, or parts when one carries a cache marker.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct ContentPart {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<serde_json::Value>,
+}
+
 #[derive(Debug, Serialize)]
 struct ChatCompletionTool {
     #[serde(rename = "type")]
@@ -462,6 +510,25 @@ struct ChatCompletionUsage {
     completion_tokens: u32,
     #[allow(dead_code)]
     total_tokens: u32,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+impl ChatCompletionUsage {
+    /// Prompt tokens read from and written to the cache.
+    fn cached_tokens(&self) -> (u32, u32) {
+        self.prompt_tokens_details
+            .as_ref()
+            .map_or((0, 0), |d| (d.cached_tokens, d.cache_write_tokens))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: u32,
+    #[serde(default)]
+    cache_write_tokens: u32,
 }
 
 #[cfg(test)]
@@ -473,7 +540,10 @@ mod tests {
         let msg = ChatMessage::user("Hello from OpenRouter");
         let chat_msg: ChatCompletionMessage = msg.into();
         assert_eq!(chat_msg.role, "user");
-        assert_eq!(chat_msg.content, Some("Hello from OpenRouter".to_string()));
+        assert_eq!(
+            chat_msg.content,
+            Some(MessageContent::Text("Hello from OpenRouter".to_string()))
+        );
     }
 
     #[test]
@@ -505,6 +575,47 @@ mod tests {
         assert_eq!(tc[0].function.name, "search");
     }
 
+    #[test]
+    fn test_cache_marker_only_for_marker_models() {
+        let provider = |model: &str| {
+            OpenRouterProvider::new(OpenRouterConfig {
+                api_key: secrecy::SecretString::from("key".to_string()),
+                model: model.to_string(),
+                base_url: "https://openrouter.ai/api/v1".to_string(),
+                referer: None,
+            })
+            .unwrap()
+        };
+        let messages = || -> Vec<ChatCompletionMessage> {
+            vec![
+                ChatMessage::system("Be brief.").into(),
+                ChatMessage::user("hi").into(),
+            ]
+        };
+
+        let mut marked = messages();
+        provider("anthropic/claude-sonnet-4").mark_cache(&mut marked, CacheControl::STABLE_PREFIX);
+        assert_eq!(
+            serde_json::to_value(&marked[0].content).unwrap(),
+            serde_json::json!([{
+                "type": "text",
+                "text": "Be brief.",
+                "cache_control": {"type": "ephemeral"}
+            }])
+        );
+        assert_eq!(
+            marked[1].content,
+            Some(MessageContent::Text("hi".to_string()))
+        );
+
+        let mut automatic = messages();
+        provider("openai/gpt-4o").mark_cache(&mut automatic, CacheControl::STABLE_PREFIX);
+        assert_eq!(
+            automatic[0].content,
+            Some(MessageContent::Text("Be brief.".to_string()))
+        );
+    }
+
     #[test]
     fn test_parse_finish_reason() {
         assert_eq!(parse_finish_reason(Some("stop")), FinishReason::Stop);
@@ -1,6 +1,9 @@
 //! LLM provider trait and types.
 
+use std::pin::Pin;
+
 use async_trait::async_trait;
+use futures::Stream;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
@@ -101,6 +104,35 @@ impl ChatMessage {
     }
 }
 
+/// Which parts of a request form a stable prefix worth caching.
+///
+/// Providers with explicit cache markers (Anthropic, Bedrock, Anthropic
+/// models on OpenRouter) place a breakpoint after each marked part, so the
+/// next request sharing that prefix reads it from the cache. Providers that
+/// cache automatically (OpenAI, Gemini) ignore the markers. Unchanged parts
+/// must be byte-identical between calls for a cache hit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    /// Cache through the system messages.
+    pub system: bool,
+    /// Cache through the tool definitions.
+    pub tools: bool,
+}
+
+impl CacheControl {
+    /// Cache the system prompt and tool definitions, which the agent keeps
+    /// identical across the iterations of a turn.
+    pub const STABLE_PREFIX: Self = Self {
+        system: true,
+        tools: true,
+    };
+
+    /// Whether any part is marked.
+    pub fn is_enabled(&self) -> bool {
+        self.system || self.tools
+    }
+}
+
 /// Request for a chat completion.
 #[derive(Debug, Clone)]
 pub struct CompletionRequest {
@@ -110,6 +142,8 @@ pub struct CompletionRequest {
     pub stop_sequences: Option<Vec<String>>,
     /// Opaque metadata passed through to the provider (e.g. thread_id for chaining).
     pub metadata: std::collections::HashMap<String, String>,
+    /// Prompt caching breakpoints.
+    pub cache_control: CacheControl,
 }
 
 impl CompletionRequest {
@@ -121,6 +155,7 @@ impl CompletionRequest {
             temperature: None,
             stop_sequences: None,
             metadata: std::collections::HashMap::new(),
+            cache_control: CacheControl::default(),
         }
     }
 
@@ -135,14 +170,25 @@ impl CompletionRequest {
         self.temperature = Some(temperature);
         self
     }
+
+    /// Mark parts of the request for prompt caching.
+    pub fn with_cache_control(mut self, cache_control: CacheControl) -> Self {
+        self.cache_control = cache_control;
+        self
+    }
 }
 
 /// Response from a chat completion.
 #[derive(Debug, Clone)]
 pub struct CompletionResponse {
     pub content: String,
+    /// Prompt tokens, including those read from or written to the cache.
     pub input_tokens: u32,
     pub output_tokens: u32,
+    /// Prompt tokens served from the provider's prompt cache.
+    pub cache_read_tokens: u32,
+    /// Prompt tokens written to the provider's prompt cache.
+    pub cache_write_tokens: u32,
     pub finish_reason: FinishReason,
     /// Provider-specific response ID (e.g. for NEAR AI response chaining).
     pub response_id: Option<String>,
@@ -167,7 +213,7 @@ pub struct ToolDefinition {
 }
 
 /// A tool call requested by the LLM.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ToolCall {
     pub id: String,
     pub name: String,
@@ -194,6 +240,8 @@ pub struct ToolCompletionRequest {
     pub tool_choice: Option<String>,
     /// Opaque metadata passed through to the provider (e.g. thread_id for chaining).
     pub metadata: std::collections::HashMap<String, String>,
+    /// Prompt caching breakpoints.
+    pub cache_control: CacheControl,
 }
 
 impl ToolCompletionRequest {
@@ -206,6 +254,7 @@ impl ToolCompletionRequest {
             temperature: None,
             tool_choice: None,
             metadata: std::collections::HashMap::new(),
+            cache_control: CacheControl::default(),
         }
     }
 
@@ -226,6 +275,12 @@ impl ToolCompletionRequest {
         self.tool_choice = Some(choice.into());
         self
     }
+
+    /// Mark parts of the request for prompt caching.
+    pub fn with_cache_control(mut self, cache_control: CacheControl) -> Self {
+        self.cache_control = cache_control;
+        self
+    }
 }
 
 /// Response from a completion with potential tool calls.
@@ -235,13 +290,39 @@ pub struct ToolCompletionResponse {
     pub content: Option<String>,
     /// Tool calls requested by the model.
     pub tool_calls: Vec<ToolCall>,
+    /// Prompt tokens, including those read from or written to the cache.
     pub input_tokens: u32,
     pub output_tokens: u32,
+    /// Prompt tokens served from the provider's prompt cache.
+    pub cache_read_tokens: u32,
+    /// Prompt tokens written to the provider's prompt cache.
+    pub cache_write_tokens: u32,
     pub finish_reason: FinishReason,
     /// Provider-specific response ID (e.g. for NEAR AI response chaining).
     pub response_id: Option<String>,
 }
 
+/// One piece of a streamed completion.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamDelta {
+    /// Newly generated text.
+    Text(String),
+    /// A tool call, emitted once its arguments are complete.
+    ToolCall(ToolCall),
+    /// The completion finished. Always the last item of a stream.
+    Done {
+        input_tokens: u32,
+        output_tokens: u32,
+        cache_read_tokens: u32,
+        cache_write_tokens: u32,
+        finish_reason: FinishReason,
+        response_id: Option<String>,
+    },
+}
+
+/// A streamed completion, as returned by [`LlmProvider::complete_stream`].
+pub type CompletionStream = Pin<Box<dyn Stream<Item = Result<StreamDelta, LlmError>> + Send>>;
+
 /// Metadata about a model returned by the provider's API.
 #[derive(Debug, Clone)]
 pub struct ModelMetadata {
@@ -268,6 +349,49 @@ pub trait LlmProvider: Send + Sync {
         request: ToolCompletionRequest,
     ) -> Result<ToolCompletionResponse, LlmError>;
 
+    /// Stream a completion as it is generated. Tools in the request are
+    /// offered as in `complete_with_tools`; with none it is a plain chat.
+    ///
+    /// The default waits for the whole response and yields it at once, so
+    /// every provider can be streamed from. Providers that override it also
+    /// return `true` from [`supports_streaming`](Self::supports_streaming).
+    async fn complete_stream(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        let response = if request.tools.is_empty() {
+            let response = self
+                .complete(CompletionRequest {
+                    messages: request.messages,
+                    max_tokens: request.max_tokens,
+                    temperature: request.temperature,
+                    stop_sequences: None,
+                    metadata: request.metadata,
+                    cache_control: request.cache_control,
+                })
+                .await?;
+            ToolCompletionResponse {
+                content: Some(response.content),
+                tool_calls: Vec::new(),
+                input_tokens: response.input_tokens,
+                output_tokens: response.output_tokens,
+                cache_read_tokens: response.cache_read_tokens,
+                cache_write_tokens: response.cache_write_tokens,
+                finish_reason: response.finish_reason,
+                response_id: response.response_id,
+            }
+        } else {
+            self.complete_with_tools(request).await?
+        };
+        Ok(crate::llm::streaming::response_stream(response))
+    }
+
+    /// Whether `complete_stream` yields tokens as the model produces them
+    /// rather than all at the end.
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
     /// List available models from the provider.
     /// Default implementation returns empty list.
     async fn list_models(&self) -> Result<Vec<String>, LlmError> {
@@ -313,6 +437,10 @@ pub trait LlmProvider: Send + Sync {
         None
     }
 
+    /// Forget the response chain for a thread, so the next call sends the
+    /// full context again (e.g. after turns were redacted).
+    fn clear_response_chain(&self, _thread_id: &str) {}
+
     /// Calculate cost for a completion.
     fn calculate_cost(&self, input_tokens: u32, output_tokens: u32) -> Decimal {
         let (input_cost, output_cost) = self.cost_per_token();
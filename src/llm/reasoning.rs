@@ -3,11 +3,14 @@
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
 
 use crate::error::LlmError;
 
+use crate::llm::streaming::collect_stream;
 use crate::llm::{
-    ChatMessage, CompletionRequest, LlmProvider, ToolCall, ToolCompletionRequest, ToolDefinition,
+    CacheControl, ChatMessage, CompletionRequest, LlmProvider, ToolCall, ToolCompletionRequest,
+    ToolCompletionResponse, ToolDefinition,
 };
 use crate::safety::SafetyLayer;
 
@@ -120,6 +123,10 @@ pub struct ToolSelection {
 pub struct TokenUsage {
     pub input_tokens: u32,
     pub output_tokens: u32,
+    /// Part of `input_tokens` served from the prompt cache.
+    pub cache_read_tokens: u32,
+    /// Part of `input_tokens` written to the prompt cache.
+    pub cache_write_tokens: u32,
 }
 
 impl TokenUsage {
@@ -158,6 +165,12 @@ pub struct Reasoning {
     safety: Arc<SafetyLayer>,
     /// Optional workspace for loading identity/system prompts.
     workspace_system_prompt: Option<String>,
+    /// Sampling temperature for conversational responses.
+    temperature: f32,
+    /// Completion token cap for conversational responses.
+    max_tokens: u32,
+    /// Extra style instructions appended to the conversation prompt.
+    style_guidance: Option<String>,
 }
 
 impl Reasoning {
@@ -167,6 +180,9 @@ impl Reasoning {
             llm,
             safety,
             workspace_system_prompt: None,
+            temperature: 0.7,
+            max_tokens: 4096,
+            style_guidance: None,
         }
     }
 
@@ -181,6 +197,25 @@ impl Reasoning {
         self
     }
 
+    /// Set the sampling temperature for conversational responses.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    /// Set the completion token cap for conversational responses.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Add style instructions (from `/style`, `/verbosity`) to the
+    /// conversation prompt.
+    pub fn with_style_guidance(mut self, guidance: Option<String>) -> Self {
+        self.style_guidance = guidance;
+        self
+    }
+
     /// Generate a plan for completing a goal.
     pub async fn plan(&self, context: &ReasoningContext) -> Result<ActionPlan, LlmError> {
         let system_prompt = self.build_planning_prompt(context);
@@ -333,63 +368,24 @@ Respond in JSON format:
         let mut messages = vec![ChatMessage::system(system_prompt)];
         messages.extend(context.messages.clone());
 
-        // If we have tools, use tool completion mode
+        // If we have tools, use tool completion mode. The system prompt and
+        // tools stay the same across a turn's iterations, so they are cached.
         if !context.available_tools.is_empty() {
             let mut request = ToolCompletionRequest::new(messages, context.available_tools.clone())
-                .with_max_tokens(4096)
-                .with_temperature(0.7)
-                .with_tool_choice("auto");
+                .with_max_tokens(self.max_tokens)
+                .with_temperature(self.temperature)
+                .with_tool_choice("auto")
+                .with_cache_control(CacheControl::STABLE_PREFIX);
             request.metadata = context.metadata.clone();
 
             let response = self.llm.complete_with_tools(request).await?;
-            let usage = TokenUsage {
-                input_tokens: response.input_tokens,
-                output_tokens: response.output_tokens,
-            };
-
-            // If there were tool calls, return them for execution
-            if !response.tool_calls.is_empty() {
-                return Ok(RespondOutput {
-                    result: RespondResult::ToolCalls {
-                        tool_calls: response.tool_calls,
-                        content: response.content,
-                    },
-                    usage,
-                });
-            }
-
-            let content = response
-                .content
-                .unwrap_or_else(|| "I'm not sure how to respond to that.".to_string());
-
-            // Some models (e.g. GLM-4.7) emit tool calls as XML tags in content
-            // instead of using the structured tool_calls field. Try to recover
-            // them before giving up and returning plain text.
-            let recovered = recover_tool_calls_from_content(&content, &context.available_tools);
-            if !recovered.is_empty() {
-                let cleaned = clean_response(&content);
-                return Ok(RespondOutput {
-                    result: RespondResult::ToolCalls {
-                        tool_calls: recovered,
-                        content: if cleaned.is_empty() {
-                            None
-                        } else {
-                            Some(cleaned)
-                        },
-                    },
-                    usage,
-                });
-            }
-
-            Ok(RespondOutput {
-                result: RespondResult::Text(clean_response(&content)),
-                usage,
-            })
+            Ok(tool_output(response, &context.available_tools))
         } else {
             // No tools, use simple completion
             let mut request = CompletionRequest::new(messages)
-                .with_max_tokens(4096)
-                .with_temperature(0.7);
+                .with_max_tokens(self.max_tokens)
+                .with_temperature(self.temperature)
+                .with_cache_control(CacheControl::STABLE_PREFIX);
             request.metadata = context.metadata.clone();
 
             let response = self.llm.complete(request).await?;
@@ -398,11 +394,56 @@ Respond in JSON format:
                 usage: TokenUsage {
                     input_tokens: response.input_tokens,
                     output_tokens: response.output_tokens,
+                    cache_read_tokens: response.cache_read_tokens,
+                    cache_write_tokens: response.cache_write_tokens,
                 },
             })
         }
     }
 
+    /// Like [`respond_with_tools`](Self::respond_with_tools), but sends the
+    /// reply text to `chunks` as the model produces it. Model-internal tags
+    /// are held back. Falls back to a single buffered call when the provider
+    /// cannot stream, in which case nothing is sent.
+    pub async fn respond_with_tools_streaming(
+        &self,
+        context: &ReasoningContext,
+        chunks: &mpsc::UnboundedSender<String>,
+    ) -> Result<RespondOutput, LlmError> {
+        if !self.llm.supports_streaming() {
+            return self.respond_with_tools(context).await;
+        }
+
+        let system_prompt = self.build_conversation_prompt(context);
+        let mut messages = vec![ChatMessage::system(system_prompt)];
+        messages.extend(context.messages.clone());
+
+        let mut request = ToolCompletionRequest::new(messages, context.available_tools.clone())
+            .with_max_tokens(self.max_tokens)
+            .with_temperature(self.temperature)
+            .with_cache_control(CacheControl::STABLE_PREFIX);
+        if !context.available_tools.is_empty() {
+            request = request.with_tool_choice("auto");
+        }
+        request.metadata = context.metadata.clone();
+
+        let stream = self.llm.complete_stream(request).await?;
+        let mut filter = StreamTagFilter::default();
+        let response = collect_stream(stream, |text| {
+            let visible = filter.push(text);
+            if !visible.is_empty() {
+                let _ = chunks.send(visible);
+            }
+        })
+        .await?;
+        let rest = filter.finish();
+        if !rest.is_empty() {
+            let _ = chunks.send(rest);
+        }
+
+        Ok(tool_output(response, &context.available_tools))
+    }
+
     fn build_planning_prompt(&self, context: &ReasoningContext) -> String {
         let tools_desc = if context.available_tools.is_empty() {
             "No tools available.".to_string()
@@ -461,6 +502,11 @@ Respond with a JSON plan in this format:
             )
         };
 
+        let style_section = match self.style_guidance {
+            Some(ref guidance) => format!("\n\n{}", guidance),
+            None => String::new(),
+        };
+
         // Include workspace identity prompt if available
         let identity_section = if let Some(ref identity) = self.workspace_system_prompt {
             format!("\n\n---\n\n{}", identity)
@@ -488,10 +534,10 @@ Here's the solution: [actual response to user]
 - Be concise and direct
 - Use markdown formatting where helpful
 - For code, use appropriate code blocks with language tags
-- Call tools when they would help accomplish the task{}
+- Call tools when they would help accomplish the task{}{}
 
 The user sees ONLY content outside <thinking> tags.{}"#,
-            tools_section, identity_section
+            tools_section, style_section, identity_section
         )
     }
 
@@ -620,6 +666,56 @@ fn clean_response(text: &str) -> String {
     strip_reasoning_patterns(&text)
 }
 
+/// Turn a tool completion into a [`RespondOutput`], recovering tool calls
+/// that the model wrote into its text instead of the structured field.
+fn tool_output(response: ToolCompletionResponse, tools: &[ToolDefinition]) -> RespondOutput {
+    let usage = TokenUsage {
+        input_tokens: response.input_tokens,
+        output_tokens: response.output_tokens,
+        cache_read_tokens: response.cache_read_tokens,
+        cache_write_tokens: response.cache_write_tokens,
+    };
+
+    // If there were tool calls, return them for execution
+    if !response.tool_calls.is_empty() {
+        return RespondOutput {
+            result: RespondResult::ToolCalls {
+                tool_calls: response.tool_calls,
+                content: response.content,
+            },
+            usage,
+        };
+    }
+
+    let content = response
+        .content
+        .unwrap_or_else(|| "I'm not sure how to respond to that.".to_string());
+
+    // Some models (e.g. GLM-4.7) emit tool calls as XML tags in content
+    // instead of using the structured tool_calls field. Try to recover
+    // them before giving up and returning plain text.
+    let recovered = recover_tool_calls_from_content(&content, tools);
+    if !recovered.is_empty() {
+        let cleaned = clean_response(&content);
+        return RespondOutput {
+            result: RespondResult::ToolCalls {
+                tool_calls: recovered,
+                content: if cleaned.is_empty() {
+                    None
+                } else {
+                    Some(cleaned)
+                },
+            },
+            usage,
+        };
+    }
+
+    RespondOutput {
+        result: RespondResult::Text(clean_response(&content)),
+        usage,
+    }
+}
+
 /// Tags that are model-internal and should never reach users.
 const INTERNAL_TAGS: &[&str] = &["thinking", "tool_call", "function_call", "tool_calls"];
 
@@ -712,6 +808,115 @@ fn strip_pipe_tag(text: &str, tag: &str) -> String {
     result
 }
 
+/// Removes [`INTERNAL_TAGS`] blocks from text arriving in pieces.
+///
+/// Text that might be the start of a tag is held until the next piece
+/// settles it; everything inside a tag block is dropped.
+#[derive(Debug, Default)]
+struct StreamTagFilter {
+    buf: String,
+    /// Closing tag of the block being dropped.
+    hidden_until: Option<String>,
+}
+
+/// How the start of a buffer that begins with `<` relates to the tags.
+enum TagStart {
+    /// An opening tag of `len` bytes, closed by `close`.
+    Open { len: usize, close: String },
+    /// Could still become an opening tag.
+    Partial,
+    /// Not a tag.
+    No,
+}
+
+impl StreamTagFilter {
+    /// Feed `text` and return the part that is safe to show.
+    fn push(&mut self, text: &str) -> String {
+        self.buf.push_str(text);
+        let mut out = String::new();
+        loop {
+            if let Some(ref close) = self.hidden_until {
+                if let Some(pos) = self.buf.find(close.as_str()) {
+                    self.buf.drain(..pos + close.len());
+                    self.hidden_until = None;
+                    continue;
+                }
+                // Keep what could be the beginning of the closing tag.
+                let keep = (1..close.len())
+                    .rev()
+                    .find(|&k| self.buf.ends_with(&close[..k]))
+                    .unwrap_or(0);
+                self.buf.drain(..self.buf.len() - keep);
+                return out;
+            }
+
+            let Some(lt) = self.buf.find('<') else {
+                out.push_str(&self.buf);
+                self.buf.clear();
+                return out;
+            };
+            out.push_str(&self.buf[..lt]);
+            self.buf.drain(..lt);
+            match tag_start(&self.buf) {
+                TagStart::Open { len, close } => {
+                    self.buf.drain(..len);
+                    self.hidden_until = Some(close);
+                }
+                TagStart::Partial => return out,
+                TagStart::No => {
+                    out.push('<');
+                    self.buf.drain(..1);
+                }
+            }
+        }
+    }
+
+    /// Text still held back once the stream has ended.
+    fn finish(&mut self) -> String {
+        if self.hidden_until.is_some() {
+            // Unclosed block, dropped like strip_xml_tag does.
+            self.buf.clear();
+        }
+        std::mem::take(&mut self.buf)
+    }
+}
+
+fn tag_start(buf: &str) -> TagStart {
+    let mut partial = false;
+    for tag in INTERNAL_TAGS {
+        let forms = [
+            (format!("<{}>", tag), format!("</{}>", tag)),
+            (format!("<|{}|>", tag), format!("<|/{}|>", tag)),
+        ];
+        for (open, close) in forms {
+            if buf.starts_with(&open) {
+                return TagStart::Open {
+                    len: open.len(),
+                    close,
+                };
+            }
+            partial |= open.starts_with(buf);
+        }
+
+        // <tag attr="...">
+        let prefix = format!("<{} ", tag);
+        if buf.starts_with(&prefix) {
+            return match buf.find('>') {
+                Some(end) => TagStart::Open {
+                    len: end + 1,
+                    close: format!("</{}>", tag),
+                },
+                None => TagStart::Partial,
+            };
+        }
+    }
+    if partial {
+        TagStart::Partial
+    } else {
+        TagStart::No
+    }
+}
+
 /// Strip any remaining reasoning that wasn't in proper <thinking> tags.
 ///
 /// This is a simple fallback for models that don't follow the <thinking> tag
@@ -877,6 +1082,25 @@ Here is my response to your question."#;
         assert_eq!(output, "Done.");
     }
 
+    #[test]
+    fn test_stream_filter_hides_tags_split_across_pieces() {
+        let pieces = [
+            "Let me <thin",
+            "king>private ",
+            "plan</thi",
+            "nking>check. a < b, <b>bold</b> <|tool_call|>x<|/tool_call|>",
+            "<tool_call name=\"echo\">{}</tool_call>done",
+        ];
+        let mut filter = StreamTagFilter::default();
+        let mut shown: String = pieces.iter().map(|p| filter.push(p)).collect();
+        shown.push_str(&filter.finish());
+        assert_eq!(shown, "Let me check. a < b, <b>bold</b> done");
+
+        let mut filter = StreamTagFilter::default();
+        assert_eq!(filter.push("answer <"), "answer ");
+        assert_eq!(filter.finish(), "<");
+    }
+
     #[test]
     fn test_clean_response_preserves_normal_content() {
         let input = "The function tool_call_handler works great. No tags here!";
@@ -4,15 +4,17 @@
 //! `Arc<dyn LlmProvider>` without changing any of the agent, reasoning, or tool code.
 
 use async_trait::async_trait;
+use futures::StreamExt;
 use rig::OneOrMany;
 use rig::completion::{
-    AssistantContent, CompletionModel, CompletionRequest as RigRequest,
+    AssistantContent, CompletionModel, CompletionRequest as RigRequest, GetTokenUsage,
     ToolDefinition as RigToolDefinition, Usage as RigUsage,
 };
 use rig::message::{
     Message as RigMessage, ToolChoice as RigToolChoice, ToolFunction, ToolResult as RigToolResult,
     ToolResultContent, UserContent,
 };
+use rig::streaming::StreamedAssistantContent;
 use rust_decimal::Decimal;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
@@ -20,10 +22,11 @@ use serde::de::DeserializeOwned;
 use crate::error::LlmError;
 use crate::llm::costs;
 use crate::llm::provider::{
-    ChatMessage, CompletionRequest, CompletionResponse, FinishReason, LlmProvider,
-    ToolCall as IronToolCall, ToolCompletionRequest, ToolCompletionResponse,
-    ToolDefinition as IronToolDefinition,
+    ChatMessage, CompletionRequest, CompletionResponse, CompletionStream, FinishReason,
+    LlmProvider, StreamDelta, ToolCall as IronToolCall, ToolCompletionRequest,
+    ToolCompletionResponse, ToolDefinition as IronToolDefinition,
 };
+use crate::llm::streaming::channel_stream;
 
 /// Adapter that wraps a rig-core `CompletionModel` and implements `LlmProvider`.
 pub struct RigAdapter<M: CompletionModel> {
@@ -255,6 +258,8 @@ where
             content: text.unwrap_or_default(),
             input_tokens: saturate_u32(response.usage.input_tokens),
             output_tokens: saturate_u32(response.usage.output_tokens),
+            cache_read_tokens: saturate_u32(response.usage.cached_input_tokens),
+            cache_write_tokens: 0,
             finish_reason: finish,
             response_id: None,
         })
@@ -293,11 +298,103 @@ where
             tool_calls,
             input_tokens: saturate_u32(response.usage.input_tokens),
             output_tokens: saturate_u32(response.usage.output_tokens),
+            cache_read_tokens: saturate_u32(response.usage.cached_input_tokens),
+            cache_write_tokens: 0,
             finish_reason: finish,
             response_id: None,
         })
     }
 
+    async fn complete_stream(
+        &self,
+        request: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        let (preamble, history) = convert_messages(&request.messages);
+        let tools = convert_tools(&request.tools);
+        let tool_choice = if tools.is_empty() {
+            None
+        } else {
+            convert_tool_choice(request.tool_choice.as_deref())
+        };
+
+        let rig_req = build_rig_request(
+            preamble,
+            history,
+            tools,
+            tool_choice,
+            request.temperature,
+            request.max_tokens,
+        )?;
+
+        let provider = self.model_name.clone();
+        let mut stream = self
+            .model
+            .stream(rig_req)
+            .await
+            .map_err(|e| LlmError::RequestFailed {
+                provider: provider.clone(),
+                reason: e.to_string(),
+            })?;
+
+        Ok(channel_stream(move |tx| async move {
+            let mut saw_tool_call = false;
+            let mut usage = None;
+            while let Some(item) = stream.next().await {
+                let delta = match item {
+                    Ok(StreamedAssistantContent::Text(text)) if !text.text.is_empty() => {
+                        StreamDelta::Text(text.text)
+                    }
+                    Ok(StreamedAssistantContent::ToolCall { tool_call, .. }) => {
+                        saw_tool_call = true;
+                        StreamDelta::ToolCall(IronToolCall {
+                            id: tool_call.id,
+                            name: tool_call.function.name,
+                            arguments: tool_call.function.arguments,
+                        })
+                    }
+                    Ok(StreamedAssistantContent::Final(response)) => {
+                        usage = response.token_usage();
+                        continue;
+                    }
+                    // Reasoning and partial tool-call deltas are not surfaced.
+                    Ok(_) => continue,
+                    Err(e) => {
+                        let _ = tx
+                            .send(Err(LlmError::RequestFailed {
+                                provider,
+                                reason: e.to_string(),
+                            }))
+                            .await;
+                        return;
+                    }
+                };
+                if tx.send(Ok(delta)).await.is_err() {
+                    return;
+                }
+            }
+
+            let usage = usage.unwrap_or_else(RigUsage::new);
+            let _ = tx
+                .send(Ok(StreamDelta::Done {
+                    input_tokens: saturate_u32(usage.input_tokens),
+                    output_tokens: saturate_u32(usage.output_tokens),
+                    cache_read_tokens: saturate_u32(usage.cached_input_tokens),
+                    cache_write_tokens: 0,
+                    finish_reason: if saw_tool_call {
+                        FinishReason::ToolUse
+                    } else {
+                        FinishReason::Stop
+                    },
+                    response_id: None,
+                }))
+                .await;
+        }))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
     fn active_model_name(&self) -> String {
         self.model_name.clone()
     }
@@ -72,10 +72,10 @@ impl SessionManager {
     pub fn new(config: SessionConfig) -> Self {
         let manager = Self {
             config,
-            client: Client::builder()
+            client: crate::outbound::client_builder()
                 .timeout(std::time::Duration::from_secs(30))
                 .build()
-                .unwrap_or_else(|_| Client::new()),
+                .unwrap_or_else(|_| crate::outbound::client()),
             token: RwLock::new(None),
             renewal_lock: Mutex::new(()),
             store: RwLock::new(None),
@@ -103,10 +103,10 @@ impl SessionManager {
     pub async fn new_async(config: SessionConfig) -> Self {
         let manager = Self {
             config,
-            client: Client::builder()
+            client: crate::outbound::client_builder()
                 .timeout(std::time::Duration::from_secs(30))
                 .build()
-                .unwrap_or_else(|_| Client::new()),
+                .unwrap_or_else(|_| crate::outbound::client()),
             token: RwLock::new(None),
             renewal_lock: Mutex::new(()),
             store: RwLock::new(None),
@@ -0,0 +1,384 @@
+//! Helpers for streamed completions.
+//!
+//! Providers build a [`CompletionStream`] with [`channel_stream`], feeding it
+//! from a task, or fall back to [`response_stream`] over a finished response.
+//! [`collect_stream`] drains a stream back into a [`ToolCompletionResponse`],
+//! and [`ChatCompletionChunks`] decodes the server-sent events of an
+//! OpenAI-style `/chat/completions` call made with `"stream": true`.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+
+use futures::StreamExt;
+use tokio::sync::mpsc;
+
+use crate::error::LlmError;
+use crate::llm::provider::{
+    CompletionStream, FinishReason, StreamDelta, ToolCall, ToolCompletionResponse,
+};
+
+/// A stream yielding a finished response all at once.
+pub fn response_stream(response: ToolCompletionResponse) -> CompletionStream {
+    let mut deltas = Vec::new();
+    if let Some(text) = response.content.filter(|t| !t.is_empty()) {
+        deltas.push(Ok(StreamDelta::Text(text)));
+    }
+    deltas.extend(
+        response
+            .tool_calls
+            .into_iter()
+            .map(|call| Ok(StreamDelta::ToolCall(call))),
+    );
+    deltas.push(Ok(StreamDelta::Done {
+        input_tokens: response.input_tokens,
+        output_tokens: response.output_tokens,
+        cache_read_tokens: response.cache_read_tokens,
+        cache_write_tokens: response.cache_write_tokens,
+        finish_reason: response.finish_reason,
+        response_id: response.response_id,
+    }));
+    Box::pin(futures::stream::iter(deltas))
+}
+
+/// A stream fed by `produce`, which runs as its own task. Once the stream
+/// is dropped its sends fail, which is the task's cue to stop.
+pub fn channel_stream<F, Fut>(produce: F) -> CompletionStream
+where
+    F: FnOnce(mpsc::Sender<Result<StreamDelta, LlmError>>) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(64);
+    tokio::spawn(produce(tx));
+    Box::pin(tokio_stream::wrappers::ReceiverStream::new(rx))
+}
+
+/// Drain `stream` into a response, handing each text delta to `on_text`.
+///
+/// A stream that ends without [`StreamDelta::Done`] keeps what it produced,
+/// with no usage and an unknown finish reason.
+pub async fn collect_stream(
+    mut stream: CompletionStream,
+    mut on_text: impl FnMut(&str),
+) -> Result<ToolCompletionResponse, LlmError> {
+    let mut content = String::new();
+    let mut tool_calls = Vec::new();
+    let mut response = ToolCompletionResponse {
+        content: None,
+        tool_calls: Vec::new(),
+        input_tokens: 0,
+        output_tokens: 0,
+        cache_read_tokens: 0,
+        cache_write_tokens: 0,
+        finish_reason: FinishReason::Unknown,
+        response_id: None,
+    };
+
+    while let Some(delta) = stream.next().await {
+        match delta? {
+            StreamDelta::Text(text) => {
+                on_text(&text);
+                content.push_str(&text);
+            }
+            StreamDelta::ToolCall(call) => tool_calls.push(call),
+            StreamDelta::Done {
+                input_tokens,
+                output_tokens,
+                cache_read_tokens,
+                cache_write_tokens,
+                finish_reason,
+                response_id,
+            } => {
+                response.input_tokens = input_tokens;
+                response.output_tokens = output_tokens;
+                response.cache_read_tokens = cache_read_tokens;
+                response.cache_write_tokens = cache_write_tokens;
+                response.finish_reason = finish_reason;
+                response.response_id = response_id;
+                break;
+            }
+        }
+    }
+
+    response.content = (!content.is_empty()).then_some(content);
+    response.tool_calls = tool_calls;
+    Ok(response)
+}
+
+/// Map an OpenAI `finish_reason`.
+pub fn finish_reason(reason: Option<&str>, has_tool_calls: bool) -> FinishReason {
+    match reason {
+        Some("stop") => FinishReason::Stop,
+        Some("length") => FinishReason::Length,
+        Some("tool_calls") | Some("function_call") => FinishReason::ToolUse,
+        Some("content_filter") => FinishReason::ContentFilter,
+        _ if has_tool_calls => FinishReason::ToolUse,
+        _ => FinishReason::Unknown,
+    }
+}
+
+/// Cached prompt tokens from an OpenAI-style `usage` object, as
+/// `(read, written)`. `prompt_tokens_details.cached_tokens` counts reads;
+/// OpenRouter adds `cache_write_tokens` for providers that bill writes.
+pub fn cached_prompt_tokens(usage: &serde_json::Value) -> (u32, u32) {
+    let details = &usage["prompt_tokens_details"];
+    let count = |key: &str| details[key].as_u64().unwrap_or(0) as u32;
+    (count("cached_tokens"), count("cache_write_tokens"))
+}
+
+/// A tool call being assembled from its deltas.
+#[derive(Debug, Default)]
+struct PartialToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// Decoder for an OpenAI chat completions event stream.
+///
+/// Text deltas come out of [`push`](Self::push) as they arrive. Tool-call
+/// fragments are stitched together by index and, with the usage and finish
+/// reason, come out of [`finish`](Self::finish).
+#[derive(Debug, Default)]
+pub struct ChatCompletionChunks {
+    line: Vec<u8>,
+    tool_calls: BTreeMap<u64, PartialToolCall>,
+    finish_reason: Option<String>,
+    input_tokens: u32,
+    output_tokens: u32,
+    cache_read_tokens: u32,
+    cache_write_tokens: u32,
+    id: Option<String>,
+    done: bool,
+}
+
+impl ChatCompletionChunks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the `[DONE]` sentinel has been seen.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Feed raw response bytes and return the text deltas they complete.
+    /// An `error` event from the server is returned as `Err`.
+    pub fn push(&mut self, bytes: &[u8]) -> Result<Vec<StreamDelta>, String> {
+        let mut deltas = Vec::new();
+        for &byte in bytes {
+            if byte != b'\n' {
+                self.line.push(byte);
+                continue;
+            }
+            let line = String::from_utf8_lossy(&self.line).into_owned();
+            self.line.clear();
+            if let Some(text) = self.handle_line(line.trim_end_matches('\r'))? {
+                deltas.push(StreamDelta::Text(text));
+            }
+        }
+        Ok(deltas)
+    }
+
+    fn handle_line(&mut self, line: &str) -> Result<Option<String>, String> {
+        let Some(data) = line.strip_prefix("data:") else {
+            // Blank separators, comments (":") and other fields.
+            return Ok(None);
+        };
+        let data = data.trim();
+        if data == "[DONE]" {
+            self.done = true;
+            return Ok(None);
+        }
+        let chunk: serde_json::Value = match serde_json::from_str(data) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                tracing::debug!("Skipping unparseable stream chunk: {}", e);
+                return Ok(None);
+            }
+        };
+
+        if let Some(error) = chunk.get("error") {
+            return Err(error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .map(String::from)
+                .unwrap_or_else(|| error.to_string()));
+        }
+        if self.id.is_none() {
+            self.id = chunk["id"].as_str().map(String::from);
+        }
+        if let Some(usage) = chunk.get("usage").filter(|u| u.is_object()) {
+            self.input_tokens = usage["prompt_tokens"].as_u64().unwrap_or(0) as u32;
+            self.output_tokens = usage["completion_tokens"].as_u64().unwrap_or(0) as u32;
+            (self.cache_read_tokens, self.cache_write_tokens) = cached_prompt_tokens(usage);
+        }
+
+        let Some(choice) = chunk["choices"].get(0) else {
+            return Ok(None);
+        };
+        if let Some(reason) = choice["finish_reason"].as_str() {
+            self.finish_reason = Some(reason.to_string());
+        }
+        let delta = &choice["delta"];
+        for call in delta["tool_calls"].as_array().into_iter().flatten() {
+            let index = call["index"].as_u64().unwrap_or(0);
+            let partial = self.tool_calls.entry(index).or_default();
+            if let Some(id) = call["id"].as_str() {
+                partial.id = id.to_string();
+            }
+            if let Some(name) = call["function"]["name"].as_str() {
+                partial.name.push_str(name);
+            }
+            if let Some(arguments) = call["function"]["arguments"].as_str() {
+                partial.arguments.push_str(arguments);
+            }
+        }
+        Ok(delta["content"]
+            .as_str()
+            .filter(|text| !text.is_empty())
+            .map(String::from))
+    }
+
+    /// The assembled tool calls followed by [`StreamDelta::Done`].
+    pub fn finish(self) -> Vec<StreamDelta> {
+        let has_tool_calls = !self.tool_calls.is_empty();
+        let mut deltas: Vec<StreamDelta> = self
+            .tool_calls
+            .into_values()
+            .map(|partial| {
+                let arguments = serde_json::from_str(&partial.arguments)
+                    .unwrap_or(serde_json::Value::Object(Default::default()));
+                StreamDelta::ToolCall(ToolCall {
+                    id: partial.id,
+                    name: partial.name,
+                    arguments,
+                })
+            })
+            .collect();
+        deltas.push(StreamDelta::Done {
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            cache_read_tokens: self.cache_read_tokens,
+            cache_write_tokens: self.cache_write_tokens,
+            finish_reason: finish_reason(self.finish_reason.as_deref(), has_tool_calls),
+            response_id: self.id,
+        });
+        deltas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_decode_text_tools_and_usage() {
+        let events = concat!(
+            "data: {\"id\":\"c1\",\"choices\":[{\"delta\":{\"role\":\"assistant\",\"content\":\"Hel\"}}]}\n\n",
+            "data: {\"id\":\"c1\",\"choices\":[{\"delta\":{\"content\":\"lo\"}}]}\n\n",
+            ": keep-alive\n\n",
+            "data: {\"id\":\"c1\",\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_1\",",
+            "\"function\":{\"name\":\"echo\",\"arguments\":\"{\\\"te\"}}]}}]}\n\n",
+            "data: {\"id\":\"c1\",\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,",
+            "\"function\":{\"arguments\":\"xt\\\":\\\"hi\\\"}\"}}]},\"finish_reason\":\"tool_calls\"}]}\n\n",
+            "data: {\"id\":\"c1\",\"choices\":[],\"usage\":{\"prompt_tokens\":12,\"completion_tokens\":7,\"prompt_tokens_details\":{\"cached_tokens\":8}}}\n\n",
+            "data: [DONE]\n\n",
+        );
+
+        // Feed in awkward pieces, splitting lines and JSON mid-way.
+        let mut decoder = ChatCompletionChunks::new();
+        let mut text = String::new();
+        for piece in events.as_bytes().chunks(7) {
+            for delta in decoder.push(piece).unwrap() {
+                if let StreamDelta::Text(t) = delta {
+                    text.push_str(&t);
+                }
+            }
+        }
+        assert_eq!(text, "Hello");
+        assert!(decoder.is_done());
+
+        let tail = decoder.finish();
+        assert_eq!(
+            tail[0],
+            StreamDelta::ToolCall(ToolCall {
+                id: "call_1".to_string(),
+                name: "echo".to_string(),
+                arguments: serde_json::json!({"text": "hi"}),
+            })
+        );
+        assert_eq!(
+            tail[1],
+            StreamDelta::Done {
+                input_tokens: 12,
+                output_tokens: 7,
+                cache_read_tokens: 8,
+                cache_write_tokens: 0,
+                finish_reason: FinishReason::ToolUse,
+                response_id: Some("c1".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_chunks_surface_server_errors() {
+        let mut decoder = ChatCompletionChunks::new();
+        let err = decoder
+            .push(b"data: {\"error\":{\"message\":\"model overloaded\"}}\n")
+            .unwrap_err();
+        assert_eq!(err, "model overloaded");
+    }
+
+    #[tokio::test]
+    async fn test_collect_stream_round_trips_a_response() {
+        let response = ToolCompletionResponse {
+            content: Some("Checking the weather.".to_string()),
+            tool_calls: vec![ToolCall {
+                id: "call_1".to_string(),
+                name: "weather".to_string(),
+                arguments: serde_json::json!({"city": "Oslo"}),
+            }],
+            input_tokens: 20,
+            output_tokens: 9,
+            cache_read_tokens: 16,
+            cache_write_tokens: 0,
+            finish_reason: FinishReason::ToolUse,
+            response_id: None,
+        };
+
+        let mut seen = Vec::new();
+        let collected = collect_stream(response_stream(response), |t| seen.push(t.to_string()))
+            .await
+            .unwrap();
+        assert_eq!(seen, ["Checking the weather."]);
+        assert_eq!(collected.content.as_deref(), Some("Checking the weather."));
+        assert_eq!(collected.tool_calls.len(), 1);
+        assert_eq!(collected.output_tokens, 9);
+        assert_eq!(collected.cache_read_tokens, 16);
+        assert_eq!(collected.finish_reason, FinishReason::ToolUse);
+    }
+
+    #[tokio::test]
+    async fn test_channel_stream_forwards_and_stops_when_dropped() {
+        let (stopped_tx, stopped_rx) = tokio::sync::oneshot::channel();
+        let mut stream = channel_stream(|tx| async move {
+            for i in 0.. {
+                if tx
+                    .send(Ok(StreamDelta::Text(format!("{i} "))))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            let _ = stopped_tx.send(());
+        });
+
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            StreamDelta::Text("0 ".to_string())
+        );
+        drop(stream);
+        stopped_rx.await.unwrap();
+    }
+}
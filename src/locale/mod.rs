@@ -0,0 +1,167 @@
+//! User locale: time zone and language preferences.
+//!
+//! A user's time zone resolves in this order:
+//! 1. the user's per-channel override (`locale.channel_timezones.<channel>`),
+//! 2. the user's `locale.timezone` setting,
+//! 3. the configured per-channel override,
+//! 4. the configured default (`AGENT_TIMEZONE`, chosen during onboarding,
+//!    or the host's zone).
+//!
+//! The resolved locale feeds the system prompt, the `time` tool, cron
+//! routines, heartbeat checks, and natural-language date parsing.
+
+mod parse;
+mod tz;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, FixedOffset, Utc};
+
+use crate::db::Database;
+
+pub use parse::parse_when;
+pub use tz::{Tz, system_zone_name};
+
+/// Language tag used when none is configured.
+pub const DEFAULT_LANGUAGE: &str = "en-US";
+
+/// Time zone and language for one user on one channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserLocale {
+    pub tz: Tz,
+    /// BCP 47 language tag (e.g. "en-GB").
+    pub language: String,
+}
+
+impl Default for UserLocale {
+    fn default() -> Self {
+        Self {
+            tz: Tz::utc(),
+            language: DEFAULT_LANGUAGE.to_string(),
+        }
+    }
+}
+
+/// Configured locale defaults for users without their own settings.
+#[derive(Debug, Clone, Default)]
+pub struct LocaleDefaults {
+    pub locale: UserLocale,
+    /// Per-channel time zone overrides.
+    pub channel_timezones: HashMap<String, Tz>,
+}
+
+impl LocaleDefaults {
+    /// The default locale on a channel.
+    pub fn for_channel(&self, channel: &str) -> UserLocale {
+        match self.channel_timezones.get(channel) {
+            Some(tz) => UserLocale::new(tz.clone(), self.locale.language.clone()),
+            None => self.locale.clone(),
+        }
+    }
+}
+
+impl UserLocale {
+    pub fn new(tz: Tz, language: impl Into<String>) -> Self {
+        Self {
+            tz,
+            language: language.into(),
+        }
+    }
+
+    /// Whether numeric dates are written day first ("20/10").
+    ///
+    /// Only US English (and bare "en") uses month-first order.
+    pub fn day_first(&self) -> bool {
+        let lang = self.language.to_ascii_lowercase().replace('_', "-");
+        !(lang == "en" || lang.starts_with("en-us"))
+    }
+
+    /// Local wall-clock time at an instant.
+    pub fn local(&self, at: DateTime<Utc>) -> DateTime<FixedOffset> {
+        self.tz.to_local(at)
+    }
+
+    /// One-line statement of the user's current local time, for prompts.
+    pub fn prompt_line(&self, now: DateTime<Utc>) -> String {
+        format!(
+            "Current time for the user: {} {} ({}, UTC{}). Interpret times the user mentions \
+             in this zone.",
+            self.local(now).format("%A %Y-%m-%d %H:%M"),
+            self.tz.abbreviation_at(now),
+            self.tz.name(),
+            self.local(now).format("%:z"),
+        )
+    }
+
+    /// Resolve a user's locale on a channel from their stored settings,
+    /// falling back to the configured defaults for anything unset or invalid.
+    pub async fn resolve(
+        store: Option<&Arc<dyn Database>>,
+        user_id: &str,
+        channel: &str,
+        defaults: &LocaleDefaults,
+    ) -> UserLocale {
+        let default = defaults.for_channel(channel);
+        let Some(store) = store else {
+            return default;
+        };
+
+        let mut tz = None;
+        let keys = [
+            format!("locale.channel_timezones.{}", channel),
+            "locale.timezone".to_string(),
+        ];
+        for key in &keys {
+            if let Ok(Some(value)) = store.get_setting(user_id, key).await
+                && let Some(name) = value.as_str()
+            {
+                match Tz::parse(name) {
+                    Ok(parsed) => {
+                        tz = Some(parsed);
+                        break;
+                    }
+                    Err(e) => tracing::warn!("Ignoring setting {} for {}: {}", key, user_id, e),
+                }
+            }
+        }
+
+        let language = match store.get_setting(user_id, "locale.language").await {
+            Ok(Some(value)) => value.as_str().map(String::from),
+            _ => None,
+        };
+
+        UserLocale {
+            tz: tz.unwrap_or(default.tz),
+            language: language.unwrap_or(default.language),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_first_and_prompt_line() {
+        let tz = Tz::parse("Europe/Berlin").unwrap();
+        let locale = UserLocale::new(tz, "de-DE");
+        assert!(locale.day_first());
+        assert!(!UserLocale::new(Tz::utc(), "en_US").day_first());
+
+        let now: DateTime<Utc> = "2026-07-03T10:00:00Z".parse().unwrap();
+        let line = locale.prompt_line(now);
+        assert!(line.contains("Friday 2026-07-03 12:00 CEST"), "{}", line);
+        assert!(line.contains("Europe/Berlin, UTC+02:00"), "{}", line);
+    }
+
+    #[test]
+    fn test_defaults_channel_override() {
+        let mut defaults = LocaleDefaults::default();
+        defaults
+            .channel_timezones
+            .insert("telegram".to_string(), Tz::parse("+09:00").unwrap());
+        assert_eq!(defaults.for_channel("telegram").tz.name(), "+09:00");
+        assert_eq!(defaults.for_channel("repl").tz, Tz::utc());
+    }
+}
@@ -0,0 +1,433 @@
+//! Natural-language date/time parsing ("tomorrow at 6pm", "in 2 hours").
+//!
+//! Wall-clock expressions are interpreted in the user's time zone, so
+//! "6pm" means 18:00 where the user is, on the correct side of any DST
+//! change.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+
+use crate::locale::UserLocale;
+
+/// Time used when only a date is given ("tomorrow", "friday").
+const DEFAULT_HOUR: u32 = 9;
+
+/// Resolve an expression to an instant, relative to `now`.
+///
+/// Accepts RFC 3339 timestamps, relative offsets ("in 90 minutes",
+/// "in 2 hours 30 minutes"), and a date and/or time in either order:
+/// "today", "tonight", "tomorrow", weekdays ("next friday"), "2026-10-20",
+/// "oct 20", "20/10" (day/month order follows the locale), "6pm", "6:30 pm",
+/// "18:00", "noon", "morning". A bare time that already passed today means
+/// tomorrow. Returns `None` when the expression is not understood.
+pub fn parse_when(input: &str, now: DateTime<Utc>, locale: &UserLocale) -> Option<DateTime<Utc>> {
+    let text = input.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    let lower = text.to_lowercase().replace([',', '!'], " ");
+    let tokens: Vec<&str> = lower
+        .split_whitespace()
+        .filter(|t| !matches!(*t, "on" | "the" | "by"))
+        .collect();
+    match tokens.as_slice() {
+        [] => return None,
+        ["now"] => return Some(now),
+        ["in", rest @ ..] => return parse_duration(rest).map(|d| now + d),
+        [rest @ .., "from", "now"] => return parse_duration(rest).map(|d| now + d),
+        _ => {}
+    }
+
+    let local_now = locale.tz.to_local(now).naive_local();
+    let today = local_now.date();
+    let mut date: Option<NaiveDate> = None;
+    let mut default_time = NaiveTime::from_hms_opt(DEFAULT_HOUR, 0, 0)?;
+    let mut time: Option<NaiveTime> = None;
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let rest = &tokens[i..];
+        if rest[0] == "at" {
+            let (t, used) = parse_time(&rest[1..], true)?;
+            if time.replace(t).is_some() {
+                return None;
+            }
+            i += 1 + used;
+            continue;
+        }
+        if date.is_none()
+            && let Some((d, hint, used)) = parse_date(rest, today, locale.day_first())
+        {
+            date = Some(d);
+            if let Some(hint) = hint {
+                default_time = hint;
+            }
+            i += used;
+            continue;
+        }
+        if time.is_none()
+            && let Some((t, used)) = parse_time(rest, false)
+        {
+            time = Some(t);
+            i += used;
+            continue;
+        }
+        return None;
+    }
+
+    let wall = match (date, time) {
+        (Some(d), t) => d.and_time(t.unwrap_or(default_time)),
+        (None, Some(t)) => {
+            let candidate = today.and_time(t);
+            if candidate > local_now {
+                candidate
+            } else {
+                (today + Duration::days(1)).and_time(t)
+            }
+        }
+        (None, None) => return None,
+    };
+    Some(locale.tz.from_local(wall))
+}
+
+/// "2 hours 30 minutes", "an hour", "half an hour", "90 min".
+fn parse_duration(tokens: &[&str]) -> Option<Duration> {
+    if tokens == ["half", "an", "hour"] {
+        return Some(Duration::minutes(30));
+    }
+    let mut total = Duration::zero();
+    let mut i = 0;
+    while i < tokens.len() {
+        if tokens[i] == "and" {
+            i += 1;
+            continue;
+        }
+        // Accept both "2 hours" and "2h".
+        let split = tokens[i]
+            .find(|c: char| !c.is_ascii_digit())
+            .filter(|&idx| idx > 0);
+        let (amount, unit, used) = match split {
+            Some(idx) => (&tokens[i][..idx], &tokens[i][idx..], 1),
+            None => (tokens[i], *tokens.get(i + 1)?, 2),
+        };
+        let amount: i64 = match amount {
+            "a" | "an" | "one" => 1,
+            n => n.parse().ok()?,
+        };
+        let unit = match unit.trim_end_matches('s') {
+            "sec" | "second" => Duration::seconds(1),
+            "m" | "min" | "minute" => Duration::minutes(1),
+            "h" | "hr" | "hour" => Duration::hours(1),
+            "d" | "day" => Duration::days(1),
+            "w" | "wk" | "week" => Duration::weeks(1),
+            _ => return None,
+        };
+        total += unit * amount as i32;
+        i += used;
+    }
+    (total > Duration::zero()).then_some(total)
+}
+
+/// A date expression at the start of `tokens`: the date, an optional default
+/// time it implies ("tonight"), and the number of tokens consumed.
+fn parse_date(
+    tokens: &[&str],
+    today: NaiveDate,
+    day_first: bool,
+) -> Option<(NaiveDate, Option<NaiveTime>, usize)> {
+    let first = tokens[0];
+    match first {
+        "today" => return Some((today, None, 1)),
+        "tonight" => return Some((today, NaiveTime::from_hms_opt(20, 0, 0), 1)),
+        "tomorrow" | "tmrw" => return Some((today + Duration::days(1), None, 1)),
+        "day" if tokens.get(1..3) == Some(&["after", "tomorrow"][..]) => {
+            return Some((today + Duration::days(2), None, 3));
+        }
+        "next" | "this" => {
+            let next = *tokens.get(1)?;
+            if next == "week" && first == "next" {
+                return Some((today + Duration::weeks(1), None, 2));
+            }
+            let weekday = parse_weekday(next)?;
+            return Some((next_weekday(today, weekday), None, 2));
+        }
+        _ => {}
+    }
+    if let Some(weekday) = parse_weekday(first) {
+        return Some((next_weekday(today, weekday), None, 1));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(first, "%Y-%m-%d") {
+        return Some((date, None, 1));
+    }
+    if let Some(date) = parse_numeric_date(first, today, day_first) {
+        return Some((date, None, 1));
+    }
+
+    // "oct 20 [2026]" or "20th october [2026]".
+    let second = tokens.get(1).copied()?;
+    let (month, day) = match (parse_month(first), parse_month(second)) {
+        (Some(m), None) => (m, parse_day(second)?),
+        (None, Some(m)) => (m, parse_day(first)?),
+        _ => return None,
+    };
+    if let Some(year) = tokens.get(2).and_then(|t| t.parse::<i32>().ok())
+        && (1970..=9999).contains(&year)
+    {
+        return Some((NaiveDate::from_ymd_opt(year, month, day)?, None, 3));
+    }
+    Some((upcoming(today, month, day)?, None, 2))
+}
+
+/// A time expression at the start of `tokens` and the tokens consumed.
+/// `allow_bare_hour` accepts "9" (after "at").
+fn parse_time(tokens: &[&str], allow_bare_hour: bool) -> Option<(NaiveTime, usize)> {
+    let first = *tokens.first()?;
+    let named = match first {
+        "noon" | "midday" => Some(12),
+        "midnight" => Some(0),
+        "morning" => Some(9),
+        "afternoon" => Some(15),
+        "evening" => Some(18),
+        "night" => Some(20),
+        _ => None,
+    };
+    if let Some(hour) = named {
+        return Some((NaiveTime::from_hms_opt(hour, 0, 0)?, 1));
+    }
+
+    let (clock, meridiem, used) = if let Some(clock) = first
+        .strip_suffix("am")
+        .or_else(|| first.strip_suffix("a.m."))
+    {
+        (clock, Some(false), 1)
+    } else if let Some(clock) = first
+        .strip_suffix("pm")
+        .or_else(|| first.strip_suffix("p.m."))
+    {
+        (clock, Some(true), 1)
+    } else {
+        match tokens.get(1).copied() {
+            Some("am" | "a.m.") => (first, Some(false), 2),
+            Some("pm" | "p.m.") => (first, Some(true), 2),
+            _ => (first, None, 1),
+        }
+    };
+
+    let (hour, minute) = match clock.split_once([':', '.']) {
+        Some((h, m)) if m.len() == 2 => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        Some(_) => return None,
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+    let hour = match meridiem {
+        Some(pm) => {
+            if !(1..=12).contains(&hour) {
+                return None;
+            }
+            match (hour, pm) {
+                (12, false) => 0,
+                (12, true) => 12,
+                (h, true) => h + 12,
+                (h, false) => h,
+            }
+        }
+        // "18:00" is unambiguous; a bare "9" only counts after "at".
+        None if clock.contains([':', '.']) || allow_bare_hour => hour,
+        None => return None,
+    };
+    Some((NaiveTime::from_hms_opt(hour, minute, 0)?, used))
+}
+
+/// "20/10", "10/20/2026", "20.10.2026".
+fn parse_numeric_date(token: &str, today: NaiveDate, day_first: bool) -> Option<NaiveDate> {
+    // Dots only as "20.10.2026" so "10.30" stays a time.
+    let parts: Vec<&str> = if token.contains('/') {
+        token.split('/').collect()
+    } else {
+        token.split('.').collect()
+    };
+    if !(2..=3).contains(&parts.len()) || (parts.len() == 2 && !token.contains('/')) {
+        return None;
+    }
+    let a: u32 = parts[0].parse().ok()?;
+    let b: u32 = parts[1].parse().ok()?;
+    let (day, month) = if day_first { (a, b) } else { (b, a) };
+    match parts.get(2) {
+        Some(year) => {
+            let year: i32 = year.parse().ok()?;
+            let year = if year < 100 { 2000 + year } else { year };
+            NaiveDate::from_ymd_opt(year, month, day)
+        }
+        None => upcoming(today, month, day),
+    }
+}
+
+/// The next occurrence of month/day on or after today.
+fn upcoming(today: NaiveDate, month: u32, day: u32) -> Option<NaiveDate> {
+    let this_year = NaiveDate::from_ymd_opt(today.year(), month, day);
+    match this_year {
+        Some(date) if date >= today => Some(date),
+        _ => NaiveDate::from_ymd_opt(today.year() + 1, month, day),
+    }
+}
+
+/// The next given weekday strictly after today.
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    today + Duration::days(if ahead == 0 { 7 } else { ahead as i64 })
+}
+
+fn parse_weekday(token: &str) -> Option<Weekday> {
+    let weekday = match token.get(..3)? {
+        "mon" => Weekday::Mon,
+        "tue" => Weekday::Tue,
+        "wed" => Weekday::Wed,
+        "thu" => Weekday::Thu,
+        "fri" => Weekday::Fri,
+        "sat" => Weekday::Sat,
+        "sun" => Weekday::Sun,
+        _ => return None,
+    };
+    // Reject words that merely share a prefix ("monkey", "sunny").
+    let full = [
+        "monday",
+        "tuesday",
+        "wednesday",
+        "thursday",
+        "friday",
+        "saturday",
+        "sunday",
+    ][weekday.num_days_from_monday() as usize];
+    full.starts_with(token).then_some(weekday)
+}
+
+fn parse_month(token: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+    let token = token.trim_end_matches('.');
+    if token.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|m| m.starts_with(token) || (token == "sept" && *m == "september"))
+        .map(|i| i as u32 + 1)
+}
+
+/// "20", "20th", "1st".
+fn parse_day(token: &str) -> Option<u32> {
+    let digits = token.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let day: u32 = digits.parse().ok()?;
+    (1..=31).contains(&day).then_some(day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locale::Tz;
+
+    fn locale(language: &str) -> UserLocale {
+        UserLocale {
+            tz: Tz::parse("America/New_York").unwrap(),
+            language: language.to_string(),
+        }
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_times_resolve_in_user_zone() {
+        // Friday 2026-10-16 14:00 EDT.
+        let now = at("2026-10-16T18:00:00Z");
+        let l = locale("en-US");
+        assert_eq!(parse_when("6pm", now, &l), Some(at("2026-10-16T22:00:00Z")));
+        assert_eq!(
+            parse_when("at 6:30 pm", now, &l),
+            Some(at("2026-10-16T22:30:00Z"))
+        );
+        // Already past today: tomorrow.
+        assert_eq!(parse_when("9am", now, &l), Some(at("2026-10-17T13:00:00Z")));
+        assert_eq!(
+            parse_when("tomorrow at 9", now, &l),
+            Some(at("2026-10-17T13:00:00Z"))
+        );
+        assert_eq!(
+            parse_when("tonight", now, &l),
+            Some(at("2026-10-17T00:00:00Z"))
+        );
+        assert_eq!(
+            parse_when("monday morning", now, &l),
+            Some(at("2026-10-19T13:00:00Z"))
+        );
+        assert_eq!(
+            parse_when("next friday noon", now, &l),
+            Some(at("2026-10-23T16:00:00Z"))
+        );
+        // After the fall-back change, 9am is EST.
+        assert_eq!(
+            parse_when("nov 2 9am", now, &l),
+            Some(at("2026-11-02T14:00:00Z"))
+        );
+        assert_eq!(
+            parse_when("2026-12-01 18:00", now, &l),
+            Some(at("2026-12-01T23:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_relative_and_absolute() {
+        let now = at("2026-10-16T18:00:00Z");
+        let l = locale("en-US");
+        assert_eq!(
+            parse_when("in 90 minutes", now, &l),
+            Some(now + Duration::minutes(90))
+        );
+        assert_eq!(
+            parse_when("in 2 hours and 15 min", now, &l),
+            Some(now + Duration::minutes(135))
+        );
+        assert_eq!(
+            parse_when("in an hour", now, &l),
+            Some(now + Duration::hours(1))
+        );
+        assert_eq!(
+            parse_when("3d from now", now, &l),
+            Some(now + Duration::days(3))
+        );
+        assert_eq!(
+            parse_when("2026-10-20T08:00:00+02:00", now, &l),
+            Some(at("2026-10-20T06:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_numeric_dates_follow_locale_and_reject_noise() {
+        let now = at("2026-10-16T18:00:00Z");
+        assert_eq!(
+            parse_when("11/3 10:00", now, &locale("en-US")),
+            Some(at("2026-11-03T15:00:00Z"))
+        );
+        assert_eq!(
+            parse_when("11/3 10:00", now, &locale("en-GB")),
+            Some(at("2027-03-11T15:00:00Z"))
+        );
+        let l = locale("en-US");
+        assert_eq!(parse_when("sometime soon", now, &l), None);
+        assert_eq!(parse_when("9", now, &l), None);
+        assert_eq!(parse_when("13pm", now, &l), None);
+        assert_eq!(parse_when("in 5 parsecs", now, &l), None);
+    }
+}
@@ -0,0 +1,291 @@
+//! Time zones backed by the IANA database bundled with `chrono-tz`.
+//!
+//! Zones carry their full history, so past instants (events in an imported
+//! calendar, old messages) get the offset that was in effect at the time,
+//! and nothing is read from the host's zoneinfo directory.
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::OffsetName;
+
+/// A named time zone with DST rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tz {
+    name: String,
+    zone: Zone,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Zone {
+    Iana(chrono_tz::Tz),
+    Fixed(FixedOffset),
+}
+
+impl Tz {
+    /// Coordinated Universal Time.
+    pub fn utc() -> Self {
+        Self {
+            name: "UTC".to_string(),
+            zone: Zone::Iana(chrono_tz::UTC),
+        }
+    }
+
+    /// Look up a zone by IANA name ("America/New_York"), "UTC", or a fixed
+    /// offset ("+05:30", "UTC-3").
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("empty time zone".to_string());
+        }
+        if matches!(
+            name.to_ascii_uppercase().as_str(),
+            "UTC" | "Z" | "GMT" | "ETC/UTC"
+        ) {
+            return Ok(Self::utc());
+        }
+        if let Some(offset) = parse_fixed_offset(name).and_then(FixedOffset::east_opt) {
+            return Ok(Self {
+                name: name.to_string(),
+                zone: Zone::Fixed(offset),
+            });
+        }
+        let zone: chrono_tz::Tz = name
+            .parse()
+            .map_err(|_| format!("unknown time zone '{}'", name))?;
+        Ok(Self {
+            name: name.to_string(),
+            zone: Zone::Iana(zone),
+        })
+    }
+
+    /// The zone name as given by the user.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// UTC offset in effect at an instant.
+    pub fn offset_at(&self, at: DateTime<Utc>) -> FixedOffset {
+        match self.zone {
+            Zone::Iana(tz) => tz.offset_from_utc_datetime(&at.naive_utc()).fix(),
+            Zone::Fixed(offset) => offset,
+        }
+    }
+
+    /// Abbreviation in effect at an instant (e.g. "EDT"). Zones without
+    /// one use their numeric offset ("+0530"), fixed offsets their name.
+    pub fn abbreviation_at(&self, at: DateTime<Utc>) -> String {
+        match self.zone {
+            Zone::Iana(tz) => {
+                let offset = tz.offset_from_utc_datetime(&at.naive_utc());
+                match offset.abbreviation() {
+                    Some(abbr) => abbr.to_string(),
+                    None => offset.fix().to_string().replace(':', ""),
+                }
+            }
+            Zone::Fixed(_) => self.name.clone(),
+        }
+    }
+
+    /// Convert an instant to local wall-clock time.
+    pub fn to_local(&self, at: DateTime<Utc>) -> DateTime<FixedOffset> {
+        at.with_timezone(&self.offset_at(at))
+    }
+
+    /// Convert a local wall-clock time to an instant.
+    ///
+    /// Ambiguous times (the repeated hour when clocks fall back) resolve to
+    /// the earlier instant. Nonexistent times (skipped when clocks spring
+    /// forward) are shifted forward by the length of the gap.
+    pub fn from_local(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        let tz = match self.zone {
+            Zone::Iana(tz) => tz,
+            Zone::Fixed(offset) => return (local - offset).and_utc(),
+        };
+        match tz.from_local_datetime(&local) {
+            chrono::LocalResult::Single(at) => at.with_timezone(&Utc),
+            chrono::LocalResult::Ambiguous(earlier, _) => earlier.with_timezone(&Utc),
+            // In a gap, interpret the time with the offset in effect before it.
+            chrono::LocalResult::None => {
+                let before = (local - chrono::Duration::days(1)).and_utc();
+                (local - self.offset_at(before)).and_utc()
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Tz {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// The host's configured zone name, from `TZ` or the `/etc/localtime` link.
+pub fn system_zone_name() -> Option<String> {
+    if let Ok(tz) = std::env::var("TZ") {
+        let tz = tz.trim_start_matches(':').trim();
+        if !tz.is_empty() && Tz::parse(tz).is_ok() {
+            return Some(tz.to_string());
+        }
+    }
+    let target = std::fs::read_link("/etc/localtime").ok()?;
+    let target = target.to_string_lossy();
+    let (_, name) = target.split_once("zoneinfo/")?;
+    Tz::parse(name).ok().map(|_| name.to_string())
+}
+
+/// Parse "+05:30", "-0800", "UTC+2", "GMT-03:00" style fixed offsets.
+fn parse_fixed_offset(s: &str) -> Option<i32> {
+    let upper = s.to_ascii_uppercase();
+    let rest = upper
+        .strip_prefix("UTC")
+        .or_else(|| upper.strip_prefix("GMT"))
+        .unwrap_or(&upper);
+    let (sign, digits) = match rest.as_bytes().first()? {
+        b'+' => (1, &rest[1..]),
+        b'-' => (-1, &rest[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = match digits.split_once(':') {
+        Some((h, m)) => (h, m),
+        None if digits.len() == 4 => digits.split_at(2),
+        None => (digits, "0"),
+    };
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if hours > 14 || minutes >= 60 {
+        return None;
+    }
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn local(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn new_york() -> Tz {
+        Tz::parse("America/New_York").unwrap()
+    }
+
+    #[test]
+    fn test_offsets_follow_dst_rules() {
+        let ny = new_york();
+        assert_eq!(
+            ny.offset_at(utc("2026-01-15T12:00:00Z")).local_minus_utc(),
+            -5 * 3600
+        );
+        assert_eq!(
+            ny.offset_at(utc("2026-07-15T12:00:00Z")).local_minus_utc(),
+            -4 * 3600
+        );
+        assert_eq!(ny.abbreviation_at(utc("2026-07-15T12:00:00Z")), "EDT");
+        // 2026-03-08 02:00 EST is 07:00 UTC.
+        assert_eq!(
+            ny.offset_at(utc("2026-03-08T06:59:59Z")).local_minus_utc(),
+            -5 * 3600
+        );
+        assert_eq!(
+            ny.offset_at(utc("2026-03-08T07:00:00Z")).local_minus_utc(),
+            -4 * 3600
+        );
+
+        let sydney = Tz::parse("Australia/Sydney").unwrap();
+        assert_eq!(
+            sydney
+                .offset_at(utc("2026-01-15T00:00:00Z"))
+                .local_minus_utc(),
+            11 * 3600
+        );
+        assert_eq!(
+            sydney
+                .offset_at(utc("2026-07-15T00:00:00Z"))
+                .local_minus_utc(),
+            10 * 3600
+        );
+
+        let kolkata = Tz::parse("Asia/Kolkata").unwrap();
+        assert_eq!(
+            kolkata.offset_at(Utc::now()).local_minus_utc(),
+            5 * 3600 + 1800
+        );
+    }
+
+    #[test]
+    fn test_historical_offsets() {
+        // Before 2007, US DST started on the first Sunday of April.
+        let ny = new_york();
+        assert_eq!(
+            ny.offset_at(utc("2006-03-20T12:00:00Z")).local_minus_utc(),
+            -5 * 3600
+        );
+        assert_eq!(
+            ny.from_local(local("2006-03-20 09:00")),
+            utc("2006-03-20T14:00:00Z")
+        );
+        // Moscow was UTC+4 year-round from 2011 to 2014.
+        let moscow = Tz::parse("Europe/Moscow").unwrap();
+        assert_eq!(
+            moscow
+                .offset_at(utc("2013-01-15T00:00:00Z"))
+                .local_minus_utc(),
+            4 * 3600
+        );
+        assert_eq!(
+            moscow
+                .offset_at(utc("2026-01-15T00:00:00Z"))
+                .local_minus_utc(),
+            3 * 3600
+        );
+    }
+
+    #[test]
+    fn test_from_local_handles_gaps_and_overlaps() {
+        let ny = new_york();
+        assert_eq!(
+            ny.from_local(local("2026-07-01 18:00")),
+            utc("2026-07-01T22:00:00Z")
+        );
+        // 02:30 does not exist on spring-forward day; it becomes 03:30 EDT.
+        assert_eq!(
+            ny.from_local(local("2026-03-08 02:30")),
+            utc("2026-03-08T07:30:00Z")
+        );
+        // 01:30 happens twice on fall-back day; the first (EDT) one wins.
+        assert_eq!(
+            ny.from_local(local("2026-11-01 01:30")),
+            utc("2026-11-01T05:30:00Z")
+        );
+    }
+
+    #[test]
+    fn test_parse_names_and_offsets() {
+        assert_eq!(Tz::parse("utc").unwrap(), Tz::utc());
+        let fixed = Tz::parse("+05:30").unwrap();
+        assert_eq!(fixed.offset_at(Utc::now()).local_minus_utc(), 19800);
+        assert_eq!(fixed.abbreviation_at(Utc::now()), "+05:30");
+        assert_eq!(
+            Tz::parse("UTC-3")
+                .unwrap()
+                .offset_at(Utc::now())
+                .local_minus_utc(),
+            -3 * 3600
+        );
+        assert!(Tz::parse("../etc/passwd").is_err());
+        assert!(Tz::parse("Not/AZone").is_err());
+
+        let berlin = Tz::parse("Europe/Berlin").unwrap();
+        assert_eq!(
+            berlin
+                .offset_at(utc("2026-07-01T00:00:00Z"))
+                .local_minus_utc(),
+            7200
+        );
+        assert_eq!(berlin.abbreviation_at(utc("2026-07-01T00:00:00Z")), "CEST");
+    }
+}
@@ -0,0 +1,164 @@
+//! Hardware accelerator detection for local inference.
+//!
+//! Reports which GPUs the host offers for local inference: Apple Silicon
+//! (Metal) or NVIDIA (CUDA). Embeddings and transcription currently go
+//! through remote APIs, so nothing selects a device yet; `ironclaw doctor
+//! --ml` prints the report.
+
+use std::process::Command;
+
+/// Compute device a local inference backend can run on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Accelerator {
+    Cpu,
+    Metal,
+    Cuda,
+}
+
+impl Accelerator {
+    pub const ALL: [Accelerator; 3] = [Accelerator::Cpu, Accelerator::Metal, Accelerator::Cuda];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Accelerator::Cpu => "cpu",
+            Accelerator::Metal => "metal",
+            Accelerator::Cuda => "cuda",
+        }
+    }
+}
+
+impl std::fmt::Display for Accelerator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Accelerator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "cpu" => Ok(Accelerator::Cpu),
+            "metal" | "mps" => Ok(Accelerator::Metal),
+            "cuda" | "gpu" => Ok(Accelerator::Cuda),
+            _ => Err(format!(
+                "unknown accelerator '{}', expected cpu, metal or cuda",
+                s
+            )),
+        }
+    }
+}
+
+/// What the host offers for one accelerator.
+#[derive(Debug, Clone)]
+pub struct AcceleratorStatus {
+    pub accelerator: Accelerator,
+    /// Hardware and driver are present.
+    pub present: bool,
+    /// Device name or reason it is missing.
+    pub detail: String,
+}
+
+/// Probe the host for every accelerator.
+pub fn detect() -> Vec<AcceleratorStatus> {
+    Accelerator::ALL
+        .iter()
+        .map(|&accelerator| {
+            let (present, detail) = match accelerator {
+                Accelerator::Cpu => (true, cpu_detail()),
+                Accelerator::Metal => detect_metal(),
+                Accelerator::Cuda => detect_cuda(),
+            };
+            AcceleratorStatus {
+                accelerator,
+                present,
+                detail,
+            }
+        })
+        .collect()
+}
+
+fn cpu_detail() -> String {
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    format!("{} ({} threads)", std::env::consts::ARCH, threads)
+}
+
+fn detect_metal() -> (bool, String) {
+    if !cfg!(target_os = "macos") {
+        return (false, "requires macOS".to_string());
+    }
+    let chip = command_stdout("sysctl", &["-n", "machdep.cpu.brand_string"])
+        .unwrap_or_else(|| "unknown chip".to_string());
+    if cfg!(target_arch = "aarch64") {
+        (true, chip)
+    } else {
+        (
+            false,
+            format!("{} (Intel Mac; Apple Silicon required)", chip),
+        )
+    }
+}
+
+/// Driver libraries that indicate a usable NVIDIA driver when `nvidia-smi`
+/// is not on PATH (containers often ship the library without the tool).
+const LIBCUDA_PATHS: &[&str] = &[
+    "/usr/lib/x86_64-linux-gnu/libcuda.so.1",
+    "/usr/lib/aarch64-linux-gnu/libcuda.so.1",
+    "/usr/lib64/libcuda.so.1",
+    "/usr/lib/wsl/lib/libcuda.so.1",
+    "C:\\Windows\\System32\\nvcuda.dll",
+];
+
+fn detect_cuda() -> (bool, String) {
+    if let Some(gpus) = command_stdout(
+        "nvidia-smi",
+        &["--query-gpu=name,memory.total", "--format=csv,noheader"],
+    ) {
+        let gpus: Vec<&str> = gpus
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect();
+        if !gpus.is_empty() {
+            return (true, gpus.join("; "));
+        }
+    }
+    match LIBCUDA_PATHS
+        .iter()
+        .find(|p| std::path::Path::new(p).exists())
+    {
+        Some(path) => (true, format!("driver found at {}", path)),
+        None => (false, "no NVIDIA driver found".to_string()),
+    }
+}
+
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!stdout.is_empty()).then_some(stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_always_reports_cpu() {
+        let statuses = detect();
+        assert_eq!(statuses.len(), Accelerator::ALL.len());
+        assert!(statuses[0].present);
+        assert_eq!(statuses[0].accelerator, Accelerator::Cpu);
+    }
+
+    #[test]
+    fn test_parse_accelerator() {
+        assert_eq!("Metal".parse::<Accelerator>().unwrap(), Accelerator::Metal);
+        assert_eq!("gpu".parse::<Accelerator>().unwrap(), Accelerator::Cuda);
+        assert!("tpu".parse::<Accelerator>().is_err());
+    }
+}
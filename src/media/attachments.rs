@@ -0,0 +1,760 @@
+//! Attachment understanding pipeline.
+//!
+//! Channels pass the files sent with a message in its metadata:
+//!
+//! ```json
+//! {"attachments": [
+//!   {"url": "https://files.example.com/report.pdf", "name": "report.pdf"},
+//!   {"data": "<base64>", "mime_type": "audio/ogg", "name": "voice.ogg"}
+//! ]}
+//! ```
+//!
+//! [`AttachmentPipeline::process`] handles all attachments of a message
+//! concurrently, each in three stages:
+//!
+//! 1. **Fetch**: download (public http(s) URLs only) or decode, capped at
+//!    the size limit.
+//! 2. **Detect**: MIME type from magic bytes, then the file name, then the
+//!    type the channel declared.
+//! 3. **Extract**: PDF text, OCR through the vision model, transcription,
+//!    text files as they are, container metadata for video.
+//!
+//! The result is one [`AttachmentSummary`] listing every attachment with
+//! its provenance (origin, size, hash, extractor and provider) and the
+//! extracted text. Text longer than the inline limit does not go into the
+//! prompt: it is parked in the [`ToolOutputStore`] and the model gets a
+//! preview plus an id to inspect the rest RLM-style with `tool_output`.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use base64::Engine;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::config::AttachmentConfig;
+use crate::error::MediaError;
+use crate::media::detection::{MediaInfo, MediaType, detect_mime_type, validate_media_url};
+use crate::media::pdf::PdfExtractor;
+use crate::media::transcription::{TranscriptionProvider, WhisperProvider};
+use crate::media::video::VideoProcessor;
+use crate::media::vision::{ImageSource, OpenAiVisionProvider, VisionProvider, VisionRequest};
+use crate::tools::builtin::ToolOutputStore;
+use crate::util::floor_char_boundary;
+
+/// Metadata key channels put attachments under.
+pub const METADATA_KEY: &str = "attachments";
+
+/// Name deferred attachment text is stored under in the output store.
+const STORE_NAME: &str = "attachment";
+
+/// Characters of deferred text shown as a preview.
+const PREVIEW_CHARS: usize = 600;
+
+const OCR_PROMPT: &str = "Transcribe all text in this image exactly as written. \
+Then describe the image in two or three sentences. If there is no text, only describe it.";
+
+/// One file sent with a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    /// File name, when the channel knows it.
+    pub name: Option<String>,
+    /// MIME type the channel declared.
+    pub mime_type: Option<String>,
+    pub source: AttachmentSource,
+}
+
+/// Where the attachment's bytes come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttachmentSource {
+    /// Downloaded from a public http(s) URL.
+    Url(String),
+    /// Base64 data embedded in the metadata.
+    Inline(String),
+}
+
+impl Attachment {
+    /// Attachments listed in message metadata. Entries with neither `url`
+    /// nor `data` are ignored.
+    pub fn from_metadata(metadata: &serde_json::Value) -> Vec<Self> {
+        let Some(items) = metadata.get(METADATA_KEY).and_then(|v| v.as_array()) else {
+            return Vec::new();
+        };
+        items
+            .iter()
+            .filter_map(|item| {
+                let text = |key: &str| {
+                    item.get(key)
+                        .and_then(|v| v.as_str())
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .map(String::from)
+                };
+                let source = match (text("url"), text("data")) {
+                    (Some(url), _) => AttachmentSource::Url(url),
+                    (None, Some(data)) => AttachmentSource::Inline(data),
+                    (None, None) => return None,
+                };
+                Some(Self {
+                    name: text("name"),
+                    mime_type: text("mime_type"),
+                    source,
+                })
+            })
+            .collect()
+    }
+
+    fn label(&self, index: usize) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("attachment {}", index + 1))
+    }
+
+    fn origin(&self) -> String {
+        match &self.source {
+            AttachmentSource::Url(url) => url::Url::parse(url)
+                .ok()
+                .and_then(|u| u.host_str().map(String::from))
+                .unwrap_or_else(|| "url".to_string()),
+            AttachmentSource::Inline(_) => "inline".to_string(),
+        }
+    }
+}
+
+/// Which extractor produced an attachment's text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Extractor {
+    Text,
+    Pdf,
+    Ocr,
+    Transcription,
+    VideoMetadata,
+}
+
+impl Extractor {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Pdf => "PDF text",
+            Self::Ocr => "OCR",
+            Self::Transcription => "transcription",
+            Self::VideoMetadata => "video metadata",
+        }
+    }
+}
+
+/// What became of an attachment's content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Extraction {
+    /// Short enough to go into the prompt.
+    Inline { text: String },
+    /// Parked in the output store; only a preview goes into the prompt.
+    Deferred {
+        output_id: String,
+        chars: usize,
+        preview: String,
+    },
+    /// Not processed (unsupported type, extractor not configured).
+    Skipped { reason: String },
+    /// Fetching or extraction failed.
+    Failed { error: String },
+}
+
+/// Outcome and provenance of one attachment.
+#[derive(Debug, Clone, Serialize)]
+pub struct AttachmentReport {
+    /// Position in the message, from 1.
+    pub index: usize,
+    pub name: String,
+    /// Host it was downloaded from, or "inline".
+    pub origin: String,
+    pub mime_type: Option<String>,
+    pub bytes: usize,
+    /// First 12 hex digits of the content's SHA-256.
+    pub sha256: Option<String>,
+    pub extractor: Option<Extractor>,
+    /// Service that ran the extractor (vision or transcription model).
+    pub provider: Option<String>,
+    pub extraction: Extraction,
+    pub elapsed_ms: u64,
+}
+
+impl AttachmentReport {
+    fn new(index: usize, attachment: &Attachment) -> Self {
+        Self {
+            index: index + 1,
+            name: attachment.label(index),
+            origin: attachment.origin(),
+            mime_type: attachment.mime_type.clone(),
+            bytes: 0,
+            sha256: None,
+            extractor: None,
+            provider: None,
+            extraction: Extraction::Skipped {
+                reason: "not processed".to_string(),
+            },
+            elapsed_ms: 0,
+        }
+    }
+}
+
+/// All attachments of one message, in order.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AttachmentSummary {
+    pub reports: Vec<AttachmentReport>,
+}
+
+impl AttachmentSummary {
+    pub fn is_empty(&self) -> bool {
+        self.reports.is_empty()
+    }
+
+    /// Text block handed to the model with the user's message.
+    pub fn render(&self) -> String {
+        let mut out = format!("Attachments ({}):", self.reports.len());
+        for report in &self.reports {
+            let mut provenance = vec![format!("from {}", report.origin)];
+            if let Some(mime) = &report.mime_type {
+                provenance.insert(0, mime.clone());
+            }
+            if report.bytes > 0 {
+                provenance.push(format_bytes(report.bytes));
+            }
+            if let Some(hash) = &report.sha256 {
+                provenance.push(format!("sha256 {}", hash));
+            }
+            if let Some(extractor) = report.extractor {
+                provenance.push(match &report.provider {
+                    Some(provider) => format!("{} via {}", extractor.label(), provider),
+                    None => extractor.label().to_string(),
+                });
+            }
+            out.push_str(&format!(
+                "\n\n[{}] {} ({})\n",
+                report.index,
+                report.name,
+                provenance.join(", ")
+            ));
+            match &report.extraction {
+                Extraction::Inline { text } if text.trim().is_empty() => {
+                    out.push_str("(no text found)")
+                }
+                Extraction::Inline { text } => out.push_str(text.trim()),
+                Extraction::Deferred {
+                    output_id,
+                    chars,
+                    preview,
+                } => out.push_str(&format!(
+                    "{} characters, too long to include. Preview:\n{}\n\
+                     Read the rest with tool_output (id {}).",
+                    chars,
+                    preview.trim(),
+                    output_id
+                )),
+                Extraction::Skipped { reason } => out.push_str(&format!("Skipped: {}", reason)),
+                Extraction::Failed { error } => out.push_str(&format!("Failed: {}", error)),
+            }
+        }
+        out
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{} KB", bytes / 1024)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+/// Detects, extracts and summarizes the attachments of a message.
+pub struct AttachmentPipeline {
+    max_bytes: usize,
+    inline_chars: usize,
+    vision: Option<Arc<dyn VisionProvider>>,
+    transcription: Option<Arc<dyn TranscriptionProvider>>,
+    outputs: Option<Arc<ToolOutputStore>>,
+    pdf: PdfExtractor,
+    video: VideoProcessor,
+}
+
+impl AttachmentPipeline {
+    /// A pipeline with text, PDF and video extraction. OCR and
+    /// transcription need [`Self::with_vision`] and
+    /// [`Self::with_transcription`].
+    pub fn new(max_bytes: usize, inline_chars: usize) -> Self {
+        Self {
+            max_bytes,
+            inline_chars: inline_chars.max(PREVIEW_CHARS),
+            vision: None,
+            transcription: None,
+            outputs: None,
+            pdf: PdfExtractor::default(),
+            video: VideoProcessor::new().with_max_file_size(max_bytes),
+        }
+    }
+
+    /// Build from config, with the OpenAI-compatible vision and Whisper
+    /// providers when an API key is set.
+    pub fn from_config(config: &AttachmentConfig) -> Self {
+        use secrecy::ExposeSecret;
+
+        let mut pipeline = Self::new(config.max_bytes, config.inline_chars);
+        let Some(key) = &config.api_key else {
+            return pipeline;
+        };
+        let key = key.expose_secret().to_string();
+        if let Some(model) = &config.vision_model {
+            let mut vision = OpenAiVisionProvider::new(key.clone(), model.clone());
+            if let Some(url) = &config.base_url {
+                vision = vision.with_base_url(url.clone());
+            }
+            pipeline = pipeline.with_vision(Arc::new(vision));
+        }
+        if let Some(model) = &config.transcription_model {
+            let mut whisper = WhisperProvider::new(key).with_model(model.clone());
+            if let Some(url) = &config.base_url {
+                whisper = whisper.with_base_url(url.clone());
+            }
+            pipeline = pipeline.with_transcription(Arc::new(whisper));
+        }
+        pipeline
+    }
+
+    /// Run OCR on images through this vision provider.
+    pub fn with_vision(mut self, provider: Arc<dyn VisionProvider>) -> Self {
+        self.vision = Some(provider);
+        self
+    }
+
+    /// Transcribe audio through this provider.
+    pub fn with_transcription(mut self, provider: Arc<dyn TranscriptionProvider>) -> Self {
+        self.transcription = Some(provider);
+        self
+    }
+
+    /// Park long extracted text here instead of truncating it.
+    pub fn with_output_store(mut self, store: Arc<ToolOutputStore>) -> Self {
+        self.outputs = Some(store);
+        self
+    }
+
+    /// Process all attachments concurrently; reports keep their order.
+    pub async fn process(&self, attachments: &[Attachment]) -> AttachmentSummary {
+        let reports = futures::future::join_all(
+            attachments
+                .iter()
+                .enumerate()
+                .map(|(index, attachment)| self.process_one(index, attachment)),
+        )
+        .await;
+        AttachmentSummary { reports }
+    }
+
+    async fn process_one(&self, index: usize, attachment: &Attachment) -> AttachmentReport {
+        let started = Instant::now();
+        let mut report = AttachmentReport::new(index, attachment);
+
+        let data = match self.fetch(&attachment.source).await {
+            Ok(data) => data,
+            Err(e) => {
+                report.extraction = Extraction::Failed {
+                    error: e.to_string(),
+                };
+                report.elapsed_ms = started.elapsed().as_millis() as u64;
+                return report;
+            }
+        };
+        report.bytes = data.len();
+        report.sha256 = Some(hex::encode(Sha256::digest(&data))[..12].to_string());
+
+        let info = detect(&data, attachment);
+        report.mime_type = Some(info.mime_type.clone());
+
+        report.extraction = match self.extract(&data, &info).await {
+            Ok(Some((extractor, provider, text))) => {
+                report.extractor = Some(extractor);
+                report.provider = provider;
+                self.place(&report.name, text)
+            }
+            Ok(None) => Extraction::Skipped {
+                reason: skip_reason(&info, self),
+            },
+            Err(e) => Extraction::Failed {
+                error: e.to_string(),
+            },
+        };
+        report.elapsed_ms = started.elapsed().as_millis() as u64;
+        tracing::debug!(
+            attachment = report.name,
+            mime = info.mime_type,
+            elapsed_ms = report.elapsed_ms,
+            "Processed attachment"
+        );
+        report
+    }
+
+    async fn fetch(&self, source: &AttachmentSource) -> Result<Vec<u8>, MediaError> {
+        let data = match source {
+            AttachmentSource::Inline(data) => base64::engine::general_purpose::STANDARD
+                .decode(data.trim())
+                .map_err(|e| MediaError::ProcessingFailed {
+                    reason: format!("invalid base64 data: {}", e),
+                })?,
+            AttachmentSource::Url(url) => {
+                validate_media_url(url)?;
+                let response = crate::outbound::client()
+                    .get(url)
+                    .send()
+                    .await
+                    .map_err(|e| MediaError::DownloadFailed {
+                        reason: e.to_string(),
+                    })?;
+                if !response.status().is_success() {
+                    return Err(MediaError::DownloadFailed {
+                        reason: format!("HTTP {}", response.status()),
+                    });
+                }
+                if let Some(len) = response.content_length()
+                    && len as usize > self.max_bytes
+                {
+                    return Err(MediaError::TooLarge {
+                        size: len as usize,
+                        max: self.max_bytes,
+                    });
+                }
+                response
+                    .bytes()
+                    .await
+                    .map_err(|e| MediaError::DownloadFailed {
+                        reason: e.to_string(),
+                    })?
+                    .to_vec()
+            }
+        };
+        if data.len() > self.max_bytes {
+            return Err(MediaError::TooLarge {
+                size: data.len(),
+                max: self.max_bytes,
+            });
+        }
+        Ok(data)
+    }
+
+    /// Run the extractor for the detected type. `None` when there is no
+    /// extractor for it.
+    async fn extract(
+        &self,
+        data: &[u8],
+        info: &MediaInfo,
+    ) -> Result<Option<(Extractor, Option<String>, String)>, MediaError> {
+        match info.media_type {
+            MediaType::Text => Ok(Some((
+                Extractor::Text,
+                None,
+                String::from_utf8_lossy(data).into_owned(),
+            ))),
+            MediaType::Pdf => Ok(Some((
+                Extractor::Pdf,
+                None,
+                self.pdf.extract_all_text(data)?,
+            ))),
+            MediaType::Image => {
+                let Some(vision) = &self.vision else {
+                    return Ok(None);
+                };
+                let response = vision
+                    .analyze(VisionRequest {
+                        image: ImageSource::Base64 {
+                            data: base64::engine::general_purpose::STANDARD.encode(data),
+                            media_type: info.mime_type.clone(),
+                        },
+                        prompt: OCR_PROMPT.to_string(),
+                        detail: Some("high".to_string()),
+                        max_tokens: Some(2048),
+                    })
+                    .await?;
+                Ok(Some((
+                    Extractor::Ocr,
+                    Some(vision.name().to_string()),
+                    response.content,
+                )))
+            }
+            MediaType::Audio => {
+                let Some(transcription) = &self.transcription else {
+                    return Ok(None);
+                };
+                let result = transcription
+                    .transcribe(data, &info.mime_type, None)
+                    .await?;
+                Ok(Some((
+                    Extractor::Transcription,
+                    Some(transcription.name().to_string()),
+                    result.text,
+                )))
+            }
+            MediaType::Video => {
+                let meta = self.video.extract_metadata(data)?;
+                let mut parts = vec![format!("{:?} video", meta.format)];
+                if let Some(secs) = meta.duration_seconds {
+                    parts.push(format!("{:.1}s", secs));
+                }
+                if let (Some(w), Some(h)) = (meta.width, meta.height) {
+                    parts.push(format!("{}x{}", w, h));
+                }
+                if let Some(codec) = meta.codec {
+                    parts.push(codec);
+                }
+                Ok(Some((Extractor::VideoMetadata, None, parts.join(", "))))
+            }
+            MediaType::Sticker | MediaType::Unknown => Ok(None),
+        }
+    }
+
+    /// Inline short text; park long text in the output store.
+    fn place(&self, name: &str, text: String) -> Extraction {
+        if text.len() <= self.inline_chars {
+            return Extraction::Inline { text };
+        }
+        let chars = text.chars().count();
+        let preview = text[..floor_char_boundary(&text, PREVIEW_CHARS)].to_string();
+        match &self.outputs {
+            Some(store) => Extraction::Deferred {
+                output_id: store.insert(&format!("{}:{}", STORE_NAME, name), text),
+                chars,
+                preview,
+            },
+            None => {
+                let cut = floor_char_boundary(&text, self.inline_chars);
+                Extraction::Inline {
+                    text: format!(
+                        "{}\n[truncated: {} of {} characters shown]",
+                        &text[..cut],
+                        text[..cut].chars().count(),
+                        chars
+                    ),
+                }
+            }
+        }
+    }
+}
+
+/// Detect the type from content and file name, falling back to the type
+/// the channel declared, then to plain text for NUL-free UTF-8.
+fn detect(data: &[u8], attachment: &Attachment) -> MediaInfo {
+    let mut info = detect_mime_type(data, attachment.name.as_deref());
+    if info.media_type == MediaType::Unknown
+        && let Some(declared) = &attachment.mime_type
+    {
+        let declared = declared.to_ascii_lowercase();
+        let media_type = match declared.split('/').next() {
+            Some("image") => MediaType::Image,
+            Some("audio") => MediaType::Audio,
+            Some("video") => MediaType::Video,
+            Some("text") => MediaType::Text,
+            _ if declared == "application/pdf" => MediaType::Pdf,
+            _ if declared == "application/json" => MediaType::Text,
+            _ => MediaType::Unknown,
+        };
+        info.media_type = media_type;
+        info.supported = media_type != MediaType::Unknown;
+        info.mime_type = declared;
+    }
+    if info.media_type == MediaType::Unknown
+        && !data.contains(&0)
+        && std::str::from_utf8(data).is_ok()
+    {
+        info.media_type = MediaType::Text;
+        info.supported = true;
+        info.mime_type = "text/plain".to_string();
+    }
+    info
+}
+
+fn skip_reason(info: &MediaInfo, pipeline: &AttachmentPipeline) -> String {
+    match info.media_type {
+        MediaType::Image if pipeline.vision.is_none() => {
+            "no vision model configured for OCR".to_string()
+        }
+        MediaType::Audio if pipeline.transcription.is_none() => {
+            "no transcription provider configured".to_string()
+        }
+        _ => format!("unsupported type {}", info.mime_type),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::media::vision::VisionResponse;
+
+    struct FakeVision;
+
+    #[async_trait]
+    impl VisionProvider for FakeVision {
+        async fn analyze(&self, request: VisionRequest) -> Result<VisionResponse, MediaError> {
+            assert!(
+                matches!(request.image, ImageSource::Base64 { ref media_type, .. } if media_type == "image/png")
+            );
+            Ok(VisionResponse {
+                content: "INVOICE #42".to_string(),
+                input_tokens: None,
+                output_tokens: None,
+                provider: "fake".to_string(),
+            })
+        }
+
+        fn name(&self) -> &str {
+            "fake-vision"
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+    }
+
+    fn inline(name: &str, data: &[u8]) -> Attachment {
+        Attachment {
+            name: Some(name.to_string()),
+            mime_type: None,
+            source: AttachmentSource::Inline(
+                base64::engine::general_purpose::STANDARD.encode(data),
+            ),
+        }
+    }
+
+    const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0];
+
+    #[test]
+    fn test_from_metadata() {
+        let metadata = serde_json::json!({
+            "attachments": [
+                {"url": "https://files.example.com/a.pdf", "name": "a.pdf"},
+                {"data": "aGk=", "mime_type": "text/plain"},
+                {"name": "nothing to fetch"}
+            ]
+        });
+        let attachments = Attachment::from_metadata(&metadata);
+        assert_eq!(attachments.len(), 2);
+        assert_eq!(
+            attachments[0].source,
+            AttachmentSource::Url("https://files.example.com/a.pdf".to_string())
+        );
+        assert_eq!(attachments[0].origin(), "files.example.com");
+        assert_eq!(attachments[1].mime_type.as_deref(), Some("text/plain"));
+        assert_eq!(attachments[1].label(1), "attachment 2");
+        assert!(Attachment::from_metadata(&serde_json::Value::Null).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mixed_attachments_keep_order_and_provenance() {
+        let pipeline = AttachmentPipeline::new(1024 * 1024, 1000).with_vision(Arc::new(FakeVision));
+        let summary = pipeline
+            .process(&[
+                inline("notes.txt", b"ship it on friday"),
+                inline("scan.png", PNG),
+                inline("blob.bin", &[0u8, 1, 2, 3]),
+                Attachment {
+                    name: None,
+                    mime_type: None,
+                    source: AttachmentSource::Inline("not base64!".to_string()),
+                },
+            ])
+            .await;
+
+        let reports = &summary.reports;
+        assert_eq!(reports.len(), 4);
+        assert_eq!(reports[0].extractor, Some(Extractor::Text));
+        assert_eq!(
+            reports[0].extraction,
+            Extraction::Inline {
+                text: "ship it on friday".to_string()
+            }
+        );
+        assert_eq!(reports[0].sha256.as_ref().map(String::len), Some(12));
+        assert_eq!(reports[1].extractor, Some(Extractor::Ocr));
+        assert_eq!(reports[1].provider.as_deref(), Some("fake-vision"));
+        assert!(matches!(reports[2].extraction, Extraction::Skipped { .. }));
+        assert!(matches!(reports[3].extraction, Extraction::Failed { .. }));
+
+        let rendered = summary.render();
+        assert!(rendered.starts_with("Attachments (4):"));
+        assert!(rendered.contains("[2] scan.png (image/png, from inline, 12 B, sha256 "));
+        assert!(rendered.contains("OCR via fake-vision)\nINVOICE #42"));
+    }
+
+    #[tokio::test]
+    async fn test_images_are_skipped_without_vision() {
+        let pipeline = AttachmentPipeline::new(1024, 1000);
+        let summary = pipeline.process(&[inline("scan.png", PNG)]).await;
+        assert_eq!(
+            summary.reports[0].extraction,
+            Extraction::Skipped {
+                reason: "no vision model configured for OCR".to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_long_text_is_deferred_to_the_output_store() {
+        let store = Arc::new(ToolOutputStore::default());
+        let pipeline =
+            AttachmentPipeline::new(1024 * 1024, 1000).with_output_store(Arc::clone(&store));
+        let text = "line of a long log\n".repeat(200);
+        let summary = pipeline
+            .process(&[inline("build.log", text.as_bytes())])
+            .await;
+
+        let Extraction::Deferred {
+            output_id,
+            chars,
+            preview,
+        } = &summary.reports[0].extraction
+        else {
+            panic!("expected deferred, got {:?}", summary.reports[0].extraction);
+        };
+        assert_eq!(*chars, text.len());
+        assert!(preview.len() <= PREVIEW_CHARS);
+        let (name, context) = store.get(output_id).unwrap();
+        assert_eq!(name, "attachment:build.log");
+        assert_eq!(context.metadata.total_chars, text.len());
+        assert!(
+            summary
+                .render()
+                .contains(&format!("tool_output (id {})", output_id))
+        );
+
+        // Without a store the text is cut at the inline limit instead.
+        let summary = AttachmentPipeline::new(1024 * 1024, 1000)
+            .process(&[inline("build.log", text.as_bytes())])
+            .await;
+        let Extraction::Inline { text: cut } = &summary.reports[0].extraction else {
+            panic!("expected inline");
+        };
+        assert!(cut.ends_with("[truncated: 1000 of 3800 characters shown]"));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_and_private_sources_fail() {
+        let pipeline = AttachmentPipeline::new(4, 1000);
+        let summary = pipeline
+            .process(&[
+                inline("big.txt", b"too many bytes"),
+                Attachment {
+                    name: None,
+                    mime_type: None,
+                    source: AttachmentSource::Url("http://169.254.169.254/latest".to_string()),
+                },
+            ])
+            .await;
+        for report in &summary.reports {
+            assert!(matches!(report.extraction, Extraction::Failed { .. }));
+        }
+    }
+}
@@ -0,0 +1,441 @@
+//! Chart rendering (bar, line and scatter) to PNG or SVG.
+//!
+//! Charts are drawn with plotters. Text needs a TrueType font, which is
+//! loaded once from the system (DejaVu, Liberation, Noto, Arial, ...) or
+//! from `~/.ironclaw/fonts/chart.ttf` when present.
+
+use std::sync::OnceLock;
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+use crate::error::MediaError;
+
+/// Most series one chart can hold.
+pub const MAX_SERIES: usize = 12;
+
+/// Most points (table rows) per series.
+pub const MAX_POINTS: usize = 1000;
+
+/// Most x-axis labels drawn; with more categories some are skipped.
+const MAX_X_LABELS: usize = 24;
+
+/// Fonts tried in order when the user hasn't provided one.
+const SYSTEM_FONTS: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf",
+    "/usr/share/fonts/liberation/LiberationSans-Regular.ttf",
+    "/usr/share/fonts/truetype/noto/NotoSans-Regular.ttf",
+    "/usr/share/fonts/noto/NotoSans-Regular.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+/// Kind of chart to draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartKind {
+    Bar,
+    Line,
+    Scatter,
+}
+
+impl ChartKind {
+    pub fn from_str_loose(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "bar" | "column" => Some(Self::Bar),
+            "line" => Some(Self::Line),
+            "scatter" | "point" => Some(Self::Scatter),
+            _ => None,
+        }
+    }
+}
+
+/// Output image format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartFormat {
+    Png,
+    Svg,
+}
+
+impl ChartFormat {
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Svg => "image/svg+xml",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Svg => "svg",
+        }
+    }
+}
+
+/// One named series of values, aligned with the chart's categories.
+/// `None` marks a missing value, which is left out of the plot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartSeries {
+    pub name: String,
+    pub values: Vec<Option<f64>>,
+}
+
+/// Everything needed to draw a chart.
+#[derive(Debug, Clone)]
+pub struct ChartSpec {
+    pub kind: ChartKind,
+    pub title: Option<String>,
+    pub x_label: Option<String>,
+    pub y_label: Option<String>,
+    /// X-axis categories, one per point.
+    pub categories: Vec<String>,
+    pub series: Vec<ChartSeries>,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ChartSpec {
+    /// Split a table into categories and series: the first column labels
+    /// the x axis and every other column with at least one number becomes
+    /// a series. Numbers may be JSON numbers or numeric strings (as SQL
+    /// decimals come back).
+    pub fn table_series(
+        columns: &[String],
+        rows: &[Vec<serde_json::Value>],
+    ) -> Result<(Vec<String>, Vec<ChartSeries>), MediaError> {
+        if columns.len() < 2 {
+            return Err(processing(
+                "need a label column and at least one value column",
+            ));
+        }
+        if rows.is_empty() {
+            return Err(processing("the table has no rows"));
+        }
+        if rows.len() > MAX_POINTS {
+            return Err(processing(&format!(
+                "{} rows is more than the {} a chart can show",
+                rows.len(),
+                MAX_POINTS
+            )));
+        }
+
+        let categories = rows
+            .iter()
+            .map(|row| match row.first() {
+                Some(serde_json::Value::String(s)) => s.clone(),
+                Some(serde_json::Value::Null) | None => String::new(),
+                Some(other) => other.to_string(),
+            })
+            .collect();
+
+        let series: Vec<ChartSeries> = columns
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, name)| ChartSeries {
+                name: name.clone(),
+                values: rows.iter().map(|row| row.get(i).and_then(number)).collect(),
+            })
+            .filter(|s| s.values.iter().any(Option::is_some))
+            .collect();
+
+        if series.is_empty() {
+            return Err(processing("no column after the first holds numbers"));
+        }
+        if series.len() > MAX_SERIES {
+            return Err(processing(&format!(
+                "{} value columns is more than the {} a chart can show",
+                series.len(),
+                MAX_SERIES
+            )));
+        }
+        Ok((categories, series))
+    }
+
+    /// Render the chart.
+    pub fn render(&self, format: ChartFormat) -> Result<Vec<u8>, MediaError> {
+        load_font()?;
+        let size = (self.width, self.height);
+        match format {
+            ChartFormat::Png => {
+                let mut rgb = vec![0u8; self.width as usize * self.height as usize * 3];
+                {
+                    let root = BitMapBackend::with_buffer(&mut rgb, size).into_drawing_area();
+                    self.draw(root)?;
+                }
+                encode_png(&rgb, self.width, self.height)
+            }
+            ChartFormat::Svg => {
+                let mut svg = String::new();
+                {
+                    let root = SVGBackend::with_string(&mut svg, size).into_drawing_area();
+                    self.draw(root)?;
+                }
+                Ok(svg.into_bytes())
+            }
+        }
+    }
+
+    fn draw<DB: DrawingBackend>(&self, root: DrawingArea<DB, Shift>) -> Result<(), MediaError> {
+        root.fill(&WHITE).map_err(draw_error)?;
+
+        let n = self.categories.len();
+        let (y_min, y_max) = self.y_range();
+
+        let mut builder = ChartBuilder::on(&root);
+        builder
+            .margin(16)
+            .x_label_area_size(if self.x_label.is_some() { 56 } else { 36 })
+            .y_label_area_size(if self.y_label.is_some() { 72 } else { 56 });
+        if let Some(ref title) = self.title {
+            builder.caption(title, ("sans-serif", 24));
+        }
+        let mut chart = builder
+            .build_cartesian_2d(-0.5f64..(n as f64 - 0.5), y_min..y_max)
+            .map_err(draw_error)?;
+
+        let label_of = |x: &f64| category_label(&self.categories, *x);
+        let mut mesh = chart.configure_mesh();
+        mesh.disable_x_mesh()
+            .x_labels(n.min(MAX_X_LABELS))
+            .x_label_formatter(&label_of)
+            .label_style(("sans-serif", 14));
+        if let Some(ref label) = self.x_label {
+            mesh.x_desc(label.as_str());
+        }
+        if let Some(ref label) = self.y_label {
+            mesh.y_desc(label.as_str());
+        }
+        mesh.draw().map_err(draw_error)?;
+
+        let count = self.series.len();
+        for (idx, series) in self.series.iter().enumerate() {
+            let color = Palette99::pick(idx).to_rgba();
+            let points = series
+                .values
+                .iter()
+                .enumerate()
+                .filter_map(|(i, v)| v.map(|v| (i as f64, v)));
+
+            let drawn = match self.kind {
+                ChartKind::Bar => {
+                    // Bars of one category sit side by side in 80% of its slot.
+                    let width = 0.8 / count as f64;
+                    let base = 0f64.clamp(y_min, y_max);
+                    chart.draw_series(points.map(|(x, v)| {
+                        let left = x - 0.4 + idx as f64 * width;
+                        Rectangle::new([(left, base), (left + width, v)], color.filled())
+                    }))
+                }
+                ChartKind::Line => {
+                    chart.draw_series(LineSeries::new(points, color.stroke_width(2)).point_size(3))
+                }
+                ChartKind::Scatter => {
+                    chart.draw_series(points.map(|p| Circle::new(p, 4, color.filled())))
+                }
+            }
+            .map_err(draw_error)?;
+            drawn.label(series.name.as_str()).legend(move |(x, y)| {
+                Rectangle::new([(x, y - 5), (x + 12, y + 5)], color.filled())
+            });
+        }
+
+        if count > 1 {
+            chart
+                .configure_series_labels()
+                .position(SeriesLabelPosition::UpperRight)
+                .background_style(WHITE.mix(0.85))
+                .border_style(BLACK.mix(0.3))
+                .label_font(("sans-serif", 14))
+                .draw()
+                .map_err(draw_error)?;
+        }
+
+        root.present().map_err(draw_error)
+    }
+
+    /// The value axis range with some headroom. Bar charts always include
+    /// zero so bar lengths stay comparable.
+    fn y_range(&self) -> (f64, f64) {
+        let values = self.series.iter().flat_map(|s| s.values.iter().flatten());
+        let (mut lo, mut hi) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(*v), hi.max(*v))
+        });
+        if !lo.is_finite() || !hi.is_finite() {
+            return (0.0, 1.0);
+        }
+        if self.kind == ChartKind::Bar {
+            lo = lo.min(0.0);
+            hi = hi.max(0.0);
+        }
+        let pad = if hi > lo {
+            (hi - lo) * 0.08
+        } else {
+            hi.abs().max(1.0) * 0.5
+        };
+        if self.kind != ChartKind::Bar || lo < 0.0 {
+            lo -= pad;
+        }
+        if self.kind != ChartKind::Bar || hi > 0.0 {
+            hi += pad;
+        }
+        (lo, hi)
+    }
+}
+
+/// Read a table cell as a number.
+fn number(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+    .filter(|v: &f64| v.is_finite())
+}
+
+/// The category under an integer x position; other positions get no label.
+fn category_label(categories: &[String], x: f64) -> String {
+    let i = x.round();
+    if (x - i).abs() > 1e-6 || i < 0.0 {
+        return String::new();
+    }
+    categories.get(i as usize).cloned().unwrap_or_default()
+}
+
+/// Register a sans-serif font with plotters, once per process.
+fn load_font() -> Result<(), MediaError> {
+    static FONT: OnceLock<Result<(), String>> = OnceLock::new();
+    FONT.get_or_init(|| {
+        let user_font = dirs::home_dir().map(|h| h.join(".ironclaw/fonts/chart.ttf"));
+        let candidates = user_font
+            .into_iter()
+            .chain(SYSTEM_FONTS.iter().map(std::path::PathBuf::from));
+        for path in candidates {
+            let Ok(bytes) = std::fs::read(&path) else {
+                continue;
+            };
+            // plotters keeps a reference for the life of the process.
+            let bytes: &'static [u8] = Box::leak(bytes.into_boxed_slice());
+            if plotters::style::register_font("sans-serif", FontStyle::Normal, bytes).is_ok() {
+                tracing::debug!("Chart font loaded from {}", path.display());
+                return Ok(());
+            }
+        }
+        Err(
+            "no usable font found; place a TrueType font at ~/.ironclaw/fonts/chart.ttf"
+                .to_string(),
+        )
+    })
+    .clone()
+    .map_err(|reason| MediaError::ProcessingFailed { reason })
+}
+
+fn encode_png(rgb: &[u8], width: u32, height: u32) -> Result<Vec<u8>, MediaError> {
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(rgb))
+        .map_err(|e| processing(&format!("PNG encoding failed: {}", e)))?;
+    Ok(out)
+}
+
+fn draw_error<E: std::error::Error + Send + Sync>(e: DrawingAreaErrorKind<E>) -> MediaError {
+    processing(&format!("drawing failed: {}", e))
+}
+
+fn processing(reason: &str) -> MediaError {
+    MediaError::ProcessingFailed {
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(kind: ChartKind) -> ChartSpec {
+        let columns = vec!["day".to_string(), "cost".to_string(), "jobs".to_string()];
+        let rows = vec![
+            vec![
+                serde_json::json!("Mon"),
+                serde_json::json!("1.25"),
+                serde_json::json!(3),
+            ],
+            vec![
+                serde_json::json!("Tue"),
+                serde_json::json!(2.5),
+                serde_json::json!(null),
+            ],
+            vec![
+                serde_json::json!("Wed"),
+                serde_json::json!(0.75),
+                serde_json::json!(1),
+            ],
+        ];
+        let (categories, series) = ChartSpec::table_series(&columns, &rows).unwrap();
+        ChartSpec {
+            kind,
+            title: Some("Job costs".to_string()),
+            x_label: Some("Day".to_string()),
+            y_label: None,
+            categories,
+            series,
+            width: 400,
+            height: 300,
+        }
+    }
+
+    #[test]
+    fn test_table_series_reads_numbers_and_strings() {
+        let s = spec(ChartKind::Bar);
+        assert_eq!(s.categories, vec!["Mon", "Tue", "Wed"]);
+        assert_eq!(s.series.len(), 2);
+        assert_eq!(s.series[0].values, vec![Some(1.25), Some(2.5), Some(0.75)]);
+        assert_eq!(s.series[1].values, vec![Some(3.0), None, Some(1.0)]);
+    }
+
+    #[test]
+    fn test_table_series_rejects_tables_without_numbers() {
+        let columns = vec!["name".to_string(), "note".to_string()];
+        let rows = vec![vec![serde_json::json!("a"), serde_json::json!("b")]];
+        assert!(ChartSpec::table_series(&columns, &rows).is_err());
+        assert!(ChartSpec::table_series(&columns[..1], &rows).is_err());
+    }
+
+    #[test]
+    fn test_bar_range_includes_zero() {
+        let (lo, hi) = spec(ChartKind::Bar).y_range();
+        assert_eq!(lo, 0.0);
+        assert!(hi > 3.0);
+        let (lo, _) = spec(ChartKind::Line).y_range();
+        assert!(lo < 0.75 && lo > 0.0);
+    }
+
+    #[test]
+    fn test_category_labels_only_on_whole_positions() {
+        let cats = vec!["a".to_string(), "b".to_string()];
+        assert_eq!(category_label(&cats, 1.0), "b");
+        assert_eq!(category_label(&cats, 0.5), "");
+        assert_eq!(category_label(&cats, 5.0), "");
+    }
+
+    #[test]
+    fn test_render_png_and_svg() {
+        // Needs a system font; nothing to check without one.
+        if load_font().is_err() {
+            return;
+        }
+        let png = spec(ChartKind::Bar).render(ChartFormat::Png).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        let svg = spec(ChartKind::Line).render(ChartFormat::Svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains("<svg") && svg.contains("Job costs"));
+    }
+}
@@ -195,7 +195,7 @@ fn detect_from_extension(filename: &str) -> Option<(&'static str, MediaType)> {
 }
 
 /// Get the default file extension for a MIME type.
-fn mime_to_extension(mime: &str) -> &str {
+pub fn mime_to_extension(mime: &str) -> &'static str {
     match mime {
         "image/jpeg" => "jpg",
         "image/png" => "png",
@@ -4,6 +4,7 @@
 //! - Image processing (resize, format conversion)
 //! - Audio transcription (via external APIs)
 //! - PDF text extraction
+//! - PDF rendering (Markdown/HTML to PDF)
 //! - MIME type detection
 //! - Vision model integration (image understanding)
 //! - Media caching
@@ -11,21 +12,35 @@
 //! - Video metadata extraction (MP4, WebM, AVI, MOV, MKV)
 //! - Text-to-speech synthesis (via OpenAI TTS API)
 //! - Large document processing via Recursive Language Model (RLM) techniques
+//! - Hardware accelerator detection for local inference (Metal, CUDA)
+//! - Attachment pipeline (detect, extract and summarize message attachments)
+//! - Chart rendering (bar, line, scatter) to PNG or SVG
 
+pub mod accel;
+mod attachments;
 mod cache;
+mod chart;
 mod detection;
 mod edge_tts;
 mod image;
 pub mod large_doc;
 mod pdf;
+mod pdf_render;
 mod sticker;
 mod transcription;
 mod tts;
 mod video;
 mod vision;
 
+pub use attachments::{
+    Attachment, AttachmentPipeline, AttachmentReport, AttachmentSource, AttachmentSummary,
+    Extraction, Extractor,
+};
 pub use cache::MediaCache;
-pub use detection::{MediaInfo, MediaType, detect_mime_type, validate_media_url};
+pub use chart::{ChartFormat, ChartKind, ChartSeries, ChartSpec};
+pub use detection::{
+    MediaInfo, MediaType, detect_mime_type, mime_to_extension, validate_media_url,
+};
 pub use edge_tts::{EdgeTtsProvider, EdgeVoice};
 pub use image::{ImageFormat, ImageProcessor, ProcessedImage};
 pub use large_doc::{
@@ -33,8 +48,13 @@ pub use large_doc::{
     ProcessingStats, RlmConfig, RlmOperation, SubQuerySpec, process_large_document,
 };
 pub use pdf::{PdfExtractor, PdfPage};
+pub use pdf_render::{
+    PageSize, PdfOptions, PdfRenderer, default_exports_dir, html_to_markdown, write_export,
+};
 pub use sticker::{ConvertedSticker, StickerConverter, StickerFormat};
-pub use transcription::{TranscriptionProvider, TranscriptionResult};
+pub use transcription::{TranscriptionProvider, TranscriptionResult, WhisperProvider};
 pub use tts::{OpenAiTtsProvider, TtsFormat, TtsProvider, TtsVoice, VoiceGender};
 pub use video::{VideoFormat, VideoInfo, VideoProcessor};
-pub use vision::{VisionProvider, VisionRequest, VisionResponse};
+pub use vision::{
+    ImageSource, OpenAiVisionProvider, VisionProvider, VisionRequest, VisionResponse,
+};
@@ -0,0 +1,1094 @@
+//! PDF rendering for documents the agent produces.
+//!
+//! Lays out Markdown, or simple HTML converted to Markdown first, on
+//! fixed-size pages using the PDF base-14 fonts, so no font files ship with
+//! the binary and every viewer can open the result. Supported blocks:
+//! headings, wrapped paragraphs, bullet and numbered lists, block quotes,
+//! fenced code, horizontal rules, and pipe tables (drawn as aligned
+//! monospace rows). Inline emphasis markers are dropped and links become
+//! `text (url)`.
+//!
+//! Text is encoded as Windows-1252; characters outside it print as `?`.
+
+use std::path::PathBuf;
+
+/// Page dimensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PageSize {
+    #[default]
+    A4,
+    Letter,
+}
+
+impl PageSize {
+    /// Width and height in points.
+    fn dimensions(&self) -> (f32, f32) {
+        match self {
+            PageSize::A4 => (595.28, 841.89),
+            PageSize::Letter => (612.0, 792.0),
+        }
+    }
+}
+
+/// Layout options for [`PdfRenderer`].
+#[derive(Debug, Clone)]
+pub struct PdfOptions {
+    pub page_size: PageSize,
+    /// Margin on every side, in points.
+    pub margin: f32,
+    /// Body text size, in points. Headings and code scale from it.
+    pub font_size: f32,
+    /// Document title for the PDF metadata. Defaults to the first heading.
+    pub title: Option<String>,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        Self {
+            page_size: PageSize::A4,
+            margin: 56.0,
+            font_size: 11.0,
+            title: None,
+        }
+    }
+}
+
+/// The base-14 fonts the renderer uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Font {
+    Regular,
+    Bold,
+    Oblique,
+    Mono,
+}
+
+impl Font {
+    const ALL: [Font; 4] = [Font::Regular, Font::Bold, Font::Oblique, Font::Mono];
+
+    fn resource(&self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+            Font::Oblique => "F3",
+            Font::Mono => "F4",
+        }
+    }
+
+    fn base_font(&self) -> &'static str {
+        match self {
+            Font::Regular => "Helvetica",
+            Font::Bold => "Helvetica-Bold",
+            Font::Oblique => "Helvetica-Oblique",
+            Font::Mono => "Courier",
+        }
+    }
+
+    /// Advance width of an encoded byte, in 1/1000 em.
+    fn width(&self, byte: u8) -> u16 {
+        let table = match self {
+            Font::Mono => return 600,
+            Font::Bold => &HELVETICA_BOLD_WIDTHS,
+            Font::Regular | Font::Oblique => &HELVETICA_WIDTHS,
+        };
+        match byte {
+            32..=126 => table[usize::from(byte - 32)],
+            _ => 556,
+        }
+    }
+
+    fn text_width(&self, text: &str, size: f32) -> f32 {
+        let units: u32 = encode(text).iter().map(|&b| u32::from(self.width(b))).sum();
+        units as f32 * size / 1000.0
+    }
+}
+
+/// Helvetica advance widths for ASCII 32..=126 (from the Adobe AFM).
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Helvetica-Bold advance widths for ASCII 32..=126 (from the Adobe AFM).
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+/// Encode text as Windows-1252, replacing what it cannot represent.
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            ' '..='~' => c as u8,
+            '\t' => b' ',
+            '\u{a0}'..='\u{ff}' => c as u32 as u8,
+            '€' => 0x80,
+            '‚' => 0x82,
+            'ƒ' => 0x83,
+            '„' => 0x84,
+            '…' => 0x85,
+            '†' => 0x86,
+            '‡' => 0x87,
+            'ˆ' => 0x88,
+            '‰' => 0x89,
+            'Š' => 0x8a,
+            '‹' => 0x8b,
+            'Œ' => 0x8c,
+            'Ž' => 0x8e,
+            '\u{2018}' => 0x91,
+            '\u{2019}' => 0x92,
+            '\u{201c}' => 0x93,
+            '\u{201d}' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            '˜' => 0x98,
+            '™' => 0x99,
+            'š' => 0x9a,
+            '›' => 0x9b,
+            'œ' => 0x9c,
+            'ž' => 0x9e,
+            'Ÿ' => 0x9f,
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// A laid-out line, before pagination.
+#[derive(Debug, Clone, PartialEq)]
+enum Row {
+    Text {
+        font: Font,
+        size: f32,
+        /// Offset from the left margin.
+        indent: f32,
+        text: String,
+        space_before: f32,
+    },
+    Rule {
+        space_before: f32,
+    },
+}
+
+impl Row {
+    fn space_before(&self) -> f32 {
+        match self {
+            Row::Text { space_before, .. } | Row::Rule { space_before } => *space_before,
+        }
+    }
+
+    fn height(&self) -> f32 {
+        match self {
+            Row::Text { size, .. } => size * 1.3,
+            Row::Rule { .. } => 8.0,
+        }
+    }
+}
+
+/// Renders Markdown or HTML to PDF bytes.
+#[derive(Debug, Clone, Default)]
+pub struct PdfRenderer {
+    options: PdfOptions,
+}
+
+impl PdfRenderer {
+    pub fn new(options: PdfOptions) -> Self {
+        Self { options }
+    }
+
+    /// Render a Markdown document.
+    pub fn render_markdown(&self, markdown: &str) -> Vec<u8> {
+        let rows = self.layout(markdown);
+        let title = self
+            .options
+            .title
+            .clone()
+            .or_else(|| first_heading(markdown));
+        self.write_pdf(&self.paginate(rows), title.as_deref())
+    }
+
+    /// Render an HTML document. Only structure is kept; styles are ignored.
+    pub fn render_html(&self, html: &str) -> Vec<u8> {
+        self.render_markdown(&html_to_markdown(html))
+    }
+
+    fn text_width(&self) -> f32 {
+        self.options.page_size.dimensions().0 - 2.0 * self.options.margin
+    }
+
+    /// Turn Markdown blocks into wrapped rows.
+    fn layout(&self, markdown: &str) -> Vec<Row> {
+        let base = self.options.font_size;
+        let width = self.text_width();
+        let mut rows = Vec::new();
+        let mut paragraph: Vec<&str> = Vec::new();
+        let mut lines = markdown.lines().peekable();
+
+        let flush = |paragraph: &mut Vec<&str>, rows: &mut Vec<Row>| {
+            if paragraph.is_empty() {
+                return;
+            }
+            let text = inline_text(&paragraph.join(" "));
+            paragraph.clear();
+            push_wrapped(rows, &text, Font::Regular, base, 0.0, width, base * 0.6);
+        };
+
+        while let Some(line) = lines.next() {
+            let trimmed = line.trim();
+
+            if trimmed.starts_with("```") {
+                flush(&mut paragraph, &mut rows);
+                let size = base * 0.9;
+                let mut first = true;
+                for code in lines.by_ref() {
+                    if code.trim_start().starts_with("```") {
+                        break;
+                    }
+                    let space = if first { base * 0.6 } else { 0.0 };
+                    first = false;
+                    for piece in split_to_width(code.trim_end(), Font::Mono, size, width - 12.0) {
+                        rows.push(Row::Text {
+                            font: Font::Mono,
+                            size,
+                            indent: 12.0,
+                            text: piece,
+                            space_before: space,
+                        });
+                    }
+                }
+                continue;
+            }
+
+            if trimmed.is_empty() {
+                flush(&mut paragraph, &mut rows);
+                continue;
+            }
+
+            if let Some((level, text)) = heading(trimmed) {
+                flush(&mut paragraph, &mut rows);
+                let size = match level {
+                    1 => base * 1.8,
+                    2 => base * 1.4,
+                    3 => base * 1.2,
+                    _ => base,
+                };
+                push_wrapped(
+                    &mut rows,
+                    &inline_text(text),
+                    Font::Bold,
+                    size,
+                    0.0,
+                    width,
+                    size * 0.8,
+                );
+                continue;
+            }
+
+            if is_rule(trimmed) {
+                flush(&mut paragraph, &mut rows);
+                rows.push(Row::Rule {
+                    space_before: base * 0.6,
+                });
+                continue;
+            }
+
+            if trimmed.starts_with('|') {
+                flush(&mut paragraph, &mut rows);
+                let mut table = vec![trimmed];
+                while let Some(next) = lines.peek() {
+                    if !next.trim().starts_with('|') {
+                        break;
+                    }
+                    table.push(next.trim());
+                    lines.next();
+                }
+                layout_table(&mut rows, &table, base * 0.85, width, base * 0.6);
+                continue;
+            }
+
+            let depth = (line.len() - line.trim_start().len()) / 2;
+            let indent = 14.0 * depth as f32;
+            if let Some((marker, text)) = list_item(trimmed) {
+                flush(&mut paragraph, &mut rows);
+                let hang = Font::Regular.text_width(&marker, base);
+                let wrapped = wrap(
+                    &inline_text(text),
+                    Font::Regular,
+                    base,
+                    width - indent - hang,
+                );
+                for (i, piece) in wrapped.into_iter().enumerate() {
+                    rows.push(Row::Text {
+                        font: Font::Regular,
+                        size: base,
+                        indent: if i == 0 { indent } else { indent + hang },
+                        text: if i == 0 {
+                            format!("{}{}", marker, piece)
+                        } else {
+                            piece
+                        },
+                        space_before: if i == 0 { base * 0.25 } else { 0.0 },
+                    });
+                }
+                continue;
+            }
+
+            if let Some(quote) = trimmed.strip_prefix('>') {
+                flush(&mut paragraph, &mut rows);
+                push_wrapped(
+                    &mut rows,
+                    &inline_text(quote.trim()),
+                    Font::Oblique,
+                    base,
+                    16.0,
+                    width - 16.0,
+                    base * 0.4,
+                );
+                continue;
+            }
+
+            paragraph.push(trimmed);
+        }
+        flush(&mut paragraph, &mut rows);
+        rows
+    }
+
+    /// Split rows into pages, dropping spacing at the top of each page.
+    fn paginate(&self, rows: Vec<Row>) -> Vec<Vec<(f32, Row)>> {
+        let (_, height) = self.options.page_size.dimensions();
+        let top = height - self.options.margin;
+        // Leave room for the page number below the text.
+        let bottom = self.options.margin + self.options.font_size * 1.5;
+
+        let mut pages = vec![Vec::new()];
+        let mut y = top;
+        for row in rows {
+            let current = pages.last_mut().expect("at least one page");
+            let mut advance = row.height();
+            if !current.is_empty() {
+                advance += row.space_before();
+            }
+            if y - advance < bottom && !current.is_empty() {
+                pages.push(Vec::new());
+                y = top;
+                advance = row.height();
+            }
+            y -= advance;
+            pages.last_mut().expect("at least one page").push((y, row));
+        }
+        pages
+    }
+
+    fn page_content(&self, rows: &[(f32, Row)], number: usize, total: usize) -> Vec<u8> {
+        let margin = self.options.margin;
+        let (width, _) = self.options.page_size.dimensions();
+        let mut out = Vec::new();
+        for (y, row) in rows {
+            match row {
+                Row::Text {
+                    font,
+                    size,
+                    indent,
+                    text,
+                    ..
+                } => {
+                    // Baseline sits a little above the bottom of the row.
+                    let baseline = y + size * 0.3;
+                    text_op(&mut out, *font, *size, margin + indent, baseline, text);
+                }
+                Row::Rule { .. } => {
+                    let ly = y + 4.0;
+                    out.extend_from_slice(
+                        format!(
+                            "0.6 w 0.6 G {:.2} {:.2} m {:.2} {:.2} l S\n",
+                            margin,
+                            ly,
+                            width - margin,
+                            ly
+                        )
+                        .as_bytes(),
+                    );
+                }
+            }
+        }
+
+        let size = self.options.font_size * 0.8;
+        let label = format!("{} / {}", number, total);
+        let x = (width - Font::Regular.text_width(&label, size)) / 2.0;
+        text_op(&mut out, Font::Regular, size, x, margin / 2.0, &label);
+        out
+    }
+
+    /// Serialize pages into a PDF file with a cross-reference table.
+    fn write_pdf(&self, pages: &[Vec<(f32, Row)>], title: Option<&str>) -> Vec<u8> {
+        let (width, height) = self.options.page_size.dimensions();
+        let font_count = Font::ALL.len();
+        // 1: catalog, 2: pages, 3: info, then fonts, then a page and its
+        // content stream per page.
+        let first_font = 4;
+        let first_page = first_font + font_count;
+        let page_id = |i: usize| first_page + 2 * i;
+
+        let mut objects: Vec<Vec<u8>> = Vec::new();
+        objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+        let kids: Vec<String> = (0..pages.len())
+            .map(|i| format!("{} 0 R", page_id(i)))
+            .collect();
+        objects.push(
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                pages.len()
+            )
+            .into_bytes(),
+        );
+
+        let mut info = b"<< /Producer ".to_vec();
+        info.extend(pdf_string(concat!("IronClaw ", env!("CARGO_PKG_VERSION"))));
+        if let Some(title) = title {
+            info.extend_from_slice(b" /Title ");
+            info.extend(pdf_string(title));
+        }
+        info.extend_from_slice(b" >>");
+        objects.push(info);
+
+        for font in Font::ALL {
+            objects.push(
+                format!(
+                    "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                    font.base_font()
+                )
+                .into_bytes(),
+            );
+        }
+
+        let fonts: Vec<String> = Font::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, f)| format!("/{} {} 0 R", f.resource(), first_font + i))
+            .collect();
+        for (i, rows) in pages.iter().enumerate() {
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                     /Resources << /Font << {} >> >> /Contents {} 0 R >>",
+                    width,
+                    height,
+                    fonts.join(" "),
+                    page_id(i) + 1
+                )
+                .into_bytes(),
+            );
+            let content = self.page_content(rows, i + 1, pages.len());
+            let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+            stream.extend(content);
+            stream.extend_from_slice(b"\nendstream");
+            objects.push(stream);
+        }
+
+        let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, body) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend(format!("{} 0 obj\n", i + 1).into_bytes());
+            out.extend_from_slice(body);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+        let xref = out.len();
+        out.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+        for offset in offsets {
+            out.extend(format!("{:010} 00000 n \n", offset).into_bytes());
+        }
+        out.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R /Info 3 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref
+            )
+            .into_bytes(),
+        );
+        out
+    }
+}
+
+/// Append a text-showing operation to a content stream.
+fn text_op(out: &mut Vec<u8>, font: Font, size: f32, x: f32, y: f32, text: &str) {
+    out.extend(
+        format!(
+            "BT /{} {:.1} Tf 1 0 0 1 {:.2} {:.2} Tm ",
+            font.resource(),
+            size,
+            x,
+            y
+        )
+        .into_bytes(),
+    );
+    out.extend(pdf_string(text));
+    out.extend_from_slice(b" Tj ET\n");
+}
+
+/// Encode text as a PDF literal string.
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut out = vec![b'('];
+    for byte in encode(text) {
+        if matches!(byte, b'(' | b')' | b'\\') {
+            out.push(b'\\');
+        }
+        out.push(byte);
+    }
+    out.push(b')');
+    out
+}
+
+fn push_wrapped(
+    rows: &mut Vec<Row>,
+    text: &str,
+    font: Font,
+    size: f32,
+    indent: f32,
+    width: f32,
+    space_before: f32,
+) {
+    for (i, piece) in wrap(text, font, size, width).into_iter().enumerate() {
+        rows.push(Row::Text {
+            font,
+            size,
+            indent,
+            text: piece,
+            space_before: if i == 0 { space_before } else { 0.0 },
+        });
+    }
+}
+
+/// Greedy word wrap. Words wider than a line are split.
+fn wrap(text: &str, font: Font, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if current.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", current, word)
+        };
+        if font.text_width(&candidate, size) <= width {
+            current = candidate;
+            continue;
+        }
+        if !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+        let mut pieces = split_to_width(word, font, size, width);
+        current = pieces.pop().unwrap_or_default();
+        lines.extend(pieces);
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Split text into pieces no wider than `width`, breaking anywhere.
+fn split_to_width(text: &str, font: Font, size: f32, width: f32) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    for c in text.chars() {
+        current.push(c);
+        if font.text_width(&current, size) > width && current.chars().count() > 1 {
+            current.pop();
+            pieces.push(std::mem::take(&mut current));
+            current.push(c);
+        }
+    }
+    pieces.push(current);
+    pieces
+}
+
+fn layout_table(rows: &mut Vec<Row>, lines: &[&str], size: f32, width: f32, space_before: f32) {
+    let mut table: Vec<Vec<String>> = Vec::new();
+    let mut header_rows = 0;
+    for line in lines {
+        let inner = line.trim().trim_start_matches('|').trim_end_matches('|');
+        // Escaped pipes belong to the cell, not the table.
+        let inner = inner.replace("\\|", "\u{0}");
+        let cells: Vec<String> = inner
+            .split('|')
+            .map(|c| inline_text(c.trim()).replace('\u{0}', "|"))
+            .collect();
+        if cells
+            .iter()
+            .all(|c| !c.is_empty() && c.chars().all(|ch| matches!(ch, '-' | ':')))
+        {
+            header_rows = table.len();
+            continue;
+        }
+        table.push(cells);
+    }
+
+    let columns = table.iter().map(Vec::len).max().unwrap_or(0);
+    let mut widths = vec![0; columns];
+    for row in &table {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let mut first = true;
+    for (r, row) in table.iter().enumerate() {
+        let line: Vec<String> = (0..columns)
+            .map(|i| {
+                let cell = row.get(i).map(String::as_str).unwrap_or("");
+                format!("{:width$}", cell, width = widths[i])
+            })
+            .collect();
+        let font = Font::Mono;
+        for piece in split_to_width(line.join("  ").trim_end(), font, size, width) {
+            rows.push(Row::Text {
+                font,
+                size,
+                indent: 0.0,
+                text: piece,
+                space_before: if first { space_before } else { 0.0 },
+            });
+            first = false;
+        }
+        if header_rows > 0 && r + 1 == header_rows {
+            let total: usize = widths.iter().sum::<usize>() + 2 * columns.saturating_sub(1);
+            rows.push(Row::Text {
+                font,
+                size,
+                indent: 0.0,
+                text: split_to_width(&"-".repeat(total), font, size, width).swap_remove(0),
+                space_before: 0.0,
+            });
+        }
+    }
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if rest.is_empty() {
+        return Some((level, ""));
+    }
+    rest.strip_prefix(' ')
+        .map(|text| (level, text.trim().trim_end_matches('#').trim_end()))
+}
+
+fn is_rule(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|&m| compact.chars().all(|c| c == m))
+}
+
+/// Split a list item into its printed marker and text.
+fn list_item(line: &str) -> Option<(String, &str)> {
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(text) = line.strip_prefix(bullet) {
+            return Some(("\u{2022}  ".to_string(), text));
+        }
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        let rest = &line[digits..];
+        if let Some(text) = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")) {
+            return Some((format!("{}.  ", &line[..digits]), text));
+        }
+    }
+    None
+}
+
+/// Drop inline Markdown markers and spell out links.
+fn inline_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if i + 1 < chars.len() => {
+                out.push(chars[i + 1]);
+                i += 2;
+                continue;
+            }
+            '`' => {}
+            '*' | '_' if chars.get(i + 1) == Some(&c) => {
+                i += 2;
+                continue;
+            }
+            // A lone `*` only marks emphasis when it touches a word.
+            '*' => {
+                let before = i > 0 && !chars[i - 1].is_whitespace();
+                let after = chars.get(i + 1).is_some_and(|n| !n.is_whitespace());
+                if !(before || after) {
+                    out.push(c);
+                }
+            }
+            '[' => {
+                if let Some((label, url, next)) = parse_link(&chars, i) {
+                    out.push_str(&label);
+                    if !url.is_empty() && url != label {
+                        out.push_str(&format!(" ({})", url));
+                    }
+                    i = next;
+                    continue;
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Parse `[label](url)` starting at `start`; returns the label, url and the
+/// index after the closing parenthesis.
+fn parse_link(chars: &[char], start: usize) -> Option<(String, String, usize)> {
+    let close = start + chars[start..].iter().position(|&c| c == ']')?;
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+    let end = close + 1 + chars[close + 1..].iter().position(|&c| c == ')')?;
+    let label: String = chars[start + 1..close].iter().collect();
+    let url: String = chars[close + 2..end].iter().collect();
+    Some((inline_text(&label), url, end + 1))
+}
+
+fn first_heading(markdown: &str) -> Option<String> {
+    markdown
+        .lines()
+        .find_map(|l| heading(l.trim()))
+        .map(|(_, text)| inline_text(text))
+        .filter(|t| !t.is_empty())
+}
+
+/// Convert simple HTML to the Markdown subset the renderer understands.
+///
+/// Headings, paragraphs, lists, tables, `pre` blocks, rules and links are
+/// kept; `head`, `script` and `style` are dropped along with all attributes
+/// other than link targets.
+pub fn html_to_markdown(html: &str) -> String {
+    let mut out = String::new();
+    let mut rest = html;
+    let mut pre = 0usize;
+    let mut row_has_th = false;
+    let mut links: Vec<Option<String>> = Vec::new();
+
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            push_html_text(&mut out, rest, pre > 0);
+            break;
+        };
+        push_html_text(&mut out, &rest[..lt], pre > 0);
+        rest = &rest[lt..];
+
+        if rest.starts_with("<!--") {
+            rest = rest.find("-->").map_or("", |end| &rest[end + 3..]);
+            continue;
+        }
+        let Some(gt) = rest.find('>') else {
+            push_html_text(&mut out, rest, pre > 0);
+            break;
+        };
+        let tag = &rest[1..gt];
+        rest = &rest[gt + 1..];
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        match name.as_str() {
+            "head" | "script" | "style" | "title" if !closing => {
+                let end = format!("</{}", name);
+                rest = find_ignore_case(rest, &end)
+                    .and_then(|i| rest[i..].find('>').map(|j| &rest[i + j + 1..]))
+                    .unwrap_or("");
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                if closing {
+                    out.push_str("\n\n");
+                } else {
+                    let level = usize::from(name.as_bytes()[1] - b'0');
+                    out.push_str(&format!("\n\n{} ", "#".repeat(level)));
+                }
+            }
+            "p" | "div" | "section" | "article" | "header" | "footer" | "ul" | "ol" | "table"
+            | "blockquote" => out.push_str("\n\n"),
+            "br" => out.push('\n'),
+            "hr" => out.push_str("\n\n---\n\n"),
+            "li" if !closing => out.push_str("\n- "),
+            "tr" => {
+                if closing {
+                    out.push('\n');
+                    if row_has_th {
+                        out.push_str("|---|\n");
+                    }
+                } else {
+                    row_has_th = false;
+                    if !out.ends_with('\n') {
+                        out.push('\n');
+                    }
+                    out.push('|');
+                }
+            }
+            "th" | "td" => {
+                if closing {
+                    out.push_str(" |");
+                } else {
+                    row_has_th |= name == "th";
+                    out.push(' ');
+                }
+            }
+            "pre" => {
+                if closing {
+                    pre = pre.saturating_sub(1);
+                    out.push_str("\n```\n\n");
+                } else {
+                    pre += 1;
+                    out.push_str("\n\n```\n");
+                }
+            }
+            "code" if pre == 0 => out.push('`'),
+            "strong" | "b" => out.push_str("**"),
+            "a" => {
+                if closing {
+                    if let Some(Some(href)) = links.pop() {
+                        out.push_str(&format!("]({})", href));
+                    }
+                } else {
+                    let href = attribute(tag, "href");
+                    if href.is_some() {
+                        out.push('[');
+                    }
+                    links.push(href);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Collapse the blank lines block tags leave behind.
+    let mut markdown = String::new();
+    let mut blank = 0;
+    for line in out.lines() {
+        if line.trim().is_empty() {
+            blank += 1;
+            continue;
+        }
+        if blank > 0 && !markdown.is_empty() {
+            markdown.push('\n');
+        }
+        blank = 0;
+        markdown.push_str(line.trim_end());
+        markdown.push('\n');
+    }
+    markdown
+}
+
+fn push_html_text(out: &mut String, text: &str, preformatted: bool) {
+    let text = decode_entities(text);
+    if preformatted {
+        out.push_str(&text);
+        return;
+    }
+    let mut last_space = out.ends_with(char::is_whitespace);
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !last_space {
+                out.push(' ');
+            }
+            last_space = true;
+        } else {
+            out.push(c);
+            last_space = false;
+        }
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&semi| semi <= 10).and_then(|semi| {
+            let entity = &rest[1..semi];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|d| d.parse().ok()))
+                    .and_then(char::from_u32),
+            }?;
+            Some((c, semi))
+        });
+        match decoded {
+            Some((c, semi)) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack.to_ascii_lowercase().find(needle)
+}
+
+/// Value of a quoted attribute in a tag body.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let start = lower.find(&format!("{}=", name))? + name.len() + 1;
+    let value = &tag[start..];
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let end = value[1..].find(quote)?;
+    Some(decode_entities(&value[1..1 + end]))
+}
+
+/// Default directory for exported documents: `~/.ironclaw/exports`.
+pub fn default_exports_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".ironclaw")
+        .join("exports")
+}
+
+/// Write an exported file under [`default_exports_dir`], creating the
+/// directory if needed. Returns the full path.
+pub fn write_export(file_name: &str, bytes: &[u8]) -> std::io::Result<PathBuf> {
+    let dir = default_exports_dir();
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(file_name);
+    std::fs::write(&path, bytes)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_rows(rows: &[Row]) -> Vec<&str> {
+        rows.iter()
+            .filter_map(|r| match r {
+                Row::Text { text, .. } => Some(text.as_str()),
+                Row::Rule { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_render_produces_valid_structure() {
+        let pdf = PdfRenderer::default().render_markdown("# Weekly (draft)\n\nHello (world).\n");
+        assert!(pdf.starts_with(b"%PDF-1.4\n"));
+        // Past the binary marker line the file is plain ASCII here.
+        let header = 15;
+        let text = std::str::from_utf8(&pdf[header..]).unwrap();
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/Title (Weekly \\(draft\\))"));
+        assert!(text.contains("(Hello \\(world\\).) Tj"));
+        assert!(text.contains("/Count 1"));
+
+        // Every xref entry points at the object it names.
+        let xref_at: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|s| s.lines().next())
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap()
+            - header;
+        assert!(text[xref_at..].starts_with("xref"));
+        for (i, entry) in text[xref_at..].lines().skip(3).enumerate() {
+            if entry.starts_with("trailer") {
+                break;
+            }
+            let offset = entry[..10].parse::<usize>().unwrap() - header;
+            assert!(text[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+
+    #[test]
+    fn test_layout_blocks_and_wrapping() {
+        let renderer = PdfRenderer::default();
+        let long = "word ".repeat(200);
+        let md = format!(
+            "## Tools\n\n- **shell**: ran `ls`\n1. see [docs](https://example.com)\n\n---\n\n\
+             | Tool | Calls |\n|---|---|\n| http | 3 |\n\n```\nfn main() {{}}\n```\n\n{}",
+            long
+        );
+        let rows = renderer.layout(&md);
+        let texts = text_rows(&rows);
+        assert_eq!(texts[0], "Tools");
+        assert_eq!(texts[1], "\u{2022}  shell: ran ls");
+        assert_eq!(texts[2], "1.  see docs (https://example.com)");
+        assert!(rows.iter().any(|r| matches!(r, Row::Rule { .. })));
+        assert_eq!(texts[3], "Tool  Calls");
+        assert_eq!(texts[4], "-----------");
+        assert_eq!(texts[5], "http  3");
+        assert_eq!(texts[6], "fn main() {}");
+
+        // The long paragraph wraps inside the text width and spills onto
+        // a second page when repeated.
+        let width = renderer.text_width();
+        assert!(texts.len() > 8);
+        for text in &texts[7..] {
+            assert!(Font::Regular.text_width(text, 11.0) <= width);
+        }
+        let pages = renderer.paginate(renderer.layout(&long.repeat(10)));
+        assert!(pages.len() > 1);
+        assert!(pages.iter().all(|p| !p.is_empty()));
+    }
+
+    #[test]
+    fn test_html_to_markdown() {
+        let html = "<!DOCTYPE html><html><head><title>x</title><style>p{}</style></head>\
+                    <body><h1>Report &amp; notes</h1><p>First   line<br>second</p>\
+                    <ul><li>one</li><li><a href=\"https://a.b\">two</a></li></ul>\
+                    <table><tr><th>A</th><th>B</th></tr><tr><td>1</td><td>2</td></tr></table>\
+                    <pre>let x = 1;\n  y</pre></body></html>";
+        let md = html_to_markdown(html);
+        assert_eq!(
+            md,
+            "# Report & notes\n\nFirst line\nsecond\n\n- one\n- [two](https://a.b)\n\n\
+             | A | B |\n|---|\n| 1 | 2 |\n\n```\nlet x = 1;\n  y\n```\n"
+        );
+        assert_eq!(first_heading(&md).as_deref(), Some("Report & notes"));
+    }
+
+    #[test]
+    fn test_encoding_replaces_unsupported_characters() {
+        assert_eq!(
+            encode("café – “ok” 日"),
+            b"caf\xe9 \x96 \x93ok\x94 ?".to_vec()
+        );
+    }
+}
@@ -46,14 +46,12 @@ pub trait TranscriptionProvider: Send + Sync {
 }
 
 /// OpenAI Whisper-based transcription provider.
-#[allow(dead_code)]
 pub struct WhisperProvider {
     api_key: String,
     base_url: String,
     model: String,
 }
 
-#[allow(dead_code)]
 impl WhisperProvider {
     /// Create a new Whisper provider.
     pub fn new(api_key: String) -> Self {
@@ -111,7 +109,7 @@ impl TranscriptionProvider for WhisperProvider {
             form = form.text("language", lang.to_string());
         }
 
-        let client = reqwest::Client::new();
+        let client = crate::outbound::client();
         let response = client
             .post(format!("{}/audio/transcriptions", self.base_url))
             .header("Authorization", format!("Bearer {}", self.api_key))
@@ -232,7 +232,7 @@ impl TtsProvider for OpenAiTtsProvider {
             "response_format": Self::format_to_api_param(format),
         });
 
-        let client = reqwest::Client::new();
+        let client = crate::outbound::client();
         let response = client
             .post(format!("{}/audio/speech", self.base_url))
             .header("Authorization", format!("Bearer {}", self.api_key))
@@ -57,14 +57,12 @@ pub trait VisionProvider: Send + Sync {
 }
 
 /// OpenAI-compatible vision provider (works with GPT-4V, Claude, etc.).
-#[allow(dead_code)]
 pub struct OpenAiVisionProvider {
     api_key: String,
     base_url: String,
     model: String,
 }
 
-#[allow(dead_code)]
 impl OpenAiVisionProvider {
     /// Create a new OpenAI vision provider.
     pub fn new(api_key: String, model: String) -> Self {
@@ -120,7 +118,7 @@ impl VisionProvider for OpenAiVisionProvider {
             "max_tokens": request.max_tokens.unwrap_or(1024)
         });
 
-        let client = reqwest::Client::new();
+        let client = crate::outbound::client();
         let response = client
             .post(format!("{}/chat/completions", self.base_url))
             .header("Authorization", format!("Bearer {}", self.api_key))
@@ -11,12 +11,14 @@ use axum::http::StatusCode;
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use serde::{Deserialize, Serialize};
-use tokio::sync::{Mutex, broadcast};
+use tokio::sync::Mutex;
 use uuid::Uuid;
 
 use crate::channels::web::types::SseEvent;
 use crate::db::Database;
-use crate::llm::{CompletionRequest, LlmProvider, ToolCompletionRequest};
+use crate::error::LlmError;
+use crate::event_bus::{BusEvent, EventBus};
+use crate::llm::{CacheControl, CompletionRequest, LlmProvider, ToolCompletionRequest};
 use crate::orchestrator::auth::{TokenStore, worker_auth_middleware};
 use crate::orchestrator::job_manager::ContainerJobManager;
 use crate::worker::api::JobEventPayload;
@@ -38,8 +40,8 @@ pub struct OrchestratorState {
     pub llm: Arc<dyn LlmProvider>,
     pub job_manager: Arc<ContainerJobManager>,
     pub token_store: TokenStore,
-    /// Broadcast channel for job events (consumed by the web gateway SSE).
-    pub job_event_tx: Option<broadcast::Sender<(Uuid, SseEvent)>>,
+    /// Event bus job events are published on (consumed by the web gateway SSE).
+    pub events: Option<Arc<EventBus>>,
     /// Buffered follow-up prompts for sandbox jobs, keyed by job_id.
     pub prompt_queue: Arc<Mutex<HashMap<Uuid, VecDeque<PendingPrompt>>>>,
     /// Database handle for persisting job events.
@@ -135,32 +137,114 @@ async fn llm_complete(
     Path(job_id): Path<Uuid>,
     Json(req): Json<ProxyCompletionRequest>,
 ) -> Result<Json<ProxyCompletionResponse>, StatusCode> {
+    proxy_completion(&state, job_id, req)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::BAD_GATEWAY)
+}
+
+async fn llm_complete_with_tools(
+    State(state): State<OrchestratorState>,
+    Path(job_id): Path<Uuid>,
+    Json(req): Json<ProxyToolCompletionRequest>,
+) -> Result<Json<ProxyToolCompletionResponse>, StatusCode> {
+    proxy_tool_completion(&state, job_id, req)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::BAD_GATEWAY)
+}
+
+async fn report_status(
+    Path(job_id): Path<Uuid>,
+    Json(update): Json<StatusUpdate>,
+) -> Result<StatusCode, StatusCode> {
+    tracing::debug!(
+        job_id = %job_id,
+        state = %update.state,
+        iteration = update.iteration,
+        "Worker status update"
+    );
+
+    Ok(StatusCode::OK)
+}
+
+async fn report_complete(
+    State(state): State<OrchestratorState>,
+    Path(job_id): Path<Uuid>,
+    Json(report): Json<CompletionReport>,
+) -> Result<StatusCode, StatusCode> {
+    finish_job(&state, job_id, &report).await;
+    Ok(StatusCode::OK)
+}
+
+// -- Sandbox job event handlers --
+
+/// Receive a job event from a worker or Claude Code bridge and broadcast + persist it.
+async fn job_event_handler(
+    State(state): State<OrchestratorState>,
+    Path(job_id): Path<Uuid>,
+    Json(payload): Json<JobEventPayload>,
+) -> Result<StatusCode, StatusCode> {
+    publish_job_event(&state, job_id, payload).await;
+    Ok(StatusCode::OK)
+}
+
+/// Return the next queued follow-up prompt for a Claude Code bridge.
+/// Returns 204 No Content if no prompt is available.
+async fn get_prompt_handler(
+    State(state): State<OrchestratorState>,
+    Path(job_id): Path<Uuid>,
+) -> Result<(StatusCode, Json<serde_json::Value>), StatusCode> {
+    if let Some(prompt) = next_prompt(&state, job_id).await {
+        return Ok((
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "content": prompt.content,
+                "done": prompt.done,
+            })),
+        ));
+    }
+
+    // Return 204 with an empty body. The Json wrapper requires some value
+    // but the status code signals "nothing here".
+    Ok((StatusCode::NO_CONTENT, Json(serde_json::Value::Null)))
+}
+
+// -- Shared with the gRPC API --
+
+/// Proxy an LLM completion for a job.
+pub(crate) async fn proxy_completion(
+    state: &OrchestratorState,
+    job_id: Uuid,
+    req: ProxyCompletionRequest,
+) -> Result<ProxyCompletionResponse, LlmError> {
     let completion_req = CompletionRequest {
         messages: req.messages,
         max_tokens: req.max_tokens,
         temperature: req.temperature,
         stop_sequences: req.stop_sequences,
         metadata: std::collections::HashMap::new(),
+        cache_control: CacheControl::default(),
     };
 
-    let resp = state.llm.complete(completion_req).await.map_err(|e| {
+    let resp = state.llm.complete(completion_req).await.inspect_err(|e| {
         tracing::error!("LLM completion failed for job {}: {}", job_id, e);
-        StatusCode::BAD_GATEWAY
     })?;
 
-    Ok(Json(ProxyCompletionResponse {
+    Ok(ProxyCompletionResponse {
         content: resp.content,
         input_tokens: resp.input_tokens,
         output_tokens: resp.output_tokens,
         finish_reason: format_finish_reason(resp.finish_reason),
-    }))
+    })
 }
 
-async fn llm_complete_with_tools(
-    State(state): State<OrchestratorState>,
-    Path(job_id): Path<Uuid>,
-    Json(req): Json<ProxyToolCompletionRequest>,
-) -> Result<Json<ProxyToolCompletionResponse>, StatusCode> {
+/// Proxy an LLM tool completion for a job.
+pub(crate) async fn proxy_tool_completion(
+    state: &OrchestratorState,
+    job_id: Uuid,
+    req: ProxyToolCompletionRequest,
+) -> Result<ProxyToolCompletionResponse, LlmError> {
     let tool_req = ToolCompletionRequest {
         messages: req.messages,
         tools: req.tools,
@@ -168,41 +252,29 @@ async fn llm_complete_with_tools(
         temperature: req.temperature,
         tool_choice: req.tool_choice,
         metadata: std::collections::HashMap::new(),
+        // Workers resend the same system prompt and tools every iteration
+        cache_control: CacheControl::STABLE_PREFIX,
     };
 
-    let resp = state.llm.complete_with_tools(tool_req).await.map_err(|e| {
-        tracing::error!("LLM tool completion failed for job {}: {}", job_id, e);
-        StatusCode::BAD_GATEWAY
-    })?;
+    let resp = state
+        .llm
+        .complete_with_tools(tool_req)
+        .await
+        .inspect_err(|e| {
+            tracing::error!("LLM tool completion failed for job {}: {}", job_id, e);
+        })?;
 
-    Ok(Json(ProxyToolCompletionResponse {
+    Ok(ProxyToolCompletionResponse {
         content: resp.content,
         tool_calls: resp.tool_calls,
         input_tokens: resp.input_tokens,
         output_tokens: resp.output_tokens,
         finish_reason: format_finish_reason(resp.finish_reason),
-    }))
+    })
 }
 
-async fn report_status(
-    Path(job_id): Path<Uuid>,
-    Json(update): Json<StatusUpdate>,
-) -> Result<StatusCode, StatusCode> {
-    tracing::debug!(
-        job_id = %job_id,
-        state = %update.state,
-        iteration = update.iteration,
-        "Worker status update"
-    );
-
-    Ok(StatusCode::OK)
-}
-
-async fn report_complete(
-    State(state): State<OrchestratorState>,
-    Path(job_id): Path<Uuid>,
-    Json(report): Json<CompletionReport>,
-) -> Result<StatusCode, StatusCode> {
+/// Record a worker's completion report and clean up its container.
+pub(crate) async fn finish_job(state: &OrchestratorState, job_id: Uuid, report: &CompletionReport) {
     if report.success {
         tracing::info!(
             job_id = %job_id,
@@ -222,18 +294,14 @@ async fn report_complete(
         message: report.message.clone(),
     };
     let _ = state.job_manager.complete_job(job_id, result).await;
-
-    Ok(StatusCode::OK)
 }
 
-// -- Sandbox job event handlers --
-
-/// Receive a job event from a worker or Claude Code bridge and broadcast + persist it.
-async fn job_event_handler(
-    State(state): State<OrchestratorState>,
-    Path(job_id): Path<Uuid>,
-    Json(payload): Json<JobEventPayload>,
-) -> Result<StatusCode, StatusCode> {
+/// Persist a job event and publish it on the event bus.
+pub(crate) async fn publish_job_event(
+    state: &OrchestratorState,
+    job_id: Uuid,
+    payload: JobEventPayload,
+) {
     tracing::debug!(
         job_id = %job_id,
         event_type = %payload.event_type,
@@ -324,36 +392,22 @@ async fn job_event_handler(
         },
     };
 
-    // Broadcast via the channel (if configured)
-    if let Some(ref tx) = state.job_event_tx {
-        let _ = tx.send((job_id, sse_event));
+    if let Some(ref events) = state.events {
+        events
+            .publish(BusEvent::Job {
+                job_id,
+                event: sse_event,
+            })
+            .await;
     }
-
-    Ok(StatusCode::OK)
 }
 
-/// Return the next queued follow-up prompt for a Claude Code bridge.
-/// Returns 204 No Content if no prompt is available.
-async fn get_prompt_handler(
-    State(state): State<OrchestratorState>,
-    Path(job_id): Path<Uuid>,
-) -> Result<(StatusCode, Json<serde_json::Value>), StatusCode> {
+/// Pop the next queued follow-up prompt for a job.
+pub(crate) async fn next_prompt(state: &OrchestratorState, job_id: Uuid) -> Option<PendingPrompt> {
     let mut queue = state.prompt_queue.lock().await;
-    if let Some(prompts) = queue.get_mut(&job_id)
-        && let Some(prompt) = prompts.pop_front()
-    {
-        return Ok((
-            StatusCode::OK,
-            Json(serde_json::json!({
-                "content": prompt.content,
-                "done": prompt.done,
-            })),
-        ));
-    }
-
-    // Return 204 with an empty body. The Json wrapper requires some value
-    // but the status code signals "nothing here".
-    Ok((StatusCode::NO_CONTENT, Json(serde_json::Value::Null)))
+    queue
+        .get_mut(&job_id)
+        .and_then(|prompts| prompts.pop_front())
 }
 
 fn format_finish_reason(reason: crate::llm::FinishReason) -> String {
@@ -419,7 +473,7 @@ mod tests {
             llm: Arc::new(StubLlm),
             job_manager: Arc::new(jm),
             token_store,
-            job_event_tx: None,
+            events: None,
             prompt_queue: Arc::new(Mutex::new(HashMap::new())),
             store: None,
         }
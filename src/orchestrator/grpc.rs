@@ -0,0 +1,695 @@
+//! Internal gRPC API for worker-to-orchestrator communication.
+//!
+//! Serves `ironclaw.worker.v1.WorkerService` (see `proto/worker.proto`) on
+//! its own port (default 50052), next to the HTTP API it mirrors. Calls are
+//! authenticated with the same per-job bearer tokens, and a job must
+//! complete the version handshake before anything else is served.
+//!
+//! Workers that open an `Attach` session stream their events and log lines
+//! on it and get follow-up prompts pushed back the moment they are queued,
+//! instead of calling `NextPrompt` on a timer.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, mpsc};
+use tokio_stream::Stream;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Response, Status, Streaming};
+use uuid::Uuid;
+
+use crate::orchestrator::api::{
+    OrchestratorState, finish_job, next_prompt, proxy_completion, proxy_tool_completion,
+    publish_job_event,
+};
+use crate::worker::api::{CompletionReport, JobEventPayload};
+use crate::worker::grpc::proto::worker_service_server::{WorkerService, WorkerServiceServer};
+use crate::worker::grpc::proto::{
+    self, ErrorCode, artifact_chunk::Payload, orchestrator_message, worker_message,
+};
+use crate::worker::grpc::{JOB_ID_METADATA, error_status, negotiate_version};
+
+/// Largest artifact a worker may upload (100 MB).
+const MAX_ARTIFACT_BYTES: u64 = 100 * 1024 * 1024;
+
+/// How often an attached session checks the in-process prompt queue.
+const PROMPT_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Outbound half of an `Attach` session.
+type SessionStream = Pin<Box<dyn Stream<Item = Result<proto::OrchestratorMessage, Status>> + Send>>;
+
+/// gRPC implementation of the worker API.
+pub struct OrchestratorGrpc {
+    state: OrchestratorState,
+    /// Negotiated protocol version per job.
+    sessions: Mutex<HashMap<Uuid, u32>>,
+    /// Uploaded artifacts land in `<artifacts_dir>/<job_id>/`.
+    artifacts_dir: PathBuf,
+}
+
+impl OrchestratorGrpc {
+    pub fn new(state: OrchestratorState, artifacts_dir: PathBuf) -> Self {
+        Self {
+            state,
+            sessions: Mutex::new(HashMap::new()),
+            artifacts_dir,
+        }
+    }
+
+    /// Default artifact directory (`~/.ironclaw/artifacts`).
+    pub fn default_artifacts_dir() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".ironclaw")
+            .join("artifacts")
+    }
+
+    /// Start the gRPC server on the given port.
+    ///
+    /// Binds like [`OrchestratorApi::start`](crate::orchestrator::OrchestratorApi::start):
+    /// all interfaces on Linux (containers come in over the docker bridge),
+    /// loopback elsewhere. Every call is token-checked.
+    pub async fn start(
+        state: OrchestratorState,
+        port: u16,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let addr = if cfg!(target_os = "linux") {
+            std::net::SocketAddr::from(([0, 0, 0, 0], port))
+        } else {
+            std::net::SocketAddr::from(([127, 0, 0, 1], port))
+        };
+
+        tracing::info!("Orchestrator gRPC API listening on {}", addr);
+
+        tonic::transport::Server::builder()
+            .add_service(WorkerServiceServer::new(Self::new(
+                state,
+                Self::default_artifacts_dir(),
+            )))
+            .serve(addr)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Check the bearer token against the job id in the metadata.
+    async fn authorize(&self, metadata: &MetadataMap) -> Result<Uuid, Status> {
+        let job_id = metadata
+            .get(JOB_ID_METADATA)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| Uuid::parse_str(v).ok())
+            .ok_or_else(|| error_status(ErrorCode::Unauthenticated, "missing or invalid job id"))?;
+        let token = metadata
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| error_status(ErrorCode::Unauthenticated, "missing bearer token"))?;
+
+        if !self.state.token_store.validate(job_id, token).await {
+            return Err(error_status(
+                ErrorCode::Unauthenticated,
+                "invalid token for job",
+            ));
+        }
+        Ok(job_id)
+    }
+
+    /// Authorize and require a completed handshake.
+    async fn session(&self, metadata: &MetadataMap) -> Result<Uuid, Status> {
+        let job_id = self.authorize(metadata).await?;
+        if !self.sessions.lock().await.contains_key(&job_id) {
+            return Err(error_status(
+                ErrorCode::HandshakeRequired,
+                "call Handshake before any other method",
+            ));
+        }
+        Ok(job_id)
+    }
+}
+
+/// Publish one message received on an `Attach` session.
+async fn handle_worker_message(
+    state: &OrchestratorState,
+    job_id: Uuid,
+    message: proto::WorkerMessage,
+) -> Result<(), Status> {
+    let payload = match message.payload {
+        Some(worker_message::Payload::Event(event)) => JobEventPayload {
+            event_type: event.event_type,
+            data: serde_json::from_str(&event.data_json).map_err(|e| {
+                error_status(
+                    ErrorCode::InvalidRequest,
+                    format!("event data is not JSON: {}", e),
+                )
+            })?,
+        },
+        Some(worker_message::Payload::Log(line)) => JobEventPayload {
+            event_type: "log".to_string(),
+            data: serde_json::json!({ "level": line.level, "message": line.message }),
+        },
+        None => {
+            return Err(error_status(
+                ErrorCode::InvalidRequest,
+                "empty session message",
+            ));
+        }
+    };
+    publish_job_event(state, job_id, payload).await;
+    Ok(())
+}
+
+/// Reduce an uploaded artifact name to a plain file name.
+fn artifact_file_name(name: &str) -> Option<&str> {
+    let name = name.rsplit(['/', '\\']).next()?.trim();
+    if name.is_empty() || name == "." || name == ".." {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+#[async_trait]
+impl WorkerService for OrchestratorGrpc {
+    async fn handshake(
+        &self,
+        request: Request<proto::HandshakeRequest>,
+    ) -> Result<Response<proto::HandshakeResponse>, Status> {
+        let job_id = self.authorize(request.metadata()).await?;
+        let request = request.into_inner();
+        let Some(version) = negotiate_version(&request.supported_versions) else {
+            return Err(error_status(
+                ErrorCode::UnsupportedVersion,
+                format!(
+                    "no common protocol version (worker offers {:?}, orchestrator speaks {:?})",
+                    request.supported_versions,
+                    crate::worker::grpc::SUPPORTED_VERSIONS
+                ),
+            ));
+        };
+        self.sessions.lock().await.insert(job_id, version);
+
+        tracing::debug!(
+            job_id = %job_id,
+            version,
+            worker = %request.worker_version,
+            "Worker gRPC handshake"
+        );
+        Ok(Response::new(proto::HandshakeResponse {
+            selected_version: version,
+            orchestrator_version: env!("CARGO_PKG_VERSION").to_string(),
+        }))
+    }
+
+    async fn get_job(
+        &self,
+        request: Request<proto::GetJobRequest>,
+    ) -> Result<Response<proto::JobDescription>, Status> {
+        let job_id = self.session(request.metadata()).await?;
+        let handle = self
+            .state
+            .job_manager
+            .get_handle(job_id)
+            .await
+            .ok_or_else(|| error_status(ErrorCode::JobNotFound, "no such job"))?;
+
+        Ok(Response::new(proto::JobDescription {
+            title: format!("Job {}", job_id),
+            description: handle.task_description,
+            project_dir: handle.project_dir.map(|p| p.display().to_string()),
+        }))
+    }
+
+    async fn heartbeat(
+        &self,
+        request: Request<proto::HeartbeatRequest>,
+    ) -> Result<Response<proto::HeartbeatResponse>, Status> {
+        let job_id = self.session(request.metadata()).await?;
+        let beat = request.into_inner();
+        tracing::debug!(
+            job_id = %job_id,
+            state = %beat.state,
+            iteration = beat.iteration,
+            "Worker heartbeat"
+        );
+
+        let job_active = self.state.job_manager.get_handle(job_id).await.is_some();
+        Ok(Response::new(proto::HeartbeatResponse { job_active }))
+    }
+
+    async fn stream_events(
+        &self,
+        request: Request<Streaming<proto::JobEvent>>,
+    ) -> Result<Response<proto::StreamEventsResponse>, Status> {
+        let (metadata, _, mut events) = request.into_parts();
+        let job_id = self.session(&metadata).await?;
+
+        let mut accepted = 0;
+        while let Some(event) = events.message().await? {
+            let data = serde_json::from_str(&event.data_json).map_err(|e| {
+                error_status(
+                    ErrorCode::InvalidRequest,
+                    format!("event data is not JSON: {}", e),
+                )
+            })?;
+            publish_job_event(
+                &self.state,
+                job_id,
+                JobEventPayload {
+                    event_type: event.event_type,
+                    data,
+                },
+            )
+            .await;
+            accepted += 1;
+        }
+        Ok(Response::new(proto::StreamEventsResponse { accepted }))
+    }
+
+    type AttachStream = SessionStream;
+
+    async fn attach(
+        &self,
+        request: Request<Streaming<proto::WorkerMessage>>,
+    ) -> Result<Response<Self::AttachStream>, Status> {
+        let (metadata, _, mut inbound) = request.into_parts();
+        let job_id = self.session(&metadata).await?;
+        let state = self.state.clone();
+        let (tx, rx) = mpsc::channel(16);
+
+        tokio::spawn(async move {
+            let mut accepted = 0u64;
+            let mut prompt_check = tokio::time::interval(PROMPT_CHECK_INTERVAL);
+            loop {
+                tokio::select! {
+                    message = inbound.message() => match message {
+                        Ok(Some(message)) => {
+                            if let Err(status) = handle_worker_message(&state, job_id, message).await {
+                                let _ = tx.send(Err(status)).await;
+                                return;
+                            }
+                            accepted += 1;
+                        }
+                        Ok(None) => {
+                            let _ = tx
+                                .send(Ok(proto::OrchestratorMessage {
+                                    payload: Some(orchestrator_message::Payload::Accepted(accepted)),
+                                }))
+                                .await;
+                            return;
+                        }
+                        Err(status) => {
+                            tracing::debug!(job_id = %job_id, "Worker session ended: {}", status);
+                            return;
+                        }
+                    },
+                    _ = prompt_check.tick() => loop {
+                        // Reserve first so a prompt is only taken off the
+                        // queue when it can be delivered.
+                        let Ok(permit) = tx.reserve().await else {
+                            return;
+                        };
+                        let Some(prompt) = next_prompt(&state, job_id).await else {
+                            break;
+                        };
+                        permit.send(Ok(proto::OrchestratorMessage {
+                            payload: Some(orchestrator_message::Payload::Prompt(proto::Prompt {
+                                content: prompt.content,
+                                done: prompt.done,
+                            })),
+                        }));
+                    },
+                }
+            }
+        });
+
+        tracing::debug!(job_id = %job_id, "Worker attached a gRPC session");
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+
+    async fn upload_artifact(
+        &self,
+        request: Request<Streaming<proto::ArtifactChunk>>,
+    ) -> Result<Response<proto::ArtifactReceipt>, Status> {
+        let (metadata, _, mut chunks) = request.into_parts();
+        let job_id = self.session(&metadata).await?;
+
+        let header = match chunks.message().await? {
+            Some(proto::ArtifactChunk {
+                payload: Some(Payload::Header(header)),
+            }) => header,
+            _ => {
+                return Err(error_status(
+                    ErrorCode::ArtifactRejected,
+                    "upload must start with a header chunk",
+                ));
+            }
+        };
+        let name = artifact_file_name(&header.name)
+            .ok_or_else(|| error_status(ErrorCode::ArtifactRejected, "invalid artifact name"))?;
+        if header.size > MAX_ARTIFACT_BYTES {
+            return Err(error_status(
+                ErrorCode::ArtifactRejected,
+                format!(
+                    "artifact is {} bytes; the limit is {}",
+                    header.size, MAX_ARTIFACT_BYTES
+                ),
+            ));
+        }
+
+        let dir = self.artifacts_dir.join(job_id.to_string());
+        let path = dir.join(name);
+        let io_error =
+            |e: std::io::Error| Status::internal(format!("failed to store artifact: {e}"));
+        tokio::fs::create_dir_all(&dir).await.map_err(io_error)?;
+        let mut file = tokio::fs::File::create(&path).await.map_err(io_error)?;
+
+        let mut hasher = blake3::Hasher::new();
+        let mut size = 0u64;
+        let result: Result<(), Status> = async {
+            while let Some(chunk) = chunks.message().await? {
+                let Some(Payload::Data(data)) = chunk.payload else {
+                    return Err(error_status(
+                        ErrorCode::ArtifactRejected,
+                        "unexpected header after data",
+                    ));
+                };
+                size += data.len() as u64;
+                if size > header.size {
+                    return Err(error_status(
+                        ErrorCode::ArtifactRejected,
+                        "more data than the header announced",
+                    ));
+                }
+                hasher.update(&data);
+                file.write_all(&data).await.map_err(io_error)?;
+            }
+            if size != header.size {
+                return Err(error_status(
+                    ErrorCode::ArtifactRejected,
+                    format!("expected {} bytes, received {}", header.size, size),
+                ));
+            }
+            file.flush().await.map_err(io_error)
+        }
+        .await;
+        if let Err(status) = result {
+            let _ = tokio::fs::remove_file(&path).await;
+            return Err(status);
+        }
+
+        tracing::info!(
+            job_id = %job_id,
+            path = %path.display(),
+            size,
+            content_type = %header.content_type,
+            "Stored worker artifact"
+        );
+        Ok(Response::new(proto::ArtifactReceipt {
+            path: path.display().to_string(),
+            size,
+            blake3: hasher.finalize().to_hex().to_string(),
+        }))
+    }
+
+    async fn llm_complete(
+        &self,
+        request: Request<proto::LlmRequest>,
+    ) -> Result<Response<proto::LlmResponse>, Status> {
+        let job_id = self.session(request.metadata()).await?;
+        let req = serde_json::from_str(&request.into_inner().request_json).map_err(|e| {
+            error_status(ErrorCode::InvalidRequest, format!("bad LLM request: {}", e))
+        })?;
+        let resp = proxy_completion(&self.state, job_id, req)
+            .await
+            .map_err(|e| error_status(ErrorCode::LlmFailed, e.to_string()))?;
+        let response_json = serde_json::to_string(&resp)
+            .map_err(|e| Status::internal(format!("failed to encode LLM response: {e}")))?;
+        Ok(Response::new(proto::LlmResponse { response_json }))
+    }
+
+    async fn llm_complete_with_tools(
+        &self,
+        request: Request<proto::LlmRequest>,
+    ) -> Result<Response<proto::LlmResponse>, Status> {
+        let job_id = self.session(request.metadata()).await?;
+        let req = serde_json::from_str(&request.into_inner().request_json).map_err(|e| {
+            error_status(ErrorCode::InvalidRequest, format!("bad LLM request: {}", e))
+        })?;
+        let resp = proxy_tool_completion(&self.state, job_id, req)
+            .await
+            .map_err(|e| error_status(ErrorCode::LlmFailed, e.to_string()))?;
+        let response_json = serde_json::to_string(&resp)
+            .map_err(|e| Status::internal(format!("failed to encode LLM response: {e}")))?;
+        Ok(Response::new(proto::LlmResponse { response_json }))
+    }
+
+    async fn next_prompt(
+        &self,
+        request: Request<proto::NextPromptRequest>,
+    ) -> Result<Response<proto::NextPromptResponse>, Status> {
+        let job_id = self.session(request.metadata()).await?;
+        let prompt = next_prompt(&self.state, job_id)
+            .await
+            .map(|p| proto::Prompt {
+                content: p.content,
+                done: p.done,
+            });
+        Ok(Response::new(proto::NextPromptResponse { prompt }))
+    }
+
+    async fn complete(
+        &self,
+        request: Request<proto::CompletionReport>,
+    ) -> Result<Response<proto::CompleteResponse>, Status> {
+        let job_id = self.session(request.metadata()).await?;
+        let report = request.into_inner();
+        finish_job(
+            &self.state,
+            job_id,
+            &CompletionReport {
+                success: report.success,
+                message: report.message,
+                iterations: report.iterations,
+            },
+        )
+        .await;
+        self.sessions.lock().await.remove(&job_id);
+        Ok(Response::new(proto::CompleteResponse {}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use tokio::sync::Mutex;
+    use tokio_stream::wrappers::TcpListenerStream;
+
+    use crate::error::WorkerError;
+    use crate::orchestrator::api::PendingPrompt;
+    use crate::orchestrator::auth::TokenStore;
+    use crate::orchestrator::job_manager::{ContainerJobConfig, ContainerJobManager};
+    use crate::worker::grpc::WorkerGrpcClient;
+
+    use super::*;
+
+    struct StubLlm;
+
+    #[async_trait]
+    impl crate::llm::LlmProvider for StubLlm {
+        fn model_name(&self) -> &str {
+            "stub"
+        }
+        fn cost_per_token(&self) -> (rust_decimal::Decimal, rust_decimal::Decimal) {
+            (rust_decimal::Decimal::ZERO, rust_decimal::Decimal::ZERO)
+        }
+        async fn complete(
+            &self,
+            _req: crate::llm::CompletionRequest,
+        ) -> Result<crate::llm::CompletionResponse, crate::error::LlmError> {
+            Ok(crate::llm::CompletionResponse {
+                content: "pong".to_string(),
+                input_tokens: 3,
+                output_tokens: 1,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                finish_reason: crate::llm::FinishReason::Stop,
+                response_id: None,
+            })
+        }
+        async fn complete_with_tools(
+            &self,
+            _req: crate::llm::ToolCompletionRequest,
+        ) -> Result<crate::llm::ToolCompletionResponse, crate::error::LlmError> {
+            Err(crate::error::LlmError::RequestFailed {
+                provider: "stub".into(),
+                reason: "not implemented".into(),
+            })
+        }
+    }
+
+    async fn serve(artifacts_dir: PathBuf) -> (String, OrchestratorState) {
+        let token_store = TokenStore::new();
+        let jm = ContainerJobManager::new(ContainerJobConfig::default(), token_store.clone());
+        let state = OrchestratorState {
+            llm: Arc::new(StubLlm),
+            job_manager: Arc::new(jm),
+            token_store,
+            events: None,
+            prompt_queue: Arc::new(Mutex::new(HashMap::new())),
+            store: None,
+        };
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let service = WorkerServiceServer::new(OrchestratorGrpc::new(state.clone(), artifacts_dir));
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(service)
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        (url, state)
+    }
+
+    #[tokio::test]
+    async fn grpc_session_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let (url, state) = serve(dir.path().to_path_buf()).await;
+        let job_id = Uuid::new_v4();
+        let token = state.token_store.create_token(job_id).await;
+
+        // Wrong token is rejected with a typed error.
+        let err = WorkerGrpcClient::connect(&url, job_id, "bogus")
+            .await
+            .err()
+            .unwrap();
+        assert!(
+            matches!(err, WorkerError::OrchestratorRejected { ref reason, .. } if reason.contains("UNAUTHENTICATED"))
+        );
+
+        let client = WorkerGrpcClient::connect(&url, job_id, &token)
+            .await
+            .unwrap();
+        assert_eq!(client.version(), 1);
+
+        // No container backs this job id.
+        assert!(matches!(
+            client.get_job().await,
+            Err(WorkerError::OrchestratorRejected { .. })
+        ));
+
+        let resp = client
+            .llm_complete(&crate::worker::api::ProxyCompletionRequest {
+                messages: vec![crate::llm::ChatMessage::user("ping")],
+                max_tokens: None,
+                temperature: None,
+                stop_sequences: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(resp.content, "pong");
+        assert_eq!(resp.finish_reason, "stop");
+
+        for i in 0..3 {
+            client
+                .post_event(&JobEventPayload {
+                    event_type: "status".to_string(),
+                    data: serde_json::json!({"message": format!("step {}", i)}),
+                })
+                .await;
+        }
+        assert_eq!(client.flush_events().await.unwrap(), 3);
+
+        let data = vec![7u8; 150 * 1024];
+        let receipt = client
+            .upload_artifact("../report.bin", "application/octet-stream", data.clone())
+            .await
+            .unwrap();
+        assert_eq!(receipt.size, data.len() as u64);
+        assert_eq!(receipt.blake3, blake3::hash(&data).to_hex().to_string());
+        let stored = dir.path().join(job_id.to_string()).join("report.bin");
+        assert_eq!(std::fs::read(stored).unwrap(), data);
+
+        assert!(client.next_prompt().await.unwrap().is_none());
+        state
+            .prompt_queue
+            .lock()
+            .await
+            .entry(job_id)
+            .or_default()
+            .push_back(PendingPrompt {
+                content: "keep going".to_string(),
+                done: false,
+            });
+        let prompt = client.next_prompt().await.unwrap().unwrap();
+        assert_eq!(prompt.content, "keep going");
+    }
+
+    #[tokio::test]
+    async fn grpc_attach_session_pushes_prompts() {
+        let dir = tempfile::tempdir().unwrap();
+        let (url, state) = serve(dir.path().to_path_buf()).await;
+        let job_id = Uuid::new_v4();
+        let token = state.token_store.create_token(job_id).await;
+
+        let client = WorkerGrpcClient::connect(&url, job_id, &token)
+            .await
+            .unwrap();
+        assert!(client.attach().await.unwrap());
+        assert!(client.is_attached().await);
+
+        client
+            .post_event(&JobEventPayload {
+                event_type: "message".to_string(),
+                data: serde_json::json!({"role": "assistant", "content": "working"}),
+            })
+            .await;
+        client.post_log("info", "compiling").await;
+
+        // Nothing queued yet: the wait times out without a prompt.
+        assert!(
+            client
+                .wait_prompt(Duration::from_millis(50))
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // A queued prompt is pushed without the worker asking for it.
+        state
+            .prompt_queue
+            .lock()
+            .await
+            .entry(job_id)
+            .or_default()
+            .push_back(PendingPrompt {
+                content: "add tests".to_string(),
+                done: false,
+            });
+        let prompt = client
+            .wait_prompt(Duration::from_secs(5))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(prompt.content, "add tests");
+        assert!(state.prompt_queue.lock().await[&job_id].is_empty());
+
+        assert_eq!(client.flush_events().await.unwrap(), 2);
+        assert!(!client.is_attached().await);
+    }
+
+    #[test]
+    fn test_artifact_file_name() {
+        assert_eq!(artifact_file_name("report.pdf"), Some("report.pdf"));
+        assert_eq!(artifact_file_name("../../etc/passwd"), Some("passwd"));
+        assert_eq!(artifact_file_name("dir\\out.txt"), Some("out.txt"));
+        assert_eq!(artifact_file_name("logs/"), None);
+        assert_eq!(artifact_file_name(".."), None);
+    }
+}
@@ -50,6 +50,8 @@ pub struct ContainerJobConfig {
     pub cpu_shares: u32,
     /// Port the orchestrator internal API listens on.
     pub orchestrator_port: u16,
+    /// Port of the orchestrator gRPC API; `None` keeps workers on HTTP.
+    pub orchestrator_grpc_port: Option<u16>,
     /// Host directory containing Claude auth config (mounted read-only for ClaudeCode mode).
     pub claude_config_dir: Option<PathBuf>,
     /// Claude model to use in ClaudeCode mode.
@@ -60,6 +62,14 @@ pub struct ContainerJobConfig {
     pub claude_code_memory_limit_mb: u64,
     /// Allowed tool patterns for Claude Code (passed as CLAUDE_CODE_ALLOWED_TOOLS env var).
     pub claude_code_allowed_tools: Vec<String>,
+    /// Maximum processes per job container.
+    pub pids_limit: Option<i64>,
+    /// Size cap for a job container's writable layer in megabytes.
+    pub disk_quota_mb: Option<u64>,
+    /// Size of the job container's `/tmp` tmpfs in megabytes (default 512).
+    pub tmpfs_size_mb: Option<u64>,
+    /// GPUs passed through to job containers.
+    pub gpus: crate::sandbox::GpuAccess,
 }
 
 impl Default for ContainerJobConfig {
@@ -69,11 +79,16 @@ impl Default for ContainerJobConfig {
             memory_limit_mb: 2048,
             cpu_shares: 1024,
             orchestrator_port: 50051,
+            orchestrator_grpc_port: Some(50052),
             claude_config_dir: None,
             claude_code_model: "sonnet".to_string(),
             claude_code_max_turns: 50,
             claude_code_memory_limit_mb: 4096,
             claude_code_allowed_tools: crate::config::ClaudeCodeConfig::default().allowed_tools,
+            pids_limit: None,
+            disk_quota_mb: None,
+            tmpfs_size_mb: None,
+            gpus: crate::sandbox::GpuAccess::None,
         }
     }
 }
@@ -85,6 +100,8 @@ pub enum ContainerState {
     Running,
     Stopped,
     Failed,
+    /// Stopped on request before the worker finished.
+    Cancelled,
 }
 
 impl std::fmt::Display for ContainerState {
@@ -93,6 +110,7 @@ impl std::fmt::Display for ContainerState {
             Self::Creating => write!(f, "creating"),
             Self::Running => write!(f, "running"),
             Self::Stopped => write!(f, "stopped"),
+            Self::Cancelled => write!(f, "cancelled"),
             Self::Failed => write!(f, "failed"),
         }
     }
@@ -211,6 +229,12 @@ impl ContainerJobManager {
             format!("IRONCLAW_JOB_ID={}", job_id),
             format!("IRONCLAW_ORCHESTRATOR_URL={}", orchestrator_url),
         ];
+        if let Some(port) = self.config.orchestrator_grpc_port {
+            env_vec.push(format!(
+                "IRONCLAW_ORCHESTRATOR_GRPC_URL=http://{}:{}",
+                orchestrator_host, port
+            ));
+        }
 
         // Build volume mounts (validate project_dir stays within ~/.ironclaw/projects/)
         let mut binds = Vec::new();
@@ -273,15 +297,23 @@ impl ContainerJobManager {
             binds: if binds.is_empty() { None } else { Some(binds) },
             memory: Some((memory_mb * 1024 * 1024) as i64),
             cpu_shares: Some(self.config.cpu_shares as i64),
+            pids_limit: self.config.pids_limit,
+            storage_opt: self.config.disk_quota_mb.map(|mb| {
+                std::collections::HashMap::from([("size".to_string(), format!("{}M", mb))])
+            }),
+            device_requests: crate::sandbox::backend::gpu_device_requests(&self.config.gpus),
             network_mode: Some("bridge".to_string()),
             extra_hosts: Some(vec!["host.docker.internal:host-gateway".to_string()]),
             cap_drop: Some(vec!["ALL".to_string()]),
             cap_add: Some(vec!["CHOWN".to_string()]),
             security_opt: Some(vec!["no-new-privileges:true".to_string()]),
             tmpfs: Some(
-                [("/tmp".to_string(), "size=512M".to_string())]
-                    .into_iter()
-                    .collect(),
+                [(
+                    "/tmp".to_string(),
+                    format!("size={}M", self.config.tmpfs_size_mb.unwrap_or(512)),
+                )]
+                .into_iter()
+                .collect(),
             ),
             ..Default::default()
         };
@@ -383,11 +415,13 @@ impl ContainerJobManager {
                 reason: e.to_string(),
             })?;
 
-        // Stop the container (10 second grace period)
+        // Stop the container: SIGTERM, then SIGKILL after the grace period
         if let Err(e) = docker
             .stop_container(
                 &container_id,
-                Some(bollard::container::StopContainerOptions { t: 10 }),
+                Some(bollard::container::StopContainerOptions {
+                    t: crate::context::CANCEL_GRACE_PERIOD.as_secs() as i64,
+                }),
             )
             .await
         {
@@ -421,6 +455,21 @@ impl ContainerJobManager {
         Ok(())
     }
 
+    /// Cancel a running container job: the container gets SIGTERM and is
+    /// killed after the grace period. The handle is kept (in the `Cancelled`
+    /// state) so a `create_job` call waiting on it can report the cancellation.
+    pub async fn cancel_job(&self, job_id: Uuid, reason: &str) -> Result<(), OrchestratorError> {
+        self.stop_job(job_id).await?;
+        if let Some(handle) = self.containers.write().await.get_mut(&job_id) {
+            handle.state = ContainerState::Cancelled;
+            handle.completion_result = Some(CompletionResult {
+                success: false,
+                message: Some(reason.to_string()),
+            });
+        }
+        Ok(())
+    }
+
     /// Mark a job as complete with a result. The container is stopped but the
     /// handle is kept so `CreateJobTool` can read the completion message.
     pub async fn complete_job(
@@ -509,6 +558,7 @@ mod tests {
     fn test_container_job_config_default() {
         let config = ContainerJobConfig::default();
         assert_eq!(config.orchestrator_port, 50051);
+        assert_eq!(config.orchestrator_grpc_port, Some(50052));
         assert_eq!(config.memory_limit_mb, 2048);
     }
 
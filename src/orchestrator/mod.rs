@@ -2,6 +2,8 @@
 //!
 //! The orchestrator runs in the main agent process and provides:
 //! - An internal HTTP API for worker communication (LLM proxy, status, secrets)
+//! - A versioned gRPC API (`proto/worker.proto`) with streaming job events,
+//!   heartbeats and artifact upload, preferred by workers that negotiate it
 //! - Per-job bearer token authentication
 //! - Container lifecycle management (create, monitor, stop)
 //!
@@ -16,6 +18,10 @@
 //! │    POST /worker/{id}/status                     │
 //! │    POST /worker/{id}/complete                   │
 //! │                                                 │
+//! │  gRPC API (:50052) ironclaw.worker.v1           │
+//! │    Handshake, GetJob, Heartbeat, StreamEvents,  │
+//! │    UploadArtifact, LlmComplete, Complete, ...   │
+//! │                                                 │
 //! │  ContainerJobManager                            │
 //! │    create_job() -> container + token             │
 //! │    stop_job()                                    │
@@ -28,10 +34,12 @@
 
 pub mod api;
 pub mod auth;
+pub mod grpc;
 pub mod job_manager;
 
 pub use api::OrchestratorApi;
 pub use auth::TokenStore;
+pub use grpc::OrchestratorGrpc;
 pub use job_manager::{
     CompletionResult, ContainerHandle, ContainerJobConfig, ContainerJobManager, JobMode,
 };
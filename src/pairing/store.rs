@@ -12,6 +12,8 @@ use fs4::FileExt;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
+use crate::contacts::ContactStore;
+
 const PAIRING_CODE_LENGTH: usize = 8;
 const PAIRING_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
 /// TTL for pending pairing requests (minutes, not hours — reduces brute-force window).
@@ -456,7 +458,30 @@ impl PairingStore {
                 return Ok(true);
             }
         }
-        Ok(false)
+        Ok(self.is_linked_identity_allowed(channel, id))
+    }
+
+    /// Approvals follow the person: a sender is allowed if any identity
+    /// linked to the same contact is approved on its own channel.
+    fn is_linked_identity_allowed(&self, channel: &str, id: &str) -> bool {
+        let contacts = ContactStore::with_base_dir(self.base_dir.clone());
+        let contact = match contacts.resolve(channel, id) {
+            Ok(Some(contact)) => contact,
+            Ok(None) => return false,
+            Err(e) => {
+                tracing::warn!("Failed to read contacts for pairing check: {}", e);
+                return false;
+            }
+        };
+        contact
+            .identities
+            .iter()
+            .filter(|i| !(i.channel.eq_ignore_ascii_case(channel) && i.id == id))
+            .any(|i| {
+                self.read_allow_from(&i.channel)
+                    .map(|allow| allow.iter().any(|e| e.trim() == i.id))
+                    .unwrap_or(false)
+            })
     }
 
     fn add_allow_from(&self, channel: &str, entry: &str) -> Result<(), PairingStoreError> {
@@ -694,6 +719,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_is_sender_allowed_via_linked_contact() {
+        let (store, dir) = test_store();
+        let r = store.upsert_request("telegram", "555", None).unwrap();
+        store.approve("telegram", &r.code).unwrap();
+        assert!(!store.is_sender_allowed("slack", "U555", None).unwrap());
+
+        let contacts = ContactStore::with_base_dir(dir.path().to_path_buf());
+        contacts
+            .link("erin", crate::contacts::Identity::new("telegram", "555"))
+            .unwrap();
+        contacts
+            .link("erin", crate::contacts::Identity::new("slack", "U555"))
+            .unwrap();
+        assert!(store.is_sender_allowed("slack", "U555", None).unwrap());
+        assert!(!store.is_sender_allowed("slack", "U556", None).unwrap());
+    }
+
     #[test]
     fn test_channel_normalization() {
         let (store, _) = test_store();
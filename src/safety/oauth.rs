@@ -242,7 +242,7 @@ impl OAuthFlowManager {
     /// Create a new OAuth flow manager.
     pub fn new() -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: crate::outbound::client(),
             pending_flows: Arc::new(Mutex::new(HashMap::new())),
             tokens: Arc::new(Mutex::new(HashMap::new())),
         }
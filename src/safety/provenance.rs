@@ -0,0 +1,287 @@
+//! Provenance metadata for content the agent sends out.
+//!
+//! Rules pick destinations by host and say what to attach:
+//! - `header`: `X-Generated-By: IronClaw/<version>` plus `X-Provenance-Job`,
+//!   `X-Provenance-Conversation` and `X-Provenance-Turn` headers
+//! - `watermark`: an HTML comment appended to text fields of a JSON body
+//!   (`<!-- generated-by: IronClaw/<version>; job=...; conversation=...; turn=... -->`),
+//!   invisible once GitHub, email clients and most Markdown renderers show it
+//!
+//! The first rule whose destination matches wins. With `ids: false` only the
+//! generator is named. The IDs map back to the job or chat thread (the
+//! conversation ID) and turn that produced the content.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::context::JobContext;
+
+/// Generator name in headers and watermarks.
+const GENERATOR: &str = concat!("IronClaw/", env!("CARGO_PKG_VERSION"));
+
+/// JSON body fields watermarked when a rule names none: the usual text
+/// fields of issue/PR, chat-webhook and email APIs.
+const DEFAULT_FIELDS: &[&str] = &["body", "text", "content", "description", "message", "html"];
+
+/// What to attach for one destination.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvenanceRule {
+    /// Host to match: exact (`api.github.com`), a `*.example.com` suffix, or `*`.
+    pub destination: String,
+    /// Add the generated-by and ID headers.
+    #[serde(default)]
+    pub header: bool,
+    /// Append a hidden watermark comment to text fields of JSON bodies.
+    #[serde(default)]
+    pub watermark: bool,
+    /// Include job, conversation and turn IDs.
+    #[serde(default = "default_true")]
+    pub ids: bool,
+    /// Top-level JSON fields to watermark (default: [`DEFAULT_FIELDS`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl ProvenanceRule {
+    /// Check the rule is well formed.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.destination.trim().is_empty() {
+            return Err("destination must not be empty".to_string());
+        }
+        if !self.header && !self.watermark {
+            return Err(format!(
+                "rule for '{}' enables neither header nor watermark",
+                self.destination
+            ));
+        }
+        Ok(())
+    }
+
+    fn matches(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        let destination = self.destination.to_ascii_lowercase();
+        match destination.strip_prefix("*.") {
+            Some(suffix) => host
+                .strip_suffix(suffix)
+                .is_some_and(|rest| rest.ends_with('.')),
+            None => destination == "*" || destination == host,
+        }
+    }
+}
+
+/// Where a piece of outbound content came from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProvenanceStamp {
+    pub job_id: Option<Uuid>,
+    /// Chat thread / conversation.
+    pub conversation_id: Option<Uuid>,
+    pub turn: Option<u64>,
+}
+
+impl ProvenanceStamp {
+    /// Stamp for content produced while running `ctx`. Chat turns carry the
+    /// turn number in `metadata.turn`.
+    pub fn from_job(ctx: &JobContext) -> Self {
+        Self {
+            job_id: Some(ctx.job_id),
+            conversation_id: ctx.conversation_id,
+            turn: ctx.metadata.get("turn").and_then(|v| v.as_u64()),
+        }
+    }
+
+    /// Stamp from an event payload's `job_id`, `thread_id` and `turn` fields.
+    pub fn from_event(data: &serde_json::Value) -> Self {
+        let uuid = |key: &str| {
+            data.get(key)
+                .and_then(|v| v.as_str())
+                .and_then(|s| Uuid::parse_str(s).ok())
+        };
+        Self {
+            job_id: uuid("job_id"),
+            conversation_id: uuid("thread_id"),
+            turn: data.get("turn").and_then(|v| v.as_u64()),
+        }
+    }
+
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = Vec::new();
+        if let Some(id) = self.job_id {
+            fields.push(("job", id.to_string()));
+        }
+        if let Some(id) = self.conversation_id {
+            fields.push(("conversation", id.to_string()));
+        }
+        if let Some(turn) = self.turn {
+            fields.push(("turn", turn.to_string()));
+        }
+        fields
+    }
+}
+
+/// Provenance rules for outbound requests.
+#[derive(Debug, Clone, Default)]
+pub struct Provenance {
+    rules: Vec<ProvenanceRule>,
+}
+
+impl Provenance {
+    pub fn new(rules: Vec<ProvenanceRule>) -> Self {
+        Self { rules }
+    }
+
+    /// The rule for requests to `host`, if any.
+    pub fn rule_for(&self, host: &str) -> Option<&ProvenanceRule> {
+        self.rules.iter().find(|r| r.matches(host))
+    }
+
+    /// Headers to add under `rule` (empty unless `rule.header`).
+    pub fn headers(rule: &ProvenanceRule, stamp: &ProvenanceStamp) -> Vec<(String, String)> {
+        if !rule.header {
+            return Vec::new();
+        }
+        let mut headers = vec![("X-Generated-By".to_string(), GENERATOR.to_string())];
+        if rule.ids {
+            for (name, value) in stamp.fields() {
+                let name = format!("X-Provenance-{}{}", name[..1].to_uppercase(), &name[1..]);
+                headers.push((name, value));
+            }
+        }
+        headers
+    }
+
+    /// The hidden comment appended to watermarked text.
+    pub fn watermark(rule: &ProvenanceRule, stamp: &ProvenanceStamp) -> String {
+        let mut parts = vec![format!("generated-by: {}", GENERATOR)];
+        if rule.ids {
+            parts.extend(
+                stamp
+                    .fields()
+                    .into_iter()
+                    .map(|(name, value)| format!("{}={}", name, value)),
+            );
+        }
+        format!("<!-- {} -->", parts.join("; "))
+    }
+
+    /// Append the watermark to the rule's text fields of a JSON object body.
+    /// Returns the number of fields stamped (0 unless `rule.watermark`).
+    pub fn stamp_body(
+        rule: &ProvenanceRule,
+        stamp: &ProvenanceStamp,
+        body: &mut serde_json::Value,
+    ) -> usize {
+        if !rule.watermark {
+            return 0;
+        }
+        let Some(object) = body.as_object_mut() else {
+            return 0;
+        };
+        let watermark = Self::watermark(rule, stamp);
+        let mut stamped = 0;
+        for (key, value) in object.iter_mut() {
+            let selected = if rule.fields.is_empty() {
+                DEFAULT_FIELDS.contains(&key.as_str())
+            } else {
+                rule.fields.iter().any(|f| f == key)
+            };
+            if selected && let serde_json::Value::String(text) = value {
+                text.push_str("\n\n");
+                text.push_str(&watermark);
+                stamped += 1;
+            }
+        }
+        stamped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(destination: &str) -> ProvenanceRule {
+        ProvenanceRule {
+            destination: destination.to_string(),
+            header: true,
+            watermark: true,
+            ids: true,
+            fields: Vec::new(),
+        }
+    }
+
+    fn stamp() -> ProvenanceStamp {
+        ProvenanceStamp {
+            job_id: Some(Uuid::nil()),
+            conversation_id: None,
+            turn: Some(3),
+        }
+    }
+
+    #[test]
+    fn test_destination_matching() {
+        let provenance = Provenance::new(vec![rule("api.github.com"), rule("*.slack.com")]);
+        assert!(provenance.rule_for("API.github.com").is_some());
+        assert!(provenance.rule_for("hooks.slack.com").is_some());
+        assert!(provenance.rule_for("slack.com").is_none());
+        assert!(provenance.rule_for("evilslack.com").is_none());
+        assert!(Provenance::new(vec![rule("*")]).rule_for("x.org").is_some());
+    }
+
+    #[test]
+    fn test_headers_carry_ids_unless_disabled() {
+        let mut r = rule("*");
+        let headers = Provenance::headers(&r, &stamp());
+        assert_eq!(
+            headers[0],
+            ("X-Generated-By".to_string(), GENERATOR.to_string())
+        );
+        assert!(headers.contains(&("X-Provenance-Job".to_string(), Uuid::nil().to_string())));
+        assert!(headers.contains(&("X-Provenance-Turn".to_string(), "3".to_string())));
+
+        r.ids = false;
+        assert_eq!(Provenance::headers(&r, &stamp()).len(), 1);
+        r.header = false;
+        assert!(Provenance::headers(&r, &stamp()).is_empty());
+    }
+
+    #[test]
+    fn test_stamp_body_appends_watermark_to_text_fields() {
+        let mut body = serde_json::json!({"title": "Fix", "body": "Details", "draft": false});
+        assert_eq!(Provenance::stamp_body(&rule("*"), &stamp(), &mut body), 1);
+        assert_eq!(body["title"], "Fix");
+        let text = body["body"].as_str().unwrap();
+        assert!(text.starts_with("Details\n\n<!-- generated-by: IronClaw/"));
+        assert!(text.ends_with(&format!("job={}; turn=3 -->", Uuid::nil())));
+
+        let mut r = rule("*");
+        r.fields = vec!["title".to_string()];
+        let mut body = serde_json::json!({"title": "Fix", "body": "Details"});
+        Provenance::stamp_body(&r, &stamp(), &mut body);
+        assert_eq!(body["body"], "Details");
+        assert_ne!(body["title"], "Fix");
+    }
+
+    #[test]
+    fn test_stamp_from_event_payload() {
+        let id = Uuid::new_v4();
+        let stamp = ProvenanceStamp::from_event(&serde_json::json!({
+            "type": "turn_finished",
+            "thread_id": id.to_string(),
+        }));
+        assert_eq!(stamp.conversation_id, Some(id));
+        assert_eq!(stamp.job_id, None);
+    }
+
+    #[test]
+    fn test_rule_validation() {
+        let mut r = rule("api.github.com");
+        assert!(r.validate().is_ok());
+        r.header = false;
+        r.watermark = false;
+        assert!(r.validate().is_err());
+        assert!(rule(" ").validate().is_err());
+    }
+}
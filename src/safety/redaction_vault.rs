@@ -0,0 +1,429 @@
+//! Encrypted side-store for tool outputs that leak detection redacted.
+//!
+//! When a tool output is redacted (or blocked) before it reaches the LLM,
+//! the original is encrypted with the secrets master key and kept here so
+//! "why did the tool fail?" can still be answered. Only an entry id goes
+//! into the transcript.
+//!
+//! Each entry belongs to the user whose tool call produced it; listing and
+//! reading only see the asking user's entries. Reading an entry back also
+//! requires elevated mode for the asking session, and every attempt,
+//! granted or denied, is appended to the audit log.
+//!
+//! ```text
+//! ~/.ironclaw/redacted/
+//!   <id>.json     tool, patterns, salt, AES-256-GCM ciphertext
+//!   audit.jsonl   one line per reveal attempt
+//! ```
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::safety::elevated::ElevatedMode;
+use crate::secrets::{SecretError, SecretsCrypto};
+
+/// File the reveal attempts are appended to.
+const AUDIT_FILE: &str = "audit.jsonl";
+
+/// Error from vault operations.
+#[derive(Debug, thiserror::Error)]
+pub enum VaultError {
+    #[error("No preserved output matches '{0}'")]
+    NotFound(String),
+
+    #[error("'{0}' matches more than one preserved output")]
+    Ambiguous(String),
+
+    #[error("Elevated mode is required to read preserved outputs")]
+    ElevationRequired,
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Crypto error: {0}")]
+    Crypto(#[from] SecretError),
+}
+
+/// Metadata of a preserved output; the content stays encrypted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultEntry {
+    pub id: Uuid,
+    /// User whose tool call produced the output. Entries written before
+    /// owners were recorded have none and are visible to nobody.
+    #[serde(default)]
+    pub owner: String,
+    pub tool_name: String,
+    /// Leak patterns that triggered the redaction.
+    pub patterns: Vec<String>,
+    /// Whether the output was blocked rather than redacted.
+    #[serde(default)]
+    pub blocked: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl VaultEntry {
+    /// Short id used in transcripts and by `/redacted show`.
+    pub fn short_id(&self) -> String {
+        self.id.simple().to_string()[..8].to_string()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredEntry {
+    #[serde(flatten)]
+    entry: VaultEntry,
+    salt: String,
+    ciphertext: String,
+}
+
+/// One reveal attempt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub at: DateTime<Utc>,
+    pub user_id: String,
+    pub session_id: String,
+    /// Id as requested.
+    pub entry: String,
+    pub granted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Who is asking to read a preserved output.
+pub struct VaultAccess<'a> {
+    pub user_id: &'a str,
+    pub session_id: &'a str,
+    pub elevated: &'a ElevatedMode,
+}
+
+/// Encrypted store of unredacted tool outputs.
+pub struct RedactionVault {
+    dir: PathBuf,
+    crypto: Arc<SecretsCrypto>,
+    retention: Duration,
+}
+
+impl RedactionVault {
+    /// Open (or create) a vault in `dir`; entries older than
+    /// `retention_days` are dropped by [`prune`](Self::prune).
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        crypto: Arc<SecretsCrypto>,
+        retention_days: u32,
+    ) -> Result<Self, VaultError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        restrict_permissions(&dir, 0o700)?;
+        Ok(Self {
+            dir,
+            crypto,
+            retention: Duration::days(i64::from(retention_days.max(1))),
+        })
+    }
+
+    /// Default location: `~/.ironclaw/redacted`.
+    pub fn default_dir() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".ironclaw")
+            .join("redacted")
+    }
+
+    /// Encrypt and keep `original` for `owner`, returning its entry.
+    pub fn preserve(
+        &self,
+        owner: &str,
+        tool_name: &str,
+        original: &str,
+        patterns: Vec<String>,
+        blocked: bool,
+    ) -> Result<VaultEntry, VaultError> {
+        let (ciphertext, salt) = self.crypto.encrypt(original.as_bytes())?;
+        let entry = VaultEntry {
+            id: Uuid::new_v4(),
+            owner: owner.to_string(),
+            tool_name: tool_name.to_string(),
+            patterns,
+            blocked,
+            created_at: Utc::now(),
+        };
+        let stored = StoredEntry {
+            entry: entry.clone(),
+            salt: hex::encode(salt),
+            ciphertext: hex::encode(ciphertext),
+        };
+        let path = self.entry_path(entry.id);
+        fs::write(&path, serde_json::to_vec(&stored)?)?;
+        restrict_permissions(&path, 0o600)?;
+        Ok(entry)
+    }
+
+    /// `owner`'s entries, newest first.
+    pub fn list(&self, owner: &str) -> Result<Vec<VaultEntry>, VaultError> {
+        let mut entries: Vec<VaultEntry> = self
+            .stored_entries()?
+            .into_iter()
+            .map(|(_, stored)| stored.entry)
+            .filter(|entry| entry.owner == owner)
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+        Ok(entries)
+    }
+
+    /// Decrypt the asking user's entry whose id starts with `id`.
+    ///
+    /// Requires elevated mode for the asking session. Other users' entries
+    /// are reported as not found. The attempt is audited whether or not it
+    /// succeeds.
+    pub fn reveal(
+        &self,
+        id: &str,
+        access: &VaultAccess<'_>,
+    ) -> Result<(VaultEntry, String), VaultError> {
+        let result = self.reveal_unaudited(id, access);
+        let record = AuditRecord {
+            at: Utc::now(),
+            user_id: access.user_id.to_string(),
+            session_id: access.session_id.to_string(),
+            entry: id.to_string(),
+            granted: result.is_ok(),
+            reason: result.as_ref().err().map(|e| e.to_string()),
+        };
+        if let Err(e) = self.append_audit(&record) {
+            // An unauditable read is not allowed to go through.
+            tracing::error!("Failed to write redaction vault audit log: {}", e);
+            return Err(e);
+        }
+        tracing::warn!(
+            user = access.user_id,
+            session = access.session_id,
+            entry = id,
+            granted = record.granted,
+            "Redaction vault read"
+        );
+        result
+    }
+
+    fn reveal_unaudited(
+        &self,
+        id: &str,
+        access: &VaultAccess<'_>,
+    ) -> Result<(VaultEntry, String), VaultError> {
+        if !access.elevated.is_active_for_session(access.session_id) {
+            return Err(VaultError::ElevationRequired);
+        }
+        let stored = self.find(access.user_id, id)?;
+        let salt = hex::decode(&stored.salt).map_err(|e| {
+            SecretError::DecryptionFailed(format!("Corrupt salt in vault entry: {}", e))
+        })?;
+        let ciphertext = hex::decode(&stored.ciphertext)
+            .map_err(|e| SecretError::DecryptionFailed(format!("Corrupt vault entry: {}", e)))?;
+        let plaintext = self.crypto.decrypt(&ciphertext, &salt)?;
+        Ok((stored.entry, plaintext.expose().to_string()))
+    }
+
+    /// Reveal attempts, oldest first.
+    pub fn audit_log(&self) -> Result<Vec<AuditRecord>, VaultError> {
+        let path = self.dir.join(AUDIT_FILE);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(fs::read_to_string(path)?
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Delete entries past the retention period; returns how many went.
+    pub fn prune(&self) -> Result<usize, VaultError> {
+        let cutoff = Utc::now() - self.retention;
+        let mut removed = 0;
+        for (path, stored) in self.stored_entries()? {
+            if stored.entry.created_at < cutoff {
+                fs::remove_file(path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn find(&self, owner: &str, id: &str) -> Result<StoredEntry, VaultError> {
+        let prefix = id.trim().to_ascii_lowercase();
+        if prefix.is_empty() {
+            return Err(VaultError::NotFound(id.to_string()));
+        }
+        let mut matching = self
+            .stored_entries()?
+            .into_iter()
+            .filter(|(_, s)| s.entry.owner == owner)
+            .filter(|(_, s)| s.entry.id.simple().to_string().starts_with(&prefix));
+        let (_, stored) = matching
+            .next()
+            .ok_or_else(|| VaultError::NotFound(id.to_string()))?;
+        if matching.next().is_some() {
+            return Err(VaultError::Ambiguous(id.to_string()));
+        }
+        Ok(stored)
+    }
+
+    fn stored_entries(&self) -> Result<Vec<(PathBuf, StoredEntry)>, VaultError> {
+        let mut entries = Vec::new();
+        for dir_entry in fs::read_dir(&self.dir)? {
+            let path = dir_entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            match fs::read(&path)
+                .map_err(VaultError::from)
+                .and_then(|bytes| Ok(serde_json::from_slice::<StoredEntry>(&bytes)?))
+            {
+                Ok(stored) => entries.push((path, stored)),
+                Err(e) => tracing::warn!("Skipping unreadable vault entry {:?}: {}", path, e),
+            }
+        }
+        Ok(entries)
+    }
+
+    fn entry_path(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.json", id.simple()))
+    }
+
+    fn append_audit(&self, record: &AuditRecord) -> Result<(), VaultError> {
+        let path = self.dir.join(AUDIT_FILE);
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        restrict_permissions(&path, 0o600)?;
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &Path, mode: u32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path, _mode: u32) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::SecretString;
+
+    use super::*;
+
+    fn vault(dir: &Path) -> RedactionVault {
+        let crypto = SecretsCrypto::new(SecretString::from(
+            "0123456789abcdef0123456789abcdef".to_string(),
+        ))
+        .unwrap();
+        RedactionVault::new(dir, Arc::new(crypto), 7).unwrap()
+    }
+
+    #[test]
+    fn test_reveal_requires_elevation_and_is_audited() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = vault(dir.path());
+        let original = "auth failed for key sk-live-abcdef";
+        let entry = vault
+            .preserve("alice", "http", original, vec!["stripe_key".into()], false)
+            .unwrap();
+
+        // Nothing readable on disk.
+        let raw = fs::read_to_string(vault.entry_path(entry.id)).unwrap();
+        assert!(!raw.contains("sk-live"));
+        assert_eq!(vault.list("alice").unwrap()[0].patterns, vec!["stripe_key"]);
+
+        let mut elevated = ElevatedMode::new();
+        let access =
+            |elevated: &ElevatedMode, session: &'static str| -> Result<String, VaultError> {
+                vault
+                    .reveal(
+                        &entry.short_id(),
+                        &VaultAccess {
+                            user_id: "alice",
+                            session_id: session,
+                            elevated,
+                        },
+                    )
+                    .map(|(_, content)| content)
+            };
+        assert!(matches!(
+            access(&elevated, "s1"),
+            Err(VaultError::ElevationRequired)
+        ));
+        elevated.activate("alice", "s1");
+        assert!(matches!(
+            access(&elevated, "s2"),
+            Err(VaultError::ElevationRequired)
+        ));
+        assert_eq!(access(&elevated, "s1").unwrap(), original);
+
+        let audit = vault.audit_log().unwrap();
+        assert_eq!(
+            audit.iter().map(|r| r.granted).collect::<Vec<_>>(),
+            vec![false, false, true]
+        );
+        assert_eq!(audit[0].session_id, "s1");
+    }
+
+    #[test]
+    fn test_prune_drops_expired_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = vault(dir.path());
+        let entry = vault
+            .preserve("alice", "shell", "x", Vec::new(), true)
+            .unwrap();
+        assert_eq!(vault.prune().unwrap(), 0);
+
+        // Age the entry past the retention period.
+        let path = vault.entry_path(entry.id);
+        let mut stored: StoredEntry = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        stored.entry.created_at = Utc::now() - Duration::days(8);
+        fs::write(&path, serde_json::to_vec(&stored).unwrap()).unwrap();
+
+        assert_eq!(vault.prune().unwrap(), 1);
+        assert!(vault.list("alice").unwrap().is_empty());
+        assert!(matches!(
+            vault.find("alice", &entry.short_id()),
+            Err(VaultError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_entries_are_private_to_their_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = vault(dir.path());
+        let entry = vault
+            .preserve("alice", "http", "token sk-live-abcdef", Vec::new(), false)
+            .unwrap();
+        assert_eq!(vault.list("alice").unwrap().len(), 1);
+        assert!(vault.list("bob").unwrap().is_empty());
+
+        let mut elevated = ElevatedMode::new();
+        elevated.activate("bob", "s1");
+        let access = VaultAccess {
+            user_id: "bob",
+            session_id: "s1",
+            elevated: &elevated,
+        };
+        assert!(matches!(
+            vault.reveal(&entry.short_id(), &access),
+            Err(VaultError::NotFound(_))
+        ));
+        assert!(!vault.audit_log().unwrap()[0].granted);
+    }
+}
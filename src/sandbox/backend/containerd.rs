@@ -0,0 +1,421 @@
+//! containerd backend, driven through the `nerdctl` CLI.
+//!
+//! containerd has no Docker-style HTTP API; `nerdctl` accepts the same
+//! run flags as `docker`, sets up CNI networking and talks to the
+//! containerd socket itself (rootless when `containerd-rootless-setuptool.sh`
+//! has been run).
+
+use std::process::Stdio;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+
+use crate::sandbox::backend::{
+    AttachedProcess, ContainerBackend, ContainerSpec, OutputChunk, push_capped,
+};
+use crate::sandbox::config::{ContainerBackendKind, GpuAccess, ResourceLimits};
+use crate::sandbox::container::ContainerOutput;
+use crate::sandbox::error::{Result, SandboxError};
+
+/// Force-removes a container if dropped while still armed (the command's
+/// future was cancelled or timed out). Killing `nerdctl run` alone would
+/// leave the container running.
+struct NerdctlGuard {
+    binary: String,
+    name: Option<String>,
+}
+
+impl NerdctlGuard {
+    fn disarm(&mut self) {
+        self.name = None;
+    }
+}
+
+impl Drop for NerdctlGuard {
+    fn drop(&mut self) {
+        let Some(name) = self.name.take() else {
+            return;
+        };
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let binary = self.binary.clone();
+        handle.spawn(async move {
+            tracing::debug!(container = %name, "Removing cancelled sandbox container");
+            let _ = Command::new(binary)
+                .args(["rm", "--force", &name])
+                .output()
+                .await;
+        });
+    }
+}
+
+/// Runs sandbox containers with `nerdctl`.
+pub struct NerdctlBackend {
+    binary: String,
+}
+
+impl Default for NerdctlBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NerdctlBackend {
+    /// Use `nerdctl` from `PATH`. The containerd namespace comes from
+    /// `CONTAINERD_NAMESPACE`, as for any nerdctl invocation.
+    pub fn new() -> Self {
+        Self {
+            binary: "nerdctl".to_string(),
+        }
+    }
+
+    async fn nerdctl(&self, args: &[&str]) -> Result<std::process::Output> {
+        Command::new(&self.binary)
+            .args(args)
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| SandboxError::BackendNotAvailable {
+                backend: "containerd".to_string(),
+                reason: format!("failed to run {}: {}", self.binary, e),
+            })
+    }
+
+    /// Run `cmd`, capping each of stdout and stderr at half of `max_output`.
+    async fn collect(&self, mut cmd: Command, limits: &ResourceLimits) -> Result<ContainerOutput> {
+        cmd.kill_on_drop(true);
+        let output = tokio::time::timeout(limits.timeout, cmd.output())
+            .await
+            .map_err(|_| SandboxError::Timeout(limits.timeout))?
+            .map_err(|e| SandboxError::ExecutionFailed {
+                reason: format!("{} failed: {}", self.binary, e),
+            })?;
+
+        let half_max = limits.max_output_bytes / 2;
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+        let truncated = push_capped(
+            &mut stdout,
+            &String::from_utf8_lossy(&output.stdout),
+            half_max,
+        ) | push_capped(
+            &mut stderr,
+            &String::from_utf8_lossy(&output.stderr),
+            half_max,
+        );
+
+        Ok(ContainerOutput {
+            exit_code: output.status.code().unwrap_or(-1) as i64,
+            stdout,
+            stderr,
+            duration: Duration::ZERO,
+            truncated,
+        })
+    }
+}
+
+/// `nerdctl run` arguments for `spec`; `detach` leaves the container
+/// running in the background.
+fn run_args(spec: &ContainerSpec, limits: &ResourceLimits, detach: bool) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "run",
+        if detach { "--detach" } else { "--rm" },
+        "--name",
+        &spec.name,
+        "--network",
+        "bridge",
+        "--memory",
+        &limits.memory_bytes.to_string(),
+        "--cpu-shares",
+        &limits.cpu_shares.to_string(),
+        "--cap-drop",
+        "ALL",
+        "--cap-add",
+        "CHOWN",
+        "--security-opt",
+        "no-new-privileges",
+        "--user",
+        &spec.user,
+        "--workdir",
+        &spec.working_dir,
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+
+    if let Some(pids) = limits.pids_limit {
+        args.push("--pids-limit".to_string());
+        args.push(pids.to_string());
+    }
+    if let Some(bytes) = limits.disk_quota_bytes {
+        args.push("--storage-opt".to_string());
+        args.push(format!("size={}", bytes));
+    }
+    match &limits.gpus {
+        GpuAccess::None => {}
+        GpuAccess::All => args.extend(["--gpus".to_string(), "all".to_string()]),
+        GpuAccess::Devices(ids) => {
+            args.push("--gpus".to_string());
+            args.push(format!("\"device={}\"", ids.join(",")));
+        }
+    }
+    if spec.read_only_rootfs {
+        args.push("--read-only".to_string());
+    }
+    for (path, options) in limits.tmpfs_mounts(&spec.tmpfs) {
+        args.push("--tmpfs".to_string());
+        args.push(format!("{}:{}", path, options));
+    }
+    for bind in &spec.binds {
+        args.push("--volume".to_string());
+        args.push(format!(
+            "{}:{}:{}",
+            bind.host.display(),
+            bind.container,
+            if bind.read_only { "ro" } else { "rw" }
+        ));
+    }
+    for (key, value) in &spec.env {
+        args.push("--env".to_string());
+        args.push(format!("{}={}", key, value));
+    }
+    args.extend([
+        spec.image.clone(),
+        "sh".to_string(),
+        "-c".to_string(),
+        spec.command.clone(),
+    ]);
+    args
+}
+
+#[async_trait]
+impl ContainerBackend for NerdctlBackend {
+    fn kind(&self) -> ContainerBackendKind {
+        ContainerBackendKind::Containerd
+    }
+
+    fn host_gateway(&self) -> &str {
+        // Gateway of nerdctl's default CNI bridge.
+        "10.4.0.1"
+    }
+
+    async fn ping(&self) -> Result<()> {
+        let output = self.nerdctl(&["info"]).await?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(SandboxError::BackendNotAvailable {
+                backend: "containerd".to_string(),
+                reason: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            })
+        }
+    }
+
+    async fn image_exists(&self, image: &str) -> bool {
+        self.nerdctl(&["image", "inspect", image])
+            .await
+            .is_ok_and(|o| o.status.success())
+    }
+
+    async fn pull_image(&self, image: &str) -> Result<()> {
+        let output = self.nerdctl(&["pull", image]).await?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(SandboxError::ContainerCreationFailed {
+                reason: format!(
+                    "image pull failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            })
+        }
+    }
+
+    async fn run(&self, spec: &ContainerSpec, limits: &ResourceLimits) -> Result<ContainerOutput> {
+        let mut guard = NerdctlGuard {
+            binary: self.binary.clone(),
+            name: Some(spec.name.clone()),
+        };
+        let mut cmd = Command::new(&self.binary);
+        cmd.args(run_args(spec, limits, false));
+        let result = self.collect(cmd, limits).await;
+        // `--rm` removed the container if it ran to completion.
+        if result.is_ok() {
+            guard.disarm();
+        }
+        result
+    }
+
+    async fn start(&self, spec: &ContainerSpec, limits: &ResourceLimits) -> Result<String> {
+        let args = run_args(spec, limits, true);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let output = self.nerdctl(&args).await?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            self.remove(&spec.name).await;
+            Err(SandboxError::ContainerStartFailed {
+                reason: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            })
+        }
+    }
+
+    async fn remove(&self, container_id: &str) {
+        let _ = self.nerdctl(&["rm", "--force", container_id]).await;
+    }
+
+    async fn exec(
+        &self,
+        container_id: &str,
+        command: &str,
+        working_dir: &str,
+        env: &[(String, String)],
+        limits: &ResourceLimits,
+    ) -> Result<ContainerOutput> {
+        let mut cmd = Command::new(&self.binary);
+        cmd.args(["exec", "--workdir", working_dir]);
+        for (key, value) in env {
+            cmd.arg("--env").arg(format!("{}={}", key, value));
+        }
+        cmd.args([container_id, "sh", "-c", command]);
+        self.collect(cmd, limits).await
+    }
+
+    async fn attach(
+        &self,
+        container_id: &str,
+        command: &str,
+        working_dir: &str,
+        env: &[(String, String)],
+    ) -> Result<AttachedProcess> {
+        let mut cmd = Command::new(&self.binary);
+        cmd.args(["exec", "--interactive", "--workdir", working_dir]);
+        for (key, value) in env {
+            cmd.arg("--env").arg(format!("{}={}", key, value));
+        }
+        cmd.args([container_id, "sh", "-c", command]);
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| SandboxError::ExecutionFailed {
+                reason: format!("{} failed: {}", self.binary, e),
+            })?;
+        let (Some(stdin), Some(mut stdout), Some(mut stderr)) =
+            (child.stdin.take(), child.stdout.take(), child.stderr.take())
+        else {
+            return Err(SandboxError::ExecutionFailed {
+                reason: "attached process has no stdio".to_string(),
+            });
+        };
+
+        // The task owns the child, so it is killed once the reader goes away.
+        let (tx, rx) = tokio::sync::mpsc::channel(64);
+        tokio::spawn(async move {
+            let mut out_buf = vec![0u8; 8192];
+            let mut err_buf = vec![0u8; 8192];
+            let (mut out_open, mut err_open) = (true, true);
+            while out_open || err_open {
+                let chunk = tokio::select! {
+                    n = stdout.read(&mut out_buf), if out_open => match n {
+                        Ok(n) if n > 0 => Some(OutputChunk::Stdout(out_buf[..n].to_vec())),
+                        _ => { out_open = false; None }
+                    },
+                    n = stderr.read(&mut err_buf), if err_open => match n {
+                        Ok(n) if n > 0 => Some(OutputChunk::Stderr(err_buf[..n].to_vec())),
+                        _ => { err_open = false; None }
+                    },
+                };
+                if let Some(chunk) = chunk
+                    && tx.send(chunk).await.is_err()
+                {
+                    return;
+                }
+            }
+            let code = child
+                .wait()
+                .await
+                .ok()
+                .and_then(|status| status.code())
+                .map(i64::from);
+            let _ = tx.send(OutputChunk::Exited(code)).await;
+        });
+
+        Ok(AttachedProcess {
+            stdin: Box::pin(stdin),
+            output: Box::pin(tokio_stream::wrappers::ReceiverStream::new(rx)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::sandbox::backend::BindMount;
+
+    #[test]
+    fn test_run_args_mirror_docker_isolation() {
+        let spec = ContainerSpec {
+            name: "sandbox-1".to_string(),
+            image: "sandbox:latest".to_string(),
+            command: "cargo test".to_string(),
+            working_dir: "/workspace".to_string(),
+            env: vec![("HTTP_PROXY".to_string(), "http://10.4.0.1:3128".to_string())],
+            binds: vec![BindMount {
+                host: PathBuf::from("/home/me/project"),
+                container: "/workspace".to_string(),
+                read_only: true,
+            }],
+            tmpfs: vec![("/tmp".to_string(), "size=512M".to_string())],
+            read_only_rootfs: true,
+            user: "1000:1000".to_string(),
+        };
+        let args = run_args(&spec, &ResourceLimits::default(), false).join(" ");
+
+        assert!(args.starts_with("run --rm --name sandbox-1 --network bridge"));
+        assert!(args.contains("--cap-drop ALL --cap-add CHOWN"));
+        assert!(args.contains("--security-opt no-new-privileges --user 1000:1000"));
+        assert!(args.contains("--read-only --tmpfs /tmp:size=512M"));
+        assert!(args.contains("--volume /home/me/project:/workspace:ro"));
+        assert!(args.contains("--env HTTP_PROXY=http://10.4.0.1:3128"));
+        assert!(args.ends_with("sandbox:latest sh -c cargo test"));
+
+        let detached = run_args(&spec, &ResourceLimits::default(), true).join(" ");
+        assert!(detached.starts_with("run --detach --name sandbox-1"));
+    }
+
+    #[test]
+    fn test_run_args_apply_quotas_and_gpus() {
+        let spec = ContainerSpec {
+            name: "sandbox-2".to_string(),
+            image: "sandbox:latest".to_string(),
+            command: "python train.py".to_string(),
+            working_dir: "/workspace".to_string(),
+            env: Vec::new(),
+            binds: Vec::new(),
+            tmpfs: vec![("/tmp".to_string(), "size=512M".to_string())],
+            read_only_rootfs: false,
+            user: "1000:1000".to_string(),
+        };
+        let limits = ResourceLimits {
+            pids_limit: Some(128),
+            disk_quota_bytes: Some(1024),
+            tmpfs_bytes: Some(2048),
+            gpus: GpuAccess::Devices(vec!["0".to_string(), "1".to_string()]),
+            ..ResourceLimits::default()
+        };
+        let args = run_args(&spec, &limits, false).join(" ");
+
+        assert!(args.contains("--pids-limit 128"));
+        assert!(args.contains("--storage-opt size=1024"));
+        assert!(args.contains("--gpus \"device=0,1\""));
+        assert!(args.contains("--tmpfs /tmp:size=2048"));
+    }
+}
@@ -0,0 +1,640 @@
+//! Docker API backend, also used for Podman's Docker-compatible API.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bollard::Docker;
+use bollard::container::{
+    Config, CreateContainerOptions, LogOutput, LogsOptions, RemoveContainerOptions,
+    StartContainerOptions, StopContainerOptions, WaitContainerOptions,
+};
+use bollard::exec::{CreateExecOptions, StartExecResults};
+use bollard::models::{DeviceRequest, HostConfig};
+use futures::StreamExt;
+
+use crate::context::CANCEL_GRACE_PERIOD;
+use crate::sandbox::backend::{AttachedProcess, ContainerBackend, ContainerSpec, OutputChunk};
+use crate::sandbox::config::{ContainerBackendKind, GpuAccess, ResourceLimits};
+use crate::sandbox::container::ContainerOutput;
+use crate::sandbox::error::{Result, SandboxError};
+
+/// Stops and removes a container if dropped while still armed, i.e. when the
+/// command's future is cancelled. `docker stop` sends SIGTERM and kills the
+/// container after [`CANCEL_GRACE_PERIOD`].
+struct ContainerGuard {
+    docker: Docker,
+    container_id: Option<String>,
+}
+
+impl ContainerGuard {
+    /// The container has been cleaned up normally.
+    fn disarm(&mut self) {
+        self.container_id = None;
+    }
+}
+
+impl Drop for ContainerGuard {
+    fn drop(&mut self) {
+        let Some(container_id) = self.container_id.take() else {
+            return;
+        };
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let docker = self.docker.clone();
+        handle.spawn(async move {
+            tracing::debug!(container_id = %container_id, "Stopping cancelled sandbox container");
+            let _ = docker
+                .stop_container(
+                    &container_id,
+                    Some(StopContainerOptions {
+                        t: CANCEL_GRACE_PERIOD.as_secs() as i64,
+                    }),
+                )
+                .await;
+            let _ = docker
+                .remove_container(
+                    &container_id,
+                    Some(RemoveContainerOptions {
+                        force: true,
+                        ..Default::default()
+                    }),
+                )
+                .await;
+        });
+    }
+}
+
+/// Runs sandbox containers through a Docker-compatible API.
+pub struct DockerBackend {
+    docker: Docker,
+    kind: ContainerBackendKind,
+    host_gateway: &'static str,
+    network_mode: Option<String>,
+}
+
+/// Docker device requests passing `gpus` through (`--gpus`).
+pub(crate) fn gpu_device_requests(gpus: &GpuAccess) -> Option<Vec<DeviceRequest>> {
+    let request = DeviceRequest {
+        capabilities: Some(vec![vec!["gpu".to_string()]]),
+        ..Default::default()
+    };
+    match gpus {
+        GpuAccess::None => None,
+        GpuAccess::All => Some(vec![DeviceRequest {
+            count: Some(-1),
+            ..request
+        }]),
+        GpuAccess::Devices(ids) => Some(vec![DeviceRequest {
+            device_ids: Some(ids.clone()),
+            ..request
+        }]),
+    }
+}
+
+impl DockerBackend {
+    /// A Docker daemon.
+    pub fn docker(docker: Docker) -> Self {
+        // host.docker.internal for Mac/Windows, the default bridge gateway on Linux
+        let host_gateway = if cfg!(target_os = "linux") {
+            "172.17.0.1"
+        } else {
+            "host.docker.internal"
+        };
+        Self {
+            docker,
+            kind: ContainerBackendKind::Docker,
+            host_gateway,
+            network_mode: Some("bridge".to_string()),
+        }
+    }
+
+    /// A Podman API service. Rootless Podman has no shared bridge, so the
+    /// container keeps Podman's default network, which resolves
+    /// `host.containers.internal` to the host.
+    pub fn podman(docker: Docker) -> Self {
+        Self {
+            docker,
+            kind: ContainerBackendKind::Podman,
+            host_gateway: "host.containers.internal",
+            network_mode: None,
+        }
+    }
+
+    /// Isolation and resource limits for `spec`.
+    fn host_config(&self, spec: &ContainerSpec, limits: &ResourceLimits) -> HostConfig {
+        let binds = spec
+            .binds
+            .iter()
+            .map(|b| {
+                format!(
+                    "{}:{}:{}",
+                    b.host.display(),
+                    b.container,
+                    if b.read_only { "ro" } else { "rw" }
+                )
+            })
+            .collect();
+
+        HostConfig {
+            binds: Some(binds),
+            memory: Some((limits.memory_bytes) as i64),
+            cpu_shares: Some(limits.cpu_shares as i64),
+            pids_limit: limits.pids_limit,
+            storage_opt: limits
+                .disk_quota_bytes
+                .map(|bytes| HashMap::from([("size".to_string(), bytes.to_string())])),
+            device_requests: gpu_device_requests(&limits.gpus),
+            auto_remove: Some(true),
+            // The proxy env vars (HTTP_PROXY/HTTPS_PROXY) route traffic
+            // through the host proxy. If the container needs direct network
+            // access, the caller should create a custom bridge with
+            // --internal and provide the name here (Finding 37).
+            network_mode: self.network_mode.clone(),
+            // ICC (inter-container communication) cannot be disabled per-container;
+            // it must be disabled on the Docker daemon with --icc=false.
+            // Security: drop all capabilities and add back only what's needed
+            cap_drop: Some(vec!["ALL".to_string()]),
+            cap_add: Some(vec!["CHOWN".to_string()]),
+            // Prevent privilege escalation
+            security_opt: Some(vec!["no-new-privileges:true".to_string()]),
+            // Read-only root filesystem (workspace is still writable if policy allows)
+            readonly_rootfs: Some(spec.read_only_rootfs),
+            tmpfs: Some(limits.tmpfs_mounts(&spec.tmpfs).into_iter().collect()),
+            ..Default::default()
+        }
+    }
+
+    /// Create a container for `spec`.
+    async fn create_container(
+        &self,
+        spec: &ContainerSpec,
+        limits: &ResourceLimits,
+    ) -> Result<String> {
+        let host_config = self.host_config(spec, limits);
+
+        let config = Config {
+            image: Some(spec.image.clone()),
+            cmd: Some(vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                spec.command.clone(),
+            ]),
+            working_dir: Some(spec.working_dir.clone()),
+            env: Some(
+                spec.env
+                    .iter()
+                    .map(|(k, v)| format!("{}={}", k, v))
+                    .collect(),
+            ),
+            host_config: Some(host_config),
+            user: Some(spec.user.clone()),
+            ..Default::default()
+        };
+
+        let options = CreateContainerOptions {
+            name: spec.name.clone(),
+            ..Default::default()
+        };
+
+        let response = self
+            .docker
+            .create_container(Some(options), config)
+            .await
+            .map_err(|e| SandboxError::ContainerCreationFailed {
+                reason: e.to_string(),
+            })?;
+
+        Ok(response.id)
+    }
+
+    /// Wait for a container to complete and collect output.
+    async fn wait_for_container(
+        &self,
+        container_id: &str,
+        max_output: usize,
+    ) -> Result<ContainerOutput> {
+        // Wait for the container to finish
+        let mut wait_stream = self.docker.wait_container(
+            container_id,
+            Some(WaitContainerOptions {
+                condition: "not-running",
+            }),
+        );
+
+        let exit_code = match wait_stream.next().await {
+            Some(Ok(response)) => response.status_code,
+            Some(Err(e)) => {
+                return Err(SandboxError::ExecutionFailed {
+                    reason: format!("wait failed: {}", e),
+                });
+            }
+            None => {
+                return Err(SandboxError::ExecutionFailed {
+                    reason: "container wait stream ended unexpectedly".to_string(),
+                });
+            }
+        };
+
+        // Collect logs
+        let (stdout, stderr, truncated) = self.collect_logs(container_id, max_output).await?;
+
+        Ok(ContainerOutput {
+            exit_code,
+            stdout,
+            stderr,
+            duration: Duration::ZERO, // Will be set by caller
+            truncated,
+        })
+    }
+
+    /// Collect stdout and stderr from a container.
+    async fn collect_logs(
+        &self,
+        container_id: &str,
+        max_output: usize,
+    ) -> Result<(String, String, bool)> {
+        let options = LogsOptions::<String> {
+            stdout: true,
+            stderr: true,
+            follow: false,
+            ..Default::default()
+        };
+
+        let mut stream = self.docker.logs(container_id, Some(options));
+
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+        let mut truncated = false;
+        let half_max = max_output / 2;
+
+        while let Some(result) = stream.next().await {
+            match result {
+                Ok(LogOutput::StdOut { message }) => {
+                    let text = String::from_utf8_lossy(&message);
+                    if stdout.len() + text.len() > half_max {
+                        truncated = true;
+                        let remaining = half_max.saturating_sub(stdout.len());
+                        stdout.push_str(&text[..remaining.min(text.len())]);
+                    } else {
+                        stdout.push_str(&text);
+                    }
+                }
+                Ok(LogOutput::StdErr { message }) => {
+                    let text = String::from_utf8_lossy(&message);
+                    if stderr.len() + text.len() > half_max {
+                        truncated = true;
+                        let remaining = half_max.saturating_sub(stderr.len());
+                        stderr.push_str(&text[..remaining.min(text.len())]);
+                    } else {
+                        stderr.push_str(&text);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!("Error reading container logs: {}", e);
+                }
+            }
+        }
+
+        Ok((stdout, stderr, truncated))
+    }
+
+    /// Run an exec and collect output.
+    async fn run_exec(&self, exec_id: &str, max_output: usize) -> Result<ContainerOutput> {
+        let start_result = self.docker.start_exec(exec_id, None).await.map_err(|e| {
+            SandboxError::ExecutionFailed {
+                reason: format!("exec start failed: {}", e),
+            }
+        })?;
+
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+        let mut truncated = false;
+        let half_max = max_output / 2;
+
+        if let StartExecResults::Attached { mut output, .. } = start_result {
+            while let Some(result) = output.next().await {
+                match result {
+                    Ok(LogOutput::StdOut { message }) => {
+                        let text = String::from_utf8_lossy(&message);
+                        if stdout.len() < half_max {
+                            let remaining = half_max.saturating_sub(stdout.len());
+                            stdout.push_str(&text[..remaining.min(text.len())]);
+                            if text.len() > remaining {
+                                truncated = true;
+                            }
+                        }
+                    }
+                    Ok(LogOutput::StdErr { message }) => {
+                        let text = String::from_utf8_lossy(&message);
+                        if stderr.len() < half_max {
+                            let remaining = half_max.saturating_sub(stderr.len());
+                            stderr.push_str(&text[..remaining.min(text.len())]);
+                            if text.len() > remaining {
+                                truncated = true;
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        tracing::warn!("Error reading exec output: {}", e);
+                    }
+                }
+            }
+        }
+
+        // Get exec exit code
+        let inspect =
+            self.docker
+                .inspect_exec(exec_id)
+                .await
+                .map_err(|e| SandboxError::ExecutionFailed {
+                    reason: format!("exec inspect failed: {}", e),
+                })?;
+
+        let exit_code = inspect.exit_code.unwrap_or(-1);
+
+        Ok(ContainerOutput {
+            exit_code,
+            stdout,
+            stderr,
+            duration: Duration::ZERO,
+            truncated,
+        })
+    }
+}
+
+#[async_trait]
+impl ContainerBackend for DockerBackend {
+    fn kind(&self) -> ContainerBackendKind {
+        self.kind
+    }
+
+    fn host_gateway(&self) -> &str {
+        self.host_gateway
+    }
+
+    async fn ping(&self) -> Result<()> {
+        self.docker
+            .ping()
+            .await
+            .map(|_| ())
+            .map_err(|e| SandboxError::BackendNotAvailable {
+                backend: self.kind.to_string(),
+                reason: e.to_string(),
+            })
+    }
+
+    async fn image_exists(&self, image: &str) -> bool {
+        self.docker.inspect_image(image).await.is_ok()
+    }
+
+    async fn pull_image(&self, image: &str) -> Result<()> {
+        use bollard::image::CreateImageOptions;
+
+        let options = CreateImageOptions {
+            from_image: image.to_string(),
+            ..Default::default()
+        };
+
+        let mut stream = self.docker.create_image(Some(options), None, None);
+
+        while let Some(result) = stream.next().await {
+            match result {
+                Ok(info) => {
+                    if let Some(status) = info.status {
+                        tracing::debug!("Pull status: {}", status);
+                    }
+                }
+                Err(e) => {
+                    return Err(SandboxError::ContainerCreationFailed {
+                        reason: format!("image pull failed: {}", e),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn run(&self, spec: &ContainerSpec, limits: &ResourceLimits) -> Result<ContainerOutput> {
+        let container_id = self.create_container(spec, limits).await?;
+        let mut guard = ContainerGuard {
+            docker: self.docker.clone(),
+            container_id: Some(container_id.clone()),
+        };
+
+        self.docker
+            .start_container(&container_id, None::<StartContainerOptions<String>>)
+            .await
+            .map_err(|e| SandboxError::ContainerStartFailed {
+                reason: e.to_string(),
+            })?;
+
+        // Wait for completion with timeout
+        let result = tokio::time::timeout(limits.timeout, async {
+            self.wait_for_container(&container_id, limits.max_output_bytes)
+                .await
+        })
+        .await;
+
+        // Always clean up the container
+        let _ = self
+            .docker
+            .remove_container(
+                &container_id,
+                Some(RemoveContainerOptions {
+                    force: true,
+                    ..Default::default()
+                }),
+            )
+            .await;
+        guard.disarm();
+
+        match result {
+            Ok(output) => output,
+            Err(_) => Err(SandboxError::Timeout(limits.timeout)),
+        }
+    }
+
+    async fn start(&self, spec: &ContainerSpec, limits: &ResourceLimits) -> Result<String> {
+        let container_id = self.create_container(spec, limits).await?;
+        if let Err(e) = self
+            .docker
+            .start_container(&container_id, None::<StartContainerOptions<String>>)
+            .await
+        {
+            self.remove(&container_id).await;
+            return Err(SandboxError::ContainerStartFailed {
+                reason: e.to_string(),
+            });
+        }
+        Ok(container_id)
+    }
+
+    async fn remove(&self, container_id: &str) {
+        let _ = self
+            .docker
+            .remove_container(
+                container_id,
+                Some(RemoveContainerOptions {
+                    force: true,
+                    ..Default::default()
+                }),
+            )
+            .await;
+    }
+
+    async fn exec(
+        &self,
+        container_id: &str,
+        command: &str,
+        working_dir: &str,
+        env: &[(String, String)],
+        limits: &ResourceLimits,
+    ) -> Result<ContainerOutput> {
+        let env: Vec<String> = env.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        let exec = self
+            .docker
+            .create_exec(
+                container_id,
+                CreateExecOptions {
+                    cmd: Some(vec!["sh", "-c", command]),
+                    attach_stdout: Some(true),
+                    attach_stderr: Some(true),
+                    working_dir: Some(working_dir),
+                    env: Some(env.iter().map(String::as_str).collect()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| SandboxError::ExecutionFailed {
+                reason: format!("exec create failed: {}", e),
+            })?;
+
+        tokio::time::timeout(
+            limits.timeout,
+            self.run_exec(&exec.id, limits.max_output_bytes),
+        )
+        .await
+        .map_err(|_| SandboxError::Timeout(limits.timeout))?
+    }
+
+    async fn attach(
+        &self,
+        container_id: &str,
+        command: &str,
+        working_dir: &str,
+        env: &[(String, String)],
+    ) -> Result<AttachedProcess> {
+        let env: Vec<String> = env.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        let exec = self
+            .docker
+            .create_exec(
+                container_id,
+                CreateExecOptions {
+                    cmd: Some(vec!["sh", "-c", command]),
+                    attach_stdin: Some(true),
+                    attach_stdout: Some(true),
+                    attach_stderr: Some(true),
+                    working_dir: Some(working_dir),
+                    env: Some(env.iter().map(String::as_str).collect()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| SandboxError::ExecutionFailed {
+                reason: format!("exec create failed: {}", e),
+            })?;
+
+        let start_result = self.docker.start_exec(&exec.id, None).await.map_err(|e| {
+            SandboxError::ExecutionFailed {
+                reason: format!("exec start failed: {}", e),
+            }
+        })?;
+        let StartExecResults::Attached { output, input } = start_result else {
+            return Err(SandboxError::ExecutionFailed {
+                reason: "exec started detached".to_string(),
+            });
+        };
+
+        let chunks = output.filter_map(|result| async move {
+            match result {
+                Ok(LogOutput::StdOut { message }) | Ok(LogOutput::Console { message }) => {
+                    Some(OutputChunk::Stdout(message.to_vec()))
+                }
+                Ok(LogOutput::StdErr { message }) => Some(OutputChunk::Stderr(message.to_vec())),
+                Ok(_) => None,
+                Err(e) => {
+                    tracing::debug!("Error reading attached exec output: {}", e);
+                    None
+                }
+            }
+        });
+        let docker = self.docker.clone();
+        let exited = futures::stream::once(async move {
+            let code = docker
+                .inspect_exec(&exec.id)
+                .await
+                .ok()
+                .and_then(|inspect| inspect.exit_code);
+            OutputChunk::Exited(code)
+        });
+
+        Ok(AttachedProcess {
+            stdin: input,
+            output: Box::pin(chunks.chain(exited)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_config_applies_quotas_and_gpus() {
+        let docker =
+            Docker::connect_with_http("http://127.0.0.1:1", 1, bollard::API_DEFAULT_VERSION)
+                .unwrap();
+        let backend = DockerBackend::docker(docker);
+        let spec = ContainerSpec {
+            name: "sandbox-1".to_string(),
+            image: "sandbox:latest".to_string(),
+            command: "nvidia-smi".to_string(),
+            working_dir: "/workspace".to_string(),
+            env: Vec::new(),
+            binds: Vec::new(),
+            tmpfs: vec![("/tmp".to_string(), "size=512M".to_string())],
+            read_only_rootfs: true,
+            user: "1000:1000".to_string(),
+        };
+
+        let plain = backend.host_config(&spec, &ResourceLimits::default());
+        assert_eq!(plain.pids_limit, None);
+        assert_eq!(plain.storage_opt, None);
+        assert_eq!(plain.device_requests, None);
+
+        let limits = ResourceLimits {
+            pids_limit: Some(64),
+            disk_quota_bytes: Some(1 << 30),
+            tmpfs_bytes: Some(1 << 20),
+            gpus: GpuAccess::All,
+            ..ResourceLimits::default()
+        };
+        let config = backend.host_config(&spec, &limits);
+        assert_eq!(config.pids_limit, Some(64));
+        assert_eq!(
+            config.storage_opt.unwrap().get("size").map(String::as_str),
+            Some("1073741824")
+        );
+        let gpu = &config.device_requests.unwrap()[0];
+        assert_eq!(gpu.count, Some(-1));
+        assert_eq!(gpu.capabilities, Some(vec![vec!["gpu".to_string()]]));
+        assert_eq!(
+            config.tmpfs.unwrap().get("/tmp").map(String::as_str),
+            Some("size=1048576")
+        );
+    }
+}
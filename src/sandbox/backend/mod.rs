@@ -0,0 +1,183 @@
+//! Container runtimes the sandbox can run commands in.
+//!
+//! ```text
+//! ┌─────────────┬──────────────────────────────┬──────────────────────────────┐
+//! │ Backend     │ Reached through              │ Containers reach the host at │
+//! ├─────────────┼──────────────────────────────┼──────────────────────────────┤
+//! │ docker      │ Docker API socket (bollard)  │ 172.17.0.1 / docker.internal │
+//! │ podman      │ Podman's Docker-compatible   │ host.containers.internal     │
+//! │             │ API socket, rootless first   │                              │
+//! │ containerd  │ `nerdctl` CLI                │ 10.4.0.1 (CNI bridge)        │
+//! └─────────────┴──────────────────────────────┴──────────────────────────────┘
+//! ```
+//!
+//! [`ContainerRunner`](crate::sandbox::ContainerRunner) turns a command and a
+//! policy into a [`ContainerSpec`]; a [`ContainerBackend`] runs it. Every
+//! backend applies the same isolation: all capabilities dropped except
+//! `CHOWN`, `no-new-privileges`, a non-root user and tmpfs scratch space.
+
+mod containerd;
+mod docker;
+mod podman;
+
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::Stream;
+use tokio::io::AsyncWrite;
+
+pub use containerd::NerdctlBackend;
+pub use docker::DockerBackend;
+pub(crate) use docker::gpu_device_requests;
+pub use podman::connect_podman;
+
+use crate::sandbox::config::{ContainerBackendKind, ResourceLimits};
+use crate::sandbox::container::ContainerOutput;
+use crate::sandbox::error::Result;
+
+/// A host directory mounted into the container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindMount {
+    pub host: PathBuf,
+    pub container: String,
+    pub read_only: bool,
+}
+
+/// Everything a backend needs to run one sandboxed command.
+#[derive(Debug, Clone)]
+pub struct ContainerSpec {
+    pub name: String,
+    pub image: String,
+    /// Run as `sh -c <command>`.
+    pub command: String,
+    pub working_dir: String,
+    pub env: Vec<(String, String)>,
+    pub binds: Vec<BindMount>,
+    /// `(path, options)` pairs, e.g. `("/tmp", "size=512M")`.
+    pub tmpfs: Vec<(String, String)>,
+    pub read_only_rootfs: bool,
+    /// `uid:gid` inside the container.
+    pub user: String,
+}
+
+/// Output of an attached process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputChunk {
+    Stdout(Vec<u8>),
+    Stderr(Vec<u8>),
+    /// The process ended, with its exit code if the runtime reported one.
+    /// Always the last item.
+    Exited(Option<i64>),
+}
+
+/// A process started with [`ContainerBackend::attach`].
+pub struct AttachedProcess {
+    pub stdin: Pin<Box<dyn AsyncWrite + Send>>,
+    pub output: Pin<Box<dyn Stream<Item = OutputChunk> + Send>>,
+}
+
+/// A container runtime.
+#[async_trait]
+pub trait ContainerBackend: Send + Sync {
+    fn kind(&self) -> ContainerBackendKind;
+
+    /// Address a container uses to reach a port on the host (the network
+    /// proxy listens there).
+    fn host_gateway(&self) -> &str;
+
+    /// Check that the runtime answers.
+    async fn ping(&self) -> Result<()>;
+
+    /// Check whether an image is present locally.
+    async fn image_exists(&self, image: &str) -> bool;
+
+    /// Pull an image.
+    async fn pull_image(&self, image: &str) -> Result<()>;
+
+    /// Run `spec` in a fresh container and remove it afterwards, also when
+    /// the returned future is dropped. Output beyond `limits.max_output_bytes`
+    /// is cut off.
+    async fn run(&self, spec: &ContainerSpec, limits: &ResourceLimits) -> Result<ContainerOutput>;
+
+    /// Start `spec` in a detached container and return its id. The caller
+    /// owns the container and must [`remove`](Self::remove) it.
+    async fn start(&self, spec: &ContainerSpec, limits: &ResourceLimits) -> Result<String>;
+
+    /// Force-remove a container, ignoring errors.
+    async fn remove(&self, container_id: &str);
+
+    /// Run a command in an existing container with extra `env`.
+    async fn exec(
+        &self,
+        container_id: &str,
+        command: &str,
+        working_dir: &str,
+        env: &[(String, String)],
+        limits: &ResourceLimits,
+    ) -> Result<ContainerOutput>;
+
+    /// Start a command in an existing container with its stdin held open,
+    /// for interactive programs. Output streams until the process exits;
+    /// there is no timeout.
+    async fn attach(
+        &self,
+        container_id: &str,
+        command: &str,
+        working_dir: &str,
+        env: &[(String, String)],
+    ) -> Result<AttachedProcess>;
+}
+
+/// Connect to the given runtime and check it answers.
+pub async fn connect_backend(kind: ContainerBackendKind) -> Result<Arc<dyn ContainerBackend>> {
+    let backend: Arc<dyn ContainerBackend> = match kind {
+        ContainerBackendKind::Docker => Arc::new(DockerBackend::docker(
+            crate::sandbox::container::connect_docker().await?,
+        )),
+        ContainerBackendKind::Podman => Arc::new(DockerBackend::podman(connect_podman().await?)),
+        ContainerBackendKind::Containerd => Arc::new(NerdctlBackend::new()),
+    };
+    backend.ping().await?;
+    Ok(backend)
+}
+
+/// Whether the given runtime answers.
+pub async fn backend_available(kind: ContainerBackendKind) -> bool {
+    connect_backend(kind).await.is_ok()
+}
+
+/// Append `text` to `buf` without letting `buf` grow past `cap` bytes.
+/// Returns `true` if anything was cut.
+pub(crate) fn push_capped(buf: &mut String, text: &str, cap: usize) -> bool {
+    let remaining = cap.saturating_sub(buf.len());
+    if text.len() <= remaining {
+        buf.push_str(text);
+        return false;
+    }
+    let mut end = remaining;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    buf.push_str(&text[..end]);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_capped_respects_char_boundaries() {
+        let mut buf = String::from("ab");
+        assert!(!push_capped(&mut buf, "cd", 4));
+        assert_eq!(buf, "abcd");
+
+        let mut buf = String::new();
+        assert!(push_capped(&mut buf, "héllo", 2));
+        assert_eq!(buf, "h");
+        assert!(push_capped(&mut buf, "more", 2));
+        assert_eq!(buf, "hm");
+    }
+}
@@ -0,0 +1,81 @@
+//! Locating the Podman API service.
+//!
+//! Podman serves a Docker-compatible API, so the Docker backend drives it
+//! once connected. The service is socket-activated on most distributions
+//! (`systemctl --user enable --now podman.socket`).
+
+use std::path::PathBuf;
+
+use bollard::Docker;
+
+use crate::sandbox::error::{Result, SandboxError};
+
+/// Socket paths to try, rootless first:
+/// 1. `CONTAINER_HOST` (`unix://` URLs only)
+/// 2. `$XDG_RUNTIME_DIR/podman/podman.sock`
+/// 3. `/run/user/<uid>/podman/podman.sock`
+/// 4. `/run/podman/podman.sock` (rootful)
+pub(crate) fn podman_socket_candidates() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Ok(host) = std::env::var("CONTAINER_HOST")
+        && let Some(path) = host.strip_prefix("unix://")
+    {
+        candidates.push(PathBuf::from(path));
+    }
+    if let Some(runtime_dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+        candidates.push(PathBuf::from(runtime_dir).join("podman/podman.sock"));
+    }
+    #[cfg(unix)]
+    {
+        // SAFETY: getuid has no preconditions and cannot fail.
+        let uid = unsafe { libc::getuid() };
+        let user_sock = PathBuf::from(format!("/run/user/{}/podman/podman.sock", uid));
+        if !candidates.contains(&user_sock) {
+            candidates.push(user_sock);
+        }
+    }
+    candidates.push(PathBuf::from("/run/podman/podman.sock"));
+    candidates
+}
+
+/// Connect to the first Podman API socket that answers.
+pub async fn connect_podman() -> Result<Docker> {
+    let candidates = podman_socket_candidates();
+    for sock in &candidates {
+        if !sock.exists() {
+            continue;
+        }
+        if let Ok(docker) =
+            Docker::connect_with_socket(&sock.to_string_lossy(), 120, bollard::API_DEFAULT_VERSION)
+            && docker.ping().await.is_ok()
+        {
+            return Ok(docker);
+        }
+    }
+
+    Err(SandboxError::BackendNotAvailable {
+        backend: "podman".to_string(),
+        reason: format!(
+            "no Podman API socket answered (tried {}); start it with `systemctl --user enable --now podman.socket`",
+            candidates
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rootful_socket_is_last_resort() {
+        let candidates = podman_socket_candidates();
+        assert_eq!(
+            candidates.last(),
+            Some(&PathBuf::from("/run/podman/podman.sock"))
+        );
+    }
+}
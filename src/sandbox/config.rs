@@ -17,12 +17,33 @@ pub struct SandboxConfig {
     pub cpu_shares: u32,
     /// Network allowlist for proxied requests.
     pub network_allowlist: Vec<String>,
-    /// Docker image to use for the sandbox.
+    /// Container runtime that runs sandboxed commands.
+    pub backend: ContainerBackendKind,
+    /// Container image to use for the sandbox.
     pub image: String,
     /// Whether to auto-pull the image if not found.
     pub auto_pull_image: bool,
     /// Port for the HTTP proxy (0 = auto-assign).
     pub proxy_port: u16,
+    /// Directory for cached package registry artifacts (None = caching off).
+    pub package_cache_dir: Option<std::path::PathBuf>,
+    /// Size budget for the package cache in bytes.
+    pub package_cache_max_bytes: u64,
+    /// Warm containers kept per policy and workspace (0 = a fresh container
+    /// per command).
+    pub warm_pool_size: usize,
+    /// Interactive sessions unused for this long are closed.
+    pub session_idle_timeout: Duration,
+    /// Audit log of per-command network grants.
+    pub network_grant_audit_path: std::path::PathBuf,
+    /// Maximum processes per container (None = runtime default).
+    pub pids_limit: Option<i64>,
+    /// Size cap for the container's writable layer in megabytes.
+    pub disk_quota_mb: Option<u64>,
+    /// Size of each tmpfs scratch mount in megabytes (None = built-in sizes).
+    pub tmpfs_size_mb: Option<u64>,
+    /// GPUs passed through to containers.
+    pub gpus: GpuAccess,
 }
 
 impl Default for SandboxConfig {
@@ -34,9 +55,19 @@ impl Default for SandboxConfig {
             memory_limit_mb: 2048,
             cpu_shares: 1024,
             network_allowlist: default_allowlist(),
+            backend: ContainerBackendKind::Docker,
             image: "ghcr.io/nearai/sandbox:latest".to_string(),
             auto_pull_image: true,
             proxy_port: 0,
+            package_cache_dir: None,
+            package_cache_max_bytes: crate::sandbox::proxy::cache::DEFAULT_PACKAGE_CACHE_MAX_BYTES,
+            warm_pool_size: 0,
+            session_idle_timeout: Duration::from_secs(10 * 60),
+            network_grant_audit_path: crate::sandbox::proxy::grants::default_audit_path(),
+            pids_limit: None,
+            disk_quota_mb: None,
+            tmpfs_size_mb: None,
+            gpus: GpuAccess::None,
         }
     }
 }
@@ -54,7 +85,7 @@ impl Default for SandboxConfig {
 /// │ FullAccess      │ Full host        │ Full network (DANGER)          │
 /// └─────────────────┴──────────────────┴────────────────────────────────┘
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum SandboxPolicy {
     /// Read-only access to workspace, proxied network.
     /// Use for: exploring code, fetching docs, read-only operations.
@@ -109,6 +140,90 @@ impl std::str::FromStr for SandboxPolicy {
     }
 }
 
+/// Container runtime used by the sandbox.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContainerBackendKind {
+    /// Docker daemon via its API socket.
+    #[default]
+    Docker,
+    /// Podman (rootless by default) via its Docker-compatible API socket.
+    Podman,
+    /// containerd, driven through the `nerdctl` CLI.
+    Containerd,
+}
+
+impl ContainerBackendKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+            Self::Containerd => "containerd",
+        }
+    }
+}
+
+impl std::fmt::Display for ContainerBackendKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ContainerBackendKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "docker" => Ok(Self::Docker),
+            "podman" => Ok(Self::Podman),
+            "containerd" | "nerdctl" => Ok(Self::Containerd),
+            _ => Err(format!(
+                "invalid sandbox backend '{}', expected 'docker', 'podman', or 'containerd'",
+                s
+            )),
+        }
+    }
+}
+
+/// GPUs a sandbox container may use.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum GpuAccess {
+    /// No GPU devices.
+    #[default]
+    None,
+    /// Every GPU on the host.
+    All,
+    /// Only these devices, by index or UUID.
+    Devices(Vec<String>),
+}
+
+impl std::str::FromStr for GpuAccess {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "none" | "off" => Ok(Self::None),
+            "all" => Ok(Self::All),
+            _ => {
+                let devices: Vec<String> = s
+                    .split(',')
+                    .map(|d| d.trim().to_string())
+                    .filter(|d| !d.is_empty())
+                    .collect();
+                if let Some(bad) = devices
+                    .iter()
+                    .find(|d| !d.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+                {
+                    return Err(format!(
+                        "invalid GPU device '{}', expected 'none', 'all', or device indexes/UUIDs",
+                        bad
+                    ));
+                }
+                Ok(Self::Devices(devices))
+            }
+        }
+    }
+}
+
 /// Resource limits for container execution.
 #[derive(Debug, Clone)]
 pub struct ResourceLimits {
@@ -120,6 +235,17 @@ pub struct ResourceLimits {
     pub timeout: Duration,
     /// Maximum output size in bytes.
     pub max_output_bytes: usize,
+    /// Maximum number of processes (None = runtime default).
+    pub pids_limit: Option<i64>,
+    /// Size cap for the container's writable layer. Needs a storage driver
+    /// with quota support (overlay2 on xfs with `pquota`, btrfs, zfs);
+    /// container creation fails on one without. The workspace is a host
+    /// bind mount and is not covered.
+    pub disk_quota_bytes: Option<u64>,
+    /// Size of each tmpfs scratch mount, replacing the built-in sizes.
+    pub tmpfs_bytes: Option<u64>,
+    /// GPUs passed through to the container.
+    pub gpus: GpuAccess,
 }
 
 impl Default for ResourceLimits {
@@ -129,10 +255,36 @@ impl Default for ResourceLimits {
             cpu_shares: 1024,
             timeout: Duration::from_secs(120),
             max_output_bytes: 64 * 1024, // 64 KB
+            pids_limit: None,
+            disk_quota_bytes: None,
+            tmpfs_bytes: None,
+            gpus: GpuAccess::None,
         }
     }
 }
 
+impl ResourceLimits {
+    /// `spec_tmpfs` with each mount's `size=` option set to `tmpfs_bytes`,
+    /// when that is set.
+    pub fn tmpfs_mounts(&self, spec_tmpfs: &[(String, String)]) -> Vec<(String, String)> {
+        let Some(bytes) = self.tmpfs_bytes else {
+            return spec_tmpfs.to_vec();
+        };
+        spec_tmpfs
+            .iter()
+            .map(|(path, options)| {
+                let mut options: Vec<String> = options
+                    .split(',')
+                    .filter(|o| !o.is_empty() && !o.starts_with("size="))
+                    .map(str::to_string)
+                    .collect();
+                options.push(format!("size={}", bytes));
+                (path.clone(), options.join(","))
+            })
+            .collect()
+    }
+}
+
 /// Default network allowlist for common development operations.
 pub fn default_allowlist() -> Vec<String> {
     vec![
@@ -236,6 +388,46 @@ mod tests {
         assert!("invalid".parse::<SandboxPolicy>().is_err());
     }
 
+    #[test]
+    fn test_backend_kind_from_str() {
+        assert_eq!(
+            "Podman".parse::<ContainerBackendKind>().unwrap(),
+            ContainerBackendKind::Podman
+        );
+        assert_eq!(
+            "nerdctl".parse::<ContainerBackendKind>().unwrap(),
+            ContainerBackendKind::Containerd
+        );
+        assert_eq!(ContainerBackendKind::Containerd.to_string(), "containerd");
+        assert!("lxc".parse::<ContainerBackendKind>().is_err());
+    }
+
+    #[test]
+    fn test_gpu_access_from_str() {
+        assert_eq!("none".parse::<GpuAccess>().unwrap(), GpuAccess::None);
+        assert_eq!("ALL".parse::<GpuAccess>().unwrap(), GpuAccess::All);
+        assert_eq!(
+            "0, 2".parse::<GpuAccess>().unwrap(),
+            GpuAccess::Devices(vec!["0".to_string(), "2".to_string()])
+        );
+        assert!("0;rm -rf".parse::<GpuAccess>().is_err());
+    }
+
+    #[test]
+    fn test_tmpfs_size_override() {
+        let mounts = vec![("/tmp".to_string(), "rw,size=512M".to_string())];
+        assert_eq!(ResourceLimits::default().tmpfs_mounts(&mounts), mounts);
+
+        let limits = ResourceLimits {
+            tmpfs_bytes: Some(64 * 1024 * 1024),
+            ..ResourceLimits::default()
+        };
+        assert_eq!(
+            limits.tmpfs_mounts(&mounts),
+            vec![("/tmp".to_string(), "rw,size=67108864".to_string())]
+        );
+    }
+
     #[test]
     fn test_policy_properties() {
         assert!(!SandboxPolicy::ReadOnly.allows_writes());
@@ -1,16 +1,20 @@
-//! Docker container lifecycle management.
+//! Container lifecycle management.
 //!
-//! Handles creating, running, and cleaning up containers for sandboxed execution.
+//! Handles creating, running, and cleaning up containers for sandboxed
+//! execution. The runtime behind it (Docker, Podman or containerd) is a
+//! [`ContainerBackend`]; see [`crate::sandbox::backend`].
 //!
 //! # Container Setup
 //!
 //! ```text
 //! ┌────────────────────────────────────────────────────────────────────────┐
-//! │                          Docker Container                               │
+//! │                          Sandbox Container                              │
 //! │                                                                         │
 //! │  Environment:                                                           │
-//! │    http_proxy=http://host.docker.internal:PORT                          │
-//! │    https_proxy=http://host.docker.internal:PORT                         │
+//! │    http_proxy=http://<host gateway>:PORT                                │
+//! │    https_proxy=http://<host gateway>:PORT                               │
+//! │    (http://<grant token>@... when the command has a network grant)     │
+//! │    npm_config_registry / PIP_INDEX_URL ─▶ proxy registry mirror        │
 //! │    (No secrets or credentials)                                          │
 //! │                                                                         │
 //! │  Mounts:                                                                │
@@ -27,17 +31,12 @@
 
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
 
 use bollard::Docker;
-use bollard::container::{
-    Config, CreateContainerOptions, LogOutput, LogsOptions, RemoveContainerOptions,
-    StartContainerOptions, WaitContainerOptions,
-};
-use bollard::exec::{CreateExecOptions, StartExecResults};
-use bollard::models::HostConfig;
-use futures::StreamExt;
 
+use crate::sandbox::backend::{BindMount, ContainerBackend, ContainerSpec};
 use crate::sandbox::config::{ResourceLimits, SandboxPolicy};
 use crate::sandbox::error::{Result, SandboxError};
 
@@ -56,61 +55,54 @@ pub struct ContainerOutput {
     pub truncated: bool,
 }
 
-/// Manages Docker container lifecycle.
+/// Runs sandboxed commands in containers on some backend.
 pub struct ContainerRunner {
-    docker: Docker,
+    backend: Arc<dyn ContainerBackend>,
     image: String,
     proxy_port: u16,
+    proxy_token: Option<String>,
 }
 
 impl ContainerRunner {
     /// Create a new container runner.
-    pub fn new(docker: Docker, image: String, proxy_port: u16) -> Self {
+    pub fn new(backend: Arc<dyn ContainerBackend>, image: String, proxy_port: u16) -> Self {
         Self {
-            docker,
+            backend,
             image,
             proxy_port,
+            proxy_token: None,
         }
     }
 
-    /// Check if the Docker daemon is available.
+    /// Present `token` to the proxy, so a per-command network grant applies.
+    pub fn with_proxy_token(mut self, token: &str) -> Self {
+        self.proxy_token = Some(token.to_string());
+        self
+    }
+
+    /// The backend containers run on.
+    pub fn backend(&self) -> &Arc<dyn ContainerBackend> {
+        &self.backend
+    }
+
+    /// Check if the container runtime is available.
     pub async fn is_available(&self) -> bool {
-        self.docker.ping().await.is_ok()
+        self.backend.ping().await.is_ok()
     }
 
     /// Check if the sandbox image exists locally.
     pub async fn image_exists(&self) -> bool {
-        self.docker.inspect_image(&self.image).await.is_ok()
+        self.backend.image_exists(&self.image).await
     }
 
     /// Pull the sandbox image.
     pub async fn pull_image(&self) -> Result<()> {
-        use bollard::image::CreateImageOptions;
-
-        tracing::info!("Pulling sandbox image: {}", self.image);
-
-        let options = CreateImageOptions {
-            from_image: self.image.clone(),
-            ..Default::default()
-        };
-
-        let mut stream = self.docker.create_image(Some(options), None, None);
-
-        while let Some(result) = stream.next().await {
-            match result {
-                Ok(info) => {
-                    if let Some(status) = info.status {
-                        tracing::debug!("Pull status: {}", status);
-                    }
-                }
-                Err(e) => {
-                    return Err(SandboxError::ContainerCreationFailed {
-                        reason: format!("image pull failed: {}", e),
-                    });
-                }
-            }
-        }
-
+        tracing::info!(
+            "Pulling sandbox image {} with {}",
+            self.image,
+            self.backend.kind()
+        );
+        self.backend.pull_image(&self.image).await?;
         tracing::info!("Successfully pulled image: {}", self.image);
         Ok(())
     }
@@ -125,47 +117,10 @@ impl ContainerRunner {
         env: HashMap<String, String>,
     ) -> Result<ContainerOutput> {
         let start_time = std::time::Instant::now();
-
-        // Create the container
-        let container_id = self
-            .create_container(command, working_dir, policy, limits, env)
-            .await?;
-
-        // Start the container
-        self.docker
-            .start_container(&container_id, None::<StartContainerOptions<String>>)
-            .await
-            .map_err(|e| SandboxError::ContainerStartFailed {
-                reason: e.to_string(),
-            })?;
-
-        // Wait for completion with timeout
-        let result = tokio::time::timeout(limits.timeout, async {
-            self.wait_for_container(&container_id, limits.max_output_bytes)
-                .await
-        })
-        .await;
-
-        // Always clean up the container
-        let _ = self
-            .docker
-            .remove_container(
-                &container_id,
-                Some(RemoveContainerOptions {
-                    force: true,
-                    ..Default::default()
-                }),
-            )
-            .await;
-
-        match result {
-            Ok(Ok(mut output)) => {
-                output.duration = start_time.elapsed();
-                Ok(output)
-            }
-            Ok(Err(e)) => Err(e),
-            Err(_) => Err(SandboxError::Timeout(limits.timeout)),
-        }
+        let spec = self.spec(command, working_dir, policy, env);
+        let mut output = self.backend.run(&spec, limits).await?;
+        output.duration = start_time.elapsed();
+        Ok(output)
     }
 
     /// Execute a command in an existing container using exec.
@@ -177,317 +132,99 @@ impl ContainerRunner {
         limits: &ResourceLimits,
     ) -> Result<ContainerOutput> {
         let start_time = std::time::Instant::now();
+        let mut output = self
+            .backend
+            .exec(container_id, command, working_dir, &[], limits)
+            .await?;
+        output.duration = start_time.elapsed();
+        Ok(output)
+    }
 
-        let exec = self
-            .docker
-            .create_exec(
-                container_id,
-                CreateExecOptions {
-                    cmd: Some(vec!["sh", "-c", command]),
-                    attach_stdout: Some(true),
-                    attach_stderr: Some(true),
-                    working_dir: Some(working_dir),
-                    ..Default::default()
-                },
-            )
-            .await
-            .map_err(|e| SandboxError::ExecutionFailed {
-                reason: format!("exec create failed: {}", e),
-            })?;
-
-        let result = tokio::time::timeout(
-            limits.timeout,
-            self.run_exec(&exec.id, limits.max_output_bytes),
-        )
-        .await;
-
-        match result {
-            Ok(Ok(mut output)) => {
-                output.duration = start_time.elapsed();
-                Ok(output)
-            }
-            Ok(Err(e)) => Err(e),
-            Err(_) => Err(SandboxError::Timeout(limits.timeout)),
+    /// Proxy variables for a command under `policy`; empty when traffic
+    /// does not go through the proxy.
+    pub(crate) fn proxy_env(&self, policy: SandboxPolicy) -> Vec<(String, String)> {
+        if self.proxy_port == 0 || !policy.is_sandboxed() {
+            return Vec::new();
         }
+        let credentials = match &self.proxy_token {
+            Some(token) => format!("{}@", token),
+            None => String::new(),
+        };
+        let proxy = format!(
+            "http://{}{}:{}",
+            credentials,
+            self.backend.host_gateway(),
+            self.proxy_port
+        );
+        let mirror = format!("http://{}", crate::sandbox::proxy::MIRROR_HOST);
+        let mut env: Vec<(String, String)> =
+            ["http_proxy", "https_proxy", "HTTP_PROXY", "HTTPS_PROXY"]
+                .into_iter()
+                .map(|key| (key.to_string(), proxy.clone()))
+                .collect();
+        // Package managers go through the proxy's registry mirror; cargo is
+        // pointed at it by the sandbox image's config.
+        env.extend([
+            (
+                "npm_config_registry".to_string(),
+                format!("{}/npm/", mirror),
+            ),
+            (
+                "PIP_INDEX_URL".to_string(),
+                format!("{}/pypi/simple/", mirror),
+            ),
+            (
+                "PIP_TRUSTED_HOST".to_string(),
+                crate::sandbox::proxy::MIRROR_HOST.to_string(),
+            ),
+        ]);
+        env
     }
 
-    /// Create a container with the appropriate configuration.
-    async fn create_container(
+    /// Describe the container for a command under `policy`.
+    pub(crate) fn spec(
         &self,
         command: &str,
         working_dir: &Path,
         policy: SandboxPolicy,
-        limits: &ResourceLimits,
         env: HashMap<String, String>,
-    ) -> Result<String> {
-        let working_dir_str = working_dir.display().to_string();
-
-        // Build environment variables
-        let mut env_vec: Vec<String> = env
-            .into_iter()
-            .map(|(k, v)| format!("{}={}", k, v))
-            .collect();
-
-        // Add proxy environment (uses host.docker.internal for Mac/Windows, 172.17.0.1 for Linux)
-        let proxy_host = if cfg!(target_os = "linux") {
-            "172.17.0.1"
-        } else {
-            "host.docker.internal"
-        };
-
-        if self.proxy_port > 0 && policy.is_sandboxed() {
-            env_vec.push(format!(
-                "http_proxy=http://{}:{}",
-                proxy_host, self.proxy_port
-            ));
-            env_vec.push(format!(
-                "https_proxy=http://{}:{}",
-                proxy_host, self.proxy_port
-            ));
-            env_vec.push(format!(
-                "HTTP_PROXY=http://{}:{}",
-                proxy_host, self.proxy_port
-            ));
-            env_vec.push(format!(
-                "HTTPS_PROXY=http://{}:{}",
-                proxy_host, self.proxy_port
-            ));
+    ) -> ContainerSpec {
+        let mut env: Vec<(String, String)> = env.into_iter().collect();
+
+        // Route traffic through the host proxy at the backend's host gateway
+        let proxy_env = self.proxy_env(policy);
+        if !proxy_env.is_empty() {
+            env.retain(|(key, _)| !proxy_env.iter().any(|(k, _)| k == key));
+            env.extend(proxy_env);
         }
 
-        // Build volume mounts based on policy
-        let binds = match policy {
-            SandboxPolicy::ReadOnly => {
-                vec![format!("{}:/workspace:ro", working_dir_str)]
-            }
-            SandboxPolicy::WorkspaceWrite => {
-                vec![format!("{}:/workspace:rw", working_dir_str)]
-            }
-            SandboxPolicy::FullAccess => {
-                // Full access: workspace writable, but do NOT mount host /tmp
-                // (Finding 38: host /tmp mount allows reading/modifying host temp files)
-                vec![format!("{}:/workspace:rw", working_dir_str)]
-            }
-        };
-
-        let host_config = HostConfig {
-            binds: Some(binds),
-            memory: Some((limits.memory_bytes) as i64),
-            cpu_shares: Some(limits.cpu_shares as i64),
-            auto_remove: Some(true),
-            // "none" disables all networking inside the container. The proxy
-            // env vars (HTTP_PROXY/HTTPS_PROXY) route traffic through the host
-            // proxy on 172.17.0.1 or host.docker.internal. If the container
-            // needs direct network access, the caller should create a custom
-            // bridge with --internal and provide the name here (Finding 37).
-            network_mode: Some("bridge".to_string()),
-            // ICC (inter-container communication) cannot be disabled per-container;
-            // it must be disabled on the Docker daemon with --icc=false.
-            // Security: drop all capabilities and add back only what's needed
-            cap_drop: Some(vec!["ALL".to_string()]),
-            cap_add: Some(vec!["CHOWN".to_string()]),
-            // Prevent privilege escalation
-            security_opt: Some(vec!["no-new-privileges:true".to_string()]),
-            // Read-only root filesystem (workspace is still writable if policy allows)
-            readonly_rootfs: Some(policy == SandboxPolicy::ReadOnly),
-            // Tmpfs mounts for /tmp and cargo cache
-            tmpfs: Some(
-                [
-                    ("/tmp".to_string(), "size=512M".to_string()),
-                    (
-                        "/home/sandbox/.cargo/registry".to_string(),
-                        "size=1G".to_string(),
-                    ),
-                ]
-                .into_iter()
-                .collect(),
-            ),
-            ..Default::default()
-        };
-
-        let config = Config {
-            image: Some(self.image.clone()),
-            cmd: Some(vec![
-                "sh".to_string(),
-                "-c".to_string(),
-                command.to_string(),
-            ]),
-            working_dir: Some("/workspace".to_string()),
-            env: Some(env_vec),
-            host_config: Some(host_config),
-            user: Some("1000:1000".to_string()), // Non-root user
-            ..Default::default()
-        };
+        // Full access: workspace writable, but do NOT mount host /tmp
+        // (Finding 38: host /tmp mount allows reading/modifying host temp files)
+        let binds = vec![BindMount {
+            host: working_dir.to_path_buf(),
+            container: "/workspace".to_string(),
+            read_only: policy == SandboxPolicy::ReadOnly,
+        }];
 
-        let options = CreateContainerOptions {
+        ContainerSpec {
             name: format!("sandbox-{}", uuid::Uuid::new_v4()),
-            ..Default::default()
-        };
-
-        let response = self
-            .docker
-            .create_container(Some(options), config)
-            .await
-            .map_err(|e| SandboxError::ContainerCreationFailed {
-                reason: e.to_string(),
-            })?;
-
-        Ok(response.id)
-    }
-
-    /// Wait for a container to complete and collect output.
-    async fn wait_for_container(
-        &self,
-        container_id: &str,
-        max_output: usize,
-    ) -> Result<ContainerOutput> {
-        // Wait for the container to finish
-        let mut wait_stream = self.docker.wait_container(
-            container_id,
-            Some(WaitContainerOptions {
-                condition: "not-running",
-            }),
-        );
-
-        let exit_code = match wait_stream.next().await {
-            Some(Ok(response)) => response.status_code,
-            Some(Err(e)) => {
-                return Err(SandboxError::ExecutionFailed {
-                    reason: format!("wait failed: {}", e),
-                });
-            }
-            None => {
-                return Err(SandboxError::ExecutionFailed {
-                    reason: "container wait stream ended unexpectedly".to_string(),
-                });
-            }
-        };
-
-        // Collect logs
-        let (stdout, stderr, truncated) = self.collect_logs(container_id, max_output).await?;
-
-        Ok(ContainerOutput {
-            exit_code,
-            stdout,
-            stderr,
-            duration: Duration::ZERO, // Will be set by caller
-            truncated,
-        })
-    }
-
-    /// Collect stdout and stderr from a container.
-    async fn collect_logs(
-        &self,
-        container_id: &str,
-        max_output: usize,
-    ) -> Result<(String, String, bool)> {
-        let options = LogsOptions::<String> {
-            stdout: true,
-            stderr: true,
-            follow: false,
-            ..Default::default()
-        };
-
-        let mut stream = self.docker.logs(container_id, Some(options));
-
-        let mut stdout = String::new();
-        let mut stderr = String::new();
-        let mut truncated = false;
-        let half_max = max_output / 2;
-
-        while let Some(result) = stream.next().await {
-            match result {
-                Ok(LogOutput::StdOut { message }) => {
-                    let text = String::from_utf8_lossy(&message);
-                    if stdout.len() + text.len() > half_max {
-                        truncated = true;
-                        let remaining = half_max.saturating_sub(stdout.len());
-                        stdout.push_str(&text[..remaining.min(text.len())]);
-                    } else {
-                        stdout.push_str(&text);
-                    }
-                }
-                Ok(LogOutput::StdErr { message }) => {
-                    let text = String::from_utf8_lossy(&message);
-                    if stderr.len() + text.len() > half_max {
-                        truncated = true;
-                        let remaining = half_max.saturating_sub(stderr.len());
-                        stderr.push_str(&text[..remaining.min(text.len())]);
-                    } else {
-                        stderr.push_str(&text);
-                    }
-                }
-                Ok(_) => {}
-                Err(e) => {
-                    tracing::warn!("Error reading container logs: {}", e);
-                }
-            }
+            image: self.image.clone(),
+            command: command.to_string(),
+            working_dir: "/workspace".to_string(),
+            env,
+            binds,
+            // Tmpfs mounts for /tmp and cargo cache
+            tmpfs: vec![
+                ("/tmp".to_string(), "size=512M".to_string()),
+                (
+                    "/home/sandbox/.cargo/registry".to_string(),
+                    "size=1G".to_string(),
+                ),
+            ],
+            // Read-only root filesystem (workspace is still writable if policy allows)
+            read_only_rootfs: policy == SandboxPolicy::ReadOnly,
+            user: "1000:1000".to_string(), // Non-root user
         }
-
-        Ok((stdout, stderr, truncated))
-    }
-
-    /// Run an exec and collect output.
-    async fn run_exec(&self, exec_id: &str, max_output: usize) -> Result<ContainerOutput> {
-        let start_result = self.docker.start_exec(exec_id, None).await.map_err(|e| {
-            SandboxError::ExecutionFailed {
-                reason: format!("exec start failed: {}", e),
-            }
-        })?;
-
-        let mut stdout = String::new();
-        let mut stderr = String::new();
-        let mut truncated = false;
-        let half_max = max_output / 2;
-
-        if let StartExecResults::Attached { mut output, .. } = start_result {
-            while let Some(result) = output.next().await {
-                match result {
-                    Ok(LogOutput::StdOut { message }) => {
-                        let text = String::from_utf8_lossy(&message);
-                        if stdout.len() < half_max {
-                            let remaining = half_max.saturating_sub(stdout.len());
-                            stdout.push_str(&text[..remaining.min(text.len())]);
-                            if text.len() > remaining {
-                                truncated = true;
-                            }
-                        }
-                    }
-                    Ok(LogOutput::StdErr { message }) => {
-                        let text = String::from_utf8_lossy(&message);
-                        if stderr.len() < half_max {
-                            let remaining = half_max.saturating_sub(stderr.len());
-                            stderr.push_str(&text[..remaining.min(text.len())]);
-                            if text.len() > remaining {
-                                truncated = true;
-                            }
-                        }
-                    }
-                    Ok(_) => {}
-                    Err(e) => {
-                        tracing::warn!("Error reading exec output: {}", e);
-                    }
-                }
-            }
-        }
-
-        // Get exec exit code
-        let inspect =
-            self.docker
-                .inspect_exec(exec_id)
-                .await
-                .map_err(|e| SandboxError::ExecutionFailed {
-                    reason: format!("exec inspect failed: {}", e),
-                })?;
-
-        let exit_code = inspect.exit_code.unwrap_or(-1);
-
-        Ok(ContainerOutput {
-            exit_code,
-            stdout,
-            stderr,
-            duration: Duration::ZERO,
-            truncated,
-        })
     }
 }
 
@@ -526,7 +263,11 @@ pub async fn connect_docker() -> Result<Docker> {
 
 #[cfg(test)]
 mod tests {
+    use async_trait::async_trait;
+
     use super::*;
+    use crate::sandbox::backend::DockerBackend;
+    use crate::sandbox::config::ContainerBackendKind;
 
     #[tokio::test]
     async fn test_docker_connection() {
@@ -538,9 +279,109 @@ mod tests {
             return;
         }
 
-        let docker = result.unwrap();
-        let runner = ContainerRunner::new(docker, "alpine:latest".to_string(), 0);
+        let backend = Arc::new(DockerBackend::docker(result.unwrap()));
+        let runner = ContainerRunner::new(backend, "alpine:latest".to_string(), 0);
         // Just check that we can query Docker (result doesn't matter for CI)
         let _available = runner.is_available().await;
     }
+
+    struct FakeBackend;
+
+    #[async_trait]
+    impl ContainerBackend for FakeBackend {
+        fn kind(&self) -> ContainerBackendKind {
+            ContainerBackendKind::Podman
+        }
+        fn host_gateway(&self) -> &str {
+            "host.containers.internal"
+        }
+        async fn ping(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn image_exists(&self, _image: &str) -> bool {
+            true
+        }
+        async fn pull_image(&self, _image: &str) -> Result<()> {
+            Ok(())
+        }
+        async fn run(
+            &self,
+            _spec: &ContainerSpec,
+            _limits: &ResourceLimits,
+        ) -> Result<ContainerOutput> {
+            unimplemented!()
+        }
+        async fn start(&self, _spec: &ContainerSpec, _limits: &ResourceLimits) -> Result<String> {
+            unimplemented!()
+        }
+        async fn remove(&self, _container_id: &str) {}
+        async fn exec(
+            &self,
+            _container_id: &str,
+            _command: &str,
+            _working_dir: &str,
+            _env: &[(String, String)],
+            _limits: &ResourceLimits,
+        ) -> Result<ContainerOutput> {
+            unimplemented!()
+        }
+        async fn attach(
+            &self,
+            _container_id: &str,
+            _command: &str,
+            _working_dir: &str,
+            _env: &[(String, String)],
+        ) -> Result<crate::sandbox::backend::AttachedProcess> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn test_spec_follows_policy_and_backend_gateway() {
+        let runner = ContainerRunner::new(Arc::new(FakeBackend), "img".to_string(), 3128);
+
+        let spec = runner.spec(
+            "ls",
+            Path::new("/src"),
+            SandboxPolicy::ReadOnly,
+            HashMap::new(),
+        );
+        assert!(spec.read_only_rootfs);
+        assert!(spec.binds[0].read_only);
+        assert!(spec.env.contains(&(
+            "HTTPS_PROXY".to_string(),
+            "http://host.containers.internal:3128".to_string()
+        )));
+
+        let spec = runner.spec(
+            "make",
+            Path::new("/src"),
+            SandboxPolicy::WorkspaceWrite,
+            HashMap::new(),
+        );
+        assert!(!spec.read_only_rootfs);
+        assert!(!spec.binds[0].read_only);
+    }
+
+    #[test]
+    fn test_spec_carries_grant_token_and_overrides_user_proxy() {
+        let runner = ContainerRunner::new(Arc::new(FakeBackend), "img".to_string(), 3128)
+            .with_proxy_token("tok");
+
+        let env = HashMap::from([("HTTP_PROXY".to_string(), "http://elsewhere:1".to_string())]);
+        let spec = runner.spec(
+            "pip install x",
+            Path::new("/src"),
+            SandboxPolicy::ReadOnly,
+            env,
+        );
+
+        let proxies: Vec<&str> = spec
+            .env
+            .iter()
+            .filter(|(k, _)| k == "HTTP_PROXY")
+            .map(|(_, v)| v.as_str())
+            .collect();
+        assert_eq!(proxies, ["http://tok@host.containers.internal:3128"]);
+    }
 }
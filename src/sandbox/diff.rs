@@ -0,0 +1,269 @@
+//! Workspace change capture for sandboxed commands.
+//!
+//! When an [`ExecPolicy`](crate::sandbox::ExecPolicy) asks for it, the
+//! manager snapshots the command's working directory before and after the
+//! run and reports what changed as a list of [`FileDiff`]s: created,
+//! modified and deleted files, with a unified diff for text files.
+//!
+//! Snapshots hash every file and keep the contents of small text files, so
+//! the cost grows with the workspace. Version control and build output
+//! directories (`.git`, `target`, `node_modules`) are skipped, symlinks are
+//! not followed, and a workspace with more than [`MAX_FILES`] files is only
+//! partly covered.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::sandbox::error::Result;
+
+/// Files recorded per snapshot.
+pub const MAX_FILES: usize = 20_000;
+
+/// Directories never walked.
+const SKIPPED_DIRS: [&str; 3] = [".git", "target", "node_modules"];
+
+/// Largest file whose text is kept for diffing.
+const MAX_TEXT_BYTES: u64 = 256 * 1024;
+
+/// Largest file that is hashed; bigger ones compare by size and mtime.
+const MAX_HASH_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Longest unified diff reported for one file.
+const MAX_DIFF_BYTES: usize = 16 * 1024;
+
+/// How a file changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChange {
+    Created,
+    Modified,
+    Deleted,
+}
+
+/// One changed file, relative to the working directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileDiff {
+    pub path: String,
+    pub change: FileChange,
+    /// Unified diff, for text files. `None` for binary or large files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct FileState {
+    len: u64,
+    modified: Option<SystemTime>,
+    hash: Option<[u8; 32]>,
+    text: Option<String>,
+}
+
+impl FileState {
+    fn read(path: &Path, metadata: &std::fs::Metadata) -> std::io::Result<Self> {
+        let len = metadata.len();
+        let (hash, text) = if len <= MAX_HASH_BYTES {
+            let bytes = std::fs::read(path)?;
+            let hash = Sha256::digest(&bytes).into();
+            let text = if len <= MAX_TEXT_BYTES && !bytes.contains(&0) {
+                String::from_utf8(bytes).ok()
+            } else {
+                None
+            };
+            (Some(hash), text)
+        } else {
+            (None, None)
+        };
+        Ok(Self {
+            len,
+            modified: metadata.modified().ok(),
+            hash,
+            text,
+        })
+    }
+
+    fn same_as(&self, other: &Self) -> bool {
+        match (self.hash, other.hash) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.len == other.len && self.modified == other.modified,
+        }
+    }
+}
+
+/// The files under a directory at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceSnapshot {
+    files: BTreeMap<String, FileState>,
+    truncated: bool,
+}
+
+impl WorkspaceSnapshot {
+    /// Snapshot `root` on a blocking thread.
+    pub async fn capture(root: &Path) -> Result<Self> {
+        let root = root.to_path_buf();
+        tokio::task::spawn_blocking(move || Self::capture_blocking(&root))
+            .await
+            .map_err(|e| std::io::Error::other(e.to_string()))?
+    }
+
+    fn capture_blocking(root: &Path) -> Result<Self> {
+        let mut snapshot = Self::default();
+        let mut pending: Vec<PathBuf> = vec![root.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let mut entries = std::fs::read_dir(&dir)?.collect::<std::io::Result<Vec<_>>>()?;
+            entries.sort_by_key(|e| e.file_name());
+            for entry in entries {
+                let path = entry.path();
+                // Files can vanish mid-walk; skip them rather than fail.
+                let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+                    continue;
+                };
+                if metadata.is_dir() {
+                    let name = entry.file_name();
+                    if !SKIPPED_DIRS.iter().any(|d| name == *d) {
+                        pending.push(path);
+                    }
+                    continue;
+                }
+                if !metadata.is_file() {
+                    continue;
+                }
+                if snapshot.files.len() >= MAX_FILES {
+                    snapshot.truncated = true;
+                    return Ok(snapshot);
+                }
+                let Ok(state) = FileState::read(&path, &metadata) else {
+                    continue;
+                };
+                let relative = path
+                    .strip_prefix(root)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                snapshot.files.insert(relative, state);
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Whether the file limit was hit, so some changes may be missing.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// What changed between this snapshot and `after`, sorted by path.
+    pub fn changes_to(&self, after: &WorkspaceSnapshot) -> Vec<FileDiff> {
+        let mut changes = Vec::new();
+        for (path, old) in &self.files {
+            match after.files.get(path) {
+                None => changes.push(FileDiff {
+                    path: path.clone(),
+                    change: FileChange::Deleted,
+                    diff: old
+                        .text
+                        .as_deref()
+                        .map(|text| unified_diff(path, Some(text), None)),
+                }),
+                Some(new) if !old.same_as(new) => changes.push(FileDiff {
+                    path: path.clone(),
+                    change: FileChange::Modified,
+                    diff: match (&old.text, &new.text) {
+                        (Some(a), Some(b)) => Some(unified_diff(path, Some(a), Some(b))),
+                        _ => None,
+                    },
+                }),
+                Some(_) => {}
+            }
+        }
+        for (path, new) in &after.files {
+            if !self.files.contains_key(path) {
+                changes.push(FileDiff {
+                    path: path.clone(),
+                    change: FileChange::Created,
+                    diff: new
+                        .text
+                        .as_deref()
+                        .map(|text| unified_diff(path, None, Some(text))),
+                });
+            }
+        }
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        changes
+    }
+}
+
+/// A unified diff with three lines of context, cut at [`MAX_DIFF_BYTES`].
+fn unified_diff(path: &str, old: Option<&str>, new: Option<&str>) -> String {
+    let old_header = old.map_or_else(|| "/dev/null".to_string(), |_| format!("a/{}", path));
+    let new_header = new.map_or_else(|| "/dev/null".to_string(), |_| format!("b/{}", path));
+    let mut diff = similar::TextDiff::from_lines(old.unwrap_or(""), new.unwrap_or(""))
+        .unified_diff()
+        .context_radius(3)
+        .header(&old_header, &new_header)
+        .to_string();
+    if diff.len() > MAX_DIFF_BYTES {
+        diff.truncate(crate::util::floor_char_boundary(&diff, MAX_DIFF_BYTES));
+        diff.push_str("\n... (diff truncated)\n");
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_changes_between_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("keep.txt"), "same\n").unwrap();
+        std::fs::write(root.join("edit.txt"), "one\ntwo\nthree\n").unwrap();
+        std::fs::write(root.join("gone.txt"), "bye\n").unwrap();
+        std::fs::create_dir(root.join(".git")).unwrap();
+        std::fs::write(root.join(".git/HEAD"), "ref: main\n").unwrap();
+
+        let before = WorkspaceSnapshot::capture(root).await.unwrap();
+
+        std::fs::write(root.join("edit.txt"), "one\n2\nthree\n").unwrap();
+        std::fs::remove_file(root.join("gone.txt")).unwrap();
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/new.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(root.join("blob.bin"), [0u8, 159, 146, 150]).unwrap();
+        std::fs::write(root.join(".git/HEAD"), "ref: other\n").unwrap();
+
+        let after = WorkspaceSnapshot::capture(root).await.unwrap();
+        let changes = before.changes_to(&after);
+
+        let summary: Vec<(&str, FileChange)> = changes
+            .iter()
+            .map(|c| (c.path.as_str(), c.change))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("blob.bin", FileChange::Created),
+                ("edit.txt", FileChange::Modified),
+                ("gone.txt", FileChange::Deleted),
+                ("src/new.rs", FileChange::Created),
+            ]
+        );
+
+        assert!(changes[0].diff.is_none(), "binary files have no diff");
+        let edit = changes[1].diff.as_deref().unwrap();
+        assert!(edit.starts_with("--- a/edit.txt\n+++ b/edit.txt\n"));
+        assert!(edit.contains("-two\n+2\n"));
+        let created = changes[3].diff.as_deref().unwrap();
+        assert!(created.starts_with("--- /dev/null\n+++ b/src/new.rs\n"));
+    }
+
+    #[test]
+    fn test_long_diff_is_truncated() {
+        let new = "line\n".repeat(10_000);
+        let diff = unified_diff("big.txt", None, Some(&new));
+        assert!(diff.len() < MAX_DIFF_BYTES + 64);
+        assert!(diff.ends_with("... (diff truncated)\n"));
+    }
+}
@@ -9,6 +9,10 @@ pub enum SandboxError {
     #[error("Docker not available: {reason}")]
     DockerNotAvailable { reason: String },
 
+    /// The configured container backend (Podman, containerd) is not usable.
+    #[error("{backend} not available: {reason}")]
+    BackendNotAvailable { backend: String, reason: String },
+
     /// Failed to create container.
     #[error("Container creation failed: {reason}")]
     ContainerCreationFailed { reason: String },
@@ -41,6 +45,10 @@ pub enum SandboxError {
     #[error("Credential injection failed for {domain}: {reason}")]
     CredentialInjectionFailed { domain: String, reason: String },
 
+    /// An ephemeral credential issuer could not mint a credential.
+    #[error("Credential issuer {issuer} failed: {reason}")]
+    CredentialIssueFailed { issuer: String, reason: String },
+
     /// Docker API error.
     #[error("Docker API error: {0}")]
     Docker(#[from] bollard::errors::Error),
@@ -52,6 +60,14 @@ pub enum SandboxError {
     /// Configuration error.
     #[error("Configuration error: {reason}")]
     Config { reason: String },
+
+    /// Host fallback execution refused (not acknowledged or not allowed).
+    #[error("Host execution refused: {reason}")]
+    HostFallbackDenied { reason: String },
+
+    /// Interactive session closed, idled out, or never existed.
+    #[error("Sandbox session {id} is closed")]
+    SessionClosed { id: uuid::Uuid },
 }
 
 /// Result type for sandbox operations.
@@ -0,0 +1,482 @@
+//! Degraded host-side execution for machines without Docker.
+//!
+//! With `SANDBOX_HOST_FALLBACK=true` and no reachable Docker daemon, commands
+//! that would otherwise need a container run directly on the host under a
+//! restricted policy instead of failing outright:
+//!
+//! | Restriction     | Enforcement                                              |
+//! |-----------------|----------------------------------------------------------|
+//! | Programs        | first word of every pipeline segment is allowlisted;     |
+//! |                 | arguments that run code (`-c`, `-e`, `-exec`, ...) are   |
+//! |                 | refused                                                  |
+//! | Paths           | working dir and absolute path arguments stay under the   |
+//! |                 | allowed roots; `..` components are refused               |
+//! | Shell features  | no command substitution, redirection or env assignments  |
+//! | Environment     | cleared except `PATH`, `HOME` and `LANG`                 |
+//! | Consent         | nothing runs until the user acknowledges the risk in     |
+//! |                 | the conversation (`/sandbox accept-risk`); the           |
+//! |                 | acknowledgement ends with the session                    |
+//!
+//! There is no network or resource isolation, which is why each run is
+//! appended to its own audit log, tagged `host_fallback`, before it starts.
+//! An unauditable run is refused.
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::sandbox::container::connect_docker;
+use crate::sandbox::error::{Result, SandboxError};
+use crate::sandbox::manager::ExecOutput;
+
+/// Shown to the user before they accept host execution.
+pub const RISK_BANNER: &str = "WARNING: Docker is not available, so sandboxed commands would \
+     run directly on this machine instead of in a container. Only allowlisted programs may \
+     run, only inside the allowed directories and with a scrubbed environment, but there is \
+     no network or resource isolation. Every command is recorded in the sandbox fallback \
+     audit log. Send /sandbox accept-risk to allow this for the current session.";
+
+/// Arguments that make an allowlisted program run other code: `git -c`
+/// (aliases, pagers), `grep`/`sed` style `-e`, `find -exec`, and long
+/// options that name a program to start.
+const CODE_ARGS: &[&str] = &["-c", "-e", "-exec", "-execdir", "-ok", "-okdir"];
+const CODE_ARG_PREFIXES: &[&str] = &[
+    "--exec",
+    "--upload-pack",
+    "--receive-pack",
+    "--config",
+    "--compress-program",
+];
+
+/// Programs allowed by default: file inspection and version control. None
+/// of them interprets its arguments as code.
+pub fn default_fallback_bins() -> Vec<String> {
+    [
+        "ls", "cat", "head", "tail", "wc", "grep", "sort", "uniq", "diff", "echo", "pwd", "mkdir",
+        "touch", "cp", "mv", "git",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+/// Default audit log location (`~/.ironclaw/sandbox_fallback_audit.jsonl`).
+pub fn default_audit_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".ironclaw")
+        .join("sandbox_fallback_audit.jsonl")
+}
+
+/// Whether a Docker daemon answers.
+pub async fn docker_available() -> bool {
+    match connect_docker().await {
+        Ok(docker) => docker.ping().await.is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// What host-side commands may do.
+#[derive(Debug, Clone)]
+pub struct HostFallbackPolicy {
+    /// Program names (not paths) that may be run.
+    pub allowed_bins: Vec<String>,
+    /// Directories commands may run in and refer to.
+    pub allowed_roots: Vec<PathBuf>,
+}
+
+impl HostFallbackPolicy {
+    /// Check every segment of a (possibly piped or chained) command.
+    pub fn check_command(&self, command: &str) -> std::result::Result<(), String> {
+        for pattern in ["`", "$(", "${", ">", "<"] {
+            if command.contains(pattern) {
+                return Err(format!(
+                    "'{}' (substitution or redirection) is not allowed outside the sandbox",
+                    pattern
+                ));
+            }
+        }
+
+        let segments = command
+            .split(['\n', ';', '|', '&'])
+            .map(str::trim)
+            .filter(|s| !s.is_empty());
+        for segment in segments {
+            let mut words = segment.split_whitespace();
+            let Some(program) = words.next() else {
+                continue;
+            };
+            if program.contains('=') {
+                return Err(format!(
+                    "environment assignment '{}' is not allowed outside the sandbox",
+                    program
+                ));
+            }
+            if !self.allowed_bins.iter().any(|b| b == program) {
+                return Err(format!(
+                    "'{}' is not in the host fallback allowlist ({})",
+                    program,
+                    self.allowed_bins.join(", ")
+                ));
+            }
+            for arg in words {
+                let arg = arg.trim_matches(|c| c == '"' || c == '\'');
+                if CODE_ARGS.contains(&arg) || CODE_ARG_PREFIXES.iter().any(|p| arg.starts_with(p))
+                {
+                    return Err(format!(
+                        "'{} {}' can run other programs and is not allowed outside the sandbox",
+                        program, arg
+                    ));
+                }
+                if Path::new(arg)
+                    .components()
+                    .any(|c| c == Component::ParentDir)
+                {
+                    return Err(format!("'..' in '{}' is not allowed", arg));
+                }
+                if arg.starts_with('~') {
+                    return Err(format!("home-relative path '{}' is not allowed", arg));
+                }
+                if arg.starts_with('/') && !self.is_within_roots(Path::new(arg)) {
+                    return Err(format!("'{}' is outside the allowed directories", arg));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve `dir` and check that it lies under an allowed root.
+    pub fn check_dir(&self, dir: &Path) -> std::result::Result<PathBuf, String> {
+        let resolved = dir
+            .canonicalize()
+            .map_err(|e| format!("cannot use working directory {}: {}", dir.display(), e))?;
+        if self.is_within_roots(&resolved) {
+            Ok(resolved)
+        } else {
+            Err(format!(
+                "working directory {} is outside the allowed directories",
+                resolved.display()
+            ))
+        }
+    }
+
+    fn is_within_roots(&self, path: &Path) -> bool {
+        self.allowed_roots.iter().any(|root| {
+            let root = root.canonicalize().unwrap_or_else(|_| root.clone());
+            path.starts_with(root)
+        })
+    }
+}
+
+/// One line of the fallback audit log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FallbackAuditRecord {
+    pub at: DateTime<Utc>,
+    /// Always `host_fallback`, so records stand out when logs are merged.
+    pub mode: String,
+    pub user_id: String,
+    /// Conversation the command ran in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<Uuid>,
+    pub command: String,
+    pub cwd: String,
+    /// `started`, `finished`, or `refused`.
+    pub event: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Restricted host execution plus per-conversation risk acknowledgements.
+pub struct HostFallback {
+    policy: HostFallbackPolicy,
+    audit_path: PathBuf,
+    /// Threads whose user accepted the risk.
+    acknowledged: Mutex<HashSet<Uuid>>,
+}
+
+impl HostFallback {
+    pub fn new(policy: HostFallbackPolicy, audit_path: PathBuf) -> Self {
+        Self {
+            policy,
+            audit_path,
+            acknowledged: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn policy(&self) -> &HostFallbackPolicy {
+        &self.policy
+    }
+
+    /// Record that `user_id` accepted the risk in thread `thread_id`.
+    pub fn acknowledge(&self, thread_id: Uuid, user_id: &str) {
+        self.lock().insert(thread_id);
+        tracing::warn!(user_id, %thread_id, "Host fallback execution acknowledged");
+    }
+
+    /// Withdraw an acknowledgement.
+    pub fn revoke(&self, thread_id: Uuid) -> bool {
+        self.lock().remove(&thread_id)
+    }
+
+    pub fn is_acknowledged(&self, thread_id: Uuid) -> bool {
+        self.lock().contains(&thread_id)
+    }
+
+    /// Drop the acknowledgements of threads that no longer exist, e.g. after
+    /// idle sessions were pruned.
+    pub fn retain_threads(&self, live: &HashSet<Uuid>) {
+        self.lock().retain(|thread_id| live.contains(thread_id));
+    }
+
+    /// Run `command` on the host for `user_id` if the risk was acknowledged
+    /// in `thread_id` and the policy allows it. Calls outside a
+    /// conversation (jobs, routines) are refused.
+    pub async fn execute(
+        &self,
+        user_id: &str,
+        thread_id: Option<Uuid>,
+        command: &str,
+        cwd: &Path,
+        timeout: Duration,
+    ) -> Result<ExecOutput> {
+        let record =
+            |event: &str, exit_code: Option<i64>, detail: Option<String>| FallbackAuditRecord {
+                at: Utc::now(),
+                mode: "host_fallback".to_string(),
+                user_id: user_id.to_string(),
+                thread_id,
+                command: command.to_string(),
+                cwd: cwd.display().to_string(),
+                event: event.to_string(),
+                exit_code,
+                detail,
+            };
+
+        if !thread_id.is_some_and(|id| self.is_acknowledged(id)) {
+            return Err(SandboxError::HostFallbackDenied {
+                reason: RISK_BANNER.to_string(),
+            });
+        }
+        let checked = self
+            .policy
+            .check_command(command)
+            .and_then(|()| self.policy.check_dir(cwd));
+        let cwd = match checked {
+            Ok(cwd) => cwd,
+            Err(reason) => {
+                let _ = self.append_audit(&record("refused", None, Some(reason.clone())));
+                return Err(SandboxError::HostFallbackDenied { reason });
+            }
+        };
+
+        // An unauditable run is not allowed to go through.
+        self.append_audit(&record("started", None, None))?;
+        tracing::warn!(
+            user_id,
+            command,
+            "Running command on host (sandbox fallback)"
+        );
+
+        let result = run_scrubbed(command, &cwd, timeout).await;
+        let finished = match result {
+            Ok(ref output) => record("finished", Some(output.exit_code), None),
+            Err(ref e) => record("finished", None, Some(e.to_string())),
+        };
+        if let Err(e) = self.append_audit(&finished) {
+            tracing::error!("Failed to write sandbox fallback audit log: {}", e);
+        }
+        result
+    }
+
+    /// Every record in the audit log, oldest first.
+    pub fn audit_log(&self) -> Result<Vec<FallbackAuditRecord>> {
+        let content = match std::fs::read_to_string(&self.audit_path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    fn append_audit(&self, record: &FallbackAuditRecord) -> Result<()> {
+        if let Some(parent) = self.audit_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut line = serde_json::to_string(record).map_err(std::io::Error::other)?;
+        line.push('\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.audit_path)?
+            .write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashSet<Uuid>> {
+        self.acknowledged
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Run through `sh -c` with only `PATH`, `HOME` and `LANG` set.
+async fn run_scrubbed(command: &str, cwd: &Path, timeout: Duration) -> Result<ExecOutput> {
+    let start = std::time::Instant::now();
+    let mut cmd = tokio::process::Command::new("sh");
+    cmd.args(["-c", command])
+        .current_dir(cwd)
+        .env_clear()
+        .env("HOME", cwd)
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true);
+    for key in ["PATH", "LANG"] {
+        if let Ok(value) = std::env::var(key) {
+            cmd.env(key, value);
+        }
+    }
+
+    let output = tokio::time::timeout(timeout, cmd.output())
+        .await
+        .map_err(|_| SandboxError::Timeout(timeout))?
+        .map_err(|e| SandboxError::ExecutionFailed {
+            reason: e.to_string(),
+        })?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    let combined = if stderr.is_empty() {
+        stdout.clone()
+    } else if stdout.is_empty() {
+        stderr.clone()
+    } else {
+        format!("{}\n\n--- stderr ---\n{}", stdout, stderr)
+    };
+    Ok(ExecOutput {
+        exit_code: output.status.code().unwrap_or(-1) as i64,
+        stdout,
+        stderr,
+        output: combined,
+        duration: start.elapsed(),
+        truncated: false,
+        changes: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(root: &Path) -> HostFallbackPolicy {
+        HostFallbackPolicy {
+            allowed_bins: vec!["echo".into(), "ls".into(), "wc".into()],
+            allowed_roots: vec![root.to_path_buf()],
+        }
+    }
+
+    #[test]
+    fn test_policy_rejects_unlisted_programs_and_escapes() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = policy(dir.path());
+        let inside = dir.path().join("a.txt").display().to_string();
+
+        assert!(policy.check_command("ls -la | wc -l && echo done").is_ok());
+        assert!(policy.check_command(&format!("wc -l {inside}")).is_ok());
+        assert!(policy.check_command("ls; curl https://x.example").is_err());
+        assert!(policy.check_command("/bin/ls").is_err());
+        assert!(policy.check_command("echo $(id)").is_err());
+        assert!(policy.check_command("echo hi > out.txt").is_err());
+        assert!(policy.check_command("PATH=/tmp ls").is_err());
+        assert!(policy.check_command("ls ../..").is_err());
+        assert!(policy.check_command("ls /etc").is_err());
+        assert!(policy.check_command("echo -e hi").is_err());
+        assert!(policy.check_command("ls --exec=sh").is_err());
+
+        assert!(policy.check_dir(dir.path()).is_ok());
+        assert!(policy.check_dir(Path::new("/")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_execute_requires_acknowledgement_and_audits() {
+        let dir = tempfile::tempdir().unwrap();
+        let fallback = HostFallback::new(policy(dir.path()), dir.path().join("audit.jsonl"));
+        let timeout = Duration::from_secs(10);
+        let thread = Uuid::new_v4();
+
+        let err = fallback
+            .execute("alice", Some(thread), "echo hi", dir.path(), timeout)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("accept-risk"));
+
+        fallback.acknowledge(thread, "alice");
+        let output = fallback
+            .execute("alice", Some(thread), "echo hi", dir.path(), timeout)
+            .await
+            .unwrap();
+        assert_eq!(output.stdout.trim(), "hi");
+        assert!(
+            fallback
+                .execute("alice", Some(thread), "rm -r x", dir.path(), timeout)
+                .await
+                .is_err()
+        );
+        // Acknowledgements belong to one conversation; jobs have none.
+        assert!(!fallback.is_acknowledged(Uuid::new_v4()));
+        let err = fallback
+            .execute("alice", None, "echo hi", dir.path(), timeout)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("accept-risk"));
+
+        let log = fallback.audit_log().unwrap();
+        assert_eq!(
+            log.iter().map(|r| r.event.as_str()).collect::<Vec<_>>(),
+            vec!["started", "finished", "refused"]
+        );
+        assert!(log.iter().all(|r| r.mode == "host_fallback"));
+        assert_eq!(log[1].exit_code, Some(0));
+        assert_eq!(log[0].thread_id, Some(thread));
+
+        // They end with the session.
+        fallback.retain_threads(&HashSet::new());
+        assert!(!fallback.is_acknowledged(thread));
+    }
+
+    #[test]
+    fn test_default_bins_do_not_interpret_code() {
+        for bin in ["python3", "node", "npm", "make", "cargo", "find", "sh"] {
+            assert!(
+                !default_fallback_bins().contains(&bin.to_string()),
+                "{}",
+                bin
+            );
+        }
+        let policy = HostFallbackPolicy {
+            allowed_bins: default_fallback_bins(),
+            allowed_roots: Vec::new(),
+        };
+        assert!(policy.check_command("git status").is_ok());
+        assert!(policy.check_command("git -c core.pager=sh log").is_err());
+        assert!(
+            policy
+                .check_command("git fetch --upload-pack=touch")
+                .is_err()
+        );
+        assert!(
+            policy
+                .check_command("sort --compress-program=sh a.txt")
+                .is_err()
+        );
+    }
+}
@@ -2,10 +2,19 @@
 //!
 //! The `SandboxManager` is the primary entry point for sandboxed execution.
 //! It coordinates:
-//! - Docker container creation and lifecycle
+//! - Container creation and lifecycle on the configured backend
 //! - HTTP proxy for network access control
 //! - Credential injection for API calls
-//! - Resource limits and timeouts
+//! - Resource limits and timeouts, plus optional process, disk and tmpfs
+//!   quotas and GPU passthrough
+//! - An optional [`WarmPool`] of pre-started containers (`warm_pool_size`)
+//! - Interactive [`SandboxSession`]s, closed after `session_idle_timeout`
+//! - Per-command network overrides through [`ExecPolicy`], audited in
+//!   `network_grant_audit_path`
+//! - An optional [`EgressLog`] of every request sandboxed commands make
+//! - Optional [`EphemeralCredentials`], minted per job and injected by the
+//!   proxy in place of long-lived secrets
+//! - Workspace change capture ([`FileDiff`]s) when the [`ExecPolicy`] asks
 //!
 //! # Architecture
 //!
@@ -35,10 +44,22 @@ use std::time::Duration;
 
 use tokio::sync::RwLock;
 
-use crate::sandbox::config::{ResourceLimits, SandboxConfig, SandboxPolicy};
-use crate::sandbox::container::{ContainerOutput, ContainerRunner, connect_docker};
+use crate::sandbox::backend::{backend_available, connect_backend};
+use crate::sandbox::config::{
+    ContainerBackendKind, GpuAccess, ResourceLimits, SandboxConfig, SandboxPolicy,
+};
+use crate::sandbox::container::{ContainerOutput, ContainerRunner};
+use crate::sandbox::diff::{FileDiff, WorkspaceSnapshot};
 use crate::sandbox::error::{Result, SandboxError};
-use crate::sandbox::proxy::{HttpProxy, NetworkProxyBuilder};
+use crate::sandbox::pool::{PoolStats, WarmPool};
+use crate::sandbox::proxy::{
+    EgressLog, EphemeralCredentials, ExecPolicy, HttpProxy, NetworkGrants, NetworkProxyBuilder,
+    PackageCache,
+};
+use crate::sandbox::session::SandboxSession;
+
+/// How often idle interactive sessions are looked for.
+const SESSION_SWEEP_INTERVAL: Duration = Duration::from_secs(30);
 
 /// Output from sandbox execution.
 #[derive(Debug, Clone)]
@@ -55,6 +76,9 @@ pub struct ExecOutput {
     pub duration: Duration,
     /// Whether output was truncated.
     pub truncated: bool,
+    /// Files the command created, modified or deleted under its working
+    /// directory, when the [`ExecPolicy`] asked for them.
+    pub changes: Option<Vec<FileDiff>>,
 }
 
 impl From<ContainerOutput> for ExecOutput {
@@ -74,6 +98,7 @@ impl From<ContainerOutput> for ExecOutput {
             output,
             duration: c.duration,
             truncated: c.truncated,
+            changes: None,
         }
     }
 }
@@ -83,38 +108,62 @@ pub struct SandboxManager {
     config: SandboxConfig,
     proxy: Arc<RwLock<Option<HttpProxy>>>,
     runner: Arc<RwLock<Option<ContainerRunner>>>,
+    pool: Arc<RwLock<Option<Arc<WarmPool>>>>,
+    sessions: Arc<RwLock<HashMap<uuid::Uuid, Arc<SandboxSession>>>>,
+    session_sweeper: std::sync::Mutex<Option<tokio::task::JoinHandle<()>>>,
+    grants: Arc<NetworkGrants>,
+    egress: Option<EgressLog>,
+    ephemeral: Option<Arc<EphemeralCredentials>>,
     initialized: std::sync::atomic::AtomicBool,
 }
 
 impl SandboxManager {
     /// Create a new sandbox manager.
     pub fn new(config: SandboxConfig) -> Self {
+        let grants = Arc::new(NetworkGrants::new(config.network_grant_audit_path.clone()));
         Self {
             config,
             proxy: Arc::new(RwLock::new(None)),
             runner: Arc::new(RwLock::new(None)),
+            pool: Arc::new(RwLock::new(None)),
+            sessions: Arc::new(RwLock::new(HashMap::new())),
+            session_sweeper: std::sync::Mutex::new(None),
+            grants,
+            egress: None,
+            ephemeral: None,
             initialized: std::sync::atomic::AtomicBool::new(false),
         }
     }
 
+    /// Record every request sandboxed commands make through the proxy.
+    /// Must be set before the manager is initialized.
+    pub fn with_egress_log(mut self, log: EgressLog) -> Self {
+        self.egress = Some(log);
+        self
+    }
+
+    /// Inject per-job credentials from `credentials` into the requests of
+    /// commands run for a job. Must be set before the manager is initialized.
+    pub fn with_ephemeral_credentials(mut self, credentials: Arc<EphemeralCredentials>) -> Self {
+        self.ephemeral = Some(credentials);
+        self
+    }
+
     /// Create with default configuration.
     pub fn with_defaults() -> Self {
         Self::new(SandboxConfig::default())
     }
 
-    /// Check if the sandbox is available (Docker running, etc.).
+    /// Check if the sandbox is available (container runtime running, etc.).
     pub async fn is_available(&self) -> bool {
         if !self.config.enabled {
             return false;
         }
 
-        match connect_docker().await {
-            Ok(docker) => docker.ping().await.is_ok(),
-            Err(_) => false,
-        }
+        backend_available(self.config.backend).await
     }
 
-    /// Initialize the sandbox (connect to Docker, start proxy).
+    /// Initialize the sandbox (connect to the container backend, start proxy).
     pub async fn initialize(&self) -> Result<()> {
         if self.initialized.load(std::sync::atomic::Ordering::SeqCst) {
             return Ok(());
@@ -126,20 +175,12 @@ impl SandboxManager {
             });
         }
 
-        // Connect to Docker
-        let docker = connect_docker().await?;
-
-        // Check if Docker is responsive
-        docker
-            .ping()
-            .await
-            .map_err(|e| SandboxError::DockerNotAvailable {
-                reason: e.to_string(),
-            })?;
+        // Connect to the container backend (checks it is responsive)
+        let backend = connect_backend(self.config.backend).await?;
 
         // Create container runner
         let runner =
-            ContainerRunner::new(docker, self.config.image.clone(), self.config.proxy_port);
+            ContainerRunner::new(backend, self.config.image.clone(), self.config.proxy_port);
 
         // Check for / pull image
         if !runner.image_exists().await {
@@ -159,13 +200,43 @@ impl SandboxManager {
 
         // Start the network proxy if we're using a sandboxed policy
         if self.config.policy.is_sandboxed() {
-            let proxy = NetworkProxyBuilder::from_config(&self.config)
-                .build_and_start(self.config.proxy_port)
-                .await?;
+            let mut builder = NetworkProxyBuilder::from_config(&self.config)
+                .with_grants(Arc::clone(&self.grants));
+            if let Some(log) = &self.egress {
+                builder = builder.with_egress_log(log.clone());
+            }
+            if let Some(credentials) = &self.ephemeral {
+                builder = builder.with_ephemeral_credentials(Arc::clone(credentials));
+            }
+            if let Some(dir) = &self.config.package_cache_dir {
+                match PackageCache::open(dir, self.config.package_cache_max_bytes).await {
+                    Ok(cache) => {
+                        tracing::info!("Sandbox package cache at {}", dir.display());
+                        builder = builder.with_package_cache(Arc::new(cache));
+                    }
+                    Err(e) => tracing::warn!("Sandbox package cache disabled: {}", e),
+                }
+            }
+            let proxy = builder.build_and_start(self.config.proxy_port).await?;
 
             *self.proxy.write().await = Some(proxy);
         }
 
+        if self.config.warm_pool_size > 0 {
+            let proxy_port = self.proxy_port().await.unwrap_or(0);
+            let backend = match self.runner.read().await.as_ref() {
+                Some(runner) => runner.backend().clone(),
+                None => connect_backend(self.config.backend).await?,
+            };
+            let runner = ContainerRunner::new(backend, self.config.image.clone(), proxy_port);
+            *self.pool.write().await =
+                Some(Arc::new(WarmPool::new(runner, self.config.warm_pool_size)));
+            tracing::info!(
+                "Sandbox warm pool enabled ({} containers per workspace)",
+                self.config.warm_pool_size
+            );
+        }
+
         self.initialized
             .store(true, std::sync::atomic::Ordering::SeqCst);
 
@@ -175,6 +246,15 @@ impl SandboxManager {
 
     /// Shutdown the sandbox (stop proxy, clean up).
     pub async fn shutdown(&self) {
+        if let Some(sweeper) = self.session_sweeper.lock().ok().and_then(|mut s| s.take()) {
+            sweeper.abort();
+        }
+        for (_, session) in self.sessions.write().await.drain() {
+            session.close().await;
+        }
+        if let Some(pool) = self.pool.write().await.take() {
+            pool.drain().await;
+        }
         if let Some(proxy) = self.proxy.write().await.take() {
             proxy.stop().await;
         }
@@ -204,6 +284,63 @@ impl SandboxManager {
         policy: SandboxPolicy,
         env: HashMap<String, String>,
     ) -> Result<ExecOutput> {
+        self.execute_with_exec_policy(command, cwd, &ExecPolicy::new(policy), env)
+            .await
+    }
+
+    /// Execute a command under `exec`, whose extra allowed and denied
+    /// domains apply to this command only. The grant is recorded in the
+    /// audit log before the command starts and revoked when it ends.
+    ///
+    /// With `exec.capture_diff`, `cwd` is snapshotted before and after the
+    /// run and the output lists the changed files. `ReadOnly` commands
+    /// cannot change anything, so nothing is captured for them.
+    pub async fn execute_with_exec_policy(
+        &self,
+        command: &str,
+        cwd: &Path,
+        exec: &ExecPolicy,
+        env: HashMap<String, String>,
+    ) -> Result<ExecOutput> {
+        if !exec.capture_diff || exec.policy == SandboxPolicy::ReadOnly {
+            return self.run(command, cwd, exec, env).await;
+        }
+
+        let before = match WorkspaceSnapshot::capture(cwd).await {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                tracing::warn!("Not capturing changes in {}: {}", cwd.display(), e);
+                None
+            }
+        };
+        let mut output = self.run(command, cwd, exec, env).await?;
+        if let Some(before) = before {
+            match WorkspaceSnapshot::capture(cwd).await {
+                Ok(after) => {
+                    if before.is_truncated() || after.is_truncated() {
+                        tracing::warn!(
+                            "{} has more than {} files; some changes may be missing",
+                            cwd.display(),
+                            crate::sandbox::diff::MAX_FILES
+                        );
+                    }
+                    output.changes = Some(before.changes_to(&after));
+                }
+                Err(e) => tracing::warn!("Not capturing changes in {}: {}", cwd.display(), e),
+            }
+        }
+        Ok(output)
+    }
+
+    async fn run(
+        &self,
+        command: &str,
+        cwd: &Path,
+        exec: &ExecPolicy,
+        env: HashMap<String, String>,
+    ) -> Result<ExecOutput> {
+        let policy = exec.policy;
+
         // FullAccess policy bypasses the sandbox entirely
         if policy == SandboxPolicy::FullAccess {
             return self.execute_direct(command, cwd, env).await;
@@ -214,27 +351,162 @@ impl SandboxManager {
             self.initialize().await?;
         }
 
-        // Get proxy port if running
-        let proxy_port = if let Some(proxy) = self.proxy.read().await.as_ref() {
-            proxy.addr().await.map(|a| a.port()).unwrap_or(0)
-        } else {
-            0
-        };
+        let limits = self.limits();
+        let grant = self.grants.register(command, exec)?;
+        let mut runner = self.current_runner().await?;
+        if let Some(grant) = &grant {
+            runner = runner.with_proxy_token(grant.token());
+        }
+
+        // Reuse a warm container when pooling is on. Pooled containers were
+        // started without the token, so it goes in with the exec.
+        let pool = self.pool.read().await.clone();
+        if let Some(pool) = pool {
+            let mut env = env;
+            if grant.is_some() {
+                env.extend(runner.proxy_env(policy));
+            }
+            let output = pool.execute(command, cwd, policy, &limits, env).await?;
+            return Ok(output.into());
+        }
 
-        // Create a runner with the current proxy port
-        let docker = connect_docker().await?;
-        let runner = ContainerRunner::new(docker, self.config.image.clone(), proxy_port);
+        let container_output = runner.execute(command, cwd, policy, &limits, env).await?;
 
-        let limits = ResourceLimits {
+        Ok(container_output.into())
+    }
+
+    /// Per-command network grants and their audit log.
+    pub fn network_grants(&self) -> &Arc<NetworkGrants> {
+        &self.grants
+    }
+
+    /// Resource limits for one command from the configuration.
+    fn limits(&self) -> ResourceLimits {
+        ResourceLimits {
             memory_bytes: self.config.memory_limit_mb * 1024 * 1024,
             cpu_shares: self.config.cpu_shares,
             timeout: self.config.timeout,
             max_output_bytes: 64 * 1024,
+            pids_limit: self.config.pids_limit,
+            disk_quota_bytes: self.config.disk_quota_mb.map(|mb| mb * 1024 * 1024),
+            tmpfs_bytes: self.config.tmpfs_size_mb.map(|mb| mb * 1024 * 1024),
+            gpus: self.config.gpus.clone(),
+        }
+    }
+
+    /// A runner with the current proxy port, reusing the backend connection
+    /// made at initialization.
+    async fn current_runner(&self) -> Result<ContainerRunner> {
+        let proxy_port = self.proxy_port().await.unwrap_or(0);
+        let backend = match self.runner.read().await.as_ref() {
+            Some(runner) => runner.backend().clone(),
+            None => connect_backend(self.config.backend).await?,
         };
+        Ok(ContainerRunner::new(
+            backend,
+            self.config.image.clone(),
+            proxy_port,
+        ))
+    }
 
-        let container_output = runner.execute(command, cwd, policy, &limits, env).await?;
+    /// Start an interactive session running `command` in its own container,
+    /// isolated like [`execute_with_policy`](Self::execute_with_policy)
+    /// (network through the proxy, credentials injected there). The
+    /// `FullAccess` policy is refused: a session always runs in a container.
+    pub async fn start_session(
+        &self,
+        command: &str,
+        cwd: &Path,
+        policy: SandboxPolicy,
+        env: HashMap<String, String>,
+    ) -> Result<Arc<SandboxSession>> {
+        if !policy.is_sandboxed() {
+            return Err(SandboxError::Config {
+                reason: format!(
+                    "interactive sessions need a sandboxed policy, not {:?}",
+                    policy
+                ),
+            });
+        }
+        if !self.initialized.load(std::sync::atomic::Ordering::SeqCst) {
+            self.initialize().await?;
+        }
 
-        Ok(container_output.into())
+        let runner = self.current_runner().await?;
+        let session = SandboxSession::start(
+            &runner,
+            command,
+            cwd,
+            policy,
+            &self.limits(),
+            env,
+            self.config.session_idle_timeout,
+        )
+        .await?;
+        self.sessions
+            .write()
+            .await
+            .insert(session.id(), Arc::clone(&session));
+        self.ensure_session_sweeper();
+        Ok(session)
+    }
+
+    /// An open session by id.
+    pub async fn session(&self, id: uuid::Uuid) -> Result<Arc<SandboxSession>> {
+        self.sessions
+            .read()
+            .await
+            .get(&id)
+            .cloned()
+            .ok_or(SandboxError::SessionClosed { id })
+    }
+
+    /// Open sessions.
+    pub async fn sessions(&self) -> Vec<Arc<SandboxSession>> {
+        self.sessions.read().await.values().cloned().collect()
+    }
+
+    /// Close a session and remove its container. Returns `false` if no
+    /// such session was open.
+    pub async fn close_session(&self, id: uuid::Uuid) -> bool {
+        let session = self.sessions.write().await.remove(&id);
+        match session {
+            Some(session) => {
+                session.close().await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Start the background task that closes idle sessions, once.
+    fn ensure_session_sweeper(&self) {
+        let Ok(mut sweeper) = self.session_sweeper.lock() else {
+            return;
+        };
+        if sweeper.is_some() {
+            return;
+        }
+        let sessions = Arc::clone(&self.sessions);
+        *sweeper = Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SESSION_SWEEP_INTERVAL);
+            loop {
+                interval.tick().await;
+                let idle: Vec<Arc<SandboxSession>> = {
+                    let mut sessions = sessions.write().await;
+                    let ids: Vec<uuid::Uuid> = sessions
+                        .values()
+                        .filter(|s| s.is_idle() || s.is_closed())
+                        .map(|s| s.id())
+                        .collect();
+                    ids.iter().filter_map(|id| sessions.remove(id)).collect()
+                };
+                for session in idle {
+                    tracing::info!(session = %session.id(), "Closing idle sandbox session");
+                    session.close().await;
+                }
+            }
+        }));
     }
 
     /// Execute a command directly on the host (no sandbox).
@@ -260,6 +532,8 @@ impl SandboxManager {
 
         cmd.current_dir(cwd);
         cmd.envs(env);
+        // Don't leave the process behind if the job is cancelled mid-command.
+        cmd.kill_on_drop(true);
 
         let output = tokio::time::timeout(self.config.timeout, cmd.output())
             .await
@@ -285,6 +559,7 @@ impl SandboxManager {
             output: combined,
             duration: start.elapsed(),
             truncated: false,
+            changes: None,
         })
     }
 
@@ -309,6 +584,11 @@ impl SandboxManager {
         self.initialized.load(std::sync::atomic::Ordering::SeqCst)
     }
 
+    /// Warm pool counters, if pooling is on and the sandbox is initialized.
+    pub async fn pool_stats(&self) -> Option<PoolStats> {
+        self.pool.read().await.as_ref().map(|pool| pool.stats())
+    }
+
     /// Get the proxy port if running.
     pub async fn proxy_port(&self) -> Option<u16> {
         if let Some(proxy) = self.proxy.read().await.as_ref() {
@@ -331,6 +611,8 @@ impl Drop for SandboxManager {
 /// Builder for creating a sandbox manager.
 pub struct SandboxManagerBuilder {
     config: SandboxConfig,
+    egress: Option<EgressLog>,
+    ephemeral: Option<Arc<EphemeralCredentials>>,
 }
 
 impl SandboxManagerBuilder {
@@ -338,6 +620,8 @@ impl SandboxManagerBuilder {
     pub fn new() -> Self {
         Self {
             config: SandboxConfig::default(),
+            egress: None,
+            ephemeral: None,
         }
     }
 
@@ -365,12 +649,72 @@ impl SandboxManagerBuilder {
         self
     }
 
-    /// Set the Docker image.
+    /// Set the container image.
     pub fn image(mut self, image: &str) -> Self {
         self.config.image = image.to_string();
         self
     }
 
+    /// Keep this many warm containers per policy and workspace (0 = off).
+    pub fn warm_pool_size(mut self, size: usize) -> Self {
+        self.config.warm_pool_size = size;
+        self
+    }
+
+    /// Close interactive sessions unused for this long.
+    pub fn session_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.config.session_idle_timeout = timeout;
+        self
+    }
+
+    /// Where per-command network grants are audited.
+    pub fn network_grant_audit_path(mut self, path: std::path::PathBuf) -> Self {
+        self.config.network_grant_audit_path = path;
+        self
+    }
+
+    /// Cap the number of processes per container.
+    pub fn pids_limit(mut self, limit: i64) -> Self {
+        self.config.pids_limit = Some(limit);
+        self
+    }
+
+    /// Cap the container's writable layer at `mb` megabytes.
+    pub fn disk_quota_mb(mut self, mb: u64) -> Self {
+        self.config.disk_quota_mb = Some(mb);
+        self
+    }
+
+    /// Size each tmpfs scratch mount at `mb` megabytes.
+    pub fn tmpfs_size_mb(mut self, mb: u64) -> Self {
+        self.config.tmpfs_size_mb = Some(mb);
+        self
+    }
+
+    /// Pass GPUs through to containers.
+    pub fn gpus(mut self, gpus: GpuAccess) -> Self {
+        self.config.gpus = gpus;
+        self
+    }
+
+    /// Record every request sandboxed commands make through the proxy.
+    pub fn egress_log(mut self, log: EgressLog) -> Self {
+        self.egress = Some(log);
+        self
+    }
+
+    /// Inject per-job credentials minted by these issuers.
+    pub fn ephemeral_credentials(mut self, credentials: Arc<EphemeralCredentials>) -> Self {
+        self.ephemeral = Some(credentials);
+        self
+    }
+
+    /// Set the container backend.
+    pub fn backend(mut self, backend: ContainerBackendKind) -> Self {
+        self.config.backend = backend;
+        self
+    }
+
     /// Add domains to the network allowlist.
     pub fn allow_domains(mut self, domains: Vec<String>) -> Self {
         self.config.network_allowlist.extend(domains);
@@ -379,7 +723,14 @@ impl SandboxManagerBuilder {
 
     /// Build the sandbox manager.
     pub fn build(self) -> SandboxManager {
-        SandboxManager::new(self.config)
+        let mut manager = SandboxManager::new(self.config);
+        if let Some(log) = self.egress {
+            manager = manager.with_egress_log(log);
+        }
+        if let Some(credentials) = self.ephemeral {
+            manager = manager.with_ephemeral_credentials(credentials);
+        }
+        manager
     }
 
     /// Build and initialize the sandbox manager.
@@ -454,6 +805,22 @@ mod tests {
         assert_eq!(manager.config.image, "custom:latest");
     }
 
+    #[test]
+    fn test_quota_settings_reach_limits() {
+        let manager = SandboxManagerBuilder::new()
+            .pids_limit(256)
+            .disk_quota_mb(10)
+            .tmpfs_size_mb(64)
+            .gpus(GpuAccess::All)
+            .build();
+
+        let limits = manager.limits();
+        assert_eq!(limits.pids_limit, Some(256));
+        assert_eq!(limits.disk_quota_bytes, Some(10 * 1024 * 1024));
+        assert_eq!(limits.tmpfs_bytes, Some(64 * 1024 * 1024));
+        assert_eq!(limits.gpus, GpuAccess::All);
+    }
+
     #[tokio::test]
     async fn test_direct_execution() {
         let manager = SandboxManager::new(SandboxConfig {
@@ -471,4 +838,59 @@ mod tests {
         let output = result.unwrap();
         assert!(output.stdout.contains("hello"));
     }
+
+    #[tokio::test]
+    async fn test_full_access_runs_without_network_grant() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = SandboxManagerBuilder::new()
+            .enabled(true)
+            .policy(SandboxPolicy::FullAccess)
+            .network_grant_audit_path(dir.path().join("grants.jsonl"))
+            .build();
+        let exec = ExecPolicy::new(SandboxPolicy::FullAccess).allow_domain("pypi.org");
+
+        let output = manager
+            .execute_with_exec_policy("echo hi", Path::new("."), &exec, HashMap::new())
+            .await
+            .unwrap();
+
+        assert!(output.stdout.contains("hi"));
+        // Nothing goes through the proxy, so there is nothing to grant.
+        assert!(manager.network_grants().audit_log().unwrap().is_empty());
+        assert_eq!(manager.network_grants().active_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_capture_diff_reports_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("old.txt"), "a\n").unwrap();
+        let manager = SandboxManagerBuilder::new()
+            .enabled(true)
+            .policy(SandboxPolicy::FullAccess)
+            .network_grant_audit_path(dir.path().join("grants.jsonl"))
+            .build();
+        let exec = ExecPolicy::new(SandboxPolicy::FullAccess).with_diff_capture();
+
+        let output = manager
+            .execute_with_exec_policy(
+                "echo b >> old.txt && echo new > new.txt",
+                dir.path(),
+                &exec,
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+
+        let changes = output.changes.unwrap();
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["new.txt", "old.txt"]);
+        assert!(changes[1].diff.as_deref().unwrap().contains("+b\n"));
+
+        // Without the option nothing is captured.
+        let output = manager
+            .execute("true", dir.path(), HashMap::new())
+            .await
+            .unwrap();
+        assert!(output.changes.is_none());
+    }
 }
@@ -1,9 +1,12 @@
-//! Docker execution sandbox for secure command execution.
+//! Container execution sandbox for secure command execution.
 //!
 //! This module provides a complete sandboxing solution for running untrusted commands:
-//! - **Container isolation**: Commands run in ephemeral Docker containers
+//! - **Container isolation**: Commands run in ephemeral containers on Docker,
+//!   rootless Podman or containerd (see [`backend`])
 //! - **Network proxy**: All network traffic goes through a validating proxy
 //! - **Credential injection**: Secrets are injected by the proxy, never exposed in containers
+//! - **Ephemeral credentials**: Short-lived per-job tokens from pluggable
+//!   issuers, revoked when the job ends ([`proxy::ephemeral`])
 //! - **Resource limits**: Memory, CPU, and timeout enforcement
 //!
 //! # Architecture
@@ -32,8 +35,8 @@
 //! │           │                              │                                   │
 //! │           ▼                              ▼                                   │
 //! │  ┌──────────────────┐          ┌───────────────────┐                        │
-//! │  │     Docker       │          │     Internet      │                        │
-//! │  │                  │          │   (allowed hosts) │                        │
+//! │  │ Docker / Podman  │          │     Internet      │                        │
+//! │  │  / containerd    │          │   (allowed hosts) │                        │
 //! │  └──────────────────┘          └───────────────────┘                        │
 //! └─────────────────────────────────────────────────────────────────────────────┘
 //! ```
@@ -85,22 +88,39 @@
 //! - **Auto-cleanup**: Containers are removed after execution (--rm + explicit cleanup)
 //! - **Timeout enforcement**: Commands are killed after the timeout
 
+pub mod backend;
 pub mod config;
 pub mod container;
+pub mod diff;
 pub mod error;
+pub mod fallback;
 pub mod manager;
+pub mod pool;
+pub mod process;
 pub mod proxy;
+pub mod session;
 
+pub use backend::{
+    BindMount, ContainerBackend, ContainerSpec, DockerBackend, NerdctlBackend, backend_available,
+    connect_backend,
+};
 pub use config::{
-    CredentialLocation, CredentialMapping, ResourceLimits, SandboxConfig, SandboxPolicy,
+    ContainerBackendKind, CredentialLocation, CredentialMapping, GpuAccess, ResourceLimits,
+    SandboxConfig, SandboxPolicy,
 };
 pub use container::{ContainerOutput, ContainerRunner, connect_docker};
+pub use diff::{FileChange, FileDiff, WorkspaceSnapshot};
 pub use error::{Result, SandboxError};
+pub use fallback::{FallbackAuditRecord, HostFallback, HostFallbackPolicy, docker_available};
 pub use manager::{ExecOutput, SandboxManager, SandboxManagerBuilder};
+pub use pool::{PoolStats, WarmPool};
 pub use proxy::{
-    CredentialResolver, DefaultPolicyDecider, DomainAllowlist, EnvCredentialResolver, HttpProxy,
-    NetworkDecision, NetworkPolicyDecider, NetworkProxyBuilder, NetworkRequest,
+    CredentialIssuer, CredentialResolver, DefaultPolicyDecider, DomainAllowlist, EgressLog,
+    EnvCredentialResolver, EphemeralCredentials, ExecPolicy, GitHubAppIssuer, HttpProxy,
+    IssuedCredential, NetworkDecision, NetworkGrantRecord, NetworkGrants, NetworkPolicyDecider,
+    NetworkProxyBuilder, NetworkRequest, TokenExchangeIssuer,
 };
+pub use session::{SandboxSession, SessionOutput};
 
 /// Default allowlist getter (re-export for convenience).
 pub fn default_allowlist() -> Vec<String> {
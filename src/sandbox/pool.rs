@@ -0,0 +1,527 @@
+//! Warm container pool.
+//!
+//! Starting a container costs a second or more per command. With a pool,
+//! sandboxed commands run via `exec` in long-lived containers instead:
+//!
+//! ```text
+//! execute(cmd, workspace, policy)
+//!     │
+//!     ▼
+//! ┌────────────────────┐ hit  ┌──────────────┐     ┌──────────────────────┐
+//! │ idle container for │─────▶│ exec cmd     │────▶│ reset: kill strays,  │──▶ idle
+//! │ (policy, workspace)│      │ in /workspace│     │ clear /tmp           │
+//! └────────────────────┘      └──────────────┘     └──────────────────────┘
+//!     │ miss                         ▲                 │ failed / worn out
+//!     ▼                              │                 ▼
+//! start container ───────────────────┘              remove
+//! (and warm up to `size` in the background)
+//! ```
+//!
+//! A bind mount cannot be changed on a running container, so each pooled
+//! container mounts one workspace and is only handed out for that workspace
+//! and policy. Pooled containers keep the root filesystem read-only whatever
+//! the policy, so the only writable places are the workspace (if the policy
+//! allows) and the tmpfs mounts; the reset between commands clears `/tmp`
+//! and kills leftover processes. The cargo registry tmpfs is kept, which is
+//! what makes repeated `cargo build`s cheap. A container whose command
+//! failed to complete (timeout, exec error), whose reset failed, or that has
+//! served [`MAX_USES`] commands is removed rather than reused.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::sandbox::backend::ContainerBackend;
+use crate::sandbox::config::{ResourceLimits, SandboxPolicy};
+use crate::sandbox::container::{ContainerOutput, ContainerRunner};
+use crate::sandbox::error::Result;
+
+/// Commands a container serves before it is replaced.
+pub const MAX_USES: u32 = 100;
+
+/// Idle containers older than this are removed.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Keeps PID 1 alive without doing anything.
+pub(crate) const IDLE_COMMAND: &str = "while :; do sleep 3600; done";
+
+/// Kills everything but PID 1 and the reset shell itself, then empties `/tmp`.
+const RESET_SCRIPT: &str = "for p in /proc/[0-9]*; do p=${p#/proc/}; \
+     [ \"$p\" = 1 ] || [ \"$p\" = $$ ] || kill -9 \"$p\" 2>/dev/null; done; \
+     find /tmp -mindepth 1 -delete";
+
+const RESET_TIMEOUT: Duration = Duration::from_secs(10);
+
+type PoolKey = (SandboxPolicy, PathBuf);
+
+struct WarmContainer {
+    id: String,
+    uses: u32,
+    idle_since: Instant,
+}
+
+#[derive(Default)]
+struct Slot {
+    idle: Vec<WarmContainer>,
+    /// Idle, in use, or starting.
+    live: usize,
+}
+
+/// Pool counters, see [`WarmPool::stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Commands that ran in an already-warm container.
+    pub hits: u64,
+    /// Commands that had to wait for a container to start.
+    pub misses: u64,
+    /// Containers started, including background warm-ups.
+    pub started: u64,
+    /// Containers removed after failure, wear or idleness.
+    pub retired: u64,
+    /// Containers currently idle.
+    pub idle: usize,
+    /// Mean time from request to the command starting, over all commands.
+    pub avg_overhead: Duration,
+}
+
+/// Pre-started containers per policy and workspace.
+pub struct WarmPool {
+    runner: ContainerRunner,
+    size: usize,
+    slots: std::sync::Mutex<HashMap<PoolKey, Slot>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    started: AtomicU64,
+    retired: AtomicU64,
+    overhead_micros: AtomicU64,
+}
+
+impl WarmPool {
+    /// Keep up to `size` containers per policy and workspace, started through
+    /// `runner`'s backend.
+    pub fn new(runner: ContainerRunner, size: usize) -> Self {
+        Self {
+            runner,
+            size: size.max(1),
+            slots: std::sync::Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            started: AtomicU64::new(0),
+            retired: AtomicU64::new(0),
+            overhead_micros: AtomicU64::new(0),
+        }
+    }
+
+    fn backend(&self) -> &Arc<dyn ContainerBackend> {
+        self.runner.backend()
+    }
+
+    /// Run `command` in a pooled container for `working_dir`.
+    pub async fn execute(
+        self: &Arc<Self>,
+        command: &str,
+        working_dir: &Path,
+        policy: SandboxPolicy,
+        limits: &ResourceLimits,
+        env: HashMap<String, String>,
+    ) -> Result<ContainerOutput> {
+        let requested = Instant::now();
+        self.sweep().await;
+
+        let key = (policy, working_dir.to_path_buf());
+        let mut container = match self.checkout(&key) {
+            Some(container) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                container
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                match self.start(&key, limits).await {
+                    Ok(container) => container,
+                    Err(e) => {
+                        self.release_slot(&key);
+                        return Err(e);
+                    }
+                }
+            }
+        };
+        self.warm_up(&key, limits);
+
+        self.overhead_micros
+            .fetch_add(requested.elapsed().as_micros() as u64, Ordering::Relaxed);
+        let env: Vec<(String, String)> = env.into_iter().collect();
+        let exec_start = Instant::now();
+        let result = self
+            .backend()
+            .exec(&container.id, command, "/workspace", &env, limits)
+            .await;
+        container.uses += 1;
+
+        let reusable = result.is_ok() && container.uses < MAX_USES && self.reset(&container).await;
+        if reusable {
+            container.idle_since = Instant::now();
+            self.checkin(&key, container);
+        } else {
+            self.retire(&key, &container.id).await;
+        }
+
+        let mut output = result?;
+        output.duration = exec_start.elapsed();
+        Ok(output)
+    }
+
+    /// Current counters.
+    pub fn stats(&self) -> PoolStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let idle = self
+            .slots
+            .lock()
+            .map(|slots| slots.values().map(|s| s.idle.len()).sum())
+            .unwrap_or(0);
+        let total = hits + misses;
+        PoolStats {
+            hits,
+            misses,
+            started: self.started.load(Ordering::Relaxed),
+            retired: self.retired.load(Ordering::Relaxed),
+            idle,
+            avg_overhead: Duration::from_micros(
+                self.overhead_micros
+                    .load(Ordering::Relaxed)
+                    .checked_div(total)
+                    .unwrap_or(0),
+            ),
+        }
+    }
+
+    /// Remove every idle container.
+    pub async fn drain(&self) {
+        let ids: Vec<String> = match self.slots.lock() {
+            Ok(mut slots) => slots
+                .drain()
+                .flat_map(|(_, slot)| slot.idle.into_iter().map(|c| c.id))
+                .collect(),
+            Err(_) => return,
+        };
+        for id in ids {
+            self.backend().remove(&id).await;
+            self.retired.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Take an idle container, or reserve room to start one.
+    fn checkout(&self, key: &PoolKey) -> Option<WarmContainer> {
+        let mut slots = self.slots.lock().ok()?;
+        let slot = slots.entry(key.clone()).or_default();
+        let container = slot.idle.pop();
+        if container.is_none() {
+            slot.live += 1;
+        }
+        container
+    }
+
+    fn checkin(&self, key: &PoolKey, container: WarmContainer) {
+        if let Ok(mut slots) = self.slots.lock() {
+            slots.entry(key.clone()).or_default().idle.push(container);
+        }
+    }
+
+    fn release_slot(&self, key: &PoolKey) {
+        if let Ok(mut slots) = self.slots.lock()
+            && let Some(slot) = slots.get_mut(key)
+        {
+            slot.live = slot.live.saturating_sub(1);
+        }
+    }
+
+    async fn retire(&self, key: &PoolKey, id: &str) {
+        self.release_slot(key);
+        self.backend().remove(id).await;
+        self.retired.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!(container_id = %id, "Retired pooled sandbox container");
+    }
+
+    async fn start(&self, key: &PoolKey, limits: &ResourceLimits) -> Result<WarmContainer> {
+        let (policy, working_dir) = key;
+        let mut spec = self
+            .runner
+            .spec(IDLE_COMMAND, working_dir, *policy, HashMap::new());
+        spec.name = format!("sandbox-warm-{}", uuid::Uuid::new_v4());
+        spec.read_only_rootfs = true;
+
+        let id = self.backend().start(&spec, limits).await?;
+        self.started.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!(container_id = %id, workspace = %working_dir.display(), "Started pooled sandbox container");
+        Ok(WarmContainer {
+            id,
+            uses: 0,
+            idle_since: Instant::now(),
+        })
+    }
+
+    /// Start containers in the background until `key` has `size` of them.
+    fn warm_up(self: &Arc<Self>, key: &PoolKey, limits: &ResourceLimits) {
+        let missing = match self.slots.lock() {
+            Ok(mut slots) => {
+                let slot = slots.entry(key.clone()).or_default();
+                let missing = self.size.saturating_sub(slot.live);
+                slot.live += missing;
+                missing
+            }
+            Err(_) => return,
+        };
+        for _ in 0..missing {
+            let pool = Arc::clone(self);
+            let key = key.clone();
+            let limits = limits.clone();
+            tokio::spawn(async move {
+                match pool.start(&key, &limits).await {
+                    Ok(container) => pool.checkin(&key, container),
+                    Err(e) => {
+                        pool.release_slot(&key);
+                        tracing::debug!("Sandbox pool warm-up failed: {}", e);
+                    }
+                }
+            });
+        }
+    }
+
+    /// Get a used container ready for the next command.
+    async fn reset(&self, container: &WarmContainer) -> bool {
+        let limits = ResourceLimits {
+            timeout: RESET_TIMEOUT,
+            ..ResourceLimits::default()
+        };
+        match self
+            .backend()
+            .exec(&container.id, RESET_SCRIPT, "/", &[], &limits)
+            .await
+        {
+            Ok(output) if output.exit_code == 0 => true,
+            Ok(output) => {
+                tracing::debug!(
+                    container_id = %container.id,
+                    "Sandbox container reset failed: {}",
+                    output.stderr.trim()
+                );
+                false
+            }
+            Err(e) => {
+                tracing::debug!(container_id = %container.id, "Sandbox container reset failed: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Remove containers idle for longer than [`IDLE_TIMEOUT`].
+    async fn sweep(&self) {
+        let expired: Vec<String> = match self.slots.lock() {
+            Ok(mut slots) => {
+                let mut expired = Vec::new();
+                for slot in slots.values_mut() {
+                    let (stale, fresh): (Vec<_>, Vec<_>) = slot
+                        .idle
+                        .drain(..)
+                        .partition(|c| c.idle_since.elapsed() > IDLE_TIMEOUT);
+                    slot.idle = fresh;
+                    slot.live = slot.live.saturating_sub(stale.len());
+                    expired.extend(stale.into_iter().map(|c| c.id));
+                }
+                slots.retain(|_, slot| slot.live > 0);
+                expired
+            }
+            Err(_) => return,
+        };
+        for id in expired {
+            self.backend().remove(&id).await;
+            self.retired.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::sandbox::backend::ContainerSpec;
+    use crate::sandbox::config::ContainerBackendKind;
+    use crate::sandbox::error::SandboxError;
+
+    #[derive(Default)]
+    struct FakeBackend {
+        next_id: AtomicU64,
+        execs: Mutex<Vec<(String, String)>>,
+        removed: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ContainerBackend for FakeBackend {
+        fn kind(&self) -> ContainerBackendKind {
+            ContainerBackendKind::Docker
+        }
+        fn host_gateway(&self) -> &str {
+            "172.17.0.1"
+        }
+        async fn ping(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn image_exists(&self, _image: &str) -> bool {
+            true
+        }
+        async fn pull_image(&self, _image: &str) -> Result<()> {
+            Ok(())
+        }
+        async fn run(
+            &self,
+            _spec: &ContainerSpec,
+            _limits: &ResourceLimits,
+        ) -> Result<ContainerOutput> {
+            unimplemented!()
+        }
+        async fn start(&self, spec: &ContainerSpec, _limits: &ResourceLimits) -> Result<String> {
+            assert!(spec.read_only_rootfs);
+            Ok(format!("c{}", self.next_id.fetch_add(1, Ordering::SeqCst)))
+        }
+        async fn remove(&self, container_id: &str) {
+            self.removed.lock().unwrap().push(container_id.to_string());
+        }
+        async fn exec(
+            &self,
+            container_id: &str,
+            command: &str,
+            _working_dir: &str,
+            _env: &[(String, String)],
+            limits: &ResourceLimits,
+        ) -> Result<ContainerOutput> {
+            self.execs
+                .lock()
+                .unwrap()
+                .push((container_id.to_string(), command.to_string()));
+            if command == "hang" {
+                return Err(SandboxError::Timeout(limits.timeout));
+            }
+            Ok(ContainerOutput {
+                exit_code: 0,
+                stdout: command.to_string(),
+                stderr: String::new(),
+                duration: Duration::ZERO,
+                truncated: false,
+            })
+        }
+        async fn attach(
+            &self,
+            _container_id: &str,
+            _command: &str,
+            _working_dir: &str,
+            _env: &[(String, String)],
+        ) -> Result<crate::sandbox::backend::AttachedProcess> {
+            unimplemented!()
+        }
+    }
+
+    fn pool(backend: &Arc<FakeBackend>) -> Arc<WarmPool> {
+        let runner = ContainerRunner::new(backend.clone(), "img".to_string(), 0);
+        Arc::new(WarmPool::new(runner, 1))
+    }
+
+    #[tokio::test]
+    async fn test_second_command_reuses_container() {
+        let backend = Arc::new(FakeBackend::default());
+        let pool = pool(&backend);
+        let limits = ResourceLimits::default();
+        let dir = Path::new("/src");
+
+        for _ in 0..2 {
+            let out = pool
+                .execute(
+                    "cargo build",
+                    dir,
+                    SandboxPolicy::WorkspaceWrite,
+                    &limits,
+                    HashMap::new(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(out.stdout, "cargo build");
+        }
+
+        let stats = pool.stats();
+        assert_eq!((stats.hits, stats.misses, stats.started), (1, 1, 1));
+        assert_eq!(stats.idle, 1);
+        let execs = backend.execs.lock().unwrap();
+        assert!(execs.iter().all(|(id, _)| id == "c0"));
+        assert_eq!(execs.iter().filter(|(_, c)| c == RESET_SCRIPT).count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_containers_are_per_policy_and_workspace() {
+        let backend = Arc::new(FakeBackend::default());
+        let pool = pool(&backend);
+        let limits = ResourceLimits::default();
+
+        for (dir, policy) in [
+            ("/a", SandboxPolicy::ReadOnly),
+            ("/a", SandboxPolicy::WorkspaceWrite),
+            ("/b", SandboxPolicy::ReadOnly),
+        ] {
+            pool.execute("ls", Path::new(dir), policy, &limits, HashMap::new())
+                .await
+                .unwrap();
+        }
+
+        let stats = pool.stats();
+        assert_eq!((stats.hits, stats.misses), (0, 3));
+        assert_eq!(stats.idle, 3);
+    }
+
+    #[tokio::test]
+    async fn test_failed_command_retires_container() {
+        let backend = Arc::new(FakeBackend::default());
+        let pool = pool(&backend);
+        let limits = ResourceLimits::default();
+        let dir = Path::new("/src");
+
+        let err = pool
+            .execute(
+                "hang",
+                dir,
+                SandboxPolicy::ReadOnly,
+                &limits,
+                HashMap::new(),
+            )
+            .await;
+        assert!(matches!(err, Err(SandboxError::Timeout(_))));
+        assert_eq!(*backend.removed.lock().unwrap(), vec!["c0".to_string()]);
+
+        pool.execute("ls", dir, SandboxPolicy::ReadOnly, &limits, HashMap::new())
+            .await
+            .unwrap();
+        let stats = pool.stats();
+        assert_eq!((stats.misses, stats.retired, stats.idle), (2, 1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_drain_removes_idle_containers() {
+        let backend = Arc::new(FakeBackend::default());
+        let pool = pool(&backend);
+        pool.execute(
+            "ls",
+            Path::new("/src"),
+            SandboxPolicy::ReadOnly,
+            &ResourceLimits::default(),
+            HashMap::new(),
+        )
+        .await
+        .unwrap();
+
+        pool.drain().await;
+        assert_eq!(pool.stats().idle, 0);
+        assert_eq!(backend.removed.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,109 @@
+//! Graceful termination of host processes started by tools.
+//!
+//! A tool future is dropped when its job is cancelled. [`TerminateOnDrop`]
+//! owns the child process so that, if that happens while the process is
+//! still running, it gets SIGTERM and a grace period to exit before SIGKILL
+//! instead of being orphaned.
+
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+
+use tokio::process::Child;
+
+/// A child process that is terminated (SIGTERM, then SIGKILL after the grace
+/// period) if dropped while still running.
+pub struct TerminateOnDrop {
+    child: Option<Child>,
+    grace: Duration,
+}
+
+impl TerminateOnDrop {
+    pub fn new(child: Child, grace: Duration) -> Self {
+        Self {
+            child: Some(child),
+            grace,
+        }
+    }
+}
+
+impl Deref for TerminateOnDrop {
+    type Target = Child;
+
+    fn deref(&self) -> &Child {
+        self.child.as_ref().expect("child taken only on drop")
+    }
+}
+
+impl DerefMut for TerminateOnDrop {
+    fn deref_mut(&mut self) -> &mut Child {
+        self.child.as_mut().expect("child taken only on drop")
+    }
+}
+
+impl Drop for TerminateOnDrop {
+    fn drop(&mut self) {
+        // `id()` is `None` once the process has been waited for.
+        let Some(child) = self.child.take().filter(|c| c.id().is_some()) else {
+            return;
+        };
+        let grace = self.grace;
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(terminate(child, grace));
+            }
+            Err(_) => {
+                let mut child = child;
+                let _ = child.start_kill();
+            }
+        }
+    }
+}
+
+/// Ask a process to exit, and kill it if it is still running after `grace`.
+pub async fn terminate(mut child: Child, grace: Duration) {
+    let Some(pid) = child.id() else {
+        return;
+    };
+
+    #[cfg(unix)]
+    {
+        // SAFETY: kill(2) only sends a signal; `pid` is our own child, which
+        // has not been reaped yet (its id is still known).
+        if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } == 0
+            && tokio::time::timeout(grace, child.wait()).await.is_ok()
+        {
+            tracing::debug!(pid, "Cancelled process exited after SIGTERM");
+            return;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = grace;
+
+    tracing::debug!(pid, "Killing cancelled process");
+    let _ = child.kill().await;
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_terminate_on_drop_stops_running_process() {
+        let child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let pid = child.id().unwrap();
+        drop(TerminateOnDrop::new(child, Duration::from_secs(5)));
+
+        // SIGTERM ends `sleep` right away; the reaper then clears the pid.
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while std::path::Path::new(&format!("/proc/{pid}")).exists() {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "process should have been terminated"
+            );
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+}
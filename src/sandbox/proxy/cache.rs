@@ -0,0 +1,417 @@
+//! Package artifact cache for the sandbox proxy.
+//!
+//! Sandboxed builds fetch the same crates, npm tarballs and Python wheels on
+//! every run. The proxy keeps a content-addressed on-disk copy of immutable
+//! registry artifacts and serves repeat downloads locally.
+//!
+//! ```text
+//! GET static.crates.io/crates/serde/serde-1.0.0.crate
+//!        │
+//!        ├─► classify: crates.io / npm / PyPI artifact?  ── no ──► forward
+//!        ├─► cache hit + sha256 matches index entry      ──────► serve from disk
+//!        └─► miss: forward, store body on 200, evict LRU until under budget
+//! ```
+//!
+//! The cache sits on the plain-HTTP forwarding path and behind the
+//! [registry mirror](super::mirror), which is how sandboxes reach the
+//! HTTPS-only registries; CONNECT tunnels are opaque to the proxy and are
+//! never cached.
+//!
+//! Only versioned artifacts are cached (never index or metadata documents,
+//! which change over time). Every entry records the SHA-256 of its bytes and
+//! is re-verified on read; a mismatch drops the entry and falls through to
+//! the network.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use crate::sandbox::error::{Result, SandboxError};
+
+/// Default cache budget (2 GiB).
+pub const DEFAULT_PACKAGE_CACHE_MAX_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+const INDEX_FILE: &str = "index.json";
+
+/// Package registries whose artifacts can be cached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageRegistry {
+    Crates,
+    Npm,
+    Pypi,
+}
+
+impl PackageRegistry {
+    /// Classify a request as an immutable registry artifact.
+    ///
+    /// Returns `None` for anything that may change between requests
+    /// (sparse index files, package metadata, search endpoints).
+    pub fn classify(host: &str, path: &str) -> Option<Self> {
+        let host = host.to_ascii_lowercase();
+        let path = path.split('?').next().unwrap_or(path);
+        match host.as_str() {
+            "static.crates.io" if path.starts_with("/crates/") && path.ends_with(".crate") => {
+                Some(Self::Crates)
+            }
+            "registry.npmjs.org" if path.contains("/-/") && path.ends_with(".tgz") => {
+                Some(Self::Npm)
+            }
+            "files.pythonhosted.org" if path.starts_with("/packages/") => {
+                let file = path.rsplit('/').next().unwrap_or("");
+                let artifact = [".whl", ".tar.gz", ".zip", ".tar.bz2"]
+                    .iter()
+                    .any(|ext| file.ends_with(ext));
+                artifact.then_some(Self::Pypi)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Index entry for a cached artifact.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    registry: PackageRegistry,
+    sha256: String,
+    size: u64,
+    /// Unix seconds of the last hit or store.
+    last_access: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheIndex {
+    /// Keyed by request URL.
+    entries: HashMap<String, CacheEntry>,
+}
+
+impl CacheIndex {
+    fn total_bytes(&self) -> u64 {
+        self.entries.values().map(|e| e.size).sum()
+    }
+}
+
+/// Cache counters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageCacheStats {
+    pub entries: usize,
+    pub total_bytes: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub integrity_failures: u64,
+}
+
+/// Size-bounded, integrity-checked store of registry artifacts.
+pub struct PackageCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    state: Mutex<CacheState>,
+}
+
+struct CacheState {
+    index: CacheIndex,
+    stats: PackageCacheStats,
+}
+
+impl PackageCache {
+    /// Open (or create) a cache rooted at `dir`.
+    ///
+    /// A missing or unreadable index starts the cache empty; stray blob files
+    /// are overwritten as artifacts are fetched again.
+    pub async fn open(dir: impl Into<PathBuf>, max_bytes: u64) -> Result<Self> {
+        let dir = dir.into();
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| cache_error(&dir, e))?;
+
+        let index = match tokio::fs::read(dir.join(INDEX_FILE)).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                tracing::warn!("Package cache index is corrupt, starting empty: {}", e);
+                CacheIndex::default()
+            }),
+            Err(_) => CacheIndex::default(),
+        };
+
+        Ok(Self {
+            dir,
+            max_bytes,
+            state: Mutex::new(CacheState {
+                index,
+                stats: PackageCacheStats::default(),
+            }),
+        })
+    }
+
+    /// Directory holding cached artifacts.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Look up an artifact, verifying its integrity.
+    pub async fn get(&self, url: &str) -> Option<Bytes> {
+        let mut state = self.state.lock().await;
+        let Some(entry) = state.index.entries.get(url).cloned() else {
+            state.stats.misses += 1;
+            return None;
+        };
+
+        let bytes = match tokio::fs::read(self.blob_path(&entry.sha256)).await {
+            Ok(bytes) if sha256_hex(&bytes) == entry.sha256 => bytes,
+            Ok(_) | Err(_) => {
+                tracing::warn!(
+                    "Package cache: integrity check failed for {}, dropping",
+                    url
+                );
+                state.index.entries.remove(url);
+                state.stats.integrity_failures += 1;
+                state.stats.misses += 1;
+                let _ = tokio::fs::remove_file(self.blob_path(&entry.sha256)).await;
+                self.save_index(&state.index).await;
+                return None;
+            }
+        };
+
+        if let Some(e) = state.index.entries.get_mut(url) {
+            e.last_access = now_secs();
+        }
+        state.stats.hits += 1;
+        Some(Bytes::from(bytes))
+    }
+
+    /// Store a freshly downloaded artifact and evict old entries if needed.
+    ///
+    /// Artifacts larger than the whole budget are not cached.
+    pub async fn put(&self, url: &str, registry: PackageRegistry, body: &[u8]) -> Result<()> {
+        let size = body.len() as u64;
+        if size > self.max_bytes {
+            return Ok(());
+        }
+
+        let sha256 = sha256_hex(body);
+        let blob = self.blob_path(&sha256);
+        let staged = blob.with_extension("partial");
+        tokio::fs::write(&staged, body)
+            .await
+            .map_err(|e| cache_error(&staged, e))?;
+        tokio::fs::rename(&staged, &blob)
+            .await
+            .map_err(|e| cache_error(&blob, e))?;
+
+        let mut state = self.state.lock().await;
+        state.index.entries.insert(
+            url.to_string(),
+            CacheEntry {
+                registry,
+                sha256,
+                size,
+                last_access: now_secs(),
+            },
+        );
+        self.evict(&mut state).await;
+        self.save_index(&state.index).await;
+        Ok(())
+    }
+
+    /// Current counters.
+    pub async fn stats(&self) -> PackageCacheStats {
+        let state = self.state.lock().await;
+        PackageCacheStats {
+            entries: state.index.entries.len(),
+            total_bytes: state.index.total_bytes(),
+            ..state.stats.clone()
+        }
+    }
+
+    /// Evict least recently used entries until the cache fits its budget.
+    async fn evict(&self, state: &mut CacheState) {
+        let mut total = state.index.total_bytes();
+        if total <= self.max_bytes {
+            return;
+        }
+
+        let mut by_age: Vec<(String, u64, u64)> = state
+            .index
+            .entries
+            .iter()
+            .map(|(url, e)| (url.clone(), e.last_access, e.size))
+            .collect();
+        by_age.sort_by_key(|(_, last_access, _)| *last_access);
+
+        for (url, _, size) in by_age {
+            if total <= self.max_bytes {
+                break;
+            }
+            if let Some(entry) = state.index.entries.remove(&url) {
+                // Identical artifacts under different URLs share one blob.
+                let shared = state
+                    .index
+                    .entries
+                    .values()
+                    .any(|e| e.sha256 == entry.sha256);
+                if !shared {
+                    let _ = tokio::fs::remove_file(self.blob_path(&entry.sha256)).await;
+                }
+                total = total.saturating_sub(size);
+                state.stats.evictions += 1;
+            }
+        }
+    }
+
+    async fn save_index(&self, index: &CacheIndex) {
+        let path = self.dir.join(INDEX_FILE);
+        match serde_json::to_vec(index) {
+            Ok(bytes) => {
+                if let Err(e) = tokio::fs::write(&path, bytes).await {
+                    tracing::warn!("Package cache: failed to write index: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!("Package cache: failed to serialize index: {}", e),
+        }
+    }
+
+    fn blob_path(&self, sha256: &str) -> PathBuf {
+        self.dir.join(sha256)
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn cache_error(path: &Path, e: std::io::Error) -> SandboxError {
+    SandboxError::ProxyError {
+        reason: format!("package cache I/O error at {}: {}", path.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_artifacts() {
+        assert_eq!(
+            PackageRegistry::classify("static.crates.io", "/crates/serde/serde-1.0.0.crate"),
+            Some(PackageRegistry::Crates)
+        );
+        // The crates.io download API only redirects to static.crates.io.
+        assert_eq!(
+            PackageRegistry::classify("crates.io", "/api/v1/crates/serde/1.0.0/download"),
+            None
+        );
+        assert_eq!(
+            PackageRegistry::classify("registry.npmjs.org", "/left-pad/-/left-pad-1.3.0.tgz"),
+            Some(PackageRegistry::Npm)
+        );
+        assert_eq!(
+            PackageRegistry::classify(
+                "files.pythonhosted.org",
+                "/packages/ab/cd/ef/requests-2.31.0-py3-none-any.whl"
+            ),
+            Some(PackageRegistry::Pypi)
+        );
+    }
+
+    #[test]
+    fn test_classify_skips_mutable_documents() {
+        assert_eq!(
+            PackageRegistry::classify("index.crates.io", "/se/rd/serde"),
+            None
+        );
+        assert_eq!(
+            PackageRegistry::classify("registry.npmjs.org", "/left-pad"),
+            None
+        );
+        assert_eq!(
+            PackageRegistry::classify("pypi.org", "/simple/requests/"),
+            None
+        );
+        assert_eq!(PackageRegistry::classify("example.com", "/a.crate"), None);
+    }
+
+    #[tokio::test]
+    async fn test_put_then_get() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = PackageCache::open(dir.path(), 1024).await.unwrap();
+        let url = "https://static.crates.io/crates/a/a-1.0.0.crate";
+
+        assert!(cache.get(url).await.is_none());
+        cache
+            .put(url, PackageRegistry::Crates, b"crate-bytes")
+            .await
+            .unwrap();
+        assert_eq!(
+            cache.get(url).await.unwrap(),
+            Bytes::from_static(b"crate-bytes")
+        );
+
+        let stats = cache.stats().await;
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+
+        // The index survives a reopen.
+        let reopened = PackageCache::open(dir.path(), 1024).await.unwrap();
+        assert!(reopened.get(url).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_tampered_blob_is_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = PackageCache::open(dir.path(), 1024).await.unwrap();
+        let url = "https://registry.npmjs.org/a/-/a-1.0.0.tgz";
+        cache
+            .put(url, PackageRegistry::Npm, b"original")
+            .await
+            .unwrap();
+
+        std::fs::write(dir.path().join(sha256_hex(b"original")), b"tampered").unwrap();
+        assert!(cache.get(url).await.is_none());
+        let stats = cache.stats().await;
+        assert_eq!(stats.integrity_failures, 1);
+        assert_eq!(stats.entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_lru_eviction_respects_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = PackageCache::open(dir.path(), 10).await.unwrap();
+        cache
+            .put("u1", PackageRegistry::Pypi, b"aaaaaa")
+            .await
+            .unwrap();
+        {
+            // Make u1 clearly older than the next entry.
+            let mut state = cache.state.lock().await;
+            state.index.entries.get_mut("u1").unwrap().last_access = 0;
+        }
+        cache
+            .put("u2", PackageRegistry::Pypi, b"bbbbbb")
+            .await
+            .unwrap();
+
+        assert!(cache.get("u1").await.is_none());
+        assert!(cache.get("u2").await.is_some());
+        let stats = cache.stats().await;
+        assert_eq!(stats.evictions, 1);
+        assert!(stats.total_bytes <= 10);
+
+        // Oversized artifacts are never stored.
+        cache
+            .put("u3", PackageRegistry::Pypi, &[0u8; 11])
+            .await
+            .unwrap();
+        assert!(cache.get("u3").await.is_none());
+    }
+}
@@ -0,0 +1,114 @@
+//! Egress audit log.
+//!
+//! The proxy records every request a sandboxed command sends: method, host,
+//! path, the policy decision, bytes each way, the upstream status, and
+//! whether a credential was injected. Records go through a bounded queue to
+//! a background task that persists them with [`Database::record_egress`],
+//! so a slow database never holds up a request; when the queue is full the
+//! record is dropped with a warning.
+//!
+//! Requests are attributed to a job through the per-command proxy token
+//! (see [`grants`](super::grants)). Query strings are never stored, since
+//! they may carry credentials.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::history::EgressRecord;
+
+/// Records waiting to be written before new ones are dropped.
+const QUEUE_CAPACITY: usize = 1024;
+
+/// Sink for egress records.
+#[derive(Clone)]
+pub struct EgressLog {
+    tx: mpsc::Sender<EgressRecord>,
+}
+
+impl EgressLog {
+    /// Persist records to `db` from a background task.
+    pub fn spawn(db: Arc<dyn Database>) -> Self {
+        let (log, mut rx) = Self::channel();
+        tokio::spawn(async move {
+            while let Some(record) = rx.recv().await {
+                if let Err(e) = db.record_egress(&record).await {
+                    tracing::warn!("Failed to persist sandbox egress record: {}", e);
+                }
+            }
+        });
+        log
+    }
+
+    /// A log whose records are read from the returned receiver.
+    pub fn channel() -> (Self, mpsc::Receiver<EgressRecord>) {
+        let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+        (Self { tx }, rx)
+    }
+
+    /// Queue `record` without waiting.
+    pub fn record(&self, record: EgressRecord) {
+        if let Err(e) = self.tx.try_send(record) {
+            tracing::warn!("Dropping sandbox egress record: {}", e);
+        }
+    }
+}
+
+/// A record for a request about to be decided, marked `allowed` until
+/// something says otherwise.
+pub(crate) fn new_record(
+    job_id: Option<Uuid>,
+    method: &str,
+    host: &str,
+    path: &str,
+) -> EgressRecord {
+    EgressRecord {
+        id: Uuid::new_v4(),
+        job_id,
+        method: method.to_string(),
+        host: host.to_lowercase(),
+        path: path
+            .split(['?', '#'])
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        decision: "allowed".to_string(),
+        reason: None,
+        status: None,
+        request_bytes: 0,
+        response_bytes: 0,
+        credential_injected: false,
+        created_at: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_record_drops_query_string() {
+        let record = new_record(None, "GET", "API.Example.com", "/v1/items?key=secret#top");
+
+        assert_eq!(record.host, "api.example.com");
+        assert_eq!(record.path, "/v1/items");
+        assert_eq!(record.decision, "allowed");
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_instead_of_blocking() {
+        let (log, mut rx) = EgressLog::channel();
+        for _ in 0..QUEUE_CAPACITY + 5 {
+            log.record(new_record(None, "GET", "example.com", "/"));
+        }
+
+        let mut received = 0;
+        while rx.try_recv().is_ok() {
+            received += 1;
+        }
+        assert_eq!(received, QUEUE_CAPACITY);
+    }
+}
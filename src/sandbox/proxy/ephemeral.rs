@@ -0,0 +1,367 @@
+//! Per-job ephemeral credentials.
+//!
+//! A [`CredentialIssuer`] turns a long-lived secret the host holds (a GitHub
+//! App private key, a cloud identity token) into a short-lived, narrowly
+//! scoped credential. [`EphemeralCredentials`] mints one per issuer when a
+//! job starts and revokes it when the job ends.
+//!
+//! The proxy injects the job's minted credential for the issuer's secret
+//! name and never falls back to the credential resolver for it, so the
+//! long-lived secret never reaches the proxy, not even as an injected
+//! header. A request that cannot be attributed to a job with live
+//! credentials goes out without one.
+//!
+//! Credentials close to expiry are re-minted on use, and the old one is
+//! revoked.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use secrecy::{ExposeSecret, SecretString};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::sandbox::config::CredentialMapping;
+use crate::sandbox::error::{Result, SandboxError};
+
+/// A credential is re-minted when it has less than this left.
+const REFRESH_MARGIN: chrono::Duration = chrono::Duration::seconds(60);
+
+/// A short-lived credential minted for one job.
+pub struct IssuedCredential {
+    secret: SecretString,
+    /// When the issuer says the credential stops working, if it says.
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl IssuedCredential {
+    pub fn new(secret: String, expires_at: Option<DateTime<Utc>>) -> Self {
+        Self {
+            secret: SecretString::from(secret),
+            expires_at,
+        }
+    }
+
+    /// The credential value.
+    pub fn secret(&self) -> &str {
+        self.secret.expose_secret()
+    }
+
+    /// Whether the credential expires within the refresh margin of `now`.
+    fn expires_soon(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at - now < REFRESH_MARGIN)
+    }
+}
+
+impl std::fmt::Debug for IssuedCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IssuedCredential")
+            .field("secret", &"[REDACTED]")
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+/// Mints and revokes short-lived credentials for one upstream service.
+#[async_trait::async_trait]
+pub trait CredentialIssuer: Send + Sync {
+    /// Short name for logs and errors (`github_app`).
+    fn name(&self) -> &str;
+
+    /// Which requests the minted credential is injected into, and how.
+    fn mapping(&self) -> CredentialMapping;
+
+    /// Mint a credential for `job_id`.
+    async fn mint(&self, job_id: Uuid) -> Result<IssuedCredential>;
+
+    /// Revoke a credential minted by this issuer.
+    async fn revoke(&self, credential: &IssuedCredential) -> Result<()>;
+}
+
+/// Credentials minted for a job, one per issuer, in issuer order.
+type JobCredentials = Vec<IssuedCredential>;
+
+/// Issuers and the credentials they minted for running jobs.
+pub struct EphemeralCredentials {
+    issuers: Vec<Arc<dyn CredentialIssuer>>,
+    active: Mutex<HashMap<Uuid, JobCredentials>>,
+}
+
+impl EphemeralCredentials {
+    pub fn new(issuers: Vec<Arc<dyn CredentialIssuer>>) -> Self {
+        Self {
+            issuers,
+            active: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether no issuers are configured.
+    pub fn is_empty(&self) -> bool {
+        self.issuers.is_empty()
+    }
+
+    /// Where each issuer's credential is injected.
+    pub fn mappings(&self) -> Vec<CredentialMapping> {
+        self.issuers.iter().map(|issuer| issuer.mapping()).collect()
+    }
+
+    /// Whether `secret_name` is minted per job rather than resolved.
+    pub fn covers(&self, secret_name: &str) -> bool {
+        self.issuer_for(secret_name).is_some()
+    }
+
+    /// Mint a credential from every issuer for `job_id`. If any issuer
+    /// fails, the ones already minted are revoked and the job gets none.
+    /// Calling this again for a running job does nothing.
+    pub async fn start_job(&self, job_id: Uuid) -> Result<()> {
+        if self.issuers.is_empty() || self.active.lock().await.contains_key(&job_id) {
+            return Ok(());
+        }
+
+        let mut minted = Vec::with_capacity(self.issuers.len());
+        for issuer in &self.issuers {
+            match issuer.mint(job_id).await {
+                Ok(credential) => minted.push(credential),
+                Err(e) => {
+                    revoke_all(&self.issuers, job_id, minted).await;
+                    return Err(SandboxError::CredentialIssueFailed {
+                        issuer: issuer.name().to_string(),
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        tracing::info!(
+            job_id = %job_id,
+            issuers = self.issuers.len(),
+            "Minted ephemeral credentials"
+        );
+        self.active.lock().await.insert(job_id, minted);
+        Ok(())
+    }
+
+    /// Revoke every credential minted for `job_id`. Revocation failures
+    /// are logged; the credentials are forgotten either way.
+    pub async fn end_job(&self, job_id: Uuid) {
+        let minted = self.active.lock().await.remove(&job_id);
+        if let Some(minted) = minted {
+            revoke_all(&self.issuers, job_id, minted).await;
+            tracing::info!(job_id = %job_id, "Revoked ephemeral credentials");
+        }
+    }
+
+    /// Revoke the credentials of every job, for shutdown.
+    pub async fn end_all(&self) {
+        let jobs: Vec<Uuid> = self.active.lock().await.keys().copied().collect();
+        for job_id in jobs {
+            self.end_job(job_id).await;
+        }
+    }
+
+    /// The credential minted for `job_id` under `secret_name`, re-minted
+    /// first if it is about to expire. `None` when the job has no live
+    /// credentials or the secret is not issued per job.
+    pub async fn credential(&self, job_id: Uuid, secret_name: &str) -> Option<String> {
+        let index = self.issuer_for(secret_name)?;
+        let issuer = &self.issuers[index];
+
+        let mut active = self.active.lock().await;
+        let minted = active.get_mut(&job_id)?;
+        let current = minted.get_mut(index)?;
+        if current.expires_soon(Utc::now()) {
+            match issuer.mint(job_id).await {
+                Ok(fresh) => {
+                    let old = std::mem::replace(current, fresh);
+                    if let Err(e) = issuer.revoke(&old).await {
+                        tracing::warn!(
+                            job_id = %job_id,
+                            issuer = issuer.name(),
+                            "Failed to revoke replaced credential: {}",
+                            e
+                        );
+                    }
+                }
+                Err(e) => tracing::warn!(
+                    job_id = %job_id,
+                    issuer = issuer.name(),
+                    "Failed to refresh credential: {}",
+                    e
+                ),
+            }
+        }
+        Some(current.secret().to_string())
+    }
+
+    /// Number of jobs holding live credentials.
+    pub async fn active_jobs(&self) -> usize {
+        self.active.lock().await.len()
+    }
+
+    fn issuer_for(&self, secret_name: &str) -> Option<usize> {
+        self.issuers
+            .iter()
+            .position(|issuer| issuer.mapping().secret_name == secret_name)
+    }
+}
+
+/// Revoke `minted`, which holds credentials from the leading `issuers`.
+async fn revoke_all(issuers: &[Arc<dyn CredentialIssuer>], job_id: Uuid, minted: JobCredentials) {
+    for (issuer, credential) in issuers.iter().zip(minted) {
+        if let Err(e) = issuer.revoke(&credential).await {
+            tracing::warn!(
+                job_id = %job_id,
+                issuer = issuer.name(),
+                "Failed to revoke ephemeral credential: {}",
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::Mutex as StdMutex;
+
+    use super::*;
+    use crate::sandbox::config::CredentialLocation;
+
+    /// Issues `<name>-<n>` tokens and remembers what it revoked.
+    pub(crate) struct FakeIssuer {
+        name: String,
+        ttl: chrono::Duration,
+        fail: bool,
+        minted: StdMutex<u32>,
+        pub(crate) revoked: StdMutex<Vec<String>>,
+    }
+
+    impl FakeIssuer {
+        pub(crate) fn new(name: &str) -> Self {
+            Self {
+                name: name.to_string(),
+                ttl: chrono::Duration::hours(1),
+                fail: false,
+                minted: StdMutex::new(0),
+                revoked: StdMutex::new(Vec::new()),
+            }
+        }
+
+        fn failing(name: &str) -> Self {
+            Self {
+                fail: true,
+                ..Self::new(name)
+            }
+        }
+
+        fn expiring(name: &str) -> Self {
+            Self {
+                ttl: chrono::Duration::seconds(5),
+                ..Self::new(name)
+            }
+        }
+
+        pub(crate) fn revoked(&self) -> Vec<String> {
+            self.revoked.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl CredentialIssuer for FakeIssuer {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn mapping(&self) -> CredentialMapping {
+            CredentialMapping {
+                domain: format!("{}.example.com", self.name),
+                secret_name: self.name.to_uppercase(),
+                location: CredentialLocation::AuthorizationBearer,
+            }
+        }
+
+        async fn mint(&self, _job_id: Uuid) -> Result<IssuedCredential> {
+            if self.fail {
+                return Err(SandboxError::Config {
+                    reason: "issuer down".to_string(),
+                });
+            }
+            let mut minted = self.minted.lock().unwrap();
+            *minted += 1;
+            Ok(IssuedCredential::new(
+                format!("{}-{}", self.name, minted),
+                Some(Utc::now() + self.ttl),
+            ))
+        }
+
+        async fn revoke(&self, credential: &IssuedCredential) -> Result<()> {
+            self.revoked
+                .lock()
+                .unwrap()
+                .push(credential.secret().to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_credentials_live_for_the_job() {
+        let github = Arc::new(FakeIssuer::new("github"));
+        let creds = EphemeralCredentials::new(vec![github.clone()]);
+        let job_id = Uuid::new_v4();
+
+        assert!(creds.covers("GITHUB"));
+        assert!(!creds.covers("OPENAI_API_KEY"));
+        assert_eq!(creds.credential(job_id, "GITHUB").await, None);
+
+        creds.start_job(job_id).await.unwrap();
+        creds.start_job(job_id).await.unwrap();
+        assert_eq!(
+            creds.credential(job_id, "GITHUB").await.as_deref(),
+            Some("github-1")
+        );
+        assert_eq!(creds.credential(Uuid::new_v4(), "GITHUB").await, None);
+
+        creds.end_job(job_id).await;
+        assert_eq!(github.revoked(), ["github-1"]);
+        assert_eq!(creds.credential(job_id, "GITHUB").await, None);
+        assert_eq!(creds.active_jobs().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_issuer_rolls_back_the_job() {
+        let github = Arc::new(FakeIssuer::new("github"));
+        let creds =
+            EphemeralCredentials::new(vec![github.clone(), Arc::new(FakeIssuer::failing("cloud"))]);
+        let job_id = Uuid::new_v4();
+
+        let err = creds.start_job(job_id).await.unwrap_err();
+
+        assert!(err.to_string().contains("cloud"));
+        assert_eq!(github.revoked(), ["github-1"]);
+        assert_eq!(creds.active_jobs().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_expiring_credential_is_reminted() {
+        let cloud = Arc::new(FakeIssuer::expiring("cloud"));
+        let creds = EphemeralCredentials::new(vec![cloud.clone()]);
+        let job_id = Uuid::new_v4();
+        creds.start_job(job_id).await.unwrap();
+
+        assert_eq!(
+            creds.credential(job_id, "CLOUD").await.as_deref(),
+            Some("cloud-2")
+        );
+        assert_eq!(cloud.revoked(), ["cloud-1"]);
+
+        creds.end_all().await;
+        assert_eq!(cloud.revoked(), ["cloud-1", "cloud-2"]);
+    }
+
+    #[test]
+    fn test_issued_credential_debug_hides_secret() {
+        let credential = IssuedCredential::new("ghs_secret".to_string(), None);
+        assert!(!format!("{:?}", credential).contains("ghs_secret"));
+    }
+}
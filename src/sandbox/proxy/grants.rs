@@ -0,0 +1,441 @@
+//! Per-command network grants.
+//!
+//! The proxy allowlist is global. An [`ExecPolicy`] widens or narrows it for
+//! one command: "allow pypi.org for this pip install". The manager registers
+//! the extra domains here and hands the container a proxy URL carrying a
+//! random token; the proxy reads the token back from `Proxy-Authorization`
+//! and the policy decider merges the grant with the base allowlist:
+//!
+//! 1. a host on the grant's deny list is refused, even if the base list
+//!    allows it;
+//! 2. a host on the base allowlist is allowed;
+//! 3. a host on the grant's allow list is allowed, and the first such use
+//!    per host is recorded.
+//!
+//! Every grant is appended to an audit log before the command starts. An
+//! unauditable grant is refused. A grant ends when its [`GrantHandle`] is
+//! dropped, so a token leaked from a finished container is worthless.
+//!
+//! A command run for a job gets a token even without overrides, so the
+//! proxy can attribute its traffic to the job ([`NetworkGrants::job_for`]).
+
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::sandbox::config::SandboxPolicy;
+use crate::sandbox::error::Result;
+use crate::sandbox::proxy::allowlist::DomainAllowlist;
+
+/// Default audit log location (`~/.ironclaw/sandbox_network_grants.jsonl`).
+pub fn default_audit_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".ironclaw")
+        .join("sandbox_network_grants.jsonl")
+}
+
+/// Sandbox policy for one command, with network overrides.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecPolicy {
+    pub policy: SandboxPolicy,
+    /// Domains allowed for this command on top of the base allowlist.
+    /// Wildcards (`*.example.com`) work as in the allowlist.
+    pub allow_domains: Vec<String>,
+    /// Domains refused for this command, even if the base allowlist has them.
+    pub deny_domains: Vec<String>,
+    /// Why the override was asked for, for the audit log.
+    pub reason: Option<String>,
+    /// The job the command runs for; its proxy traffic is attributed to it.
+    pub job_id: Option<uuid::Uuid>,
+    /// Report the files the command created, modified or deleted in its
+    /// working directory ([`ExecOutput::changes`](crate::sandbox::ExecOutput::changes)).
+    pub capture_diff: bool,
+}
+
+impl ExecPolicy {
+    /// `policy` with no network overrides.
+    pub fn new(policy: SandboxPolicy) -> Self {
+        Self {
+            policy,
+            allow_domains: Vec::new(),
+            deny_domains: Vec::new(),
+            reason: None,
+            job_id: None,
+            capture_diff: false,
+        }
+    }
+
+    /// Also allow `domain` for this command.
+    pub fn allow_domain(mut self, domain: &str) -> Self {
+        self.allow_domains.push(domain.to_string());
+        self
+    }
+
+    /// Refuse `domain` for this command.
+    pub fn deny_domain(mut self, domain: &str) -> Self {
+        self.deny_domains.push(domain.to_string());
+        self
+    }
+
+    /// Record why the overrides are needed.
+    pub fn with_reason(mut self, reason: &str) -> Self {
+        self.reason = Some(reason.to_string());
+        self
+    }
+
+    /// Attribute the command's traffic to `job_id`.
+    pub fn for_job(mut self, job_id: uuid::Uuid) -> Self {
+        self.job_id = Some(job_id);
+        self
+    }
+
+    /// Capture the workspace changes the command makes.
+    pub fn with_diff_capture(mut self) -> Self {
+        self.capture_diff = true;
+        self
+    }
+
+    /// Whether this changes what the proxy lets through.
+    pub fn has_network_overrides(&self) -> bool {
+        !self.allow_domains.is_empty() || !self.deny_domains.is_empty()
+    }
+}
+
+impl From<SandboxPolicy> for ExecPolicy {
+    fn from(policy: SandboxPolicy) -> Self {
+        Self::new(policy)
+    }
+}
+
+/// One line of the grant audit log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkGrantRecord {
+    pub at: DateTime<Utc>,
+    pub grant_id: uuid::Uuid,
+    /// `granted`, `used`, or `denied`.
+    pub event: String,
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow_domains: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny_domains: Vec<String>,
+    /// The host a `used` or `denied` request went to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// What a grant says about a host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrantVerdict {
+    /// No grant for this token, or the grant does not mention the host.
+    NotCovered,
+    /// The grant allows the host.
+    Allowed,
+    /// The grant denies the host.
+    Denied(String),
+}
+
+struct ActiveGrant {
+    id: uuid::Uuid,
+    job_id: Option<uuid::Uuid>,
+    command: String,
+    allow: DomainAllowlist,
+    deny: DomainAllowlist,
+    /// Hosts already recorded as `used`.
+    used: HashSet<String>,
+}
+
+/// Grants in force, keyed by proxy token.
+pub struct NetworkGrants {
+    audit_path: PathBuf,
+    active: Mutex<HashMap<String, ActiveGrant>>,
+}
+
+impl NetworkGrants {
+    pub fn new(audit_path: PathBuf) -> Self {
+        Self {
+            audit_path,
+            active: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Put `policy`'s overrides in force for `command`. Returns `None` when
+    /// there are none and no job to attribute traffic to. The grant lasts
+    /// until the handle is dropped.
+    pub fn register(
+        self: &Arc<Self>,
+        command: &str,
+        policy: &ExecPolicy,
+    ) -> Result<Option<GrantHandle>> {
+        if !policy.has_network_overrides() && policy.job_id.is_none() {
+            return Ok(None);
+        }
+
+        let id = uuid::Uuid::new_v4();
+        if policy.has_network_overrides() {
+            self.append_audit(&NetworkGrantRecord {
+                at: Utc::now(),
+                grant_id: id,
+                event: "granted".to_string(),
+                command: command.to_string(),
+                allow_domains: policy.allow_domains.clone(),
+                deny_domains: policy.deny_domains.clone(),
+                host: None,
+                reason: policy.reason.clone(),
+            })?;
+            tracing::info!(
+                grant = %id,
+                allow = ?policy.allow_domains,
+                deny = ?policy.deny_domains,
+                "Granted per-command network access"
+            );
+        }
+
+        // The token is a capability handed to the container; it never
+        // appears in the audit log.
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        self.lock().insert(
+            token.clone(),
+            ActiveGrant {
+                id,
+                job_id: policy.job_id,
+                command: command.to_string(),
+                allow: DomainAllowlist::new(&policy.allow_domains),
+                deny: DomainAllowlist::new(&policy.deny_domains),
+                used: HashSet::new(),
+            },
+        );
+        Ok(Some(GrantHandle {
+            token,
+            grants: Arc::clone(self),
+        }))
+    }
+
+    /// What the grant behind `token` says about `host`. Denials are
+    /// recorded.
+    pub fn check(&self, token: &str, host: &str) -> GrantVerdict {
+        let (id, command) = {
+            let active = self.lock();
+            let Some(grant) = active.get(token) else {
+                return GrantVerdict::NotCovered;
+            };
+            if !grant.deny.is_empty() && grant.deny.is_allowed(host).is_allowed() {
+                (grant.id, grant.command.clone())
+            } else if !grant.allow.is_empty() && grant.allow.is_allowed(host).is_allowed() {
+                return GrantVerdict::Allowed;
+            } else {
+                return GrantVerdict::NotCovered;
+            }
+        };
+
+        let reason = format!("host '{}' denied for this command", host);
+        self.record(id, "denied", command, host, Some(reason.clone()));
+        GrantVerdict::Denied(reason)
+    }
+
+    /// Record that a request to `host` got through only because of the grant
+    /// behind `token`. Each host is recorded once per grant.
+    pub fn record_use(&self, token: &str, host: &str) {
+        let first_use = {
+            let mut active = self.lock();
+            match active.get_mut(token) {
+                Some(grant) => grant
+                    .used
+                    .insert(host.to_lowercase())
+                    .then(|| (grant.id, grant.command.clone())),
+                None => None,
+            }
+        };
+        if let Some((id, command)) = first_use {
+            self.record(id, "used", command, host, None);
+        }
+    }
+
+    /// The job behind `token`, if its command runs for one.
+    pub fn job_for(&self, token: &str) -> Option<uuid::Uuid> {
+        self.lock().get(token).and_then(|grant| grant.job_id)
+    }
+
+    /// Number of grants in force.
+    pub fn active_count(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Every record in the audit log, oldest first.
+    pub fn audit_log(&self) -> Result<Vec<NetworkGrantRecord>> {
+        let content = match std::fs::read_to_string(&self.audit_path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    fn record(
+        &self,
+        grant_id: uuid::Uuid,
+        event: &str,
+        command: String,
+        host: &str,
+        reason: Option<String>,
+    ) {
+        let record = NetworkGrantRecord {
+            at: Utc::now(),
+            grant_id,
+            event: event.to_string(),
+            command,
+            allow_domains: Vec::new(),
+            deny_domains: Vec::new(),
+            host: Some(host.to_string()),
+            reason,
+        };
+        if let Err(e) = self.append_audit(&record) {
+            tracing::error!("Failed to write sandbox network grant audit log: {}", e);
+        }
+    }
+
+    fn append_audit(&self, record: &NetworkGrantRecord) -> Result<()> {
+        if let Some(parent) = self.audit_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut line = serde_json::to_string(record).map_err(std::io::Error::other)?;
+        line.push('\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.audit_path)?
+            .write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ActiveGrant>> {
+        self.active
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Keeps a grant in force. Dropping it revokes the grant.
+pub struct GrantHandle {
+    token: String,
+    grants: Arc<NetworkGrants>,
+}
+
+impl GrantHandle {
+    /// Token the container presents to the proxy.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+impl Drop for GrantHandle {
+    fn drop(&mut self) {
+        self.grants.lock().remove(&self.token);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grants(dir: &tempfile::TempDir) -> Arc<NetworkGrants> {
+        Arc::new(NetworkGrants::new(dir.path().join("grants.jsonl")))
+    }
+
+    #[test]
+    fn test_policy_without_overrides_needs_no_grant() {
+        let dir = tempfile::tempdir().unwrap();
+        let grants = grants(&dir);
+
+        let handle = grants
+            .register("ls", &ExecPolicy::new(SandboxPolicy::ReadOnly))
+            .unwrap();
+
+        assert!(handle.is_none());
+        assert!(grants.audit_log().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_grant_allows_denies_and_expires() {
+        let dir = tempfile::tempdir().unwrap();
+        let grants = grants(&dir);
+        let policy = ExecPolicy::new(SandboxPolicy::WorkspaceWrite)
+            .allow_domain("*.pypi.org")
+            .deny_domain("github.com")
+            .with_reason("pip install");
+
+        let handle = grants.register("pip install x", &policy).unwrap().unwrap();
+        let token = handle.token().to_string();
+
+        assert_eq!(
+            grants.check(&token, "files.pypi.org"),
+            GrantVerdict::Allowed
+        );
+        assert!(matches!(
+            grants.check(&token, "github.com"),
+            GrantVerdict::Denied(_)
+        ));
+        assert_eq!(grants.check(&token, "crates.io"), GrantVerdict::NotCovered);
+        assert_eq!(
+            grants.check("unknown", "files.pypi.org"),
+            GrantVerdict::NotCovered
+        );
+
+        drop(handle);
+        assert_eq!(grants.active_count(), 0);
+        assert_eq!(
+            grants.check(&token, "files.pypi.org"),
+            GrantVerdict::NotCovered
+        );
+    }
+
+    #[test]
+    fn test_audit_records_grant_uses_once_and_hides_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let grants = grants(&dir);
+        let policy = ExecPolicy::new(SandboxPolicy::ReadOnly)
+            .allow_domain("pypi.org")
+            .deny_domain("evil.example");
+
+        let handle = grants.register("pip download", &policy).unwrap().unwrap();
+        grants.record_use(handle.token(), "pypi.org");
+        grants.record_use(handle.token(), "pypi.org");
+        grants.check(handle.token(), "evil.example");
+
+        let log = grants.audit_log().unwrap();
+        let events: Vec<&str> = log.iter().map(|r| r.event.as_str()).collect();
+        assert_eq!(events, ["granted", "used", "denied"]);
+        assert_eq!(log[0].allow_domains, ["pypi.org"]);
+        assert_eq!(log[1].host.as_deref(), Some("pypi.org"));
+        assert!(log.iter().all(|r| r.grant_id == log[0].grant_id));
+
+        let raw = std::fs::read_to_string(dir.path().join("grants.jsonl")).unwrap();
+        assert!(!raw.contains(handle.token()));
+    }
+
+    #[test]
+    fn test_job_gets_token_without_audited_grant() {
+        let dir = tempfile::tempdir().unwrap();
+        let grants = grants(&dir);
+        let job_id = uuid::Uuid::new_v4();
+        let policy = ExecPolicy::new(SandboxPolicy::ReadOnly).for_job(job_id);
+
+        let handle = grants.register("curl x", &policy).unwrap().unwrap();
+
+        assert_eq!(grants.job_for(handle.token()), Some(job_id));
+        assert_eq!(grants.check(handle.token(), "x"), GrantVerdict::NotCovered);
+        assert!(grants.audit_log().unwrap().is_empty());
+        drop(handle);
+        assert_eq!(grants.active_count(), 0);
+    }
+}
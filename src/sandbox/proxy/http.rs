@@ -10,6 +10,15 @@
 //!                                                             ├─► Inject credentials
 //!                                                             └─► Log requests
 //! ```
+//!
+//! Requests for [`MIRROR_HOST`] are answered by the [`RegistryMirror`],
+//! which fetches packages over HTTPS on the container's behalf and verifies
+//! them against the registries' published checksums.
+//!
+//! With an [`EgressLog`] attached, every request is also recorded for
+//! `ironclaw logs egress`. With [`EphemeralCredentials`] attached, secrets
+//! its issuers cover are injected from the requesting job's minted
+//! credentials instead of the resolver.
 
 use std::convert::Infallible;
 use std::net::SocketAddr;
@@ -24,8 +33,16 @@ use hyper_util::rt::TokioIo;
 use tokio::net::TcpListener;
 use tokio::sync::RwLock;
 
+use crate::history::EgressRecord;
 use crate::sandbox::config::CredentialLocation;
 use crate::sandbox::error::{Result, SandboxError};
+use crate::sandbox::proxy::cache::{PackageCache, PackageRegistry};
+use crate::sandbox::proxy::egress::{EgressLog, new_record};
+use crate::sandbox::proxy::ephemeral::EphemeralCredentials;
+use crate::sandbox::proxy::grants::NetworkGrants;
+use crate::sandbox::proxy::mirror::{
+    MIRROR_HOST, MirrorError, MirrorRoute, RegistryMirror, RegistryUpstreams, UpstreamAccess,
+};
 use crate::sandbox::proxy::policy::{NetworkDecision, NetworkPolicyDecider, NetworkRequest};
 
 /// State shared across proxy connections.
@@ -38,6 +55,58 @@ struct ProxyState {
     request_count: std::sync::atomic::AtomicU64,
     /// Whether the proxy is running.
     running: std::sync::atomic::AtomicBool,
+    /// Optional cache for package registry artifacts.
+    package_cache: Option<Arc<PackageCache>>,
+    /// Registry mirror answering requests for [`MIRROR_HOST`].
+    mirror: RegistryMirror,
+    /// Where requests are recorded, if anywhere.
+    egress: Option<EgressLog>,
+    /// Grants, for attributing requests to the job behind their token.
+    grants: Option<Arc<NetworkGrants>>,
+    /// Per-job credentials that replace resolved secrets.
+    ephemeral: Option<Arc<EphemeralCredentials>>,
+}
+
+impl ProxyState {
+    /// The job behind a grant token, if any.
+    fn job_for(&self, grant: Option<&str>) -> Option<uuid::Uuid> {
+        match (grant, &self.grants) {
+            (Some(token), Some(grants)) => grants.job_for(token),
+            _ => None,
+        }
+    }
+
+    /// Start an egress record for `req`, if requests are being recorded.
+    fn egress_record(&self, req: &NetworkRequest) -> Option<EgressRecord> {
+        self.egress.as_ref()?;
+        let job_id = self.job_for(req.grant.as_deref());
+        Some(new_record(job_id, &req.method, &req.host, &req.path))
+    }
+
+    /// The value to inject for `secret_name` into a request carrying
+    /// `grant`. Secrets covered by an issuer come only from the job's
+    /// minted credentials; the resolver is never asked for them.
+    async fn credential(&self, secret_name: &str, grant: Option<&str>) -> Option<String> {
+        if let Some(ephemeral) = &self.ephemeral
+            && ephemeral.covers(secret_name)
+        {
+            let Some(job_id) = self.job_for(grant) else {
+                tracing::warn!(
+                    "Proxy: {} is issued per job but the request has no job",
+                    secret_name
+                );
+                return None;
+            };
+            return ephemeral.credential(job_id, secret_name).await;
+        }
+        self.credential_resolver.resolve(secret_name).await
+    }
+
+    fn log_egress(&self, record: Option<EgressRecord>) {
+        if let (Some(log), Some(record)) = (&self.egress, record) {
+            log.record(record);
+        }
+    }
 }
 
 /// Resolves secret names to their values.
@@ -104,12 +173,74 @@ impl HttpProxy {
                 credential_resolver,
                 request_count: std::sync::atomic::AtomicU64::new(0),
                 running: std::sync::atomic::AtomicBool::new(false),
+                package_cache: None,
+                mirror: RegistryMirror::new(RegistryUpstreams::default()),
+                egress: None,
+                grants: None,
+                ephemeral: None,
             }),
             addr: RwLock::new(None),
             shutdown_tx: RwLock::new(None),
         }
     }
 
+    /// Serve package registry artifacts through a local cache.
+    ///
+    /// Must be called before [`HttpProxy::start`].
+    pub fn with_package_cache(mut self, cache: Arc<PackageCache>) -> Self {
+        match Arc::get_mut(&mut self.state) {
+            Some(state) => state.package_cache = Some(cache),
+            None => tracing::warn!("Proxy already started; package cache not attached"),
+        }
+        self
+    }
+
+    /// Mirror registries from `upstreams` instead of the public ones.
+    ///
+    /// Must be called before [`HttpProxy::start`].
+    pub fn with_registry_upstreams(mut self, upstreams: RegistryUpstreams) -> Self {
+        match Arc::get_mut(&mut self.state) {
+            Some(state) => state.mirror = RegistryMirror::new(upstreams),
+            None => tracing::warn!("Proxy already started; registry upstreams not changed"),
+        }
+        self
+    }
+
+    /// Record every request in `log`.
+    ///
+    /// Must be called before [`HttpProxy::start`].
+    pub fn with_egress_log(mut self, log: EgressLog) -> Self {
+        match Arc::get_mut(&mut self.state) {
+            Some(state) => state.egress = Some(log),
+            None => tracing::warn!("Proxy already started; egress log not attached"),
+        }
+        self
+    }
+
+    /// Attribute recorded requests to jobs through the tokens in `grants`.
+    ///
+    /// Must be called before [`HttpProxy::start`].
+    pub fn with_grants(mut self, grants: Arc<NetworkGrants>) -> Self {
+        match Arc::get_mut(&mut self.state) {
+            Some(state) => state.grants = Some(grants),
+            None => tracing::warn!("Proxy already started; grants not attached"),
+        }
+        self
+    }
+
+    /// Inject the requesting job's minted credentials for the secrets
+    /// `credentials` covers. Jobs are identified through the grants, so
+    /// [`HttpProxy::with_grants`] must be attached too.
+    ///
+    /// Must be called before [`HttpProxy::start`].
+    pub fn with_ephemeral_credentials(mut self, credentials: Arc<EphemeralCredentials>) -> Self {
+        match Arc::get_mut(&mut self.state) {
+            Some(state) => state.ephemeral = Some(credentials),
+            None => tracing::warn!("Proxy already started; ephemeral credentials not attached"),
+        }
+        self
+    }
+
     /// Start the proxy server on the given port (0 for auto-assign).
     pub async fn start(&self, port: u16) -> Result<SocketAddr> {
         let listener = TcpListener::bind(format!("127.0.0.1:{}", port))
@@ -226,7 +357,7 @@ async fn handle_request(
     let uri = req.uri().to_string();
     let method = req.method().to_string();
 
-    let network_req = match NetworkRequest::from_url(&method, &uri) {
+    let mut network_req = match NetworkRequest::from_url(&method, &uri) {
         Some(r) => r,
         None => {
             tracing::warn!("Proxy: invalid URL: {}", uri);
@@ -237,21 +368,144 @@ async fn handle_request(
         }
     };
 
+    network_req.grant = grant_token(req.headers());
+    if network_req.host.eq_ignore_ascii_case(MIRROR_HOST) {
+        return Ok(handle_mirror(req.method(), &network_req, &state).await);
+    }
+    let mut egress = state.egress_record(&network_req);
+
     // Make policy decision
     let decision = state.decider.decide(&network_req).await;
 
     match decision {
         NetworkDecision::Deny { reason } => {
             tracing::info!("Proxy: blocked {} {} - {}", method, uri, reason);
+            if let Some(record) = egress.as_mut() {
+                record.decision = "denied".to_string();
+                record.reason = Some(reason.clone());
+            }
+            state.log_egress(egress);
             Ok(error_response(StatusCode::FORBIDDEN, reason))
         }
         NetworkDecision::Allow | NetworkDecision::AllowWithCredentials { .. } => {
+            // Registry artifacts are immutable; serve repeats from the cache.
+            let cacheable = match &state.package_cache {
+                Some(_) if req.method() == Method::GET => {
+                    PackageRegistry::classify(&network_req.host, &network_req.path)
+                }
+                _ => None,
+            };
+            if cacheable.is_some()
+                && let Some(cache) = &state.package_cache
+                && let Some(body) = cache.get(&uri).await
+            {
+                tracing::debug!("Proxy: package cache hit for {}", uri);
+                if let Some(record) = egress.as_mut() {
+                    record.decision = "cached".to_string();
+                    record.response_bytes = body.len() as i64;
+                }
+                state.log_egress(egress);
+                return Ok(cached_response(body));
+            }
+
             // Forward the request
-            forward_request(req, decision, state).await
+            let response =
+                forward_request(req, decision, Arc::clone(&state), cacheable, &mut egress).await;
+            state.log_egress(egress);
+            response
         }
     }
 }
 
+/// The per-command grant token, sent by clients as the user part of the
+/// proxy URL (`http://<token>@host:port`), which arrives as Basic
+/// `Proxy-Authorization`.
+fn grant_token(headers: &hyper::HeaderMap) -> Option<String> {
+    use base64::Engine;
+
+    let value = headers.get("proxy-authorization")?.to_str().ok()?;
+    let (scheme, credentials) = value.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(credentials.trim())
+        .ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let user = decoded.split(':').next().unwrap_or_default();
+    (!user.is_empty()).then(|| user.to_string())
+}
+
+/// Serve a registry mirror request. The egress record names the upstream,
+/// not the mirror.
+async fn handle_mirror(
+    method: &Method,
+    network_req: &NetworkRequest,
+    state: &ProxyState,
+) -> Response<BoxBody<Bytes, Infallible>> {
+    let route = match MirrorRoute::parse(&network_req.path) {
+        Some(route) if method == Method::GET => route,
+        _ => {
+            return error_response(
+                StatusCode::NOT_FOUND,
+                "Not a registry mirror path".to_string(),
+            );
+        }
+    };
+
+    let upstream = state.mirror.upstream_url(&route);
+    let mut egress = NetworkRequest::from_url("GET", &upstream).and_then(|mut upstream_req| {
+        upstream_req.grant = network_req.grant.clone();
+        state.egress_record(&upstream_req)
+    });
+    let access = UpstreamAccess {
+        decider: state.decider.as_ref(),
+        grant: network_req.grant.as_deref(),
+    };
+    let result = state
+        .mirror
+        .serve(&route, access, state.package_cache.as_deref())
+        .await;
+
+    let response = match result {
+        Ok(served) => {
+            if let Some(record) = egress.as_mut() {
+                record.status = Some(i32::from(served.status));
+                record.response_bytes = served.body.len() as i64;
+                if served.cached {
+                    record.decision = "cached".to_string();
+                }
+            }
+            let mut builder = Response::builder().status(served.status);
+            if let Some(content_type) = &served.content_type {
+                builder = builder.header("Content-Type", content_type.as_str());
+            }
+            if served.cached {
+                builder = builder.header("X-IronClaw-Cache", "HIT");
+            }
+            builder.body(full_body(served.body)).unwrap_or_else(|_| {
+                error_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to build mirror response".to_string(),
+                )
+            })
+        }
+        Err(e) => {
+            tracing::info!("Proxy: registry mirror refused {}: {}", upstream, e);
+            if let Some(record) = egress.as_mut() {
+                if matches!(e, MirrorError::Denied { .. }) {
+                    record.decision = "denied".to_string();
+                }
+                record.reason = Some(e.to_string());
+            }
+            let status = StatusCode::from_u16(e.status()).unwrap_or(StatusCode::BAD_GATEWAY);
+            error_response(status, e.to_string())
+        }
+    };
+    state.log_egress(egress);
+    response
+}
+
 /// Handle CONNECT method for HTTPS tunneling.
 async fn handle_connect(
     req: Request<hyper::body::Incoming>,
@@ -273,7 +527,9 @@ async fn handle_connect(
         url: format!("https://{}", host),
         host: host.clone(),
         path: "/".to_string(),
+        grant: grant_token(req.headers()),
     };
+    let mut egress = state.egress_record(&network_req);
 
     let decision = state.decider.decide(&network_req).await;
 
@@ -281,6 +537,11 @@ async fn handle_connect(
         && let NetworkDecision::Deny { reason } = decision
     {
         tracing::info!("Proxy: blocked CONNECT {} - {}", host, reason);
+        if let Some(record) = egress.as_mut() {
+            record.decision = "denied".to_string();
+            record.reason = Some(reason.clone());
+        }
+        state.log_egress(egress);
         return error_response(StatusCode::FORBIDDEN, reason);
     }
 
@@ -293,6 +554,10 @@ async fn handle_connect(
         "Proxy: CONNECT tunneling not implemented, returning 502 for {}",
         host
     );
+    if let Some(record) = egress.as_mut() {
+        record.reason = Some("CONNECT tunneling is not supported".to_string());
+    }
+    state.log_egress(egress);
     error_response(
         StatusCode::BAD_GATEWAY,
         "CONNECT tunneling is not supported by this proxy".to_string(),
@@ -304,16 +569,19 @@ async fn forward_request(
     req: Request<hyper::body::Incoming>,
     decision: NetworkDecision,
     state: Arc<ProxyState>,
+    cacheable: Option<PackageRegistry>,
+    egress: &mut Option<EgressRecord>,
 ) -> std::result::Result<Response<BoxBody<Bytes, Infallible>>, Infallible> {
     let method = req.method().clone();
     let uri = req.uri().clone();
+    let grant = grant_token(req.headers());
 
     // Build the forwarded request (redirect following disabled to prevent
     // allowlisted domains from redirecting to non-allowlisted targets — Finding 4)
-    let client = reqwest::Client::builder()
+    let client = crate::outbound::client_builder()
         .redirect(reqwest::redirect::Policy::none())
         .build()
-        .unwrap_or_else(|_| reqwest::Client::new());
+        .unwrap_or_else(|_| crate::outbound::client());
     let mut builder = client.request(
         reqwest::Method::from_bytes(method.as_str().as_bytes()).unwrap_or(reqwest::Method::GET),
         uri.to_string(),
@@ -357,7 +625,7 @@ async fn forward_request(
         location,
     } = decision
     {
-        if let Some(credential) = state.credential_resolver.resolve(&secret_name).await {
+        if let Some(credential) = state.credential(&secret_name, grant.as_deref()).await {
             builder = match location {
                 CredentialLocation::AuthorizationBearer => {
                     builder.header("Authorization", format!("Bearer {}", credential))
@@ -368,6 +636,9 @@ async fn forward_request(
                 }
             };
             tracing::debug!("Proxy: injected credential for {}", secret_name);
+            if let Some(record) = egress.as_mut() {
+                record.credential_injected = true;
+            }
         } else {
             tracing::warn!("Proxy: credential {} not found", secret_name);
         }
@@ -385,6 +656,9 @@ async fn forward_request(
         }
     };
 
+    if let Some(record) = egress.as_mut() {
+        record.request_bytes = body_bytes.len() as i64;
+    }
     if !body_bytes.is_empty() {
         builder = builder.body(body_bytes.to_vec());
     }
@@ -394,9 +668,22 @@ async fn forward_request(
         Ok(response) => {
             let status = response.status();
             let headers = response.headers().clone();
+            if let Some(record) = egress.as_mut() {
+                record.status = Some(i32::from(status.as_u16()));
+            }
 
             match response.bytes().await {
                 Ok(body) => {
+                    if let Some(record) = egress.as_mut() {
+                        record.response_bytes = body.len() as i64;
+                    }
+                    if status == reqwest::StatusCode::OK
+                        && let (Some(registry), Some(cache)) = (cacheable, &state.package_cache)
+                        && let Err(e) = cache.put(&uri.to_string(), registry, &body).await
+                    {
+                        tracing::warn!("Proxy: failed to cache {}: {}", uri, e);
+                    }
+
                     let mut builder = Response::builder().status(status.as_u16());
 
                     for (name, value) in headers.iter() {
@@ -427,6 +714,9 @@ async fn forward_request(
         }
         Err(e) => {
             tracing::error!("Proxy: request failed: {}", e);
+            if let Some(record) = egress.as_mut() {
+                record.reason = Some(format!("request failed: {}", e));
+            }
             Ok(error_response(
                 StatusCode::BAD_GATEWAY,
                 format!("Request failed: {}", e),
@@ -435,6 +725,22 @@ async fn forward_request(
     }
 }
 
+/// Build a response for an artifact served from the package cache.
+fn cached_response(body: Bytes) -> Response<BoxBody<Bytes, Infallible>> {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/octet-stream")
+        .header("Content-Length", body.len())
+        .header("X-IronClaw-Cache", "HIT")
+        .body(full_body(body))
+        .unwrap_or_else(|_| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to build cached response".to_string(),
+            )
+        })
+}
+
 /// Compute the lowercased header names that must be stripped from the
 /// container's request before forwarding, given a network policy decision.
 ///
@@ -506,6 +812,126 @@ mod tests {
         tokio::time::sleep(std::time::Duration::from_millis(50)).await;
     }
 
+    #[tokio::test]
+    async fn test_denied_request_is_recorded_for_its_job() {
+        use crate::sandbox::config::SandboxPolicy;
+        use crate::sandbox::proxy::grants::ExecPolicy;
+        use crate::sandbox::proxy::policy::DenyAllDecider;
+
+        let dir = tempfile::tempdir().unwrap();
+        let grants = Arc::new(NetworkGrants::new(dir.path().join("grants.jsonl")));
+        let job_id = uuid::Uuid::new_v4();
+        let handle = grants
+            .register(
+                "curl",
+                &ExecPolicy::new(SandboxPolicy::ReadOnly).for_job(job_id),
+            )
+            .unwrap()
+            .unwrap();
+        let (log, mut records) = EgressLog::channel();
+        let proxy = HttpProxy::new(
+            Arc::new(DenyAllDecider::new("no network")),
+            Arc::new(NoCredentialResolver),
+        )
+        .with_grants(Arc::clone(&grants))
+        .with_egress_log(log);
+        let addr = proxy.start(0).await.unwrap();
+
+        let client = reqwest::Client::builder()
+            .proxy(reqwest::Proxy::http(format!("http://{}@{}", handle.token(), addr)).unwrap())
+            .build()
+            .unwrap();
+        let response = client
+            .get("http://blocked.example/upload?token=secret")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+
+        let record = records.recv().await.unwrap();
+        assert_eq!(record.job_id, Some(job_id));
+        assert_eq!(record.decision, "denied");
+        assert_eq!(record.host, "blocked.example");
+        assert_eq!(record.path, "/upload");
+        assert!(!record.credential_injected);
+
+        proxy.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_issued_secret_comes_only_from_the_job() {
+        use crate::sandbox::config::SandboxPolicy;
+        use crate::sandbox::proxy::ephemeral::tests::FakeIssuer;
+        use crate::sandbox::proxy::grants::ExecPolicy;
+        use crate::sandbox::proxy::policy::AllowAllDecider;
+
+        /// Resolves every secret to a long-lived value.
+        struct LongLived;
+
+        #[async_trait::async_trait]
+        impl CredentialResolver for LongLived {
+            async fn resolve(&self, name: &str) -> Option<String> {
+                Some(format!("long-lived-{}", name))
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let grants = Arc::new(NetworkGrants::new(dir.path().join("grants.jsonl")));
+        let ephemeral = Arc::new(EphemeralCredentials::new(vec![Arc::new(FakeIssuer::new(
+            "github",
+        ))]));
+        let proxy = HttpProxy::new(Arc::new(AllowAllDecider), Arc::new(LongLived))
+            .with_grants(Arc::clone(&grants))
+            .with_ephemeral_credentials(Arc::clone(&ephemeral));
+        let state = &proxy.state;
+
+        let job_id = uuid::Uuid::new_v4();
+        let handle = grants
+            .register(
+                "git push",
+                &ExecPolicy::new(SandboxPolicy::ReadOnly).for_job(job_id),
+            )
+            .unwrap()
+            .unwrap();
+        let token = Some(handle.token());
+
+        // Before the job starts, and for requests without a job, nothing.
+        assert_eq!(state.credential("GITHUB", token).await, None);
+        ephemeral.start_job(job_id).await.unwrap();
+        assert_eq!(state.credential("GITHUB", None).await, None);
+        assert_eq!(
+            state.credential("GITHUB", token).await.as_deref(),
+            Some("github-1")
+        );
+
+        // Secrets no issuer covers still go through the resolver.
+        assert_eq!(
+            state.credential("OPENAI_API_KEY", token).await.as_deref(),
+            Some("long-lived-OPENAI_API_KEY")
+        );
+
+        ephemeral.end_job(job_id).await;
+        assert_eq!(state.credential("GITHUB", token).await, None);
+    }
+
+    #[test]
+    fn test_grant_token_from_proxy_authorization() {
+        use base64::Engine;
+
+        let mut headers = hyper::HeaderMap::new();
+        assert_eq!(grant_token(&headers), None);
+
+        let encoded = base64::engine::general_purpose::STANDARD.encode("abc123:");
+        headers.insert(
+            "proxy-authorization",
+            format!("Basic {}", encoded).parse().unwrap(),
+        );
+        assert_eq!(grant_token(&headers).as_deref(), Some("abc123"));
+
+        headers.insert("proxy-authorization", "Bearer abc123".parse().unwrap());
+        assert_eq!(grant_token(&headers), None);
+    }
+
     #[test]
     fn test_hop_by_hop_headers() {
         assert!(is_hop_by_hop_header("connection"));
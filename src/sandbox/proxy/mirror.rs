@@ -0,0 +1,790 @@
+//! Package registry mirror served by the sandbox proxy.
+//!
+//! Registries are HTTPS-only and the proxy does not look inside (or even
+//! open) CONNECT tunnels, so downloads from crates.io, npm and PyPI would
+//! never reach the [`PackageCache`]. Sandbox containers are instead pointed
+//! at a plain-HTTP mirror on the proxy itself, addressed as [`MIRROR_HOST`];
+//! the proxy fetches from the real registries over HTTPS.
+//!
+//! ```text
+//! GET http://registry.sandbox.internal/crates/dl/serde/1.0.0/download
+//!        │
+//!        ├─► upstream allowed by the network policy?        ── no ──► 403
+//!        ├─► cache hit (sha256 re-verified by the cache)    ──────► serve from disk
+//!        ├─► look up the registry's published checksum      ── none ─► 404
+//!        └─► fetch, verify against it, cache, serve         ── mismatch ─► 502
+//! ```
+//!
+//! | Mirror path | Client setting | Upstream | Published checksum |
+//! |-------------|----------------|----------|--------------------|
+//! | `/crates/index/`, `/crates/dl/` | cargo source replacement in the sandbox image | `index.crates.io`, `static.crates.io` | `cksum` in the sparse index |
+//! | `/npm/` | `npm_config_registry` | `registry.npmjs.org` | `dist.integrity` (sha512) |
+//! | `/pypi/simple/`, `/pypi/files/` | `PIP_INDEX_URL` | `pypi.org`, `files.pythonhosted.org` | `#sha256=` in the simple index |
+//!
+//! Index and metadata documents change over time; they are fetched fresh on
+//! every request and have their download URLs rewritten to the mirror.
+
+use bytes::Bytes;
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::sandbox::proxy::cache::{PackageCache, PackageRegistry};
+use crate::sandbox::proxy::policy::{NetworkDecision, NetworkPolicyDecider, NetworkRequest};
+
+/// Host name that addresses the mirror through the proxy.
+pub const MIRROR_HOST: &str = "registry.sandbox.internal";
+
+/// Base URLs of the mirrored registries, without trailing slashes.
+#[derive(Debug, Clone)]
+pub struct RegistryUpstreams {
+    pub crates_index: String,
+    pub crates_dl: String,
+    pub npm: String,
+    pub pypi_simple: String,
+    pub pypi_files: String,
+}
+
+impl Default for RegistryUpstreams {
+    fn default() -> Self {
+        Self {
+            crates_index: "https://index.crates.io".to_string(),
+            crates_dl: "https://static.crates.io/crates".to_string(),
+            npm: "https://registry.npmjs.org".to_string(),
+            pypi_simple: "https://pypi.org/simple".to_string(),
+            pypi_files: "https://files.pythonhosted.org".to_string(),
+        }
+    }
+}
+
+/// A mirror request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MirrorRoute {
+    /// A file of the crates.io sparse index (`config.json` or a crate's entries).
+    CratesIndex(String),
+    /// A `.crate` download.
+    CrateDownload { name: String, version: String },
+    /// An npm package document (`name` or `@scope/name`).
+    NpmMetadata(String),
+    /// An npm tarball.
+    NpmTarball { package: String, file: String },
+    /// A PyPI simple-index project page.
+    PypiSimple(String),
+    /// A file on the PyPI file host, by path.
+    PypiFile(String),
+}
+
+impl MirrorRoute {
+    /// Parse a mirror path. Returns `None` for anything the mirror does not
+    /// serve, including paths with `..` segments.
+    pub fn parse(path: &str) -> Option<Self> {
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        let path = path.replace("%2f", "/").replace("%2F", "/");
+        let path = path.replace("%40", "@");
+        if path.split('/').any(|segment| segment == "..") {
+            return None;
+        }
+
+        if let Some(rest) = path.strip_prefix("/crates/index/") {
+            return (!rest.is_empty()).then(|| Self::CratesIndex(rest.to_string()));
+        }
+        if let Some(rest) = path.strip_prefix("/crates/dl/") {
+            let mut parts = rest.split('/');
+            let (Some(name), Some(version), Some("download"), None) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            else {
+                return None;
+            };
+            let valid_name = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            return (valid_name && !version.is_empty()).then(|| Self::CrateDownload {
+                name: name.to_string(),
+                version: version.to_string(),
+            });
+        }
+        if let Some(rest) = path.strip_prefix("/npm/") {
+            if let Some((package, file)) = rest.split_once("/-/") {
+                let valid =
+                    is_npm_package(package) && file.ends_with(".tgz") && !file.contains('/');
+                return valid.then(|| Self::NpmTarball {
+                    package: package.to_string(),
+                    file: file.to_string(),
+                });
+            }
+            return is_npm_package(rest).then(|| Self::NpmMetadata(rest.to_string()));
+        }
+        if let Some(rest) = path.strip_prefix("/pypi/simple/") {
+            let project = rest.strip_suffix('/').unwrap_or(rest);
+            let valid = !project.is_empty() && !project.contains('/');
+            return valid.then(|| Self::PypiSimple(project.to_string()));
+        }
+        if let Some(rest) = path.strip_prefix("/pypi/files/") {
+            return (!rest.is_empty()).then(|| Self::PypiFile(rest.to_string()));
+        }
+        None
+    }
+}
+
+/// `name` or `@scope/name`.
+fn is_npm_package(package: &str) -> bool {
+    match package.strip_prefix('@') {
+        Some(scoped) => scoped.split_once('/').is_some_and(|(scope, name)| {
+            !scope.is_empty() && !name.is_empty() && !name.contains('/')
+        }),
+        None => !package.is_empty() && !package.contains('/'),
+    }
+}
+
+/// Errors serving a mirror request.
+#[derive(Debug, thiserror::Error)]
+pub enum MirrorError {
+    #[error("{url} is not allowed: {reason}")]
+    Denied { url: String, reason: String },
+
+    #[error("upstream request failed: {0}")]
+    Upstream(String),
+
+    #[error("no published checksum for {0}")]
+    NoChecksum(String),
+
+    #[error("{0} does not match its published checksum")]
+    ChecksumMismatch(String),
+}
+
+impl MirrorError {
+    /// HTTP status to answer with.
+    pub fn status(&self) -> u16 {
+        match self {
+            Self::Denied { .. } => 403,
+            Self::NoChecksum(_) => 404,
+            Self::Upstream(_) | Self::ChecksumMismatch(_) => 502,
+        }
+    }
+}
+
+/// A mirror response.
+#[derive(Debug)]
+pub struct MirrorResponse {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: Bytes,
+    /// Served from the package cache.
+    pub cached: bool,
+}
+
+/// Who is asking, for the network policy applied to upstream fetches.
+#[derive(Clone, Copy)]
+pub struct UpstreamAccess<'a> {
+    pub decider: &'a dyn NetworkPolicyDecider,
+    /// Per-command grant token of the request.
+    pub grant: Option<&'a str>,
+}
+
+/// A checksum a registry publishes for an artifact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PublishedDigest {
+    /// Lowercase hex.
+    Sha256(String),
+    Sha512(Vec<u8>),
+}
+
+impl PublishedDigest {
+    fn matches(&self, body: &[u8]) -> bool {
+        match self {
+            Self::Sha256(hex_digest) => hex::encode(Sha256::digest(body)) == *hex_digest,
+            Self::Sha512(digest) => Sha512::digest(body).as_slice() == digest.as_slice(),
+        }
+    }
+}
+
+/// Serves [`MirrorRoute`]s from the upstream registries.
+pub struct RegistryMirror {
+    upstreams: RegistryUpstreams,
+    client: reqwest::Client,
+}
+
+impl RegistryMirror {
+    pub fn new(upstreams: RegistryUpstreams) -> Self {
+        // Redirects would bypass the per-URL policy check.
+        let client = crate::outbound::client_builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_else(|_| crate::outbound::client());
+        Self { upstreams, client }
+    }
+
+    /// Upstream URL a route is fetched from.
+    pub fn upstream_url(&self, route: &MirrorRoute) -> String {
+        let up = &self.upstreams;
+        match route {
+            MirrorRoute::CratesIndex(path) => format!("{}/{}", up.crates_index, path),
+            MirrorRoute::CrateDownload { name, version } => {
+                format!("{}/{name}/{name}-{version}.crate", up.crates_dl)
+            }
+            MirrorRoute::NpmMetadata(package) => format!("{}/{}", up.npm, npm_path(package)),
+            MirrorRoute::NpmTarball { package, file } => {
+                format!("{}/{}/-/{}", up.npm, package, file)
+            }
+            MirrorRoute::PypiSimple(project) => format!("{}/{}/", up.pypi_simple, project),
+            MirrorRoute::PypiFile(path) => format!("{}/{}", up.pypi_files, path),
+        }
+    }
+
+    /// Serve a route. Artifacts are verified against the registry's
+    /// published checksum before they are cached or returned.
+    pub async fn serve(
+        &self,
+        route: &MirrorRoute,
+        access: UpstreamAccess<'_>,
+        cache: Option<&PackageCache>,
+    ) -> Result<MirrorResponse, MirrorError> {
+        let url = self.upstream_url(route);
+        let mirror = format!("http://{}", MIRROR_HOST);
+        match route {
+            MirrorRoute::CratesIndex(path) if path == "config.json" => {
+                let mut response = self.document(&url, access, None).await?;
+                if response.status == 200 {
+                    let mut config: serde_json::Value = serde_json::from_slice(&response.body)
+                        .map_err(|e| MirrorError::Upstream(format!("bad index config: {}", e)))?;
+                    config["dl"] = serde_json::Value::String(format!("{}/crates/dl", mirror));
+                    response.body = Bytes::from(config.to_string());
+                }
+                Ok(response)
+            }
+            MirrorRoute::CratesIndex(_) => self.document(&url, access, None).await,
+            MirrorRoute::NpmMetadata(_) => {
+                let rewrite = (
+                    format!("{}/", self.upstreams.npm),
+                    format!("{}/npm/", mirror),
+                );
+                self.document(&url, access, Some(rewrite)).await
+            }
+            MirrorRoute::PypiSimple(_) => {
+                let rewrite = (
+                    format!("{}/", self.upstreams.pypi_files),
+                    format!("{}/pypi/files/", mirror),
+                );
+                self.document(&url, access, Some(rewrite)).await
+            }
+            MirrorRoute::CrateDownload { name, version } => {
+                self.artifact(&url, PackageRegistry::Crates, access, cache, || {
+                    self.crate_checksum(name, version, access)
+                })
+                .await
+            }
+            MirrorRoute::NpmTarball { package, file } => {
+                self.artifact(&url, PackageRegistry::Npm, access, cache, || {
+                    self.npm_integrity(package, file, access)
+                })
+                .await
+            }
+            MirrorRoute::PypiFile(path) => {
+                self.artifact(&url, PackageRegistry::Pypi, access, cache, || {
+                    self.pypi_sha256(path, access)
+                })
+                .await
+            }
+        }
+    }
+
+    /// Fetch a mutable document, replacing `rewrite.0` with `rewrite.1` in
+    /// successful bodies.
+    async fn document(
+        &self,
+        url: &str,
+        access: UpstreamAccess<'_>,
+        rewrite: Option<(String, String)>,
+    ) -> Result<MirrorResponse, MirrorError> {
+        let response = self.fetch(url, access).await?;
+        let status = response.status().as_u16();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let mut body = response
+            .bytes()
+            .await
+            .map_err(|e| MirrorError::Upstream(e.to_string()))?;
+        if status == 200
+            && let Some((from, to)) = rewrite
+        {
+            body = Bytes::from(String::from_utf8_lossy(&body).replace(&from, &to));
+        }
+        Ok(MirrorResponse {
+            status,
+            content_type,
+            body,
+            cached: false,
+        })
+    }
+
+    /// Serve an immutable artifact from the cache, or fetch and verify it.
+    async fn artifact<F, Fut>(
+        &self,
+        url: &str,
+        registry: PackageRegistry,
+        access: UpstreamAccess<'_>,
+        cache: Option<&PackageCache>,
+        published: F,
+    ) -> Result<MirrorResponse, MirrorError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<PublishedDigest, MirrorError>>,
+    {
+        // The policy applies to cached artifacts too.
+        self.check_allowed(url, access).await?;
+        if let Some(cache) = cache
+            && let Some(body) = cache.get(url).await
+        {
+            return Ok(MirrorResponse {
+                status: 200,
+                content_type: Some("application/octet-stream".to_string()),
+                body,
+                cached: true,
+            });
+        }
+
+        let digest = published().await?;
+        let response = self.fetch(url, access).await?;
+        let status = response.status().as_u16();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| MirrorError::Upstream(e.to_string()))?;
+        if status != 200 {
+            return Ok(MirrorResponse {
+                status,
+                content_type: None,
+                body,
+                cached: false,
+            });
+        }
+        if !digest.matches(&body) {
+            tracing::warn!("Registry mirror: checksum mismatch for {}", url);
+            return Err(MirrorError::ChecksumMismatch(url.to_string()));
+        }
+        if let Some(cache) = cache
+            && let Err(e) = cache.put(url, registry, &body).await
+        {
+            tracing::warn!("Registry mirror: failed to cache {}: {}", url, e);
+        }
+        Ok(MirrorResponse {
+            status,
+            content_type: Some("application/octet-stream".to_string()),
+            body,
+            cached: false,
+        })
+    }
+
+    /// `cksum` of `name` `version` in the sparse index.
+    async fn crate_checksum(
+        &self,
+        name: &str,
+        version: &str,
+        access: UpstreamAccess<'_>,
+    ) -> Result<PublishedDigest, MirrorError> {
+        let url = format!("{}/{}", self.upstreams.crates_index, crate_index_path(name));
+        let entries = self.fetch_text(&url, access).await?;
+        entries
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .find(|entry| entry["vers"] == version)
+            .and_then(|entry| entry["cksum"].as_str().map(str::to_ascii_lowercase))
+            .map(PublishedDigest::Sha256)
+            .ok_or_else(|| MirrorError::NoChecksum(format!("{} {}", name, version)))
+    }
+
+    /// sha512 `dist.integrity` of the version whose tarball is `file`.
+    async fn npm_integrity(
+        &self,
+        package: &str,
+        file: &str,
+        access: UpstreamAccess<'_>,
+    ) -> Result<PublishedDigest, MirrorError> {
+        use base64::Engine;
+
+        let url = format!("{}/{}", self.upstreams.npm, npm_path(package));
+        let metadata: serde_json::Value =
+            serde_json::from_str(&self.fetch_text(&url, access).await?)
+                .map_err(|e| MirrorError::Upstream(format!("bad package document: {}", e)))?;
+        let suffix = format!("/-/{}", file);
+        let integrity = metadata["versions"]
+            .as_object()
+            .into_iter()
+            .flat_map(|versions| versions.values())
+            .map(|version| &version["dist"])
+            .find(|dist| {
+                dist["tarball"]
+                    .as_str()
+                    .is_some_and(|t| t.ends_with(&suffix))
+            })
+            .and_then(|dist| dist["integrity"].as_str());
+        integrity
+            .into_iter()
+            .flat_map(str::split_whitespace)
+            .find_map(|sri| sri.strip_prefix("sha512-"))
+            .and_then(|b64| base64::engine::general_purpose::STANDARD.decode(b64).ok())
+            .map(PublishedDigest::Sha512)
+            .ok_or_else(|| MirrorError::NoChecksum(format!("{} {}", package, file)))
+    }
+
+    /// `#sha256=` of `path` on its project's simple-index page.
+    async fn pypi_sha256(
+        &self,
+        path: &str,
+        access: UpstreamAccess<'_>,
+    ) -> Result<PublishedDigest, MirrorError> {
+        let file = path.rsplit('/').next().unwrap_or(path);
+        let project =
+            project_from_filename(file).ok_or_else(|| MirrorError::NoChecksum(file.to_string()))?;
+        let url = format!("{}/{}/", self.upstreams.pypi_simple, project);
+        let page = self.fetch_text(&url, access).await?;
+        let marker = format!("/{}#sha256=", file);
+        page.find(&marker)
+            .map(|i| &page[i + marker.len()..])
+            .map(|rest| {
+                rest.chars()
+                    .take_while(char::is_ascii_hexdigit)
+                    .collect::<String>()
+            })
+            .filter(|digest| digest.len() == 64)
+            .map(|digest| PublishedDigest::Sha256(digest.to_ascii_lowercase()))
+            .ok_or_else(|| MirrorError::NoChecksum(file.to_string()))
+    }
+
+    async fn fetch_text(
+        &self,
+        url: &str,
+        access: UpstreamAccess<'_>,
+    ) -> Result<String, MirrorError> {
+        let response = self.fetch(url, access).await?;
+        if !response.status().is_success() {
+            return Err(MirrorError::Upstream(format!(
+                "{} returned {}",
+                url,
+                response.status()
+            )));
+        }
+        response
+            .text()
+            .await
+            .map_err(|e| MirrorError::Upstream(e.to_string()))
+    }
+
+    async fn fetch(
+        &self,
+        url: &str,
+        access: UpstreamAccess<'_>,
+    ) -> Result<reqwest::Response, MirrorError> {
+        self.check_allowed(url, access).await?;
+        self.client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| MirrorError::Upstream(e.to_string()))
+    }
+
+    /// Apply the network policy to an upstream URL. Credentials are never
+    /// injected into registry fetches.
+    async fn check_allowed(
+        &self,
+        url: &str,
+        access: UpstreamAccess<'_>,
+    ) -> Result<(), MirrorError> {
+        let mut request = NetworkRequest::from_url("GET", url)
+            .ok_or_else(|| MirrorError::Upstream(format!("invalid upstream URL {}", url)))?;
+        request.grant = access.grant.map(str::to_string);
+        match access.decider.decide(&request).await {
+            NetworkDecision::Deny { reason } => Err(MirrorError::Denied {
+                url: url.to_string(),
+                reason,
+            }),
+            NetworkDecision::Allow | NetworkDecision::AllowWithCredentials { .. } => Ok(()),
+        }
+    }
+}
+
+/// npm addresses scoped package documents as `@scope%2fname`.
+fn npm_path(package: &str) -> String {
+    package.replacen('/', "%2f", 1)
+}
+
+/// Path of a crate's entries in the sparse index.
+fn crate_index_path(name: &str) -> String {
+    let name = name.to_ascii_lowercase();
+    match name.len() {
+        1 => format!("1/{}", name),
+        2 => format!("2/{}", name),
+        3 => format!("3/{}/{}", &name[..1], name),
+        _ => format!("{}/{}/{}", &name[..2], &name[2..4], name),
+    }
+}
+
+/// Normalized (PEP 503) project name of a wheel or sdist file.
+fn project_from_filename(file: &str) -> Option<String> {
+    let name = if file.ends_with(".whl") {
+        file.split('-').next()?
+    } else {
+        let stem = [".tar.gz", ".zip", ".tar.bz2"]
+            .iter()
+            .find_map(|ext| file.strip_suffix(ext))?;
+        stem.rsplit_once('-')?.0
+    };
+    if name.is_empty() {
+        return None;
+    }
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.push(c.to_ascii_lowercase());
+        }
+    }
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::proxy::policy::{AllowAllDecider, DenyAllDecider};
+    use axum::Router;
+    use axum::routing::get;
+    use base64::Engine;
+
+    #[test]
+    fn test_parse_routes() {
+        assert_eq!(
+            MirrorRoute::parse("/crates/index/se/rd/serde"),
+            Some(MirrorRoute::CratesIndex("se/rd/serde".to_string()))
+        );
+        assert_eq!(
+            MirrorRoute::parse("/crates/dl/serde/1.0.0/download"),
+            Some(MirrorRoute::CrateDownload {
+                name: "serde".to_string(),
+                version: "1.0.0".to_string()
+            })
+        );
+        assert_eq!(
+            MirrorRoute::parse("/npm/%40types%2fnode"),
+            Some(MirrorRoute::NpmMetadata("@types/node".to_string()))
+        );
+        assert_eq!(
+            MirrorRoute::parse("/npm/@types/node/-/node-20.0.0.tgz"),
+            Some(MirrorRoute::NpmTarball {
+                package: "@types/node".to_string(),
+                file: "node-20.0.0.tgz".to_string()
+            })
+        );
+        assert_eq!(
+            MirrorRoute::parse("/pypi/simple/requests/"),
+            Some(MirrorRoute::PypiSimple("requests".to_string()))
+        );
+        assert_eq!(
+            MirrorRoute::parse("/pypi/files/packages/ab/cd/x-1.0-py3-none-any.whl"),
+            Some(MirrorRoute::PypiFile(
+                "packages/ab/cd/x-1.0-py3-none-any.whl".to_string()
+            ))
+        );
+
+        assert_eq!(MirrorRoute::parse("/crates/dl/../x/1/download"), None);
+        assert_eq!(MirrorRoute::parse("/pypi/files/../../etc/passwd"), None);
+        assert_eq!(MirrorRoute::parse("/npm/a/b/c"), None);
+        assert_eq!(MirrorRoute::parse("/other"), None);
+    }
+
+    #[test]
+    fn test_crate_index_path() {
+        assert_eq!(crate_index_path("a"), "1/a");
+        assert_eq!(crate_index_path("ab"), "2/ab");
+        assert_eq!(crate_index_path("abc"), "3/a/abc");
+        assert_eq!(crate_index_path("Serde"), "se/rd/serde");
+    }
+
+    #[test]
+    fn test_project_from_filename() {
+        assert_eq!(
+            project_from_filename("Flask_Login-0.6.3-py3-none-any.whl").as_deref(),
+            Some("flask-login")
+        );
+        assert_eq!(
+            project_from_filename("zope.interface-6.0.tar.gz").as_deref(),
+            Some("zope-interface")
+        );
+        assert_eq!(project_from_filename("notes.txt"), None);
+    }
+
+    /// A fake registry host serving all five upstreams under one base URL.
+    async fn fake_registry() -> (String, RegistryMirror) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+
+        let good = b"good crate".to_vec();
+        let crate_entries = format!(
+            "{}\n{}\n",
+            serde_json::json!({"name": "demo", "vers": "1.0.0", "cksum": hex::encode(Sha256::digest(&good))}),
+            serde_json::json!({"name": "demo", "vers": "2.0.0", "cksum": hex::encode(Sha256::digest(b"other"))}),
+        );
+        let tarball = b"npm tarball".to_vec();
+        let npm_doc = serde_json::json!({
+            "name": "left-pad",
+            "versions": {"1.0.0": {"dist": {
+                "tarball": format!("{}/npm/left-pad/-/left-pad-1.0.0.tgz", base),
+                "integrity": format!(
+                    "sha512-{}",
+                    base64::engine::general_purpose::STANDARD.encode(Sha512::digest(&tarball))
+                ),
+            }}}
+        })
+        .to_string();
+        let wheel = b"wheel bytes".to_vec();
+        let simple = format!(
+            "<a href=\"{}/files/packages/aa/demo_pkg-1.0-py3-none-any.whl#sha256={}\">demo_pkg-1.0-py3-none-any.whl</a>",
+            base,
+            hex::encode(Sha256::digest(&wheel))
+        );
+
+        let app = Router::new()
+            .route(
+                "/index/config.json",
+                get(|| async {
+                    r#"{"dl":"https://static.crates.io/crates","api":"https://crates.io"}"#
+                }),
+            )
+            .route(
+                "/index/de/mo/demo",
+                get(move || async move { crate_entries }),
+            )
+            .route(
+                "/dl/demo/demo-1.0.0.crate",
+                get(move || async move { good }),
+            )
+            .route(
+                "/dl/demo/demo-2.0.0.crate",
+                get(|| async { b"tampered".to_vec() }),
+            )
+            .route("/npm/left-pad", get(move || async move { npm_doc }))
+            .route(
+                "/npm/left-pad/-/left-pad-1.0.0.tgz",
+                get(move || async move { tarball }),
+            )
+            .route("/simple/demo-pkg/", get(move || async move { simple }))
+            .route(
+                "/files/packages/aa/demo_pkg-1.0-py3-none-any.whl",
+                get(move || async move { wheel }),
+            );
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let mirror = RegistryMirror::new(RegistryUpstreams {
+            crates_index: format!("{}/index", base),
+            crates_dl: format!("{}/dl", base),
+            npm: format!("{}/npm", base),
+            pypi_simple: format!("{}/simple", base),
+            pypi_files: format!("{}/files", base),
+        });
+        (base, mirror)
+    }
+
+    fn route(path: &str) -> MirrorRoute {
+        MirrorRoute::parse(path).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_crates_are_verified_and_cached() {
+        let (_, mirror) = fake_registry().await;
+        let dir = tempfile::tempdir().unwrap();
+        let cache = PackageCache::open(dir.path(), 1024 * 1024).await.unwrap();
+        let access = UpstreamAccess {
+            decider: &AllowAllDecider,
+            grant: None,
+        };
+
+        let config = mirror
+            .serve(&route("/crates/index/config.json"), access, Some(&cache))
+            .await
+            .unwrap();
+        let config: serde_json::Value = serde_json::from_slice(&config.body).unwrap();
+        assert_eq!(config["dl"], "http://registry.sandbox.internal/crates/dl");
+
+        let download = route("/crates/dl/demo/1.0.0/download");
+        let first = mirror.serve(&download, access, Some(&cache)).await.unwrap();
+        assert_eq!((first.status, first.cached), (200, false));
+        assert_eq!(first.body, Bytes::from_static(b"good crate"));
+        let second = mirror.serve(&download, access, Some(&cache)).await.unwrap();
+        assert!(second.cached);
+
+        let tampered = mirror
+            .serve(
+                &route("/crates/dl/demo/2.0.0/download"),
+                access,
+                Some(&cache),
+            )
+            .await;
+        assert!(matches!(tampered, Err(MirrorError::ChecksumMismatch(_))));
+        let unpublished = mirror
+            .serve(
+                &route("/crates/dl/demo/3.0.0/download"),
+                access,
+                Some(&cache),
+            )
+            .await;
+        assert!(matches!(unpublished, Err(MirrorError::NoChecksum(_))));
+        assert_eq!(cache.stats().await.entries, 1);
+    }
+
+    #[tokio::test]
+    async fn test_npm_and_pypi_documents_point_at_the_mirror() {
+        let (base, mirror) = fake_registry().await;
+        let access = UpstreamAccess {
+            decider: &AllowAllDecider,
+            grant: None,
+        };
+
+        let doc = mirror
+            .serve(&route("/npm/left-pad"), access, None)
+            .await
+            .unwrap();
+        let doc = String::from_utf8(doc.body.to_vec()).unwrap();
+        assert!(doc.contains("http://registry.sandbox.internal/npm/left-pad/-/left-pad-1.0.0.tgz"));
+        assert!(!doc.contains(&base));
+        let tarball = mirror
+            .serve(&route("/npm/left-pad/-/left-pad-1.0.0.tgz"), access, None)
+            .await
+            .unwrap();
+        assert_eq!(tarball.body, Bytes::from_static(b"npm tarball"));
+
+        let page = mirror
+            .serve(&route("/pypi/simple/demo-pkg/"), access, None)
+            .await
+            .unwrap();
+        let page = String::from_utf8(page.body.to_vec()).unwrap();
+        assert!(page.contains("http://registry.sandbox.internal/pypi/files/packages/aa/"));
+        let wheel = mirror
+            .serve(
+                &route("/pypi/files/packages/aa/demo_pkg-1.0-py3-none-any.whl"),
+                access,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(wheel.body, Bytes::from_static(b"wheel bytes"));
+    }
+
+    #[tokio::test]
+    async fn test_policy_applies_to_upstreams() {
+        let (_, mirror) = fake_registry().await;
+        let deny = DenyAllDecider::new("not on the allowlist");
+        let access = UpstreamAccess {
+            decider: &deny,
+            grant: None,
+        };
+        let err = mirror
+            .serve(&route("/crates/dl/demo/1.0.0/download"), access, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), 403);
+    }
+}
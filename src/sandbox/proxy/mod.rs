@@ -2,8 +2,13 @@
 //!
 //! The proxy provides:
 //! - Domain allowlist validation
-//! - Credential injection for API calls
-//! - Request logging and monitoring
+//! - Per-command allow/deny overrides ([`grants`])
+//! - Credential injection for API calls, with per-job short-lived
+//!   credentials from [`ephemeral`] issuers in place of long-lived secrets
+//! - Request logging and monitoring, with an optional [`egress`] audit log
+//! - A plain-HTTP [`mirror`] of crates.io, npm and PyPI that verifies
+//!   artifacts against the registries' published checksums, with optional
+//!   caching of those artifacts
 //!
 //! # Architecture
 //!
@@ -29,11 +34,23 @@
 //! ```
 
 pub mod allowlist;
+pub mod cache;
+pub mod egress;
+pub mod ephemeral;
+pub mod grants;
 pub mod http;
+pub mod issuers;
+pub mod mirror;
 pub mod policy;
 
 pub use allowlist::{DomainAllowlist, DomainPattern, DomainValidationResult};
+pub use cache::{PackageCache, PackageCacheStats, PackageRegistry};
+pub use egress::EgressLog;
+pub use ephemeral::{CredentialIssuer, EphemeralCredentials, IssuedCredential};
+pub use grants::{ExecPolicy, GrantHandle, NetworkGrantRecord, NetworkGrants};
 pub use http::{CredentialResolver, EnvCredentialResolver, HttpProxy, NoCredentialResolver};
+pub use issuers::{GitHubAppIssuer, TokenExchangeIssuer};
+pub use mirror::{MIRROR_HOST, RegistryMirror, RegistryUpstreams};
 pub use policy::{
     AllowAllDecider, DefaultPolicyDecider, DenyAllDecider, NetworkDecision, NetworkPolicyDecider,
     NetworkRequest,
@@ -52,6 +69,10 @@ pub struct NetworkProxyBuilder {
     credential_mappings: Vec<CredentialMapping>,
     credential_resolver: Arc<dyn CredentialResolver>,
     policy: SandboxPolicy,
+    package_cache: Option<Arc<PackageCache>>,
+    grants: Option<Arc<NetworkGrants>>,
+    egress: Option<EgressLog>,
+    ephemeral: Option<Arc<EphemeralCredentials>>,
 }
 
 impl NetworkProxyBuilder {
@@ -64,6 +85,10 @@ impl NetworkProxyBuilder {
             credential_resolver: Arc::new(EnvCredentialResolver::new(allowed_names)),
             credential_mappings: mappings,
             policy: SandboxPolicy::ReadOnly,
+            package_cache: None,
+            grants: None,
+            egress: None,
+            ephemeral: None,
         }
     }
 
@@ -76,6 +101,10 @@ impl NetworkProxyBuilder {
             credential_resolver: Arc::new(EnvCredentialResolver::new(allowed_names)),
             credential_mappings: mappings,
             policy: config.policy,
+            package_cache: None,
+            grants: None,
+            egress: None,
+            ephemeral: None,
         }
     }
 
@@ -109,18 +138,67 @@ impl NetworkProxyBuilder {
         self
     }
 
+    /// Cache package registry artifacts fetched through the proxy and its
+    /// registry mirror.
+    pub fn with_package_cache(mut self, cache: Arc<PackageCache>) -> Self {
+        self.package_cache = Some(cache);
+        self
+    }
+
+    /// Honor per-command grants registered in `grants`.
+    pub fn with_grants(mut self, grants: Arc<NetworkGrants>) -> Self {
+        self.grants = Some(grants);
+        self
+    }
+
+    /// Record every request in `log`.
+    pub fn with_egress_log(mut self, log: EgressLog) -> Self {
+        self.egress = Some(log);
+        self
+    }
+
+    /// Inject per-job credentials minted by `credentials` for the secrets
+    /// its issuers cover. Their mappings take precedence over the others.
+    pub fn with_ephemeral_credentials(mut self, credentials: Arc<EphemeralCredentials>) -> Self {
+        self.ephemeral = Some(credentials);
+        self
+    }
+
     /// Build the HTTP proxy.
-    pub fn build(self) -> HttpProxy {
+    pub fn build(mut self) -> HttpProxy {
+        if let Some(ephemeral) = &self.ephemeral {
+            let mut mappings = ephemeral.mappings();
+            mappings.append(&mut self.credential_mappings);
+            self.credential_mappings = mappings;
+        }
+
         let decider: Arc<dyn NetworkPolicyDecider> = if self.policy.has_full_network() {
             Arc::new(AllowAllDecider)
         } else {
-            Arc::new(DefaultPolicyDecider::new(
+            let decider = DefaultPolicyDecider::new(
                 DomainAllowlist::new(&self.allowlist),
                 self.credential_mappings,
-            ))
+            );
+            Arc::new(match &self.grants {
+                Some(grants) => decider.with_grants(Arc::clone(grants)),
+                None => decider,
+            })
         };
 
-        HttpProxy::new(decider, self.credential_resolver)
+        let mut proxy = HttpProxy::new(decider, self.credential_resolver);
+        if let Some(cache) = self.package_cache {
+            proxy = proxy.with_package_cache(cache);
+        }
+        if let Some(grants) = self.grants {
+            proxy = proxy.with_grants(grants);
+        }
+        if let Some(log) = self.egress {
+            proxy = proxy.with_egress_log(log);
+        }
+        if let Some(ephemeral) = self.ephemeral {
+            proxy = proxy.with_ephemeral_credentials(ephemeral);
+        }
+        proxy
     }
 
     /// Build and start the proxy on the given port.
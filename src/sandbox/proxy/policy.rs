@@ -3,10 +3,13 @@
 //! Determines whether network requests should be allowed, denied,
 //! or allowed with credential injection.
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
 
 use crate::sandbox::config::{CredentialLocation, CredentialMapping};
 use crate::sandbox::proxy::allowlist::DomainAllowlist;
+use crate::sandbox::proxy::grants::{GrantVerdict, NetworkGrants};
 
 /// A network request to be evaluated.
 #[derive(Debug, Clone)]
@@ -19,6 +22,8 @@ pub struct NetworkRequest {
     pub host: String,
     /// Path portion of the URL.
     pub path: String,
+    /// Per-command grant token presented in `Proxy-Authorization`.
+    pub grant: Option<String>,
 }
 
 impl NetworkRequest {
@@ -32,6 +37,7 @@ impl NetworkRequest {
             url: url.to_string(),
             host,
             path,
+            grant: None,
         })
     }
 }
@@ -84,6 +90,7 @@ pub trait NetworkPolicyDecider: Send + Sync {
 pub struct DefaultPolicyDecider {
     allowlist: DomainAllowlist,
     credential_mappings: Vec<CredentialMapping>,
+    grants: Option<Arc<NetworkGrants>>,
 }
 
 impl DefaultPolicyDecider {
@@ -92,9 +99,16 @@ impl DefaultPolicyDecider {
         Self {
             allowlist,
             credential_mappings,
+            grants: None,
         }
     }
 
+    /// Merge per-command grants with the allowlist.
+    pub fn with_grants(mut self, grants: Arc<NetworkGrants>) -> Self {
+        self.grants = Some(grants);
+        self
+    }
+
     /// Find credential mapping for a domain.
     fn find_credential(&self, host: &str) -> Option<&CredentialMapping> {
         let host_lower = host.to_lowercase();
@@ -107,13 +121,31 @@ impl DefaultPolicyDecider {
 #[async_trait]
 impl NetworkPolicyDecider for DefaultPolicyDecider {
     async fn decide(&self, request: &NetworkRequest) -> NetworkDecision {
-        // First check if the domain is allowed
+        // A per-command deny wins over everything; a per-command allow only
+        // matters where the allowlist says no.
+        let grant = match (&self.grants, &request.grant) {
+            (Some(grants), Some(token)) => Some((grants, token)),
+            _ => None,
+        };
+        let verdict = grant.map_or(GrantVerdict::NotCovered, |(grants, token)| {
+            grants.check(token, &request.host)
+        });
+        if let GrantVerdict::Denied(reason) = verdict {
+            return NetworkDecision::Deny { reason };
+        }
+
+        // Then check if the domain is allowed
         let validation = self.allowlist.is_allowed(&request.host);
         if !validation.is_allowed()
             && let crate::sandbox::proxy::allowlist::DomainValidationResult::Denied(reason) =
                 validation
         {
-            return NetworkDecision::Deny { reason };
+            match grant {
+                Some((grants, token)) if verdict == GrantVerdict::Allowed => {
+                    grants.record_use(token, &request.host);
+                }
+                _ => return NetworkDecision::Deny { reason },
+            }
         }
 
         // Check if we need to inject credentials
@@ -225,4 +257,67 @@ mod tests {
             _ => panic!("Expected AllowWithCredentials"),
         }
     }
+
+    #[tokio::test]
+    async fn test_grant_merges_with_allowlist() {
+        use crate::sandbox::config::SandboxPolicy;
+        use crate::sandbox::proxy::grants::ExecPolicy;
+
+        let dir = tempfile::tempdir().unwrap();
+        let grants = Arc::new(NetworkGrants::new(dir.path().join("grants.jsonl")));
+        let allowlist = DomainAllowlist::new(&["crates.io".to_string(), "github.com".to_string()]);
+        let decider = DefaultPolicyDecider::new(allowlist, vec![]).with_grants(grants.clone());
+        let policy = ExecPolicy::new(SandboxPolicy::ReadOnly)
+            .allow_domain("pypi.org")
+            .deny_domain("github.com");
+        let handle = grants.register("pip install x", &policy).unwrap().unwrap();
+
+        let request = |url: &str, grant: Option<&str>| {
+            let mut req = NetworkRequest::from_url("GET", url).unwrap();
+            req.grant = grant.map(str::to_string);
+            req
+        };
+        let token = handle.token().to_string();
+        let token = Some(token.as_str());
+
+        assert!(
+            decider
+                .decide(&request("https://pypi.org/simple", token))
+                .await
+                .is_allowed()
+        );
+        assert!(
+            decider
+                .decide(&request("https://crates.io/", token))
+                .await
+                .is_allowed()
+        );
+        assert!(
+            !decider
+                .decide(&request("https://github.com/", token))
+                .await
+                .is_allowed()
+        );
+        // Without the token the base allowlist applies unchanged.
+        assert!(
+            !decider
+                .decide(&request("https://pypi.org/simple", None))
+                .await
+                .is_allowed()
+        );
+        assert!(
+            decider
+                .decide(&request("https://github.com/", None))
+                .await
+                .is_allowed()
+        );
+
+        drop(handle);
+        assert!(
+            !decider
+                .decide(&request("https://pypi.org/simple", token))
+                .await
+                .is_allowed()
+        );
+    }
 }
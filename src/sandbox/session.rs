@@ -0,0 +1,437 @@
+//! Interactive sandbox sessions.
+//!
+//! [`SandboxManager::execute`](crate::sandbox::SandboxManager::execute) runs
+//! a command to completion. A session instead keeps a program running with
+//! its stdin open, for REPLs, interpreters and watch-mode build tools:
+//!
+//! ```text
+//! start_session(cmd, workspace, policy)
+//!     │  start idle container (same spec as a one-shot command:
+//!     │  proxy env, binds, dropped capabilities)
+//!     │  attach `sh -c cmd` with stdin open
+//!     ▼
+//! write_stdin(bytes) ──▶ process ──▶ output buffer ──▶ read_output(wait)
+//!     │
+//!     ▼
+//! close() / idle timeout ──▶ remove container
+//! ```
+//!
+//! Output is buffered until read. Past `max_output_bytes` the oldest output
+//! is dropped, so a long-running watcher keeps its latest lines. A session
+//! that is neither written to nor read from for its idle timeout is closed
+//! by the manager.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+use crate::sandbox::backend::{ContainerBackend, OutputChunk};
+use crate::sandbox::config::{ResourceLimits, SandboxPolicy};
+use crate::sandbox::container::ContainerRunner;
+use crate::sandbox::error::{Result, SandboxError};
+use crate::sandbox::pool::IDLE_COMMAND;
+
+/// Output gathered since the last read.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// Whether output was dropped because nobody read it in time.
+    pub truncated: bool,
+    /// Whether the process has exited; later reads return nothing new.
+    pub exited: bool,
+    pub exit_code: Option<i64>,
+}
+
+#[derive(Default)]
+struct OutputBuffer {
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    truncated: bool,
+    exited: bool,
+    exit_code: Option<i64>,
+}
+
+impl OutputBuffer {
+    fn push(&mut self, chunk: OutputChunk, cap: usize) {
+        let buf = match chunk {
+            OutputChunk::Stdout(bytes) => {
+                self.stdout.extend_from_slice(&bytes);
+                &mut self.stdout
+            }
+            OutputChunk::Stderr(bytes) => {
+                self.stderr.extend_from_slice(&bytes);
+                &mut self.stderr
+            }
+            OutputChunk::Exited(code) => {
+                self.exited = true;
+                self.exit_code = code;
+                return;
+            }
+        };
+        if buf.len() > cap {
+            let excess = buf.len() - cap;
+            buf.drain(..excess);
+            self.truncated = true;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.stdout.is_empty() && self.stderr.is_empty() && !self.exited
+    }
+
+    fn take(&mut self) -> SessionOutput {
+        SessionOutput {
+            stdout: take_utf8(&mut self.stdout, self.exited),
+            stderr: take_utf8(&mut self.stderr, self.exited),
+            truncated: std::mem::take(&mut self.truncated),
+            exited: self.exited,
+            exit_code: self.exit_code,
+        }
+    }
+}
+
+/// Decode and remove `buf`, keeping a trailing partial UTF-8 sequence for
+/// the next read unless no more output is coming.
+fn take_utf8(buf: &mut Vec<u8>, finished: bool) -> String {
+    let keep = match std::str::from_utf8(buf) {
+        Err(e) if e.error_len().is_none() && !finished => buf.len() - e.valid_up_to(),
+        _ => 0,
+    };
+    let rest = buf.split_off(buf.len() - keep);
+    let text = String::from_utf8_lossy(buf).into_owned();
+    *buf = rest;
+    text
+}
+
+/// A program running in its own sandbox container with stdin attached.
+pub struct SandboxSession {
+    id: Uuid,
+    command: String,
+    backend: Arc<dyn ContainerBackend>,
+    container_id: String,
+    stdin: tokio::sync::Mutex<Pin<Box<dyn AsyncWrite + Send>>>,
+    output: Arc<std::sync::Mutex<OutputBuffer>>,
+    notify: Arc<Notify>,
+    reader: tokio::task::JoinHandle<()>,
+    last_active: std::sync::Mutex<Instant>,
+    idle_timeout: Duration,
+    closed: AtomicBool,
+}
+
+impl SandboxSession {
+    /// Start `command` in a new container for `working_dir`, set up like a
+    /// one-shot command under `policy`. `limits.timeout` is not applied;
+    /// the session lives until closed or idle for `idle_timeout`.
+    pub async fn start(
+        runner: &ContainerRunner,
+        command: &str,
+        working_dir: &Path,
+        policy: SandboxPolicy,
+        limits: &ResourceLimits,
+        env: HashMap<String, String>,
+        idle_timeout: Duration,
+    ) -> Result<Arc<Self>> {
+        let id = Uuid::new_v4();
+        let backend = Arc::clone(runner.backend());
+        let mut spec = runner.spec(IDLE_COMMAND, working_dir, policy, env);
+        spec.name = format!("sandbox-session-{}", id);
+
+        let container_id = backend.start(&spec, limits).await?;
+        let process = match backend
+            .attach(&container_id, command, &spec.working_dir, &[])
+            .await
+        {
+            Ok(process) => process,
+            Err(e) => {
+                backend.remove(&container_id).await;
+                return Err(e);
+            }
+        };
+
+        let output = Arc::new(std::sync::Mutex::new(OutputBuffer::default()));
+        let notify = Arc::new(Notify::new());
+        let reader = {
+            let output = Arc::clone(&output);
+            let notify = Arc::clone(&notify);
+            let cap = limits.max_output_bytes;
+            let mut stream = process.output;
+            tokio::spawn(async move {
+                let mut exited = false;
+                while let Some(chunk) = stream.next().await {
+                    exited |= matches!(chunk, OutputChunk::Exited(_));
+                    if let Ok(mut buf) = output.lock() {
+                        buf.push(chunk, cap);
+                    }
+                    notify.notify_waiters();
+                }
+                if !exited && let Ok(mut buf) = output.lock() {
+                    buf.push(OutputChunk::Exited(None), cap);
+                }
+                notify.notify_waiters();
+            })
+        };
+
+        tracing::info!(session = %id, container_id = %container_id, "Started sandbox session: {}", command);
+        Ok(Arc::new(Self {
+            id,
+            command: command.to_string(),
+            backend,
+            container_id,
+            stdin: tokio::sync::Mutex::new(process.stdin),
+            output,
+            notify,
+            reader,
+            last_active: std::sync::Mutex::new(Instant::now()),
+            idle_timeout,
+            closed: AtomicBool::new(false),
+        }))
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// The command the session runs.
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    fn touch(&self) {
+        if let Ok(mut last) = self.last_active.lock() {
+            *last = Instant::now();
+        }
+    }
+
+    fn ensure_open(&self) -> Result<()> {
+        if self.closed.load(Ordering::SeqCst) {
+            Err(SandboxError::SessionClosed { id: self.id })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Send bytes to the program's stdin.
+    pub async fn write_stdin(&self, data: &[u8]) -> Result<()> {
+        self.ensure_open()?;
+        self.touch();
+        let mut stdin = self.stdin.lock().await;
+        stdin.write_all(data).await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+
+    /// Output since the last read. Waits up to `wait` if there is none yet.
+    pub async fn read_output(&self, wait: Duration) -> Result<SessionOutput> {
+        self.ensure_open()?;
+        self.touch();
+        let notified = self.notify.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        let empty = self.output.lock().map(|b| b.is_empty()).unwrap_or(true);
+        if empty {
+            let _ = tokio::time::timeout(wait, notified).await;
+        }
+        Ok(self.output.lock().map(|mut b| b.take()).unwrap_or_default())
+    }
+
+    /// Whether the program has exited.
+    pub fn has_exited(&self) -> bool {
+        self.output.lock().map(|b| b.exited).unwrap_or(true)
+    }
+
+    /// Whether the session has gone unused for its idle timeout.
+    pub fn is_idle(&self) -> bool {
+        self.last_active
+            .lock()
+            .map(|last| last.elapsed() > self.idle_timeout)
+            .unwrap_or(true)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Stop the program and remove its container.
+    pub async fn close(&self) {
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.reader.abort();
+        self.backend.remove(&self.container_id).await;
+        self.notify.notify_waiters();
+        tracing::info!(session = %self.id, "Closed sandbox session");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+    use crate::sandbox::backend::{AttachedProcess, ContainerSpec};
+    use crate::sandbox::config::ContainerBackendKind;
+    use crate::sandbox::container::ContainerOutput;
+
+    /// Attaches an "echo" process: every line written to stdin comes back
+    /// on stdout, and `exit` ends it with code 3.
+    #[derive(Default)]
+    struct FakeBackend {
+        specs: Mutex<Vec<ContainerSpec>>,
+        removed: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ContainerBackend for FakeBackend {
+        fn kind(&self) -> ContainerBackendKind {
+            ContainerBackendKind::Docker
+        }
+        fn host_gateway(&self) -> &str {
+            "172.17.0.1"
+        }
+        async fn ping(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn image_exists(&self, _image: &str) -> bool {
+            true
+        }
+        async fn pull_image(&self, _image: &str) -> Result<()> {
+            Ok(())
+        }
+        async fn run(
+            &self,
+            _spec: &ContainerSpec,
+            _limits: &ResourceLimits,
+        ) -> Result<ContainerOutput> {
+            unimplemented!()
+        }
+        async fn start(&self, spec: &ContainerSpec, _limits: &ResourceLimits) -> Result<String> {
+            self.specs.lock().unwrap().push(spec.clone());
+            Ok("c1".to_string())
+        }
+        async fn remove(&self, container_id: &str) {
+            self.removed.lock().unwrap().push(container_id.to_string());
+        }
+        async fn exec(
+            &self,
+            _container_id: &str,
+            _command: &str,
+            _working_dir: &str,
+            _env: &[(String, String)],
+            _limits: &ResourceLimits,
+        ) -> Result<ContainerOutput> {
+            unimplemented!()
+        }
+        async fn attach(
+            &self,
+            _container_id: &str,
+            _command: &str,
+            _working_dir: &str,
+            _env: &[(String, String)],
+        ) -> Result<AttachedProcess> {
+            let (stdin, mut process_in) = tokio::io::duplex(1024);
+            let (tx, rx) = tokio::sync::mpsc::channel(16);
+            tokio::spawn(async move {
+                let mut buf = [0u8; 256];
+                while let Ok(n) = process_in.read(&mut buf).await {
+                    if n == 0 || buf[..n].starts_with(b"exit") {
+                        break;
+                    }
+                    let _ = tx.send(OutputChunk::Stdout(buf[..n].to_vec())).await;
+                }
+                let _ = tx.send(OutputChunk::Exited(Some(3))).await;
+            });
+            Ok(AttachedProcess {
+                stdin: Box::pin(stdin),
+                output: Box::pin(tokio_stream::wrappers::ReceiverStream::new(rx)),
+            })
+        }
+    }
+
+    async fn start(backend: &Arc<FakeBackend>, idle_timeout: Duration) -> Arc<SandboxSession> {
+        let runner = ContainerRunner::new(backend.clone(), "sandbox:latest".to_string(), 3128);
+        SandboxSession::start(
+            &runner,
+            "python3 -i",
+            Path::new("/tmp/project"),
+            SandboxPolicy::WorkspaceWrite,
+            &ResourceLimits::default(),
+            HashMap::new(),
+            idle_timeout,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_session_round_trip() {
+        let backend = Arc::new(FakeBackend::default());
+        let session = start(&backend, Duration::from_secs(60)).await;
+
+        {
+            let specs = backend.specs.lock().unwrap();
+            assert_eq!(specs[0].command, IDLE_COMMAND);
+            assert!(specs[0].env.iter().any(|(k, _)| k == "HTTP_PROXY"));
+        }
+
+        session.write_stdin(b"print(1)\n").await.unwrap();
+        let output = session.read_output(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(output.stdout, "print(1)\n");
+        assert!(!output.exited);
+
+        session.write_stdin(b"exit\n").await.unwrap();
+        let mut output = session.read_output(Duration::from_secs(5)).await.unwrap();
+        while !output.exited {
+            output = session.read_output(Duration::from_secs(5)).await.unwrap();
+        }
+        assert_eq!(output.exit_code, Some(3));
+        assert!(session.has_exited());
+
+        session.close().await;
+        assert_eq!(*backend.removed.lock().unwrap(), vec!["c1".to_string()]);
+        assert!(matches!(
+            session.write_stdin(b"x").await,
+            Err(SandboxError::SessionClosed { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_waits_only_as_long_as_asked() {
+        let backend = Arc::new(FakeBackend::default());
+        let session = start(&backend, Duration::ZERO).await;
+        let output = session
+            .read_output(Duration::from_millis(20))
+            .await
+            .unwrap();
+        assert_eq!(output, SessionOutput::default());
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert!(session.is_idle());
+        session.close().await;
+    }
+
+    #[test]
+    fn test_buffer_keeps_latest_output_and_whole_characters() {
+        let mut buf = OutputBuffer::default();
+        buf.push(OutputChunk::Stdout(b"abcdef".to_vec()), 4);
+        let output = buf.take();
+        assert_eq!(output.stdout, "cdef");
+        assert!(output.truncated);
+
+        let snowman = "☃".as_bytes();
+        buf.push(OutputChunk::Stdout(snowman[..2].to_vec()), 64);
+        assert_eq!(buf.take().stdout, "");
+        buf.push(OutputChunk::Stdout(snowman[2..].to_vec()), 64);
+        assert_eq!(buf.take().stdout, "☃");
+    }
+}
@@ -3,6 +3,7 @@
 //! Provides platform-specific keychain support:
 //! - macOS: security-framework (Keychain Services)
 //! - Linux: secret-service (GNOME Keyring, KWallet)
+//! - Windows: DPAPI (key encrypted to the current user, stored under `~/.ironclaw`)
 //!
 //! # Example
 //!
@@ -19,15 +20,21 @@
 
 use crate::secrets::SecretError;
 
-// These are consumed only by the macOS/Linux `platform` modules below; the
-// fallback module for other targets (e.g. Windows) doesn't reference them, so
-// silence dead_code there while keeping the lint live where they're used.
+// These are consumed only by the macOS/Linux/Windows `platform` modules below;
+// the fallback module for other targets doesn't reference them, so silence
+// dead_code there while keeping the lint live where they're used.
 /// Service name for keychain entries.
-#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), allow(dead_code))]
+#[cfg_attr(
+    not(any(target_os = "macos", target_os = "linux", target_os = "windows")),
+    allow(dead_code)
+)]
 const SERVICE_NAME: &str = "ironclaw";
 
 /// Account name for the master key.
-#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), allow(dead_code))]
+#[cfg_attr(
+    not(any(target_os = "macos", target_os = "linux", target_os = "windows")),
+    allow(dead_code)
+)]
 const MASTER_KEY_ACCOUNT: &str = "master_key";
 
 /// Generate a random 32-byte master key using OS-level RNG.
@@ -231,11 +238,155 @@ mod platform {
     }
 }
 
+// ============================================================================
+// Windows implementation using DPAPI
+// ============================================================================
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::path::PathBuf;
+
+    use windows_sys::Win32::Foundation::LocalFree;
+    use windows_sys::Win32::Security::Cryptography::{
+        CRYPT_INTEGER_BLOB, CRYPTPROTECT_UI_FORBIDDEN, CryptProtectData, CryptUnprotectData,
+    };
+
+    use super::*;
+
+    /// DPAPI-protected key file (`~/.ironclaw/master_key.dpapi`).
+    fn key_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".ironclaw")
+            .join(format!("{}.dpapi", MASTER_KEY_ACCOUNT))
+    }
+
+    /// Copy a DPAPI output blob and release the system allocation.
+    fn take_blob(blob: CRYPT_INTEGER_BLOB) -> Vec<u8> {
+        // SAFETY: on success DPAPI returns a LocalAlloc'd buffer of exactly
+        // `cbData` bytes, which we copy out before freeing it once.
+        unsafe {
+            let bytes = std::slice::from_raw_parts(blob.pbData, blob.cbData as usize).to_vec();
+            LocalFree(blob.pbData.cast());
+            bytes
+        }
+    }
+
+    fn protect(data: &[u8]) -> Result<Vec<u8>, SecretError> {
+        let input = CRYPT_INTEGER_BLOB {
+            cbData: data.len() as u32,
+            pbData: data.as_ptr() as *mut u8,
+        };
+        let mut output = CRYPT_INTEGER_BLOB {
+            cbData: 0,
+            pbData: std::ptr::null_mut(),
+        };
+        let description: Vec<u16> = SERVICE_NAME
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+
+        // SAFETY: all pointers reference live buffers for the duration of the
+        // call; optional arguments are null as permitted by the API.
+        let ok = unsafe {
+            CryptProtectData(
+                &input,
+                description.as_ptr(),
+                std::ptr::null(),
+                std::ptr::null(),
+                std::ptr::null(),
+                CRYPTPROTECT_UI_FORBIDDEN,
+                &mut output,
+            )
+        };
+        if ok == 0 {
+            return Err(SecretError::KeychainError(format!(
+                "DPAPI encryption failed: {}",
+                std::io::Error::last_os_error()
+            )));
+        }
+        Ok(take_blob(output))
+    }
+
+    fn unprotect(data: &[u8]) -> Result<Vec<u8>, SecretError> {
+        let input = CRYPT_INTEGER_BLOB {
+            cbData: data.len() as u32,
+            pbData: data.as_ptr() as *mut u8,
+        };
+        let mut output = CRYPT_INTEGER_BLOB {
+            cbData: 0,
+            pbData: std::ptr::null_mut(),
+        };
+
+        // SAFETY: see `protect`.
+        let ok = unsafe {
+            CryptUnprotectData(
+                &input,
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                std::ptr::null(),
+                std::ptr::null(),
+                CRYPTPROTECT_UI_FORBIDDEN,
+                &mut output,
+            )
+        };
+        if ok == 0 {
+            return Err(SecretError::KeychainError(format!(
+                "DPAPI decryption failed: {}",
+                std::io::Error::last_os_error()
+            )));
+        }
+        Ok(take_blob(output))
+    }
+
+    /// Store the master key encrypted with DPAPI for the current user.
+    pub async fn store_master_key(key: &[u8]) -> Result<(), SecretError> {
+        let key_hex: String = key.iter().map(|b| format!("{:02x}", b)).collect();
+        let protected = protect(key_hex.as_bytes())?;
+
+        let path = key_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                SecretError::KeychainError(format!("Failed to create key directory: {}", e))
+            })?;
+        }
+        std::fs::write(&path, protected)
+            .map_err(|e| SecretError::KeychainError(format!("Failed to write key file: {}", e)))
+    }
+
+    /// Retrieve the master key from the DPAPI-protected key file.
+    pub async fn get_master_key() -> Result<Vec<u8>, SecretError> {
+        let protected = std::fs::read(key_path())
+            .map_err(|e| SecretError::KeychainError(format!("Failed to read key file: {}", e)))?;
+        let hex_bytes = unprotect(&protected)?;
+        let hex_str = String::from_utf8(hex_bytes)
+            .map_err(|_| SecretError::KeychainError("Invalid UTF-8 in key file".to_string()))?;
+        hex_to_bytes(&hex_str)
+    }
+
+    /// Delete the DPAPI-protected key file.
+    pub async fn delete_master_key() -> Result<(), SecretError> {
+        match std::fs::remove_file(key_path()) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(SecretError::KeychainError(format!(
+                "Failed to delete key file: {}",
+                e
+            ))),
+        }
+    }
+
+    /// Check if a DPAPI-protected master key exists.
+    pub async fn has_master_key() -> bool {
+        key_path().exists()
+    }
+}
+
 // ============================================================================
 // Fallback for unsupported platforms
 // ============================================================================
 
-#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
 mod platform {
     use super::*;
 
@@ -266,7 +417,10 @@ mod platform {
 pub use platform::{delete_master_key, get_master_key, has_master_key, store_master_key};
 
 /// Parse a hex string to bytes.
-#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), allow(dead_code))]
+#[cfg_attr(
+    not(any(target_os = "macos", target_os = "linux", target_os = "windows")),
+    allow(dead_code)
+)]
 fn hex_to_bytes(hex: &str) -> Result<Vec<u8>, SecretError> {
     if !hex.len().is_multiple_of(2) {
         return Err(SecretError::KeychainError(
@@ -6,6 +6,10 @@
 //! - PostgreSQL persistence
 //! - OS keychain integration for master key
 //! - Access control for WASM tools
+//! - Per-extension namespaces: a secret is injected only for extensions
+//!   granted it via [`SecretsStore::grant`]
+//! - Secret groups: an admin shares a secret with a group, and tools running
+//!   for any member resolve it, with every use audited
 //!
 //! # Security Model
 //!
@@ -17,7 +21,7 @@
 //! │                          (per-secret key via HKDF)                          │
 //! │                                                                              │
 //! │   WASM requests HTTP ──► Host checks allowlist ──► Decrypt secret ──►       │
-//! │                          & allowed_secrets        (in memory only)           │
+//! │                          allowed_secrets & grant  (in memory only)           │
 //! │                                                         │                    │
 //! │                                                         ▼                    │
 //! │                          Inject into request ──► Execute HTTP call          │
@@ -71,7 +75,7 @@ pub use store::PostgresSecretsStore;
 pub use store::SecretsStore;
 pub use types::{
     CreateSecretParams, CredentialLocation, CredentialMapping, DecryptedSecret, Secret,
-    SecretError, SecretRef,
+    SecretError, SecretGroup, SecretRef, SecretSource, SharedSecret, SharedSecretUse,
 };
 
 #[cfg(test)]
@@ -5,6 +5,7 @@
 //! - Expiration checking
 //! - Usage tracking
 //! - Access control (which secrets a tool can use)
+//! - Secret groups (secrets shared with a set of users) and their audit log
 
 use std::sync::Arc;
 
@@ -16,7 +17,10 @@ use secrecy::ExposeSecret;
 use uuid::Uuid;
 
 use crate::secrets::crypto::SecretsCrypto;
-use crate::secrets::types::{CreateSecretParams, DecryptedSecret, Secret, SecretError, SecretRef};
+use crate::secrets::types::{
+    CreateSecretParams, DecryptedSecret, Secret, SecretError, SecretGroup, SecretRef, SecretSource,
+    SharedSecret, SharedSecretUse,
+};
 
 /// Trait for secret storage operations.
 ///
@@ -59,6 +63,164 @@ pub trait SecretsStore: Send + Sync {
         secret_name: &str,
         allowed_secrets: &[String],
     ) -> Result<bool, SecretError>;
+
+    /// Grant `extension` access to a secret, adding it to that extension's
+    /// namespace. Granting twice is a no-op.
+    async fn grant(
+        &self,
+        user_id: &str,
+        secret_name: &str,
+        extension: &str,
+    ) -> Result<(), SecretError>;
+
+    /// Extensions whose namespace includes a secret.
+    async fn grantees(&self, user_id: &str, secret_name: &str) -> Result<Vec<String>, SecretError>;
+
+    /// Drop every grant held by `extension`. Returns how many were removed.
+    async fn revoke_grants(&self, user_id: &str, extension: &str) -> Result<usize, SecretError>;
+
+    /// Check whether `extension` may use a secret.
+    async fn is_granted(
+        &self,
+        user_id: &str,
+        secret_name: &str,
+        extension: &str,
+    ) -> Result<bool, SecretError> {
+        Ok(self
+            .grantees(user_id, secret_name)
+            .await?
+            .iter()
+            .any(|e| e == extension))
+    }
+
+    /// Add `user_id` to a secret group. Groups exist while they have
+    /// members or shares; adding twice is a no-op.
+    async fn add_group_member(&self, group: &str, user_id: &str) -> Result<(), SecretError>;
+
+    /// Remove `user_id` from a group. Returns whether they were a member.
+    async fn remove_group_member(&self, group: &str, user_id: &str) -> Result<bool, SecretError>;
+
+    /// Share `owner_id`'s secret with every member of `group`.
+    async fn share_with_group(
+        &self,
+        owner_id: &str,
+        secret_name: &str,
+        group: &str,
+    ) -> Result<(), SecretError>;
+
+    /// Stop sharing a secret with `group`. Returns whether it was shared.
+    async fn unshare_from_group(
+        &self,
+        owner_id: &str,
+        secret_name: &str,
+        group: &str,
+    ) -> Result<bool, SecretError>;
+
+    /// Every secret group with its members and shares, by name.
+    async fn groups(&self) -> Result<Vec<SecretGroup>, SecretError>;
+
+    /// A stored secret named `secret_name` shared with a group `user_id`
+    /// belongs to. When several groups share one, the first group by name
+    /// (then owner) wins.
+    async fn shared_source(
+        &self,
+        user_id: &str,
+        secret_name: &str,
+    ) -> Result<Option<SecretSource>, SecretError>;
+
+    /// Append an audit entry for a use of a group-shared secret.
+    async fn record_shared_use(&self, entry: &SharedSecretUse) -> Result<(), SecretError>;
+
+    /// The most recent shared-secret uses, newest first.
+    async fn shared_uses(&self, limit: usize) -> Result<Vec<SharedSecretUse>, SecretError>;
+
+    /// Find whose copy of a secret `user_id` resolves to: their own, or one
+    /// shared with one of their groups.
+    async fn resolve(&self, user_id: &str, secret_name: &str) -> Result<SecretSource, SecretError> {
+        if self.exists(user_id, secret_name).await? {
+            return Ok(SecretSource {
+                owner: user_id.to_string(),
+                group: None,
+            });
+        }
+        self.shared_source(user_id, secret_name)
+            .await?
+            .ok_or_else(|| SecretError::NotFound(secret_name.to_string()))
+    }
+
+    /// Decrypt a secret for `tool` running on behalf of `user_id`.
+    ///
+    /// Falls back to a group-shared secret when the user has no copy of
+    /// their own. Each shared use is audited before the value is returned,
+    /// so a failed audit write withholds the secret.
+    async fn get_for_tool(
+        &self,
+        user_id: &str,
+        secret_name: &str,
+        tool: &str,
+    ) -> Result<DecryptedSecret, SecretError> {
+        let source = self.resolve(user_id, secret_name).await?;
+        let secret = self.get_decrypted(&source.owner, secret_name).await?;
+        if let Some(group) = source.group {
+            tracing::info!(
+                group = %group,
+                owner = %source.owner,
+                secret = %secret_name,
+                user = %user_id,
+                tool = %tool,
+                "Resolved group-shared secret"
+            );
+            self.record_shared_use(&SharedSecretUse {
+                group,
+                owner: source.owner,
+                secret_name: secret_name.to_string(),
+                acting_user: user_id.to_string(),
+                tool: tool.to_string(),
+                used_at: Utc::now(),
+            })
+            .await?;
+        }
+        Ok(secret)
+    }
+
+    /// Re-encrypt every stored secret (all users) under a new master key.
+    ///
+    /// Runs in a single transaction, so a failure leaves every secret
+    /// readable with the current key. Returns the number of secrets
+    /// re-encrypted. The store keeps its original key afterwards; open a
+    /// new store with `new_crypto` to read the rotated secrets.
+    async fn rotate_master_key(&self, new_crypto: &SecretsCrypto) -> Result<usize, SecretError>;
+}
+
+/// Build the group listing from `(group, user)` member rows and
+/// `(group, owner, secret)` share rows, both sorted.
+fn assemble_groups(
+    members: impl IntoIterator<Item = (String, String)>,
+    shares: impl IntoIterator<Item = (String, String, String)>,
+) -> Vec<SecretGroup> {
+    let mut groups: std::collections::BTreeMap<String, SecretGroup> =
+        std::collections::BTreeMap::new();
+    for (group, user) in members {
+        groups
+            .entry(group.clone())
+            .or_insert_with(|| SecretGroup {
+                name: group,
+                ..Default::default()
+            })
+            .members
+            .push(user);
+    }
+    for (group, owner, secret_name) in shares {
+        groups
+            .entry(group.clone())
+            .or_insert_with(|| SecretGroup {
+                name: group,
+                ..Default::default()
+            })
+            .shares
+            .push(SharedSecret { owner, secret_name });
+    }
+    groups.into_values().collect()
 }
 
 /// PostgreSQL implementation of SecretsStore.
@@ -231,6 +393,20 @@ impl SecretsStore for PostgresSecretsStore {
             )
             .await
             .map_err(|e| SecretError::Database(e.to_string()))?;
+        client
+            .execute(
+                "DELETE FROM secret_grants WHERE user_id = $1 AND secret_name = $2",
+                &[&user_id, &name],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+        client
+            .execute(
+                "DELETE FROM secret_group_shares WHERE owner_id = $1 AND secret_name = $2",
+                &[&user_id, &name],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
 
         Ok(result > 0)
     }
@@ -285,6 +461,327 @@ impl SecretsStore for PostgresSecretsStore {
 
         Ok(false)
     }
+
+    async fn grant(
+        &self,
+        user_id: &str,
+        secret_name: &str,
+        extension: &str,
+    ) -> Result<(), SecretError> {
+        let client = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        client
+            .execute(
+                r#"
+                INSERT INTO secret_grants (user_id, secret_name, extension)
+                VALUES ($1, $2, $3)
+                ON CONFLICT DO NOTHING
+                "#,
+                &[&user_id, &secret_name, &extension],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn grantees(&self, user_id: &str, secret_name: &str) -> Result<Vec<String>, SecretError> {
+        let client = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        let rows = client
+            .query(
+                "SELECT extension FROM secret_grants WHERE user_id = $1 AND secret_name = $2 ORDER BY extension",
+                &[&user_id, &secret_name],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(rows.into_iter().map(|r| r.get(0)).collect())
+    }
+
+    async fn revoke_grants(&self, user_id: &str, extension: &str) -> Result<usize, SecretError> {
+        let client = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        let removed = client
+            .execute(
+                "DELETE FROM secret_grants WHERE user_id = $1 AND extension = $2",
+                &[&user_id, &extension],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(removed as usize)
+    }
+
+    async fn add_group_member(&self, group: &str, user_id: &str) -> Result<(), SecretError> {
+        let client = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        client
+            .execute(
+                r#"
+                INSERT INTO secret_group_members (group_name, user_id)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING
+                "#,
+                &[&group, &user_id],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn remove_group_member(&self, group: &str, user_id: &str) -> Result<bool, SecretError> {
+        let client = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        let removed = client
+            .execute(
+                "DELETE FROM secret_group_members WHERE group_name = $1 AND user_id = $2",
+                &[&group, &user_id],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(removed > 0)
+    }
+
+    async fn share_with_group(
+        &self,
+        owner_id: &str,
+        secret_name: &str,
+        group: &str,
+    ) -> Result<(), SecretError> {
+        if !self.exists(owner_id, secret_name).await? {
+            return Err(SecretError::NotFound(secret_name.to_string()));
+        }
+
+        let client = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        client
+            .execute(
+                r#"
+                INSERT INTO secret_group_shares (group_name, owner_id, secret_name)
+                VALUES ($1, $2, $3)
+                ON CONFLICT DO NOTHING
+                "#,
+                &[&group, &owner_id, &secret_name],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn unshare_from_group(
+        &self,
+        owner_id: &str,
+        secret_name: &str,
+        group: &str,
+    ) -> Result<bool, SecretError> {
+        let client = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        let removed = client
+            .execute(
+                "DELETE FROM secret_group_shares WHERE group_name = $1 AND owner_id = $2 AND secret_name = $3",
+                &[&group, &owner_id, &secret_name],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(removed > 0)
+    }
+
+    async fn groups(&self) -> Result<Vec<SecretGroup>, SecretError> {
+        let client = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        let members = client
+            .query(
+                "SELECT group_name, user_id FROM secret_group_members ORDER BY group_name, user_id",
+                &[],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+        let shares = client
+            .query(
+                "SELECT group_name, owner_id, secret_name FROM secret_group_shares ORDER BY group_name, owner_id, secret_name",
+                &[],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(assemble_groups(
+            members.into_iter().map(|r| (r.get(0), r.get(1))),
+            shares.into_iter().map(|r| (r.get(0), r.get(1), r.get(2))),
+        ))
+    }
+
+    async fn shared_source(
+        &self,
+        user_id: &str,
+        secret_name: &str,
+    ) -> Result<Option<SecretSource>, SecretError> {
+        let client = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        let row = client
+            .query_opt(
+                r#"
+                SELECT sh.group_name, sh.owner_id
+                FROM secret_group_shares sh
+                JOIN secret_group_members m ON m.group_name = sh.group_name
+                JOIN secrets s ON s.user_id = sh.owner_id AND s.name = sh.secret_name
+                WHERE m.user_id = $1 AND sh.secret_name = $2
+                ORDER BY sh.group_name, sh.owner_id
+                LIMIT 1
+                "#,
+                &[&user_id, &secret_name],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(row.map(|r| SecretSource {
+            group: Some(r.get(0)),
+            owner: r.get(1),
+        }))
+    }
+
+    async fn record_shared_use(&self, entry: &SharedSecretUse) -> Result<(), SecretError> {
+        let client = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        client
+            .execute(
+                r#"
+                INSERT INTO secret_group_uses (id, group_name, owner_id, secret_name, acting_user, tool, used_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+                &[
+                    &Uuid::new_v4(),
+                    &entry.group,
+                    &entry.owner,
+                    &entry.secret_name,
+                    &entry.acting_user,
+                    &entry.tool,
+                    &entry.used_at,
+                ],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn shared_uses(&self, limit: usize) -> Result<Vec<SharedSecretUse>, SecretError> {
+        let client = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        let rows = client
+            .query(
+                r#"
+                SELECT group_name, owner_id, secret_name, acting_user, tool, used_at
+                FROM secret_group_uses
+                ORDER BY used_at DESC
+                LIMIT $1
+                "#,
+                &[&(limit as i64)],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| SharedSecretUse {
+                group: r.get(0),
+                owner: r.get(1),
+                secret_name: r.get(2),
+                acting_user: r.get(3),
+                tool: r.get(4),
+                used_at: r.get(5),
+            })
+            .collect())
+    }
+
+    async fn rotate_master_key(&self, new_crypto: &SecretsCrypto) -> Result<usize, SecretError> {
+        let mut client = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+        let tx = client
+            .transaction()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        let rows = tx
+            .query(
+                "SELECT id, encrypted_value, key_salt FROM secrets FOR UPDATE",
+                &[],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        for row in &rows {
+            let id: Uuid = row.get(0);
+            let encrypted_value: Vec<u8> = row.get(1);
+            let key_salt: Vec<u8> = row.get(2);
+
+            let plaintext = self.crypto.decrypt(&encrypted_value, &key_salt)?;
+            let (encrypted_value, key_salt) = new_crypto.encrypt(plaintext.expose().as_bytes())?;
+
+            tx.execute(
+                "UPDATE secrets SET encrypted_value = $2, key_salt = $3, updated_at = NOW() WHERE id = $1",
+                &[&id, &encrypted_value, &key_salt],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+        Ok(rows.len())
+    }
 }
 
 #[cfg(feature = "postgres")]
@@ -468,87 +965,412 @@ impl SecretsStore for LibSqlSecretsStore {
             .next()
             .await
             .map_err(|e| SecretError::Database(e.to_string()))?
-            .is_some())
+            .is_some())
+    }
+
+    async fn list(&self, user_id: &str) -> Result<Vec<SecretRef>, SecretError> {
+        let conn = self.connect()?;
+        let mut rows = conn
+            .query(
+                "SELECT name, provider FROM secrets WHERE user_id = ?1 ORDER BY name",
+                libsql::params![user_id],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        let mut refs = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?
+        {
+            refs.push(SecretRef {
+                name: row.get::<String>(0).unwrap_or_default(),
+                provider: row.get::<String>(1).ok(),
+            });
+        }
+        Ok(refs)
+    }
+
+    async fn delete(&self, user_id: &str, name: &str) -> Result<bool, SecretError> {
+        let conn = self.connect()?;
+        let affected = conn
+            .execute(
+                "DELETE FROM secrets WHERE user_id = ?1 AND name = ?2",
+                libsql::params![user_id, name],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+        conn.execute(
+            "DELETE FROM secret_grants WHERE user_id = ?1 AND secret_name = ?2",
+            libsql::params![user_id, name],
+        )
+        .await
+        .map_err(|e| SecretError::Database(e.to_string()))?;
+        conn.execute(
+            "DELETE FROM secret_group_shares WHERE owner_id = ?1 AND secret_name = ?2",
+            libsql::params![user_id, name],
+        )
+        .await
+        .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(affected > 0)
+    }
+
+    async fn record_usage(&self, secret_id: Uuid) -> Result<(), SecretError> {
+        let now = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        let conn = self.connect()?;
+
+        conn.execute(
+            r#"
+                UPDATE secrets
+                SET last_used_at = ?1, usage_count = usage_count + 1
+                WHERE id = ?2
+                "#,
+            libsql::params![now.as_str(), secret_id.to_string()],
+        )
+        .await
+        .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn is_accessible(
+        &self,
+        user_id: &str,
+        secret_name: &str,
+        allowed_secrets: &[String],
+    ) -> Result<bool, SecretError> {
+        if !self.exists(user_id, secret_name).await? {
+            return Ok(false);
+        }
+
+        for pattern in allowed_secrets {
+            if pattern == secret_name {
+                return Ok(true);
+            }
+
+            if let Some(prefix) = pattern.strip_suffix('*')
+                && secret_name.starts_with(prefix)
+            {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    async fn grant(
+        &self,
+        user_id: &str,
+        secret_name: &str,
+        extension: &str,
+    ) -> Result<(), SecretError> {
+        let conn = self.connect()?;
+        conn.execute(
+            r#"
+                INSERT OR IGNORE INTO secret_grants (user_id, secret_name, extension)
+                VALUES (?1, ?2, ?3)
+                "#,
+            libsql::params![user_id, secret_name, extension],
+        )
+        .await
+        .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn grantees(&self, user_id: &str, secret_name: &str) -> Result<Vec<String>, SecretError> {
+        let conn = self.connect()?;
+        let mut rows = conn
+            .query(
+                "SELECT extension FROM secret_grants WHERE user_id = ?1 AND secret_name = ?2 ORDER BY extension",
+                libsql::params![user_id, secret_name],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        let mut extensions = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?
+        {
+            extensions.push(row.get::<String>(0).unwrap_or_default());
+        }
+        Ok(extensions)
+    }
+
+    async fn revoke_grants(&self, user_id: &str, extension: &str) -> Result<usize, SecretError> {
+        let conn = self.connect()?;
+        let removed = conn
+            .execute(
+                "DELETE FROM secret_grants WHERE user_id = ?1 AND extension = ?2",
+                libsql::params![user_id, extension],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(removed as usize)
+    }
+
+    async fn add_group_member(&self, group: &str, user_id: &str) -> Result<(), SecretError> {
+        let conn = self.connect()?;
+        conn.execute(
+            "INSERT OR IGNORE INTO secret_group_members (group_name, user_id) VALUES (?1, ?2)",
+            libsql::params![group, user_id],
+        )
+        .await
+        .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn remove_group_member(&self, group: &str, user_id: &str) -> Result<bool, SecretError> {
+        let conn = self.connect()?;
+        let removed = conn
+            .execute(
+                "DELETE FROM secret_group_members WHERE group_name = ?1 AND user_id = ?2",
+                libsql::params![group, user_id],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(removed > 0)
+    }
+
+    async fn share_with_group(
+        &self,
+        owner_id: &str,
+        secret_name: &str,
+        group: &str,
+    ) -> Result<(), SecretError> {
+        if !self.exists(owner_id, secret_name).await? {
+            return Err(SecretError::NotFound(secret_name.to_string()));
+        }
+
+        let conn = self.connect()?;
+        conn.execute(
+            r#"
+                INSERT OR IGNORE INTO secret_group_shares (group_name, owner_id, secret_name)
+                VALUES (?1, ?2, ?3)
+                "#,
+            libsql::params![group, owner_id, secret_name],
+        )
+        .await
+        .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn unshare_from_group(
+        &self,
+        owner_id: &str,
+        secret_name: &str,
+        group: &str,
+    ) -> Result<bool, SecretError> {
+        let conn = self.connect()?;
+        let removed = conn
+            .execute(
+                "DELETE FROM secret_group_shares WHERE group_name = ?1 AND owner_id = ?2 AND secret_name = ?3",
+                libsql::params![group, owner_id, secret_name],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(removed > 0)
+    }
+
+    async fn groups(&self) -> Result<Vec<SecretGroup>, SecretError> {
+        let conn = self.connect()?;
+
+        let mut rows = conn
+            .query(
+                "SELECT group_name, user_id FROM secret_group_members ORDER BY group_name, user_id",
+                (),
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+        let mut members = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?
+        {
+            members.push((
+                row.get::<String>(0).unwrap_or_default(),
+                row.get::<String>(1).unwrap_or_default(),
+            ));
+        }
+
+        let mut rows = conn
+            .query(
+                "SELECT group_name, owner_id, secret_name FROM secret_group_shares ORDER BY group_name, owner_id, secret_name",
+                (),
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+        let mut shares = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?
+        {
+            shares.push((
+                row.get::<String>(0).unwrap_or_default(),
+                row.get::<String>(1).unwrap_or_default(),
+                row.get::<String>(2).unwrap_or_default(),
+            ));
+        }
+
+        Ok(assemble_groups(members, shares))
+    }
+
+    async fn shared_source(
+        &self,
+        user_id: &str,
+        secret_name: &str,
+    ) -> Result<Option<SecretSource>, SecretError> {
+        let conn = self.connect()?;
+        let mut rows = conn
+            .query(
+                r#"
+                SELECT sh.group_name, sh.owner_id
+                FROM secret_group_shares sh
+                JOIN secret_group_members m ON m.group_name = sh.group_name
+                JOIN secrets s ON s.user_id = sh.owner_id AND s.name = sh.secret_name
+                WHERE m.user_id = ?1 AND sh.secret_name = ?2
+                ORDER BY sh.group_name, sh.owner_id
+                LIMIT 1
+                "#,
+                libsql::params![user_id, secret_name],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(rows
+            .next()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?
+            .map(|row| SecretSource {
+                group: Some(row.get::<String>(0).unwrap_or_default()),
+                owner: row.get::<String>(1).unwrap_or_default(),
+            }))
     }
 
-    async fn list(&self, user_id: &str) -> Result<Vec<SecretRef>, SecretError> {
+    async fn record_shared_use(&self, entry: &SharedSecretUse) -> Result<(), SecretError> {
+        let conn = self.connect()?;
+        conn.execute(
+            r#"
+                INSERT INTO secret_group_uses (id, group_name, owner_id, secret_name, acting_user, tool, used_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                "#,
+            libsql::params![
+                Uuid::new_v4().to_string(),
+                entry.group.as_str(),
+                entry.owner.as_str(),
+                entry.secret_name.as_str(),
+                entry.acting_user.as_str(),
+                entry.tool.as_str(),
+                entry
+                    .used_at
+                    .to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            ],
+        )
+        .await
+        .map_err(|e| SecretError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn shared_uses(&self, limit: usize) -> Result<Vec<SharedSecretUse>, SecretError> {
         let conn = self.connect()?;
         let mut rows = conn
             .query(
-                "SELECT name, provider FROM secrets WHERE user_id = ?1 ORDER BY name",
-                libsql::params![user_id],
+                r#"
+                SELECT group_name, owner_id, secret_name, acting_user, tool, used_at
+                FROM secret_group_uses
+                ORDER BY used_at DESC
+                LIMIT ?1
+                "#,
+                libsql::params![limit as i64],
             )
             .await
             .map_err(|e| SecretError::Database(e.to_string()))?;
 
-        let mut refs = Vec::new();
+        let mut uses = Vec::new();
         while let Some(row) = rows
             .next()
             .await
             .map_err(|e| SecretError::Database(e.to_string()))?
         {
-            refs.push(SecretRef {
-                name: row.get::<String>(0).unwrap_or_default(),
-                provider: row.get::<String>(1).ok(),
+            let used_at: String = row
+                .get(5)
+                .map_err(|e| SecretError::Database(e.to_string()))?;
+            uses.push(SharedSecretUse {
+                group: row.get::<String>(0).unwrap_or_default(),
+                owner: row.get::<String>(1).unwrap_or_default(),
+                secret_name: row.get::<String>(2).unwrap_or_default(),
+                acting_user: row.get::<String>(3).unwrap_or_default(),
+                tool: row.get::<String>(4).unwrap_or_default(),
+                used_at: libsql_parse_timestamp(&used_at)?,
             });
         }
-        Ok(refs)
+        Ok(uses)
     }
 
-    async fn delete(&self, user_id: &str, name: &str) -> Result<bool, SecretError> {
+    async fn rotate_master_key(&self, new_crypto: &SecretsCrypto) -> Result<usize, SecretError> {
+        let now = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
         let conn = self.connect()?;
-        let affected = conn
-            .execute(
-                "DELETE FROM secrets WHERE user_id = ?1 AND name = ?2",
-                libsql::params![user_id, name],
-            )
+        let tx = conn
+            .transaction()
             .await
             .map_err(|e| SecretError::Database(e.to_string()))?;
 
-        Ok(affected > 0)
-    }
-
-    async fn record_usage(&self, secret_id: Uuid) -> Result<(), SecretError> {
-        let now = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
-        let conn = self.connect()?;
-
-        conn.execute(
-            r#"
-                UPDATE secrets
-                SET last_used_at = ?1, usage_count = usage_count + 1
-                WHERE id = ?2
-                "#,
-            libsql::params![now.as_str(), secret_id.to_string()],
-        )
-        .await
-        .map_err(|e| SecretError::Database(e.to_string()))?;
-
-        Ok(())
-    }
+        let mut rows = tx
+            .query("SELECT id, encrypted_value, key_salt FROM secrets", ())
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
 
-    async fn is_accessible(
-        &self,
-        user_id: &str,
-        secret_name: &str,
-        allowed_secrets: &[String],
-    ) -> Result<bool, SecretError> {
-        if !self.exists(user_id, secret_name).await? {
-            return Ok(false);
+        let mut rotated = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?
+        {
+            let id: String = row
+                .get(0)
+                .map_err(|e| SecretError::Database(e.to_string()))?;
+            let encrypted_value: Vec<u8> = row
+                .get(1)
+                .map_err(|e| SecretError::Database(e.to_string()))?;
+            let key_salt: Vec<u8> = row
+                .get(2)
+                .map_err(|e| SecretError::Database(e.to_string()))?;
+
+            let plaintext = self.crypto.decrypt(&encrypted_value, &key_salt)?;
+            rotated.push((id, new_crypto.encrypt(plaintext.expose().as_bytes())?));
         }
+        drop(rows);
 
-        for pattern in allowed_secrets {
-            if pattern == secret_name {
-                return Ok(true);
-            }
-
-            if let Some(prefix) = pattern.strip_suffix('*')
-                && secret_name.starts_with(prefix)
-            {
-                return Ok(true);
-            }
+        for (id, (encrypted_value, key_salt)) in &rotated {
+            tx.execute(
+                "UPDATE secrets SET encrypted_value = ?2, key_salt = ?3, updated_at = ?4 WHERE id = ?1",
+                libsql::params![
+                    id.as_str(),
+                    libsql::Value::Blob(encrypted_value.clone()),
+                    libsql::Value::Blob(key_salt.clone()),
+                    now.as_str(),
+                ],
+            )
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
         }
 
-        Ok(false)
+        tx.commit()
+            .await
+            .map_err(|e| SecretError::Database(e.to_string()))?;
+        Ok(rotated.len())
     }
 }
 
@@ -633,7 +1455,7 @@ fn libsql_row_to_secret(row: &libsql::Row) -> Result<Secret, SecretError> {
 /// In-memory implementation for testing.
 #[cfg(test)]
 pub mod testing {
-    use std::collections::HashMap;
+    use std::collections::{BTreeSet, HashMap};
     use std::sync::Arc;
 
     use async_trait::async_trait;
@@ -643,13 +1465,21 @@ pub mod testing {
     use uuid::Uuid;
 
     use crate::secrets::crypto::SecretsCrypto;
-    use crate::secrets::store::SecretsStore;
+    use crate::secrets::store::{SecretsStore, assemble_groups};
     use crate::secrets::types::{
-        CreateSecretParams, DecryptedSecret, Secret, SecretError, SecretRef,
+        CreateSecretParams, DecryptedSecret, Secret, SecretError, SecretGroup, SecretRef,
+        SecretSource, SharedSecretUse,
     };
 
     pub struct InMemorySecretsStore {
         secrets: RwLock<HashMap<(String, String), Secret>>,
+        /// (user_id, secret_name, extension)
+        grants: RwLock<BTreeSet<(String, String, String)>>,
+        /// (group, user_id)
+        members: RwLock<BTreeSet<(String, String)>>,
+        /// (group, owner_id, secret_name)
+        shares: RwLock<BTreeSet<(String, String, String)>>,
+        uses: RwLock<Vec<SharedSecretUse>>,
         crypto: Arc<SecretsCrypto>,
     }
 
@@ -657,6 +1487,10 @@ pub mod testing {
         pub fn new(crypto: Arc<SecretsCrypto>) -> Self {
             Self {
                 secrets: RwLock::new(HashMap::new()),
+                grants: RwLock::new(BTreeSet::new()),
+                members: RwLock::new(BTreeSet::new()),
+                shares: RwLock::new(BTreeSet::new()),
+                uses: RwLock::new(Vec::new()),
                 crypto,
             }
         }
@@ -736,6 +1570,14 @@ pub mod testing {
         }
 
         async fn delete(&self, user_id: &str, name: &str) -> Result<bool, SecretError> {
+            self.grants
+                .write()
+                .await
+                .retain(|(uid, secret, _)| uid != user_id || secret != name);
+            self.shares
+                .write()
+                .await
+                .retain(|(_, owner, secret)| owner != user_id || secret != name);
             Ok(self
                 .secrets
                 .write()
@@ -769,6 +1611,170 @@ pub mod testing {
             }
             Ok(false)
         }
+
+        async fn grant(
+            &self,
+            user_id: &str,
+            secret_name: &str,
+            extension: &str,
+        ) -> Result<(), SecretError> {
+            self.grants.write().await.insert((
+                user_id.to_string(),
+                secret_name.to_string(),
+                extension.to_string(),
+            ));
+            Ok(())
+        }
+
+        async fn grantees(
+            &self,
+            user_id: &str,
+            secret_name: &str,
+        ) -> Result<Vec<String>, SecretError> {
+            Ok(self
+                .grants
+                .read()
+                .await
+                .iter()
+                .filter(|(uid, secret, _)| uid == user_id && secret == secret_name)
+                .map(|(_, _, ext)| ext.clone())
+                .collect())
+        }
+
+        async fn revoke_grants(
+            &self,
+            user_id: &str,
+            extension: &str,
+        ) -> Result<usize, SecretError> {
+            let mut grants = self.grants.write().await;
+            let before = grants.len();
+            grants.retain(|(uid, _, ext)| uid != user_id || ext != extension);
+            Ok(before - grants.len())
+        }
+
+        async fn add_group_member(&self, group: &str, user_id: &str) -> Result<(), SecretError> {
+            self.members
+                .write()
+                .await
+                .insert((group.to_string(), user_id.to_string()));
+            Ok(())
+        }
+
+        async fn remove_group_member(
+            &self,
+            group: &str,
+            user_id: &str,
+        ) -> Result<bool, SecretError> {
+            Ok(self
+                .members
+                .write()
+                .await
+                .remove(&(group.to_string(), user_id.to_string())))
+        }
+
+        async fn share_with_group(
+            &self,
+            owner_id: &str,
+            secret_name: &str,
+            group: &str,
+        ) -> Result<(), SecretError> {
+            if !self.exists(owner_id, secret_name).await? {
+                return Err(SecretError::NotFound(secret_name.to_string()));
+            }
+            self.shares.write().await.insert((
+                group.to_string(),
+                owner_id.to_string(),
+                secret_name.to_string(),
+            ));
+            Ok(())
+        }
+
+        async fn unshare_from_group(
+            &self,
+            owner_id: &str,
+            secret_name: &str,
+            group: &str,
+        ) -> Result<bool, SecretError> {
+            Ok(self.shares.write().await.remove(&(
+                group.to_string(),
+                owner_id.to_string(),
+                secret_name.to_string(),
+            )))
+        }
+
+        async fn groups(&self) -> Result<Vec<SecretGroup>, SecretError> {
+            Ok(assemble_groups(
+                self.members.read().await.iter().cloned(),
+                self.shares.read().await.iter().cloned(),
+            ))
+        }
+
+        async fn shared_source(
+            &self,
+            user_id: &str,
+            secret_name: &str,
+        ) -> Result<Option<SecretSource>, SecretError> {
+            let members = self.members.read().await;
+            let secrets = self.secrets.read().await;
+            Ok(self
+                .shares
+                .read()
+                .await
+                .iter()
+                .find(|(group, owner, name)| {
+                    name == secret_name
+                        && members.contains(&(group.clone(), user_id.to_string()))
+                        && secrets.contains_key(&(owner.clone(), name.clone()))
+                })
+                .map(|(group, owner, _)| SecretSource {
+                    owner: owner.clone(),
+                    group: Some(group.clone()),
+                }))
+        }
+
+        async fn record_shared_use(&self, entry: &SharedSecretUse) -> Result<(), SecretError> {
+            self.uses.write().await.push(entry.clone());
+            Ok(())
+        }
+
+        async fn shared_uses(&self, limit: usize) -> Result<Vec<SharedSecretUse>, SecretError> {
+            Ok(self
+                .uses
+                .read()
+                .await
+                .iter()
+                .rev()
+                .take(limit)
+                .cloned()
+                .collect())
+        }
+
+        async fn rotate_master_key(
+            &self,
+            new_crypto: &SecretsCrypto,
+        ) -> Result<usize, SecretError> {
+            let mut secrets = self.secrets.write().await;
+            // Re-encrypt everything before writing anything back, so a
+            // decryption failure leaves the store untouched.
+            let mut rotated = Vec::with_capacity(secrets.len());
+            for (key, secret) in secrets.iter() {
+                let plaintext = self
+                    .crypto
+                    .decrypt(&secret.encrypted_value, &secret.key_salt)?;
+                rotated.push((
+                    key.clone(),
+                    new_crypto.encrypt(plaintext.expose().as_bytes())?,
+                ));
+            }
+            for (key, (encrypted_value, key_salt)) in &rotated {
+                if let Some(secret) = secrets.get_mut(key) {
+                    secret.encrypted_value = encrypted_value.clone();
+                    secret.key_salt = key_salt.clone();
+                    secret.updated_at = Utc::now();
+                }
+            }
+            Ok(rotated.len())
+        }
     }
 }
 
@@ -889,6 +1895,137 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_grants_are_per_extension() {
+        let store = test_store();
+        store
+            .create("user1", CreateSecretParams::new("slack_bot_token", "xoxb"))
+            .await
+            .unwrap();
+        store
+            .grant("user1", "slack_bot_token", "slack")
+            .await
+            .unwrap();
+        store
+            .grant("user1", "slack_bot_token", "slack")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.grantees("user1", "slack_bot_token").await.unwrap(),
+            vec!["slack"]
+        );
+        assert!(
+            !store
+                .is_granted("user1", "slack_bot_token", "github")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !store
+                .is_granted("user2", "slack_bot_token", "slack")
+                .await
+                .unwrap()
+        );
+
+        assert_eq!(store.revoke_grants("user1", "slack").await.unwrap(), 1);
+        assert!(
+            !store
+                .is_granted("user1", "slack_bot_token", "slack")
+                .await
+                .unwrap()
+        );
+
+        // Deleting a secret drops its grants.
+        store
+            .grant("user1", "slack_bot_token", "slack")
+            .await
+            .unwrap();
+        store.delete("user1", "slack_bot_token").await.unwrap();
+        assert!(
+            store
+                .grantees("user1", "slack_bot_token")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_group_shared_secret_resolves_for_members() {
+        let store = test_store();
+        store
+            .create(
+                "alice",
+                CreateSecretParams::new("pagerduty_token", "pd-ops"),
+            )
+            .await
+            .unwrap();
+        store
+            .share_with_group("alice", "pagerduty_token", "ops")
+            .await
+            .unwrap();
+        store.add_group_member("ops", "bob").await.unwrap();
+
+        // A member gets the owner's copy, and the use is audited.
+        let secret = store
+            .get_for_tool("bob", "pagerduty_token", "pagerduty")
+            .await
+            .unwrap();
+        assert_eq!(secret.expose(), "pd-ops");
+        let uses = store.shared_uses(10).await.unwrap();
+        assert_eq!(uses.len(), 1);
+        assert_eq!(uses[0].group, "ops");
+        assert_eq!(uses[0].owner, "alice");
+        assert_eq!(uses[0].acting_user, "bob");
+        assert_eq!(uses[0].tool, "pagerduty");
+
+        // The owner's own use is not a shared use.
+        store
+            .get_for_tool("alice", "pagerduty_token", "pagerduty")
+            .await
+            .unwrap();
+        assert_eq!(store.shared_uses(10).await.unwrap().len(), 1);
+
+        // Non-members get nothing.
+        assert!(
+            store
+                .get_for_tool("mallory", "pagerduty_token", "pagerduty")
+                .await
+                .is_err()
+        );
+
+        let groups = store.groups().await.unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].members, vec!["bob"]);
+        assert_eq!(groups[0].shares[0].secret_name, "pagerduty_token");
+
+        // A member's own secret takes precedence over the shared one.
+        store
+            .create("bob", CreateSecretParams::new("pagerduty_token", "pd-bob"))
+            .await
+            .unwrap();
+        let secret = store
+            .get_for_tool("bob", "pagerduty_token", "pagerduty")
+            .await
+            .unwrap();
+        assert_eq!(secret.expose(), "pd-bob");
+
+        // Leaving the group or deleting the secret ends sharing.
+        store.delete("bob", "pagerduty_token").await.unwrap();
+        assert!(store.remove_group_member("ops", "bob").await.unwrap());
+        assert!(store.resolve("bob", "pagerduty_token").await.is_err());
+        store.add_group_member("ops", "bob").await.unwrap();
+        store.delete("alice", "pagerduty_token").await.unwrap();
+        assert!(store.resolve("bob", "pagerduty_token").await.is_err());
+        assert!(
+            store
+                .share_with_group("alice", "pagerduty_token", "ops")
+                .await
+                .is_err()
+        );
+    }
+
     #[tokio::test]
     async fn test_user_isolation() {
         let store = test_store();
@@ -914,4 +2051,29 @@ mod tests {
         assert_eq!(v1.expose(), "user1_value");
         assert_eq!(v2.expose(), "user2_value");
     }
+
+    #[tokio::test]
+    async fn test_rotate_master_key() {
+        let store = test_store();
+        store
+            .create("user1", CreateSecretParams::new("a", "alpha"))
+            .await
+            .unwrap();
+        store
+            .create("user2", CreateSecretParams::new("b", "beta"))
+            .await
+            .unwrap();
+
+        let new_key = "fedcba9876543210fedcba9876543210";
+        let new_crypto = SecretsCrypto::new(SecretString::from(new_key.to_string())).unwrap();
+        assert_eq!(store.rotate_master_key(&new_crypto).await.unwrap(), 2);
+
+        // Secrets now decrypt only with the new key.
+        let secret = store.get("user2", "b").await.unwrap();
+        let value = new_crypto
+            .decrypt(&secret.encrypted_value, &secret.key_salt)
+            .unwrap();
+        assert_eq!(value.expose(), "beta");
+        assert!(store.get_decrypted("user2", "b").await.is_err());
+    }
 }
@@ -191,6 +191,43 @@ impl CreateSecretParams {
     }
 }
 
+/// Whose copy of a secret a user's tools resolve to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretSource {
+    /// User the secret is stored under.
+    pub owner: String,
+    /// Group that shares it, when the secret is not the user's own.
+    pub group: Option<String>,
+}
+
+/// A secret group: its members and the secrets shared with them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecretGroup {
+    pub name: String,
+    pub members: Vec<String>,
+    pub shares: Vec<SharedSecret>,
+}
+
+/// A secret shared with a group, named by its owner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharedSecret {
+    pub owner: String,
+    pub secret_name: String,
+}
+
+/// Audit record for one use of a group-shared secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedSecretUse {
+    pub group: String,
+    pub owner: String,
+    pub secret_name: String,
+    /// Member the tool ran for.
+    pub acting_user: String,
+    /// Tool or extension that consumed the secret.
+    pub tool: String,
+    pub used_at: DateTime<Utc>,
+}
+
 /// Where a credential should be injected in an HTTP request.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub enum CredentialLocation {
@@ -73,6 +73,19 @@ pub struct Settings {
     #[serde(default)]
     pub heartbeat: HeartbeatSettings,
 
+    // === Step 8: Locale ===
+    /// Time zone and language preferences.
+    #[serde(default)]
+    pub locale: LocaleSettings,
+
+    /// Where background notifications are delivered.
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+
+    /// Workspace memory quotas and conversation history indexing.
+    #[serde(default)]
+    pub memory: MemorySettings,
+
     // === Advanced Settings (not asked during setup, editable via CLI) ===
     /// Agent behavior configuration.
     #[serde(default)]
@@ -93,6 +106,14 @@ pub struct Settings {
     /// Builder configuration.
     #[serde(default)]
     pub builder: BuilderSettings,
+
+    /// Remote browser for the browser tool.
+    #[serde(default)]
+    pub browser: BrowserSettings,
+
+    /// Corporate proxy and CA bundle for outbound HTTP.
+    #[serde(default)]
+    pub outbound: OutboundSettings,
 }
 
 /// Source for the secrets master key.
@@ -122,6 +143,18 @@ pub struct EmbeddingsSettings {
     /// Model to use for embeddings.
     #[serde(default = "default_embeddings_model")]
     pub model: String,
+
+    /// Search re-ranker: "none", "cross-encoder" or "llm".
+    #[serde(default = "default_reranker")]
+    pub reranker: String,
+
+    /// Base URL of the cross-encoder `/rerank` server.
+    #[serde(default)]
+    pub rerank_url: Option<String>,
+}
+
+fn default_reranker() -> String {
+    "none".to_string()
 }
 
 fn default_embeddings_provider() -> String {
@@ -138,6 +171,8 @@ impl Default for EmbeddingsSettings {
             enabled: false,
             provider: default_embeddings_provider(),
             model: default_embeddings_model(),
+            reranker: default_reranker(),
+            rerank_url: None,
         }
     }
 }
@@ -187,6 +222,44 @@ pub struct ChannelSettings {
     pub wasm_channels_dir: Option<PathBuf>,
 }
 
+/// Time zone and language preferences.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LocaleSettings {
+    /// IANA time zone (e.g. "Europe/Berlin"). Defaults to the host's zone.
+    #[serde(default)]
+    pub timezone: Option<String>,
+
+    /// BCP 47 language tag (e.g. "en-GB"); decides day/month order when
+    /// parsing numeric dates.
+    #[serde(default)]
+    pub language: Option<String>,
+
+    /// Per-channel time zone overrides, keyed by channel name.
+    #[serde(default)]
+    pub channel_timezones: std::collections::HashMap<String, String>,
+}
+
+/// Notification routing preferences.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct NotificationSettings {
+    /// Category-to-channel routing rules, editable in chat with the
+    /// `notification_routes` tool.
+    #[serde(default)]
+    pub routes: Vec<crate::channels::RouteRule>,
+}
+
+/// Workspace memory storage settings.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MemorySettings {
+    /// Per-workspace and per-space storage quotas.
+    #[serde(default)]
+    pub quota: crate::workspace::QuotaConfig,
+
+    /// Conversation history indexing for recall (opt-in).
+    #[serde(default)]
+    pub history: crate::workspace::HistoryIndexConfig,
+}
+
 /// Heartbeat configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HeartbeatSettings {
@@ -257,6 +330,38 @@ pub struct AgentSettings {
     /// longer than this are pruned from memory.
     #[serde(default = "default_session_idle_timeout")]
     pub session_idle_timeout_secs: u64,
+
+    /// Window in seconds during which an identical message from the same
+    /// user reuses the previous response (0 disables deduplication).
+    #[serde(default = "default_dedup_window")]
+    pub dedup_window_secs: u64,
+
+    /// Half-life in seconds of tool failure hints shown to the LLM
+    /// (0 disables failure hints).
+    #[serde(default = "default_tool_hint_half_life")]
+    pub tool_hint_half_life_secs: u64,
+
+    /// Record per-phase turn timings to ~/.ironclaw/logs/turns.jsonl.
+    #[serde(default)]
+    pub profile_turns: bool,
+
+    /// Tool outputs longer than this many characters are summarized before
+    /// the LLM sees them (0 disables summarization).
+    #[serde(default)]
+    pub tool_summary_threshold: usize,
+
+    /// Model used for tool output summaries (default: the main model).
+    #[serde(default)]
+    pub tool_summary_model: Option<String>,
+
+    /// Cheaper model for short chat turns (default: none, always the main model).
+    #[serde(default)]
+    pub cheap_model: Option<String>,
+
+    /// Times a turn resumes after an LLM failure partway through
+    /// (0 fails the turn on the first error).
+    #[serde(default = "default_turn_recovery_attempts")]
+    pub turn_recovery_attempts: u32,
 }
 
 fn default_agent_name() -> String {
@@ -283,10 +388,22 @@ fn default_session_idle_timeout() -> u64 {
     7 * 24 * 3600 // 7 days
 }
 
+fn default_dedup_window() -> u64 {
+    30
+}
+
+fn default_tool_hint_half_life() -> u64 {
+    3600 // 1 hour
+}
+
 fn default_max_repair_attempts() -> u32 {
     3
 }
 
+fn default_turn_recovery_attempts() -> u32 {
+    2
+}
+
 fn default_true() -> bool {
     true
 }
@@ -302,6 +419,13 @@ impl Default for AgentSettings {
             repair_check_interval_secs: default_repair_interval(),
             max_repair_attempts: default_max_repair_attempts(),
             session_idle_timeout_secs: default_session_idle_timeout(),
+            dedup_window_secs: default_dedup_window(),
+            tool_hint_half_life_secs: default_tool_hint_half_life(),
+            profile_turns: false,
+            tool_summary_threshold: 0,
+            tool_summary_model: None,
+            cheap_model: None,
+            turn_recovery_attempts: default_turn_recovery_attempts(),
         }
     }
 }
@@ -504,6 +628,66 @@ impl Default for BuilderSettings {
     }
 }
 
+/// Remote browser configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserSettings {
+    /// CDP endpoint of a remote browser: a Browserless `wss://` URL or a
+    /// Chrome remote debugging address (`http://host:9222`). The browser
+    /// tool is only registered when this is set.
+    #[serde(default)]
+    pub cdp_endpoint: Option<String>,
+
+    /// Blank tabs kept open for new sessions.
+    #[serde(default = "default_browser_pool_size")]
+    pub pool_size: usize,
+
+    /// Maximum concurrent browser sessions.
+    #[serde(default = "default_browser_max_sessions")]
+    pub max_sessions: usize,
+
+    /// Where screenshots are saved (default ~/.ironclaw/screenshots).
+    #[serde(default)]
+    pub screenshot_dir: Option<PathBuf>,
+}
+
+fn default_browser_pool_size() -> usize {
+    2
+}
+
+fn default_browser_max_sessions() -> usize {
+    5
+}
+
+impl Default for BrowserSettings {
+    fn default() -> Self {
+        Self {
+            cdp_endpoint: None,
+            pool_size: default_browser_pool_size(),
+            max_sessions: default_browser_max_sessions(),
+            screenshot_dir: None,
+        }
+    }
+}
+
+/// Outbound HTTP through a corporate proxy.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OutboundSettings {
+    /// Proxy for all outbound HTTP: `http://`, `https://`, `socks5://` or
+    /// `socks5h://`, optionally with `user:password@`.
+    #[serde(default)]
+    pub proxy: Option<String>,
+
+    /// Hosts reached directly: `example.com` (and its subdomains),
+    /// `.example.com`, IPs or CIDR ranges. Loopback is always direct.
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+
+    /// PEM bundle of extra root certificates, trusted alongside the
+    /// built-in roots.
+    #[serde(default)]
+    pub ca_bundle: Option<PathBuf>,
+}
+
 impl Settings {
     /// Get the default settings file path (~/.ironclaw/settings.json).
     pub fn default_path() -> PathBuf {
@@ -727,7 +911,7 @@ impl Settings {
 }
 
 /// Recursively collect settings paths with their JSON values (for DB storage).
-fn collect_settings_json(
+pub(crate) fn collect_settings_json(
     value: &serde_json::Value,
     prefix: String,
     results: &mut std::collections::HashMap<String, serde_json::Value>,
@@ -920,4 +1104,82 @@ mod tests {
             .unwrap();
         assert_eq!(settings.channels.telegram_owner_id, Some(987654321));
     }
+
+    #[test]
+    fn test_channel_timezone_via_set_and_db_map() {
+        let mut settings = Settings::default();
+        settings.set("locale.timezone", "Europe/Berlin").unwrap();
+        settings
+            .set("locale.channel_timezones.telegram", "Asia/Tokyo")
+            .unwrap();
+        assert_eq!(settings.locale.timezone.as_deref(), Some("Europe/Berlin"));
+
+        let map = settings.to_db_map();
+        assert_eq!(
+            map.get("locale.channel_timezones.telegram"),
+            Some(&serde_json::json!("Asia/Tokyo"))
+        );
+        let restored = Settings::from_db_map(&map);
+        assert_eq!(
+            restored
+                .locale
+                .channel_timezones
+                .get("telegram")
+                .map(String::as_str),
+            Some("Asia/Tokyo")
+        );
+    }
+
+    #[test]
+    fn test_notification_routes_db_map_roundtrip() {
+        let mut settings = Settings::default();
+        settings
+            .set(
+                "notifications.routes",
+                r#"[{"category": "security_alert", "channel": "telegram"}]"#,
+            )
+            .unwrap();
+        assert_eq!(settings.notifications.routes.len(), 1);
+
+        let restored = Settings::from_db_map(&settings.to_db_map());
+        assert_eq!(restored.notifications.routes, settings.notifications.routes);
+    }
+
+    #[test]
+    fn test_memory_quota_db_map_roundtrip() {
+        let mut settings = Settings::default();
+        settings
+            .set("memory.quota.total.max_documents", "500")
+            .unwrap();
+        settings.set("memory.quota.action", "consolidate").unwrap();
+        settings
+            .set(
+                "memory.quota.spaces",
+                r#"[{"space": "research", "max_chunks": 2000}]"#,
+            )
+            .unwrap();
+        assert_eq!(settings.memory.quota.total.max_documents, Some(500));
+
+        let restored = Settings::from_db_map(&settings.to_db_map());
+        assert_eq!(restored.memory.quota, settings.memory.quota);
+        assert_eq!(
+            restored.memory.quota.action,
+            crate::workspace::QuotaAction::Consolidate
+        );
+    }
+
+    #[test]
+    fn test_memory_history_db_map_roundtrip() {
+        let mut settings = Settings::default();
+        assert!(!settings.memory.history.enabled);
+        settings.set("memory.history.enabled", "true").unwrap();
+        settings
+            .set("memory.history.channel_retention.telegram", "30")
+            .unwrap();
+
+        let restored = Settings::from_db_map(&settings.to_db_map());
+        assert!(restored.memory.history.enabled);
+        assert_eq!(restored.memory.history.retention_for("telegram"), 30);
+        assert_eq!(restored.memory.history.retention_for("slack"), 90);
+    }
 }
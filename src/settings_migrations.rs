@@ -0,0 +1,406 @@
+//! Versioned migrations for stored settings.
+//!
+//! Settings are stored as a flat map of dotted keys (`agent.name`) to JSON
+//! values, in the database `settings` table or, without a database, in
+//! `~/.ironclaw/settings.json`. When a key is renamed or a value format
+//! changes, old installs would otherwise keep values the current code
+//! ignores. Each [`Migration`] upgrades the map by one schema version; the
+//! version reached is stored under [`VERSION_KEY`].
+//!
+//! Migrations run at startup before settings are loaded, and on demand with
+//! `ironclaw config migrate` (`--check` reports without writing). Every
+//! change is logged. Migrations must be idempotent: a map whose version key
+//! was lost (for example when settings are rewritten wholesale) is migrated
+//! again from version 0.
+//!
+//! To change the schema, append a migration with the next version number to
+//! [`MIGRATIONS`]; never edit or reorder released ones.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::bootstrap::MigrationError;
+
+/// Key holding the schema version the stored settings were migrated to.
+pub const VERSION_KEY: &str = "_schema_version";
+
+/// Flat settings map, as stored in the database.
+pub type SettingsMap = HashMap<String, Value>;
+
+/// One schema upgrade.
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    apply: fn(&mut SettingsMap, &mut Vec<Change>),
+}
+
+/// All migrations, in version order.
+pub static MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "rename setup_completed to onboard_completed",
+        apply: |map, changes| rename(map, "setup_completed", "onboard_completed", changes),
+    },
+    Migration {
+        version: 2,
+        description: "replace removed and alias sandbox policies with canonical names",
+        apply: |map, changes| {
+            rewrite(map, "sandbox.policy", changes, |value| {
+                let policy = value.as_str()?.to_lowercase();
+                let canonical = match policy.as_str() {
+                    // 'none' was removed as ambiguous; keep the safe default.
+                    "none" | "ro" | "read_only" => "readonly",
+                    "rw" | "workspacewrite" => "workspace_write",
+                    "full" | "fullaccess" => "full_access",
+                    _ => return None,
+                };
+                Some(Value::String(canonical.to_string()))
+            })
+        },
+    },
+];
+
+/// One change a migration made.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Renamed { from: String, to: String },
+    Rewritten { key: String, from: Value, to: Value },
+    Removed { key: String, value: Value },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Renamed { from, to } => write!(f, "renamed {} -> {}", from, to),
+            Change::Rewritten { key, from, to } => write!(f, "{}: {} -> {}", key, from, to),
+            Change::Removed { key, value } => write!(f, "removed {} (was {})", key, value),
+        }
+    }
+}
+
+/// A migration that ran and what it changed.
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub description: &'static str,
+    pub changes: Vec<Change>,
+}
+
+/// Outcome of migrating a settings map.
+#[derive(Debug, Clone)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub applied: Vec<AppliedMigration>,
+}
+
+impl MigrationReport {
+    /// Whether the settings were already at the latest version.
+    pub fn is_current(&self) -> bool {
+        self.applied.is_empty()
+    }
+
+    /// Number of individual changes across all migrations.
+    pub fn change_count(&self) -> usize {
+        self.applied.iter().map(|m| m.changes.len()).sum()
+    }
+}
+
+/// The schema version after all migrations.
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+/// The version `map` was migrated to (0 if never).
+pub fn stored_version(map: &SettingsMap) -> u32 {
+    map.get(VERSION_KEY)
+        .and_then(Value::as_u64)
+        .and_then(|v| u32::try_from(v).ok())
+        .unwrap_or(0)
+}
+
+/// Run every migration newer than the map's version, logging each change,
+/// and record the new version. Settings from a newer release are left alone.
+pub fn migrate(map: &mut SettingsMap) -> MigrationReport {
+    let from_version = stored_version(map);
+    let mut report = MigrationReport {
+        from_version,
+        to_version: from_version,
+        applied: Vec::new(),
+    };
+    if from_version > latest_version() {
+        tracing::warn!(
+            "Settings schema version {} is newer than this build supports ({}); not migrating",
+            from_version,
+            latest_version()
+        );
+        return report;
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > from_version) {
+        let mut changes = Vec::new();
+        (migration.apply)(map, &mut changes);
+        for change in &changes {
+            tracing::info!(
+                "Settings migration {} ({}): {}",
+                migration.version,
+                migration.description,
+                change
+            );
+        }
+        report.to_version = migration.version;
+        report.applied.push(AppliedMigration {
+            version: migration.version,
+            description: migration.description,
+            changes,
+        });
+    }
+    if !report.is_current() {
+        map.insert(VERSION_KEY.to_string(), Value::from(report.to_version));
+    }
+    report
+}
+
+/// Migrate a user's settings in the database. With `dry_run` nothing is
+/// written. A user without settings (fresh install) is left alone.
+pub async fn migrate_db(
+    store: &dyn crate::db::Database,
+    user_id: &str,
+    dry_run: bool,
+) -> Result<MigrationReport, MigrationError> {
+    let original = store
+        .get_all_settings(user_id)
+        .await
+        .map_err(|e| MigrationError::Database(format!("Failed to load settings: {}", e)))?;
+    if original.is_empty() {
+        return Ok(MigrationReport {
+            from_version: latest_version(),
+            to_version: latest_version(),
+            applied: Vec::new(),
+        });
+    }
+
+    let mut migrated = original.clone();
+    let report = migrate(&mut migrated);
+    if dry_run || report.is_current() {
+        return Ok(report);
+    }
+
+    let updated: SettingsMap = migrated
+        .iter()
+        .filter(|(key, value)| original.get(*key) != Some(*value))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    store
+        .set_all_settings(user_id, &updated)
+        .await
+        .map_err(|e| MigrationError::Database(format!("Failed to write settings: {}", e)))?;
+    for key in original.keys().filter(|key| !migrated.contains_key(*key)) {
+        store
+            .delete_setting(user_id, key)
+            .await
+            .map_err(|e| MigrationError::Database(format!("Failed to delete {}: {}", key, e)))?;
+    }
+    Ok(report)
+}
+
+/// Migrate a `settings.json` file. With `dry_run` nothing is written. A
+/// missing file is left alone.
+pub fn migrate_file(path: &Path, dry_run: bool) -> Result<MigrationReport, MigrationError> {
+    let mut map = SettingsMap::new();
+    if path.exists() {
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| MigrationError::Io(format!("Failed to read {}: {}", path.display(), e)))?;
+        let json: Value = serde_json::from_str(&data).map_err(|e| {
+            MigrationError::Io(format!("Failed to parse {}: {}", path.display(), e))
+        })?;
+        crate::settings::collect_settings_json(&json, String::new(), &mut map);
+    }
+    if map.is_empty() {
+        return Ok(MigrationReport {
+            from_version: latest_version(),
+            to_version: latest_version(),
+            applied: Vec::new(),
+        });
+    }
+
+    let report = migrate(&mut map);
+    if dry_run || report.is_current() {
+        return Ok(report);
+    }
+    let json = serde_json::to_string_pretty(&unflatten(&map))
+        .map_err(|e| MigrationError::Io(e.to_string()))?;
+    std::fs::write(path, json)
+        .map_err(|e| MigrationError::Io(format!("Failed to write {}: {}", path.display(), e)))?;
+    Ok(report)
+}
+
+/// Rebuild nested JSON from dotted keys.
+fn unflatten(map: &SettingsMap) -> Value {
+    let mut root = serde_json::Map::new();
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+    for key in keys {
+        let mut parts: Vec<&str> = key.split('.').collect();
+        let Some(last) = parts.pop() else {
+            continue;
+        };
+        let mut node = &mut root;
+        for part in parts {
+            let entry = node
+                .entry(part.to_string())
+                .or_insert_with(|| Value::Object(serde_json::Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(serde_json::Map::new());
+            }
+            let Value::Object(next) = entry else {
+                unreachable!("just made an object");
+            };
+            node = next;
+        }
+        node.insert(last.to_string(), map[key].clone());
+    }
+    Value::Object(root)
+}
+
+/// Move `from` to `to`. If both exist, the value already under `to` wins.
+fn rename(map: &mut SettingsMap, from: &str, to: &str, changes: &mut Vec<Change>) {
+    let Some(value) = map.remove(from) else {
+        return;
+    };
+    if map.contains_key(to) {
+        changes.push(Change::Removed {
+            key: from.to_string(),
+            value,
+        });
+    } else {
+        map.insert(to.to_string(), value);
+        changes.push(Change::Renamed {
+            from: from.to_string(),
+            to: to.to_string(),
+        });
+    }
+}
+
+/// Replace the value under `key` when `f` returns a new one.
+fn rewrite(
+    map: &mut SettingsMap,
+    key: &str,
+    changes: &mut Vec<Change>,
+    f: impl Fn(&Value) -> Option<Value>,
+) {
+    let Some(value) = map.get_mut(key) else {
+        return;
+    };
+    if let Some(new) = f(value).filter(|new| new != value) {
+        let old = std::mem::replace(value, new.clone());
+        changes.push(Change::Rewritten {
+            key: key.to_string(),
+            from: old,
+            to: new,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn map(pairs: &[(&str, Value)]) -> SettingsMap {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_migrations_are_ordered() {
+        let versions: Vec<u32> = MIGRATIONS.iter().map(|m| m.version).collect();
+        let expected: Vec<u32> = (1..=MIGRATIONS.len() as u32).collect();
+        assert_eq!(versions, expected);
+    }
+
+    #[test]
+    fn test_migrate_upgrades_and_is_idempotent() {
+        let mut settings = map(&[
+            ("setup_completed", json!(true)),
+            ("sandbox.policy", json!("none")),
+            ("agent.name", json!("bot")),
+        ]);
+        let report = migrate(&mut settings);
+        assert_eq!(
+            (report.from_version, report.to_version),
+            (0, latest_version())
+        );
+        assert_eq!(report.change_count(), 2);
+        assert_eq!(settings["onboard_completed"], json!(true));
+        assert!(!settings.contains_key("setup_completed"));
+        assert_eq!(settings["sandbox.policy"], json!("readonly"));
+        assert_eq!(settings[VERSION_KEY], json!(latest_version()));
+
+        assert!(migrate(&mut settings).is_current());
+
+        // Re-running from scratch changes nothing further.
+        settings.remove(VERSION_KEY);
+        assert_eq!(migrate(&mut settings).change_count(), 0);
+    }
+
+    #[test]
+    fn test_rename_keeps_existing_target() {
+        let mut settings = map(&[
+            ("setup_completed", json!(false)),
+            ("onboard_completed", json!(true)),
+        ]);
+        let report = migrate(&mut settings);
+        assert!(matches!(
+            report.applied[0].changes[0],
+            Change::Removed { .. }
+        ));
+        assert_eq!(settings["onboard_completed"], json!(true));
+    }
+
+    #[test]
+    fn test_newer_schema_is_left_alone() {
+        let mut settings = map(&[
+            (VERSION_KEY, json!(latest_version() + 1)),
+            ("setup_completed", json!(true)),
+        ]);
+        assert!(migrate(&mut settings).is_current());
+        assert!(settings.contains_key("setup_completed"));
+    }
+
+    #[test]
+    fn test_migrate_file_respects_dry_run() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        let original =
+            json!({"setup_completed": true, "sandbox": {"policy": "RW", "timeout_secs": 60}});
+        std::fs::write(&path, original.to_string()).unwrap();
+
+        let report = migrate_file(&path, true).unwrap();
+        assert_eq!(report.change_count(), 2);
+        let on_disk: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(on_disk, original);
+
+        migrate_file(&path, false).unwrap();
+        let on_disk: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(on_disk["onboard_completed"], json!(true));
+        assert_eq!(on_disk["sandbox"]["policy"], json!("workspace_write"));
+        assert_eq!(on_disk["sandbox"]["timeout_secs"], json!(60));
+        assert!(migrate_file(&path, true).unwrap().is_current());
+
+        assert!(
+            migrate_file(&dir.path().join("missing.json"), false)
+                .unwrap()
+                .is_current()
+        );
+    }
+}
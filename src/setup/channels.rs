@@ -8,7 +8,6 @@
 
 use std::sync::Arc;
 
-use reqwest::Client;
 use secrecy::{ExposeSecret, SecretString};
 use serde::Deserialize;
 
@@ -248,7 +247,7 @@ async fn bind_telegram_owner(token: &SecretString) -> Result<Option<i64>, String
     print_info("Send any message (e.g. /start) to your bot in Telegram.");
     print_info("Waiting for your message (up to 120 seconds)...");
 
-    let client = Client::builder()
+    let client = crate::outbound::client_builder()
         .timeout(std::time::Duration::from_secs(35))
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
@@ -438,7 +437,7 @@ async fn setup_telegram_webhook_secret(secrets: &SecretsContext) -> Result<Optio
 ///
 /// Returns the bot's username if valid.
 pub async fn validate_telegram_token(token: &SecretString) -> Result<Option<String>, String> {
-    let client = Client::builder()
+    let client = crate::outbound::client_builder()
         .timeout(std::time::Duration::from_secs(10))
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
@@ -8,6 +8,7 @@
 //! 5. Embeddings
 //! 6. Channel configuration
 //! 7. Heartbeat (background tasks)
+//! 8. Time zone and language
 
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
@@ -122,7 +123,7 @@ impl SetupWizard {
             print_step(1, 1, "Channel Configuration");
             self.step_channels().await?;
         } else {
-            let total_steps = 7;
+            let total_steps = 8;
 
             // Step 1: Database
             print_step(1, total_steps, "Database Connection");
@@ -155,6 +156,10 @@ impl SetupWizard {
             // Step 7: Heartbeat
             print_step(7, total_steps, "Background Tasks");
             self.step_heartbeat()?;
+
+            // Step 8: Locale
+            print_step(8, total_steps, "Time Zone");
+            self.step_locale()?;
         }
 
         // Save settings and print summary
@@ -873,6 +878,7 @@ impl SetupWizard {
             gemini: None,
             bedrock: None,
             openrouter: None,
+            redaction: crate::config::RedactionConfig::default(),
         };
 
         match create_llm_provider(&config, Arc::clone(session)) {
@@ -1258,6 +1264,45 @@ impl SetupWizard {
         Ok(())
     }
 
+    /// Step 8: Time zone and language.
+    fn step_locale(&mut self) -> Result<(), SetupError> {
+        print_info("Your time zone is used for reminders, scheduled routines, and dates");
+        print_info("like \"tomorrow at 6pm\".");
+        println!();
+
+        let detected = self
+            .settings
+            .locale
+            .timezone
+            .clone()
+            .or_else(crate::locale::system_zone_name)
+            .unwrap_or_else(|| "UTC".to_string());
+        loop {
+            let answer = optional_input("Time zone", Some(&format!("default: {}", detected)))
+                .map_err(SetupError::Io)?;
+            let name = answer.unwrap_or_else(|| detected.clone());
+            match crate::locale::Tz::parse(&name) {
+                Ok(_) => {
+                    self.settings.locale.timezone = Some(name);
+                    break;
+                }
+                Err(e) => print_error(&format!("{} (use a name like Europe/Berlin)", e)),
+            }
+        }
+
+        let language = optional_input("Language", Some("default: en-US, e.g. en-GB, de-DE"))
+            .map_err(SetupError::Io)?;
+        if language.is_some() {
+            self.settings.locale.language = language;
+        }
+
+        print_success(&format!(
+            "Time zone set to {}",
+            self.settings.locale.timezone.as_deref().unwrap_or("UTC")
+        ));
+        Ok(())
+    }
+
     /// Save settings and print summary.
     fn save_and_summarize(&mut self) -> Result<(), SetupError> {
         self.settings.onboard_completed = true;
@@ -1366,6 +1411,10 @@ impl SetupWizard {
             );
         }
 
+        if let Some(ref tz) = self.settings.locale.timezone {
+            println!("  Time zone: {}", tz);
+        }
+
         println!();
         println!("To start the agent, run:");
         println!("  ironclaw");
@@ -54,6 +54,9 @@ pub struct SkillConfig {
     pub max_concurrent: Option<u32>,
     /// Timeout in seconds for skill operations.
     pub timeout_secs: Option<u64>,
+    /// Answers drawing on this skill's tools must cite a source for every claim.
+    #[serde(default)]
+    pub strict_citations: bool,
     /// Custom metadata.
     pub metadata: HashMap<String, serde_json::Value>,
 }
@@ -133,6 +136,28 @@ impl SkillRegistry {
         }
     }
 
+    /// Require (or stop requiring) cited answers for a skill.
+    pub async fn set_strict_citations(&self, name: &str, strict: bool) -> bool {
+        let mut skills = self.skills.write().await;
+        if let Some(skill) = skills.get_mut(name) {
+            skill.config.strict_citations = strict;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Tools of active skills that require cited answers.
+    pub async fn strict_citation_tools(&self) -> Vec<String> {
+        self.skills
+            .read()
+            .await
+            .values()
+            .filter(|s| s.enabled && s.config.strict_citations)
+            .flat_map(|s| s.tools.iter().map(|t| t.name.clone()))
+            .collect()
+    }
+
     /// Get all tool names from active skills.
     pub async fn active_tools(&self) -> Vec<String> {
         self.skills
@@ -144,6 +169,22 @@ impl SkillRegistry {
             .collect()
     }
 
+    /// The active skill each tool belongs to. A tool shared by several
+    /// skills maps to the first of them by name.
+    pub async fn tool_skills(&self) -> HashMap<String, String> {
+        let skills = self.skills.read().await;
+        let mut active: Vec<&Skill> = skills.values().filter(|s| s.enabled).collect();
+        active.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut map = HashMap::new();
+        for skill in active {
+            for tool in &skill.tools {
+                map.entry(tool.name.clone())
+                    .or_insert_with(|| skill.name.clone());
+            }
+        }
+        map
+    }
+
     /// Get combined system prompt additions from active skills.
     pub async fn system_prompt_additions(&self) -> String {
         let skills = self.skills.read().await;
@@ -338,6 +379,22 @@ mod tests {
         assert!(skills.len() >= 3);
     }
 
+    #[tokio::test]
+    async fn test_strict_citation_tools() {
+        let registry = SkillRegistry::new();
+        registry.register_defaults().await;
+        assert!(registry.strict_citation_tools().await.is_empty());
+
+        assert!(registry.set_strict_citations("research", true).await);
+        let tools = registry.strict_citation_tools().await;
+        assert!(tools.contains(&"http".to_string()));
+        assert!(!tools.contains(&"shell".to_string()));
+
+        registry.set_enabled("research", false).await;
+        assert!(registry.strict_citation_tools().await.is_empty());
+        assert!(!registry.set_strict_citations("missing", true).await);
+    }
+
     #[tokio::test]
     async fn test_system_prompt_additions() {
         let registry = SkillRegistry::new();
@@ -346,4 +403,16 @@ mod tests {
         let prompt = registry.system_prompt_additions().await;
         assert!(prompt.contains("Test prompt"));
     }
+
+    #[tokio::test]
+    async fn test_tool_skills() {
+        let registry = SkillRegistry::new();
+        registry.register_defaults().await;
+        let map = registry.tool_skills().await;
+        assert_eq!(map.get("shell").map(String::as_str), Some("coding"));
+        assert_eq!(map.get("http").map(String::as_str), Some("research"));
+
+        registry.set_enabled("research", false).await;
+        assert!(!registry.tool_skills().await.contains_key("http"));
+    }
 }
@@ -0,0 +1,559 @@
+//! Structured "ask the user" tool.
+//!
+//! Lets the agent gather parameters through a typed form (choices, numbers,
+//! dates, yes/no, free text) instead of free-text back-and-forth. The tool
+//! itself only validates the form spec and returns `awaiting_input`; the
+//! agent loop parks the turn, renders the form for the channel (numbered
+//! prompt in the CLI, reply buttons on Telegram/Slack, a form card in the
+//! web UI), validates the reply against the form, and feeds the answers back
+//! to the LLM as the tool result.
+
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::context::JobContext;
+use crate::tools::tool::{Tool, ToolError, ToolOutput};
+
+/// Default time the user has to answer before the request expires.
+pub const DEFAULT_INPUT_TIMEOUT_SECS: u64 = 600;
+
+/// Bounds for the `timeout_secs` parameter.
+const MIN_TIMEOUT_SECS: u64 = 30;
+const MAX_TIMEOUT_SECS: u64 = 86_400;
+
+/// Maximum number of fields in a single form.
+const MAX_FIELDS: usize = 20;
+
+/// Maximum number of options for a choice field.
+const MAX_OPTIONS: usize = 25;
+
+/// Kind of value a form field accepts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    /// Free text.
+    #[default]
+    Text,
+    /// One of a fixed list of options.
+    Choice,
+    /// A number, optionally bounded by `min`/`max`.
+    Number,
+    /// A calendar date (YYYY-MM-DD).
+    Date,
+    /// Yes or no.
+    Boolean,
+}
+
+impl FieldKind {
+    fn hint(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Choice => "pick one",
+            Self::Number => "number",
+            Self::Date => "YYYY-MM-DD",
+            Self::Boolean => "yes/no",
+        }
+    }
+}
+
+/// A single field of an input form.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormField {
+    /// Key used in the answers object.
+    pub name: String,
+    /// Human-readable label (defaults to the name).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Value type.
+    #[serde(rename = "type", default)]
+    pub kind: FieldKind,
+    /// Allowed values for choice fields.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+    /// Lower bound for number fields.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    /// Upper bound for number fields.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    /// Whether an answer is mandatory.
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+impl FormField {
+    /// Label shown to the user.
+    pub fn display_label(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.name)
+    }
+
+    fn constraint_hint(&self) -> String {
+        match (self.kind, self.min, self.max) {
+            (FieldKind::Number, Some(min), Some(max)) => format!("number {}-{}", min, max),
+            (FieldKind::Number, Some(min), None) => format!("number >= {}", min),
+            (FieldKind::Number, None, Some(max)) => format!("number <= {}", max),
+            (FieldKind::Choice, _, _) => self.options.join(" | "),
+            _ => self.kind.hint().to_string(),
+        }
+    }
+
+    /// Validate a raw answer and convert it to a typed JSON value.
+    fn validate(&self, raw: &str) -> Result<serde_json::Value, String> {
+        let raw = raw.trim();
+        let label = self.display_label();
+        match self.kind {
+            FieldKind::Text => Ok(serde_json::Value::String(raw.to_string())),
+            FieldKind::Choice => {
+                if let Ok(index) = raw.parse::<usize>()
+                    && (1..=self.options.len()).contains(&index)
+                {
+                    return Ok(serde_json::Value::String(self.options[index - 1].clone()));
+                }
+                self.options
+                    .iter()
+                    .find(|o| o.eq_ignore_ascii_case(raw))
+                    .map(|o| serde_json::Value::String(o.clone()))
+                    .ok_or_else(|| {
+                        format!(
+                            "'{}' is not a valid choice for {} (options: {})",
+                            raw,
+                            label,
+                            self.options.join(", ")
+                        )
+                    })
+            }
+            FieldKind::Number => {
+                let value: f64 = raw
+                    .parse()
+                    .map_err(|_| format!("{} must be a number, got '{}'", label, raw))?;
+                if !value.is_finite() {
+                    return Err(format!("{} must be a finite number", label));
+                }
+                if let Some(min) = self.min
+                    && value < min
+                {
+                    return Err(format!("{} must be at least {}", label, min));
+                }
+                if let Some(max) = self.max
+                    && value > max
+                {
+                    return Err(format!("{} must be at most {}", label, max));
+                }
+                Ok(serde_json::json!(value))
+            }
+            FieldKind::Date => NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+                .map(|d| serde_json::Value::String(d.to_string()))
+                .map_err(|_| format!("{} must be a date like 2026-03-14, got '{}'", label, raw)),
+            FieldKind::Boolean => match raw.to_ascii_lowercase().as_str() {
+                "yes" | "y" | "true" | "1" => Ok(serde_json::Value::Bool(true)),
+                "no" | "n" | "false" | "0" => Ok(serde_json::Value::Bool(false)),
+                _ => Err(format!("{} must be yes or no, got '{}'", label, raw)),
+            },
+        }
+    }
+}
+
+/// A structured input request presented to the user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputForm {
+    /// The question or instructions shown above the fields.
+    pub question: String,
+    /// Fields to fill in. Never empty once validated.
+    pub fields: Vec<FormField>,
+    /// Seconds the user has to answer.
+    pub timeout_secs: u64,
+}
+
+impl InputForm {
+    /// Build and validate a form from `ask_user` tool parameters.
+    ///
+    /// A request without fields becomes a single free-text field.
+    pub fn from_params(params: &serde_json::Value) -> Result<Self, String> {
+        let question = params
+            .get("question")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .ok_or_else(|| "missing 'question' parameter".to_string())?
+            .to_string();
+
+        let mut fields: Vec<FormField> = match params.get("fields") {
+            None | Some(serde_json::Value::Null) => Vec::new(),
+            Some(v) => {
+                serde_json::from_value(v.clone()).map_err(|e| format!("invalid 'fields': {}", e))?
+            }
+        };
+        if fields.is_empty() {
+            fields.push(FormField {
+                name: "answer".to_string(),
+                label: None,
+                kind: FieldKind::Text,
+                options: Vec::new(),
+                min: None,
+                max: None,
+                required: true,
+            });
+        }
+        if fields.len() > MAX_FIELDS {
+            return Err(format!("at most {} fields are allowed", MAX_FIELDS));
+        }
+
+        let mut seen = HashSet::new();
+        for field in &fields {
+            if field.name.trim().is_empty() {
+                return Err("every field needs a non-empty 'name'".to_string());
+            }
+            if !seen.insert(field.name.to_ascii_lowercase()) {
+                return Err(format!("duplicate field name '{}'", field.name));
+            }
+            if field.kind == FieldKind::Choice
+                && (field.options.is_empty() || field.options.len() > MAX_OPTIONS)
+            {
+                return Err(format!(
+                    "choice field '{}' needs 1-{} options",
+                    field.name, MAX_OPTIONS
+                ));
+            }
+            if let (Some(min), Some(max)) = (field.min, field.max)
+                && min > max
+            {
+                return Err(format!("field '{}' has min greater than max", field.name));
+            }
+        }
+
+        let timeout_secs = params
+            .get("timeout_secs")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_INPUT_TIMEOUT_SECS)
+            .clamp(MIN_TIMEOUT_SECS, MAX_TIMEOUT_SECS);
+
+        Ok(Self {
+            question,
+            fields,
+            timeout_secs,
+        })
+    }
+
+    /// Render the form as a plain-text prompt for text-only channels.
+    pub fn render_text(&self) -> String {
+        let mut out = self.question.clone();
+        if let [field] = self.fields.as_slice() {
+            match field.kind {
+                FieldKind::Choice => {
+                    for (i, option) in field.options.iter().enumerate() {
+                        out.push_str(&format!("\n  {}. {}", i + 1, option));
+                    }
+                    out.push_str("\n\nReply with a number or the option text.");
+                }
+                FieldKind::Text => {}
+                _ => out.push_str(&format!(" ({})", field.constraint_hint())),
+            }
+            return out;
+        }
+
+        out.push_str("\n\nReply with one line per field, as `name: value`:");
+        for field in &self.fields {
+            let optional = if field.required { "" } else { ", optional" };
+            out.push_str(&format!(
+                "\n  - {}: {} ({}{})",
+                field.name,
+                field.display_label(),
+                field.constraint_hint(),
+                optional
+            ));
+        }
+        out
+    }
+
+    /// Quick-reply options for channels that support buttons.
+    ///
+    /// Only single-field choice and yes/no forms have a fixed answer set.
+    pub fn reply_options(&self) -> Vec<String> {
+        match self.fields.as_slice() {
+            [field] if field.kind == FieldKind::Choice => field.options.clone(),
+            [field] if field.kind == FieldKind::Boolean => vec!["Yes".into(), "No".into()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Parse and validate a user reply.
+    ///
+    /// Accepts a JSON object keyed by field name (web form submissions), the
+    /// raw reply for single-field forms, or `name: value` lines. Returns all
+    /// validation errors at once so the user can fix them in one go.
+    pub fn parse_answer(
+        &self,
+        reply: &str,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+        let reply = reply.trim();
+        let raw_values: Vec<Option<String>> = match serde_json::from_str::<serde_json::Value>(reply)
+        {
+            Ok(serde_json::Value::Object(obj)) => self
+                .fields
+                .iter()
+                .map(|f| obj.get(&f.name).and_then(json_to_raw))
+                .collect(),
+            _ if self.fields.len() == 1 => vec![Some(reply.to_string())],
+            _ => {
+                let lines = parse_lines(reply);
+                self.fields
+                    .iter()
+                    .map(|f| {
+                        lines
+                            .iter()
+                            .find(|(key, _)| {
+                                key.eq_ignore_ascii_case(&f.name)
+                                    || key.eq_ignore_ascii_case(f.display_label())
+                            })
+                            .map(|(_, value)| value.clone())
+                    })
+                    .collect()
+            }
+        };
+
+        let mut answers = serde_json::Map::new();
+        let mut errors = Vec::new();
+        for (field, raw) in self.fields.iter().zip(raw_values) {
+            match raw.filter(|r| !r.trim().is_empty()) {
+                Some(raw) => match field.validate(&raw) {
+                    Ok(value) => {
+                        answers.insert(field.name.clone(), value);
+                    }
+                    Err(e) => errors.push(e),
+                },
+                None if field.required => {
+                    errors.push(format!("{} is required", field.display_label()))
+                }
+                None => {
+                    answers.insert(field.name.clone(), serde_json::Value::Null);
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(answers)
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+/// Convert a JSON answer value to the raw string form used for validation.
+fn json_to_raw(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Split `name: value` (or `name = value`) lines.
+fn parse_lines(reply: &str) -> Vec<(String, String)> {
+    reply
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_start_matches(['-', '*']).trim();
+            let (key, value) = line.split_once(':').or_else(|| line.split_once('='))?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Tool that asks the user for structured input.
+pub struct AskUserTool;
+
+#[async_trait]
+impl Tool for AskUserTool {
+    fn name(&self) -> &str {
+        "ask_user"
+    }
+
+    fn description(&self) -> &str {
+        "Ask the user for structured input (choices, numbers, dates, yes/no, or \
+         several fields at once) and wait for a validated answer. Prefer this over \
+         asking in free text when you need specific parameters. The answers are \
+         returned as the tool result."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "question": {
+                    "type": "string",
+                    "description": "The question or instructions shown to the user"
+                },
+                "fields": {
+                    "type": "array",
+                    "description": "Fields to collect. Omit for a single free-text answer.",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": {
+                                "type": "string",
+                                "description": "Key used in the returned answers"
+                            },
+                            "label": {
+                                "type": "string",
+                                "description": "Label shown to the user"
+                            },
+                            "type": {
+                                "type": "string",
+                                "enum": ["text", "choice", "number", "date", "boolean"],
+                                "description": "Value type (default: text)"
+                            },
+                            "options": {
+                                "type": "array",
+                                "items": { "type": "string" },
+                                "description": "Allowed values for choice fields"
+                            },
+                            "min": { "type": "number", "description": "Minimum for number fields" },
+                            "max": { "type": "number", "description": "Maximum for number fields" },
+                            "required": {
+                                "type": "boolean",
+                                "description": "Whether an answer is mandatory (default: true)"
+                            }
+                        },
+                        "required": ["name"]
+                    }
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": "Seconds to wait for an answer (default: 600)"
+                }
+            },
+            "required": ["question"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        _ctx: &JobContext,
+    ) -> Result<ToolOutput, ToolError> {
+        let start = std::time::Instant::now();
+        let form = InputForm::from_params(&params).map_err(ToolError::InvalidParameters)?;
+        let result = serde_json::json!({
+            "awaiting_input": true,
+            "form": form,
+        });
+        Ok(ToolOutput::success(result, start.elapsed()))
+    }
+
+    fn requires_sanitization(&self) -> bool {
+        false // Echoes the agent's own form spec
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn trip_form() -> InputForm {
+        InputForm::from_params(&json!({
+            "question": "Trip details?",
+            "fields": [
+                {"name": "city", "type": "choice", "options": ["Paris", "Rome"]},
+                {"name": "nights", "type": "number", "min": 1, "max": 14},
+                {"name": "start", "type": "date"},
+                {"name": "pets", "label": "Bringing pets", "type": "boolean", "required": false}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_from_params_defaults_and_validation() {
+        let form = InputForm::from_params(&json!({"question": "Name?"})).unwrap();
+        assert_eq!(form.fields.len(), 1);
+        assert_eq!(form.fields[0].name, "answer");
+        assert_eq!(form.timeout_secs, DEFAULT_INPUT_TIMEOUT_SECS);
+
+        assert!(InputForm::from_params(&json!({})).is_err());
+        assert!(
+            InputForm::from_params(&json!({
+                "question": "?",
+                "fields": [{"name": "a", "type": "choice"}]
+            }))
+            .is_err()
+        );
+        assert!(
+            InputForm::from_params(&json!({
+                "question": "?",
+                "fields": [{"name": "a"}, {"name": "A"}]
+            }))
+            .is_err()
+        );
+        let clamped = InputForm::from_params(&json!({"question": "?", "timeout_secs": 1})).unwrap();
+        assert_eq!(clamped.timeout_secs, MIN_TIMEOUT_SECS);
+    }
+
+    #[test]
+    fn test_parse_answer_lines_and_json() {
+        let form = trip_form();
+        let answers = form
+            .parse_answer("city: 2\nnights: 3\nstart: 2026-05-01\nBringing pets: yes")
+            .unwrap();
+        assert_eq!(answers["city"], json!("Rome"));
+        assert_eq!(answers["nights"], json!(3.0));
+        assert_eq!(answers["start"], json!("2026-05-01"));
+        assert_eq!(answers["pets"], json!(true));
+
+        let answers = form
+            .parse_answer(r#"{"city": "paris", "nights": 2, "start": "2026-05-01"}"#)
+            .unwrap();
+        assert_eq!(answers["city"], json!("Paris"));
+        assert_eq!(answers["pets"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_parse_answer_reports_all_errors() {
+        let form = trip_form();
+        let err = form
+            .parse_answer("city: Berlin\nnights: 30\nstart: tomorrow")
+            .unwrap_err();
+        assert!(err.contains("not a valid choice"));
+        assert!(err.contains("at most 14"));
+        assert!(err.contains("must be a date"));
+    }
+
+    #[test]
+    fn test_single_field_prompt_and_options() {
+        let form = InputForm::from_params(&json!({
+            "question": "Which size?",
+            "fields": [{"name": "size", "type": "choice", "options": ["S", "M", "L"]}]
+        }))
+        .unwrap();
+        assert!(form.render_text().contains("2. M"));
+        assert_eq!(form.reply_options(), vec!["S", "M", "L"]);
+        assert_eq!(form.parse_answer("3").unwrap()["size"], json!("L"));
+        assert!(trip_form().reply_options().is_empty());
+        assert!(
+            trip_form()
+                .render_text()
+                .contains("- nights: nights (number 1-14)")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_returns_awaiting_input() {
+        let ctx = JobContext::default();
+        let output = AskUserTool
+            .execute(
+                json!({"question": "Proceed?", "fields": [{"name": "ok", "type": "boolean"}]}),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(output.result["awaiting_input"], json!(true));
+        assert_eq!(output.result["form"]["fields"][0]["type"], json!("boolean"));
+    }
+}
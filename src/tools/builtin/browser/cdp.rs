@@ -0,0 +1,578 @@
+//! Chrome DevTools Protocol client for a remote browser.
+//!
+//! Connects to a CDP endpoint so servers without a local Chrome can still
+//! drive a browser:
+//!
+//! - Browserless or any WebSocket endpoint: `wss://host?token=...`
+//! - Chrome started with `--remote-debugging-port`: `http://host:9222`,
+//!   whose WebSocket URL is read from `/json/version`
+//!
+//! Every tab is attached with a flattened CDP session, so one connection
+//! carries all of them. Released tabs are reset to `about:blank` and kept
+//! in a small pool, so a new browser session does not pay for opening one.
+//! A dropped connection is re-established on next use; tabs from the old
+//! connection are gone with it.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+use base64::Engine;
+use futures::{SinkExt, StreamExt};
+use serde_json::{Value, json};
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::tools::tool::ToolError;
+
+/// Longest wait for one CDP reply.
+const CALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest wait for a page to finish loading after navigation.
+const LOAD_TIMEOUT: Duration = Duration::from_secs(15);
+
+type Pending = Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+
+/// One WebSocket to the browser, with replies matched to requests by id.
+struct Connection {
+    outgoing: mpsc::UnboundedSender<Message>,
+    pending: Pending,
+    next_id: AtomicU64,
+    closed: Arc<AtomicBool>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl Connection {
+    async fn open(ws_url: &str) -> Result<Self, ToolError> {
+        let (socket, _) = tokio_tungstenite::connect_async(ws_url)
+            .await
+            .map_err(|e| {
+                ToolError::ExternalService(format!(
+                    "CDP connection to {} failed: {}",
+                    redact(ws_url),
+                    e
+                ))
+            })?;
+        let (mut sink, mut stream) = socket.split();
+        let (outgoing, mut outgoing_rx) = mpsc::unbounded_channel::<Message>();
+        let pending: Pending = Arc::default();
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let task = {
+            let pending = Arc::clone(&pending);
+            let closed = Arc::clone(&closed);
+            tokio::spawn(async move {
+                loop {
+                    tokio::select! {
+                        message = outgoing_rx.recv() => {
+                            let Some(message) = message else { break };
+                            if sink.send(message).await.is_err() {
+                                break;
+                            }
+                        }
+                        incoming = stream.next() => {
+                            let text = match incoming {
+                                Some(Ok(Message::Text(text))) => text,
+                                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                                Some(Ok(_)) => continue,
+                            };
+                            // Events carry no id; only replies are routed.
+                            let Ok(reply) = serde_json::from_str::<Value>(&text) else {
+                                continue;
+                            };
+                            let Some(id) = reply.get("id").and_then(Value::as_u64) else {
+                                continue;
+                            };
+                            let waiter = lock(&pending).remove(&id);
+                            if let Some(waiter) = waiter {
+                                let result = match reply.get("error") {
+                                    Some(error) => Err(error
+                                        .get("message")
+                                        .and_then(Value::as_str)
+                                        .unwrap_or("unknown CDP error")
+                                        .to_string()),
+                                    None => Ok(reply.get("result").cloned().unwrap_or(Value::Null)),
+                                };
+                                let _ = waiter.send(result);
+                            }
+                        }
+                    }
+                }
+                closed.store(true, Ordering::SeqCst);
+                // Wake anyone still waiting; their senders drop here.
+                lock(&pending).clear();
+            })
+        };
+
+        Ok(Self {
+            outgoing,
+            pending,
+            next_id: AtomicU64::new(1),
+            closed,
+            task,
+        })
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    async fn call(
+        &self,
+        method: &str,
+        params: Value,
+        session_id: Option<&str>,
+    ) -> Result<Value, ToolError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut request = json!({ "id": id, "method": method, "params": params });
+        if let Some(session_id) = session_id {
+            request["sessionId"] = json!(session_id);
+        }
+
+        let (tx, rx) = oneshot::channel();
+        lock(&self.pending).insert(id, tx);
+        if self
+            .outgoing
+            .send(Message::Text(request.to_string().into()))
+            .is_err()
+        {
+            lock(&self.pending).remove(&id);
+            return Err(ToolError::ExternalService(
+                "CDP connection closed".to_string(),
+            ));
+        }
+
+        match tokio::time::timeout(CALL_TIMEOUT, rx).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(message))) => Err(ToolError::ExecutionFailed(format!(
+                "{} failed: {}",
+                method, message
+            ))),
+            Ok(Err(_)) => Err(ToolError::ExternalService(
+                "CDP connection closed".to_string(),
+            )),
+            Err(_) => {
+                lock(&self.pending).remove(&id);
+                Err(ToolError::Timeout(CALL_TIMEOUT))
+            }
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn lock<T>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// A browser tab attached over CDP.
+#[derive(Debug, Clone)]
+pub struct CdpPage {
+    target_id: String,
+    session_id: String,
+    /// Which connection the tab belongs to.
+    generation: u64,
+}
+
+/// A remote browser reached over CDP, with a pool of blank tabs.
+pub struct CdpBrowser {
+    endpoint: String,
+    pool_size: usize,
+    connection: tokio::sync::Mutex<Option<Arc<Connection>>>,
+    generation: AtomicU64,
+    idle: std::sync::Mutex<Vec<CdpPage>>,
+}
+
+impl CdpBrowser {
+    /// A browser at `endpoint` (`ws://`, `wss://`, `http://` or `https://`),
+    /// keeping up to `pool_size` blank tabs ready. Nothing is connected until
+    /// first use.
+    pub fn new(endpoint: &str, pool_size: usize) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            pool_size,
+            connection: tokio::sync::Mutex::new(None),
+            generation: AtomicU64::new(0),
+            idle: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The configured endpoint, without its query string (Browserless puts
+    /// the API token there).
+    pub fn endpoint(&self) -> &str {
+        redact(&self.endpoint)
+    }
+
+    /// Blank tabs ready for new sessions.
+    pub fn idle_count(&self) -> usize {
+        lock(&self.idle).len()
+    }
+
+    /// The open connection, reconnecting if it dropped.
+    async fn connection(&self) -> Result<Arc<Connection>, ToolError> {
+        let mut connection = self.connection.lock().await;
+        if let Some(open) = connection.as_ref()
+            && !open.is_closed()
+        {
+            return Ok(Arc::clone(open));
+        }
+
+        let ws_url = resolve_ws_url(&self.endpoint).await?;
+        let open = Arc::new(Connection::open(&ws_url).await?);
+        if connection.is_some() {
+            tracing::info!("Reconnected to remote browser at {}", self.endpoint());
+        }
+        // Tabs of the old connection went away with it.
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        lock(&self.idle).clear();
+        *connection = Some(Arc::clone(&open));
+        Ok(open)
+    }
+
+    /// A tab for a new session: a pooled one if available.
+    pub async fn acquire(&self) -> Result<CdpPage, ToolError> {
+        let connection = self.connection().await?;
+        let generation = self.generation.load(Ordering::SeqCst);
+        let pooled = lock(&self.idle).pop();
+        match pooled {
+            Some(page) if page.generation == generation => Ok(page),
+            _ => self.open_page(&connection, generation).await,
+        }
+    }
+
+    /// Hand a tab back. It is reset and pooled if there is room, and closed
+    /// otherwise.
+    pub async fn release(&self, page: CdpPage) {
+        let current = page.generation == self.generation.load(Ordering::SeqCst);
+        if !current {
+            return;
+        }
+        let pooled = self.idle_count() < self.pool_size
+            && self
+                .call(&page, "Page.navigate", json!({ "url": "about:blank" }))
+                .await
+                .is_ok();
+        if pooled {
+            lock(&self.idle).push(page);
+            return;
+        }
+        if let Ok(connection) = self.connection().await {
+            let _ = connection
+                .call(
+                    "Target.closeTarget",
+                    json!({ "targetId": page.target_id }),
+                    None,
+                )
+                .await;
+        }
+    }
+
+    /// Open tabs until the pool is full.
+    pub async fn warm(&self) -> Result<(), ToolError> {
+        let connection = self.connection().await?;
+        let generation = self.generation.load(Ordering::SeqCst);
+        while self.idle_count() < self.pool_size {
+            let page = self.open_page(&connection, generation).await?;
+            lock(&self.idle).push(page);
+        }
+        Ok(())
+    }
+
+    async fn open_page(
+        &self,
+        connection: &Connection,
+        generation: u64,
+    ) -> Result<CdpPage, ToolError> {
+        let target = connection
+            .call("Target.createTarget", json!({ "url": "about:blank" }), None)
+            .await?;
+        let target_id = string_field(&target, "targetId")?;
+        let attached = connection
+            .call(
+                "Target.attachToTarget",
+                json!({ "targetId": target_id, "flatten": true }),
+                None,
+            )
+            .await?;
+        let session_id = string_field(&attached, "sessionId")?;
+        connection
+            .call("Page.enable", json!({}), Some(&session_id))
+            .await?;
+        Ok(CdpPage {
+            target_id,
+            session_id,
+            generation,
+        })
+    }
+
+    /// Send a command to one tab.
+    pub async fn call(
+        &self,
+        page: &CdpPage,
+        method: &str,
+        params: Value,
+    ) -> Result<Value, ToolError> {
+        let connection = self.connection().await?;
+        if page.generation != self.generation.load(Ordering::SeqCst) {
+            return Err(ToolError::ExternalService(
+                "remote browser reconnected; this session's tab is gone".to_string(),
+            ));
+        }
+        connection
+            .call(method, params, Some(&page.session_id))
+            .await
+    }
+
+    /// Evaluate `expression` in the tab and return its value.
+    pub async fn evaluate(&self, page: &CdpPage, expression: &str) -> Result<Value, ToolError> {
+        let result = self
+            .call(
+                page,
+                "Runtime.evaluate",
+                json!({
+                    "expression": expression,
+                    "returnByValue": true,
+                    "awaitPromise": true,
+                }),
+            )
+            .await?;
+        if let Some(details) = result.get("exceptionDetails") {
+            let message = details
+                .pointer("/exception/description")
+                .or_else(|| details.get("text"))
+                .and_then(Value::as_str)
+                .unwrap_or("script threw");
+            return Err(ToolError::ExecutionFailed(message.to_string()));
+        }
+        Ok(result
+            .pointer("/result/value")
+            .cloned()
+            .unwrap_or(Value::Null))
+    }
+
+    /// Wait until the tab's document has loaded, up to a limit.
+    pub async fn wait_for_load(&self, page: &CdpPage) -> Result<(), ToolError> {
+        let deadline = tokio::time::Instant::now() + LOAD_TIMEOUT;
+        loop {
+            let state = self.evaluate(page, "document.readyState").await?;
+            if state == "complete" {
+                return Ok(());
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(ToolError::Timeout(LOAD_TIMEOUT));
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// Capture the tab as PNG bytes.
+    pub async fn screenshot(&self, page: &CdpPage, full_page: bool) -> Result<Vec<u8>, ToolError> {
+        let result = self
+            .call(
+                page,
+                "Page.captureScreenshot",
+                json!({ "format": "png", "captureBeyondViewport": full_page }),
+            )
+            .await?;
+        let data = string_field(&result, "data")?;
+        base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|e| ToolError::ExternalService(format!("bad screenshot data: {}", e)))
+    }
+}
+
+fn string_field(value: &Value, key: &str) -> Result<String, ToolError> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .map(String::from)
+        .ok_or_else(|| ToolError::ExternalService(format!("CDP reply without '{}'", key)))
+}
+
+/// The browser-level WebSocket URL for `endpoint`. HTTP endpoints (Chrome's
+/// remote debugging port) are asked via `/json/version`.
+async fn resolve_ws_url(endpoint: &str) -> Result<String, ToolError> {
+    if endpoint.starts_with("ws://") || endpoint.starts_with("wss://") {
+        return Ok(endpoint.to_string());
+    }
+    if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+        return Err(ToolError::InvalidParameters(format!(
+            "CDP endpoint must be a ws://, wss://, http:// or https:// URL, got '{}'",
+            endpoint
+        )));
+    }
+
+    let version: Value = reqwest::Client::new()
+        .get(format!("{}/json/version", endpoint))
+        .timeout(CALL_TIMEOUT)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| {
+            ToolError::ExternalService(format!(
+                "CDP endpoint {}: {}",
+                redact(endpoint),
+                e.without_url()
+            ))
+        })?
+        .json()
+        .await
+        .map_err(|e| {
+            ToolError::ExternalService(format!(
+                "CDP endpoint {}: {}",
+                redact(endpoint),
+                e.without_url()
+            ))
+        })?;
+    string_field(&version, "webSocketDebuggerUrl")
+}
+
+/// `url` up to its query string.
+fn redact(url: &str) -> &str {
+    url.split_once('?').map_or(url, |(base, _)| base)
+}
+
+/// `s` as a JavaScript string literal.
+pub(crate) fn js_string(s: &str) -> String {
+    Value::String(s.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A CDP server that answers the calls the client makes, counting the
+    /// tabs it creates.
+    async fn fake_browser() -> (String, Arc<AtomicU64>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let created = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&created);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let counter = Arc::clone(&counter);
+                tokio::spawn(async move {
+                    let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    while let Some(Ok(Message::Text(text))) = socket.next().await {
+                        let request: Value = serde_json::from_str(&text).unwrap();
+                        let result = match request["method"].as_str().unwrap() {
+                            "Target.createTarget" => {
+                                let n = counter.fetch_add(1, Ordering::SeqCst);
+                                json!({ "targetId": format!("target-{}", n) })
+                            }
+                            "Target.attachToTarget" => {
+                                json!({ "sessionId": format!("session-{}", request["params"]["targetId"].as_str().unwrap()) })
+                            }
+                            "Runtime.evaluate" => {
+                                let expression = request["params"]["expression"].as_str().unwrap();
+                                if expression.contains("throw") {
+                                    let reply = json!({ "id": request["id"], "result": {
+                                        "result": {},
+                                        "exceptionDetails": { "text": "Uncaught", "exception": { "description": "Error: boom" } }
+                                    }});
+                                    socket
+                                        .send(Message::Text(reply.to_string().into()))
+                                        .await
+                                        .unwrap();
+                                    continue;
+                                }
+                                json!({ "result": { "value": request["sessionId"] } })
+                            }
+                            "Page.captureScreenshot" => json!({ "data": "iVBORw==" }),
+                            _ => json!({}),
+                        };
+                        // An event first, as a real browser interleaves them.
+                        let event = json!({ "method": "Page.loadEventFired", "params": {} });
+                        socket
+                            .send(Message::Text(event.to_string().into()))
+                            .await
+                            .unwrap();
+                        let reply = json!({ "id": request["id"], "result": result });
+                        socket
+                            .send(Message::Text(reply.to_string().into()))
+                            .await
+                            .unwrap();
+                    }
+                });
+            }
+        });
+        (format!("ws://{}", addr), created)
+    }
+
+    #[tokio::test]
+    async fn test_pages_are_attached_and_pooled() {
+        let (endpoint, created) = fake_browser().await;
+        let browser = CdpBrowser::new(&endpoint, 1);
+
+        let page = browser.acquire().await.unwrap();
+        let value = browser.evaluate(&page, "1").await.unwrap();
+        assert_eq!(value, json!("session-target-0"));
+
+        browser.release(page).await;
+        assert_eq!(browser.idle_count(), 1);
+        let again = browser.acquire().await.unwrap();
+        assert_eq!(created.load(Ordering::SeqCst), 1, "pooled tab reused");
+
+        // The pool is full once a tab is back; a second one gets closed.
+        let other = browser.acquire().await.unwrap();
+        browser.release(again).await;
+        browser.release(other).await;
+        assert_eq!(browser.idle_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_script_errors_and_screenshots() {
+        let (endpoint, _) = fake_browser().await;
+        let browser = CdpBrowser::new(&endpoint, 0);
+        let page = browser.acquire().await.unwrap();
+
+        let err = browser.evaluate(&page, "throw 1").await.unwrap_err();
+        assert!(err.to_string().contains("boom"));
+
+        let png = browser.screenshot(&page, false).await.unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unknown_endpoint_scheme() {
+        let err = resolve_ws_url("ftp://browser").await.unwrap_err();
+        assert!(err.to_string().contains("ws://"));
+        assert_eq!(js_string("a\"b"), "\"a\\\"b\"");
+        assert_eq!(
+            CdpBrowser::new("wss://chrome.example?token=secret", 1).endpoint(),
+            "wss://chrome.example"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_manager_saves_remote_screenshots() {
+        use crate::tools::builtin::browser::{BrowserAction, BrowserManager};
+
+        let (endpoint, _) = fake_browser().await;
+        let dir = tempfile::tempdir().unwrap();
+        let remote = Arc::new(CdpBrowser::new(&endpoint, 2));
+        let manager = BrowserManager::new()
+            .with_remote(Arc::clone(&remote))
+            .with_screenshot_dir(dir.path().to_path_buf());
+
+        let id = manager.create_session().await.unwrap();
+        let result = manager
+            .execute_action(id, &BrowserAction::Screenshot { full_page: None })
+            .await
+            .unwrap();
+
+        let path = std::path::PathBuf::from(result.data["path"].as_str().unwrap());
+        assert!(path.starts_with(dir.path()));
+        assert_eq!(std::fs::read(&path).unwrap(), result.screenshot.unwrap());
+
+        assert!(manager.close_session(id).await);
+        assert_eq!(remote.idle_count(), 1);
+    }
+}
@@ -3,10 +3,18 @@
 //! Provides web browser automation capabilities including navigation,
 //! element interaction, screenshot capture, and page content extraction.
 //! Uses headless browser control for automated web interactions.
+//!
+//! With a remote CDP endpoint configured (`BROWSER_CDP_ENDPOINT`, e.g.
+//! Browserless or Chrome in a container), actions drive a real browser
+//! through [`CdpBrowser`]; screenshots are copied to a local directory.
+//! Without one, sessions only track navigation state.
+
+mod cdp;
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -14,8 +22,14 @@ use serde_json::Value;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+pub use cdp::{CdpBrowser, CdpPage};
+
 use crate::context::JobContext;
 use crate::tools::tool::{Tool, ToolError, ToolOutput};
+use cdp::js_string;
+
+/// Default wait for `wait_for` when no timeout is given.
+const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Browser automation actions.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +80,8 @@ pub struct BrowserSession {
 pub struct BrowserActionResult {
     pub success: bool,
     pub data: Value,
+    /// PNG bytes of a screenshot; `data` holds where it was saved.
+    #[serde(skip)]
     pub screenshot: Option<Vec<u8>>,
 }
 
@@ -73,6 +89,10 @@ pub struct BrowserActionResult {
 pub struct BrowserManager {
     sessions: Arc<RwLock<HashMap<Uuid, BrowserSession>>>,
     max_sessions: usize,
+    remote: Option<Arc<CdpBrowser>>,
+    /// Remote tabs by session.
+    pages: RwLock<HashMap<Uuid, CdpPage>>,
+    screenshot_dir: PathBuf,
 }
 
 impl BrowserManager {
@@ -81,6 +101,9 @@ impl BrowserManager {
         Self {
             sessions: Arc::new(RwLock::new(HashMap::new())),
             max_sessions: 5,
+            remote: None,
+            pages: RwLock::new(HashMap::new()),
+            screenshot_dir: std::env::temp_dir().join("ironclaw-screenshots"),
         }
     }
 
@@ -90,6 +113,23 @@ impl BrowserManager {
         self
     }
 
+    /// Drive a remote browser over CDP.
+    pub fn with_remote(mut self, browser: Arc<CdpBrowser>) -> Self {
+        self.remote = Some(browser);
+        self
+    }
+
+    /// Where screenshots from the remote browser are saved.
+    pub fn with_screenshot_dir(mut self, dir: PathBuf) -> Self {
+        self.screenshot_dir = dir;
+        self
+    }
+
+    /// The remote browser, if one is configured.
+    pub fn remote(&self) -> Option<&Arc<CdpBrowser>> {
+        self.remote.as_ref()
+    }
+
     /// Create a new browser session, returning its ID.
     pub async fn create_session(&self) -> Result<Uuid, ToolError> {
         let sessions = self.sessions.read().await;
@@ -101,6 +141,11 @@ impl BrowserManager {
         }
         drop(sessions);
 
+        let page = match &self.remote {
+            Some(remote) => Some(remote.acquire().await?),
+            None => None,
+        };
+
         let session = BrowserSession {
             id: Uuid::new_v4(),
             current_url: None,
@@ -111,6 +156,9 @@ impl BrowserManager {
         };
         let id = session.id;
         self.sessions.write().await.insert(id, session);
+        if let Some(page) = page {
+            self.pages.write().await.insert(id, page);
+        }
         Ok(id)
     }
 
@@ -126,6 +174,14 @@ impl BrowserManager {
         })?;
         session.action_count += 1;
 
+        let page = self.pages.read().await.get(&session_id).cloned();
+        if let (Some(remote), Some(page)) = (&self.remote, page)
+            && !matches!(action, BrowserAction::Close)
+        {
+            drop(sessions);
+            return self.execute_remote(remote, session_id, &page, action).await;
+        }
+
         match action {
             BrowserAction::Navigate { url } => {
                 if !url.starts_with("http://") && !url.starts_with("https://") {
@@ -154,7 +210,7 @@ impl BrowserManager {
             BrowserAction::Close => {
                 let id = session.id;
                 drop(sessions);
-                self.sessions.write().await.remove(&id);
+                self.close_session(id).await;
                 Ok(BrowserActionResult {
                     success: true,
                     data: Value::String("Session closed".to_string()),
@@ -223,6 +279,172 @@ impl BrowserManager {
         }
     }
 
+    /// Run an action in the session's remote tab.
+    async fn execute_remote(
+        &self,
+        remote: &CdpBrowser,
+        session_id: Uuid,
+        page: &CdpPage,
+        action: &BrowserAction,
+    ) -> Result<BrowserActionResult, ToolError> {
+        let done = |data: Value| BrowserActionResult {
+            success: true,
+            data,
+            screenshot: None,
+        };
+        let on_element = |selector: &str, body: &str| {
+            format!(
+                "(() => {{ const el = document.querySelector({}); if (!el) return false; {} return true; }})()",
+                js_string(selector),
+                body
+            )
+        };
+        let missing = |selector: &str| {
+            ToolError::ExecutionFailed(format!("No element matches '{}'", selector))
+        };
+
+        let result = match action {
+            BrowserAction::Navigate { url } => {
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    return Err(ToolError::InvalidParameters(
+                        "URL must start with http:// or https://".to_string(),
+                    ));
+                }
+                let navigated = remote
+                    .call(page, "Page.navigate", serde_json::json!({ "url": url }))
+                    .await?;
+                if let Some(error) = navigated.get("errorText").and_then(Value::as_str) {
+                    return Err(ToolError::ExecutionFailed(format!(
+                        "Navigation to {} failed: {}",
+                        url, error
+                    )));
+                }
+                remote.wait_for_load(page).await?;
+                done(Value::String(format!("Navigated to {}", url)))
+            }
+            BrowserAction::Click { selector } => {
+                if remote
+                    .evaluate(page, &on_element(selector, "el.click();"))
+                    .await?
+                    != true
+                {
+                    return Err(missing(selector));
+                }
+                done(Value::String(format!("Clicked element '{}'", selector)))
+            }
+            BrowserAction::Type { selector, text } => {
+                if remote
+                    .evaluate(page, &on_element(selector, "el.focus();"))
+                    .await?
+                    != true
+                {
+                    return Err(missing(selector));
+                }
+                remote
+                    .call(
+                        page,
+                        "Input.insertText",
+                        serde_json::json!({ "text": text }),
+                    )
+                    .await?;
+                done(Value::String(format!(
+                    "Typed {} characters into '{}'",
+                    text.chars().count(),
+                    selector
+                )))
+            }
+            BrowserAction::GetContent { format } => {
+                let expression = match format.as_deref() {
+                    Some("html") => "document.documentElement.outerHTML",
+                    _ => "document.body ? document.body.innerText : ''",
+                };
+                done(remote.evaluate(page, expression).await?)
+            }
+            BrowserAction::Screenshot { full_page } => {
+                let png = remote.screenshot(page, full_page.unwrap_or(false)).await?;
+                let path = self.save_screenshot(session_id, &png).await?;
+                BrowserActionResult {
+                    success: true,
+                    data: serde_json::json!({
+                        "path": path.display().to_string(),
+                        "bytes": png.len(),
+                    }),
+                    screenshot: Some(png),
+                }
+            }
+            BrowserAction::Evaluate { script } => done(remote.evaluate(page, script).await?),
+            BrowserAction::WaitFor {
+                selector,
+                timeout_ms,
+            } => {
+                let timeout = timeout_ms
+                    .map(Duration::from_millis)
+                    .unwrap_or(DEFAULT_WAIT_TIMEOUT);
+                let deadline = tokio::time::Instant::now() + timeout;
+                let present = format!("document.querySelector({}) !== null", js_string(selector));
+                while remote.evaluate(page, &present).await? != true {
+                    if tokio::time::Instant::now() >= deadline {
+                        return Err(ToolError::Timeout(timeout));
+                    }
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+                done(Value::String(format!("Element '{}' present", selector)))
+            }
+            BrowserAction::GetTitle => done(remote.evaluate(page, "document.title").await?),
+            BrowserAction::GetUrl => done(remote.evaluate(page, "location.href").await?),
+            BrowserAction::Back | BrowserAction::Forward => {
+                let (script, label) = match action {
+                    BrowserAction::Back => ("history.back()", "back"),
+                    _ => ("history.forward()", "forward"),
+                };
+                remote.evaluate(page, script).await?;
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                remote.wait_for_load(page).await?;
+                done(Value::String(format!("Navigated {}", label)))
+            }
+            BrowserAction::Close => unreachable!("close is handled by execute_action"),
+        };
+
+        // Keep the session's view of the page current.
+        if matches!(
+            action,
+            BrowserAction::Navigate { .. }
+                | BrowserAction::Click { .. }
+                | BrowserAction::Back
+                | BrowserAction::Forward
+        ) {
+            let location = remote
+                .evaluate(page, "[location.href, document.title]")
+                .await
+                .unwrap_or(Value::Null);
+            if let Some(session) = self.sessions.write().await.get_mut(&session_id) {
+                session.current_url = location[0].as_str().map(String::from);
+                session.title = location[1].as_str().map(String::from);
+                if matches!(action, BrowserAction::Navigate { .. }) {
+                    session.page_count += 1;
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Write a screenshot from the remote browser to the screenshot
+    /// directory.
+    async fn save_screenshot(&self, session_id: Uuid, png: &[u8]) -> Result<PathBuf, ToolError> {
+        tokio::fs::create_dir_all(&self.screenshot_dir)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("Cannot save screenshot: {}", e)))?;
+        let path = self.screenshot_dir.join(format!(
+            "{}-{}.png",
+            session_id,
+            chrono::Utc::now().timestamp_millis()
+        ));
+        tokio::fs::write(&path, png)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("Cannot save screenshot: {}", e)))?;
+        Ok(path)
+    }
+
     /// List all active browser sessions.
     pub async fn list_sessions(&self) -> Vec<BrowserSession> {
         self.sessions.read().await.values().cloned().collect()
@@ -230,12 +452,23 @@ impl BrowserManager {
 
     /// Close a specific browser session. Returns true if the session existed.
     pub async fn close_session(&self, id: Uuid) -> bool {
-        self.sessions.write().await.remove(&id).is_some()
+        let existed = self.sessions.write().await.remove(&id).is_some();
+        let page = self.pages.write().await.remove(&id);
+        if let (Some(remote), Some(page)) = (&self.remote, page) {
+            remote.release(page).await;
+        }
+        existed
     }
 
     /// Close all browser sessions.
     pub async fn close_all(&self) {
         self.sessions.write().await.clear();
+        let pages: Vec<CdpPage> = self.pages.write().await.drain().map(|(_, p)| p).collect();
+        if let Some(remote) = &self.remote {
+            for page in pages {
+                remote.release(page).await;
+            }
+        }
     }
 }
 
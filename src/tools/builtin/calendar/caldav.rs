@@ -0,0 +1,174 @@
+//! CalDAV (RFC 4791) calendar-query and event upload.
+//!
+//! There is no XML dependency, so multistatus responses are scanned for
+//! `calendar-data` elements directly; that is the only part we need.
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+
+/// Most bytes accepted in one REPORT response.
+const MAX_RESPONSE_BYTES: usize = 20 * 1024 * 1024;
+
+/// One CalDAV collection and its login.
+pub struct CalDav<'a> {
+    pub http: &'a reqwest::Client,
+    pub url: &'a str,
+    pub username: Option<&'a str>,
+    pub password: Option<&'a str>,
+}
+
+fn xml_unescape(text: &str) -> String {
+    let entity = Regex::new(r"&(#x[0-9a-fA-F]+|#[0-9]+|[a-z]+);").expect("valid regex");
+    entity
+        .replace_all(text, |caps: &regex::Captures| {
+            let name = &caps[1];
+            let decoded = match name {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => name
+                    .strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16).ok())
+                    .unwrap_or_else(|| name.strip_prefix('#').and_then(|d| d.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            decoded.map_or_else(|| caps[0].to_string(), String::from)
+        })
+        .into_owned()
+}
+
+/// iCalendar bodies in a multistatus response, whatever the namespace prefix.
+pub fn calendar_data(multistatus: &str) -> Vec<String> {
+    let element = Regex::new(
+        r"(?s)<(?:[A-Za-z0-9_-]+:)?calendar-data(?:\s[^>]*[^/>])?>(.*?)</(?:[A-Za-z0-9_-]+:)?calendar-data>",
+    )
+    .expect("valid regex");
+    element
+        .captures_iter(multistatus)
+        .map(|caps| {
+            let body = caps[1].trim();
+            match body
+                .strip_prefix("<![CDATA[")
+                .and_then(|b| b.strip_suffix("]]>"))
+            {
+                Some(cdata) => cdata.to_string(),
+                None => xml_unescape(body),
+            }
+        })
+        .filter(|body| !body.is_empty())
+        .collect()
+}
+
+impl CalDav<'_> {
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        let request = self.http.request(method, url);
+        match self.username {
+            Some(user) => request.basic_auth(user, self.password),
+            None => request,
+        }
+    }
+
+    /// Calendar objects with a VEVENT overlapping `[from, to)`.
+    pub async fn events(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<String>, String> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<C:calendar-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <D:prop><C:calendar-data/></D:prop>
+  <C:filter>
+    <C:comp-filter name="VCALENDAR">
+      <C:comp-filter name="VEVENT">
+        <C:time-range start="{}" end="{}"/>
+      </C:comp-filter>
+    </C:comp-filter>
+  </C:filter>
+</C:calendar-query>"#,
+            from.format("%Y%m%dT%H%M%SZ"),
+            to.format("%Y%m%dT%H%M%SZ")
+        );
+        let method = reqwest::Method::from_bytes(b"REPORT").expect("valid method");
+        let response = self
+            .request(method, self.url)
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("CalDAV request failed: {}", e))?;
+        let status = response.status();
+        if status.as_u16() != 207 && !status.is_success() {
+            return Err(format!("CalDAV query failed: HTTP {}", status));
+        }
+        if response
+            .content_length()
+            .is_some_and(|len| len as usize > MAX_RESPONSE_BYTES)
+        {
+            return Err("CalDAV response is too large".to_string());
+        }
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| format!("CalDAV read failed: {}", e))?;
+        if bytes.len() > MAX_RESPONSE_BYTES {
+            return Err("CalDAV response is too large".to_string());
+        }
+        Ok(calendar_data(&String::from_utf8_lossy(&bytes)))
+    }
+
+    /// Upload a new calendar object; returns its URL.
+    pub async fn create(&self, uid: &str, ics: String) -> Result<String, String> {
+        let url = format!(
+            "{}/{}.ics",
+            self.url.trim_end_matches('/'),
+            urlencoding::encode(uid)
+        );
+        let response = self
+            .request(reqwest::Method::PUT, &url)
+            .header("Content-Type", "text/calendar; charset=utf-8")
+            .header("If-None-Match", "*")
+            .body(ics)
+            .send()
+            .await
+            .map_err(|e| format!("CalDAV request failed: {}", e))?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(url)
+        } else {
+            Err(format!("CalDAV create failed: HTTP {}", status))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calendar_data_extraction() {
+        let xml = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">
+  <d:response><d:propstat><d:prop><cal:calendar-data /></d:prop></d:propstat></d:response>
+  <d:response><d:propstat><d:prop>
+    <cal:calendar-data>BEGIN:VCALENDAR&#13;
+SUMMARY:R&amp;D &lt;sync&gt;&#13;
+END:VCALENDAR&#13;
+</cal:calendar-data>
+  </d:prop></d:propstat></d:response>
+  <d:response><d:propstat><d:prop>
+    <calendar-data xmlns="urn:ietf:params:xml:ns:caldav"><![CDATA[BEGIN:VCALENDAR
+SUMMARY:a < b
+END:VCALENDAR]]></calendar-data>
+  </d:prop></d:propstat></d:response>
+  <d:response><d:propstat><d:prop><cal:calendar-data/></d:prop></d:propstat></d:response>
+</d:multistatus>"#;
+        let bodies = calendar_data(xml);
+        assert_eq!(bodies.len(), 2);
+        assert!(bodies[0].contains("SUMMARY:R&D <sync>\r\n"));
+        assert!(bodies[1].contains("SUMMARY:a < b"));
+    }
+}
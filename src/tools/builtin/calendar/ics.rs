@@ -0,0 +1,871 @@
+//! iCalendar (RFC 5545): parsing, recurrence expansion and event writing.
+//!
+//! Recurrence covers what calendar clients emit in practice: FREQ
+//! DAILY/WEEKLY/MONTHLY/YEARLY with INTERVAL, COUNT, UNTIL, BYDAY,
+//! BYMONTHDAY and BYMONTH, plus EXDATE and overridden instances
+//! (RECURRENCE-ID). Occurrences are stepped in the event's own wall-clock
+//! time, so a 9:00 meeting stays at 9:00 across DST changes.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc, Weekday,
+};
+
+use crate::locale::Tz;
+
+/// Property parameters (`TZID=Europe/Paris`), names uppercased.
+type Params = Vec<(String, String)>;
+
+/// Most recurrence periods stepped through per event.
+const MAX_PERIODS: u32 = 20_000;
+
+/// A date or date-time value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum When {
+    /// All-day date.
+    Date(NaiveDate),
+    /// Wall-clock time in the named zone; `None` is floating (the viewer's zone).
+    Local(NaiveDateTime, Option<String>),
+    Utc(DateTime<Utc>),
+}
+
+impl When {
+    fn parse(value: &str, params: &[(String, String)]) -> Option<Self> {
+        let value = value.trim();
+        let is_date = param(params, "VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE"))
+            || value.len() == 8;
+        if is_date {
+            return NaiveDate::parse_from_str(value, "%Y%m%d")
+                .ok()
+                .map(When::Date);
+        }
+        if let Some(utc) = value.strip_suffix('Z') {
+            return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+                .ok()
+                .map(|t| When::Utc(t.and_utc()));
+        }
+        let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+        Some(When::Local(local, param(params, "TZID").map(String::from)))
+    }
+
+    pub fn is_date(&self) -> bool {
+        matches!(self, When::Date(_))
+    }
+
+    /// Wall-clock time in the value's own zone.
+    fn naive(&self) -> NaiveDateTime {
+        match self {
+            When::Date(d) => d.and_time(NaiveTime::MIN),
+            When::Local(t, _) => *t,
+            When::Utc(t) => t.naive_utc(),
+        }
+    }
+
+    /// Same zone, different wall-clock time.
+    fn with_naive(&self, naive: NaiveDateTime) -> Self {
+        match self {
+            When::Date(_) => When::Date(naive.date()),
+            When::Local(_, tz) => When::Local(naive, tz.clone()),
+            When::Utc(_) => When::Utc(naive.and_utc()),
+        }
+    }
+
+    /// The instant this value denotes.
+    pub fn resolve(&self, zones: &mut Zones) -> DateTime<Utc> {
+        match self {
+            When::Date(d) => zones.floating.from_local(d.and_time(NaiveTime::MIN)),
+            When::Local(t, None) => zones.floating.from_local(*t),
+            When::Local(t, Some(tzid)) => zones.get(tzid).from_local(*t),
+            When::Utc(t) => *t,
+        }
+    }
+
+    /// Serialize as a property (`DTSTART;VALUE=DATE:20260101`).
+    fn render(&self, name: &str) -> String {
+        match self {
+            When::Date(d) => format!("{};VALUE=DATE:{}", name, d.format("%Y%m%d")),
+            When::Local(t, Some(tz)) => {
+                format!("{};TZID={}:{}", name, tz, t.format("%Y%m%dT%H%M%S"))
+            }
+            When::Local(t, None) => format!("{}:{}", name, t.format("%Y%m%dT%H%M%S")),
+            When::Utc(t) => format!("{}:{}", name, t.format("%Y%m%dT%H%M%SZ")),
+        }
+    }
+}
+
+/// Resolves TZIDs, caching zones. Unknown zones (e.g. Windows names) fall
+/// back to the floating zone.
+pub struct Zones {
+    floating: Tz,
+    cache: HashMap<String, Tz>,
+}
+
+impl Zones {
+    pub fn new(floating: Tz) -> Self {
+        Self {
+            floating,
+            cache: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, tzid: &str) -> &Tz {
+        if !self.cache.contains_key(tzid) {
+            // Some clients prefix the IANA name ("/mozilla.org/.../Europe/Berlin").
+            let tz = Tz::parse(tzid)
+                .or_else(|_| {
+                    let parts: Vec<&str> = tzid.rsplitn(3, '/').collect();
+                    match parts.as_slice() {
+                        [city, region, _] => Tz::parse(&format!("{}/{}", region, city)),
+                        _ => Err(String::new()),
+                    }
+                })
+                .unwrap_or_else(|_| self.floating.clone());
+            self.cache.insert(tzid.to_string(), tz);
+        }
+        &self.cache[tzid]
+    }
+}
+
+/// Recurrence frequency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freq {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A parsed RRULE.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RRule {
+    pub freq: Freq,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<When>,
+    /// Weekdays, optionally with an ordinal (`1MO`, `-1FR`).
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    pub by_month_day: Vec<i32>,
+    pub by_month: Vec<u32>,
+}
+
+fn weekday(code: &str) -> Option<Weekday> {
+    Some(match code {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+impl RRule {
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut rule = RRule {
+            freq: Freq::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+        };
+        let mut freq = None;
+        for part in value.split(';') {
+            let (key, val) = part.split_once('=')?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match val.to_ascii_uppercase().as_str() {
+                        "DAILY" => Freq::Daily,
+                        "WEEKLY" => Freq::Weekly,
+                        "MONTHLY" => Freq::Monthly,
+                        "YEARLY" => Freq::Yearly,
+                        // HOURLY and finer are not expanded.
+                        _ => return None,
+                    })
+                }
+                "INTERVAL" => rule.interval = val.parse().ok().filter(|&n| n > 0)?,
+                "COUNT" => rule.count = val.parse().ok(),
+                "UNTIL" => rule.until = When::parse(val, &[]),
+                "BYDAY" => {
+                    for day in val.split(',') {
+                        let day = day.trim().to_ascii_uppercase();
+                        let (ordinal, code) = day.split_at(day.len().saturating_sub(2));
+                        let ordinal = if ordinal.is_empty() {
+                            None
+                        } else {
+                            Some(ordinal.trim_start_matches('+').parse().ok()?)
+                        };
+                        rule.by_day.push((ordinal, weekday(code)?));
+                    }
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = val.split(',').filter_map(|d| d.parse().ok()).collect()
+                }
+                "BYMONTH" => {
+                    rule.by_month = val.split(',').filter_map(|m| m.parse().ok()).collect()
+                }
+                _ => {}
+            }
+        }
+        rule.freq = freq?;
+        Some(rule)
+    }
+
+    /// Candidate dates in period `n` (0 = the period containing `start`).
+    fn period_dates(&self, start: NaiveDate, n: u32) -> Vec<NaiveDate> {
+        let step = self.interval * n;
+        let mut dates = match self.freq {
+            Freq::Daily => {
+                let date = start + Duration::days(step as i64);
+                let weekday_ok = self.by_day.is_empty()
+                    || self.by_day.iter().any(|(_, wd)| *wd == date.weekday());
+                let monthday_ok = self.by_month_day.is_empty()
+                    || self
+                        .by_month_day
+                        .iter()
+                        .any(|&d| month_day(date.year(), date.month(), d) == Some(date));
+                if weekday_ok && monthday_ok {
+                    vec![date]
+                } else {
+                    Vec::new()
+                }
+            }
+            Freq::Weekly => {
+                let week_start = start
+                    - Duration::days(start.weekday().num_days_from_monday() as i64)
+                    + Duration::weeks(step as i64);
+                if self.by_day.is_empty() {
+                    vec![week_start + Duration::days(start.weekday().num_days_from_monday() as i64)]
+                } else {
+                    self.by_day
+                        .iter()
+                        .map(|(_, wd)| {
+                            week_start + Duration::days(wd.num_days_from_monday() as i64)
+                        })
+                        .collect()
+                }
+            }
+            Freq::Monthly => {
+                let months = start.year() * 12 + start.month0() as i32 + step as i32;
+                let (year, month) = (months.div_euclid(12), months.rem_euclid(12) as u32 + 1);
+                self.month_dates(start, year, month)
+            }
+            Freq::Yearly => {
+                let year = start.year() + step as i32;
+                let months = if self.by_month.is_empty() {
+                    vec![start.month()]
+                } else {
+                    self.by_month.clone()
+                };
+                months
+                    .into_iter()
+                    .flat_map(|m| self.month_dates(start, year, m))
+                    .collect()
+            }
+        };
+        if !self.by_month.is_empty() {
+            dates.retain(|d| self.by_month.contains(&d.month()));
+        }
+        dates.sort();
+        dates.dedup();
+        dates
+    }
+
+    fn month_dates(&self, start: NaiveDate, year: i32, month: u32) -> Vec<NaiveDate> {
+        if !self.by_month_day.is_empty() {
+            return self
+                .by_month_day
+                .iter()
+                .filter_map(|&d| month_day(year, month, d))
+                .collect();
+        }
+        if !self.by_day.is_empty() {
+            return self
+                .by_day
+                .iter()
+                .flat_map(|&(ordinal, wd)| nth_weekdays(year, month, wd, ordinal))
+                .collect();
+        }
+        NaiveDate::from_ymd_opt(year, month, start.day())
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Day `d` of a month; negative counts from the end (-1 = last day).
+fn month_day(year: i32, month: u32, d: i32) -> Option<NaiveDate> {
+    if d > 0 {
+        NaiveDate::from_ymd_opt(year, month, d as u32)
+    } else if d < 0 {
+        let first_next = if month == 12 {
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+        } else {
+            NaiveDate::from_ymd_opt(year, month + 1, 1)?
+        };
+        let date = first_next + Duration::days(d as i64);
+        (date.month() == month).then_some(date)
+    } else {
+        None
+    }
+}
+
+/// Weekdays `wd` in a month: all of them, or the `n`th (negative from the end).
+fn nth_weekdays(year: i32, month: u32, wd: Weekday, ordinal: Option<i32>) -> Vec<NaiveDate> {
+    let all: Vec<NaiveDate> = (1..=31)
+        .filter_map(|d| NaiveDate::from_ymd_opt(year, month, d))
+        .filter(|d| d.weekday() == wd)
+        .collect();
+    match ordinal {
+        None => all,
+        Some(n) if n > 0 => all.get(n as usize - 1).copied().into_iter().collect(),
+        Some(n) if n < 0 => all
+            .len()
+            .checked_sub(n.unsigned_abs() as usize)
+            .and_then(|i| all.get(i))
+            .copied()
+            .into_iter()
+            .collect(),
+        Some(_) => Vec::new(),
+    }
+}
+
+/// One VEVENT.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub uid: String,
+    pub summary: String,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub start: When,
+    pub end: Option<When>,
+    pub duration: Option<Duration>,
+    pub rrule: Option<RRule>,
+    pub exdates: Vec<When>,
+    pub recurrence_id: Option<When>,
+    pub cancelled: bool,
+    /// TRANSP:TRANSPARENT events do not block time.
+    pub transparent: bool,
+}
+
+/// One instance of an event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub all_day: bool,
+}
+
+/// Unfold continuation lines (CRLF followed by a space or tab).
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => {
+                if let Some(last) = lines.last_mut() {
+                    last.push_str(rest);
+                }
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Split `NAME;PARAM=x;PARAM="a:b":value`.
+fn parse_line(line: &str) -> Option<(String, Params, String)> {
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|p| {
+            let (k, v) = p.split_once('=')?;
+            Some((
+                k.trim().to_ascii_uppercase(),
+                v.trim_matches('"').to_string(),
+            ))
+        })
+        .collect();
+    Some((name, params, value.to_string()))
+}
+
+fn param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v.as_str())
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n' | 'N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\n', '\r'], "\\n")
+}
+
+/// ISO 8601 duration as used by iCalendar (`PT1H30M`, `P1D`, `P2W`).
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let (negative, rest) = match value.trim().strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.trim().trim_start_matches('+')),
+    };
+    let rest = rest.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in rest.chars() {
+        match c {
+            'T' => in_time = true,
+            '0'..='9' => number.push(c),
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match (unit, in_time) {
+                    ('W', false) => Duration::weeks(n),
+                    ('D', false) => Duration::days(n),
+                    ('H', true) => Duration::hours(n),
+                    ('M', true) => Duration::minutes(n),
+                    ('S', true) => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(if negative { -total } else { total })
+}
+
+/// Every VEVENT in a calendar. Events without DTSTART are skipped.
+pub fn parse_events(text: &str) -> Vec<Event> {
+    let mut events = Vec::new();
+    // Depth of nested components (VALARM) inside the current VEVENT.
+    let mut current: Option<(Event, bool, usize)> = None;
+    for line in unfold(text) {
+        let Some((name, params, value)) = parse_line(&line) else {
+            continue;
+        };
+        match (name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                current = Some((
+                    Event {
+                        uid: String::new(),
+                        summary: String::new(),
+                        location: None,
+                        description: None,
+                        start: When::Date(NaiveDate::MIN),
+                        end: None,
+                        duration: None,
+                        rrule: None,
+                        exdates: Vec::new(),
+                        recurrence_id: None,
+                        cancelled: false,
+                        transparent: false,
+                    },
+                    false,
+                    0,
+                ));
+            }
+            ("BEGIN", Some((_, _, depth))) => *depth += 1,
+            ("END", Some((_, _, depth))) if *depth > 0 => *depth -= 1,
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                if let Some((event, has_start, _)) = current.take()
+                    && has_start
+                {
+                    events.push(event);
+                }
+            }
+            (_, Some((event, has_start, 0))) => match name.as_str() {
+                "UID" => event.uid = value.trim().to_string(),
+                "SUMMARY" => event.summary = unescape(&value),
+                "LOCATION" => event.location = Some(unescape(&value)).filter(|s| !s.is_empty()),
+                "DESCRIPTION" => {
+                    event.description = Some(unescape(&value)).filter(|s| !s.is_empty())
+                }
+                "DTSTART" => {
+                    if let Some(when) = When::parse(&value, &params) {
+                        event.start = when;
+                        *has_start = true;
+                    }
+                }
+                "DTEND" => event.end = When::parse(&value, &params),
+                "DURATION" => event.duration = parse_duration(&value),
+                "RRULE" => event.rrule = RRule::parse(&value),
+                "EXDATE" => event
+                    .exdates
+                    .extend(value.split(',').filter_map(|v| When::parse(v, &params))),
+                "RECURRENCE-ID" => event.recurrence_id = When::parse(&value, &params),
+                "STATUS" => event.cancelled = value.trim().eq_ignore_ascii_case("CANCELLED"),
+                "TRANSP" => event.transparent = value.trim().eq_ignore_ascii_case("TRANSPARENT"),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    events
+}
+
+impl Event {
+    /// End of an instance starting at `start` (wall clock in the event's zone).
+    fn instance(&self, start: &When, zones: &mut Zones) -> Occurrence {
+        let begin = start.resolve(zones);
+        let end = match (&self.start, &self.end, self.duration) {
+            // All-day spans keep whole days in the viewer's zone.
+            (When::Date(s), Some(When::Date(e)), _) => {
+                let days = (*e - *s).num_days().max(1);
+                start
+                    .with_naive(start.naive() + Duration::days(days))
+                    .resolve(zones)
+            }
+            (_, _, Some(duration)) if start.is_date() => {
+                start.with_naive(start.naive() + duration).resolve(zones)
+            }
+            (_, _, Some(duration)) => begin + duration,
+            (first, Some(end), None) => begin + (end.resolve(zones) - first.resolve(zones)),
+            (When::Date(_), None, None) => start
+                .with_naive(start.naive() + Duration::days(1))
+                .resolve(zones),
+            (_, None, None) => begin,
+        };
+        Occurrence {
+            start: begin,
+            end: end.max(begin),
+            all_day: start.is_date(),
+        }
+    }
+
+    /// The instance at DTSTART.
+    pub fn first(&self, zones: &mut Zones) -> Occurrence {
+        self.instance(&self.start, zones)
+    }
+
+    /// Instances overlapping `[from, to)`, excluding those listed in
+    /// `overridden` (start instants replaced by RECURRENCE-ID events).
+    pub fn occurrences(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        overridden: &HashSet<DateTime<Utc>>,
+        zones: &mut Zones,
+    ) -> Vec<Occurrence> {
+        let overlaps = |o: &Occurrence| o.start < to && (o.end > from || o.start >= from);
+        let Some(ref rule) = self.rrule else {
+            let occurrence = self.instance(&self.start, zones);
+            return if overlaps(&occurrence) {
+                vec![occurrence]
+            } else {
+                Vec::new()
+            };
+        };
+
+        let excluded: HashSet<DateTime<Utc>> = self
+            .exdates
+            .iter()
+            .map(|w| w.resolve(zones))
+            .chain(overridden.iter().copied())
+            .collect();
+        let until = rule.until.as_ref().map(|u| u.resolve(zones));
+        let first = self.start.naive();
+        let time = first.time();
+        let mut produced = 0u32;
+        let mut out = Vec::new();
+        for period in 0..MAX_PERIODS {
+            for date in rule.period_dates(first.date(), period) {
+                let naive = date.and_time(time);
+                if naive < first {
+                    continue;
+                }
+                let when = self.start.with_naive(naive);
+                let occurrence = self.instance(&when, zones);
+                if until.is_some_and(|u| occurrence.start > u) || occurrence.start >= to {
+                    return out;
+                }
+                produced += 1;
+                if !excluded.contains(&occurrence.start) && overlaps(&occurrence) {
+                    out.push(occurrence);
+                }
+                if rule.count.is_some_and(|c| produced >= c) {
+                    return out;
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Instances of all events overlapping `[from, to)`, sorted by start.
+/// Cancelled events are dropped and overridden instances replaced.
+pub fn expand<'a>(
+    events: &'a [Event],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    zones: &mut Zones,
+) -> Vec<(&'a Event, Occurrence)> {
+    let mut overridden: HashMap<&str, HashSet<DateTime<Utc>>> = HashMap::new();
+    for event in events {
+        if let Some(ref rid) = event.recurrence_id {
+            overridden
+                .entry(event.uid.as_str())
+                .or_default()
+                .insert(rid.resolve(zones));
+        }
+    }
+    let none = HashSet::new();
+    let mut out: Vec<(&Event, Occurrence)> = Vec::new();
+    for event in events {
+        let skip = if event.recurrence_id.is_some() {
+            &none
+        } else {
+            overridden.get(event.uid.as_str()).unwrap_or(&none)
+        };
+        let instances = event.occurrences(from, to, skip, zones);
+        if !event.cancelled {
+            out.extend(instances.into_iter().map(|o| (event, o)));
+        }
+    }
+    out.sort_by(|a, b| {
+        a.1.start
+            .cmp(&b.1.start)
+            .then(a.0.summary.cmp(&b.0.summary))
+    });
+    out
+}
+
+/// Fold a content line at 75 octets.
+fn fold(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + line.len() / 70 * 3);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+/// An event to create.
+#[derive(Debug, Clone)]
+pub struct NewEvent {
+    pub uid: String,
+    pub summary: String,
+    pub start: When,
+    pub end: When,
+    pub location: Option<String>,
+    pub description: Option<String>,
+}
+
+/// A VCALENDAR holding one VEVENT.
+pub fn render_event(event: &NewEvent, now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//IronClaw//{}//EN", env!("CARGO_PKG_VERSION")),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", escape(&event.uid)),
+        format!(
+            "DTSTAMP:{}",
+            now.with_nanosecond(0)
+                .unwrap_or(now)
+                .format("%Y%m%dT%H%M%SZ")
+        ),
+        event.start.render("DTSTART"),
+        event.end.render("DTEND"),
+        format!("SUMMARY:{}", escape(&event.summary)),
+    ];
+    if let Some(ref location) = event.location {
+        lines.push(format!("LOCATION:{}", escape(location)));
+    }
+    if let Some(ref description) = event.description {
+        lines.push(format!("DESCRIPTION:{}", escape(description)));
+    }
+    lines.extend(["END:VEVENT".to_string(), "END:VCALENDAR".to_string()]);
+    lines.iter().map(|l| fold(l)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    const CAL: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup@example.com\r\n\
+SUMMARY:Standup\\, daily\r\n\
+DTSTART:20260302T090000Z\r\n\
+DURATION:PT15M\r\n\
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR;COUNT=6\r\n\
+EXDATE:20260304T090000Z\r\n\
+BEGIN:VALARM\r\n\
+TRIGGER:-PT5M\r\n\
+DESCRIPTION:not the event\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup@example.com\r\n\
+RECURRENCE-ID:20260306T090000Z\r\n\
+SUMMARY:Standup (moved)\r\n\
+DTSTART:20260306T100000Z\r\n\
+DTEND:20260306T101500Z\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:offsite\r\n\
+SUMMARY:Offsite\r\n\
+DESCRIPTION:Bring a\r\n  laptop\\nand snacks\r\n\
+DTSTART;VALUE=DATE:20260305\r\n\
+DTEND;VALUE=DATE:20260307\r\n\
+TRANSP:TRANSPARENT\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:gone\r\n\
+SUMMARY:Cancelled\r\n\
+STATUS:CANCELLED\r\n\
+DTSTART:20260303T120000Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_parse_properties() {
+        let events = parse_events(CAL);
+        assert_eq!(events.len(), 4);
+        let standup = &events[0];
+        assert_eq!(standup.summary, "Standup, daily");
+        assert_eq!(standup.duration, Some(Duration::minutes(15)));
+        assert_eq!(standup.description, None);
+        let rule = standup.rrule.as_ref().unwrap();
+        assert_eq!(rule.freq, Freq::Weekly);
+        assert_eq!(rule.count, Some(6));
+        assert_eq!(
+            events[2].description.as_deref(),
+            Some("Bring a laptop\nand snacks")
+        );
+        assert!(events[2].transparent);
+        assert!(events[3].cancelled);
+    }
+
+    #[test]
+    fn test_expand_with_exdate_override_and_cancel() {
+        let events = parse_events(CAL);
+        let mut zones = Zones::new(Tz::utc());
+        let got = expand(
+            &events,
+            utc("2026-03-01T00:00:00Z"),
+            utc("2026-03-31T00:00:00Z"),
+            &mut zones,
+        );
+        let summary: Vec<(String, String)> = got
+            .iter()
+            .map(|(e, o)| (e.summary.clone(), o.start.format("%d %H:%M").to_string()))
+            .collect();
+        let expected = [
+            ("Standup, daily", "02 09:00"),
+            ("Offsite", "05 00:00"),
+            ("Standup (moved)", "06 10:00"),
+            ("Standup, daily", "09 09:00"),
+            ("Standup, daily", "11 09:00"),
+            // Excluded and moved instances still count towards COUNT=6.
+            ("Standup, daily", "13 09:00"),
+        ];
+        assert_eq!(
+            summary,
+            expected
+                .iter()
+                .map(|(a, b)| (a.to_string(), b.to_string()))
+                .collect::<Vec<_>>()
+        );
+        // Two all-day days
+        let offsite = &got[1].1;
+        assert!(offsite.all_day);
+        assert_eq!(offsite.end - offsite.start, Duration::days(2));
+    }
+
+    #[test]
+    fn test_monthly_rules_and_wall_clock() {
+        let rule = RRule::parse("FREQ=MONTHLY;BYDAY=-1FR").unwrap();
+        let dates = rule.period_dates(NaiveDate::from_ymd_opt(2026, 1, 30).unwrap(), 1);
+        assert_eq!(dates, vec![NaiveDate::from_ymd_opt(2026, 2, 27).unwrap()]);
+        let rule = RRule::parse("FREQ=MONTHLY;BYMONTHDAY=-1").unwrap();
+        let dates = rule.period_dates(NaiveDate::from_ymd_opt(2026, 1, 31).unwrap(), 1);
+        assert_eq!(dates, vec![NaiveDate::from_ymd_opt(2026, 2, 28).unwrap()]);
+        assert!(RRule::parse("FREQ=HOURLY").is_none());
+
+        // A fixed-offset zone keeps 09:00 local = 07:00 UTC every day.
+        let event = parse_events(
+            "BEGIN:VEVENT\nUID:x\nDTSTART;TZID=\"+02:00\":20260101T090000\n\
+             DTEND;TZID=\"+02:00\":20260101T093000\nRRULE:FREQ=DAILY;UNTIL=20260103T235959Z\nEND:VEVENT\n",
+        );
+        let mut zones = Zones::new(Tz::utc());
+        let got = event[0].occurrences(
+            utc("2025-12-01T00:00:00Z"),
+            utc("2026-02-01T00:00:00Z"),
+            &HashSet::new(),
+            &mut zones,
+        );
+        assert_eq!(got.len(), 3);
+        assert_eq!(got[2].start, utc("2026-01-03T07:00:00Z"));
+        assert_eq!(got[2].end, utc("2026-01-03T07:30:00Z"));
+    }
+
+    #[test]
+    fn test_render_round_trip() {
+        let text = render_event(
+            &NewEvent {
+                uid: "abc@ironclaw".into(),
+                summary: "Lunch; with Ana, and Bo".into(),
+                start: When::Utc(utc("2026-04-01T12:00:00Z")),
+                end: When::Utc(utc("2026-04-01T13:00:00Z")),
+                location: None,
+                description: Some(format!("Agenda:\n{}", "x".repeat(120))),
+            },
+            utc("2026-03-20T08:00:00.5Z"),
+        );
+        assert!(text.lines().all(|l| l.len() <= 75));
+        assert!(text.contains("DTSTAMP:20260320T080000Z"));
+        let events = parse_events(&text);
+        assert_eq!(events[0].summary, "Lunch; with Ana, and Bo");
+        assert_eq!(
+            events[0].description.as_deref().unwrap().len(),
+            "Agenda:\n".len() + 120
+        );
+        assert_eq!(
+            parse_duration("P1W2DT3H"),
+            Some(Duration::hours(9 * 24 + 3))
+        );
+    }
+}
@@ -0,0 +1,896 @@
+//! Calendar tool over ICS feeds and CalDAV.
+//!
+//! Calendars live in the encrypted secrets store as `calendar_<name>`
+//! (provider `calendar`), holding JSON with the source: a CalDAV collection
+//! URL and login, or an ICS feed (URL or local file). Subscription URLs
+//! often embed an access token, so they are kept secret too. Register them
+//! with `ironclaw tool calendar add`; the model only sees calendar names.
+//!
+//! Events are fetched per call, recurrences expanded locally, and times
+//! shown in the user's time zone. Creating an event needs approval and is
+//! only possible on CalDAV calendars.
+
+mod caldav;
+mod ics;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+pub use ics::{Event, Occurrence, When};
+
+use crate::config::CalendarConfig;
+use crate::context::JobContext;
+use crate::locale::{DEFAULT_LANGUAGE, Tz, UserLocale, parse_when};
+use crate::secrets::SecretsStore;
+use crate::tools::builtin::email::valid_account_name;
+use crate::tools::tool::{Tool, ToolError, ToolOutput};
+use caldav::CalDav;
+use ics::{NewEvent, Zones};
+
+/// Secret name prefix for calendars.
+pub const CALENDAR_SECRET_PREFIX: &str = "calendar_";
+/// Provider of calendar secrets.
+pub const CALENDAR_PROVIDER: &str = "calendar";
+
+/// Most bytes read from one ICS feed or file.
+const MAX_ICS_BYTES: usize = 10 * 1024 * 1024;
+
+/// Longest window `upcoming` and `free_busy` look at.
+const MAX_WINDOW_DAYS: i64 = 366;
+
+/// Where a calendar's events come from, as stored in its secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CalendarSource {
+    /// CalDAV collection (read and write).
+    Caldav {
+        url: String,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    /// ICS feed over HTTP(S), or a local `.ics` file (read only).
+    Ics { url: String },
+}
+
+impl CalendarSource {
+    fn kind(&self) -> &'static str {
+        match self {
+            CalendarSource::Caldav { .. } => "caldav",
+            CalendarSource::Ics { .. } => "ics",
+        }
+    }
+}
+
+fn str_param<'a>(params: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+    params
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn require_str<'a>(params: &'a serde_json::Value, key: &str) -> Result<&'a str, ToolError> {
+    str_param(params, key)
+        .ok_or_else(|| ToolError::InvalidParameters(format!("missing '{}' parameter", key)))
+}
+
+/// RFC 3339, a local `YYYY-MM-DDTHH:MM`, or a natural-language expression.
+fn parse_time(value: &str, tz: &Tz, now: DateTime<Utc>) -> Result<DateTime<Utc>, ToolError> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"] {
+        if let Ok(local) = NaiveDateTime::parse_from_str(value, format) {
+            return Ok(tz.from_local(local));
+        }
+    }
+    parse_when(value, now, &UserLocale::new(tz.clone(), DEFAULT_LANGUAGE)).ok_or_else(|| {
+        ToolError::InvalidParameters(format!("could not understand time '{}'", value))
+    })
+}
+
+/// `HH:MM-HH:MM` as minutes since midnight; the end may be `24:00`.
+fn parse_hours(value: &str) -> Option<(u32, u32)> {
+    let minutes = |s: &str| {
+        let (h, m) = s.trim().split_once(':')?;
+        let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+        (m < 60 && h * 60 + m <= 24 * 60).then_some(h * 60 + m)
+    };
+    let (start, end) = value.split_once('-')?;
+    let (start, end) = (minutes(start)?, minutes(end)?);
+    (start < end).then_some((start, end))
+}
+
+/// Sort and merge overlapping or touching intervals.
+fn merge_busy(
+    mut intervals: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    intervals.sort();
+    let mut merged: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Gaps of at least `min` between `busy` intervals, inside the daily
+/// `hours` window (local minutes) of each day in `[from, to)`.
+fn free_slots(
+    busy: &[(DateTime<Utc>, DateTime<Utc>)],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    tz: &Tz,
+    hours: (u32, u32),
+    weekends: bool,
+    min: chrono::Duration,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut slots = Vec::new();
+    let mut day = tz.to_local(from).date_naive();
+    let last = tz.to_local(to).date_naive();
+    while day <= last {
+        let midnight = day.and_hms_opt(0, 0, 0).expect("valid time");
+        let at = |minutes: u32| tz.from_local(midnight + chrono::Duration::minutes(minutes as i64));
+        if weekends || !matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+            let mut cursor = at(hours.0).max(from);
+            let day_end = at(hours.1).min(to);
+            for &(start, end) in busy {
+                if end <= cursor || start >= day_end {
+                    continue;
+                }
+                if start - cursor >= min {
+                    slots.push((cursor, start));
+                }
+                cursor = cursor.max(end);
+            }
+            if day_end - cursor >= min {
+                slots.push((cursor, day_end));
+            }
+        }
+        match day.succ_opt() {
+            Some(next) => day = next,
+            None => break,
+        }
+    }
+    slots
+}
+
+/// Builtin `calendar` tool. Each user's calendars are their own secrets.
+pub struct CalendarTool {
+    secrets: Arc<dyn SecretsStore + Send + Sync>,
+    http: reqwest::Client,
+    config: CalendarConfig,
+}
+
+impl CalendarTool {
+    pub fn new(secrets: Arc<dyn SecretsStore + Send + Sync>, config: CalendarConfig) -> Self {
+        let http = crate::outbound::client_builder()
+            .timeout(config.timeout)
+            .build()
+            .unwrap_or_default();
+        Self {
+            secrets,
+            http,
+            config,
+        }
+    }
+
+    /// `user_id`'s registered calendar names.
+    async fn names(&self, user_id: &str) -> Result<Vec<String>, ToolError> {
+        let refs = self
+            .secrets
+            .list(user_id)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("list failed: {}", e)))?;
+        Ok(refs
+            .iter()
+            .filter(|r| r.provider.as_deref() == Some(CALENDAR_PROVIDER))
+            .filter_map(|r| {
+                r.name
+                    .strip_prefix(CALENDAR_SECRET_PREFIX)
+                    .map(String::from)
+            })
+            .collect())
+    }
+
+    async fn load(&self, user_id: &str, name: &str) -> Result<CalendarSource, ToolError> {
+        if !valid_account_name(name) {
+            return Err(ToolError::InvalidParameters(format!(
+                "invalid calendar name '{}'",
+                name
+            )));
+        }
+        let secret_name = format!("{}{}", CALENDAR_SECRET_PREFIX, name);
+        let secret = self
+            .secrets
+            .get(user_id, &secret_name)
+            .await
+            .ok()
+            .filter(|s| s.provider.as_deref() == Some(CALENDAR_PROVIDER))
+            .ok_or_else(|| {
+                ToolError::InvalidParameters(format!(
+                    "no calendar '{}'; register it with `ironclaw tool calendar add {}`",
+                    name, name
+                ))
+            })?;
+        let value = self
+            .secrets
+            .get_decrypted(user_id, &secret_name)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("decrypt failed: {}", e)))?;
+        let _ = self.secrets.record_usage(secret.id).await;
+        serde_json::from_str(value.expose()).map_err(|e| {
+            ToolError::ExecutionFailed(format!("calendar '{}' is malformed: {}", name, e))
+        })
+    }
+
+    /// The calendar named in `params`, or every one `user_id` registered.
+    async fn selected(
+        &self,
+        user_id: &str,
+        params: &serde_json::Value,
+    ) -> Result<Vec<String>, ToolError> {
+        if let Some(name) = str_param(params, "calendar") {
+            return Ok(vec![name.to_string()]);
+        }
+        let names = self.names(user_id).await?;
+        if names.is_empty() {
+            return Err(ToolError::InvalidParameters(
+                "no calendars; register one with `ironclaw tool calendar add`".to_string(),
+            ));
+        }
+        Ok(names)
+    }
+
+    /// Raw ICS text of a feed or file.
+    async fn read_ics(&self, url: &str) -> Result<String, String> {
+        let bytes = if url.starts_with("https://") || url.starts_with("http://") {
+            let response = self
+                .http
+                .get(url)
+                .send()
+                .await
+                .map_err(|e| format!("fetch failed: {}", e))?;
+            if !response.status().is_success() {
+                return Err(format!("fetch failed: HTTP {}", response.status()));
+            }
+            if response
+                .content_length()
+                .is_some_and(|len| len as usize > MAX_ICS_BYTES)
+            {
+                return Err("calendar feed is too large".to_string());
+            }
+            response
+                .bytes()
+                .await
+                .map_err(|e| format!("fetch failed: {}", e))?
+                .to_vec()
+        } else {
+            let path = url.strip_prefix("file://").unwrap_or(url);
+            let meta = tokio::fs::metadata(path)
+                .await
+                .map_err(|e| format!("cannot read {}: {}", path, e))?;
+            if meta.len() as usize > MAX_ICS_BYTES {
+                return Err("calendar file is too large".to_string());
+            }
+            tokio::fs::read(path)
+                .await
+                .map_err(|e| format!("cannot read {}: {}", path, e))?
+        };
+        if bytes.len() > MAX_ICS_BYTES {
+            return Err("calendar feed is too large".to_string());
+        }
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Events of one calendar that may fall in `[from, to)`.
+    async fn events(
+        &self,
+        source: &CalendarSource,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Event>, String> {
+        match source {
+            CalendarSource::Caldav {
+                url,
+                username,
+                password,
+            } => {
+                let client = CalDav {
+                    http: &self.http,
+                    url,
+                    username: username.as_deref(),
+                    password: password.as_deref(),
+                };
+                Ok(client
+                    .events(from, to)
+                    .await?
+                    .iter()
+                    .flat_map(|body| ics::parse_events(body))
+                    .collect())
+            }
+            CalendarSource::Ics { url } => Ok(ics::parse_events(&self.read_ics(url).await?)),
+        }
+    }
+
+    /// Occurrences across the selected calendars, with per-calendar errors.
+    async fn occurrences(
+        &self,
+        user_id: &str,
+        params: &serde_json::Value,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        tz: &Tz,
+    ) -> Result<(Vec<(String, Event, Occurrence)>, Vec<serde_json::Value>), ToolError> {
+        let names = self.selected(user_id, params).await?;
+        let single = names.len() == 1;
+        let mut found = Vec::new();
+        let mut errors = Vec::new();
+        for name in names {
+            let source = self.load(user_id, &name).await?;
+            let events = match self.events(&source, from, to).await {
+                Ok(events) => events,
+                Err(e) if single => return Err(ToolError::ExternalService(e)),
+                Err(e) => {
+                    errors.push(serde_json::json!({ "calendar": name, "error": e }));
+                    continue;
+                }
+            };
+            let mut zones = Zones::new(tz.clone());
+            found.extend(
+                ics::expand(&events, from, to, &mut zones)
+                    .into_iter()
+                    .map(|(event, o)| (name.clone(), event.clone(), o)),
+            );
+        }
+        found.sort_by_key(|(_, _, o)| o.start);
+        Ok((found, errors))
+    }
+
+    /// Window from `start`/`end` (or `days` from now).
+    fn window(
+        &self,
+        params: &serde_json::Value,
+        tz: &Tz,
+        now: DateTime<Utc>,
+    ) -> Result<(DateTime<Utc>, DateTime<Utc>), ToolError> {
+        let from = match str_param(params, "start") {
+            Some(value) => parse_time(value, tz, now)?,
+            None => now,
+        };
+        let to = match str_param(params, "end") {
+            Some(value) => parse_time(value, tz, now)?,
+            None => {
+                let days = params
+                    .get("days")
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(7)
+                    .clamp(1, MAX_WINDOW_DAYS);
+                from + chrono::Duration::days(days)
+            }
+        };
+        if to <= from {
+            return Err(ToolError::InvalidParameters(
+                "'end' must be after 'start'".to_string(),
+            ));
+        }
+        if to - from > chrono::Duration::days(MAX_WINDOW_DAYS) {
+            return Err(ToolError::InvalidParameters(format!(
+                "window is longer than {} days",
+                MAX_WINDOW_DAYS
+            )));
+        }
+        Ok((from, to))
+    }
+
+    async fn create(
+        &self,
+        user_id: &str,
+        params: &serde_json::Value,
+        tz: &Tz,
+        now: DateTime<Utc>,
+    ) -> Result<serde_json::Value, ToolError> {
+        let summary = require_str(params, "summary")?;
+        let start = parse_time(require_str(params, "start")?, tz, now)?;
+        let all_day = params.get("all_day").and_then(|v| v.as_bool()) == Some(true);
+        let end = match str_param(params, "end") {
+            Some(value) => parse_time(value, tz, now)?,
+            None if all_day => start + chrono::Duration::days(1),
+            None => {
+                let minutes = params
+                    .get("duration_minutes")
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(60);
+                start + chrono::Duration::minutes(minutes)
+            }
+        };
+        if end <= start {
+            return Err(ToolError::InvalidParameters(
+                "'end' must be after 'start'".to_string(),
+            ));
+        }
+
+        let name = match str_param(params, "calendar") {
+            Some(name) => name.to_string(),
+            None => {
+                let mut writable = Vec::new();
+                for name in self.names(user_id).await? {
+                    if matches!(
+                        self.load(user_id, &name).await?,
+                        CalendarSource::Caldav { .. }
+                    ) {
+                        writable.push(name);
+                    }
+                }
+                match writable.as_slice() {
+                    [only] => only.clone(),
+                    [] => {
+                        return Err(ToolError::InvalidParameters(
+                            "no CalDAV calendar to create events in".to_string(),
+                        ));
+                    }
+                    _ => {
+                        return Err(ToolError::InvalidParameters(format!(
+                            "pass 'calendar' (one of: {})",
+                            writable.join(", ")
+                        )));
+                    }
+                }
+            }
+        };
+        let CalendarSource::Caldav {
+            url,
+            username,
+            password,
+        } = self.load(user_id, &name).await?
+        else {
+            return Err(ToolError::InvalidParameters(format!(
+                "calendar '{}' is a read-only ICS feed",
+                name
+            )));
+        };
+
+        let (start_when, end_when) = if all_day {
+            let first = tz.to_local(start).date_naive();
+            let last = tz
+                .to_local(end)
+                .date_naive()
+                .max(first.succ_opt().unwrap_or(first));
+            (When::Date(first), When::Date(last))
+        } else {
+            (When::Utc(start), When::Utc(end))
+        };
+        let uid = format!("{}@ironclaw", uuid::Uuid::new_v4());
+        let event = NewEvent {
+            uid: uid.clone(),
+            summary: summary.to_string(),
+            start: start_when,
+            end: end_when,
+            location: str_param(params, "location").map(String::from),
+            description: str_param(params, "description").map(String::from),
+        };
+        let client = CalDav {
+            http: &self.http,
+            url: &url,
+            username: username.as_deref(),
+            password: password.as_deref(),
+        };
+        let href = client
+            .create(&uid, ics::render_event(&event, now))
+            .await
+            .map_err(ToolError::ExternalService)?;
+        Ok(serde_json::json!({
+            "created": true,
+            "calendar": name,
+            "uid": uid,
+            "url": href,
+            "summary": summary,
+            "start": tz.to_local(start).to_rfc3339(),
+            "end": tz.to_local(end).to_rfc3339(),
+            "all_day": all_day,
+        }))
+    }
+}
+
+/// JSON for one occurrence, in the user's zone.
+fn describe(
+    calendar: Option<&str>,
+    event: &Event,
+    occurrence: &Occurrence,
+    tz: &Tz,
+) -> serde_json::Value {
+    let (start, end) = if occurrence.all_day {
+        let start = tz.to_local(occurrence.start).date_naive();
+        // DTEND is exclusive for all-day events; show the last day.
+        let last = tz
+            .to_local(occurrence.end)
+            .date_naive()
+            .pred_opt()
+            .unwrap_or(start)
+            .max(start);
+        (start.to_string(), last.to_string())
+    } else {
+        (
+            tz.to_local(occurrence.start).to_rfc3339(),
+            tz.to_local(occurrence.end).to_rfc3339(),
+        )
+    };
+    let mut value = serde_json::json!({
+        "summary": event.summary,
+        "start": start,
+        "end": end,
+        "all_day": occurrence.all_day,
+        "location": event.location,
+        "description": event.description.as_deref().map(|d| {
+            match d.char_indices().nth(500) {
+                Some((cut, _)) => format!("{}…", &d[..cut]),
+                None => d.to_string(),
+            }
+        }),
+        "recurring": event.rrule.is_some() || event.recurrence_id.is_some(),
+        "busy": !event.transparent,
+        "uid": event.uid,
+    });
+    if let (Some(calendar), serde_json::Value::Object(map)) = (calendar, &mut value) {
+        map.insert("calendar".into(), serde_json::json!(calendar));
+    }
+    value
+}
+
+#[async_trait]
+impl Tool for CalendarTool {
+    fn name(&self) -> &str {
+        "calendar"
+    }
+
+    fn description(&self) -> &str {
+        "Read and schedule on registered calendars (CalDAV or ICS feeds). \
+         'calendars' lists them, 'upcoming' lists events in a window (default \
+         the next 7 days), 'free_busy' returns merged busy blocks and free slots \
+         within working hours, 'create' adds an event to a CalDAV calendar \
+         (needs approval), and 'parse' reads raw ICS text. Times accept RFC 3339, \
+         'YYYY-MM-DDTHH:MM' in the user's time zone, or phrases like 'tomorrow 3pm'."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["calendars", "upcoming", "free_busy", "create", "parse"],
+                    "description": "Action to perform"
+                },
+                "calendar": {
+                    "type": "string",
+                    "description": "Calendar name (default: all for reads, the only CalDAV calendar for create)"
+                },
+                "start": { "type": "string", "description": "Window start, or event start (create)" },
+                "end": { "type": "string", "description": "Window end, or event end (create)" },
+                "days": { "type": "integer", "description": "Window length in days when 'end' is omitted (default 7)" },
+                "limit": { "type": "integer", "description": "Maximum events to return (upcoming, parse)" },
+                "working_hours": {
+                    "type": "string",
+                    "description": "Daily window for free slots, HH:MM-HH:MM (free_busy, default 09:00-17:00)"
+                },
+                "include_weekends": { "type": "boolean", "description": "Offer free slots on weekends (free_busy)" },
+                "min_minutes": { "type": "integer", "description": "Shortest free slot to report (free_busy, default 30)" },
+                "summary": { "type": "string", "description": "Event title (create)" },
+                "duration_minutes": { "type": "integer", "description": "Length when 'end' is omitted (create, default 60)" },
+                "all_day": { "type": "boolean", "description": "All-day event (create)" },
+                "location": { "type": "string", "description": "Event location (create)" },
+                "description": { "type": "string", "description": "Event notes (create)" },
+                "content": { "type": "string", "description": "Raw ICS text (parse)" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        ctx: &JobContext,
+    ) -> Result<ToolOutput, ToolError> {
+        let started = Instant::now();
+        let action = require_str(&params, "action")?;
+        let tz = match ctx.timezone.as_deref() {
+            Some(name) => Tz::parse(name).unwrap_or_else(|_| Tz::utc()),
+            None => Tz::utc(),
+        };
+        let now = Utc::now();
+        let limit = params
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map_or(self.config.max_events, |n| n as usize)
+            .clamp(1, self.config.max_events);
+
+        let result = match action {
+            "calendars" => {
+                let mut calendars = Vec::new();
+                for name in self.names(&ctx.user_id).await? {
+                    let kind = self
+                        .load(&ctx.user_id, &name)
+                        .await
+                        .map(|s| s.kind())
+                        .unwrap_or("invalid");
+                    calendars.push(serde_json::json!({
+                        "name": name,
+                        "kind": kind,
+                        "writable": kind == "caldav",
+                    }));
+                }
+                serde_json::json!({ "calendars": calendars })
+            }
+            "upcoming" => {
+                let (from, to) = self.window(&params, &tz, now)?;
+                let (found, errors) = self
+                    .occurrences(&ctx.user_id, &params, from, to, &tz)
+                    .await?;
+                let events: Vec<_> = found
+                    .iter()
+                    .take(limit)
+                    .map(|(name, event, o)| describe(Some(name), event, o, &tz))
+                    .collect();
+                serde_json::json!({
+                    "timezone": tz.name(),
+                    "start": tz.to_local(from).to_rfc3339(),
+                    "end": tz.to_local(to).to_rfc3339(),
+                    "total": found.len(),
+                    "events": events,
+                    "errors": errors,
+                })
+            }
+            "free_busy" => {
+                let (from, to) = self.window(&params, &tz, now)?;
+                let hours = match str_param(&params, "working_hours") {
+                    Some(value) => parse_hours(value).ok_or_else(|| {
+                        ToolError::InvalidParameters(format!("'{}' is not HH:MM-HH:MM", value))
+                    })?,
+                    None => (9 * 60, 17 * 60),
+                };
+                let weekends =
+                    params.get("include_weekends").and_then(|v| v.as_bool()) == Some(true);
+                let min = chrono::Duration::minutes(
+                    params
+                        .get("min_minutes")
+                        .and_then(|v| v.as_i64())
+                        .unwrap_or(30)
+                        .max(1),
+                );
+                let (found, errors) = self
+                    .occurrences(&ctx.user_id, &params, from, to, &tz)
+                    .await?;
+                let busy = merge_busy(
+                    found
+                        .iter()
+                        .filter(|(_, event, o)| !event.transparent && o.end > o.start)
+                        .map(|(_, _, o)| (o.start.max(from), o.end.min(to)))
+                        .collect(),
+                );
+                let free = free_slots(&busy, from, to, &tz, hours, weekends, min);
+                let render = |intervals: &[(DateTime<Utc>, DateTime<Utc>)]| -> Vec<_> {
+                    intervals
+                        .iter()
+                        .map(|(s, e)| {
+                            serde_json::json!({
+                                "start": tz.to_local(*s).to_rfc3339(),
+                                "end": tz.to_local(*e).to_rfc3339(),
+                            })
+                        })
+                        .collect()
+                };
+                serde_json::json!({
+                    "timezone": tz.name(),
+                    "start": tz.to_local(from).to_rfc3339(),
+                    "end": tz.to_local(to).to_rfc3339(),
+                    "busy": render(&busy),
+                    "free": render(&free),
+                    "errors": errors,
+                })
+            }
+            "create" => self.create(&ctx.user_id, &params, &tz, now).await?,
+            "parse" => {
+                let content = require_str(&params, "content")?;
+                if content.len() > MAX_ICS_BYTES {
+                    return Err(ToolError::InvalidParameters(
+                        "ICS content is too large".to_string(),
+                    ));
+                }
+                let events = ics::parse_events(content);
+                let mut zones = Zones::new(tz.clone());
+                let listed: Vec<_> = events
+                    .iter()
+                    .take(limit)
+                    .map(|event| describe(None, event, &event.first(&mut zones), &tz))
+                    .collect();
+                serde_json::json!({
+                    "timezone": tz.name(),
+                    "total": events.len(),
+                    "events": listed,
+                })
+            }
+            other => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "unknown action '{}'",
+                    other
+                )));
+            }
+        };
+        Ok(ToolOutput::success(result, started.elapsed()))
+    }
+
+    fn requires_approval_for(&self, params: &serde_json::Value) -> bool {
+        str_param(params, "action") == Some("create")
+    }
+
+    fn execution_timeout(&self) -> Duration {
+        self.config.timeout + Duration::from_secs(30)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_merge_and_free_slots() {
+        let busy = merge_busy(vec![
+            (utc("2026-03-02T10:00:00Z"), utc("2026-03-02T11:00:00Z")),
+            (utc("2026-03-02T09:00:00Z"), utc("2026-03-02T09:20:00Z")),
+            (utc("2026-03-02T10:30:00Z"), utc("2026-03-02T12:00:00Z")),
+            (utc("2026-03-02T12:00:00Z"), utc("2026-03-02T12:30:00Z")),
+        ]);
+        assert_eq!(
+            busy,
+            vec![
+                (utc("2026-03-02T09:00:00Z"), utc("2026-03-02T09:20:00Z")),
+                (utc("2026-03-02T10:00:00Z"), utc("2026-03-02T12:30:00Z")),
+            ]
+        );
+        // Friday to Monday evening, 9-17 in UTC+1, weekends skipped.
+        let tz = Tz::parse("+01:00").unwrap();
+        let free = free_slots(
+            &busy,
+            utc("2026-02-27T15:00:00Z"),
+            utc("2026-03-02T20:00:00Z"),
+            &tz,
+            (9 * 60, 17 * 60),
+            false,
+            chrono::Duration::minutes(30),
+        );
+        assert_eq!(
+            free,
+            vec![
+                // Friday: from the window start to 17:00 local.
+                (utc("2026-02-27T15:00:00Z"), utc("2026-02-27T16:00:00Z")),
+                // Monday: 08:00-09:00Z, the 20-minute gap is too short.
+                (utc("2026-03-02T08:00:00Z"), utc("2026-03-02T09:00:00Z")),
+                (utc("2026-03-02T09:20:00Z"), utc("2026-03-02T10:00:00Z")),
+                (utc("2026-03-02T12:30:00Z"), utc("2026-03-02T16:00:00Z")),
+            ]
+        );
+        assert_eq!(parse_hours("08:30-24:00"), Some((510, 1440)));
+        assert_eq!(parse_hours("17:00-09:00"), None);
+    }
+
+    #[test]
+    fn test_source_and_time_parsing() {
+        let source: CalendarSource = serde_json::from_str(
+            r#"{"kind": "caldav", "url": "https://dav.example.com/cal/work/", "username": "me"}"#,
+        )
+        .unwrap();
+        assert_eq!(source.kind(), "caldav");
+        let feed: CalendarSource =
+            serde_json::from_value(serde_json::json!({"kind": "ics", "url": "/tmp/x.ics"}))
+                .unwrap();
+        assert_eq!(feed.kind(), "ics");
+
+        let tz = Tz::parse("-05:00").unwrap();
+        let now = utc("2026-03-02T12:00:00Z");
+        assert_eq!(
+            parse_time("2026-03-03T09:30", &tz, now).unwrap(),
+            utc("2026-03-03T14:30:00Z")
+        );
+        assert_eq!(
+            parse_time("2026-03-03T09:30:00Z", &tz, now).unwrap(),
+            utc("2026-03-03T09:30:00Z")
+        );
+        assert!(parse_time("tomorrow at 3pm", &tz, now).is_ok());
+        assert!(parse_time("whenever", &tz, now).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_needs_approval_and_parse() {
+        let secrets: Arc<dyn SecretsStore + Send + Sync> =
+            Arc::new(crate::secrets::InMemorySecretsStore::new(Arc::new(
+                crate::secrets::SecretsCrypto::new(secrecy::SecretString::from(
+                    "0123456789abcdef0123456789abcdef".to_string(),
+                ))
+                .unwrap(),
+            )));
+        let tool = CalendarTool::new(secrets, CalendarConfig::default());
+        assert!(tool.requires_approval_for(&serde_json::json!({"action": "create"})));
+        assert!(!tool.requires_approval_for(&serde_json::json!({"action": "upcoming"})));
+
+        let output = tool
+            .execute(
+                serde_json::json!({
+                    "action": "parse",
+                    "content": "BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:1\nSUMMARY:Dentist\n\
+                                DTSTART:20260310T143000Z\nDTEND:20260310T151500Z\n\
+                                END:VEVENT\nEND:VCALENDAR\n"
+                }),
+                &JobContext::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(output.result["total"], 1);
+        assert_eq!(output.result["events"][0]["summary"], "Dentist");
+        assert_eq!(
+            output.result["events"][0]["end"],
+            "2026-03-10T15:15:00+00:00"
+        );
+
+        let err = tool
+            .execute(
+                serde_json::json!({"action": "upcoming"}),
+                &JobContext::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no calendars"));
+    }
+
+    #[tokio::test]
+    async fn test_calendars_belong_to_the_calling_user() {
+        use crate::secrets::CreateSecretParams;
+
+        let secrets: Arc<dyn SecretsStore + Send + Sync> =
+            Arc::new(crate::secrets::InMemorySecretsStore::new(Arc::new(
+                crate::secrets::SecretsCrypto::new(secrecy::SecretString::from(
+                    "0123456789abcdef0123456789abcdef".to_string(),
+                ))
+                .unwrap(),
+            )));
+        secrets
+            .create(
+                "alice",
+                CreateSecretParams::new(
+                    "calendar_work",
+                    r#"{"kind": "ics", "url": "https://example.com/work.ics"}"#,
+                )
+                .with_provider(CALENDAR_PROVIDER),
+            )
+            .await
+            .unwrap();
+        let tool = CalendarTool::new(secrets, CalendarConfig::default());
+        let calendars = |user: &str| {
+            let ctx = JobContext::with_user(user, "t", "d");
+            let tool = &tool;
+            async move {
+                tool.execute(serde_json::json!({"action": "calendars"}), &ctx)
+                    .await
+                    .unwrap()
+                    .result
+            }
+        };
+
+        assert_eq!(
+            calendars("alice").await["calendars"],
+            serde_json::json!([{"name": "work", "kind": "ics", "writable": false}])
+        );
+        assert_eq!(calendars("bob").await["calendars"], serde_json::json!([]));
+    }
+}
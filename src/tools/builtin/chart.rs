@@ -0,0 +1,295 @@
+//! Chart generation tool.
+//!
+//! Turns tabular data (e.g. an `sql` query result) into a bar, line or
+//! scatter chart. The image goes into the media cache and the agent hands
+//! it to the channel, so the user sees an actual chart instead of a table.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::context::JobContext;
+use crate::media::{ChartFormat, ChartKind, ChartSpec, MediaCache};
+use crate::tools::tool::{Tool, ToolError, ToolOutput};
+
+/// Default image size in pixels.
+const DEFAULT_WIDTH: u32 = 800;
+const DEFAULT_HEIGHT: u32 = 500;
+
+/// Accepted range for either dimension.
+const MIN_SIDE: u32 = 200;
+const MAX_SIDE: u32 = 2000;
+
+/// Renders charts into the media cache.
+pub struct ChartTool {
+    cache: Arc<MediaCache>,
+}
+
+impl ChartTool {
+    pub fn new(cache: Arc<MediaCache>) -> Self {
+        Self { cache }
+    }
+}
+
+/// Media cache key for a chart: identical requests share one image.
+fn media_id(params: &serde_json::Value) -> String {
+    let hash = blake3::hash(params.to_string().as_bytes());
+    format!("chart-{}", &hash.to_hex()[..16])
+}
+
+fn dimension(params: &serde_json::Value, key: &str, default: u32) -> Result<u32, ToolError> {
+    match params.get(key) {
+        None | Some(serde_json::Value::Null) => Ok(default),
+        Some(v) => v
+            .as_u64()
+            .and_then(|n| u32::try_from(n).ok())
+            .filter(|n| (MIN_SIDE..=MAX_SIDE).contains(n))
+            .ok_or_else(|| {
+                ToolError::InvalidParameters(format!(
+                    "'{}' must be a whole number from {} to {}",
+                    key, MIN_SIDE, MAX_SIDE
+                ))
+            }),
+    }
+}
+
+fn optional_text(params: &serde_json::Value, key: &str) -> Option<String> {
+    params
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+/// Build the chart spec and output format from tool parameters.
+fn parse_params(params: &serde_json::Value) -> Result<(ChartSpec, ChartFormat), ToolError> {
+    let kind = match params.get("kind").and_then(|v| v.as_str()) {
+        None => ChartKind::Bar,
+        Some(kind) => ChartKind::from_str_loose(kind).ok_or_else(|| {
+            ToolError::InvalidParameters(format!(
+                "unknown kind '{}' (expected bar, line or scatter)",
+                kind
+            ))
+        })?,
+    };
+    let format = match params.get("format").and_then(|v| v.as_str()) {
+        None | Some("png") => ChartFormat::Png,
+        Some("svg") => ChartFormat::Svg,
+        Some(other) => {
+            return Err(ToolError::InvalidParameters(format!(
+                "unknown format '{}' (expected png or svg)",
+                other
+            )));
+        }
+    };
+
+    let columns: Vec<String> = params
+        .get("columns")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| ToolError::InvalidParameters("missing 'columns' parameter".to_string()))?
+        .iter()
+        .map(|c| {
+            c.as_str()
+                .map(String::from)
+                .unwrap_or_else(|| c.to_string())
+        })
+        .collect();
+    let rows: Vec<Vec<serde_json::Value>> = params
+        .get("rows")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| ToolError::InvalidParameters("missing 'rows' parameter".to_string()))?
+        .iter()
+        .map(|row| {
+            row.as_array().cloned().ok_or_else(|| {
+                ToolError::InvalidParameters("each row must be an array of cells".to_string())
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    let (categories, series) = ChartSpec::table_series(&columns, &rows)
+        .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+
+    let spec = ChartSpec {
+        kind,
+        title: optional_text(params, "title"),
+        x_label: optional_text(params, "x_label").or_else(|| Some(columns[0].clone())),
+        y_label: optional_text(params, "y_label"),
+        categories,
+        series,
+        width: dimension(params, "width", DEFAULT_WIDTH)?,
+        height: dimension(params, "height", DEFAULT_HEIGHT)?,
+    };
+    Ok((spec, format))
+}
+
+#[async_trait]
+impl Tool for ChartTool {
+    fn name(&self) -> &str {
+        "generate_chart"
+    }
+
+    fn description(&self) -> &str {
+        "Draw a bar, line or scatter chart from a table and show it to the user \
+         as an image. Pass 'columns' and 'rows' (e.g. straight from the sql tool): the \
+         first column labels the x axis, every numeric column after it becomes a \
+         series. Use it whenever the user asks to plot, chart or graph data."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "columns": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Column names; the first is the x-axis label column"
+                },
+                "rows": {
+                    "type": "array",
+                    "items": { "type": "array" },
+                    "description": "Table rows, one cell per column. Value cells are numbers or numeric strings"
+                },
+                "kind": {
+                    "type": "string",
+                    "enum": ["bar", "line", "scatter"],
+                    "description": "Chart type (default: bar)"
+                },
+                "title": {
+                    "type": "string",
+                    "description": "Chart title"
+                },
+                "x_label": {
+                    "type": "string",
+                    "description": "X-axis caption (default: first column name)"
+                },
+                "y_label": {
+                    "type": "string",
+                    "description": "Y-axis caption, e.g. the unit"
+                },
+                "format": {
+                    "type": "string",
+                    "enum": ["png", "svg"],
+                    "description": "Image format (default: png)"
+                },
+                "width": {
+                    "type": "integer",
+                    "description": "Width in pixels, 200-2000 (default: 800)"
+                },
+                "height": {
+                    "type": "integer",
+                    "description": "Height in pixels, 200-2000 (default: 500)"
+                }
+            },
+            "required": ["columns", "rows"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        _ctx: &JobContext,
+    ) -> Result<ToolOutput, ToolError> {
+        let start = std::time::Instant::now();
+        let (spec, format) = parse_params(&params)?;
+        let media_id = media_id(&params);
+
+        let (bytes, cached) = match self.cache.get(&media_id).await {
+            Some((data, _)) => (data.len(), true),
+            None => {
+                let rendered = {
+                    let spec = spec.clone();
+                    tokio::task::spawn_blocking(move || spec.render(format))
+                        .await
+                        .map_err(|e| ToolError::ExecutionFailed(format!("render task: {e}")))?
+                        .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?
+                };
+                let len = rendered.len();
+                self.cache
+                    .insert(media_id.clone(), rendered, format.mime_type().to_string())
+                    .await;
+                (len, false)
+            }
+        };
+
+        Ok(ToolOutput::success(
+            serde_json::json!({
+                "media_id": media_id,
+                "mime_type": format.mime_type(),
+                "file_name": format!("{}.{}", media_id, format.extension()),
+                "title": spec.title,
+                "series": spec.series.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+                "points": spec.categories.len(),
+                "bytes": bytes,
+                "cached": cached,
+                "note": "The chart is shown to the user with your reply. Refer to it rather than repeating the data as a table.",
+            }),
+            start.elapsed(),
+        ))
+    }
+
+    fn requires_sanitization(&self) -> bool {
+        false // Output describes an image we rendered, not external data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> serde_json::Value {
+        serde_json::json!({
+            "columns": ["job", "cost"],
+            "rows": [["build", "1.50"], ["deploy", 0.25]],
+            "kind": "bar",
+            "title": "Job costs"
+        })
+    }
+
+    #[test]
+    fn test_parse_params_defaults_and_validation() {
+        let (spec, format) = parse_params(&params()).unwrap();
+        assert_eq!(format, ChartFormat::Png);
+        assert_eq!(spec.kind, ChartKind::Bar);
+        assert_eq!(spec.x_label.as_deref(), Some("job"));
+        assert_eq!((spec.width, spec.height), (DEFAULT_WIDTH, DEFAULT_HEIGHT));
+
+        let mut bad = params();
+        bad["kind"] = serde_json::json!("pie");
+        assert!(parse_params(&bad).is_err());
+        let mut bad = params();
+        bad["width"] = serde_json::json!(10_000);
+        assert!(parse_params(&bad).is_err());
+        let mut bad = params();
+        bad["rows"] = serde_json::json!(["build"]);
+        assert!(parse_params(&bad).is_err());
+    }
+
+    #[test]
+    fn test_media_id_is_stable() {
+        assert_eq!(media_id(&params()), media_id(&params()));
+        let mut other = params();
+        other["kind"] = serde_json::json!("line");
+        assert_ne!(media_id(&params()), media_id(&other));
+    }
+
+    #[tokio::test]
+    async fn test_generate_chart_caches_image() {
+        let cache = Arc::new(MediaCache::new());
+        let tool = ChartTool::new(Arc::clone(&cache));
+        let ctx = JobContext::default();
+
+        let mut params = params();
+        params["format"] = serde_json::json!("svg");
+        let output = tool.execute(params.clone(), &ctx).await.unwrap();
+        let id = output.result["media_id"].as_str().unwrap().to_string();
+        assert_eq!(output.result["cached"], false);
+
+        let (data, mime) = cache.get(&id).await.unwrap();
+        assert_eq!(mime, "image/svg+xml");
+        assert!(String::from_utf8(data).unwrap().contains("Job costs"));
+
+        let again = tool.execute(params, &ctx).await.unwrap();
+        assert_eq!(again.result["cached"], true);
+    }
+}
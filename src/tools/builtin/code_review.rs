@@ -0,0 +1,1167 @@
+//! `code_review_submit`: turn a sandbox job's changes into a pull request
+//! (GitHub) or merge request (GitLab).
+//!
+//! The tool stages everything the job changed in its project directory,
+//! commits it on a fresh branch, pushes the branch and opens the request
+//! with a templated description linking back to the job's transcript.
+//!
+//! [`ReviewPolicy`] is checked before anything leaves the machine: the
+//! pushed branch must carry the configured prefix and match no protected
+//! pattern, the forge must not report it as protected either, the target
+//! must be an allowed base, and the change set must stay under the file
+//! limit without touching blocked paths (CI definitions by default).
+//!
+//! GitHub pushes use an installation token minted from the sandbox's GitHub
+//! App, narrowed to the one repository and revoked afterwards, or the
+//! `github_token` secret when no app is configured. GitLab uses the
+//! `gitlab_token` secret. Git receives the token through `GIT_CONFIG_*`
+//! environment variables, never on its command line or in a remote URL.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::Engine;
+use uuid::Uuid;
+
+use crate::config::{CodeReviewConfig, GitHubAppIssuerConfig};
+use crate::context::JobContext;
+use crate::db::Database;
+use crate::sandbox::{CredentialIssuer, GitHubAppIssuer, IssuedCredential};
+use crate::secrets::SecretsStore;
+use crate::tools::tool::{Tool, ToolError, ToolOutput};
+
+/// Secret holding a GitHub token, used when no GitHub App is configured.
+const GITHUB_TOKEN_SECRET: &str = "github_token";
+
+/// Secret holding a GitLab project or personal access token.
+const GITLAB_TOKEN_SECRET: &str = "gitlab_token";
+
+/// Changed files listed in the description; the rest are counted.
+const MAX_LISTED_FILES: usize = 50;
+
+/// Description used when no template file is configured.
+const DEFAULT_TEMPLATE: &str = "{summary}
+
+---
+
+**Task:** {task}
+
+**Changed files ({file_count}, {stats}):**
+{files}
+
+Opened by IronClaw from sandbox job `{job_id}`. Transcript: {transcript_url}
+";
+
+/// Where a repository is hosted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForgeKind {
+    GitHub,
+    GitLab,
+}
+
+impl ForgeKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::GitHub => "github",
+            Self::GitLab => "gitlab",
+        }
+    }
+}
+
+/// A repository on a forge: `owner/name`, or a nested GitLab project path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoRef {
+    pub forge: ForgeKind,
+    pub host: String,
+    pub path: String,
+}
+
+impl RepoRef {
+    /// Parse a clone URL (`https://host/owner/name.git`,
+    /// `git@host:owner/name.git`, `ssh://git@host/owner/name`). The forge is
+    /// recognized from the host; `gitlab_host` names a self-hosted GitLab.
+    pub fn from_remote(url: &str, gitlab_host: &str) -> Option<Self> {
+        let url = url.trim();
+        let (host, path) = if let Some(rest) = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))
+            .or_else(|| url.strip_prefix("ssh://"))
+        {
+            let (authority, path) = rest.split_once('/')?;
+            let host = authority.rsplit('@').next()?;
+            (host.split(':').next()?, path)
+        } else {
+            // scp-like: git@host:owner/name.git
+            let (user_host, path) = url.split_once(':')?;
+            (user_host.rsplit('@').next()?, path)
+        };
+        let host = host.to_ascii_lowercase();
+        let forge = if host == gitlab_host || host.contains("gitlab") {
+            ForgeKind::GitLab
+        } else if host.contains("github") {
+            ForgeKind::GitHub
+        } else {
+            return None;
+        };
+        Self::new(forge, &host, path)
+    }
+
+    /// Parse the tool's `repo` parameter: `owner/name` (GitHub),
+    /// `github:owner/name` or `gitlab:group/project`.
+    pub fn from_param(spec: &str, gitlab_host: &str) -> Option<Self> {
+        let spec = spec.trim();
+        if spec.contains("://") || spec.contains('@') {
+            return Self::from_remote(spec, gitlab_host);
+        }
+        match spec.split_once(':') {
+            Some(("gitlab", path)) => Self::new(ForgeKind::GitLab, gitlab_host, path),
+            Some(("github", path)) => Self::new(ForgeKind::GitHub, "github.com", path),
+            Some(_) => None,
+            None => Self::new(ForgeKind::GitHub, "github.com", spec),
+        }
+    }
+
+    fn new(forge: ForgeKind, host: &str, path: &str) -> Option<Self> {
+        let path = path.trim_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path);
+        let segments: Vec<&str> = path.split('/').collect();
+        let valid = segments.len() >= 2
+            && (forge == ForgeKind::GitLab || segments.len() == 2)
+            && segments.iter().all(|s| {
+                !s.is_empty()
+                    && *s != "."
+                    && *s != ".."
+                    && s.chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            });
+        valid.then(|| Self {
+            forge,
+            host: host.to_string(),
+            path: path.to_string(),
+        })
+    }
+
+    /// HTTPS URL git pushes to and fetches from.
+    pub fn clone_url(&self) -> String {
+        format!("https://{}/{}.git", self.host, self.path)
+    }
+
+    /// REST API root: api.github.com, `/api/v3` on GitHub Enterprise
+    /// Server, `/api/v4` on GitLab.
+    fn api_base(&self) -> String {
+        match self.forge {
+            ForgeKind::GitHub if self.host == "github.com" => "https://api.github.com".to_string(),
+            ForgeKind::GitHub => format!("https://{}/api/v3", self.host),
+            ForgeKind::GitLab => format!("https://{}/api/v4", self.host),
+        }
+    }
+
+    /// The repository name without its owner.
+    fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+}
+
+/// Branch and change-set rules for submissions.
+#[derive(Debug, Clone)]
+pub struct ReviewPolicy {
+    protected: Vec<glob::Pattern>,
+    branch_prefix: String,
+    allowed_bases: Vec<glob::Pattern>,
+    blocked_paths: Vec<glob::Pattern>,
+    max_files: usize,
+}
+
+impl ReviewPolicy {
+    pub fn from_config(config: &CodeReviewConfig) -> Self {
+        fn patterns(list: &[String]) -> Vec<glob::Pattern> {
+            list.iter()
+                .filter_map(|p| match glob::Pattern::new(p) {
+                    Ok(pattern) => Some(pattern),
+                    Err(e) => {
+                        tracing::warn!("Ignoring code review pattern '{}': {}", p, e);
+                        None
+                    }
+                })
+                .collect()
+        }
+        Self {
+            protected: patterns(&config.protected_branches),
+            branch_prefix: config.branch_prefix.clone(),
+            allowed_bases: patterns(&config.allowed_bases),
+            blocked_paths: patterns(&config.blocked_paths),
+            max_files: config.max_files,
+        }
+    }
+
+    /// The branch pushed when the caller doesn't name one.
+    pub fn default_branch_for(&self, job_id: Uuid) -> String {
+        format!(
+            "{}job-{}",
+            self.branch_prefix,
+            &job_id.simple().to_string()[..8]
+        )
+    }
+
+    /// Check that `head` may be pushed and a request may target `base`.
+    pub fn check_branches(&self, head: &str, base: &str) -> Result<(), String> {
+        if !valid_branch_name(head) {
+            return Err(format!("'{}' is not a valid branch name", head));
+        }
+        if !head.starts_with(&self.branch_prefix) {
+            return Err(format!(
+                "branch '{}' must start with '{}'",
+                head, self.branch_prefix
+            ));
+        }
+        if let Some(pattern) = self.protected.iter().find(|p| p.matches(head)) {
+            return Err(format!(
+                "branch '{}' is protected (matches '{}')",
+                head, pattern
+            ));
+        }
+        if head == base {
+            return Err("head and base branch are the same".to_string());
+        }
+        if !valid_branch_name(base) {
+            return Err(format!("'{}' is not a valid branch name", base));
+        }
+        if !self.allowed_bases.is_empty() && !self.allowed_bases.iter().any(|p| p.matches(base)) {
+            return Err(format!("requests may not target '{}'", base));
+        }
+        Ok(())
+    }
+
+    /// Check the paths a submission changes.
+    pub fn check_files(&self, files: &[String]) -> Result<(), String> {
+        if files.len() > self.max_files {
+            return Err(format!(
+                "{} files changed, more than the limit of {}",
+                files.len(),
+                self.max_files
+            ));
+        }
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        for file in files {
+            if let Some(pattern) = self
+                .blocked_paths
+                .iter()
+                .find(|p| p.matches_with(file, options))
+            {
+                return Err(format!(
+                    "'{}' may not be changed by a submission (matches '{}')",
+                    file, pattern
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Conservative branch name check: what git accepts, minus anything that
+/// could read as an option or a revision expression.
+fn valid_branch_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 200
+        && !name.starts_with(['-', '/', '.'])
+        && !name.ends_with(['/', '.'])
+        && !name.ends_with(".lock")
+        && !name.contains("..")
+        && !name.contains("//")
+        && !name.contains("/.")
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'))
+}
+
+/// Split "Name <email>" into its parts.
+pub fn parse_author(author: &str) -> Option<(String, String)> {
+    let (name, rest) = author.split_once('<')?;
+    let email = rest.strip_suffix('>')?.trim();
+    let name = name.trim();
+    (!name.is_empty() && email.contains('@')).then(|| (name.to_string(), email.to_string()))
+}
+
+/// Fill `{placeholders}` in a description template.
+fn render_description(template: &str, vars: &[(&str, String)]) -> String {
+    vars.iter()
+        .fold(template.to_string(), |text, (key, value)| {
+            text.replace(&format!("{{{}}}", key), value)
+        })
+}
+
+/// Markdown list of changed files, capped at [`MAX_LISTED_FILES`].
+fn file_list(files: &[String]) -> String {
+    let mut lines: Vec<String> = files
+        .iter()
+        .take(MAX_LISTED_FILES)
+        .map(|f| format!("- `{}`", f))
+        .collect();
+    if files.len() > MAX_LISTED_FILES {
+        lines.push(format!("- ... and {} more", files.len() - MAX_LISTED_FILES));
+    }
+    lines.join("\n")
+}
+
+/// Git in a job's project directory, optionally authenticated.
+struct Git {
+    dir: PathBuf,
+    /// `Authorization` header value for HTTPS remotes.
+    auth_header: Option<String>,
+}
+
+impl Git {
+    fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            auth_header: None,
+        }
+    }
+
+    /// Authenticate HTTPS fetches and pushes with `token` as the password.
+    fn with_token(mut self, forge: ForgeKind, token: &str) -> Self {
+        let user = match forge {
+            ForgeKind::GitHub => "x-access-token",
+            ForgeKind::GitLab => "oauth2",
+        };
+        let basic = base64::engine::general_purpose::STANDARD.encode(format!("{user}:{token}"));
+        self.auth_header = Some(format!("Authorization: Basic {basic}"));
+        self
+    }
+
+    async fn run(&self, args: &[&str]) -> Result<String, ToolError> {
+        let mut command = tokio::process::Command::new("git");
+        command
+            .arg("-C")
+            .arg(&self.dir)
+            .args(args)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .kill_on_drop(true);
+        if let Some(ref header) = self.auth_header {
+            command
+                .env("GIT_CONFIG_COUNT", "1")
+                .env("GIT_CONFIG_KEY_0", "http.extraHeader")
+                .env("GIT_CONFIG_VALUE_0", header);
+        }
+        let output = command
+            .output()
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("cannot run git: {}", e)))?;
+        if !output.status.success() {
+            return Err(ToolError::ExecutionFailed(format!(
+                "git {} failed: {}",
+                args.first().copied().unwrap_or_default(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Fetch `base` from `url` and return its commit.
+    async fn fetch_base(&self, url: &str, base: &str) -> Result<String, ToolError> {
+        self.run(&[
+            "fetch",
+            "--quiet",
+            "--no-tags",
+            url,
+            &format!("refs/heads/{}", base),
+        ])
+        .await?;
+        self.run(&["rev-parse", "FETCH_HEAD"]).await
+    }
+
+    /// Stage everything and list the paths that differ from `base_commit`.
+    async fn stage_all(&self, base_commit: &str) -> Result<Vec<String>, ToolError> {
+        self.run(&["add", "--all"]).await?;
+        let names = self
+            .run(&[
+                "diff",
+                "--cached",
+                "--name-only",
+                "--no-renames",
+                base_commit,
+            ])
+            .await?;
+        Ok(names.lines().map(String::from).collect())
+    }
+
+    /// Move the staged changes onto `branch` and commit them, if any.
+    async fn commit(
+        &self,
+        branch: &str,
+        title: &str,
+        (name, email): &(String, String),
+    ) -> Result<(), ToolError> {
+        self.run(&["checkout", "--quiet", "-B", branch]).await?;
+        if self.run(&["diff", "--cached", "--quiet"]).await.is_err() {
+            self.run(&[
+                "-c",
+                &format!("user.name={}", name),
+                "-c",
+                &format!("user.email={}", email),
+                "commit",
+                "--quiet",
+                "--no-verify",
+                "-m",
+                title,
+            ])
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn push(&self, url: &str, branch: &str) -> Result<(), ToolError> {
+        self.run(&[
+            "push",
+            "--quiet",
+            url,
+            &format!("HEAD:refs/heads/{}", branch),
+        ])
+        .await
+        .map(|_| ())
+    }
+}
+
+/// REST calls to the forge hosting the repository.
+struct ForgeClient<'a> {
+    repo: &'a RepoRef,
+    token: &'a str,
+}
+
+/// An opened pull or merge request.
+struct OpenedRequest {
+    number: u64,
+    url: String,
+}
+
+impl ForgeClient<'_> {
+    fn repo_url(&self) -> String {
+        match self.repo.forge {
+            ForgeKind::GitHub => format!("{}/repos/{}", self.repo.api_base(), self.repo.path),
+            ForgeKind::GitLab => format!(
+                "{}/projects/{}",
+                self.repo.api_base(),
+                urlencoding::encode(&self.repo.path)
+            ),
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, url: String) -> reqwest::RequestBuilder {
+        let request = crate::outbound::client()
+            .request(method, url)
+            .bearer_auth(self.token)
+            .header("User-Agent", "ironclaw")
+            .timeout(Duration::from_secs(30));
+        match self.repo.forge {
+            ForgeKind::GitHub => request.header("Accept", "application/vnd.github+json"),
+            ForgeKind::GitLab => request,
+        }
+    }
+
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<(reqwest::StatusCode, serde_json::Value), ToolError> {
+        let response = request.send().await.map_err(|e| {
+            ToolError::ExternalService(format!("{}: {}", self.repo.forge.as_str(), e))
+        })?;
+        let status = response.status();
+        let body = response.json().await.unwrap_or(serde_json::Value::Null);
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            return Err(ToolError::NotAuthorized(format!(
+                "{} refused the token for {} ({})",
+                self.repo.forge.as_str(),
+                self.repo.path,
+                status
+            )));
+        }
+        Ok((status, body))
+    }
+
+    fn api_error(
+        &self,
+        what: &str,
+        status: reqwest::StatusCode,
+        body: &serde_json::Value,
+    ) -> ToolError {
+        let detail = body
+            .get("message")
+            .map(|m| match m {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .unwrap_or_default();
+        ToolError::ExternalService(format!(
+            "{} {} returned {}: {}",
+            self.repo.forge.as_str(),
+            what,
+            status,
+            detail
+        ))
+    }
+
+    async fn default_branch(&self) -> Result<String, ToolError> {
+        let (status, body) = self
+            .send(self.request(reqwest::Method::GET, self.repo_url()))
+            .await?;
+        if !status.is_success() {
+            return Err(self.api_error("repository lookup", status, &body));
+        }
+        body.get("default_branch")
+            .and_then(|b| b.as_str())
+            .map(String::from)
+            .ok_or_else(|| {
+                ToolError::ExternalService("repository has no default branch".to_string())
+            })
+    }
+
+    /// Whether the forge protects `branch`. A branch that doesn't exist yet
+    /// is protected only if a GitLab wildcard rule covers its name.
+    async fn is_protected(&self, branch: &str) -> Result<bool, ToolError> {
+        let url = match self.repo.forge {
+            ForgeKind::GitHub => format!(
+                "{}/branches/{}",
+                self.repo_url(),
+                urlencoding::encode(branch)
+            ),
+            ForgeKind::GitLab => format!(
+                "{}/protected_branches/{}",
+                self.repo_url(),
+                urlencoding::encode(branch)
+            ),
+        };
+        let (status, body) = self.send(self.request(reqwest::Method::GET, url)).await?;
+        match (self.repo.forge, status) {
+            (_, reqwest::StatusCode::NOT_FOUND) => Ok(false),
+            (ForgeKind::GitHub, s) if s.is_success() => {
+                Ok(body.get("protected").and_then(|p| p.as_bool()) == Some(true))
+            }
+            (ForgeKind::GitLab, s) if s.is_success() => Ok(true),
+            (_, s) => Err(self.api_error("branch protection lookup", s, &body)),
+        }
+    }
+
+    async fn open_request(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        description: &str,
+        draft: bool,
+    ) -> Result<OpenedRequest, ToolError> {
+        let (url, payload) = match self.repo.forge {
+            ForgeKind::GitHub => (
+                format!("{}/pulls", self.repo_url()),
+                serde_json::json!({
+                    "title": title,
+                    "head": head,
+                    "base": base,
+                    "body": description,
+                    "draft": draft,
+                }),
+            ),
+            ForgeKind::GitLab => (
+                format!("{}/merge_requests", self.repo_url()),
+                serde_json::json!({
+                    "title": if draft { format!("Draft: {}", title) } else { title.to_string() },
+                    "source_branch": head,
+                    "target_branch": base,
+                    "description": description,
+                    "remove_source_branch": true,
+                }),
+            ),
+        };
+        let (status, body) = self
+            .send(self.request(reqwest::Method::POST, url).json(&payload))
+            .await?;
+        if !status.is_success() {
+            return Err(self.api_error("request creation", status, &body));
+        }
+        let (number_key, url_key) = match self.repo.forge {
+            ForgeKind::GitHub => ("number", "html_url"),
+            ForgeKind::GitLab => ("iid", "web_url"),
+        };
+        Ok(OpenedRequest {
+            number: body.get(number_key).and_then(|n| n.as_u64()).unwrap_or(0),
+            url: body
+                .get(url_key)
+                .and_then(|u| u.as_str())
+                .unwrap_or_default()
+                .to_string(),
+        })
+    }
+}
+
+/// A token for one submission; minted ones are revoked afterwards.
+struct ForgeToken {
+    value: String,
+    minted: Option<(GitHubAppIssuer, IssuedCredential)>,
+}
+
+impl ForgeToken {
+    async fn release(self) {
+        if let Some((issuer, credential)) = self.minted
+            && let Err(e) = issuer.revoke(&credential).await
+        {
+            tracing::warn!("Failed to revoke code review token: {}", e);
+        }
+    }
+}
+
+/// Builtin `code_review_submit` tool.
+pub struct CodeReviewTool {
+    store: Arc<dyn Database>,
+    config: CodeReviewConfig,
+    policy: ReviewPolicy,
+    secrets: Option<Arc<dyn SecretsStore + Send + Sync>>,
+    github_app: Option<GitHubAppIssuerConfig>,
+    transcript_base: Option<String>,
+}
+
+impl CodeReviewTool {
+    pub fn new(store: Arc<dyn Database>, config: CodeReviewConfig) -> Self {
+        Self {
+            store,
+            policy: ReviewPolicy::from_config(&config),
+            config,
+            secrets: None,
+            github_app: None,
+            transcript_base: None,
+        }
+    }
+
+    /// Read `github_token` / `gitlab_token` from the secrets store.
+    pub fn with_secrets(mut self, secrets: Arc<dyn SecretsStore + Send + Sync>) -> Self {
+        self.secrets = Some(secrets);
+        self
+    }
+
+    /// Mint GitHub installation tokens from this app.
+    pub fn with_github_app(mut self, app: GitHubAppIssuerConfig) -> Self {
+        self.github_app = Some(app);
+        self
+    }
+
+    /// Gateway URL the transcript link points at.
+    pub fn with_transcript_base(mut self, base_url: String) -> Self {
+        self.transcript_base = Some(base_url.trim_end_matches('/').to_string());
+        self
+    }
+
+    fn gitlab_host(&self) -> String {
+        self.config
+            .gitlab_url
+            .split("://")
+            .last()
+            .unwrap_or_default()
+            .trim_end_matches('/')
+            .to_ascii_lowercase()
+    }
+
+    async fn token(
+        &self,
+        repo: &RepoRef,
+        job_id: Uuid,
+        user_id: &str,
+    ) -> Result<ForgeToken, ToolError> {
+        if repo.forge == ForgeKind::GitHub
+            && let Some(ref app) = self.github_app
+        {
+            let pem = std::fs::read_to_string(&app.key_path).map_err(|e| {
+                ToolError::ExecutionFailed(format!("cannot read GitHub App key: {}", e))
+            })?;
+            let permissions = [("contents", "write"), ("pull_requests", "write")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            let mut issuer = GitHubAppIssuer::new(&app.app_id, app.installation_id, &pem)
+                .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?
+                .with_permissions(permissions)
+                .with_repositories(vec![repo.name().to_string()]);
+            if repo.host != "github.com" {
+                issuer = issuer.with_api_base(&repo.api_base());
+            }
+            let credential = issuer
+                .mint(job_id)
+                .await
+                .map_err(|e| ToolError::NotAuthorized(e.to_string()))?;
+            return Ok(ForgeToken {
+                value: credential.secret().to_string(),
+                minted: Some((issuer, credential)),
+            });
+        }
+
+        let secret_name = match repo.forge {
+            ForgeKind::GitHub => GITHUB_TOKEN_SECRET,
+            ForgeKind::GitLab => GITLAB_TOKEN_SECRET,
+        };
+        let secrets = self.secrets.as_ref().ok_or_else(|| {
+            ToolError::NotAuthorized(format!(
+                "no {} credentials: the secrets store is unavailable",
+                repo.forge.as_str()
+            ))
+        })?;
+        let secret = secrets
+            .get_for_tool(user_id, secret_name, "code_review_submit")
+            .await
+            .map_err(|_| {
+                ToolError::NotAuthorized(format!(
+                    "no {} credentials; store a token as the '{}' secret{}",
+                    repo.forge.as_str(),
+                    secret_name,
+                    if repo.forge == ForgeKind::GitHub {
+                        " or configure SANDBOX_GITHUB_APP_*"
+                    } else {
+                        ""
+                    }
+                ))
+            })?;
+        Ok(ForgeToken {
+            value: secret.expose().to_string(),
+            minted: None,
+        })
+    }
+
+    async fn template(&self) -> Result<String, ToolError> {
+        match self.config.template_path {
+            Some(ref path) => tokio::fs::read_to_string(path).await.map_err(|e| {
+                ToolError::ExecutionFailed(format!(
+                    "cannot read description template {}: {}",
+                    path.display(),
+                    e
+                ))
+            }),
+            None => Ok(DEFAULT_TEMPLATE.to_string()),
+        }
+    }
+
+    /// Everything after the token is in hand, so the caller can always
+    /// release it.
+    #[allow(clippy::too_many_arguments)]
+    async fn submit(
+        &self,
+        git: &Git,
+        repo: &RepoRef,
+        token: &str,
+        job_id: Uuid,
+        task: &str,
+        params: &serde_json::Value,
+        title: &str,
+    ) -> Result<serde_json::Value, ToolError> {
+        let client = ForgeClient { repo, token };
+        let head = match params.get("branch").and_then(|v| v.as_str()) {
+            Some(branch) => branch.trim().to_string(),
+            None => self.policy.default_branch_for(job_id),
+        };
+        let base = match params.get("base").and_then(|v| v.as_str()) {
+            Some(base) => base.trim().to_string(),
+            None => client.default_branch().await?,
+        };
+        self.policy
+            .check_branches(&head, &base)
+            .map_err(|e| ToolError::NotAuthorized(format!("policy: {}", e)))?;
+        if client.is_protected(&head).await? {
+            return Err(ToolError::NotAuthorized(format!(
+                "policy: {} protects branch '{}'",
+                repo.forge.as_str(),
+                head
+            )));
+        }
+
+        let url = repo.clone_url();
+        let base_commit = git.fetch_base(&url, &base).await?;
+        let files = git.stage_all(&base_commit).await?;
+        if files.is_empty() {
+            return Err(ToolError::ExecutionFailed(format!(
+                "the job made no changes relative to '{}'",
+                base
+            )));
+        }
+        self.policy
+            .check_files(&files)
+            .map_err(|e| ToolError::NotAuthorized(format!("policy: {}", e)))?;
+
+        let author = parse_author(&self.config.author)
+            .ok_or_else(|| ToolError::ExecutionFailed("invalid CODE_REVIEW_AUTHOR".to_string()))?;
+        git.commit(&head, title, &author).await?;
+        let stats = git
+            .run(&["diff", "--shortstat", &base_commit, "HEAD"])
+            .await?;
+        git.push(&url, &head).await?;
+
+        let transcript_url = match self.transcript_base {
+            Some(ref base_url) => format!("{}/#jobs/{}", base_url, job_id),
+            None => "not available (gateway disabled)".to_string(),
+        };
+        let summary = params
+            .get("summary")
+            .and_then(|v| v.as_str())
+            .unwrap_or(title)
+            .to_string();
+        let description = render_description(
+            &self.template().await?,
+            &[
+                ("summary", summary),
+                ("task", task.to_string()),
+                ("job_id", job_id.to_string()),
+                ("transcript_url", transcript_url),
+                ("files", file_list(&files)),
+                ("file_count", files.len().to_string()),
+                ("stats", stats.clone()),
+                ("branch", head.clone()),
+                ("base", base.clone()),
+            ],
+        );
+        let draft = params
+            .get("draft")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(self.config.draft);
+        let opened = client
+            .open_request(&head, &base, title, &description, draft)
+            .await?;
+
+        Ok(serde_json::json!({
+            "forge": repo.forge.as_str(),
+            "repo": repo.path,
+            "number": opened.number,
+            "url": opened.url,
+            "branch": head,
+            "base": base,
+            "draft": draft,
+            "files_changed": files.len(),
+            "stats": stats,
+        }))
+    }
+}
+
+#[async_trait]
+impl Tool for CodeReviewTool {
+    fn name(&self) -> &str {
+        "code_review_submit"
+    }
+
+    fn description(&self) -> &str {
+        "Submit a finished sandbox job's changes for review: commits them on a new \
+         branch, pushes it and opens a GitHub pull request or GitLab merge request \
+         whose description links to the job transcript. Branch and file policies \
+         apply; protected branches are never pushed to."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "job_id": {
+                    "type": "string",
+                    "description": "Sandbox job whose project directory holds the changes"
+                },
+                "title": {
+                    "type": "string",
+                    "description": "Request title, also the commit message"
+                },
+                "summary": {
+                    "type": "string",
+                    "description": "What changed and why, for the description (Markdown)"
+                },
+                "repo": {
+                    "type": "string",
+                    "description": "owner/name, github:owner/name or gitlab:group/project (default: the project's origin remote)"
+                },
+                "base": {
+                    "type": "string",
+                    "description": "Branch to merge into (default: the repository's default branch)"
+                },
+                "branch": {
+                    "type": "string",
+                    "description": "Branch to push (default: <prefix>job-<id>)"
+                },
+                "draft": {
+                    "type": "boolean",
+                    "description": "Open as a draft (default from configuration)"
+                }
+            },
+            "required": ["job_id", "title"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        ctx: &JobContext,
+    ) -> Result<ToolOutput, ToolError> {
+        let start = std::time::Instant::now();
+        let job_id = params
+            .get("job_id")
+            .and_then(|v| v.as_str())
+            .and_then(|s| Uuid::parse_str(s.trim()).ok())
+            .ok_or_else(|| {
+                ToolError::InvalidParameters("'job_id' must be a job UUID".to_string())
+            })?;
+        let title = params
+            .get("title")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| ToolError::InvalidParameters("missing 'title' parameter".to_string()))?;
+
+        let job = self
+            .store
+            .get_sandbox_job(job_id)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?
+            .ok_or_else(|| ToolError::InvalidParameters(format!("no sandbox job {}", job_id)))?;
+        if job.user_id != ctx.user_id {
+            return Err(ToolError::NotAuthorized(format!(
+                "job {} belongs to another user",
+                job_id
+            )));
+        }
+        if matches!(job.status.as_str(), "creating" | "running") {
+            return Err(ToolError::ExecutionFailed(format!(
+                "job {} is still {}; submit it once it has finished",
+                job_id, job.status
+            )));
+        }
+        let dir = PathBuf::from(&job.project_dir);
+        if !dir.join(".git").exists() {
+            return Err(ToolError::ExecutionFailed(format!(
+                "the project directory of job {} is not a git repository",
+                job_id
+            )));
+        }
+
+        let gitlab_host = self.gitlab_host();
+        let repo = match params.get("repo").and_then(|v| v.as_str()) {
+            Some(spec) => RepoRef::from_param(spec, &gitlab_host).ok_or_else(|| {
+                ToolError::InvalidParameters(format!("cannot parse repository '{}'", spec))
+            })?,
+            None => {
+                let remote = Git::new(&dir)
+                    .run(&["remote", "get-url", "origin"])
+                    .await
+                    .map_err(|_| {
+                        ToolError::InvalidParameters(
+                            "the project has no origin remote; pass 'repo'".to_string(),
+                        )
+                    })?;
+                RepoRef::from_remote(&remote, &gitlab_host).ok_or_else(|| {
+                    ToolError::InvalidParameters(format!(
+                        "origin '{}' is not a GitHub or GitLab repository; pass 'repo'",
+                        remote
+                    ))
+                })?
+            }
+        };
+
+        let token = self.token(&repo, job_id, &ctx.user_id).await?;
+        let git = Git::new(&dir).with_token(repo.forge, &token.value);
+        let result = self
+            .submit(&git, &repo, &token.value, job_id, &job.task, &params, title)
+            .await;
+        token.release().await;
+
+        Ok(ToolOutput::success(result?, start.elapsed()))
+    }
+
+    fn requires_approval(&self) -> bool {
+        true // Publishes code to a remote repository
+    }
+
+    fn execution_timeout(&self) -> Duration {
+        Duration::from_secs(300)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> ReviewPolicy {
+        ReviewPolicy::from_config(&CodeReviewConfig {
+            allowed_bases: vec!["main".to_string(), "release/*".to_string()],
+            max_files: 3,
+            ..CodeReviewConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_repo_ref_from_remote() {
+        let https =
+            RepoRef::from_remote("https://github.com/nearai/ironclaw.git", "gitlab.com").unwrap();
+        assert_eq!(https.forge, ForgeKind::GitHub);
+        assert_eq!(https.path, "nearai/ironclaw");
+        assert_eq!(https.clone_url(), "https://github.com/nearai/ironclaw.git");
+        assert_eq!(https.api_base(), "https://api.github.com");
+
+        let scp =
+            RepoRef::from_remote("git@gitlab.com:group/sub/project.git", "gitlab.com").unwrap();
+        assert_eq!(scp.forge, ForgeKind::GitLab);
+        assert_eq!(scp.path, "group/sub/project");
+        assert_eq!(scp.api_base(), "https://gitlab.com/api/v4");
+
+        let hosted = RepoRef::from_remote(
+            "ssh://git@code.example.com:2222/team/app",
+            "code.example.com",
+        )
+        .unwrap();
+        assert_eq!(hosted.forge, ForgeKind::GitLab);
+        assert_eq!(hosted.host, "code.example.com");
+
+        assert!(RepoRef::from_remote("https://example.com/a/b.git", "gitlab.com").is_none());
+        assert!(RepoRef::from_remote("https://github.com/a/b/c", "gitlab.com").is_none());
+        assert!(RepoRef::from_remote("https://github.com/a/..", "gitlab.com").is_none());
+    }
+
+    #[test]
+    fn test_repo_ref_from_param() {
+        let gh = RepoRef::from_param("owner/name", "gitlab.com").unwrap();
+        assert_eq!(
+            (gh.forge, gh.host.as_str()),
+            (ForgeKind::GitHub, "github.com")
+        );
+        let gl = RepoRef::from_param("gitlab:group/project", "gitlab.example.org").unwrap();
+        assert_eq!(gl.host, "gitlab.example.org");
+        assert!(RepoRef::from_param("bitbucket:a/b", "gitlab.com").is_none());
+        assert!(RepoRef::from_param("just-a-name", "gitlab.com").is_none());
+    }
+
+    #[test]
+    fn test_policy_branches() {
+        let policy = policy();
+        let head = policy.default_branch_for(Uuid::nil());
+        assert_eq!(head, "ironclaw/job-00000000");
+        assert!(policy.check_branches(&head, "main").is_ok());
+        assert!(policy.check_branches(&head, "release/1.2").is_ok());
+
+        // Prefix, protection, base allow-list and name validity
+        assert!(policy.check_branches("feature/x", "main").is_err());
+        assert!(policy.check_branches("ironclaw/x", "develop").is_err());
+        assert!(policy.check_branches("ironclaw/--force", "main").is_ok());
+        assert!(policy.check_branches("ironclaw/a..b", "main").is_err());
+        assert!(policy.check_branches("ironclaw/x", "-main").is_err());
+
+        let open = ReviewPolicy::from_config(&CodeReviewConfig {
+            branch_prefix: String::new(),
+            ..CodeReviewConfig::default()
+        });
+        assert!(open.check_branches("main", "develop").is_err());
+        assert!(open.check_branches("release/2.0", "main").is_err());
+        assert!(open.check_branches("fix", "anything").is_ok());
+    }
+
+    #[test]
+    fn test_policy_files() {
+        let policy = policy();
+        assert!(policy.check_files(&["src/lib.rs".to_string()]).is_ok());
+        let err = policy
+            .check_files(&[".github/workflows/ci.yml".to_string()])
+            .unwrap_err();
+        assert!(err.contains(".github/workflows/*"));
+        assert!(policy.check_files(&[".gitlab-ci.yml".to_string()]).is_err());
+        // `*` does not cross directories
+        assert!(
+            policy
+                .check_files(&[".github/workflows/nested/x.yml".to_string()])
+                .is_ok()
+        );
+        let many: Vec<String> = (0..4).map(|i| format!("f{}", i)).collect();
+        assert!(
+            policy
+                .check_files(&many)
+                .unwrap_err()
+                .contains("limit of 3")
+        );
+    }
+
+    #[test]
+    fn test_parse_author_and_template() {
+        assert_eq!(
+            parse_author("Ada Lovelace <ada@example.com>"),
+            Some(("Ada Lovelace".to_string(), "ada@example.com".to_string()))
+        );
+        assert!(parse_author("no email").is_none());
+        assert!(parse_author("<a@b.c>").is_none());
+
+        let files: Vec<String> = (0..52).map(|i| format!("f{}", i)).collect();
+        let text = render_description(
+            DEFAULT_TEMPLATE,
+            &[
+                ("summary", "Fix it".to_string()),
+                ("task", "fix the bug".to_string()),
+                ("transcript_url", "http://gw/#jobs/1".to_string()),
+                ("files", file_list(&files)),
+            ],
+        );
+        assert!(text.starts_with("Fix it\n"));
+        assert!(text.contains("Transcript: http://gw/#jobs/1"));
+        assert!(text.contains("- `f49`\n- ... and 2 more"));
+        assert!(!text.contains("- `f50`"));
+    }
+
+    async fn git_in(dir: &Path, args: &[&str]) {
+        Git::new(dir).run(args).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_git_stage_commit_and_push() {
+        let tmp = tempfile::tempdir().unwrap();
+        let remote = tmp.path().join("remote.git");
+        let work = tmp.path().join("work");
+        std::fs::create_dir_all(&work).unwrap();
+        git_in(tmp.path(), &["init", "--quiet", "--bare", "remote.git"]).await;
+        git_in(&work, &["init", "--quiet", "-b", "main"]).await;
+        std::fs::write(work.join("README.md"), "hello\n").unwrap();
+        git_in(&work, &["add", "."]).await;
+        git_in(
+            &work,
+            &[
+                "-c",
+                "user.name=T",
+                "-c",
+                "user.email=t@e",
+                "commit",
+                "-qm",
+                "init",
+            ],
+        )
+        .await;
+        let remote_url = remote.display().to_string();
+        git_in(&work, &["push", "--quiet", &remote_url, "main"]).await;
+
+        // The job edits one file and adds another
+        std::fs::write(work.join("README.md"), "hello world\n").unwrap();
+        std::fs::write(work.join("new.txt"), "new\n").unwrap();
+
+        let git = Git::new(&work);
+        let base = git.fetch_base(&remote_url, "main").await.unwrap();
+        let mut files = git.stage_all(&base).await.unwrap();
+        files.sort();
+        assert_eq!(files, vec!["README.md", "new.txt"]);
+
+        let author = ("Bot".to_string(), "bot@example.com".to_string());
+        git.commit("ironclaw/job-1", "Update readme", &author)
+            .await
+            .unwrap();
+        git.push(&remote_url, "ironclaw/job-1").await.unwrap();
+
+        let pushed = Git::new(&remote);
+        let log = pushed
+            .run(&["log", "--format=%an %s", "-1", "ironclaw/job-1"])
+            .await
+            .unwrap();
+        assert_eq!(log, "Bot Update readme");
+        // The branch holds both changes and the worktree is clean
+        assert_eq!(git.stage_all(&base).await.unwrap().len(), 2);
+        assert!(
+            git.run(&["status", "--porcelain"])
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! `delegate` tool: hand a subtask to a scoped sub-agent.
+//!
+//! The work happens in [`SubAgent`]; this tool parses the call, streams the
+//! sub-agent's progress and returns its [`DelegateResult`](crate::agent::delegate::DelegateResult).
+
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use crate::agent::ModelTier;
+use crate::agent::delegate::{DelegateRequest, SubAgent};
+use crate::context::JobContext;
+use crate::tools::tool::{ProgressSender, Tool, ToolError, ToolOutput};
+
+/// Upper bound for a whole delegation; each of its tool calls still has
+/// the tool's own timeout.
+const RUN_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Tool that runs a subtask on a [`SubAgent`].
+pub struct DelegateTool {
+    agent: SubAgent,
+}
+
+impl DelegateTool {
+    pub fn new(agent: SubAgent) -> Self {
+        Self { agent }
+    }
+}
+
+fn parse_request(params: &serde_json::Value) -> Result<DelegateRequest, ToolError> {
+    let task = params
+        .get("task")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| ToolError::InvalidParameters("missing 'task' parameter".to_string()))?;
+    let tools = match params.get("tools") {
+        None | Some(serde_json::Value::Null) => Vec::new(),
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .map(|item| {
+                item.as_str().map(str::to_string).ok_or_else(|| {
+                    ToolError::InvalidParameters("'tools' must be a list of names".to_string())
+                })
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => {
+            return Err(ToolError::InvalidParameters(
+                "'tools' must be a list of names".to_string(),
+            ));
+        }
+    };
+    let tier = match params.get("model").and_then(|v| v.as_str()) {
+        None => None,
+        Some("cheap") => Some(ModelTier::Cheap),
+        Some("primary") => Some(ModelTier::Primary),
+        Some(other) => {
+            return Err(ToolError::InvalidParameters(format!(
+                "unknown model '{}': use 'cheap' or 'primary'",
+                other
+            )));
+        }
+    };
+    Ok(DelegateRequest {
+        task: task.to_string(),
+        context: params
+            .get("context")
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+        tools,
+        max_tokens: params.get("max_tokens").and_then(|v| v.as_u64()),
+        tier,
+    })
+}
+
+#[async_trait]
+impl Tool for DelegateTool {
+    fn name(&self) -> &str {
+        "delegate"
+    }
+
+    fn description(&self) -> &str {
+        "Hand a self-contained subtask to a sub-agent with its own session, a restricted \
+         set of tools and a token budget, optionally on the cheaper model. It sees nothing \
+         of this conversation, so put everything it needs in 'task' and 'context'. Returns \
+         its final answer with a status, the tools it called and the tokens it used. Use it \
+         for lookups and research that would take many tool calls here."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        let config = self.agent.config();
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "task": {
+                    "type": "string",
+                    "description": "What the sub-agent should do and what to report back"
+                },
+                "context": {
+                    "type": "string",
+                    "description": "Background it needs: facts, constraints, prior findings"
+                },
+                "tools": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": format!(
+                        "Tools it may use (default: {}). Tools that need approval are refused.",
+                        config.default_tools.join(", ")
+                    )
+                },
+                "max_tokens": {
+                    "type": "integer",
+                    "description": format!("Token budget (at most {})", config.max_tokens)
+                },
+                "model": {
+                    "type": "string",
+                    "enum": ["cheap", "primary"],
+                    "description": format!("Model to run on (default: {})", config.tier)
+                }
+            },
+            "required": ["task"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        ctx: &JobContext,
+    ) -> Result<ToolOutput, ToolError> {
+        // Nobody is listening; progress sent to a closed channel is dropped.
+        let (progress, _) = tokio::sync::mpsc::unbounded_channel();
+        self.execute_streaming(params, ctx, progress).await
+    }
+
+    async fn execute_streaming(
+        &self,
+        params: serde_json::Value,
+        ctx: &JobContext,
+        progress: ProgressSender,
+    ) -> Result<ToolOutput, ToolError> {
+        let start = Instant::now();
+        let request = parse_request(&params)?;
+        let result = self.agent.run(request, ctx, &progress).await?;
+        let value = serde_json::to_value(&result)
+            .map_err(|e| ToolError::ExecutionFailed(format!("serialize result: {}", e)))?;
+        Ok(ToolOutput::success(value, start.elapsed()))
+    }
+
+    fn execution_timeout(&self) -> Duration {
+        RUN_TIMEOUT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        let request = parse_request(&serde_json::json!({
+            "task": "  find the config file  ",
+            "tools": ["read_file", "list_dir"],
+            "max_tokens": 2000,
+            "model": "primary"
+        }))
+        .unwrap();
+        assert_eq!(request.task, "find the config file");
+        assert_eq!(request.tools, vec!["read_file", "list_dir"]);
+        assert_eq!(request.max_tokens, Some(2000));
+        assert_eq!(request.tier, Some(ModelTier::Primary));
+
+        let defaults = parse_request(&serde_json::json!({"task": "t"})).unwrap();
+        assert!(defaults.tools.is_empty());
+        assert_eq!(defaults.tier, None);
+
+        assert!(parse_request(&serde_json::json!({"task": " "})).is_err());
+        assert!(parse_request(&serde_json::json!({"task": "t", "tools": "shell"})).is_err());
+        assert!(parse_request(&serde_json::json!({"task": "t", "model": "huge"})).is_err());
+    }
+}
@@ -0,0 +1,455 @@
+//! Minimal IMAP4rev1 client: login, folder listing, search, fetch and
+//! flagging. Only what `email_read` needs.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+use super::Credentials;
+use super::net::{Connection, Security, ServerConfig};
+
+/// A parsed IMAP data item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Atom(String),
+    Str(Vec<u8>),
+    Nil,
+    List(Vec<Value>),
+}
+
+impl Value {
+    pub fn as_text(&self) -> Option<String> {
+        match self {
+            Value::Atom(s) => Some(s.clone()),
+            Value::Str(b) => Some(String::from_utf8_lossy(b).into_owned()),
+            _ => None,
+        }
+    }
+}
+
+/// Parse the values of one untagged response (after `* `). Literals
+/// (`{n}\r\n` followed by n bytes) are inlined by the reader, so they are
+/// parsed in place.
+pub fn parse_values(data: &[u8]) -> Vec<Value> {
+    let mut pos = 0;
+    let mut values = Vec::new();
+    while let Some(value) = parse_value(data, &mut pos, 0) {
+        values.push(value);
+    }
+    values
+}
+
+fn parse_value(data: &[u8], pos: &mut usize, depth: usize) -> Option<Value> {
+    while *pos < data.len() && data[*pos] == b' ' {
+        *pos += 1;
+    }
+    let &c = data.get(*pos)?;
+    match c {
+        b'\r' | b'\n' | b')' => None,
+        b'(' if depth < 16 => {
+            *pos += 1;
+            let mut items = Vec::new();
+            while let Some(item) = parse_value(data, pos, depth + 1) {
+                items.push(item);
+            }
+            if data.get(*pos) == Some(&b')') {
+                *pos += 1;
+            }
+            Some(Value::List(items))
+        }
+        b'"' => {
+            *pos += 1;
+            let mut out = Vec::new();
+            while let Some(&b) = data.get(*pos) {
+                *pos += 1;
+                match b {
+                    b'\\' => {
+                        if let Some(&next) = data.get(*pos) {
+                            out.push(next);
+                            *pos += 1;
+                        }
+                    }
+                    b'"' => break,
+                    _ => out.push(b),
+                }
+            }
+            Some(Value::Str(out))
+        }
+        b'{' => {
+            let close = data[*pos..].iter().position(|&b| b == b'}')? + *pos;
+            let len: usize = std::str::from_utf8(&data[*pos + 1..close])
+                .ok()?
+                .trim_end_matches('+')
+                .parse()
+                .ok()?;
+            let mut start = close + 1;
+            if data.get(start) == Some(&b'\r') {
+                start += 1;
+            }
+            if data.get(start) == Some(&b'\n') {
+                start += 1;
+            }
+            let end = (start + len).min(data.len());
+            *pos = end;
+            Some(Value::Str(data[start..end].to_vec()))
+        }
+        _ => {
+            let start = *pos;
+            while let Some(&b) = data.get(*pos) {
+                match b {
+                    // Section specs like BODY[HEADER.FIELDS (FROM)] stay one atom.
+                    b'[' => {
+                        let close = data[*pos..]
+                            .iter()
+                            .position(|&b| b == b']')
+                            .map_or(data.len(), |p| *pos + p + 1);
+                        *pos = close;
+                    }
+                    b' ' | b'(' | b')' | b'\r' | b'\n' | b'"' | b'{' => break,
+                    _ => *pos += 1,
+                }
+            }
+            let atom = String::from_utf8_lossy(&data[start..*pos]).into_owned();
+            if atom.eq_ignore_ascii_case("NIL") {
+                Some(Value::Nil)
+            } else {
+                Some(Value::Atom(atom))
+            }
+        }
+    }
+}
+
+/// Quote a string argument. Line breaks cannot be sent in a quoted string.
+pub fn quote(s: &str) -> Result<String, String> {
+    if s.contains(['\r', '\n', '\0']) {
+        return Err("IMAP arguments may not contain line breaks".to_string());
+    }
+    Ok(format!(
+        "\"{}\"",
+        s.replace('\\', "\\\\").replace('"', "\\\"")
+    ))
+}
+
+/// One message from a FETCH response.
+#[derive(Debug, Clone, Default)]
+pub struct Fetched {
+    pub uid: u32,
+    pub flags: Vec<String>,
+    pub size: Option<u64>,
+    /// Bytes of the requested `BODY[...]` section.
+    pub body: Vec<u8>,
+}
+
+impl Fetched {
+    fn from_items(items: &[Value]) -> Self {
+        let mut fetched = Self::default();
+        for pair in items.chunks(2) {
+            let [Value::Atom(key), value] = pair else {
+                continue;
+            };
+            let key = key.to_ascii_uppercase();
+            match (key.as_str(), value) {
+                ("UID", Value::Atom(n)) => fetched.uid = n.parse().unwrap_or(0),
+                ("RFC822.SIZE", Value::Atom(n)) => fetched.size = n.parse().ok(),
+                ("FLAGS", Value::List(flags)) => {
+                    fetched.flags = flags.iter().filter_map(Value::as_text).collect()
+                }
+                (k, Value::Str(bytes)) if k.starts_with("BODY[") => fetched.body = bytes.clone(),
+                _ => {}
+            }
+        }
+        fetched
+    }
+}
+
+/// An authenticated IMAP session.
+pub struct ImapSession {
+    conn: Connection,
+    tag: u32,
+    max_literal: usize,
+}
+
+impl ImapSession {
+    /// Connect and log in. `max_literal` caps any single literal the server
+    /// sends (i.e. the largest message that can be fetched).
+    pub async fn login(
+        server: &ServerConfig,
+        credentials: &Credentials,
+        max_literal: usize,
+    ) -> Result<Self, String> {
+        let mut conn = Connection::open(server).await?;
+        let greeting = conn.read_line().await?;
+        if !greeting.starts_with(b"* OK") && !greeting.starts_with(b"* PREAUTH") {
+            return Err(format!(
+                "unexpected IMAP greeting: {}",
+                String::from_utf8_lossy(&greeting).trim()
+            ));
+        }
+        let mut session = Self {
+            conn,
+            tag: 0,
+            max_literal,
+        };
+        if server.security == Security::Starttls {
+            session.command("STARTTLS").await?;
+            session.conn = session.conn.start_tls().await?;
+        }
+        let login = match credentials {
+            Credentials::Password { username, password } => {
+                format!("LOGIN {} {}", quote(username)?, quote(password)?)
+            }
+            Credentials::OAuth2 { username, token } => format!(
+                "AUTHENTICATE XOAUTH2 {}",
+                BASE64.encode(format!(
+                    "user={}\x01auth=Bearer {}\x01\x01",
+                    username, token
+                ))
+            ),
+        };
+        session
+            .command(&login)
+            .await
+            .map_err(|e| format!("IMAP login failed: {}", e))?;
+        Ok(session)
+    }
+
+    /// Send a command and collect its untagged responses (without `* `).
+    pub async fn command(&mut self, command: &str) -> Result<Vec<Vec<u8>>, String> {
+        self.tag += 1;
+        let tag = format!("A{}", self.tag);
+        self.conn
+            .write_all(format!("{} {}\r\n", tag, command).as_bytes())
+            .await?;
+
+        let mut responses = Vec::new();
+        loop {
+            let mut response = self.conn.read_line().await?;
+            // Pull in literals announced at the end of the line.
+            while let Some(len) = trailing_literal(&response) {
+                if len > self.max_literal {
+                    return Err(format!(
+                        "server sent {} bytes, over the {} byte limit",
+                        len, self.max_literal
+                    ));
+                }
+                response.extend(self.conn.read_exact(len).await?);
+                response.extend(self.conn.read_line().await?);
+            }
+
+            if let Some(rest) = response.strip_prefix(b"* ") {
+                responses.push(rest.to_vec());
+            } else if response.starts_with(b"+") {
+                // A challenge here means the server rejected SASL-IR
+                // credentials; an empty reply makes it send the tagged NO.
+                self.conn.write_all(b"\r\n").await?;
+            } else if let Some(rest) = response.strip_prefix(tag.as_bytes()) {
+                let status = String::from_utf8_lossy(rest).trim().to_string();
+                return if status.starts_with("OK") {
+                    Ok(responses)
+                } else {
+                    Err(status)
+                };
+            }
+        }
+    }
+
+    /// All folder names.
+    pub async fn list_folders(&mut self) -> Result<Vec<String>, String> {
+        let responses = self.command("LIST \"\" \"*\"").await?;
+        Ok(responses
+            .iter()
+            .filter_map(|r| {
+                let values = parse_values(r);
+                match values.as_slice() {
+                    [Value::Atom(kind), Value::List(attrs), _, name]
+                        if kind.eq_ignore_ascii_case("LIST") =>
+                    {
+                        let selectable = !attrs.iter().any(|a| {
+                            a.as_text()
+                                .is_some_and(|a| a.eq_ignore_ascii_case("\\Noselect"))
+                        });
+                        selectable.then(|| name.as_text()).flatten()
+                    }
+                    _ => None,
+                }
+            })
+            .collect())
+    }
+
+    /// Open `folder`; read-only unless flags will be changed.
+    pub async fn open_folder(&mut self, folder: &str, writable: bool) -> Result<(), String> {
+        let verb = if writable { "SELECT" } else { "EXAMINE" };
+        self.command(&format!("{} {}", verb, quote(folder)?))
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("cannot open folder '{}': {}", folder, e))
+    }
+
+    /// UIDs matching `criteria` (an IMAP search key such as `UNSEEN`).
+    pub async fn search(&mut self, criteria: &str) -> Result<Vec<u32>, String> {
+        let responses = self.command(&format!("UID SEARCH {}", criteria)).await?;
+        let mut uids: Vec<u32> = responses
+            .iter()
+            .filter_map(|r| r.strip_prefix(b"SEARCH"))
+            .flat_map(|rest| {
+                String::from_utf8_lossy(rest)
+                    .split_whitespace()
+                    .filter_map(|n| n.parse().ok())
+                    .collect::<Vec<u32>>()
+            })
+            .collect();
+        uids.sort_unstable();
+        Ok(uids)
+    }
+
+    /// Fetch `items` (e.g. `(UID FLAGS BODY.PEEK[HEADER])`) for `uids`.
+    pub async fn fetch(&mut self, uids: &[u32], items: &str) -> Result<Vec<Fetched>, String> {
+        if uids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let set = uids
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        let responses = self
+            .command(&format!("UID FETCH {} {}", set, items))
+            .await?;
+        Ok(responses
+            .iter()
+            .filter_map(|r| {
+                let values = parse_values(r);
+                match values.as_slice() {
+                    [Value::Atom(_), Value::Atom(kind), Value::List(items)]
+                        if kind.eq_ignore_ascii_case("FETCH") =>
+                    {
+                        Some(Fetched::from_items(items))
+                    }
+                    _ => None,
+                }
+            })
+            .filter(|f| f.uid != 0)
+            .collect())
+    }
+
+    /// Mark a message as read.
+    pub async fn mark_seen(&mut self, uid: u32) -> Result<(), String> {
+        self.command(&format!("UID STORE {} +FLAGS.SILENT (\\Seen)", uid))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn logout(mut self) {
+        let _ = self.command("LOGOUT").await;
+    }
+}
+
+/// Length of a `{n}` literal that ends `line`, if any.
+fn trailing_literal(line: &[u8]) -> Option<usize> {
+    let line = line.strip_suffix(b"\r\n").or(line.strip_suffix(b"\n"))?;
+    let body = line.strip_suffix(b"}")?;
+    let open = body.iter().rposition(|&b| b == b'{')?;
+    std::str::from_utf8(&body[open + 1..])
+        .ok()?
+        .trim_end_matches('+')
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fetch_with_literal() {
+        let raw = b"12 FETCH (UID 4827 FLAGS (\\Seen \\Flagged) RFC822.SIZE 3021 \
+BODY[HEADER.FIELDS (FROM SUBJECT)] {30}\r\nFrom: a@b.com\r\nSubject: Hi\r\n\r\n)\r\n";
+        let values = parse_values(raw);
+        let [Value::Atom(seq), Value::Atom(kind), Value::List(items)] = values.as_slice() else {
+            panic!("unexpected parse: {:?}", values);
+        };
+        assert_eq!((seq.as_str(), kind.as_str()), ("12", "FETCH"));
+        let fetched = Fetched::from_items(items);
+        assert_eq!(fetched.uid, 4827);
+        assert_eq!(fetched.size, Some(3021));
+        assert_eq!(fetched.flags, vec!["\\Seen", "\\Flagged"]);
+        assert_eq!(fetched.body, b"From: a@b.com\r\nSubject: Hi\r\n\r\n");
+    }
+
+    #[test]
+    fn test_parse_list_and_literal_detection() {
+        let values = parse_values(b"LIST (\\HasNoChildren) \"/\" \"Sent \\\"Mail\\\"\"\r\n");
+        assert_eq!(values[3], Value::Str(b"Sent \"Mail\"".to_vec()));
+        let values = parse_values(b"LIST (\\Noselect) NIL INBOX\r\n");
+        assert_eq!(values[2], Value::Nil);
+        assert_eq!(values[3], Value::Atom("INBOX".into()));
+
+        assert_eq!(trailing_literal(b"* 1 FETCH (BODY[] {120}\r\n"), Some(120));
+        assert_eq!(trailing_literal(b"* OK done\r\n"), None);
+        assert_eq!(quote("a\"b\\c").unwrap(), "\"a\\\"b\\\\c\"");
+        assert!(quote("x\r\nA2 LOGOUT").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_session_against_fake_server() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut lines = BufReader::new(read).lines();
+            write.write_all(b"* OK ready\r\n").await.unwrap();
+            let mut commands = Vec::new();
+            while let Some(line) = lines.next_line().await.unwrap() {
+                let (tag, cmd) = line.split_once(' ').unwrap();
+                let reply: &[u8] = if cmd.starts_with("LOGIN") {
+                    b""
+                } else if cmd.starts_with("EXAMINE") {
+                    b"* 3 EXISTS\r\n"
+                } else if cmd.starts_with("UID SEARCH") {
+                    b"* SEARCH 7 3\r\n"
+                } else if cmd.starts_with("UID FETCH") {
+                    b"* 1 FETCH (UID 3 BODY[] {11}\r\nSubject: \r\n)\r\n"
+                } else {
+                    b""
+                };
+                commands.push(cmd.to_string());
+                write.write_all(reply).await.unwrap();
+                write
+                    .write_all(format!("{} OK done\r\n", tag).as_bytes())
+                    .await
+                    .unwrap();
+                if cmd == "LOGOUT" {
+                    break;
+                }
+            }
+            commands
+        });
+
+        let mut session = ImapSession::login(
+            &ServerConfig {
+                host: "127.0.0.1".into(),
+                port,
+                security: Security::Plain,
+            },
+            &Credentials::Password {
+                username: "me@example.com".into(),
+                password: "p\"w".into(),
+            },
+            1024,
+        )
+        .await
+        .unwrap();
+        session.open_folder("INBOX", false).await.unwrap();
+        assert_eq!(session.search("UNSEEN").await.unwrap(), vec![3, 7]);
+        let fetched = session.fetch(&[3], "(UID BODY.PEEK[])").await.unwrap();
+        assert_eq!(fetched[0].body, b"Subject: \r\n");
+        session.logout().await;
+
+        let commands = server.await.unwrap();
+        assert_eq!(commands[0], "LOGIN \"me@example.com\" \"p\\\"w\"");
+        assert_eq!(commands[1], "EXAMINE \"INBOX\"");
+        assert_eq!(commands[3], "UID FETCH 3 (UID BODY.PEEK[])");
+    }
+}
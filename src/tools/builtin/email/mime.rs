@@ -0,0 +1,514 @@
+//! Just enough MIME to read and write mail.
+//!
+//! Parsing handles folded headers, RFC 2047 encoded words, nested
+//! multiparts, base64 and quoted-printable bodies, and UTF-8 or Latin-1
+//! text. Building emits a single `text/plain` part.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+/// A parsed message.
+#[derive(Debug, Default, Clone)]
+pub struct ParsedMessage {
+    /// Unfolded headers in order, values decoded.
+    pub headers: Vec<(String, String)>,
+    /// First `text/plain` body part.
+    pub text: Option<String>,
+    /// First `text/html` body part.
+    pub html: Option<String>,
+    pub attachments: Vec<MimeAttachment>,
+}
+
+impl ParsedMessage {
+    /// First header named `name` (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Readable body: the text part, or the HTML part converted to Markdown.
+    pub fn body_text(&self) -> String {
+        match (&self.text, &self.html) {
+            (Some(text), _) if !text.trim().is_empty() => text.clone(),
+            (_, Some(html)) => crate::media::html_to_markdown(html),
+            (Some(text), None) => text.clone(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// A non-body part.
+#[derive(Debug, Clone)]
+pub struct MimeAttachment {
+    pub filename: Option<String>,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Split raw bytes into header block and body.
+fn split_head(raw: &[u8]) -> (&[u8], &[u8]) {
+    for (i, w) in raw.windows(2).enumerate() {
+        if w == b"\n\n" {
+            return (&raw[..i], &raw[i + 2..]);
+        }
+        if i + 4 <= raw.len() && &raw[i..i + 4] == b"\r\n\r\n" {
+            return (&raw[..i], &raw[i + 4..]);
+        }
+    }
+    (raw, &[])
+}
+
+/// Unfold and decode a header block.
+pub fn parse_headers(block: &[u8]) -> Vec<(String, String)> {
+    let text = String::from_utf8_lossy(block);
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in text.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    for (_, value) in &mut headers {
+        *value = decode_words(value);
+    }
+    headers
+}
+
+/// Decode RFC 2047 encoded words (`=?utf-8?B?...?=`). Whitespace between
+/// adjacent encoded words is dropped, as the RFC requires.
+pub fn decode_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut last_was_word = false;
+    while let Some(start) = rest.find("=?") {
+        let (before, after) = rest.split_at(start);
+        let decoded = after[2..].split_once('?').and_then(|(charset, tail)| {
+            let (encoding, tail) = tail.split_once('?')?;
+            let end = tail.find("?=")?;
+            let bytes = match encoding {
+                "B" | "b" => BASE64.decode(tail[..end].trim()).ok()?,
+                "Q" | "q" => decode_qp(tail[..end].replace('_', " ").as_bytes()),
+                _ => return None,
+            };
+            Some((decode_charset(&bytes, charset), &tail[end + 2..]))
+        });
+        match decoded {
+            Some((text, tail)) => {
+                if !(last_was_word && before.trim().is_empty()) {
+                    out.push_str(before);
+                }
+                out.push_str(&text);
+                rest = tail;
+                last_was_word = true;
+            }
+            None => {
+                out.push_str(before);
+                out.push_str("=?");
+                rest = &after[2..];
+                last_was_word = false;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Decode quoted-printable data.
+pub fn decode_qp(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        if data[i] == b'=' {
+            // Soft line break
+            if data.get(i + 1) == Some(&b'\r') && data.get(i + 2) == Some(&b'\n') {
+                i += 3;
+                continue;
+            }
+            if data.get(i + 1) == Some(&b'\n') {
+                i += 2;
+                continue;
+            }
+            if let Some(hex) = data.get(i + 1..i + 3)
+                && let Ok(s) = std::str::from_utf8(hex)
+                && let Ok(byte) = u8::from_str_radix(s, 16)
+            {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(data[i]);
+        i += 1;
+    }
+    out
+}
+
+/// Text from bytes in `charset`; unknown charsets are read as UTF-8.
+fn decode_charset(bytes: &[u8], charset: &str) -> String {
+    let charset = charset.to_ascii_lowercase();
+    if matches!(
+        charset.as_str(),
+        "iso-8859-1" | "latin1" | "iso8859-1" | "windows-1252" | "cp1252" | "us-ascii"
+    ) && std::str::from_utf8(bytes).is_err()
+    {
+        return bytes.iter().map(|&b| b as char).collect();
+    }
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// `type/subtype` (lowercased) and parameters of a Content-Type or
+/// Content-Disposition value.
+fn parse_params(value: &str) -> (String, Vec<(String, String)>) {
+    let mut parts = value.split(';');
+    let main = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+    let params = parts
+        .filter_map(|p| {
+            let (k, v) = p.split_once('=')?;
+            let k = k.trim().to_ascii_lowercase();
+            let v = v.trim().trim_matches('"').to_string();
+            // RFC 2231: filename*=utf-8''na%C3%AFve.txt
+            if let Some(k) = k.strip_suffix('*') {
+                let encoded = v.rsplit('\'').next().unwrap_or(&v);
+                let decoded = urlencoding::decode(encoded)
+                    .map(|d| d.into_owned())
+                    .unwrap_or_else(|_| encoded.to_string());
+                return Some((k.to_string(), decoded));
+            }
+            Some((k, v))
+        })
+        .collect();
+    (main, params)
+}
+
+fn param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v.as_str())
+}
+
+/// Parse a full RFC 5322 message.
+pub fn parse_message(raw: &[u8]) -> ParsedMessage {
+    let (head, body) = split_head(raw);
+    let mut message = ParsedMessage {
+        headers: parse_headers(head),
+        ..Default::default()
+    };
+    let headers = message.headers.clone();
+    collect_part(&headers, body, &mut message, 0);
+    message
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+fn collect_part(
+    headers: &[(String, String)],
+    body: &[u8],
+    message: &mut ParsedMessage,
+    depth: usize,
+) {
+    let (content_type, type_params) =
+        parse_params(header(headers, "content-type").unwrap_or("text/plain"));
+    if content_type.starts_with("multipart/") && depth < 8 {
+        if let Some(boundary) = param(&type_params, "boundary") {
+            for part in split_multipart(body, boundary) {
+                let (head, body) = split_head(part);
+                collect_part(&parse_headers(head), body, message, depth + 1);
+            }
+        }
+        return;
+    }
+
+    let data = match header(headers, "content-transfer-encoding")
+        .map(|e| e.trim().to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("base64") => {
+            let compact: Vec<u8> = body
+                .iter()
+                .copied()
+                .filter(|b| !b.is_ascii_whitespace())
+                .collect();
+            BASE64.decode(compact).unwrap_or_default()
+        }
+        Some("quoted-printable") => decode_qp(body),
+        _ => body.to_vec(),
+    };
+
+    let (disposition, disp_params) =
+        parse_params(header(headers, "content-disposition").unwrap_or(""));
+    let filename = param(&disp_params, "filename")
+        .or_else(|| param(&type_params, "name"))
+        .map(decode_words);
+    let is_body = disposition != "attachment"
+        && filename.is_none()
+        && (content_type == "text/plain" || content_type == "text/html");
+
+    if is_body {
+        let text = decode_charset(&data, param(&type_params, "charset").unwrap_or("utf-8"));
+        let slot = if content_type == "text/html" {
+            &mut message.html
+        } else {
+            &mut message.text
+        };
+        if slot.is_none() {
+            *slot = Some(text);
+        }
+    } else if content_type == "message/rfc822" && filename.is_none() {
+        // Forwarded messages count as attachments so they can be fetched.
+        message.attachments.push(MimeAttachment {
+            filename: Some("forwarded.eml".to_string()),
+            content_type,
+            data,
+        });
+    } else {
+        message.attachments.push(MimeAttachment {
+            filename,
+            content_type,
+            data,
+        });
+    }
+}
+
+/// Body parts between `--boundary` delimiters.
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{}", boundary);
+    let delimiter = delimiter.as_bytes();
+    let mut parts = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut line_start = 0;
+    while line_start < body.len() {
+        let line_end = body[line_start..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(body.len(), |p| line_start + p + 1);
+        let line = &body[line_start..line_end];
+        let trimmed = line.strip_suffix(b"\n").unwrap_or(line);
+        let trimmed = trimmed.strip_suffix(b"\r").unwrap_or(trimmed);
+        if trimmed.starts_with(delimiter) {
+            if let Some(s) = start {
+                // The line break before a delimiter belongs to the delimiter.
+                let mut end = line_start;
+                if end > s && body[end - 1] == b'\n' {
+                    end -= 1;
+                    if end > s && body[end - 1] == b'\r' {
+                        end -= 1;
+                    }
+                }
+                parts.push(&body[s..end]);
+            }
+            if trimmed[delimiter.len()..].starts_with(b"--") {
+                return parts;
+            }
+            start = Some(line_end);
+        }
+        line_start = line_end;
+    }
+    parts
+}
+
+/// Whether `addr` looks like a bare `local@domain` address.
+pub fn valid_address(addr: &str) -> bool {
+    let Some((local, domain)) = addr.rsplit_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && addr.len() <= 254
+        && !addr
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || "<>,;:\"()[]\\".contains(c))
+}
+
+/// Encode a header value as RFC 2047 words when it is not plain ASCII.
+fn encode_header_value(value: &str) -> String {
+    if value.is_ascii() {
+        value.to_string()
+    } else {
+        format!("=?UTF-8?B?{}?=", BASE64.encode(value.as_bytes()))
+    }
+}
+
+/// `Name <addr>` or `addr`.
+fn mailbox(name: Option<&str>, addr: &str) -> String {
+    match name.map(str::trim).filter(|n| !n.is_empty()) {
+        Some(name) if name.is_ascii() => {
+            format!(
+                "\"{}\" <{}>",
+                name.replace('\\', "\\\\").replace('"', "\\\""),
+                addr
+            )
+        }
+        Some(name) => format!("{} <{}>", encode_header_value(name), addr),
+        None => addr.to_string(),
+    }
+}
+
+/// An outgoing plain-text message.
+#[derive(Debug, Clone, Default)]
+pub struct OutgoingMessage {
+    pub from: String,
+    pub from_name: Option<String>,
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub subject: String,
+    pub body: String,
+    /// Message-ID being replied to.
+    pub in_reply_to: Option<String>,
+}
+
+impl OutgoingMessage {
+    /// Every envelope recipient (To and Cc; Bcc is passed separately).
+    pub fn recipients(&self) -> impl Iterator<Item = &String> {
+        self.to.iter().chain(self.cc.iter())
+    }
+
+    /// Render as RFC 5322 text with CRLF line endings. Fails on header
+    /// injection attempts or invalid addresses.
+    pub fn render(
+        &self,
+        message_id: &str,
+        date: chrono::DateTime<chrono::Utc>,
+    ) -> Result<String, String> {
+        for addr in std::iter::once(&self.from).chain(self.recipients()) {
+            if !valid_address(addr) {
+                return Err(format!("invalid address '{}'", addr));
+            }
+        }
+        for value in [
+            Some(&self.subject),
+            self.in_reply_to.as_ref(),
+            self.from_name.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if value.contains(['\r', '\n']) {
+                return Err("header values may not contain line breaks".to_string());
+            }
+        }
+
+        let mut out = String::new();
+        out.push_str(&format!("Date: {}\r\n", date.to_rfc2822()));
+        out.push_str(&format!(
+            "From: {}\r\n",
+            mailbox(self.from_name.as_deref(), &self.from)
+        ));
+        out.push_str(&format!("To: {}\r\n", self.to.join(", ")));
+        if !self.cc.is_empty() {
+            out.push_str(&format!("Cc: {}\r\n", self.cc.join(", ")));
+        }
+        out.push_str(&format!(
+            "Subject: {}\r\n",
+            encode_header_value(&self.subject)
+        ));
+        out.push_str(&format!("Message-ID: {}\r\n", message_id));
+        if let Some(ref parent) = self.in_reply_to {
+            out.push_str(&format!(
+                "In-Reply-To: {}\r\nReferences: {}\r\n",
+                parent, parent
+            ));
+        }
+        out.push_str("MIME-Version: 1.0\r\n");
+        out.push_str("Content-Type: text/plain; charset=utf-8\r\n");
+        out.push_str("Content-Transfer-Encoding: base64\r\n\r\n");
+        let encoded = BASE64.encode(self.body.replace("\r\n", "\n").replace('\n', "\r\n"));
+        for chunk in encoded.as_bytes().chunks(76) {
+            out.push_str(std::str::from_utf8(chunk).unwrap_or_default());
+            out.push_str("\r\n");
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multipart_with_attachment() {
+        let raw = b"From: =?UTF-8?B?Sm9zw6k=?= <jose@example.com>\r\n\
+Subject: =?utf-8?Q?Caf=C3=A9?= =?utf-8?Q?_menu?=\r\n\
+Content-Type: multipart/mixed;\r\n boundary=\"outer\"\r\n\
+\r\n\
+preamble\r\n\
+--outer\r\n\
+Content-Type: multipart/alternative; boundary=inner\r\n\
+\r\n\
+--inner\r\n\
+Content-Type: text/plain; charset=utf-8\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+\r\n\
+Soup of the day: lentil=\r\n\
+\x20=E2=80=94 4=E2=82=AC\r\n\
+--inner\r\n\
+Content-Type: text/html\r\n\
+\r\n\
+<p>Soup</p>\r\n\
+--inner--\r\n\
+--outer\r\n\
+Content-Type: application/pdf; name=\"menu.pdf\"\r\n\
+Content-Disposition: attachment; filename*=utf-8''men%C3%BC.pdf\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+JVBERi0x\r\n\
+LjQK\r\n\
+--outer--\r\n";
+        let msg = parse_message(raw);
+        assert_eq!(msg.header("from"), Some("José <jose@example.com>"));
+        assert_eq!(msg.header("Subject"), Some("Café menu"));
+        assert_eq!(msg.text.as_deref(), Some("Soup of the day: lentil — 4€"));
+        assert_eq!(msg.html.as_deref(), Some("<p>Soup</p>"));
+        assert_eq!(msg.attachments.len(), 1);
+        let pdf = &msg.attachments[0];
+        assert_eq!(pdf.filename.as_deref(), Some("menü.pdf"));
+        assert_eq!(pdf.content_type, "application/pdf");
+        assert_eq!(pdf.data, b"%PDF-1.4\n");
+    }
+
+    #[test]
+    fn test_html_only_body_is_converted() {
+        let raw = b"Subject: hi\nContent-Type: text/html; charset=iso-8859-1\n\n<p>Ol\xe1 <b>mundo</b></p>";
+        let msg = parse_message(raw);
+        let body = msg.body_text();
+        assert!(body.contains("Olá"), "{body}");
+        assert!(!body.contains("<p>"));
+    }
+
+    #[test]
+    fn test_render_rejects_injection_and_encodes() {
+        let mut out = OutgoingMessage {
+            from: "me@example.com".into(),
+            from_name: Some("Zoë".into()),
+            to: vec!["you@example.org".into()],
+            subject: "Résumé".into(),
+            body: "line one\nline two".into(),
+            ..Default::default()
+        };
+        let text = out.render("<1@example.com>", chrono::Utc::now()).unwrap();
+        assert!(text.contains("Subject: =?UTF-8?B?"));
+        assert!(text.contains("From: =?UTF-8?B?"));
+        let round = parse_message(text.as_bytes());
+        assert_eq!(round.header("subject"), Some("Résumé"));
+        assert_eq!(round.text.as_deref(), Some("line one\r\nline two"));
+
+        out.subject = "hi\r\nBcc: everyone@example.com".into();
+        assert!(out.render("<2@example.com>", chrono::Utc::now()).is_err());
+        out.subject = "hi".into();
+        out.to = vec!["a@b.com, c@d.com".into()];
+        assert!(out.render("<3@example.com>", chrono::Utc::now()).is_err());
+    }
+}
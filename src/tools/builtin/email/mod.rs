@@ -0,0 +1,954 @@
+//! Email tools over IMAP and SMTP.
+//!
+//! Accounts live in the encrypted secrets store as `email_<name>` (provider
+//! `email`), holding JSON with the address, server settings and either an
+//! app password or OAuth2 tokens. Register them with
+//! `ironclaw tool email add`; the model only ever sees account names.
+//!
+//! `email_read` lists folders, searches, reads messages and hands
+//! attachments to the attachment pipeline. `email_send` always needs
+//! approval, and the agent loop refuses to auto-approve it, so every
+//! outgoing message is confirmed by the user.
+
+mod imap;
+mod mime;
+mod net;
+mod smtp;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+pub use net::{Security, ServerConfig};
+
+use crate::config::EmailConfig;
+use crate::context::JobContext;
+use crate::media::{Attachment, AttachmentPipeline, AttachmentSource};
+use crate::secrets::{CreateSecretParams, SecretsStore};
+use crate::tools::tool::{Tool, ToolError, ToolOutput};
+use imap::ImapSession;
+
+/// Secret name prefix for email accounts.
+pub const EMAIL_SECRET_PREFIX: &str = "email_";
+/// Provider of email account secrets.
+pub const EMAIL_PROVIDER: &str = "email";
+
+/// Header fields fetched for search results.
+const SUMMARY_ITEMS: &str =
+    "(UID FLAGS RFC822.SIZE BODY.PEEK[HEADER.FIELDS (FROM TO SUBJECT DATE MESSAGE-ID)])";
+
+/// How the account authenticates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EmailAuth {
+    /// Account or app-specific password.
+    Password { password: String },
+    /// OAuth2 bearer token (XOAUTH2), refreshed when it expires.
+    Oauth2 {
+        access_token: String,
+        #[serde(default)]
+        refresh_token: Option<String>,
+        #[serde(default)]
+        client_id: Option<String>,
+        #[serde(default)]
+        client_secret: Option<String>,
+        #[serde(default)]
+        token_url: Option<String>,
+        #[serde(default)]
+        expires_at: Option<DateTime<Utc>>,
+    },
+}
+
+/// A registered account, as stored in its secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailAccount {
+    pub address: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Login name when it differs from the address.
+    #[serde(default)]
+    pub username: Option<String>,
+    pub imap: ServerConfig,
+    pub smtp: ServerConfig,
+    pub auth: EmailAuth,
+}
+
+/// Login material for one connection.
+#[derive(Debug, Clone)]
+pub enum Credentials {
+    Password { username: String, password: String },
+    OAuth2 { username: String, token: String },
+}
+
+impl EmailAccount {
+    pub fn username(&self) -> &str {
+        self.username.as_deref().unwrap_or(&self.address)
+    }
+
+    fn credentials(&self) -> Credentials {
+        let username = self.username().to_string();
+        match &self.auth {
+            EmailAuth::Password { password } => Credentials::Password {
+                username,
+                password: password.clone(),
+            },
+            EmailAuth::Oauth2 { access_token, .. } => Credentials::OAuth2 {
+                username,
+                token: access_token.clone(),
+            },
+        }
+    }
+
+    /// Whether the OAuth2 token expires within a minute and can be renewed.
+    fn needs_refresh(&self, now: DateTime<Utc>) -> bool {
+        match &self.auth {
+            EmailAuth::Oauth2 {
+                refresh_token: Some(_),
+                token_url: Some(_),
+                expires_at: Some(expires_at),
+                ..
+            } => *expires_at <= now + chrono::Duration::seconds(60),
+            _ => false,
+        }
+    }
+
+    /// Exchange the refresh token for a new access token.
+    async fn refresh(&mut self, http: &reqwest::Client) -> Result<(), String> {
+        let EmailAuth::Oauth2 {
+            access_token,
+            refresh_token: Some(refresh_token),
+            client_id,
+            client_secret,
+            token_url: Some(token_url),
+            expires_at,
+        } = &mut self.auth
+        else {
+            return Ok(());
+        };
+        let mut form = vec![
+            ("grant_type", "refresh_token".to_string()),
+            ("refresh_token", refresh_token.clone()),
+        ];
+        if let Some(id) = client_id {
+            form.push(("client_id", id.clone()));
+        }
+        if let Some(secret) = client_secret {
+            form.push(("client_secret", secret.clone()));
+        }
+
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            access_token: String,
+            #[serde(default)]
+            expires_in: Option<i64>,
+            #[serde(default)]
+            refresh_token: Option<String>,
+        }
+        let response = http
+            .post(token_url.as_str())
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| format!("token refresh failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("token refresh failed: HTTP {}", response.status()));
+        }
+        let token: TokenResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("token refresh failed: {}", e))?;
+        *access_token = token.access_token;
+        *expires_at = token
+            .expires_in
+            .map(|secs| Utc::now() + chrono::Duration::seconds(secs));
+        if let Some(rotated) = token.refresh_token {
+            *refresh_token = rotated;
+        }
+        Ok(())
+    }
+}
+
+/// Server settings of a well-known provider.
+#[derive(Debug, Clone)]
+pub struct EmailPreset {
+    pub imap: ServerConfig,
+    pub smtp: ServerConfig,
+    /// OAuth2 token endpoint for refreshing access tokens.
+    pub token_url: Option<&'static str>,
+}
+
+/// Settings for `gmail`, `outlook` or `fastmail`.
+pub fn preset(name: &str) -> Option<EmailPreset> {
+    let server = |host: &str, port, security| ServerConfig {
+        host: host.to_string(),
+        port,
+        security,
+    };
+    match name.to_ascii_lowercase().as_str() {
+        "gmail" => Some(EmailPreset {
+            imap: server("imap.gmail.com", 993, Security::Tls),
+            smtp: server("smtp.gmail.com", 465, Security::Tls),
+            token_url: Some("https://oauth2.googleapis.com/token"),
+        }),
+        "outlook" | "office365" => Some(EmailPreset {
+            imap: server("outlook.office365.com", 993, Security::Tls),
+            smtp: server("smtp.office365.com", 587, Security::Starttls),
+            token_url: Some("https://login.microsoftonline.com/common/oauth2/v2.0/token"),
+        }),
+        "fastmail" => Some(EmailPreset {
+            imap: server("imap.fastmail.com", 993, Security::Tls),
+            smtp: server("smtp.fastmail.com", 465, Security::Tls),
+            token_url: None,
+        }),
+        _ => None,
+    }
+}
+
+/// Valid account name: letters, digits, `_` and `-`.
+pub fn valid_account_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Accounts in the secrets store, shared by both tools. Each user's
+/// accounts are their own secrets.
+pub struct EmailAccounts {
+    secrets: Arc<dyn SecretsStore + Send + Sync>,
+    http: reqwest::Client,
+}
+
+impl EmailAccounts {
+    pub fn new(secrets: Arc<dyn SecretsStore + Send + Sync>) -> Self {
+        Self {
+            secrets,
+            http: crate::outbound::client(),
+        }
+    }
+
+    /// `user_id`'s registered account names.
+    async fn names(&self, user_id: &str) -> Result<Vec<String>, ToolError> {
+        let refs = self
+            .secrets
+            .list(user_id)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("list failed: {}", e)))?;
+        Ok(refs
+            .iter()
+            .filter(|r| r.provider.as_deref() == Some(EMAIL_PROVIDER))
+            .filter_map(|r| r.name.strip_prefix(EMAIL_SECRET_PREFIX).map(String::from))
+            .collect())
+    }
+
+    /// Account named in `params`, or the user's only account when none is
+    /// named.
+    async fn resolve_name(
+        &self,
+        user_id: &str,
+        params: &serde_json::Value,
+    ) -> Result<String, ToolError> {
+        if let Some(name) = params
+            .get("account")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            return Ok(name.to_string());
+        }
+        let names = self.names(user_id).await?;
+        match names.as_slice() {
+            [only] => Ok(only.clone()),
+            [] => Err(ToolError::InvalidParameters(
+                "no email accounts; register one with `ironclaw tool email add`".to_string(),
+            )),
+            _ => Err(ToolError::InvalidParameters(format!(
+                "several accounts are registered, pass 'account' (one of: {})",
+                names.join(", ")
+            ))),
+        }
+    }
+
+    /// Load one of `user_id`'s accounts, refreshing and re-storing its
+    /// OAuth2 token when due.
+    async fn load(
+        &self,
+        user_id: &str,
+        name: &str,
+    ) -> Result<(EmailAccount, Credentials), ToolError> {
+        if !valid_account_name(name) {
+            return Err(ToolError::InvalidParameters(format!(
+                "invalid account name '{}'",
+                name
+            )));
+        }
+        let secret_name = format!("{}{}", EMAIL_SECRET_PREFIX, name);
+        let secret = self
+            .secrets
+            .get(user_id, &secret_name)
+            .await
+            .ok()
+            .filter(|s| s.provider.as_deref() == Some(EMAIL_PROVIDER))
+            .ok_or_else(|| {
+                ToolError::InvalidParameters(format!(
+                    "no email account '{}'; register it with `ironclaw tool email add {}`",
+                    name, name
+                ))
+            })?;
+        let value = self
+            .secrets
+            .get_decrypted(user_id, &secret_name)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("decrypt failed: {}", e)))?;
+        let _ = self.secrets.record_usage(secret.id).await;
+        let mut account: EmailAccount = serde_json::from_str(value.expose()).map_err(|e| {
+            ToolError::ExecutionFailed(format!("account '{}' is malformed: {}", name, e))
+        })?;
+
+        if account.needs_refresh(Utc::now()) {
+            account
+                .refresh(&self.http)
+                .await
+                .map_err(ToolError::ExternalService)?;
+            let json = serde_json::to_string(&account)
+                .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+            self.secrets
+                .create(
+                    user_id,
+                    CreateSecretParams::new(secret_name, json).with_provider(EMAIL_PROVIDER),
+                )
+                .await
+                .map_err(|e| ToolError::ExecutionFailed(format!("store token failed: {}", e)))?;
+        }
+        let credentials = account.credentials();
+        Ok((account, credentials))
+    }
+}
+
+fn str_param<'a>(params: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+    params
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn require_str<'a>(params: &'a serde_json::Value, key: &str) -> Result<&'a str, ToolError> {
+    str_param(params, key)
+        .ok_or_else(|| ToolError::InvalidParameters(format!("missing '{}' parameter", key)))
+}
+
+fn uid_param(params: &serde_json::Value) -> Result<u32, ToolError> {
+    params
+        .get("uid")
+        .and_then(|v| v.as_u64())
+        .and_then(|n| u32::try_from(n).ok())
+        .filter(|&n| n > 0)
+        .ok_or_else(|| ToolError::InvalidParameters("missing 'uid' parameter".to_string()))
+}
+
+/// `YYYY-MM-DD` as an IMAP date (`1-Feb-2026`).
+fn imap_date(value: &str) -> Result<String, ToolError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|d| d.format("%-d-%b-%Y").to_string())
+        .map_err(|_| ToolError::InvalidParameters(format!("'{}' is not a YYYY-MM-DD date", value)))
+}
+
+/// IMAP search key for the filters in `params`.
+fn search_criteria(params: &serde_json::Value) -> Result<String, ToolError> {
+    let mut keys = Vec::new();
+    if params.get("unread").and_then(|v| v.as_bool()) == Some(true) {
+        keys.push("UNSEEN".to_string());
+    }
+    for (field, key) in [
+        ("from", "FROM"),
+        ("to", "TO"),
+        ("subject", "SUBJECT"),
+        ("text", "TEXT"),
+    ] {
+        if let Some(value) = str_param(params, field) {
+            let quoted = imap::quote(value).map_err(ToolError::InvalidParameters)?;
+            keys.push(format!("{} {}", key, quoted));
+        }
+    }
+    for (field, key) in [("since", "SINCE"), ("before", "BEFORE")] {
+        if let Some(value) = str_param(params, field) {
+            keys.push(format!("{} {}", key, imap_date(value)?));
+        }
+    }
+    let criteria = if keys.is_empty() {
+        "ALL".to_string()
+    } else {
+        keys.join(" ")
+    };
+    if criteria.is_ascii() {
+        Ok(criteria)
+    } else {
+        Ok(format!("CHARSET UTF-8 {}", criteria))
+    }
+}
+
+/// Cut `text` to `max` characters.
+fn truncate_chars(text: &str, max: usize) -> (String, bool) {
+    match text.char_indices().nth(max) {
+        Some((cut, _)) => (format!("{}\n[... truncated]", &text[..cut]), true),
+        None => (text.to_string(), false),
+    }
+}
+
+/// Builtin `email_read` tool.
+pub struct EmailReadTool {
+    accounts: Arc<EmailAccounts>,
+    attachments: Option<Arc<AttachmentPipeline>>,
+    config: EmailConfig,
+}
+
+impl EmailReadTool {
+    pub fn new(
+        accounts: Arc<EmailAccounts>,
+        attachments: Option<Arc<AttachmentPipeline>>,
+        config: EmailConfig,
+    ) -> Self {
+        Self {
+            accounts,
+            attachments,
+            config,
+        }
+    }
+
+    async fn session(
+        &self,
+        user_id: &str,
+        account: &str,
+    ) -> Result<(EmailAccount, ImapSession), ToolError> {
+        let (account, credentials) = self.accounts.load(user_id, account).await?;
+        let session =
+            ImapSession::login(&account.imap, &credentials, self.config.max_message_bytes)
+                .await
+                .map_err(ToolError::ExternalService)?;
+        Ok((account, session))
+    }
+
+    async fn run(
+        &self,
+        user_id: &str,
+        action: &str,
+        params: &serde_json::Value,
+    ) -> Result<serde_json::Value, ToolError> {
+        let name = self.accounts.resolve_name(user_id, params).await?;
+        let (account, mut session) = self.session(user_id, &name).await?;
+        let folder = str_param(params, "folder").unwrap_or("INBOX");
+        let result = self.run_action(action, params, folder, &mut session).await;
+        session.logout().await;
+        let mut result = result.map_err(ToolError::ExternalService)??;
+        if let serde_json::Value::Object(ref mut map) = result {
+            map.insert("account".into(), serde_json::json!(name));
+            map.insert("address".into(), serde_json::json!(account.address));
+        }
+        Ok(result)
+    }
+
+    /// The outer error is a protocol failure, the inner one a bad request.
+    async fn run_action(
+        &self,
+        action: &str,
+        params: &serde_json::Value,
+        folder: &str,
+        session: &mut ImapSession,
+    ) -> Result<Result<serde_json::Value, ToolError>, String> {
+        match action {
+            "folders" => {
+                let folders = session.list_folders().await?;
+                Ok(Ok(serde_json::json!({ "folders": folders })))
+            }
+            "search" => {
+                let criteria = match search_criteria(params) {
+                    Ok(c) => c,
+                    Err(e) => return Ok(Err(e)),
+                };
+                let limit = params
+                    .get("limit")
+                    .and_then(|v| v.as_u64())
+                    .map_or(20, |n| n as usize)
+                    .clamp(1, self.config.max_results);
+                session.open_folder(folder, false).await?;
+                let uids = session.search(&criteria).await?;
+                let total = uids.len();
+                let newest = &uids[total.saturating_sub(limit)..];
+                let mut fetched = session.fetch(newest, SUMMARY_ITEMS).await?;
+                fetched.sort_by_key(|f| std::cmp::Reverse(f.uid));
+                let messages: Vec<_> = fetched
+                    .iter()
+                    .map(|f| {
+                        let headers = mime::parse_headers(&f.body);
+                        let get = |name: &str| {
+                            headers
+                                .iter()
+                                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                                .map(|(_, v)| v.clone())
+                        };
+                        serde_json::json!({
+                            "uid": f.uid,
+                            "from": get("from"),
+                            "to": get("to"),
+                            "subject": get("subject"),
+                            "date": get("date"),
+                            "message_id": get("message-id"),
+                            "unread": !f.flags.iter().any(|fl| fl.eq_ignore_ascii_case("\\Seen")),
+                            "size": f.size,
+                        })
+                    })
+                    .collect();
+                Ok(Ok(serde_json::json!({
+                    "folder": folder,
+                    "total_matches": total,
+                    "messages": messages,
+                })))
+            }
+            "read" | "attachment" => {
+                let uid = match uid_param(params) {
+                    Ok(uid) => uid,
+                    Err(e) => return Ok(Err(e)),
+                };
+                let mark_read = action == "read"
+                    && params.get("mark_read").and_then(|v| v.as_bool()) == Some(true);
+                session.open_folder(folder, mark_read).await?;
+                let fetched = session.fetch(&[uid], "(UID FLAGS BODY.PEEK[])").await?;
+                let Some(raw) = fetched.into_iter().find(|f| f.uid == uid) else {
+                    return Ok(Err(ToolError::InvalidParameters(format!(
+                        "no message with uid {} in '{}'",
+                        uid, folder
+                    ))));
+                };
+                let message = mime::parse_message(&raw.body);
+                if action == "attachment" {
+                    return Ok(self.attachment(&message, params).await);
+                }
+                if mark_read {
+                    session.mark_seen(uid).await?;
+                }
+                let (body, truncated) =
+                    truncate_chars(&message.body_text(), self.config.max_body_chars);
+                let attachments: Vec<_> = message
+                    .attachments
+                    .iter()
+                    .enumerate()
+                    .map(|(i, a)| {
+                        serde_json::json!({
+                            "index": i,
+                            "filename": a.filename,
+                            "content_type": a.content_type,
+                            "bytes": a.data.len(),
+                        })
+                    })
+                    .collect();
+                Ok(Ok(serde_json::json!({
+                    "folder": folder,
+                    "uid": uid,
+                    "from": message.header("from"),
+                    "to": message.header("to"),
+                    "cc": message.header("cc"),
+                    "subject": message.header("subject"),
+                    "date": message.header("date"),
+                    "message_id": message.header("message-id"),
+                    "body": body,
+                    "body_truncated": truncated,
+                    "attachments": attachments,
+                })))
+            }
+            other => Ok(Err(ToolError::InvalidParameters(format!(
+                "unknown action '{}'",
+                other
+            )))),
+        }
+    }
+
+    /// Run one attachment through the attachment pipeline.
+    async fn attachment(
+        &self,
+        message: &mime::ParsedMessage,
+        params: &serde_json::Value,
+    ) -> Result<serde_json::Value, ToolError> {
+        let Some(pipeline) = &self.attachments else {
+            return Err(ToolError::ExecutionFailed(
+                "attachment processing is disabled (ATTACHMENTS_ENABLED=false)".to_string(),
+            ));
+        };
+        let index = params.get("index").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let part = message.attachments.get(index).ok_or_else(|| {
+            ToolError::InvalidParameters(format!(
+                "message has {} attachment(s), index {} is out of range",
+                message.attachments.len(),
+                index
+            ))
+        })?;
+        let summary = pipeline
+            .process(&[Attachment {
+                name: part.filename.clone(),
+                mime_type: Some(part.content_type.clone()),
+                source: AttachmentSource::Inline(BASE64.encode(&part.data)),
+            }])
+            .await;
+        Ok(serde_json::json!({
+            "index": index,
+            "filename": part.filename,
+            "content": summary.render(),
+            "report": summary,
+        }))
+    }
+}
+
+#[async_trait]
+impl Tool for EmailReadTool {
+    fn name(&self) -> &str {
+        "email_read"
+    }
+
+    fn description(&self) -> &str {
+        "Read mail from registered IMAP accounts. 'accounts' lists them, 'folders' \
+         lists folders, 'search' finds messages (newest first) by unread/from/to/\
+         subject/text/since/before, 'read' returns one message by uid, and \
+         'attachment' extracts the text of one attachment by index."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["accounts", "folders", "search", "read", "attachment"],
+                    "description": "Action to perform"
+                },
+                "account": {
+                    "type": "string",
+                    "description": "Account name (optional when only one is registered)"
+                },
+                "folder": {
+                    "type": "string",
+                    "description": "Folder to search or read from (default INBOX)"
+                },
+                "unread": { "type": "boolean", "description": "Only unread messages (search)" },
+                "from": { "type": "string", "description": "Sender contains (search)" },
+                "to": { "type": "string", "description": "Recipient contains (search)" },
+                "subject": { "type": "string", "description": "Subject contains (search)" },
+                "text": { "type": "string", "description": "Headers or body contain (search)" },
+                "since": { "type": "string", "description": "On or after YYYY-MM-DD (search)" },
+                "before": { "type": "string", "description": "Before YYYY-MM-DD (search)" },
+                "limit": { "type": "integer", "description": "Maximum messages to return (search, default 20)" },
+                "uid": { "type": "integer", "description": "Message UID (read, attachment)" },
+                "mark_read": { "type": "boolean", "description": "Mark the message as read (read)" },
+                "index": { "type": "integer", "description": "Attachment index from 'read' (attachment)" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        ctx: &JobContext,
+    ) -> Result<ToolOutput, ToolError> {
+        let start = Instant::now();
+        let action = require_str(&params, "action")?;
+        if action == "accounts" {
+            let names = self.accounts.names(&ctx.user_id).await?;
+            return Ok(ToolOutput::success(
+                serde_json::json!({ "accounts": names }),
+                start.elapsed(),
+            ));
+        }
+        let result =
+            tokio::time::timeout(self.config.timeout, self.run(&ctx.user_id, action, &params))
+                .await
+                .map_err(|_| ToolError::Timeout(self.config.timeout))??;
+        Ok(ToolOutput::success(result, start.elapsed()))
+    }
+
+    fn execution_timeout(&self) -> Duration {
+        self.config.timeout + Duration::from_secs(30)
+    }
+}
+
+/// Addresses from a string or array parameter.
+fn address_list(params: &serde_json::Value, key: &str) -> Result<Vec<String>, ToolError> {
+    let values: Vec<String> = match params.get(key) {
+        None | Some(serde_json::Value::Null) => Vec::new(),
+        Some(serde_json::Value::String(s)) => s.split(',').map(|a| a.trim().to_string()).collect(),
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|a| a.trim().to_string())
+            .collect(),
+        Some(_) => {
+            return Err(ToolError::InvalidParameters(format!(
+                "'{}' must be an address or a list of addresses",
+                key
+            )));
+        }
+    };
+    let values: Vec<String> = values.into_iter().filter(|a| !a.is_empty()).collect();
+    if let Some(bad) = values.iter().find(|a| !mime::valid_address(a)) {
+        return Err(ToolError::InvalidParameters(format!(
+            "invalid address '{}' in '{}'",
+            bad, key
+        )));
+    }
+    Ok(values)
+}
+
+/// Builtin `email_send` tool.
+pub struct EmailSendTool {
+    accounts: Arc<EmailAccounts>,
+    config: EmailConfig,
+}
+
+impl EmailSendTool {
+    pub fn new(accounts: Arc<EmailAccounts>, config: EmailConfig) -> Self {
+        Self { accounts, config }
+    }
+}
+
+#[async_trait]
+impl Tool for EmailSendTool {
+    fn name(&self) -> &str {
+        "email_send"
+    }
+
+    fn description(&self) -> &str {
+        "Send a plain-text email from a registered account over SMTP. Every send \
+         is shown to the user for approval. Set in_reply_to to the message_id \
+         of a message to reply in its thread."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "account": {
+                    "type": "string",
+                    "description": "Account to send from (optional when only one is registered)"
+                },
+                "to": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Recipient addresses"
+                },
+                "cc": { "type": "array", "items": { "type": "string" } },
+                "bcc": { "type": "array", "items": { "type": "string" } },
+                "subject": { "type": "string" },
+                "body": { "type": "string", "description": "Plain-text body" },
+                "in_reply_to": {
+                    "type": "string",
+                    "description": "Message-ID being replied to, e.g. <abc@example.com>"
+                }
+            },
+            "required": ["to", "subject", "body"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        ctx: &JobContext,
+    ) -> Result<ToolOutput, ToolError> {
+        let start = Instant::now();
+        let to = address_list(&params, "to")?;
+        if to.is_empty() {
+            return Err(ToolError::InvalidParameters(
+                "at least one 'to' address is required".to_string(),
+            ));
+        }
+        let cc = address_list(&params, "cc")?;
+        let bcc = address_list(&params, "bcc")?;
+        let subject = require_str(&params, "subject")?.to_string();
+        let body = params
+            .get("body")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidParameters("missing 'body' parameter".to_string()))?
+            .to_string();
+
+        let name = self.accounts.resolve_name(&ctx.user_id, &params).await?;
+        let (account, credentials) = self.accounts.load(&ctx.user_id, &name).await?;
+        let message = mime::OutgoingMessage {
+            from: account.address.clone(),
+            from_name: account.display_name.clone(),
+            to,
+            cc,
+            subject,
+            body,
+            in_reply_to: str_param(&params, "in_reply_to").map(String::from),
+        };
+        let domain = account.address.rsplit('@').next().unwrap_or("localhost");
+        let message_id = format!("<{}@{}>", uuid::Uuid::new_v4(), domain);
+        let rendered = message
+            .render(&message_id, Utc::now())
+            .map_err(ToolError::InvalidParameters)?;
+        let recipients: Vec<String> = message.recipients().chain(bcc.iter()).cloned().collect();
+
+        let accepted = tokio::time::timeout(
+            self.config.timeout,
+            smtp::send(
+                &account.smtp,
+                &credentials,
+                &account.address,
+                &recipients,
+                &rendered,
+            ),
+        )
+        .await
+        .map_err(|_| ToolError::Timeout(self.config.timeout))?
+        .map_err(ToolError::ExternalService)?;
+
+        Ok(ToolOutput::success(
+            serde_json::json!({
+                "account": name,
+                "from": account.address,
+                "recipients": recipients.len(),
+                "message_id": message_id,
+                "server_reply": accepted,
+            }),
+            start.elapsed(),
+        ))
+    }
+
+    fn requires_approval(&self) -> bool {
+        true
+    }
+
+    /// Outgoing mail is approved one message at a time.
+    fn requires_explicit_approval(&self, _params: &serde_json::Value) -> bool {
+        true
+    }
+
+    fn execution_timeout(&self) -> Duration {
+        self.config.timeout + Duration::from_secs(30)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_round_trip_and_refresh_due() {
+        let json = r#"{
+            "address": "me@example.com",
+            "imap": {"host": "imap.example.com", "port": 993, "security": "tls"},
+            "smtp": {"host": "smtp.example.com", "port": 587, "security": "starttls"},
+            "auth": {"type": "oauth2", "access_token": "at", "refresh_token": "rt",
+                     "token_url": "https://auth.example.com/token",
+                     "expires_at": "2026-01-01T00:00:00Z"}
+        }"#;
+        let account: EmailAccount = serde_json::from_str(json).unwrap();
+        assert_eq!(account.username(), "me@example.com");
+        assert_eq!(account.smtp.security, Security::Starttls);
+        assert!(account.needs_refresh(Utc::now()));
+        assert!(matches!(
+            account.credentials(),
+            Credentials::OAuth2 { ref token, .. } if token == "at"
+        ));
+
+        let password: EmailAccount = serde_json::from_value(serde_json::json!({
+            "address": "me@example.com",
+            "username": "me",
+            "imap": preset("fastmail").unwrap().imap,
+            "smtp": preset("fastmail").unwrap().smtp,
+            "auth": {"type": "password", "password": "app-pw"}
+        }))
+        .unwrap();
+        assert!(!password.needs_refresh(Utc::now()));
+        assert_eq!(password.username(), "me");
+    }
+
+    #[test]
+    fn test_search_criteria() {
+        let params = serde_json::json!({
+            "unread": true,
+            "from": "alice@example.com",
+            "since": "2026-02-01"
+        });
+        assert_eq!(
+            search_criteria(&params).unwrap(),
+            "UNSEEN FROM \"alice@example.com\" SINCE 1-Feb-2026"
+        );
+        assert_eq!(search_criteria(&serde_json::json!({})).unwrap(), "ALL");
+        assert_eq!(
+            search_criteria(&serde_json::json!({"subject": "Café"})).unwrap(),
+            "CHARSET UTF-8 SUBJECT \"Café\""
+        );
+        assert!(search_criteria(&serde_json::json!({"since": "yesterday"})).is_err());
+        assert!(search_criteria(&serde_json::json!({"text": "a\r\nA9 DELETE INBOX"})).is_err());
+    }
+
+    #[test]
+    fn test_send_always_needs_approval() {
+        let secrets: Arc<dyn SecretsStore + Send + Sync> =
+            Arc::new(crate::secrets::InMemorySecretsStore::new(Arc::new(
+                crate::secrets::SecretsCrypto::new(secrecy::SecretString::from(
+                    "0123456789abcdef0123456789abcdef".to_string(),
+                ))
+                .unwrap(),
+            )));
+        let tool = EmailSendTool::new(
+            Arc::new(EmailAccounts::new(secrets)),
+            EmailConfig::default(),
+        );
+        assert!(tool.requires_approval_for(&serde_json::json!({})));
+        assert!(tool.requires_explicit_approval(&serde_json::json!({})));
+        assert!(
+            address_list(&serde_json::json!({"to": "a@b.com, c@d.org"}), "to")
+                .unwrap()
+                .len()
+                == 2
+        );
+        assert!(address_list(&serde_json::json!({"to": ["not an address"]}), "to").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_accounts_belong_to_the_calling_user() {
+        use crate::secrets::CreateSecretParams;
+
+        let secrets: Arc<dyn SecretsStore + Send + Sync> =
+            Arc::new(crate::secrets::InMemorySecretsStore::new(Arc::new(
+                crate::secrets::SecretsCrypto::new(secrecy::SecretString::from(
+                    "0123456789abcdef0123456789abcdef".to_string(),
+                ))
+                .unwrap(),
+            )));
+        secrets
+            .create(
+                "alice",
+                CreateSecretParams::new("email_work", "{}").with_provider(EMAIL_PROVIDER),
+            )
+            .await
+            .unwrap();
+        let tool = EmailReadTool::new(
+            Arc::new(EmailAccounts::new(secrets)),
+            None,
+            EmailConfig::default(),
+        );
+        let accounts = |user: &str| {
+            let ctx = JobContext::with_user(user, "t", "d");
+            let tool = &tool;
+            async move {
+                tool.execute(serde_json::json!({"action": "accounts"}), &ctx)
+                    .await
+                    .unwrap()
+                    .result
+            }
+        };
+
+        assert_eq!(
+            accounts("alice").await["accounts"],
+            serde_json::json!(["work"])
+        );
+        assert_eq!(accounts("bob").await["accounts"], serde_json::json!([]));
+    }
+}
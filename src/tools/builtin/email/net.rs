@@ -0,0 +1,205 @@
+//! Line-oriented TCP/TLS connection shared by the IMAP and SMTP clients.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls;
+
+/// Longest protocol line accepted from a server.
+const MAX_LINE: usize = 64 * 1024;
+
+/// How a server connection is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Security {
+    /// TLS from the first byte (IMAPS 993, SMTPS 465).
+    Tls,
+    /// Plain connection upgraded with STARTTLS (SMTP 587).
+    Starttls,
+    /// No encryption; only allowed to loopback hosts such as local bridges.
+    Plain,
+}
+
+/// One IMAP or SMTP server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: Security,
+}
+
+fn is_loopback(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_matches(['[', ']'])
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+enum Stream {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Stream::Plain(s) => Pin::new(s).poll_read(cx, buf),
+            Stream::Tls(s) => Pin::new(s.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            Stream::Plain(s) => Pin::new(s).poll_write(cx, buf),
+            Stream::Tls(s) => Pin::new(s.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Stream::Plain(s) => Pin::new(s).poll_flush(cx),
+            Stream::Tls(s) => Pin::new(s.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Stream::Plain(s) => Pin::new(s).poll_shutdown(cx),
+            Stream::Tls(s) => Pin::new(s.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
+
+async fn tls_handshake(host: &str, tcp: TcpStream) -> Result<Stream, String> {
+    // Built-in roots plus OUTBOUND_CA_BUNDLE, for internal mail servers.
+    let config = crate::outbound::tls_config()?;
+    let name = rustls::pki_types::ServerName::try_from(host.to_string())
+        .map_err(|_| format!("invalid server name '{}'", host))?;
+    let stream = tokio_rustls::TlsConnector::from(Arc::new(config))
+        .connect(name, tcp)
+        .await
+        .map_err(|e| format!("TLS handshake with {} failed: {}", host, e))?;
+    Ok(Stream::Tls(Box::new(stream)))
+}
+
+/// A buffered connection to one server.
+pub struct Connection {
+    host: String,
+    reader: BufReader<Stream>,
+}
+
+impl Connection {
+    /// Connect, doing the TLS handshake for [`Security::Tls`]. STARTTLS is
+    /// left to the protocol client, which calls [`Self::start_tls`].
+    pub async fn open(server: &ServerConfig) -> Result<Self, String> {
+        if server.security == Security::Plain && !is_loopback(&server.host) {
+            return Err(format!(
+                "refusing unencrypted connection to {}; plain is only allowed for localhost",
+                server.host
+            ));
+        }
+        let tcp = TcpStream::connect((server.host.as_str(), server.port))
+            .await
+            .map_err(|e| format!("connect to {}:{} failed: {}", server.host, server.port, e))?;
+        let stream = match server.security {
+            Security::Tls => tls_handshake(&server.host, tcp).await?,
+            Security::Starttls | Security::Plain => Stream::Plain(tcp),
+        };
+        Ok(Self {
+            host: server.host.clone(),
+            reader: BufReader::new(stream),
+        })
+    }
+
+    /// Upgrade a plain connection after the server accepted STARTTLS.
+    pub async fn start_tls(self) -> Result<Self, String> {
+        if !self.reader.buffer().is_empty() {
+            return Err("server sent data before the TLS handshake".to_string());
+        }
+        let Stream::Plain(tcp) = self.reader.into_inner() else {
+            return Err("connection is already encrypted".to_string());
+        };
+        let stream = tls_handshake(&self.host, tcp).await?;
+        Ok(Self {
+            host: self.host,
+            reader: BufReader::new(stream),
+        })
+    }
+
+    /// One line including its line ending.
+    pub async fn read_line(&mut self) -> Result<Vec<u8>, String> {
+        let mut line = Vec::new();
+        let read = (&mut self.reader)
+            .take(MAX_LINE as u64)
+            .read_until(b'\n', &mut line)
+            .await
+            .map_err(|e| format!("read failed: {}", e))?;
+        if read == 0 {
+            return Err("connection closed by server".to_string());
+        }
+        if !line.ends_with(b"\n") {
+            return Err("server line too long".to_string());
+        }
+        Ok(line)
+    }
+
+    /// Exactly `n` bytes.
+    pub async fn read_exact(&mut self, n: usize) -> Result<Vec<u8>, String> {
+        let mut buf = vec![0; n];
+        self.reader
+            .read_exact(&mut buf)
+            .await
+            .map_err(|e| format!("read failed: {}", e))?;
+        Ok(buf)
+    }
+
+    pub async fn write_all(&mut self, data: &[u8]) -> Result<(), String> {
+        let stream = self.reader.get_mut();
+        stream
+            .write_all(data)
+            .await
+            .map_err(|e| format!("write failed: {}", e))?;
+        stream
+            .flush()
+            .await
+            .map_err(|e| format!("write failed: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_plain_refused_for_remote_hosts() {
+        assert!(is_loopback("localhost"));
+        assert!(is_loopback("127.0.0.1"));
+        assert!(is_loopback("[::1]"));
+        assert!(!is_loopback("imap.example.com"));
+        let err = Connection::open(&ServerConfig {
+            host: "imap.example.com".into(),
+            port: 143,
+            security: Security::Plain,
+        })
+        .await
+        .err()
+        .unwrap();
+        assert!(err.contains("refusing unencrypted"));
+    }
+}
@@ -0,0 +1,197 @@
+//! Minimal SMTP submission client (RFC 5321 with STARTTLS and AUTH).
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+use super::Credentials;
+use super::net::{Connection, Security, ServerConfig};
+
+/// Read one (possibly multi-line) reply: code and joined text.
+async fn reply(conn: &mut Connection) -> Result<(u16, String), String> {
+    let mut text = Vec::new();
+    loop {
+        let line = conn.read_line().await?;
+        let line = String::from_utf8_lossy(&line).trim_end().to_string();
+        let code = line
+            .get(..3)
+            .and_then(|c| c.parse().ok())
+            .ok_or_else(|| format!("malformed SMTP reply: {}", line))?;
+        text.push(line.get(4..).unwrap_or("").to_string());
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok((code, text.join("\n")));
+        }
+    }
+}
+
+/// Send `line` and require a reply code in `expected`.
+async fn exchange(
+    conn: &mut Connection,
+    line: &str,
+    expected: &[u16],
+) -> Result<(u16, String), String> {
+    conn.write_all(format!("{}\r\n", line).as_bytes()).await?;
+    let (code, text) = reply(conn).await?;
+    if expected.contains(&code) {
+        Ok((code, text))
+    } else {
+        let verb = line.split([' ', ':']).next().unwrap_or(line);
+        Err(format!("{} rejected: {} {}", verb, code, text))
+    }
+}
+
+/// Escape lines starting with `.` and make every line end in CRLF.
+fn dot_stuff(message: &str) -> String {
+    let mut out = String::with_capacity(message.len() + 16);
+    for line in message.replace("\r\n", "\n").split('\n') {
+        if line.starts_with('.') {
+            out.push('.');
+        }
+        out.push_str(line);
+        out.push_str("\r\n");
+    }
+    // `split` yields a trailing empty line for text ending in a newline.
+    if message.ends_with('\n') {
+        out.truncate(out.len() - 2);
+    }
+    out
+}
+
+/// Submit `message` from `from` to every address in `recipients`.
+pub async fn send(
+    server: &ServerConfig,
+    credentials: &Credentials,
+    from: &str,
+    recipients: &[String],
+    message: &str,
+) -> Result<String, String> {
+    let mut conn = Connection::open(server).await?;
+    let (code, text) = reply(&mut conn).await?;
+    if code != 220 {
+        return Err(format!("unexpected SMTP greeting: {} {}", code, text));
+    }
+    let ehlo = "EHLO ironclaw.localhost";
+    let (_, mut capabilities) = exchange(&mut conn, ehlo, &[250]).await?;
+    if server.security == Security::Starttls {
+        exchange(&mut conn, "STARTTLS", &[220]).await?;
+        conn = conn.start_tls().await?;
+        capabilities = exchange(&mut conn, ehlo, &[250]).await?.1;
+    }
+
+    let auth = match credentials {
+        Credentials::Password { username, password } => format!(
+            "AUTH PLAIN {}",
+            BASE64.encode(format!("\0{}\0{}", username, password))
+        ),
+        Credentials::OAuth2 { username, token } => format!(
+            "AUTH XOAUTH2 {}",
+            BASE64.encode(format!(
+                "user={}\x01auth=Bearer {}\x01\x01",
+                username, token
+            ))
+        ),
+    };
+    if !capabilities.to_ascii_uppercase().contains("AUTH") {
+        return Err("SMTP server does not offer authentication".to_string());
+    }
+    conn.write_all(format!("{}\r\n", auth).as_bytes()).await?;
+    let (mut code, mut text) = reply(&mut conn).await?;
+    if code == 334 {
+        // XOAUTH2 error details; an empty line ends the exchange.
+        conn.write_all(b"\r\n").await?;
+        (code, text) = reply(&mut conn).await?;
+    }
+    if code != 235 {
+        return Err(format!("SMTP login failed: {} {}", code, text));
+    }
+
+    exchange(&mut conn, &format!("MAIL FROM:<{}>", from), &[250]).await?;
+    for rcpt in recipients {
+        exchange(&mut conn, &format!("RCPT TO:<{}>", rcpt), &[250, 251]).await?;
+    }
+    exchange(&mut conn, "DATA", &[354]).await?;
+    conn.write_all(dot_stuff(message).as_bytes()).await?;
+    let (_, accepted) = exchange(&mut conn, ".", &[250]).await?;
+    let _ = exchange(&mut conn, "QUIT", &[221]).await;
+    Ok(accepted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dot_stuff() {
+        assert_eq!(
+            dot_stuff("hi\n.\n..x\nend\n"),
+            "hi\r\n..\r\n...x\r\nend\r\n"
+        );
+        assert_eq!(dot_stuff("a\r\nb"), "a\r\nb\r\n");
+    }
+
+    #[tokio::test]
+    async fn test_send_against_fake_server() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut lines = BufReader::new(read).lines();
+            write.write_all(b"220 fake ESMTP\r\n").await.unwrap();
+            let mut log = Vec::new();
+            let mut in_data = false;
+            while let Some(line) = lines.next_line().await.unwrap() {
+                log.push(line.clone());
+                let reply: &[u8] = if in_data {
+                    if line != "." {
+                        continue;
+                    }
+                    in_data = false;
+                    b"250 2.0.0 queued as 42\r\n"
+                } else if line.starts_with("EHLO") {
+                    b"250-fake\r\n250-AUTH PLAIN XOAUTH2\r\n250 8BITMIME\r\n"
+                } else if line.starts_with("AUTH") {
+                    b"235 ok\r\n"
+                } else if line == "DATA" {
+                    in_data = true;
+                    b"354 go\r\n"
+                } else if line == "QUIT" {
+                    write.write_all(b"221 bye\r\n").await.unwrap();
+                    break;
+                } else {
+                    b"250 ok\r\n"
+                };
+                write.write_all(reply).await.unwrap();
+            }
+            log
+        });
+
+        let accepted = send(
+            &ServerConfig {
+                host: "127.0.0.1".into(),
+                port,
+                security: Security::Plain,
+            },
+            &Credentials::Password {
+                username: "me".into(),
+                password: "secret".into(),
+            },
+            "me@example.com",
+            &["you@example.org".to_string(), "cc@example.org".to_string()],
+            "Subject: hi\r\n\r\n.hidden\r\n",
+        )
+        .await
+        .unwrap();
+        assert!(accepted.contains("queued"));
+
+        let log = server.await.unwrap();
+        assert_eq!(
+            log[1],
+            format!("AUTH PLAIN {}", BASE64.encode("\0me\0secret"))
+        );
+        assert_eq!(log[2], "MAIL FROM:<me@example.com>");
+        assert_eq!(log[4], "RCPT TO:<cc@example.org>");
+        assert!(log.contains(&"..hidden".to_string()));
+    }
+}
@@ -8,7 +8,7 @@ use std::sync::Arc;
 use async_trait::async_trait;
 
 use crate::context::JobContext;
-use crate::extensions::{ExtensionKind, ExtensionManager};
+use crate::extensions::{ExtensionError, ExtensionKind, ExtensionManager};
 use crate::tools::tool::{Tool, ToolError, ToolOutput};
 
 // ── tool_search ──────────────────────────────────────────────────────────
@@ -114,7 +114,7 @@ impl Tool for ToolInstallTool {
                 },
                 "url": {
                     "type": "string",
-                    "description": "Explicit URL (for extensions not in the registry)"
+                    "description": "Explicit URL, or a local path to a .wasm file or directory for offline installs (for extensions not in the registry)"
                 },
                 "kind": {
                     "type": "string",
@@ -317,6 +317,21 @@ impl Tool for ToolActivateTool {
                     .unwrap_or_else(|_| serde_json::json!({"error": "serialization failed"}));
                 Ok(ToolOutput::success(output, start.elapsed()))
             }
+            Err(ExtensionError::ConsentRequired(request)) => {
+                // Only the user can grant capabilities; surface the summary.
+                let output = serde_json::json!({
+                    "name": name,
+                    "status": "consent_required",
+                    "capabilities": request.summary(),
+                    "instructions": format!(
+                        "Show these capabilities to the user. They can be approved from \
+                         the Extensions tab of the web gateway or with \
+                         `ironclaw tool consent {}`.",
+                        name
+                    ),
+                });
+                Ok(ToolOutput::success(output, start.elapsed()))
+            }
             Err(activate_err) => {
                 let err_str = activate_err.to_string();
                 let needs_auth = err_str.contains("authentication")
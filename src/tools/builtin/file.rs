@@ -74,6 +74,24 @@ fn normalize_lexical(path: &Path) -> PathBuf {
     components.iter().collect()
 }
 
+/// Resolve a tool path for the current job.
+///
+/// When the chat session is bound to a project (and the tool isn't confined
+/// to a base directory), relative paths are taken relative to the project
+/// root instead of the process working directory.
+fn resolve_path(
+    path_str: &str,
+    base_dir: Option<&Path>,
+    ctx: &JobContext,
+) -> Result<PathBuf, ToolError> {
+    match (&ctx.working_dir, base_dir) {
+        (Some(root), None) if Path::new(path_str).is_relative() => {
+            validate_path(&root.join(path_str).to_string_lossy(), None)
+        }
+        _ => validate_path(path_str, base_dir),
+    }
+}
+
 /// Validate that a path is safe (no traversal attacks).
 ///
 /// For sandboxed paths (base_dir is set), we normalize the joined path lexically
@@ -201,7 +219,7 @@ impl Tool for ReadFileTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &JobContext,
+        ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError> {
         let path_str = params
             .get("path")
@@ -213,7 +231,7 @@ impl Tool for ReadFileTool {
 
         let start = std::time::Instant::now();
 
-        let path = validate_path(path_str, self.base_dir.as_deref())?;
+        let path = resolve_path(path_str, self.base_dir.as_deref(), ctx)?;
 
         // Check file size
         let metadata = fs::metadata(&path)
@@ -326,7 +344,7 @@ impl Tool for WriteFileTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &JobContext,
+        ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError> {
         let path_str = params
             .get("path")
@@ -358,7 +376,7 @@ impl Tool for WriteFileTool {
             )));
         }
 
-        let path = validate_path(path_str, self.base_dir.as_deref())?;
+        let path = resolve_path(path_str, self.base_dir.as_deref(), ctx)?;
 
         // Create parent directories
         if let Some(parent) = path.parent() {
@@ -446,7 +464,7 @@ impl Tool for ListDirTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &JobContext,
+        ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError> {
         let path_str = params.get("path").and_then(|v| v.as_str()).unwrap_or(".");
 
@@ -462,7 +480,7 @@ impl Tool for ListDirTool {
 
         let start = std::time::Instant::now();
 
-        let path = validate_path(path_str, self.base_dir.as_deref())?;
+        let path = resolve_path(path_str, self.base_dir.as_deref(), ctx)?;
 
         let mut entries = Vec::new();
         list_dir_inner(&path, &path, recursive, max_depth, 0, &mut entries).await?;
@@ -648,7 +666,7 @@ impl Tool for ApplyPatchTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &JobContext,
+        ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError> {
         let path_str = params
             .get("path")
@@ -672,7 +690,7 @@ impl Tool for ApplyPatchTool {
 
         let start = std::time::Instant::now();
 
-        let path = validate_path(path_str, self.base_dir.as_deref())?;
+        let path = resolve_path(path_str, self.base_dir.as_deref(), ctx)?;
 
         // Read current content
         let content = fs::read_to_string(&path)
@@ -755,6 +773,26 @@ mod tests {
         assert!(content.contains("line 2"));
     }
 
+    #[tokio::test]
+    async fn test_relative_path_uses_project_root() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "from project\n").unwrap();
+
+        let tool = ReadFileTool::new();
+        let ctx = JobContext {
+            working_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+
+        let result = tool
+            .execute(serde_json::json!({"path": "notes.txt"}), &ctx)
+            .await
+            .unwrap();
+
+        let content = result.result.get("content").unwrap().as_str().unwrap();
+        assert!(content.contains("from project"));
+    }
+
     #[tokio::test]
     async fn test_write_file() {
         let dir = TempDir::new().unwrap();
@@ -2,13 +2,14 @@
 
 use std::collections::HashMap;
 use std::net::{IpAddr, ToSocketAddrs};
+use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
 use reqwest::Client;
 
 use crate::context::JobContext;
-use crate::safety::LeakDetector;
+use crate::safety::{LeakDetector, Provenance, ProvenanceStamp};
 use crate::tools::tool::{Tool, ToolError, ToolOutput};
 
 /// Maximum response body size (5 MB). Prevents OOM from unbounded responses.
@@ -17,22 +18,32 @@ const MAX_RESPONSE_SIZE: usize = 5 * 1024 * 1024;
 /// Tool for making HTTP requests.
 pub struct HttpTool {
     client: Client,
+    provenance: Option<Arc<Provenance>>,
 }
 
 impl HttpTool {
     /// Create a new HTTP tool.
     pub fn new() -> Self {
-        let client = Client::builder()
+        let client = crate::outbound::client_builder()
             .timeout(Duration::from_secs(30))
             .redirect(reqwest::redirect::Policy::none())
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { client }
+        Self {
+            client,
+            provenance: None,
+        }
+    }
+
+    /// Stamp requests to matching destinations with provenance metadata.
+    pub fn with_provenance(mut self, provenance: Arc<Provenance>) -> Self {
+        self.provenance = Some(provenance);
+        self
     }
 }
 
-fn validate_url(url: &str) -> Result<reqwest::Url, ToolError> {
+pub(crate) fn validate_url(url: &str) -> Result<reqwest::Url, ToolError> {
     let parsed = reqwest::Url::parse(url)
         .map_err(|e| ToolError::InvalidParameters(format!("invalid URL: {}", e)))?;
 
@@ -166,7 +177,7 @@ impl Tool for HttpTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &JobContext,
+        ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError> {
         let start = std::time::Instant::now();
 
@@ -184,10 +195,29 @@ impl Tool for HttpTool {
         let parsed_url = validate_url(url)?;
 
         // Parse headers
-        let headers: HashMap<String, String> = params
+        let mut headers: HashMap<String, String> = params
             .get("headers")
             .and_then(|v| serde_json::from_value(v.clone()).ok())
             .unwrap_or_default();
+        let mut body = params.get("body").cloned();
+
+        // Provenance metadata for this destination
+        if let Some(rule) = self
+            .provenance
+            .as_ref()
+            .and_then(|p| p.rule_for(parsed_url.host_str().unwrap_or_default()))
+        {
+            let stamp = ProvenanceStamp::from_job(ctx);
+            headers.extend(Provenance::headers(rule, &stamp));
+            if let Some(body) = body.as_mut() {
+                Provenance::stamp_body(rule, &stamp, body);
+            }
+            tracing::debug!(
+                destination = %rule.destination,
+                job_id = %ctx.job_id,
+                "Stamped outbound request with provenance"
+            );
+        }
         let headers_vec: Vec<(String, String)> = headers
             .iter()
             .map(|(k, v)| (k.clone(), v.clone()))
@@ -214,7 +244,7 @@ impl Tool for HttpTool {
         }
 
         // Add body if present
-        let body_bytes = if let Some(body) = params.get("body") {
+        let body_bytes = if let Some(body) = &body {
             let bytes = serde_json::to_vec(body)
                 .map_err(|e| ToolError::InvalidParameters(format!("invalid body JSON: {}", e)))?;
             request = request.json(body);
@@ -6,7 +6,7 @@
 //! - Check job status
 //! - Cancel running jobs
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -14,7 +14,7 @@ use async_trait::async_trait;
 use chrono::Utc;
 use uuid::Uuid;
 
-use crate::context::{ContextManager, JobContext, JobState};
+use crate::context::{CANCEL_GRACE_PERIOD, ContextManager, JobContext, JobState};
 use crate::db::Database;
 use crate::history::SandboxJobRecord;
 use crate::orchestrator::job_manager::{ContainerJobManager, JobMode};
@@ -110,6 +110,15 @@ impl CreateJobTool {
             .await
         {
             Ok(job_id) => {
+                // Cancellation notices go back to the channel the job came from.
+                if let Some(channel) = ctx.metadata.get("origin_channel").cloned() {
+                    let _ = self
+                        .context_manager
+                        .update_context(job_id, |job| {
+                            job.metadata = serde_json::json!({ "origin_channel": channel });
+                        })
+                        .await;
+                }
                 let result = serde_json::json!({
                     "job_id": job_id.to_string(),
                     "title": title,
@@ -140,7 +149,13 @@ impl CreateJobTool {
         let jm = self.job_manager.as_ref().expect("sandbox deps required");
 
         let job_id = Uuid::new_v4();
-        let (project_dir, browse_id) = resolve_project_dir(explicit_dir, job_id)?;
+        let (project_dir, browse_id) = match (explicit_dir, ctx.working_dir.as_deref()) {
+            // A session bound to a project mounts the project itself. The user
+            // picked it with `/project use`, so it isn't confined to the
+            // projects base like LLM-supplied paths are.
+            (None, Some(root)) => bound_project_dir(root)?,
+            (explicit, _) => resolve_project_dir(explicit, job_id)?,
+        };
         let project_dir_str = project_dir.display().to_string();
 
         // Persist the job to DB before creating the container.
@@ -277,6 +292,26 @@ impl CreateJobTool {
                             )));
                         }
                     }
+                    crate::orchestrator::job_manager::ContainerState::Cancelled => {
+                        let message = handle
+                            .completion_result
+                            .as_ref()
+                            .and_then(|r| r.message.clone())
+                            .unwrap_or_else(|| "Cancelled".to_string());
+                        jm.cleanup_job(job_id).await;
+                        self.update_status(
+                            job_id,
+                            "failed",
+                            Some(false),
+                            Some(message.clone()),
+                            None,
+                            Some(Utc::now()),
+                        );
+                        return Err(ToolError::ExecutionFailed(format!(
+                            "container job was cancelled: {}",
+                            message
+                        )));
+                    }
                     crate::orchestrator::job_manager::ContainerState::Failed => {
                         let message = handle
                             .completion_result
@@ -329,6 +364,22 @@ fn projects_base() -> PathBuf {
         .join("projects")
 }
 
+/// Use the session's bound project root as the job's project directory.
+fn bound_project_dir(root: &Path) -> Result<(PathBuf, String), ToolError> {
+    let canonical = root.canonicalize().map_err(|e| {
+        ToolError::ExecutionFailed(format!(
+            "bound project {} is not accessible: {}",
+            root.display(),
+            e
+        ))
+    })?;
+    let browse_id = canonical
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "project".to_string());
+    Ok((canonical, browse_id))
+}
+
 /// Resolve the project directory, creating it if it doesn't exist.
 ///
 /// Auto-creates `~/.ironclaw/projects/{project_id}/` so every sandbox job has a
@@ -679,13 +730,58 @@ impl Tool for JobStatusTool {
 }
 
 /// Tool for canceling a job.
+///
+/// Cancelling fires the job's cancellation signal, so its worker stops
+/// mid-call and records what it had finished. With sandbox deps, sandboxed
+/// container jobs can be cancelled too.
 pub struct CancelJobTool {
     context_manager: Arc<ContextManager>,
+    job_manager: Option<Arc<ContainerJobManager>>,
+    store: Option<Arc<dyn Database>>,
 }
 
 impl CancelJobTool {
     pub fn new(context_manager: Arc<ContextManager>) -> Self {
-        Self { context_manager }
+        Self {
+            context_manager,
+            job_manager: None,
+            store: None,
+        }
+    }
+
+    /// Inject sandbox dependencies so container jobs can be cancelled.
+    pub fn with_sandbox(
+        mut self,
+        job_manager: Arc<ContainerJobManager>,
+        store: Option<Arc<dyn Database>>,
+    ) -> Self {
+        self.job_manager = Some(job_manager);
+        self.store = store;
+        self
+    }
+
+    /// Stop a sandboxed container job owned by `requester_id`.
+    ///
+    /// Returns `None` when no such container job exists.
+    async fn cancel_container(
+        &self,
+        job_id: Uuid,
+        requester_id: &str,
+    ) -> Option<Result<(), String>> {
+        let jm = self.job_manager.as_ref()?;
+        jm.get_handle(job_id).await?;
+        if let Some(ref store) = self.store {
+            match store.get_sandbox_job(job_id).await {
+                Ok(Some(record)) if record.user_id == requester_id => {}
+                _ => return None,
+            }
+        }
+
+        Some(
+            jm.cancel_job(job_id, "Cancelled by user")
+                .await
+                .map_err(|e| e.to_string()),
+        )
     }
 }
 
@@ -741,10 +837,14 @@ impl Tool for CancelJobTool {
             .await
         {
             Ok(Ok(())) => {
+                // Stop the worker and whatever tool call it has in flight.
+                self.context_manager.cancel(job_id).await;
                 let result = serde_json::json!({
                     "job_id": job_id.to_string(),
                     "status": "cancelled",
-                    "message": "Job cancelled successfully"
+                    "message": "Job cancelled. In-flight work is being stopped; what it \
+                                completed is recorded on the job and reported to the \
+                                channel it came from."
                 });
                 Ok(ToolOutput::success(result, start.elapsed()))
             }
@@ -755,9 +855,22 @@ impl Tool for CancelJobTool {
                 Ok(ToolOutput::success(result, start.elapsed()))
             }
             Err(e) => {
-                let result = serde_json::json!({
-                    "error": format!("Job not found: {}", e)
-                });
+                let result = match self.cancel_container(job_id, &requester_id).await {
+                    Some(Ok(())) => serde_json::json!({
+                        "job_id": job_id.to_string(),
+                        "status": "cancelled",
+                        "message": format!(
+                            "Container stopped (SIGTERM, then SIGKILL after {}s).",
+                            CANCEL_GRACE_PERIOD.as_secs()
+                        )
+                    }),
+                    Some(Err(reason)) => serde_json::json!({
+                        "error": format!("Cannot cancel job: {}", reason)
+                    }),
+                    None => serde_json::json!({
+                        "error": format!("Job not found: {}", e)
+                    }),
+                };
                 Ok(ToolOutput::success(result, start.elapsed()))
             }
         }
@@ -864,6 +977,41 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_cancel_job_tool_fires_cancellation() {
+        let manager = Arc::new(ContextManager::new(5));
+        let ctx = JobContext::default();
+        let job_id = manager
+            .create_job_for_user(&ctx.user_id, "Job", "Desc")
+            .await
+            .unwrap();
+        let signal = manager.cancellation(job_id).await;
+
+        let tool = CancelJobTool::new(Arc::clone(&manager));
+        let result = tool
+            .execute(serde_json::json!({"job_id": job_id.to_string()}), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(result.result["status"], "cancelled");
+        assert!(signal.is_cancelled());
+        assert_eq!(
+            manager.get_context(job_id).await.unwrap().state,
+            JobState::Cancelled
+        );
+
+        // Other users' jobs look like they don't exist.
+        let other = manager
+            .create_job_for_user("someone-else", "Job", "Desc")
+            .await
+            .unwrap();
+        let result = tool
+            .execute(serde_json::json!({"job_id": other.to_string()}), &ctx)
+            .await
+            .unwrap();
+        assert!(result.result.get("error").is_some());
+        assert!(!manager.cancellation(other).await.is_cancelled());
+    }
+
     #[test]
     fn test_resolve_project_dir_auto() {
         let project_id = Uuid::new_v4();
@@ -18,14 +18,24 @@ use async_trait::async_trait;
 
 use crate::context::JobContext;
 use crate::tools::tool::{Tool, ToolError, ToolOutput};
-use crate::workspace::{ConnectionType, ProfileType, Workspace, paths};
+use crate::workspace::{
+    ConnectionType, DocumentMetadata, GlossaryEntry, ProfileType, Workspace, paths,
+};
 
 /// Identity files that the LLM must not overwrite via tool calls.
 /// These are loaded into the system prompt and could be used for prompt
 /// injection if an attacker tricks the agent into overwriting them.
-const PROTECTED_IDENTITY_FILES: &[&str] =
+pub(crate) const PROTECTED_IDENTITY_FILES: &[&str] =
     &[paths::IDENTITY, paths::SOUL, paths::AGENTS, paths::USER];
 
+/// `workspace` restricted to the memory of the agent handling the job.
+pub(crate) fn memory_workspace(workspace: &Arc<Workspace>, ctx: &JobContext) -> Arc<Workspace> {
+    match ctx.memory {
+        Some(owner) => Arc::new(workspace.scoped(owner)),
+        None => Arc::clone(workspace),
+    }
+}
+
 /// Tool for searching workspace memory.
 ///
 /// Performs hybrid search (FTS + semantic) across all memory documents.
@@ -51,7 +61,8 @@ impl Tool for MemorySearchTool {
     fn description(&self) -> &str {
         "Search past memories, decisions, and context. MUST be called before answering \
          questions about prior work, decisions, dates, people, preferences, or todos. \
-         Returns relevant snippets with relevance scores."
+         Returns relevant snippets with relevance scores, their document path, and the \
+         source (file or URL) of ingested documents."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -77,9 +88,10 @@ impl Tool for MemorySearchTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &JobContext,
+        ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError> {
         let start = std::time::Instant::now();
+        let workspace = memory_workspace(&self.workspace, ctx);
 
         let query = params
             .get("query")
@@ -92,20 +104,42 @@ impl Tool for MemorySearchTool {
             .unwrap_or(5)
             .min(20) as usize;
 
-        let results = self
-            .workspace
+        let results = workspace
             .search(query, limit)
             .await
             .map_err(|e| ToolError::ExecutionFailed(format!("Search failed: {}", e)))?;
 
+        // Path and source of each document, so results can be cited.
+        let mut documents = std::collections::HashMap::new();
+        for result in &results {
+            if documents.contains_key(&result.document_id) {
+                continue;
+            }
+            if let Ok(doc) = workspace.document(result.document_id).await {
+                let source = DocumentMetadata::from_json(&doc.metadata).source_url;
+                documents.insert(result.document_id, (doc.path, source, doc.content));
+            }
+        }
+
         let output = serde_json::json!({
             "query": query,
-            "results": results.iter().map(|r| serde_json::json!({
-                "content": r.content,
-                "score": r.score,
-                "document_id": r.document_id.to_string(),
-                "is_hybrid_match": r.is_hybrid(),
-            })).collect::<Vec<_>>(),
+            "results": results.iter().map(|r| {
+                let (path, source, lines) = documents.get(&r.document_id).map_or(
+                    (None, None, None),
+                    |(path, source, content)| {
+                        (Some(path), source.as_ref(), chunk_lines(content, &r.content))
+                    },
+                );
+                serde_json::json!({
+                    "content": r.content,
+                    "score": r.score,
+                    "document_id": r.document_id.to_string(),
+                    "path": path,
+                    "lines": lines,
+                    "source": source,
+                    "is_hybrid_match": r.is_hybrid(),
+                })
+            }).collect::<Vec<_>>(),
             "result_count": results.len(),
         });
 
@@ -117,6 +151,14 @@ impl Tool for MemorySearchTool {
     }
 }
 
+/// First and last line (1-based) of a chunk within its document.
+fn chunk_lines(document: &str, chunk: &str) -> Option<[usize; 2]> {
+    let start = document.find(chunk.trim())?;
+    let first = document[..start].matches('\n').count() + 1;
+    let last = first + chunk.trim().matches('\n').count();
+    Some([first, last])
+}
+
 /// Tool for writing to workspace memory.
 ///
 /// Use this to persist important information that should be remembered
@@ -172,9 +214,10 @@ impl Tool for MemoryWriteTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &JobContext,
+        ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError> {
         let start = std::time::Instant::now();
+        let workspace = memory_workspace(&self.workspace, ctx);
 
         let content = params
             .get("content")
@@ -212,12 +255,12 @@ impl Tool for MemoryWriteTool {
         let path = match target {
             "memory" => {
                 if append {
-                    self.workspace
+                    workspace
                         .append_memory(content)
                         .await
                         .map_err(|e| ToolError::ExecutionFailed(format!("Write failed: {}", e)))?;
                 } else {
-                    self.workspace
+                    workspace
                         .write(paths::MEMORY, content)
                         .await
                         .map_err(|e| ToolError::ExecutionFailed(format!("Write failed: {}", e)))?;
@@ -225,7 +268,7 @@ impl Tool for MemoryWriteTool {
                 paths::MEMORY.to_string()
             }
             "daily_log" => {
-                self.workspace
+                workspace
                     .append_daily_log(content)
                     .await
                     .map_err(|e| ToolError::ExecutionFailed(format!("Write failed: {}", e)))?;
@@ -233,12 +276,12 @@ impl Tool for MemoryWriteTool {
             }
             "heartbeat" => {
                 if append {
-                    self.workspace
+                    workspace
                         .append(paths::HEARTBEAT, content)
                         .await
                         .map_err(|e| ToolError::ExecutionFailed(format!("Write failed: {}", e)))?;
                 } else {
-                    self.workspace
+                    workspace
                         .write(paths::HEARTBEAT, content)
                         .await
                         .map_err(|e| ToolError::ExecutionFailed(format!("Write failed: {}", e)))?;
@@ -261,12 +304,12 @@ impl Tool for MemoryWriteTool {
                 }
 
                 if append {
-                    self.workspace
+                    workspace
                         .append(path, content)
                         .await
                         .map_err(|e| ToolError::ExecutionFailed(format!("Write failed: {}", e)))?;
                 } else {
-                    self.workspace
+                    workspace
                         .write(path, content)
                         .await
                         .map_err(|e| ToolError::ExecutionFailed(format!("Write failed: {}", e)))?;
@@ -333,17 +376,17 @@ impl Tool for MemoryReadTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &JobContext,
+        ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError> {
         let start = std::time::Instant::now();
+        let workspace = memory_workspace(&self.workspace, ctx);
 
         let path = params
             .get("path")
             .and_then(|v| v.as_str())
             .ok_or_else(|| ToolError::InvalidParameters("missing 'path' parameter".to_string()))?;
 
-        let doc = self
-            .workspace
+        let doc = workspace
             .read(path)
             .await
             .map_err(|e| ToolError::ExecutionFailed(format!("Read failed: {}", e)))?;
@@ -380,7 +423,7 @@ impl MemoryTreeTool {
     ///
     /// Returns a compact format where directories end with `/` and may have children.
     async fn build_tree(
-        &self,
+        workspace: &Workspace,
         path: &str,
         current_depth: usize,
         max_depth: usize,
@@ -389,8 +432,7 @@ impl MemoryTreeTool {
             return Ok(Vec::new());
         }
 
-        let entries = self
-            .workspace
+        let entries = workspace
             .list(path)
             .await
             .map_err(|e| ToolError::ExecutionFailed(format!("Tree failed: {}", e)))?;
@@ -405,8 +447,13 @@ impl MemoryTreeTool {
             };
 
             if entry.is_directory && current_depth < max_depth {
-                let children =
-                    Box::pin(self.build_tree(&entry.path, current_depth + 1, max_depth)).await?;
+                let children = Box::pin(Self::build_tree(
+                    workspace,
+                    &entry.path,
+                    current_depth + 1,
+                    max_depth,
+                ))
+                .await?;
                 if children.is_empty() {
                     result.push(serde_json::Value::String(display_path));
                 } else {
@@ -456,9 +503,10 @@ impl Tool for MemoryTreeTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &JobContext,
+        ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError> {
         let start = std::time::Instant::now();
+        let workspace = memory_workspace(&self.workspace, ctx);
 
         let path = params.get("path").and_then(|v| v.as_str()).unwrap_or("");
 
@@ -468,7 +516,7 @@ impl Tool for MemoryTreeTool {
             .unwrap_or(1)
             .clamp(1, 10) as usize;
 
-        let tree = self.build_tree(path, 1, depth).await?;
+        let tree = Self::build_tree(&workspace, path, 1, depth).await?;
 
         // Compact output: just the tree array
         Ok(ToolOutput::success(
@@ -551,9 +599,10 @@ impl Tool for MemoryConnectTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &JobContext,
+        ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError> {
         let start = std::time::Instant::now();
+        let workspace = memory_workspace(&self.workspace, ctx);
 
         let action = params
             .get("action")
@@ -585,8 +634,7 @@ impl Tool for MemoryConnectTool {
                     ))
                 })?;
 
-                let conn = self
-                    .workspace
+                let conn = workspace
                     .connect(source_path, target_path, connection_type)
                     .await
                     .map_err(|e| {
@@ -611,11 +659,11 @@ impl Tool for MemoryConnectTool {
                     .ok_or_else(|| {
                         ToolError::InvalidParameters("missing 'document_path' for list".to_string())
                     })?;
-                let doc = self.workspace.read(doc_path).await.map_err(|e| {
+                let doc = workspace.read(doc_path).await.map_err(|e| {
                     ToolError::ExecutionFailed(format!("Read document failed: {}", e))
                 })?;
 
-                let connections = self.workspace.get_connections(doc.id).await.map_err(|e| {
+                let connections = workspace.get_connections(doc.id).await.map_err(|e| {
                     ToolError::ExecutionFailed(format!("List connections failed: {}", e))
                 })?;
 
@@ -655,12 +703,9 @@ impl Tool for MemoryConnectTool {
                     ToolError::InvalidParameters(format!("invalid UUID: '{}'", conn_id_str))
                 })?;
 
-                self.workspace
-                    .delete_connection(conn_id)
-                    .await
-                    .map_err(|e| {
-                        ToolError::ExecutionFailed(format!("Delete connection failed: {}", e))
-                    })?;
+                workspace.delete_connection(conn_id).await.map_err(|e| {
+                    ToolError::ExecutionFailed(format!("Delete connection failed: {}", e))
+                })?;
 
                 Ok(ToolOutput::success(
                     serde_json::json!({ "status": "deleted", "connection_id": conn_id_str }),
@@ -734,9 +779,10 @@ impl Tool for MemorySpacesTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &JobContext,
+        ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError> {
         let start = std::time::Instant::now();
+        let workspace = memory_workspace(&self.workspace, ctx);
 
         let action = params
             .get("action")
@@ -755,8 +801,7 @@ impl Tool for MemorySpacesTool {
                     .and_then(|v| v.as_str())
                     .unwrap_or("");
 
-                let space = self
-                    .workspace
+                let space = workspace
                     .create_space(name, description)
                     .await
                     .map_err(|e| {
@@ -773,7 +818,7 @@ impl Tool for MemorySpacesTool {
                 ))
             }
             "list" => {
-                let spaces = self.workspace.list_spaces().await.map_err(|e| {
+                let spaces = workspace.list_spaces().await.map_err(|e| {
                     ToolError::ExecutionFailed(format!("List spaces failed: {}", e))
                 })?;
 
@@ -808,12 +853,9 @@ impl Tool for MemorySpacesTool {
                         ToolError::InvalidParameters("missing 'document_path' for add".to_string())
                     })?;
 
-                self.workspace
-                    .add_to_space(name, doc_path)
-                    .await
-                    .map_err(|e| {
-                        ToolError::ExecutionFailed(format!("Add to space failed: {}", e))
-                    })?;
+                workspace.add_to_space(name, doc_path).await.map_err(|e| {
+                    ToolError::ExecutionFailed(format!("Add to space failed: {}", e))
+                })?;
 
                 Ok(ToolOutput::success(
                     serde_json::json!({
@@ -837,7 +879,7 @@ impl Tool for MemorySpacesTool {
                         )
                     })?;
 
-                self.workspace
+                workspace
                     .remove_from_space(name, doc_path)
                     .await
                     .map_err(|e| {
@@ -858,13 +900,9 @@ impl Tool for MemorySpacesTool {
                     ToolError::InvalidParameters("missing 'name' for contents".to_string())
                 })?;
 
-                let docs = self
-                    .workspace
-                    .list_space_documents(name)
-                    .await
-                    .map_err(|e| {
-                        ToolError::ExecutionFailed(format!("List space contents failed: {}", e))
-                    })?;
+                let docs = workspace.list_space_documents(name).await.map_err(|e| {
+                    ToolError::ExecutionFailed(format!("List space contents failed: {}", e))
+                })?;
 
                 let output: Vec<serde_json::Value> = docs
                     .iter()
@@ -891,7 +929,7 @@ impl Tool for MemorySpacesTool {
                     ToolError::InvalidParameters("missing 'name' for delete".to_string())
                 })?;
 
-                self.workspace.delete_space(name).await.map_err(|e| {
+                workspace.delete_space(name).await.map_err(|e| {
                     ToolError::ExecutionFailed(format!("Delete space failed: {}", e))
                 })?;
 
@@ -925,6 +963,17 @@ impl MemoryProfileTool {
     pub fn new(workspace: Arc<Workspace>) -> Self {
         Self { workspace }
     }
+
+    /// Profile facts of a linked contact are kept under the contact's user
+    /// id so they follow the person across channels.
+    fn profile_workspace(&self, ctx: &JobContext) -> Arc<Workspace> {
+        let workspace = memory_workspace(&self.workspace, ctx);
+        if ctx.contact.is_some() && ctx.user_id != workspace.user_id() {
+            Arc::new(workspace.for_user(&ctx.user_id))
+        } else {
+            workspace
+        }
+    }
 }
 
 #[async_trait]
@@ -976,9 +1025,10 @@ impl Tool for MemoryProfileTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &JobContext,
+        ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError> {
         let start = std::time::Instant::now();
+        let workspace = self.profile_workspace(ctx);
 
         let action = params
             .get("action")
@@ -1012,7 +1062,7 @@ impl Tool for MemoryProfileTool {
                     .and_then(|v| v.as_str())
                     .unwrap_or("user_stated");
 
-                self.workspace
+                workspace
                     .set_profile_fact(profile_type, key, value, source)
                     .await
                     .map_err(|e| {
@@ -1030,7 +1080,7 @@ impl Tool for MemoryProfileTool {
                 ))
             }
             "get" => {
-                let facts = self.workspace.get_profile().await.map_err(|e| {
+                let facts = workspace.get_profile().await.map_err(|e| {
                     ToolError::ExecutionFailed(format!("Get profile failed: {}", e))
                 })?;
 
@@ -1061,7 +1111,7 @@ impl Tool for MemoryProfileTool {
                     ToolError::InvalidParameters("missing 'key' for delete".to_string())
                 })?;
 
-                self.workspace.delete_profile_fact(key).await.map_err(|e| {
+                workspace.delete_profile_fact(key).await.map_err(|e| {
                     ToolError::ExecutionFailed(format!("Delete profile entry failed: {}", e))
                 })?;
 
@@ -1082,6 +1132,191 @@ impl Tool for MemoryProfileTool {
     }
 }
 
+/// Tool for managing the project glossary (`GLOSSARY.md`).
+///
+/// Terms relevant to a conversation are added to the prompt, and final
+/// answers are rewritten to use them (see [`crate::workspace::glossary`]).
+pub struct MemoryGlossaryTool {
+    workspace: Arc<Workspace>,
+}
+
+impl MemoryGlossaryTool {
+    /// Create a new memory glossary tool.
+    pub fn new(workspace: Arc<Workspace>) -> Self {
+        Self { workspace }
+    }
+}
+
+#[async_trait]
+impl Tool for MemoryGlossaryTool {
+    fn name(&self) -> &str {
+        "memory_glossary"
+    }
+
+    fn description(&self) -> &str {
+        "Manage the user's project glossary: terms with definitions, phrasings to avoid \
+         in favor of each term, and banned words. Use 'add' when the user defines a term \
+         or corrects your wording, 'ban' for words they never want to see, 'list' to read \
+         the glossary."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "add", "remove", "ban", "unban"],
+                    "description": "Action to perform"
+                },
+                "term": {
+                    "type": "string",
+                    "description": "The term as it should be written (for add, remove)"
+                },
+                "definition": {
+                    "type": "string",
+                    "description": "What the term means (for add)"
+                },
+                "avoid": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Phrasings to replace with the term (for add)"
+                },
+                "word": {
+                    "type": "string",
+                    "description": "Banned word or phrase (for ban, unban)"
+                },
+                "replacement": {
+                    "type": "string",
+                    "description": "What to say instead of the banned word (for ban); masked when omitted"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        ctx: &JobContext,
+    ) -> Result<ToolOutput, ToolError> {
+        let start = std::time::Instant::now();
+        let workspace = memory_workspace(&self.workspace, ctx);
+
+        let action = params
+            .get("action")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                ToolError::InvalidParameters("missing 'action' parameter".to_string())
+            })?;
+
+        let mut glossary = workspace
+            .glossary()
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("Read glossary failed: {}", e)))?;
+
+        let output = match action {
+            "list" => {
+                return Ok(ToolOutput::success(
+                    serde_json::json!({
+                        "entries": glossary.entries,
+                        "banned": glossary.banned,
+                    }),
+                    start.elapsed(),
+                ));
+            }
+            "add" => {
+                let term = params
+                    .get("term")
+                    .and_then(|v| v.as_str())
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .ok_or_else(|| {
+                        ToolError::InvalidParameters("missing 'term' for add".to_string())
+                    })?;
+                let definition = params
+                    .get("definition")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .trim();
+                let avoid: Vec<String> = params
+                    .get("avoid")
+                    .and_then(|v| v.as_array())
+                    .map(|items| {
+                        items
+                            .iter()
+                            .filter_map(|v| v.as_str())
+                            .map(|s| s.trim().to_string())
+                            .filter(|s| !s.is_empty())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                glossary.upsert(GlossaryEntry {
+                    term: term.to_string(),
+                    definition: definition.to_string(),
+                    avoid,
+                });
+                serde_json::json!({ "status": "added", "entry": glossary.get(term) })
+            }
+            "remove" => {
+                let term = params.get("term").and_then(|v| v.as_str()).ok_or_else(|| {
+                    ToolError::InvalidParameters("missing 'term' for remove".to_string())
+                })?;
+                if !glossary.remove(term) {
+                    return Err(ToolError::InvalidParameters(format!(
+                        "'{}' is not in the glossary",
+                        term
+                    )));
+                }
+                serde_json::json!({ "status": "removed", "term": term })
+            }
+            "ban" => {
+                let word = params
+                    .get("word")
+                    .and_then(|v| v.as_str())
+                    .map(str::trim)
+                    .filter(|w| !w.is_empty())
+                    .ok_or_else(|| {
+                        ToolError::InvalidParameters("missing 'word' for ban".to_string())
+                    })?;
+                let replacement = params.get("replacement").and_then(|v| v.as_str());
+                glossary.ban(word, replacement);
+                serde_json::json!({ "status": "banned", "word": word, "replacement": replacement })
+            }
+            "unban" => {
+                let word = params.get("word").and_then(|v| v.as_str()).ok_or_else(|| {
+                    ToolError::InvalidParameters("missing 'word' for unban".to_string())
+                })?;
+                if !glossary.unban(word) {
+                    return Err(ToolError::InvalidParameters(format!(
+                        "'{}' is not banned",
+                        word
+                    )));
+                }
+                serde_json::json!({ "status": "unbanned", "word": word })
+            }
+            other => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "unknown action '{}'. Use: list, add, remove, ban, unban",
+                    other
+                )));
+            }
+        };
+
+        workspace
+            .save_glossary(&glossary)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("Write glossary failed: {}", e)))?;
+
+        Ok(ToolOutput::success(output, start.elapsed()))
+    }
+
+    fn requires_sanitization(&self) -> bool {
+        false
+    }
+}
+
 #[cfg(all(test, feature = "postgres"))]
 mod tests {
     use super::*;
@@ -1098,6 +1333,17 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn test_chunk_lines() {
+        let doc = "# Plan\n\nShip in March.\nBudget 40k.\n\nOwner: Dana\n";
+        assert_eq!(
+            chunk_lines(doc, "Ship in March.\nBudget 40k.\n"),
+            Some([3, 4])
+        );
+        assert_eq!(chunk_lines(doc, "# Plan"), Some([1, 1]));
+        assert_eq!(chunk_lines(doc, "not there"), None);
+    }
+
     #[test]
     fn test_memory_search_schema() {
         let workspace = make_test_workspace();
@@ -1,8 +1,14 @@
 //! Built-in tools that come with the agent.
 
+mod ask_user;
 mod browser;
+pub mod calendar;
+mod chart;
+pub mod code_review;
+mod delegate;
 mod echo;
 mod ecommerce;
+pub mod email;
 pub mod extension_tools;
 mod file;
 mod http;
@@ -10,33 +16,56 @@ mod job;
 mod json;
 mod marketplace;
 mod memory;
+mod notification_routes;
+mod pdf;
+mod pipeline;
 mod restaurant;
 pub mod routine;
+pub mod scratchpad;
 mod session_tools;
 pub(crate) mod shell;
+pub mod sql;
 mod taskrabbit;
 mod time;
+mod tool_output;
+pub mod web_fetch;
+mod workspace_edit;
 
-pub use browser::{BrowserAction, BrowserManager, BrowserSession, BrowserTool};
+pub use ask_user::{AskUserTool, DEFAULT_INPUT_TIMEOUT_SECS, FieldKind, FormField, InputForm};
+pub use browser::{BrowserAction, BrowserManager, BrowserSession, BrowserTool, CdpBrowser};
+pub use calendar::CalendarTool;
+pub use chart::ChartTool;
+pub use code_review::CodeReviewTool;
+pub use delegate::DelegateTool;
 pub use echo::EchoTool;
 pub use ecommerce::EcommerceTool;
+pub use email::{EmailAccounts, EmailReadTool, EmailSendTool};
 pub use extension_tools::{
     ToolActivateTool, ToolAuthTool, ToolInstallTool, ToolListTool, ToolRemoveTool, ToolSearchTool,
 };
 pub use file::{ApplyPatchTool, ListDirTool, ReadFileTool, WriteFileTool};
 pub use http::HttpTool;
+pub(crate) use http::validate_url;
 pub use job::{CancelJobTool, CreateJobTool, JobStatusTool, ListJobsTool};
 pub use json::JsonTool;
 pub use marketplace::MarketplaceTool;
 pub use memory::{
-    MemoryConnectTool, MemoryProfileTool, MemoryReadTool, MemorySearchTool, MemorySpacesTool,
-    MemoryTreeTool, MemoryWriteTool,
+    MemoryConnectTool, MemoryGlossaryTool, MemoryProfileTool, MemoryReadTool, MemorySearchTool,
+    MemorySpacesTool, MemoryTreeTool, MemoryWriteTool,
 };
+pub use notification_routes::NotificationRoutesTool;
+pub use pdf::GeneratePdfTool;
+pub use pipeline::{PipelineDef, PipelineRunner, PipelineStep, PipelineTool, StepReport};
 pub use restaurant::RestaurantTool;
 pub use routine::{
     RoutineCreateTool, RoutineDeleteTool, RoutineHistoryTool, RoutineListTool, RoutineUpdateTool,
 };
+pub use scratchpad::{ScratchpadStore, ScratchpadTool};
 pub use session_tools::{SessionHistoryTool, SessionListTool, SessionSendTool};
 pub use shell::ShellTool;
+pub use sql::SqlTool;
 pub use taskrabbit::TaskRabbitTool;
 pub use time::TimeTool;
+pub use tool_output::{ToolOutputStore, ToolOutputTool};
+pub use web_fetch::WebFetchTool;
+pub use workspace_edit::WorkspaceEditTool;
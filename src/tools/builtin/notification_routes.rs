@@ -0,0 +1,176 @@
+//! LLM-facing tool for editing notification routing rules.
+//!
+//! Lets the user say "send security alerts to Telegram" in chat. Changes
+//! apply immediately and are saved to the `notifications.routes` setting.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::channels::{NotificationCategory, NotificationRouter, RouteRule};
+use crate::context::JobContext;
+use crate::db::Database;
+use crate::settings::Settings;
+use crate::tools::tool::{Tool, ToolError, ToolOutput};
+
+/// Settings key the rules are stored under.
+const ROUTES_SETTING: &str = "notifications.routes";
+
+pub struct NotificationRoutesTool {
+    router: Arc<NotificationRouter>,
+    store: Option<Arc<dyn Database>>,
+}
+
+impl NotificationRoutesTool {
+    pub fn new(router: Arc<NotificationRouter>, store: Option<Arc<dyn Database>>) -> Self {
+        Self { router, store }
+    }
+
+    /// Save the rule set where config is loaded from: the DB settings of the
+    /// "default" user when a database is connected, else settings.json.
+    async fn persist(&self, rules: &[RouteRule]) -> Result<(), ToolError> {
+        let value = serde_json::to_value(rules)
+            .map_err(|e| ToolError::ExecutionFailed(format!("serialize routes: {e}")))?;
+        match self.store {
+            Some(ref store) => store
+                .set_setting("default", ROUTES_SETTING, &value)
+                .await
+                .map_err(|e| ToolError::ExecutionFailed(format!("DB error: {e}"))),
+            None => {
+                let mut settings = Settings::load();
+                settings.notifications.routes = rules.to_vec();
+                settings
+                    .save()
+                    .map_err(|e| ToolError::ExecutionFailed(format!("save settings: {e}")))
+            }
+        }
+    }
+}
+
+fn listing(rules: &[RouteRule]) -> serde_json::Value {
+    serde_json::json!({
+        "routes": rules
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                let mut entry = serde_json::to_value(rule).unwrap_or_default();
+                entry["index"] = index.into();
+                entry
+            })
+            .collect::<Vec<_>>(),
+    })
+}
+
+#[async_trait]
+impl Tool for NotificationRoutesTool {
+    fn name(&self) -> &str {
+        "notification_routes"
+    }
+
+    fn description(&self) -> &str {
+        "List, add or remove rules that route background notifications (job finished, \
+         approval needed, security alert, routine digest) to specific channels, optionally \
+         only during certain local hours. Notifications without an active rule go where \
+         they always did."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "add", "remove"],
+                    "description": "What to do"
+                },
+                "category": {
+                    "type": "string",
+                    "enum": NotificationCategory::ALL.map(|c| c.as_str()),
+                    "description": "Notification category (for add)"
+                },
+                "channel": {
+                    "type": "string",
+                    "description": "Channel to deliver to, e.g. 'telegram' (for add)"
+                },
+                "user": {
+                    "type": "string",
+                    "description": "Recipient on that channel; defaults to the usual recipient"
+                },
+                "hours": {
+                    "type": "string",
+                    "description": "Local time window HH:MM-HH:MM, may wrap midnight (e.g. '08:00-18:00'). Omit for always."
+                },
+                "index": {
+                    "type": "integer",
+                    "description": "Index of the rule to remove, from 'list'"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        _ctx: &JobContext,
+    ) -> Result<ToolOutput, ToolError> {
+        let start = std::time::Instant::now();
+        let action = params
+            .get("action")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidParameters("missing 'action'".to_string()))?;
+        let str_param = |key: &str| params.get(key).and_then(|v| v.as_str()).map(String::from);
+
+        let rules = match action {
+            "list" => {
+                return Ok(ToolOutput::success(
+                    listing(&self.router.rules()),
+                    start.elapsed(),
+                ));
+            }
+            "add" => {
+                let category = str_param("category")
+                    .ok_or_else(|| ToolError::InvalidParameters("missing 'category'".to_string()))?
+                    .parse::<NotificationCategory>()
+                    .map_err(ToolError::InvalidParameters)?;
+                let channel = str_param("channel")
+                    .ok_or_else(|| ToolError::InvalidParameters("missing 'channel'".to_string()))?;
+                self.router
+                    .add(RouteRule {
+                        category,
+                        channel,
+                        user: str_param("user"),
+                        hours: str_param("hours"),
+                    })
+                    .map_err(ToolError::InvalidParameters)?
+            }
+            "remove" => {
+                let index = params
+                    .get("index")
+                    .and_then(|v| v.as_u64())
+                    .ok_or_else(|| ToolError::InvalidParameters("missing 'index'".to_string()))?;
+                self.router
+                    .remove(index as usize)
+                    .map_err(ToolError::InvalidParameters)?
+            }
+            other => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "unknown action '{}'",
+                    other
+                )));
+            }
+        };
+
+        self.persist(&rules).await?;
+        Ok(ToolOutput::success(listing(&rules), start.elapsed()))
+    }
+
+    fn requires_sanitization(&self) -> bool {
+        false
+    }
+
+    /// Redirecting alerts is sensitive; only listing runs without approval.
+    fn requires_approval_for(&self, params: &serde_json::Value) -> bool {
+        params.get("action").and_then(|v| v.as_str()) != Some("list")
+    }
+}
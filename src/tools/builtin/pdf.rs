@@ -0,0 +1,223 @@
+//! PDF generation tool.
+//!
+//! Renders Markdown or HTML to a PDF in the exports directory so the agent
+//! can hand over a polished document instead of a wall of chat text.
+
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+
+use crate::context::JobContext;
+use crate::media::{PageSize, PdfOptions, PdfRenderer, default_exports_dir};
+use crate::tools::tool::{Tool, ToolError, ToolOutput};
+
+/// Largest source document accepted, in bytes.
+const MAX_CONTENT_BYTES: usize = 1024 * 1024;
+
+/// Renders documents to PDF files.
+pub struct GeneratePdfTool {
+    dir: PathBuf,
+}
+
+impl GeneratePdfTool {
+    pub fn new() -> Self {
+        Self {
+            dir: default_exports_dir(),
+        }
+    }
+
+    /// Write PDFs to `dir` instead of the default exports directory.
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+}
+
+impl Default for GeneratePdfTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reduce a requested file name to a safe `<stem>.pdf`.
+fn pdf_file_name(requested: Option<&str>) -> String {
+    let stem: String = requested
+        .map(|name| name.trim().trim_end_matches(".pdf"))
+        .unwrap_or("")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .collect();
+    let stem = stem.trim_matches('.');
+    if stem.is_empty() {
+        format!(
+            "document-{}.pdf",
+            chrono::Utc::now().format("%Y%m%d-%H%M%S")
+        )
+    } else {
+        format!("{}.pdf", stem)
+    }
+}
+
+#[async_trait]
+impl Tool for GeneratePdfTool {
+    fn name(&self) -> &str {
+        "generate_pdf"
+    }
+
+    fn description(&self) -> &str {
+        "Render Markdown or HTML into a PDF file and return its path. Use it for \
+         reports, summaries or anything long enough to read better as a document \
+         than as a chat message."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "content": {
+                    "type": "string",
+                    "description": "Document body in Markdown (default) or HTML"
+                },
+                "format": {
+                    "type": "string",
+                    "enum": ["markdown", "html"],
+                    "description": "Format of 'content' (default: markdown)"
+                },
+                "title": {
+                    "type": "string",
+                    "description": "Document title for the PDF metadata (default: first heading)"
+                },
+                "file_name": {
+                    "type": "string",
+                    "description": "Output file name, e.g. 'q3-summary' (default: timestamped)"
+                },
+                "page_size": {
+                    "type": "string",
+                    "enum": ["a4", "letter"],
+                    "description": "Page size (default: a4)"
+                }
+            },
+            "required": ["content"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        _ctx: &JobContext,
+    ) -> Result<ToolOutput, ToolError> {
+        let start = std::time::Instant::now();
+
+        let content = params
+            .get("content")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                ToolError::InvalidParameters("missing 'content' parameter".to_string())
+            })?;
+        if content.len() > MAX_CONTENT_BYTES {
+            return Err(ToolError::InvalidParameters(format!(
+                "content is {} bytes; the limit is {}",
+                content.len(),
+                MAX_CONTENT_BYTES
+            )));
+        }
+        let html = match params.get("format").and_then(|v| v.as_str()) {
+            None | Some("markdown") => false,
+            Some("html") => true,
+            Some(other) => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "unknown format '{}' (expected markdown or html)",
+                    other
+                )));
+            }
+        };
+        let page_size = match params.get("page_size").and_then(|v| v.as_str()) {
+            None | Some("a4") => PageSize::A4,
+            Some("letter") => PageSize::Letter,
+            Some(other) => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "unknown page size '{}' (expected a4 or letter)",
+                    other
+                )));
+            }
+        };
+
+        let renderer = PdfRenderer::new(PdfOptions {
+            page_size,
+            title: params
+                .get("title")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            ..Default::default()
+        });
+        let pdf = if html {
+            renderer.render_html(content)
+        } else {
+            renderer.render_markdown(content)
+        };
+
+        let file_name = pdf_file_name(params.get("file_name").and_then(|v| v.as_str()));
+        let path = self.dir.join(&file_name);
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("failed to create directory: {e}")))?;
+        tokio::fs::write(&path, &pdf)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("failed to write PDF: {e}")))?;
+
+        Ok(ToolOutput::success(
+            serde_json::json!({
+                "path": path.display().to_string(),
+                "file_name": file_name,
+                "mime_type": "application/pdf",
+                "bytes": pdf.len(),
+            }),
+            start.elapsed(),
+        ))
+    }
+
+    fn requires_sanitization(&self) -> bool {
+        false // Output is a path we chose, not external data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pdf_file_name_is_sanitized() {
+        assert_eq!(pdf_file_name(Some("q3-summary")), "q3-summary.pdf");
+        assert_eq!(pdf_file_name(Some("notes.pdf")), "notes.pdf");
+        assert_eq!(pdf_file_name(Some("../../etc/passwd")), "etcpasswd.pdf");
+        assert!(pdf_file_name(Some("..")).starts_with("document-"));
+        assert!(pdf_file_name(None).ends_with(".pdf"));
+    }
+
+    #[tokio::test]
+    async fn test_generate_pdf_writes_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = GeneratePdfTool::with_dir(dir.path().to_path_buf());
+        let ctx = JobContext::default();
+
+        let output = tool
+            .execute(
+                serde_json::json!({
+                    "content": "<h1>Summary</h1><p>All good.</p>",
+                    "format": "html",
+                    "file_name": "summary"
+                }),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(output.result["file_name"], "summary.pdf");
+        let bytes = std::fs::read(dir.path().join("summary.pdf")).unwrap();
+        assert!(bytes.starts_with(b"%PDF-"));
+        assert_eq!(output.result["bytes"], bytes.len());
+
+        let err = tool
+            .execute(serde_json::json!({"content": "x", "format": "docx"}), &ctx)
+            .await;
+        assert!(matches!(err, Err(ToolError::InvalidParameters(_))));
+    }
+}
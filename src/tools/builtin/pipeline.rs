@@ -0,0 +1,1136 @@
+//! Composite tool pipelines.
+//!
+//! A pipeline is a named DAG of existing tools, stored as JSON at
+//! `pipelines/<name>.json` in the workspace so both the agent and the user
+//! can write one:
+//!
+//! ```json
+//! {
+//!   "name": "weather_brief",
+//!   "description": "Fetch a forecast and pick out today's entry",
+//!   "steps": [
+//!     {"id": "fetch", "tool": "http",
+//!      "params": {"method": "GET", "url": "https://api.example.com/forecast?city={{$.input.city}}"}},
+//!     {"id": "today", "tool": "json",
+//!      "params": {"operation": "query", "data": "$.steps.fetch.body", "path": "days[0]"}}
+//!   ],
+//!   "output": "$.steps.today"
+//! }
+//! ```
+//!
+//! Step parameters bind to earlier results with JSONPath-style paths
+//! rooted at `$.input` (the run's input) or `$.steps.<id>` (a step's
+//! output). A string that is exactly a path takes the JSON value; `{{path}}`
+//! inside a longer string interpolates it. Those references, plus an
+//! optional `after` list, make up the DAG. Steps whose dependencies are
+//! done run concurrently.
+//!
+//! Every step goes through the same checks as a direct call: parameter
+//! validation, sanitization of outputs from tools that need it, and
+//! approval. Approval is asked once for the `pipeline` call when any step's
+//! tool would ask, for its template or for the template with the run's
+//! input filled in, and the prompt lists the steps. A step whose resolved
+//! parameters need approval that wasn't asked for (a shell command bound
+//! to an earlier step's output, say) is refused, as is any step that must
+//! be approved call by call. Replacing a stored pipeline needs approval
+//! too, and approving a pipeline is never remembered.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, RwLock, Weak};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::context::JobContext;
+use crate::error::WorkspaceError;
+use crate::safety::SafetyLayer;
+use crate::tools::registry::ToolRegistry;
+use crate::tools::tool::{Tool, ToolError, ToolOutput};
+use crate::workspace::{Workspace, paths};
+
+/// Tools a pipeline step may not call: pipelines don't nest, and steps
+/// can't stop to ask the user anything.
+const FORBIDDEN_STEP_TOOLS: &[&str] = &["pipeline", "ask_user"];
+
+/// Upper bound for a whole run; each step still has its tool's own timeout.
+const RUN_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// A stored pipeline definition.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineDef {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub steps: Vec<PipelineStep>,
+    /// Template for the run's result; the last step's output when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<serde_json::Value>,
+}
+
+/// One tool call in a pipeline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineStep {
+    pub id: String,
+    pub tool: String,
+    /// Parameter template; bindings are resolved when the step runs.
+    #[serde(default = "empty_object")]
+    pub params: serde_json::Value,
+    /// Steps that must finish first without feeding this one any data.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<String>,
+}
+
+fn empty_object() -> serde_json::Value {
+    serde_json::json!({})
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+impl PipelineDef {
+    /// Check names, tools and references, and order the steps into stages:
+    /// every step's dependencies are in earlier stages.
+    pub fn stages(&self) -> Result<Vec<Vec<usize>>, String> {
+        if !valid_name(&self.name) {
+            return Err(format!(
+                "invalid pipeline name '{}': use letters, digits, '_' and '-'",
+                self.name
+            ));
+        }
+        if self.steps.is_empty() {
+            return Err("a pipeline needs at least one step".to_string());
+        }
+
+        let mut index = HashMap::new();
+        for (i, step) in self.steps.iter().enumerate() {
+            if !valid_name(&step.id) {
+                return Err(format!("invalid step id '{}'", step.id));
+            }
+            if FORBIDDEN_STEP_TOOLS.contains(&step.tool.as_str()) {
+                return Err(format!(
+                    "step '{}' can't call '{}' from a pipeline",
+                    step.id, step.tool
+                ));
+            }
+            if index.insert(step.id.as_str(), i).is_some() {
+                return Err(format!("duplicate step id '{}'", step.id));
+            }
+        }
+
+        let mut deps = Vec::with_capacity(self.steps.len());
+        for step in &self.steps {
+            let mut refs = step_refs(&step.params)?;
+            refs.extend(step.after.iter().cloned());
+            let mut ids = BTreeSet::new();
+            for r in refs {
+                let Some(&i) = index.get(r.as_str()) else {
+                    return Err(format!("step '{}' refers to unknown step '{}'", step.id, r));
+                };
+                if r == step.id {
+                    return Err(format!("step '{}' refers to itself", step.id));
+                }
+                ids.insert(i);
+            }
+            deps.push(ids);
+        }
+        if let Some(output) = &self.output {
+            for r in step_refs(output)? {
+                if !index.contains_key(r.as_str()) {
+                    return Err(format!("output refers to unknown step '{}'", r));
+                }
+            }
+        }
+
+        // Kahn's algorithm, one stage per round.
+        let mut done = vec![false; self.steps.len()];
+        let mut stages = Vec::new();
+        while done.iter().any(|d| !d) {
+            let stage: Vec<usize> = (0..self.steps.len())
+                .filter(|&i| !done[i] && deps[i].iter().all(|&d| done[d]))
+                .collect();
+            if stage.is_empty() {
+                let stuck: Vec<&str> = (0..self.steps.len())
+                    .filter(|&i| !done[i])
+                    .map(|i| self.steps[i].id.as_str())
+                    .collect();
+                return Err(format!("steps form a cycle: {}", stuck.join(", ")));
+            }
+            for &i in &stage {
+                done[i] = true;
+            }
+            stages.push(stage);
+        }
+        Ok(stages)
+    }
+}
+
+/// A parsed path segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Parse `$.a.b[0]['c d']` into segments.
+fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+    let invalid = || format!("invalid binding '{}'", path);
+    let rest = path.trim().strip_prefix('$').ok_or_else(invalid)?;
+    let mut segments = Vec::new();
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                let mut key = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == '.' || c == '[' {
+                        break;
+                    }
+                    key.push(c);
+                    chars.next();
+                }
+                if key.is_empty() {
+                    return Err(invalid());
+                }
+                segments.push(Segment::Key(key));
+            }
+            '[' => {
+                let mut inner = String::new();
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    inner.push(c);
+                }
+                let quoted = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+                segments.push(match quoted {
+                    Some(key) => Segment::Key(key.to_string()),
+                    None => Segment::Index(inner.trim().parse().map_err(|_| invalid())?),
+                });
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    match segments.first() {
+        Some(Segment::Key(root)) if root == "input" => Ok(segments),
+        Some(Segment::Key(root)) if root == "steps" && segments.len() >= 2 => Ok(segments),
+        _ => Err(format!(
+            "binding '{}' must start with $.input or $.steps.<id>",
+            path
+        )),
+    }
+}
+
+fn lookup<'a>(scope: &'a serde_json::Value, segments: &[Segment]) -> Option<&'a serde_json::Value> {
+    segments
+        .iter()
+        .try_fold(scope, |value, segment| match segment {
+            Segment::Key(key) => value.get(key),
+            Segment::Index(i) => value.get(i),
+        })
+}
+
+/// Whole-value binding: the string is a path and nothing else.
+fn as_binding(s: &str) -> Option<&str> {
+    let s = s.trim();
+    (s == "$" || s.starts_with("$.") || s.starts_with("$[")).then_some(s)
+}
+
+/// `{{path}}` spans inside a string, as (start, end, path).
+fn interpolations(s: &str) -> Vec<(usize, usize, &str)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(open) = s[from..].find("{{") {
+        let start = from + open;
+        let Some(close) = s[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + close + 2;
+        found.push((start, end, s[start + 2..end - 2].trim()));
+        from = end;
+    }
+    found
+}
+
+/// Ids of the steps a template reads from.
+fn step_refs(template: &serde_json::Value) -> Result<BTreeSet<String>, String> {
+    fn walk(value: &serde_json::Value, refs: &mut BTreeSet<String>) -> Result<(), String> {
+        match value {
+            serde_json::Value::String(s) => {
+                let paths: Vec<&str> = match as_binding(s) {
+                    Some(path) => vec![path],
+                    None => interpolations(s).into_iter().map(|(_, _, p)| p).collect(),
+                };
+                for path in paths {
+                    if let [Segment::Key(root), Segment::Key(id), ..] = parse_path(path)?.as_slice()
+                        && root == "steps"
+                    {
+                        refs.insert(id.clone());
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    walk(item, refs)?;
+                }
+            }
+            serde_json::Value::Object(map) => {
+                for item in map.values() {
+                    walk(item, refs)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    let mut refs = BTreeSet::new();
+    walk(template, &mut refs)?;
+    Ok(refs)
+}
+
+/// A step's parameters as far as they are known before the run: the
+/// template with its `$.input` bindings filled in. `None` when it also
+/// reads earlier steps' outputs.
+fn preview_params(step: &PipelineStep, input: &serde_json::Value) -> Option<serde_json::Value> {
+    resolve(
+        &step.params,
+        &serde_json::json!({ "input": input, "steps": {} }),
+    )
+    .ok()
+}
+
+/// Fill a template's bindings from `scope` (`{"input": .., "steps": {..}}`).
+fn resolve(
+    template: &serde_json::Value,
+    scope: &serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let fetch = |path: &str| {
+        lookup(scope, &parse_path(path)?).ok_or_else(|| format!("'{}' matched nothing", path))
+    };
+    Ok(match template {
+        serde_json::Value::String(s) => {
+            if let Some(path) = as_binding(s) {
+                fetch(path)?.clone()
+            } else {
+                let spans = interpolations(s);
+                if spans.is_empty() {
+                    return Ok(template.clone());
+                }
+                let mut out = String::with_capacity(s.len());
+                let mut last = 0;
+                for (start, end, path) in spans {
+                    out.push_str(&s[last..start]);
+                    match fetch(path)? {
+                        serde_json::Value::String(v) => out.push_str(v),
+                        v => out.push_str(&v.to_string()),
+                    }
+                    last = end;
+                }
+                out.push_str(&s[last..]);
+                serde_json::Value::String(out)
+            }
+        }
+        serde_json::Value::Array(items) => serde_json::Value::Array(
+            items
+                .iter()
+                .map(|item| resolve(item, scope))
+                .collect::<Result<_, _>>()?,
+        ),
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(k, v)| Ok((k.clone(), resolve(v, scope)?)))
+                .collect::<Result<_, String>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+/// What one step did, reported with the run's result.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepReport {
+    pub id: String,
+    pub tool: String,
+    pub elapsed_ms: u64,
+    /// Whether sanitization changed the output.
+    pub sanitized: bool,
+}
+
+/// Executes pipeline definitions against the tool registry.
+pub struct PipelineRunner {
+    registry: Weak<ToolRegistry>,
+    safety: Arc<SafetyLayer>,
+}
+
+impl PipelineRunner {
+    pub fn new(registry: Weak<ToolRegistry>, safety: Arc<SafetyLayer>) -> Self {
+        Self { registry, safety }
+    }
+
+    fn registry(&self) -> Result<Arc<ToolRegistry>, ToolError> {
+        self.registry
+            .upgrade()
+            .ok_or_else(|| ToolError::ExecutionFailed("tool registry is gone".to_string()))
+    }
+
+    /// Whether running `def` with `input` needs approval: any step's tool
+    /// would ask for its parameter template or for the template with the
+    /// input filled in, or the tool isn't known yet.
+    pub fn requires_approval(&self, def: &PipelineDef, input: &serde_json::Value) -> bool {
+        let Some(registry) = self.registry.upgrade() else {
+            return true;
+        };
+        def.steps
+            .iter()
+            .any(|step| match registry.get_sync(&step.tool) {
+                Some(tool) => step_requires_approval(tool.as_ref(), step, input),
+                None => true,
+            })
+    }
+
+    /// Run every step and return the output with per-step reports.
+    pub async fn run(
+        &self,
+        def: &PipelineDef,
+        input: serde_json::Value,
+        ctx: &JobContext,
+    ) -> Result<(serde_json::Value, Vec<StepReport>), ToolError> {
+        let stages = def.stages().map_err(ToolError::InvalidParameters)?;
+        let mut scope = serde_json::json!({ "input": input, "steps": {} });
+        let mut reports = Vec::with_capacity(def.steps.len());
+
+        for stage in stages {
+            let results = futures::future::join_all(
+                stage
+                    .iter()
+                    .map(|&i| self.run_step(&def.steps[i], &scope, ctx)),
+            )
+            .await;
+            for (&i, result) in stage.iter().zip(results) {
+                let (value, report) = result.map_err(|e| {
+                    ToolError::ExecutionFailed(format!(
+                        "step '{}' ({}) failed: {}",
+                        def.steps[i].id, def.steps[i].tool, e
+                    ))
+                })?;
+                scope["steps"][def.steps[i].id.as_str()] = value;
+                reports.push(report);
+            }
+        }
+
+        let output = match &def.output {
+            Some(template) => resolve(template, &scope)
+                .map_err(|e| ToolError::ExecutionFailed(format!("output: {}", e)))?,
+            None => {
+                let last = &def.steps[def.steps.len() - 1].id;
+                scope["steps"][last.as_str()].take()
+            }
+        };
+        Ok((output, reports))
+    }
+
+    async fn run_step(
+        &self,
+        step: &PipelineStep,
+        scope: &serde_json::Value,
+        ctx: &JobContext,
+    ) -> Result<(serde_json::Value, StepReport), String> {
+        let tool = self
+            .registry()
+            .map_err(|e| e.to_string())?
+            .get(&step.tool)
+            .await
+            .ok_or_else(|| format!("tool '{}' not found", step.tool))?;
+        let params = resolve(&step.params, scope)?;
+
+        let validation = self.safety.validator().validate_tool_params(&params);
+        if !validation.is_valid {
+            let details = validation
+                .errors
+                .iter()
+                .map(|e| format!("{}: {}", e.field, e.message))
+                .collect::<Vec<_>>()
+                .join("; ");
+            return Err(format!("invalid parameters: {}", details));
+        }
+        // Some calls are approved one at a time, never as part of a batch.
+        if tool.requires_explicit_approval(&params) {
+            return Err(format!(
+                "'{}' must be approved call by call; call it directly",
+                step.tool
+            ));
+        }
+        // The pipeline call was approved against the templates and the run's
+        // input; earlier steps' outputs must not add an approval it didn't ask.
+        if tool.requires_approval_for(&params)
+            && !step_requires_approval(tool.as_ref(), step, &scope["input"])
+        {
+            return Err(format!(
+                "these parameters need approval; call '{}' directly",
+                step.tool
+            ));
+        }
+
+        let start = Instant::now();
+        let timeout = tool.execution_timeout();
+        let output = tokio::time::timeout(timeout, tool.execute(params, ctx))
+            .await
+            .map_err(|_| format!("timed out after {:?}", timeout))?
+            .map_err(|e| e.to_string())?;
+
+        let mut value = output.result;
+        let mut sanitized = false;
+        if tool.requires_sanitization() {
+            let text = match &value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            let clean = self
+                .safety
+                .sanitize_tool_output(&ctx.user_id, &step.tool, &text);
+            if clean.was_modified {
+                sanitized = true;
+                value = serde_json::from_str(&clean.content)
+                    .unwrap_or(serde_json::Value::String(clean.content));
+            }
+        }
+
+        Ok((
+            value,
+            StepReport {
+                id: step.id.clone(),
+                tool: step.tool.clone(),
+                elapsed_ms: start.elapsed().as_millis() as u64,
+                sanitized,
+            },
+        ))
+    }
+}
+
+/// Whether `step` needs approval for its template or its input-filled preview.
+fn step_requires_approval(tool: &dyn Tool, step: &PipelineStep, input: &serde_json::Value) -> bool {
+    tool.requires_approval_for(&step.params)
+        || preview_params(step, input).is_some_and(|params| tool.requires_approval_for(&params))
+}
+
+/// Tool for defining, listing and running pipelines.
+pub struct PipelineTool {
+    workspace: Arc<Workspace>,
+    runner: PipelineRunner,
+    /// Definitions as last loaded from the workspace; approval is decided
+    /// from these synchronously, so `run` uses the same copy.
+    pipelines: RwLock<BTreeMap<String, PipelineDef>>,
+}
+
+impl PipelineTool {
+    pub fn new(workspace: Arc<Workspace>, runner: PipelineRunner) -> Self {
+        Self {
+            workspace,
+            runner,
+            pipelines: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    fn path(name: &str) -> String {
+        format!("{}{}.json", paths::PIPELINES_DIR, name)
+    }
+
+    /// Reload definitions from `pipelines/` in the workspace. Files that
+    /// don't parse are logged and skipped.
+    pub async fn reload(&self) -> Result<usize, WorkspaceError> {
+        let mut loaded = BTreeMap::new();
+        let entries = self
+            .workspace
+            .list(paths::PIPELINES_DIR.trim_end_matches('/'))
+            .await?;
+        for entry in entries {
+            if entry.is_directory || !entry.name().ends_with(".json") {
+                continue;
+            }
+            let path = format!("{}{}", paths::PIPELINES_DIR, entry.name());
+            let doc = self.workspace.read(&path).await?;
+            match serde_json::from_str::<PipelineDef>(&doc.content) {
+                Ok(def) => {
+                    loaded.insert(def.name.clone(), def);
+                }
+                Err(e) => tracing::warn!(path = %path, "Skipping invalid pipeline: {}", e),
+            }
+        }
+        let count = loaded.len();
+        *self.pipelines.write().unwrap_or_else(|e| e.into_inner()) = loaded;
+        Ok(count)
+    }
+
+    fn cached(&self, name: &str) -> Option<PipelineDef> {
+        self.pipelines
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(name)
+            .cloned()
+    }
+
+    fn summaries(&self) -> Vec<serde_json::Value> {
+        self.pipelines
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .map(|def| {
+                serde_json::json!({
+                    "name": def.name,
+                    "description": def.description,
+                    "tools": def.steps.iter().map(|s| s.tool.as_str()).collect::<Vec<_>>(),
+                })
+            })
+            .collect()
+    }
+
+    async fn get_or_reload(&self, name: &str) -> Result<PipelineDef, ToolError> {
+        if let Some(def) = self.cached(name) {
+            return Ok(def);
+        }
+        self.reload()
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("Load pipelines failed: {}", e)))?;
+        self.cached(name)
+            .ok_or_else(|| ToolError::InvalidParameters(format!("no pipeline named '{}'", name)))
+    }
+}
+
+/// The definition a `define` call carries, if it parses.
+fn defined(params: &serde_json::Value) -> Option<PipelineDef> {
+    serde_json::from_value(params.get("pipeline")?.clone()).ok()
+}
+
+/// One line per step, parameters filled in from `input` where known.
+fn describe_steps(def: &PipelineDef, input: &serde_json::Value) -> String {
+    def.steps
+        .iter()
+        .map(|step| {
+            let params = preview_params(step, input).unwrap_or_else(|| step.params.clone());
+            format!("  {}: {} {}", step.id, step.tool, params)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn required_str<'a>(params: &'a serde_json::Value, key: &str) -> Result<&'a str, ToolError> {
+    params
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| ToolError::InvalidParameters(format!("missing '{}' parameter", key)))
+}
+
+#[async_trait]
+impl Tool for PipelineTool {
+    fn name(&self) -> &str {
+        "pipeline"
+    }
+
+    fn description(&self) -> &str {
+        "Define and run composite tool pipelines: a named DAG of existing tools where \
+         step parameters bind to the run input ($.input.x) and earlier outputs \
+         ($.steps.<id>.field, or {{...}} inside strings). Use 'define' to save a \
+         repeatable multi-tool workflow, 'run' to execute one as a single call, \
+         'list'/'show'/'delete' to manage them."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "show", "define", "delete", "run"],
+                    "description": "Action to perform"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Pipeline name (for show, delete, run)"
+                },
+                "pipeline": {
+                    "type": "object",
+                    "description": "Definition (for define): {name, description, steps: [{id, tool, params, after?}], output?}"
+                },
+                "input": {
+                    "type": "object",
+                    "description": "Values for $.input bindings (for run)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        ctx: &JobContext,
+    ) -> Result<ToolOutput, ToolError> {
+        let start = Instant::now();
+        let action = required_str(&params, "action")?;
+
+        let result = match action {
+            "list" => {
+                self.reload().await.map_err(|e| {
+                    ToolError::ExecutionFailed(format!("Load pipelines failed: {}", e))
+                })?;
+                serde_json::json!({ "pipelines": self.summaries() })
+            }
+            "show" => {
+                let def = self.get_or_reload(required_str(&params, "name")?).await?;
+                serde_json::to_value(def).unwrap_or_default()
+            }
+            "define" => {
+                let def: PipelineDef =
+                    serde_json::from_value(params.get("pipeline").cloned().unwrap_or_default())
+                        .map_err(|e| {
+                            ToolError::InvalidParameters(format!("invalid pipeline: {}", e))
+                        })?;
+                let stages = def.stages().map_err(ToolError::InvalidParameters)?;
+                let registry = self.runner.registry()?;
+                for step in &def.steps {
+                    let Some(tool) = registry.get(&step.tool).await else {
+                        return Err(ToolError::InvalidParameters(format!(
+                            "step '{}' uses unknown tool '{}'",
+                            step.id, step.tool
+                        )));
+                    };
+                    if tool.requires_explicit_approval(&step.params) {
+                        return Err(ToolError::InvalidParameters(format!(
+                            "step '{}': '{}' must be approved call by call and can't run in a pipeline",
+                            step.id, step.tool
+                        )));
+                    }
+                }
+                // Replacing a pipeline is approved against the loaded copy; one
+                // that wasn't loaded when approval was decided is not replaced.
+                if self.cached(&def.name).is_none()
+                    && self
+                        .workspace
+                        .exists(&Self::path(&def.name))
+                        .await
+                        .unwrap_or(true)
+                {
+                    let _ = self.reload().await;
+                    return Err(ToolError::ExecutionFailed(format!(
+                        "a pipeline named '{}' already exists; define it again to replace it",
+                        def.name
+                    )));
+                }
+                let body = serde_json::to_string_pretty(&def).unwrap_or_default();
+                self.workspace
+                    .write(&Self::path(&def.name), &body)
+                    .await
+                    .map_err(|e| ToolError::ExecutionFailed(format!("Save failed: {}", e)))?;
+                let requires_approval = self.runner.requires_approval(&def, &empty_object());
+                let name = def.name.clone();
+                self.pipelines
+                    .write()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(name.clone(), def);
+                serde_json::json!({
+                    "status": "defined",
+                    "name": name,
+                    "stages": stages.len(),
+                    "requires_approval": requires_approval,
+                })
+            }
+            "delete" => {
+                let name = required_str(&params, "name")?;
+                self.workspace
+                    .delete(&Self::path(name))
+                    .await
+                    .map_err(|e| ToolError::ExecutionFailed(format!("Delete failed: {}", e)))?;
+                self.pipelines
+                    .write()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .remove(name);
+                serde_json::json!({ "status": "deleted", "name": name })
+            }
+            "run" => {
+                let def = self.get_or_reload(required_str(&params, "name")?).await?;
+                let input = params.get("input").cloned().unwrap_or_else(empty_object);
+                let (output, steps) = self.runner.run(&def, input, ctx).await?;
+                serde_json::json!({
+                    "pipeline": def.name,
+                    "output": output,
+                    "steps": steps,
+                })
+            }
+            other => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "unknown action '{}'",
+                    other
+                )));
+            }
+        };
+        Ok(ToolOutput::success(result, start.elapsed()))
+    }
+
+    fn requires_approval_for(&self, params: &serde_json::Value) -> bool {
+        match params.get("action").and_then(|v| v.as_str()) {
+            Some("run") => {
+                let Some(name) = params.get("name").and_then(|v| v.as_str()) else {
+                    return false;
+                };
+                let input = params.get("input").cloned().unwrap_or_else(empty_object);
+                // Unknown until loaded: ask rather than run unchecked.
+                self.cached(name.trim())
+                    .is_none_or(|def| self.runner.requires_approval(&def, &input))
+            }
+            // Replacing a pipeline changes what an earlier approval covered.
+            Some("define") => defined(params).is_some_and(|def| {
+                self.cached(&def.name)
+                    .is_some_and(|existing| existing != def)
+            }),
+            _ => false,
+        }
+    }
+
+    fn approval_details(&self, params: &serde_json::Value) -> Option<String> {
+        match params.get("action").and_then(|v| v.as_str()) {
+            Some("run") => {
+                let name = params.get("name").and_then(|v| v.as_str())?.trim();
+                let input = params.get("input").cloned().unwrap_or_else(empty_object);
+                Some(match self.cached(name) {
+                    Some(def) => format!(
+                        "Run pipeline '{}' with input {}:\n{}",
+                        name,
+                        input,
+                        describe_steps(&def, &input)
+                    ),
+                    None => format!(
+                        "Run pipeline '{}' (not loaded; its steps are unknown)",
+                        name
+                    ),
+                })
+            }
+            Some("define") => {
+                let def = defined(params)?;
+                Some(format!(
+                    "Replace pipeline '{}' with:\n{}",
+                    def.name,
+                    describe_steps(&def, &empty_object())
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn execution_timeout(&self) -> Duration {
+        RUN_TIMEOUT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SafetyConfig;
+
+    struct UpperTool;
+
+    #[async_trait]
+    impl Tool for UpperTool {
+        fn name(&self) -> &str {
+            "upper"
+        }
+        fn description(&self) -> &str {
+            "Uppercases text"
+        }
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+        async fn execute(
+            &self,
+            params: serde_json::Value,
+            _ctx: &JobContext,
+        ) -> Result<ToolOutput, ToolError> {
+            let text = params["text"].as_str().unwrap_or_default().to_uppercase();
+            Ok(ToolOutput::success(
+                serde_json::json!({ "text": text, "len": text.len() }),
+                Duration::ZERO,
+            ))
+        }
+        fn requires_sanitization(&self) -> bool {
+            false
+        }
+    }
+
+    /// Needs approval only when asked to delete.
+    struct RiskyTool;
+
+    #[async_trait]
+    impl Tool for RiskyTool {
+        fn name(&self) -> &str {
+            "risky"
+        }
+        fn description(&self) -> &str {
+            "Sometimes destructive"
+        }
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+        async fn execute(
+            &self,
+            _params: serde_json::Value,
+            _ctx: &JobContext,
+        ) -> Result<ToolOutput, ToolError> {
+            Ok(ToolOutput::text("done", Duration::ZERO))
+        }
+        fn requires_approval_for(&self, params: &serde_json::Value) -> bool {
+            params["mode"]
+                .as_str()
+                .is_some_and(|mode| mode.eq_ignore_ascii_case("delete"))
+        }
+    }
+
+    /// Asks every time, like sending mail.
+    struct SendTool;
+
+    #[async_trait]
+    impl Tool for SendTool {
+        fn name(&self) -> &str {
+            "send"
+        }
+        fn description(&self) -> &str {
+            "Sends a message"
+        }
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+        async fn execute(
+            &self,
+            _params: serde_json::Value,
+            _ctx: &JobContext,
+        ) -> Result<ToolOutput, ToolError> {
+            panic!("a pipeline step sent without asking");
+        }
+        fn requires_approval(&self) -> bool {
+            true
+        }
+        fn requires_explicit_approval(&self, _params: &serde_json::Value) -> bool {
+            true
+        }
+    }
+
+    fn runner() -> (Arc<ToolRegistry>, PipelineRunner) {
+        let registry = Arc::new(ToolRegistry::new());
+        registry.register_sync(Arc::new(UpperTool));
+        registry.register_sync(Arc::new(RiskyTool));
+        registry.register_sync(Arc::new(SendTool));
+        let safety = Arc::new(SafetyLayer::new(&SafetyConfig {
+            max_output_length: 100_000,
+            injection_check_enabled: true,
+            preserve_redacted: false,
+            preserve_redacted_days: 7,
+            provenance: Vec::new(),
+        }));
+        let runner = PipelineRunner::new(Arc::downgrade(&registry), safety);
+        (registry, runner)
+    }
+
+    fn def(value: serde_json::Value) -> PipelineDef {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_bindings() {
+        let scope = serde_json::json!({
+            "input": {"city": "Oslo"},
+            "steps": {"a": {"items": [{"name": "first item"}], "n": 3}}
+        });
+        let template = serde_json::json!({
+            "whole": "$.steps.a.items[0]",
+            "quoted": "$.steps.a.items[0]['name']",
+            "text": "{{$.input.city}} has {{ $.steps.a.n }} items",
+            "plain": "no bindings {here}",
+            "list": ["$.input.city", 1]
+        });
+        assert_eq!(
+            resolve(&template, &scope).unwrap(),
+            serde_json::json!({
+                "whole": {"name": "first item"},
+                "quoted": "first item",
+                "text": "Oslo has 3 items",
+                "plain": "no bindings {here}",
+                "list": ["Oslo", 1]
+            })
+        );
+        assert!(resolve(&serde_json::json!("$.steps.a.missing"), &scope).is_err());
+        assert!(resolve(&serde_json::json!("$.env.HOME"), &scope).is_err());
+    }
+
+    #[test]
+    fn test_stages_follow_bindings_and_reject_bad_graphs() {
+        let pipeline = def(serde_json::json!({
+            "name": "fan",
+            "steps": [
+                {"id": "join", "tool": "upper", "params": {"text": "{{$.steps.a.text}} {{$.steps.b.text}}"}},
+                {"id": "a", "tool": "upper", "params": {"text": "$.input.x"}},
+                {"id": "b", "tool": "upper", "params": {"text": "$.input.y"}},
+                {"id": "last", "tool": "upper", "params": {"text": "done"}, "after": ["join"]}
+            ]
+        }));
+        assert_eq!(
+            pipeline.stages().unwrap(),
+            vec![vec![1, 2], vec![0], vec![3]]
+        );
+
+        let cycle = def(serde_json::json!({
+            "name": "loop",
+            "steps": [
+                {"id": "a", "tool": "upper", "params": {"text": "$.steps.b.text"}},
+                {"id": "b", "tool": "upper", "params": {"text": "$.steps.a.text"}}
+            ]
+        }));
+        assert!(cycle.stages().unwrap_err().contains("cycle"));
+
+        let unknown = def(serde_json::json!({
+            "name": "dangling",
+            "steps": [{"id": "a", "tool": "upper", "params": {"text": "$.steps.zzz"}}]
+        }));
+        assert!(unknown.stages().unwrap_err().contains("unknown step 'zzz'"));
+
+        let nested = def(serde_json::json!({
+            "name": "nested",
+            "steps": [{"id": "a", "tool": "pipeline"}]
+        }));
+        assert!(nested.stages().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_maps_outputs_into_inputs() {
+        let (_registry, runner) = runner();
+        let pipeline = def(serde_json::json!({
+            "name": "shout",
+            "steps": [
+                {"id": "first", "tool": "upper", "params": {"text": "$.input.text"}},
+                {"id": "second", "tool": "upper", "params": {"text": "{{$.steps.first.text}}!"}}
+            ],
+            "output": {"result": "$.steps.second.text", "first_len": "$.steps.first.len"}
+        }));
+        let ctx = JobContext::new("test", "test");
+        let (output, reports) = runner
+            .run(&pipeline, serde_json::json!({"text": "hi"}), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(output, serde_json::json!({"result": "HI!", "first_len": 2}));
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[1].id, "second");
+    }
+
+    #[tokio::test]
+    async fn test_approval_follows_step_templates() {
+        let (_registry, runner) = runner();
+        let safe = def(serde_json::json!({
+            "name": "safe",
+            "steps": [{"id": "a", "tool": "risky", "params": {"mode": "list"}}]
+        }));
+        let destructive = def(serde_json::json!({
+            "name": "destructive",
+            "steps": [{"id": "a", "tool": "risky", "params": {"mode": "delete"}}]
+        }));
+        let none = serde_json::json!({});
+        assert!(!runner.requires_approval(&safe, &none));
+        assert!(runner.requires_approval(&destructive, &none));
+
+        // Input filling in an approval-requiring value asks up front.
+        let bound = def(serde_json::json!({
+            "name": "bound",
+            "steps": [{"id": "a", "tool": "risky", "params": {"mode": "$.input.mode"}}]
+        }));
+        assert!(!runner.requires_approval(&bound, &serde_json::json!({"mode": "list"})));
+        assert!(runner.requires_approval(&bound, &serde_json::json!({"mode": "delete"})));
+
+        // An earlier step's output can't be known up front, so a step it
+        // turns into one needing approval is refused.
+        let chained = def(serde_json::json!({
+            "name": "chained",
+            "steps": [
+                {"id": "a", "tool": "upper", "params": {"text": "$.input.mode"}},
+                {"id": "b", "tool": "risky", "params": {"mode": "$.steps.a.text"}}
+            ]
+        }));
+        let input = serde_json::json!({"mode": "delete"});
+        assert!(!runner.requires_approval(&chained, &input));
+        let ctx = JobContext::new("test", "test");
+        let err = runner.run(&chained, input, &ctx).await.unwrap_err();
+        assert!(err.to_string().contains("need approval"));
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn test_run_and_replace_approval_show_the_steps() {
+        let (_registry, runner) = runner();
+        let workspace = Arc::new(Workspace::new(
+            "test_user",
+            deadpool_postgres::Pool::builder(deadpool_postgres::Manager::new(
+                tokio_postgres::Config::new(),
+                tokio_postgres::NoTls,
+            ))
+            .build()
+            .unwrap(),
+        ));
+        let tool = PipelineTool::new(workspace, runner);
+        let stored = def(serde_json::json!({
+            "name": "cleanup",
+            "steps": [{"id": "a", "tool": "risky", "params": {"mode": "$.input.mode"}}]
+        }));
+        tool.pipelines
+            .write()
+            .unwrap()
+            .insert("cleanup".to_string(), stored.clone());
+
+        let run =
+            serde_json::json!({"action": "run", "name": "cleanup", "input": {"mode": "delete"}});
+        assert!(tool.requires_approval_for(&run));
+        let details = tool.approval_details(&run).unwrap();
+        assert!(
+            details.contains(r#"a: risky {"mode":"delete"}"#),
+            "{}",
+            details
+        );
+
+        // Redefining as-is needs nothing; changing the steps needs approval.
+        let same = serde_json::json!({"action": "define", "pipeline": stored});
+        assert!(!tool.requires_approval_for(&same));
+        let swapped = serde_json::json!({"action": "define", "pipeline": {
+            "name": "cleanup",
+            "steps": [{"id": "a", "tool": "upper", "params": {"text": "hi"}}]
+        }});
+        assert!(tool.requires_approval_for(&swapped));
+        assert!(
+            tool.approval_details(&swapped)
+                .unwrap()
+                .contains("a: upper")
+        );
+        let fresh = serde_json::json!({"action": "define", "pipeline": {
+            "name": "other",
+            "steps": [{"id": "a", "tool": "upper", "params": {"text": "hi"}}]
+        }});
+        assert!(!tool.requires_approval_for(&fresh));
+    }
+
+    #[tokio::test]
+    async fn test_steps_needing_explicit_approval_are_refused() {
+        let (_registry, runner) = runner();
+        let pipeline = def(serde_json::json!({
+            "name": "mailer",
+            "steps": [{"id": "a", "tool": "send", "params": {"to": "a@b.com"}}]
+        }));
+        let ctx = JobContext::new("test", "test");
+        let err = runner
+            .run(&pipeline, serde_json::json!({}), &ctx)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("approved call by call"));
+    }
+}
@@ -61,13 +61,17 @@ impl Tool for RoutineCreateTool {
                 },
                 "trigger_type": {
                     "type": "string",
-                    "enum": ["cron", "event", "webhook", "manual"],
+                    "enum": ["cron", "event", "webhook", "file", "manual"],
                     "description": "When the routine fires"
                 },
                 "schedule": {
                     "type": "string",
                     "description": "Cron expression (for cron trigger). E.g. '0 9 * * MON-FRI' for weekdays at 9am. Uses 6-field cron (sec min hour day month weekday)."
                 },
+                "timezone": {
+                    "type": "string",
+                    "description": "IANA time zone the schedule runs in (e.g. 'America/New_York'). Defaults to the user's time zone."
+                },
                 "event_pattern": {
                     "type": "string",
                     "description": "Regex pattern to match messages (for event trigger)"
@@ -76,6 +80,14 @@ impl Tool for RoutineCreateTool {
                     "type": "string",
                     "description": "Optional channel filter for event trigger (e.g. 'telegram')"
                 },
+                "file_path": {
+                    "type": "string",
+                    "description": "Host path glob to watch (for file trigger), e.g. '~/Downloads/*.pdf'. The routine receives the new or changed file paths."
+                },
+                "debounce_secs": {
+                    "type": "integer",
+                    "description": "Seconds a file must stop changing before the file trigger fires (default: 2)"
+                },
                 "prompt": {
                     "type": "string",
                     "description": "The prompt/instructions for the routine"
@@ -138,12 +150,18 @@ impl Tool for RoutineCreateTool {
                                 "cron trigger requires 'schedule'".to_string(),
                             )
                         })?;
-                // Validate cron expression
-                next_cron_fire(schedule).map_err(|e| {
+                let timezone = params
+                    .get("timezone")
+                    .and_then(|v| v.as_str())
+                    .map(String::from)
+                    .or_else(|| ctx.timezone.clone());
+                // Validate cron expression and time zone
+                next_cron_fire(schedule, timezone.as_deref()).map_err(|e| {
                     ToolError::InvalidParameters(format!("invalid cron schedule: {e}"))
                 })?;
                 Trigger::Cron {
                     schedule: schedule.to_string(),
+                    timezone,
                 }
             }
             "event" => {
@@ -171,6 +189,27 @@ impl Tool for RoutineCreateTool {
                 path: None,
                 secret: None,
             },
+            "file" => {
+                let path = params
+                    .get("file_path")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| {
+                        ToolError::InvalidParameters(
+                            "file trigger requires 'file_path'".to_string(),
+                        )
+                    })?;
+                // Validate glob
+                glob::Pattern::new(path)
+                    .map_err(|e| ToolError::InvalidParameters(format!("invalid path glob: {e}")))?;
+                let debounce_secs = params
+                    .get("debounce_secs")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(2);
+                Trigger::File {
+                    path: path.to_string(),
+                    debounce_secs,
+                }
+            }
             "manual" => Trigger::Manual,
             other => {
                 return Err(ToolError::InvalidParameters(format!(
@@ -219,8 +258,12 @@ impl Tool for RoutineCreateTool {
             .unwrap_or(300);
 
         // Compute next fire time for cron
-        let next_fire = if let Trigger::Cron { ref schedule } = trigger {
-            next_cron_fire(schedule).unwrap_or(None)
+        let next_fire = if let Trigger::Cron {
+            ref schedule,
+            ref timezone,
+        } = trigger
+        {
+            next_cron_fire(schedule, timezone.as_deref()).unwrap_or(None)
         } else {
             None
         };
@@ -253,9 +296,11 @@ impl Tool for RoutineCreateTool {
             .await
             .map_err(|e| ToolError::ExecutionFailed(format!("failed to create routine: {e}")))?;
 
-        // Refresh event cache if this is an event trigger
-        if routine.trigger.type_tag() == "event" {
-            self.engine.refresh_event_cache().await;
+        // Refresh event cache or file watches if this trigger needs one
+        match routine.trigger.type_tag() {
+            "event" => self.engine.refresh_event_cache().await,
+            "file" => self.engine.refresh_file_watches(),
+            _ => {}
         }
 
         let result = serde_json::json!({
@@ -392,6 +437,10 @@ impl Tool for RoutineUpdateTool {
                     "type": "string",
                     "description": "New cron schedule (for cron triggers)"
                 },
+                "timezone": {
+                    "type": "string",
+                    "description": "New IANA time zone for the cron schedule"
+                },
                 "description": {
                     "type": "string",
                     "description": "New description"
@@ -433,18 +482,46 @@ impl Tool for RoutineUpdateTool {
             match &mut routine.action {
                 RoutineAction::Lightweight { prompt: p, .. } => *p = prompt.to_string(),
                 RoutineAction::FullJob { description: d, .. } => *d = prompt.to_string(),
+                RoutineAction::Report { .. } => {
+                    return Err(ToolError::InvalidParameters(
+                        "report routines have no prompt".to_string(),
+                    ));
+                }
+                RoutineAction::Tool { .. } => {
+                    return Err(ToolError::InvalidParameters(
+                        "tool routines have no prompt".to_string(),
+                    ));
+                }
             }
         }
 
-        if let Some(schedule) = params.get("schedule").and_then(|v| v.as_str()) {
+        let new_schedule = params.get("schedule").and_then(|v| v.as_str());
+        let new_timezone = params.get("timezone").and_then(|v| v.as_str());
+        if new_schedule.is_some() || new_timezone.is_some() {
+            let (schedule, timezone) = match &routine.trigger {
+                Trigger::Cron { schedule, timezone } => (
+                    new_schedule.unwrap_or(schedule).to_string(),
+                    new_timezone.map(String::from).or_else(|| timezone.clone()),
+                ),
+                _ => (
+                    new_schedule
+                        .ok_or_else(|| {
+                            ToolError::InvalidParameters(
+                                "'timezone' only applies to cron routines".to_string(),
+                            )
+                        })?
+                        .to_string(),
+                    new_timezone
+                        .map(String::from)
+                        .or_else(|| ctx.timezone.clone()),
+                ),
+            };
             // Validate
-            next_cron_fire(schedule)
+            let next_fire = next_cron_fire(&schedule, timezone.as_deref())
                 .map_err(|e| ToolError::InvalidParameters(format!("invalid cron schedule: {e}")))?;
 
-            routine.trigger = Trigger::Cron {
-                schedule: schedule.to_string(),
-            };
-            routine.next_fire_at = next_cron_fire(schedule).unwrap_or(None);
+            routine.trigger = Trigger::Cron { schedule, timezone };
+            routine.next_fire_at = next_fire;
         }
 
         self.store
@@ -452,8 +529,9 @@ impl Tool for RoutineUpdateTool {
             .await
             .map_err(|e| ToolError::ExecutionFailed(format!("failed to update: {e}")))?;
 
-        // Refresh event cache in case trigger changed
+        // Refresh event cache and file watches in case trigger changed
         self.engine.refresh_event_cache().await;
+        self.engine.refresh_file_watches();
 
         let result = serde_json::json!({
             "name": routine.name,
@@ -532,8 +610,9 @@ impl Tool for RoutineDeleteTool {
             .await
             .map_err(|e| ToolError::ExecutionFailed(format!("failed to delete: {e}")))?;
 
-        // Refresh event cache
+        // Refresh event cache and file watches
         self.engine.refresh_event_cache().await;
+        self.engine.refresh_file_watches();
 
         let result = serde_json::json!({
             "name": name,
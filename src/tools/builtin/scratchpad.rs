@@ -0,0 +1,423 @@
+//! Per-turn scratchpad for intermediate notes.
+//!
+//! During a multi-tool turn the agent often needs somewhere to keep partial
+//! results (a list of candidate files, numbers to add up later, a draft)
+//! without writing them to workspace memory, where they would outlive the
+//! turn and show up in future searches. The `scratchpad` tool stores such
+//! notes in memory, keyed by the conversation thread (or the job, for
+//! background work). The agent discards a thread's pad when its turn ends;
+//! a turn paused for approval or input keeps it. A note worth keeping can
+//! be promoted to workspace memory before then.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::memory::{PROTECTED_IDENTITY_FILES, memory_workspace};
+use crate::context::JobContext;
+use crate::tools::tool::{Tool, ToolError, ToolOutput};
+use crate::workspace::{Workspace, paths};
+
+/// Pads kept before the oldest is evicted. Background jobs never have
+/// their pad discarded explicitly, so this bounds what they leave behind.
+const DEFAULT_MAX_PADS: usize = 64;
+
+/// Notes allowed on one pad.
+const MAX_NOTES: usize = 100;
+
+/// Longest note, in characters.
+const MAX_NOTE_CHARS: usize = 16_000;
+
+/// Bounded set of scratchpads, one per thread or job.
+pub struct ScratchpadStore {
+    max_pads: usize,
+    pads: Mutex<VecDeque<(Uuid, BTreeMap<String, String>)>>,
+}
+
+impl Default for ScratchpadStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_PADS)
+    }
+}
+
+impl ScratchpadStore {
+    pub fn new(max_pads: usize) -> Self {
+        Self {
+            max_pads: max_pads.max(1),
+            pads: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Pad that a tool call in `ctx` uses.
+    pub fn pad_id(ctx: &JobContext) -> Uuid {
+        ctx.conversation_id.unwrap_or(ctx.job_id)
+    }
+
+    /// Store `content` under `key`, appending to the note if `append`.
+    /// Returns the note's new length in characters.
+    pub fn write(
+        &self,
+        pad: Uuid,
+        key: &str,
+        content: &str,
+        append: bool,
+    ) -> Result<usize, String> {
+        let mut pads = self.pads.lock().unwrap_or_else(|e| e.into_inner());
+        let notes = match pads.iter().position(|(id, _)| *id == pad) {
+            Some(i) => &mut pads[i].1,
+            None => {
+                while pads.len() >= self.max_pads {
+                    pads.pop_front();
+                }
+                pads.push_back((pad, BTreeMap::new()));
+                &mut pads.back_mut().expect("just pushed").1
+            }
+        };
+
+        if !notes.contains_key(key) && notes.len() >= MAX_NOTES {
+            return Err(format!(
+                "scratchpad is full ({} notes); delete some first",
+                MAX_NOTES
+            ));
+        }
+        let existing = notes.get(key).filter(|_| append).cloned();
+        let note = match existing {
+            Some(mut note) => {
+                note.push_str(content);
+                note
+            }
+            None => content.to_string(),
+        };
+        let len = note.chars().count();
+        if len > MAX_NOTE_CHARS {
+            return Err(format!(
+                "note '{}' would be {} characters (limit {})",
+                key, len, MAX_NOTE_CHARS
+            ));
+        }
+        notes.insert(key.to_string(), note);
+        Ok(len)
+    }
+
+    /// The note under `key`.
+    pub fn read(&self, pad: Uuid, key: &str) -> Option<String> {
+        self.with_pad(pad, |notes| notes.get(key).cloned())
+            .flatten()
+    }
+
+    /// Every note on the pad, by key.
+    pub fn notes(&self, pad: Uuid) -> BTreeMap<String, String> {
+        self.with_pad(pad, |notes| notes.clone())
+            .unwrap_or_default()
+    }
+
+    /// Remove the note under `key`; whether it existed.
+    pub fn delete(&self, pad: Uuid, key: &str) -> bool {
+        let mut pads = self.pads.lock().unwrap_or_else(|e| e.into_inner());
+        pads.iter_mut()
+            .find(|(id, _)| *id == pad)
+            .is_some_and(|(_, notes)| notes.remove(key).is_some())
+    }
+
+    /// Drop the whole pad and return how many notes it held.
+    pub fn discard(&self, pad: Uuid) -> usize {
+        let mut pads = self.pads.lock().unwrap_or_else(|e| e.into_inner());
+        match pads.iter().position(|(id, _)| *id == pad) {
+            Some(i) => pads.remove(i).map(|(_, notes)| notes.len()).unwrap_or(0),
+            None => 0,
+        }
+    }
+
+    fn with_pad<T>(&self, pad: Uuid, f: impl FnOnce(&BTreeMap<String, String>) -> T) -> Option<T> {
+        let pads = self.pads.lock().unwrap_or_else(|e| e.into_inner());
+        pads.iter()
+            .find(|(id, _)| *id == pad)
+            .map(|(_, notes)| f(notes))
+    }
+}
+
+/// Tool for the agent's per-turn scratchpad.
+pub struct ScratchpadTool {
+    store: Arc<ScratchpadStore>,
+    workspace: Option<Arc<Workspace>>,
+}
+
+impl ScratchpadTool {
+    /// Create the tool. Without a workspace, notes cannot be promoted.
+    pub fn new(store: Arc<ScratchpadStore>, workspace: Option<Arc<Workspace>>) -> Self {
+        Self { store, workspace }
+    }
+
+    /// Append a note to workspace memory and return the path written.
+    async fn promote(
+        &self,
+        ctx: &JobContext,
+        target: &str,
+        key: &str,
+        note: &str,
+    ) -> Result<String, ToolError> {
+        let workspace = self.workspace.as_ref().ok_or_else(|| {
+            ToolError::ExecutionFailed("no workspace to promote notes to".to_string())
+        })?;
+        let workspace = memory_workspace(workspace, ctx);
+        let entry = format!("{}: {}", key, note);
+        let written = match target {
+            "memory" => workspace
+                .append_memory(&entry)
+                .await
+                .map(|_| paths::MEMORY.to_string()),
+            "daily_log" => workspace
+                .append_daily_log(&entry)
+                .await
+                .map(|_| format!("daily/{}.md", chrono::Utc::now().format("%Y-%m-%d"))),
+            path => {
+                let normalized = path.trim_start_matches('/');
+                if PROTECTED_IDENTITY_FILES
+                    .iter()
+                    .any(|p| normalized.eq_ignore_ascii_case(p))
+                {
+                    return Err(ToolError::NotAuthorized(format!(
+                        "writing to '{}' is not allowed (identity file protected from tool access)",
+                        path
+                    )));
+                }
+                workspace
+                    .append(path, &entry)
+                    .await
+                    .map(|_| path.to_string())
+            }
+        };
+        written.map_err(|e| ToolError::ExecutionFailed(format!("Promote failed: {}", e)))
+    }
+}
+
+fn str_param<'a>(params: &'a serde_json::Value, name: &str) -> Result<&'a str, ToolError> {
+    params
+        .get(name)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ToolError::InvalidParameters(format!("missing '{}' parameter", name)))
+}
+
+#[async_trait]
+impl Tool for ScratchpadTool {
+    fn name(&self) -> &str {
+        "scratchpad"
+    }
+
+    fn description(&self) -> &str {
+        "Keep intermediate notes and partial results while working through a task. \
+         Notes last until the end of the current turn and are not saved to memory, so \
+         use this instead of memory_write for transient working state. Operations: \
+         write (key, content, optional append), read (key, or omit for all notes), \
+         list, delete (key), promote (key, optional target) to copy a note into \
+         persistent memory."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "enum": ["write", "read", "list", "delete", "promote"],
+                    "description": "What to do with the scratchpad"
+                },
+                "key": {
+                    "type": "string",
+                    "description": "Name of the note (e.g. 'candidates', 'totals')"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "Text to store (write)"
+                },
+                "append": {
+                    "type": "boolean",
+                    "description": "Append to the note instead of replacing it (write)",
+                    "default": false
+                },
+                "target": {
+                    "type": "string",
+                    "description": "Where promote writes: 'memory' for MEMORY.md, 'daily_log' for today's log, or a workspace path",
+                    "default": "daily_log"
+                }
+            },
+            "required": ["operation"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        ctx: &JobContext,
+    ) -> Result<ToolOutput, ToolError> {
+        let start = std::time::Instant::now();
+        let pad = ScratchpadStore::pad_id(ctx);
+        let operation = str_param(&params, "operation")?;
+
+        let result = match operation {
+            "write" => {
+                let key = str_param(&params, "key")?;
+                let content = str_param(&params, "content")?;
+                let append = params
+                    .get("append")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let length = self
+                    .store
+                    .write(pad, key, content, append)
+                    .map_err(ToolError::ExecutionFailed)?;
+                serde_json::json!({ "status": "written", "key": key, "length": length })
+            }
+            "read" => match params.get("key").and_then(|v| v.as_str()) {
+                Some(key) => {
+                    let note = self.store.read(pad, key).ok_or_else(|| {
+                        ToolError::ExecutionFailed(format!("no note '{}' on the scratchpad", key))
+                    })?;
+                    serde_json::json!({ "key": key, "content": note })
+                }
+                None => serde_json::json!({ "notes": self.store.notes(pad) }),
+            },
+            "list" => {
+                let keys: Vec<serde_json::Value> = self
+                    .store
+                    .notes(pad)
+                    .into_iter()
+                    .map(|(key, note)| {
+                        serde_json::json!({ "key": key, "length": note.chars().count() })
+                    })
+                    .collect();
+                serde_json::json!({ "notes": keys })
+            }
+            "delete" => {
+                let key = str_param(&params, "key")?;
+                serde_json::json!({ "deleted": self.store.delete(pad, key) })
+            }
+            "promote" => {
+                let key = str_param(&params, "key")?;
+                let note = self.store.read(pad, key).ok_or_else(|| {
+                    ToolError::ExecutionFailed(format!("no note '{}' on the scratchpad", key))
+                })?;
+                let target = params
+                    .get("target")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("daily_log");
+                let path = self.promote(ctx, target, key, &note).await?;
+                serde_json::json!({ "status": "promoted", "key": key, "path": path })
+            }
+            _ => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "unknown operation: {}",
+                    operation
+                )));
+            }
+        };
+
+        Ok(ToolOutput::success(result, start.elapsed()))
+    }
+
+    fn requires_sanitization(&self) -> bool {
+        false // Internal tool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_keeps_pads_apart_and_evicts_oldest() {
+        let store = ScratchpadStore::new(2);
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        store.write(a, "n", "alpha", false).unwrap();
+        store.write(b, "n", "beta", false).unwrap();
+        assert_eq!(store.read(a, "n").as_deref(), Some("alpha"));
+        assert_eq!(store.read(b, "n").as_deref(), Some("beta"));
+
+        store.write(c, "n", "gamma", false).unwrap();
+        assert!(store.read(a, "n").is_none());
+        assert_eq!(store.discard(b), 1);
+        assert!(store.notes(b).is_empty());
+    }
+
+    #[test]
+    fn test_store_limits() {
+        let store = ScratchpadStore::default();
+        let pad = Uuid::new_v4();
+        store
+            .write(pad, "big", &"x".repeat(MAX_NOTE_CHARS), false)
+            .unwrap();
+        assert!(store.write(pad, "big", "y", true).is_err());
+        for i in 1..MAX_NOTES {
+            store.write(pad, &format!("k{i}"), "v", false).unwrap();
+        }
+        assert!(store.write(pad, "one-more", "v", false).is_err());
+        // Overwriting an existing note is still allowed.
+        store.write(pad, "k1", "w", false).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tool_round_trip_within_a_thread() {
+        let store = Arc::new(ScratchpadStore::default());
+        let tool = ScratchpadTool::new(Arc::clone(&store), None);
+        let ctx = JobContext {
+            conversation_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+
+        tool.execute(
+            serde_json::json!({"operation": "write", "key": "files", "content": "a.rs"}),
+            &ctx,
+        )
+        .await
+        .unwrap();
+        tool.execute(
+            serde_json::json!({"operation": "write", "key": "files", "content": ", b.rs", "append": true}),
+            &ctx,
+        )
+        .await
+        .unwrap();
+
+        // A later call in the same thread (new job context) sees the note.
+        let later = JobContext {
+            conversation_id: ctx.conversation_id,
+            ..Default::default()
+        };
+        let out = tool
+            .execute(
+                serde_json::json!({"operation": "read", "key": "files"}),
+                &later,
+            )
+            .await
+            .unwrap();
+        assert_eq!(out.result["content"], "a.rs, b.rs");
+
+        // Another thread does not.
+        let other = JobContext::default();
+        let out = tool
+            .execute(serde_json::json!({"operation": "list"}), &other)
+            .await
+            .unwrap();
+        assert_eq!(out.result["notes"], serde_json::json!([]));
+
+        let err = tool
+            .execute(
+                serde_json::json!({"operation": "promote", "key": "files"}),
+                &ctx,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no workspace"));
+
+        store.discard(ScratchpadStore::pad_id(&ctx));
+        assert!(
+            tool.execute(
+                serde_json::json!({"operation": "read", "key": "files"}),
+                &ctx
+            )
+            .await
+            .is_err()
+        );
+    }
+}
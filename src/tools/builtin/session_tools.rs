@@ -954,6 +954,10 @@ mod tests {
             Ok(())
         }
 
+        async fn ping(&self) -> Result<(), crate::error::DatabaseError> {
+            Ok(())
+        }
+
         async fn create_conversation(
             &self,
             _channel: &str,
@@ -1048,6 +1052,14 @@ mod tests {
             Ok(vec![])
         }
 
+        async fn delete_conversation_messages(
+            &self,
+            _conversation_id: uuid::Uuid,
+            _message_ids: &[uuid::Uuid],
+        ) -> Result<u64, crate::error::DatabaseError> {
+            Ok(0)
+        }
+
         async fn conversation_belongs_to_user(
             &self,
             _conversation_id: uuid::Uuid,
@@ -1223,6 +1235,21 @@ mod tests {
             Ok(vec![])
         }
 
+        async fn record_egress(
+            &self,
+            _record: &crate::history::EgressRecord,
+        ) -> Result<(), crate::error::DatabaseError> {
+            Ok(())
+        }
+
+        async fn list_egress(
+            &self,
+            _job_id: Option<uuid::Uuid>,
+            _limit: i64,
+        ) -> Result<Vec<crate::history::EgressRecord>, crate::error::DatabaseError> {
+            Ok(vec![])
+        }
+
         async fn create_routine(
             &self,
             _routine: &crate::agent::routine::Routine,
@@ -1258,6 +1285,12 @@ mod tests {
             Ok(vec![])
         }
 
+        async fn list_file_routines(
+            &self,
+        ) -> Result<Vec<crate::agent::routine::Routine>, crate::error::DatabaseError> {
+            Ok(vec![])
+        }
+
         async fn list_due_cron_routines(
             &self,
         ) -> Result<Vec<crate::agent::routine::Routine>, crate::error::DatabaseError> {
@@ -1351,6 +1384,14 @@ mod tests {
             Ok(())
         }
 
+        async fn activity_since(
+            &self,
+            _user_id: Option<&str>,
+            _since: chrono::DateTime<chrono::Utc>,
+        ) -> Result<crate::history::ActivityStats, crate::error::DatabaseError> {
+            Ok(crate::history::ActivityStats::default())
+        }
+
         async fn get_setting(
             &self,
             _user_id: &str,
@@ -1487,6 +1528,14 @@ mod tests {
             Ok(vec![])
         }
 
+        async fn workspace_usage(
+            &self,
+            _user_id: &str,
+            _agent_id: Option<uuid::Uuid>,
+        ) -> Result<Vec<crate::workspace::DocumentUsage>, crate::error::WorkspaceError> {
+            Ok(vec![])
+        }
+
         async fn delete_chunks(
             &self,
             _document_id: uuid::Uuid,
@@ -1512,6 +1561,13 @@ mod tests {
             Ok(())
         }
 
+        async fn list_chunks(
+            &self,
+            _document_id: uuid::Uuid,
+        ) -> Result<Vec<crate::workspace::MemoryChunk>, crate::error::WorkspaceError> {
+            Ok(vec![])
+        }
+
         async fn get_chunks_without_embeddings(
             &self,
             _user_id: &str,
@@ -13,10 +13,17 @@
 //! - Commands run inside ephemeral Docker containers
 //! - Network traffic goes through a validating proxy
 //! - Credentials are injected by the proxy, never exposed to commands
+//! - `allow_domains` / `deny_domains` widen or narrow the proxy allowlist
+//!   for one command; the grant is audited
 //!
 //! When sandbox is unavailable:
 //! - Commands run directly on host with basic protections
 //! - Blocked command patterns are still enforced
+//!
+//! When sandbox is required but Docker is down and host fallback is enabled:
+//! - Commands run on the host through [`HostFallback`], restricted to
+//!   allowlisted programs and directories, and only after the user
+//!   acknowledged the risk for their session
 
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
@@ -28,8 +35,9 @@ use async_trait::async_trait;
 use tokio::io::AsyncReadExt;
 use tokio::process::Command;
 
-use crate::context::JobContext;
-use crate::sandbox::{SandboxManager, SandboxPolicy};
+use crate::context::{CANCEL_GRACE_PERIOD, JobContext};
+use crate::sandbox::process::TerminateOnDrop;
+use crate::sandbox::{ExecPolicy, FileDiff, HostFallback, SandboxManager, SandboxPolicy};
 use crate::tools::tool::{Tool, ToolDomain, ToolError, ToolOutput};
 
 /// Maximum output size before truncation (64KB).
@@ -38,6 +46,9 @@ const MAX_OUTPUT_SIZE: usize = 64 * 1024;
 /// Default command timeout.
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
 
+/// Combined output, exit code, and workspace changes when they were captured.
+type CommandOutput = (String, i64, Option<Vec<FileDiff>>);
+
 /// Commands that are always blocked for safety.
 static BLOCKED_COMMANDS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
     HashSet::from([
@@ -138,6 +149,8 @@ pub struct ShellTool {
     sandbox: Option<Arc<SandboxManager>>,
     /// Sandbox policy to use when sandbox is available.
     sandbox_policy: SandboxPolicy,
+    /// Restricted host execution used in place of a missing sandbox.
+    host_fallback: Option<Arc<HostFallback>>,
 }
 
 impl std::fmt::Debug for ShellTool {
@@ -148,6 +161,7 @@ impl std::fmt::Debug for ShellTool {
             .field("allow_dangerous", &self.allow_dangerous)
             .field("sandbox", &self.sandbox.is_some())
             .field("sandbox_policy", &self.sandbox_policy)
+            .field("host_fallback", &self.host_fallback.is_some())
             .finish()
     }
 }
@@ -161,6 +175,7 @@ impl ShellTool {
             allow_dangerous: false,
             sandbox: None,
             sandbox_policy: SandboxPolicy::ReadOnly,
+            host_fallback: None,
         }
     }
 
@@ -188,6 +203,13 @@ impl ShellTool {
         self
     }
 
+    /// Run commands on the host under `fallback`'s restricted policy, because
+    /// the sandbox they would need is unavailable.
+    pub fn with_host_fallback(mut self, fallback: Arc<HostFallback>) -> Self {
+        self.host_fallback = Some(fallback);
+        self
+    }
+
     /// Check if a command is blocked.
     fn is_blocked(&self, cmd: &str) -> Option<&'static str> {
         let normalized = cmd.to_lowercase();
@@ -216,16 +238,12 @@ impl ShellTool {
         cmd: &str,
         workdir: &Path,
         timeout: Duration,
-    ) -> Result<(String, i64), ToolError> {
+        exec: &ExecPolicy,
+    ) -> Result<CommandOutput, ToolError> {
         // Override sandbox config timeout if needed
         let result = tokio::time::timeout(timeout, async {
             sandbox
-                .execute_with_policy(
-                    cmd,
-                    workdir,
-                    self.sandbox_policy,
-                    std::collections::HashMap::new(),
-                )
+                .execute_with_exec_policy(cmd, workdir, exec, std::collections::HashMap::new())
                 .await
         })
         .await;
@@ -233,7 +251,7 @@ impl ShellTool {
         match result {
             Ok(Ok(output)) => {
                 let combined = truncate_output(&output.output);
-                Ok((combined, output.exit_code))
+                Ok((combined, output.exit_code, output.changes))
             }
             Ok(Err(e)) => Err(ToolError::ExecutionFailed(format!("Sandbox error: {}", e))),
             Err(_) => Err(ToolError::Timeout(timeout)),
@@ -264,10 +282,12 @@ impl ShellTool {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
 
-        // Spawn process
-        let mut child = command
+        // Spawn process. If the job is cancelled while it runs, this future is
+        // dropped and the guard terminates the process gracefully.
+        let child = command
             .spawn()
             .map_err(|e| ToolError::ExecutionFailed(format!("Failed to spawn command: {}", e)))?;
+        let mut child = TerminateOnDrop::new(child, CANCEL_GRACE_PERIOD);
 
         // Wait with timeout
         let result = tokio::time::timeout(timeout, async {
@@ -319,10 +339,13 @@ impl ShellTool {
     /// Execute a command, using sandbox if available.
     async fn execute_command(
         &self,
+        user_id: &str,
+        thread_id: Option<uuid::Uuid>,
         cmd: &str,
         workdir: Option<&str>,
         timeout: Option<u64>,
-    ) -> Result<(String, i64), ToolError> {
+        exec: &ExecPolicy,
+    ) -> Result<CommandOutput, ToolError> {
         // Check for blocked commands
         if let Some(reason) = self.is_blocked(cmd) {
             return Err(ToolError::NotAuthorized(format!(
@@ -347,13 +370,31 @@ impl ShellTool {
             && (sandbox.is_initialized() || sandbox.config().enabled)
         {
             return self
-                .execute_sandboxed(sandbox, cmd, &cwd, timeout_duration)
+                .execute_sandboxed(sandbox, cmd, &cwd, timeout_duration, exec)
                 .await;
         }
 
+        // Degraded mode: the sandbox is required but Docker is missing.
+        if let Some(ref fallback) = self.host_fallback {
+            return match fallback
+                .execute(user_id, thread_id, cmd, &cwd, timeout_duration)
+                .await
+            {
+                Ok(output) => Ok((truncate_output(&output.output), output.exit_code, None)),
+                Err(crate::sandbox::SandboxError::Timeout(t)) => Err(ToolError::Timeout(t)),
+                Err(crate::sandbox::SandboxError::HostFallbackDenied { reason }) => {
+                    Err(ToolError::NotAuthorized(reason))
+                }
+                Err(e) => Err(ToolError::ExecutionFailed(format!(
+                    "Host fallback error: {}",
+                    e
+                ))),
+            };
+        }
+
         // Only execute directly when no sandbox was configured at all.
         let (output, code) = self.execute_direct(cmd, &cwd, timeout_duration).await?;
-        Ok((output, code as i64))
+        Ok((output, code as i64, None))
     }
 }
 
@@ -390,6 +431,20 @@ impl Tool for ShellTool {
                 "timeout": {
                     "type": "integer",
                     "description": "Timeout in seconds (optional, default 120)"
+                },
+                "allow_domains": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Extra domains this command may reach through the sandbox proxy, e.g. [\"pypi.org\", \"*.pythonhosted.org\"] for a pip install (optional)"
+                },
+                "deny_domains": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Domains this command must not reach, even if normally allowed (optional)"
+                },
+                "capture_changes": {
+                    "type": "boolean",
+                    "description": "Report the files the command created, modified or deleted in the working directory, with diffs for text files. Sandboxed commands only (optional)"
                 }
             },
             "required": ["command"]
@@ -399,28 +454,64 @@ impl Tool for ShellTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &JobContext,
+        ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError> {
         let command = params
             .get("command")
             .and_then(|v| v.as_str())
             .ok_or_else(|| ToolError::InvalidParameters("missing 'command' parameter".into()))?;
 
-        let workdir = params.get("workdir").and_then(|v| v.as_str());
+        // A session bound to a project runs commands from the project root,
+        // and a relative `workdir` is resolved against it.
+        let workdir = match (
+            params.get("workdir").and_then(|v| v.as_str()),
+            ctx.working_dir.as_ref(),
+        ) {
+            (Some(dir), Some(root)) => Some(root.join(dir).display().to_string()),
+            (Some(dir), None) => Some(dir.to_string()),
+            (None, Some(root)) => Some(root.display().to_string()),
+            (None, None) => None,
+        };
         let timeout = params.get("timeout").and_then(|v| v.as_u64());
+        let exec = ExecPolicy {
+            policy: self.sandbox_policy,
+            allow_domains: domain_list(&params, "allow_domains")?,
+            deny_domains: domain_list(&params, "deny_domains")?,
+            reason: Some(format!("shell: {}", truncate_for_error(command))),
+            job_id: Some(ctx.job_id),
+            capture_diff: params
+                .get("capture_changes")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        };
 
         let start = std::time::Instant::now();
-        let (output, exit_code) = self.execute_command(command, workdir, timeout).await?;
+        let (output, exit_code, changes) = self
+            .execute_command(
+                &ctx.user_id,
+                ctx.conversation_id,
+                command,
+                workdir.as_deref(),
+                timeout,
+                &exec,
+            )
+            .await?;
         let duration = start.elapsed();
 
         let sandboxed = self.sandbox.is_some();
 
-        let result = serde_json::json!({
+        let mut result = serde_json::json!({
             "output": output,
             "exit_code": exit_code,
             "success": exit_code == 0,
             "sandboxed": sandboxed
         });
+        if self.host_fallback.is_some() {
+            result["execution_mode"] = serde_json::json!("host_fallback");
+        }
+        if let Some(changes) = changes {
+            result["changes"] = serde_json::to_value(changes).unwrap_or_default();
+        }
 
         Ok(ToolOutput::success(result, duration))
     }
@@ -429,6 +520,20 @@ impl Tool for ShellTool {
         true // Shell commands should require approval
     }
 
+    fn requires_explicit_approval(&self, params: &serde_json::Value) -> bool {
+        // Remembered approvals cover a command, not the network access
+        // `allow_domains` adds to it.
+        let widens_network = params
+            .get("allow_domains")
+            .and_then(|d| d.as_array())
+            .is_some_and(|d| !d.is_empty());
+        widens_network
+            || params
+                .get("command")
+                .and_then(|c| c.as_str())
+                .is_some_and(requires_explicit_approval)
+    }
+
     fn requires_sanitization(&self) -> bool {
         true // Shell output could contain anything
     }
@@ -438,6 +543,60 @@ impl Tool for ShellTool {
     }
 }
 
+/// Second-level labels registries sell names under, as in `example.co.uk`.
+const SHARED_SECOND_LEVEL: &[&str] = &[
+    "ac", "co", "com", "edu", "go", "gob", "gov", "ltd", "mil", "ne", "net", "nic", "or", "org",
+    "plc", "sch",
+];
+
+/// Whether `suffix` is a public suffix (`com`, `co.uk`) rather than one
+/// registrant's domain, so `*.suffix` would open up a whole registry.
+fn is_public_suffix(suffix: &str) -> bool {
+    match suffix.split('.').collect::<Vec<_>>().as_slice() {
+        [_] => true,
+        [second, country] => {
+            country.len() == 2
+                && SHARED_SECOND_LEVEL.contains(&second.to_ascii_lowercase().as_str())
+        }
+        _ => false,
+    }
+}
+
+/// A list of domain names (or `*.` wildcards below a registrable domain)
+/// from `params[key]`.
+fn domain_list(params: &serde_json::Value, key: &str) -> Result<Vec<String>, ToolError> {
+    let Some(value) = params.get(key) else {
+        return Ok(Vec::new());
+    };
+    let items = value
+        .as_array()
+        .ok_or_else(|| ToolError::InvalidParameters(format!("'{}' must be an array", key)))?;
+    items
+        .iter()
+        .map(|item| {
+            let domain = item.as_str().map(str::trim).unwrap_or_default();
+            let name = domain.strip_prefix("*.").unwrap_or(domain);
+            let valid = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+            if valid && name.len() < domain.len() && is_public_suffix(name) {
+                Err(ToolError::InvalidParameters(format!(
+                    "'{}' can't cover a whole public suffix, got {}",
+                    key, item
+                )))
+            } else if valid {
+                Ok(domain.to_string())
+            } else {
+                Err(ToolError::InvalidParameters(format!(
+                    "'{}' entries must be domain names, got {}",
+                    key, item
+                )))
+            }
+        })
+        .collect()
+}
+
 /// Truncate output to fit within limits (UTF-8 safe).
 fn truncate_output(s: &str) -> String {
     if s.len() <= MAX_OUTPUT_SIZE {
@@ -483,6 +642,65 @@ mod tests {
         assert_eq!(result.result.get("exit_code").unwrap().as_i64().unwrap(), 0);
     }
 
+    #[tokio::test]
+    async fn test_runs_in_project_working_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        let tool = ShellTool::new();
+        let ctx = JobContext {
+            working_dir: Some(dir.path().canonicalize().unwrap()),
+            ..Default::default()
+        };
+
+        let result = tool
+            .execute(
+                serde_json::json!({"command": "pwd", "workdir": "sub"}),
+                &ctx,
+            )
+            .await
+            .unwrap();
+
+        let output = result.result.get("output").unwrap().as_str().unwrap();
+        assert!(output.trim().ends_with("sub"));
+        assert!(output.contains(&*dir.path().canonicalize().unwrap().to_string_lossy()));
+    }
+
+    #[test]
+    fn test_domain_list_parsing() {
+        let params = serde_json::json!({
+            "allow_domains": ["pypi.org", "*.pythonhosted.org"],
+            "deny_domains": ["https://evil.example/"],
+        });
+
+        assert_eq!(
+            domain_list(&params, "allow_domains").unwrap(),
+            ["pypi.org", "*.pythonhosted.org"]
+        );
+        assert!(domain_list(&params, "deny_domains").is_err());
+        assert!(domain_list(&params, "missing").unwrap().is_empty());
+
+        for wide in ["*.com", "*.co.uk", "*.*.com", "*"] {
+            let params = serde_json::json!({ "allow_domains": [wide] });
+            assert!(domain_list(&params, "allow_domains").is_err(), "{}", wide);
+        }
+        let params =
+            serde_json::json!({ "allow_domains": ["*.example.co.uk", "*.githubusercontent.com"] });
+        assert!(domain_list(&params, "allow_domains").is_ok());
+    }
+
+    #[test]
+    fn test_allow_domains_always_asks() {
+        let tool = ShellTool::new();
+        let install = serde_json::json!({ "command": "pip install requests" });
+        assert!(!tool.requires_explicit_approval(&install));
+        let widened = serde_json::json!({
+            "command": "pip install requests",
+            "allow_domains": ["pypi.org"],
+        });
+        assert!(tool.requires_explicit_approval(&widened));
+        assert!(tool.requires_explicit_approval(&serde_json::json!({ "command": "rm -rf x" })));
+    }
+
     #[test]
     fn test_blocked_commands() {
         let tool = ShellTool::new();
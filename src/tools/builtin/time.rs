@@ -4,6 +4,7 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 
 use crate::context::JobContext;
+use crate::locale::{DEFAULT_LANGUAGE, Tz, UserLocale, parse_when};
 use crate::tools::tool::{Tool, ToolError, ToolOutput};
 
 /// Tool for getting current time and date operations.
@@ -16,7 +17,9 @@ impl Tool for TimeTool {
     }
 
     fn description(&self) -> &str {
-        "Get current time, convert timezones, or calculate time differences."
+        "Get current time, convert timezones, or calculate time differences. \
+         Times are shown in the user's time zone unless 'timezone' is given; \
+         'parse' also understands phrases like 'tomorrow at 6pm' or 'in 2 hours'."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -25,12 +28,16 @@ impl Tool for TimeTool {
             "properties": {
                 "operation": {
                     "type": "string",
-                    "enum": ["now", "parse", "format", "diff"],
+                    "enum": ["now", "parse", "format", "diff", "convert"],
                     "description": "The time operation to perform"
                 },
                 "timestamp": {
                     "type": "string",
-                    "description": "ISO 8601 timestamp (for parse/format/diff operations)"
+                    "description": "ISO 8601 timestamp (for parse/format/diff/convert operations). 'parse' and 'convert' also accept natural language such as 'friday 9am'."
+                },
+                "timezone": {
+                    "type": "string",
+                    "description": "IANA time zone (e.g. 'Europe/Berlin') for local times; defaults to the user's time zone"
                 },
                 "format": {
                     "type": "string",
@@ -48,10 +55,19 @@ impl Tool for TimeTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &JobContext,
+        ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError> {
         let start = std::time::Instant::now();
 
+        let tz = match params
+            .get("timezone")
+            .and_then(|v| v.as_str())
+            .or(ctx.timezone.as_deref())
+        {
+            Some(name) => Tz::parse(name).map_err(ToolError::InvalidParameters)?,
+            None => Tz::utc(),
+        };
+
         let operation = params
             .get("operation")
             .and_then(|v| v.as_str())
@@ -60,15 +76,8 @@ impl Tool for TimeTool {
             })?;
 
         let result = match operation {
-            "now" => {
-                let now = Utc::now();
-                serde_json::json!({
-                    "iso": now.to_rfc3339(),
-                    "unix": now.timestamp(),
-                    "unix_millis": now.timestamp_millis()
-                })
-            }
-            "parse" => {
+            "now" => describe(Utc::now(), &tz),
+            "parse" | "convert" => {
                 let timestamp = params
                     .get("timestamp")
                     .and_then(|v| v.as_str())
@@ -76,15 +85,17 @@ impl Tool for TimeTool {
                         ToolError::InvalidParameters("missing 'timestamp' parameter".to_string())
                     })?;
 
-                let dt: DateTime<Utc> = timestamp.parse().map_err(|e| {
-                    ToolError::InvalidParameters(format!("invalid timestamp: {}", e))
-                })?;
+                let dt = match timestamp.parse::<DateTime<Utc>>() {
+                    Ok(dt) => dt,
+                    Err(e) => {
+                        let locale = UserLocale::new(tz.clone(), DEFAULT_LANGUAGE);
+                        parse_when(timestamp, Utc::now(), &locale).ok_or_else(|| {
+                            ToolError::InvalidParameters(format!("invalid timestamp: {}", e))
+                        })?
+                    }
+                };
 
-                serde_json::json!({
-                    "iso": dt.to_rfc3339(),
-                    "unix": dt.timestamp(),
-                    "unix_millis": dt.timestamp_millis()
-                })
+                describe(dt, &tz)
             }
             "diff" => {
                 let ts1 = params
@@ -133,6 +144,18 @@ impl Tool for TimeTool {
     }
 }
 
+/// An instant as UTC plus local time in `tz`.
+fn describe(dt: DateTime<Utc>, tz: &Tz) -> serde_json::Value {
+    serde_json::json!({
+        "iso": dt.to_rfc3339(),
+        "unix": dt.timestamp(),
+        "unix_millis": dt.timestamp_millis(),
+        "local": tz.to_local(dt).to_rfc3339(),
+        "timezone": tz.name(),
+        "abbreviation": tz.abbreviation_at(dt)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,6 +198,33 @@ mod tests {
         assert!(output.result.get("unix").is_some());
     }
 
+    #[tokio::test]
+    async fn test_execute_parse_natural_language_in_user_zone() {
+        let tool = TimeTool;
+        let ctx = JobContext {
+            timezone: Some("+02:00".to_string()),
+            ..Default::default()
+        };
+        let params = serde_json::json!({
+            "operation": "parse",
+            "timestamp": "tomorrow at 6pm"
+        });
+        let output = tool.execute(params, &ctx).await.unwrap();
+        let local = output.result.get("local").unwrap().as_str().unwrap();
+        assert!(local.ends_with("T18:00:00+02:00"), "{}", local);
+
+        let params = serde_json::json!({
+            "operation": "convert",
+            "timestamp": "2024-01-15T10:30:00Z",
+            "timezone": "-05:00"
+        });
+        let output = tool.execute(params, &ctx).await.unwrap();
+        assert_eq!(
+            output.result.get("local").unwrap().as_str(),
+            Some("2024-01-15T05:30:00-05:00")
+        );
+    }
+
     #[tokio::test]
     async fn test_execute_parse_invalid_timestamp() {
         let tool = TimeTool;
@@ -0,0 +1,277 @@
+//! Access to full tool outputs that were summarized before reaching the LLM.
+//!
+//! When a tool returns more text than the summarization threshold, the agent
+//! hands the model a short summary instead and parks the full output here.
+//! The `tool_output` tool lets the model inspect it RLM-style: by character
+//! slice, line range, regex search or preview, without pulling the whole
+//! thing back into context.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use crate::context::JobContext;
+use crate::media::DocumentContext;
+use crate::tools::tool::{Tool, ToolError, ToolOutput};
+use crate::util::floor_char_boundary;
+use crate::workspace::ChunkConfig;
+
+/// Outputs kept before the oldest is evicted.
+const DEFAULT_CAPACITY: usize = 32;
+
+/// Largest slice returned by one read, in characters.
+const MAX_READ_CHARS: usize = 8_000;
+
+/// Most search matches returned by one search.
+const MAX_SEARCH_MATCHES: usize = 50;
+
+struct StoredOutput {
+    id: String,
+    tool_name: String,
+    context: Arc<DocumentContext>,
+}
+
+/// Bounded store of full tool outputs, keyed by a short id.
+pub struct ToolOutputStore {
+    capacity: usize,
+    outputs: Mutex<VecDeque<StoredOutput>>,
+}
+
+impl Default for ToolOutputStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl ToolOutputStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            outputs: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Store a full output and return its id.
+    pub fn insert(&self, tool_name: &str, output: String) -> String {
+        let id = format!("out_{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        let context = Arc::new(DocumentContext::new(output, ChunkConfig::default()));
+        let mut outputs = self.outputs.lock().unwrap_or_else(|e| e.into_inner());
+        while outputs.len() >= self.capacity {
+            outputs.pop_front();
+        }
+        outputs.push_back(StoredOutput {
+            id: id.clone(),
+            tool_name: tool_name.to_string(),
+            context,
+        });
+        id
+    }
+
+    /// Look up a stored output and the tool that produced it.
+    pub fn get(&self, id: &str) -> Option<(String, Arc<DocumentContext>)> {
+        let outputs = self.outputs.lock().unwrap_or_else(|e| e.into_inner());
+        outputs
+            .iter()
+            .find(|o| o.id == id)
+            .map(|o| (o.tool_name.clone(), Arc::clone(&o.context)))
+    }
+}
+
+/// Tool for reading full outputs that were summarized.
+pub struct ToolOutputTool {
+    store: Arc<ToolOutputStore>,
+}
+
+impl ToolOutputTool {
+    pub fn new(store: Arc<ToolOutputStore>) -> Self {
+        Self { store }
+    }
+}
+
+fn usize_param(params: &serde_json::Value, name: &str) -> Result<usize, ToolError> {
+    params
+        .get(name)
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize)
+        .ok_or_else(|| ToolError::InvalidParameters(format!("missing '{}' parameter", name)))
+}
+
+/// Clamp a byte range to char boundaries and the read limit.
+fn slice(text: &str, start: usize, end: usize) -> &str {
+    let start = floor_char_boundary(text, start);
+    let end = floor_char_boundary(text, end.min(start + MAX_READ_CHARS));
+    &text[start..end.max(start)]
+}
+
+#[async_trait]
+impl Tool for ToolOutputTool {
+    fn name(&self) -> &str {
+        "tool_output"
+    }
+
+    fn description(&self) -> &str {
+        "Inspect the full output of an earlier tool call that was summarized. \
+         Use the output id from the summary header. Operations: metadata, preview, \
+         read_slice (character range), read_lines (0-indexed, end exclusive), \
+         search (regex, returns matching lines)."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "string",
+                    "description": "Output id from the summary header (e.g. 'out_1a2b3c4d')"
+                },
+                "operation": {
+                    "type": "string",
+                    "enum": ["metadata", "preview", "read_slice", "read_lines", "search"],
+                    "description": "How to inspect the output"
+                },
+                "start": {
+                    "type": "integer",
+                    "description": "Start offset (characters for read_slice, lines for read_lines)"
+                },
+                "end": {
+                    "type": "integer",
+                    "description": "End offset, exclusive"
+                },
+                "pattern": {
+                    "type": "string",
+                    "description": "Regex for search"
+                }
+            },
+            "required": ["id", "operation"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        _ctx: &JobContext,
+    ) -> Result<ToolOutput, ToolError> {
+        let start = std::time::Instant::now();
+
+        let id = params
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidParameters("missing 'id' parameter".to_string()))?;
+        let operation = params
+            .get("operation")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                ToolError::InvalidParameters("missing 'operation' parameter".to_string())
+            })?;
+
+        let (tool_name, doc) = self.store.get(id).ok_or_else(|| {
+            ToolError::ExecutionFailed(format!("no stored output '{}' (it may have expired)", id))
+        })?;
+        let text = doc.read_slice(0, usize::MAX);
+
+        let result = match operation {
+            "metadata" => serde_json::json!({
+                "tool": tool_name,
+                "metadata": doc.metadata,
+            }),
+            "preview" => serde_json::Value::String(slice(text, 0, 2_000).to_string()),
+            "read_slice" => {
+                let from = usize_param(&params, "start")?;
+                let to = usize_param(&params, "end")?;
+                serde_json::Value::String(slice(text, from, to).to_string())
+            }
+            "read_lines" => {
+                let from = usize_param(&params, "start")?;
+                let to = usize_param(&params, "end")?;
+                let lines = doc.read_lines(from, to).join("\n");
+                serde_json::Value::String(slice(&lines, 0, MAX_READ_CHARS).to_string())
+            }
+            "search" => {
+                let pattern = params
+                    .get("pattern")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| {
+                        ToolError::InvalidParameters("missing 'pattern' parameter".to_string())
+                    })?;
+                let matches = doc
+                    .search(pattern)
+                    .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+                let total = matches.len();
+                serde_json::json!({
+                    "total_matches": total,
+                    "matches": matches.into_iter().take(MAX_SEARCH_MATCHES).collect::<Vec<_>>(),
+                })
+            }
+            _ => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "unknown operation: {}",
+                    operation
+                )));
+            }
+        };
+
+        Ok(ToolOutput::success(result, start.elapsed()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_evicts_oldest() {
+        let store = ToolOutputStore::new(2);
+        let a = store.insert("shell", "a".to_string());
+        let b = store.insert("shell", "b".to_string());
+        let c = store.insert("http", "c".to_string());
+        assert!(store.get(&a).is_none());
+        assert!(store.get(&b).is_some());
+        assert_eq!(store.get(&c).unwrap().0, "http");
+    }
+
+    #[tokio::test]
+    async fn test_tool_reads_stored_output() {
+        let store = Arc::new(ToolOutputStore::default());
+        let id = store.insert("shell", "line one\nerror: héllo\nline three".to_string());
+        let tool = ToolOutputTool::new(Arc::clone(&store));
+        let ctx = JobContext::default();
+
+        let out = tool
+            .execute(
+                serde_json::json!({"id": id, "operation": "search", "pattern": "^error"}),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(out.result["total_matches"], 1);
+        assert_eq!(out.result["matches"][0]["line_number"], 1);
+
+        let out = tool
+            .execute(
+                serde_json::json!({"id": id, "operation": "read_lines", "start": 1, "end": 3}),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(out.result, "error: héllo\nline three");
+
+        // Offsets inside a multi-byte character snap to a boundary.
+        let out = tool
+            .execute(
+                serde_json::json!({"id": id, "operation": "read_slice", "start": 16, "end": 18}),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(out.result, "h");
+
+        let err = tool
+            .execute(
+                serde_json::json!({"id": "out_missing", "operation": "preview"}),
+                &ctx,
+            )
+            .await;
+        assert!(err.is_err());
+    }
+}
@@ -0,0 +1,743 @@
+//! Polite web page fetching for research.
+//!
+//! Unlike the raw `http` tool, `web_fetch` only reads pages and behaves like
+//! a well-mannered crawler: it honours robots.txt for its product token,
+//! spaces requests to one host by the configured interval (or the site's
+//! `Crawl-delay`), and keeps pages in a cache that is revalidated with
+//! `ETag`/`Last-Modified` once it goes stale. HTML is reduced to its article
+//! text; pages that only render with JavaScript are loaded in the remote
+//! browser when one is configured. Every result carries the source URL and
+//! a ready-made citation.
+
+mod readable;
+mod robots;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::config::WebFetchConfig;
+use crate::context::JobContext;
+use crate::safety::LeakDetector;
+use crate::tools::builtin::browser::CdpBrowser;
+use crate::tools::builtin::http::validate_url;
+use crate::tools::tool::{Tool, ToolError, ToolOutput};
+
+pub use readable::{Readable, extract};
+pub use robots::Robots;
+
+/// Largest page body read.
+const MAX_BODY_BYTES: usize = 5 * 1024 * 1024;
+
+/// Only the first 500 KiB of a robots.txt count (RFC 9309).
+const MAX_ROBOTS_BYTES: usize = 500 * 1024;
+
+/// Redirects followed per fetch.
+const MAX_REDIRECTS: usize = 5;
+
+/// How long robots.txt rules are reused.
+const ROBOTS_TTL: Duration = Duration::from_secs(3600);
+
+/// Unreachable robots.txt blocks a host only this long before it is retried.
+const ROBOTS_ERROR_TTL: Duration = Duration::from_secs(300);
+
+/// Longest `Crawl-delay` honoured; sites asking for more are capped.
+const MAX_CRAWL_DELAY: Duration = Duration::from_secs(60);
+
+/// Longest a call waits for its turn at a busy host.
+const MAX_PACING_WAIT: Duration = Duration::from_secs(30);
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// When each host may next be contacted.
+#[derive(Default)]
+struct Pacer {
+    next: HashMap<String, Instant>,
+}
+
+impl Pacer {
+    /// Reserve the next slot for `host`, `interval` after the previous one.
+    /// Returns how long to wait for it, or `None` (and reserves nothing) if
+    /// that is longer than `max_wait`.
+    fn reserve(
+        &mut self,
+        host: &str,
+        interval: Duration,
+        max_wait: Duration,
+        now: Instant,
+    ) -> Option<Duration> {
+        let slot = self
+            .next
+            .get(host)
+            .copied()
+            .filter(|next| *next > now)
+            .unwrap_or(now);
+        let wait = slot - now;
+        if wait > max_wait {
+            return None;
+        }
+        self.next.retain(|_, next| *next > now);
+        self.next.insert(host.to_string(), slot + interval);
+        Some(wait)
+    }
+}
+
+/// A page as returned to the model.
+#[derive(Debug, Clone)]
+struct Page {
+    final_url: String,
+    title: Option<String>,
+    content: String,
+    fetched_at: DateTime<Utc>,
+    rendered: bool,
+}
+
+struct CachedPage {
+    page: Page,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    stored: Instant,
+}
+
+/// Outcome of one HTTP exchange after redirects.
+enum Fetched {
+    Body {
+        url: reqwest::Url,
+        content_type: String,
+        etag: Option<String>,
+        last_modified: Option<String>,
+        cacheable: bool,
+        body: String,
+    },
+    NotModified,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenderMode {
+    Auto,
+    Always,
+    Never,
+}
+
+/// Fetch a web page's readable text with robots.txt, pacing and caching.
+pub struct WebFetchTool {
+    client: reqwest::Client,
+    config: WebFetchConfig,
+    /// Product token matched against robots.txt user-agent lines.
+    product: String,
+    browser: Option<Arc<CdpBrowser>>,
+    robots: Mutex<HashMap<String, (Arc<Robots>, Instant)>>,
+    pacer: Mutex<Pacer>,
+    cache: Mutex<HashMap<String, CachedPage>>,
+}
+
+impl WebFetchTool {
+    pub fn new(config: WebFetchConfig) -> Self {
+        let client = crate::outbound::client_builder()
+            .timeout(config.timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent(config.user_agent.clone())
+            .build()
+            .expect("Failed to create HTTP client");
+        let product = config
+            .user_agent
+            .split(['/', ' '])
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        Self {
+            client,
+            config,
+            product,
+            browser: None,
+            robots: Mutex::new(HashMap::new()),
+            pacer: Mutex::new(Pacer::default()),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Render pages that need JavaScript in this remote browser.
+    pub fn with_browser(mut self, browser: Arc<CdpBrowser>) -> Self {
+        self.browser = Some(browser);
+        self
+    }
+
+    /// GET `url`, following redirects. Every hop is checked against the
+    /// SSRF rules, and against robots.txt and pacing when `polite`.
+    async fn get(
+        &self,
+        url: reqwest::Url,
+        polite: bool,
+        revalidate: Option<&CachedPage>,
+    ) -> Result<(reqwest::Response, reqwest::Url), ToolError> {
+        let mut url = url;
+        for _ in 0..=MAX_REDIRECTS {
+            let url_checked = validate_url(url.as_str())?;
+            if polite {
+                let robots = self.robots_for(&url_checked).await;
+                let path = match url_checked.query() {
+                    Some(query) => format!("{}?{}", url_checked.path(), query),
+                    None => url_checked.path().to_string(),
+                };
+                if !robots.allowed(&path) {
+                    return Err(ToolError::NotAuthorized(format!(
+                        "robots.txt of {} disallows fetching {}",
+                        url_checked.host_str().unwrap_or_default(),
+                        path
+                    )));
+                }
+                self.pace(&url_checked, &robots).await?;
+            }
+
+            let mut request = self.client.get(url_checked.clone()).header(
+                "Accept",
+                "text/html,application/xhtml+xml,text/plain;q=0.9,*/*;q=0.5",
+            );
+            if let Some(cached) = revalidate.filter(|c| c.page.final_url == url_checked.as_str()) {
+                if let Some(etag) = &cached.etag {
+                    request = request.header("If-None-Match", etag);
+                }
+                if let Some(modified) = &cached.last_modified {
+                    request = request.header("If-Modified-Since", modified);
+                }
+            }
+            let response = request.send().await.map_err(|e| {
+                if e.is_timeout() {
+                    ToolError::Timeout(self.config.timeout)
+                } else {
+                    ToolError::ExternalService(e.to_string())
+                }
+            })?;
+
+            if response.status().is_redirection()
+                && response.status() != reqwest::StatusCode::NOT_MODIFIED
+            {
+                let location = response
+                    .headers()
+                    .get(reqwest::header::LOCATION)
+                    .and_then(|v| v.to_str().ok())
+                    .ok_or_else(|| {
+                        ToolError::ExternalService(format!(
+                            "HTTP {} without a Location header",
+                            response.status()
+                        ))
+                    })?;
+                url = url_checked.join(location).map_err(|e| {
+                    ToolError::ExternalService(format!("bad redirect target: {}", e))
+                })?;
+                url.set_fragment(None);
+                continue;
+            }
+            return Ok((response, url_checked));
+        }
+        Err(ToolError::ExternalService(format!(
+            "more than {} redirects",
+            MAX_REDIRECTS
+        )))
+    }
+
+    /// The robots.txt rules for `url`'s origin, fetched at most once per TTL.
+    async fn robots_for(&self, url: &reqwest::Url) -> Arc<Robots> {
+        let origin = url.origin().ascii_serialization();
+        if let Some((robots, expires)) = lock(&self.robots).get(&origin)
+            && *expires > Instant::now()
+        {
+            return Arc::clone(robots);
+        }
+
+        let fetched = match url.join("/robots.txt") {
+            // Boxed: `get` calls back into this for polite fetches.
+            Ok(robots_url) => Box::pin(self.get(robots_url, false, None)).await,
+            Err(e) => Err(ToolError::ExternalService(e.to_string())),
+        };
+        let (robots, ttl) = match fetched {
+            Ok((response, _)) if response.status().is_success() => {
+                match read_capped(response, MAX_ROBOTS_BYTES, true).await {
+                    Ok(body) => (Robots::parse(&body, &self.product), ROBOTS_TTL),
+                    Err(_) => (Robots::disallow_all(), ROBOTS_ERROR_TTL),
+                }
+            }
+            // No robots.txt (or no access to it) means no restrictions.
+            Ok((response, _)) if response.status().is_client_error() => {
+                (Robots::allow_all(), ROBOTS_TTL)
+            }
+            Ok((response, _)) => {
+                tracing::debug!(
+                    "robots.txt for {} answered HTTP {}; treating as disallowed",
+                    origin,
+                    response.status()
+                );
+                (Robots::disallow_all(), ROBOTS_ERROR_TTL)
+            }
+            Err(e) => {
+                tracing::debug!("robots.txt for {} unreachable: {}", origin, e);
+                (Robots::disallow_all(), ROBOTS_ERROR_TTL)
+            }
+        };
+        let robots = Arc::new(robots);
+        let mut cache = lock(&self.robots);
+        let now = Instant::now();
+        cache.retain(|_, (_, expires)| *expires > now);
+        cache.insert(origin, (Arc::clone(&robots), now + ttl));
+        robots
+    }
+
+    /// Wait for this host's next free slot.
+    async fn pace(&self, url: &reqwest::Url, robots: &Robots) -> Result<(), ToolError> {
+        let host = url.host_str().unwrap_or_default();
+        let interval = robots
+            .crawl_delay()
+            .map(|delay| delay.min(MAX_CRAWL_DELAY))
+            .unwrap_or_default()
+            .max(self.config.min_interval);
+        let wait = lock(&self.pacer)
+            .reserve(host, interval, MAX_PACING_WAIT, Instant::now())
+            .ok_or_else(|| {
+                ToolError::ExecutionFailed(format!(
+                    "too many recent requests to {}; try again later",
+                    host
+                ))
+            })?;
+        if !wait.is_zero() {
+            tracing::debug!("Waiting {:?} before fetching from {}", wait, host);
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+
+    async fn fetch(
+        &self,
+        url: reqwest::Url,
+        revalidate: Option<&CachedPage>,
+    ) -> Result<Fetched, ToolError> {
+        let (response, final_url) = self.get(url, true, revalidate).await?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_MODIFIED && revalidate.is_some() {
+            return Ok(Fetched::NotModified);
+        }
+        if !status.is_success() {
+            return Err(ToolError::ExternalService(format!(
+                "{} answered HTTP {}",
+                final_url, status
+            )));
+        }
+        let header = |name: reqwest::header::HeaderName| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
+        };
+        let content_type = header(reqwest::header::CONTENT_TYPE)
+            .unwrap_or_else(|| "text/html".to_string())
+            .to_ascii_lowercase();
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+        let cacheable = !header(reqwest::header::CACHE_CONTROL)
+            .is_some_and(|cc| cc.to_ascii_lowercase().contains("no-store"));
+        let body = read_capped(response, MAX_BODY_BYTES, false).await?;
+        Ok(Fetched::Body {
+            url: final_url,
+            content_type,
+            etag,
+            last_modified,
+            cacheable,
+            body,
+        })
+    }
+
+    /// Load `url` in the remote browser and return the rendered HTML.
+    async fn render(&self, browser: &CdpBrowser, url: &str) -> Result<String, ToolError> {
+        let page = browser.acquire().await?;
+        let rendered = async {
+            let navigated = browser
+                .call(&page, "Page.navigate", serde_json::json!({ "url": url }))
+                .await?;
+            if let Some(error) = navigated.get("errorText").and_then(Value::as_str) {
+                return Err(ToolError::ExternalService(format!(
+                    "browser could not load {}: {}",
+                    url, error
+                )));
+            }
+            browser.wait_for_load(&page).await?;
+            browser
+                .evaluate(&page, "document.documentElement.outerHTML")
+                .await?
+                .as_str()
+                .map(String::from)
+                .ok_or_else(|| ToolError::ExternalService("browser returned no HTML".to_string()))
+        }
+        .await;
+        browser.release(page).await;
+        rendered
+    }
+
+    fn store(&self, key: String, page: Page, etag: Option<String>, last_modified: Option<String>) {
+        if self.config.cache_entries == 0 {
+            return;
+        }
+        let mut cache = lock(&self.cache);
+        if cache.len() >= self.config.cache_entries
+            && !cache.contains_key(&key)
+            && let Some(oldest) = cache
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored)
+                .map(|(key, _)| key.clone())
+        {
+            cache.remove(&oldest);
+        }
+        cache.insert(
+            key,
+            CachedPage {
+                page,
+                etag,
+                last_modified,
+                stored: Instant::now(),
+            },
+        );
+    }
+
+    fn output(&self, url: &str, page: &Page, cached: bool, max_chars: usize) -> Value {
+        let total = page.content.chars().count();
+        let content: String = page.content.chars().take(max_chars).collect();
+        let citation = match &page.title {
+            Some(title) => format!("[{}]({})", title.replace(['[', ']'], ""), page.final_url),
+            None => format!("<{}>", page.final_url),
+        };
+        serde_json::json!({
+            "url": url,
+            "final_url": page.final_url,
+            "title": page.title,
+            "content": content,
+            "truncated": total > max_chars,
+            "total_chars": total,
+            "fetched_at": page.fetched_at.to_rfc3339(),
+            "cached": cached,
+            "rendered": page.rendered,
+            "citation": citation,
+        })
+    }
+}
+
+/// Read at most `limit` bytes of a body as text. Longer bodies are an error
+/// unless `truncate`.
+async fn read_capped(
+    mut response: reqwest::Response,
+    limit: usize,
+    truncate: bool,
+) -> Result<String, ToolError> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| ToolError::ExternalService(format!("failed to read response: {}", e)))?
+    {
+        body.extend_from_slice(&chunk);
+        if body.len() > limit {
+            if !truncate {
+                return Err(ToolError::ExecutionFailed(format!(
+                    "page is larger than {} bytes",
+                    limit
+                )));
+            }
+            body.truncate(limit);
+            break;
+        }
+    }
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+fn is_html(content_type: &str) -> bool {
+    content_type.contains("html")
+}
+
+fn is_text(content_type: &str) -> bool {
+    content_type.starts_with("text/")
+        || content_type.contains("json")
+        || content_type.contains("markdown")
+}
+
+#[async_trait]
+impl Tool for WebFetchTool {
+    fn name(&self) -> &str {
+        "web_fetch"
+    }
+
+    fn description(&self) -> &str {
+        "Read a web page as clean article text. Respects robots.txt, rate-limits \
+         requests per site, caches pages, and renders JavaScript-only pages in the \
+         browser when available. Use this (not `http`) for reading pages; cite the \
+         returned `citation` whenever you use the content in a reply."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "https URL of the page"
+                },
+                "render": {
+                    "type": "string",
+                    "enum": ["auto", "always", "never"],
+                    "description": "Load the page in the browser: 'auto' (default) only when the plain fetch yields almost no text"
+                },
+                "max_chars": {
+                    "type": "integer",
+                    "description": "Most characters of text to return"
+                },
+                "refresh": {
+                    "type": "boolean",
+                    "description": "Skip the cache and fetch again (default false)"
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    async fn execute(&self, params: Value, _ctx: &JobContext) -> Result<ToolOutput, ToolError> {
+        let start = Instant::now();
+        let raw_url = params
+            .get("url")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ToolError::InvalidParameters("missing 'url' parameter".to_string()))?;
+        let render = match params.get("render").and_then(Value::as_str) {
+            None | Some("auto") => RenderMode::Auto,
+            Some("always") => RenderMode::Always,
+            Some("never") => RenderMode::Never,
+            Some(other) => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "unknown render mode '{}'",
+                    other
+                )));
+            }
+        };
+        if render == RenderMode::Always && self.browser.is_none() {
+            return Err(ToolError::InvalidParameters(
+                "no browser is configured for rendering".to_string(),
+            ));
+        }
+        let max_chars = params
+            .get("max_chars")
+            .and_then(Value::as_u64)
+            .map_or(self.config.max_chars, |n| {
+                (n as usize).clamp(1, self.config.max_chars)
+            });
+        let refresh = params
+            .get("refresh")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+        let mut url = reqwest::Url::parse(raw_url)
+            .map_err(|e| ToolError::InvalidParameters(format!("invalid URL: {}", e)))?;
+        url.set_fragment(None);
+        let key = url.to_string();
+
+        // Fresh enough to serve as is?
+        let revalidate = {
+            let mut cache = lock(&self.cache);
+            match cache.get(&key) {
+                Some(entry)
+                    if !refresh
+                        && entry.stored.elapsed() < self.config.cache_ttl
+                        && (render != RenderMode::Always || entry.page.rendered) =>
+                {
+                    let result = self.output(&key, &entry.page, true, max_chars);
+                    return Ok(ToolOutput::success(result, start.elapsed()));
+                }
+                Some(_) if refresh => {
+                    cache.remove(&key);
+                    None
+                }
+                Some(entry) if render != RenderMode::Always || entry.page.rendered => {
+                    Some(CachedPage {
+                        page: entry.page.clone(),
+                        etag: entry.etag.clone(),
+                        last_modified: entry.last_modified.clone(),
+                        stored: entry.stored,
+                    })
+                }
+                _ => None,
+            }
+        };
+
+        // Cache hits send nothing, so the checks can wait until here.
+        validate_url(&key)?;
+        LeakDetector::new()
+            .scan_http_request(&key, &[], None)
+            .map_err(|e| ToolError::NotAuthorized(format!("{}", e)))?;
+
+        let (final_url, content_type, etag, last_modified, cacheable, body) =
+            match self.fetch(url.clone(), revalidate.as_ref()).await? {
+                Fetched::NotModified => {
+                    let cached = revalidate.expect("revalidated without an entry");
+                    let result = self.output(&key, &cached.page, true, max_chars);
+                    self.store(key, cached.page, cached.etag, cached.last_modified);
+                    return Ok(ToolOutput::success(result, start.elapsed()));
+                }
+                Fetched::Body {
+                    url,
+                    content_type,
+                    etag,
+                    last_modified,
+                    cacheable,
+                    body,
+                } => (url, content_type, etag, last_modified, cacheable, body),
+            };
+
+        let (title, content, rendered) = if is_html(&content_type) {
+            let mut readable = extract(&body, Some(&final_url));
+            let mut rendered = false;
+            if let Some(browser) = &self.browser
+                && (render == RenderMode::Always
+                    || (render == RenderMode::Auto && readable.is_thin()))
+            {
+                match self.render(browser, final_url.as_str()).await {
+                    Ok(html) => {
+                        let from_browser = extract(&html, Some(&final_url));
+                        if render == RenderMode::Always
+                            || from_browser.markdown.len() > readable.markdown.len()
+                        {
+                            readable = Readable {
+                                title: from_browser.title.or(readable.title),
+                                markdown: from_browser.markdown,
+                            };
+                            rendered = true;
+                        }
+                    }
+                    Err(e) if render == RenderMode::Always => return Err(e),
+                    Err(e) => tracing::debug!("Rendering {} failed: {}", final_url, e),
+                }
+            }
+            (readable.title, readable.markdown, rendered)
+        } else if is_text(&content_type) {
+            (None, body, false)
+        } else {
+            return Err(ToolError::ExecutionFailed(format!(
+                "unsupported content type '{}'",
+                content_type
+            )));
+        };
+
+        let page = Page {
+            final_url: final_url.to_string(),
+            title,
+            content,
+            fetched_at: Utc::now(),
+            rendered,
+        };
+        let result = self.output(&key, &page, false, max_chars);
+        if cacheable {
+            self.store(key, page, etag, last_modified);
+        }
+        Ok(ToolOutput::success(result, start.elapsed()))
+    }
+
+    fn estimated_duration(&self, _params: &Value) -> Option<Duration> {
+        Some(Duration::from_secs(5))
+    }
+
+    fn execution_timeout(&self) -> Duration {
+        // Room for pacing, redirects, robots.txt and a browser render.
+        MAX_PACING_WAIT + self.config.timeout * 3
+    }
+
+    fn requires_sanitization(&self) -> bool {
+        true // Page text is untrusted
+    }
+
+    fn requires_approval(&self) -> bool {
+        true // Requests go to external sites, like the http tool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pacer_spaces_requests_per_host() {
+        let mut pacer = Pacer::default();
+        let now = Instant::now();
+        let second = Duration::from_secs(1);
+        let max = Duration::from_secs(3);
+        assert_eq!(
+            pacer.reserve("a.com", second, max, now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(pacer.reserve("a.com", second, max, now), Some(second));
+        assert_eq!(
+            pacer.reserve("b.com", second, max, now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(pacer.reserve("a.com", second, max, now), Some(second * 2));
+        assert_eq!(pacer.reserve("a.com", second, max, now), Some(second * 3));
+        // Too far out: refused without taking a slot.
+        assert_eq!(pacer.reserve("a.com", second, max, now), None);
+        assert_eq!(
+            pacer.reserve("a.com", second, max, now + second * 4),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit_and_citation() {
+        let tool = WebFetchTool::new(WebFetchConfig {
+            cache_entries: 1,
+            ..WebFetchConfig::default()
+        });
+        let page = Page {
+            final_url: "https://example.com/post".to_string(),
+            title: Some("A [draft] post".to_string()),
+            content: "x".repeat(50),
+            fetched_at: Utc::now(),
+            rendered: false,
+        };
+        tool.store("https://example.com/p".to_string(), page, None, None);
+        let output = tool
+            .execute(
+                serde_json::json!({ "url": "https://example.com/p", "max_chars": 10 }),
+                &JobContext::default(),
+            )
+            .await
+            .unwrap();
+        let result = &output.result;
+        assert_eq!(result["cached"], true);
+        assert_eq!(result["truncated"], true);
+        assert_eq!(result["content"].as_str().unwrap().len(), 10);
+        assert_eq!(
+            result["citation"],
+            "[A draft post](https://example.com/post)"
+        );
+
+        // One entry only: a second page evicts the first.
+        let other = Page {
+            final_url: "https://example.com/q".to_string(),
+            title: None,
+            content: String::new(),
+            fetched_at: Utc::now(),
+            rendered: false,
+        };
+        tool.store("https://example.com/q".to_string(), other, None, None);
+        assert_eq!(lock(&tool.cache).len(), 1);
+        assert!(lock(&tool.cache).contains_key("https://example.com/q"));
+
+        let err = tool
+            .execute(
+                serde_json::json!({ "url": "https://example.com/", "render": "always" }),
+                &JobContext::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidParameters(_)));
+    }
+}
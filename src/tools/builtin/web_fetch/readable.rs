@@ -0,0 +1,478 @@
+//! Readable article text from a web page.
+//!
+//! A small take on Readability: boilerplate elements (navigation, footers,
+//! sidebars, scripts) are dropped, every paragraph scores its container by
+//! its length, commas and share of non-link text, and the container above
+//! that at half weight. The best container after a link-density penalty
+//! becomes the article. That HTML is converted with
+//! [`html_to_markdown`] after its links are made absolute.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::media::html_to_markdown;
+
+/// Below this many characters of text the page probably needs JavaScript.
+pub const MIN_READABLE_CHARS: usize = 200;
+
+/// Paragraphs shorter than this do not count towards a container's score.
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+/// Elements that never hold article text.
+const DROPPED: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "iframe", "nav", "header", "footer", "aside",
+    "form", "button", "select", "dialog",
+];
+
+/// Elements whose content is not markup.
+const RAW_TEXT: &[&str] = &["script", "style", "template", "textarea"];
+
+/// Elements kept whatever their class says.
+const KEPT: &[&str] = &["html", "body", "main", "article"];
+
+const VOID: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+const CONTAINERS: &[&str] = &["div", "section", "article", "main", "td", "body"];
+
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\b(?:class|id|role)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))"#)
+        .expect("valid regex")
+});
+static UNLIKELY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?:^|[\s_-])(?:comments?|sidebar|footer|nav|navbar|navigation|menu|share|sharing|social|ads?|advert|advertisement|promo|cookies?|banner|related|newsletter|subscribe|breadcrumbs?|popup|modal|complementary|contentinfo)(?:$|[\s_-])",
+    )
+    .expect("valid regex")
+});
+static LIKELY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)article|body|content|entry|main|page|post|text|blog|story")
+        .expect("valid regex")
+});
+static HREF: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\shref\s*=\s*(?:"([^"]*)"|'([^']*)')"#).expect("valid regex")
+});
+static TITLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title>").expect("valid regex"));
+static OG_TITLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)<meta\s[^>]*property\s*=\s*["']og:title["'][^>]*>"#).expect("valid regex")
+});
+static CONTENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)\scontent\s*=\s*(?:"([^"]*)"|'([^']*)')"#).expect("valid regex")
+});
+
+/// The readable part of a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Readable {
+    pub title: Option<String>,
+    pub markdown: String,
+}
+
+impl Readable {
+    /// Whether so little text came out that the page likely renders it
+    /// with JavaScript.
+    pub fn is_thin(&self) -> bool {
+        self.markdown.chars().filter(|c| !c.is_whitespace()).count() < MIN_READABLE_CHARS
+    }
+}
+
+struct Tag {
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    start: usize,
+    end: usize,
+}
+
+/// The next tag at or after `from`, skipping comments and doctypes.
+fn next_tag(html: &str, from: usize) -> Option<Tag> {
+    let mut pos = from;
+    loop {
+        let lt = pos + html.get(pos..)?.find('<')?;
+        let after = &html[lt + 1..];
+        if after.starts_with("!--") {
+            pos = lt + html[lt..].find("-->")? + 3;
+            continue;
+        }
+        let closing = after.starts_with('/');
+        let name: String = after
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if name.is_empty() {
+            pos = if after.starts_with('!') || after.starts_with('?') {
+                lt + html[lt..].find('>')? + 1
+            } else {
+                lt + 1
+            };
+            continue;
+        }
+        // Quotes only count right after `=`, so stray apostrophes in
+        // unquoted values do not swallow the rest of the page.
+        let mut quote = None;
+        let mut previous = ' ';
+        let mut end = None;
+        for (i, c) in html[lt..].char_indices() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if (c == '"' || c == '\'') && previous == '=' => quote = Some(c),
+                None if c == '>' => {
+                    end = Some(lt + i + 1);
+                    break;
+                }
+                None => {}
+            }
+            if !c.is_whitespace() {
+                previous = c;
+            }
+        }
+        let end = end?;
+        return Some(Tag {
+            self_closing: html[..end - 1].ends_with('/'),
+            name,
+            closing,
+            start: lt,
+            end,
+        });
+    }
+}
+
+/// Where the element opened by `open` ends (after its closing tag).
+fn element_end(html: &str, lower: &str, open: &Tag) -> Option<usize> {
+    if RAW_TEXT.contains(&open.name.as_str()) {
+        let close = open.end + lower[open.end..].find(&format!("</{}", open.name))?;
+        return Some(close + html[close..].find('>')? + 1);
+    }
+    let mut depth = 1usize;
+    let mut pos = open.end;
+    while let Some(tag) = next_tag(html, pos) {
+        pos = tag.end;
+        if tag.name != open.name || tag.self_closing {
+            continue;
+        }
+        if tag.closing {
+            depth -= 1;
+            if depth == 0 {
+                return Some(tag.end);
+            }
+        } else {
+            depth += 1;
+        }
+    }
+    None
+}
+
+/// Whether a tag's class, id or role marks it as page furniture.
+fn unlikely(tag_source: &str) -> bool {
+    ATTRIBUTE.captures_iter(tag_source).any(|caps| {
+        let value = caps
+            .get(1)
+            .or_else(|| caps.get(2))
+            .or_else(|| caps.get(3))
+            .map_or("", |m| m.as_str());
+        UNLIKELY.is_match(value) && !LIKELY.is_match(value)
+    })
+}
+
+/// `html` without boilerplate elements; `by_attributes` also drops elements
+/// whose class or id looks like furniture.
+fn strip(html: &str, by_attributes: bool) -> String {
+    let lower = html.to_ascii_lowercase();
+    let mut out = String::with_capacity(html.len());
+    let mut copied = 0;
+    let mut pos = 0;
+    while let Some(tag) = next_tag(html, pos) {
+        pos = tag.end;
+        if tag.closing {
+            continue;
+        }
+        let name = tag.name.as_str();
+        let drop = DROPPED.contains(&name)
+            || (by_attributes && !KEPT.contains(&name) && unlikely(&html[tag.start..tag.end]));
+        if !drop {
+            // Skip raw text so a `<` inside a script is not read as a tag.
+            if RAW_TEXT.contains(&name)
+                && let Some(end) = element_end(html, &lower, &tag)
+            {
+                pos = end;
+            }
+            continue;
+        }
+        out.push_str(&html[copied..tag.start]);
+        let end = if tag.self_closing || VOID.contains(&name) {
+            tag.end
+        } else {
+            // An unclosed element loses only its opening tag.
+            element_end(html, &lower, &tag).unwrap_or(tag.end)
+        };
+        copied = end;
+        pos = end;
+    }
+    out.push_str(&html[copied..]);
+    out
+}
+
+/// Visible text of an HTML fragment with whitespace collapsed.
+fn visible_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut pos = 0;
+    while let Some(tag) = next_tag(html, pos) {
+        text.push_str(&html[pos..tag.start]);
+        text.push(' ');
+        pos = tag.end;
+    }
+    text.push_str(html.get(pos..).unwrap_or_default());
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Share of a fragment's text that sits inside links.
+fn link_density(html: &str) -> f64 {
+    let total = visible_text(html).chars().count();
+    if total == 0 {
+        return 0.0;
+    }
+    let mut linked = 0;
+    let mut open = None;
+    let mut pos = 0;
+    while let Some(tag) = next_tag(html, pos) {
+        pos = tag.end;
+        if tag.name != "a" {
+            continue;
+        }
+        match (tag.closing, open) {
+            (false, _) => open = Some(tag.end),
+            (true, Some(start)) => {
+                linked += visible_text(&html[start..tag.start]).chars().count();
+                open = None;
+            }
+            (true, None) => {}
+        }
+    }
+    linked as f64 / total as f64
+}
+
+struct Candidate {
+    name: String,
+    start: usize,
+    end: usize,
+    score: f64,
+}
+
+/// Inner HTML of the container that most likely holds the article.
+fn best_container(html: &str) -> Option<&str> {
+    let mut candidates: Vec<Candidate> = Vec::new();
+    let mut stack: Vec<usize> = Vec::new();
+    let mut paragraph: Option<usize> = None;
+
+    let finish = |candidates: &mut Vec<Candidate>,
+                  stack: &[usize],
+                  paragraph: &mut Option<usize>,
+                  end: usize| {
+        let Some(start) = paragraph.take() else {
+            return;
+        };
+        let text = visible_text(&html[start..end]);
+        let len = text.chars().count();
+        if len < MIN_PARAGRAPH_CHARS {
+            return;
+        }
+        // Menus of links are not prose, however long.
+        let score = (1.0 + text.matches(',').count() as f64 + (len as f64 / 100.0).min(3.0))
+            * (1.0 - link_density(&html[start..end]));
+        if let Some(&parent) = stack.last() {
+            candidates[parent].score += score;
+        }
+        if let Some(&grandparent) = stack.iter().rev().nth(1) {
+            candidates[grandparent].score += score / 2.0;
+        }
+    };
+
+    let mut pos = 0;
+    while let Some(tag) = next_tag(html, pos) {
+        pos = tag.end;
+        let name = tag.name.as_str();
+        if name == "p" || name == "pre" {
+            finish(&mut candidates, &stack, &mut paragraph, tag.start);
+            if !tag.closing {
+                paragraph = Some(tag.end);
+            }
+        } else if CONTAINERS.contains(&name) {
+            // Block containers end any open paragraph, as browsers do.
+            finish(&mut candidates, &stack, &mut paragraph, tag.start);
+            if tag.closing {
+                if let Some(depth) = stack.iter().rposition(|&i| candidates[i].name == name) {
+                    for &i in &stack[depth..] {
+                        candidates[i].end = tag.start;
+                    }
+                    stack.truncate(depth);
+                }
+            } else if !tag.self_closing {
+                stack.push(candidates.len());
+                candidates.push(Candidate {
+                    name: tag.name,
+                    start: tag.end,
+                    end: html.len(),
+                    score: 0.0,
+                });
+            }
+        }
+    }
+    finish(&mut candidates, &stack, &mut paragraph, html.len());
+
+    candidates
+        .iter()
+        .filter(|c| c.score > 0.0)
+        .map(|c| {
+            let inner = &html[c.start..c.end];
+            let bonus = if c.name == "article" || c.name == "main" {
+                5.0
+            } else {
+                0.0
+            };
+            ((c.score + bonus) * (1.0 - link_density(inner)), inner)
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, inner)| inner)
+}
+
+/// Rewrite link targets relative to `base`; script and fragment-only links
+/// lose their target.
+fn absolute_links(html: &str, base: Option<&reqwest::Url>) -> String {
+    HREF.replace_all(html, |caps: &regex::Captures| {
+        let href = caps
+            .get(1)
+            .or_else(|| caps.get(2))
+            .map_or("", |m| m.as_str())
+            .trim();
+        if href.is_empty()
+            || href.starts_with('#')
+            || href.to_ascii_lowercase().starts_with("javascript:")
+        {
+            return String::new();
+        }
+        let joined = match base {
+            Some(base) => base.join(href).map(|u| u.to_string()).ok(),
+            None => reqwest::Url::parse(href).map(|u| u.to_string()).ok(),
+        };
+        match joined {
+            Some(url) => format!(" href=\"{}\"", url),
+            None => String::new(),
+        }
+    })
+    .into_owned()
+}
+
+/// The page title: `og:title` if present, else `<title>`.
+fn title(html: &str) -> Option<String> {
+    let og = OG_TITLE.find(html).and_then(|tag| {
+        CONTENT.captures(tag.as_str()).and_then(|caps| {
+            caps.get(1)
+                .or_else(|| caps.get(2))
+                .map(|m| m.as_str().to_string())
+        })
+    });
+    let raw = og.or_else(|| TITLE.captures(html).map(|caps| caps[1].to_string()))?;
+    // The converter decodes entities and collapses whitespace.
+    let text = html_to_markdown(&format!("<p>{}</p>", raw.replace('<', "&lt;")));
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Extract the article from `html`, resolving links against `base`.
+pub fn extract(html: &str, base: Option<&reqwest::Url>) -> Readable {
+    let title = title(html);
+    let convert = |by_attributes: bool| {
+        let cleaned = strip(html, by_attributes);
+        let article = best_container(&cleaned).unwrap_or(&cleaned);
+        Readable {
+            title: title.clone(),
+            markdown: html_to_markdown(&absolute_links(article, base))
+                .trim()
+                .to_string(),
+        }
+    };
+    let readable = convert(true);
+    if !readable.is_thin() {
+        return readable;
+    }
+    // Class names can mislead; try again with only the element filter.
+    let lenient = convert(false);
+    if lenient.markdown.len() > readable.markdown.len() {
+        lenient
+    } else {
+        readable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<!DOCTYPE html>
+<html><head>
+<title>Fallback title</title>
+<meta property="og:title" content="Rust &amp; Readability">
+<script>if (a < b) { document.write("<p>not text</p>"); }</script>
+<style>p { color: red }</style>
+</head>
+<body class="nav-open">
+<header><a href="/">Home</a> <a href="/about">About</a></header>
+<nav><ul><li><a href="/a">Section A, Section B, Section C and more links</a></li></ul></nav>
+<div class="layout">
+  <div class="sidebar-widget"><p>Popular posts, trending topics, many, many commas here.</p></div>
+  <article>
+    <h1>The headline</h1>
+    <p>The first paragraph of the story is long enough to count, with a comma or two, and then some.</p>
+    <p>A second paragraph links to <a href="../docs/guide.html">the guide</a> and keeps going, at length.</p>
+    <p>Short.</p>
+    <p><a href="javascript:void(0)">Share</a></p>
+  </article>
+  <div class="comments"><p>First comment here, with an opinion, and another, and another one.</p></div>
+</div>
+<footer><p>Copyright notice, legal text, privacy, terms and so forth.</p></footer>
+</body></html>"#;
+
+    #[test]
+    fn test_extract_article() {
+        let base = reqwest::Url::parse("https://example.com/blog/post/").unwrap();
+        let readable = extract(PAGE, Some(&base));
+        assert_eq!(readable.title.as_deref(), Some("Rust & Readability"));
+        let md = &readable.markdown;
+        assert!(md.contains("# The headline"), "{md}");
+        assert!(md.contains("The first paragraph of the story"));
+        assert!(md.contains("[the guide](https://example.com/blog/docs/guide.html)"));
+        assert!(md.contains("Share") && !md.contains("javascript:"));
+        for furniture in [
+            "Popular posts",
+            "First comment",
+            "Copyright",
+            "About",
+            "not text",
+        ] {
+            assert!(!md.contains(furniture), "{furniture} leaked into {md}");
+        }
+    }
+
+    #[test]
+    fn test_best_container_without_article_tag() {
+        let html = format!(
+            "<body><div id=\"menu\"><p><a href=\"/x\">{}</a></p></div><div id=\"x\"><p>{}</p><p>{}</p></div></body>",
+            "a, b, c, d, e, f, g, h, i, j, k",
+            "Plain story text that goes on for a while, sentence after sentence.",
+            "More of the same story, which makes this div the clear winner here."
+        );
+        let cleaned = strip(&html, false);
+        let best = best_container(&cleaned).unwrap();
+        assert!(best.contains("Plain story text") && !best.contains("a, b, c"));
+
+        assert_eq!(visible_text("<p>a <b>b</b>\n c</p>"), "a b c");
+        assert!(link_density("<p><a href=x>all linked</a></p>") > 0.99);
+        assert!(next_tag("a < b and no tags", 0).is_none());
+    }
+}
@@ -0,0 +1,206 @@
+//! robots.txt rules (RFC 9309).
+//!
+//! Only the group for our product token applies; if there is none, the `*`
+//! group does. Among matching rules the longest pattern wins and `Allow`
+//! wins ties. `Crawl-delay` is not part of the RFC but is honoured.
+
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+/// The rules that apply to one user agent on one origin.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Robots {
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+#[derive(Default)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+impl Robots {
+    /// Everything allowed (no robots.txt, or a 4xx for it).
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Everything disallowed (robots.txt unreachable, as the RFC asks).
+    pub fn disallow_all() -> Self {
+        Self {
+            rules: vec![Rule {
+                allow: false,
+                pattern: "/".to_string(),
+            }],
+            crawl_delay: None,
+        }
+    }
+
+    /// The rules in `body` for `product` (e.g. `IronClaw`).
+    pub fn parse(body: &str, product: &str) -> Self {
+        let product = product.to_ascii_lowercase();
+        let mut groups: Vec<Group> = Vec::new();
+        // A user-agent line after rules starts a new group.
+        let mut open = false;
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+            match key.as_str() {
+                "user-agent" => {
+                    if !open {
+                        groups.push(Group::default());
+                        open = true;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.agents.push(value.to_ascii_lowercase());
+                    }
+                }
+                "allow" | "disallow" => {
+                    open = false;
+                    let Some(group) = groups.last_mut() else {
+                        continue;
+                    };
+                    // An empty Disallow allows everything; no rule needed.
+                    if value.is_empty() {
+                        continue;
+                    }
+                    group.rules.push(Rule {
+                        allow: key == "allow",
+                        pattern: value.to_string(),
+                    });
+                }
+                "crawl-delay" => {
+                    open = false;
+                    if let Some(group) = groups.last_mut()
+                        && let Ok(secs) = value.parse::<f64>()
+                        && secs.is_finite()
+                        && secs >= 0.0
+                    {
+                        group.crawl_delay = Some(Duration::from_secs_f64(secs));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let matches = |group: &&Group, wanted: &dyn Fn(&str) -> bool| {
+            group.agents.iter().any(|agent| wanted(agent))
+        };
+        let mut chosen: Vec<&Group> = groups
+            .iter()
+            .filter(|g| matches(g, &|agent| agent != "*" && product.contains(agent)))
+            .collect();
+        if chosen.is_empty() {
+            chosen = groups
+                .iter()
+                .filter(|g| matches(g, &|agent| agent == "*"))
+                .collect();
+        }
+        Self {
+            rules: chosen.iter().flat_map(|g| g.rules.clone()).collect(),
+            crawl_delay: chosen.iter().find_map(|g| g.crawl_delay),
+        }
+    }
+
+    /// Whether `path` (path plus query) may be fetched.
+    pub fn allowed(&self, path: &str) -> bool {
+        let path = if path.is_empty() { "/" } else { path };
+        self.rules
+            .iter()
+            .filter(|rule| pattern_matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+
+    pub fn crawl_delay(&self) -> Option<Duration> {
+        self.crawl_delay
+    }
+}
+
+/// Match a robots.txt path pattern: `*` is any run of characters and a
+/// trailing `$` anchors the end; otherwise patterns are prefixes.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        let last = i + 1 == parts.len();
+        if last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_groups_and_longest_match() {
+        let body = "\
+# comment
+User-agent: *
+Disallow: /private
+Crawl-delay: 5
+
+User-agent: GoogleBot
+User-agent: ironclaw
+Disallow: /search
+Disallow: /*.pdf$
+Allow: /search/about
+Crawl-delay: 0.5
+";
+        let ours = Robots::parse(body, "IronClaw");
+        assert!(ours.allowed("/private/page"));
+        assert!(!ours.allowed("/search?q=x"));
+        assert!(ours.allowed("/search/about"));
+        assert!(!ours.allowed("/files/report.pdf"));
+        assert!(ours.allowed("/files/report.pdf?download=1"));
+        assert_eq!(ours.crawl_delay(), Some(Duration::from_millis(500)));
+
+        let others = Robots::parse(body, "OtherBot");
+        assert!(!others.allowed("/private"));
+        assert!(others.allowed("/search"));
+        assert_eq!(others.crawl_delay(), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_empty_and_edge_cases() {
+        let open = Robots::parse("User-agent: *\nDisallow:\n", "IronClaw");
+        assert!(open.allowed("/anything"));
+        assert!(Robots::allow_all().allowed(""));
+        assert!(!Robots::disallow_all().allowed("/"));
+
+        // Equal length: Allow wins.
+        let tie = Robots::parse("User-agent: *\nDisallow: /a\nAllow: /a\n", "IronClaw");
+        assert!(tie.allowed("/a"));
+
+        assert!(pattern_matches("/*/edit$", "/page/edit"));
+        assert!(!pattern_matches("/*/edit$", "/page/edit/x"));
+        assert!(pattern_matches("/$", "/"));
+        assert!(!pattern_matches("/$", "/x"));
+    }
+}
@@ -0,0 +1,633 @@
+//! Workspace-wide regex find and replace.
+//!
+//! `workspace_edit` rewrites every match of a pattern across memory
+//! documents, or across the files of the bound project directory, in two
+//! steps:
+//!
+//! 1. `preview` computes the edit, returns a line diff and a `preview_id`,
+//!    and refuses if the match count exceeds the limit.
+//! 2. `apply` with that `preview_id` writes exactly the previewed changes.
+//!    It needs user approval, and fails if any file changed since the
+//!    preview.
+//!
+//! Identity files loaded into the system prompt are never edited.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use regex::Regex;
+
+use crate::context::JobContext;
+use crate::tools::builtin::memory::PROTECTED_IDENTITY_FILES;
+use crate::tools::tool::{Tool, ToolError, ToolOutput};
+use crate::workspace::{ScopedOwner, Workspace};
+
+/// Default cap on matches in one edit.
+const DEFAULT_MAX_MATCHES: usize = 200;
+
+/// Hard cap on matches, whatever the caller asks for.
+const MAX_MATCHES_LIMIT: usize = 2_000;
+
+/// Changed lines shown per file in a preview.
+const MAX_DIFF_LINES_PER_FILE: usize = 20;
+
+/// Files listed in a preview diff; the rest are only counted.
+const MAX_DIFF_FILES: usize = 25;
+
+/// Previews kept before the oldest is dropped.
+const MAX_PENDING_PREVIEWS: usize = 16;
+
+/// Project files larger than this are skipped.
+const MAX_PROJECT_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Project files scanned before giving up.
+const MAX_PROJECT_FILES: usize = 5_000;
+
+/// Directories never scanned in a project.
+const SKIP_DIRS: &[&str] = &[".git", "target", "node_modules", ".venv", "__pycache__"];
+
+/// Where an edit applies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Scope {
+    /// Memory, restricted to the owner of the agent that previewed the edit.
+    Memory(Option<ScopedOwner>),
+    Project(PathBuf),
+}
+
+/// One file's previewed change.
+#[derive(Debug, Clone)]
+struct FileEdit {
+    path: String,
+    before: String,
+    after: String,
+    matches: usize,
+}
+
+/// A preview waiting to be applied.
+#[derive(Debug, Clone)]
+struct PendingEdit {
+    scope: Scope,
+    edits: Vec<FileEdit>,
+}
+
+/// Tool for regex find and replace across the workspace or project.
+pub struct WorkspaceEditTool {
+    workspace: Option<Arc<Workspace>>,
+    pending: Mutex<Vec<(String, PendingEdit)>>,
+}
+
+impl WorkspaceEditTool {
+    /// Create the tool. Without a workspace only the project scope works.
+    pub fn new(workspace: Option<Arc<Workspace>>) -> Self {
+        Self {
+            workspace,
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn store_preview(&self, edit: PendingEdit) -> String {
+        let id = format!("edit_{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        if pending.len() >= MAX_PENDING_PREVIEWS {
+            pending.remove(0);
+        }
+        pending.push((id.clone(), edit));
+        id
+    }
+
+    fn take_preview(&self, id: &str) -> Option<PendingEdit> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let pos = pending.iter().position(|(pid, _)| pid == id)?;
+        Some(pending.remove(pos).1)
+    }
+
+    fn workspace(&self, owner: Option<ScopedOwner>) -> Result<Arc<Workspace>, ToolError> {
+        let workspace = self.workspace.as_ref().ok_or_else(|| {
+            ToolError::ExecutionFailed("memory is not available (no database)".to_string())
+        })?;
+        Ok(match owner {
+            Some(owner) => Arc::new(workspace.scoped(owner)),
+            None => Arc::clone(workspace),
+        })
+    }
+
+    /// Current contents of every candidate file in scope.
+    async fn load(&self, scope: &Scope, prefix: &str) -> Result<Vec<(String, String)>, ToolError> {
+        match scope {
+            Scope::Memory(owner) => {
+                let workspace = self.workspace(*owner)?;
+                let paths = workspace
+                    .list_all()
+                    .await
+                    .map_err(|e| ToolError::ExecutionFailed(format!("List failed: {}", e)))?;
+                let mut files = Vec::new();
+                for path in paths {
+                    if !path.starts_with(prefix) || is_protected(&path) {
+                        continue;
+                    }
+                    let doc = workspace
+                        .read(&path)
+                        .await
+                        .map_err(|e| ToolError::ExecutionFailed(format!("Read failed: {}", e)))?;
+                    files.push((path, doc.content));
+                }
+                Ok(files)
+            }
+            Scope::Project(root) => {
+                let root = root.clone();
+                let prefix = prefix.to_string();
+                tokio::task::spawn_blocking(move || read_project(&root, &prefix))
+                    .await
+                    .map_err(|e| ToolError::ExecutionFailed(format!("Scan failed: {}", e)))?
+            }
+        }
+    }
+
+    async fn current(&self, scope: &Scope, path: &str) -> Result<String, ToolError> {
+        match scope {
+            Scope::Memory(owner) => self
+                .workspace(*owner)?
+                .read(path)
+                .await
+                .map(|doc| doc.content)
+                .map_err(|e| ToolError::ExecutionFailed(format!("Read failed: {}", e))),
+            Scope::Project(root) => tokio::fs::read_to_string(root.join(path))
+                .await
+                .map_err(|e| ToolError::ExecutionFailed(format!("Read failed: {}", e))),
+        }
+    }
+
+    async fn write(&self, scope: &Scope, path: &str, content: &str) -> Result<(), ToolError> {
+        match scope {
+            Scope::Memory(owner) => self
+                .workspace(*owner)?
+                .write(path, content)
+                .await
+                .map(|_| ())
+                .map_err(|e| ToolError::ExecutionFailed(format!("Write failed: {}", e))),
+            Scope::Project(root) => tokio::fs::write(root.join(path), content)
+                .await
+                .map_err(|e| ToolError::ExecutionFailed(format!("Write failed: {}", e))),
+        }
+    }
+
+    async fn preview(
+        &self,
+        params: &serde_json::Value,
+        ctx: &JobContext,
+    ) -> Result<serde_json::Value, ToolError> {
+        let pattern = str_param(params, "pattern")?;
+        let replacement = str_param(params, "replacement")?;
+        let regex = Regex::new(pattern)
+            .map_err(|e| ToolError::InvalidParameters(format!("invalid pattern: {}", e)))?;
+        let prefix = params
+            .get("path_prefix")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let max_matches = params
+            .get("max_matches")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+            .unwrap_or(DEFAULT_MAX_MATCHES)
+            .min(MAX_MATCHES_LIMIT);
+
+        let scope = match params.get("scope").and_then(|v| v.as_str()) {
+            None | Some("memory") => Scope::Memory(ctx.memory),
+            Some("project") => Scope::Project(ctx.working_dir.clone().ok_or_else(|| {
+                ToolError::InvalidParameters(
+                    "no project directory is bound (use /project <dir>)".to_string(),
+                )
+            })?),
+            Some(other) => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "unknown scope '{}', expected memory or project",
+                    other
+                )));
+            }
+        };
+
+        let mut edits = Vec::new();
+        let mut total = 0;
+        for (path, before) in self.load(&scope, prefix).await? {
+            let matches = regex.find_iter(&before).count();
+            if matches == 0 {
+                continue;
+            }
+            total += matches;
+            if total > max_matches {
+                return Err(ToolError::ExecutionFailed(format!(
+                    "more than {} matches; narrow the pattern or path_prefix, or raise max_matches",
+                    max_matches
+                )));
+            }
+            let after = regex.replace_all(&before, replacement).into_owned();
+            if after != before {
+                edits.push(FileEdit {
+                    path,
+                    before,
+                    after,
+                    matches,
+                });
+            }
+        }
+
+        if edits.is_empty() {
+            return Ok(serde_json::json!({
+                "status": "no_matches",
+                "total_matches": total,
+            }));
+        }
+
+        let diff = render_diff(&edits);
+        let files = edits.len();
+        let preview_id = self.store_preview(PendingEdit { scope, edits });
+        Ok(serde_json::json!({
+            "status": "preview",
+            "preview_id": preview_id,
+            "files": files,
+            "total_matches": total,
+            "diff": diff,
+        }))
+    }
+
+    async fn apply(&self, params: &serde_json::Value) -> Result<serde_json::Value, ToolError> {
+        let id = str_param(params, "preview_id")?;
+        let pending = self.take_preview(id).ok_or_else(|| {
+            ToolError::InvalidParameters(format!(
+                "no preview '{}' (run action=preview first; previews apply once)",
+                id
+            ))
+        })?;
+
+        // Refuse if anything moved under us, before writing a single file.
+        for edit in &pending.edits {
+            if self.current(&pending.scope, &edit.path).await? != edit.before {
+                return Err(ToolError::ExecutionFailed(format!(
+                    "{} changed since the preview; run the preview again",
+                    edit.path
+                )));
+            }
+        }
+
+        let mut paths = Vec::new();
+        let mut total = 0;
+        for edit in &pending.edits {
+            self.write(&pending.scope, &edit.path, &edit.after).await?;
+            paths.push(edit.path.clone());
+            total += edit.matches;
+        }
+
+        Ok(serde_json::json!({
+            "status": "applied",
+            "files": paths,
+            "total_matches": total,
+        }))
+    }
+}
+
+fn str_param<'a>(params: &'a serde_json::Value, name: &str) -> Result<&'a str, ToolError> {
+    params
+        .get(name)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ToolError::InvalidParameters(format!("missing '{}' parameter", name)))
+}
+
+fn is_protected(path: &str) -> bool {
+    let normalized = path.trim_start_matches('/');
+    PROTECTED_IDENTITY_FILES
+        .iter()
+        .any(|p| normalized.eq_ignore_ascii_case(p))
+}
+
+/// Text files under `root` whose relative path starts with `prefix`.
+fn read_project(root: &Path, prefix: &str) -> Result<Vec<(String, String)>, ToolError> {
+    let mut files = Vec::new();
+    let mut scanned = 0;
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|e| ToolError::ExecutionFailed(format!("Scan failed: {}", e)))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if file_type.is_dir() {
+                if !SKIP_DIRS.contains(&name.as_ref()) {
+                    dirs.push(path);
+                }
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if !relative.starts_with(prefix) {
+                continue;
+            }
+            scanned += 1;
+            if scanned > MAX_PROJECT_FILES {
+                return Err(ToolError::ExecutionFailed(format!(
+                    "more than {} files in scope; narrow path_prefix",
+                    MAX_PROJECT_FILES
+                )));
+            }
+            if entry
+                .metadata()
+                .map_or(true, |m| m.len() > MAX_PROJECT_FILE_BYTES)
+            {
+                continue;
+            }
+            // Binary and non-UTF-8 files are left alone.
+            if let Ok(content) = std::fs::read_to_string(&path) {
+                files.push((relative, content));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Changed lines per file, as `-`/`+` pairs with line numbers.
+fn render_diff(edits: &[FileEdit]) -> String {
+    let mut out = String::new();
+    for edit in edits.iter().take(MAX_DIFF_FILES) {
+        out.push_str(&format!("--- {} ({} matches)\n", edit.path, edit.matches));
+        let before: Vec<&str> = edit.before.lines().collect();
+        let after: Vec<&str> = edit.after.lines().collect();
+        let mut shown = 0;
+        let mut changed = 0;
+        if before.len() == after.len() {
+            for (i, (old, new)) in before.iter().zip(&after).enumerate() {
+                if old == new {
+                    continue;
+                }
+                changed += 1;
+                if shown < MAX_DIFF_LINES_PER_FILE {
+                    out.push_str(&format!("{:>5} - {}\n{:>5} + {}\n", i + 1, old, "", new));
+                    shown += 1;
+                }
+            }
+        } else {
+            // The replacement changed the line structure; show the whole
+            // first changed region instead of pairing lines.
+            let start = before
+                .iter()
+                .zip(&after)
+                .position(|(old, new)| old != new)
+                .unwrap_or(before.len().min(after.len()));
+            for line in before.iter().skip(start).take(MAX_DIFF_LINES_PER_FILE) {
+                out.push_str(&format!("{:>5} - {}\n", start + 1, line));
+            }
+            for line in after.iter().skip(start).take(MAX_DIFF_LINES_PER_FILE) {
+                out.push_str(&format!("{:>5} + {}\n", start + 1, line));
+            }
+            changed = before.len().max(after.len()) - start;
+            shown = changed.min(MAX_DIFF_LINES_PER_FILE);
+        }
+        if changed > shown {
+            out.push_str(&format!(
+                "      ... {} more changed lines\n",
+                changed - shown
+            ));
+        }
+    }
+    if edits.len() > MAX_DIFF_FILES {
+        out.push_str(&format!(
+            "... {} more files\n",
+            edits.len() - MAX_DIFF_FILES
+        ));
+    }
+    out
+}
+
+fn is_apply(params: &serde_json::Value) -> bool {
+    params.get("action").and_then(|v| v.as_str()) == Some("apply")
+}
+
+#[async_trait]
+impl Tool for WorkspaceEditTool {
+    fn name(&self) -> &str {
+        "workspace_edit"
+    }
+
+    fn description(&self) -> &str {
+        "Regex find and replace across memory documents (scope 'memory') or the bound \
+         project directory (scope 'project'). Always run action 'preview' first: it returns \
+         a diff and a preview_id, and refuses if there are more matches than max_matches. \
+         Then call action 'apply' with the preview_id to write exactly the previewed \
+         changes (requires user approval). Replacement supports $1 / ${name} capture groups."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["preview", "apply"],
+                    "description": "'preview' computes the diff, 'apply' writes a previewed edit"
+                },
+                "pattern": {
+                    "type": "string",
+                    "description": "Regex to find (preview)"
+                },
+                "replacement": {
+                    "type": "string",
+                    "description": "Replacement text, may reference capture groups (preview)"
+                },
+                "scope": {
+                    "type": "string",
+                    "enum": ["memory", "project"],
+                    "description": "Memory documents or the bound project directory",
+                    "default": "memory"
+                },
+                "path_prefix": {
+                    "type": "string",
+                    "description": "Only edit files whose path starts with this (e.g. 'projects/alpha/')"
+                },
+                "max_matches": {
+                    "type": "integer",
+                    "description": "Refuse the edit above this many matches",
+                    "default": DEFAULT_MAX_MATCHES
+                },
+                "preview_id": {
+                    "type": "string",
+                    "description": "Id returned by preview (apply)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        ctx: &JobContext,
+    ) -> Result<ToolOutput, ToolError> {
+        let start = std::time::Instant::now();
+        let result = match str_param(&params, "action")? {
+            "preview" => self.preview(&params, ctx).await?,
+            "apply" => self.apply(&params).await?,
+            other => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "unknown action: {}",
+                    other
+                )));
+            }
+        };
+        Ok(ToolOutput::success(result, start.elapsed()))
+    }
+
+    fn requires_approval_for(&self, params: &serde_json::Value) -> bool {
+        is_apply(params)
+    }
+
+    fn requires_sanitization(&self) -> bool {
+        false // Internal tool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project_ctx(dir: &Path) -> JobContext {
+        JobContext {
+            working_dir: Some(dir.to_path_buf()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preview_then_apply_in_project() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("a.md"),
+            "Alpha launch\nnothing\nAlpha again\n",
+        )
+        .unwrap();
+        std::fs::create_dir(dir.path().join("notes")).unwrap();
+        std::fs::write(dir.path().join("notes/b.md"), "see Alpha\n").unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        std::fs::write(dir.path().join(".git/config"), "Alpha").unwrap();
+        let ctx = project_ctx(dir.path());
+        let tool = WorkspaceEditTool::new(None);
+
+        let preview = tool
+            .execute(
+                serde_json::json!({
+                    "action": "preview",
+                    "scope": "project",
+                    "pattern": r"\bAlpha\b",
+                    "replacement": "Beta",
+                }),
+                &ctx,
+            )
+            .await
+            .unwrap()
+            .result;
+        assert_eq!(preview["files"], 2);
+        assert_eq!(preview["total_matches"], 3);
+        let diff = preview["diff"].as_str().unwrap();
+        assert!(diff.contains("--- a.md (2 matches)"));
+        assert!(diff.contains("    1 - Alpha launch\n      + Beta launch"));
+        // Nothing is written by a preview.
+        assert!(
+            std::fs::read_to_string(dir.path().join("a.md"))
+                .unwrap()
+                .starts_with("Alpha")
+        );
+
+        let apply = serde_json::json!({"action": "apply", "preview_id": preview["preview_id"]});
+        assert!(tool.requires_approval_for(&apply));
+        tool.execute(apply.clone(), &ctx).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.md")).unwrap(),
+            "Beta launch\nnothing\nBeta again\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("notes/b.md")).unwrap(),
+            "see Beta\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(".git/config")).unwrap(),
+            "Alpha"
+        );
+
+        // A preview applies once.
+        assert!(tool.execute(apply, &ctx).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_limits_and_stale_previews() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "x x x x").unwrap();
+        let ctx = project_ctx(dir.path());
+        let tool = WorkspaceEditTool::new(None);
+
+        let err = tool
+            .execute(
+                serde_json::json!({
+                    "action": "preview",
+                    "scope": "project",
+                    "pattern": "x",
+                    "replacement": "y",
+                    "max_matches": 3,
+                }),
+                &ctx,
+            )
+            .await;
+        assert!(err.unwrap_err().to_string().contains("more than 3 matches"));
+
+        let preview = tool
+            .execute(
+                serde_json::json!({
+                    "action": "preview",
+                    "scope": "project",
+                    "pattern": "x",
+                    "replacement": "y",
+                }),
+                &ctx,
+            )
+            .await
+            .unwrap()
+            .result;
+        std::fs::write(dir.path().join("a.txt"), "x x x x x").unwrap();
+        let err = tool
+            .execute(
+                serde_json::json!({"action": "apply", "preview_id": preview["preview_id"]}),
+                &ctx,
+            )
+            .await;
+        assert!(
+            err.unwrap_err()
+                .to_string()
+                .contains("changed since the preview")
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "x x x x x"
+        );
+
+        // The project scope needs a bound directory.
+        let err = tool
+            .execute(
+                serde_json::json!({
+                    "action": "preview",
+                    "scope": "project",
+                    "pattern": "x",
+                    "replacement": "y",
+                }),
+                &JobContext::default(),
+            )
+            .await;
+        assert!(err.is_err());
+    }
+}
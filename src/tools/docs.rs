@@ -0,0 +1,297 @@
+//! Human-readable tool documentation.
+//!
+//! Renders what the agent can do right now from the live tool registry:
+//! each tool's description, parameters (derived from its JSON Schema),
+//! an example call, approval/sanitization flags, and where it came from.
+
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+
+use crate::tools::tool::{Tool, ToolDomain};
+
+/// Source label for tools registered at startup.
+pub const BUILTIN_SOURCE: &str = "built-in";
+
+/// Documentation for a single parameter, flattened from the JSON Schema.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParamDoc {
+    pub name: String,
+    /// JSON Schema type (`string`, `integer`, `array<string>`, ...).
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub required: bool,
+    pub description: Option<String>,
+    pub default: Option<Value>,
+    /// Allowed values when the schema declares an `enum`.
+    pub allowed: Vec<Value>,
+}
+
+/// Documentation for a registered tool.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolDoc {
+    pub name: String,
+    pub description: String,
+    pub parameters: Vec<ParamDoc>,
+    /// Example argument object built from the schema.
+    pub example: Value,
+    pub requires_approval: bool,
+    pub requires_sanitization: bool,
+    /// `orchestrator` or `container`.
+    pub domain: &'static str,
+    /// Extension that provided the tool, or `built-in`.
+    pub source: String,
+}
+
+impl ToolDoc {
+    /// Build documentation for a tool.
+    pub fn from_tool(tool: &dyn Tool, source: Option<&str>) -> Self {
+        let schema = tool.parameters_schema();
+        Self {
+            name: tool.name().to_string(),
+            description: tool.description().to_string(),
+            parameters: params_from_schema(&schema),
+            example: example_from_schema(&schema),
+            requires_approval: tool.requires_approval(),
+            requires_sanitization: tool.requires_sanitization(),
+            domain: match tool.domain() {
+                ToolDomain::Orchestrator => "orchestrator",
+                ToolDomain::Container => "container",
+            },
+            source: source.unwrap_or(BUILTIN_SOURCE).to_string(),
+        }
+    }
+
+    /// Render as Markdown.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("## {}\n\n{}\n\n", self.name, self.description.trim());
+
+        let mut flags = vec![format!("source: {}", self.source), self.domain.to_string()];
+        if self.requires_approval {
+            flags.push("requires approval".to_string());
+        }
+        if self.requires_sanitization {
+            flags.push("output sanitized".to_string());
+        }
+        out.push_str(&format!("_{}_\n\n", flags.join(" · ")));
+
+        if self.parameters.is_empty() {
+            out.push_str("No parameters.\n");
+        } else {
+            out.push_str("Parameters:\n");
+            for p in &self.parameters {
+                out.push_str(&format!(
+                    "- `{}` ({}{})",
+                    p.name,
+                    p.ty,
+                    if p.required { ", required" } else { "" }
+                ));
+                if let Some(desc) = &p.description {
+                    out.push_str(&format!(": {}", desc));
+                }
+                if !p.allowed.is_empty() {
+                    let allowed: Vec<String> = p.allowed.iter().map(|v| v.to_string()).collect();
+                    out.push_str(&format!(" One of {}.", allowed.join(", ")));
+                }
+                if let Some(default) = &p.default {
+                    out.push_str(&format!(" Default: {}.", default));
+                }
+                out.push('\n');
+            }
+        }
+
+        if self.example.as_object().is_some_and(|o| !o.is_empty()) {
+            out.push_str(&format!(
+                "\nExample:\n```json\n{}\n```\n",
+                serde_json::to_string_pretty(&self.example).unwrap_or_default()
+            ));
+        }
+        out
+    }
+}
+
+/// Render a collection of tool docs as a single Markdown document.
+pub fn render_markdown(docs: &[ToolDoc]) -> String {
+    let mut out = format!("# Available tools ({})\n", docs.len());
+    for doc in docs {
+        out.push('\n');
+        out.push_str(&doc.to_markdown());
+    }
+    out
+}
+
+/// Flatten the top-level properties of an object schema.
+fn params_from_schema(schema: &Value) -> Vec<ParamDoc> {
+    let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) else {
+        return Vec::new();
+    };
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+
+    let mut params: Vec<ParamDoc> = properties
+        .iter()
+        .map(|(name, prop)| ParamDoc {
+            name: name.clone(),
+            ty: type_label(prop),
+            required: required.contains(&name.as_str()),
+            description: prop
+                .get("description")
+                .and_then(|d| d.as_str())
+                .map(String::from),
+            default: prop.get("default").cloned(),
+            allowed: prop
+                .get("enum")
+                .and_then(|e| e.as_array())
+                .cloned()
+                .unwrap_or_default(),
+        })
+        .collect();
+    // Required parameters first, otherwise keep schema order.
+    params.sort_by_key(|p| !p.required);
+    params
+}
+
+fn type_label(prop: &Value) -> String {
+    match prop.get("type") {
+        Some(Value::String(t)) if t == "array" => match prop.get("items") {
+            Some(items) => format!("array<{}>", type_label(items)),
+            None => "array".to_string(),
+        },
+        Some(Value::String(t)) => t.clone(),
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(|t| t.as_str())
+            .collect::<Vec<_>>()
+            .join(" | "),
+        _ => "any".to_string(),
+    }
+}
+
+/// Build an example argument object covering the required parameters.
+///
+/// Uses the schema's own `examples`/`default`/`enum` when present and a
+/// type-appropriate placeholder otherwise. Tools without required
+/// parameters get an example with every parameter.
+fn example_from_schema(schema: &Value) -> Value {
+    let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) else {
+        return json!({});
+    };
+    if let Some(example) = schema
+        .get("examples")
+        .and_then(|e| e.as_array())
+        .and_then(|e| e.first())
+    {
+        return example.clone();
+    }
+
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+
+    let mut example = Map::new();
+    for (name, prop) in properties {
+        if required.is_empty() || required.contains(&name.as_str()) {
+            example.insert(name.clone(), example_value(name, prop));
+        }
+    }
+    Value::Object(example)
+}
+
+fn example_value(name: &str, prop: &Value) -> Value {
+    if let Some(v) = prop
+        .get("examples")
+        .and_then(|e| e.as_array())
+        .and_then(|e| e.first())
+    {
+        return v.clone();
+    }
+    if let Some(v) = prop.get("default") {
+        return v.clone();
+    }
+    if let Some(v) = prop
+        .get("enum")
+        .and_then(|e| e.as_array())
+        .and_then(|e| e.first())
+    {
+        return v.clone();
+    }
+    let ty = match prop.get("type") {
+        Some(Value::String(t)) => t.as_str(),
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(|t| t.as_str())
+            .find(|t| *t != "null")
+            .unwrap_or("string"),
+        _ => "string",
+    };
+    match ty {
+        "integer" => json!(1),
+        "number" => json!(1.0),
+        "boolean" => json!(true),
+        "array" => match prop.get("items") {
+            Some(items) => json!([example_value(name, items)]),
+            None => json!([]),
+        },
+        "object" => example_from_schema(prop),
+        _ => Value::String(format!("<{}>", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::builtin::EchoTool;
+
+    fn sample_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "limit": { "type": "integer", "default": 10 },
+                "query": { "type": "string", "description": "Search text" },
+                "mode": { "type": "string", "enum": ["fast", "exact"] },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["query", "mode"]
+        })
+    }
+
+    #[test]
+    fn test_params_required_first() {
+        let params = params_from_schema(&sample_schema());
+        assert_eq!(params.len(), 4);
+        assert!(params[0].required && params[1].required);
+        let tags = params.iter().find(|p| p.name == "tags").unwrap();
+        assert_eq!(tags.ty, "array<string>");
+        let mode = params.iter().find(|p| p.name == "mode").unwrap();
+        assert_eq!(mode.allowed.len(), 2);
+    }
+
+    #[test]
+    fn test_example_covers_required_only() {
+        let example = example_from_schema(&sample_schema());
+        assert_eq!(example, json!({ "query": "<query>", "mode": "fast" }));
+    }
+
+    #[test]
+    fn test_example_without_required_uses_all() {
+        let schema = json!({
+            "type": "object",
+            "properties": { "n": { "type": "integer" }, "on": { "type": "boolean" } }
+        });
+        assert_eq!(example_from_schema(&schema), json!({ "n": 1, "on": true }));
+    }
+
+    #[test]
+    fn test_tool_doc_markdown() {
+        let doc = ToolDoc::from_tool(&EchoTool, None);
+        assert_eq!(doc.source, BUILTIN_SOURCE);
+        let md = doc.to_markdown();
+        assert!(md.starts_with("## echo"));
+        assert!(md.contains("source: built-in"));
+        assert!(md.contains("Example:"));
+    }
+}
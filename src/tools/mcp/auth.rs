@@ -2,6 +2,10 @@
 //!
 //! Implements the MCP Authorization specification using OAuth 2.1 with PKCE.
 //! See: https://spec.modelcontextprotocol.io/specification/2025-03-26/basic/authorization/
+//!
+//! Headless installs that cannot receive a browser redirect on `localhost`
+//! use the device authorization grant (RFC 8628) instead: the user enters a
+//! short code on another device while IronClaw polls the token endpoint.
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -48,6 +52,9 @@ pub enum AuthError {
     #[error("Could not bind to callback port")]
     PortUnavailable,
 
+    #[error("Server does not support the device authorization flow")]
+    DeviceFlowNotSupported,
+
     #[error("HTTP error: {0}")]
     Http(String),
 
@@ -103,6 +110,11 @@ pub struct AuthorizationServerMetadata {
     /// Scopes supported by this server.
     #[serde(default)]
     pub scopes_supported: Vec<String>,
+
+    /// Device authorization endpoint (RFC 8628), if the device-code grant is
+    /// supported.
+    #[serde(default)]
+    pub device_authorization_endpoint: Option<String>,
 }
 
 /// Dynamic Client Registration request.
@@ -111,13 +123,15 @@ pub struct ClientRegistrationRequest {
     /// Human-readable client name.
     pub client_name: String,
 
-    /// Redirect URIs for OAuth callbacks.
+    /// Redirect URIs for OAuth callbacks (empty for device-code clients).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub redirect_uris: Vec<String>,
 
     /// Grant types the client will use.
     pub grant_types: Vec<String>,
 
-    /// Response types the client will use.
+    /// Response types the client will use (empty for device-code clients).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub response_types: Vec<String>,
 
     /// Token endpoint authentication method.
@@ -258,7 +272,7 @@ impl PkceChallenge {
 pub async fn discover_protected_resource(
     server_url: &str,
 ) -> Result<ProtectedResourceMetadata, AuthError> {
-    let client = reqwest::Client::builder()
+    let client = crate::outbound::client_builder()
         .timeout(Duration::from_secs(10))
         .build()
         .map_err(|e| AuthError::Http(e.to_string()))?;
@@ -292,7 +306,7 @@ pub async fn discover_protected_resource(
 pub async fn discover_authorization_server(
     auth_server_url: &str,
 ) -> Result<AuthorizationServerMetadata, AuthError> {
-    let client = reqwest::Client::builder()
+    let client = crate::outbound::client_builder()
         .timeout(Duration::from_secs(10))
         .build()
         .map_err(|e| AuthError::Http(e.to_string()))?;
@@ -376,7 +390,7 @@ pub async fn register_client(
     registration_endpoint: &str,
     redirect_uri: &str,
 ) -> Result<ClientRegistrationResponse, AuthError> {
-    let client = reqwest::Client::builder()
+    let client = crate::outbound::client_builder()
         .timeout(Duration::from_secs(30))
         .build()
         .map_err(|e| AuthError::Http(e.to_string()))?;
@@ -428,13 +442,33 @@ pub async fn register_client(
 /// 5. Receives callback with authorization code
 /// 6. Exchanges code for access token
 /// 7. Stores token securely
+///
+/// Falls back to [`authorize_mcp_server_device`] when the local callback
+/// cannot be reached: on a headless machine, or when no callback port is free.
 pub async fn authorize_mcp_server(
     server_config: &McpServerConfig,
     secrets: &Arc<dyn SecretsStore + Send + Sync>,
     user_id: &str,
 ) -> Result<AccessToken, AuthError> {
+    let headless = is_headless();
+    if headless {
+        match authorize_mcp_server_device(server_config, secrets, user_id).await {
+            Err(AuthError::DeviceFlowNotSupported) => {
+                println!("  Server does not support device login, using the browser flow.");
+            }
+            result => return result,
+        }
+    }
+
     // Find an available port for the callback first (needed for DCR)
-    let (listener, port) = find_available_port().await?;
+    let (listener, port) = match find_available_port().await {
+        Ok(found) => found,
+        Err(_) if !headless => {
+            println!("  No local callback port is free, using device login instead.");
+            return authorize_mcp_server_device(server_config, secrets, user_id).await;
+        }
+        Err(e) => return Err(e),
+    };
     let redirect_uri = format!("http://localhost:{}/callback", port);
 
     // Determine client_id and endpoints
@@ -522,6 +556,19 @@ pub async fn authorize_mcp_server(
     Ok(token)
 }
 
+/// Whether a browser opened by the user can't reach a `localhost` callback.
+///
+/// Over SSH, or on Linux without a display, the login completes in a browser
+/// on another machine and the redirect never arrives.
+pub fn is_headless() -> bool {
+    if std::env::var_os("SSH_CONNECTION").is_some() || std::env::var_os("SSH_TTY").is_some() {
+        return true;
+    }
+    cfg!(target_os = "linux")
+        && std::env::var_os("DISPLAY").is_none()
+        && std::env::var_os("WAYLAND_DISPLAY").is_none()
+}
+
 /// Find an available port for the OAuth callback.
 pub async fn find_available_port() -> Result<(TcpListener, u16), AuthError> {
     for port in 9876..=9886 {
@@ -717,7 +764,7 @@ pub async fn exchange_code_for_token(
     redirect_uri: &str,
     pkce: Option<&PkceChallenge>,
 ) -> Result<AccessToken, AuthError> {
-    let client = reqwest::Client::builder()
+    let client = crate::outbound::client_builder()
         .timeout(Duration::from_secs(30))
         .build()
         .map_err(|e| AuthError::Http(e.to_string()))?;
@@ -762,6 +809,376 @@ pub async fn exchange_code_for_token(
     })
 }
 
+/// OAuth grant type for polling a device code (RFC 8628).
+pub const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Extra seconds added to the poll interval on `slow_down` (RFC 8628 §3.5).
+const SLOW_DOWN_INCREMENT: Duration = Duration::from_secs(5);
+
+/// Device authorization response from the authorization server.
+#[derive(Deserialize)]
+struct DeviceAuthorizationResponse {
+    device_code: String,
+    user_code: String,
+    // Some providers (e.g. Google) still use the draft name.
+    #[serde(alias = "verification_url")]
+    verification_uri: String,
+    #[serde(default)]
+    verification_uri_complete: Option<String>,
+    #[serde(default = "default_device_expires_in")]
+    expires_in: u64,
+    #[serde(default = "default_device_interval")]
+    interval: u64,
+}
+
+fn default_device_expires_in() -> u64 {
+    600
+}
+
+fn default_device_interval() -> u64 {
+    5
+}
+
+/// A device code the user has to confirm on another device.
+///
+/// The device code itself is a bearer credential until it expires, so it is
+/// kept in a `SecretString` and redacted from `Debug`.
+#[derive(Clone)]
+pub struct DeviceAuthorization {
+    /// Code used to poll the token endpoint (protected).
+    pub device_code: SecretString,
+
+    /// Short code the user types at the verification URI.
+    pub user_code: String,
+
+    /// Where the user enters the code.
+    pub verification_uri: String,
+
+    /// Verification URI with the code pre-filled, if the server offers one.
+    pub verification_uri_complete: Option<String>,
+
+    /// How long the codes stay valid.
+    pub expires_in: Duration,
+
+    /// Minimum wait between polls.
+    pub interval: Duration,
+}
+
+impl DeviceAuthorization {
+    /// URL to hand the user, preferring the one with the code pre-filled.
+    pub fn browser_url(&self) -> &str {
+        self.verification_uri_complete
+            .as_deref()
+            .unwrap_or(&self.verification_uri)
+    }
+}
+
+impl From<DeviceAuthorizationResponse> for DeviceAuthorization {
+    fn from(response: DeviceAuthorizationResponse) -> Self {
+        Self {
+            device_code: SecretString::from(response.device_code),
+            user_code: response.user_code,
+            verification_uri: response.verification_uri,
+            verification_uri_complete: response.verification_uri_complete,
+            expires_in: Duration::from_secs(response.expires_in),
+            interval: Duration::from_secs(response.interval.max(1)),
+        }
+    }
+}
+
+impl std::fmt::Debug for DeviceAuthorization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeviceAuthorization")
+            .field("device_code", &"[REDACTED]")
+            .field("user_code", &self.user_code)
+            .field("verification_uri", &self.verification_uri)
+            .field("verification_uri_complete", &self.verification_uri_complete)
+            .field("expires_in", &self.expires_in)
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+/// A started device-code flow: the code to show and where to poll for it.
+#[derive(Debug, Clone)]
+pub struct DeviceFlow {
+    pub client_id: String,
+    pub token_url: String,
+    pub authorization: DeviceAuthorization,
+    /// The client was registered dynamically, so its id must be stored for
+    /// token refresh.
+    registered: bool,
+}
+
+/// Error body returned by the token endpoint while polling.
+#[derive(Deserialize)]
+struct OAuthErrorResponse {
+    error: String,
+}
+
+/// What to do after a failed poll of the token endpoint.
+#[derive(Debug)]
+enum DevicePoll {
+    /// The user has not confirmed yet.
+    Pending,
+    /// Polling too fast; back off.
+    SlowDown,
+    /// The flow is over.
+    Failed(AuthError),
+}
+
+fn classify_device_poll_error(error: &str, status: reqwest::StatusCode) -> DevicePoll {
+    match error {
+        "authorization_pending" => DevicePoll::Pending,
+        "slow_down" => DevicePoll::SlowDown,
+        "access_denied" => DevicePoll::Failed(AuthError::AuthorizationDenied),
+        "expired_token" => DevicePoll::Failed(AuthError::Timeout),
+        "" => DevicePoll::Failed(AuthError::TokenExchangeFailed(format!("HTTP {}", status))),
+        other => DevicePoll::Failed(AuthError::TokenExchangeFailed(other.to_string())),
+    }
+}
+
+/// Register a public client for the device-code grant (no redirect URI).
+pub async fn register_device_client(
+    registration_endpoint: &str,
+) -> Result<ClientRegistrationResponse, AuthError> {
+    let client = crate::outbound::client_builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| AuthError::Http(e.to_string()))?;
+
+    let request = ClientRegistrationRequest {
+        client_name: "IronClaw".to_string(),
+        redirect_uris: Vec::new(),
+        grant_types: vec![
+            DEVICE_CODE_GRANT_TYPE.to_string(),
+            "refresh_token".to_string(),
+        ],
+        response_types: Vec::new(),
+        token_endpoint_auth_method: "none".to_string(),
+    };
+
+    let response = client
+        .post(registration_endpoint)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| AuthError::DiscoveryFailed(format!("DCR request failed: {}", e)))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(AuthError::DiscoveryFailed(format!(
+            "DCR failed: HTTP {} - {}",
+            status, body
+        )));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| AuthError::DiscoveryFailed(format!("Invalid DCR response: {}", e)))
+}
+
+/// Request a device and user code from the device authorization endpoint.
+pub async fn request_device_code(
+    device_authorization_url: &str,
+    client_id: &str,
+    scopes: &[String],
+) -> Result<DeviceAuthorization, AuthError> {
+    let client = crate::outbound::client_builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| AuthError::Http(e.to_string()))?;
+
+    let mut params = vec![("client_id", client_id.to_string())];
+    if !scopes.is_empty() {
+        params.push(("scope", scopes.join(" ")));
+    }
+
+    let response = client
+        .post(device_authorization_url)
+        .form(&params)
+        .send()
+        .await
+        .map_err(|e| AuthError::Http(e.to_string()))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(AuthError::Http(format!(
+            "Device authorization failed: HTTP {} - {}",
+            status, body
+        )));
+    }
+
+    response
+        .json::<DeviceAuthorizationResponse>()
+        .await
+        .map(DeviceAuthorization::from)
+        .map_err(|e| AuthError::Http(format!("Invalid device authorization response: {}", e)))
+}
+
+/// Start a device-code flow for an MCP server.
+///
+/// Uses the configured client and endpoints when present, otherwise
+/// discovers them and registers a client dynamically. Returns
+/// [`AuthError::DeviceFlowNotSupported`] when the server has no device
+/// authorization endpoint.
+pub async fn start_device_authorization(
+    server_config: &McpServerConfig,
+) -> Result<DeviceFlow, AuthError> {
+    let (client_id, device_url, token_url, scopes, registered) = if let Some(oauth) =
+        &server_config.oauth
+    {
+        let (device_url, token_url) = match (&oauth.device_authorization_url, &oauth.token_url) {
+            (Some(device), Some(token)) => (device.clone(), token.clone()),
+            (device, token) => {
+                let meta = discover_full_oauth_metadata(&server_config.url).await?;
+                (
+                    device
+                        .clone()
+                        .or(meta.device_authorization_endpoint)
+                        .ok_or(AuthError::DeviceFlowNotSupported)?,
+                    token.clone().unwrap_or(meta.token_endpoint),
+                )
+            }
+        };
+        (
+            oauth.client_id.clone(),
+            device_url,
+            token_url,
+            oauth.scopes.clone(),
+            false,
+        )
+    } else {
+        let meta = discover_full_oauth_metadata(&server_config.url).await?;
+        let device_url = meta
+            .device_authorization_endpoint
+            .ok_or(AuthError::DeviceFlowNotSupported)?;
+        let registration_endpoint = meta.registration_endpoint.ok_or(AuthError::NotSupported)?;
+        let registration = register_device_client(&registration_endpoint).await?;
+        (
+            registration.client_id,
+            device_url,
+            meta.token_endpoint,
+            meta.scopes_supported,
+            true,
+        )
+    };
+
+    let authorization = request_device_code(&device_url, &client_id, &scopes).await?;
+    Ok(DeviceFlow {
+        client_id,
+        token_url,
+        authorization,
+        registered,
+    })
+}
+
+/// Poll the token endpoint until the user confirms the device code, denies
+/// it, or it expires.
+pub async fn poll_device_token(
+    token_url: &str,
+    client_id: &str,
+    authorization: &DeviceAuthorization,
+) -> Result<AccessToken, AuthError> {
+    let client = crate::outbound::client_builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| AuthError::Http(e.to_string()))?;
+
+    let deadline = tokio::time::Instant::now() + authorization.expires_in;
+    let mut interval = authorization.interval;
+
+    loop {
+        tokio::time::sleep(interval).await;
+        if tokio::time::Instant::now() >= deadline {
+            return Err(AuthError::Timeout);
+        }
+
+        let params = [
+            ("grant_type", DEVICE_CODE_GRANT_TYPE),
+            ("device_code", authorization.device_code.expose_secret()),
+            ("client_id", client_id),
+        ];
+        let response = client
+            .post(token_url)
+            .form(&params)
+            .send()
+            .await
+            .map_err(|e| AuthError::TokenExchangeFailed(e.to_string()))?;
+
+        if response.status().is_success() {
+            let token_response: TokenResponse = response
+                .json()
+                .await
+                .map_err(|e| AuthError::TokenExchangeFailed(format!("Invalid response: {}", e)))?;
+            return Ok(AccessToken {
+                access_token: SecretString::from(token_response.access_token),
+                token_type: token_response.token_type,
+                expires_in: token_response.expires_in,
+                refresh_token: token_response.refresh_token.map(SecretString::from),
+                scope: token_response.scope,
+            });
+        }
+
+        // Only the `error` code is read; the body is otherwise discarded
+        // (Finding 13).
+        let status = response.status();
+        let error = response
+            .json::<OAuthErrorResponse>()
+            .await
+            .map(|e| e.error)
+            .unwrap_or_default();
+        match classify_device_poll_error(&error, status) {
+            DevicePoll::Pending => {}
+            DevicePoll::SlowDown => interval += SLOW_DOWN_INCREMENT,
+            DevicePoll::Failed(e) => return Err(e),
+        }
+    }
+}
+
+/// Wait for a started device-code flow to finish and store the tokens.
+pub async fn complete_device_authorization(
+    flow: &DeviceFlow,
+    server_config: &McpServerConfig,
+    secrets: &Arc<dyn SecretsStore + Send + Sync>,
+    user_id: &str,
+) -> Result<AccessToken, AuthError> {
+    let token = poll_device_token(&flow.token_url, &flow.client_id, &flow.authorization).await?;
+
+    store_tokens(secrets, user_id, server_config, &token).await?;
+    if flow.registered {
+        store_client_id(secrets, user_id, server_config, &flow.client_id).await?;
+    }
+
+    Ok(token)
+}
+
+/// Authorize an MCP server with the device-code flow from the CLI.
+///
+/// Prints the verification URL and user code, then polls until the user
+/// confirms on another device.
+pub async fn authorize_mcp_server_device(
+    server_config: &McpServerConfig,
+    secrets: &Arc<dyn SecretsStore + Send + Sync>,
+    user_id: &str,
+) -> Result<AccessToken, AuthError> {
+    let flow = start_device_authorization(server_config).await?;
+    let authorization = &flow.authorization;
+
+    println!(
+        "  To authorize {}, open this URL on any device:",
+        server_config.name
+    );
+    println!("  {}", authorization.verification_uri);
+    println!("  and enter the code: {}", authorization.user_code);
+    println!("  Waiting for authorization...");
+
+    complete_device_authorization(&flow, server_config, secrets, user_id).await
+}
+
 /// Store access and refresh tokens securely.
 pub async fn store_tokens(
     secrets: &Arc<dyn SecretsStore + Send + Sync>,
@@ -905,7 +1322,7 @@ pub async fn refresh_access_token(
         auth_meta.token_endpoint
     };
 
-    let client = reqwest::Client::builder()
+    let client = crate::outbound::client_builder()
         .timeout(Duration::from_secs(30))
         .build()
         .map_err(|e| AuthError::Http(e.to_string()))?;
@@ -1161,4 +1578,112 @@ mod tests {
         let result = server_handle.await.unwrap();
         assert!(matches!(result, Err(AuthError::StateMismatch)));
     }
+
+    #[test]
+    fn test_device_authorization_response_parsing() {
+        let response: DeviceAuthorizationResponse = serde_json::from_str(
+            r#"{"device_code":"dev-secret-123","user_code":"WDJB-MJHT",
+                "verification_url":"https://example.com/device","interval":0}"#,
+        )
+        .unwrap();
+        let auth = DeviceAuthorization::from(response);
+        assert_eq!(auth.user_code, "WDJB-MJHT");
+        assert_eq!(auth.browser_url(), "https://example.com/device");
+        assert_eq!(auth.expires_in, Duration::from_secs(600));
+        // A zero interval is clamped so polling never spins.
+        assert_eq!(auth.interval, Duration::from_secs(1));
+
+        let dbg = format!("{:?}", auth);
+        assert!(!dbg.contains("dev-secret-123"));
+        assert!(dbg.contains("WDJB-MJHT"));
+    }
+
+    #[test]
+    fn test_classify_device_poll_error() {
+        let status = reqwest::StatusCode::BAD_REQUEST;
+        assert!(matches!(
+            classify_device_poll_error("authorization_pending", status),
+            DevicePoll::Pending
+        ));
+        assert!(matches!(
+            classify_device_poll_error("slow_down", status),
+            DevicePoll::SlowDown
+        ));
+        assert!(matches!(
+            classify_device_poll_error("access_denied", status),
+            DevicePoll::Failed(AuthError::AuthorizationDenied)
+        ));
+        assert!(matches!(
+            classify_device_poll_error("expired_token", status),
+            DevicePoll::Failed(AuthError::Timeout)
+        ));
+        assert!(matches!(
+            classify_device_poll_error("", status),
+            DevicePoll::Failed(AuthError::TokenExchangeFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_device_client_registration_omits_redirects() {
+        let request = ClientRegistrationRequest {
+            client_name: "IronClaw".to_string(),
+            redirect_uris: Vec::new(),
+            grant_types: vec![DEVICE_CODE_GRANT_TYPE.to_string()],
+            response_types: Vec::new(),
+            token_endpoint_auth_method: "none".to_string(),
+        };
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json.get("redirect_uris").is_none());
+        assert!(json.get("response_types").is_none());
+        assert_eq!(json["grant_types"][0], DEVICE_CODE_GRANT_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_poll_device_token_waits_for_approval() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // Token endpoint: pending on the first poll, a token on the second.
+        let server_handle = tokio::spawn(async move {
+            let responses = [
+                ("400 Bad Request", r#"{"error":"authorization_pending"}"#),
+                (
+                    "200 OK",
+                    r#"{"access_token":"tok","token_type":"Bearer","expires_in":60}"#,
+                ),
+            ];
+            for (status, body) in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut buf).await;
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+                let _ = socket.shutdown().await;
+            }
+        });
+
+        let authorization = DeviceAuthorization {
+            device_code: SecretString::from("dev".to_string()),
+            user_code: "ABCD".to_string(),
+            verification_uri: "https://example.com/device".to_string(),
+            verification_uri_complete: None,
+            expires_in: Duration::from_secs(30),
+            interval: Duration::from_millis(10),
+        };
+        let token = poll_device_token(
+            &format!("http://127.0.0.1:{}/token", port),
+            "client",
+            &authorization,
+        )
+        .await
+        .unwrap();
+        assert_eq!(token.expose_access_token(), "tok");
+        server_handle.await.unwrap();
+    }
 }
@@ -65,7 +65,7 @@ impl McpClient {
         Self {
             server_url: url,
             server_name: name,
-            http_client: reqwest::Client::builder()
+            http_client: crate::outbound::client_builder()
                 .timeout(Duration::from_secs(30))
                 .build()
                 .expect("Failed to create HTTP client"),
@@ -85,7 +85,7 @@ impl McpClient {
         Self {
             server_url: server_url.into(),
             server_name: server_name.into(),
-            http_client: reqwest::Client::builder()
+            http_client: crate::outbound::client_builder()
                 .timeout(Duration::from_secs(30))
                 .build()
                 .expect("Failed to create HTTP client"),
@@ -110,7 +110,7 @@ impl McpClient {
         Self {
             server_url: config.url.clone(),
             server_name: config.name.clone(),
-            http_client: reqwest::Client::builder()
+            http_client: crate::outbound::client_builder()
                 .timeout(Duration::from_secs(30))
                 .build()
                 .expect("Failed to create HTTP client"),
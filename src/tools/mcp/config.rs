@@ -124,6 +124,12 @@ pub struct OAuthConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub token_url: Option<String>,
 
+    /// Device authorization endpoint URL, for headless installs that cannot
+    /// receive a browser callback.
+    /// If not provided, will be discovered from /.well-known/oauth-authorization-server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_authorization_url: Option<String>,
+
     /// Scopes to request.
     #[serde(default)]
     pub scopes: Vec<String>,
@@ -144,6 +150,7 @@ impl OAuthConfig {
             client_id: client_id.into(),
             authorization_url: None,
             token_url: None,
+            device_authorization_url: None,
             scopes: Vec::new(),
             use_pkce: true,
             extra_params: HashMap::new(),
@@ -9,6 +9,7 @@
 
 pub mod builder;
 pub mod builtin;
+pub mod docs;
 pub mod mcp;
 pub mod wasm;
 
@@ -21,6 +22,7 @@ pub use builder::{
     LlmSoftwareBuilder, SoftwareBuilder, SoftwareType, Template, TemplateEngine, TemplateType,
     TestCase, TestHarness, TestResult, TestSuite, ValidationError, ValidationResult, WasmValidator,
 };
+pub use docs::ToolDoc;
 pub use registry::ToolRegistry;
 pub use sandbox::ToolSandbox;
-pub use tool::{Tool, ToolDomain, ToolError, ToolOutput};
+pub use tool::{ProgressSender, Tool, ToolDomain, ToolError, ToolOutput};
@@ -1,28 +1,39 @@
 //! Tool registry for managing available tools.
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use tokio::sync::RwLock;
 
+use crate::agent::{BudgetManager, SubAgent};
+use crate::config::{
+    CalendarConfig, CodeReviewConfig, DelegateConfig, EmailConfig, GitHubAppIssuerConfig,
+    SqlConfig, WebFetchConfig,
+};
 use crate::context::ContextManager;
 use crate::db::Database;
 use crate::extensions::ExtensionManager;
 use crate::llm::{LlmProvider, ToolDefinition};
+use crate::media::{AttachmentPipeline, MediaCache};
 use crate::orchestrator::job_manager::ContainerJobManager;
 use crate::safety::SafetyLayer;
+use crate::secrets::SecretsStore;
 use crate::tools::builder::{BuildSoftwareTool, BuilderConfig, LlmSoftwareBuilder};
 use crate::tools::builtin::{
-    ApplyPatchTool, CancelJobTool, CreateJobTool, EchoTool, HttpTool, JobStatusTool, JsonTool,
-    ListDirTool, ListJobsTool, MemoryConnectTool, MemoryProfileTool, MemoryReadTool,
-    MemorySearchTool, MemorySpacesTool, MemoryTreeTool, MemoryWriteTool, ReadFileTool, ShellTool,
-    TimeTool, ToolActivateTool, ToolAuthTool, ToolInstallTool, ToolListTool, ToolRemoveTool,
-    ToolSearchTool, WriteFileTool,
+    ApplyPatchTool, AskUserTool, CalendarTool, CancelJobTool, CdpBrowser, ChartTool,
+    CodeReviewTool, CreateJobTool, DelegateTool, EchoTool, EmailAccounts, EmailReadTool,
+    EmailSendTool, GeneratePdfTool, HttpTool, JobStatusTool, JsonTool, ListDirTool, ListJobsTool,
+    MemoryConnectTool, MemoryGlossaryTool, MemoryProfileTool, MemoryReadTool, MemorySearchTool,
+    MemorySpacesTool, MemoryTreeTool, MemoryWriteTool, NotificationRoutesTool, PipelineRunner,
+    PipelineTool, ReadFileTool, ScratchpadStore, ScratchpadTool, ShellTool, SqlTool, TimeTool,
+    ToolActivateTool, ToolAuthTool, ToolInstallTool, ToolListTool, ToolOutputStore, ToolOutputTool,
+    ToolRemoveTool, ToolSearchTool, WebFetchTool, WorkspaceEditTool, WriteFileTool,
 };
+use crate::tools::docs::ToolDoc;
 use crate::tools::tool::{Tool, ToolDomain};
 use crate::tools::wasm::{
-    Capabilities, ResourceLimits, WasmError, WasmStorageError, WasmToolRuntime, WasmToolStore,
-    WasmToolWrapper,
+    Capabilities, MisbehaviorTracker, ResourceLimits, WasmError, WasmStorageError, WasmToolRuntime,
+    WasmToolStore, WasmToolWrapper,
 };
 use crate::workspace::Workspace;
 
@@ -32,8 +43,13 @@ use crate::workspace::Workspace;
 const PROTECTED_TOOL_NAMES: &[&str] = &[
     "echo",
     "time",
+    "ask_user",
     "json",
     "http",
+    "generate_pdf",
+    "generate_chart",
+    "code_review_submit",
+    "delegate",
     "shell",
     "read_file",
     "write_file",
@@ -46,6 +62,9 @@ const PROTECTED_TOOL_NAMES: &[&str] = &[
     "memory_connect",
     "memory_spaces",
     "memory_profile",
+    "memory_glossary",
+    "workspace_edit",
+    "scratchpad",
     "create_job",
     "list_jobs",
     "job_status",
@@ -62,6 +81,13 @@ const PROTECTED_TOOL_NAMES: &[&str] = &[
     "routine_update",
     "routine_delete",
     "routine_history",
+    "tool_output",
+    "pipeline",
+    "sql",
+    "email_read",
+    "email_send",
+    "calendar",
+    "web_fetch",
 ];
 
 /// Registry of available tools.
@@ -69,6 +95,12 @@ pub struct ToolRegistry {
     tools: RwLock<HashMap<String, Arc<dyn Tool>>>,
     /// Tracks which names were registered as built-in (protected from shadowing).
     builtin_names: RwLock<std::collections::HashSet<String>>,
+    /// Extension that provided each dynamically registered tool.
+    sources: RwLock<HashMap<String, String>>,
+    /// Strike counter handed to every WASM tool registered.
+    misbehavior: OnceLock<Arc<MisbehaviorTracker>>,
+    /// Store the credentials of every WASM tool registered are read from.
+    secrets: OnceLock<Arc<dyn SecretsStore + Send + Sync>>,
 }
 
 impl ToolRegistry {
@@ -77,9 +109,25 @@ impl ToolRegistry {
         Self {
             tools: RwLock::new(HashMap::new()),
             builtin_names: RwLock::new(std::collections::HashSet::new()),
+            sources: RwLock::new(HashMap::new()),
+            misbehavior: OnceLock::new(),
+            secrets: OnceLock::new(),
         }
     }
 
+    /// Count strikes against WASM tools registered from now on and refuse
+    /// to register quarantined ones. Only the first tracker set is used.
+    pub fn set_misbehavior_tracker(&self, tracker: Arc<MisbehaviorTracker>) {
+        let _ = self.misbehavior.set(tracker);
+    }
+
+    /// Inject credentials from `secrets` into the HTTP requests of WASM
+    /// tools registered from now on, for the user running each call. Only
+    /// the first store set is used.
+    pub fn set_secrets_store(&self, secrets: Arc<dyn SecretsStore + Send + Sync>) {
+        let _ = self.secrets.set(secrets);
+    }
+
     /// Register a tool. Rejects dynamic tools that try to shadow a built-in name.
     pub async fn register(&self, tool: Arc<dyn Tool>) {
         let name = tool.name().to_string();
@@ -94,6 +142,15 @@ impl ToolRegistry {
         tracing::debug!("Registered tool: {}", name);
     }
 
+    /// Register a tool provided by an extension, remembering where it came from.
+    pub async fn register_with_source(&self, tool: Arc<dyn Tool>, source: impl Into<String>) {
+        let name = tool.name().to_string();
+        self.register(tool).await;
+        if self.has(&name).await {
+            self.sources.write().await.insert(name, source.into());
+        }
+    }
+
     /// Register a tool (sync version for startup, marks as built-in).
     pub fn register_sync(&self, tool: Arc<dyn Tool>) {
         let name = tool.name().to_string();
@@ -119,6 +176,7 @@ impl ToolRegistry {
             tracing::warn!("Refused to unregister protected built-in tool: {}", name);
             return None;
         }
+        self.sources.write().await.remove(name);
         self.tools.write().await.remove(name)
     }
 
@@ -127,6 +185,12 @@ impl ToolRegistry {
         self.tools.read().await.get(name).cloned()
     }
 
+    /// Get a tool by name without waiting; `None` while the registry is
+    /// being written to. For synchronous checks like approval.
+    pub fn get_sync(&self, name: &str) -> Option<Arc<dyn Tool>> {
+        self.tools.try_read().ok()?.get(name).cloned()
+    }
+
     /// Check if a tool exists.
     pub async fn has(&self, name: &str) -> bool {
         self.tools.read().await.contains_key(name)
@@ -161,6 +225,32 @@ impl ToolRegistry {
             .collect()
     }
 
+    /// Extension that provided a tool, if it was not built in.
+    pub async fn source_of(&self, name: &str) -> Option<String> {
+        self.sources.read().await.get(name).cloned()
+    }
+
+    /// Human-readable documentation for every registered tool, sorted by name.
+    pub async fn tool_docs(&self) -> Vec<ToolDoc> {
+        let tools = self.tools.read().await;
+        let sources = self.sources.read().await;
+        let mut docs: Vec<ToolDoc> = tools
+            .values()
+            .map(|tool| {
+                ToolDoc::from_tool(tool.as_ref(), sources.get(tool.name()).map(|s| s.as_str()))
+            })
+            .collect();
+        docs.sort_by(|a, b| a.name.cmp(&b.name));
+        docs
+    }
+
+    /// Documentation for a single tool.
+    pub async fn tool_doc(&self, name: &str) -> Option<ToolDoc> {
+        let tool = self.get(name).await?;
+        let source = self.source_of(name).await;
+        Some(ToolDoc::from_tool(tool.as_ref(), source.as_deref()))
+    }
+
     /// Get tool definitions for specific tools.
     pub async fn tool_definitions_for(&self, names: &[&str]) -> Vec<ToolDefinition> {
         let tools = self.tools.read().await;
@@ -181,14 +271,26 @@ impl ToolRegistry {
         self.register_sync(Arc::new(TimeTool));
         self.register_sync(Arc::new(JsonTool));
         self.register_sync(Arc::new(HttpTool::new()));
+        self.register_sync(Arc::new(AskUserTool));
+        self.register_sync(Arc::new(GeneratePdfTool::new()));
 
         tracing::info!("Registered {} built-in tools", self.count());
     }
 
+    /// Register the built-in tools with no effect outside the process (echo,
+    /// time, json, ask_user), for demo mode.
+    pub fn register_demo_tools(&self) {
+        self.register_sync(Arc::new(EchoTool));
+        self.register_sync(Arc::new(TimeTool));
+        self.register_sync(Arc::new(JsonTool));
+        self.register_sync(Arc::new(AskUserTool));
+    }
+
     /// Register only orchestrator-domain tools (safe for the main process).
     ///
     /// This registers tools that don't touch the filesystem or run shell commands:
-    /// echo, time, json, http. Use this when `allow_local_tools = false` and
+    /// echo, time, json, http, ask_user, and generate_pdf (which only writes into
+    /// the exports directory). Use this when `allow_local_tools = false` and
     /// container-domain tools should only be available inside sandboxed containers.
     pub fn register_orchestrator_tools(&self) {
         self.register_builtin_tools();
@@ -244,9 +346,162 @@ impl ToolRegistry {
         self.register_sync(Arc::new(MemoryTreeTool::new(Arc::clone(&workspace))));
         self.register_sync(Arc::new(MemoryConnectTool::new(Arc::clone(&workspace))));
         self.register_sync(Arc::new(MemorySpacesTool::new(Arc::clone(&workspace))));
-        self.register_sync(Arc::new(MemoryProfileTool::new(workspace)));
+        self.register_sync(Arc::new(MemoryProfileTool::new(Arc::clone(&workspace))));
+        self.register_sync(Arc::new(MemoryGlossaryTool::new(Arc::clone(&workspace))));
+        self.register_sync(Arc::new(WorkspaceEditTool::new(Some(workspace))));
+
+        tracing::info!("Registered 9 memory tools");
+    }
+
+    /// Register the `tool_output` tool for reading summarized tool outputs.
+    ///
+    /// Call this when tool output summarization is enabled; the store is
+    /// shared with the summarizer that fills it.
+    pub fn register_tool_output_tool(&self, store: Arc<ToolOutputStore>) {
+        self.register_sync(Arc::new(ToolOutputTool::new(store)));
+    }
+
+    /// Register the `scratchpad` tool. The agent discards a thread's notes
+    /// from `store` when its turn ends; `workspace` enables promoting them.
+    pub fn register_scratchpad_tool(
+        &self,
+        store: Arc<ScratchpadStore>,
+        workspace: Option<Arc<Workspace>>,
+    ) {
+        self.register_sync(Arc::new(ScratchpadTool::new(store, workspace)));
+    }
+
+    /// Register the `generate_chart` tool. Rendered images land in `cache`,
+    /// where the agent picks them up for delivery to the channel.
+    pub fn register_chart_tool(&self, cache: Arc<MediaCache>) {
+        self.register_sync(Arc::new(ChartTool::new(cache)));
+    }
+
+    /// Register the `pipeline` tool with the definitions stored in the
+    /// workspace. Steps look tools up at run time, so tools registered
+    /// later (WASM, MCP) can be used too.
+    pub async fn register_pipeline_tool(
+        self: &Arc<Self>,
+        workspace: Arc<Workspace>,
+        safety: Arc<SafetyLayer>,
+    ) {
+        let runner = PipelineRunner::new(Arc::downgrade(self), safety);
+        let tool = PipelineTool::new(workspace, runner);
+        match tool.reload().await {
+            Ok(count) => tracing::info!("Registered pipeline tool ({} pipelines)", count),
+            Err(e) => tracing::warn!("Registered pipeline tool, loading pipelines failed: {}", e),
+        }
+        self.register_sync(Arc::new(tool));
+    }
+
+    /// Register the `delegate` tool. Sub-agents run on `llm`, or on `cheap`
+    /// when asked for the cheap model, and their spend counts toward
+    /// `budget`. Tools are looked up at run time, like pipeline steps.
+    pub fn register_delegate_tool(
+        self: &Arc<Self>,
+        llm: Arc<dyn LlmProvider>,
+        cheap: Option<Arc<dyn LlmProvider>>,
+        safety: Arc<SafetyLayer>,
+        budget: Option<Arc<BudgetManager>>,
+        config: DelegateConfig,
+    ) {
+        let mut agent = SubAgent::new(llm, safety, Arc::downgrade(self), config);
+        if let Some(cheap) = cheap {
+            agent = agent.with_cheap_llm(cheap);
+        }
+        if let Some(budget) = budget {
+            agent = agent.with_budget(budget);
+        }
+        self.register_sync(Arc::new(DelegateTool::new(agent)));
+        tracing::info!("Registered delegate tool");
+    }
+
+    /// Register the `sql` tool over the connections in the secrets store.
+    pub fn register_sql_tool(
+        &self,
+        secrets: Arc<dyn SecretsStore + Send + Sync>,
+        safety: Arc<SafetyLayer>,
+        config: SqlConfig,
+    ) {
+        self.register_sync(Arc::new(SqlTool::new(secrets, safety, config)));
+        tracing::info!("Registered sql tool");
+    }
+
+    /// Register `email_read` and `email_send` over the accounts in the
+    /// secrets store. Attachments are extracted by `attachments` when given.
+    pub fn register_email_tools(
+        &self,
+        secrets: Arc<dyn SecretsStore + Send + Sync>,
+        attachments: Option<Arc<AttachmentPipeline>>,
+        config: EmailConfig,
+    ) {
+        let accounts = Arc::new(EmailAccounts::new(secrets));
+        self.register_sync(Arc::new(EmailReadTool::new(
+            Arc::clone(&accounts),
+            attachments,
+            config.clone(),
+        )));
+        self.register_sync(Arc::new(EmailSendTool::new(accounts, config)));
+        tracing::info!("Registered email tools");
+    }
 
-        tracing::info!("Registered 7 memory tools");
+    /// Register the `calendar` tool over the calendars in the secrets store.
+    pub fn register_calendar_tool(
+        &self,
+        secrets: Arc<dyn SecretsStore + Send + Sync>,
+        config: CalendarConfig,
+    ) {
+        self.register_sync(Arc::new(CalendarTool::new(secrets, config)));
+        tracing::info!("Registered calendar tool");
+    }
+
+    /// Register the `code_review_submit` tool for jobs recorded in `store`.
+    /// Tokens come from `github_app` when set, otherwise from `secrets`;
+    /// `transcript_base` is the gateway URL the descriptions link to.
+    pub fn register_code_review_tool(
+        &self,
+        store: Arc<dyn Database>,
+        secrets: Option<Arc<dyn SecretsStore + Send + Sync>>,
+        github_app: Option<GitHubAppIssuerConfig>,
+        transcript_base: Option<String>,
+        config: CodeReviewConfig,
+    ) {
+        let mut tool = CodeReviewTool::new(store, config);
+        if let Some(secrets) = secrets {
+            tool = tool.with_secrets(secrets);
+        }
+        if let Some(app) = github_app {
+            tool = tool.with_github_app(app);
+        }
+        if let Some(base_url) = transcript_base {
+            tool = tool.with_transcript_base(base_url);
+        }
+        self.register_sync(Arc::new(tool));
+        tracing::info!("Registered code_review_submit tool");
+    }
+
+    /// Register the `web_fetch` tool, rendering through `browser` when given.
+    pub fn register_web_fetch_tool(
+        &self,
+        config: WebFetchConfig,
+        browser: Option<Arc<CdpBrowser>>,
+    ) {
+        let tool = WebFetchTool::new(config);
+        let tool = match browser {
+            Some(browser) => tool.with_browser(browser),
+            None => tool,
+        };
+        self.register_sync(Arc::new(tool));
+        tracing::info!("Registered web_fetch tool");
+    }
+
+    /// Register the `notification_routes` tool for editing routing rules in chat.
+    pub fn register_notification_routes_tool(
+        &self,
+        router: Arc<crate::channels::NotificationRouter>,
+        store: Option<Arc<dyn Database>>,
+    ) {
+        self.register_sync(Arc::new(NotificationRoutesTool::new(router, store)));
     }
 
     /// Register job management tools.
@@ -261,13 +516,15 @@ impl ToolRegistry {
         store: Option<Arc<dyn Database>>,
     ) {
         let mut create_tool = CreateJobTool::new(Arc::clone(&context_manager));
+        let mut cancel_tool = CancelJobTool::new(Arc::clone(&context_manager));
         if let Some(jm) = job_manager {
+            cancel_tool = cancel_tool.with_sandbox(Arc::clone(&jm), store.clone());
             create_tool = create_tool.with_sandbox(jm, store);
         }
         self.register_sync(Arc::new(create_tool));
         self.register_sync(Arc::new(ListJobsTool::new(Arc::clone(&context_manager))));
         self.register_sync(Arc::new(JobStatusTool::new(Arc::clone(&context_manager))));
-        self.register_sync(Arc::new(CancelJobTool::new(context_manager)));
+        self.register_sync(Arc::new(cancel_tool));
 
         tracing::info!("Registered 4 job management tools");
     }
@@ -365,6 +622,11 @@ impl ToolRegistry {
     /// }).await?;
     /// ```
     pub async fn register_wasm(&self, reg: WasmToolRegistration<'_>) -> Result<(), WasmError> {
+        let tracker = self.misbehavior.get();
+        if tracker.is_some_and(|tracker| tracker.is_quarantined(reg.name)) {
+            return Err(WasmError::Quarantined(reg.name.to_string()));
+        }
+
         // Prepare the module (validates and compiles)
         let prepared = reg
             .runtime
@@ -381,9 +643,18 @@ impl ToolRegistry {
         if let Some(s) = reg.schema {
             wrapper = wrapper.with_schema(s);
         }
+        if let Some(tracker) = tracker {
+            wrapper = wrapper.with_misbehavior_tracker(Arc::clone(tracker));
+        }
+        if let Some(secrets) = self.secrets.get() {
+            wrapper = wrapper.with_secrets(Arc::clone(secrets));
+        }
+
+        // Have instances ready before the first call, if the runtime keeps any
+        wrapper.warm_up();
 
         // Register the tool
-        self.register(Arc::new(wrapper)).await;
+        self.register_with_source(Arc::new(wrapper), reg.name).await;
 
         tracing::info!(name = reg.name, "Registered WASM tool");
         Ok(())
@@ -527,6 +798,21 @@ mod tests {
         assert_eq!(defs[0].name, "echo");
     }
 
+    #[tokio::test]
+    async fn test_tool_docs_record_source() {
+        let registry = ToolRegistry::new();
+        registry
+            .register_with_source(Arc::new(EchoTool), "my-extension")
+            .await;
+
+        let doc = registry.tool_doc("echo").await.unwrap();
+        assert_eq!(doc.source, "my-extension");
+        assert_eq!(registry.tool_docs().await.len(), 1);
+
+        registry.unregister("echo").await;
+        assert!(registry.source_of("echo").await.is_none());
+    }
+
     #[tokio::test]
     async fn test_builtin_tool_cannot_be_shadowed() {
         let registry = ToolRegistry::new();
@@ -576,4 +862,190 @@ mod tests {
         assert_eq!(desc, original_desc);
         assert_ne!(desc, "EVIL SHADOW");
     }
+
+    /// A `near:agent` component whose `execute` sends `GET
+    /// https://127.0.0.1/x` and returns the host's error, or `"ok"`.
+    const FETCH_COMPONENT: &str = r#"
+(component
+  (import "near:agent/host" (instance $host
+    (type $response-t (record
+      (field "status" u16) (field "headers-json" string) (field "body" (list u8))))
+    (export "http-response" (type $response (eq $response-t)))
+    (export "http-request" (func
+      (param "method" string) (param "url" string) (param "headers-json" string)
+      (param "body" (option (list u8))) (param "timeout-ms" (option u32))
+      (result (result $response (error string)))))))
+  (core module $mem
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 4096))
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr (global.get $heap))
+      (global.set $heap (i32.add (global.get $heap) (local.get 3)))
+      (local.get $ptr)))
+  (core instance $mem_i (instantiate $mem))
+  (core func $http
+    (canon lower (func $host "http-request")
+      (memory $mem_i "memory") (realloc (func $mem_i "realloc"))))
+  (core module $m
+    (import "host" "memory" (memory 1))
+    (import "host" "http" (func $http
+      (param i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32)))
+    (data (i32.const 16) "Fetches a URL")
+    (data (i32.const 64) "{}")
+    (data (i32.const 80) "GET")
+    (data (i32.const 96) "https://127.0.0.1/x")
+    (data (i32.const 128) "{}")
+    (data (i32.const 144) "\"ok\"")
+    (func (export "description") (result i32)
+      (i32.store (i32.const 512) (i32.const 16))
+      (i32.store (i32.const 516) (i32.const 13))
+      (i32.const 512))
+    (func (export "schema") (result i32)
+      (i32.store (i32.const 520) (i32.const 64))
+      (i32.store (i32.const 524) (i32.const 2))
+      (i32.const 520))
+    (func (export "execute") (param i32 i32 i32 i32 i32) (result i32)
+      (call $http
+        (i32.const 80) (i32.const 3) (i32.const 96) (i32.const 19)
+        (i32.const 128) (i32.const 2) (i32.const 0) (i32.const 0) (i32.const 0)
+        (i32.const 0) (i32.const 0) (i32.const 1024))
+      (if (i32.load8_u (i32.const 1024))
+        (then
+          (i32.store8 (i32.const 2048) (i32.const 0))
+          (i32.store8 (i32.const 2060) (i32.const 1))
+          (i32.store (i32.const 2064) (i32.load (i32.const 1028)))
+          (i32.store (i32.const 2068) (i32.load (i32.const 1032))))
+        (else
+          (i32.store8 (i32.const 2048) (i32.const 1))
+          (i32.store (i32.const 2052) (i32.const 144))
+          (i32.store (i32.const 2056) (i32.const 4))
+          (i32.store8 (i32.const 2060) (i32.const 0))))
+      (i32.const 2048)))
+  (core instance $host_i
+    (export "memory" (memory $mem_i "memory"))
+    (export "http" (func $http)))
+  (core instance $i (instantiate $m (with "host" (instance $host_i))))
+  (type $request (record (field "params" string) (field "context" (option string))))
+  (type $response (record (field "output" (option string)) (field "error" (option string))))
+  (func $execute (param "req" $request) (result $response)
+    (canon lift (core func $i "execute") (memory $mem_i "memory")
+      (realloc (func $mem_i "realloc"))))
+  (func $schema (result string)
+    (canon lift (core func $i "schema") (memory $mem_i "memory")))
+  (func $description (result string)
+    (canon lift (core func $i "description") (memory $mem_i "memory")))
+  (component $shim
+    (type $request (record (field "params" string) (field "context" (option string))))
+    (type $response (record (field "output" (option string)) (field "error" (option string))))
+    (import "import-type-request" (type $request-i (eq $request)))
+    (import "import-type-response" (type $response-i (eq $response)))
+    (import "import-func-execute" (func $execute (param "req" $request-i) (result $response-i)))
+    (import "import-func-schema" (func $schema (result string)))
+    (import "import-func-description" (func $description (result string)))
+    (export $request-e "request" (type $request-i))
+    (export $response-e "response" (type $response-i))
+    (export "execute" (func $execute) (func (param "req" $request-e) (result $response-e)))
+    (export "schema" (func $schema))
+    (export "description" (func $description)))
+  (instance $tool (instantiate $shim
+    (with "import-type-request" (type $request))
+    (with "import-type-response" (type $response))
+    (with "import-func-execute" (func $execute))
+    (with "import-func-schema" (func $schema))
+    (with "import-func-description" (func $description))))
+  (export "near:agent/tool" (instance $tool))
+)
+"#;
+
+    #[tokio::test]
+    async fn test_wasm_tools_get_granted_credentials_for_the_caller() {
+        use secrecy::SecretString;
+
+        use crate::context::JobContext;
+        use crate::secrets::{
+            CreateSecretParams, CredentialMapping, InMemorySecretsStore, SecretsCrypto,
+        };
+        use crate::tools::wasm::{EndpointPattern, HttpCapability, WasmRuntimeConfig};
+
+        let key = "0123456789abcdef0123456789abcdef";
+        let crypto = Arc::new(SecretsCrypto::new(SecretString::from(key.to_string())).unwrap());
+        let secrets = Arc::new(InMemorySecretsStore::new(crypto));
+        secrets
+            .create(
+                "alice",
+                CreateSecretParams::new("fetch_key", "sk-alice-secret"),
+            )
+            .await
+            .unwrap();
+        secrets
+            .create("bob", CreateSecretParams::new("fetch_key", "sk-bob-secret"))
+            .await
+            .unwrap();
+        // Alice granted her key to the tool; Bob only to another extension.
+        secrets
+            .grant("alice", "fetch_key", "fetcher")
+            .await
+            .unwrap();
+        secrets.grant("bob", "fetch_key", "other").await.unwrap();
+
+        let registry = ToolRegistry::new();
+        registry.set_secrets_store(Arc::clone(&secrets) as Arc<dyn SecretsStore + Send + Sync>);
+        let runtime = Arc::new(WasmToolRuntime::new(WasmRuntimeConfig::for_testing()).unwrap());
+        let http = HttpCapability::new(vec![EndpointPattern::host("127.0.0.1")])
+            .with_credential("fetch", CredentialMapping::bearer("fetch_key", "127.0.0.1"));
+        registry
+            .register_wasm(WasmToolRegistration {
+                name: "fetcher",
+                wasm_bytes: FETCH_COMPONENT.as_bytes(),
+                runtime: &runtime,
+                capabilities: Capabilities::default().with_http(http),
+                limits: None,
+                description: None,
+                schema: None,
+            })
+            .await
+            .unwrap();
+        let tool = registry.get("fetcher").await.unwrap();
+
+        let run = |user: &str| {
+            let tool = Arc::clone(&tool);
+            let ctx = JobContext::with_user(user, "t", "d");
+            async move {
+                tool.execute(serde_json::json!({}), &ctx)
+                    .await
+                    .unwrap_err()
+                    .to_string()
+            }
+        };
+
+        // Alice's key is injected; the request then stops at the private-IP
+        // check, so it gets past injection.
+        let err = run("alice").await;
+        assert!(err.contains("private/internal IP"), "{}", err);
+        assert!(!err.contains("sk-alice-secret"));
+
+        // Bob's key is outside the tool's namespace.
+        let err = run("bob").await;
+        assert!(err.contains("Credential injection failed"), "{}", err);
+        assert!(err.contains("access denied"), "{}", err);
+
+        // Carol has no key at all; nobody else's is used.
+        let err = run("carol").await;
+        assert!(err.contains("Secret not found"), "{}", err);
+
+        // Once Alice shares her key with Carol's group, Carol's calls use
+        // it, and each use is audited under Carol's name.
+        secrets
+            .share_with_group("alice", "fetch_key", "ops")
+            .await
+            .unwrap();
+        secrets.add_group_member("ops", "carol").await.unwrap();
+        let err = run("carol").await;
+        assert!(err.contains("private/internal IP"), "{}", err);
+        let uses = secrets.shared_uses(10).await.unwrap();
+        assert_eq!(uses.len(), 1);
+        assert_eq!(uses[0].acting_user, "carol");
+        assert_eq!(uses[0].tool, "fetcher");
+    }
 }
@@ -123,6 +123,11 @@ impl ToolSchema {
     }
 }
 
+/// Receives partial results from a running tool, see
+/// [`Tool::execute_streaming`]. The agent streams them to the channel as
+/// text, except `{"status": "..."}`, which is shown as a status line.
+pub type ProgressSender = tokio::sync::mpsc::UnboundedSender<serde_json::Value>;
+
 /// Trait for tools that the agent can use.
 #[async_trait]
 pub trait Tool: Send + Sync {
@@ -142,6 +147,20 @@ pub trait Tool: Send + Sync {
         ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError>;
 
+    /// Execute the tool, pushing partial results to `progress` while it runs.
+    ///
+    /// The final result is still returned as from [`Tool::execute`], which
+    /// the default implementation calls without emitting anything.
+    async fn execute_streaming(
+        &self,
+        params: serde_json::Value,
+        ctx: &JobContext,
+        progress: ProgressSender,
+    ) -> Result<ToolOutput, ToolError> {
+        drop(progress);
+        self.execute(params, ctx).await
+    }
+
     /// Estimate the cost of running this tool with the given parameters.
     fn estimated_cost(&self, _params: &serde_json::Value) -> Option<Decimal> {
         None
@@ -172,6 +191,32 @@ pub trait Tool: Send + Sync {
         false
     }
 
+    /// Whether this particular call requires approval.
+    ///
+    /// Defaults to [`Tool::requires_approval`]. Override for tools where only
+    /// some operations are destructive, e.g. applying an edit but not
+    /// previewing it.
+    fn requires_approval_for(&self, _params: &serde_json::Value) -> bool {
+        self.requires_approval()
+    }
+
+    /// Whether this call must be approved by the user every time, even
+    /// when a remembered approval would cover it.
+    ///
+    /// Every dispatcher honors this: the agent loop always prompts, and
+    /// callers that can't ask (pipeline steps, sub-agents, background
+    /// jobs) refuse the call.
+    fn requires_explicit_approval(&self, _params: &serde_json::Value) -> bool {
+        false
+    }
+
+    /// What the user is shown when asked to approve this call; the tool's
+    /// description when `None`. Override when the parameters alone don't
+    /// show what the call will do, e.g. running a stored pipeline by name.
+    fn approval_details(&self, _params: &serde_json::Value) -> Option<String> {
+        None
+    }
+
     /// Maximum time this tool is allowed to run before the caller kills it.
     /// Override for long-running tools like sandbox execution.
     /// Default: 60 seconds.
@@ -152,6 +152,14 @@ impl AllowlistValidator {
         }
     }
 
+    /// The first pattern that allows this request, if any.
+    pub fn matching_pattern(&self, url: &str, method: &str) -> Option<&EndpointPattern> {
+        let parsed = parse_url(url).ok()?;
+        self.patterns
+            .iter()
+            .find(|p| p.matches(&parsed.host, &parsed.path, method))
+    }
+
     /// Check if any pattern would allow this host.
     pub fn host_allowed(&self, host: &str) -> bool {
         self.patterns.iter().any(|p| p.host_matches(host))
@@ -9,6 +9,7 @@
 //! - **HTTP**: Make HTTP requests to allowlisted endpoints
 //! - **ToolInvoke**: Call other tools via aliases
 //! - **Secrets**: Check if secrets exist (never read values)
+//! - **Progress**: Push partial results to the channel while running
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -32,6 +33,8 @@ pub struct Capabilities {
     pub tool_invoke: Option<ToolInvokeCapability>,
     /// Check if secrets exist.
     pub secrets: Option<SecretsCapability>,
+    /// Push partial results while running.
+    pub progress: Option<ProgressCapability>,
 }
 
 impl Capabilities {
@@ -71,6 +74,12 @@ impl Capabilities {
         });
         self
     }
+
+    /// Enable progress emission with the given limits.
+    pub fn with_progress(mut self, progress: ProgressCapability) -> Self {
+        self.progress = Some(progress);
+        self
+    }
 }
 
 /// Workspace read capability configuration.
@@ -111,6 +120,10 @@ pub struct HttpCapability {
     pub max_request_bytes: usize,
     /// Maximum response body size in bytes.
     pub max_response_bytes: usize,
+    /// Accepted response content types (empty = any).
+    pub allowed_content_types: Vec<String>,
+    /// Redirects the host follows before failing (0 = return them to the tool).
+    pub max_redirects: u32,
     /// Request timeout.
     pub timeout: Duration,
 }
@@ -123,6 +136,8 @@ impl Default for HttpCapability {
             rate_limit: RateLimitConfig::default(),
             max_request_bytes: 1024 * 1024,       // 1 MB
             max_response_bytes: 10 * 1024 * 1024, // 10 MB
+            allowed_content_types: Vec::new(),
+            max_redirects: 0,
             timeout: Duration::from_secs(30),
         }
     }
@@ -166,6 +181,18 @@ impl HttpCapability {
         self.max_response_bytes = bytes;
         self
     }
+
+    /// Restrict response content types (e.g. `application/json`, `text/*`).
+    pub fn with_allowed_content_types(mut self, types: Vec<String>) -> Self {
+        self.allowed_content_types = types;
+        self
+    }
+
+    /// Follow up to `count` redirects.
+    pub fn with_max_redirects(mut self, count: u32) -> Self {
+        self.max_redirects = count;
+        self
+    }
 }
 
 /// Pattern for matching allowed HTTP endpoints.
@@ -177,6 +204,16 @@ pub struct EndpointPattern {
     pub path_prefix: Option<String>,
     /// Allowed HTTP methods (empty = all methods allowed).
     pub methods: Vec<String>,
+    /// Response size limit for this endpoint (overrides the capability's).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_response_bytes: Option<usize>,
+    /// Accepted response content types for this endpoint (empty = the
+    /// capability's list).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_content_types: Vec<String>,
+    /// Redirects followed for this endpoint (overrides the capability's).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_redirects: Option<u32>,
 }
 
 impl EndpointPattern {
@@ -186,6 +223,9 @@ impl EndpointPattern {
             host: host.into(),
             path_prefix: None,
             methods: Vec::new(),
+            max_response_bytes: None,
+            allowed_content_types: Vec::new(),
+            max_redirects: None,
         }
     }
 
@@ -201,6 +241,24 @@ impl EndpointPattern {
         self
     }
 
+    /// Cap response bodies from this endpoint.
+    pub fn with_max_response_bytes(mut self, bytes: usize) -> Self {
+        self.max_response_bytes = Some(bytes);
+        self
+    }
+
+    /// Restrict response content types from this endpoint.
+    pub fn with_allowed_content_types(mut self, types: Vec<String>) -> Self {
+        self.allowed_content_types = types;
+        self
+    }
+
+    /// Follow up to `count` redirects from this endpoint.
+    pub fn with_max_redirects(mut self, count: u32) -> Self {
+        self.max_redirects = Some(count);
+        self
+    }
+
     /// Check if this pattern matches a URL and method.
     pub fn matches(&self, url_host: &str, url_path: &str, method: &str) -> bool {
         // Check host
@@ -302,6 +360,27 @@ impl SecretsCapability {
     }
 }
 
+/// Progress capability: push partial results to the channel while running.
+#[derive(Debug, Clone)]
+pub struct ProgressCapability {
+    /// Maximum emissions per execution.
+    pub max_emissions: u32,
+    /// Minimum time between two emissions.
+    pub min_interval: Duration,
+    /// Largest emission in bytes.
+    pub max_bytes: usize,
+}
+
+impl Default for ProgressCapability {
+    fn default() -> Self {
+        Self {
+            max_emissions: 100,
+            min_interval: Duration::from_millis(250),
+            max_bytes: 4096,
+        }
+    }
+}
+
 /// Rate limiting configuration.
 #[derive(Debug, Clone)]
 pub struct RateLimitConfig {
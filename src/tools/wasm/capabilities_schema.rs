@@ -34,8 +34,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::secrets::{CredentialLocation, CredentialMapping};
 use crate::tools::wasm::{
-    Capabilities, EndpointPattern, HttpCapability, RateLimitConfig, SecretsCapability,
-    ToolInvokeCapability, WorkspaceCapability,
+    Capabilities, EndpointPattern, HttpCapability, ProgressCapability, RateLimitConfig,
+    SecretsCapability, ToolInvokeCapability, WorkspaceCapability,
 };
 
 /// Root schema for a capabilities JSON file.
@@ -57,10 +57,19 @@ pub struct CapabilitiesFile {
     #[serde(default)]
     pub workspace: Option<WorkspaceCapabilitySchema>,
 
+    /// Partial results pushed to the channel while running.
+    #[serde(default)]
+    pub progress: Option<ProgressCapabilitySchema>,
+
     /// Authentication setup instructions.
     /// Used by `ironclaw config` to guide users through auth setup.
     #[serde(default)]
     pub auth: Option<AuthCapabilitySchema>,
+
+    /// Run the tool on a cron schedule. Installing the tool registers a
+    /// routine for it (see [`crate::tools::wasm::sync_tool_schedule`]).
+    #[serde(default)]
+    pub schedule: Option<ScheduleCapabilitySchema>,
 }
 
 impl CapabilitiesFile {
@@ -74,6 +83,30 @@ impl CapabilitiesFile {
         serde_json::from_slice(bytes)
     }
 
+    /// Secrets this tool declares by exact name: its auth secret, the
+    /// secrets its HTTP credentials inject, and `secrets.allowed_names`
+    /// entries without a `*`. Sorted and deduplicated.
+    pub fn declared_secrets(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .auth
+            .iter()
+            .map(|a| a.secret_name.clone())
+            .chain(
+                self.http
+                    .iter()
+                    .flat_map(|h| h.credentials.values().map(|c| c.secret_name.clone())),
+            )
+            .chain(
+                self.secrets
+                    .iter()
+                    .flat_map(|s| s.allowed_names.iter().filter(|n| !n.contains('*')).cloned()),
+            )
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
     /// Convert to runtime Capabilities.
     pub fn to_capabilities(&self) -> Capabilities {
         let mut caps = Capabilities::default();
@@ -106,6 +139,14 @@ impl CapabilitiesFile {
             });
         }
 
+        if let Some(progress) = &self.progress {
+            caps.progress = Some(ProgressCapability {
+                max_emissions: progress.max_emissions,
+                min_interval: Duration::from_millis(progress.min_interval_ms),
+                max_bytes: progress.max_bytes,
+            });
+        }
+
         caps
     }
 }
@@ -133,6 +174,14 @@ pub struct HttpCapabilitySchema {
     #[serde(default)]
     pub max_response_bytes: Option<usize>,
 
+    /// Accepted response content types (e.g. "application/json", "text/*").
+    #[serde(default)]
+    pub allowed_content_types: Vec<String>,
+
+    /// Redirects to follow before failing (default 0: returned to the tool).
+    #[serde(default)]
+    pub max_redirects: Option<u32>,
+
     /// Request timeout in seconds.
     #[serde(default)]
     pub timeout_secs: Option<u64>,
@@ -156,6 +205,7 @@ impl HttpCapabilitySchema {
                 .as_ref()
                 .map(|r| r.to_rate_limit_config())
                 .unwrap_or_default(),
+            allowed_content_types: self.allowed_content_types.clone(),
             ..Default::default()
         };
 
@@ -165,6 +215,9 @@ impl HttpCapabilitySchema {
         if let Some(max) = self.max_response_bytes {
             cap.max_response_bytes = max;
         }
+        if let Some(count) = self.max_redirects {
+            cap.max_redirects = count;
+        }
         if let Some(secs) = self.timeout_secs {
             cap.timeout = Duration::from_secs(secs);
         }
@@ -186,6 +239,18 @@ pub struct EndpointPatternSchema {
     /// Allowed HTTP methods (empty = all).
     #[serde(default)]
     pub methods: Vec<String>,
+
+    /// Response size limit for this endpoint.
+    #[serde(default)]
+    pub max_response_bytes: Option<usize>,
+
+    /// Accepted response content types for this endpoint.
+    #[serde(default)]
+    pub allowed_content_types: Vec<String>,
+
+    /// Redirects to follow for this endpoint.
+    #[serde(default)]
+    pub max_redirects: Option<u32>,
 }
 
 impl EndpointPatternSchema {
@@ -194,6 +259,9 @@ impl EndpointPatternSchema {
             host: self.host.clone(),
             path_prefix: self.path_prefix.clone(),
             methods: self.methods.clone(),
+            max_response_bytes: self.max_response_bytes,
+            allowed_content_types: self.allowed_content_types.clone(),
+            max_redirects: self.max_redirects,
         }
     }
 }
@@ -326,6 +394,75 @@ pub struct WorkspaceCapabilitySchema {
     pub allowed_prefixes: Vec<String>,
 }
 
+/// Progress capability schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressCapabilitySchema {
+    /// Maximum emissions per execution.
+    #[serde(default = "default_max_emissions")]
+    pub max_emissions: u32,
+
+    /// Minimum milliseconds between two emissions.
+    #[serde(default = "default_min_interval_ms")]
+    pub min_interval_ms: u64,
+
+    /// Largest emission in bytes.
+    #[serde(default = "default_max_progress_bytes")]
+    pub max_bytes: usize,
+}
+
+/// Schedule capability schema.
+///
+/// ```json
+/// {
+///   "schedule": {
+///     "cron": "0 0 7 * * *",
+///     "entrypoint": "sync",
+///     "params": { "since": "24h" },
+///     "timezone": "Europe/Berlin"
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleCapabilitySchema {
+    /// Cron expression (`0 0 7 * * *`) or interval (`every 2h`).
+    pub cron: String,
+
+    /// Operation to run, passed to the tool as its `action` parameter.
+    pub entrypoint: String,
+
+    /// Further parameters for the scheduled call.
+    #[serde(default)]
+    pub params: serde_json::Map<String, serde_json::Value>,
+
+    /// IANA time zone the schedule is evaluated in (UTC when unset).
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+impl ScheduleCapabilitySchema {
+    /// Parameters for each scheduled call: `params` plus `action`.
+    pub fn call_params(&self) -> serde_json::Value {
+        let mut params = self.params.clone();
+        params.insert(
+            "action".to_string(),
+            serde_json::Value::String(self.entrypoint.clone()),
+        );
+        serde_json::Value::Object(params)
+    }
+}
+
+fn default_max_emissions() -> u32 {
+    100
+}
+
+fn default_min_interval_ms() -> u64 {
+    250
+}
+
+fn default_max_progress_bytes() -> usize {
+    4096
+}
+
 /// Authentication setup schema.
 ///
 /// Tools declare their auth requirements here. The agent uses this to provide
@@ -520,6 +657,39 @@ mod tests {
         assert_eq!(http.allowlist[0].methods, vec!["GET", "POST"]);
     }
 
+    #[test]
+    fn test_parse_endpoint_response_limits() {
+        let json = r#"{
+            "http": {
+                "allowlist": [
+                    {
+                        "host": "api.example.com",
+                        "max_response_bytes": 65536,
+                        "allowed_content_types": ["application/json"],
+                        "max_redirects": 3
+                    },
+                    { "host": "cdn.example.com" }
+                ],
+                "allowed_content_types": ["text/*"]
+            }
+        }"#;
+
+        let http = CapabilitiesFile::from_json(json)
+            .unwrap()
+            .to_capabilities()
+            .http
+            .unwrap();
+        assert_eq!(http.allowlist[0].max_response_bytes, Some(65536));
+        assert_eq!(
+            http.allowlist[0].allowed_content_types,
+            ["application/json"]
+        );
+        assert_eq!(http.allowlist[0].max_redirects, Some(3));
+        assert_eq!(http.allowlist[1].max_response_bytes, None);
+        assert_eq!(http.allowed_content_types, ["text/*"]);
+        assert_eq!(http.max_redirects, 0);
+    }
+
     #[test]
     fn test_parse_credentials() {
         let json = r#"{
@@ -544,6 +714,29 @@ mod tests {
         assert_eq!(cred.host_patterns, vec!["slack.com", "*.slack.com"]);
     }
 
+    #[test]
+    fn test_declared_secrets() {
+        let json = r#"{
+            "http": {
+                "credentials": {
+                    "slack": {
+                        "secret_name": "slack_bot_token",
+                        "location": { "type": "bearer" },
+                        "host_patterns": ["slack.com"]
+                    }
+                }
+            },
+            "secrets": { "allowed_names": ["slack_*", "slack_team_id"] },
+            "auth": { "secret_name": "slack_bot_token" }
+        }"#;
+
+        let caps = CapabilitiesFile::from_json(json).unwrap();
+        assert_eq!(
+            caps.declared_secrets(),
+            vec!["slack_bot_token", "slack_team_id"]
+        );
+    }
+
     #[test]
     fn test_parse_custom_header_credential() {
         let json = r#"{
@@ -651,6 +844,19 @@ mod tests {
         assert_eq!(workspace.allowed_prefixes, vec!["context/", "daily/"]);
     }
 
+    #[test]
+    fn test_parse_progress() {
+        use std::time::Duration;
+
+        let json = r#"{ "progress": { "max_emissions": 10 } }"#;
+
+        let caps = CapabilitiesFile::from_json(json).unwrap().to_capabilities();
+        let progress = caps.progress.unwrap();
+        assert_eq!(progress.max_emissions, 10);
+        assert_eq!(progress.min_interval, Duration::from_millis(250));
+        assert_eq!(progress.max_bytes, 4096);
+    }
+
     #[test]
     fn test_to_capabilities() {
         let json = r#"{
@@ -754,4 +960,24 @@ mod tests {
         assert!(auth.display_name.is_none());
         assert!(auth.setup_url.is_none());
     }
+
+    #[test]
+    fn test_parse_schedule_capability() {
+        let json = r#"{
+            "schedule": {
+                "cron": "0 0 7 * * *",
+                "entrypoint": "sync",
+                "params": { "since": "24h" }
+            }
+        }"#;
+
+        let caps = CapabilitiesFile::from_json(json).unwrap();
+        let schedule = caps.schedule.unwrap();
+        assert_eq!(schedule.cron, "0 0 7 * * *");
+        assert!(schedule.timezone.is_none());
+        assert_eq!(
+            schedule.call_params(),
+            serde_json::json!({"action": "sync", "since": "24h"})
+        );
+    }
 }
@@ -10,17 +10,29 @@
 //!                                                        │
 //!                                    ┌───────────────────┘
 //!                                    ▼
+//!                        Check allowed_secrets, resolve the
+//!                        owner (self or a secret group) and
+//!                        check the extension's grant (namespace)
+//!                                    │
+//!                                    ▼
 //!                        Decrypt secret from store
 //!                                    │
 //!                                    ▼
 //!                        Inject into request:
 //!                        ├─► Authorization header (Bearer/Basic)
 //!                        ├─► Custom header (X-API-Key, etc.)
-//!                        └─► Query parameter
+//!                        ├─► Query parameter
+//!                        └─► URL placeholder
 //!                                    │
 //!                                    ▼
 //!                        Execute HTTP request
 //! ```
+//!
+//! Every injector belongs to one extension. A secret is only injected if the
+//! store holds a grant for that extension, so a tool that names another
+//! tool's secret in its capabilities still cannot obtain it. A user with no
+//! copy of a secret gets one shared with their secret group, provided the
+//! owner granted it to the same extension; each such use is audited.
 
 use std::collections::HashMap;
 
@@ -66,6 +78,8 @@ pub struct InjectedCredentials {
     pub headers: HashMap<String, String>,
     /// Query parameters to add.
     pub query_params: HashMap<String, String>,
+    /// URL placeholders (e.g. `{TELEGRAM_BOT_TOKEN}`) and their values.
+    pub placeholders: HashMap<String, String>,
 }
 
 impl InjectedCredentials {
@@ -73,29 +87,50 @@ impl InjectedCredentials {
         Self {
             headers: HashMap::new(),
             query_params: HashMap::new(),
+            placeholders: HashMap::new(),
         }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.headers.is_empty() && self.query_params.is_empty()
+        self.headers.is_empty() && self.query_params.is_empty() && self.placeholders.is_empty()
+    }
+
+    /// Every injected secret value, for redacting them from error text.
+    pub fn values(&self) -> impl Iterator<Item = &str> {
+        self.headers
+            .values()
+            .chain(self.query_params.values())
+            .chain(self.placeholders.values())
+            .map(String::as_str)
     }
 }
 
-/// Injects credentials into HTTP requests.
+/// Injects credentials into HTTP requests on behalf of one extension.
 pub struct CredentialInjector {
+    extension: String,
     mappings: HashMap<String, CredentialMapping>,
     allowed_secrets: Vec<String>,
 }
 
 impl CredentialInjector {
-    /// Create a new injector with the given mappings.
-    pub fn new(mappings: HashMap<String, CredentialMapping>, allowed_secrets: Vec<String>) -> Self {
+    /// Create an injector for `extension` with the given mappings.
+    pub fn new(
+        extension: impl Into<String>,
+        mappings: HashMap<String, CredentialMapping>,
+        allowed_secrets: Vec<String>,
+    ) -> Self {
         Self {
+            extension: extension.into(),
             mappings,
             allowed_secrets,
         }
     }
 
+    /// The extension whose secret namespace this injector reads.
+    pub fn extension(&self) -> &str {
+        &self.extension
+    }
+
     /// Find credentials that should be injected for a given host.
     pub fn find_credentials_for_host(&self, host: &str) -> Vec<&CredentialMapping> {
         self.mappings
@@ -133,9 +168,33 @@ impl CredentialInjector {
                 return Err(InjectionError::AccessDenied(mapping.secret_name.clone()));
             }
 
-            // Get the decrypted secret
+            // A member of a secret group may be using a secret shared by
+            // another user; grants belong to the secret's owner.
+            let source =
+                store
+                    .resolve(user_id, &mapping.secret_name)
+                    .await
+                    .map_err(|e| match e {
+                        SecretError::NotFound(name) => InjectionError::SecretNotFound(name),
+                        _ => InjectionError::DecryptionFailed(e.to_string()),
+                    })?;
+
+            // Check the secret is in this extension's namespace
+            if !store
+                .is_granted(&source.owner, &mapping.secret_name, &self.extension)
+                .await?
+            {
+                tracing::warn!(
+                    extension = %self.extension,
+                    secret = %mapping.secret_name,
+                    "Blocked credential injection for a secret outside the extension's namespace"
+                );
+                return Err(InjectionError::AccessDenied(mapping.secret_name.clone()));
+            }
+
+            // Get the decrypted secret, auditing the use if it is shared
             let secret = store
-                .get_decrypted(user_id, &mapping.secret_name)
+                .get_for_tool(user_id, &mapping.secret_name, &self.extension)
                 .await
                 .map_err(|e| match e {
                     SecretError::NotFound(name) => InjectionError::SecretNotFound(name),
@@ -200,9 +259,11 @@ fn inject_credential(
                 .query_params
                 .insert(name.clone(), secret.expose().to_string());
         }
-        CredentialLocation::UrlPath { .. } => {
-            // URL placeholder replacement is handled by channel/tool wrappers
-            // that substitute {PLACEHOLDER} values in templated strings.
+        CredentialLocation::UrlPath { placeholder } => {
+            // The caller substitutes the placeholder in the request URL.
+            result
+                .placeholders
+                .insert(placeholder.clone(), secret.expose().to_string());
         }
     }
 }
@@ -272,7 +333,7 @@ mod tests {
         SecretsCrypto, SecretsStore,
     };
     use crate::tools::wasm::credential_injector::{
-        CredentialInjector, base64_encode, host_matches_pattern,
+        CredentialInjector, InjectionError, base64_encode, host_matches_pattern,
     };
 
     fn test_store() -> InMemorySecretsStore {
@@ -307,6 +368,10 @@ mod tests {
             .create("user1", CreateSecretParams::new("openai_key", "sk-test123"))
             .await
             .unwrap();
+        store
+            .grant("user1", "openai_key", "test_tool")
+            .await
+            .unwrap();
 
         let mut mappings = HashMap::new();
         mappings.insert(
@@ -318,7 +383,8 @@ mod tests {
             },
         );
 
-        let injector = CredentialInjector::new(mappings, vec!["openai_key".to_string()]);
+        let injector =
+            CredentialInjector::new("test_tool", mappings, vec!["openai_key".to_string()]);
         let result = injector
             .inject("user1", "api.openai.com", &store)
             .await
@@ -337,6 +403,7 @@ mod tests {
             .create("user1", CreateSecretParams::new("api_key", "secret123"))
             .await
             .unwrap();
+        store.grant("user1", "api_key", "test_tool").await.unwrap();
 
         let mut mappings = HashMap::new();
         mappings.insert(
@@ -351,7 +418,7 @@ mod tests {
             },
         );
 
-        let injector = CredentialInjector::new(mappings, vec!["api_key".to_string()]);
+        let injector = CredentialInjector::new("test_tool", mappings, vec!["api_key".to_string()]);
         let result = injector
             .inject("user1", "api.example.com", &store)
             .await
@@ -370,6 +437,7 @@ mod tests {
             .create("user1", CreateSecretParams::new("password", "mypassword"))
             .await
             .unwrap();
+        store.grant("user1", "password", "test_tool").await.unwrap();
 
         let mut mappings = HashMap::new();
         mappings.insert(
@@ -383,7 +451,7 @@ mod tests {
             },
         );
 
-        let injector = CredentialInjector::new(mappings, vec!["password".to_string()]);
+        let injector = CredentialInjector::new("test_tool", mappings, vec!["password".to_string()]);
         let result = injector
             .inject("user1", "api.service.com", &store)
             .await
@@ -398,7 +466,7 @@ mod tests {
     async fn test_no_credentials_for_host() {
         let store = test_store();
 
-        let injector = CredentialInjector::new(HashMap::new(), vec![]);
+        let injector = CredentialInjector::new("test_tool", HashMap::new(), vec![]);
         let result = injector
             .inject("user1", "unknown.com", &store)
             .await
@@ -426,9 +494,100 @@ mod tests {
         );
 
         // Empty allowed list = nothing allowed
-        let injector = CredentialInjector::new(mappings, vec![]);
+        let injector = CredentialInjector::new("test_tool", mappings, vec![]);
         let result = injector.inject("user1", "api.test.com", &store).await;
 
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_secret_outside_namespace_is_denied() {
+        let store = test_store();
+        store
+            .create(
+                "user1",
+                CreateSecretParams::new("slack_bot_token", "xoxb-1"),
+            )
+            .await
+            .unwrap();
+        store
+            .grant("user1", "slack_bot_token", "slack")
+            .await
+            .unwrap();
+
+        let mappings = || {
+            HashMap::from([(
+                "slack_bot_token".to_string(),
+                CredentialMapping::bearer("slack_bot_token", "slack.com"),
+            )])
+        };
+        let allowed = vec!["slack_bot_token".to_string()];
+
+        // Another tool naming the same secret in its capabilities gets nothing.
+        let other = CredentialInjector::new("evil_tool", mappings(), allowed.clone());
+        assert!(matches!(
+            other.inject("user1", "slack.com", &store).await,
+            Err(InjectionError::AccessDenied(name)) if name == "slack_bot_token"
+        ));
+
+        let owner = CredentialInjector::new("slack", mappings(), allowed);
+        let result = owner.inject("user1", "slack.com", &store).await.unwrap();
+        assert_eq!(
+            result.headers.get("Authorization"),
+            Some(&"Bearer xoxb-1".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_group_member_gets_shared_secret() {
+        let store = test_store();
+        store
+            .create("alice", CreateSecretParams::new("pagerduty_token", "pd-1"))
+            .await
+            .unwrap();
+        store
+            .grant("alice", "pagerduty_token", "pagerduty")
+            .await
+            .unwrap();
+        store
+            .share_with_group("alice", "pagerduty_token", "ops")
+            .await
+            .unwrap();
+
+        let mappings = || {
+            HashMap::from([(
+                "pagerduty_token".to_string(),
+                CredentialMapping::bearer("pagerduty_token", "api.pagerduty.com"),
+            )])
+        };
+        let allowed = vec!["pagerduty_token".to_string()];
+        let injector = CredentialInjector::new("pagerduty", mappings(), allowed.clone());
+
+        // Not a member yet.
+        assert!(matches!(
+            injector.inject("bob", "api.pagerduty.com", &store).await,
+            Err(InjectionError::SecretNotFound(_))
+        ));
+
+        store.add_group_member("ops", "bob").await.unwrap();
+        let result = injector
+            .inject("bob", "api.pagerduty.com", &store)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.headers.get("Authorization"),
+            Some(&"Bearer pd-1".to_string())
+        );
+        let uses = store.shared_uses(10).await.unwrap();
+        assert_eq!(uses.len(), 1);
+        assert_eq!(uses[0].acting_user, "bob");
+        assert_eq!(uses[0].tool, "pagerduty");
+
+        // The owner's grant is per extension, for members too.
+        let other = CredentialInjector::new("evil_tool", mappings(), allowed);
+        assert!(matches!(
+            other.inject("bob", "api.pagerduty.com", &store).await,
+            Err(InjectionError::AccessDenied(_))
+        ));
+    }
 }
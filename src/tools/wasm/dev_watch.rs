@@ -0,0 +1,291 @@
+//! Hot reload for WASM tools under development.
+//!
+//! `ironclaw tool dev <dir>` and `WASM_DEV_WATCH` watch a tool crate's
+//! build output (`target/wasm32-wasip*/<profile>/<crate>.wasm`) and its
+//! capabilities file. When either changes and then stays quiet for
+//! [`SETTLE`] (cargo writes the artifact in several steps), the
+//! capabilities are parsed again, the module is prepared from the new bytes
+//! and the registry entry is swapped. The agent keeps running throughout.
+//!
+//! A rebuild that fails validation leaves the previous version registered;
+//! the error is reported as a [`DevReload::Failed`] so it can be shown
+//! where the developer is looking.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+use crate::tools::wasm::{WasmLoadError, WasmToolLoader};
+
+/// Quiet period after the last change before a reload.
+pub const SETTLE: Duration = Duration::from_millis(300);
+
+/// Target triples cargo-component builds for, newest first.
+const WASM_TARGETS: &[&str] = &["wasm32-wasip2", "wasm32-wasip1"];
+
+/// The files of one tool being developed.
+#[derive(Debug, Clone)]
+pub struct DevWatchTarget {
+    /// Name the tool is registered under.
+    pub name: String,
+    /// Build artifact to load.
+    pub wasm_path: PathBuf,
+    /// Capabilities sidecar; the tool gets none while it does not exist.
+    pub capabilities_path: PathBuf,
+}
+
+impl DevWatchTarget {
+    /// Resolve the files for the tool crate in `source_dir`. The name
+    /// defaults to the crate name; the artifact need not be built yet.
+    pub fn from_source_dir(
+        source_dir: &Path,
+        name: Option<&str>,
+        release: bool,
+    ) -> Result<Self, WasmLoadError> {
+        let cargo_toml = source_dir.join("Cargo.toml");
+        let manifest = std::fs::read_to_string(&cargo_toml)?;
+        let crate_name = package_name(&manifest)
+            .ok_or_else(|| WasmLoadError::InvalidName(cargo_toml.display().to_string()))?;
+        let name = name.unwrap_or(&crate_name).to_string();
+
+        let profile = if release { "release" } else { "debug" };
+        let artifact = format!("{}.wasm", crate_name.replace('-', "_"));
+        let candidates: Vec<PathBuf> = WASM_TARGETS
+            .iter()
+            .map(|target| {
+                source_dir
+                    .join("target")
+                    .join(target)
+                    .join(profile)
+                    .join(&artifact)
+            })
+            .collect();
+        let wasm_path = candidates
+            .iter()
+            .find(|path| path.exists())
+            .unwrap_or(&candidates[0])
+            .clone();
+
+        let capabilities_path = [
+            source_dir.join(format!("{}.capabilities.json", name)),
+            source_dir.join("capabilities.json"),
+        ]
+        .into_iter()
+        .find(|path| path.exists())
+        .unwrap_or_else(|| source_dir.join(format!("{}.capabilities.json", name)));
+
+        Ok(Self {
+            name,
+            wasm_path,
+            capabilities_path,
+        })
+    }
+
+    /// Whether a change to `path` calls for a reload.
+    pub fn is_relevant(&self, path: &Path) -> bool {
+        path == self.wasm_path || path == self.capabilities_path
+    }
+
+    /// Load the current build, replacing the registered version.
+    pub async fn reload(&self, loader: &WasmToolLoader) -> DevReload {
+        let started = Instant::now();
+        match loader
+            .reload_from_files(
+                &self.name,
+                &self.wasm_path,
+                Some(self.capabilities_path.as_path()),
+            )
+            .await
+        {
+            Ok(()) => DevReload::Reloaded {
+                name: self.name.clone(),
+                took: started.elapsed(),
+            },
+            Err(e) => DevReload::Failed {
+                name: self.name.clone(),
+                error: e.to_string(),
+            },
+        }
+    }
+}
+
+/// Outcome of one reload.
+#[derive(Debug, Clone)]
+pub enum DevReload {
+    /// The new build is registered.
+    Reloaded { name: String, took: Duration },
+    /// The new build was rejected; the previous one is still registered.
+    Failed { name: String, error: String },
+}
+
+impl std::fmt::Display for DevReload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Reloaded { name, took } => {
+                write!(f, "Reloaded {} in {}ms", name, took.as_millis())
+            }
+            Self::Failed { name, error } => {
+                write!(
+                    f,
+                    "Reload of {} failed, keeping the previous build: {}",
+                    name, error
+                )
+            }
+        }
+    }
+}
+
+/// Watch `target` and reload it through `loader` after every change.
+/// Outcomes are sent on the returned channel; the task ends when the
+/// receiver is dropped.
+pub fn spawn_dev_watch(
+    loader: Arc<WasmToolLoader>,
+    target: DevWatchTarget,
+) -> Result<
+    (
+        tokio::task::JoinHandle<()>,
+        mpsc::UnboundedReceiver<DevReload>,
+    ),
+    WasmLoadError,
+> {
+    let (change_tx, mut change_rx) = mpsc::unbounded_channel();
+    let relevant = target.clone();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        let Ok(event) = res else { return };
+        if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+            && event.paths.iter().any(|path| relevant.is_relevant(path))
+        {
+            let _ = change_tx.send(());
+        }
+    })
+    .map_err(watch_error)?;
+
+    // Watch the directories, not the files: the artifact is replaced
+    // rather than rewritten and may not exist before the first build.
+    let mut dirs: Vec<&Path> = [&target.wasm_path, &target.capabilities_path]
+        .into_iter()
+        .filter_map(|path| path.parent())
+        .collect();
+    dirs.dedup();
+    for dir in dirs {
+        std::fs::create_dir_all(dir)?;
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(watch_error)?;
+    }
+
+    let (reload_tx, reload_rx) = mpsc::unbounded_channel();
+    let handle = tokio::spawn(async move {
+        let _watcher: RecommendedWatcher = watcher;
+        let mut dirty = false;
+        loop {
+            let change = if dirty {
+                tokio::time::timeout(SETTLE, change_rx.recv()).await
+            } else {
+                Ok(change_rx.recv().await)
+            };
+            match change {
+                Ok(Some(())) => dirty = true,
+                Ok(None) => break,
+                Err(_) => {
+                    dirty = false;
+                    if reload_tx.send(target.reload(&loader).await).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+    Ok((handle, reload_rx))
+}
+
+fn watch_error(e: notify::Error) -> WasmLoadError {
+    WasmLoadError::Io(std::io::Error::other(e))
+}
+
+/// `name` from the `[package]` table of a Cargo manifest.
+fn package_name(manifest: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in manifest.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if in_package
+            && let Some((key, value)) = line.split_once('=')
+            && key.trim() == "name"
+        {
+            return Some(
+                value
+                    .trim()
+                    .trim_matches('"')
+                    .trim_matches('\'')
+                    .to_string(),
+            );
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::ToolRegistry;
+    use crate::tools::wasm::world::tests::ECHO_COMPONENT;
+    use crate::tools::wasm::{WasmRuntimeConfig, WasmToolRuntime};
+
+    fn tool_crate(dir: &Path) {
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"echo-tool\"\nversion = \"0.1.0\"\n\n[lib]\nname = \"ignored\"\n",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_target_from_source_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        tool_crate(dir.path());
+
+        let target = DevWatchTarget::from_source_dir(dir.path(), None, true).unwrap();
+        assert_eq!(target.name, "echo-tool");
+        assert_eq!(
+            target.wasm_path,
+            dir.path()
+                .join("target/wasm32-wasip2/release/echo_tool.wasm")
+        );
+        assert!(target.is_relevant(&dir.path().join("echo-tool.capabilities.json")));
+        assert!(!target.is_relevant(&dir.path().join("src/lib.rs")));
+
+        let debug = DevWatchTarget::from_source_dir(dir.path(), Some("echo"), false).unwrap();
+        assert_eq!(debug.name, "echo");
+        assert!(debug.wasm_path.ends_with("debug/echo_tool.wasm"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_reload_keeps_previous_build() {
+        let dir = tempfile::tempdir().unwrap();
+        tool_crate(dir.path());
+        let target = DevWatchTarget::from_source_dir(dir.path(), Some("echo"), true).unwrap();
+        std::fs::create_dir_all(target.wasm_path.parent().unwrap()).unwrap();
+
+        let runtime = Arc::new(WasmToolRuntime::new(WasmRuntimeConfig::for_testing()).unwrap());
+        let registry = Arc::new(ToolRegistry::new());
+        let loader = WasmToolLoader::new(runtime, Arc::clone(&registry));
+
+        std::fs::write(&target.wasm_path, ECHO_COMPONENT).unwrap();
+        assert!(matches!(
+            target.reload(&loader).await,
+            DevReload::Reloaded { .. }
+        ));
+        assert!(registry.has("echo").await);
+
+        std::fs::write(&target.capabilities_path, "{ not json").unwrap();
+        let outcome = target.reload(&loader).await;
+        assert!(matches!(outcome, DevReload::Failed { .. }));
+        assert!(outcome.to_string().contains("Invalid capabilities"));
+        assert!(registry.has("echo").await);
+    }
+}
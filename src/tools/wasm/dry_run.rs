@@ -0,0 +1,117 @@
+//! Dry-run reports for auditing a WASM tool before activation.
+//!
+//! A dry run executes the tool once with every side-effecting host function
+//! replaced by a recorder:
+//!
+//! | Host function    | Dry-run behaviour                                     |
+//! |------------------|-------------------------------------------------------|
+//! | `http-request`   | recorded, answered with `200 {}`, nothing is sent     |
+//! | `secret-exists`  | recorded, answers `true`                              |
+//! | `workspace-read` | recorded, answers "not found"                         |
+//! | `tool-invoke`    | recorded, answers with an error                       |
+//!
+//! Each attempt notes whether the tool's capabilities would have allowed it,
+//! so the report shows both what the tool reaches for and what it asks for
+//! without using. No credentials are injected: URLs keep their
+//! `{PLACEHOLDER}`s.
+
+use serde::Serialize;
+
+/// An HTTP request the tool tried to make.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HttpAttempt {
+    pub method: String,
+    pub url: String,
+    /// Whether the HTTP allowlist would have let it through.
+    pub allowed: bool,
+}
+
+/// A secret, workspace path or tool alias the tool asked about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Attempt {
+    pub target: String,
+    /// Whether the tool's capabilities cover it.
+    pub allowed: bool,
+}
+
+/// Everything a tool tried to touch during a dry run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DryRunReport {
+    pub http: Vec<HttpAttempt>,
+    pub secrets: Vec<Attempt>,
+    pub workspace: Vec<Attempt>,
+    pub tool_invocations: Vec<Attempt>,
+    /// Log lines the tool emitted, as `LEVEL message`.
+    pub logs: Vec<String>,
+    /// The tool's output, if it finished.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Why the run stopped early, if it did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DryRunReport {
+    /// Whether the tool tried anything its capabilities would refuse.
+    pub fn has_denied(&self) -> bool {
+        self.http.iter().any(|a| !a.allowed)
+            || [&self.secrets, &self.workspace, &self.tool_invocations]
+                .iter()
+                .any(|attempts| attempts.iter().any(|a| !a.allowed))
+    }
+
+    /// Human-readable summary, one line per attempt.
+    pub fn summary(&self) -> String {
+        let mark = |allowed: bool| if allowed { "" } else { " (not allowed)" };
+        let mut lines = Vec::new();
+        for a in &self.http {
+            lines.push(format!("http: {} {}{}", a.method, a.url, mark(a.allowed)));
+        }
+        for (label, attempts) in [
+            ("secret", &self.secrets),
+            ("workspace", &self.workspace),
+            ("tool", &self.tool_invocations),
+        ] {
+            for a in attempts {
+                lines.push(format!("{}: {}{}", label, a.target, mark(a.allowed)));
+            }
+        }
+        if lines.is_empty() {
+            lines.push("no network, secret, workspace or tool access".to_string());
+        }
+        if let Some(ref error) = self.error {
+            lines.push(format!("stopped: {}", error));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_marks_denied_attempts() {
+        let report = DryRunReport {
+            http: vec![HttpAttempt {
+                method: "GET".into(),
+                url: "https://evil.example.net/x".into(),
+                allowed: false,
+            }],
+            secrets: vec![Attempt {
+                target: "slack_bot_token".into(),
+                allowed: true,
+            }],
+            ..Default::default()
+        };
+        assert!(report.has_denied());
+        assert_eq!(
+            report.summary(),
+            "http: GET https://evil.example.net/x (not allowed)\nsecret: slack_bot_token"
+        );
+        assert_eq!(
+            DryRunReport::default().summary(),
+            "no network, secret, workspace or tool access"
+        );
+    }
+}
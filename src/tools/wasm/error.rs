@@ -78,6 +78,10 @@ pub enum WasmError {
     /// Path traversal attempt blocked.
     #[error("Path traversal blocked: {0}")]
     PathTraversalBlocked(String),
+
+    /// Tool was quarantined for repeated misbehavior.
+    #[error("Tool '{0}' is quarantined; run `ironclaw tool unquarantine {0}` to review it")]
+    Quarantined(String),
 }
 
 impl From<std::io::Error> for WasmError {
@@ -22,7 +22,7 @@
 //!                          (sanitized, no secrets)
 //! ```
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 use crate::tools::wasm::capabilities::Capabilities;
 use crate::tools::wasm::error::WasmError;
@@ -82,6 +82,10 @@ pub struct HostState {
     http_request_count: u32,
     /// Tool invoke count for rate limiting within this execution.
     tool_invoke_count: u32,
+    /// Progress emissions accepted within this execution.
+    progress_count: u32,
+    /// When the last progress emission was accepted.
+    last_progress: Option<Instant>,
 }
 
 impl std::fmt::Debug for HostState {
@@ -93,6 +97,7 @@ impl std::fmt::Debug for HostState {
             .field("user_id", &self.user_id)
             .field("http_request_count", &self.http_request_count)
             .field("tool_invoke_count", &self.tool_invoke_count)
+            .field("progress_count", &self.progress_count)
             .finish()
     }
 }
@@ -108,6 +113,8 @@ impl HostState {
             user_id: None,
             http_request_count: 0,
             tool_invoke_count: 0,
+            progress_count: 0,
+            last_progress: None,
         }
     }
 
@@ -121,6 +128,8 @@ impl HostState {
             user_id: Some(user_id.into()),
             http_request_count: 0,
             tool_invoke_count: 0,
+            progress_count: 0,
+            last_progress: None,
         }
     }
 
@@ -223,6 +232,20 @@ impl HostState {
         }
     }
 
+    /// Whether `workspace_read` would serve `path` (ignoring whether a
+    /// reader is configured).
+    pub fn workspace_read_allowed(&self, path: &str) -> bool {
+        let Some(capability) = &self.capabilities.workspace_read else {
+            return false;
+        };
+        validate_workspace_path(path).is_ok()
+            && (capability.allowed_prefixes.is_empty()
+                || capability
+                    .allowed_prefixes
+                    .iter()
+                    .any(|prefix| path.starts_with(prefix)))
+    }
+
     /// Get collected logs after execution.
     pub fn take_logs(&mut self) -> Vec<LogEntry> {
         std::mem::take(&mut self.logs)
@@ -353,6 +376,45 @@ impl HostState {
         Ok(())
     }
 
+    /// Check a progress emission of `len` bytes against the progress
+    /// capability and count it.
+    ///
+    /// Returns error if the capability is missing, the emission is too large,
+    /// or it comes too soon after the last one or past the per-execution cap.
+    pub fn record_progress(&mut self, len: usize) -> Result<(), String> {
+        let capability = self
+            .capabilities
+            .progress
+            .as_ref()
+            .ok_or_else(|| "Progress capability not granted".to_string())?;
+
+        if len > capability.max_bytes {
+            return Err(format!(
+                "Progress too large ({} bytes, max {})",
+                len, capability.max_bytes
+            ));
+        }
+        if self.progress_count >= capability.max_emissions {
+            return Err(format!(
+                "Too many progress emissions in single execution (max {})",
+                capability.max_emissions
+            ));
+        }
+        let now = Instant::now();
+        if let Some(last) = self.last_progress
+            && now.duration_since(last) < capability.min_interval
+        {
+            return Err(format!(
+                "Progress emitted too often (min interval {}ms)",
+                capability.min_interval.as_millis()
+            ));
+        }
+
+        self.progress_count += 1;
+        self.last_progress = Some(now);
+        Ok(())
+    }
+
     /// Get HTTP request count for this execution.
     pub fn http_request_count(&self) -> u32 {
         self.http_request_count
@@ -404,7 +466,7 @@ mod tests {
     use std::sync::Arc;
 
     use crate::tools::wasm::capabilities::{
-        Capabilities, SecretsCapability, WorkspaceCapability, WorkspaceReader,
+        Capabilities, ProgressCapability, SecretsCapability, WorkspaceCapability, WorkspaceReader,
     };
     use crate::tools::wasm::host::{
         HostState, LogLevel, MAX_LOG_ENTRIES, MAX_LOG_MESSAGE_BYTES, validate_workspace_path,
@@ -522,6 +584,11 @@ mod tests {
         // Disallowed prefix
         let result = state.workspace_read("secrets/api_key.txt").unwrap();
         assert!(result.is_none());
+
+        assert!(state.workspace_read_allowed("context/test.md"));
+        assert!(!state.workspace_read_allowed("secrets/api_key.txt"));
+        assert!(!state.workspace_read_allowed("context/../secrets"));
+        assert!(!HostState::minimal().workspace_read_allowed("context/test.md"));
     }
 
     #[test]
@@ -625,4 +692,25 @@ mod tests {
         let state = HostState::new_with_user(Capabilities::default(), "user123");
         assert_eq!(state.user_id(), Some("user123"));
     }
+
+    #[test]
+    fn test_progress_limits() {
+        let mut state = HostState::minimal();
+        assert!(state.record_progress(10).is_err(), "capability required");
+
+        let mut state = HostState::new(Capabilities::none().with_progress(ProgressCapability {
+            max_emissions: 2,
+            min_interval: std::time::Duration::ZERO,
+            max_bytes: 16,
+        }));
+        assert!(state.record_progress(17).is_err());
+        assert!(state.record_progress(16).is_ok());
+        assert!(state.record_progress(1).is_ok());
+        assert!(state.record_progress(1).is_err());
+
+        let mut state =
+            HostState::new(Capabilities::none().with_progress(ProgressCapability::default()));
+        assert!(state.record_progress(1).is_ok());
+        assert!(state.record_progress(1).is_err(), "within min interval");
+    }
 }
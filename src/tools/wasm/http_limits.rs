@@ -0,0 +1,323 @@
+//! Per-endpoint response limits for the `http-request` host function.
+//!
+//! An allowlist entry in `capabilities.json` can tighten what its endpoint
+//! may send back:
+//!
+//! ```json
+//! {
+//!   "host": "api.example.com",
+//!   "path_prefix": "/v1/",
+//!   "max_response_bytes": 262144,
+//!   "allowed_content_types": ["application/json", "text/*"],
+//!   "max_redirects": 2
+//! }
+//! ```
+//!
+//! Fields left out fall back to the `http` capability's values. A response
+//! that breaks a limit never reaches the tool; it gets an [`HttpLimitError`]
+//! serialized as JSON instead, so it can tell the cases apart.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::tools::wasm::allowlist::AllowlistValidator;
+use crate::tools::wasm::capabilities::HttpCapability;
+
+/// Response cap used when no HTTP capability is present (10 MB).
+const DEFAULT_MAX_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
+
+/// The limits applying to one request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseLimits {
+    /// Largest response body accepted.
+    pub max_response_bytes: usize,
+    /// Accepted content types (empty = any).
+    pub allowed_content_types: Vec<String>,
+    /// Redirects the host follows (0 = hand them to the tool).
+    pub max_redirects: u32,
+}
+
+impl Default for ResponseLimits {
+    fn default() -> Self {
+        Self {
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            allowed_content_types: Vec::new(),
+            max_redirects: 0,
+        }
+    }
+}
+
+impl ResponseLimits {
+    /// Limits for `method url`: the matching allowlist entry's values,
+    /// falling back to the capability's.
+    pub fn resolve(capability: Option<&HttpCapability>, url: &str, method: &str) -> Self {
+        let Some(cap) = capability else {
+            return Self::default();
+        };
+        let validator = AllowlistValidator::new(cap.allowlist.clone());
+        let pattern = validator.matching_pattern(url, method);
+
+        Self {
+            max_response_bytes: pattern
+                .and_then(|p| p.max_response_bytes)
+                .unwrap_or(cap.max_response_bytes),
+            allowed_content_types: match pattern {
+                Some(p) if !p.allowed_content_types.is_empty() => p.allowed_content_types.clone(),
+                _ => cap.allowed_content_types.clone(),
+            },
+            max_redirects: pattern
+                .and_then(|p| p.max_redirects)
+                .unwrap_or(cap.max_redirects),
+        }
+    }
+
+    /// Check a response's `Content-Type` header against the allowed types.
+    pub fn check_content_type(&self, content_type: Option<&str>) -> Result<(), HttpLimitError> {
+        if self.allowed_content_types.is_empty() {
+            return Ok(());
+        }
+        let essence = content_type.map(|ct| {
+            ct.split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase()
+        });
+        if let Some(ref essence) = essence
+            && self
+                .allowed_content_types
+                .iter()
+                .any(|allowed| content_type_matches(allowed, essence))
+        {
+            return Ok(());
+        }
+        Err(HttpLimitError::ContentTypeNotAllowed {
+            content_type: essence,
+            allowed: self.allowed_content_types.clone(),
+        })
+    }
+
+    /// Check a declared body length against the size limit.
+    pub fn check_length(&self, length: u64) -> Result<(), HttpLimitError> {
+        if length > self.max_response_bytes as u64 {
+            return Err(HttpLimitError::ResponseTooLarge {
+                limit: self.max_response_bytes,
+                size: Some(length),
+            });
+        }
+        Ok(())
+    }
+
+    /// Read `response`'s body, giving up as soon as it passes the size
+    /// limit rather than after buffering all of it.
+    pub async fn read_body(&self, mut response: reqwest::Response) -> Result<Vec<u8>, String> {
+        if let Some(length) = response.content_length() {
+            self.check_length(length).map_err(|e| e.to_string())?;
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Failed to read response body: {}", e))?
+        {
+            if body.len() + chunk.len() > self.max_response_bytes {
+                return Err(HttpLimitError::ResponseTooLarge {
+                    limit: self.max_response_bytes,
+                    size: None,
+                }
+                .to_string());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+}
+
+/// Whether `essence` (e.g. `application/json`) matches an allowed entry,
+/// which may be exact, `type/*` or `*/*`.
+fn content_type_matches(allowed: &str, essence: &str) -> bool {
+    let allowed = allowed.trim().to_ascii_lowercase();
+    if allowed == "*/*" || allowed == "*" {
+        return true;
+    }
+    match allowed.strip_suffix("/*") {
+        Some(top) => essence.split('/').next() == Some(top),
+        None => allowed == essence,
+    }
+}
+
+/// Resolve a redirect's `Location` against the URL that was requested.
+pub fn redirect_target(url: &str, location: &str) -> Option<String> {
+    reqwest::Url::parse(url)
+        .ok()?
+        .join(location)
+        .ok()
+        .map(String::from)
+}
+
+/// Whether the request after a redirect with `status` becomes a body-less
+/// GET: always for 303, and for 301/302 after a POST, as browsers do.
+pub fn redirect_becomes_get(method: &str, status: u16) -> bool {
+    status == 303 || (matches!(status, 301 | 302) && method.eq_ignore_ascii_case("POST"))
+}
+
+/// Why a response was refused. Its `Display` is a JSON object with an
+/// `error` code, the fields below and a human-readable `message`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "error", rename_all = "snake_case")]
+pub enum HttpLimitError {
+    /// The body is larger than the endpoint allows (`size` is `None` when
+    /// the server sent no Content-Length and the read was cut off).
+    ResponseTooLarge { limit: usize, size: Option<u64> },
+    /// The response's content type is not in the endpoint's list.
+    ContentTypeNotAllowed {
+        content_type: Option<String>,
+        allowed: Vec<String>,
+    },
+    /// The server redirected more often than the endpoint allows.
+    TooManyRedirects { limit: u32 },
+    /// A redirect pointed somewhere the tool may not go.
+    RedirectNotAllowed { location: String, reason: String },
+}
+
+impl HttpLimitError {
+    /// Human-readable description.
+    pub fn message(&self) -> String {
+        match self {
+            Self::ResponseTooLarge {
+                limit,
+                size: Some(size),
+            } => format!(
+                "Response body too large: {} bytes exceeds limit of {} bytes",
+                size, limit
+            ),
+            Self::ResponseTooLarge { limit, size: None } => {
+                format!("Response body too large: exceeds limit of {} bytes", limit)
+            }
+            Self::ContentTypeNotAllowed {
+                content_type,
+                allowed,
+            } => format!(
+                "Response content type {} not allowed (expected {})",
+                content_type.as_deref().unwrap_or("(none)"),
+                allowed.join(", ")
+            ),
+            Self::TooManyRedirects { limit } => {
+                format!("Too many redirects (limit {})", limit)
+            }
+            Self::RedirectNotAllowed { location, reason } => {
+                format!("Redirect to {} not allowed: {}", location, reason)
+            }
+        }
+    }
+}
+
+impl fmt::Display for HttpLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut value = serde_json::to_value(self).map_err(|_| fmt::Error)?;
+        value["message"] = serde_json::Value::String(self.message());
+        write!(f, "{}", value)
+    }
+}
+
+impl std::error::Error for HttpLimitError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::tools::wasm::capabilities::{EndpointPattern, HttpCapability};
+    use crate::tools::wasm::http_limits::{
+        HttpLimitError, ResponseLimits, redirect_becomes_get, redirect_target,
+    };
+
+    fn capability() -> HttpCapability {
+        HttpCapability::new(vec![
+            EndpointPattern::host("api.example.com")
+                .with_path_prefix("/v1/")
+                .with_max_response_bytes(1024)
+                .with_allowed_content_types(vec!["application/json".to_string()])
+                .with_max_redirects(2),
+            EndpointPattern::host("*.example.com"),
+        ])
+        .with_max_response_bytes(4096)
+        .with_allowed_content_types(vec!["text/*".to_string()])
+    }
+
+    #[test]
+    fn test_endpoint_limits_override_capability() {
+        let cap = capability();
+
+        let api = ResponseLimits::resolve(Some(&cap), "https://api.example.com/v1/items", "GET");
+        assert_eq!(api.max_response_bytes, 1024);
+        assert_eq!(api.allowed_content_types, ["application/json"]);
+        assert_eq!(api.max_redirects, 2);
+
+        let cdn = ResponseLimits::resolve(Some(&cap), "https://cdn.example.com/a.txt", "GET");
+        assert_eq!(cdn.max_response_bytes, 4096);
+        assert_eq!(cdn.allowed_content_types, ["text/*"]);
+        assert_eq!(cdn.max_redirects, 0);
+
+        assert_eq!(
+            ResponseLimits::resolve(None, "https://api.example.com/", "GET"),
+            ResponseLimits::default()
+        );
+    }
+
+    #[test]
+    fn test_content_type_matching() {
+        let limits = ResponseLimits {
+            allowed_content_types: vec!["application/json".to_string(), "text/*".to_string()],
+            ..Default::default()
+        };
+        assert!(
+            limits
+                .check_content_type(Some("application/json; charset=utf-8"))
+                .is_ok()
+        );
+        assert!(limits.check_content_type(Some("Text/HTML")).is_ok());
+        assert!(limits.check_content_type(Some("image/png")).is_err());
+        assert!(limits.check_content_type(None).is_err());
+
+        assert!(
+            ResponseLimits::default()
+                .check_content_type(Some("application/octet-stream"))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_errors_serialize_with_code_and_message() {
+        let err = ResponseLimits {
+            max_response_bytes: 10,
+            ..Default::default()
+        }
+        .check_length(11)
+        .unwrap_err();
+        let json: serde_json::Value = serde_json::from_str(&err.to_string()).unwrap();
+        assert_eq!(json["error"], "response_too_large");
+        assert_eq!(json["limit"], 10);
+        assert_eq!(json["size"], 11);
+        assert!(json["message"].as_str().unwrap().contains("11 bytes"));
+
+        let err = HttpLimitError::TooManyRedirects { limit: 2 };
+        let json: serde_json::Value = serde_json::from_str(&err.to_string()).unwrap();
+        assert_eq!(json["error"], "too_many_redirects");
+    }
+
+    #[test]
+    fn test_redirect_helpers() {
+        assert_eq!(
+            redirect_target("https://api.example.com/v1/a?x=1", "/v1/b").as_deref(),
+            Some("https://api.example.com/v1/b")
+        );
+        assert_eq!(
+            redirect_target("https://api.example.com/v1/a", "https://cdn.example.com/f").as_deref(),
+            Some("https://cdn.example.com/f")
+        );
+        assert!(redirect_becomes_get("POST", 302));
+        assert!(redirect_becomes_get("PUT", 303));
+        assert!(!redirect_becomes_get("POST", 307));
+        assert!(!redirect_becomes_get("GET", 301));
+    }
+}
@@ -27,6 +27,9 @@
 //! skipping the install directory. This means during development you just
 //! rebuild the WASM and restart the host, no manual copy step needed.
 //!
+//! For a tool being worked on, [`spawn_dev_watch`](super::spawn_dev_watch)
+//! reloads it on every rebuild without a restart.
+//!
 //! # Security
 //!
 //! Tools loaded from files are assigned `TrustLevel::User` by default, meaning
@@ -39,8 +42,12 @@ use std::sync::Arc;
 
 use tokio::fs;
 
+use crate::extensions::consent::{CapabilityConsent, ConsentStatus};
 use crate::tools::registry::{ToolRegistry, WasmRegistrationError, WasmToolRegistration};
 use crate::tools::wasm::capabilities_schema::CapabilitiesFile;
+use crate::tools::wasm::permissions::{
+    CapabilitySet, PermissionDiff, accepted_path, load_accepted,
+};
 use crate::tools::wasm::{
     Capabilities, WasmError, WasmStorageError, WasmToolRuntime, WasmToolStore,
 };
@@ -71,6 +78,19 @@ pub enum WasmLoadError {
 
     #[error("Invalid tool name: {0}")]
     InvalidName(String),
+
+    #[error(
+        "Capabilities for '{name}' exceed what was accepted at install; reinstall the tool to review:\n{added}"
+    )]
+    UnapprovedCapabilities { name: String, added: String },
+
+    #[error(
+        "'{name}' needs consent for its capabilities; grant it with `ironclaw tool consent {name}` or from the web gateway:\n{summary}"
+    )]
+    ConsentRequired { name: String, summary: String },
+
+    #[error("Capabilities requested by '{0}' were denied")]
+    ConsentDenied(String),
 }
 
 /// Loads WASM tools from files or storage into the registry.
@@ -114,6 +134,7 @@ impl WasmToolLoader {
                 let cap_bytes = fs::read(cap_path).await?;
                 let cap_file = CapabilitiesFile::from_bytes(&cap_bytes)
                     .map_err(|e| WasmLoadError::InvalidCapabilities(e.to_string()))?;
+                check_accepted(name, wasm_path, &cap_file).await?;
                 cap_file.to_capabilities()
             } else {
                 tracing::warn!(
@@ -148,6 +169,22 @@ impl WasmToolLoader {
         Ok(())
     }
 
+    /// Load a tool again from its files, replacing the registered version.
+    ///
+    /// The module is prepared from the new bytes rather than taken from the
+    /// runtime's cache. If anything fails, the version already registered
+    /// stays in place.
+    pub async fn reload_from_files(
+        &self,
+        name: &str,
+        wasm_path: &Path,
+        capabilities_path: Option<&Path>,
+    ) -> Result<(), WasmLoadError> {
+        self.runtime.remove(name).await;
+        self.load_from_files(name, wasm_path, capabilities_path)
+            .await
+    }
+
     /// Load all WASM tools from a directory.
     ///
     /// Scans the directory for `*.wasm` files and loads each one, looking for
@@ -164,6 +201,9 @@ impl WasmToolLoader {
     /// ```
     ///
     /// Tools without a capabilities file get no permissions (default deny).
+    /// A tool whose capabilities the user has not granted (see
+    /// [`CapabilityConsent`]) is skipped with
+    /// [`WasmLoadError::ConsentRequired`].
     pub async fn load_from_dir(&self, dir: &Path) -> Result<LoadResults, WasmLoadError> {
         if !dir.is_dir() {
             return Err(WasmLoadError::Io(std::io::Error::new(
@@ -202,9 +242,14 @@ impl WasmToolLoader {
         }
 
         // Load all tools in parallel (file I/O + WASM compilation + registration)
-        let load_futures = tool_entries
-            .iter()
-            .map(|(name, path, cap_path)| self.load_from_files(name, path, cap_path.as_deref()));
+        let consent = CapabilityConsent::new(dir);
+        let load_futures = tool_entries.iter().map(|(name, path, cap_path)| {
+            let consent = &consent;
+            async move {
+                check_consent(consent, name, cap_path.as_deref()).await?;
+                self.load_from_files(name, path, cap_path.as_deref()).await
+            }
+        });
 
         let load_results = futures::future::join_all(load_futures).await;
 
@@ -293,6 +338,57 @@ impl WasmToolLoader {
     }
 }
 
+/// Refuse a tool whose requested capabilities the user has not granted.
+async fn check_consent(
+    consent: &CapabilityConsent,
+    name: &str,
+    cap_path: Option<&Path>,
+) -> Result<(), WasmLoadError> {
+    let requested = match cap_path {
+        Some(path) => {
+            let file = CapabilitiesFile::from_bytes(&fs::read(path).await?)
+                .map_err(|e| WasmLoadError::InvalidCapabilities(e.to_string()))?;
+            CapabilitySet::from_file(&file)
+        }
+        None => CapabilitySet::default(),
+    };
+    match consent.check(name, &requested).await? {
+        ConsentStatus::Granted => Ok(()),
+        ConsentStatus::Denied => Err(WasmLoadError::ConsentDenied(name.to_string())),
+        ConsentStatus::Required(request) => Err(WasmLoadError::ConsentRequired {
+            name: name.to_string(),
+            summary: request.summary().join("\n"),
+        }),
+    }
+}
+
+/// Refuse capabilities broader than the set accepted when the tool was
+/// installed. Tools without an accepted record load as before.
+async fn check_accepted(
+    name: &str,
+    wasm_path: &Path,
+    cap_file: &CapabilitiesFile,
+) -> Result<(), WasmLoadError> {
+    let Some(dir) = wasm_path.parent() else {
+        return Ok(());
+    };
+    let Some(accepted) = load_accepted(&accepted_path(dir, name)).await? else {
+        return Ok(());
+    };
+    let diff = PermissionDiff::between(&accepted.capabilities, &CapabilitySet::from_file(cap_file));
+    if diff.broadens() {
+        let added = PermissionDiff {
+            added: diff.added,
+            ..Default::default()
+        };
+        return Err(WasmLoadError::UnapprovedCapabilities {
+            name: name.to_string(),
+            added: added.to_string(),
+        });
+    }
+    Ok(())
+}
+
 /// Results from loading multiple tools.
 #[derive(Debug, Default)]
 pub struct LoadResults {
@@ -531,7 +627,9 @@ mod tests {
 
     use tempfile::TempDir;
 
-    use crate::tools::wasm::loader::{WasmLoadError, discover_tools};
+    use crate::tools::wasm::capabilities_schema::CapabilitiesFile;
+    use crate::tools::wasm::loader::{WasmLoadError, check_accepted, discover_tools};
+    use crate::tools::wasm::permissions::{CapabilitySet, accepted_path};
 
     #[tokio::test]
     async fn test_discover_tools_empty_dir() {
@@ -592,6 +690,72 @@ mod tests {
         assert!(err.to_string().contains("/foo/bar.wasm"));
     }
 
+    #[tokio::test]
+    async fn test_load_from_dir_requires_consent() {
+        use std::sync::Arc;
+
+        use crate::extensions::consent::CapabilityConsent;
+        use crate::tools::ToolRegistry;
+        use crate::tools::wasm::loader::WasmToolLoader;
+        use crate::tools::wasm::{WasmRuntimeConfig, WasmToolRuntime};
+
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("fetch.wasm"), b"not compiled").unwrap();
+        let caps = r#"{"http": {"allowlist": [{"host": "api.example.com"}]}}"#;
+        std::fs::write(dir.path().join("fetch.capabilities.json"), caps).unwrap();
+        let runtime = Arc::new(WasmToolRuntime::new(WasmRuntimeConfig::default()).unwrap());
+        let loader = WasmToolLoader::new(runtime, Arc::new(ToolRegistry::new()));
+
+        let results = loader.load_from_dir(dir.path()).await.unwrap();
+        let (_, err) = &results.errors[0];
+        assert!(matches!(err, WasmLoadError::ConsentRequired { .. }));
+        assert!(err.to_string().contains("http: api.example.com"));
+
+        // Once granted, the tool gets as far as compiling.
+        let requested = CapabilitySet::from_file(&CapabilitiesFile::from_json(caps).unwrap());
+        let consent = CapabilityConsent::new(dir.path());
+        consent.grant("fetch", requested.clone()).await.unwrap();
+        let results = loader.load_from_dir(dir.path()).await.unwrap();
+        assert!(matches!(results.errors[0].1, WasmLoadError::Compilation(_)));
+
+        consent.forget("fetch").await.unwrap();
+        consent.deny("fetch", requested).await.unwrap();
+        let results = loader.load_from_dir(dir.path()).await.unwrap();
+        assert!(matches!(
+            results.errors[0].1,
+            WasmLoadError::ConsentDenied(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_check_accepted_refuses_broader_capabilities() {
+        let dir = TempDir::new().unwrap();
+        let wasm_path = dir.path().join("slack.wasm");
+        let narrow =
+            CapabilitiesFile::from_json(r#"{"http": {"allowlist": [{"host": "slack.com"}]}}"#)
+                .unwrap();
+        let broad = CapabilitiesFile::from_json(
+            r#"{"http": {"allowlist": [{"host": "slack.com"}, {"host": "example.net"}]}}"#,
+        )
+        .unwrap();
+
+        // No record yet: anything loads.
+        assert!(check_accepted("slack", &wasm_path, &broad).await.is_ok());
+
+        crate::tools::wasm::permissions::record_accepted(
+            &accepted_path(dir.path(), "slack"),
+            CapabilitySet::from_file(&narrow),
+        )
+        .await
+        .unwrap();
+        assert!(check_accepted("slack", &wasm_path, &narrow).await.is_ok());
+        let err = check_accepted("slack", &wasm_path, &broad)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, WasmLoadError::UnapprovedCapabilities { .. }));
+        assert!(err.to_string().contains("+ http: example.net"));
+    }
+
     #[test]
     fn test_tools_src_dir_default() {
         let dir = super::tools_src_dir();
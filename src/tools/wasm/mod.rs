@@ -38,6 +38,7 @@
 //! | Infinite loops | Epoch interruption + tokio timeout |
 //! | Filesystem access | No WASI FS, only host workspace_read |
 //! | Network access | Allowlisted endpoints only |
+//! | Oversized/binary responses | Per-endpoint size and content-type limits |
 //! | Credential exposure | Injection at host boundary only |
 //! | Secret exfiltration | Leak detector scans all outputs |
 //! | Log spam | Max 1000 entries, 4KB per message |
@@ -45,6 +46,7 @@
 //! | Trap recovery | Discard instance, never reuse |
 //! | Side channels | Fresh instance per execution |
 //! | Rate abuse | Per-tool rate limiting |
+//! | Repeated misbehavior | Strikes, then automatic quarantine |
 //! | WASM tampering | BLAKE3 hash verification on load |
 //! | Direct tool access | Tool aliasing (indirection layer) |
 //!
@@ -77,34 +79,44 @@ mod allowlist;
 mod capabilities;
 mod capabilities_schema;
 mod credential_injector;
+mod dev_watch;
+mod dry_run;
 mod error;
 mod host;
+mod http_limits;
 mod limits;
 mod loader;
+mod permissions;
+mod quarantine;
 mod rate_limiter;
 mod runtime;
+mod schedule;
 mod storage;
+mod world;
 mod wrapper;
 
 // Core types
+pub use dry_run::{Attempt, DryRunReport, HttpAttempt};
 pub use error::{TrapCode, TrapInfo, WasmError};
 pub use host::{HostState, LogEntry, LogLevel};
 pub use limits::{
     DEFAULT_FUEL_LIMIT, DEFAULT_MEMORY_LIMIT, DEFAULT_TIMEOUT, FuelConfig, ResourceLimits,
     WasmResourceLimiter,
 };
-pub use runtime::{PreparedModule, WasmRuntimeConfig, WasmToolRuntime};
+pub use runtime::{PoolingConfig, PreparedModule, WasmRuntimeConfig, WasmToolRuntime};
+pub use world::ToolWorld;
 pub use wrapper::WasmToolWrapper;
 
 // Capabilities (V2)
 pub use capabilities::{
-    Capabilities, EndpointPattern, HttpCapability, RateLimitConfig, SecretsCapability,
-    ToolInvokeCapability, WorkspaceCapability, WorkspaceReader,
+    Capabilities, EndpointPattern, HttpCapability, ProgressCapability, RateLimitConfig,
+    SecretsCapability, ToolInvokeCapability, WorkspaceCapability, WorkspaceReader,
 };
 
 // Security components (V2)
 pub use allowlist::{AllowlistResult, AllowlistValidator, DenyReason};
 pub use credential_injector::{CredentialInjector, InjectedCredentials, InjectionError};
+pub use http_limits::{HttpLimitError, ResponseLimits};
 pub use rate_limiter::{LimitType, RateLimitError, RateLimitResult, RateLimiter};
 
 // Storage (V2)
@@ -113,8 +125,9 @@ pub use storage::LibSqlWasmToolStore;
 #[cfg(feature = "postgres")]
 pub use storage::PostgresWasmToolStore;
 pub use storage::{
-    StoreToolParams, StoredCapabilities, StoredWasmTool, StoredWasmToolWithBinary, ToolStatus,
-    TrustLevel, WasmStorageError, WasmToolStore, compute_binary_hash, verify_binary_integrity,
+    Misbehavior, StoreToolParams, StoredCapabilities, StoredWasmTool, StoredWasmToolWithBinary,
+    ToolIncident, ToolStatus, TrustLevel, WasmStorageError, WasmToolStore, compute_binary_hash,
+    verify_binary_integrity,
 };
 
 // Loader
@@ -123,10 +136,29 @@ pub use loader::{
     load_dev_tools,
 };
 
+// Hot reload during development
+pub use dev_watch::{DevReload, DevWatchTarget, spawn_dev_watch};
+
+// Strikes and automatic quarantine
+pub use quarantine::{
+    DEFAULT_STRIKE_THRESHOLD, MisbehaviorTracker, QuarantineReport, quarantine_path,
+};
+
+// Accepted capabilities and update permission diffs
+pub use permissions::{
+    AcceptedCapabilities, CapabilitySet, PermissionDiff, accepted_path, load_accepted,
+    record_accepted,
+};
+
+// Routines for tools with a schedule capability
+pub use schedule::{
+    ScheduleSync, schedule_routine_name, scheduled_tool_routine, sync_tool_schedule,
+};
+
 // Capabilities schema (for parsing *.capabilities.json files)
 pub use capabilities_schema::{
-    AuthCapabilitySchema, CapabilitiesFile, OAuthConfigSchema, RateLimitSchema,
-    ValidationEndpointSchema,
+    AuthCapabilitySchema, CapabilitiesFile, OAuthConfigSchema, ProgressCapabilitySchema,
+    RateLimitSchema, ScheduleCapabilitySchema, ValidationEndpointSchema,
 };
 
 #[cfg(test)]
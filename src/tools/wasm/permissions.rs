@@ -0,0 +1,253 @@
+//! Capability sets and permission diffs for WASM tool updates.
+//!
+//! Installing a tool means accepting the permissions in its
+//! `*.capabilities.json`. That accepted set is recorded next to the tool as
+//! `<name>.accepted.json`. When an update (or a hand-edited capabilities
+//! file) asks for more, such as new HTTP endpoints, secrets, workspace
+//! prefixes or tool aliases, the difference is shown and must be approved
+//! before the new set takes effect. The loader refuses tools whose
+//! capabilities exceed the accepted set, so drift never goes unnoticed.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::tools::wasm::capabilities_schema::CapabilitiesFile;
+
+/// The permission-relevant parts of a capabilities file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilitySet {
+    /// HTTP endpoints as `host` or `host/path-prefix`.
+    #[serde(default)]
+    pub http_endpoints: BTreeSet<String>,
+    /// Secrets the tool can have injected or check for.
+    #[serde(default)]
+    pub secrets: BTreeSet<String>,
+    /// Workspace path prefixes the tool can read.
+    #[serde(default)]
+    pub workspace_prefixes: BTreeSet<String>,
+    /// Tools the tool can invoke, as `alias -> tool`.
+    #[serde(default)]
+    pub tool_aliases: BTreeSet<String>,
+}
+
+impl CapabilitySet {
+    /// Extract the permission-relevant parts of a capabilities file.
+    pub fn from_file(file: &CapabilitiesFile) -> Self {
+        let mut set = Self::default();
+        if let Some(http) = &file.http {
+            for endpoint in &http.allowlist {
+                let entry = match endpoint.path_prefix.as_deref() {
+                    Some(prefix) if !prefix.is_empty() && prefix != "/" => {
+                        format!("{}/{}", endpoint.host, prefix.trim_start_matches('/'))
+                    }
+                    _ => endpoint.host.clone(),
+                };
+                set.http_endpoints.insert(entry);
+            }
+            set.secrets.extend(
+                http.credentials
+                    .values()
+                    .map(|cred| cred.secret_name.clone()),
+            );
+        }
+        if let Some(secrets) = &file.secrets {
+            set.secrets.extend(secrets.allowed_names.iter().cloned());
+        }
+        if let Some(auth) = &file.auth {
+            set.secrets.insert(auth.secret_name.clone());
+        }
+        if let Some(workspace) = &file.workspace {
+            set.workspace_prefixes
+                .extend(workspace.allowed_prefixes.iter().cloned());
+        }
+        if let Some(tool_invoke) = &file.tool_invoke {
+            set.tool_aliases.extend(
+                tool_invoke
+                    .aliases
+                    .iter()
+                    .map(|(alias, tool)| format!("{} -> {}", alias, tool)),
+            );
+        }
+        set
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.http_endpoints.is_empty()
+            && self.secrets.is_empty()
+            && self.workspace_prefixes.is_empty()
+            && self.tool_aliases.is_empty()
+    }
+
+    /// Entries in `self` that are not in `other`.
+    fn minus(&self, other: &Self) -> Self {
+        let diff = |a: &BTreeSet<String>, b: &BTreeSet<String>| a.difference(b).cloned().collect();
+        Self {
+            http_endpoints: diff(&self.http_endpoints, &other.http_endpoints),
+            secrets: diff(&self.secrets, &other.secrets),
+            workspace_prefixes: diff(&self.workspace_prefixes, &other.workspace_prefixes),
+            tool_aliases: diff(&self.tool_aliases, &other.tool_aliases),
+        }
+    }
+
+    /// `(label, entries)` pairs for display.
+    pub fn sections(&self) -> [(&'static str, &BTreeSet<String>); 4] {
+        [
+            ("http", &self.http_endpoints),
+            ("secret", &self.secrets),
+            ("workspace", &self.workspace_prefixes),
+            ("tool", &self.tool_aliases),
+        ]
+    }
+}
+
+/// What an update adds to and drops from the accepted capabilities.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PermissionDiff {
+    pub added: CapabilitySet,
+    pub removed: CapabilitySet,
+}
+
+impl PermissionDiff {
+    pub fn between(accepted: &CapabilitySet, requested: &CapabilitySet) -> Self {
+        Self {
+            added: requested.minus(accepted),
+            removed: accepted.minus(requested),
+        }
+    }
+
+    /// Whether the requested set grants anything the accepted one did not.
+    pub fn broadens(&self) -> bool {
+        !self.added.is_empty()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl std::fmt::Display for PermissionDiff {
+    /// One line per change: `+ http: api.example.com`, `- secret: old_key`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (sign, set) in [('+', &self.added), ('-', &self.removed)] {
+            for (label, entries) in set.sections() {
+                for entry in entries {
+                    writeln!(f, "{} {}: {}", sign, label, entry)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Capabilities the user accepted for an installed tool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcceptedCapabilities {
+    pub capabilities: CapabilitySet,
+    pub accepted_at: DateTime<Utc>,
+}
+
+/// Where the accepted set for a tool is recorded.
+pub fn accepted_path(tools_dir: &Path, name: &str) -> PathBuf {
+    tools_dir.join(format!("{}.accepted.json", name))
+}
+
+/// Read the accepted set for a tool, if one was recorded.
+pub async fn load_accepted(path: &Path) -> std::io::Result<Option<AcceptedCapabilities>> {
+    match tokio::fs::read(path).await {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Record `capabilities` as accepted for a tool.
+pub async fn record_accepted(path: &Path, capabilities: CapabilitySet) -> std::io::Result<()> {
+    let record = AcceptedCapabilities {
+        capabilities,
+        accepted_at: Utc::now(),
+    };
+    let json = serde_json::to_vec_pretty(&record)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    tokio::fs::write(path, json).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caps(json: &str) -> CapabilitySet {
+        CapabilitySet::from_file(&CapabilitiesFile::from_json(json).unwrap())
+    }
+
+    #[test]
+    fn test_capability_set_from_file() {
+        let set = caps(
+            r#"{
+                "http": {
+                    "allowlist": [
+                        {"host": "slack.com", "path_prefix": "/api/"},
+                        {"host": "files.slack.com"}
+                    ],
+                    "credentials": {
+                        "slack": {
+                            "secret_name": "slack_bot_token",
+                            "location": {"type": "bearer"},
+                            "host_patterns": ["slack.com"]
+                        }
+                    }
+                },
+                "secrets": {"allowed_names": ["slack_*"]},
+                "workspace": {"allowed_prefixes": ["context/"]}
+            }"#,
+        );
+        assert_eq!(
+            set.http_endpoints.iter().collect::<Vec<_>>(),
+            vec!["files.slack.com", "slack.com/api/"]
+        );
+        assert!(set.secrets.contains("slack_bot_token"));
+        assert!(set.secrets.contains("slack_*"));
+        assert!(set.workspace_prefixes.contains("context/"));
+        assert!(set.tool_aliases.is_empty());
+    }
+
+    #[test]
+    fn test_diff_detects_broadening() {
+        let old = caps(r#"{"http": {"allowlist": [{"host": "api.example.com"}]}}"#);
+        let new = caps(
+            r#"{"http": {"allowlist": [{"host": "api.example.com"}, {"host": "evil.example.net"}]},
+                "secrets": {"allowed_names": ["openai_key"]}}"#,
+        );
+
+        let diff = PermissionDiff::between(&old, &new);
+        assert!(diff.broadens());
+        assert_eq!(
+            diff.to_string(),
+            "+ http: evil.example.net\n+ secret: openai_key\n"
+        );
+
+        // Dropping permissions is not a broadening.
+        let diff = PermissionDiff::between(&new, &old);
+        assert!(!diff.broadens());
+        assert!(!diff.is_empty());
+        assert!(PermissionDiff::between(&old, &old).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_accepted_record_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = accepted_path(dir.path(), "slack");
+        assert!(load_accepted(&path).await.unwrap().is_none());
+
+        let set = caps(r#"{"http": {"allowlist": [{"host": "slack.com"}]}}"#);
+        record_accepted(&path, set.clone()).await.unwrap();
+        assert_eq!(
+            load_accepted(&path).await.unwrap().unwrap().capabilities,
+            set
+        );
+    }
+}
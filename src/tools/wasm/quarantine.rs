@@ -0,0 +1,341 @@
+//! Automatic quarantine for misbehaving WASM tools.
+//!
+//! A call that traps, is refused an HTTP request or tool call by the
+//! allowlist, trips the leak detector or runs past its rate limit earns the
+//! tool one strike per kind of misbehavior. Strikes are recorded in the
+//! [`WasmToolStore`], or in memory when there is no database. Once a tool
+//! reaches the threshold (`WASM_QUARANTINE_STRIKES`) it is unregistered,
+//! marked [`ToolStatus::Quarantined`], and a [`QuarantineReport`] listing
+//! its strikes is written next to it as `<name>.quarantine.json`. The
+//! registry refuses to load a tool with a report, so the quarantine holds
+//! across restarts and reinstalls. The user is told on the channel they
+//! last wrote from.
+//!
+//! `ironclaw tool unquarantine <name>` shows the report, asks for
+//! confirmation, then removes it and clears the strikes.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::tools::ToolRegistry;
+use crate::tools::wasm::{Misbehavior, ToolIncident, ToolStatus, WasmStorageError, WasmToolStore};
+
+/// Strikes before a tool is quarantined, unless configured otherwise.
+pub const DEFAULT_STRIKE_THRESHOLD: u32 = 3;
+
+/// Path of the quarantine report for `name` in `tools_dir`.
+pub fn quarantine_path(tools_dir: &Path, name: &str) -> PathBuf {
+    tools_dir.join(format!("{}.quarantine.json", name))
+}
+
+/// Why a tool was quarantined, written as `<name>.quarantine.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineReport {
+    pub tool: String,
+    pub quarantined_at: DateTime<Utc>,
+    /// Strikes that led to the quarantine, oldest first.
+    pub incidents: Vec<ToolIncident>,
+}
+
+impl QuarantineReport {
+    /// Read the report for `name`, if the tool is quarantined.
+    pub fn load(tools_dir: &Path, name: &str) -> std::io::Result<Option<Self>> {
+        match std::fs::read(quarantine_path(tools_dir, name)) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(std::io::Error::other),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write the report to `tools_dir`.
+    pub fn save(&self, tools_dir: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(tools_dir)?;
+        let json = serde_json::to_vec_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(quarantine_path(tools_dir, &self.tool), json)
+    }
+
+    /// Remove the report for `name`. Returns whether there was one.
+    pub fn remove(tools_dir: &Path, name: &str) -> std::io::Result<bool> {
+        match std::fs::remove_file(quarantine_path(tools_dir, name)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Strikes per kind of misbehavior, most frequent first.
+    pub fn summary(&self) -> Vec<(Misbehavior, usize)> {
+        let mut counts: Vec<(Misbehavior, usize)> = Vec::new();
+        for incident in &self.incidents {
+            match counts.iter_mut().find(|(kind, _)| *kind == incident.kind) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((incident.kind, 1)),
+            }
+        }
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        counts
+    }
+
+    /// The incident report shown before a tool is let out of quarantine.
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "Tool '{}' was quarantined on {} after {} strike(s):\n",
+            self.tool,
+            self.quarantined_at.format("%Y-%m-%d %H:%M UTC"),
+            self.incidents.len()
+        );
+        for incident in &self.incidents {
+            out.push_str(&format!(
+                "  {}  {:<17} {}\n",
+                incident.at.format("%Y-%m-%d %H:%M:%S"),
+                incident.kind,
+                incident.detail
+            ));
+        }
+        out
+    }
+
+    /// Message sent to the user when the tool is quarantined.
+    pub fn notice(&self) -> String {
+        let summary = self
+            .summary()
+            .iter()
+            .map(|(kind, count)| format!("{} x{}", kind, count))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "Quarantined WASM tool '{}' after repeated misbehavior ({}). It stays disabled until you run `ironclaw tool unquarantine {}`, which shows the full incident report.",
+            self.tool, summary, self.tool
+        )
+    }
+}
+
+/// Counts strikes against WASM tools and quarantines those that reach the
+/// threshold.
+pub struct MisbehaviorTracker {
+    tools_dir: PathBuf,
+    threshold: u32,
+    user_id: String,
+    store: Option<Arc<dyn WasmToolStore>>,
+    registry: Weak<ToolRegistry>,
+    /// Strikes per tool when there is no store.
+    strikes: Mutex<HashMap<String, Vec<ToolIncident>>>,
+    quarantined: Mutex<HashSet<String>>,
+    /// Channel and user of the last message, where notices go.
+    active: Mutex<Option<(String, String)>>,
+    reports: broadcast::Sender<QuarantineReport>,
+}
+
+impl MisbehaviorTracker {
+    /// A tracker quarantining tools registered in `registry` after
+    /// `threshold` strikes, with reports written to `tools_dir`.
+    pub fn new(registry: &Arc<ToolRegistry>, tools_dir: PathBuf, threshold: u32) -> Self {
+        let (reports, _) = broadcast::channel(16);
+        Self {
+            tools_dir,
+            threshold: threshold.max(1),
+            user_id: "default".to_string(),
+            store: None,
+            registry: Arc::downgrade(registry),
+            strikes: Mutex::new(HashMap::new()),
+            quarantined: Mutex::new(HashSet::new()),
+            active: Mutex::new(None),
+            reports,
+        }
+    }
+
+    /// Record strikes and quarantine status in `store`.
+    pub fn with_store(mut self, store: Arc<dyn WasmToolStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Reports of tools as they are quarantined.
+    pub fn subscribe(&self) -> broadcast::Receiver<QuarantineReport> {
+        self.reports.subscribe()
+    }
+
+    /// Note the channel and user of an incoming message; quarantine notices
+    /// go there.
+    pub fn note_activity(&self, channel: &str, user_id: &str) {
+        *self.active.lock().unwrap() = Some((channel.to_string(), user_id.to_string()));
+    }
+
+    /// Channel and user of the last message, if any.
+    pub fn active_channel(&self) -> Option<(String, String)> {
+        self.active.lock().unwrap().clone()
+    }
+
+    /// Whether `name` is quarantined, in this run or by an earlier one.
+    pub fn is_quarantined(&self, name: &str) -> bool {
+        self.quarantined.lock().unwrap().contains(name)
+            || quarantine_path(&self.tools_dir, name).exists()
+    }
+
+    /// Record what a call of `name` did wrong, one strike per kind.
+    /// Returns the report if this call got the tool quarantined.
+    pub async fn record(
+        &self,
+        name: &str,
+        misbehavior: &[(Misbehavior, String)],
+    ) -> Option<QuarantineReport> {
+        let mut seen = HashSet::new();
+        let mut strikes = 0;
+        for (kind, detail) in misbehavior {
+            if !seen.insert(*kind) {
+                continue;
+            }
+            tracing::warn!(tool = name, kind = %kind, "WASM tool strike: {}", detail);
+            strikes = self.strike(name, *kind, detail).await;
+        }
+        if strikes < self.threshold || !self.quarantined.lock().unwrap().insert(name.to_string()) {
+            return None;
+        }
+        Some(self.quarantine(name).await)
+    }
+
+    /// Record one strike. Returns the tool's strikes so far.
+    async fn strike(&self, name: &str, kind: Misbehavior, detail: &str) -> u32 {
+        if let Some(ref store) = self.store {
+            match store.record_strike(&self.user_id, name, kind, detail).await {
+                Ok(count) => return count,
+                Err(e) => tracing::warn!("Failed to record strike against {}: {}", name, e),
+            }
+        }
+        let mut strikes = self.strikes.lock().unwrap();
+        let incidents = strikes.entry(name.to_string()).or_default();
+        incidents.push(ToolIncident {
+            kind,
+            detail: detail.to_string(),
+            at: Utc::now(),
+        });
+        incidents.len() as u32
+    }
+
+    /// Unregister the tool, mark it quarantined and tell the user.
+    async fn quarantine(&self, name: &str) -> QuarantineReport {
+        let mut incidents = self
+            .strikes
+            .lock()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .unwrap_or_default();
+        if let Some(ref store) = self.store {
+            match store.strikes(&self.user_id, name).await {
+                Ok(stored) if !stored.is_empty() => incidents = stored,
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to read strikes against {}: {}", name, e),
+            }
+            match store
+                .update_status(&self.user_id, name, ToolStatus::Quarantined)
+                .await
+            {
+                Ok(()) | Err(WasmStorageError::NotFound(_)) => {}
+                Err(e) => tracing::warn!("Failed to mark {} quarantined: {}", name, e),
+            }
+        }
+
+        if let Some(registry) = self.registry.upgrade() {
+            registry.unregister(name).await;
+        }
+
+        let report = QuarantineReport {
+            tool: name.to_string(),
+            quarantined_at: Utc::now(),
+            incidents,
+        };
+        if let Err(e) = report.save(&self.tools_dir) {
+            tracing::error!("Failed to write quarantine report for {}: {}", name, e);
+        }
+        tracing::error!(
+            tool = name,
+            strikes = report.incidents.len(),
+            "Quarantined WASM tool after repeated misbehavior"
+        );
+        let _ = self.reports.send(report.clone());
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strike(kind: Misbehavior) -> Vec<(Misbehavior, String)> {
+        vec![(kind, format!("{} happened", kind))]
+    }
+
+    #[tokio::test]
+    async fn test_quarantines_at_threshold() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = Arc::new(ToolRegistry::new());
+        let tracker = MisbehaviorTracker::new(&registry, dir.path().to_path_buf(), 3);
+        let mut reports = tracker.subscribe();
+
+        // Several denials in one call count once.
+        let mut call = strike(Misbehavior::AllowlistDenied);
+        call.extend(strike(Misbehavior::AllowlistDenied));
+        assert!(tracker.record("scraper", &call).await.is_none());
+        assert!(
+            tracker
+                .record("scraper", &strike(Misbehavior::Trap))
+                .await
+                .is_none()
+        );
+        assert!(!tracker.is_quarantined("scraper"));
+
+        let report = tracker
+            .record("scraper", &strike(Misbehavior::LeakDetected))
+            .await
+            .unwrap();
+        assert_eq!(report.incidents.len(), 3);
+        assert!(tracker.is_quarantined("scraper"));
+        assert_eq!(reports.try_recv().unwrap().tool, "scraper");
+
+        // Later strikes do not quarantine it again.
+        assert!(
+            tracker
+                .record("scraper", &strike(Misbehavior::Trap))
+                .await
+                .is_none()
+        );
+        assert!(reports.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_report_survives_restart_until_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = Arc::new(ToolRegistry::new());
+        let tracker = MisbehaviorTracker::new(&registry, dir.path().to_path_buf(), 1);
+        tracker
+            .record("feeds", &strike(Misbehavior::RateLimited))
+            .await
+            .unwrap();
+
+        let restarted = MisbehaviorTracker::new(&registry, dir.path().to_path_buf(), 1);
+        assert!(restarted.is_quarantined("feeds"));
+
+        let report = QuarantineReport::load(dir.path(), "feeds")
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.summary(), vec![(Misbehavior::RateLimited, 1)]);
+        assert!(report.render().contains("rate_limited"));
+        assert!(report.notice().contains("ironclaw tool unquarantine feeds"));
+
+        assert!(QuarantineReport::remove(dir.path(), "feeds").unwrap());
+        assert!(!restarted.is_quarantined("feeds"));
+        assert!(
+            QuarantineReport::load(dir.path(), "feeds")
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -2,6 +2,10 @@
 //!
 //! Follows the principle: compile once at registration, instantiate fresh per execution.
 //! This matches NEAR blockchain patterns for deterministic, isolated execution.
+//!
+//! With [`PoolingConfig`] set, instances come from Wasmtime's pooling
+//! allocator: memories and tables live in slots reserved up front and are
+//! reset for reuse, instead of being mapped and unmapped on every call.
 
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -9,10 +13,13 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use tokio::sync::RwLock;
-use wasmtime::{Config, Engine, OptLevel};
+use wasmtime::component::Component;
+use wasmtime::{Config, Engine, InstanceAllocationStrategy, OptLevel, PoolingAllocationConfig};
 
 use crate::tools::wasm::error::WasmError;
 use crate::tools::wasm::limits::{FuelConfig, ResourceLimits};
+use crate::tools::wasm::world::ToolWorld;
+use crate::tools::wasm::wrapper::{LinkedComponent, read_standard_metadata};
 
 /// Default epoch tick interval. Each tick increments the engine's epoch counter,
 /// which causes any store with an expired epoch deadline to trap.
@@ -31,6 +38,48 @@ pub struct WasmRuntimeConfig {
     pub cache_dir: Option<PathBuf>,
     /// Cranelift optimization level.
     pub optimization_level: OptLevel,
+    /// Pooling allocator settings; `None` allocates each instance on demand.
+    pub pooling: Option<PoolingConfig>,
+    /// Instances each tool keeps ready ahead of its next call (0 = none).
+    pub warm_instances: usize,
+}
+
+/// Wasmtime pooling allocator settings.
+#[derive(Debug, Clone)]
+pub struct PoolingConfig {
+    /// Component instances alive at once across all tools, warm ones
+    /// included. Instantiation fails past this.
+    pub max_instances: u32,
+    /// Largest linear memory one instance can grow to, in bytes.
+    pub max_memory_bytes: u64,
+}
+
+impl Default for PoolingConfig {
+    fn default() -> Self {
+        Self {
+            max_instances: 100,
+            max_memory_bytes: crate::tools::wasm::DEFAULT_MEMORY_LIMIT,
+        }
+    }
+}
+
+/// Core instances, memories and tables budgeted per component instance in
+/// the pool. Components built for WASI link a few adapter modules besides
+/// the tool itself.
+const POOL_SLOTS_PER_COMPONENT: u32 = 8;
+
+impl PoolingConfig {
+    fn allocation_strategy(&self) -> InstanceAllocationStrategy {
+        let slots = self.max_instances.saturating_mul(POOL_SLOTS_PER_COMPONENT);
+        let mut pooling = PoolingAllocationConfig::new();
+        pooling
+            .total_component_instances(self.max_instances)
+            .total_core_instances(slots)
+            .total_memories(slots)
+            .total_tables(slots)
+            .max_memory_size(self.max_memory_bytes as usize);
+        InstanceAllocationStrategy::Pooling(pooling)
+    }
 }
 
 impl Default for WasmRuntimeConfig {
@@ -41,6 +90,8 @@ impl Default for WasmRuntimeConfig {
             cache_compiled: true,
             cache_dir: None,
             optimization_level: OptLevel::Speed,
+            pooling: None,
+            warm_instances: 0,
         }
     }
 }
@@ -57,6 +108,8 @@ impl WasmRuntimeConfig {
             cache_compiled: false,
             cache_dir: None,
             optimization_level: OptLevel::None, // Faster compilation for tests
+            pooling: None,
+            warm_instances: 0,
         }
     }
 }
@@ -65,7 +118,6 @@ impl WasmRuntimeConfig {
 ///
 /// Contains the pre-compiled component plus cached metadata extracted
 /// from the component during preparation.
-#[derive(Debug)]
 pub struct PreparedModule {
     /// Tool name.
     pub name: String,
@@ -77,6 +129,10 @@ pub struct PreparedModule {
     component_bytes: Vec<u8>,
     /// Resource limits for this tool.
     pub limits: ResourceLimits,
+    /// WIT world the component targets.
+    pub world: ToolWorld,
+    /// The compiled component, linked for its world.
+    linked: LinkedComponent,
 }
 
 impl PreparedModule {
@@ -84,6 +140,22 @@ impl PreparedModule {
     pub fn component_bytes(&self) -> &[u8] {
         &self.component_bytes
     }
+
+    /// The component linked against its world's host functions.
+    pub(crate) fn linked(&self) -> &LinkedComponent {
+        &self.linked
+    }
+}
+
+impl std::fmt::Debug for PreparedModule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PreparedModule")
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("limits", &self.limits)
+            .field("world", &self.world)
+            .finish()
+    }
 }
 
 /// WASM tool runtime.
@@ -123,6 +195,13 @@ impl WasmToolRuntime {
         // Disable debug info in production for smaller modules
         wasmtime_config.debug_info(false);
 
+        if let Some(ref pooling) = config.pooling {
+            wasmtime_config.allocation_strategy(pooling.allocation_strategy());
+            // Reserve what a memory may grow to rather than the 4 GiB default,
+            // which would be multiplied by every slot in the pool.
+            wasmtime_config.memory_reservation(pooling.max_memory_bytes);
+        }
+
         let engine = Engine::new(&wasmtime_config).map_err(|e| {
             WasmError::EngineCreationFailed(format!("Failed to create Wasmtime engine: {}", e))
         })?;
@@ -181,26 +260,38 @@ impl WasmToolRuntime {
         let name = name.to_string();
         let wasm_bytes = wasm_bytes.to_vec();
         let engine = self.engine.clone();
-        let default_limits = self.config.default_limits.clone();
+        let limits = limits.unwrap_or_else(|| self.config.default_limits.clone());
+        let fuel_enabled = self.config.fuel_config.enabled;
 
         // Compile in blocking task (Wasmtime compilation is synchronous)
         let prepared = tokio::task::spawn_blocking(move || {
             // Validate and compile the component
-            let component = wasmtime::component::Component::new(&engine, &wasm_bytes)
+            let component = Component::new(&engine, &wasm_bytes)
                 .map_err(|e| WasmError::CompilationFailed(e.to_string()))?;
 
-            // We need to instantiate briefly to extract metadata.
-            // In a full implementation, we'd use WIT bindgen to get typed access.
-            // For now, we extract what we can from the component.
-            let description = extract_tool_description(&engine, &component)?;
-            let schema = extract_tool_schema(&engine, &component)?;
+            // Standard components have no host imports with side effects, so
+            // their metadata is read by calling the exports. `near:agent`
+            // components need the full host to instantiate; we extract what
+            // we can from the component type instead.
+            let world = ToolWorld::detect(&engine, &component)?;
+            let (description, schema) = match world {
+                ToolWorld::Standard => {
+                    read_standard_metadata(&engine, &component, &limits, fuel_enabled)?
+                }
+                ToolWorld::Sandboxed => (
+                    extract_tool_description(&engine, &component)?,
+                    extract_tool_schema(&engine, &component)?,
+                ),
+            };
 
             Ok::<_, WasmError>(PreparedModule {
                 name: name.clone(),
                 description,
                 schema,
                 component_bytes: wasm_bytes,
-                limits: limits.unwrap_or(default_limits),
+                limits,
+                world,
+                linked: LinkedComponent::link(&engine, &component, world)?,
             })
         })
         .await
@@ -218,6 +309,7 @@ impl WasmToolRuntime {
 
         tracing::info!(
             name = %prepared.name,
+            world = prepared.world.as_str(),
             "Prepared WASM tool for execution"
         );
 
@@ -0,0 +1,163 @@
+//! Routines for WASM tools that declare a `schedule` capability.
+//!
+//! Installing such a tool registers a cron routine named `tool:<name>` that
+//! calls the tool directly, without an LLM, through the routine engine. Run
+//! history shows up in `ironclaw cron history tool:<name>` like any other
+//! routine. Reinstalling updates the schedule and keeps the routine's
+//! enabled flag and run count; removing the tool, or dropping the
+//! capability, removes the routine.
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::agent::routine::{
+    NotifyConfig, Routine, RoutineAction, RoutineGuardrails, Trigger, next_cron_fire,
+};
+use crate::db::Database;
+use crate::tools::wasm::ScheduleCapabilitySchema;
+
+/// Name of the routine registered for `tool_name`.
+pub fn schedule_routine_name(tool_name: &str) -> String {
+    format!("tool:{}", tool_name)
+}
+
+/// A new routine running `tool_name` on `schedule` for `user_id`.
+pub fn scheduled_tool_routine(
+    tool_name: &str,
+    schedule: &ScheduleCapabilitySchema,
+    user_id: &str,
+) -> Result<Routine, String> {
+    let next_fire_at = next_cron_fire(&schedule.cron, schedule.timezone.as_deref())?;
+    let now = Utc::now();
+    Ok(Routine {
+        id: Uuid::new_v4(),
+        name: schedule_routine_name(tool_name),
+        description: format!(
+            "Scheduled '{}' run of the {} tool",
+            schedule.entrypoint, tool_name
+        ),
+        user_id: user_id.to_string(),
+        enabled: true,
+        trigger: Trigger::Cron {
+            schedule: schedule.cron.clone(),
+            timezone: schedule.timezone.clone(),
+        },
+        action: RoutineAction::Tool {
+            tool: tool_name.to_string(),
+            params: schedule.call_params(),
+        },
+        guardrails: RoutineGuardrails::default(),
+        notify: NotifyConfig {
+            user: user_id.to_string(),
+            ..Default::default()
+        },
+        last_run_at: None,
+        next_fire_at,
+        run_count: 0,
+        consecutive_failures: 0,
+        state: serde_json::json!({}),
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+/// What [`sync_tool_schedule`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleSync {
+    /// A routine was created.
+    Registered(String),
+    /// The existing routine got the new schedule.
+    Updated(String),
+    /// The routine was removed.
+    Removed(String),
+    /// No schedule and no routine.
+    Unscheduled,
+}
+
+/// Make the routine for `tool_name` match `schedule`: create or update it
+/// when there is one, remove it when there is none.
+pub async fn sync_tool_schedule(
+    store: &dyn Database,
+    user_id: &str,
+    tool_name: &str,
+    schedule: Option<&ScheduleCapabilitySchema>,
+) -> Result<ScheduleSync, String> {
+    let name = schedule_routine_name(tool_name);
+    let existing = store
+        .get_routine_by_name(user_id, &name)
+        .await
+        .map_err(|e| format!("Failed to look up routine '{}': {}", name, e))?;
+
+    match (schedule, existing) {
+        (Some(schedule), None) => {
+            let routine = scheduled_tool_routine(tool_name, schedule, user_id)?;
+            store
+                .create_routine(&routine)
+                .await
+                .map_err(|e| format!("Failed to create routine '{}': {}", name, e))?;
+            Ok(ScheduleSync::Registered(name))
+        }
+        (Some(schedule), Some(existing)) => {
+            let fresh = scheduled_tool_routine(tool_name, schedule, user_id)?;
+            let routine = Routine {
+                id: existing.id,
+                enabled: existing.enabled,
+                notify: existing.notify,
+                guardrails: existing.guardrails,
+                last_run_at: existing.last_run_at,
+                run_count: existing.run_count,
+                consecutive_failures: existing.consecutive_failures,
+                state: existing.state,
+                created_at: existing.created_at,
+                ..fresh
+            };
+            store
+                .update_routine(&routine)
+                .await
+                .map_err(|e| format!("Failed to update routine '{}': {}", name, e))?;
+            Ok(ScheduleSync::Updated(name))
+        }
+        (None, Some(existing)) => {
+            store
+                .delete_routine(existing.id)
+                .await
+                .map_err(|e| format!("Failed to remove routine '{}': {}", name, e))?;
+            Ok(ScheduleSync::Removed(name))
+        }
+        (None, None) => Ok(ScheduleSync::Unscheduled),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(cron: &str) -> ScheduleCapabilitySchema {
+        ScheduleCapabilitySchema {
+            cron: cron.to_string(),
+            entrypoint: "sync".to_string(),
+            params: serde_json::Map::new(),
+            timezone: None,
+        }
+    }
+
+    #[test]
+    fn test_scheduled_tool_routine() {
+        let routine = scheduled_tool_routine("feeds", &schedule("0 0 7 * * *"), "default").unwrap();
+
+        assert_eq!(routine.name, "tool:feeds");
+        assert!(routine.next_fire_at.is_some());
+        match routine.action {
+            RoutineAction::Tool { tool, params } => {
+                assert_eq!(tool, "feeds");
+                assert_eq!(params["action"], "sync");
+            }
+            other => panic!("Expected Tool action, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_cron_is_rejected() {
+        assert!(scheduled_tool_routine("feeds", &schedule("whenever"), "default").is_err());
+    }
+}
@@ -93,6 +93,53 @@ impl std::str::FromStr for ToolStatus {
     }
 }
 
+/// Kind of misbehavior that earns a WASM tool a strike.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Misbehavior {
+    /// Execution trapped or panicked.
+    Trap,
+    /// An HTTP request or tool call outside the tool's allowlist.
+    AllowlistDenied,
+    /// The leak detector blocked an outgoing request or progress emission.
+    LeakDetected,
+    /// HTTP requests or tool calls beyond the tool's rate limit.
+    RateLimited,
+}
+
+impl std::fmt::Display for Misbehavior {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Misbehavior::Trap => write!(f, "trap"),
+            Misbehavior::AllowlistDenied => write!(f, "allowlist_denied"),
+            Misbehavior::LeakDetected => write!(f, "leak_detected"),
+            Misbehavior::RateLimited => write!(f, "rate_limited"),
+        }
+    }
+}
+
+impl std::str::FromStr for Misbehavior {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "trap" => Ok(Misbehavior::Trap),
+            "allowlist_denied" => Ok(Misbehavior::AllowlistDenied),
+            "leak_detected" => Ok(Misbehavior::LeakDetected),
+            "rate_limited" => Ok(Misbehavior::RateLimited),
+            _ => Err(format!("Unknown misbehavior: {}", s)),
+        }
+    }
+}
+
+/// One strike recorded against a tool.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ToolIncident {
+    pub kind: Misbehavior,
+    pub detail: String,
+    pub at: DateTime<Utc>,
+}
+
 /// A stored WASM tool.
 #[derive(Debug, Clone)]
 pub struct StoredWasmTool {
@@ -154,6 +201,9 @@ impl StoredCapabilities {
                 },
                 max_request_bytes: self.max_request_body_bytes as usize,
                 max_response_bytes: self.max_response_body_bytes as usize,
+                // Per-endpoint limits travel with `http_allowlist`.
+                allowed_content_types: Vec::new(),
+                max_redirects: 0,
                 timeout: std::time::Duration::from_secs(self.http_timeout_secs as u64),
             });
         }
@@ -237,6 +287,27 @@ pub trait WasmToolStore: Send + Sync {
 
     /// Delete a tool.
     async fn delete(&self, user_id: &str, name: &str) -> Result<bool, WasmStorageError>;
+
+    /// Record a strike against a tool. Returns the tool's strikes since they
+    /// were last cleared, this one included. Tools installed as files have
+    /// no stored row but still collect strikes by name.
+    async fn record_strike(
+        &self,
+        user_id: &str,
+        name: &str,
+        kind: Misbehavior,
+        detail: &str,
+    ) -> Result<u32, WasmStorageError>;
+
+    /// Strikes against a tool since they were last cleared, oldest first.
+    async fn strikes(
+        &self,
+        user_id: &str,
+        name: &str,
+    ) -> Result<Vec<ToolIncident>, WasmStorageError>;
+
+    /// Clear a tool's strikes, when it is let out of quarantine.
+    async fn clear_strikes(&self, user_id: &str, name: &str) -> Result<(), WasmStorageError>;
 }
 
 /// Parameters for storing a new tool.
@@ -540,6 +611,100 @@ impl WasmToolStore for PostgresWasmToolStore {
 
         Ok(result > 0)
     }
+
+    async fn record_strike(
+        &self,
+        user_id: &str,
+        name: &str,
+        kind: Misbehavior,
+        detail: &str,
+    ) -> Result<u32, WasmStorageError> {
+        let client = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| WasmStorageError::Database(e.to_string()))?;
+
+        client
+            .execute(
+                r#"
+                INSERT INTO wasm_tool_incidents (id, user_id, tool_name, kind, detail)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+                &[&Uuid::new_v4(), &user_id, &name, &kind.to_string(), &detail],
+            )
+            .await
+            .map_err(|e| WasmStorageError::Database(e.to_string()))?;
+
+        let row = client
+            .query_one(
+                r#"
+                SELECT COUNT(*) FROM wasm_tool_incidents
+                WHERE user_id = $1 AND tool_name = $2 AND cleared_at IS NULL
+                "#,
+                &[&user_id, &name],
+            )
+            .await
+            .map_err(|e| WasmStorageError::Database(e.to_string()))?;
+
+        Ok(row.get::<_, i64>(0) as u32)
+    }
+
+    async fn strikes(
+        &self,
+        user_id: &str,
+        name: &str,
+    ) -> Result<Vec<ToolIncident>, WasmStorageError> {
+        let client = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| WasmStorageError::Database(e.to_string()))?;
+
+        let rows = client
+            .query(
+                r#"
+                SELECT kind, detail, created_at FROM wasm_tool_incidents
+                WHERE user_id = $1 AND tool_name = $2 AND cleared_at IS NULL
+                ORDER BY created_at
+                "#,
+                &[&user_id, &name],
+            )
+            .await
+            .map_err(|e| WasmStorageError::Database(e.to_string()))?;
+
+        rows.into_iter()
+            .map(|r| {
+                let kind: String = r.get("kind");
+                Ok(ToolIncident {
+                    kind: kind.parse().map_err(WasmStorageError::InvalidData)?,
+                    detail: r.get("detail"),
+                    at: r.get("created_at"),
+                })
+            })
+            .collect()
+    }
+
+    async fn clear_strikes(&self, user_id: &str, name: &str) -> Result<(), WasmStorageError> {
+        let client = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| WasmStorageError::Database(e.to_string()))?;
+
+        client
+            .execute(
+                r#"
+                UPDATE wasm_tool_incidents SET cleared_at = NOW()
+                WHERE user_id = $1 AND tool_name = $2 AND cleared_at IS NULL
+                "#,
+                &[&user_id, &name],
+            )
+            .await
+            .map_err(|e| WasmStorageError::Database(e.to_string()))?;
+
+        Ok(())
+    }
 }
 
 #[cfg(feature = "postgres")]
@@ -906,6 +1071,114 @@ impl WasmToolStore for LibSqlWasmToolStore {
 
         Ok(result > 0)
     }
+
+    async fn record_strike(
+        &self,
+        user_id: &str,
+        name: &str,
+        kind: Misbehavior,
+        detail: &str,
+    ) -> Result<u32, WasmStorageError> {
+        let now = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        let conn = self.connect()?;
+
+        conn.execute(
+            r#"
+            INSERT INTO wasm_tool_incidents (id, user_id, tool_name, kind, detail, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+            libsql::params![
+                Uuid::new_v4().to_string(),
+                user_id,
+                name,
+                kind.to_string(),
+                detail,
+                now.as_str()
+            ],
+        )
+        .await
+        .map_err(|e| WasmStorageError::Database(e.to_string()))?;
+
+        let mut rows = conn
+            .query(
+                r#"
+                SELECT COUNT(*) FROM wasm_tool_incidents
+                WHERE user_id = ?1 AND tool_name = ?2 AND cleared_at IS NULL
+                "#,
+                libsql::params![user_id, name],
+            )
+            .await
+            .map_err(|e| WasmStorageError::Database(e.to_string()))?;
+        let count: i64 = match rows
+            .next()
+            .await
+            .map_err(|e| WasmStorageError::Database(e.to_string()))?
+        {
+            Some(row) => row
+                .get(0)
+                .map_err(|e| WasmStorageError::Database(e.to_string()))?,
+            None => 0,
+        };
+
+        Ok(count as u32)
+    }
+
+    async fn strikes(
+        &self,
+        user_id: &str,
+        name: &str,
+    ) -> Result<Vec<ToolIncident>, WasmStorageError> {
+        let conn = self.connect()?;
+        let mut rows = conn
+            .query(
+                r#"
+                SELECT kind, detail, created_at FROM wasm_tool_incidents
+                WHERE user_id = ?1 AND tool_name = ?2 AND cleared_at IS NULL
+                ORDER BY created_at
+                "#,
+                libsql::params![user_id, name],
+            )
+            .await
+            .map_err(|e| WasmStorageError::Database(e.to_string()))?;
+
+        let mut incidents = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| WasmStorageError::Database(e.to_string()))?
+        {
+            let kind: String = row
+                .get(0)
+                .map_err(|e| WasmStorageError::Database(e.to_string()))?;
+            let created_at: String = row
+                .get(2)
+                .map_err(|e| WasmStorageError::Database(e.to_string()))?;
+            incidents.push(ToolIncident {
+                kind: kind.parse().map_err(WasmStorageError::InvalidData)?,
+                detail: row
+                    .get(1)
+                    .map_err(|e| WasmStorageError::Database(e.to_string()))?,
+                at: libsql_wasm_parse_ts(&created_at)?,
+            });
+        }
+        Ok(incidents)
+    }
+
+    async fn clear_strikes(&self, user_id: &str, name: &str) -> Result<(), WasmStorageError> {
+        let now = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        let conn = self.connect()?;
+        conn.execute(
+            r#"
+            UPDATE wasm_tool_incidents SET cleared_at = ?1
+            WHERE user_id = ?2 AND tool_name = ?3 AND cleared_at IS NULL
+            "#,
+            libsql::params![now.as_str(), user_id, name],
+        )
+        .await
+        .map_err(|e| WasmStorageError::Database(e.to_string()))?;
+
+        Ok(())
+    }
 }
 
 #[cfg(feature = "libsql")]
@@ -1020,7 +1293,7 @@ fn libsql_row_to_tool_at(
 #[cfg(test)]
 mod tests {
     use crate::tools::wasm::storage::{
-        ToolStatus, TrustLevel, compute_binary_hash, verify_binary_integrity,
+        Misbehavior, ToolStatus, TrustLevel, compute_binary_hash, verify_binary_integrity,
     };
 
     #[test]
@@ -1069,4 +1342,17 @@ mod tests {
         );
         assert!("invalid".parse::<ToolStatus>().is_err());
     }
+
+    #[test]
+    fn test_misbehavior_round_trip() {
+        for kind in [
+            Misbehavior::Trap,
+            Misbehavior::AllowlistDenied,
+            Misbehavior::LeakDetected,
+            Misbehavior::RateLimited,
+        ] {
+            assert_eq!(kind.to_string().parse::<Misbehavior>().unwrap(), kind);
+        }
+        assert!("misbehaving".parse::<Misbehavior>().is_err());
+    }
 }
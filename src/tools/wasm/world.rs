@@ -0,0 +1,148 @@
+//! The WIT worlds a tool component can target.
+//!
+//! - [`ToolWorld::Sandboxed`]: `near:agent/sandboxed-tool` (`wit/tool.wit`),
+//!   with the full host API (HTTP, workspace, tool invoke, secrets).
+//! - [`ToolWorld::Standard`]: `ironclaw:tool/tool` (`wit/ironclaw-tool.wit`),
+//!   which imports only WASI 0.2, log and progress, so components built
+//!   with `cargo component`, `componentize-py` and similar tooling load
+//!   without the bespoke host ABI.
+//!
+//! The world is detected from the component's exports when it is prepared.
+
+use wasmtime::Engine;
+use wasmtime::component::Component;
+
+use crate::tools::wasm::error::WasmError;
+
+/// Bindings for the `ironclaw:tool` world. Kept in their own module so the
+/// generated `exports` tree does not clash with the `near:agent` bindings.
+pub(crate) mod bindings {
+    wasmtime::component::bindgen!({
+        path: "wit/ironclaw-tool.wit",
+        world: "tool",
+    });
+}
+
+/// Export name of the `near:agent` tool interface.
+const SANDBOXED_EXPORT: &str = "near:agent/tool";
+
+/// Export name of the `ironclaw:tool` handler interface, without version.
+const STANDARD_EXPORT: &str = "ironclaw:tool/handler";
+
+/// Which WIT world a tool component was built against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolWorld {
+    /// `near:agent/sandboxed-tool`.
+    Sandboxed,
+    /// `ironclaw:tool/tool`.
+    Standard,
+}
+
+impl ToolWorld {
+    /// Detect the world from the component's exported interfaces.
+    pub fn detect(engine: &Engine, component: &Component) -> Result<Self, WasmError> {
+        let component_type = component.component_type();
+        let mut standard = false;
+        for (name, _) in component_type.exports(engine) {
+            if name == SANDBOXED_EXPORT {
+                return Ok(Self::Sandboxed);
+            }
+            let unversioned = name.split_once('@').map_or(name, |(base, _)| base);
+            if unversioned == STANDARD_EXPORT {
+                standard = true;
+            }
+        }
+        if standard {
+            Ok(Self::Standard)
+        } else {
+            Err(WasmError::MissingExport(format!(
+                "component exports neither '{}' nor '{}'",
+                SANDBOXED_EXPORT, STANDARD_EXPORT
+            )))
+        }
+    }
+
+    /// The WIT world name, for logs.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sandboxed => "near:agent/sandboxed-tool",
+            Self::Standard => "ironclaw:tool/tool",
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// An `ironclaw:tool` component that echoes `params` back, or returns an
+    /// error when no context is passed.
+    pub(crate) const ECHO_COMPONENT: &str = r#"
+(component
+  (core module $m
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 4096))
+    (data (i32.const 16) "Echoes its input")
+    (data (i32.const 64) "{\"type\":\"object\"}")
+    (data (i32.const 128) "no context")
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr (global.get $heap))
+      (global.set $heap (i32.add (global.get $heap) (local.get 3)))
+      (local.get $ptr))
+    (func (export "description") (result i32)
+      (i32.store (i32.const 512) (i32.const 16))
+      (i32.store (i32.const 516) (i32.const 16))
+      (i32.const 512))
+    (func (export "schema") (result i32)
+      (i32.store (i32.const 520) (i32.const 64))
+      (i32.store (i32.const 524) (i32.const 17))
+      (i32.const 520))
+    (func (export "execute")
+      (param $ptr i32) (param $len i32) (param $has_context i32) (param i32) (param i32)
+      (result i32)
+      (if (i32.eqz (local.get $has_context))
+        (then
+          (i32.store8 (i32.const 528) (i32.const 1))
+          (i32.store (i32.const 532) (i32.const 128))
+          (i32.store (i32.const 536) (i32.const 10)))
+        (else
+          (i32.store8 (i32.const 528) (i32.const 0))
+          (i32.store (i32.const 532) (local.get $ptr))
+          (i32.store (i32.const 536) (local.get $len))))
+      (i32.const 528))
+  )
+  (core instance $i (instantiate $m))
+  (func $description (result string)
+    (canon lift (core func $i "description") (memory $i "memory")))
+  (func $schema (result string)
+    (canon lift (core func $i "schema") (memory $i "memory")))
+  (func $execute
+    (param "params" string) (param "context" (option string))
+    (result (result string (error string)))
+    (canon lift (core func $i "execute") (memory $i "memory") (realloc (func $i "realloc"))))
+  (instance $handler
+    (export "description" (func $description))
+    (export "schema" (func $schema))
+    (export "execute" (func $execute)))
+  (export "ironclaw:tool/handler@0.1.0" (instance $handler))
+)
+"#;
+
+    #[test]
+    fn test_detect_world() {
+        let engine = Engine::default();
+
+        let standard = Component::new(&engine, ECHO_COMPONENT).unwrap();
+        assert_eq!(
+            ToolWorld::detect(&engine, &standard).unwrap(),
+            ToolWorld::Standard
+        );
+
+        let empty = Component::new(&engine, "(component)").unwrap();
+        assert!(matches!(
+            ToolWorld::detect(&engine, &empty),
+            Err(WasmError::MissingExport(_))
+        ));
+    }
+}
@@ -2,28 +2,47 @@
 //!
 //! Uses wasmtime::component::bindgen! to generate typed bindings from the WIT
 //! interface, ensuring all host functions are properly registered under the
-//! correct `near:agent/host` namespace.
+//! correct `near:agent/host` namespace. Components built against the
+//! standard `ironclaw:tool` world (see [`crate::tools::wasm::ToolWorld`]) are
+//! linked with WASI and the `ironclaw:tool` log and progress interfaces only.
 //!
-//! Each execution creates a fresh instance (NEAR pattern) to ensure
-//! isolation and deterministic behavior.
+//! Each execution runs in a fresh instance (NEAR pattern) to ensure
+//! isolation and deterministic behavior. Components are compiled and linked
+//! once, when prepared. When the runtime's `warm_instances` is set, each
+//! tool also keeps that many instances created ahead of time: a call takes
+//! one instead of instantiating, the instance is dropped afterwards, and a
+//! replacement is built in the background.
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
-use wasmtime::Store;
 use wasmtime::component::{Component, HasSelf, Linker};
+use wasmtime::{Engine, Store};
 use wasmtime_wasi::{ResourceTable, WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView};
 
 use crate::context::JobContext;
 use crate::safety::LeakDetector;
-use crate::tools::tool::{Tool, ToolError, ToolOutput};
+use crate::secrets::SecretsStore;
+use crate::tools::tool::{ProgressSender, Tool, ToolError, ToolOutput};
 use crate::tools::wasm::capabilities::Capabilities;
+use crate::tools::wasm::credential_injector::CredentialInjector;
+use crate::tools::wasm::dry_run::{Attempt, DryRunReport, HttpAttempt};
 use crate::tools::wasm::error::WasmError;
 use crate::tools::wasm::host::{HostState, LogLevel};
+use crate::tools::wasm::http_limits::{
+    HttpLimitError, ResponseLimits, redirect_becomes_get, redirect_target,
+};
 use crate::tools::wasm::limits::{ResourceLimits, WasmResourceLimiter};
+use crate::tools::wasm::quarantine::MisbehaviorTracker;
 use crate::tools::wasm::runtime::{EPOCH_TICK_INTERVAL, PreparedModule, WasmToolRuntime};
+use crate::tools::wasm::storage::Misbehavior;
+use crate::tools::wasm::world::ToolWorld;
+use crate::tools::wasm::world::bindings::Tool as StandardTool;
+use crate::tools::wasm::world::bindings::ToolPre as StandardToolPre;
+use crate::tools::wasm::world::bindings::ironclaw::tool::log as standard_log;
+use crate::tools::wasm::world::bindings::ironclaw::tool::progress as standard_progress;
 
 // Generate component model bindings from the WIT file.
 //
@@ -43,7 +62,7 @@ use exports::near::agent::tool as wit_tool;
 ///
 /// Contains the resource limiter, host state, WASI context, and injected
 /// credentials. Fresh instance created per execution (NEAR pattern).
-struct StoreData {
+pub(crate) struct StoreData {
     limiter: WasmResourceLimiter,
     host_state: HostState,
     wasi: WasiCtx,
@@ -51,6 +70,25 @@ struct StoreData {
     /// Injected credentials for URL/header substitution.
     /// Keys are placeholder names like "GOOGLE_ACCESS_TOKEN".
     credentials: HashMap<String, String>,
+    /// Set for dry runs: host functions record the call and return a stub.
+    dry_run: Option<DryRunReport>,
+    /// Where accepted progress emissions go, for streaming executions.
+    progress: Option<ProgressSender>,
+    /// Host calls refused during this execution, for strike counting.
+    misbehavior: Vec<(Misbehavior, String)>,
+    /// Resolves the tool's mapped secrets for the calling user.
+    host_credentials: Option<HostCredentials>,
+    /// Secret values injected so far, kept only to redact them.
+    injected: Vec<String>,
+}
+
+/// A tool's credential injector, the store it reads and the user it reads
+/// for. Secrets are resolved per request host, at the host boundary.
+#[derive(Clone)]
+struct HostCredentials {
+    injector: Arc<CredentialInjector>,
+    store: Arc<dyn SecretsStore + Send + Sync>,
+    user_id: String,
 }
 
 impl StoreData {
@@ -68,9 +106,20 @@ impl StoreData {
             wasi,
             table: ResourceTable::new(),
             credentials,
+            dry_run: None,
+            progress: None,
+            misbehavior: Vec::new(),
+            host_credentials: None,
+            injected: Vec::new(),
         }
     }
 
+    /// Note a refused host call and pass its error on.
+    fn strike(&mut self, kind: Misbehavior, error: String) -> String {
+        self.misbehavior.push((kind, error.clone()));
+        error
+    }
+
     /// Inject credentials into a string by replacing placeholders.
     ///
     /// Replaces patterns like `{GOOGLE_ACCESS_TOKEN}` with actual values.
@@ -93,6 +142,83 @@ impl StoreData {
         result
     }
 
+    /// Add the secrets mapped to the request's host, resolved for the
+    /// calling user: headers, query parameters and URL placeholders.
+    /// Only secrets granted to this tool are injected.
+    fn inject_host_credentials(
+        &mut self,
+        url: String,
+        headers: &mut HashMap<String, String>,
+    ) -> Result<String, String> {
+        let Some(credentials) = self.host_credentials.clone() else {
+            return Ok(url);
+        };
+        let Some(host) = reqwest::Url::parse(&url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+        else {
+            return Ok(url);
+        };
+
+        // We're inside a spawn_blocking context, so use block_on.
+        let injected = tokio::runtime::Handle::current()
+            .block_on(credentials.injector.inject(
+                &credentials.user_id,
+                &host,
+                credentials.store.as_ref(),
+            ))
+            .map_err(|e| format!("Credential injection failed: {}", e))?;
+        if injected.is_empty() {
+            return Ok(url);
+        }
+        self.injected.extend(
+            injected
+                .values()
+                .filter(|v| !v.is_empty())
+                .map(str::to_string),
+        );
+
+        let mut url = url;
+        for (placeholder, value) in &injected.placeholders {
+            url = url.replace(placeholder, value);
+        }
+        if !injected.query_params.is_empty() {
+            let mut parsed =
+                reqwest::Url::parse(&url).map_err(|e| format!("Invalid URL: {}", e))?;
+            parsed
+                .query_pairs_mut()
+                .extend_pairs(&injected.query_params);
+            url = parsed.to_string();
+        }
+        headers.extend(injected.headers);
+        Ok(url)
+    }
+
+    /// Check a progress emission against the capability, scan it for leaks
+    /// and forward it.
+    fn emit_progress(&mut self, json: String) -> Result<(), String> {
+        self.host_state.record_progress(json.len())?;
+        serde_json::from_str::<serde_json::Value>(&json)
+            .map_err(|e| format!("Invalid progress JSON: {}", e))?;
+        let cleaned = LeakDetector::new()
+            .scan_and_clean(&self.redact_credentials(&json))
+            .map_err(|e| {
+                self.strike(
+                    Misbehavior::LeakDetected,
+                    format!("Potential secret leak blocked: {}", e),
+                )
+            })?;
+        if self.dry_run.is_some() {
+            return Ok(());
+        }
+        if let Some(progress) = &self.progress {
+            let value =
+                serde_json::from_str(&cleaned).unwrap_or(serde_json::Value::String(cleaned));
+            let _ = progress.send(value);
+        }
+        Ok(())
+    }
+
     /// Replace injected credential values with `[REDACTED]` in text.
     ///
     /// Prevents credentials from leaking through error messages or logs.
@@ -106,6 +232,9 @@ impl StoreData {
                 result = result.replace(value, &format!("[REDACTED:{}]", name));
             }
         }
+        for value in &self.injected {
+            result = result.replace(value, "[REDACTED]");
+        }
         result
     }
 }
@@ -121,10 +250,31 @@ impl WasiView for StoreData {
     }
 }
 
+// The host interfaces of the `ironclaw:tool` world.
+impl standard_log::Host for StoreData {
+    fn log(&mut self, level: standard_log::Level, message: String) {
+        let log_level = match level {
+            standard_log::Level::Trace => LogLevel::Trace,
+            standard_log::Level::Debug => LogLevel::Debug,
+            standard_log::Level::Info => LogLevel::Info,
+            standard_log::Level::Warn => LogLevel::Warn,
+            standard_log::Level::Error => LogLevel::Error,
+        };
+        let _ = self.host_state.log(log_level, message);
+    }
+}
+
+impl standard_progress::Host for StoreData {
+    fn emit(&mut self, json: String) -> Result<(), String> {
+        self.emit_progress(json)
+    }
+}
+
 // Implement the generated Host trait from bindgen.
 //
-// This registers all 6 host functions under the `near:agent/host` namespace:
-// log, now-millis, workspace-read, http-request, secret-exists, tool-invoke
+// This registers all 7 host functions under the `near:agent/host` namespace:
+// log, now-millis, workspace-read, http-request, secret-exists, tool-invoke,
+// emit-progress
 impl near::agent::host::Host for StoreData {
     fn log(&mut self, level: near::agent::host::LogLevel, message: String) {
         let log_level = match level {
@@ -142,6 +292,16 @@ impl near::agent::host::Host for StoreData {
     }
 
     fn workspace_read(&mut self, path: String) -> Option<String> {
+        if self.dry_run.is_some() {
+            let allowed = self.host_state.workspace_read_allowed(&path);
+            if let Some(report) = self.dry_run.as_mut() {
+                report.workspace.push(Attempt {
+                    target: path,
+                    allowed,
+                });
+            }
+            return None;
+        }
         self.host_state.workspace_read(&path).ok().flatten()
     }
 
@@ -153,32 +313,54 @@ impl near::agent::host::Host for StoreData {
         body: Option<Vec<u8>>,
         timeout_ms: Option<u32>,
     ) -> Result<near::agent::host::HttpResponse, String> {
+        if self.dry_run.is_some() {
+            let allowed = self.host_state.check_http_allowed(&url, &method).is_ok();
+            if let Some(report) = self.dry_run.as_mut() {
+                report.http.push(HttpAttempt {
+                    method,
+                    url,
+                    allowed,
+                });
+            }
+            return Ok(near::agent::host::HttpResponse {
+                status: 200,
+                headers_json: "{}".to_string(),
+                body: b"{}".to_vec(),
+            });
+        }
+
         // Inject credentials into URL (e.g., replace {TELEGRAM_BOT_TOKEN})
         let injected_url = self.inject_credentials(&url, "url");
 
         // Check HTTP allowlist
-        self.host_state
-            .check_http_allowed(&injected_url, &method)
-            .map_err(|e| format!("HTTP not allowed: {}", e))?;
+        if let Err(e) = self.host_state.check_http_allowed(&injected_url, &method) {
+            let error = format!("HTTP not allowed: {}", self.redact_credentials(&e));
+            return Err(self.strike(Misbehavior::AllowlistDenied, error));
+        }
 
         // Record for rate limiting
-        self.host_state
-            .record_http_request()
-            .map_err(|e| format!("Rate limit exceeded: {}", e))?;
+        if let Err(e) = self.host_state.record_http_request() {
+            return Err(self.strike(
+                Misbehavior::RateLimited,
+                format!("Rate limit exceeded: {}", e),
+            ));
+        }
 
-        // Parse headers and inject credentials into header values
+        // Parse headers and inject credentials into header values. Headers
+        // without a credential are kept apart so a redirect to another host
+        // can be followed without handing it the tool's secrets.
         let raw_headers: HashMap<String, String> =
             serde_json::from_str(&headers_json).unwrap_or_default();
 
-        let headers: HashMap<String, String> = raw_headers
-            .into_iter()
-            .map(|(k, v)| {
-                (
-                    k.clone(),
-                    self.inject_credentials(&v, &format!("header:{}", k)),
-                )
-            })
-            .collect();
+        let mut headers: HashMap<String, String> = HashMap::new();
+        let mut plain_headers: HashMap<String, String> = HashMap::new();
+        for (k, v) in raw_headers {
+            let injected = self.inject_credentials(&v, &format!("header:{}", k));
+            if injected == v {
+                plain_headers.insert(k.clone(), v);
+            }
+            headers.insert(k, injected);
+        }
 
         let url = injected_url;
         let leak_detector = LeakDetector::new();
@@ -187,60 +369,120 @@ impl near::agent::host::Host for StoreData {
             .map(|(k, v)| (k.clone(), v.clone()))
             .collect();
 
-        leak_detector
-            .scan_http_request(&url, &header_vec, body.as_deref())
-            .map_err(|e| format!("Potential secret leak blocked: {}", e))?;
+        if let Err(e) = leak_detector.scan_http_request(&url, &header_vec, body.as_deref()) {
+            let error = format!("Potential secret leak blocked: {}", e);
+            return Err(self.strike(Misbehavior::LeakDetected, self.redact_credentials(&error)));
+        }
 
-        // Get the max response size from capabilities (default 10MB).
-        let max_response_bytes = self
-            .host_state
-            .capabilities()
-            .http
-            .as_ref()
-            .map(|h| h.max_response_bytes)
-            .unwrap_or(10 * 1024 * 1024);
+        // Secrets the host holds for this tool go in after the leak scan,
+        // and only into headers dropped on a redirect to another host.
+        let url = self
+            .inject_host_credentials(url, &mut headers)
+            .map_err(|e| self.redact_credentials(&e))?;
+
+        // Size, content-type and redirect limits for this endpoint.
+        let http_capability = self.host_state.capabilities().http.as_ref();
+        let max_redirects = ResponseLimits::resolve(http_capability, &url, &method).max_redirects;
 
         // Resolve hostname and reject private/internal IPs to prevent DNS rebinding.
         reject_private_ip(&url)?;
 
         // Make HTTP request using blocking I/O.
         // We're inside a spawn_blocking context, so use block_on.
+        let host_state = &self.host_state;
         let result = tokio::runtime::Handle::current().block_on(async {
-            let client = reqwest::Client::builder()
+            let client = crate::outbound::client_builder()
                 .redirect(reqwest::redirect::Policy::none())
                 .build()
                 .map_err(|e| format!("failed to create HTTP client: {e}"))?;
 
-            let mut request = match method.to_uppercase().as_str() {
-                "GET" => client.get(&url),
-                "POST" => client.post(&url),
-                "PUT" => client.put(&url),
-                "DELETE" => client.delete(&url),
-                "PATCH" => client.patch(&url),
-                "HEAD" => client.head(&url),
-                _ => return Err(format!("Unsupported HTTP method: {}", method)),
-            };
+            // Caller-specified timeout (default 30s)
+            let timeout = Duration::from_millis(timeout_ms.unwrap_or(30_000) as u64);
 
-            for (key, value) in headers {
-                request = request.header(&key, &value);
-            }
+            // Redirects are followed here rather than by reqwest so every hop
+            // goes through the allowlist and the private-IP check.
+            let mut url = url;
+            let mut method = method;
+            let mut body = body;
+            let mut redirects = 0;
+            let response = loop {
+                let mut request = match method.to_uppercase().as_str() {
+                    "GET" => client.get(&url),
+                    "POST" => client.post(&url),
+                    "PUT" => client.put(&url),
+                    "DELETE" => client.delete(&url),
+                    "PATCH" => client.patch(&url),
+                    "HEAD" => client.head(&url),
+                    _ => return Err(format!("Unsupported HTTP method: {}", method)),
+                };
+
+                for (key, value) in &headers {
+                    request = request.header(key, value);
+                }
 
-            if let Some(body_bytes) = body {
-                request = request.body(body_bytes);
-            }
+                if let Some(ref body_bytes) = body {
+                    request = request.body(body_bytes.clone());
+                }
 
-            // Caller-specified timeout (default 30s)
-            let timeout = Duration::from_millis(timeout_ms.unwrap_or(30_000) as u64);
-            let response = request.timeout(timeout).send().await.map_err(|e| {
-                // Walk the full error chain for the actual root cause
-                let mut chain = format!("HTTP request failed: {}", e);
-                let mut source = std::error::Error::source(&e);
-                while let Some(cause) = source {
-                    chain.push_str(&format!(" -> {}", cause));
-                    source = cause.source();
+                let response = request.timeout(timeout).send().await.map_err(|e| {
+                    // Walk the full error chain for the actual root cause
+                    let mut chain = format!("HTTP request failed: {}", e);
+                    let mut source = std::error::Error::source(&e);
+                    while let Some(cause) = source {
+                        chain.push_str(&format!(" -> {}", cause));
+                        source = cause.source();
+                    }
+                    chain
+                })?;
+
+                let status = response.status();
+                let location = match response
+                    .headers()
+                    .get(reqwest::header::LOCATION)
+                    .and_then(|v| v.to_str().ok())
+                {
+                    Some(location) if status.is_redirection() && max_redirects > 0 => {
+                        location.to_string()
+                    }
+                    _ => break response,
+                };
+                if redirects == max_redirects {
+                    return Err(HttpLimitError::TooManyRedirects {
+                        limit: max_redirects,
+                    }
+                    .to_string());
                 }
-                chain
-            })?;
+
+                let next = redirect_target(&url, &location).ok_or_else(|| {
+                    HttpLimitError::RedirectNotAllowed {
+                        location: location.clone(),
+                        reason: "invalid location".to_string(),
+                    }
+                    .to_string()
+                })?;
+                let denied = |reason: String| {
+                    HttpLimitError::RedirectNotAllowed {
+                        location: next.clone(),
+                        reason,
+                    }
+                    .to_string()
+                };
+                if redirect_becomes_get(&method, status.as_u16()) {
+                    method = "GET".to_string();
+                    body = None;
+                }
+                host_state
+                    .check_http_allowed(&next, &method)
+                    .map_err(&denied)?;
+                reject_private_ip(&next).map_err(&denied)?;
+
+                let host_of = |u: &str| reqwest::Url::parse(u).ok()?.host_str().map(String::from);
+                if host_of(&next) != host_of(&url) {
+                    headers = std::mem::take(&mut plain_headers);
+                }
+                url = next;
+                redirects += 1;
+            };
 
             let status = response.status().as_u16();
             let response_headers: HashMap<String, String> = response
@@ -254,30 +496,25 @@ impl near::agent::host::Host for StoreData {
                 .collect();
             let headers_json = serde_json::to_string(&response_headers).unwrap_or_default();
 
-            // Check Content-Length header for early rejection of oversized responses.
-            let max_response = max_response_bytes;
-            if let Some(cl) = response.content_length()
-                && cl as usize > max_response
-            {
-                return Err(format!(
-                    "Response body too large: {} bytes exceeds limit of {} bytes",
-                    cl, max_response
-                ));
+            // Limits of the endpoint that produced the response, which after a
+            // redirect may not be the one first asked for.
+            let limits = ResponseLimits::resolve(http_capability, &url, &method);
+            let empty = status == 204
+                || status == 304
+                || method.eq_ignore_ascii_case("HEAD")
+                || response.content_length() == Some(0);
+            if !empty {
+                let content_type = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok());
+                limits
+                    .check_content_type(content_type)
+                    .map_err(|e| e.to_string())?;
             }
 
             // Read body with a size cap to prevent memory exhaustion.
-            let body = response
-                .bytes()
-                .await
-                .map_err(|e| format!("Failed to read response body: {}", e))?;
-            if body.len() > max_response {
-                return Err(format!(
-                    "Response body too large: {} bytes exceeds limit of {} bytes",
-                    body.len(),
-                    max_response
-                ));
-            }
-            let body = body.to_vec();
+            let body = limits.read_body(response).await?;
 
             // Leak detection on response body
             if let Ok(body_str) = std::str::from_utf8(&body) {
@@ -298,9 +535,25 @@ impl near::agent::host::Host for StoreData {
     }
 
     fn tool_invoke(&mut self, alias: String, _params_json: String) -> Result<String, String> {
+        if self.dry_run.is_some() {
+            let allowed = self.host_state.check_tool_invoke_allowed(&alias).is_ok();
+            if let Some(report) = self.dry_run.as_mut() {
+                report.tool_invocations.push(Attempt {
+                    target: alias,
+                    allowed,
+                });
+            }
+            return Err("Tool invocation is stubbed in a dry run".to_string());
+        }
+
         // Validate capability and resolve alias
-        let _real_name = self.host_state.check_tool_invoke_allowed(&alias)?;
-        self.host_state.record_tool_invoke()?;
+        let _real_name = self
+            .host_state
+            .check_tool_invoke_allowed(&alias)
+            .map_err(|e| self.strike(Misbehavior::AllowlistDenied, e))?;
+        self.host_state
+            .record_tool_invoke()
+            .map_err(|e| self.strike(Misbehavior::RateLimited, e))?;
 
         // Tool invocation requires async context and access to the tool registry,
         // which aren't available inside a synchronous WASM callback.
@@ -308,13 +561,96 @@ impl near::agent::host::Host for StoreData {
     }
 
     fn secret_exists(&mut self, name: String) -> bool {
+        if self.dry_run.is_some() {
+            let allowed = self.host_state.secret_exists(&name);
+            if let Some(report) = self.dry_run.as_mut() {
+                report.secrets.push(Attempt {
+                    target: name,
+                    allowed,
+                });
+            }
+            return true;
+        }
         self.host_state.secret_exists(&name)
     }
+
+    fn emit_progress(&mut self, json: String) -> Result<(), String> {
+        StoreData::emit_progress(self, json)
+    }
+}
+
+/// A component linked against its world's host functions, so instances are
+/// created without compiling or resolving imports again.
+pub(crate) enum LinkedComponent {
+    Sandboxed(SandboxedToolPre<StoreData>),
+    Standard(StandardToolPre<StoreData>),
+}
+
+impl LinkedComponent {
+    /// Link `component` for `world`. Fails if the component imports
+    /// anything the world's host does not provide.
+    pub(crate) fn link(
+        engine: &Engine,
+        component: &Component,
+        world: ToolWorld,
+    ) -> Result<Self, WasmError> {
+        let mut linker = Linker::new(engine);
+        WasmToolWrapper::add_host_functions(&mut linker, world)?;
+        let pre = linker
+            .instantiate_pre(component)
+            .map_err(|e| WasmError::InstantiationFailed(e.to_string()))?;
+        match world {
+            ToolWorld::Sandboxed => SandboxedToolPre::new(pre).map(Self::Sandboxed),
+            ToolWorld::Standard => StandardToolPre::new(pre).map(Self::Standard),
+        }
+        .map_err(|e| WasmError::InstantiationFailed(e.to_string()))
+    }
+
+    fn instantiate(&self, store: &mut Store<StoreData>) -> Result<ToolInstance, WasmError> {
+        match self {
+            Self::Sandboxed(pre) => pre.instantiate(store).map(ToolInstance::Sandboxed),
+            Self::Standard(pre) => pre.instantiate(store).map(ToolInstance::Standard),
+        }
+        .map_err(|e| WasmError::InstantiationFailed(e.to_string()))
+    }
+}
+
+enum ToolInstance {
+    Sandboxed(SandboxedTool),
+    Standard(StandardTool),
+}
+
+/// An instance created ahead of time, with its store.
+struct WarmInstance {
+    store: Store<StoreData>,
+    instance: ToolInstance,
+}
+
+/// Instances waiting for a call. Each serves exactly one.
+#[derive(Default)]
+struct WarmPool {
+    ready: Mutex<Vec<WarmInstance>>,
+}
+
+impl WarmPool {
+    fn take(&self) -> Option<WarmInstance> {
+        self.ready.lock().ok()?.pop()
+    }
+
+    fn len(&self) -> usize {
+        self.ready.lock().map_or(0, |ready| ready.len())
+    }
+
+    fn put(&self, warm: WarmInstance) {
+        if let Ok(mut ready) = self.ready.lock() {
+            ready.push(warm);
+        }
+    }
 }
 
 /// A Tool implementation backed by a WASM component.
 ///
-/// Each call to `execute` creates a fresh instance for isolation.
+/// Each call to `execute` runs in a fresh instance for isolation.
 pub struct WasmToolWrapper {
     /// Runtime for engine access.
     runtime: Arc<WasmToolRuntime>,
@@ -329,6 +665,13 @@ pub struct WasmToolWrapper {
     /// Injected credentials for HTTP requests (e.g., OAuth tokens).
     /// Keys are placeholder names like "GOOGLE_ACCESS_TOKEN".
     credentials: HashMap<String, String>,
+    /// Injects the secrets mapped in the HTTP capability, read from the
+    /// store for the calling user.
+    injector: Option<(Arc<CredentialInjector>, Arc<dyn SecretsStore + Send + Sync>)>,
+    /// Instances created ahead of time, up to the runtime's `warm_instances`.
+    warm: Arc<WarmPool>,
+    /// Counts strikes and quarantines the tool when there are too many.
+    tracker: Option<Arc<MisbehaviorTracker>>,
 }
 
 impl WasmToolWrapper {
@@ -345,6 +688,9 @@ impl WasmToolWrapper {
             prepared,
             capabilities,
             credentials: HashMap::new(),
+            injector: None,
+            warm: Arc::new(WarmPool::default()),
+            tracker: None,
         }
     }
 
@@ -366,110 +712,450 @@ impl WasmToolWrapper {
         self
     }
 
+    /// Inject the secrets mapped in the tool's HTTP capability, read from
+    /// `store` for the user running the tool. A secret is only injected if
+    /// its owner granted it to this tool (see
+    /// [`CredentialInjector`](crate::tools::wasm::CredentialInjector)).
+    pub fn with_secrets(mut self, store: Arc<dyn SecretsStore + Send + Sync>) -> Self {
+        let mappings = self
+            .capabilities
+            .http
+            .as_ref()
+            .map(|http| http.credentials.clone())
+            .unwrap_or_default();
+        if mappings.is_empty() {
+            return self;
+        }
+        let allowed = mappings.values().map(|m| m.secret_name.clone()).collect();
+        let injector = CredentialInjector::new(self.prepared.name.clone(), mappings, allowed);
+        self.injector = Some((Arc::new(injector), store));
+        self
+    }
+
+    /// Report misbehavior to `tracker`, which may quarantine the tool.
+    pub fn with_misbehavior_tracker(mut self, tracker: Arc<MisbehaviorTracker>) -> Self {
+        self.tracker = Some(tracker);
+        self
+    }
+
+    /// Fill the warm pool in the background. Call once the wrapper is fully
+    /// configured; instances carry the capabilities and credentials they
+    /// were created with. Does nothing outside a Tokio runtime or when the
+    /// runtime keeps no warm instances.
+    pub fn warm_up(&self) {
+        if self.runtime.config().warm_instances == 0 {
+            return;
+        }
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let wrapper = self.clone_for_blocking();
+        handle.spawn_blocking(move || wrapper.refill_warm_pool());
+    }
+
+    /// Number of instances waiting in the warm pool.
+    pub fn warm_instances(&self) -> usize {
+        self.warm.len()
+    }
+
+    /// Top the warm pool up to the runtime's `warm_instances`.
+    fn refill_warm_pool(&self) {
+        let target = self.runtime.config().warm_instances;
+        while self.warm.len() < target {
+            let warm = self.new_store(self.store_data()).and_then(|mut store| {
+                let instance = self.prepared.linked().instantiate(&mut store)?;
+                Ok(WarmInstance { store, instance })
+            });
+            match warm {
+                Ok(warm) => self.warm.put(warm),
+                Err(e) => {
+                    tracing::debug!(
+                        name = %self.prepared.name,
+                        "Failed to create warm instance: {}",
+                        e
+                    );
+                    return;
+                }
+            }
+        }
+    }
+
+    /// A copy sharing the prepared module and warm pool, for blocking tasks.
+    fn clone_for_blocking(&self) -> Self {
+        Self {
+            runtime: Arc::clone(&self.runtime),
+            prepared: Arc::clone(&self.prepared),
+            capabilities: self.capabilities.clone(),
+            description: self.description.clone(),
+            schema: self.schema.clone(),
+            credentials: self.credentials.clone(),
+            injector: self.injector.clone(),
+            warm: Arc::clone(&self.warm),
+            tracker: self.tracker.clone(),
+        }
+    }
+
+    /// Fresh store data for one execution.
+    fn store_data(&self) -> StoreData {
+        StoreData::new(
+            self.prepared.limits.memory_bytes,
+            self.capabilities.clone(),
+            self.credentials.clone(),
+        )
+    }
+
     /// Get the resource limits for this tool.
     pub fn limits(&self) -> &ResourceLimits {
         &self.prepared.limits
     }
 
-    /// Add all host functions to the linker using generated bindings.
+    /// Add the host functions for `world` to the linker using generated bindings.
     ///
-    /// Uses the bindgen-generated `add_to_linker` function to properly register
-    /// all host functions with correct component model signatures under the
-    /// `near:agent/host` namespace.
-    fn add_host_functions(linker: &mut Linker<StoreData>) -> Result<(), WasmError> {
+    /// Uses the bindgen-generated `add_to_linker` functions to properly register
+    /// all host functions with correct component model signatures: the
+    /// `near:agent/host` namespace for sandboxed tools, only the
+    /// `ironclaw:tool` log and progress interfaces for standard ones.
+    fn add_host_functions(
+        linker: &mut Linker<StoreData>,
+        world: ToolWorld,
+    ) -> Result<(), WasmError> {
         // Add WASI support (required by components built with wasm32-wasip2)
         wasmtime_wasi::p2::add_to_linker_sync(linker)
             .map_err(|e| WasmError::ConfigError(format!("Failed to add WASI functions: {}", e)))?;
 
         // Add our custom host interface using the generated add_to_linker
-        near::agent::host::add_to_linker::<_, HasSelf<_>>(linker, |state| state)
-            .map_err(|e| WasmError::ConfigError(format!("Failed to add host functions: {}", e)))?;
+        match world {
+            ToolWorld::Sandboxed => {
+                near::agent::host::add_to_linker::<_, HasSelf<_>>(linker, |state| state)
+            }
+            ToolWorld::Standard => {
+                standard_log::add_to_linker::<_, HasSelf<_>>(linker, |state| state).and_then(|()| {
+                    standard_progress::add_to_linker::<_, HasSelf<_>>(linker, |state| state)
+                })
+            }
+        }
+        .map_err(|e| WasmError::ConfigError(format!("Failed to add host functions: {}", e)))?;
 
         Ok(())
     }
 
+    /// Run the tool on a blocking thread under its timeout, forwarding
+    /// progress emissions to `progress` when given.
+    async fn run(
+        &self,
+        params: serde_json::Value,
+        ctx: &JobContext,
+        progress: Option<ProgressSender>,
+    ) -> Result<ToolOutput, ToolError> {
+        if let Some(ref tracker) = self.tracker
+            && tracker.is_quarantined(&self.prepared.name)
+        {
+            return Err(WasmError::Quarantined(self.prepared.name.clone()).into());
+        }
+
+        let start = Instant::now();
+        let timeout = self.prepared.limits.timeout;
+
+        // Serialize context for WASM
+        let context_json = serde_json::to_string(ctx).ok();
+        let host_credentials = self
+            .injector
+            .as_ref()
+            .map(|(injector, store)| HostCredentials {
+                injector: Arc::clone(injector),
+                store: Arc::clone(store),
+                user_id: ctx.user_id.clone(),
+            });
+
+        // Clone what we need for the blocking task
+        let wrapper = self.clone_for_blocking();
+
+        // Execute in blocking task with timeout
+        let result = tokio::time::timeout(timeout, async move {
+            tokio::task::spawn_blocking(move || {
+                wrapper.execute_sync(params, context_json, progress, host_credentials)
+            })
+            .await
+            .map_err(|e| WasmError::ExecutionPanicked(e.to_string()))
+        })
+        .await;
+
+        let duration = start.elapsed();
+
+        // Replace the instance this call used, off the response path.
+        if self.warm.len() < self.runtime.config().warm_instances {
+            self.warm_up();
+        }
+
+        let (result, mut misbehavior) = match result {
+            Ok(Ok((result, misbehavior))) => (result, misbehavior),
+            Ok(Err(e)) => (Err(e), Vec::new()),
+            Err(_) => (Err(WasmError::Timeout(timeout)), Vec::new()),
+        };
+        if let Err(WasmError::Trapped(ref trap)) = result {
+            misbehavior.push((Misbehavior::Trap, trap.clone()));
+        }
+        if let Some(ref tracker) = self.tracker
+            && !misbehavior.is_empty()
+        {
+            tracker.record(&self.prepared.name, &misbehavior).await;
+        }
+
+        match result {
+            Ok((result_json, logs)) => {
+                // Emit collected logs
+                for log in logs {
+                    match log.level {
+                        LogLevel::Trace => tracing::trace!(target: "wasm_tool", "{}", log.message),
+                        LogLevel::Debug => tracing::debug!(target: "wasm_tool", "{}", log.message),
+                        LogLevel::Info => tracing::info!(target: "wasm_tool", "{}", log.message),
+                        LogLevel::Warn => tracing::warn!(target: "wasm_tool", "{}", log.message),
+                        LogLevel::Error => tracing::error!(target: "wasm_tool", "{}", log.message),
+                    }
+                }
+
+                // Parse result JSON
+                let result: serde_json::Value = serde_json::from_str(&result_json)
+                    .unwrap_or(serde_json::Value::String(result_json));
+
+                Ok(ToolOutput::success(result, duration))
+            }
+            Err(wasm_err) => Err(wasm_err.into()),
+        }
+    }
+
     /// Execute the WASM tool synchronously (called from spawn_blocking).
+    /// Also returns the host calls that were refused.
+    #[allow(clippy::type_complexity)]
     fn execute_sync(
         &self,
         params: serde_json::Value,
         context_json: Option<String>,
-    ) -> Result<(String, Vec<crate::tools::wasm::host::LogEntry>), WasmError> {
-        let engine = self.runtime.engine();
-        let limits = &self.prepared.limits;
+        progress: Option<ProgressSender>,
+        host_credentials: Option<HostCredentials>,
+    ) -> (
+        Result<(String, Vec<crate::tools::wasm::host::LogEntry>), WasmError>,
+        Vec<(Misbehavior, String)>,
+    ) {
+        // An instance made ahead of time, or a fresh one (NEAR pattern:
+        // fresh instance per call either way).
+        if let Some(WarmInstance {
+            mut store,
+            instance,
+        }) = self.warm.take()
+        {
+            store.data_mut().progress = progress;
+            store.data_mut().host_credentials = host_credentials;
+            let result = self.call_instance(&mut store, &instance, params, context_json);
+            return (result, std::mem::take(&mut store.data_mut().misbehavior));
+        }
 
-        // Create store with fresh state (NEAR pattern: fresh instance per call)
-        let store_data = StoreData::new(
-            limits.memory_bytes,
-            self.capabilities.clone(),
-            self.credentials.clone(),
-        );
-        let mut store = Store::new(engine, store_data);
+        let mut store_data = self.store_data();
+        store_data.progress = progress;
+        store_data.host_credentials = host_credentials;
+        let (result, store_data) = self.run_in_store(store_data, params, context_json);
+        (result, store_data.misbehavior)
+    }
 
-        // Configure fuel if enabled
-        if self.runtime.config().fuel_config.enabled {
-            store
-                .set_fuel(limits.fuel)
-                .map_err(|e| WasmError::ConfigError(format!("Failed to set fuel: {}", e)))?;
+    /// Execute the tool once with network, secret, workspace and tool access
+    /// stubbed out, recording what it tried to touch. See
+    /// [`crate::tools::wasm::DryRunReport`].
+    pub async fn dry_run(self, params: serde_json::Value) -> DryRunReport {
+        let timeout = self.prepared.limits.timeout;
+        let run = tokio::task::spawn_blocking(move || self.dry_run_sync(params));
+        match tokio::time::timeout(timeout, run).await {
+            Ok(Ok(report)) => report,
+            Ok(Err(e)) => DryRunReport {
+                error: Some(WasmError::ExecutionPanicked(e.to_string()).to_string()),
+                ..Default::default()
+            },
+            Err(_) => DryRunReport {
+                error: Some(WasmError::Timeout(timeout).to_string()),
+                ..Default::default()
+            },
         }
+    }
 
-        // Configure epoch deadline as a hard timeout backup.
-        // The epoch ticker thread increments the engine epoch every EPOCH_TICK_INTERVAL.
-        // Setting deadline to N means "trap after N ticks", so we compute the number
-        // of ticks that fit in the tool's timeout. Minimum 1 to always have a backstop.
-        store.epoch_deadline_trap();
-        let ticks = (limits.timeout.as_millis() / EPOCH_TICK_INTERVAL.as_millis()).max(1) as u64;
-        store.set_epoch_deadline(ticks);
+    /// Dry-run body (called from spawn_blocking). No credentials are injected.
+    fn dry_run_sync(&self, params: serde_json::Value) -> DryRunReport {
+        let mut store_data = StoreData::new(
+            self.prepared.limits.memory_bytes,
+            self.capabilities.clone(),
+            HashMap::new(),
+        );
+        store_data.dry_run = Some(DryRunReport::default());
 
-        // Set up resource limiter
-        store.limiter(|data| &mut data.limiter);
+        let (result, mut store_data) = self.run_in_store(store_data, params, None);
+        let mut report = store_data.dry_run.take().unwrap_or_default();
+        match result {
+            Ok((output, logs)) => {
+                report.output = Some(output);
+                report.logs = logs
+                    .into_iter()
+                    .map(|log| format!("{} {}", log.level, log.message))
+                    .collect();
+            }
+            Err(e) => report.error = Some(e.to_string()),
+        }
+        report
+    }
 
-        // Compile the component (uses cached bytes)
-        let component = Component::new(engine, self.prepared.component_bytes())
-            .map_err(|e| WasmError::CompilationFailed(e.to_string()))?;
+    /// Instantiate the component in a store built from `store_data` and call
+    /// `execute`. Hands the store data back so callers can inspect it.
+    #[allow(clippy::type_complexity)]
+    fn run_in_store(
+        &self,
+        store_data: StoreData,
+        params: serde_json::Value,
+        context_json: Option<String>,
+    ) -> (
+        Result<(String, Vec<crate::tools::wasm::host::LogEntry>), WasmError>,
+        StoreData,
+    ) {
+        let mut store = match self.new_store(store_data) {
+            Ok(store) => store,
+            Err(e) => {
+                // The store data is gone with the failed store; hand back
+                // an empty one so callers still get the error.
+                return (Err(e), self.store_data());
+            }
+        };
+        let result = self
+            .prepared
+            .linked()
+            .instantiate(&mut store)
+            .and_then(|instance| self.call_instance(&mut store, &instance, params, context_json));
+        (result, store.into_data())
+    }
 
-        // Create linker with all host functions properly namespaced
-        let mut linker = Linker::new(engine);
-        Self::add_host_functions(&mut linker)?;
+    /// A store for `store_data` with the tool's limits applied.
+    fn new_store(&self, store_data: StoreData) -> Result<Store<StoreData>, WasmError> {
+        let mut store = Store::new(self.runtime.engine(), store_data);
+        configure_store(
+            &mut store,
+            &self.prepared.limits,
+            self.runtime.config().fuel_config.enabled,
+        )?;
+        Ok(store)
+    }
 
-        // Instantiate using the generated bindings
-        let instance = SandboxedTool::instantiate(&mut store, &component, &linker)
-            .map_err(|e| WasmError::InstantiationFailed(e.to_string()))?;
+    /// Call `execute` on an instance. Fuel and the epoch deadline are reset
+    /// first, so instantiation does not count against the call.
+    fn call_instance(
+        &self,
+        store: &mut Store<StoreData>,
+        instance: &ToolInstance,
+        params: serde_json::Value,
+        context_json: Option<String>,
+    ) -> Result<(String, Vec<crate::tools::wasm::host::LogEntry>), WasmError> {
+        let limits = &self.prepared.limits;
+        configure_store(store, limits, self.runtime.config().fuel_config.enabled)?;
 
         // Prepare the request
         let params_json = serde_json::to_string(&params)
             .map_err(|e| WasmError::InvalidResponseJson(e.to_string()))?;
 
-        let request = wit_tool::Request {
-            params: params_json,
-            context: context_json,
-        };
-
         // Call execute using the generated typed interface
-        let tool_iface = instance.near_agent_tool();
-        let response = tool_iface.call_execute(&mut store, &request).map_err(|e| {
-            let error_str = e.to_string();
-            if error_str.contains("out of fuel") {
-                WasmError::FuelExhausted { limit: limits.fuel }
-            } else if error_str.contains("unreachable") {
-                WasmError::Trapped("unreachable code executed".to_string())
-            } else {
-                WasmError::Trapped(error_str)
+        let result = match instance {
+            ToolInstance::Sandboxed(instance) => {
+                let request = wit_tool::Request {
+                    params: params_json,
+                    context: context_json,
+                };
+                let response = instance
+                    .near_agent_tool()
+                    .call_execute(&mut *store, &request)
+                    .map_err(|e| trap_error(e, limits.fuel))?;
+                match response.error {
+                    Some(err) => Err(err),
+                    None => Ok(response.output.unwrap_or_default()),
+                }
             }
-        })?;
+            ToolInstance::Standard(instance) => instance
+                .ironclaw_tool_handler()
+                .call_execute(&mut *store, &params_json, context_json.as_deref())
+                .map_err(|e| trap_error(e, limits.fuel))?,
+        };
 
         // Get logs from host state
         let logs = store.data_mut().host_state.take_logs();
 
         // Check for tool-level error
-        if let Some(err) = response.error {
-            return Err(WasmError::ToolReturnedError(err));
-        }
+        let output = result.map_err(WasmError::ToolReturnedError)?;
+        Ok((output, logs))
+    }
+}
+
+/// Apply fuel, the epoch deadline and the memory limiter to a fresh store.
+fn configure_store(
+    store: &mut Store<StoreData>,
+    limits: &ResourceLimits,
+    fuel_enabled: bool,
+) -> Result<(), WasmError> {
+    // Configure fuel if enabled
+    if fuel_enabled {
+        store
+            .set_fuel(limits.fuel)
+            .map_err(|e| WasmError::ConfigError(format!("Failed to set fuel: {}", e)))?;
+    }
+
+    // Configure epoch deadline as a hard timeout backup.
+    // The epoch ticker thread increments the engine epoch every EPOCH_TICK_INTERVAL.
+    // Setting deadline to N means "trap after N ticks", so we compute the number
+    // of ticks that fit in the tool's timeout. Minimum 1 to always have a backstop.
+    store.epoch_deadline_trap();
+    let ticks = (limits.timeout.as_millis() / EPOCH_TICK_INTERVAL.as_millis()).max(1) as u64;
+    store.set_epoch_deadline(ticks);
+
+    // Set up resource limiter
+    store.limiter(|data| &mut data.limiter);
+    Ok(())
+}
 
-        // Return result (or empty string if none)
-        Ok((response.output.unwrap_or_default(), logs))
+/// Map an error from a guest call to a [`WasmError`].
+fn trap_error(e: wasmtime::Error, fuel: u64) -> WasmError {
+    let error_str = e.to_string();
+    if error_str.contains("out of fuel") {
+        WasmError::FuelExhausted { limit: fuel }
+    } else if error_str.contains("unreachable") {
+        WasmError::Trapped("unreachable code executed".to_string())
+    } else {
+        WasmError::Trapped(error_str)
     }
 }
 
+/// Call `description` and `schema` on an `ironclaw:tool` component.
+///
+/// Standard components import nothing with side effects, so unlike
+/// `near:agent` tools they can be instantiated at load time to read their
+/// metadata. Runs under the tool's own limits, without capabilities.
+pub(crate) fn read_standard_metadata(
+    engine: &Engine,
+    component: &Component,
+    limits: &ResourceLimits,
+    fuel_enabled: bool,
+) -> Result<(String, serde_json::Value), WasmError> {
+    let store_data = StoreData::new(limits.memory_bytes, Capabilities::default(), HashMap::new());
+    let mut store = Store::new(engine, store_data);
+    configure_store(&mut store, limits, fuel_enabled)?;
+
+    let mut linker = Linker::new(engine);
+    WasmToolWrapper::add_host_functions(&mut linker, ToolWorld::Standard)?;
+    let instance = StandardTool::instantiate(&mut store, component, &linker)
+        .map_err(|e| WasmError::InstantiationFailed(e.to_string()))?;
+    let handler = instance.ironclaw_tool_handler();
+
+    let description = handler
+        .call_description(&mut store)
+        .map_err(|e| trap_error(e, limits.fuel))?;
+    let schema = handler
+        .call_schema(&mut store)
+        .map_err(|e| trap_error(e, limits.fuel))?;
+    let schema = serde_json::from_str(&schema)
+        .map_err(|e| WasmError::InvalidResponseJson(format!("schema: {}", e)))?;
+    Ok((description, schema))
+}
+
 #[async_trait]
 impl Tool for WasmToolWrapper {
     fn name(&self) -> &str {
@@ -489,61 +1175,16 @@ impl Tool for WasmToolWrapper {
         params: serde_json::Value,
         ctx: &JobContext,
     ) -> Result<ToolOutput, ToolError> {
-        let start = Instant::now();
-        let timeout = self.prepared.limits.timeout;
-
-        // Serialize context for WASM
-        let context_json = serde_json::to_string(ctx).ok();
-
-        // Clone what we need for the blocking task
-        let runtime = Arc::clone(&self.runtime);
-        let prepared = Arc::clone(&self.prepared);
-        let capabilities = self.capabilities.clone();
-        let description = self.description.clone();
-        let schema = self.schema.clone();
-        let credentials = self.credentials.clone();
-
-        // Execute in blocking task with timeout
-        let result = tokio::time::timeout(timeout, async move {
-            let wrapper = WasmToolWrapper {
-                runtime,
-                prepared,
-                capabilities,
-                description,
-                schema,
-                credentials,
-            };
-
-            tokio::task::spawn_blocking(move || wrapper.execute_sync(params, context_json))
-                .await
-                .map_err(|e| WasmError::ExecutionPanicked(e.to_string()))?
-        })
-        .await;
-
-        let duration = start.elapsed();
-
-        match result {
-            Ok(Ok((result_json, logs))) => {
-                // Emit collected logs
-                for log in logs {
-                    match log.level {
-                        LogLevel::Trace => tracing::trace!(target: "wasm_tool", "{}", log.message),
-                        LogLevel::Debug => tracing::debug!(target: "wasm_tool", "{}", log.message),
-                        LogLevel::Info => tracing::info!(target: "wasm_tool", "{}", log.message),
-                        LogLevel::Warn => tracing::warn!(target: "wasm_tool", "{}", log.message),
-                        LogLevel::Error => tracing::error!(target: "wasm_tool", "{}", log.message),
-                    }
-                }
-
-                // Parse result JSON
-                let result: serde_json::Value = serde_json::from_str(&result_json)
-                    .unwrap_or(serde_json::Value::String(result_json));
+        self.run(params, ctx, None).await
+    }
 
-                Ok(ToolOutput::success(result, duration))
-            }
-            Ok(Err(wasm_err)) => Err(wasm_err.into()),
-            Err(_) => Err(WasmError::Timeout(timeout).into()),
-        }
+    async fn execute_streaming(
+        &self,
+        params: serde_json::Value,
+        ctx: &JobContext,
+        progress: ProgressSender,
+    ) -> Result<ToolOutput, ToolError> {
+        self.run(params, ctx, Some(progress)).await
     }
 
     fn requires_approval(&self) -> bool {
@@ -660,6 +1301,7 @@ mod tests {
     use std::sync::Arc;
 
     use crate::tools::wasm::capabilities::Capabilities;
+    use crate::tools::wasm::error::WasmError;
     use crate::tools::wasm::runtime::{WasmRuntimeConfig, WasmToolRuntime};
 
     #[test]
@@ -673,6 +1315,265 @@ mod tests {
         assert!(runtime.config().fuel_config.enabled);
     }
 
+    #[tokio::test]
+    async fn test_standard_world_component_runs() {
+        use crate::context::JobContext;
+        use crate::tools::tool::Tool;
+        use crate::tools::wasm::world::ToolWorld;
+        use crate::tools::wasm::world::tests::ECHO_COMPONENT;
+        use crate::tools::wasm::wrapper::WasmToolWrapper;
+
+        let runtime = Arc::new(WasmToolRuntime::new(WasmRuntimeConfig::for_testing()).unwrap());
+        let prepared = runtime
+            .prepare("echo", ECHO_COMPONENT.as_bytes(), None)
+            .await
+            .unwrap();
+        assert_eq!(prepared.world, ToolWorld::Standard);
+        // Metadata comes from the component itself.
+        assert_eq!(prepared.description, "Echoes its input");
+        assert_eq!(prepared.schema, serde_json::json!({"type": "object"}));
+
+        let tool = WasmToolWrapper::new(runtime, prepared, Capabilities::default());
+        let ctx = JobContext::default();
+        let output = tool
+            .execute(serde_json::json!({"text": "hi"}), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(output.result, serde_json::json!({"text": "hi"}));
+
+        // Without a job context the component returns its own error.
+        let err = tool
+            .execute_sync(serde_json::json!({"text": "hi"}), None, None, None)
+            .0
+            .unwrap_err();
+        assert!(matches!(err, WasmError::ToolReturnedError(msg) if msg == "no context"));
+    }
+
+    /// An `ironclaw:tool` component that emits its params as progress, then
+    /// returns `"done"`.
+    const PROGRESS_COMPONENT: &str = r#"
+(component
+  (import "ironclaw:tool/progress@0.1.0" (instance $progress
+    (export "emit" (func (param "json" string) (result (result (error string)))))))
+  (core module $mem
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 4096))
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr (global.get $heap))
+      (global.set $heap (i32.add (global.get $heap) (local.get 3)))
+      (local.get $ptr)))
+  (core instance $mem_i (instantiate $mem))
+  (core func $emit
+    (canon lower (func $progress "emit") (memory $mem_i "memory") (realloc (func $mem_i "realloc"))))
+  (core module $m
+    (import "host" "memory" (memory 1))
+    (import "host" "emit" (func $emit (param i32 i32 i32)))
+    (data (i32.const 16) "Streams progress")
+    (data (i32.const 64) "{}")
+    (data (i32.const 80) "\"done\"")
+    (func (export "description") (result i32)
+      (i32.store (i32.const 512) (i32.const 16))
+      (i32.store (i32.const 516) (i32.const 16))
+      (i32.const 512))
+    (func (export "schema") (result i32)
+      (i32.store (i32.const 520) (i32.const 64))
+      (i32.store (i32.const 524) (i32.const 2))
+      (i32.const 520))
+    (func (export "execute")
+      (param $ptr i32) (param $len i32) (param i32) (param i32) (param i32)
+      (result i32)
+      (call $emit (local.get $ptr) (local.get $len) (i32.const 600))
+      (i32.store8 (i32.const 528) (i32.const 0))
+      (i32.store (i32.const 532) (i32.const 80))
+      (i32.store (i32.const 536) (i32.const 6))
+      (i32.const 528)))
+  (core instance $host
+    (export "memory" (memory $mem_i "memory"))
+    (export "emit" (func $emit)))
+  (core instance $i (instantiate $m (with "host" (instance $host))))
+  (func $description (result string)
+    (canon lift (core func $i "description") (memory $mem_i "memory")))
+  (func $schema (result string)
+    (canon lift (core func $i "schema") (memory $mem_i "memory")))
+  (func $execute
+    (param "params" string) (param "context" (option string))
+    (result (result string (error string)))
+    (canon lift (core func $i "execute") (memory $mem_i "memory") (realloc (func $mem_i "realloc"))))
+  (instance $handler
+    (export "description" (func $description))
+    (export "schema" (func $schema))
+    (export "execute" (func $execute)))
+  (export "ironclaw:tool/handler@0.1.0" (instance $handler))
+)
+"#;
+
+    #[tokio::test]
+    async fn test_progress_is_streamed() {
+        use crate::context::JobContext;
+        use crate::tools::tool::Tool;
+        use crate::tools::wasm::capabilities::ProgressCapability;
+        use crate::tools::wasm::wrapper::WasmToolWrapper;
+
+        let runtime = Arc::new(WasmToolRuntime::new(WasmRuntimeConfig::for_testing()).unwrap());
+        let prepared = runtime
+            .prepare("progress", PROGRESS_COMPONENT.as_bytes(), None)
+            .await
+            .unwrap();
+        let ctx = JobContext::default();
+
+        let tool = WasmToolWrapper::new(
+            Arc::clone(&runtime),
+            Arc::clone(&prepared),
+            Capabilities::none().with_progress(ProgressCapability::default()),
+        );
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let output = tool
+            .execute_streaming(serde_json::json!({"step": 1}), &ctx, tx)
+            .await
+            .unwrap();
+        assert_eq!(output.result, serde_json::json!("done"));
+        assert_eq!(rx.recv().await, Some(serde_json::json!({"step": 1})));
+        assert_eq!(rx.recv().await, None);
+
+        // Without the capability the emission is refused; the tool still runs.
+        let tool = WasmToolWrapper::new(runtime, prepared, Capabilities::default());
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let output = tool
+            .execute_streaming(serde_json::json!({"step": 1}), &ctx, tx)
+            .await
+            .unwrap();
+        assert_eq!(output.result, serde_json::json!("done"));
+        assert_eq!(rx.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_warm_instances_from_pool_serve_one_call_each() {
+        use crate::context::JobContext;
+        use crate::tools::tool::Tool;
+        use crate::tools::wasm::runtime::PoolingConfig;
+        use crate::tools::wasm::world::tests::ECHO_COMPONENT;
+        use crate::tools::wasm::wrapper::WasmToolWrapper;
+
+        let runtime = Arc::new(
+            WasmToolRuntime::new(WasmRuntimeConfig {
+                pooling: Some(PoolingConfig {
+                    max_instances: 8,
+                    max_memory_bytes: 1024 * 1024,
+                }),
+                warm_instances: 2,
+                ..WasmRuntimeConfig::for_testing()
+            })
+            .unwrap(),
+        );
+        let prepared = runtime
+            .prepare("echo", ECHO_COMPONENT.as_bytes(), None)
+            .await
+            .unwrap();
+        let tool = WasmToolWrapper::new(runtime, prepared, Capabilities::default());
+        let ctx = JobContext::default();
+
+        tool.warm_up();
+        while tool.warm_instances() < 2 {
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+
+        for n in 0..3 {
+            let output = tool
+                .execute(serde_json::json!({"n": n}), &ctx)
+                .await
+                .unwrap();
+            assert_eq!(output.result, serde_json::json!({"n": n}));
+        }
+        // Each call used up an instance and the pool was topped up again.
+        while tool.warm_instances() < 2 {
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+    }
+
+    /// An `ironclaw:tool` component whose `execute` always traps.
+    const TRAP_COMPONENT: &str = r#"
+(component
+  (core module $m
+    (memory (export "memory") 1)
+    (data (i32.const 16) "Always traps")
+    (data (i32.const 64) "{}")
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (i32.const 4096))
+    (func (export "description") (result i32)
+      (i32.store (i32.const 512) (i32.const 16))
+      (i32.store (i32.const 516) (i32.const 12))
+      (i32.const 512))
+    (func (export "schema") (result i32)
+      (i32.store (i32.const 520) (i32.const 64))
+      (i32.store (i32.const 524) (i32.const 2))
+      (i32.const 520))
+    (func (export "execute")
+      (param i32 i32 i32 i32 i32) (result i32)
+      unreachable)
+  )
+  (core instance $i (instantiate $m))
+  (func $description (result string)
+    (canon lift (core func $i "description") (memory $i "memory")))
+  (func $schema (result string)
+    (canon lift (core func $i "schema") (memory $i "memory")))
+  (func $execute
+    (param "params" string) (param "context" (option string))
+    (result (result string (error string)))
+    (canon lift (core func $i "execute") (memory $i "memory") (realloc (func $i "realloc"))))
+  (instance $handler
+    (export "description" (func $description))
+    (export "schema" (func $schema))
+    (export "execute" (func $execute)))
+  (export "ironclaw:tool/handler@0.1.0" (instance $handler))
+)
+"#;
+
+    #[tokio::test]
+    async fn test_repeated_traps_quarantine_the_tool() {
+        use crate::context::JobContext;
+        use crate::tools::ToolRegistry;
+        use crate::tools::registry::WasmToolRegistration;
+        use crate::tools::wasm::quarantine::MisbehaviorTracker;
+
+        let dir = tempfile::tempdir().unwrap();
+        let runtime = Arc::new(WasmToolRuntime::new(WasmRuntimeConfig::for_testing()).unwrap());
+        let registry = Arc::new(ToolRegistry::new());
+        let tracker = Arc::new(MisbehaviorTracker::new(
+            &registry,
+            dir.path().to_path_buf(),
+            2,
+        ));
+        registry.set_misbehavior_tracker(Arc::clone(&tracker));
+        let registration = || WasmToolRegistration {
+            name: "trapper",
+            wasm_bytes: TRAP_COMPONENT.as_bytes(),
+            runtime: &runtime,
+            capabilities: Capabilities::default(),
+            limits: None,
+            description: None,
+            schema: None,
+        };
+        registry.register_wasm(registration()).await.unwrap();
+        let tool = registry.get("trapper").await.unwrap();
+        let ctx = JobContext::default();
+
+        for _ in 0..2 {
+            let err = tool.execute(serde_json::json!({}), &ctx).await.unwrap_err();
+            assert!(err.to_string().contains("trapped"), "{}", err);
+        }
+        assert!(tracker.is_quarantined("trapper"));
+        assert!(!registry.has("trapper").await);
+
+        // Calls through a handle taken earlier are refused too.
+        let err = tool.execute(serde_json::json!({}), &ctx).await.unwrap_err();
+        assert!(err.to_string().contains("quarantined"), "{}", err);
+        assert!(matches!(
+            registry.register_wasm(registration()).await,
+            Err(WasmError::Quarantined(_))
+        ));
+    }
+
     #[test]
     fn test_capabilities_default() {
         let caps = Capabilities::default();
@@ -70,9 +70,119 @@ pub fn llm_signals_completion(response: &str) -> bool {
     positive_phrases.iter().any(|p| lower.contains(p))
 }
 
+/// Write `entries` as a gzip-compressed ustar archive, each under a
+/// top-level `root/` directory. Names must fit in 100 bytes with the root.
+pub fn write_tar_gz(
+    path: &std::path::Path,
+    root: &str,
+    entries: &[(String, Vec<u8>)],
+) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let file = std::fs::File::create(path)?;
+    let mut gz = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    let mtime = chrono::Utc::now().timestamp().max(0) as u64;
+    for (name, data) in entries {
+        gz.write_all(&tar_header(
+            &format!("{}/{}", root, name),
+            data.len() as u64,
+            mtime,
+        ))?;
+        gz.write_all(data)?;
+        let pad = (512 - data.len() % 512) % 512;
+        gz.write_all(&vec![0u8; pad])?;
+    }
+    // End of archive: two zero blocks.
+    gz.write_all(&[0u8; 1024])?;
+    gz.finish()?.sync_all()
+}
+
+/// Read the regular files of a gzip-compressed ustar archive written by
+/// [`write_tar_gz`], with the top-level `root/` stripped from their names.
+/// Entries outside `root` are skipped.
+pub fn read_tar_gz(path: &std::path::Path, root: &str) -> std::io::Result<Vec<(String, Vec<u8>)>> {
+    use std::io::Read;
+
+    let invalid = |reason: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, reason);
+    let mut tar = Vec::new();
+    flate2::read::GzDecoder::new(std::fs::File::open(path)?).read_to_end(&mut tar)?;
+
+    let prefix = format!("{}/", root);
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while offset + 512 <= tar.len() {
+        let header = &tar[offset..offset + 512];
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+        let field = |range: std::ops::Range<usize>| {
+            let bytes = &header[range];
+            let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+        };
+        let size = u64::from_str_radix(&field(124..136), 8)
+            .map_err(|_| invalid("bad tar entry size"))? as usize;
+        let start = offset + 512;
+        let data = tar
+            .get(start..start + size)
+            .ok_or_else(|| invalid("truncated tar archive"))?;
+        let name = field(0..100);
+        if matches!(header[156], b'0' | 0)
+            && let Some(name) = name.strip_prefix(&prefix)
+        {
+            entries.push((name.to_string(), data.to_vec()));
+        }
+        offset = start + size.div_ceil(512) * 512;
+    }
+    Ok(entries)
+}
+
+/// A ustar header block for a regular file. Names must fit in 100 bytes.
+fn tar_header(name: &str, size: u64, mtime: u64) -> [u8; 512] {
+    fn octal(field: &mut [u8], value: u64) {
+        let digits = format!("{:0width$o}", value, width = field.len() - 1);
+        field[..digits.len()].copy_from_slice(digits.as_bytes());
+    }
+
+    let mut header = [0u8; 512];
+    let name = name.as_bytes();
+    let len = name.len().min(100);
+    header[..len].copy_from_slice(&name[..len]);
+    octal(&mut header[100..108], 0o644);
+    octal(&mut header[108..116], 0);
+    octal(&mut header[116..124], 0);
+    octal(&mut header[124..136], size);
+    octal(&mut header[136..148], mtime);
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    // The checksum is computed with its own field set to spaces.
+    header[148..156].fill(b' ');
+    let sum: u64 = header.iter().map(|&b| u64::from(b)).sum();
+    let digits = format!("{:06o}\0 ", sum);
+    header[148..156].copy_from_slice(digits.as_bytes());
+    header
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::util::{floor_char_boundary, llm_signals_completion};
+    use crate::util::{floor_char_boundary, llm_signals_completion, read_tar_gz, write_tar_gz};
+
+    // ── tar archives ──
+
+    #[test]
+    fn tar_gz_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.tar.gz");
+        let entries = vec![
+            ("empty.txt".to_string(), Vec::new()),
+            ("data/blob.bin".to_string(), vec![7u8; 1300]),
+        ];
+        write_tar_gz(&path, "root", &entries).unwrap();
+        assert_eq!(read_tar_gz(&path, "root").unwrap(), entries);
+        assert!(read_tar_gz(&path, "other").unwrap().is_empty());
+    }
 
     // ── floor_char_boundary ──
 
@@ -2,8 +2,17 @@
 //!
 //! Every request includes a bearer token from `IRONCLAW_WORKER_TOKEN` env var.
 //! The orchestrator validates this token is scoped to the correct job.
+//!
+//! When a gRPC URL is known (`IRONCLAW_ORCHESTRATOR_GRPC_URL`), the first
+//! call negotiates the gRPC API and, if the handshake succeeds, every call
+//! goes over [`WorkerGrpcClient`] instead. Otherwise the HTTP endpoints are
+//! used as before. [`OrchestratorProto`] (`--orchestrator-proto`) pins the
+//! choice: `http` never tries gRPC, `grpc` makes a failed handshake fatal.
+
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
 use uuid::Uuid;
 
 use crate::error::WorkerError;
@@ -11,6 +20,19 @@ use crate::llm::{
     ChatMessage, CompletionRequest, CompletionResponse, FinishReason, ToolCall,
     ToolCompletionRequest, ToolCompletionResponse, ToolDefinition,
 };
+use crate::worker::grpc::{WorkerGrpcClient, proto::ArtifactReceipt};
+
+/// Transport a worker uses to reach the orchestrator.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OrchestratorProto {
+    /// gRPC when the handshake succeeds, HTTP otherwise.
+    #[default]
+    Auto,
+    /// HTTP only.
+    Http,
+    /// gRPC only; fail if it cannot be negotiated.
+    Grpc,
+}
 
 /// HTTP client that a container worker uses to talk to the orchestrator.
 pub struct WorkerHttpClient {
@@ -18,6 +40,12 @@ pub struct WorkerHttpClient {
     orchestrator_url: String,
     job_id: Uuid,
     token: String,
+    proto: OrchestratorProto,
+    /// gRPC endpoint to negotiate with, if the orchestrator offers one.
+    grpc_url: Option<String>,
+    /// Result of the gRPC handshake, made on first use (the error says why
+    /// HTTP is used instead).
+    grpc: OnceCell<Result<WorkerGrpcClient, String>>,
 }
 
 /// Status update sent from worker to orchestrator.
@@ -107,6 +135,11 @@ impl WorkerHttpClient {
             orchestrator_url: orchestrator_url.trim_end_matches('/').to_string(),
             job_id,
             token,
+            proto: OrchestratorProto::Auto,
+            grpc_url: std::env::var("IRONCLAW_ORCHESTRATOR_GRPC_URL")
+                .ok()
+                .filter(|url| !url.is_empty()),
+            grpc: OnceCell::new(),
         })
     }
 
@@ -117,9 +150,84 @@ impl WorkerHttpClient {
             orchestrator_url: orchestrator_url.trim_end_matches('/').to_string(),
             job_id,
             token,
+            proto: OrchestratorProto::Auto,
+            grpc_url: None,
+            grpc: OnceCell::new(),
         }
     }
 
+    /// Negotiate the gRPC API at `url` before falling back to HTTP.
+    pub fn with_grpc_url(mut self, url: impl Into<String>) -> Self {
+        self.grpc_url = Some(url.into());
+        self
+    }
+
+    /// Pick the transport (see [`OrchestratorProto`]).
+    pub fn with_proto(mut self, proto: OrchestratorProto) -> Self {
+        self.proto = proto;
+        self
+    }
+
+    /// Negotiate the transport now rather than on the first call. Fails
+    /// only with [`OrchestratorProto::Grpc`] when gRPC is unavailable.
+    pub async fn connect(&self) -> Result<(), WorkerError> {
+        match self.negotiate().await {
+            Err(reason) if self.proto == OrchestratorProto::Grpc => {
+                Err(WorkerError::ProtocolMismatch {
+                    reason: format!("gRPC transport required but unavailable: {}", reason),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Handshake and open the job session, once.
+    async fn negotiate(&self) -> &Result<WorkerGrpcClient, String> {
+        self.grpc
+            .get_or_init(|| async {
+                if self.proto == OrchestratorProto::Http {
+                    return Err("HTTP transport selected".to_string());
+                }
+                let url = self
+                    .grpc_url
+                    .as_deref()
+                    .ok_or_else(|| "IRONCLAW_ORCHESTRATOR_GRPC_URL is not set".to_string())?;
+                let client = match WorkerGrpcClient::connect(url, self.job_id, &self.token).await {
+                    Ok(client) => client,
+                    Err(e) => {
+                        tracing::info!("gRPC worker API unavailable ({}), using HTTP", e);
+                        return Err(e.to_string());
+                    }
+                };
+                match client.attach().await {
+                    Ok(true) => tracing::info!(
+                        "Using gRPC worker API v{} at {} (streaming session)",
+                        client.version(),
+                        url
+                    ),
+                    Ok(false) => tracing::info!(
+                        "Using gRPC worker API v{} at {} (no session support, prompts are polled)",
+                        client.version(),
+                        url
+                    ),
+                    Err(e) => tracing::warn!(
+                        "Using gRPC worker API v{} at {}, session failed: {}",
+                        client.version(),
+                        url,
+                        e
+                    ),
+                }
+                Ok(client)
+            })
+            .await
+    }
+
+    /// The negotiated gRPC client, or `None` to use HTTP. The handshake is
+    /// attempted once; any failure keeps the worker on HTTP for good.
+    async fn grpc(&self) -> Option<&WorkerGrpcClient> {
+        self.negotiate().await.as_ref().ok()
+    }
+
     /// Get the base orchestrator URL.
     pub fn orchestrator_url(&self) -> &str {
         &self.orchestrator_url
@@ -131,6 +239,10 @@ impl WorkerHttpClient {
 
     /// Fetch the job description from the orchestrator.
     pub async fn get_job(&self) -> Result<JobDescription, WorkerError> {
+        if let Some(grpc) = self.grpc().await {
+            return grpc.get_job().await;
+        }
+
         let resp = self
             .client
             .get(self.url("job"))
@@ -166,6 +278,19 @@ impl WorkerHttpClient {
             stop_sequences: request.stop_sequences.clone(),
         };
 
+        if let Some(grpc) = self.grpc().await {
+            let proxy_resp = grpc.llm_complete(&proxy_req).await?;
+            return Ok(CompletionResponse {
+                content: proxy_resp.content,
+                input_tokens: proxy_resp.input_tokens,
+                output_tokens: proxy_resp.output_tokens,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                finish_reason: parse_finish_reason(&proxy_resp.finish_reason),
+                response_id: None,
+            });
+        }
+
         let resp = self
             .client
             .post(self.url("llm/complete"))
@@ -194,6 +319,8 @@ impl WorkerHttpClient {
             content: proxy_resp.content,
             input_tokens: proxy_resp.input_tokens,
             output_tokens: proxy_resp.output_tokens,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
             finish_reason: parse_finish_reason(&proxy_resp.finish_reason),
             response_id: None,
         })
@@ -212,6 +339,20 @@ impl WorkerHttpClient {
             tool_choice: request.tool_choice.clone(),
         };
 
+        if let Some(grpc) = self.grpc().await {
+            let proxy_resp = grpc.llm_complete_with_tools(&proxy_req).await?;
+            return Ok(ToolCompletionResponse {
+                content: proxy_resp.content,
+                tool_calls: proxy_resp.tool_calls,
+                input_tokens: proxy_resp.input_tokens,
+                output_tokens: proxy_resp.output_tokens,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                finish_reason: parse_finish_reason(&proxy_resp.finish_reason),
+                response_id: None,
+            });
+        }
+
         let resp = self
             .client
             .post(self.url("llm/complete_with_tools"))
@@ -241,13 +382,25 @@ impl WorkerHttpClient {
             tool_calls: proxy_resp.tool_calls,
             input_tokens: proxy_resp.input_tokens,
             output_tokens: proxy_resp.output_tokens,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
             finish_reason: parse_finish_reason(&proxy_resp.finish_reason),
             response_id: None,
         })
     }
 
-    /// Report status to the orchestrator.
+    /// Report status to the orchestrator (a heartbeat over gRPC).
     pub async fn report_status(&self, update: &StatusUpdate) -> Result<(), WorkerError> {
+        if let Some(grpc) = self.grpc().await {
+            if !grpc.heartbeat(update).await? {
+                tracing::warn!(
+                    job_id = %self.job_id,
+                    "Orchestrator no longer tracks this job"
+                );
+            }
+            return Ok(());
+        }
+
         let resp = self
             .client
             .post(self.url("status"))
@@ -272,7 +425,14 @@ impl WorkerHttpClient {
     }
 
     /// Post a job event to the orchestrator (fire-and-forget style, logs on failure).
+    ///
+    /// Over gRPC the event is queued on the job's event stream.
     pub async fn post_event(&self, payload: &JobEventPayload) {
+        if let Some(grpc) = self.grpc().await {
+            grpc.post_event(payload).await;
+            return;
+        }
+
         let resp = self
             .client
             .post(self.url("event"))
@@ -301,10 +461,45 @@ impl WorkerHttpClient {
         }
     }
 
+    /// Send a log line. Over gRPC it goes on the job session; over HTTP it
+    /// is posted as a `log` event.
+    pub async fn post_log(&self, level: &str, message: &str) {
+        if let Some(grpc) = self.grpc().await {
+            grpc.post_log(level, message).await;
+            return;
+        }
+        self.post_event(&JobEventPayload {
+            event_type: "log".to_string(),
+            data: serde_json::json!({ "level": level, "message": message }),
+        })
+        .await;
+    }
+
+    /// Wait up to `timeout` for a follow-up prompt. A gRPC job session
+    /// delivers it as soon as it is queued; otherwise this polls once and
+    /// sleeps out the rest.
+    pub async fn wait_prompt(
+        &self,
+        timeout: Duration,
+    ) -> Result<Option<PromptResponse>, WorkerError> {
+        if let Some(grpc) = self.grpc().await {
+            return grpc.wait_prompt(timeout).await;
+        }
+        let prompt = self.poll_prompt().await?;
+        if prompt.is_none() {
+            tokio::time::sleep(timeout).await;
+        }
+        Ok(prompt)
+    }
+
     /// Poll the orchestrator for a follow-up prompt.
     ///
     /// Returns `None` if no prompt is available (204 No Content).
     pub async fn poll_prompt(&self) -> Result<Option<PromptResponse>, WorkerError> {
+        if let Some(grpc) = self.grpc().await {
+            return grpc.next_prompt().await;
+        }
+
         let resp = self
             .client
             .get(self.url("prompt"))
@@ -335,8 +530,27 @@ impl WorkerHttpClient {
         Ok(Some(prompt))
     }
 
+    /// Upload a file produced by the job. Only available over gRPC.
+    pub async fn upload_artifact(
+        &self,
+        name: &str,
+        content_type: &str,
+        data: Vec<u8>,
+    ) -> Result<ArtifactReceipt, WorkerError> {
+        match self.grpc().await {
+            Some(grpc) => grpc.upload_artifact(name, content_type, data).await,
+            None => Err(WorkerError::ProtocolMismatch {
+                reason: "artifact upload needs the gRPC worker API".to_string(),
+            }),
+        }
+    }
+
     /// Signal job completion to the orchestrator.
     pub async fn report_complete(&self, report: &CompletionReport) -> Result<(), WorkerError> {
+        if let Some(grpc) = self.grpc().await {
+            return grpc.complete(report).await;
+        }
+
         let resp = self
             .client
             .post(self.url("complete"))
@@ -1,8 +1,8 @@
 //! Claude Code bridge for sandboxed execution.
 //!
 //! Spawns the `claude` CLI inside a Docker container and streams its NDJSON
-//! output back to the orchestrator via HTTP. Supports follow-up prompts via
-//! `--resume`.
+//! output back to the orchestrator (over a gRPC job session when available,
+//! HTTP otherwise). Supports follow-up prompts via `--resume`.
 //!
 //! Security model: the Docker container is the primary security boundary
 //! (cap-drop ALL, non-root user, memory limits, network isolation).
@@ -20,8 +20,8 @@
 //! │    └─ claude -p "task" --output-format        │
 //! │       stream-json                             │
 //! │    └─ reads stdout line-by-line               │
-//! │    └─ POSTs events to orchestrator            │
-//! │    └─ polls for follow-up prompts             │
+//! │    └─ streams events, stderr log lines        │
+//! │    └─ waits for follow-up prompts             │
 //! │    └─ on follow-up: claude --resume           │
 //! └──────────────────────────────────────────────┘
 //! ```
@@ -35,12 +35,16 @@ use tokio::process::Command;
 use uuid::Uuid;
 
 use crate::error::WorkerError;
-use crate::worker::api::{CompletionReport, JobEventPayload, PromptResponse, WorkerHttpClient};
+use crate::worker::api::{
+    CompletionReport, JobEventPayload, OrchestratorProto, PromptResponse, WorkerHttpClient,
+};
 
 /// Configuration for the Claude bridge runtime.
 pub struct ClaudeBridgeConfig {
     pub job_id: Uuid,
     pub orchestrator_url: String,
+    /// Transport to the orchestrator (`--orchestrator-proto`).
+    pub orchestrator_proto: OrchestratorProto,
     pub max_turns: u32,
     pub model: String,
     pub timeout: Duration,
@@ -121,10 +125,10 @@ impl ClaudeBridgeRuntime {
     ///
     /// Reads `IRONCLAW_WORKER_TOKEN` from the environment for auth.
     pub fn new(config: ClaudeBridgeConfig) -> Result<Self, WorkerError> {
-        let client = Arc::new(WorkerHttpClient::from_env(
-            config.orchestrator_url.clone(),
-            config.job_id,
-        )?);
+        let client = Arc::new(
+            WorkerHttpClient::from_env(config.orchestrator_url.clone(), config.job_id)?
+                .with_proto(config.orchestrator_proto),
+        );
 
         Ok(Self { config, client })
     }
@@ -159,6 +163,7 @@ impl ClaudeBridgeRuntime {
         // This replaces --dangerously-skip-permissions with defense-in-depth:
         // only the listed tools are auto-approved, unknown tools fail safely.
         self.write_permission_settings()?;
+        self.client.connect().await?;
 
         // Fetch the job description from the orchestrator
         let job = self.client.get_job().await?;
@@ -194,11 +199,11 @@ impl ClaudeBridgeRuntime {
             }
         };
 
-        // Follow-up loop: poll for prompts, resume Claude sessions
+        // Follow-up loop: wait for prompts, resume Claude sessions
         let mut iteration = 1u32;
         loop {
-            // Poll for a follow-up prompt (2 second intervals)
-            match self.poll_for_prompt().await {
+            // Pushed immediately over a gRPC session, polled every 2s over HTTP
+            match self.wait_for_prompt().await {
                 Ok(Some(prompt)) => {
                     if prompt.done {
                         tracing::info!(job_id = %self.config.job_id, "Orchestrator signaled done");
@@ -227,10 +232,7 @@ impl ClaudeBridgeRuntime {
                         .await;
                     }
                 }
-                Ok(None) => {
-                    // No prompt available, wait and poll again
-                    tokio::time::sleep(Duration::from_secs(2)).await;
-                }
+                Ok(None) => {}
                 Err(e) => {
                     tracing::warn!(
                         job_id = %self.config.job_id,
@@ -304,11 +306,7 @@ impl ClaudeBridgeRuntime {
             let mut lines = reader.lines();
             while let Ok(Some(line)) = lines.next_line().await {
                 tracing::debug!(job_id = %job_id, "claude stderr: {}", line);
-                let payload = JobEventPayload {
-                    event_type: "status".to_string(),
-                    data: serde_json::json!({ "message": line }),
-                };
-                client_for_stderr.post_event(&payload).await;
+                client_for_stderr.post_log("info", &line).await;
             }
         });
 
@@ -412,9 +410,9 @@ impl ClaudeBridgeRuntime {
         self.client.post_event(&payload).await;
     }
 
-    /// Poll the orchestrator for a follow-up prompt.
-    async fn poll_for_prompt(&self) -> Result<Option<PromptResponse>, WorkerError> {
-        self.client.poll_prompt().await
+    /// Wait briefly for a follow-up prompt from the orchestrator.
+    async fn wait_for_prompt(&self) -> Result<Option<PromptResponse>, WorkerError> {
+        self.client.wait_prompt(Duration::from_secs(2)).await
     }
 }
 
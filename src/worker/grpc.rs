@@ -0,0 +1,566 @@
+//! gRPC client for worker-to-orchestrator communication.
+//!
+//! Speaks the versioned `ironclaw.worker.v1` API defined in
+//! `proto/worker.proto`. [`WorkerGrpcClient::connect`] opens with the version
+//! handshake; if that fails the worker stays on the HTTP API, so old and new
+//! worker and orchestrator binaries can be mixed freely.
+//!
+//! After the handshake the worker opens an `Attach` session: events and log
+//! lines go out on it and follow-up prompts come back on it as soon as they
+//! are queued, so nothing is polled. Against an orchestrator without
+//! `Attach`, events fall back to one long-lived `StreamEvents` client stream
+//! and prompts to `NextPrompt`. Failed calls carry a typed
+//! [`proto::ErrorCode`].
+
+use std::time::Duration;
+
+use prost::Message;
+use tokio::sync::{Mutex, mpsc};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::transport::{Channel, Endpoint};
+use uuid::Uuid;
+
+use crate::error::WorkerError;
+use crate::worker::api::{
+    CompletionReport, JobDescription, JobEventPayload, PromptResponse, ProxyCompletionRequest,
+    ProxyCompletionResponse, ProxyToolCompletionRequest, ProxyToolCompletionResponse, StatusUpdate,
+};
+
+/// Generated bindings for `proto/worker.proto`
+/// (regenerate with `scripts/gen-worker-proto.sh`).
+#[allow(clippy::all, clippy::pedantic)]
+pub mod proto {
+    include!("proto/ironclaw.worker.v1.rs");
+}
+
+use proto::worker_service_client::WorkerServiceClient;
+use proto::{ErrorCode, ErrorDetail, orchestrator_message, worker_message};
+
+/// Protocol versions this build speaks, oldest first.
+pub const SUPPORTED_VERSIONS: &[u32] = &[1];
+
+/// Metadata key carrying the job id on every call.
+pub const JOB_ID_METADATA: &str = "x-ironclaw-job-id";
+
+/// Size of each data chunk in an artifact upload.
+const ARTIFACT_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Events buffered on the event stream before `post_event` waits.
+const EVENT_BUFFER: usize = 256;
+
+/// Pushed prompts buffered until the worker asks for them.
+const PROMPT_BUFFER: usize = 16;
+
+/// Build a status carrying an [`ErrorDetail`], so the peer can match on the
+/// code rather than the message text.
+pub fn error_status(code: ErrorCode, message: impl Into<String>) -> tonic::Status {
+    let message = message.into();
+    let grpc_code = match code {
+        ErrorCode::Unauthenticated => tonic::Code::Unauthenticated,
+        ErrorCode::JobNotFound => tonic::Code::NotFound,
+        ErrorCode::UnsupportedVersion | ErrorCode::HandshakeRequired => {
+            tonic::Code::FailedPrecondition
+        }
+        ErrorCode::InvalidRequest | ErrorCode::ArtifactRejected => tonic::Code::InvalidArgument,
+        ErrorCode::LlmFailed => tonic::Code::Unavailable,
+        ErrorCode::Unspecified => tonic::Code::Internal,
+    };
+    let detail = ErrorDetail {
+        code: code as i32,
+        message: message.clone(),
+    };
+    tonic::Status::with_details(grpc_code, message, detail.encode_to_vec().into())
+}
+
+/// Typed error code of a failed call; `Unspecified` when the status has no
+/// [`ErrorDetail`] (transport errors, older peers).
+pub fn error_code(status: &tonic::Status) -> ErrorCode {
+    ErrorDetail::decode(status.details())
+        .ok()
+        .and_then(|detail| ErrorCode::try_from(detail.code).ok())
+        .unwrap_or(ErrorCode::Unspecified)
+}
+
+/// Highest protocol version both sides speak.
+pub fn negotiate_version(offered: &[u32]) -> Option<u32> {
+    offered
+        .iter()
+        .copied()
+        .filter(|v| SUPPORTED_VERSIONS.contains(v))
+        .max()
+}
+
+/// Open client stream of job events and the task driving it.
+struct EventStream {
+    tx: mpsc::Sender<proto::JobEvent>,
+    task: JoinHandle<Result<u64, tonic::Status>>,
+}
+
+/// Open `Attach` session and the task reading what the orchestrator sends.
+struct JobSession {
+    tx: mpsc::Sender<proto::WorkerMessage>,
+    task: JoinHandle<Result<u64, tonic::Status>>,
+}
+
+/// gRPC client that a container worker uses to talk to the orchestrator.
+pub struct WorkerGrpcClient {
+    client: WorkerServiceClient<Channel>,
+    url: String,
+    job_id: Uuid,
+    auth: MetadataValue<Ascii>,
+    job: MetadataValue<Ascii>,
+    version: u32,
+    events: Mutex<Option<EventStream>>,
+    session: Mutex<Option<JobSession>>,
+    /// Prompts pushed on the session; `None` when not attached.
+    prompts: Mutex<Option<mpsc::Receiver<PromptResponse>>>,
+}
+
+impl WorkerGrpcClient {
+    /// Connect and negotiate a protocol version.
+    pub async fn connect(url: &str, job_id: Uuid, token: &str) -> Result<Self, WorkerError> {
+        let connection_failed = |reason: String| WorkerError::ConnectionFailed {
+            url: url.to_string(),
+            reason,
+        };
+        let channel = Endpoint::from_shared(url.to_string())
+            .map_err(|e| connection_failed(e.to_string()))?
+            .connect_timeout(Duration::from_secs(5))
+            .connect()
+            .await
+            .map_err(|e| connection_failed(e.to_string()))?;
+        let auth = format!("Bearer {}", token)
+            .parse()
+            .map_err(|_| WorkerError::MissingToken)?;
+        let job = job_id
+            .to_string()
+            .parse()
+            .map_err(|_| connection_failed("invalid job id".to_string()))?;
+
+        let mut client = Self {
+            client: WorkerServiceClient::new(channel),
+            url: url.to_string(),
+            job_id,
+            auth,
+            job,
+            version: 0,
+            events: Mutex::new(None),
+            session: Mutex::new(None),
+            prompts: Mutex::new(None),
+        };
+
+        let response = client
+            .client
+            .clone()
+            .handshake(client.request(proto::HandshakeRequest {
+                supported_versions: SUPPORTED_VERSIONS.to_vec(),
+                worker_version: env!("CARGO_PKG_VERSION").to_string(),
+            }))
+            .await
+            .map_err(|s| client.status_error(s))?
+            .into_inner();
+        if !SUPPORTED_VERSIONS.contains(&response.selected_version) {
+            return Err(WorkerError::ProtocolMismatch {
+                reason: format!(
+                    "orchestrator {} selected unsupported version {}",
+                    response.orchestrator_version, response.selected_version
+                ),
+            });
+        }
+        client.version = response.selected_version;
+
+        tracing::debug!(
+            job_id = %job_id,
+            version = client.version,
+            orchestrator = %response.orchestrator_version,
+            "Negotiated worker gRPC protocol"
+        );
+        Ok(client)
+    }
+
+    /// Negotiated protocol version.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Open the bidirectional job session. Returns `false` when the
+    /// orchestrator predates `Attach`; events and prompts then keep using
+    /// `StreamEvents` and `NextPrompt`.
+    pub async fn attach(&self) -> Result<bool, WorkerError> {
+        let (tx, rx) = mpsc::channel(EVENT_BUFFER);
+        let response = match self
+            .client
+            .clone()
+            .attach(self.request(ReceiverStream::new(rx)))
+            .await
+        {
+            Ok(response) => response,
+            Err(status) if status.code() == tonic::Code::Unimplemented => return Ok(false),
+            Err(status) => return Err(self.status_error(status)),
+        };
+
+        let mut inbound = response.into_inner();
+        let (prompt_tx, prompt_rx) = mpsc::channel(PROMPT_BUFFER);
+        let task = tokio::spawn(async move {
+            while let Some(message) = inbound.message().await? {
+                match message.payload {
+                    Some(orchestrator_message::Payload::Prompt(p)) => {
+                        let _ = prompt_tx
+                            .send(PromptResponse {
+                                content: p.content,
+                                done: p.done,
+                            })
+                            .await;
+                    }
+                    Some(orchestrator_message::Payload::Accepted(accepted)) => {
+                        return Ok(accepted);
+                    }
+                    None => {}
+                }
+            }
+            Ok(0)
+        });
+
+        *self.session.lock().await = Some(JobSession { tx, task });
+        *self.prompts.lock().await = Some(prompt_rx);
+        Ok(true)
+    }
+
+    /// Whether an `Attach` session is open.
+    pub async fn is_attached(&self) -> bool {
+        self.session.lock().await.is_some()
+    }
+
+    /// Send on the session. `false` when none is open or it has ended.
+    async fn send_on_session(&self, payload: worker_message::Payload) -> bool {
+        match self.session.lock().await.as_ref() {
+            Some(session) => session
+                .tx
+                .send(proto::WorkerMessage {
+                    payload: Some(payload),
+                })
+                .await
+                .is_ok(),
+            None => false,
+        }
+    }
+
+    fn request<T>(&self, message: T) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        let metadata = request.metadata_mut();
+        metadata.insert("authorization", self.auth.clone());
+        metadata.insert(JOB_ID_METADATA, self.job.clone());
+        request
+    }
+
+    fn status_error(&self, status: tonic::Status) -> WorkerError {
+        let reason = status.message().to_string();
+        match error_code(&status) {
+            ErrorCode::LlmFailed => WorkerError::LlmProxyFailed { reason },
+            ErrorCode::UnsupportedVersion | ErrorCode::HandshakeRequired => {
+                WorkerError::ProtocolMismatch { reason }
+            }
+            ErrorCode::Unspecified => WorkerError::ConnectionFailed {
+                url: self.url.clone(),
+                reason: format!("{}: {}", status.code(), reason),
+            },
+            code => WorkerError::OrchestratorRejected {
+                job_id: self.job_id,
+                reason: format!("{}: {}", code.as_str_name(), reason),
+            },
+        }
+    }
+
+    /// Fetch the job description.
+    pub async fn get_job(&self) -> Result<JobDescription, WorkerError> {
+        let job = self
+            .client
+            .clone()
+            .get_job(self.request(proto::GetJobRequest {}))
+            .await
+            .map_err(|s| self.status_error(s))?
+            .into_inner();
+        Ok(JobDescription {
+            title: job.title,
+            description: job.description,
+            project_dir: job.project_dir,
+        })
+    }
+
+    /// Send a heartbeat. Returns `false` once the orchestrator no longer
+    /// tracks the job.
+    pub async fn heartbeat(&self, update: &StatusUpdate) -> Result<bool, WorkerError> {
+        let response = self
+            .client
+            .clone()
+            .heartbeat(self.request(proto::HeartbeatRequest {
+                state: update.state.clone(),
+                message: update.message.clone(),
+                iteration: update.iteration,
+            }))
+            .await
+            .map_err(|s| self.status_error(s))?
+            .into_inner();
+        Ok(response.job_active)
+    }
+
+    /// Queue a job event on the session, or on the event stream (opened on
+    /// first use) when there is none.
+    pub async fn post_event(&self, payload: &JobEventPayload) {
+        let event = proto::JobEvent {
+            event_type: payload.event_type.clone(),
+            data_json: payload.data.to_string(),
+        };
+        if self
+            .send_on_session(worker_message::Payload::Event(event.clone()))
+            .await
+        {
+            return;
+        }
+
+        let mut events = self.events.lock().await;
+        let stream = events.get_or_insert_with(|| {
+            let (tx, rx) = mpsc::channel(EVENT_BUFFER);
+            let mut client = self.client.clone();
+            let request = self.request(ReceiverStream::new(rx));
+            let task = tokio::spawn(async move {
+                client
+                    .stream_events(request)
+                    .await
+                    .map(|r| r.into_inner().accepted)
+            });
+            EventStream { tx, task }
+        });
+        if stream.tx.send(event).await.is_err() {
+            // The stream ended early; the next event opens a fresh one.
+            if let Some(stream) = events.take() {
+                match stream.task.await {
+                    Ok(Err(status)) => tracing::debug!(
+                        job_id = %self.job_id,
+                        "Job event stream failed: {}", status
+                    ),
+                    Err(e) => {
+                        tracing::debug!(job_id = %self.job_id, "Job event stream panicked: {}", e)
+                    }
+                    Ok(Ok(_)) => {}
+                }
+            }
+        }
+    }
+
+    /// Queue a log line on the session; without one it is sent as a `log`
+    /// job event.
+    pub async fn post_log(&self, level: &str, message: &str) {
+        let line = proto::LogLine {
+            level: level.to_string(),
+            message: message.to_string(),
+        };
+        if !self
+            .send_on_session(worker_message::Payload::Log(line.clone()))
+            .await
+        {
+            self.post_event(&JobEventPayload {
+                event_type: "log".to_string(),
+                data: serde_json::json!({ "level": line.level, "message": line.message }),
+            })
+            .await;
+        }
+    }
+
+    /// Close the session and event stream and wait for the orchestrator to
+    /// take every queued event and log line. Returns how many it took.
+    pub async fn flush_events(&self) -> Result<u64, WorkerError> {
+        let mut accepted = 0;
+        if let Some(JobSession { tx, task }) = self.session.lock().await.take() {
+            drop(tx);
+            accepted += self.join_stream(task).await?;
+        }
+        if let Some(EventStream { tx, task }) = self.events.lock().await.take() {
+            drop(tx);
+            accepted += self.join_stream(task).await?;
+        }
+        Ok(accepted)
+    }
+
+    async fn join_stream(
+        &self,
+        task: JoinHandle<Result<u64, tonic::Status>>,
+    ) -> Result<u64, WorkerError> {
+        match task.await {
+            Ok(result) => result.map_err(|s| self.status_error(s)),
+            Err(e) => Err(WorkerError::ExecutionFailed {
+                reason: format!("event stream task failed: {}", e),
+            }),
+        }
+    }
+
+    /// Upload a file produced by the job.
+    pub async fn upload_artifact(
+        &self,
+        name: &str,
+        content_type: &str,
+        data: Vec<u8>,
+    ) -> Result<proto::ArtifactReceipt, WorkerError> {
+        use proto::artifact_chunk::Payload;
+
+        let mut chunks = vec![proto::ArtifactChunk {
+            payload: Some(Payload::Header(proto::ArtifactHeader {
+                name: name.to_string(),
+                content_type: content_type.to_string(),
+                size: data.len() as u64,
+            })),
+        }];
+        chunks.extend(
+            data.chunks(ARTIFACT_CHUNK_BYTES)
+                .map(|chunk| proto::ArtifactChunk {
+                    payload: Some(Payload::Data(chunk.to_vec())),
+                }),
+        );
+
+        let receipt = self
+            .client
+            .clone()
+            .upload_artifact(self.request(tokio_stream::iter(chunks)))
+            .await
+            .map_err(|s| self.status_error(s))?
+            .into_inner();
+        Ok(receipt)
+    }
+
+    /// Proxy an LLM completion through the orchestrator.
+    pub async fn llm_complete(
+        &self,
+        request: &ProxyCompletionRequest,
+    ) -> Result<ProxyCompletionResponse, WorkerError> {
+        let response = self
+            .client
+            .clone()
+            .llm_complete(self.request(llm_request(request)?))
+            .await
+            .map_err(|s| self.status_error(s))?
+            .into_inner();
+        llm_response(&response)
+    }
+
+    /// Proxy an LLM tool completion through the orchestrator.
+    pub async fn llm_complete_with_tools(
+        &self,
+        request: &ProxyToolCompletionRequest,
+    ) -> Result<ProxyToolCompletionResponse, WorkerError> {
+        let response = self
+            .client
+            .clone()
+            .llm_complete_with_tools(self.request(llm_request(request)?))
+            .await
+            .map_err(|s| self.status_error(s))?
+            .into_inner();
+        llm_response(&response)
+    }
+
+    /// Take the next queued follow-up prompt, if any, without waiting.
+    pub async fn next_prompt(&self) -> Result<Option<PromptResponse>, WorkerError> {
+        {
+            let mut prompts = self.prompts.lock().await;
+            if let Some(rx) = prompts.as_mut() {
+                match rx.try_recv() {
+                    Ok(prompt) => return Ok(Some(prompt)),
+                    Err(mpsc::error::TryRecvError::Empty) => return Ok(None),
+                    // Session gone: ask directly from now on.
+                    Err(mpsc::error::TryRecvError::Disconnected) => *prompts = None,
+                }
+            }
+        }
+        let response = self
+            .client
+            .clone()
+            .next_prompt(self.request(proto::NextPromptRequest {}))
+            .await
+            .map_err(|s| self.status_error(s))?
+            .into_inner();
+        Ok(response.prompt.map(|p| PromptResponse {
+            content: p.content,
+            done: p.done,
+        }))
+    }
+
+    /// Wait up to `timeout` for a follow-up prompt. On a session the prompt
+    /// arrives as soon as it is queued; otherwise this asks once and sleeps
+    /// out the rest of the timeout.
+    pub async fn wait_prompt(
+        &self,
+        timeout: Duration,
+    ) -> Result<Option<PromptResponse>, WorkerError> {
+        {
+            let mut prompts = self.prompts.lock().await;
+            if let Some(rx) = prompts.as_mut() {
+                match tokio::time::timeout(timeout, rx.recv()).await {
+                    Ok(Some(prompt)) => return Ok(Some(prompt)),
+                    Ok(None) => *prompts = None,
+                    Err(_) => return Ok(None),
+                }
+            }
+        }
+        let prompt = self.next_prompt().await?;
+        if prompt.is_none() {
+            tokio::time::sleep(timeout).await;
+        }
+        Ok(prompt)
+    }
+
+    /// Report job completion, after flushing any queued events.
+    pub async fn complete(&self, report: &CompletionReport) -> Result<(), WorkerError> {
+        if let Err(e) = self.flush_events().await {
+            tracing::warn!(job_id = %self.job_id, "Failed to flush job events: {}", e);
+        }
+        self.client
+            .clone()
+            .complete(self.request(proto::CompletionReport {
+                success: report.success,
+                message: report.message.clone(),
+                iterations: report.iterations,
+            }))
+            .await
+            .map_err(|s| self.status_error(s))?;
+        Ok(())
+    }
+}
+
+fn llm_request<T: serde::Serialize>(request: &T) -> Result<proto::LlmRequest, WorkerError> {
+    let request_json = serde_json::to_string(request).map_err(|e| WorkerError::LlmProxyFailed {
+        reason: format!("failed to encode LLM request: {}", e),
+    })?;
+    Ok(proto::LlmRequest { request_json })
+}
+
+fn llm_response<T: serde::de::DeserializeOwned>(
+    response: &proto::LlmResponse,
+) -> Result<T, WorkerError> {
+    serde_json::from_str(&response.response_json).map_err(|e| WorkerError::LlmProxyFailed {
+        reason: format!("failed to parse LLM response: {}", e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code_roundtrip() {
+        let status = error_status(ErrorCode::HandshakeRequired, "call Handshake first");
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+        assert_eq!(error_code(&status), ErrorCode::HandshakeRequired);
+
+        let plain = tonic::Status::unavailable("connection refused");
+        assert_eq!(error_code(&plain), ErrorCode::Unspecified);
+    }
+
+    #[test]
+    fn test_negotiate_version() {
+        assert_eq!(negotiate_version(&[1]), Some(1));
+        assert_eq!(negotiate_version(&[1, 7]), Some(1));
+        assert_eq!(negotiate_version(&[0, 7]), None);
+        assert_eq!(negotiate_version(&[]), None);
+    }
+}
@@ -1,7 +1,10 @@
 //! Worker mode for running inside Docker containers.
 //!
 //! When `ironclaw worker` is invoked, the binary starts in worker mode:
-//! - Connects to the orchestrator over HTTP
+//! - Connects to the orchestrator over gRPC when the version handshake
+//!   succeeds, otherwise over HTTP (`--orchestrator-proto` pins either);
+//!   over gRPC, events and log lines stream out and follow-up prompts are
+//!   pushed back on one job session
 //! - Uses a `ProxyLlmProvider` that routes LLM calls through the orchestrator
 //! - Runs container-safe tools (shell, file ops, patch)
 //! - Reports status and completion back to the orchestrator
@@ -26,10 +29,12 @@
 
 pub mod api;
 pub mod claude_bridge;
+pub mod grpc;
 pub mod proxy_llm;
 pub mod runtime;
 
 pub use api::WorkerHttpClient;
 pub use claude_bridge::ClaudeBridgeRuntime;
+pub use grpc::WorkerGrpcClient;
 pub use proxy_llm::ProxyLlmProvider;
 pub use runtime::WorkerRuntime;
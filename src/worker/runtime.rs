@@ -18,13 +18,17 @@ use crate::llm::{
 };
 use crate::safety::SafetyLayer;
 use crate::tools::ToolRegistry;
-use crate::worker::api::{CompletionReport, JobEventPayload, StatusUpdate, WorkerHttpClient};
+use crate::worker::api::{
+    CompletionReport, JobEventPayload, OrchestratorProto, StatusUpdate, WorkerHttpClient,
+};
 use crate::worker::proxy_llm::ProxyLlmProvider;
 
 /// Configuration for the worker runtime.
 pub struct WorkerConfig {
     pub job_id: Uuid,
     pub orchestrator_url: String,
+    /// Transport to the orchestrator (`--orchestrator-proto`).
+    pub orchestrator_proto: OrchestratorProto,
     pub max_iterations: u32,
     pub timeout: Duration,
 }
@@ -34,6 +38,7 @@ impl Default for WorkerConfig {
         Self {
             job_id: Uuid::nil(),
             orchestrator_url: String::new(),
+            orchestrator_proto: OrchestratorProto::Auto,
             max_iterations: 50,
             timeout: Duration::from_secs(600),
         }
@@ -58,10 +63,10 @@ impl WorkerRuntime {
     ///
     /// Reads `IRONCLAW_WORKER_TOKEN` from the environment for auth.
     pub fn new(config: WorkerConfig) -> Result<Self, WorkerError> {
-        let client = Arc::new(WorkerHttpClient::from_env(
-            config.orchestrator_url.clone(),
-            config.job_id,
-        )?);
+        let client = Arc::new(
+            WorkerHttpClient::from_env(config.orchestrator_url.clone(), config.job_id)?
+                .with_proto(config.orchestrator_proto),
+        );
 
         let llm: Arc<dyn LlmProvider> = Arc::new(ProxyLlmProvider::new(
             Arc::clone(&client),
@@ -71,6 +76,9 @@ impl WorkerRuntime {
         let safety = Arc::new(SafetyLayer::new(&SafetyConfig {
             max_output_length: 100_000,
             injection_check_enabled: true,
+            preserve_redacted: false,
+            preserve_redacted_days: 7,
+            provenance: Vec::new(),
         }));
 
         let tools = Arc::new(ToolRegistry::new());
@@ -89,6 +97,7 @@ impl WorkerRuntime {
     /// Run the worker until the job is complete or an error occurs.
     pub async fn run(self) -> Result<(), WorkerError> {
         tracing::info!("Worker starting for job {}", self.config.job_id);
+        self.client.connect().await?;
 
         // Fetch job description from orchestrator
         let job = self.client.get_job().await?;
@@ -412,9 +421,11 @@ Work independently to complete this job. Report when done."#,
     ) -> bool {
         match result {
             Ok(output) => {
-                let sanitized = self
-                    .safety
-                    .sanitize_tool_output(&selection.tool_name, &output);
+                let sanitized = self.safety.sanitize_tool_output(
+                    &JobContext::default().user_id,
+                    &selection.tool_name,
+                    &output,
+                );
                 let wrapped = self.safety.wrap_for_llm(
                     &selection.tool_name,
                     &sanitized.content,
@@ -0,0 +1,616 @@
+//! Portable memory archives.
+//!
+//! [`Workspace::export_archive`] collects a workspace's documents, chunks,
+//! connections, spaces and profile facts into a [`MemoryArchive`], which is
+//! written as a `.tar.gz` of JSON files plus the chunk embeddings as raw
+//! little-endian `f32` rows:
+//!
+//! ```text
+//! ironclaw-memory/
+//! ├── manifest.json      <- format version, counts, embedding dimension
+//! ├── documents.json
+//! ├── chunks.json        <- each chunk names its row in embeddings.f32
+//! ├── embeddings.f32
+//! ├── connections.json
+//! ├── spaces.json
+//! └── profiles.json
+//! ```
+//!
+//! Records refer to documents by path, not database id, so an archive can be
+//! imported into any backend (PostgreSQL or libSQL) and any user.
+//! [`Workspace::import_archive`] restores the chunks and embeddings as they
+//! were, so nothing is re-embedded; a chunk whose embedding the target
+//! rejects (e.g. a different vector size) is stored without one for
+//! `backfill_embeddings` to fill in.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::WorkspaceError;
+use crate::workspace::{
+    ConnectionType, MemoryConnection, MemorySpace, ProfileType, UserProfile, Workspace,
+};
+
+/// Version written to new archives; newer versions are refused on import.
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// Top-level directory inside the archive.
+const ARCHIVE_ROOT: &str = "ironclaw-memory";
+
+/// What an archive contains.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// IronClaw version that wrote the archive.
+    pub exported_by: String,
+    pub user_id: String,
+    pub agent_id: Option<Uuid>,
+    pub documents: usize,
+    pub chunks: usize,
+    /// Rows in `embeddings.f32`.
+    pub embeddings: usize,
+    /// Length of each embedding row, if there are any.
+    pub embedding_dimension: Option<usize>,
+}
+
+/// A document, keyed by its path.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedDocument {
+    pub path: String,
+    pub content: String,
+    pub metadata: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A chunk of a document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedChunk {
+    /// Path of the document the chunk belongs to.
+    pub path: String,
+    pub chunk_index: i32,
+    pub content: String,
+    /// Row in `embeddings.f32`, if the chunk was embedded.
+    pub embedding: Option<usize>,
+}
+
+/// A typed link between two documents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedConnection {
+    pub source: String,
+    pub target: String,
+    pub connection_type: ConnectionType,
+    pub strength: f32,
+    pub metadata: serde_json::Value,
+}
+
+/// A space and the paths of its documents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedSpace {
+    pub name: String,
+    pub description: String,
+    pub documents: Vec<String>,
+}
+
+/// A profile fact.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedProfile {
+    pub profile_type: ProfileType,
+    pub key: String,
+    pub value: String,
+    pub confidence: f32,
+    pub source: String,
+}
+
+/// A workspace's memory, independent of the database it came from.
+#[derive(Debug, Clone)]
+pub struct MemoryArchive {
+    pub manifest: ArchiveManifest,
+    pub documents: Vec<ArchivedDocument>,
+    pub chunks: Vec<ArchivedChunk>,
+    pub embeddings: Vec<Vec<f32>>,
+    pub connections: Vec<ArchivedConnection>,
+    pub spaces: Vec<ArchivedSpace>,
+    pub profiles: Vec<ArchivedProfile>,
+}
+
+impl MemoryArchive {
+    /// Write the archive as a `.tar.gz` at `path`.
+    pub fn write(&self, path: &Path) -> Result<(), WorkspaceError> {
+        fn json<T: Serialize>(name: &str, value: &T) -> Result<(String, Vec<u8>), WorkspaceError> {
+            let bytes = serde_json::to_vec_pretty(value).map_err(|e| archive_error(name, e))?;
+            Ok((name.to_string(), bytes))
+        }
+
+        let embeddings: Vec<u8> = self
+            .embeddings
+            .iter()
+            .flatten()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+        let entries = vec![
+            json("manifest.json", &self.manifest)?,
+            json("documents.json", &self.documents)?,
+            json("chunks.json", &self.chunks)?,
+            ("embeddings.f32".to_string(), embeddings),
+            json("connections.json", &self.connections)?,
+            json("spaces.json", &self.spaces)?,
+            json("profiles.json", &self.profiles)?,
+        ];
+        crate::util::write_tar_gz(path, ARCHIVE_ROOT, &entries)
+            .map_err(|e| archive_error(&path.display().to_string(), e))
+    }
+
+    /// Read an archive written by [`MemoryArchive::write`].
+    pub fn read(path: &Path) -> Result<Self, WorkspaceError> {
+        let entries: HashMap<String, Vec<u8>> = crate::util::read_tar_gz(path, ARCHIVE_ROOT)
+            .map_err(|e| archive_error(&path.display().to_string(), e))?
+            .into_iter()
+            .collect();
+        fn json<T: DeserializeOwned>(
+            entries: &HashMap<String, Vec<u8>>,
+            name: &str,
+        ) -> Result<T, WorkspaceError> {
+            let bytes = entries
+                .get(name)
+                .ok_or_else(|| archive_error(name, "missing from the archive"))?;
+            serde_json::from_slice(bytes).map_err(|e| archive_error(name, e))
+        }
+
+        let manifest: ArchiveManifest = json(&entries, "manifest.json")?;
+        if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+            return Err(archive_error(
+                "manifest.json",
+                format!(
+                    "format version {} is newer than this IronClaw supports ({})",
+                    manifest.format_version, ARCHIVE_FORMAT_VERSION
+                ),
+            ));
+        }
+
+        let raw = entries.get("embeddings.f32").cloned().unwrap_or_default();
+        let values: Vec<f32> = raw
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let embeddings: Vec<Vec<f32>> = match manifest.embedding_dimension {
+            Some(dim) if dim > 0 => values.chunks_exact(dim).map(<[f32]>::to_vec).collect(),
+            _ => Vec::new(),
+        };
+        if embeddings.len() != manifest.embeddings {
+            return Err(archive_error(
+                "embeddings.f32",
+                format!(
+                    "holds {} rows, the manifest lists {}",
+                    embeddings.len(),
+                    manifest.embeddings
+                ),
+            ));
+        }
+
+        Ok(Self {
+            documents: json(&entries, "documents.json")?,
+            chunks: json(&entries, "chunks.json")?,
+            connections: json(&entries, "connections.json")?,
+            spaces: json(&entries, "spaces.json")?,
+            profiles: json(&entries, "profiles.json")?,
+            manifest,
+            embeddings,
+        })
+    }
+}
+
+/// How an import treats documents and facts that already exist.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImportOptions {
+    /// Replace existing non-empty documents and profile facts instead of
+    /// keeping them.
+    pub overwrite: bool,
+}
+
+/// What an import did.
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub documents_imported: usize,
+    /// Paths kept as they were because they already had content.
+    pub documents_skipped: Vec<String>,
+    pub chunks: usize,
+    /// Chunks stored without their embedding (left for backfill).
+    pub chunks_without_embedding: usize,
+    pub connections: usize,
+    pub spaces: usize,
+    pub profiles: usize,
+}
+
+impl Workspace {
+    /// Collect the workspace's memory into a portable archive.
+    pub async fn export_archive(&self) -> Result<MemoryArchive, WorkspaceError> {
+        let docs = self
+            .storage
+            .list_documents(&self.user_id, self.agent_id)
+            .await?;
+        let paths: HashMap<Uuid, String> = docs.iter().map(|d| (d.id, d.path.clone())).collect();
+
+        let mut documents = Vec::with_capacity(docs.len());
+        let mut chunks = Vec::new();
+        let mut embeddings: Vec<Vec<f32>> = Vec::new();
+        let mut connections = Vec::new();
+        let mut seen_connections = HashSet::new();
+        for doc in &docs {
+            documents.push(ArchivedDocument {
+                path: doc.path.clone(),
+                content: doc.content.clone(),
+                metadata: doc.metadata.clone(),
+                created_at: doc.created_at,
+                updated_at: doc.updated_at,
+            });
+
+            for chunk in self.storage.list_chunks(doc.id).await? {
+                // Rows share one width; a stray size is left for backfill.
+                let embedding = chunk.embedding.filter(|e| {
+                    !e.is_empty()
+                        && embeddings
+                            .first()
+                            .is_none_or(|first| first.len() == e.len())
+                });
+                let row = embedding.map(|e| {
+                    embeddings.push(e);
+                    embeddings.len() - 1
+                });
+                chunks.push(ArchivedChunk {
+                    path: doc.path.clone(),
+                    chunk_index: chunk.chunk_index,
+                    content: chunk.content,
+                    embedding: row,
+                });
+            }
+
+            for connection in self.storage.get_connections(doc.id).await? {
+                if !seen_connections.insert(connection.id) {
+                    continue;
+                }
+                let (Some(source), Some(target)) = (
+                    paths.get(&connection.source_id),
+                    paths.get(&connection.target_id),
+                ) else {
+                    continue;
+                };
+                connections.push(ArchivedConnection {
+                    source: source.clone(),
+                    target: target.clone(),
+                    connection_type: connection.connection_type,
+                    strength: connection.strength,
+                    metadata: connection.metadata,
+                });
+            }
+        }
+
+        let mut spaces = Vec::new();
+        for space in self.storage.list_spaces(&self.user_id).await? {
+            let members = self.storage.list_space_documents(space.id).await?;
+            spaces.push(ArchivedSpace {
+                name: space.name,
+                description: space.description,
+                documents: members.into_iter().map(|d| d.path).collect(),
+            });
+        }
+
+        let profiles = self
+            .storage
+            .get_profile(&self.user_id)
+            .await?
+            .into_iter()
+            .map(|p| ArchivedProfile {
+                profile_type: p.profile_type,
+                key: p.key,
+                value: p.value,
+                confidence: p.confidence,
+                source: p.source,
+            })
+            .collect();
+
+        Ok(MemoryArchive {
+            manifest: ArchiveManifest {
+                format_version: ARCHIVE_FORMAT_VERSION,
+                exported_at: Utc::now(),
+                exported_by: env!("CARGO_PKG_VERSION").to_string(),
+                user_id: self.user_id.clone(),
+                agent_id: self.agent_id,
+                documents: documents.len(),
+                chunks: chunks.len(),
+                embeddings: embeddings.len(),
+                embedding_dimension: embeddings.first().map(Vec::len),
+            },
+            documents,
+            chunks,
+            embeddings,
+            connections,
+            spaces,
+            profiles,
+        })
+    }
+
+    /// Restore an archive into this workspace. Documents are matched by
+    /// path; existing ones with content are kept unless `overwrite` is set.
+    pub async fn import_archive(
+        &self,
+        archive: &MemoryArchive,
+        options: ImportOptions,
+    ) -> Result<ImportReport, WorkspaceError> {
+        let mut report = ImportReport::default();
+        let mut chunks_by_path: HashMap<&str, Vec<&ArchivedChunk>> = HashMap::new();
+        for chunk in &archive.chunks {
+            chunks_by_path.entry(&chunk.path).or_default().push(chunk);
+        }
+
+        // Ids in this workspace of every archived path, imported or kept.
+        let mut ids: HashMap<&str, Uuid> = HashMap::new();
+        for doc in &archive.documents {
+            let existing = match self
+                .storage
+                .get_document_by_path(&self.user_id, self.agent_id, &doc.path)
+                .await
+            {
+                Ok(existing) => Some(existing),
+                Err(WorkspaceError::DocumentNotFound { .. }) => None,
+                Err(e) => return Err(e),
+            };
+            if let Some(existing) = &existing
+                && !existing.content.is_empty()
+                && !options.overwrite
+            {
+                ids.insert(&doc.path, existing.id);
+                report.documents_skipped.push(doc.path.clone());
+                continue;
+            }
+
+            let target = match existing {
+                Some(existing) => existing,
+                None => {
+                    self.storage
+                        .get_or_create_document_by_path(&self.user_id, self.agent_id, &doc.path)
+                        .await?
+                }
+            };
+            self.storage
+                .update_document(target.id, &doc.content)
+                .await?;
+            if doc.metadata.as_object().is_some_and(|m| !m.is_empty()) {
+                self.storage
+                    .update_document_metadata(target.id, &doc.metadata)
+                    .await?;
+            }
+            self.storage.delete_chunks(target.id).await?;
+
+            let chunks = chunks_by_path.remove(doc.path.as_str()).unwrap_or_default();
+            if chunks.is_empty() && !doc.content.is_empty() {
+                self.reindex_document(target.id).await?;
+            }
+            for chunk in chunks {
+                let embedding = chunk
+                    .embedding
+                    .and_then(|row| archive.embeddings.get(row))
+                    .map(Vec::as_slice);
+                let stored = self
+                    .storage
+                    .insert_chunk(target.id, chunk.chunk_index, &chunk.content, embedding)
+                    .await;
+                if stored.is_err() || embedding.is_none() {
+                    if stored.is_err() {
+                        self.storage
+                            .insert_chunk(target.id, chunk.chunk_index, &chunk.content, None)
+                            .await?;
+                    }
+                    report.chunks_without_embedding += 1;
+                }
+                report.chunks += 1;
+            }
+
+            ids.insert(&doc.path, target.id);
+            report.documents_imported += 1;
+        }
+
+        for connection in &archive.connections {
+            let (Some(&source), Some(&target)) = (
+                ids.get(connection.source.as_str()),
+                ids.get(connection.target.as_str()),
+            ) else {
+                continue;
+            };
+            let exists = self.storage.get_connections(source).await?.iter().any(|c| {
+                c.source_id == source
+                    && c.target_id == target
+                    && c.connection_type == connection.connection_type
+            });
+            if exists {
+                continue;
+            }
+            let mut link = MemoryConnection::new(source, target, connection.connection_type);
+            link.strength = connection.strength;
+            link.metadata = connection.metadata.clone();
+            self.storage.create_connection(&link).await?;
+            report.connections += 1;
+        }
+
+        for space in &archive.spaces {
+            let target = match self
+                .storage
+                .get_space_by_name(&self.user_id, &space.name)
+                .await?
+            {
+                Some(existing) => existing,
+                None => {
+                    let mut created = MemorySpace::new(&self.user_id, &space.name);
+                    created.description = space.description.clone();
+                    self.storage.create_space(&created).await?;
+                    created
+                }
+            };
+            for path in &space.documents {
+                if let Some(&id) = ids.get(path.as_str()) {
+                    self.storage.add_to_space(target.id, id).await?;
+                }
+            }
+            report.spaces += 1;
+        }
+
+        let existing: HashSet<String> = self
+            .storage
+            .get_profile(&self.user_id)
+            .await?
+            .into_iter()
+            .map(|p| p.key)
+            .collect();
+        for fact in &archive.profiles {
+            if existing.contains(&fact.key) && !options.overwrite {
+                continue;
+            }
+            let mut profile =
+                UserProfile::new(&self.user_id, fact.profile_type, &fact.key, &fact.value)
+                    .with_source(&fact.source);
+            profile.confidence = fact.confidence;
+            self.storage.upsert_profile(&profile).await?;
+            report.profiles += 1;
+        }
+
+        Ok(report)
+    }
+}
+
+fn archive_error(what: &str, reason: impl std::fmt::Display) -> WorkspaceError {
+    WorkspaceError::Archive {
+        reason: format!("{}: {}", what, reason),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> MemoryArchive {
+        let now = Utc::now();
+        MemoryArchive {
+            manifest: ArchiveManifest {
+                format_version: ARCHIVE_FORMAT_VERSION,
+                exported_at: now,
+                exported_by: "test".to_string(),
+                user_id: "default".to_string(),
+                agent_id: None,
+                documents: 2,
+                chunks: 3,
+                embeddings: 2,
+                embedding_dimension: Some(3),
+            },
+            documents: vec![
+                ArchivedDocument {
+                    path: "MEMORY.md".to_string(),
+                    content: "Prefers tea.".to_string(),
+                    metadata: serde_json::json!({"tags": ["pref"]}),
+                    created_at: now,
+                    updated_at: now,
+                },
+                ArchivedDocument {
+                    path: "notes/plan.md".to_string(),
+                    content: "Ship in March.\n\nBudget 40k.".to_string(),
+                    metadata: serde_json::json!({}),
+                    created_at: now,
+                    updated_at: now,
+                },
+            ],
+            chunks: vec![
+                ArchivedChunk {
+                    path: "MEMORY.md".to_string(),
+                    chunk_index: 0,
+                    content: "Prefers tea.".to_string(),
+                    embedding: Some(0),
+                },
+                ArchivedChunk {
+                    path: "notes/plan.md".to_string(),
+                    chunk_index: 0,
+                    content: "Ship in March.".to_string(),
+                    embedding: Some(1),
+                },
+                ArchivedChunk {
+                    path: "notes/plan.md".to_string(),
+                    chunk_index: 1,
+                    content: "Budget 40k.".to_string(),
+                    embedding: None,
+                },
+            ],
+            embeddings: vec![vec![0.1, -0.2, 0.3], vec![1.5, 0.0, -7.25]],
+            connections: vec![ArchivedConnection {
+                source: "notes/plan.md".to_string(),
+                target: "MEMORY.md".to_string(),
+                connection_type: ConnectionType::Extends,
+                strength: 0.8,
+                metadata: serde_json::json!({}),
+            }],
+            spaces: vec![ArchivedSpace {
+                name: "work".to_string(),
+                description: "Work notes".to_string(),
+                documents: vec!["notes/plan.md".to_string()],
+            }],
+            profiles: vec![ArchivedProfile {
+                profile_type: ProfileType::Static,
+                key: "name".to_string(),
+                value: "Sam".to_string(),
+                confidence: 1.0,
+                source: "user_stated".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memory.tar.gz");
+        let archive = sample();
+        archive.write(&path).unwrap();
+
+        let read = MemoryArchive::read(&path).unwrap();
+        assert_eq!(read.documents, archive.documents);
+        assert_eq!(read.chunks, archive.chunks);
+        assert_eq!(read.embeddings, archive.embeddings);
+        assert_eq!(read.connections, archive.connections);
+        assert_eq!(read.spaces, archive.spaces);
+        assert_eq!(read.profiles, archive.profiles);
+        assert_eq!(read.manifest.embedding_dimension, Some(3));
+    }
+
+    #[test]
+    fn test_read_rejects_newer_format_and_bad_embeddings() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut newer = sample();
+        newer.manifest.format_version = ARCHIVE_FORMAT_VERSION + 1;
+        let path = dir.path().join("newer.tar.gz");
+        newer.write(&path).unwrap();
+        let err = MemoryArchive::read(&path).unwrap_err().to_string();
+        assert!(err.contains("newer than this IronClaw supports"), "{err}");
+
+        let mut short = sample();
+        short.manifest.embeddings = 3;
+        let path = dir.path().join("short.tar.gz");
+        short.write(&path).unwrap();
+        let err = MemoryArchive::read(&path).unwrap_err().to_string();
+        assert!(err.contains("holds 2 rows, the manifest lists 3"), "{err}");
+    }
+
+    #[test]
+    fn test_read_rejects_foreign_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("other.tar.gz");
+        crate::util::write_tar_gz(&path, "something-else", &[("a".to_string(), vec![1])]).unwrap();
+        let err = MemoryArchive::read(&path).unwrap_err().to_string();
+        assert!(
+            err.contains("manifest.json: missing from the archive"),
+            "{err}"
+        );
+    }
+}
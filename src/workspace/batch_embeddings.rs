@@ -2,9 +2,18 @@
 //!
 //! Processes multiple embedding requests together for efficiency,
 //! reducing API call overhead and latency.
+//!
+//! [`ParallelEmbedder`] is what indexing uses: it packs chunks into
+//! batches sized to the provider's item and token limits, sends several
+//! batches at once, and retries or splits batches that fail so one bad
+//! chunk or a rate limit doesn't cost the whole document its embeddings.
 
+use std::ops::Range;
 use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
 
 use tokio::sync::{Mutex, Notify};
 
@@ -117,6 +126,283 @@ impl BatchEmbeddingProcessor {
     }
 }
 
+/// Tuning for [`ParallelEmbedder`].
+#[derive(Debug, Clone)]
+pub struct EmbedderConfig {
+    /// Batches in flight at once.
+    pub concurrency: usize,
+    /// Cap on texts per batch, below the provider's own limit.
+    pub max_batch_size: Option<usize>,
+    /// Retries per batch on rate limits and transport errors.
+    pub max_retries: u32,
+    /// First retry delay when the provider gives no `Retry-After`; doubles per attempt.
+    pub retry_backoff: Duration,
+}
+
+impl Default for EmbedderConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            max_batch_size: None,
+            max_retries: 3,
+            retry_backoff: Duration::from_millis(250),
+        }
+    }
+}
+
+/// Longest wait between retries of one batch.
+const MAX_BACKOFF: Duration = Duration::from_secs(8);
+
+/// Rough token count of `text` (about four bytes per token).
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4).max(1)
+}
+
+/// Split `texts` into consecutive batches of at most `max_items` texts and
+/// `max_tokens` estimated tokens. A text over the token limit goes alone.
+pub fn plan_batches(texts: &[String], max_items: usize, max_tokens: usize) -> Vec<Range<usize>> {
+    let max_items = max_items.max(1);
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut tokens = 0;
+    for (i, text) in texts.iter().enumerate() {
+        let t = estimate_tokens(text);
+        if i > start && (i - start >= max_items || tokens + t > max_tokens) {
+            batches.push(start..i);
+            start = i;
+            tokens = 0;
+        }
+        tokens += t;
+    }
+    if start < texts.len() {
+        batches.push(start..texts.len());
+    }
+    batches
+}
+
+/// Running totals for everything a [`ParallelEmbedder`] has embedded.
+#[derive(Debug, Default)]
+struct Counters {
+    texts: AtomicU64,
+    tokens: AtomicU64,
+    batches: AtomicU64,
+    retries: AtomicU64,
+    failures: AtomicU64,
+    busy_ms: AtomicU64,
+}
+
+/// Snapshot of embedding throughput.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EmbeddingStats {
+    /// Texts embedded successfully.
+    pub texts: u64,
+    /// Estimated tokens in those texts.
+    pub tokens: u64,
+    /// Provider calls that succeeded.
+    pub batches: u64,
+    /// Provider calls retried after a rate limit or transport error.
+    pub retries: u64,
+    /// Texts left without an embedding.
+    pub failures: u64,
+    /// Wall-clock time spent in [`ParallelEmbedder::embed_all`].
+    pub busy: Duration,
+    /// Current adaptive batch size.
+    pub batch_size: usize,
+}
+
+impl EmbeddingStats {
+    /// Texts embedded per second of busy time.
+    pub fn texts_per_sec(&self) -> f64 {
+        let secs = self.busy.as_secs_f64();
+        if secs > 0.0 {
+            self.texts as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Embeds many texts concurrently in provider-sized batches.
+///
+/// The batch size starts at the provider's limit, halves whenever the
+/// provider rate-limits, and grows back by a quarter after each success.
+pub struct ParallelEmbedder {
+    provider: Arc<dyn EmbeddingProvider>,
+    config: EmbedderConfig,
+    batch_size: AtomicUsize,
+    counters: Counters,
+}
+
+impl ParallelEmbedder {
+    /// Create an embedder for `provider`.
+    pub fn new(provider: Arc<dyn EmbeddingProvider>, config: EmbedderConfig) -> Self {
+        let embedder = Self {
+            provider,
+            config,
+            batch_size: AtomicUsize::new(0),
+            counters: Counters::default(),
+        };
+        embedder
+            .batch_size
+            .store(embedder.batch_cap(), Ordering::Relaxed);
+        embedder
+    }
+
+    /// The underlying provider.
+    pub fn provider(&self) -> &Arc<dyn EmbeddingProvider> {
+        &self.provider
+    }
+
+    /// Totals since this embedder was created.
+    pub fn stats(&self) -> EmbeddingStats {
+        let c = &self.counters;
+        EmbeddingStats {
+            texts: c.texts.load(Ordering::Relaxed),
+            tokens: c.tokens.load(Ordering::Relaxed),
+            batches: c.batches.load(Ordering::Relaxed),
+            retries: c.retries.load(Ordering::Relaxed),
+            failures: c.failures.load(Ordering::Relaxed),
+            busy: Duration::from_millis(c.busy_ms.load(Ordering::Relaxed)),
+            batch_size: self.batch_size.load(Ordering::Relaxed),
+        }
+    }
+
+    fn batch_cap(&self) -> usize {
+        let cap = self.provider.max_batch_size().max(1);
+        self.config.max_batch_size.map_or(cap, |n| n.clamp(1, cap))
+    }
+
+    /// Embed every text, returning `None` for those that could not be embedded.
+    ///
+    /// Results are in input order. Only an authentication failure stops
+    /// early; every other error is confined to the smallest batch that
+    /// still fails.
+    pub async fn embed_all(&self, texts: &[String]) -> Vec<Option<Vec<f32>>> {
+        let mut results: Vec<Option<Vec<f32>>> = vec![None; texts.len()];
+        if texts.is_empty() {
+            return results;
+        }
+
+        let started = Instant::now();
+        let batches = plan_batches(
+            texts,
+            self.batch_size.load(Ordering::Relaxed),
+            self.provider.max_batch_tokens(),
+        );
+        let mut done = futures::stream::iter(batches)
+            .map(|range| self.embed_range(texts, range))
+            .buffer_unordered(self.config.concurrency.max(1));
+        while let Some(embedded) = done.next().await {
+            for (i, embedding) in embedded {
+                results[i] = Some(embedding);
+            }
+        }
+
+        let ok = results.iter().filter(|r| r.is_some()).count() as u64;
+        let c = &self.counters;
+        c.texts.fetch_add(ok, Ordering::Relaxed);
+        c.failures
+            .fetch_add(texts.len() as u64 - ok, Ordering::Relaxed);
+        c.busy_ms
+            .fetch_add(started.elapsed().as_millis() as u64, Ordering::Relaxed);
+        tracing::debug!(
+            texts = texts.len(),
+            embedded = ok,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "Embedded batch of texts"
+        );
+        results
+    }
+
+    /// Embed `range` of `texts`, splitting it on failure.
+    async fn embed_range(&self, texts: &[String], range: Range<usize>) -> Vec<(usize, Vec<f32>)> {
+        let mut embedded = Vec::new();
+        let mut pending = vec![range];
+        while let Some(range) = pending.pop() {
+            let batch = &texts[range.clone()];
+            match self.embed_with_retry(batch).await {
+                Ok(vectors) => {
+                    embedded.extend(range.zip(vectors));
+                }
+                Err(EmbeddingError::AuthFailed) => {
+                    tracing::warn!("Embedding provider rejected credentials");
+                    break;
+                }
+                Err(e) if range.len() > 1 => {
+                    tracing::debug!(
+                        size = range.len(),
+                        "Splitting failed embedding batch: {}",
+                        e
+                    );
+                    let mid = range.start + range.len() / 2;
+                    pending.push(mid..range.end);
+                    pending.push(range.start..mid);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to generate embedding: {}", e);
+                }
+            }
+        }
+        embedded
+    }
+
+    /// One provider call with retries on rate limits and transport errors.
+    async fn embed_with_retry(&self, batch: &[String]) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        let mut attempt = 0;
+        loop {
+            let err = match self.provider.embed_batch(batch).await {
+                Ok(vectors) if vectors.len() == batch.len() => {
+                    self.record_success(batch);
+                    return Ok(vectors);
+                }
+                Ok(vectors) => EmbeddingError::InvalidResponse(format!(
+                    "expected {} embeddings, got {}",
+                    batch.len(),
+                    vectors.len()
+                )),
+                Err(e) => e,
+            };
+
+            let wait = match &err {
+                EmbeddingError::RateLimited { retry_after } => {
+                    let halved = (batch.len() / 2).max(1);
+                    self.batch_size.fetch_min(halved, Ordering::Relaxed);
+                    *retry_after
+                }
+                EmbeddingError::HttpError(_) => None,
+                _ => return Err(err),
+            };
+            if attempt >= self.config.max_retries {
+                return Err(err);
+            }
+            let backoff = wait.unwrap_or_else(|| {
+                self.config
+                    .retry_backoff
+                    .saturating_mul(1 << attempt.min(16))
+                    .min(MAX_BACKOFF)
+            });
+            attempt += 1;
+            self.counters.retries.fetch_add(1, Ordering::Relaxed);
+            tracing::debug!(attempt, ?backoff, "Retrying embedding batch: {}", err);
+            tokio::time::sleep(backoff).await;
+        }
+    }
+
+    fn record_success(&self, batch: &[String]) {
+        let c = &self.counters;
+        c.batches.fetch_add(1, Ordering::Relaxed);
+        let tokens: usize = batch.iter().map(|t| estimate_tokens(t)).sum();
+        c.tokens.fetch_add(tokens as u64, Ordering::Relaxed);
+
+        let cap = self.batch_cap();
+        let _ = self
+            .batch_size
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |size| {
+                (size < cap).then(|| (size + size.div_ceil(4)).min(cap))
+            });
+    }
+}
+
 /// Citation support for search results.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Citation {
@@ -175,7 +461,155 @@ impl CitedSearchResult {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Mutex as StdMutex;
+
+    use async_trait::async_trait;
+
     use super::*;
+    use crate::workspace::MockEmbeddings;
+
+    /// Rejects any batch containing "bad"; rate-limits the first `limited` calls.
+    struct FlakyEmbeddings {
+        inner: MockEmbeddings,
+        limited: AtomicUsize,
+        calls: StdMutex<Vec<usize>>,
+    }
+
+    impl FlakyEmbeddings {
+        fn new(limited: usize) -> Self {
+            Self {
+                inner: MockEmbeddings::new(8),
+                limited: AtomicUsize::new(limited),
+                calls: StdMutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl EmbeddingProvider for FlakyEmbeddings {
+        fn dimension(&self) -> usize {
+            8
+        }
+
+        fn model_name(&self) -> &str {
+            "flaky"
+        }
+
+        fn max_input_length(&self) -> usize {
+            10_000
+        }
+
+        fn max_batch_size(&self) -> usize {
+            8
+        }
+
+        async fn embed(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+            self.inner.embed(text).await
+        }
+
+        async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+            self.calls.lock().unwrap().push(texts.len());
+            if self
+                .limited
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Err(EmbeddingError::RateLimited {
+                    retry_after: Some(Duration::ZERO),
+                });
+            }
+            if texts.iter().any(|t| t.contains("bad")) {
+                return Err(EmbeddingError::InvalidResponse("rejected".to_string()));
+            }
+            let mut out = Vec::new();
+            for text in texts {
+                out.push(self.inner.embed(text).await?);
+            }
+            Ok(out)
+        }
+    }
+
+    fn texts(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("chunk {i}")).collect()
+    }
+
+    #[test]
+    fn test_plan_batches_respects_items_and_tokens() {
+        let batches = plan_batches(&texts(10), 4, usize::MAX);
+        assert_eq!(batches, vec![0..4, 4..8, 8..10]);
+
+        let mut big = texts(3);
+        big[1] = "x".repeat(400); // 100 tokens, over the budget on its own
+        assert_eq!(plan_batches(&big, 10, 50), vec![0..1, 1..2, 2..3]);
+        assert_eq!(plan_batches(&texts(6), 10, 6), vec![0..3, 3..6]);
+        assert!(plan_batches(&[], 4, 100).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_embed_all_keeps_order_in_batches() {
+        let provider = Arc::new(FlakyEmbeddings::new(0));
+        let embedder = ParallelEmbedder::new(provider.clone(), EmbedderConfig::default());
+        let input = texts(20);
+
+        let results = embedder.embed_all(&input).await;
+        let mock = MockEmbeddings::new(8);
+        for (text, result) in input.iter().zip(&results) {
+            assert_eq!(result.as_ref().unwrap(), &mock.embed(text).await.unwrap());
+        }
+        assert_eq!(provider.calls.lock().unwrap().len(), 3);
+
+        let stats = embedder.stats();
+        assert_eq!((stats.texts, stats.batches, stats.failures), (20, 3, 0));
+    }
+
+    #[tokio::test]
+    async fn test_failed_batch_is_split_to_isolate_bad_text() {
+        let provider = Arc::new(FlakyEmbeddings::new(0));
+        let embedder = ParallelEmbedder::new(provider, EmbedderConfig::default());
+        let mut input = texts(8);
+        input[5] = "bad chunk".to_string();
+
+        let results = embedder.embed_all(&input).await;
+        for (i, result) in results.iter().enumerate() {
+            assert_eq!(result.is_none(), i == 5, "chunk {i}");
+        }
+        assert_eq!(embedder.stats().failures, 1);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_retries_and_shrinks_batches() {
+        let provider = Arc::new(FlakyEmbeddings::new(1));
+        let config = EmbedderConfig {
+            concurrency: 1,
+            ..EmbedderConfig::default()
+        };
+        let embedder = ParallelEmbedder::new(provider.clone(), config);
+
+        let results = embedder.embed_all(&texts(8)).await;
+        assert!(results.iter().all(Option::is_some));
+        let stats = embedder.stats();
+        assert_eq!(stats.retries, 1);
+        // Halved to 4 by the rate limit, then grown by a quarter on success.
+        assert_eq!(stats.batch_size, 5);
+
+        embedder.embed_all(&texts(8)).await;
+        let calls = provider.calls.lock().unwrap().clone();
+        assert_eq!(calls, vec![8, 8, 5, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_size_capped_by_config() {
+        let provider = Arc::new(FlakyEmbeddings::new(0));
+        let config = EmbedderConfig {
+            max_batch_size: Some(3),
+            ..EmbedderConfig::default()
+        };
+        let embedder = ParallelEmbedder::new(provider.clone(), config);
+        embedder.embed_all(&texts(7)).await;
+        let mut calls = provider.calls.lock().unwrap().clone();
+        calls.sort();
+        assert_eq!(calls, vec![1, 3, 3]);
+    }
 
     #[test]
     fn test_citation_format() {
@@ -25,6 +25,8 @@ pub mod paths {
     pub const USER: &str = "USER.md";
     /// Periodic checklist for heartbeat.
     pub const HEARTBEAT: &str = "HEARTBEAT.md";
+    /// Project terminology and banned words.
+    pub const GLOSSARY: &str = "GLOSSARY.md";
     /// Root runbook/readme.
     pub const README: &str = "README.md";
     /// Daily logs directory.
@@ -33,6 +35,8 @@ pub mod paths {
     pub const CONTEXT_DIR: &str = "context/";
     /// Spaces directory for organized collections.
     pub const SPACES_DIR: &str = "spaces/";
+    /// Composite tool pipeline definitions (`<name>.json`).
+    pub const PIPELINES_DIR: &str = "pipelines/";
 }
 
 /// A memory document stored in the database.
@@ -353,6 +357,9 @@ pub struct DocumentMetadata {
     /// URL the content was ingested from (if applicable).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source_url: Option<String>,
+    /// SHA-256 of the source file, for documents ingested from local files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_hash: Option<String>,
     /// When the event described in the document occurred (vs when it was stored).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub event_date: Option<NaiveDate>,
@@ -431,6 +438,23 @@ impl MemoryChunk {
     }
 }
 
+/// Storage used by one document and its chunks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentUsage {
+    /// Document ID.
+    pub document_id: Uuid,
+    /// Document path.
+    pub path: String,
+    /// Size of the document content in bytes.
+    pub content_bytes: i64,
+    /// Number of search chunks.
+    pub chunks: i64,
+    /// Bytes taken by chunk embeddings.
+    pub embedding_bytes: i64,
+    /// Last update timestamp.
+    pub updated_at: DateTime<Utc>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -575,6 +599,7 @@ mod tests {
     fn test_document_metadata_roundtrip() {
         let meta = DocumentMetadata {
             source_url: Some("https://example.com".to_string()),
+            source_hash: None,
             event_date: Some(NaiveDate::from_ymd_opt(2025, 6, 15).unwrap()),
             importance: 0.8,
             access_count: 5,
@@ -45,6 +45,18 @@ pub trait EmbeddingProvider: Send + Sync {
     /// Maximum input length in characters.
     fn max_input_length(&self) -> usize;
 
+    /// Most texts one `embed_batch` call accepts.
+    ///
+    /// Defaults to 1 for providers without a native batch endpoint.
+    fn max_batch_size(&self) -> usize {
+        1
+    }
+
+    /// Most estimated tokens one `embed_batch` call accepts.
+    fn max_batch_tokens(&self) -> usize {
+        8_000
+    }
+
     /// Generate an embedding for a single text.
     async fn embed(&self, text: &str) -> Result<Vec<f32>, EmbeddingError>;
 
@@ -74,7 +86,7 @@ impl OpenAiEmbeddings {
     /// Uses text-embedding-3-small which has 1536 dimensions.
     pub fn new(api_key: impl Into<String>) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: crate::outbound::client(),
             api_key: api_key.into(),
             model: "text-embedding-3-small".to_string(),
             dimension: 1536,
@@ -84,7 +96,7 @@ impl OpenAiEmbeddings {
     /// Use text-embedding-ada-002 model.
     pub fn ada_002(api_key: impl Into<String>) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: crate::outbound::client(),
             api_key: api_key.into(),
             model: "text-embedding-ada-002".to_string(),
             dimension: 1536,
@@ -94,7 +106,7 @@ impl OpenAiEmbeddings {
     /// Use text-embedding-3-large model.
     pub fn large(api_key: impl Into<String>) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: crate::outbound::client(),
             api_key: api_key.into(),
             model: "text-embedding-3-large".to_string(),
             dimension: 3072,
@@ -108,7 +120,7 @@ impl OpenAiEmbeddings {
         dimension: usize,
     ) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: crate::outbound::client(),
             api_key: api_key.into(),
             model: model.into(),
             dimension,
@@ -148,6 +160,16 @@ impl EmbeddingProvider for OpenAiEmbeddings {
         32_000
     }
 
+    fn max_batch_size(&self) -> usize {
+        // The embeddings endpoint takes up to 2048 inputs per request
+        2048
+    }
+
+    fn max_batch_tokens(&self) -> usize {
+        // ...and up to 300k tokens across them
+        300_000
+    }
+
     async fn embed(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
         if text.len() > self.max_input_length() {
             return Err(EmbeddingError::TextTooLong {
@@ -233,7 +255,7 @@ impl NearAiEmbeddings {
         session: std::sync::Arc<crate::llm::SessionManager>,
     ) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: crate::outbound::client(),
             base_url: base_url.into(),
             session,
             model: "text-embedding-3-small".to_string(),
@@ -279,6 +301,14 @@ impl EmbeddingProvider for NearAiEmbeddings {
         32_000
     }
 
+    fn max_batch_size(&self) -> usize {
+        256
+    }
+
+    fn max_batch_tokens(&self) -> usize {
+        100_000
+    }
+
     async fn embed(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
         if text.len() > self.max_input_length() {
             return Err(EmbeddingError::TextTooLong {
@@ -383,6 +413,10 @@ impl EmbeddingProvider for MockEmbeddings {
         10_000
     }
 
+    fn max_batch_size(&self) -> usize {
+        64
+    }
+
     async fn embed(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
         // Generate a deterministic embedding based on text hash
         use std::hash::{Hash, Hasher};
@@ -21,7 +21,7 @@ impl GeminiEmbeddings {
     /// Create a new Gemini embedding provider with the default model (text-embedding-004).
     pub fn new(api_key: impl Into<String>) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: crate::outbound::client(),
             api_key: api_key.into(),
             model: "text-embedding-004".to_string(),
             dimension: 768,
@@ -36,7 +36,7 @@ impl GeminiEmbeddings {
         dimension: usize,
     ) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: crate::outbound::client(),
             api_key: api_key.into(),
             model: model.into(),
             dimension,
@@ -106,6 +106,15 @@ impl EmbeddingProvider for GeminiEmbeddings {
         8_000
     }
 
+    fn max_batch_size(&self) -> usize {
+        // batchEmbedContents takes up to 100 requests
+        100
+    }
+
+    fn max_batch_tokens(&self) -> usize {
+        200_000
+    }
+
     async fn embed(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
         if text.len() > self.max_input_length() {
             return Err(EmbeddingError::TextTooLong {
@@ -0,0 +1,520 @@
+//! Project glossary: terms, definitions, preferred phrasing and banned words.
+//!
+//! The glossary lives in the workspace as `GLOSSARY.md`, so it can be
+//! edited with `/glossary`, the `memory_glossary` tool or plain
+//! `memory_write`:
+//!
+//! ```markdown
+//! # Glossary
+//!
+//! ## deployment unit
+//! A versioned bundle of services released together.
+//! Avoid: release bundle, deploy package
+//!
+//! ## Banned words
+//! - utilize -> use
+//! - synergy
+//! ```
+//!
+//! Each `##` heading is a term, written the way it should be used. The
+//! lines under it are its definition, except `Avoid:`, which lists
+//! phrasings that should be replaced by the term. Items under
+//! `## Banned words` are never used; an `-> replacement` swaps them,
+//! otherwise they are masked.
+//!
+//! Only entries that come up in the conversation are added to the prompt
+//! ([`Glossary::prompt_section`]). The agent's transformResponse step
+//! runs [`Glossary::enforce`] on every final answer, leaving code blocks
+//! and inline code alone.
+
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+use crate::error::WorkspaceError;
+use crate::workspace::{Workspace, paths};
+
+/// Heading of the banned-words section.
+const BANNED_HEADING: &str = "Banned words";
+
+/// Most entries added to one prompt.
+const MAX_PROMPT_ENTRIES: usize = 20;
+
+/// One glossary term.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    /// The term, as it should be written.
+    pub term: String,
+    #[serde(default)]
+    pub definition: String,
+    /// Phrasings to replace with the term.
+    #[serde(default)]
+    pub avoid: Vec<String>,
+}
+
+/// A word the agent must not use.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BannedWord {
+    pub word: String,
+    /// Used in its place; the word is masked when unset.
+    #[serde(default)]
+    pub replacement: Option<String>,
+}
+
+/// The parsed `GLOSSARY.md`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Glossary {
+    pub entries: Vec<GlossaryEntry>,
+    pub banned: Vec<BannedWord>,
+}
+
+impl Glossary {
+    /// Parse the document. Text before the first `##` heading is ignored.
+    pub fn parse(content: &str) -> Self {
+        let mut glossary = Self::default();
+        let mut in_banned = false;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if let Some(heading) = line.strip_prefix("## ") {
+                let heading = heading.trim();
+                in_banned = heading.eq_ignore_ascii_case(BANNED_HEADING);
+                if !in_banned && !heading.is_empty() {
+                    glossary.entries.push(GlossaryEntry {
+                        term: heading.to_string(),
+                        definition: String::new(),
+                        avoid: Vec::new(),
+                    });
+                }
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if in_banned {
+                let item = line.trim_start_matches(['-', '*']).trim();
+                let (word, replacement) = match item.split_once("->") {
+                    Some((word, replacement)) => (word.trim(), Some(replacement.trim())),
+                    None => (item, None),
+                };
+                if !word.is_empty() {
+                    glossary.banned.push(BannedWord {
+                        word: word.to_string(),
+                        replacement: replacement.filter(|r| !r.is_empty()).map(String::from),
+                    });
+                }
+            } else if let Some(entry) = glossary.entries.last_mut() {
+                let avoid = line
+                    .get(..6)
+                    .filter(|prefix| prefix.eq_ignore_ascii_case("avoid:"))
+                    .map(|_| &line[6..]);
+                match avoid {
+                    Some(list) => entry.avoid.extend(split_list(list)),
+                    None if entry.definition.is_empty() => entry.definition = line.to_string(),
+                    None => {
+                        entry.definition.push(' ');
+                        entry.definition.push_str(line);
+                    }
+                }
+            }
+        }
+        glossary
+    }
+
+    /// Render back to the document format [`Glossary::parse`] reads.
+    pub fn render(&self) -> String {
+        let mut out = String::from("# Glossary\n");
+        for entry in &self.entries {
+            out.push_str(&format!("\n## {}\n", entry.term));
+            if !entry.definition.is_empty() {
+                out.push_str(&format!("{}\n", entry.definition));
+            }
+            if !entry.avoid.is_empty() {
+                out.push_str(&format!("Avoid: {}\n", entry.avoid.join(", ")));
+            }
+        }
+        if !self.banned.is_empty() {
+            out.push_str(&format!("\n## {}\n", BANNED_HEADING));
+            for banned in &self.banned {
+                match &banned.replacement {
+                    Some(replacement) => {
+                        out.push_str(&format!("- {} -> {}\n", banned.word, replacement))
+                    }
+                    None => out.push_str(&format!("- {}\n", banned.word)),
+                }
+            }
+        }
+        out
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.banned.is_empty()
+    }
+
+    /// Look up a term, ignoring case.
+    pub fn get(&self, term: &str) -> Option<&GlossaryEntry> {
+        self.entries
+            .iter()
+            .find(|e| e.term.eq_ignore_ascii_case(term.trim()))
+    }
+
+    /// Add a term or update the one with the same name. An empty
+    /// definition or avoid list keeps the existing one.
+    pub fn upsert(&mut self, entry: GlossaryEntry) {
+        match self
+            .entries
+            .iter_mut()
+            .find(|e| e.term.eq_ignore_ascii_case(&entry.term))
+        {
+            Some(existing) => {
+                existing.term = entry.term;
+                if !entry.definition.is_empty() {
+                    existing.definition = entry.definition;
+                }
+                for phrase in entry.avoid {
+                    if !existing
+                        .avoid
+                        .iter()
+                        .any(|a| a.eq_ignore_ascii_case(&phrase))
+                    {
+                        existing.avoid.push(phrase);
+                    }
+                }
+            }
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// Remove a term. Returns whether it existed.
+    pub fn remove(&mut self, term: &str) -> bool {
+        let before = self.entries.len();
+        self.entries
+            .retain(|e| !e.term.eq_ignore_ascii_case(term.trim()));
+        self.entries.len() < before
+    }
+
+    /// Ban a word, or change its replacement.
+    pub fn ban(&mut self, word: &str, replacement: Option<&str>) {
+        let replacement = replacement.map(str::trim).filter(|r| !r.is_empty());
+        match self
+            .banned
+            .iter_mut()
+            .find(|b| b.word.eq_ignore_ascii_case(word.trim()))
+        {
+            Some(existing) => existing.replacement = replacement.map(String::from),
+            None => self.banned.push(BannedWord {
+                word: word.trim().to_string(),
+                replacement: replacement.map(String::from),
+            }),
+        }
+    }
+
+    /// Lift a ban. Returns whether the word was banned.
+    pub fn unban(&mut self, word: &str) -> bool {
+        let before = self.banned.len();
+        self.banned
+            .retain(|b| !b.word.eq_ignore_ascii_case(word.trim()));
+        self.banned.len() < before
+    }
+
+    /// Entries whose term or an avoided phrasing appears in `text`.
+    pub fn relevant(&self, text: &str) -> Vec<&GlossaryEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| {
+                std::iter::once(&entry.term)
+                    .chain(&entry.avoid)
+                    .filter_map(|phrase| phrase_regex(phrase))
+                    .any(|re| re.is_match(text))
+            })
+            .take(MAX_PROMPT_ENTRIES)
+            .collect()
+    }
+
+    /// Prompt section with the entries relevant to `text` and the banned
+    /// words, or `None` when there is nothing to say.
+    pub fn prompt_section(&self, text: &str) -> Option<String> {
+        let entries = self.relevant(text);
+        if entries.is_empty() && self.banned.is_empty() {
+            return None;
+        }
+
+        let mut out = String::from("## Glossary\n\n");
+        if !entries.is_empty() {
+            out.push_str("Use these project terms exactly as written:\n");
+            for entry in entries {
+                out.push_str(&format!("- **{}**", entry.term));
+                if !entry.definition.is_empty() {
+                    out.push_str(&format!(": {}", entry.definition));
+                }
+                if !entry.avoid.is_empty() {
+                    out.push_str(&format!(" (not: {})", entry.avoid.join(", ")));
+                }
+                out.push('\n');
+            }
+        }
+        if !self.banned.is_empty() {
+            let words: Vec<String> = self
+                .banned
+                .iter()
+                .map(|b| match &b.replacement {
+                    Some(r) => format!("{} (say \"{}\")", b.word, r),
+                    None => b.word.clone(),
+                })
+                .collect();
+            out.push_str(&format!("Never use: {}.\n", words.join(", ")));
+        }
+        Some(out.trim_end().to_string())
+    }
+
+    /// Rewrite avoided phrasings to their term, and banned words to their
+    /// replacement or a mask. Fenced code blocks and inline code are left
+    /// untouched. Returns the new text and how many replacements were made.
+    pub fn enforce(&self, text: &str) -> (String, usize) {
+        let mut rules: Vec<(&str, Option<&str>)> = Vec::new();
+        for entry in &self.entries {
+            for phrase in &entry.avoid {
+                rules.push((phrase, Some(&entry.term)));
+            }
+        }
+        for banned in &self.banned {
+            rules.push((&banned.word, banned.replacement.as_deref()));
+        }
+        rules.retain(|(phrase, _)| !phrase.trim().is_empty());
+        if rules.is_empty() {
+            return (text.to_string(), 0);
+        }
+        // Longest phrasing first, so "deploy package" wins over "deploy".
+        rules.sort_by_key(|(phrase, _)| std::cmp::Reverse(phrase.len()));
+
+        let alternation: Vec<String> = rules.iter().map(|(p, _)| bounded(p)).collect();
+        let Ok(re) = Regex::new(&format!("(?i){}", alternation.join("|"))) else {
+            return (text.to_string(), 0);
+        };
+
+        let mut count = 0;
+        let mut replace = |prose: &str| -> String {
+            re.replace_all(prose, |caps: &Captures| {
+                let found = &caps[0];
+                let Some((_, target)) = rules
+                    .iter()
+                    .find(|(phrase, _)| phrase.to_lowercase() == found.to_lowercase())
+                else {
+                    return found.to_string();
+                };
+                count += 1;
+                match target {
+                    Some(target) => match_case(found, target),
+                    None => "*".repeat(found.chars().count()),
+                }
+            })
+            .into_owned()
+        };
+
+        let mut out = String::with_capacity(text.len());
+        let mut in_fence = false;
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+                out.push_str(line);
+                continue;
+            }
+            if in_fence {
+                out.push_str(line);
+                continue;
+            }
+            // Odd pieces sit between backticks: inline code.
+            for (j, piece) in line.split('`').enumerate() {
+                if j > 0 {
+                    out.push('`');
+                }
+                if j % 2 == 0 {
+                    out.push_str(&replace(piece));
+                } else {
+                    out.push_str(piece);
+                }
+            }
+        }
+        (out, count)
+    }
+}
+
+fn split_list(list: &str) -> impl Iterator<Item = String> + '_ {
+    list.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+/// The escaped phrase, with word boundaries on the sides that start or
+/// end with a word character.
+fn bounded(phrase: &str) -> String {
+    let phrase = phrase.trim();
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    format!(
+        "{}{}{}",
+        if is_word(phrase.chars().next()) {
+            r"\b"
+        } else {
+            ""
+        },
+        regex::escape(phrase),
+        if is_word(phrase.chars().last()) {
+            r"\b"
+        } else {
+            ""
+        },
+    )
+}
+
+fn phrase_regex(phrase: &str) -> Option<Regex> {
+    if phrase.trim().is_empty() {
+        return None;
+    }
+    Regex::new(&format!("(?i){}", bounded(phrase))).ok()
+}
+
+/// `target`, capitalized when `found` starts a capitalized word and the
+/// target starts lowercase.
+fn match_case(found: &str, target: &str) -> String {
+    let capitalized = found.chars().next().is_some_and(char::is_uppercase);
+    let mut chars = target.chars();
+    match chars.next() {
+        Some(first) if capitalized && first.is_lowercase() => {
+            first.to_uppercase().chain(chars).collect()
+        }
+        _ => target.to_string(),
+    }
+}
+
+impl Workspace {
+    /// The glossary, empty when `GLOSSARY.md` does not exist yet.
+    pub async fn glossary(&self) -> Result<Glossary, WorkspaceError> {
+        match self.read(paths::GLOSSARY).await {
+            Ok(doc) => Ok(Glossary::parse(&doc.content)),
+            Err(WorkspaceError::DocumentNotFound { .. }) => Ok(Glossary::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write the glossary back to `GLOSSARY.md`.
+    pub async fn save_glossary(&self, glossary: &Glossary) -> Result<(), WorkspaceError> {
+        self.write(paths::GLOSSARY, &glossary.render()).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "# Glossary\n\
+        \n\
+        Intro text is ignored.\n\
+        \n\
+        ## deployment unit\n\
+        A versioned bundle of services\n\
+        released together.\n\
+        Avoid: release bundle, deploy package\n\
+        \n\
+        ## Kubernetes\n\
+        avoid: k8s\n\
+        \n\
+        ## Banned words\n\
+        - utilize -> use\n\
+        - synergy\n";
+
+    #[test]
+    fn test_parse_and_render_round_trip() {
+        let glossary = Glossary::parse(DOC);
+        assert_eq!(glossary.entries.len(), 2);
+        assert_eq!(
+            glossary.entries[0].definition,
+            "A versioned bundle of services released together."
+        );
+        assert_eq!(
+            glossary.entries[0].avoid,
+            vec!["release bundle", "deploy package"]
+        );
+        assert_eq!(glossary.entries[1].avoid, vec!["k8s"]);
+        assert_eq!(
+            glossary.banned,
+            vec![
+                BannedWord {
+                    word: "utilize".into(),
+                    replacement: Some("use".into()),
+                },
+                BannedWord {
+                    word: "synergy".into(),
+                    replacement: None,
+                },
+            ]
+        );
+        assert_eq!(Glossary::parse(&glossary.render()), glossary);
+    }
+
+    #[test]
+    fn test_prompt_section_only_lists_relevant_terms() {
+        let glossary = Glossary::parse(DOC);
+        let section = glossary
+            .prompt_section("How do I roll back a K8S deploy?")
+            .unwrap();
+        assert!(section.contains("**Kubernetes** (not: k8s)"));
+        assert!(!section.contains("deployment unit"));
+        assert!(section.contains("utilize (say \"use\")"));
+
+        assert!(Glossary::default().prompt_section("anything").is_none());
+    }
+
+    #[test]
+    fn test_enforce_rewrites_prose_but_not_code() {
+        let glossary = Glossary::parse(DOC);
+        let (text, count) = glossary.enforce(
+            "Utilize the release bundle on k8s for synergy.\n\
+             Run `k8s apply`.\n\
+             ```\n\
+             utilize k8s\n\
+             ```\n\
+             Ignore k8sctl.",
+        );
+        assert_eq!(
+            text,
+            "Use the deployment unit on Kubernetes for *******.\n\
+             Run `k8s apply`.\n\
+             ```\n\
+             utilize k8s\n\
+             ```\n\
+             Ignore k8sctl."
+        );
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn test_upsert_merges_and_ban_updates() {
+        let mut glossary = Glossary::default();
+        glossary.upsert(GlossaryEntry {
+            term: "PR".into(),
+            definition: "A pull request.".into(),
+            avoid: vec!["MR".into()],
+        });
+        glossary.upsert(GlossaryEntry {
+            term: "pr".into(),
+            definition: String::new(),
+            avoid: vec!["mr".into(), "merge request".into()],
+        });
+        assert_eq!(glossary.entries.len(), 1);
+        assert_eq!(glossary.entries[0].definition, "A pull request.");
+        assert_eq!(glossary.entries[0].avoid, vec!["MR", "merge request"]);
+
+        glossary.ban("leverage", None);
+        glossary.ban("Leverage", Some("use"));
+        assert_eq!(glossary.banned.len(), 1);
+        assert_eq!(glossary.banned[0].replacement.as_deref(), Some("use"));
+        assert!(glossary.unban("LEVERAGE"));
+        assert!(glossary.remove("Pr"));
+        assert!(glossary.is_empty());
+    }
+}
@@ -0,0 +1,348 @@
+//! Background indexing of conversation history for recall.
+//!
+//! When enabled, every completed turn is queued here and, in batches,
+//! appended to a per-channel, per-day document:
+//!
+//! ```text
+//! conversations/
+//! └── telegram/
+//!     ├── 2026-03-01.md
+//!     └── 2026-03-02.md
+//! ```
+//!
+//! The documents belong to a dedicated memory space and are chunked and
+//! embedded like any other workspace file, so `memory_search` can answer
+//! "what did we decide about the pricing page last month?". Each turn
+//! carries its channel and timestamp so a lone chunk still says when and
+//! where it was said.
+//!
+//! Retention is per channel: day documents older than the channel's
+//! retention are deleted once a day, and a retention of 0 keeps the
+//! channel out of the index entirely.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::error::WorkspaceError;
+use crate::workspace::Workspace;
+
+/// Directory the day documents live under.
+pub const HISTORY_DIR: &str = "conversations";
+
+/// Turns buffered before new ones are dropped.
+const QUEUE_CAPACITY: usize = 1024;
+
+/// How often queued turns are written out.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often old day documents are pruned.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(24 * 3600);
+
+/// Longest message kept per turn; the rest is cut.
+const MAX_MESSAGE_CHARS: usize = 4000;
+
+/// Settings for conversation history indexing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryIndexConfig {
+    /// Off by default: conversations stay out of memory unless opted in.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Memory space the day documents are added to.
+    #[serde(default = "default_space")]
+    pub space: String,
+    /// Days to keep indexed history for channels without an override.
+    #[serde(default = "default_retention_days")]
+    pub retention_days: u32,
+    /// Per-channel retention in days; 0 keeps the channel out of the index.
+    #[serde(default)]
+    pub channel_retention: HashMap<String, u32>,
+}
+
+fn default_space() -> String {
+    "conversations".to_string()
+}
+
+fn default_retention_days() -> u32 {
+    90
+}
+
+impl Default for HistoryIndexConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            space: default_space(),
+            retention_days: default_retention_days(),
+            channel_retention: HashMap::new(),
+        }
+    }
+}
+
+impl HistoryIndexConfig {
+    /// Retention in days for `channel`.
+    pub fn retention_for(&self, channel: &str) -> u32 {
+        self.channel_retention
+            .get(channel)
+            .copied()
+            .unwrap_or(self.retention_days)
+    }
+}
+
+/// One completed turn, queued for indexing.
+#[derive(Debug, Clone)]
+pub struct IndexedTurn {
+    pub channel: String,
+    pub user_input: String,
+    pub response: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+/// What the agent sends the background writer.
+enum Queued {
+    Turn(IndexedTurn),
+    /// Drop queued turns whose user input is one of these texts.
+    Forget(Vec<String>),
+}
+
+/// Handle the agent uses to queue turns. Cheap to clone.
+#[derive(Clone)]
+pub struct HistoryIndexer {
+    tx: mpsc::Sender<Queued>,
+    config: Arc<HistoryIndexConfig>,
+}
+
+impl HistoryIndexer {
+    /// Start the background writer. Returns `None` when indexing is disabled.
+    pub fn spawn(workspace: Arc<Workspace>, config: HistoryIndexConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+        let config = Arc::new(config);
+        tokio::spawn(run(workspace, Arc::clone(&config), rx));
+        Some(Self { tx, config })
+    }
+
+    /// Queue a turn. Channels with zero retention are skipped; when the
+    /// queue is full the turn is dropped rather than blocking the agent.
+    pub fn record(&self, turn: IndexedTurn) {
+        if self.config.retention_for(&turn.channel) == 0 {
+            return;
+        }
+        if let Err(e) = self.tx.try_send(Queued::Turn(turn)) {
+            tracing::debug!("Conversation history queue full, dropping turn: {}", e);
+        }
+    }
+
+    /// Drop turns that are still queued and whose user input is one of
+    /// `user_inputs`, so redacted turns never reach the index. Turns
+    /// already written out have to be scrubbed from the workspace.
+    pub fn forget(&self, user_inputs: Vec<String>) {
+        if let Err(e) = self.tx.try_send(Queued::Forget(user_inputs)) {
+            tracing::warn!(
+                "Conversation history queue full, cannot forget turns: {}",
+                e
+            );
+        }
+    }
+}
+
+async fn run(
+    workspace: Arc<Workspace>,
+    config: Arc<HistoryIndexConfig>,
+    mut rx: mpsc::Receiver<Queued>,
+) {
+    if let Err(e) = ensure_space(&workspace, &config.space).await {
+        tracing::warn!("Failed to create memory space '{}': {}", config.space, e);
+    }
+
+    let mut pending = Vec::new();
+    let mut flush = tokio::time::interval(FLUSH_INTERVAL);
+    let mut prune = tokio::time::interval(PRUNE_INTERVAL);
+    loop {
+        tokio::select! {
+            queued = rx.recv() => match queued {
+                Some(Queued::Turn(turn)) => pending.push(turn),
+                Some(Queued::Forget(inputs)) => {
+                    pending.retain(|turn: &IndexedTurn| !inputs.contains(&turn.user_input));
+                }
+                None => break,
+            },
+            _ = flush.tick() => {
+                if let Err(e) = flush_turns(&workspace, &config, std::mem::take(&mut pending)).await {
+                    tracing::warn!("Failed to index conversation history: {}", e);
+                }
+            }
+            _ = prune.tick() => match prune_history(&workspace, &config, Utc::now()).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("Pruned {} expired conversation history documents", n),
+                Err(e) => tracing::warn!("Failed to prune conversation history: {}", e),
+            },
+        }
+    }
+
+    // Agent is shutting down: write out what is left.
+    if let Err(e) = flush_turns(&workspace, &config, pending).await {
+        tracing::warn!("Failed to index conversation history: {}", e);
+    }
+}
+
+async fn ensure_space(workspace: &Workspace, space: &str) -> Result<(), WorkspaceError> {
+    if workspace.get_space(space).await?.is_none() {
+        workspace
+            .create_space(space, "Conversation history indexed for recall")
+            .await?;
+    }
+    Ok(())
+}
+
+/// Day document path for a channel and date.
+pub fn history_path(channel: &str, date: NaiveDate) -> String {
+    let channel: String = channel
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("{}/{}/{}.md", HISTORY_DIR, channel, date.format("%Y-%m-%d"))
+}
+
+fn clip(text: &str) -> &str {
+    match text.char_indices().nth(MAX_MESSAGE_CHARS) {
+        Some((idx, _)) => &text[..idx],
+        None => text,
+    }
+}
+
+/// Render one turn as a self-describing block.
+fn render_turn(turn: &IndexedTurn) -> String {
+    let stamp = format!(
+        "[{} {}]",
+        turn.channel,
+        turn.at.format("%Y-%m-%d %H:%M UTC")
+    );
+    let mut out = format!("\n{} user: {}\n", stamp, clip(turn.user_input.trim()));
+    if let Some(ref response) = turn.response {
+        out.push_str(&format!("{} assistant: {}\n", stamp, clip(response.trim())));
+    }
+    out
+}
+
+/// Append queued turns to their day documents, one write per document.
+async fn flush_turns(
+    workspace: &Workspace,
+    config: &HistoryIndexConfig,
+    turns: Vec<IndexedTurn>,
+) -> Result<(), WorkspaceError> {
+    let mut by_doc: BTreeMap<String, (String, String)> = BTreeMap::new();
+    for turn in &turns {
+        let date = turn.at.date_naive();
+        let path = history_path(&turn.channel, date);
+        let entry = by_doc.entry(path).or_insert_with(|| {
+            (
+                format!(
+                    "# Conversations on {}, {}\n",
+                    turn.channel,
+                    date.format("%Y-%m-%d")
+                ),
+                String::new(),
+            )
+        });
+        entry.1.push_str(&render_turn(turn));
+    }
+
+    for (path, (heading, body)) in by_doc {
+        let created = !workspace.exists(&path).await?;
+        if created {
+            workspace
+                .write(&path, &format!("{}{}", heading, body))
+                .await?;
+            workspace.add_to_space(&config.space, &path).await?;
+        } else {
+            workspace.append(&path, &body).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Delete day documents past their channel's retention. Returns how many
+/// were deleted.
+pub async fn prune_history(
+    workspace: &Workspace,
+    config: &HistoryIndexConfig,
+    now: DateTime<Utc>,
+) -> Result<usize, WorkspaceError> {
+    let today = now.date_naive();
+    let mut deleted = 0;
+    for path in workspace.list_all().await? {
+        let Some((channel, date)) = parse_history_path(&path) else {
+            continue;
+        };
+        let retention = config.retention_for(channel);
+        if (today - date).num_days() >= i64::from(retention) {
+            workspace.delete(&path).await?;
+            deleted += 1;
+        }
+    }
+    Ok(deleted)
+}
+
+/// Split `conversations/<channel>/<date>.md` into channel and date.
+fn parse_history_path(path: &str) -> Option<(&str, NaiveDate)> {
+    let rest = path.strip_prefix(HISTORY_DIR)?.strip_prefix('/')?;
+    let (channel, file) = rest.split_once('/')?;
+    let date = NaiveDate::parse_from_str(file.strip_suffix(".md")?, "%Y-%m-%d").ok()?;
+    Some((channel, date))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_history_paths_roundtrip() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let path = history_path("telegram", date);
+        assert_eq!(path, "conversations/telegram/2026-03-02.md");
+        assert_eq!(parse_history_path(&path), Some(("telegram", date)));
+        assert_eq!(
+            history_path("web/ui", date),
+            "conversations/web-ui/2026-03-02.md"
+        );
+        assert!(parse_history_path("conversations/notes.md").is_none());
+        assert!(parse_history_path("daily/2026-03-02.md").is_none());
+    }
+
+    #[test]
+    fn test_retention_and_rendering() {
+        let config: HistoryIndexConfig = serde_json::from_value(serde_json::json!({
+            "enabled": true,
+            "channel_retention": {"telegram": 30, "cli": 0}
+        }))
+        .unwrap();
+        assert_eq!(config.space, "conversations");
+        assert_eq!(config.retention_for("telegram"), 30);
+        assert_eq!(config.retention_for("cli"), 0);
+        assert_eq!(config.retention_for("slack"), 90);
+
+        let turn = IndexedTurn {
+            channel: "telegram".to_string(),
+            user_input: "Let's keep the pricing page at three tiers".to_string(),
+            response: Some("Agreed, three tiers.".to_string()),
+            at: Utc.with_ymd_and_hms(2026, 3, 2, 14, 5, 0).unwrap(),
+        };
+        let text = render_turn(&turn);
+        assert!(text.contains("[telegram 2026-03-02 14:05 UTC] user: Let's keep the pricing page"));
+        assert!(text.contains("[telegram 2026-03-02 14:05 UTC] assistant: Agreed"));
+    }
+}
@@ -0,0 +1,425 @@
+//! Ingestion of local files into searchable memory.
+//!
+//! [`collect_files`] expands files, directories and glob patterns into the
+//! files we can read: Markdown and plain text, source code, HTML and PDF.
+//! [`Workspace::ingest`] stores each one as a document under a prefix
+//! (`sources/` by default) mirroring its absolute path, e.g.
+//! `sources/home/me/notes/plan.md`. Writing a document chunks and embeds it
+//! like any other, so `memory_search` finds it.
+//!
+//! The document's metadata records the file as a `file://` source URL and
+//! the SHA-256 of its bytes. Re-ingesting compares hashes and skips
+//! unchanged files, so only new or edited files are re-embedded. With
+//! `prune`, documents whose source file no longer exists are deleted.
+
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::error::WorkspaceError;
+use crate::media::{PdfExtractor, html_to_markdown};
+use crate::workspace::{DocumentMetadata, Workspace};
+
+/// Default workspace directory ingested files are stored under.
+pub const DEFAULT_PREFIX: &str = "sources";
+
+/// Tag added to every ingested document.
+pub const INGEST_TAG: &str = "ingested";
+
+/// Directories never descended into.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "__pycache__", "venv"];
+
+const TEXT_EXTENSIONS: &[&str] = &["md", "markdown", "mdx", "txt", "rst", "org", "adoc", "tex"];
+
+const CODE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "js", "jsx", "ts", "tsx", "go", "java", "kt", "c", "h", "cc", "cpp", "hpp", "cs",
+    "rb", "php", "swift", "scala", "sh", "bash", "sql", "lua", "ex", "exs", "hs", "ml", "toml",
+    "yaml", "yml", "json", "csv",
+];
+
+/// How a file's text is obtained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceKind {
+    Text,
+    Code,
+    Html,
+    Pdf,
+}
+
+impl SourceKind {
+    /// The kind of `path`, by extension; `None` if we cannot ingest it.
+    pub fn of(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        let ext = ext.as_str();
+        if TEXT_EXTENSIONS.contains(&ext) {
+            Some(Self::Text)
+        } else if CODE_EXTENSIONS.contains(&ext) {
+            Some(Self::Code)
+        } else if ext == "html" || ext == "htm" {
+            Some(Self::Html)
+        } else if ext == "pdf" {
+            Some(Self::Pdf)
+        } else {
+            None
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Code => "code",
+            Self::Html => "html",
+            Self::Pdf => "pdf",
+        }
+    }
+
+    /// The text to index for a file's bytes.
+    pub fn extract(self, bytes: &[u8]) -> Result<String, String> {
+        let utf8 = || {
+            std::str::from_utf8(bytes)
+                .map(str::to_string)
+                .map_err(|_| "not UTF-8 text".to_string())
+        };
+        match self {
+            Self::Text | Self::Code => utf8(),
+            Self::Html => utf8().map(|html| html_to_markdown(&html)),
+            Self::Pdf => PdfExtractor::new()
+                .extract_all_text(bytes)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Options for [`Workspace::ingest`].
+#[derive(Debug, Clone)]
+pub struct IngestOptions {
+    /// Workspace directory documents are stored under.
+    pub prefix: String,
+    /// Files larger than this are skipped.
+    pub max_file_bytes: u64,
+    /// Re-write and re-embed files even when unchanged.
+    pub force: bool,
+    /// Delete ingested documents whose source file is gone.
+    pub prune: bool,
+}
+
+impl Default for IngestOptions {
+    fn default() -> Self {
+        Self {
+            prefix: DEFAULT_PREFIX.to_string(),
+            max_file_bytes: 20 * 1024 * 1024,
+            force: false,
+            prune: false,
+        }
+    }
+}
+
+/// What happened to one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IngestStatus {
+    Added,
+    Updated,
+    Unchanged,
+    Skipped(String),
+}
+
+/// Outcome of an ingestion run.
+#[derive(Debug, Default)]
+pub struct IngestReport {
+    /// Each file with its document path and status.
+    pub files: Vec<(PathBuf, String, IngestStatus)>,
+    /// Documents deleted because their source file is gone.
+    pub pruned: Vec<String>,
+}
+
+impl IngestReport {
+    pub fn count(&self, wanted: fn(&IngestStatus) -> bool) -> usize {
+        self.files.iter().filter(|(_, _, s)| wanted(s)).count()
+    }
+}
+
+/// Expand `inputs` (files, directories or glob patterns) into the files that
+/// can be ingested, sorted and without duplicates. Inputs that match nothing
+/// are returned with the reason.
+pub fn collect_files(inputs: &[String]) -> (Vec<PathBuf>, Vec<(String, String)>) {
+    let mut files = BTreeSet::new();
+    let mut problems = Vec::new();
+    for input in inputs {
+        let expanded = match input.strip_prefix("~/").zip(dirs::home_dir()) {
+            Some((rest, home)) => home.join(rest).to_string_lossy().into_owned(),
+            None => input.clone(),
+        };
+        let path = Path::new(&expanded);
+        let found = if path.is_dir() {
+            walk(path, &mut files)
+        } else if path.is_file() {
+            if SourceKind::of(path).is_none() {
+                problems.push((input.clone(), "unsupported file type".to_string()));
+                continue;
+            }
+            files.insert(path.to_path_buf());
+            1
+        } else if expanded.contains(['*', '?', '[']) {
+            match glob::glob(&expanded) {
+                Ok(paths) => paths
+                    .flatten()
+                    .filter(|path| path.is_file() && SourceKind::of(path).is_some())
+                    .map(|path| files.insert(path))
+                    .count(),
+                Err(e) => {
+                    problems.push((input.clone(), format!("bad pattern: {}", e)));
+                    continue;
+                }
+            }
+        } else {
+            problems.push((input.clone(), "no such file or directory".to_string()));
+            continue;
+        };
+        if found == 0 {
+            problems.push((input.clone(), "no supported files".to_string()));
+        }
+    }
+    (files.into_iter().collect(), problems)
+}
+
+/// Add the supported files under `dir`, skipping hidden and build
+/// directories. Returns how many were found.
+fn walk(dir: &Path, files: &mut BTreeSet<PathBuf>) -> usize {
+    let mut found = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with('.') {
+                continue;
+            }
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() && !SKIPPED_DIRS.contains(&name.as_ref()) => {
+                    pending.push(path);
+                }
+                Ok(kind) if kind.is_file() && SourceKind::of(&path).is_some() => {
+                    files.insert(path);
+                    found += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+    found
+}
+
+/// Workspace path for the file at absolute path `file` under `prefix`.
+pub fn document_path(prefix: &str, file: &Path) -> String {
+    let parts: Vec<String> = file
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            Component::Prefix(drive) => Some(
+                drive
+                    .as_os_str()
+                    .to_string_lossy()
+                    .trim_end_matches(':')
+                    .to_string(),
+            ),
+            _ => None,
+        })
+        .collect();
+    format!("{}/{}", prefix.trim_matches('/'), parts.join("/"))
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+impl Workspace {
+    /// Store `files` as documents under `options.prefix`, skipping files
+    /// whose content hash matches the stored one.
+    pub async fn ingest(
+        &self,
+        files: &[PathBuf],
+        options: &IngestOptions,
+    ) -> Result<IngestReport, WorkspaceError> {
+        let mut report = IngestReport::default();
+        for file in files {
+            let absolute = std::fs::canonicalize(file).unwrap_or_else(|_| file.clone());
+            let path = document_path(&options.prefix, &absolute);
+            let status = self.ingest_file(&absolute, &path, options).await?;
+            if let IngestStatus::Skipped(reason) = &status {
+                tracing::debug!("Skipped {}: {}", absolute.display(), reason);
+            }
+            report.files.push((absolute, path, status));
+        }
+        if options.prune {
+            report.pruned = self.prune_ingested(&options.prefix).await?;
+        }
+        Ok(report)
+    }
+
+    async fn ingest_file(
+        &self,
+        file: &Path,
+        path: &str,
+        options: &IngestOptions,
+    ) -> Result<IngestStatus, WorkspaceError> {
+        let Some(kind) = SourceKind::of(file) else {
+            return Ok(IngestStatus::Skipped("unsupported file type".to_string()));
+        };
+        match tokio::fs::metadata(file).await {
+            Ok(meta) if meta.len() > options.max_file_bytes => {
+                return Ok(IngestStatus::Skipped(format!(
+                    "larger than {} bytes",
+                    options.max_file_bytes
+                )));
+            }
+            Ok(_) => {}
+            Err(e) => return Ok(IngestStatus::Skipped(e.to_string())),
+        }
+        let bytes = match tokio::fs::read(file).await {
+            Ok(bytes) => bytes,
+            Err(e) => return Ok(IngestStatus::Skipped(e.to_string())),
+        };
+        let hash = sha256_hex(&bytes);
+
+        let existing = match self.read(path).await {
+            Ok(doc) => Some(doc),
+            Err(WorkspaceError::DocumentNotFound { .. }) => None,
+            Err(e) => return Err(e),
+        };
+        let mut metadata = existing
+            .as_ref()
+            .map(|doc| DocumentMetadata::from_json(&doc.metadata))
+            .unwrap_or_default();
+        if !options.force && metadata.source_hash.as_deref() == Some(hash.as_str()) {
+            return Ok(IngestStatus::Unchanged);
+        }
+
+        let text = match kind.extract(&bytes) {
+            Ok(text) if !text.trim().is_empty() => text,
+            Ok(_) => return Ok(IngestStatus::Skipped("no text".to_string())),
+            Err(reason) => return Ok(IngestStatus::Skipped(reason)),
+        };
+        self.write(path, &text).await?;
+
+        metadata.source_url = reqwest::Url::from_file_path(file)
+            .map(|url| url.to_string())
+            .ok();
+        metadata.source_hash = Some(hash);
+        for tag in [INGEST_TAG, kind.label()] {
+            if !metadata.tags.iter().any(|t| t == tag) {
+                metadata.tags.push(tag.to_string());
+            }
+        }
+        self.update_metadata(path, &metadata).await?;
+
+        Ok(if existing.is_some() {
+            IngestStatus::Updated
+        } else {
+            IngestStatus::Added
+        })
+    }
+
+    /// Delete documents under `prefix` that were ingested from a local file
+    /// that no longer exists. Returns their paths.
+    async fn prune_ingested(&self, prefix: &str) -> Result<Vec<String>, WorkspaceError> {
+        let dir = format!("{}/", prefix.trim_matches('/'));
+        let mut pruned = Vec::new();
+        for path in self.list_all().await? {
+            if !path.starts_with(&dir) {
+                continue;
+            }
+            let doc = self.read(&path).await?;
+            let metadata = DocumentMetadata::from_json(&doc.metadata);
+            if metadata.source_hash.is_none() {
+                continue;
+            }
+            let gone = metadata
+                .source_url
+                .as_deref()
+                .and_then(|url| reqwest::Url::parse(url).ok())
+                .and_then(|url| url.to_file_path().ok())
+                .is_some_and(|file| !file.exists());
+            if gone {
+                self.delete(&path).await?;
+                pruned.push(path);
+            }
+        }
+        Ok(pruned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_kinds_and_paths() {
+        assert_eq!(SourceKind::of(Path::new("a/b.MD")), Some(SourceKind::Text));
+        assert_eq!(SourceKind::of(Path::new("main.rs")), Some(SourceKind::Code));
+        assert_eq!(
+            SourceKind::of(Path::new("page.htm")),
+            Some(SourceKind::Html)
+        );
+        assert_eq!(
+            SourceKind::of(Path::new("paper.pdf")),
+            Some(SourceKind::Pdf)
+        );
+        assert_eq!(SourceKind::of(Path::new("photo.png")), None);
+        assert_eq!(SourceKind::of(Path::new("Makefile")), None);
+
+        assert_eq!(
+            SourceKind::Html
+                .extract(b"<h1>Title</h1><p>Body</p>")
+                .unwrap()
+                .trim(),
+            "# Title\n\nBody"
+        );
+        assert!(SourceKind::Text.extract(&[0xff, 0xfe, 0x00]).is_err());
+
+        assert_eq!(
+            document_path("sources/", Path::new("/home/me/notes/plan.md")),
+            "sources/home/me/notes/plan.md"
+        );
+    }
+
+    #[test]
+    fn test_collect_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("docs/deep")).unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        for file in [
+            "docs/a.md",
+            "docs/deep/b.rs",
+            "docs/image.png",
+            ".git/config.txt",
+            "node_modules/pkg/index.js",
+        ] {
+            std::fs::write(root.join(file), "x").unwrap();
+        }
+        let root_str = root.to_string_lossy().into_owned();
+
+        let (files, problems) = collect_files(&[
+            root_str.clone(),
+            format!("{}/docs/*.md", root_str),
+            format!("{}/docs/image.png", root_str),
+            format!("{}/missing", root_str),
+        ]);
+        let names: Vec<_> = files
+            .iter()
+            .map(|f| f.strip_prefix(root).unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["docs/a.md", "docs/deep/b.rs"]);
+        let reasons: Vec<_> = problems.iter().map(|(_, r)| r.as_str()).collect();
+        assert_eq!(
+            reasons,
+            ["unsupported file type", "no such file or directory"]
+        );
+    }
+}
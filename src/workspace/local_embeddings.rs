@@ -164,6 +164,14 @@ impl EmbeddingProvider for LocalEmbeddings {
         100_000
     }
 
+    fn max_batch_size(&self) -> usize {
+        256
+    }
+
+    fn max_batch_tokens(&self) -> usize {
+        usize::MAX
+    }
+
     async fn embed(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
         if text.len() > self.max_input_length() {
             return Err(EmbeddingError::TextTooLong {
@@ -11,6 +11,7 @@
 //! ├── README.md              <- Root runbook/index
 //! ├── MEMORY.md              <- Long-term curated memory
 //! ├── HEARTBEAT.md           <- Periodic checklist
+//! ├── GLOSSARY.md            <- Project terms and banned words
 //! ├── context/               <- Identity and context
 //! │   ├── vision.md
 //! │   └── priorities.md
@@ -32,6 +33,9 @@
 //! - `list(dir)` - List directory contents
 //! - `delete(path)` - Delete a file
 //! - `search(query)` - Full-text + semantic search across all files
+//! - `usage(quotas)` - Storage per memory space, checked against quotas
+//! - `ingest(files)` - Index local files as documents under `sources/`
+//! - `export_archive()` / `import_archive(archive)` - Portable backup of all memory
 //!
 //! # Key Patterns
 //!
@@ -40,27 +44,50 @@
 //! 3. **Self-documenting**: Use README.md files to describe directory structure
 //! 4. **Hybrid search**: Vector similarity + BM25 full-text via RRF
 
+pub mod archive;
 pub mod batch_embeddings;
 mod chunker;
 mod document;
 mod embeddings;
 pub mod gemini_embeddings;
+pub mod glossary;
+pub mod history_index;
+pub mod ingest;
 pub mod local_embeddings;
 #[cfg(feature = "postgres")]
 mod repository;
+pub mod rerank;
+pub mod retrieval_eval;
+pub mod scope;
 mod search;
+pub mod usage;
 
+pub use archive::{ImportOptions, ImportReport, MemoryArchive};
+pub use batch_embeddings::{EmbedderConfig, EmbeddingStats, ParallelEmbedder};
 pub use chunker::{ChunkConfig, chunk_document};
 pub use document::{
-    ConnectionType, DocumentMetadata, MemoryChunk, MemoryConnection, MemoryDocument, MemorySpace,
-    ProfileType, UserProfile, WorkspaceEntry, paths,
+    ConnectionType, DocumentMetadata, DocumentUsage, MemoryChunk, MemoryConnection, MemoryDocument,
+    MemorySpace, ProfileType, UserProfile, WorkspaceEntry, paths,
 };
 pub use embeddings::{EmbeddingProvider, MockEmbeddings, NearAiEmbeddings, OpenAiEmbeddings};
 pub use gemini_embeddings::GeminiEmbeddings;
+pub use glossary::{BannedWord, Glossary, GlossaryEntry};
+pub use history_index::{HistoryIndexConfig, HistoryIndexer, IndexedTurn};
+pub use ingest::{
+    DEFAULT_PREFIX as DEFAULT_INGEST_PREFIX, IngestOptions, IngestReport, IngestStatus, SourceKind,
+    collect_files,
+};
 pub use local_embeddings::LocalEmbeddings;
 #[cfg(feature = "postgres")]
 pub use repository::Repository;
+pub use rerank::{CrossEncoderReranker, LlmReranker, Reranker, create_reranker};
+pub use retrieval_eval::{RetrievalCase, RetrievalReport};
+pub use scope::{MemoryScope, ScopedOwner};
 pub use search::{RankedResult, SearchConfig, SearchResult, reciprocal_rank_fusion};
+pub use usage::{
+    QuotaAction, QuotaConfig, QuotaLimits, QuotaStatus, SpaceQuota, SpaceUsage, WorkspaceUsage,
+    spawn_quota_monitor,
+};
 
 use std::sync::Arc;
 
@@ -69,12 +96,15 @@ use chrono::{NaiveDate, Utc};
 use deadpool_postgres::Pool;
 use uuid::Uuid;
 
+use crate::agent::priority::yield_every;
 use crate::error::WorkspaceError;
+use crate::event_bus::{BusEvent, EventBus, MemoryChange};
 
 /// Internal storage abstraction for Workspace.
 ///
 /// Allows Workspace to work with either a PostgreSQL `Repository` (the original
 /// path) or any `Database` trait implementation (e.g. libSQL backend).
+#[derive(Clone)]
 enum WorkspaceStorage {
     /// PostgreSQL-backed repository (uses connection pool directly).
     #[cfg(feature = "postgres")]
@@ -170,6 +200,30 @@ impl WorkspaceStorage {
         }
     }
 
+    async fn list_documents(
+        &self,
+        user_id: &str,
+        agent_id: Option<Uuid>,
+    ) -> Result<Vec<MemoryDocument>, WorkspaceError> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Repo(repo) => repo.list_documents(user_id, agent_id).await,
+            Self::Db(db) => db.list_documents(user_id, agent_id).await,
+        }
+    }
+
+    async fn workspace_usage(
+        &self,
+        user_id: &str,
+        agent_id: Option<Uuid>,
+    ) -> Result<Vec<DocumentUsage>, WorkspaceError> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Repo(repo) => repo.workspace_usage(user_id, agent_id).await,
+            Self::Db(db) => db.workspace_usage(user_id, agent_id).await,
+        }
+    }
+
     async fn delete_chunks(&self, document_id: Uuid) -> Result<(), WorkspaceError> {
         match self {
             #[cfg(feature = "postgres")]
@@ -210,6 +264,14 @@ impl WorkspaceStorage {
         }
     }
 
+    async fn list_chunks(&self, document_id: Uuid) -> Result<Vec<MemoryChunk>, WorkspaceError> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Repo(repo) => repo.list_chunks(document_id).await,
+            Self::Db(db) => db.list_chunks(document_id).await,
+        }
+    }
+
     async fn get_chunks_without_embeddings(
         &self,
         user_id: &str,
@@ -436,10 +498,16 @@ pub struct Workspace {
     user_id: String,
     /// Optional agent ID for multi-agent isolation.
     agent_id: Option<Uuid>,
+    /// Whether reads fall back to the shared (agentless) pool.
+    read_shared: bool,
     /// Database storage backend.
     storage: WorkspaceStorage,
-    /// Embedding provider for semantic search.
-    embeddings: Option<Arc<dyn EmbeddingProvider>>,
+    /// Embedder for semantic search and indexing.
+    embedder: Option<Arc<ParallelEmbedder>>,
+    /// Re-ranker applied to fused search candidates.
+    reranker: Option<Arc<dyn Reranker>>,
+    /// Bus that document writes and deletes are published on.
+    events: Option<Arc<EventBus>>,
 }
 
 impl Workspace {
@@ -449,8 +517,11 @@ impl Workspace {
         Self {
             user_id: user_id.into(),
             agent_id: None,
+            read_shared: false,
             storage: WorkspaceStorage::Repo(Repository::new(pool)),
-            embeddings: None,
+            embedder: None,
+            reranker: None,
+            events: None,
         }
     }
 
@@ -461,8 +532,11 @@ impl Workspace {
         Self {
             user_id: user_id.into(),
             agent_id: None,
+            read_shared: false,
             storage: WorkspaceStorage::Db(db),
-            embeddings: None,
+            embedder: None,
+            reranker: None,
+            events: None,
         }
     }
 
@@ -472,12 +546,86 @@ impl Workspace {
         self
     }
 
+    /// The same workspace restricted to `owner`'s memory.
+    ///
+    /// Writes go to `owner.home`; reads, listings and search also see the
+    /// shared pool when `owner.read_shared` is set. See [`MemoryScope`].
+    pub fn scoped(&self, owner: ScopedOwner) -> Self {
+        Self {
+            agent_id: owner.home,
+            read_shared: owner.read_shared,
+            ..self.for_user(self.user_id.clone())
+        }
+    }
+
+    /// The owner this workspace is restricted to, for scoping another
+    /// workspace the same way.
+    pub fn owner(&self) -> ScopedOwner {
+        ScopedOwner {
+            home: self.agent_id,
+            read_shared: self.read_shared,
+        }
+    }
+
+    /// Owners this workspace reads from, most specific first.
+    fn readable_owners(&self) -> Vec<Option<Uuid>> {
+        self.owner().readable()
+    }
+
     /// Set the embedding provider for semantic search.
     pub fn with_embeddings(mut self, provider: Arc<dyn EmbeddingProvider>) -> Self {
-        self.embeddings = Some(provider);
+        self.embedder = Some(Arc::new(ParallelEmbedder::new(
+            provider,
+            EmbedderConfig::default(),
+        )));
+        self
+    }
+
+    /// Tune batching and concurrency of the embedding provider set by
+    /// [`Workspace::with_embeddings`]. No-op without one.
+    pub fn with_embedder_config(mut self, config: EmbedderConfig) -> Self {
+        if let Some(embedder) = self.embedder.take() {
+            let provider = Arc::clone(embedder.provider());
+            self.embedder = Some(Arc::new(ParallelEmbedder::new(provider, config)));
+        }
+        self
+    }
+
+    /// Embedding throughput so far, if embeddings are configured.
+    pub fn embedding_stats(&self) -> Option<EmbeddingStats> {
+        self.embedder.as_ref().map(|e| e.stats())
+    }
+
+    /// Set the re-ranker for search results.
+    pub fn with_reranker(mut self, reranker: Arc<dyn Reranker>) -> Self {
+        self.reranker = Some(reranker);
         self
     }
 
+    /// Publish document writes and deletes on `bus`.
+    pub fn with_event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.events = Some(bus);
+        self
+    }
+
+    /// The re-ranker in use, if any.
+    pub fn reranker(&self) -> Option<&Arc<dyn Reranker>> {
+        self.reranker.as_ref()
+    }
+
+    /// The same workspace scoped to another user (e.g. a linked contact).
+    pub fn for_user(&self, user_id: impl Into<String>) -> Self {
+        Self {
+            user_id: user_id.into(),
+            agent_id: self.agent_id,
+            read_shared: self.read_shared,
+            storage: self.storage.clone(),
+            embedder: self.embedder.clone(),
+            reranker: self.reranker.clone(),
+            events: self.events.clone(),
+        }
+    }
+
     /// Get the user ID.
     pub fn user_id(&self) -> &str {
         &self.user_id
@@ -501,9 +649,18 @@ impl Workspace {
     /// ```
     pub async fn read(&self, path: &str) -> Result<MemoryDocument, WorkspaceError> {
         let path = normalize_path(path);
-        self.storage
-            .get_document_by_path(&self.user_id, self.agent_id, &path)
-            .await
+        let mut owners = self.readable_owners().into_iter().peekable();
+        while let Some(owner) = owners.next() {
+            match self
+                .storage
+                .get_document_by_path(&self.user_id, owner, &path)
+                .await
+            {
+                Err(WorkspaceError::DocumentNotFound { .. }) if owners.peek().is_some() => {}
+                result => return result,
+            }
+        }
+        unreachable!("readable_owners is never empty")
     }
 
     /// Write (create or update) a file.
@@ -523,6 +680,7 @@ impl Workspace {
             .await?;
         self.storage.update_document(doc.id, content).await?;
         self.reindex_document(doc.id).await?;
+        self.publish_change(&path, MemoryChange::Written).await;
 
         // Return updated doc
         self.storage.get_document_by_id(doc.id).await
@@ -547,17 +705,13 @@ impl Workspace {
 
         self.storage.update_document(doc.id, &new_content).await?;
         self.reindex_document(doc.id).await?;
+        self.publish_change(&path, MemoryChange::Written).await;
         Ok(())
     }
 
     /// Check if a file exists.
     pub async fn exists(&self, path: &str) -> Result<bool, WorkspaceError> {
-        let path = normalize_path(path);
-        match self
-            .storage
-            .get_document_by_path(&self.user_id, self.agent_id, &path)
-            .await
-        {
+        match self.read(path).await {
             Ok(_) => Ok(true),
             Err(WorkspaceError::DocumentNotFound { .. }) => Ok(false),
             Err(e) => Err(e),
@@ -571,7 +725,21 @@ impl Workspace {
         let path = normalize_path(path);
         self.storage
             .delete_document_by_path(&self.user_id, self.agent_id, &path)
-            .await
+            .await?;
+        self.publish_change(&path, MemoryChange::Deleted).await;
+        Ok(())
+    }
+
+    async fn publish_change(&self, path: &str, change: MemoryChange) {
+        if let Some(ref events) = self.events {
+            events
+                .publish(BusEvent::Memory {
+                    user_id: self.user_id.clone(),
+                    path: path.to_string(),
+                    change,
+                })
+                .await;
+        }
     }
 
     /// List files and directories in a path.
@@ -592,16 +760,31 @@ impl Workspace {
     /// ```
     pub async fn list(&self, directory: &str) -> Result<Vec<WorkspaceEntry>, WorkspaceError> {
         let directory = normalize_directory(directory);
-        self.storage
-            .list_directory(&self.user_id, self.agent_id, &directory)
-            .await
+        let mut entries: Vec<WorkspaceEntry> = Vec::new();
+        for owner in self.readable_owners() {
+            for entry in self
+                .storage
+                .list_directory(&self.user_id, owner, &directory)
+                .await?
+            {
+                if !entries.iter().any(|e| e.path == entry.path) {
+                    entries.push(entry);
+                }
+            }
+        }
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(entries)
     }
 
     /// List all files recursively (flat list of all paths).
     pub async fn list_all(&self) -> Result<Vec<String>, WorkspaceError> {
-        self.storage
-            .list_all_paths(&self.user_id, self.agent_id)
-            .await
+        let mut paths = Vec::new();
+        for owner in self.readable_owners() {
+            paths.extend(self.storage.list_all_paths(&self.user_id, owner).await?);
+        }
+        paths.sort();
+        paths.dedup();
+        Ok(paths)
     }
 
     // ==================== Convenience Methods ====================
@@ -741,34 +924,78 @@ impl Workspace {
     }
 
     /// Search with custom configuration.
+    ///
+    /// With a re-ranker set and `config.rerank` on, the top
+    /// `config.rerank_candidates` fused results are re-scored and the best
+    /// `config.limit` returned.
     pub async fn search_with_config(
         &self,
         query: &str,
         config: SearchConfig,
+    ) -> Result<Vec<SearchResult>, WorkspaceError> {
+        let reranker = self.reranker.as_ref().filter(|_| config.rerank);
+        let Some(reranker) = reranker else {
+            return self.fused_search(query, &config).await;
+        };
+
+        let limit = config.limit;
+        let candidates = config
+            .clone()
+            .with_limit(limit.max(config.rerank_candidates));
+        let results = self.fused_search(query, &candidates).await?;
+        let fallback: Vec<SearchResult> = results.iter().take(limit).cloned().collect();
+        match rerank::rerank(reranker.as_ref(), query, results, limit).await {
+            Ok(reranked) => Ok(reranked),
+            Err(e) => {
+                tracing::warn!(
+                    "{} re-ranking failed, using fused order: {}",
+                    reranker.name(),
+                    e
+                );
+                Ok(fallback)
+            }
+        }
+    }
+
+    /// Hybrid search without re-ranking.
+    async fn fused_search(
+        &self,
+        query: &str,
+        config: &SearchConfig,
     ) -> Result<Vec<SearchResult>, WorkspaceError> {
         // Generate embedding for semantic search if provider available
-        let embedding = if let Some(ref provider) = self.embeddings {
-            Some(
-                provider
-                    .embed(query)
-                    .await
-                    .map_err(|e| WorkspaceError::EmbeddingFailed {
-                        reason: e.to_string(),
-                    })?,
-            )
+        let embedding = if let Some(ref embedder) = self.embedder {
+            Some(embedder.provider().embed(query).await.map_err(|e| {
+                WorkspaceError::EmbeddingFailed {
+                    reason: e.to_string(),
+                }
+            })?)
         } else {
             None
         };
 
-        self.storage
-            .hybrid_search(
-                &self.user_id,
-                self.agent_id,
-                query,
-                embedding.as_deref(),
-                &config,
-            )
-            .await
+        let mut per_owner = Vec::new();
+        for owner in self.readable_owners() {
+            per_owner.push(
+                self.storage
+                    .hybrid_search(&self.user_id, owner, query, embedding.as_deref(), config)
+                    .await?,
+            );
+        }
+        if per_owner.len() == 1 {
+            return Ok(per_owner.remove(0));
+        }
+        Ok(scope::merge_results(per_owner, config.limit))
+    }
+
+    /// Path of the document with `id`.
+    pub(crate) async fn document_path(&self, id: Uuid) -> Result<String, WorkspaceError> {
+        Ok(self.storage.get_document_by_id(id).await?.path)
+    }
+
+    /// The document with `id`.
+    pub(crate) async fn document(&self, id: Uuid) -> Result<MemoryDocument, WorkspaceError> {
+        self.storage.get_document_by_id(id).await
     }
 
     // ==================== Indexing ====================
@@ -784,21 +1011,16 @@ impl Workspace {
         // Delete old chunks
         self.storage.delete_chunks(document_id).await?;
 
-        // Insert new chunks
-        for (index, content) in chunks.into_iter().enumerate() {
-            // Generate embedding if provider available
-            let embedding = if let Some(ref provider) = self.embeddings {
-                match provider.embed(&content).await {
-                    Ok(emb) => Some(emb),
-                    Err(e) => {
-                        tracing::warn!("Failed to generate embedding: {}", e);
-                        None
-                    }
-                }
-            } else {
-                None
-            };
+        // Embed all chunks up front, batched and in parallel
+        let embeddings = match self.embedder {
+            Some(ref embedder) => embedder.embed_all(&chunks).await,
+            None => vec![None; chunks.len()],
+        };
 
+        // Insert new chunks
+        let mut done = 0;
+        for (index, (content, embedding)) in chunks.into_iter().zip(embeddings).enumerate() {
+            yield_every(&mut done, 8).await;
             self.storage
                 .insert_chunk(document_id, index as i32, &content, embedding.as_deref())
                 .await?;
@@ -902,7 +1124,7 @@ impl Workspace {
     ///
     /// This is useful for backfilling embeddings after enabling the provider.
     pub async fn backfill_embeddings(&self) -> Result<usize, WorkspaceError> {
-        let Some(ref provider) = self.embeddings else {
+        let Some(ref embedder) = self.embedder else {
             return Ok(0);
         };
 
@@ -910,19 +1132,18 @@ impl Workspace {
             .storage
             .get_chunks_without_embeddings(&self.user_id, self.agent_id, 100)
             .await?;
+        let texts: Vec<String> = chunks.iter().map(|c| c.content.clone()).collect();
+        let embeddings = embedder.embed_all(&texts).await;
 
         let mut count = 0;
-        for chunk in chunks {
-            match provider.embed(&chunk.content).await {
-                Ok(embedding) => {
-                    self.storage
-                        .update_chunk_embedding(chunk.id, &embedding)
-                        .await?;
-                    count += 1;
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to embed chunk {}: {}", chunk.id, e);
-                }
+        let mut done = 0;
+        for (chunk, embedding) in chunks.iter().zip(embeddings) {
+            yield_every(&mut done, 8).await;
+            if let Some(embedding) = embedding {
+                self.storage
+                    .update_chunk_embedding(chunk.id, &embedding)
+                    .await?;
+                count += 1;
             }
         }
 
@@ -12,12 +12,13 @@ use uuid::Uuid;
 use crate::error::WorkspaceError;
 
 use crate::workspace::document::{
-    ConnectionType, MemoryChunk, MemoryConnection, MemoryDocument, MemorySpace, ProfileType,
-    UserProfile, WorkspaceEntry,
+    ConnectionType, DocumentUsage, MemoryChunk, MemoryConnection, MemoryDocument, MemorySpace,
+    ProfileType, UserProfile, WorkspaceEntry,
 };
 use crate::workspace::search::{RankedResult, SearchConfig, SearchResult, reciprocal_rank_fusion};
 
 /// Database repository for workspace operations.
+#[derive(Clone)]
 pub struct Repository {
     pool: Pool,
 }
@@ -270,6 +271,47 @@ impl Repository {
         Ok(rows.iter().map(|r| self.row_to_document(r)).collect())
     }
 
+    /// Per-document storage usage for a user.
+    pub async fn workspace_usage(
+        &self,
+        user_id: &str,
+        agent_id: Option<Uuid>,
+    ) -> Result<Vec<DocumentUsage>, WorkspaceError> {
+        let conn = self.conn().await?;
+
+        let rows = conn
+            .query(
+                r#"
+                SELECT d.id, d.path, d.updated_at,
+                       octet_length(d.content)::bigint AS content_bytes,
+                       COUNT(c.id) AS chunks,
+                       COALESCE(SUM(pg_column_size(c.embedding)), 0)::bigint AS embedding_bytes
+                FROM memory_documents d
+                LEFT JOIN memory_chunks c ON c.document_id = d.id
+                WHERE d.user_id = $1 AND d.agent_id IS NOT DISTINCT FROM $2
+                GROUP BY d.id
+                ORDER BY d.updated_at DESC
+                "#,
+                &[&user_id, &agent_id],
+            )
+            .await
+            .map_err(|e| WorkspaceError::SearchFailed {
+                reason: format!("Usage query failed: {}", e),
+            })?;
+
+        Ok(rows
+            .iter()
+            .map(|r| DocumentUsage {
+                document_id: r.get("id"),
+                path: r.get("path"),
+                content_bytes: r.get("content_bytes"),
+                chunks: r.get("chunks"),
+                embedding_bytes: r.get("embedding_bytes"),
+                updated_at: r.get("updated_at"),
+            })
+            .collect())
+    }
+
     fn row_to_document(&self, row: &tokio_postgres::Row) -> MemoryDocument {
         MemoryDocument {
             id: row.get("id"),
@@ -350,6 +392,40 @@ impl Repository {
         Ok(())
     }
 
+    /// All chunks of a document in order, with their embeddings.
+    pub async fn list_chunks(&self, document_id: Uuid) -> Result<Vec<MemoryChunk>, WorkspaceError> {
+        let conn = self.conn().await?;
+
+        let rows = conn
+            .query(
+                r#"
+                SELECT id, document_id, chunk_index, content, embedding, created_at
+                FROM memory_chunks
+                WHERE document_id = $1
+                ORDER BY chunk_index
+                "#,
+                &[&document_id],
+            )
+            .await
+            .map_err(|e| WorkspaceError::SearchFailed {
+                reason: format!("Query failed: {}", e),
+            })?;
+
+        Ok(rows
+            .iter()
+            .map(|row| MemoryChunk {
+                id: row.get("id"),
+                document_id: row.get("document_id"),
+                chunk_index: row.get("chunk_index"),
+                content: row.get("content"),
+                embedding: row
+                    .get::<_, Option<Vector>>("embedding")
+                    .map(|v| v.to_vec()),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+
     /// Get chunks without embeddings for backfilling.
     pub async fn get_chunks_without_embeddings(
         &self,
@@ -0,0 +1,311 @@
+//! Cross-encoder re-ranking of hybrid search results.
+//!
+//! RRF fuses ranks without ever looking at query and passage together. A
+//! re-ranker does: it scores each of the top candidates (50 by default)
+//! against the query and the best `limit` of those are returned. Two scorers
+//! are available:
+//!
+//! - [`CrossEncoderReranker`]: a cross-encoder model behind the
+//!   text-embeddings-inference `/rerank` API, e.g. a local ONNX
+//!   `bge-reranker-base`.
+//! - [`LlmReranker`]: asks the configured LLM to grade every passage.
+//!
+//! A failing re-ranker never fails the search; the fused order is kept.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::config::EmbeddingsConfig;
+use crate::llm::{ChatMessage, CompletionRequest, LlmProvider};
+use crate::workspace::search::SearchResult;
+
+/// Candidates handed to the re-ranker by default.
+pub const DEFAULT_RERANK_CANDIDATES: usize = 50;
+
+/// Longest passage (in characters) shown to the LLM scorer.
+const LLM_PASSAGE_CHARS: usize = 600;
+
+/// Error type for re-ranking.
+#[derive(Debug, thiserror::Error)]
+pub enum RerankError {
+    #[error("HTTP request failed: {0}")]
+    HttpError(String),
+
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+
+    #[error("LLM scoring failed: {0}")]
+    LlmFailed(String),
+}
+
+impl From<reqwest::Error> for RerankError {
+    fn from(e: reqwest::Error) -> Self {
+        RerankError::HttpError(e.to_string())
+    }
+}
+
+/// Scores passages against a query.
+#[async_trait]
+pub trait Reranker: Send + Sync {
+    /// Short name for logs and reports.
+    fn name(&self) -> &str;
+
+    /// Relevance of each passage to `query`, in input order. Higher is
+    /// better; the scale is up to the scorer.
+    async fn score(&self, query: &str, passages: &[&str]) -> Result<Vec<f32>, RerankError>;
+}
+
+/// Re-order `results` by re-ranker score and keep the best `limit`.
+///
+/// Scores are min-max normalized to 0.0-1.0 and replace the RRF scores.
+/// Ties keep their fused order.
+pub async fn rerank(
+    reranker: &dyn Reranker,
+    query: &str,
+    mut results: Vec<SearchResult>,
+    limit: usize,
+) -> Result<Vec<SearchResult>, RerankError> {
+    if results.is_empty() {
+        return Ok(results);
+    }
+    let passages: Vec<&str> = results.iter().map(|r| r.content.as_str()).collect();
+    let scores = reranker.score(query, &passages).await?;
+    if scores.len() != results.len() {
+        return Err(RerankError::InvalidResponse(format!(
+            "expected {} scores, got {}",
+            results.len(),
+            scores.len()
+        )));
+    }
+
+    let min = scores.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let span = max - min;
+    for (result, score) in results.iter_mut().zip(&scores) {
+        result.score = if span > 0.0 {
+            (score - min) / span
+        } else {
+            1.0
+        };
+    }
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    results.truncate(limit);
+    Ok(results)
+}
+
+/// A cross-encoder served with the text-embeddings-inference `/rerank` API.
+pub struct CrossEncoderReranker {
+    client: reqwest::Client,
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct RankedPassage {
+    index: usize,
+    score: f32,
+}
+
+impl CrossEncoderReranker {
+    /// `base_url` is the server root, e.g. `http://127.0.0.1:8088`.
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: crate::outbound::client(),
+            url: format!("{}/rerank", base_url.trim_end_matches('/')),
+        }
+    }
+}
+
+#[async_trait]
+impl Reranker for CrossEncoderReranker {
+    fn name(&self) -> &str {
+        "cross-encoder"
+    }
+
+    async fn score(&self, query: &str, passages: &[&str]) -> Result<Vec<f32>, RerankError> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&serde_json::json!({
+                "query": query,
+                "texts": passages,
+                "truncate": true,
+            }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(RerankError::HttpError(format!("{}: {}", status, body)));
+        }
+        let ranked: Vec<RankedPassage> = response
+            .json()
+            .await
+            .map_err(|e| RerankError::InvalidResponse(e.to_string()))?;
+
+        let mut scores = vec![f32::NEG_INFINITY; passages.len()];
+        for passage in ranked {
+            let slot = scores.get_mut(passage.index).ok_or_else(|| {
+                RerankError::InvalidResponse(format!("index {} out of range", passage.index))
+            })?;
+            *slot = passage.score;
+        }
+        Ok(scores)
+    }
+}
+
+/// Grades passages with an LLM in a single call.
+pub struct LlmReranker {
+    llm: Arc<dyn LlmProvider>,
+}
+
+impl LlmReranker {
+    pub fn new(llm: Arc<dyn LlmProvider>) -> Self {
+        Self { llm }
+    }
+}
+
+/// The first JSON array of numbers in an LLM reply.
+fn parse_scores(reply: &str) -> Option<Vec<f32>> {
+    let start = reply.find('[')?;
+    let end = reply[start..].find(']')? + start;
+    serde_json::from_str(&reply[start..=end]).ok()
+}
+
+#[async_trait]
+impl Reranker for LlmReranker {
+    fn name(&self) -> &str {
+        "llm"
+    }
+
+    async fn score(&self, query: &str, passages: &[&str]) -> Result<Vec<f32>, RerankError> {
+        let listing: Vec<String> = passages
+            .iter()
+            .enumerate()
+            .map(|(i, passage)| {
+                let passage: String = passage.chars().take(LLM_PASSAGE_CHARS).collect();
+                format!("[{}] {}", i, passage.replace('\n', " "))
+            })
+            .collect();
+        let prompt = format!(
+            "Rate how well each passage answers the query, from 0 (irrelevant) to 10 \
+             (answers it directly).\n\nQuery: {}\n\nPassages:\n{}\n\n\
+             Reply with only a JSON array of {} numbers, one per passage, in order.",
+            query,
+            listing.join("\n"),
+            passages.len()
+        );
+        let request = CompletionRequest::new(vec![ChatMessage::user(prompt)])
+            .with_max_tokens(passages.len() as u32 * 4 + 32)
+            .with_temperature(0.0);
+
+        let response = self
+            .llm
+            .complete(request)
+            .await
+            .map_err(|e| RerankError::LlmFailed(e.to_string()))?;
+        parse_scores(&response.content).ok_or_else(|| {
+            RerankError::InvalidResponse(format!("no score array in: {}", response.content))
+        })
+    }
+}
+
+/// Build the re-ranker selected by `SEARCH_RERANKER`, if any.
+///
+/// `llm` is only needed for the `llm` re-ranker.
+pub fn create_reranker(
+    config: &EmbeddingsConfig,
+    llm: Option<Arc<dyn LlmProvider>>,
+) -> Option<Arc<dyn Reranker>> {
+    match config.reranker.as_str() {
+        "cross-encoder" => match config.rerank_url {
+            Some(ref url) => {
+                tracing::info!("Search re-ranking with cross-encoder at {}", url);
+                Some(Arc::new(CrossEncoderReranker::new(url)))
+            }
+            None => {
+                tracing::warn!("SEARCH_RERANKER=cross-encoder needs SEARCH_RERANK_URL");
+                None
+            }
+        },
+        "llm" => match llm {
+            Some(llm) => {
+                tracing::info!("Search re-ranking with LLM {}", llm.active_model_name());
+                Some(Arc::new(LlmReranker::new(llm)))
+            }
+            None => {
+                tracing::warn!("LLM re-ranking requested but no LLM is available");
+                None
+            }
+        },
+        "" | "none" => None,
+        other => {
+            tracing::warn!("Unknown search re-ranker '{}', re-ranking disabled", other);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    /// Scores passages by how often they mention the query.
+    struct CountingReranker;
+
+    #[async_trait]
+    impl Reranker for CountingReranker {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        async fn score(&self, query: &str, passages: &[&str]) -> Result<Vec<f32>, RerankError> {
+            Ok(passages
+                .iter()
+                .map(|p| p.matches(query).count() as f32)
+                .collect())
+        }
+    }
+
+    fn result(content: &str, score: f32) -> SearchResult {
+        SearchResult {
+            document_id: Uuid::new_v4(),
+            chunk_id: Uuid::new_v4(),
+            content: content.to_string(),
+            score,
+            fts_rank: None,
+            vector_rank: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rerank_reorders_and_truncates() {
+        let results = vec![
+            result("nothing here", 1.0),
+            result("tea once", 0.9),
+            result("tea, tea and more tea", 0.8),
+        ];
+        let reranked = rerank(&CountingReranker, "tea", results, 2).await.unwrap();
+
+        assert_eq!(reranked.len(), 2);
+        assert_eq!(reranked[0].content, "tea, tea and more tea");
+        assert!((reranked[0].score - 1.0).abs() < f32::EPSILON);
+        assert_eq!(reranked[1].content, "tea once");
+    }
+
+    #[test]
+    fn test_parse_scores() {
+        assert_eq!(
+            parse_scores("Scores: [3, 9.5, 0]\n"),
+            Some(vec![3.0, 9.5, 0.0])
+        );
+        assert_eq!(parse_scores("no idea"), None);
+    }
+}
@@ -0,0 +1,123 @@
+//! Retrieval evaluation harness.
+//!
+//! Runs a set of labelled queries against the workspace and reports mean
+//! reciprocal rank and recall@k over documents, so search changes such as
+//! re-ranking can be compared on the same data. Cases are JSON lines:
+//!
+//! ```text
+//! {"query": "when is the dentist", "relevant": ["daily/2024-03-02.md"]}
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::WorkspaceError;
+use crate::workspace::{SearchConfig, Workspace};
+
+/// One labelled query.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetrievalCase {
+    pub query: String,
+    /// Paths of the documents that answer the query.
+    pub relevant: Vec<String>,
+}
+
+/// Parse JSON-lines cases, skipping blank lines.
+pub fn parse_cases(text: &str) -> Result<Vec<RetrievalCase>, serde_json::Error> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str)
+        .collect()
+}
+
+/// Aggregate metrics over a case set.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RetrievalReport {
+    pub cases: usize,
+    pub k: usize,
+    /// Mean reciprocal rank of the first relevant document.
+    pub mrr: f64,
+    /// Mean share of relevant documents found in the top k.
+    pub recall_at_k: f64,
+}
+
+impl RetrievalReport {
+    /// Fold per-case `(reciprocal_rank, recall)` pairs into a report.
+    pub fn from_scores(k: usize, scores: &[(f64, f64)]) -> Self {
+        let n = scores.len().max(1) as f64;
+        Self {
+            cases: scores.len(),
+            k,
+            mrr: scores.iter().map(|(rr, _)| rr).sum::<f64>() / n,
+            recall_at_k: scores.iter().map(|(_, recall)| recall).sum::<f64>() / n,
+        }
+    }
+}
+
+/// Reciprocal rank and recall@k for one ranked list of document paths.
+pub fn score_ranking(ranked: &[String], relevant: &[String], k: usize) -> (f64, f64) {
+    let reciprocal_rank = ranked
+        .iter()
+        .position(|path| relevant.contains(path))
+        .map_or(0.0, |i| 1.0 / (i + 1) as f64);
+    let found = relevant
+        .iter()
+        .filter(|path| ranked.iter().take(k).any(|p| p == *path))
+        .count();
+    let recall = if relevant.is_empty() {
+        1.0
+    } else {
+        found as f64 / relevant.len() as f64
+    };
+    (reciprocal_rank, recall)
+}
+
+impl Workspace {
+    /// Run `cases` with `config` and score the document rankings.
+    ///
+    /// Results are collapsed to documents (first chunk wins) before scoring.
+    pub async fn evaluate_retrieval(
+        &self,
+        cases: &[RetrievalCase],
+        config: SearchConfig,
+    ) -> Result<RetrievalReport, WorkspaceError> {
+        let k = config.limit;
+        let mut scores = Vec::with_capacity(cases.len());
+        for case in cases {
+            let results = self.search_with_config(&case.query, config.clone()).await?;
+            let mut ranked: Vec<String> = Vec::new();
+            for result in results {
+                let path = self.document_path(result.document_id).await?;
+                if !ranked.contains(&path) {
+                    ranked.push(path);
+                }
+            }
+            scores.push(score_ranking(&ranked, &case.relevant, k));
+        }
+        Ok(RetrievalReport::from_scores(k, &scores))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_ranking() {
+        let ranked: Vec<String> = ["a.md", "b.md", "c.md"].map(String::from).to_vec();
+        let relevant: Vec<String> = ["b.md", "z.md"].map(String::from).to_vec();
+
+        let (rr, recall) = score_ranking(&ranked, &relevant, 2);
+        assert!((rr - 0.5).abs() < 1e-9);
+        assert!((recall - 0.5).abs() < 1e-9);
+
+        let (rr, recall) = score_ranking(&ranked, &["x.md".to_string()], 3);
+        assert_eq!((rr, recall), (0.0, 0.0));
+
+        let report = RetrievalReport::from_scores(2, &[(1.0, 1.0), (0.5, 0.0)]);
+        assert!((report.mrr - 0.75).abs() < 1e-9);
+        assert!((report.recall_at_k - 0.5).abs() < 1e-9);
+
+        let cases = parse_cases("{\"query\": \"q\", \"relevant\": [\"a.md\"]}\n\n").unwrap();
+        assert_eq!(cases.len(), 1);
+    }
+}
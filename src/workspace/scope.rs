@@ -0,0 +1,227 @@
+//! Memory scoping for multi-agent setups.
+//!
+//! Every memory document is stored under an owner: `None` for the shared
+//! pool, or an agent id. A [`MemoryScope`] decides which owner an agent
+//! writes to and which owners it may read:
+//!
+//! | Scope | Writes to | Reads from |
+//! |-------|-----------|------------|
+//! | `private` | the agent | the agent, then the shared pool |
+//! | `shared` | the shared pool | the shared pool |
+//! | `channel` | the agent in this channel | that, then the shared pool |
+//!
+//! An agent never sees another agent's private or channel notes. Owner ids
+//! are derived from agent (and channel) names, so they are stable across
+//! restarts without being stored anywhere.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::search::SearchResult;
+
+/// Which memory an agent reads and writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryScope {
+    /// Notes belong to the agent; the shared pool is readable.
+    #[default]
+    Private,
+    /// Every note is in the pool all agents share.
+    Shared,
+    /// Notes belong to the agent in one channel; the shared pool is readable.
+    Channel,
+}
+
+impl std::str::FromStr for MemoryScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "private" | "agent" => Ok(Self::Private),
+            "shared" | "all" => Ok(Self::Shared),
+            "channel" | "per-channel" | "per_channel" => Ok(Self::Channel),
+            other => Err(format!(
+                "unknown memory scope '{other}' (expected private, shared or channel)"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for MemoryScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Private => "private",
+            Self::Shared => "shared",
+            Self::Channel => "channel",
+        })
+    }
+}
+
+/// Stable owner id for `parts` (agent name, then optionally channel).
+fn owner_id(parts: &[&str]) -> Uuid {
+    let mut hasher = Sha256::new();
+    hasher.update(b"ironclaw-memory-owner");
+    for part in parts {
+        hasher.update([0]);
+        hasher.update(part.as_bytes());
+    }
+    let digest = hasher.finalize();
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    uuid::Builder::from_bytes(bytes)
+        .with_variant(uuid::Variant::RFC4122)
+        .with_version(uuid::Version::Custom)
+        .into_uuid()
+}
+
+/// The owners a scoped workspace writes to and reads from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScopedOwner {
+    /// Owner that writes go to (`None` is the shared pool).
+    pub home: Option<Uuid>,
+    /// Whether reads fall back to the shared pool.
+    pub read_shared: bool,
+}
+
+impl ScopedOwner {
+    /// Resolve `scope` for `agent`, handling a message from `channel`.
+    pub fn resolve(scope: MemoryScope, agent: &str, channel: &str) -> Self {
+        match scope {
+            MemoryScope::Private => Self {
+                home: Some(owner_id(&[agent])),
+                read_shared: true,
+            },
+            MemoryScope::Shared => Self {
+                home: None,
+                read_shared: false,
+            },
+            MemoryScope::Channel => Self {
+                home: Some(owner_id(&[agent, channel])),
+                read_shared: true,
+            },
+        }
+    }
+
+    /// Owners to read from, most specific first.
+    pub fn readable(&self) -> Vec<Option<Uuid>> {
+        let mut owners = vec![self.home];
+        if self.read_shared && self.home.is_some() {
+            owners.push(None);
+        }
+        owners
+    }
+}
+
+/// Merge per-owner search results: best score first, at most `limit`.
+pub(crate) fn merge_results(per_owner: Vec<Vec<SearchResult>>, limit: usize) -> Vec<SearchResult> {
+    let mut merged: Vec<SearchResult> = per_owner.into_iter().flatten().collect();
+    merged.sort_by(|a, b| b.score.total_cmp(&a.score));
+    merged.truncate(limit);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(content: &str, score: f32) -> SearchResult {
+        SearchResult {
+            document_id: Uuid::new_v4(),
+            chunk_id: Uuid::new_v4(),
+            content: content.to_string(),
+            score,
+            fts_rank: None,
+            vector_rank: None,
+        }
+    }
+
+    #[test]
+    fn test_scope_parse_and_display() {
+        for scope in [
+            MemoryScope::Private,
+            MemoryScope::Shared,
+            MemoryScope::Channel,
+        ] {
+            assert_eq!(scope.to_string().parse::<MemoryScope>().unwrap(), scope);
+        }
+        assert_eq!(
+            "per-channel".parse::<MemoryScope>().unwrap(),
+            MemoryScope::Channel
+        );
+        assert!("team".parse::<MemoryScope>().is_err());
+    }
+
+    #[test]
+    fn test_owners_are_stable_and_distinct() {
+        let coder = ScopedOwner::resolve(MemoryScope::Private, "coder", "telegram");
+        let researcher = ScopedOwner::resolve(MemoryScope::Private, "researcher", "telegram");
+        assert_eq!(
+            coder,
+            ScopedOwner::resolve(MemoryScope::Private, "coder", "cli")
+        );
+        assert_ne!(coder.home, researcher.home);
+        assert_eq!(coder.readable(), vec![coder.home, None]);
+
+        let tg = ScopedOwner::resolve(MemoryScope::Channel, "coder", "telegram");
+        let slack = ScopedOwner::resolve(MemoryScope::Channel, "coder", "slack");
+        assert_ne!(tg.home, slack.home);
+        assert_ne!(tg.home, coder.home);
+
+        let shared = ScopedOwner::resolve(MemoryScope::Shared, "coder", "telegram");
+        assert_eq!(shared.readable(), vec![None]);
+    }
+
+    #[test]
+    fn test_merge_results_orders_and_limits() {
+        let merged = merge_results(
+            vec![
+                vec![result("own-a", 0.9), result("own-b", 0.2)],
+                vec![result("shared", 0.5)],
+            ],
+            2,
+        );
+        let contents: Vec<_> = merged.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(contents, ["own-a", "shared"]);
+    }
+
+    #[cfg(feature = "libsql")]
+    #[tokio::test]
+    async fn test_agents_do_not_see_each_others_notes() {
+        use std::sync::Arc;
+
+        use crate::db::Database;
+        use crate::db::libsql_backend::LibSqlBackend;
+        use crate::workspace::Workspace;
+
+        let dir = tempfile::tempdir().unwrap();
+        let backend = LibSqlBackend::new_local(&dir.path().join("memory.db"))
+            .await
+            .unwrap();
+        backend.run_migrations().await.unwrap();
+        let base = Workspace::new_with_db("default", Arc::new(backend));
+        base.write("team.md", "Standup is at nine").await.unwrap();
+
+        let coder = base.scoped(ScopedOwner::resolve(MemoryScope::Private, "coder", "cli"));
+        let researcher = base.scoped(ScopedOwner::resolve(
+            MemoryScope::Private,
+            "researcher",
+            "cli",
+        ));
+        coder
+            .write("notes.md", "Deploy keys rotate on Fridays")
+            .await
+            .unwrap();
+
+        assert!(coder.exists("notes.md").await.unwrap());
+        assert!(!researcher.exists("notes.md").await.unwrap());
+        assert!(!base.exists("notes.md").await.unwrap());
+        assert!(researcher.exists("team.md").await.unwrap());
+        assert_eq!(coder.list_all().await.unwrap(), ["notes.md", "team.md"]);
+        assert_eq!(researcher.list_all().await.unwrap(), ["team.md"]);
+
+        assert_eq!(coder.search("Fridays", 5).await.unwrap().len(), 1);
+        assert!(researcher.search("Fridays", 5).await.unwrap().is_empty());
+        assert_eq!(researcher.search("Standup", 5).await.unwrap().len(), 1);
+    }
+}
@@ -12,6 +12,8 @@ use std::collections::HashMap;
 
 use uuid::Uuid;
 
+use crate::workspace::rerank::DEFAULT_RERANK_CANDIDATES;
+
 /// Configuration for hybrid search.
 #[derive(Debug, Clone)]
 pub struct SearchConfig {
@@ -27,6 +29,10 @@ pub struct SearchConfig {
     pub min_score: f32,
     /// Maximum results to fetch from each method before fusion.
     pub pre_fusion_limit: usize,
+    /// Whether to re-rank with the workspace's re-ranker, if it has one.
+    pub rerank: bool,
+    /// Fused candidates handed to the re-ranker.
+    pub rerank_candidates: usize,
 }
 
 impl Default for SearchConfig {
@@ -38,6 +44,8 @@ impl Default for SearchConfig {
             use_vector: true,
             min_score: 0.0,
             pre_fusion_limit: 50,
+            rerank: true,
+            rerank_candidates: DEFAULT_RERANK_CANDIDATES,
         }
     }
 }
@@ -69,6 +77,18 @@ impl SearchConfig {
         self
     }
 
+    /// Skip the re-ranking stage.
+    pub fn without_rerank(mut self) -> Self {
+        self.rerank = false;
+        self
+    }
+
+    /// Set how many fused candidates the re-ranker sees.
+    pub fn with_rerank_candidates(mut self, candidates: usize) -> Self {
+        self.rerank_candidates = candidates;
+        self
+    }
+
     /// Set minimum score threshold.
     pub fn with_min_score(mut self, score: f32) -> Self {
         self.min_score = score.clamp(0.0, 1.0);
@@ -85,7 +105,8 @@ pub struct SearchResult {
     pub chunk_id: Uuid,
     /// Chunk content.
     pub content: String,
-    /// Combined RRF score (0.0-1.0 normalized).
+    /// Combined RRF score, or the re-ranker's score when one ran
+    /// (0.0-1.0 normalized).
     pub score: f32,
     /// Rank in FTS results (1-based, None if not in FTS results).
     pub fts_rank: Option<u32>,
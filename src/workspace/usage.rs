@@ -0,0 +1,438 @@
+//! Storage accounting and quotas for workspace memory.
+//!
+//! [`Workspace::usage`] counts documents, chunks, content bytes and
+//! embedding bytes for the whole workspace and for each memory space.
+//! Quotas cap those numbers, for the workspace as a whole or per space:
+//!
+//! ```json
+//! {
+//!   "total": {"max_embedding_mb": 512},
+//!   "spaces": [
+//!     {"space": "research", "max_documents": 500},
+//!     {"space": "*", "max_chunks": 5000}
+//!   ],
+//!   "action": "consolidate"
+//! }
+//! ```
+//!
+//! At 80% of any limit a space is reported as `near`; past 100% the
+//! configured action runs. `warn` only logs. `consolidate` folds the
+//! oldest half of the space's documents into one archive document,
+//! which re-chunks many small notes into fewer, fuller chunks. Root files
+//! (MEMORY.md, identity files) and earlier archives are never folded.
+//! Workspace-wide limits only warn.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::WorkspaceError;
+use crate::workspace::{DocumentUsage, Workspace};
+
+/// Directory consolidated documents are written to.
+pub const ARCHIVE_DIR: &str = "archive";
+
+/// Name reported for documents that belong to no space.
+pub const UNASSIGNED: &str = "(no space)";
+
+/// Share of a limit (in percent) at which a space counts as near its quota.
+const NEAR_PERCENT: u64 = 80;
+
+/// Limits for one scope. Unset limits are not enforced.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaLimits {
+    #[serde(default)]
+    pub max_documents: Option<u64>,
+    #[serde(default)]
+    pub max_chunks: Option<u64>,
+    #[serde(default)]
+    pub max_embedding_mb: Option<u64>,
+}
+
+impl QuotaLimits {
+    pub fn is_empty(&self) -> bool {
+        self.max_documents.is_none() && self.max_chunks.is_none() && self.max_embedding_mb.is_none()
+    }
+
+    /// `(metric, used, limit)` for every limit that is set.
+    fn checks(&self, counts: &UsageCounts) -> Vec<(&'static str, u64, u64)> {
+        [
+            ("documents", counts.documents, self.max_documents),
+            ("chunks", counts.chunks, self.max_chunks),
+            (
+                "embedding bytes",
+                counts.embedding_bytes,
+                self.max_embedding_mb.map(|mb| mb * 1024 * 1024),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(metric, used, limit)| limit.map(|limit| (metric, used, limit)))
+        .collect()
+    }
+
+    /// Where `counts` stands against these limits.
+    pub fn status(&self, counts: &UsageCounts) -> QuotaStatus {
+        self.checks(counts)
+            .into_iter()
+            .map(|(_, used, limit)| {
+                if used > limit {
+                    QuotaStatus::Exceeded
+                } else if used * 100 >= limit * NEAR_PERCENT {
+                    QuotaStatus::Near
+                } else {
+                    QuotaStatus::Ok
+                }
+            })
+            .max()
+            .unwrap_or(QuotaStatus::Ok)
+    }
+
+    /// Human-readable list of the limits `counts` is over.
+    pub fn overages(&self, counts: &UsageCounts) -> Vec<String> {
+        self.checks(counts)
+            .into_iter()
+            .filter(|(_, used, limit)| used > limit)
+            .map(|(metric, used, limit)| format!("{}/{} {}", used, limit, metric))
+            .collect()
+    }
+}
+
+/// Limits for a named space; `"*"` applies to spaces without their own entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpaceQuota {
+    pub space: String,
+    #[serde(flatten)]
+    pub limits: QuotaLimits,
+}
+
+/// What to do when a space goes over its quota.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaAction {
+    #[default]
+    Warn,
+    Consolidate,
+}
+
+/// All configured quotas.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaConfig {
+    /// Limits for the workspace as a whole.
+    #[serde(default)]
+    pub total: QuotaLimits,
+    /// Per-space limits.
+    #[serde(default)]
+    pub spaces: Vec<SpaceQuota>,
+    #[serde(default)]
+    pub action: QuotaAction,
+}
+
+impl QuotaConfig {
+    /// Whether any limit is configured.
+    pub fn is_empty(&self) -> bool {
+        self.total.is_empty() && self.spaces.iter().all(|s| s.limits.is_empty())
+    }
+
+    /// Limits for `space`: its own entry, else the `"*"` entry.
+    pub fn for_space(&self, space: &str) -> Option<&QuotaLimits> {
+        self.spaces
+            .iter()
+            .find(|q| q.space == space)
+            .or_else(|| self.spaces.iter().find(|q| q.space == "*"))
+            .map(|q| &q.limits)
+    }
+}
+
+/// Position against a quota.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaStatus {
+    Ok,
+    Near,
+    Exceeded,
+}
+
+/// Summed storage numbers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct UsageCounts {
+    pub documents: u64,
+    pub chunks: u64,
+    pub content_bytes: u64,
+    pub embedding_bytes: u64,
+}
+
+impl UsageCounts {
+    fn add(&mut self, doc: &DocumentUsage) {
+        self.documents += 1;
+        self.chunks += doc.chunks.max(0) as u64;
+        self.content_bytes += doc.content_bytes.max(0) as u64;
+        self.embedding_bytes += doc.embedding_bytes.max(0) as u64;
+    }
+}
+
+/// Usage of one space (or of the whole workspace).
+#[derive(Debug, Clone, Serialize)]
+pub struct SpaceUsage {
+    pub name: String,
+    #[serde(flatten)]
+    pub counts: UsageCounts,
+    /// Limits that apply, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quota: Option<QuotaLimits>,
+    pub status: QuotaStatus,
+}
+
+impl SpaceUsage {
+    fn new(name: &str, counts: UsageCounts, quota: Option<&QuotaLimits>) -> Self {
+        let quota = quota.filter(|q| !q.is_empty()).cloned();
+        let status = quota
+            .as_ref()
+            .map_or(QuotaStatus::Ok, |q| q.status(&counts));
+        Self {
+            name: name.to_string(),
+            counts,
+            quota,
+            status,
+        }
+    }
+}
+
+/// Report returned by [`Workspace::usage`].
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceUsage {
+    pub total: SpaceUsage,
+    /// Named spaces, then documents in no space.
+    pub spaces: Vec<SpaceUsage>,
+}
+
+impl Workspace {
+    /// Storage used by the workspace and each of its spaces, checked
+    /// against `quotas`.
+    pub async fn usage(&self, quotas: &QuotaConfig) -> Result<WorkspaceUsage, WorkspaceError> {
+        let docs = self
+            .storage
+            .workspace_usage(&self.user_id, self.agent_id)
+            .await?;
+        let by_id: HashMap<Uuid, &DocumentUsage> =
+            docs.iter().map(|d| (d.document_id, d)).collect();
+
+        let mut total = UsageCounts::default();
+        for doc in &docs {
+            total.add(doc);
+        }
+
+        let mut spaces = Vec::new();
+        let mut assigned = HashSet::new();
+        for space in self.list_spaces().await? {
+            let mut counts = UsageCounts::default();
+            for member in self.storage.list_space_documents(space.id).await? {
+                if let Some(doc) = by_id.get(&member.id) {
+                    counts.add(doc);
+                    assigned.insert(member.id);
+                }
+            }
+            spaces.push(SpaceUsage::new(
+                &space.name,
+                counts,
+                quotas.for_space(&space.name),
+            ));
+        }
+
+        let mut unassigned = UsageCounts::default();
+        for doc in docs.iter().filter(|d| !assigned.contains(&d.document_id)) {
+            unassigned.add(doc);
+        }
+        if unassigned.documents > 0 {
+            spaces.push(SpaceUsage::new(UNASSIGNED, unassigned, None));
+        }
+
+        Ok(WorkspaceUsage {
+            total: SpaceUsage::new("total", total, Some(&quotas.total)),
+            spaces,
+        })
+    }
+
+    /// Check quotas and apply the configured action to anything over.
+    /// Returns one line per over-quota scope describing what was done.
+    pub async fn enforce_quotas(
+        &self,
+        quotas: &QuotaConfig,
+    ) -> Result<Vec<String>, WorkspaceError> {
+        if quotas.is_empty() {
+            return Ok(Vec::new());
+        }
+        let usage = self.usage(quotas).await?;
+        let mut notes = Vec::new();
+
+        if usage.total.status == QuotaStatus::Exceeded {
+            notes.push(format!(
+                "Workspace is over its quota: {}",
+                quotas.total.overages(&usage.total.counts).join(", ")
+            ));
+        }
+
+        for space in &usage.spaces {
+            let Some(ref quota) = space.quota else {
+                continue;
+            };
+            if space.status != QuotaStatus::Exceeded {
+                continue;
+            }
+            let over = quota.overages(&space.counts).join(", ");
+            let note = match quotas.action {
+                QuotaAction::Warn => format!("Space '{}' is over its quota: {}", space.name, over),
+                QuotaAction::Consolidate => match self.consolidate_space(&space.name).await? {
+                    Some((path, folded)) => format!(
+                        "Space '{}' was over its quota ({}); folded {} documents into {}",
+                        space.name, over, folded, path
+                    ),
+                    None => format!(
+                        "Space '{}' is over its quota ({}) and has nothing left to consolidate",
+                        space.name, over
+                    ),
+                },
+            };
+            notes.push(note);
+        }
+
+        for note in &notes {
+            tracing::warn!("{}", note);
+        }
+        Ok(notes)
+    }
+
+    /// Fold the oldest half of a space's documents into one archive
+    /// document in the same space. Returns the archive path and how many
+    /// documents were folded, or `None` when there are too few to fold.
+    pub async fn consolidate_space(
+        &self,
+        space: &str,
+    ) -> Result<Option<(String, usize)>, WorkspaceError> {
+        // Newest first from storage; keep root files and earlier archives.
+        let mut candidates: Vec<_> = self
+            .list_space_documents(space)
+            .await?
+            .into_iter()
+            .filter(|d| d.path.contains('/') && !d.path.starts_with(&format!("{ARCHIVE_DIR}/")))
+            .collect();
+        candidates.reverse();
+        let fold = candidates.len() / 2;
+        if fold < 2 {
+            return Ok(None);
+        }
+        let folded = &candidates[..fold];
+
+        let now = Utc::now();
+        let slug: String = space
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let path = format!(
+            "{}/{}-{}.md",
+            ARCHIVE_DIR,
+            slug,
+            now.format("%Y%m%d-%H%M%S")
+        );
+        let mut content = format!(
+            "# Archive of space '{}'\n\n{} documents consolidated on {}.\n",
+            space,
+            fold,
+            now.format("%Y-%m-%d")
+        );
+        for doc in folded {
+            content.push_str(&format!("\n## {}\n\n{}\n", doc.path, doc.content.trim()));
+        }
+
+        self.write(&path, &content).await?;
+        self.add_to_space(space, &path).await?;
+        for doc in folded {
+            self.delete(&doc.path).await?;
+        }
+        tracing::info!(space = space, folded = fold, archive = %path, "Consolidated memory space");
+        Ok(Some((path, fold)))
+    }
+}
+
+/// Check quotas every `interval` in the background. Returns `None` when no
+/// quota is configured.
+pub fn spawn_quota_monitor(
+    workspace: Arc<Workspace>,
+    quotas: QuotaConfig,
+    interval: Duration,
+) -> Option<tokio::task::JoinHandle<()>> {
+    if quotas.is_empty() {
+        return None;
+    }
+    Some(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = workspace.enforce_quotas(&quotas).await {
+                tracing::warn!("Memory quota check failed: {}", e);
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(documents: u64, chunks: u64, embedding_bytes: u64) -> UsageCounts {
+        UsageCounts {
+            documents,
+            chunks,
+            content_bytes: 0,
+            embedding_bytes,
+        }
+    }
+
+    #[test]
+    fn test_quota_status_and_overages() {
+        let limits = QuotaLimits {
+            max_documents: Some(100),
+            max_chunks: None,
+            max_embedding_mb: Some(1),
+        };
+        assert_eq!(limits.status(&counts(10, 5000, 0)), QuotaStatus::Ok);
+        assert_eq!(limits.status(&counts(80, 0, 0)), QuotaStatus::Near);
+        assert_eq!(limits.status(&counts(100, 0, 0)), QuotaStatus::Near);
+        assert_eq!(
+            limits.status(&counts(10, 0, 2 * 1024 * 1024)),
+            QuotaStatus::Exceeded
+        );
+        assert_eq!(
+            limits.overages(&counts(101, 0, 0)),
+            vec!["101/100 documents".to_string()]
+        );
+        assert_eq!(
+            QuotaLimits::default().status(&counts(1_000_000, 0, 0)),
+            QuotaStatus::Ok
+        );
+    }
+
+    #[test]
+    fn test_space_lookup_falls_back_to_wildcard() {
+        let config: QuotaConfig = serde_json::from_value(serde_json::json!({
+            "spaces": [
+                {"space": "research", "max_documents": 500},
+                {"space": "*", "max_chunks": 5000}
+            ],
+            "action": "consolidate"
+        }))
+        .unwrap();
+        assert!(!config.is_empty());
+        assert_eq!(config.action, QuotaAction::Consolidate);
+        assert_eq!(
+            config.for_space("research").unwrap().max_documents,
+            Some(500)
+        );
+        assert_eq!(config.for_space("work").unwrap().max_chunks, Some(5000));
+        assert!(QuotaConfig::default().for_space("work").is_none());
+        assert!(QuotaConfig::default().is_empty());
+    }
+}
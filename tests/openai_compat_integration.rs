@@ -14,8 +14,8 @@ use ironclaw::channels::web::sse::SseManager;
 use ironclaw::channels::web::ws::WsConnectionTracker;
 use ironclaw::error::LlmError;
 use ironclaw::llm::{
-    CompletionRequest, CompletionResponse, FinishReason, LlmProvider, ToolCompletionRequest,
-    ToolCompletionResponse,
+    CompletionRequest, CompletionResponse, CompletionStream, FinishReason, LlmProvider,
+    StreamDelta, ToolCompletionRequest, ToolCompletionResponse,
 };
 
 const AUTH_TOKEN: &str = "test-openai-token";
@@ -50,6 +50,8 @@ impl LlmProvider for MockLlmProvider {
             content: format!("Mock response to: {}", user_msg),
             input_tokens: 10,
             output_tokens: 5,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
             finish_reason: FinishReason::Stop,
             response_id: None,
         })
@@ -70,6 +72,8 @@ impl LlmProvider for MockLlmProvider {
                 }],
                 input_tokens: 15,
                 output_tokens: 8,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
                 finish_reason: FinishReason::ToolUse,
                 response_id: None,
             })
@@ -79,6 +83,8 @@ impl LlmProvider for MockLlmProvider {
                 tool_calls: vec![],
                 input_tokens: 10,
                 output_tokens: 4,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
                 finish_reason: FinishReason::Stop,
                 response_id: None,
             })
@@ -93,11 +99,63 @@ impl LlmProvider for MockLlmProvider {
     }
 }
 
+/// Streams a fixed reply one piece at a time.
+struct StreamingMockProvider;
+
+#[async_trait]
+impl LlmProvider for StreamingMockProvider {
+    fn model_name(&self) -> &str {
+        "mock-model-v1"
+    }
+
+    fn cost_per_token(&self) -> (Decimal, Decimal) {
+        (Decimal::ZERO, Decimal::ZERO)
+    }
+
+    async fn complete(&self, _req: CompletionRequest) -> Result<CompletionResponse, LlmError> {
+        panic!("streaming requests should not be buffered");
+    }
+
+    async fn complete_with_tools(
+        &self,
+        _req: ToolCompletionRequest,
+    ) -> Result<ToolCompletionResponse, LlmError> {
+        panic!("streaming requests should not be buffered");
+    }
+
+    async fn complete_stream(
+        &self,
+        _req: ToolCompletionRequest,
+    ) -> Result<CompletionStream, LlmError> {
+        let mut deltas: Vec<Result<StreamDelta, LlmError>> = ["Hel", "lo, ", "world"]
+            .into_iter()
+            .map(|t| Ok(StreamDelta::Text(t.to_string())))
+            .collect();
+        deltas.push(Ok(StreamDelta::Done {
+            input_tokens: 3,
+            output_tokens: 3,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            finish_reason: FinishReason::Length,
+            response_id: None,
+        }));
+        Ok(Box::pin(futures::stream::iter(deltas)))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Test helpers
 // ---------------------------------------------------------------------------
 
 async fn start_test_server() -> (SocketAddr, Arc<GatewayState>) {
+    start_test_server_with(Arc::new(MockLlmProvider)).await
+}
+
+async fn start_test_server_with(llm: Arc<dyn LlmProvider>) -> (SocketAddr, Arc<GatewayState>) {
     let state = Arc::new(GatewayState {
         msg_tx: tokio::sync::RwLock::new(None),
         sse: SseManager::new(),
@@ -112,8 +170,15 @@ async fn start_test_server() -> (SocketAddr, Arc<GatewayState>) {
         user_id: "test-user".to_string(),
         shutdown_tx: tokio::sync::RwLock::new(None),
         ws_tracker: Some(Arc::new(WsConnectionTracker::new())),
-        llm_provider: Some(Arc::new(MockLlmProvider)),
+        llm_provider: Some(llm),
         chat_rate_limiter: ironclaw::channels::web::server::RateLimiter::new(30, 60),
+        model_tiers: None,
+        focus: None,
+        memory_quota: Default::default(),
+        overflow: Arc::new(ironclaw::channels::OverflowStore::new()),
+        health: Default::default(),
+        integration_limiter: Default::default(),
+        budget: None,
     });
 
     let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
@@ -316,6 +381,53 @@ async fn test_chat_completions_streaming() {
     );
 }
 
+#[tokio::test]
+async fn test_chat_completions_native_streaming() {
+    let (addr, _state) = start_test_server_with(Arc::new(StreamingMockProvider)).await;
+    let url = format!("http://{}/v1/chat/completions", addr);
+
+    let resp = client()
+        .post(&url)
+        .bearer_auth(AUTH_TOKEN)
+        .json(&serde_json::json!({
+            "model": "mock-model-v1",
+            "messages": [{"role": "user", "content": "Say hello"}],
+            "stream": true
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers()
+            .get("x-ironclaw-streaming")
+            .and_then(|v| v.to_str().ok()),
+        Some("native")
+    );
+
+    let text = resp.text().await.unwrap();
+    let chunks: Vec<serde_json::Value> = text
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(str::trim)
+        .filter(|data| *data != "[DONE]")
+        .map(|data| serde_json::from_str(data).unwrap())
+        .collect();
+
+    // Each provider delta is forwarded as its own chunk.
+    let contents: Vec<&str> = chunks
+        .iter()
+        .filter_map(|c| c["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(contents, ["Hel", "lo, ", "world"]);
+    assert_eq!(
+        chunks.last().unwrap()["choices"][0]["finish_reason"],
+        "length"
+    );
+    assert!(text.trim_end().ends_with("data: [DONE]"));
+}
+
 #[tokio::test]
 async fn test_chat_completions_empty_messages() {
     let (addr, _state) = start_test_server().await;
@@ -434,6 +546,13 @@ async fn test_no_llm_provider_returns_503() {
         ws_tracker: Some(Arc::new(WsConnectionTracker::new())),
         llm_provider: None, // No LLM!
         chat_rate_limiter: ironclaw::channels::web::server::RateLimiter::new(30, 60),
+        model_tiers: None,
+        focus: None,
+        memory_quota: Default::default(),
+        overflow: Arc::new(ironclaw::channels::OverflowStore::new()),
+        health: Default::default(),
+        integration_limiter: Default::default(),
+        budget: None,
     });
 
     let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
@@ -53,6 +53,13 @@ async fn start_test_server() -> (
         ws_tracker: Some(Arc::new(WsConnectionTracker::new())),
         llm_provider: None,
         chat_rate_limiter: ironclaw::channels::web::server::RateLimiter::new(30, 60),
+        model_tiers: None,
+        focus: None,
+        memory_quota: Default::default(),
+        overflow: Arc::new(ironclaw::channels::OverflowStore::new()),
+        health: Default::default(),
+        integration_limiter: Default::default(),
+        budget: None,
     });
 
     let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();